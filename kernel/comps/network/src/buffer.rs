@@ -1,18 +1,21 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use alloc::sync::Arc;
+use alloc::{sync::Arc, vec::Vec};
 use core::marker::PhantomData;
 
 use ostd::{
     Result,
     mm::{
         Daddr, HasDaddr, HasSize, Infallible, VmReader, VmWriter,
-        dma::{FromDevice, ToDevice},
+        dma::{DmaDirection, FromDevice, ToDevice},
     },
 };
 use ostd_pod::Pod;
 
-use crate::dma_pool::{DmaPool, DmaSegment};
+use crate::{
+    checksum::ChecksumOffload,
+    dma_pool::{DmaPool, DmaSegment},
+};
 
 pub struct TxBuffer {
     segment: DmaSegment<ToDevice>,
@@ -33,6 +36,27 @@ impl TxBuffer {
         Ok(builder.build(header))
     }
 
+    /// Creates a buffer that contains the continued payload without any header.
+    ///
+    /// A large packet can be sent in multiple buffers, where only the first buffer contains the
+    /// header.
+    pub fn new_continued(payload: &[u8], pool: &Arc<DmaPool<ToDevice>>) -> Result<Self> {
+        assert!(payload.len() <= pool.segment_size());
+
+        let segment = pool.alloc_segment()?;
+        segment
+            .writer()
+            .unwrap()
+            .write(&mut VmReader::from(payload));
+
+        let tx_buffer = Self {
+            segment,
+            nbytes: payload.len(),
+        };
+        tx_buffer.sync_to_device();
+        Ok(tx_buffer)
+    }
+
     pub fn new_builder<H: Pod>(pool: &Arc<DmaPool<ToDevice>>) -> Result<TxBufferBuilder<H>> {
         assert!(size_of::<H>() <= pool.segment_size());
 
@@ -46,6 +70,12 @@ impl TxBuffer {
         Ok(builder)
     }
 
+    pub fn buf(&self) -> VmReader<'_, Infallible> {
+        let mut reader = self.segment.reader().unwrap();
+        reader.limit(self.nbytes);
+        reader
+    }
+
     fn sync_to_device(&self) {
         self.segment.sync_to_device(0..self.nbytes).unwrap();
     }
@@ -103,14 +133,23 @@ impl<H: Pod> TxBufferBuilder<H> {
     }
 }
 
-pub struct RxBuffer {
-    segment: DmaSegment<FromDevice>,
+/// A buffer that receives a packet from the device.
+///
+/// The DMA direction `D` is always [`FromDevice`] except in tests, where the buffer needs to be
+/// filled by the kernel.
+pub struct RxBuffer<D: DmaDirection = FromDevice> {
+    segment: DmaSegment<D>,
     header_len: usize,
     payload_len: usize,
+    /// The segments that contain the rest of the payload and their lengths.
+    ///
+    /// A large packet can be received in multiple buffers. See [`Self::merge`].
+    merged_segments: Vec<(DmaSegment<D>, usize)>,
+    checksum: ChecksumOffload,
 }
 
-impl RxBuffer {
-    pub fn new(header_len: usize, pool: &Arc<DmaPool<FromDevice>>) -> Result<Self> {
+impl<D: DmaDirection> RxBuffer<D> {
+    pub fn new(header_len: usize, pool: &Arc<DmaPool<D>>) -> Result<Self> {
         assert!(header_len <= pool.segment_size());
 
         let segment = pool.alloc_segment()?;
@@ -118,11 +157,19 @@ impl RxBuffer {
             segment,
             header_len,
             payload_len: 0,
+            merged_segments: Vec::new(),
+            checksum: ChecksumOffload::None,
         })
     }
 
-    pub const fn payload_len(&self) -> usize {
+    /// Returns the length of the payload, including the payload in the merged buffers.
+    pub fn payload_len(&self) -> usize {
         self.payload_len
+            + self
+                .merged_segments
+                .iter()
+                .map(|(_, len)| *len)
+                .sum::<usize>()
     }
 
     pub fn set_payload_len(&mut self, payload_len: usize) {
@@ -130,7 +177,24 @@ impl RxBuffer {
         self.payload_len = payload_len;
     }
 
+    /// Appends the first `len` bytes of another buffer to the payload.
+    ///
+    /// The other buffer should contain the continued payload without any header.
+    pub fn merge(&mut self, buffer: RxBuffer<D>, len: usize) {
+        assert!(len <= buffer.segment.size());
+        debug_assert!(buffer.merged_segments.is_empty());
+        self.merged_segments.push((buffer.segment, len));
+    }
+
+    /// Returns a reader of the payload.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if the buffer has been merged with other buffers. Use
+    /// [`Self::read_payload`] instead in that case.
     pub fn payload(&self) -> VmReader<'_, Infallible> {
+        assert!(self.merged_segments.is_empty());
+
         self.segment
             .sync_from_device(self.header_len..self.header_len + self.payload_len)
             .unwrap();
@@ -140,6 +204,27 @@ impl RxBuffer {
         reader
     }
 
+    /// Reads the payload, including the payload in the merged buffers, to the writer.
+    ///
+    /// Returns the number of bytes read.
+    pub fn read_payload(&self, writer: &mut VmWriter<Infallible>) -> usize {
+        self.segment
+            .sync_from_device(self.header_len..self.header_len + self.payload_len)
+            .unwrap();
+        let mut reader = self.segment.reader().unwrap();
+        reader.skip(self.header_len).limit(self.payload_len);
+        let mut read_len = writer.write(&mut reader);
+
+        for (segment, len) in self.merged_segments.iter() {
+            segment.sync_from_device(0..*len).unwrap();
+            let mut reader = segment.reader().unwrap();
+            reader.limit(*len);
+            read_len += writer.write(&mut reader);
+        }
+
+        read_len
+    }
+
     pub fn buf(&self) -> VmReader<'_, Infallible> {
         self.segment
             .sync_from_device(0..self.header_len + self.payload_len)
@@ -149,16 +234,76 @@ impl RxBuffer {
         reader.limit(self.header_len + self.payload_len);
         reader
     }
+
+    /// Returns the state of the checksum reported by the device.
+    pub const fn checksum(&self) -> ChecksumOffload {
+        self.checksum
+    }
+
+    pub fn set_checksum(&mut self, checksum: ChecksumOffload) {
+        self.checksum = checksum;
+    }
 }
 
-impl HasSize for RxBuffer {
+impl<D: DmaDirection> HasSize for RxBuffer<D> {
     fn size(&self) -> usize {
         self.segment.size()
     }
 }
 
-impl HasDaddr for RxBuffer {
+impl<D: DmaDirection> HasDaddr for RxBuffer<D> {
     fn daddr(&self) -> Daddr {
         self.segment.daddr()
     }
 }
+
+#[cfg(ktest)]
+mod test {
+    use alloc::{vec, vec::Vec};
+
+    use ostd::{mm::dma::FromAndToDevice, prelude::*};
+
+    use super::*;
+
+    const SEGMENT_SIZE: usize = 64;
+    const HEADER_LEN: usize = 12;
+
+    /// Creates a buffer whose payload starts with `bytes` and is followed by garbage.
+    fn new_filled_buffer(
+        pool: &Arc<DmaPool<FromAndToDevice>>,
+        header_len: usize,
+        bytes: &[u8],
+    ) -> RxBuffer<FromAndToDevice> {
+        let buffer = RxBuffer::new(header_len, pool).unwrap();
+
+        let garbage = [0xffu8; SEGMENT_SIZE];
+        let mut writer = buffer.segment.writer().unwrap();
+        writer.write(&mut VmReader::from(garbage.as_slice()));
+        let mut writer = buffer.segment.writer().unwrap();
+        writer.skip(header_len);
+        assert_eq!(writer.write(&mut VmReader::from(bytes)), bytes.len());
+
+        buffer
+    }
+
+    #[ktest]
+    fn merge_rx_buffers() {
+        let pool = DmaPool::<FromAndToDevice>::new(SEGMENT_SIZE, 0, 10, false);
+        let packet: Vec<u8> = (0..150).collect();
+
+        // Only the first buffer contains the header. The last buffer is partially filled.
+        let (first, rest) = packet.split_at(SEGMENT_SIZE - HEADER_LEN);
+        let mut rx_buffer = new_filled_buffer(&pool, HEADER_LEN, first);
+        rx_buffer.set_payload_len(first.len());
+        for chunk in rest.chunks(SEGMENT_SIZE) {
+            let buffer = new_filled_buffer(&pool, 0, chunk);
+            rx_buffer.merge(buffer, chunk.len());
+        }
+        assert_eq!(rx_buffer.payload_len(), packet.len());
+
+        let mut read_buf = vec![0u8; packet.len() + 1];
+        let read_len = rx_buffer.read_payload(&mut VmWriter::from(read_buf.as_mut_slice()));
+        assert_eq!(read_len, packet.len());
+        assert_eq!(&read_buf[..read_len], packet.as_slice());
+    }
+}
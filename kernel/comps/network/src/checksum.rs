@@ -0,0 +1,374 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Offloading of the TCP and UDP checksums and the TCP segmentation.
+
+use core::ops::Range;
+
+/// The state of the transport checksum of a packet.
+///
+/// This corresponds to the checksum fields in the `virtio_net_hdr` of virtio-net devices. See
+/// "5.1.6.2 Packet Transmission" and "5.1.6.4 Processing of Incoming Packets" in the virtio
+/// specification.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ChecksumOffload {
+    /// The checksum is complete (for outgoing packets) or has not been verified (for incoming
+    /// packets).
+    #[default]
+    None,
+    /// The checksum is partial.
+    ///
+    /// The checksum field contains the sum of the pseudo-header. The checksum of the bytes from
+    /// `start` to the end of the packet should be stored at `start + offset`.
+    ///
+    /// For outgoing packets, the device computes the checksum. Incoming packets with partial
+    /// checksums come from a local peer (e.g., another virtual machine on the same host), so
+    /// their checksums need no verification.
+    Partial { start: u16, offset: u16 },
+    /// The checksum of an incoming packet has been verified by the device.
+    Verified,
+}
+
+/// Prepares the partial checksum of an outgoing Ethernet frame.
+///
+/// This function should only be called for the frames whose checksums are left to the device by
+/// the network stack. If the frame is a TCP or UDP packet, this function fills the checksum field
+/// with the sum of the pseudo-header and returns [`ChecksumOffload::Partial`], so that the device
+/// can complete the checksum. Otherwise, the frame is left untouched.
+pub(crate) fn offload_tx_checksum(frame: &mut [u8]) -> ChecksumOffload {
+    let Some(transport) = TransportHeader::parse(frame) else {
+        return ChecksumOffload::None;
+    };
+
+    let csum_field = transport.csum_field();
+    let partial_sum = fold_checksum(transport.pseudo_header_sum(frame));
+    frame[csum_field].copy_from_slice(&partial_sum.to_be_bytes());
+
+    ChecksumOffload::Partial {
+        start: transport.offset as u16,
+        offset: transport.csum_offset as u16,
+    }
+}
+
+/// The TCP segmentation of an outgoing packet.
+///
+/// This corresponds to the GSO fields in the `virtio_net_hdr` of virtio-net devices. See
+/// "5.1.6.2 Packet Transmission" in the virtio specification.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TcpSegmentation {
+    /// Whether the IP version is IPv6.
+    pub is_ipv6: bool,
+    /// The length of the Ethernet, IP, and TCP headers, which are replicated in each segment.
+    pub header_len: u16,
+    /// The length of the TCP payload in each segment.
+    pub segment_size: u16,
+}
+
+/// Prepares the TCP segmentation of an outgoing Ethernet frame.
+///
+/// This function should only be called for the frames that are requested to be split into
+/// segments of `segment_size` by the network stack. The checksum of the frame should be prepared
+/// by [`offload_tx_checksum`] as well, since the device computes the checksum of each segment.
+///
+/// Returns `None` if the frame is not a TCP packet carried directly by IPv4 or IPv6.
+pub(crate) fn offload_tx_segmentation(frame: &[u8], segment_size: u16) -> Option<TcpSegmentation> {
+    let transport = TransportHeader::parse(frame)?;
+    if transport.protocol != IPPROTO_TCP {
+        return None;
+    }
+
+    let tcp_header_len = usize::from(frame[transport.offset + 12] >> 4) * 4;
+    if tcp_header_len < 20 || tcp_header_len > transport.len {
+        return None;
+    }
+
+    Some(TcpSegmentation {
+        is_ipv6: !transport.is_ipv4,
+        header_len: (transport.offset + tcp_header_len) as u16,
+        segment_size,
+    })
+}
+
+/// Verifies the checksum of an incoming Ethernet frame.
+///
+/// Only the checksums of TCP and UDP packets are verified. This function returns `true` for other
+/// frames, whose checksums should be verified by the network stack.
+pub(crate) fn verify_rx_checksum(frame: &[u8]) -> bool {
+    let Some(transport) = TransportHeader::parse(frame) else {
+        return true;
+    };
+
+    // A zero UDP checksum means that the checksum is not computed over IPv4.
+    if transport.is_udp_over_ipv4() && frame[transport.csum_field()] == [0, 0] {
+        return true;
+    }
+
+    let sum = transport.pseudo_header_sum(frame) + sum_words(&frame[transport.payload()]);
+    fold_checksum(sum) == 0xffff
+}
+
+/// The location of a TCP or UDP header in an Ethernet frame.
+struct TransportHeader {
+    /// The offset of the transport header.
+    offset: usize,
+    /// The length of the transport header and its payload.
+    len: usize,
+    /// The offset of the checksum field in the transport header.
+    csum_offset: usize,
+    /// The protocol number.
+    protocol: u8,
+    /// The range of the source and destination addresses in the IP header.
+    addrs: Range<usize>,
+    /// Whether the IP version is IPv4.
+    is_ipv4: bool,
+}
+
+impl TransportHeader {
+    /// Parses the IP header in an Ethernet frame.
+    ///
+    /// Returns `None` if the frame is ill-formed, or if it is not a TCP or UDP packet carried
+    /// directly by IPv4 or IPv6. IP fragments and IPv6 extension headers are not supported.
+    fn parse(frame: &[u8]) -> Option<Self> {
+        let ethertype = u16::from_be_bytes(frame.get(12..ETHER_HEADER_LEN)?.try_into().unwrap());
+        let ip_header = &frame[ETHER_HEADER_LEN..];
+
+        let (header_len, total_len, protocol, addrs) = match ethertype {
+            ETHERTYPE_IPV4 => {
+                if ip_header.len() < IPV4_HEADER_LEN || ip_header[0] >> 4 != 4 {
+                    return None;
+                }
+                let header_len = usize::from(ip_header[0] & 0x0f) * 4;
+                let total_len = usize::from(u16::from_be_bytes([ip_header[2], ip_header[3]]));
+                // Fragments cannot be checked individually.
+                if u16::from_be_bytes([ip_header[6], ip_header[7]]) & IPV4_FRAG_MASK != 0 {
+                    return None;
+                }
+                (header_len, total_len, ip_header[9], 12..20)
+            }
+            ETHERTYPE_IPV6 => {
+                if ip_header.len() < IPV6_HEADER_LEN || ip_header[0] >> 4 != 6 {
+                    return None;
+                }
+                let payload_len = usize::from(u16::from_be_bytes([ip_header[4], ip_header[5]]));
+                (
+                    IPV6_HEADER_LEN,
+                    IPV6_HEADER_LEN + payload_len,
+                    ip_header[6],
+                    8..40,
+                )
+            }
+            _ => return None,
+        };
+        if header_len < IPV4_HEADER_LEN || header_len > total_len || total_len > ip_header.len() {
+            return None;
+        }
+
+        let (csum_offset, min_len) = match protocol {
+            IPPROTO_TCP => (16, 20),
+            IPPROTO_UDP => (6, 8),
+            _ => return None,
+        };
+        let len = total_len - header_len;
+        if len < min_len {
+            return None;
+        }
+
+        Some(Self {
+            offset: ETHER_HEADER_LEN + header_len,
+            len,
+            csum_offset,
+            protocol,
+            addrs: ETHER_HEADER_LEN + addrs.start..ETHER_HEADER_LEN + addrs.end,
+            is_ipv4: ethertype == ETHERTYPE_IPV4,
+        })
+    }
+
+    fn is_udp_over_ipv4(&self) -> bool {
+        self.protocol == IPPROTO_UDP && self.is_ipv4
+    }
+
+    fn csum_field(&self) -> Range<usize> {
+        let start = self.offset + self.csum_offset;
+        start..start + 2
+    }
+
+    fn payload(&self) -> Range<usize> {
+        self.offset..self.offset + self.len
+    }
+
+    /// Returns the unfolded sum of the pseudo-header.
+    fn pseudo_header_sum(&self, frame: &[u8]) -> u64 {
+        sum_words(&frame[self.addrs.clone()]) + u64::from(self.protocol) + self.len as u64
+    }
+}
+
+/// Returns the unfolded sum of the 16-bit words in the bytes.
+///
+/// If the number of bytes is odd, the last byte is padded with zero.
+fn sum_words(bytes: &[u8]) -> u64 {
+    bytes
+        .chunks(2)
+        .map(|chunk| u64::from(u16::from_be_bytes([chunk[0], *chunk.get(1).unwrap_or(&0)])))
+        .sum()
+}
+
+/// Folds the sum into a 16-bit one's complement sum.
+///
+/// Reference: <https://datatracker.ietf.org/doc/html/rfc1071>
+fn fold_checksum(mut sum: u64) -> u16 {
+    while sum >> 16 != 0 {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    sum as u16
+}
+
+const ETHER_HEADER_LEN: usize = 14;
+const ETHERTYPE_IPV4: u16 = 0x0800;
+const ETHERTYPE_IPV6: u16 = 0x86dd;
+
+const IPV4_HEADER_LEN: usize = 20;
+const IPV6_HEADER_LEN: usize = 40;
+/// The mask of the "More Fragments" flag and the fragment offset.
+const IPV4_FRAG_MASK: u16 = 0x3fff;
+
+const IPPROTO_TCP: u8 = 6;
+const IPPROTO_UDP: u8 = 17;
+
+#[cfg(ktest)]
+mod test {
+    use alloc::{vec, vec::Vec};
+
+    use ostd::prelude::*;
+
+    use super::*;
+
+    const PAYLOAD: &[u8] = b"hello";
+
+    fn new_ipv4_udp_frame() -> Vec<u8> {
+        let udp_len = 8 + PAYLOAD.len();
+        let ip_len = (IPV4_HEADER_LEN + udp_len) as u16;
+
+        let mut frame = vec![0u8; 12];
+        frame.extend_from_slice(&ETHERTYPE_IPV4.to_be_bytes());
+        frame.extend_from_slice(&[0x45, 0x00]);
+        frame.extend_from_slice(&ip_len.to_be_bytes());
+        // The identification, the "Don't Fragment" flag, the TTL, the protocol, and the checksum.
+        frame.extend_from_slice(&[0, 0, 0x40, 0, 64, IPPROTO_UDP, 0, 0]);
+        frame.extend_from_slice(&[10, 0, 0, 1, 10, 0, 0, 2]);
+        frame.extend_from_slice(&[0x04, 0xd2, 0x16, 0x2e]);
+        frame.extend_from_slice(&(udp_len as u16).to_be_bytes());
+        frame.extend_from_slice(&[0, 0]);
+        frame.extend_from_slice(PAYLOAD);
+        frame
+    }
+
+    fn new_ipv6_tcp_frame() -> Vec<u8> {
+        let tcp_len = 20 + PAYLOAD.len();
+
+        let mut frame = vec![0u8; 12];
+        frame.extend_from_slice(&ETHERTYPE_IPV6.to_be_bytes());
+        frame.extend_from_slice(&[0x60, 0, 0, 0]);
+        frame.extend_from_slice(&(tcp_len as u16).to_be_bytes());
+        frame.extend_from_slice(&[IPPROTO_TCP, 64]);
+        frame.extend_from_slice(&[0xfe, 0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]);
+        frame.extend_from_slice(&[0xfe, 0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2]);
+        // The ports, the sequence number, and the acknowledgment number.
+        frame.extend_from_slice(&[0x04, 0xd2, 0x16, 0x2e, 0, 0, 0, 1, 0, 0, 0, 0]);
+        // The data offset, the flags, the window size, the checksum, and the urgent pointer.
+        frame.extend_from_slice(&[0x50, 0x18, 0xff, 0xff, 0, 0, 0, 0]);
+        frame.extend_from_slice(PAYLOAD);
+        frame
+    }
+
+    /// Completes the partial checksum like a device.
+    fn complete_checksum(frame: &mut [u8], checksum: ChecksumOffload) {
+        let ChecksumOffload::Partial { start, offset } = checksum else {
+            panic!("the checksum is not partial: {:?}", checksum);
+        };
+        let (start, offset) = (usize::from(start), usize::from(offset));
+
+        let csum = !fold_checksum(sum_words(&frame[start..]));
+        frame[start + offset..start + offset + 2].copy_from_slice(&csum.to_be_bytes());
+    }
+
+    #[ktest]
+    fn complete_ipv4_udp_checksum() {
+        let mut frame = new_ipv4_udp_frame();
+
+        let checksum = offload_tx_checksum(&mut frame);
+        assert_eq!(
+            checksum,
+            ChecksumOffload::Partial {
+                start: 34,
+                offset: 6
+            }
+        );
+
+        complete_checksum(&mut frame, checksum);
+        assert!(verify_rx_checksum(&frame));
+
+        *frame.last_mut().unwrap() ^= 1;
+        assert!(!verify_rx_checksum(&frame));
+    }
+
+    #[ktest]
+    fn complete_ipv6_tcp_checksum() {
+        let mut frame = new_ipv6_tcp_frame();
+
+        let checksum = offload_tx_checksum(&mut frame);
+        assert_eq!(
+            checksum,
+            ChecksumOffload::Partial {
+                start: 54,
+                offset: 16
+            }
+        );
+
+        complete_checksum(&mut frame, checksum);
+        assert!(verify_rx_checksum(&frame));
+
+        *frame.last_mut().unwrap() ^= 1;
+        assert!(!verify_rx_checksum(&frame));
+    }
+
+    #[ktest]
+    fn overwrite_checksum_field() {
+        let mut frame = new_ipv4_udp_frame();
+        let checksum = offload_tx_checksum(&mut frame);
+        let partial = frame.clone();
+
+        // The checksum field is not necessarily zero (e.g., in reused buffers), but it is always
+        // overwritten with the sum of the pseudo-header.
+        frame[40..42].copy_from_slice(&[0x12, 0x34]);
+        assert_eq!(offload_tx_checksum(&mut frame), checksum);
+        assert_eq!(frame, partial);
+    }
+
+    #[ktest]
+    fn segment_ipv6_tcp_frame() {
+        let frame = new_ipv6_tcp_frame();
+
+        assert_eq!(
+            offload_tx_segmentation(&frame, 2),
+            Some(TcpSegmentation {
+                is_ipv6: true,
+                header_len: 74,
+                segment_size: 2,
+            })
+        );
+
+        // UDP packets cannot be split into TCP segments.
+        assert_eq!(offload_tx_segmentation(&new_ipv4_udp_frame(), 2), None);
+    }
+
+    #[ktest]
+    fn skip_unknown_frame() {
+        let mut frame = new_ipv4_udp_frame();
+        // Change the protocol to ICMP.
+        frame[23] = 1;
+
+        let unchanged = frame.clone();
+        assert_eq!(offload_tx_checksum(&mut frame), ChecksumOffload::None);
+        assert_eq!(frame, unchanged);
+        assert!(verify_rx_checksum(&frame));
+    }
+}
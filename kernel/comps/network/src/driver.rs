@@ -1,71 +1,166 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use alloc::vec;
+use alloc::{sync::Arc, vec, vec::Vec};
 
 use aster_bigtcp::{
-    device::{self, NotifyDevice},
+    device::{self, ChecksumCapabilities, FilterDevice, NotifyDevice, OffloadDevice, TxOffload},
     time::Instant,
+    wire::EthernetAddress,
 };
-use ostd::mm::VmWriter;
+use ostd::{mm::VmWriter, warn};
 
-use crate::{AnyNetworkDevice, buffer::RxBuffer};
+use crate::{
+    AnyNetworkDevice, EthernetAddr,
+    checksum::{ChecksumOffload, offload_tx_checksum, offload_tx_segmentation, verify_rx_checksum},
+};
+
+/// A handle via which the network stack polls a network device.
+///
+/// The network stack requires mutable references to poll the device (see
+/// [`WithDevice`](aster_bigtcp::device::WithDevice)). Since the device protects its states with
+/// its own locks (see [`AnyNetworkDevice`]), each polling process can use its own handle without
+/// locking the whole device.
+pub struct DeviceHandle(Arc<dyn AnyNetworkDevice>);
+
+impl DeviceHandle {
+    /// Creates a handle of the device.
+    pub fn new(device: Arc<dyn AnyNetworkDevice>) -> Self {
+        Self(device)
+    }
+}
 
-impl device::Device for dyn AnyNetworkDevice {
+impl device::Device for DeviceHandle {
     type RxToken<'a> = RxToken;
     type TxToken<'a> = TxToken<'a>;
 
     fn receive(&mut self, _timestamp: Instant) -> Option<(Self::RxToken<'_>, Self::TxToken<'_>)> {
-        if self.can_receive() && self.can_send() {
-            let rx_buffer = self.receive().unwrap();
-            Some((RxToken(rx_buffer), TxToken(self)))
-        } else {
-            None
+        let device = self.0.as_ref();
+        let checksum_caps = device.capabilities().checksum;
+
+        while device.can_receive() && device.can_send() {
+            let Ok(rx_buffer) = device.receive() else {
+                break;
+            };
+
+            let mut packet = vec![0u8; rx_buffer.payload_len()];
+            rx_buffer.read_payload(&mut VmWriter::from(packet.as_mut_slice()));
+
+            // If the network stack does not verify the checksums, we must verify those that the
+            // device has not verified.
+            if offloads_rx_checksum(&checksum_caps)
+                && rx_buffer.checksum() == ChecksumOffload::None
+                && !verify_rx_checksum(&packet)
+            {
+                continue;
+            }
+
+            return Some((RxToken(packet), TxToken(device)));
         }
+
+        None
     }
 
     fn transmit(&mut self, _timestamp: Instant) -> Option<Self::TxToken<'_>> {
-        if self.can_send() {
-            Some(TxToken(self))
+        if self.0.can_send() {
+            Some(TxToken(self.0.as_ref()))
         } else {
             None
         }
     }
 
     fn capabilities(&self) -> device::DeviceCapabilities {
-        self.capabilities()
+        self.0.capabilities()
     }
 }
 
-impl NotifyDevice for dyn AnyNetworkDevice {
+impl NotifyDevice for DeviceHandle {
     fn notify_poll_end(&mut self) {
-        self.notify_poll_end();
+        self.0.notify_poll_end();
     }
 }
 
-pub struct RxToken(RxBuffer);
+impl OffloadDevice for DeviceHandle {
+    fn max_tso_len(&self) -> Option<usize> {
+        self.0.max_tso_len()
+    }
+
+    fn consume_with_offload<R, F>(token: TxToken<'_>, len: usize, offload: TxOffload, f: F) -> R
+    where
+        F: FnOnce(&mut [u8]) -> R,
+    {
+        token.consume_with_offload(len, offload, f)
+    }
+}
+
+impl FilterDevice for DeviceHandle {
+    fn set_rx_filter(&mut self, is_promisc: bool, multicast_addrs: &[EthernetAddress]) {
+        let multicast_addrs = multicast_addrs
+            .iter()
+            .map(|addr| EthernetAddr(addr.0))
+            .collect::<Vec<_>>();
+        self.0.set_rx_filter(is_promisc, &multicast_addrs);
+    }
+}
+
+pub struct RxToken(Vec<u8>);
 
 impl device::RxToken for RxToken {
     fn consume<R, F>(self, f: F) -> R
     where
         F: FnOnce(&[u8]) -> R,
     {
-        let mut payload = self.0.payload();
-        let mut buffer = vec![0u8; payload.remain()];
-        payload.read(&mut VmWriter::from(&mut buffer as &mut [u8]));
-        f(&buffer)
+        f(&self.0)
     }
 }
 
-pub struct TxToken<'a>(&'a mut dyn AnyNetworkDevice);
+pub struct TxToken<'a>(&'a dyn AnyNetworkDevice);
 
 impl device::TxToken for TxToken<'_> {
     fn consume<R, F>(self, len: usize, f: F) -> R
+    where
+        F: FnOnce(&mut [u8]) -> R,
+    {
+        self.consume_with_offload(len, TxOffload::default(), f)
+    }
+}
+
+impl TxToken<'_> {
+    fn consume_with_offload<R, F>(self, len: usize, offload: TxOffload, f: F) -> R
     where
         F: FnOnce(&mut [u8]) -> R,
     {
         let mut buffer = vec![0u8; len];
         let res = f(&mut buffer);
-        self.0.send(&buffer).expect("Send packet failed");
+
+        let checksum = if offload.checksum {
+            debug_assert!(offloads_tx_checksum(&self.0.capabilities().checksum));
+            offload_tx_checksum(&mut buffer)
+        } else {
+            ChecksumOffload::None
+        };
+        let segmentation = offload.segment_size.and_then(|segment_size| {
+            debug_assert!(self.0.max_tso_len().is_some());
+            offload_tx_segmentation(&buffer, segment_size)
+        });
+        // Like a real network device, the packet is dropped if it cannot be sent. The network
+        // stack will retransmit it if necessary.
+        if let Err(err) = self.0.send(&buffer, checksum, segmentation) {
+            warn!("failed to send a packet: {:?}", err);
+        }
+
         res
     }
 }
+
+/// Returns whether the TCP and UDP checksums of outgoing packets can be left to the device.
+///
+/// In this case, the network stack requests the offloads via [`TxOffload::checksum`].
+fn offloads_tx_checksum(caps: &ChecksumCapabilities) -> bool {
+    !caps.tcp.tx() && !caps.udp.tx()
+}
+
+/// Returns whether the TCP and UDP checksums of incoming packets are not verified by the network
+/// stack.
+fn offloads_rx_checksum(caps: &ChecksumCapabilities) -> bool {
+    !caps.tcp.rx() && !caps.udp.rx()
+}
@@ -4,7 +4,15 @@
 #![deny(unsafe_code)]
 #![feature(trait_alias)]
 
+// Set this crate's log prefix for `ostd::log`.
+macro_rules! __log_prefix {
+    () => {
+        "network: "
+    };
+}
+
 mod buffer;
+mod checksum;
 pub mod dma_pool;
 mod driver;
 
@@ -21,7 +29,9 @@ use aster_softirq::{
     softirq_id::{NETWORK_RX_SOFTIRQ_ID, NETWORK_TX_SOFTIRQ_ID},
 };
 pub use buffer::{RxBuffer, TxBuffer, TxBufferBuilder};
+pub use checksum::{ChecksumOffload, TcpSegmentation};
 use component::{ComponentInitError, init_component};
+pub use driver::DeviceHandle;
 use ostd::sync::SpinLock;
 use spin::Once;
 
@@ -36,6 +46,10 @@ pub enum NetError {
     NoMemory,
 }
 
+/// A network device.
+///
+/// The methods can be called concurrently (e.g., from the polling process and the softirq
+/// handlers), so the device should protect its states with its own locks.
 pub trait AnyNetworkDevice: Send + Sync + Any + Debug {
     // ================Device Information=================
 
@@ -49,28 +63,45 @@ pub trait AnyNetworkDevice: Send + Sync + Any + Debug {
 
     /// Receives a packet from network. If packet is ready, returns a `RxBuffer` containing the packet.
     /// Otherwise, return [`NetError::NotReady`].
-    fn receive(&mut self) -> Result<RxBuffer, NetError>;
+    ///
+    /// If the device verifies checksums, it should report it via [`RxBuffer::set_checksum`].
+    fn receive(&self) -> Result<RxBuffer, NetError>;
+
+    /// Returns the maximum length of the IP packets that the device can split into TCP segments.
+    ///
+    /// Returns `None` if the device does not support TCP segmentation offload (TSO).
+    fn max_tso_len(&self) -> Option<usize>;
 
     /// Sends a packet to network.
-    fn send(&mut self, packet: &[u8]) -> Result<(), NetError>;
+    ///
+    /// If `checksum` is [`ChecksumOffload::Partial`], the device should complete the checksum
+    /// before sending the packet. If `segmentation` is not `None`, the device should split the
+    /// packet into TCP segments, whose checksums are always partial.
+    fn send(
+        &self,
+        packet: &[u8],
+        checksum: ChecksumOffload,
+        segmentation: Option<TcpSegmentation>,
+    ) -> Result<(), NetError>;
 
     /// Frees processes tx buffers.
-    fn free_processed_tx_buffers(&mut self);
+    fn free_processed_tx_buffers(&self);
 
     /// Notifies the device driver that a polling operation has ended.
     ///
-    /// The driver can assume that the device remains protected by acquiring a poll lock
-    /// for the entire duration of the polling process.
-    /// Thus two polling process cannot happen simultaneously.
-    fn notify_poll_end(&mut self);
+    /// Polling operations may happen simultaneously on different CPUs.
+    fn notify_poll_end(&self);
+
+    /// Sets the frames that the device should receive.
+    ///
+    /// Besides the broadcast frames and the frames sent to [`Self::mac_addr`], the device should
+    /// receive the frames sent to `multicast_addrs`, or all frames if `is_promisc` is true.
+    fn set_rx_filter(&self, is_promisc: bool, multicast_addrs: &[EthernetAddr]);
 }
 
 pub trait NetDeviceCallback = Fn() + Send + Sync + 'static;
 
-pub fn register_device(
-    name: String,
-    device: Arc<SpinLock<dyn AnyNetworkDevice, BottomHalfDisabled>>,
-) {
+pub fn register_device(name: String, device: Arc<dyn AnyNetworkDevice>) {
     COMPONENT
         .get()
         .unwrap()
@@ -79,7 +110,7 @@ pub fn register_device(
         .insert(name, NetworkDeviceIrqCallbackSet::new(device));
 }
 
-pub fn get_device(str: &str) -> Option<Arc<SpinLock<dyn AnyNetworkDevice, BottomHalfDisabled>>> {
+pub fn get_device(str: &str) -> Option<Arc<dyn AnyNetworkDevice>> {
     let table = COMPONENT.get().unwrap().network_device_table.lock();
     let callbacks = table.get(str)?;
     Some(callbacks.device.clone())
@@ -132,11 +163,9 @@ fn handle_tx_softirq() {
     // rather than processing events for all devices.
     // This issue should be addressed once new network devices are added.
     for callback_set in device_table.values() {
-        let can_send = {
-            let mut device = callback_set.device.lock();
-            device.free_processed_tx_buffers();
-            device.can_send()
-        };
+        let device = &callback_set.device;
+        device.free_processed_tx_buffers();
+        let can_send = device.can_send();
 
         if !can_send {
             continue;
@@ -181,7 +210,7 @@ fn init() -> Result<(), ComponentInitError> {
 }
 
 type NetDeviceCallbackListRef = Arc<SpinLock<Vec<Arc<dyn NetDeviceCallback>>, BottomHalfDisabled>>;
-type NetworkDeviceRef = Arc<SpinLock<dyn AnyNetworkDevice, BottomHalfDisabled>>;
+type NetworkDeviceRef = Arc<dyn AnyNetworkDevice>;
 
 struct Component {
     /// Device list, the key is device name, value is (callbacks, device);
@@ -51,7 +51,44 @@ bitflags! {
 
 impl NetworkFeatures {
     pub(super) fn support_features() -> Self {
-        NetworkFeatures::VIRTIO_NET_F_MAC | NetworkFeatures::VIRTIO_NET_F_STATUS
+        NetworkFeatures::VIRTIO_NET_F_CSUM
+            | NetworkFeatures::VIRTIO_NET_F_GUEST_CSUM
+            | NetworkFeatures::VIRTIO_NET_F_MAC
+            | NetworkFeatures::VIRTIO_NET_F_GUEST_TSO4
+            | NetworkFeatures::VIRTIO_NET_F_GUEST_TSO6
+            | NetworkFeatures::VIRTIO_NET_F_GUEST_ECN
+            | NetworkFeatures::VIRTIO_NET_F_HOST_TSO4
+            | NetworkFeatures::VIRTIO_NET_F_HOST_TSO6
+            | NetworkFeatures::VIRTIO_NET_F_MRG_RXBUF
+            | NetworkFeatures::VIRTIO_NET_F_STATUS
+            | NetworkFeatures::VIRTIO_NET_F_CTRL_VQ
+            | NetworkFeatures::VIRTIO_NET_F_CTRL_RX
+            | NetworkFeatures::VIRTIO_NET_F_MQ
+    }
+
+    /// Removes the features whose dependencies are not negotiated.
+    ///
+    /// See "5.1.3.1 Feature bit requirements" in the virtio specification.
+    pub(super) fn remove_unmet_dependencies(mut self) -> Self {
+        // Receiving TSO packets requires merging the receive buffers, since each receive buffer is
+        // too small to hold a TSO packet.
+        if !self.contains(Self::VIRTIO_NET_F_GUEST_CSUM | Self::VIRTIO_NET_F_MRG_RXBUF) {
+            self.remove(Self::VIRTIO_NET_F_GUEST_TSO4 | Self::VIRTIO_NET_F_GUEST_TSO6);
+        }
+        if !self.intersects(Self::VIRTIO_NET_F_GUEST_TSO4 | Self::VIRTIO_NET_F_GUEST_TSO6) {
+            self.remove(Self::VIRTIO_NET_F_GUEST_ECN);
+        }
+        // Sending TSO packets requires the device to compute their checksums. In addition, the
+        // network stack cannot request TSO only for IPv4 or IPv6 packets, so both are required.
+        if !self.contains(
+            Self::VIRTIO_NET_F_CSUM | Self::VIRTIO_NET_F_HOST_TSO4 | Self::VIRTIO_NET_F_HOST_TSO6,
+        ) {
+            self.remove(Self::VIRTIO_NET_F_HOST_TSO4 | Self::VIRTIO_NET_F_HOST_TSO6);
+        }
+        if !self.contains(Self::VIRTIO_NET_F_CTRL_VQ) {
+            self.remove(Self::VIRTIO_NET_F_CTRL_RX | Self::VIRTIO_NET_F_MQ);
+        }
+        self
     }
 }
 
@@ -69,7 +106,7 @@ bitflags! {
 pub(super) struct VirtioNetConfig {
     pub mac: EthernetAddr,
     pub status: Status,
-    max_virtqueue_pairs: u16,
+    pub max_virtqueue_pairs: u16,
     pub mtu: u16,
     speed: u32,
     duplex: u8,
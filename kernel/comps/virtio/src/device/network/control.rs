@@ -0,0 +1,162 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The control queue of a virtio-net device.
+
+use alloc::{sync::Arc, vec::Vec};
+use core::hint::spin_loop;
+
+use aster_network::EthernetAddr;
+use aster_util::mem_obj_slice::Slice;
+use ostd::mm::{PAGE_SIZE, VmIo, dma::DmaStream};
+
+use crate::{device::VirtioDeviceError, queue::VirtQueue, transport::VirtioTransport};
+
+/// The control queue of a virtio-net device.
+///
+/// The driver uses the control queue to send commands (e.g., to configure the receive filter) to
+/// the device. Commands are sent synchronously: each command waits until the device acknowledges
+/// it.
+///
+/// See "5.1.6.5 Control Virtqueue" in the virtio specification for the command formats.
+pub(super) struct ControlQueue {
+    queue: VirtQueue,
+    buffer: Arc<DmaStream>,
+}
+
+impl ControlQueue {
+    const QUEUE_SIZE: u16 = 16;
+
+    /// The maximum number of multicast addresses in the receive filter.
+    ///
+    /// If there are more multicast addresses, the device should receive all multicast frames.
+    pub(super) const MAX_MULTICAST_ADDRS: usize = 256;
+
+    /// Creates the control queue.
+    ///
+    /// The control queue follows the receive and send queues, so its index is twice the maximum
+    /// number of queue pairs.
+    pub(super) fn new(
+        max_queue_pairs: u16,
+        transport: &mut dyn VirtioTransport,
+    ) -> Result<Self, VirtioDeviceError> {
+        let queue = VirtQueue::new(max_queue_pairs * 2, Self::QUEUE_SIZE, transport)?;
+        let buffer =
+            Arc::new(DmaStream::alloc(1, false).map_err(VirtioDeviceError::ResourceAlloc)?);
+
+        Ok(Self { queue, buffer })
+    }
+
+    /// Sets whether the device receives all frames, regardless of their destination addresses.
+    ///
+    /// This method returns whether the device accepts the command.
+    pub(super) fn set_promisc(&mut self, is_promisc: bool) -> bool {
+        self.send_command(
+            VIRTIO_NET_CTRL_RX,
+            VIRTIO_NET_CTRL_RX_PROMISC,
+            &[&[is_promisc as u8]],
+        )
+    }
+
+    /// Sets whether the device receives all multicast frames.
+    ///
+    /// This method returns whether the device accepts the command.
+    pub(super) fn set_allmulti(&mut self, is_allmulti: bool) -> bool {
+        self.send_command(
+            VIRTIO_NET_CTRL_RX,
+            VIRTIO_NET_CTRL_RX_ALLMULTI,
+            &[&[is_allmulti as u8]],
+        )
+    }
+
+    /// Sets the multicast addresses from which the device receives frames.
+    ///
+    /// Frames sent to the device's own address are always received, so the unicast address table
+    /// is left empty.
+    ///
+    /// This method returns whether the device accepts the command.
+    pub(super) fn set_multicast_addrs(&mut self, multicast_addrs: &[EthernetAddr]) -> bool {
+        let unicast_table = 0u32.to_le_bytes();
+
+        let mut multicast_table = Vec::with_capacity(size_of::<u32>() + 6 * multicast_addrs.len());
+        multicast_table.extend_from_slice(&(multicast_addrs.len() as u32).to_le_bytes());
+        for addr in multicast_addrs {
+            multicast_table.extend_from_slice(&addr.0);
+        }
+
+        self.send_command(
+            VIRTIO_NET_CTRL_MAC,
+            VIRTIO_NET_CTRL_MAC_TABLE_SET,
+            &[&unicast_table, &multicast_table],
+        )
+    }
+
+    /// Sets the number of queue pairs that the device uses.
+    ///
+    /// Until this command is accepted, the device uses only the first queue pair. The received
+    /// packets are steered to the queue pairs automatically.
+    ///
+    /// This method returns whether the device accepts the command.
+    pub(super) fn set_queue_pairs(&mut self, num_queue_pairs: u16) -> bool {
+        self.send_command(
+            VIRTIO_NET_CTRL_MQ,
+            VIRTIO_NET_CTRL_MQ_VQ_PAIRS_SET,
+            &[&num_queue_pairs.to_le_bytes()],
+        )
+    }
+
+    /// Sends a command and waits for the device to acknowledge it.
+    ///
+    /// A command consists of the class and the command number, the command-specific data, and
+    /// an acknowledgement byte written by the device. This method returns whether the device
+    /// acknowledges the command with `VIRTIO_NET_OK`.
+    fn send_command(&mut self, class: u8, command: u8, data: &[&[u8]]) -> bool {
+        let mut inputs = Vec::with_capacity(data.len() + 1);
+        let mut offset = 0;
+        for bytes in core::iter::once(&[class, command][..]).chain(data.iter().copied()) {
+            let slice = Slice::new(&self.buffer, offset..offset + bytes.len());
+            slice.write_bytes(0, bytes).unwrap();
+            slice.sync_to_device().unwrap();
+            inputs.push(slice);
+            offset += bytes.len();
+        }
+        debug_assert!(offset < PAGE_SIZE);
+
+        let ack_slice = Slice::new(&self.buffer, offset..offset + 1);
+        ack_slice.write_val(0, &VIRTIO_NET_ERR).unwrap();
+        ack_slice.sync_to_device().unwrap();
+
+        let input_refs = inputs.iter().collect::<Vec<_>>();
+        if self
+            .queue
+            .add_dma_bufs(input_refs.as_slice(), &[&ack_slice])
+            .is_err()
+        {
+            return false;
+        }
+        if self.queue.should_notify() {
+            self.queue.notify();
+        }
+        while self.queue.pop_used().is_err() {
+            spin_loop();
+        }
+
+        ack_slice.sync_from_device().unwrap();
+        let ack: u8 = ack_slice.read_val(0).unwrap();
+        ack == VIRTIO_NET_OK
+    }
+}
+
+// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/virtio_net.h>
+
+const VIRTIO_NET_OK: u8 = 0;
+const VIRTIO_NET_ERR: u8 = 1;
+
+const VIRTIO_NET_CTRL_RX: u8 = 0;
+const VIRTIO_NET_CTRL_RX_PROMISC: u8 = 0;
+const VIRTIO_NET_CTRL_RX_ALLMULTI: u8 = 1;
+
+const VIRTIO_NET_CTRL_MAC: u8 = 1;
+const VIRTIO_NET_CTRL_MAC_TABLE_SET: u8 = 0;
+
+const VIRTIO_NET_CTRL_MQ: u8 = 4;
+const VIRTIO_NET_CTRL_MQ_VQ_PAIRS_SET: u8 = 0;
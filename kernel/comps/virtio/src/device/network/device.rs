@@ -1,40 +1,72 @@
 // SPDX-License-Identifier: MPL-2.0
 
 use alloc::{boxed::Box, string::ToString, sync::Arc, vec::Vec};
-use core::fmt::Debug;
+use core::{
+    fmt::Debug,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
 use aster_bigtcp::device::{Checksum, DeviceCapabilities, Medium};
-use aster_network::{AnyNetworkDevice, EthernetAddr, NetError, RxBuffer, TxBuffer};
+use aster_network::{
+    AnyNetworkDevice, ChecksumOffload, EthernetAddr, NetError, RxBuffer, TcpSegmentation, TxBuffer,
+};
+use aster_softirq::BottomHalfDisabled;
 use aster_util::slot_vec::SlotVec;
-use ostd::{arch::trap::TrapFrame, debug, sync::SpinLock, warn};
+use ostd::{
+    arch::trap::TrapFrame,
+    cpu::{CpuId, num_cpus},
+    debug,
+    sync::SpinLock,
+    warn,
+};
 
-use super::{config::VirtioNetConfig, header::VirtioNetHdr};
+use super::{config::VirtioNetConfig, control::ControlQueue, header::VirtioNetHdr};
 use crate::{
     device::{
         VirtioDeviceError,
         network::{
-            buffer::{RX_BUFFER_POOL, TX_BUFFER_POOL},
+            buffer::{RX_BUFFER_POOL, TX_BUFFER_LEN, TX_BUFFER_POOL},
             config::NetworkFeatures,
         },
     },
-    queue::{self, VirtQueue},
+    queue::VirtQueue,
     transport::{ConfigManager, VirtioTransport},
 };
 
 pub struct NetworkDevice {
     config_manager: ConfigManager<VirtioNetConfig>,
+    features: NetworkFeatures,
     // For smoltcp use
     caps: DeviceCapabilities,
     mac_addr: EthernetAddr,
+    /// The queue pairs.
+    ///
+    /// Each CPU sends packets via its own queue pair, so there are no more queue pairs than CPUs.
+    /// Each queue pair has its own lock, so CPUs using different queue pairs do not contend.
+    queue_pairs: Vec<SpinLock<QueuePair, BottomHalfDisabled>>,
+    /// The number of queue pairs that the device is using.
+    ///
+    /// The device uses only the first queue pair if it does not accept to use more of them.
+    num_active_pairs: usize,
+    /// The index of the queue pair from which the next packet is received.
+    next_rx_pair: AtomicUsize,
+    /// The control queue, which exists only if `VIRTIO_NET_F_CTRL_VQ` is negotiated.
+    control_queue: Option<SpinLock<ControlQueue, BottomHalfDisabled>>,
+    transport: Box<dyn VirtioTransport>,
+}
+
+/// A pair of a receive queue and a send queue.
+struct QueuePair {
     send_queue: VirtQueue,
     recv_queue: VirtQueue,
-    // Since the virtio net header remains consistent for each sending packet,
-    // we store it to avoid recreating the header repeatedly.
-    header: VirtioNetHdr,
-    tx_buffers: Vec<Option<TxBuffer>>,
+    /// The buffers of the packets being sent, indexed by their tokens.
+    ///
+    /// A large packet is sent in multiple buffers, where only the first buffer contains the
+    /// header.
+    tx_buffers: Vec<Vec<TxBuffer>>,
+    /// The maximum number of buffers that a packet can span.
+    max_tx_buffers: usize,
     rx_buffers: SlotVec<RxBuffer>,
-    new_rx_buffer: Option<RxBuffer>,
-    transport: Box<dyn VirtioTransport>,
     poll_stat: PollStatistics,
 }
 
@@ -66,6 +98,7 @@ impl NetworkDevice {
             );
         }
 
+        let network_features = network_features.remove_unmet_dependencies();
         debug!("{:?}", network_features);
         network_features.bits()
     }
@@ -82,40 +115,26 @@ impl NetworkDevice {
 
         let caps = init_caps(&features, &config);
 
-        let mut send_queue = VirtQueue::new(QUEUE_SEND, QUEUE_SIZE, transport.as_mut())?;
-        send_queue.disable_callback();
-
-        let mut recv_queue = VirtQueue::new(QUEUE_RECV, QUEUE_SIZE, transport.as_mut())?;
+        let max_queue_pairs = if features.contains(NetworkFeatures::VIRTIO_NET_F_MQ) {
+            config.max_virtqueue_pairs.max(1)
+        } else {
+            1
+        };
+        let num_queue_pairs = (max_queue_pairs as usize).min(num_cpus());
 
-        let tx_buffers = (0..QUEUE_SIZE).map(|_| None).collect();
+        let max_tx_buffers = max_tso_len(&features).map_or(1, |max_len| {
+            (size_of::<VirtioNetHdr>() + ETHER_HEADER_LEN + max_len).div_ceil(TX_BUFFER_LEN)
+        });
 
-        let mut rx_buffers = SlotVec::new();
-        for i in 0..QUEUE_SIZE {
-            let rx_pool = RX_BUFFER_POOL.get().unwrap();
-            let rx_buffer = RxBuffer::new(size_of::<VirtioNetHdr>(), rx_pool)
-                .map_err(VirtioDeviceError::ResourceAlloc)?;
-            let token = recv_queue.add_output_bufs(&[&rx_buffer]).unwrap();
-            assert_eq!(i, token);
-            assert_eq!(rx_buffers.put(rx_buffer) as u16, i);
+        let mut queue_pairs = Vec::with_capacity(num_queue_pairs);
+        for index in 0..num_queue_pairs as u16 {
+            queue_pairs.push(QueuePair::new(index, max_tx_buffers, transport.as_mut())?);
         }
 
-        if recv_queue.should_notify() {
-            debug!("notify receive queue");
-            recv_queue.notify();
-        }
-
-        let mut device = Self {
-            config_manager,
-            caps,
-            mac_addr,
-            send_queue,
-            recv_queue,
-            header: VirtioNetHdr::default(),
-            tx_buffers,
-            rx_buffers,
-            new_rx_buffer: None,
-            transport,
-            poll_stat: PollStatistics::new(),
+        let mut control_queue = if features.contains(NetworkFeatures::VIRTIO_NET_F_CTRL_VQ) {
+            Some(ControlQueue::new(max_queue_pairs, transport.as_mut())?)
+        } else {
+            None
         };
 
         /// Interrupt handler if network device config space changes
@@ -131,28 +150,90 @@ impl NetworkDevice {
             aster_network::raise_receive_softirq();
         }
 
-        device
-            .transport
-            .register_cfg_callback(Box::new(config_space_change))?;
-        device
-            .transport
-            .register_queue_callback(QUEUE_SEND, Box::new(handle_send_event), true)?;
-        device
-            .transport
-            .register_queue_callback(QUEUE_RECV, Box::new(handle_recv_event), true)?;
-
-        device.transport.finish_init();
-
-        aster_network::register_device(
-            super::DEVICE_NAME.to_string(),
-            Arc::new(SpinLock::new(device)),
-        );
+        transport.register_cfg_callback(Box::new(config_space_change))?;
+        for index in 0..num_queue_pairs as u16 {
+            transport.register_queue_callback(
+                send_queue_index(index),
+                Box::new(handle_send_event),
+                true,
+            )?;
+            transport.register_queue_callback(
+                recv_queue_index(index),
+                Box::new(handle_recv_event),
+                true,
+            )?;
+        }
+
+        transport.finish_init();
+
+        // The control queue can only be used after the device is initialized.
+        let mut num_active_pairs = 1;
+        if num_queue_pairs > 1 {
+            let control_queue = control_queue.as_mut().unwrap();
+            if control_queue.set_queue_pairs(num_queue_pairs as u16) {
+                num_active_pairs = num_queue_pairs;
+            } else {
+                warn!("the device rejects {} queue pairs", num_queue_pairs);
+            }
+        }
+
+        let device = Self {
+            config_manager,
+            features,
+            caps,
+            mac_addr,
+            queue_pairs: queue_pairs.into_iter().map(SpinLock::new).collect(),
+            num_active_pairs,
+            next_rx_pair: AtomicUsize::new(0),
+            control_queue: control_queue.map(SpinLock::new),
+            transport,
+        };
+        aster_network::register_device(super::DEVICE_NAME.to_string(), Arc::new(device));
         Ok(())
     }
 
+    /// Returns the queue pair via which the current CPU sends packets.
+    fn tx_queue_pair(&self) -> &SpinLock<QueuePair, BottomHalfDisabled> {
+        let index = u32::from(CpuId::current_racy()) as usize % self.num_active_pairs;
+        &self.queue_pairs[index]
+    }
+}
+
+impl QueuePair {
+    fn new(
+        index: u16,
+        max_tx_buffers: usize,
+        transport: &mut dyn VirtioTransport,
+    ) -> Result<Self, VirtioDeviceError> {
+        let mut send_queue = VirtQueue::new(send_queue_index(index), QUEUE_SIZE, transport)?;
+        send_queue.disable_callback();
+
+        let recv_queue = VirtQueue::new(recv_queue_index(index), QUEUE_SIZE, transport)?;
+
+        let mut queue_pair = Self {
+            send_queue,
+            recv_queue,
+            tx_buffers: (0..QUEUE_SIZE).map(|_| Vec::new()).collect(),
+            max_tx_buffers,
+            rx_buffers: SlotVec::new(),
+            poll_stat: PollStatistics::new(),
+        };
+
+        let rx_pool = RX_BUFFER_POOL.get().unwrap();
+        for _ in 0..QUEUE_SIZE {
+            let rx_buffer = RxBuffer::new(size_of::<VirtioNetHdr>(), rx_pool)
+                .map_err(VirtioDeviceError::ResourceAlloc)?;
+            queue_pair.add_rx_buffer(rx_buffer);
+        }
+
+        Ok(queue_pair)
+    }
+
     /// Adds a `RxBuffer` to the receive queue.
-    fn add_rx_buffer(&mut self, rx_buffer: RxBuffer) -> Result<(), queue::AddBufsError> {
-        let token = self.recv_queue.add_output_bufs(&[&rx_buffer])?;
+    ///
+    /// The receive queue must have free descriptors.
+    fn add_rx_buffer(&mut self, rx_buffer: RxBuffer) {
+        let token = self.recv_queue.add_output_bufs(&[&rx_buffer]).unwrap();
         assert!(self.rx_buffers.put_at(token as usize, rx_buffer).is_none());
 
         self.poll_stat.received_packet += 1;
@@ -162,48 +243,99 @@ impl NetworkDevice {
             // we will notify the receive queue as soon as possible.
             self.notify_receive_queue();
         }
+    }
 
-        Ok(())
+    /// Adds new `RxBuffer`s to the receive queue until it is full.
+    fn refill_recv_queue(&mut self) {
+        let rx_pool = RX_BUFFER_POOL.get().unwrap();
+
+        while self.recv_queue.available_desc() > 0 {
+            // FIXME: If the allocation keeps failing until the receive queue becomes empty, the
+            // device can no longer notify us of new packets. The receive queue will then be
+            // refilled only when the device is polled for other reasons.
+            let Ok(rx_buffer) = RxBuffer::new(size_of::<VirtioNetHdr>(), rx_pool) else {
+                break;
+            };
+            self.add_rx_buffer(rx_buffer);
+        }
     }
 
     /// Receives a packet from network.
-    fn receive(&mut self) -> Result<RxBuffer, NetError> {
-        if self.new_rx_buffer.is_none() {
-            // FIXME: Ideally, we can reuse the returned buffer without creating new buffer.
-            // But this requires locking device to be compatible with smoltcp interface.
-            let rx_pool = RX_BUFFER_POOL.get().unwrap();
-            let new_rx_buffer = RxBuffer::new(size_of::<VirtioNetHdr>(), rx_pool)
-                .map_err(|_| NetError::NoMemory)?;
-
-            self.new_rx_buffer = Some(new_rx_buffer);
+    fn receive(&mut self, has_mrg_rxbuf: bool) -> Result<RxBuffer, NetError> {
+        loop {
+            let (token, len) = self
+                .recv_queue
+                .pop_used_with_min_bytes(size_of::<VirtioNetHdr>())
+                .map_err(|_| NetError::NotReady)?;
+            debug!("receive packet: token = {}, len = {}", token, len);
+
+            let mut rx_buffer = self.rx_buffers.remove(token as usize).unwrap();
+            rx_buffer.set_payload_len(len as usize - size_of::<VirtioNetHdr>());
+            let header = rx_buffer.buf().read_val::<VirtioNetHdr>().unwrap();
+
+            // If `VIRTIO_NET_F_MRG_RXBUF` is negotiated, a large packet can span multiple buffers,
+            // where only the first buffer contains the header. See "5.1.6.4 Processing of Incoming
+            // Packets" in the virtio specification.
+            let num_buffers = if has_mrg_rxbuf {
+                header.num_buffers()
+            } else {
+                1
+            };
+            let mut is_complete = true;
+            for _ in 1..num_buffers {
+                let Ok((token, len)) = self.recv_queue.pop_used() else {
+                    is_complete = false;
+                    break;
+                };
+                let buffer = self.rx_buffers.remove(token as usize).unwrap();
+                rx_buffer.merge(buffer, len as usize);
+            }
+
+            self.refill_recv_queue();
+
+            if !is_complete {
+                warn!("a packet that spans {} buffers is incomplete", num_buffers);
+                continue;
+            }
+
+            rx_buffer.set_checksum(header.rx_checksum());
+            return Ok(rx_buffer);
         }
+    }
 
-        let (token, len) = self
-            .recv_queue
-            .pop_used_with_min_bytes(size_of::<VirtioNetHdr>())
-            .map_err(|_| NetError::NotReady)?;
-        debug!("receive packet: token = {}, len = {}", token, len);
-
-        let mut rx_buffer = self.rx_buffers.remove(token as usize).unwrap();
-        rx_buffer.set_payload_len(len as usize - size_of::<VirtioNetHdr>());
-
-        let new_rx_buffer = self.new_rx_buffer.take().unwrap();
-        self.add_rx_buffer(new_rx_buffer).unwrap();
-
-        Ok(rx_buffer)
+    /// Returns whether a packet of any length can be sent.
+    fn can_send(&self) -> bool {
+        self.send_queue.available_desc() >= self.max_tx_buffers
     }
 
     /// Sends a packet to network.
-    fn send(&mut self, packet: &[u8]) -> Result<(), NetError> {
+    fn send(
+        &mut self,
+        packet: &[u8],
+        checksum: ChecksumOffload,
+        segmentation: Option<TcpSegmentation>,
+    ) -> Result<(), NetError> {
         if !self.can_send() {
             return Err(NetError::Busy);
         }
 
+        let header = VirtioNetHdr::new_tx(checksum, segmentation);
         let tx_pool = TX_BUFFER_POOL.get().unwrap();
-        let tx_buffer =
-            TxBuffer::new(&self.header, packet, tx_pool).map_err(|_| NetError::NoMemory)?;
 
-        let token = self.send_queue.add_input_bufs(&[&tx_buffer]).unwrap();
+        let (first, rest) =
+            packet.split_at(packet.len().min(TX_BUFFER_LEN - size_of::<VirtioNetHdr>()));
+        let mut tx_buffers = Vec::with_capacity(1 + rest.len().div_ceil(TX_BUFFER_LEN));
+        tx_buffers.push(TxBuffer::new(&header, first, tx_pool).map_err(|_| NetError::NoMemory)?);
+        for chunk in rest.chunks(TX_BUFFER_LEN) {
+            tx_buffers
+                .push(TxBuffer::new_continued(chunk, tx_pool).map_err(|_| NetError::NoMemory)?);
+        }
+        debug_assert!(tx_buffers.len() <= self.max_tx_buffers);
+
+        let token = self
+            .send_queue
+            .add_input_bufs(&tx_buffers.iter().collect::<Vec<_>>())
+            .unwrap();
 
         self.poll_stat.sent_packet += 1;
 
@@ -215,8 +347,8 @@ impl NetworkDevice {
 
         debug!("send packet, token = {}, len = {}", token, packet.len());
 
-        debug_assert!(self.tx_buffers[token as usize].is_none());
-        self.tx_buffers[token as usize] = Some(tx_buffer);
+        debug_assert!(self.tx_buffers[token as usize].is_empty());
+        self.tx_buffers[token as usize] = tx_buffers;
 
         self.free_processed_tx_buffers();
 
@@ -234,6 +366,12 @@ impl NetworkDevice {
         Ok(())
     }
 
+    fn free_processed_tx_buffers(&mut self) {
+        while let Ok((token, _)) = self.send_queue.pop_used() {
+            self.tx_buffers[token as usize].clear();
+        }
+    }
+
     fn notify_send_queue(&mut self) {
         if self.poll_stat.sent_packet == 0 {
             return;
@@ -277,35 +415,53 @@ fn init_caps(features: &NetworkFeatures, config: &VirtioNetConfig) -> DeviceCapa
         // If `VIRTIO_NET_F_MTU` is negotiated, the MTU is decided by the device.
         caps.max_transmission_unit = config.mtu as usize;
     } else {
-        // We do not support these features,
-        // so this asserts that they are _not_ negotiated.
-        //
-        // Without these features, the MTU is 1514 bytes per the virtio-net specification
+        // Without `VIRTIO_NET_F_MTU`, the MTU is 1514 bytes per the virtio-net specification
         // (see "5.1.6.3 Setting Up Receive Buffers" and "5.1.6.2 Packet Transmission").
-        assert!(
-            !features.contains(NetworkFeatures::VIRTIO_NET_F_GUEST_TSO4)
-                && !features.contains(NetworkFeatures::VIRTIO_NET_F_GUEST_TSO6)
-                && !features.contains(NetworkFeatures::VIRTIO_NET_F_GUEST_UFO)
+        //
+        // Larger TSO packets can be received if `VIRTIO_NET_F_GUEST_TSO4` or
+        // `VIRTIO_NET_F_GUEST_TSO6` is negotiated, which is possible only if the receive buffers
+        // can be merged.
+        debug_assert!(
+            !features.intersects(
+                NetworkFeatures::VIRTIO_NET_F_GUEST_TSO4 | NetworkFeatures::VIRTIO_NET_F_GUEST_TSO6
+            ) || features.contains(NetworkFeatures::VIRTIO_NET_F_MRG_RXBUF)
         );
         caps.max_transmission_unit = 1514;
     }
 
-    // We do not support checksum offloading.
-    // So the features must not be negotiated,
-    // and we must deliver fully checksummed packets to the device
-    // and validate all checksums for packets from the device.
-    assert!(
-        !features.contains(NetworkFeatures::VIRTIO_NET_F_CSUM)
-            && !features.contains(NetworkFeatures::VIRTIO_NET_F_GUEST_CSUM)
-    );
-    caps.checksum.tcp = Checksum::Both;
-    caps.checksum.udp = Checksum::Both;
+    // If `VIRTIO_NET_F_CSUM` is negotiated, the device computes the TCP and UDP checksums of
+    // outgoing packets. If `VIRTIO_NET_F_GUEST_CSUM` is negotiated, the device reports whether
+    // the checksums of incoming packets are valid, and `aster-network` verifies the rest of them.
+    // In both cases, the network stack need not compute or verify the checksums.
+    let l4_checksum = match (
+        features.contains(NetworkFeatures::VIRTIO_NET_F_CSUM),
+        features.contains(NetworkFeatures::VIRTIO_NET_F_GUEST_CSUM),
+    ) {
+        (false, false) => Checksum::Both,
+        (true, false) => Checksum::Rx,
+        (false, true) => Checksum::Tx,
+        (true, true) => Checksum::None,
+    };
+    caps.checksum.tcp = l4_checksum;
+    caps.checksum.udp = l4_checksum;
     caps.checksum.ipv4 = Checksum::Both;
     caps.checksum.icmpv4 = Checksum::Both;
 
     caps
 }
 
+/// Returns the maximum length of the IP packets that the device can split into TCP segments.
+fn max_tso_len(features: &NetworkFeatures) -> Option<usize> {
+    // The TSO packets are still IP packets, whose lengths cannot exceed 65535 bytes.
+    if features
+        .contains(NetworkFeatures::VIRTIO_NET_F_HOST_TSO4 | NetworkFeatures::VIRTIO_NET_F_HOST_TSO6)
+    {
+        Some(u16::MAX as usize)
+    } else {
+        None
+    }
+}
+
 impl AnyNetworkDevice for NetworkDevice {
     fn mac_addr(&self) -> EthernetAddr {
         self.mac_addr
@@ -316,30 +472,85 @@ impl AnyNetworkDevice for NetworkDevice {
     }
 
     fn can_receive(&self) -> bool {
-        self.recv_queue.can_pop()
+        self.queue_pairs
+            .iter()
+            .any(|queue_pair| queue_pair.lock().recv_queue.can_pop())
     }
 
     fn can_send(&self) -> bool {
-        self.send_queue.available_desc() >= 1
+        self.tx_queue_pair().lock().can_send()
+    }
+
+    fn receive(&self) -> Result<RxBuffer, NetError> {
+        let has_mrg_rxbuf = self
+            .features
+            .contains(NetworkFeatures::VIRTIO_NET_F_MRG_RXBUF);
+        let num_pairs = self.queue_pairs.len();
+
+        // Receive packets from the queue pairs in turn, so that no queue pair is starved.
+        for _ in 0..num_pairs {
+            let index = self.next_rx_pair.fetch_add(1, Ordering::Relaxed) % num_pairs;
+
+            if let Ok(rx_buffer) = self.queue_pairs[index].lock().receive(has_mrg_rxbuf) {
+                return Ok(rx_buffer);
+            }
+        }
+
+        Err(NetError::NotReady)
     }
 
-    fn receive(&mut self) -> Result<RxBuffer, NetError> {
-        self.receive()
+    fn max_tso_len(&self) -> Option<usize> {
+        max_tso_len(&self.features)
     }
 
-    fn send(&mut self, packet: &[u8]) -> Result<(), NetError> {
-        self.send(packet)
+    fn send(
+        &self,
+        packet: &[u8],
+        checksum: ChecksumOffload,
+        segmentation: Option<TcpSegmentation>,
+    ) -> Result<(), NetError> {
+        self.tx_queue_pair()
+            .lock()
+            .send(packet, checksum, segmentation)
     }
 
-    fn free_processed_tx_buffers(&mut self) {
-        while let Ok((token, _)) = self.send_queue.pop_used() {
-            self.tx_buffers[token as usize] = None;
+    fn free_processed_tx_buffers(&self) {
+        for queue_pair in self.queue_pairs.iter() {
+            queue_pair.lock().free_processed_tx_buffers();
         }
     }
 
-    fn notify_poll_end(&mut self) {
-        self.notify_send_queue();
-        self.notify_receive_queue();
+    fn notify_poll_end(&self) {
+        for queue_pair in self.queue_pairs.iter() {
+            let mut queue_pair = queue_pair.lock();
+            queue_pair.notify_send_queue();
+            queue_pair.notify_receive_queue();
+        }
+    }
+
+    fn set_rx_filter(&self, is_promisc: bool, multicast_addrs: &[EthernetAddr]) {
+        // Without `VIRTIO_NET_F_CTRL_RX`, the receive filter cannot be configured, so the device
+        // keeps filtering frames in its own way.
+        if !self
+            .features
+            .contains(NetworkFeatures::VIRTIO_NET_F_CTRL_RX)
+        {
+            return;
+        }
+        let Some(control_queue) = self.control_queue.as_ref() else {
+            return;
+        };
+        let mut control_queue = control_queue.lock();
+
+        let is_allmulti = multicast_addrs.len() > ControlQueue::MAX_MULTICAST_ADDRS;
+        let multicast_addrs = if is_allmulti { &[] } else { multicast_addrs };
+
+        if !control_queue.set_promisc(is_promisc)
+            || !control_queue.set_allmulti(is_allmulti)
+            || !control_queue.set_multicast_addrs(multicast_addrs)
+        {
+            warn!("the device rejects the receive filter");
+        }
     }
 }
 
@@ -348,14 +559,31 @@ impl Debug for NetworkDevice {
         f.debug_struct("NetworkDevice")
             .field("config", &self.config_manager.read_config())
             .field("mac_addr", &self.mac_addr)
+            .field("queue_pairs", &self.queue_pairs)
+            .field("transport", &self.transport)
+            .finish()
+    }
+}
+
+impl Debug for QueuePair {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("QueuePair")
             .field("send_queue", &self.send_queue)
             .field("recv_queue", &self.recv_queue)
-            .field("transport", &self.transport)
             .finish()
     }
 }
 
-const QUEUE_RECV: u16 = 0;
-const QUEUE_SEND: u16 = 1;
+/// Returns the index of the receive queue in the queue pair.
+const fn recv_queue_index(queue_pair: u16) -> u16 {
+    queue_pair * 2
+}
+
+/// Returns the index of the send queue in the queue pair.
+const fn send_queue_index(queue_pair: u16) -> u16 {
+    queue_pair * 2 + 1
+}
 
 const QUEUE_SIZE: u16 = 64;
+
+const ETHER_HEADER_LEN: usize = 14;
@@ -1,5 +1,6 @@
 // SPDX-License-Identifier: MPL-2.0
 
+use aster_network::{ChecksumOffload, TcpSegmentation};
 use bitflags::bitflags;
 use int_to_c_enum::TryFromInt;
 
@@ -19,6 +20,58 @@ pub(super) struct VirtioNetHdr {
                       // padding_reserved: u16,  // Only if VIRTIO_NET_F_HASH_REPORT negotiated
 }
 
+impl VirtioNetHdr {
+    /// Creates the header of an outgoing packet.
+    ///
+    /// If `segmentation` is not `None`, the checksum must be partial, because the device computes
+    /// the checksum of each segment.
+    pub(super) fn new_tx(checksum: ChecksumOffload, segmentation: Option<TcpSegmentation>) -> Self {
+        let ChecksumOffload::Partial { start, offset } = checksum else {
+            debug_assert!(segmentation.is_none());
+            return Self::default();
+        };
+
+        let mut header = Self {
+            flags: Flags::VIRTIO_NET_HDR_F_NEEDS_CSUM,
+            csum_start: start,
+            csum_offset: offset,
+            ..Self::default()
+        };
+        if let Some(segmentation) = segmentation {
+            let gso_type = if segmentation.is_ipv6 {
+                GsoType::VIRTIO_NET_HDR_GSO_TCPV6
+            } else {
+                GsoType::VIRTIO_NET_HDR_GSO_TCPV4
+            };
+            header.gso_type = gso_type as u8;
+            header.hdr_len = segmentation.header_len;
+            header.gso_size = segmentation.segment_size;
+        }
+        header
+    }
+
+    /// Returns the state of the checksum of an incoming packet.
+    pub(super) fn rx_checksum(&self) -> ChecksumOffload {
+        if self.flags.contains(Flags::VIRTIO_NET_HDR_F_NEEDS_CSUM) {
+            ChecksumOffload::Partial {
+                start: self.csum_start,
+                offset: self.csum_offset,
+            }
+        } else if self.flags.contains(Flags::VIRTIO_NET_HDR_F_DATA_VALID) {
+            ChecksumOffload::Verified
+        } else {
+            ChecksumOffload::None
+        }
+    }
+
+    /// Returns the number of buffers that an incoming packet spans.
+    ///
+    /// This is valid only if `VIRTIO_NET_F_MRG_RXBUF` is negotiated.
+    pub(super) fn num_buffers(&self) -> u16 {
+        self.num_buffers
+    }
+}
+
 bitflags! {
     #[repr(C)]
     #[derive(Default, Pod)]
@@ -30,7 +83,6 @@ bitflags! {
 }
 
 #[expect(non_camel_case_types)]
-#[expect(dead_code)]
 #[repr(u8)]
 #[derive(Clone, Copy, Debug, Default, TryFromInt)]
 pub(super) enum GsoType {
@@ -2,6 +2,7 @@
 
 mod buffer;
 mod config;
+mod control;
 pub mod device;
 mod header;
 
@@ -49,6 +49,6 @@ bitflags! {
 
 impl VsockFeatures {
     pub(super) const fn supported_features() -> Self {
-        Self::VIRTIO_VSOCK_F_STREAM
+        Self::VIRTIO_VSOCK_F_STREAM.union(Self::VIRTIO_VSOCK_F_SEQPACKET)
     }
 }
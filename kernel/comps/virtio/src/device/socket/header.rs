@@ -11,6 +11,8 @@ use int_to_c_enum::TryFromInt;
 pub enum VirtioVsockType {
     /// Identifies a byte-stream vsock connection.
     Stream = 1,
+    /// Identifies a message-boundary-preserving vsock connection.
+    SeqPacket = 2,
 }
 
 /// The operation encoded in a virtio-vsock packet.
@@ -43,6 +45,17 @@ bitflags! {
     }
 }
 
+bitflags! {
+    /// The message bits carried by `VirtioVsockOp::Rw` packets of `VirtioVsockType::SeqPacket`
+    /// connections.
+    pub struct VirtioVsockRwFlags: u32 {
+        /// Indicates that the packet ends a message.
+        const SEQ_EOM = 1;
+        /// Indicates that the packet ends a record (i.e., `MSG_EOR` is specified).
+        const SEQ_EOR = 2;
+    }
+}
+
 /// The common header of a virtio-vsock packet.
 #[repr(C, packed)]
 #[derive(Clone, Copy, Debug, Pod)]
@@ -70,7 +83,7 @@ pub struct VirtioVsockHdr {
 }
 
 impl VirtioVsockHdr {
-    /// Creates a stream-type virtio-vsock header.
+    /// Creates a virtio-vsock header.
     #[expect(
         clippy::too_many_arguments,
         reason = "the wire header fields map directly to the virtio-vsock specification"
@@ -81,6 +94,7 @@ impl VirtioVsockHdr {
         src_port: u32,
         dst_port: u32,
         len: u32,
+        type_: VirtioVsockType,
         op: VirtioVsockOp,
         flags: u32,
         buf_alloc: u32,
@@ -92,7 +106,7 @@ impl VirtioVsockHdr {
             src_port,
             dst_port,
             len,
-            type_: VirtioVsockType::Stream as u16,
+            type_: type_ as u16,
             op: op as u16,
             flags,
             buf_alloc,
@@ -100,6 +114,11 @@ impl VirtioVsockHdr {
         }
     }
 
+    /// Decodes and returns the socket type.
+    pub fn type_(&self) -> Option<VirtioVsockType> {
+        VirtioVsockType::try_from(self.type_).ok()
+    }
+
     /// Decodes and returns the packet operation.
     pub fn op(&self) -> Option<VirtioVsockOp> {
         VirtioVsockOp::try_from(self.op).ok()
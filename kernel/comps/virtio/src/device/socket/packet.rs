@@ -5,7 +5,7 @@
 use aster_network::{RxBuffer, TxBuffer, TxBufferBuilder};
 use ostd::{
     Result,
-    mm::{Infallible, VmReader, VmWriter},
+    mm::{HasSize, Infallible, VmReader, VmWriter},
 };
 
 use crate::device::socket::{
@@ -30,6 +30,26 @@ impl TxPacket {
     pub(super) fn inner(&self) -> &TxBuffer {
         &self.0
     }
+
+    /// Returns the packet header.
+    pub fn header(&self) -> VirtioVsockHdr {
+        self.0.buf().read_val::<VirtioVsockHdr>().unwrap()
+    }
+
+    /// Returns the payload length in bytes.
+    pub fn payload_len(&self) -> usize {
+        self.0.size() - size_of::<VirtioVsockHdr>()
+    }
+
+    /// Returns a reader over the packet payload.
+    ///
+    /// This allows the packet to be delivered locally (e.g., via a loopback transport) without
+    /// going through the device.
+    pub fn payload(&self) -> VmReader<'_, Infallible> {
+        let mut reader = self.0.buf();
+        reader.skip(size_of::<VirtioVsockHdr>());
+        reader
+    }
 }
 
 /// A builder that builds a [`TxPacket`] with payload before the header is finalized.
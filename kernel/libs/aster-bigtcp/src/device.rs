@@ -3,6 +3,7 @@
 pub use smoltcp::phy::{
     Checksum, ChecksumCapabilities, Device, DeviceCapabilities, Loopback, Medium, RxToken, TxToken,
 };
+use smoltcp::wire::EthernetAddress;
 
 /// A trait that allows to obtain a mutable reference of [`Device`].
 ///
@@ -24,3 +25,55 @@ pub trait NotifyDevice {
     /// Notifies the device driver that polling has ended.
     fn notify_poll_end(&mut self);
 }
+
+/// A trait for configuring the receive filter of device drivers.
+pub trait FilterDevice {
+    /// Sets the frames that the device should receive.
+    ///
+    /// Besides the broadcast frames and the frames sent to the device's own address, the device
+    /// should receive the frames sent to `multicast_addrs`, or all frames if `is_promisc` is true.
+    fn set_rx_filter(&mut self, is_promisc: bool, multicast_addrs: &[EthernetAddress]);
+}
+
+/// The work on an outgoing frame that is left to the device driver.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TxOffload {
+    /// Whether the TCP or UDP checksum is left to the device.
+    ///
+    /// If this is true, the frame is a TCP or UDP packet carried directly by IPv4 or IPv6, and
+    /// its checksum field is left as zero.
+    pub checksum: bool,
+    /// The size of the TCP payload in each segment, if the TCP packet should be split into
+    /// segments by the device (TSO).
+    ///
+    /// If this is not `None`, the frame is a TCP packet and [`Self::checksum`] is also true.
+    pub segment_size: Option<u16>,
+}
+
+/// A trait for offloading the work on outgoing frames to device drivers.
+pub trait OffloadDevice: Device {
+    /// Returns the maximum length of the IP packets that the device can split into TCP segments.
+    ///
+    /// The default implementation returns `None`, which means that the device does not support
+    /// TCP segmentation offload (TSO).
+    fn max_tso_len(&self) -> Option<usize> {
+        None
+    }
+
+    /// Consumes the token to transmit a frame, leaving the work in `offload` to the device.
+    ///
+    /// The network stack only requests the offloads that the device reports via
+    /// [`Device::capabilities`]. The default implementation is for devices that report none.
+    fn consume_with_offload<R, F>(
+        token: Self::TxToken<'_>,
+        len: usize,
+        offload: TxOffload,
+        f: F,
+    ) -> R
+    where
+        F: FnOnce(&mut [u8]) -> R,
+    {
+        debug_assert_eq!(offload, TxOffload::default());
+        token.consume(len, f)
+    }
+}
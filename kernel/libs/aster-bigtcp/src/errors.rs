@@ -66,6 +66,15 @@ pub mod tcp {
         }
     }
 
+    /// An error that causes a TCP connection to be closed abnormally.
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub enum ConnError {
+        /// The connection is reset by a RST packet.
+        Reset,
+        /// The peer does not respond to keep-alive probes.
+        TimedOut,
+    }
+
     /// An error returned by [`TcpConnection::send`].
     ///
     /// [`TcpConnection::send`]: crate::socket::TcpConnection::send
@@ -74,6 +83,17 @@ pub mod tcp {
         InvalidState,
         /// The connection is reset.
         ConnReset,
+        /// The connection is timed out.
+        TimedOut,
+    }
+
+    impl From<ConnError> for SendError {
+        fn from(value: ConnError) -> Self {
+            match value {
+                ConnError::Reset => Self::ConnReset,
+                ConnError::TimedOut => Self::TimedOut,
+            }
+        }
     }
 
     impl From<smoltcp::socket::tcp::SendError> for SendError {
@@ -99,6 +119,17 @@ pub mod tcp {
         Finished,
         /// The connection is reset.
         ConnReset,
+        /// The connection is timed out.
+        TimedOut,
+    }
+
+    impl From<ConnError> for RecvError {
+        fn from(value: ConnError) -> Self {
+            match value {
+                ConnError::Reset => Self::ConnReset,
+                ConnError::TimedOut => Self::TimedOut,
+            }
+        }
     }
 
     impl From<smoltcp::socket::tcp::RecvError> for RecvError {
@@ -140,3 +171,68 @@ pub mod udp {
         }
     }
 }
+
+pub mod raw {
+    pub use smoltcp::socket::raw::RecvError;
+
+    /// An error returned by [`RawIpSocket::send`] and [`RawIpSocket::send_hdrincl`].
+    ///
+    /// [`RawIpSocket::send`]: crate::socket::RawIpSocket::send
+    /// [`RawIpSocket::send_hdrincl`]: crate::socket::RawIpSocket::send_hdrincl
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub enum SendError {
+        Unaddressable,
+        BufferFull,
+        /// The packet is too large.
+        TooLarge,
+        /// The packet is ill-formed.
+        Malformed,
+    }
+
+    impl From<smoltcp::socket::raw::SendError> for SendError {
+        fn from(value: smoltcp::socket::raw::SendError) -> Self {
+            match value {
+                smoltcp::socket::raw::SendError::BufferFull => Self::BufferFull,
+            }
+        }
+    }
+}
+
+pub mod link {
+    /// An error returned when sending a link-layer frame via an iface.
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub enum SendError {
+        /// The iface does not support sending link-layer frames.
+        Unsupported,
+        /// The iface is down.
+        Down,
+        /// The transmit queue of the iface is full.
+        BufferFull,
+        /// The frame is too large.
+        TooLarge,
+        /// The frame is ill-formed.
+        Malformed,
+    }
+}
+
+pub mod iface {
+    /// An error returned when adding or removing an IP address of an iface.
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub enum AddrError {
+        /// The address already exists.
+        Exists,
+        /// The address does not exist.
+        NotFound,
+        /// The iface cannot hold more addresses.
+        TooMany,
+    }
+
+    /// An error returned when setting the MTU of an iface.
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub enum MtuError {
+        /// The MTU is smaller than the minimum MTU.
+        TooSmall,
+        /// The MTU is larger than the maximum MTU that the device supports.
+        TooLarge,
+    }
+}
@@ -7,9 +7,8 @@ use alloc::{
     vec::Vec,
 };
 use core::{
-    ffi::CStr,
     ops::Deref,
-    sync::atomic::{AtomicBool, AtomicU32, Ordering},
+    sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering},
 };
 
 use aster_softirq::BottomHalfDisabled;
@@ -17,34 +16,59 @@ use bitflags::bitflags;
 use int_to_c_enum::TryFromInt;
 use ostd::sync::{SpinLock, SpinLockGuard};
 use smoltcp::{
-    iface::{Context, packet::Packet},
-    phy::Device,
-    wire::{IpAddress, IpEndpoint, Ipv4Address, Ipv4Packet, Ipv6Address, Ipv6Packet},
+    iface::{
+        Context,
+        packet::{IpPayload, Packet},
+    },
+    phy::{ChecksumCapabilities, Device},
+    wire::{IpAddress, IpCidr, IpEndpoint, Ipv4Packet, Ipv6Address, Ipv6Packet},
 };
 
 use super::{
     Iface,
+    filter::{
+        ActiveFilter, FilterHook, FilterIface, FilterVerdict, PacketFilter, ip_packet_in_frame,
+    },
+    multicast::MulticastGroups,
     poll::{FnHelper, PollContext, SocketTableAction},
     poll_iface::PollableIface,
     port::BindPortConfig,
+    stats::IfaceStats,
+    tap::{FrameTap, FrameType, PendingFrames, TapTable},
     time::get_network_timestamp,
 };
 use crate::{
-    errors::BindError,
+    errors::{
+        BindError,
+        iface::{AddrError, MtuError},
+    },
     ext::Ext,
-    socket::{TcpListenerBg, UdpSocketBg},
+    socket::{RawIpSocketBg, TcpListenerBg, TcpSocketInfo, UdpSocketBg, UdpSocketInfo},
     socket_table::SocketTable,
 };
 
 pub struct IfaceCommon<E: Ext> {
     index: u32,
-    name: CString,
+    name: SpinLock<CString>,
     type_: InterfaceType,
     flags: InterfaceFlags,
+    is_up: AtomicBool,
+    mtu: AtomicUsize,
+    max_mtu: usize,
 
     interface: SpinLock<PollableIface<E>, BottomHalfDisabled>,
+    /// The IP addresses of the iface.
+    ///
+    /// This is a copy of the addresses in `interface`, so that they can be read without locking
+    /// the whole interface.
+    ip_addrs: SpinLock<Arc<[IpCidr]>, BottomHalfDisabled>,
     used_ports: SpinLock<PortTable, BottomHalfDisabled>,
     sockets: SpinLock<SocketTable<E>, BottomHalfDisabled>,
+    taps: SpinLock<TapTable, BottomHalfDisabled>,
+    packet_filter: SpinLock<Option<Arc<dyn PacketFilter>>, BottomHalfDisabled>,
+    multicast: MulticastGroups,
+    pending_frames: SpinLock<PendingFrames, BottomHalfDisabled>,
+    stats: IfaceStats,
     sched_poll: E::ScheduleNextPoll,
 }
 
@@ -54,6 +78,17 @@ pub(super) enum IpPacket<'a> {
     Ipv6(Ipv6Packet<&'a [u8]>),
 }
 
+impl<'a> IpPacket<'a> {
+    /// Parses an IPv4 or IPv6 packet according to its version.
+    pub(super) fn new_checked(data: &'a [u8]) -> Option<Self> {
+        match data.first()? >> 4 {
+            4 => Ipv4Packet::new_checked(data).ok().map(Self::Ipv4),
+            6 => Ipv6Packet::new_checked(data).ok().map(Self::Ipv6),
+            _ => None,
+        }
+    }
+}
+
 /// A normalized IP address for binding purposes.
 ///
 /// IPv4 addresses are normalized to IPv4-mapped IPv6 addresses. IPv6 addresses
@@ -85,19 +120,31 @@ impl<E: Ext> IfaceCommon<E> {
         name: CString,
         type_: InterfaceType,
         flags: InterfaceFlags,
-        interface: smoltcp::iface::Interface,
+        mut interface: smoltcp::iface::Interface,
         sched_poll: E::ScheduleNextPoll,
     ) -> Self {
         let index = INTERFACE_INDEX_ALLOCATOR.fetch_add(1, Ordering::Relaxed);
 
+        let ip_addrs = Arc::from(interface.ip_addrs());
+        let max_mtu = interface.context().caps.ip_mtu();
+
         Self {
             index,
-            name,
+            name: SpinLock::new(name),
             type_,
             flags,
+            is_up: AtomicBool::new(flags.contains(InterfaceFlags::UP)),
+            mtu: AtomicUsize::new(max_mtu),
+            max_mtu,
             interface: SpinLock::new(PollableIface::new(interface)),
+            ip_addrs: SpinLock::new(ip_addrs),
             used_ports: SpinLock::new(PortTable::new()),
             sockets: SpinLock::new(SocketTable::new()),
+            taps: SpinLock::new(TapTable::new()),
+            packet_filter: SpinLock::new(None),
+            multicast: MulticastGroups::new(type_ == InterfaceType::LOOPBACK),
+            pending_frames: SpinLock::new(PendingFrames::new()),
+            stats: IfaceStats::new(),
             sched_poll,
         }
     }
@@ -106,8 +153,12 @@ impl<E: Ext> IfaceCommon<E> {
         self.index
     }
 
-    pub(super) fn name(&self) -> &CStr {
-        &self.name
+    pub(super) fn name(&self) -> CString {
+        self.name.lock().clone()
+    }
+
+    pub(super) fn set_name(&self, name: CString) {
+        *self.name.lock() = name;
     }
 
     pub(super) fn type_(&self) -> InterfaceType {
@@ -115,32 +166,119 @@ impl<E: Ext> IfaceCommon<E> {
     }
 
     pub(super) fn flags(&self) -> InterfaceFlags {
-        self.flags
+        if self.is_up() {
+            self.flags | InterfaceFlags::UP
+        } else {
+            // Like Linux, the operational states are not reported if the iface is down.
+            self.flags
+                - InterfaceFlags::UP
+                - InterfaceFlags::RUNNING
+                - InterfaceFlags::LOWER_UP
+                - InterfaceFlags::DORMANT
+        }
+    }
+
+    pub(super) fn is_up(&self) -> bool {
+        self.is_up.load(Ordering::Relaxed)
+    }
+
+    pub(super) fn set_up(&self, is_up: bool) -> bool {
+        self.is_up.swap(is_up, Ordering::Relaxed) != is_up
+    }
+
+    pub(super) fn mtu(&self) -> usize {
+        self.mtu.load(Ordering::Relaxed)
     }
 
-    pub(super) fn ipv4_addr(&self) -> Option<Ipv4Address> {
-        self.interface.lock().ipv4_addr()
+    pub(super) fn set_mtu(&self, mtu: usize) -> Result<(), MtuError> {
+        if mtu < MIN_MTU {
+            return Err(MtuError::TooSmall);
+        }
+        if mtu > self.max_mtu {
+            return Err(MtuError::TooLarge);
+        }
+
+        let mut interface = self.interface();
+        let caps = &mut interface.context_mut().caps;
+        // For Ethernet devices, the MTU of the device includes the Ethernet header.
+        caps.max_transmission_unit = caps.max_transmission_unit - caps.ip_mtu() + mtu;
+        self.mtu.store(mtu, Ordering::Relaxed);
+
+        Ok(())
+    }
+
+    pub(super) fn ip_addrs(&self) -> Arc<[IpCidr]> {
+        self.ip_addrs.lock().clone()
+    }
+
+    pub(super) fn has_ip_addr(&self, addr: IpAddress) -> bool {
+        self.ip_addrs
+            .lock()
+            .iter()
+            .any(|ip_cidr| ip_cidr.address() == addr)
+    }
+
+    pub(super) fn add_ip_addr(&self, ip_cidr: IpCidr) -> Result<(), AddrError> {
+        let mut interface = self.interface();
+        let mut ip_addrs = self.ip_addrs.lock();
+
+        if ip_addrs
+            .iter()
+            .any(|other| other.address() == ip_cidr.address())
+        {
+            return Err(AddrError::Exists);
+        }
+        interface.add_ip_addr(ip_cidr)?;
+        *ip_addrs = Arc::from(interface.ip_addrs());
+
+        // The solicited-node multicast addresses depend on the IPv6 addresses.
+        self.multicast.mark_filter_dirty();
+
+        Ok(())
     }
 
-    pub(super) fn ipv6_addr(&self) -> Option<Ipv6Address> {
-        self.interface.lock().ipv6_addr()
+    pub(super) fn remove_ip_addr(&self, ip_cidr: &IpCidr) -> Result<(), AddrError> {
+        let mut interface = self.interface();
+        let mut ip_addrs = self.ip_addrs.lock();
+
+        interface.remove_ip_addr(ip_cidr)?;
+        *ip_addrs = Arc::from(interface.ip_addrs());
+
+        self.multicast.mark_filter_dirty();
+
+        Ok(())
     }
 
-    pub(super) fn prefix_len(&self) -> Option<u8> {
-        self.interface.lock().prefix_len()
+    pub(super) fn set_gateway_routes(&self, routes: &[(IpCidr, IpAddress)]) {
+        self.interface().set_gateway_routes(routes);
     }
 
     pub(super) fn sched_poll(&self) -> &E::ScheduleNextPoll {
         &self.sched_poll
     }
+
+    pub(super) fn stats(&self) -> &IfaceStats {
+        &self.stats
+    }
 }
 
+/// The minimum MTU of an iface.
+///
+/// This is the minimum MTU that every IPv4 host must support.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/if_ether.h#L36>
+const MIN_MTU: usize = 68;
+
 /// An allocator that allocates a unique index for each interface.
 //
 // FIXME: This allocator is specific to each network namespace.
 static INTERFACE_INDEX_ALLOCATOR: AtomicU32 = AtomicU32::new(1);
 
-// Lock order: `interface` -> `sockets`
+// Lock order: `interface` -> `ip_addrs`, `interface` -> `sockets` -> `taps`, and `interface` ->
+// `sockets` -> `multicast`
+//
+// `pending_frames` is always acquired without holding other locks, except for the lock of the
+// device.
 impl<E: Ext> IfaceCommon<E> {
     /// Acquires the lock to the interface.
     pub(crate) fn interface(&self) -> SpinLockGuard<'_, PollableIface<E>, BottomHalfDisabled> {
@@ -151,6 +289,101 @@ impl<E: Ext> IfaceCommon<E> {
     pub(crate) fn sockets(&self) -> SpinLockGuard<'_, SocketTable<E>, BottomHalfDisabled> {
         self.sockets.lock()
     }
+
+    /// Acquires the lock to the frames waiting to be transmitted.
+    pub(super) fn pending_frames(&self) -> SpinLockGuard<'_, PendingFrames, BottomHalfDisabled> {
+        self.pending_frames.lock()
+    }
+}
+
+impl<E: Ext> IfaceCommon<E> {
+    pub(super) fn register_tap(&self, tap: Arc<dyn FrameTap>) {
+        self.taps.lock().register(tap);
+    }
+
+    pub(super) fn unregister_tap(&self, tap: &Arc<dyn FrameTap>) {
+        self.taps.lock().unregister(tap);
+    }
+
+    pub(super) fn inc_promiscuity(&self) {
+        if self.taps.lock().inc_promiscuity() {
+            self.multicast.mark_filter_dirty();
+        }
+    }
+
+    pub(super) fn dec_promiscuity(&self) {
+        if self.taps.lock().dec_promiscuity() {
+            self.multicast.mark_filter_dirty();
+        }
+    }
+
+    /// Delivers a received or transmitted frame to the taps.
+    ///
+    /// If `sender` is specified, the frame will not be delivered to it.
+    pub(super) fn tap_frame(
+        &self,
+        frame: &[u8],
+        frame_type: FrameType,
+        sender: Option<&Arc<dyn FrameTap>>,
+    ) {
+        self.taps
+            .lock()
+            .deliver(frame, self.index, frame_type, sender);
+    }
+}
+
+impl<E: Ext> IfaceCommon<E> {
+    pub(super) fn set_packet_filter(&self, filter: Option<Arc<dyn PacketFilter>>) {
+        *self.packet_filter.lock() = filter;
+    }
+
+    pub(super) fn filter_forwarded_frame(&self, frame: &mut [u8]) -> FilterVerdict {
+        let Some(filter) = self
+            .packet_filter
+            .lock()
+            .clone()
+            .filter(|filter| filter.is_active())
+        else {
+            return FilterVerdict::Accept;
+        };
+
+        // Frames that do not carry IP packets are not seen by the packet filter.
+        let Some(pkt) = ip_packet_in_frame(frame) else {
+            return FilterVerdict::Accept;
+        };
+
+        let name = self.name();
+        let ip_addrs = self.ip_addrs();
+        let iface = FilterIface {
+            index: self.index,
+            name: &name,
+            ip_addrs: &ip_addrs,
+        };
+        filter.filter(&[FilterHook::Forward], pkt, &iface)
+    }
+}
+
+impl<E: Ext> IfaceCommon<E> {
+    pub(super) fn join_multicast_group(&self, group: IpAddress) {
+        self.multicast.join(group);
+    }
+
+    pub(super) fn leave_multicast_group(&self, group: IpAddress) {
+        self.multicast.leave(group);
+    }
+
+    /// Returns the new receive filter if it needs to be updated.
+    ///
+    /// The receive filter consists of whether the iface is in the promiscuous mode and the
+    /// multicast groups that the iface has joined.
+    pub(super) fn take_rx_filter(&self) -> Option<(bool, Vec<IpAddress>)> {
+        if !self.multicast.take_filter_dirty() {
+            return None;
+        }
+
+        let is_promisc = self.taps.lock().is_promiscuous();
+        Some((is_promisc, self.multicast.groups()))
+    }
 }
 
 const IP_LOCAL_PORT_START: u16 = 32768;
@@ -175,6 +408,28 @@ impl<E: Ext> IfaceCommon<E> {
             .map(BoundUdpPort)
     }
 
+    pub(super) fn bind_raw(
+        &self,
+        iface: Arc<dyn Iface<E>>,
+        addr: IpAddress,
+        protocol: u8,
+    ) -> Result<BoundRawPort<E>, BindError> {
+        // Like Linux, the protocol number is used as the port number of raw sockets. Raw sockets
+        // never conflict with each other, so the port can always be reused.
+        let config = BindPortConfig::new_raw(addr, protocol);
+        self.bind(iface, config, PortProtocol::Raw)
+            .map(BoundRawPort)
+    }
+
+    pub(super) fn bind_ping(
+        &self,
+        iface: Arc<dyn Iface<E>>,
+        config: BindPortConfig,
+    ) -> Result<BoundRawPort<E>, BindError> {
+        self.bind(iface, config, PortProtocol::Ping)
+            .map(BoundRawPort)
+    }
+
     fn bind(
         &self,
         iface: Arc<dyn Iface<E>>,
@@ -198,6 +453,10 @@ impl<E: Ext> IfaceCommon<E> {
             .lock()
             .release(addr, port, can_reuse, protocol);
     }
+
+    pub(super) fn has_bound_ports(&self) -> bool {
+        !self.used_ports.lock().used_ports.is_empty()
+    }
 }
 
 impl<E: Ext> IfaceCommon<E> {
@@ -206,6 +465,11 @@ impl<E: Ext> IfaceCommon<E> {
         sockets.insert_udp_socket(socket);
     }
 
+    pub(crate) fn register_raw_socket(&self, socket: Arc<RawIpSocketBg<E>>) {
+        let mut sockets = self.sockets.lock();
+        sockets.insert_raw_socket(socket);
+    }
+
     pub(crate) fn remove_tcp_listener(&self, socket: &Arc<TcpListenerBg<E>>) {
         let mut sockets = self.sockets.lock();
         let removed = sockets.remove_listener(socket.listener_key());
@@ -217,12 +481,54 @@ impl<E: Ext> IfaceCommon<E> {
         let removed = sockets.remove_udp_socket(socket);
         debug_assert!(removed.is_some());
     }
+
+    pub(crate) fn remove_raw_socket(&self, socket: &Arc<RawIpSocketBg<E>>) {
+        let mut sockets = self.sockets.lock();
+        let removed = sockets.remove_raw_socket(socket);
+        debug_assert!(removed.is_some());
+    }
+
+    pub(super) fn has_tcp_connections(&self) -> bool {
+        self.sockets.lock().connection_iter().next().is_some()
+    }
+
+    pub(super) fn visit_tcp_sockets<F>(&self, mut f: F)
+    where
+        F: FnMut(TcpSocketInfo<'_, E>),
+    {
+        let sockets = self.sockets.lock();
+
+        for listener in sockets.listener_iter() {
+            f(TcpSocketInfo::Listener(listener.info()));
+        }
+        for connection in sockets.connection_iter() {
+            connection.with_info(|info| f(TcpSocketInfo::Connection(info)));
+        }
+    }
+
+    pub(super) fn visit_udp_sockets<F>(&self, mut f: F)
+    where
+        F: FnMut(UdpSocketInfo<'_, E>),
+    {
+        let sockets = self.sockets.lock();
+
+        for socket in sockets.udp_socket_iter() {
+            f(socket.info());
+        }
+    }
 }
 
 impl<E: Ext> IfaceCommon<E> {
+    /// Polls the iface.
+    ///
+    /// `max_tso_len` is the maximum length of the TCP packets that the device can split into
+    /// segments (TSO), if the device supports TSO. Consecutive TCP segments are then coalesced,
+    /// and `dispatch_phy` is called with the segment size that the device should use to split
+    /// the coalesced packet.
     pub(super) fn poll<D, P, Q>(
         &self,
         device: &mut D,
+        max_tso_len: Option<usize>,
         mut process_phy: P,
         mut dispatch_phy: Q,
     ) -> Option<u64>
@@ -234,18 +540,90 @@ impl<E: Ext> IfaceCommon<E> {
                 D::TxToken<'tx>,
                 Option<(IpPacket<'pkt>, D::TxToken<'tx>)>,
             >,
-        Q: FnMut(&Packet, &mut Context, D::TxToken<'_>),
+        Q: FnMut(&Packet, Option<u16>, &mut Context, D::TxToken<'_>),
     {
         let mut interface = self.interface();
         interface.context_mut().now = get_network_timestamp();
 
+        let ip_addrs = self.ip_addrs();
         let mut sockets = self.sockets.lock();
         let mut socket_actions = Vec::new();
 
-        let mut context = PollContext::new(interface.as_mut(), &sockets, &mut socket_actions);
+        // Packets are passed to the packet filter only if it is active.
+        let packet_filter = self
+            .packet_filter
+            .lock()
+            .clone()
+            .filter(|filter| filter.is_active());
+        let name = packet_filter.as_ref().map(|_| self.name());
+        let active_filter = packet_filter
+            .as_deref()
+            .zip(name.as_deref())
+            .map(|(filter, name)| {
+                let iface = FilterIface {
+                    index: self.index,
+                    name,
+                    ip_addrs: &ip_addrs,
+                };
+                ActiveFilter::new(filter, iface)
+            });
+
+        // Outgoing packets traverse the output hooks before being transmitted.
+        let mut dispatch_phy = |pkt: &Packet,
+                                segment_size: Option<u16>,
+                                iface_cx: &mut Context,
+                                tx_token: D::TxToken<'_>| {
+            self.stats.on_ip_send(&pkt.ip_repr());
+
+            let Some(filter) = active_filter.as_ref() else {
+                dispatch_phy(pkt, segment_size, iface_cx, tx_token);
+                return;
+            };
+            // TCP segments are never coalesced if there is a packet filter, so that the filter
+            // sees the segments that are actually transmitted.
+            debug_assert!(segment_size.is_none());
+
+            // The filter may rewrite the packet and update its checksums incrementally, so the
+            // checksums must be complete even if the device can compute them.
+            let mut caps = iface_cx.caps.clone();
+            caps.checksum = ChecksumCapabilities::default();
+
+            let ip_repr = pkt.ip_repr();
+            let Some((ip_repr, ip_payload)) =
+                filter.filter(FilterHook::OUTPUT_PATH, &ip_repr, |payload| {
+                    pkt.emit_payload(&ip_repr, payload, &caps)
+                })
+            else {
+                return;
+            };
+            dispatch_phy(
+                &Packet::new(ip_repr, IpPayload::Raw(&ip_payload)),
+                None,
+                iface_cx,
+                tx_token,
+            );
+        };
+
+        let mut context = PollContext::new(
+            interface.as_mut(),
+            &ip_addrs,
+            &self.multicast,
+            &sockets,
+            active_filter.as_ref(),
+            &self.stats,
+            max_tso_len.filter(|_| active_filter.is_none()),
+            &mut socket_actions,
+        );
         context.poll_ingress(device, &mut process_phy, &mut dispatch_phy);
         context.poll_egress(device, &mut dispatch_phy);
 
+        // Membership reports sent via a loopback device will only be received by ourselves, so
+        // there is no need to send them.
+        let reports = self.multicast.take_reports();
+        if !self.multicast.is_looped_back_by_device() {
+            context.dispatch_reports(reports, device, &mut dispatch_phy);
+        }
+
         // Insert new connections and remove dead connections.
         for action in socket_actions.into_iter() {
             match action {
@@ -333,6 +711,8 @@ impl<E: Ext> Drop for BoundPort<E> {
 pub struct BoundTcpPort<E: Ext>(BoundPort<E>);
 /// A UDP port bound to an iface.
 pub struct BoundUdpPort<E: Ext>(BoundPort<E>);
+/// A raw IP socket or an ICMP echo identifier bound to an iface.
+pub struct BoundRawPort<E: Ext>(BoundPort<E>);
 
 impl<E: Ext> Deref for BoundTcpPort<E> {
     type Target = BoundPort<E>;
@@ -346,6 +726,12 @@ impl<E: Ext> Deref for BoundUdpPort<E> {
         &self.0
     }
 }
+impl<E: Ext> Deref for BoundRawPort<E> {
+    type Target = BoundPort<E>;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
 
 #[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
 struct PortKey {
@@ -358,6 +744,8 @@ struct PortKey {
 enum PortProtocol {
     Tcp,
     Udp,
+    Raw,
+    Ping,
 }
 
 struct PortState {
@@ -548,6 +936,9 @@ pub enum InterfaceType {
     LOOPBACK = 772,
     /// Localtalk device
     LOCALTALK = 773,
+
+    /// Zero header length
+    NONE = 0xFFFE,
     // TODO: This enum is not exhaustive
 }
 
@@ -0,0 +1,187 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::{vec, vec::Vec};
+use core::ffi::CStr;
+
+use smoltcp::{
+    phy::ChecksumCapabilities,
+    wire::{
+        ETHERNET_HEADER_LEN, EthernetFrame, EthernetProtocol, IPV6_HEADER_LEN, IpCidr, IpRepr,
+        Ipv4Packet, Ipv4Repr, Ipv6Packet, Ipv6Repr,
+    },
+};
+
+use super::common::IpPacket;
+
+/// A point in the packet flow where IP packets are passed to the packet filter.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/netfilter.h>
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FilterHook {
+    /// The packet is received by the iface and has not been routed.
+    PreRouting = 0,
+    /// The packet is received by the iface and will be delivered to local sockets.
+    LocalIn = 1,
+    /// The packet is received by the iface and will be forwarded to another host.
+    ///
+    /// Packets are not routed between ifaces, so only the packets that a bridge forwards between
+    /// its ports traverse this hook, like Linux with `br_netfilter`.
+    Forward = 2,
+    /// The packet is generated by local sockets.
+    LocalOut = 3,
+    /// The packet has been routed and will be transmitted by the iface.
+    PostRouting = 4,
+}
+
+impl FilterHook {
+    /// The hooks traversed by the packets received by the iface.
+    pub const INPUT_PATH: &[Self] = &[Self::PreRouting, Self::LocalIn];
+    /// The hooks traversed by the packets transmitted by the iface.
+    pub const OUTPUT_PATH: &[Self] = &[Self::LocalOut, Self::PostRouting];
+    /// The hooks traversed by the packets sent to local addresses.
+    ///
+    /// Such packets are looped back without reaching the device, but they traverse the hooks as
+    /// if they were transmitted and then received by the iface.
+    pub const LOCAL_PATH: &[Self] = &[
+        Self::LocalOut,
+        Self::PostRouting,
+        Self::PreRouting,
+        Self::LocalIn,
+    ];
+}
+
+/// The verdict of the packet filter.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FilterVerdict {
+    /// The packet continues to traverse the network stack.
+    Accept,
+    /// The packet is dropped silently.
+    Drop,
+}
+
+/// The iface that a filtered packet is received or transmitted by.
+#[derive(Clone, Copy, Debug)]
+pub struct FilterIface<'a> {
+    /// The interface index.
+    pub index: u32,
+    /// The interface name.
+    pub name: &'a CStr,
+    /// The IP addresses of the iface.
+    pub ip_addrs: &'a [IpCidr],
+}
+
+/// A filter of the IP packets received or transmitted by an iface.
+///
+/// This can be used to implement firewalls and network address translation (e.g., netfilter in
+/// Linux).
+pub trait PacketFilter: Send + Sync {
+    /// Returns whether the filter needs to see packets.
+    ///
+    /// Packets are copied before being passed to the filter, so an inactive filter (e.g., one
+    /// without any rules) should return `false` to avoid the overhead.
+    fn is_active(&self) -> bool;
+
+    /// Called when an IP packet traverses the hooks, in the order given by `hooks`.
+    ///
+    /// `pkt` starts with its IP header. The filter may rewrite the addresses and the ports in
+    /// place, in which case it must update the checksums accordingly. The length of the packet
+    /// cannot be changed.
+    ///
+    /// `iface` is the input iface for [`FilterHook::PreRouting`] and [`FilterHook::LocalIn`],
+    /// the output iface for [`FilterHook::LocalOut`] and [`FilterHook::PostRouting`], and the
+    /// bridge iface, which is both the input and the output iface, for [`FilterHook::Forward`].
+    ///
+    /// The method is called with some locks of the iface held, so it must not poll the iface or
+    /// send packets.
+    fn filter(&self, hooks: &[FilterHook], pkt: &mut [u8], iface: &FilterIface) -> FilterVerdict;
+}
+
+/// A packet filter that is in effect while the iface is being polled.
+pub(super) struct ActiveFilter<'a> {
+    filter: &'a dyn PacketFilter,
+    iface: FilterIface<'a>,
+}
+
+impl<'a> ActiveFilter<'a> {
+    pub(super) fn new(filter: &'a dyn PacketFilter, iface: FilterIface<'a>) -> Self {
+        Self { filter, iface }
+    }
+
+    /// Filters a packet received by the iface.
+    ///
+    /// Returns a copy of the (possibly modified) packet, or `None` if the packet is dropped.
+    pub(super) fn filter_received(&self, ip_packet: &IpPacket) -> Option<Vec<u8>> {
+        let mut packet = match ip_packet {
+            IpPacket::Ipv4(pkt) => pkt.as_ref()[..pkt.total_len() as usize].to_vec(),
+            IpPacket::Ipv6(pkt) => {
+                pkt.as_ref()[..IPV6_HEADER_LEN + pkt.payload_len() as usize].to_vec()
+            }
+        };
+
+        let verdict = self
+            .filter
+            .filter(FilterHook::INPUT_PATH, &mut packet, &self.iface);
+        (verdict == FilterVerdict::Accept).then_some(packet)
+    }
+
+    /// Filters a packet given by its IP header and a function that emits its IP payload.
+    ///
+    /// Returns the IP header and the IP payload of the (possibly modified) packet, or `None` if
+    /// the packet is dropped.
+    pub(super) fn filter<F>(
+        &self,
+        hooks: &[FilterHook],
+        ip_repr: &IpRepr,
+        emit_payload: F,
+    ) -> Option<(IpRepr, Vec<u8>)>
+    where
+        F: FnOnce(&mut [u8]),
+    {
+        let header_len = ip_repr.header_len();
+        let mut packet = vec![0; ip_repr.buffer_len()];
+        ip_repr.emit(&mut packet[..], &ChecksumCapabilities::default());
+        emit_payload(&mut packet[header_len..]);
+
+        if self.filter.filter(hooks, &mut packet, &self.iface) == FilterVerdict::Drop {
+            return None;
+        }
+
+        // The filter cannot change the header length, so only the addresses need to be parsed
+        // again.
+        let ip_repr = match ip_repr {
+            IpRepr::Ipv4(_) => {
+                let pkt = Ipv4Packet::new_checked(&packet[..]).ok()?;
+                IpRepr::Ipv4(Ipv4Repr::parse(&pkt, &ChecksumCapabilities::ignored()).ok()?)
+            }
+            IpRepr::Ipv6(_) => {
+                let pkt = Ipv6Packet::new_checked(&packet[..]).ok()?;
+                IpRepr::Ipv6(Ipv6Repr::parse(&pkt).ok()?)
+            }
+        };
+        let ip_payload = packet.split_off(header_len);
+
+        Some((ip_repr, ip_payload))
+    }
+}
+
+/// Returns the IP packet carried by an Ethernet frame.
+///
+/// The padding after the IP packet is excluded. `None` is returned if the frame does not carry a
+/// valid IP packet.
+pub(super) fn ip_packet_in_frame(frame: &mut [u8]) -> Option<&mut [u8]> {
+    let ether_frame = EthernetFrame::new_checked(&*frame).ok()?;
+    let ip_len = match ether_frame.ethertype() {
+        EthernetProtocol::Ipv4 => {
+            let pkt = Ipv4Packet::new_checked(ether_frame.payload()).ok()?;
+            pkt.total_len() as usize
+        }
+        EthernetProtocol::Ipv6 => {
+            let pkt = Ipv6Packet::new_checked(ether_frame.payload()).ok()?;
+            IPV6_HEADER_LEN + pkt.payload_len() as usize
+        }
+        _ => return None,
+    };
+
+    frame.get_mut(ETHERNET_HEADER_LEN..ETHERNET_HEADER_LEN + ip_len)
+}
@@ -1,12 +1,22 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use alloc::sync::Arc;
-use core::ffi::CStr;
+use alloc::{ffi::CString, sync::Arc};
 
-use smoltcp::wire::{Ipv4Address, Ipv4Cidr, Ipv6Address};
+use smoltcp::wire::{ETHERNET_HEADER_LEN, EthernetAddress, IpAddress, IpCidr};
 
-use super::{BindPortConfig, BoundTcpPort, BoundUdpPort, InterfaceFlags, InterfaceType};
-use crate::{errors::BindError, ext::Ext};
+use super::{
+    BindPortConfig, BoundRawPort, BoundTcpPort, BoundUdpPort, FilterVerdict, FrameTap, FrameType,
+    InterfaceFlags, InterfaceType, LinkStats, PacketFilter, ProtocolStats,
+};
+use crate::{
+    errors::{
+        BindError,
+        iface::{AddrError, MtuError},
+        link::SendError,
+    },
+    ext::Ext,
+    socket::{TcpSocketInfo, UdpSocketInfo},
+};
 
 /// A network interface.
 ///
@@ -18,8 +28,8 @@ pub trait Iface<E>: internal::IfaceInternal<E> + Send + Sync {
     /// Transmits or receives packets queued in the iface, and updates socket status accordingly.
     fn poll(&self);
 
-    /// Returns the maximum transmission unit.
-    fn mtu(&self) -> usize;
+    /// Returns the Ethernet address of the iface, if any.
+    fn ether_addr(&self) -> Option<EthernetAddress>;
 }
 
 impl<E: Ext> dyn Iface<E> {
@@ -51,6 +61,28 @@ impl<E: Ext> dyn Iface<E> {
         common.bind_udp(self.clone(), config)
     }
 
+    /// Binds a raw IP socket of the specified protocol to the iface.
+    pub fn bind_raw(
+        self: &Arc<Self>,
+        addr: IpAddress,
+        protocol: u8,
+    ) -> Result<BoundRawPort<E>, BindError> {
+        let common = self.common();
+        common.bind_raw(self.clone(), addr, protocol)
+    }
+
+    /// Binds an ICMP echo identifier to the iface.
+    ///
+    /// The port in [`BindPortConfig`] is used as the identifier. If no specific identifier is
+    /// given, the iface will pick up an ephemeral one.
+    pub fn bind_ping(
+        self: &Arc<Self>,
+        config: BindPortConfig,
+    ) -> Result<BoundRawPort<E>, BindError> {
+        let common = self.common();
+        common.bind_ping(self.clone(), config)
+    }
+
     /// Returns the interface index.
     pub fn index(&self) -> u32 {
         self.common().index()
@@ -59,10 +91,17 @@ impl<E: Ext> dyn Iface<E> {
     /// Gets the name of the iface.
     ///
     /// In Linux, the name is usually the driver name followed by a unit number.
-    pub fn name(&self) -> &CStr {
+    pub fn name(&self) -> CString {
         self.common().name()
     }
 
+    /// Renames the iface.
+    ///
+    /// The caller is responsible for ensuring that the new name is unique.
+    pub fn set_name(&self, name: CString) {
+        self.common().set_name(name);
+    }
+
     /// Returns the interface type.
     pub fn type_(&self) -> InterfaceType {
         self.common().type_()
@@ -73,35 +112,193 @@ impl<E: Ext> dyn Iface<E> {
         self.common().flags()
     }
 
-    /// Gets the IPv4 address of the iface, if any.
-    //
-    // FIXME: One iface may have multiple IPv4 addresses.
-    pub fn ipv4_addr(&self) -> Option<Ipv4Address> {
-        self.common().ipv4_addr()
+    /// Returns whether the iface is administratively up.
+    pub fn is_up(&self) -> bool {
+        self.common().is_up()
     }
 
-    /// Gets the IPv6 address of the iface, if any.
-    pub fn ipv6_addr(&self) -> Option<Ipv6Address> {
-        self.common().ipv6_addr()
+    /// Brings the iface up or down.
+    ///
+    /// Packets are neither received nor transmitted while the iface is down. This method returns
+    /// whether the state of the iface has changed.
+    pub fn set_up(&self, is_up: bool) -> bool {
+        self.common().set_up(is_up)
     }
 
-    /// Retrieves the prefix length of the interface's IPv4 address.
+    /// Returns the maximum transmission unit.
     ///
-    /// Both `Self::ipv4_addr` and this method will either return `Some(_)`
-    /// or both will return `None`.
-    pub fn prefix_len(&self) -> Option<u8> {
-        self.common().prefix_len()
+    /// This is the maximum size of IP packets, excluding link-layer headers.
+    pub fn mtu(&self) -> usize {
+        self.common().mtu()
+    }
+
+    /// Sets the maximum transmission unit.
+    pub fn set_mtu(&self, mtu: usize) -> Result<(), MtuError> {
+        self.common().set_mtu(mtu)
     }
 
-    /// Gets the broadcast address of the iface, if any.
-    pub fn broadcast_addr(&self) -> Option<Ipv4Address> {
-        let cidr = {
-            let common = self.common();
-            let ipv4_addr = common.ipv4_addr()?;
-            let prefix_len = common.prefix_len()?;
-            Ipv4Cidr::new(ipv4_addr, prefix_len)
-        };
-        cidr.broadcast()
+    /// Returns all the IP addresses of the iface.
+    pub fn ip_addrs(&self) -> Arc<[IpCidr]> {
+        self.common().ip_addrs()
+    }
+
+    /// Returns whether the iface owns the IP address.
+    pub fn has_ip_addr(&self, addr: IpAddress) -> bool {
+        self.common().has_ip_addr(addr)
+    }
+
+    /// Adds an IP address to the iface.
+    pub fn add_ip_addr(&self, ip_cidr: IpCidr) -> Result<(), AddrError> {
+        self.common().add_ip_addr(ip_cidr)
+    }
+
+    /// Removes an IP address from the iface.
+    pub fn remove_ip_addr(&self, ip_cidr: &IpCidr) -> Result<(), AddrError> {
+        self.common().remove_ip_addr(ip_cidr)
+    }
+
+    /// Replaces the routes that go through gateways.
+    ///
+    /// Each route is described by its destination and the address of its gateway. Destinations
+    /// in the subnets of the iface's addresses are always reachable and need no routes.
+    pub fn set_gateway_routes(&self, routes: &[(IpCidr, IpAddress)]) {
+        self.common().set_gateway_routes(routes);
+    }
+
+    /// Registers a tap to observe the link-layer frames received or transmitted by the iface.
+    pub fn register_tap(&self, tap: Arc<dyn FrameTap>) {
+        self.common().register_tap(tap);
+    }
+
+    /// Unregisters a tap that is registered by [`Self::register_tap`].
+    pub fn unregister_tap(&self, tap: &Arc<dyn FrameTap>) {
+        self.common().unregister_tap(tap);
+    }
+
+    /// Sets the filter of the IP packets received or transmitted by the iface.
+    ///
+    /// If `filter` is `None`, the existing filter is removed.
+    pub fn set_packet_filter(&self, filter: Option<Arc<dyn PacketFilter>>) {
+        self.common().set_packet_filter(filter);
+    }
+
+    /// Passes the IP packet in an Ethernet frame forwarded by the iface to the packet filter.
+    ///
+    /// This is used by bridges, which forward frames between their ports without passing them to
+    /// the iface. The packet traverses [`FilterHook::Forward`] and may be rewritten in place.
+    /// Frames that do not carry IP packets are always accepted.
+    ///
+    /// [`FilterHook::Forward`]: crate::iface::FilterHook::Forward
+    pub fn filter_forwarded_frame(&self, frame: &mut [u8]) -> FilterVerdict {
+        self.common().filter_forwarded_frame(frame)
+    }
+
+    /// Puts the iface into the promiscuous mode.
+    ///
+    /// The iface leaves the promiscuous mode after [`Self::dec_promiscuity`] is called the same
+    /// number of times.
+    pub fn inc_promiscuity(&self) {
+        self.common().inc_promiscuity();
+    }
+
+    /// Reverts a previous call to [`Self::inc_promiscuity`].
+    pub fn dec_promiscuity(&self) {
+        self.common().dec_promiscuity();
+    }
+
+    /// Returns whether any sockets have bound ports on the iface.
+    pub fn has_bound_ports(&self) -> bool {
+        self.common().has_bound_ports()
+    }
+
+    /// Returns whether any TCP connections are bound to the iface.
+    ///
+    /// This includes the connections that have been closed by the user but are still exchanging
+    /// segments with the peer (e.g., in the TIME-WAIT state).
+    pub fn has_tcp_connections(&self) -> bool {
+        self.common().has_tcp_connections()
+    }
+
+    /// Visits the TCP listeners and connections bound to the iface.
+    ///
+    /// The socket table is locked during the visit, so `f` must not sleep.
+    pub fn visit_tcp_sockets<F>(&self, f: F)
+    where
+        F: FnMut(TcpSocketInfo<'_, E>),
+    {
+        self.common().visit_tcp_sockets(f);
+    }
+
+    /// Visits the UDP sockets bound to the iface.
+    ///
+    /// The socket table is locked during the visit, so `f` must not sleep.
+    pub fn visit_udp_sockets<F>(&self, f: F)
+    where
+        F: FnMut(UdpSocketInfo<'_, E>),
+    {
+        self.common().visit_udp_sockets(f);
+    }
+
+    /// Returns the statistics of the link-layer frames received or transmitted by the iface.
+    pub fn link_stats(&self) -> LinkStats {
+        self.common().stats().link_stats()
+    }
+
+    /// Returns the statistics of the IP, TCP, and UDP packets processed by the iface.
+    pub fn protocol_stats(&self) -> ProtocolStats {
+        self.common().stats().protocol_stats()
+    }
+
+    /// Joins a multicast group.
+    ///
+    /// The iface leaves the group after [`Self::leave_multicast_group`] is called the same number
+    /// of times.
+    ///
+    /// Polling the iface is _always_ required after this method succeeds.
+    pub fn join_multicast_group(&self, group: IpAddress) {
+        self.common().join_multicast_group(group);
+    }
+
+    /// Reverts a previous call to [`Self::join_multicast_group`].
+    ///
+    /// Polling the iface is _always_ required after this method succeeds.
+    pub fn leave_multicast_group(&self, group: IpAddress) {
+        self.common().leave_multicast_group(group);
+    }
+
+    /// Sends a link-layer frame that starts with its link-layer header.
+    ///
+    /// The frame is also delivered to the taps of the iface as an outgoing frame, except for
+    /// `sender`.
+    ///
+    /// Polling the iface is _always_ required after this method succeeds.
+    pub fn send_frame(
+        &self,
+        frame: &[u8],
+        sender: Option<&Arc<dyn FrameTap>>,
+    ) -> Result<(), SendError> {
+        // TODO: Support sending frames via ifaces without link-layer headers (e.g., loopback).
+        if self.ether_addr().is_none() {
+            return Err(SendError::Unsupported);
+        }
+
+        if frame.len() < ETHERNET_HEADER_LEN {
+            return Err(SendError::Malformed);
+        }
+        if frame.len() > ETHERNET_HEADER_LEN + self.mtu() {
+            return Err(SendError::TooLarge);
+        }
+
+        let common = self.common();
+        if !common.is_up() {
+            return Err(SendError::Down);
+        }
+        if !common.pending_frames().push(frame.to_vec()) {
+            return Err(SendError::BufferFull);
+        }
+        common.tap_frame(frame, FrameType::Outgoing, sender);
+
+        Ok(())
     }
 
     /// Returns a reference to the associated [`ScheduleNextPoll`].
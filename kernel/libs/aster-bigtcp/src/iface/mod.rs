@@ -1,18 +1,29 @@
 // SPDX-License-Identifier: MPL-2.0
 
 mod common;
+mod filter;
 #[expect(clippy::module_inception)]
 mod iface;
+mod multicast;
 mod phy;
 mod poll;
 mod poll_iface;
 mod port;
 mod sched;
+mod stats;
+mod tap;
 mod time;
+mod tso;
 
-pub use common::{BoundPort, BoundTcpPort, BoundUdpPort, InterfaceFlags, InterfaceType};
+pub use common::{
+    BoundPort, BoundRawPort, BoundTcpPort, BoundUdpPort, InterfaceFlags, InterfaceType,
+};
+pub use filter::{FilterHook, FilterIface, FilterVerdict, PacketFilter};
 pub use iface::Iface;
 pub use phy::{EtherIface, IpIface};
 pub(crate) use poll_iface::{PollKey, PollableIfaceMut};
 pub use port::BindPortConfig;
 pub use sched::ScheduleNextPoll;
+pub use stats::{LinkStats, ProtocolStats};
+pub use tap::{FrameTap, FrameType};
+pub(crate) use time::get_network_timestamp;
@@ -0,0 +1,370 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Multicast group membership of ifaces.
+//!
+//! The memberships of IPv4 groups are reported via IGMPv2 (see
+//! <https://datatracker.ietf.org/doc/html/rfc2236>), while those of IPv6 groups are reported via
+//! MLDv1 (see <https://datatracker.ietf.org/doc/html/rfc2710>).
+
+use alloc::{vec, vec::Vec};
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use aster_softirq::BottomHalfDisabled;
+use ostd::sync::SpinLock;
+use smoltcp::wire::{
+    IpAddress, IpCidr, IpProtocol, IpRepr, Ipv4Address, Ipv4Repr, Ipv6Address, Ipv6Repr,
+};
+
+use crate::wire::{
+    IPV4_ALL_ROUTERS, IPV4_ALL_SYSTEMS, IPV6_LINK_LOCAL_ALL_NODES, IPV6_LINK_LOCAL_ALL_ROUTERS,
+    Icmpv6Packet,
+};
+
+/// The multicast groups that an iface has joined.
+pub(super) struct MulticastGroups {
+    inner: SpinLock<Inner, BottomHalfDisabled>,
+    /// Whether the receive filter of the device needs to be updated.
+    is_filter_dirty: AtomicBool,
+    /// Whether the multicast packets sent via the iface are looped back by the device itself.
+    is_looped_back_by_device: bool,
+}
+
+struct Inner {
+    /// The joined groups and the number of times that each group is joined.
+    groups: Vec<(IpAddress, usize)>,
+    /// The membership reports waiting to be sent.
+    pending_reports: Vec<Report>,
+}
+
+/// A membership report.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum Report {
+    /// Reports that the iface is a member of the group.
+    Join(IpAddress),
+    /// Reports that the iface is no longer a member of the group.
+    Leave(IpAddress),
+}
+
+impl MulticastGroups {
+    pub(super) fn new(is_looped_back_by_device: bool) -> Self {
+        Self {
+            inner: SpinLock::new(Inner {
+                groups: Vec::new(),
+                pending_reports: Vec::new(),
+            }),
+            // The receive filter should be initialized in the first poll.
+            is_filter_dirty: AtomicBool::new(true),
+            is_looped_back_by_device,
+        }
+    }
+
+    /// Joins a multicast group.
+    pub(super) fn join(&self, group: IpAddress) {
+        let mut inner = self.inner.lock();
+
+        if let Some((_, count)) = inner.groups.iter_mut().find(|(addr, _)| *addr == group) {
+            *count += 1;
+            return;
+        }
+
+        inner.groups.push((group, 1));
+        if need_report(&group) {
+            inner.pending_reports.push(Report::Join(group));
+        }
+        self.mark_filter_dirty();
+    }
+
+    /// Leaves a multicast group that is joined by [`Self::join`].
+    pub(super) fn leave(&self, group: IpAddress) {
+        let mut inner = self.inner.lock();
+
+        let Some(pos) = inner.groups.iter().position(|(addr, _)| *addr == group) else {
+            return;
+        };
+        let count = &mut inner.groups[pos].1;
+        *count -= 1;
+        if *count != 0 {
+            return;
+        }
+
+        inner.groups.swap_remove(pos);
+        inner
+            .pending_reports
+            .retain(|report| *report != Report::Join(group));
+        if need_report(&group) {
+            inner.pending_reports.push(Report::Leave(group));
+        }
+        self.mark_filter_dirty();
+    }
+
+    /// Returns whether the iface is a member of the multicast group.
+    ///
+    /// All ifaces are always members of the all-systems group and the all-nodes group.
+    pub(super) fn contains(&self, group: &IpAddress) -> bool {
+        match group {
+            IpAddress::Ipv4(addr) if *addr == IPV4_ALL_SYSTEMS => return true,
+            IpAddress::Ipv6(addr) if *addr == IPV6_LINK_LOCAL_ALL_NODES => return true,
+            _ => (),
+        }
+
+        self.inner
+            .lock()
+            .groups
+            .iter()
+            .any(|(addr, _)| addr == group)
+    }
+
+    /// Returns all the joined multicast groups, including the ones that are always joined.
+    pub(super) fn groups(&self) -> Vec<IpAddress> {
+        let mut groups = vec![
+            IpAddress::Ipv4(IPV4_ALL_SYSTEMS),
+            IpAddress::Ipv6(IPV6_LINK_LOCAL_ALL_NODES),
+        ];
+        for (group, _) in self.inner.lock().groups.iter() {
+            if !groups.contains(group) {
+                groups.push(*group);
+            }
+        }
+        groups
+    }
+
+    /// Schedules reports in response to a query.
+    ///
+    /// If the queried group is unspecified, the query is a general query and all the joined groups
+    /// of the same IP version are reported.
+    pub(super) fn on_query(&self, queried_group: IpAddress) {
+        let mut inner = self.inner.lock();
+        let Inner {
+            groups,
+            pending_reports,
+        } = &mut *inner;
+
+        for (group, _) in groups.iter() {
+            let is_queried = if queried_group.is_unspecified() {
+                group.version() == queried_group.version()
+            } else {
+                *group == queried_group
+            };
+            let report = Report::Join(*group);
+            if is_queried && need_report(group) && !pending_reports.contains(&report) {
+                pending_reports.push(report);
+            }
+        }
+    }
+
+    /// Takes the membership reports waiting to be sent.
+    pub(super) fn take_reports(&self) -> Vec<Report> {
+        core::mem::take(&mut self.inner.lock().pending_reports)
+    }
+
+    /// Marks that the receive filter of the device needs to be updated.
+    pub(super) fn mark_filter_dirty(&self) {
+        self.is_filter_dirty.store(true, Ordering::Release);
+    }
+
+    /// Clears the mark set by [`Self::mark_filter_dirty`] and returns whether it was set.
+    ///
+    /// This should be called before reading the state that determines the receive filter.
+    pub(super) fn take_filter_dirty(&self) -> bool {
+        self.is_filter_dirty.swap(false, Ordering::AcqRel)
+    }
+
+    /// Returns whether the multicast packets sent via the iface are looped back by the device
+    /// itself.
+    pub(super) fn is_looped_back_by_device(&self) -> bool {
+        self.is_looped_back_by_device
+    }
+}
+
+/// Returns whether the membership of the multicast group should be reported.
+///
+/// The memberships of the all-systems group and the all-nodes group are never reported. See
+/// <https://datatracker.ietf.org/doc/html/rfc2236#section-6> and
+/// <https://datatracker.ietf.org/doc/html/rfc2710#section-5>.
+fn need_report(group: &IpAddress) -> bool {
+    match group {
+        IpAddress::Ipv4(addr) => *addr != IPV4_ALL_SYSTEMS,
+        IpAddress::Ipv6(addr) => *addr != IPV6_LINK_LOCAL_ALL_NODES,
+    }
+}
+
+/// The hop limit of IGMP and MLD messages.
+const REPORT_HOP_LIMIT: u8 = 1;
+
+const IGMP_HEADER_LEN: usize = 8;
+const IGMP_MEMBERSHIP_QUERY: u8 = 0x11;
+const IGMP_V2_MEMBERSHIP_REPORT: u8 = 0x16;
+const IGMP_LEAVE_GROUP: u8 = 0x17;
+
+const MLD_HEADER_LEN: usize = 24;
+const MLD_LISTENER_QUERY: u8 = 130;
+const MLD_LISTENER_REPORT: u8 = 131;
+const MLD_LISTENER_DONE: u8 = 132;
+
+/// The hop-by-hop options header that contains a router alert option for MLD.
+///
+/// Reference: <https://datatracker.ietf.org/doc/html/rfc2711#section-2.1>
+const MLD_HOP_BY_HOP_HEADER: [u8; 8] = [
+    IPPROTO_ICMPV6,
+    0, // Header length in 8-octet units, excluding the first 8 octets
+    5, // Option type: router alert
+    2, // Option length
+    0, // Router alert value: MLD (the high byte)
+    0, // Router alert value: MLD (the low byte)
+    1, // Option type: PadN
+    0, // Option length
+];
+const IPPROTO_ICMPV6: u8 = 58;
+
+impl Report {
+    /// Builds the IGMP or MLD packet of the report.
+    ///
+    /// The source address is selected from the addresses of the iface.
+    pub(super) fn build(&self, ip_addrs: &[IpCidr]) -> (IpRepr, Vec<u8>) {
+        match self {
+            Report::Join(IpAddress::Ipv4(group)) => {
+                build_igmp(IGMP_V2_MEMBERSHIP_REPORT, *group, *group, ip_addrs)
+            }
+            Report::Leave(IpAddress::Ipv4(group)) => {
+                build_igmp(IGMP_LEAVE_GROUP, *group, IPV4_ALL_ROUTERS, ip_addrs)
+            }
+            Report::Join(IpAddress::Ipv6(group)) => {
+                build_mld(MLD_LISTENER_REPORT, *group, *group, ip_addrs)
+            }
+            Report::Leave(IpAddress::Ipv6(group)) => build_mld(
+                MLD_LISTENER_DONE,
+                *group,
+                IPV6_LINK_LOCAL_ALL_ROUTERS,
+                ip_addrs,
+            ),
+        }
+    }
+}
+
+fn build_igmp(
+    msg_type: u8,
+    group: Ipv4Address,
+    dst_addr: Ipv4Address,
+    ip_addrs: &[IpCidr],
+) -> (IpRepr, Vec<u8>) {
+    let src_addr = ip_addrs
+        .iter()
+        .find_map(|ip_cidr| match ip_cidr.address() {
+            IpAddress::Ipv4(addr) => Some(addr),
+            IpAddress::Ipv6(_) => None,
+        })
+        .unwrap_or(Ipv4Address::UNSPECIFIED);
+
+    let mut payload = vec![0; IGMP_HEADER_LEN];
+    payload[0] = msg_type;
+    payload[4..8].copy_from_slice(&group.octets());
+    let checksum = internet_checksum(&payload);
+    payload[2..4].copy_from_slice(&checksum.to_be_bytes());
+
+    // TODO: Add the router alert option required by RFC 2236. `Ipv4Repr` cannot emit IP options
+    // yet, but routers typically accept reports without the option.
+    let ip_repr = IpRepr::Ipv4(Ipv4Repr {
+        src_addr,
+        dst_addr,
+        next_header: IpProtocol::Igmp,
+        payload_len: payload.len(),
+        hop_limit: REPORT_HOP_LIMIT,
+    });
+
+    (ip_repr, payload)
+}
+
+fn build_mld(
+    msg_type: u8,
+    group: Ipv6Address,
+    dst_addr: Ipv6Address,
+    ip_addrs: &[IpCidr],
+) -> (IpRepr, Vec<u8>) {
+    // MLD messages should be sent from a link-local address. See
+    // <https://datatracker.ietf.org/doc/html/rfc2710#section-3>.
+    let ipv6_addrs = ip_addrs
+        .iter()
+        .filter_map(|ip_cidr| match ip_cidr.address() {
+            IpAddress::Ipv4(_) => None,
+            IpAddress::Ipv6(addr) => Some(addr),
+        });
+    let src_addr = ipv6_addrs
+        .clone()
+        .find(|addr| addr.is_unicast_link_local())
+        .or_else(|| ipv6_addrs.clone().next())
+        .unwrap_or(Ipv6Address::UNSPECIFIED);
+
+    let mut payload = vec![0; MLD_HOP_BY_HOP_HEADER.len() + MLD_HEADER_LEN];
+    payload[..MLD_HOP_BY_HOP_HEADER.len()].copy_from_slice(&MLD_HOP_BY_HOP_HEADER);
+
+    let message = &mut payload[MLD_HOP_BY_HOP_HEADER.len()..];
+    message[0] = msg_type;
+    message[8..24].copy_from_slice(&group.octets());
+    Icmpv6Packet::new_unchecked(message).fill_checksum(&src_addr, &dst_addr);
+
+    let ip_repr = IpRepr::Ipv6(Ipv6Repr {
+        src_addr,
+        dst_addr,
+        next_header: IpProtocol::HopByHop,
+        payload_len: payload.len(),
+        hop_limit: REPORT_HOP_LIMIT,
+    });
+
+    (ip_repr, payload)
+}
+
+/// Parses an IGMP message and returns the queried group if it is a membership query.
+///
+/// For general queries, the returned group is the unspecified address.
+pub(super) fn parse_igmp_query(ip_payload: &[u8]) -> Option<IpAddress> {
+    if ip_payload.len() < IGMP_HEADER_LEN || internet_checksum(ip_payload) != 0 {
+        return None;
+    }
+    if ip_payload[0] != IGMP_MEMBERSHIP_QUERY {
+        return None;
+    }
+
+    let group = <[u8; 4]>::try_from(&ip_payload[4..8]).unwrap();
+    Some(IpAddress::Ipv4(Ipv4Address::from(group)))
+}
+
+/// Parses an MLD message after the hop-by-hop options header and returns the queried group if it
+/// is a listener query.
+///
+/// For general queries, the returned group is the unspecified address.
+pub(super) fn parse_mld_query(ipv6_repr: &Ipv6Repr, ip_payload: &[u8]) -> Option<IpAddress> {
+    // MLD messages must be sent with a router alert option in a hop-by-hop options header. See
+    // <https://datatracker.ietf.org/doc/html/rfc2710#section-3>.
+    if ipv6_repr.next_header != IpProtocol::HopByHop || ip_payload.len() < 2 {
+        return None;
+    }
+    let header_len = (usize::from(ip_payload[1]) + 1) * 8;
+    if ip_payload[0] != IPPROTO_ICMPV6 || ip_payload.len() < header_len + MLD_HEADER_LEN {
+        return None;
+    }
+
+    let message = &ip_payload[header_len..];
+    let icmp_pkt = Icmpv6Packet::new_checked(message).ok()?;
+    if !icmp_pkt.verify_checksum(&ipv6_repr.src_addr, &ipv6_repr.dst_addr)
+        || message[0] != MLD_LISTENER_QUERY
+    {
+        return None;
+    }
+
+    let group = <[u8; 16]>::try_from(&message[8..24]).unwrap();
+    Some(IpAddress::Ipv6(Ipv6Address::from(group)))
+}
+
+/// Computes the Internet checksum.
+///
+/// Reference: <https://datatracker.ietf.org/doc/html/rfc1071>
+fn internet_checksum(data: &[u8]) -> u16 {
+    let mut sum = data
+        .chunks(2)
+        .map(|chunk| u32::from(u16::from_be_bytes([chunk[0], *chunk.get(1).unwrap_or(&0)])))
+        .sum::<u32>();
+    while sum >> 16 != 0 {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
@@ -1,27 +1,33 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use alloc::{collections::btree_map::BTreeMap, ffi::CString, sync::Arc};
+use alloc::{collections::btree_map::BTreeMap, ffi::CString, sync::Arc, vec::Vec};
 
 use aster_softirq::BottomHalfDisabled;
 use ostd::sync::SpinLock;
 use smoltcp::{
-    iface::{Config, Context, packet::Packet},
-    phy::{Device, DeviceCapabilities, TxToken},
+    iface::{
+        Config, Context,
+        packet::{IpPayload, Packet},
+    },
+    phy::{Checksum, ChecksumCapabilities, Device, DeviceCapabilities, TxToken},
     wire::{
         self, ArpOperation, ArpPacket, ArpRepr, EthernetAddress, EthernetFrame, EthernetProtocol,
-        EthernetRepr, IpAddress, Ipv4Address, Ipv4AddressExt, Ipv4Cidr, Ipv4Packet, Ipv6Packet,
+        EthernetRepr, Icmpv6Packet, Icmpv6Repr, IpAddress, IpProtocol, IpRepr, Ipv4Address,
+        Ipv4AddressExt, Ipv4Cidr, Ipv4Packet, Ipv6Address, Ipv6Packet, Ipv6Repr,
+        NdiscNeighborFlags, NdiscRepr, RawHardwareAddress,
     },
 };
 
 use crate::{
-    device::{NotifyDevice, WithDevice},
+    device::{FilterDevice, NotifyDevice, OffloadDevice, TxOffload, WithDevice},
     ext::Ext,
     iface::{
-        Iface, InterfaceFlags, ScheduleNextPoll,
+        FrameType, Iface, InterfaceFlags, ScheduleNextPoll,
         common::{IfaceCommon, InterfaceType, IpPacket},
         iface::internal::IfaceInternal,
         time::get_network_timestamp,
     },
+    wire::IPV6_LINK_LOCAL_ALL_NODES,
 };
 
 pub struct EtherIface<D, E: Ext> {
@@ -29,14 +35,28 @@ pub struct EtherIface<D, E: Ext> {
     common: IfaceCommon<E>,
     ether_addr: EthernetAddress,
     arp_table: SpinLock<BTreeMap<Ipv4Address, EthernetAddress>, BottomHalfDisabled>,
+    ndisc_table: SpinLock<BTreeMap<Ipv6Address, EthernetAddress>, BottomHalfDisabled>,
+}
+
+/// A packet generated by the neighbor discovery protocols.
+///
+/// The link-layer addresses of IPv4 neighbors are resolved via ARP, while those of IPv6 neighbors
+/// are resolved via NDP (see <https://datatracker.ietf.org/doc/html/rfc4861>).
+enum NeighborPacket {
+    Arp(ArpRepr),
+    Ndisc {
+        dst_ether: EthernetAddress,
+        src_addr: Ipv6Address,
+        dst_addr: Ipv6Address,
+        repr: NdiscRepr<'static>,
+    },
 }
 
 impl<D: WithDevice, E: Ext> EtherIface<D, E> {
     pub fn new(
         driver: D,
         ether_addr: EthernetAddress,
-        ip_cidr: Ipv4Cidr,
-        gateway: Ipv4Address,
+        ip_cidr: Option<Ipv4Cidr>,
         name: CString,
         sched_poll: E::ScheduleNextPoll,
         flags: InterfaceFlags,
@@ -48,13 +68,11 @@ impl<D: WithDevice, E: Ext> EtherIface<D, E> {
             let mut interface = smoltcp::iface::Interface::new(config, device, now);
             interface.update_ip_addrs(|ip_addrs| {
                 debug_assert!(ip_addrs.is_empty());
-                ip_addrs.push(wire::IpCidr::Ipv4(ip_cidr)).unwrap();
+                if let Some(ip_cidr) = ip_cidr {
+                    ip_addrs.push(wire::IpCidr::Ipv4(ip_cidr)).unwrap();
+                }
             });
             interface
-                .routes_mut()
-                .add_default_ipv4_route(gateway)
-                .unwrap();
-            interface
         });
 
         let common = IfaceCommon::new(name, InterfaceType::ETHER, flags, interface, sched_poll);
@@ -64,6 +82,7 @@ impl<D: WithDevice, E: Ext> EtherIface<D, E> {
             common,
             ether_addr,
             arp_table: SpinLock::new(BTreeMap::new()),
+            ndisc_table: SpinLock::new(BTreeMap::new()),
         })
     }
 }
@@ -76,54 +95,142 @@ impl<D, E: Ext> IfaceInternal<E> for EtherIface<D, E> {
 
 impl<D: WithDevice + 'static, E: Ext> Iface<E> for EtherIface<D, E>
 where
-    D::Device: NotifyDevice,
+    D::Device: NotifyDevice + FilterDevice + OffloadDevice,
 {
     fn poll(&self) {
         self.driver.with(|device| {
+            if let Some((is_promisc, groups)) = self.common.take_rx_filter() {
+                device.set_rx_filter(is_promisc, &self.multicast_ether_addrs(&groups));
+            }
+
+            self.transmit_pending_frames(&mut *device);
+
+            let max_tso_len = device.max_tso_len();
             let next_poll = self.common.poll(
                 &mut *device,
-                |data, iface_cx, tx_token| self.process(data, iface_cx, tx_token),
-                |pkt, iface_cx, tx_token| self.dispatch(pkt, iface_cx, tx_token),
+                max_tso_len,
+                |data, iface_cx, tx_token| self.process::<D::Device>(data, iface_cx, tx_token),
+                |pkt, segment_size, iface_cx, tx_token| {
+                    self.dispatch::<D::Device>(pkt, segment_size, iface_cx, tx_token)
+                },
             );
             device.notify_poll_end();
             self.common.sched_poll().schedule_next_poll(next_poll);
         });
     }
 
-    fn mtu(&self) -> usize {
-        self.driver
-            .with(|device| device.capabilities().max_transmission_unit)
+    fn ether_addr(&self) -> Option<EthernetAddress> {
+        Some(self.ether_addr)
     }
 }
 
 impl<D, E: Ext> EtherIface<D, E> {
-    fn process<'pkt, T: TxToken>(
+    /// Returns the multicast Ethernet addresses from which the iface should receive frames.
+    ///
+    /// They include the Ethernet addresses that the multicast groups are mapped to, as well as
+    /// those of the solicited-node multicast addresses of the IPv6 addresses (required by NDP).
+    fn multicast_ether_addrs(&self, groups: &[IpAddress]) -> Vec<EthernetAddress> {
+        let solicited_node_addrs = self.common.ip_addrs().iter().filter_map(|ip_cidr| {
+            if let IpAddress::Ipv6(addr) = ip_cidr.address() {
+                Some(IpAddress::Ipv6(solicited_node_addr(&addr)))
+            } else {
+                None
+            }
+        });
+
+        let mut ether_addrs = groups
+            .iter()
+            .copied()
+            .chain(solicited_node_addrs)
+            .map(|group| match group {
+                IpAddress::Ipv4(addr) => ipv4_multicast_ether(&addr),
+                IpAddress::Ipv6(addr) => ipv6_multicast_ether(&addr),
+            })
+            .collect::<Vec<_>>();
+        ether_addrs.sort_unstable();
+        ether_addrs.dedup();
+        ether_addrs
+    }
+
+    /// Transmits the frames sent via [`Iface::send_frame`].
+    ///
+    /// The frames that cannot be transmitted now will be transmitted in later polls.
+    fn transmit_pending_frames<T: Device + ?Sized>(&self, device: &mut T) {
+        if !self.common.is_up() {
+            return;
+        }
+
+        let mut pending_frames = self.common.pending_frames();
+
+        while !pending_frames.is_empty() {
+            let Some(tx_token) = device.transmit(get_network_timestamp()) else {
+                break;
+            };
+
+            let frame = pending_frames.pop().unwrap();
+            tx_token.consume(frame.len(), |buffer| buffer.copy_from_slice(&frame));
+            self.common.stats().on_link_send(frame.len());
+        }
+    }
+
+    fn process<'pkt, 'tx, T: OffloadDevice + ?Sized>(
         &self,
         data: &'pkt [u8],
         iface_cx: &mut Context,
-        tx_token: T,
-    ) -> Option<(IpPacket<'pkt>, T)> {
-        match self.parse_ip_or_process_arp(data, iface_cx) {
+        tx_token: T::TxToken<'tx>,
+    ) -> Option<(IpPacket<'pkt>, T::TxToken<'tx>)> {
+        let stats = self.common.stats();
+        stats.on_link_recv(data.len());
+
+        // Ignore all incoming frames if the iface is down.
+        if !self.common.is_up() {
+            stats.on_link_recv_dropped();
+            return None;
+        }
+
+        match self.parse_ip_or_process_neighbor(data, iface_cx) {
             Ok(pkt) => Some((pkt, tx_token)),
-            Err(Some(arp)) => {
-                Self::emit_arp(&arp, tx_token);
+            Err(Some(neighbor)) => {
+                self.emit_neighbor::<T>(neighbor, &iface_cx.caps, tx_token);
                 None
             }
             Err(None) => None,
         }
     }
 
-    fn parse_ip_or_process_arp<'pkt>(
+    fn parse_ip_or_process_neighbor<'pkt>(
         &self,
         data: &'pkt [u8],
         iface_cx: &mut Context,
-    ) -> Result<IpPacket<'pkt>, Option<ArpRepr>> {
+    ) -> Result<IpPacket<'pkt>, Option<NeighborPacket>> {
         // Parse the Ethernet header. Ignore the packet if the header is ill-formed.
         let frame = EthernetFrame::new_checked(data).map_err(|_| None)?;
         let repr = EthernetRepr::parse(&frame).map_err(|_| None)?;
 
-        // Ignore the Ethernet frame if it is not sent to us.
-        if !repr.dst_addr.is_broadcast() && repr.dst_addr != self.ether_addr {
+        let frame_type = if repr.dst_addr.is_broadcast() {
+            FrameType::Broadcast
+        } else if repr.dst_addr.is_multicast() {
+            FrameType::Multicast
+        } else if repr.dst_addr == self.ether_addr {
+            FrameType::Host
+        } else {
+            FrameType::OtherHost
+        };
+        if matches!(frame_type, FrameType::Broadcast | FrameType::Multicast) {
+            self.common.stats().on_link_recv_multicast();
+        }
+        self.common.tap_frame(data, frame_type, None);
+
+        // Ignore the Ethernet frame if it is not sent to us. IP multicast frames are accepted
+        // because the device filter may be imprecise (e.g., several IP multicast addresses can be
+        // mapped to the same Ethernet address). The IP layer will filter them by the multicast
+        // groups that the iface has joined.
+        let is_ip_multicast = frame_type == FrameType::Multicast
+            && matches!(
+                repr.ethertype,
+                EthernetProtocol::Ipv4 | EthernetProtocol::Ipv6
+            );
+        if frame_type != FrameType::Broadcast && frame_type != FrameType::Host && !is_ip_multicast {
             return Err(None);
         }
 
@@ -135,14 +242,22 @@ impl<D, E: Ext> EtherIface<D, E> {
             }
             EthernetProtocol::Ipv6 => {
                 let pkt = Ipv6Packet::new_checked(frame.payload()).map_err(|_| None)?;
+                if let Some((ipv6_repr, ndisc_repr)) =
+                    parse_neighbor_ndisc(&pkt, &iface_cx.checksum_caps())
+                {
+                    return Err(self.process_ndisc(&ipv6_repr, &ndisc_repr));
+                }
                 Ok(IpPacket::Ipv6(pkt))
             }
             EthernetProtocol::Arp => {
                 let pkt = ArpPacket::new_checked(frame.payload()).map_err(|_| None)?;
                 let arp = ArpRepr::parse(&pkt).map_err(|_| None)?;
-                Err(self.process_arp(&arp, iface_cx))
+                Err(self.process_arp(&arp, iface_cx).map(NeighborPacket::Arp))
+            }
+            _ => {
+                self.common.stats().on_link_recv_dropped();
+                Err(None)
             }
-            _ => Err(None),
         }
     }
 
@@ -183,9 +298,9 @@ impl<D, E: Ext> EtherIface<D, E> {
                 }
 
                 // Ignore the ARP packet if we do not own the target address.
-                if iface_cx
-                    .ipv4_addr()
-                    .is_none_or(|addr| addr != *target_protocol_addr)
+                if !self
+                    .common
+                    .has_ip_addr(IpAddress::Ipv4(*target_protocol_addr))
                 {
                     return None;
                 }
@@ -202,82 +317,277 @@ impl<D, E: Ext> EtherIface<D, E> {
         }
     }
 
-    fn dispatch<T: TxToken>(&self, pkt: &Packet, iface_cx: &mut Context, tx_token: T) {
-        match self.resolve_ether_or_generate_arp(pkt, iface_cx) {
-            Ok(ether) => Self::emit_ip(&ether, pkt, &iface_cx.caps, tx_token),
-            Err(Some(arp)) => Self::emit_arp(&arp, tx_token),
-            Err(None) => (),
+    fn process_ndisc(
+        &self,
+        ipv6_repr: &Ipv6Repr,
+        ndisc_repr: &NdiscRepr,
+    ) -> Option<NeighborPacket> {
+        match ndisc_repr {
+            NdiscRepr::NeighborAdvert {
+                target_addr,
+                lladdr: Some(lladdr),
+                ..
+            } => {
+                let target_ether = parse_ether_addr(lladdr)?;
+
+                // Insert the mapping between the Ethernet address and the IP address.
+                //
+                // TODO: Remove the mapping if it expires.
+                self.ndisc_table.lock().insert(*target_addr, target_ether);
+
+                None
+            }
+            NdiscRepr::NeighborSolicit {
+                target_addr,
+                lladdr,
+            } => {
+                // Ignore the NDP packet if we do not own the target address.
+                if !self.common.has_ip_addr(IpAddress::Ipv6(*target_addr)) {
+                    return None;
+                }
+
+                // A solicitation from the unspecified address is for duplicate address detection,
+                // so the advertisement must be multicast to all nodes. See
+                // <https://datatracker.ietf.org/doc/html/rfc4861#section-7.2.4>.
+                let (dst_addr, dst_ether, flags) = if ipv6_repr.src_addr.is_unspecified() {
+                    (
+                        IPV6_LINK_LOCAL_ALL_NODES,
+                        ipv6_multicast_ether(&IPV6_LINK_LOCAL_ALL_NODES),
+                        NdiscNeighborFlags::OVERRIDE,
+                    )
+                } else {
+                    let src_ether = parse_ether_addr(lladdr.as_ref()?)?;
+                    self.ndisc_table
+                        .lock()
+                        .insert(ipv6_repr.src_addr, src_ether);
+                    (
+                        ipv6_repr.src_addr,
+                        src_ether,
+                        NdiscNeighborFlags::SOLICITED | NdiscNeighborFlags::OVERRIDE,
+                    )
+                };
+
+                Some(NeighborPacket::Ndisc {
+                    dst_ether,
+                    src_addr: *target_addr,
+                    dst_addr,
+                    repr: NdiscRepr::NeighborAdvert {
+                        flags,
+                        target_addr: *target_addr,
+                        lladdr: Some(RawHardwareAddress::from_bytes(self.ether_addr.as_bytes())),
+                    },
+                })
+            }
+            _ => None,
         }
     }
 
-    fn resolve_ether_or_generate_arp(
+    fn dispatch<T: OffloadDevice + ?Sized>(
         &self,
         pkt: &Packet,
+        segment_size: Option<u16>,
         iface_cx: &mut Context,
-    ) -> Result<EthernetRepr, Option<ArpRepr>> {
-        // Resolve the next-hop IP address.
-        let next_hop_ip = match iface_cx.route(&pkt.ip_repr().dst_addr(), iface_cx.now()) {
-            Some(IpAddress::Ipv4(next_hop_ip)) => next_hop_ip,
-            Some(IpAddress::Ipv6(_)) => {
-                // FIXME: Currently, we drop outbound IPv6 packets because neighbor discovery is not
-                // implemented and we have no way to resolve the next-hop link-layer address.
-                ostd::debug!("IPv6 neighbor discovery is not implemented for Ethernet interfaces");
-                return Err(None);
+        tx_token: T::TxToken<'_>,
+    ) {
+        // Drop all outgoing packets if the iface is down.
+        if !self.common.is_up() {
+            self.common.stats().on_link_send_dropped();
+            return;
+        }
+
+        match self.resolve_ether_or_generate_neighbor(pkt, iface_cx) {
+            Ok(ether) => self.emit_ip::<T>(&ether, pkt, segment_size, &iface_cx.caps, tx_token),
+            Err(Some(neighbor)) => {
+                // The original packet is dropped in favor of the neighbor discovery packet.
+                self.common.stats().on_link_send_dropped();
+                self.emit_neighbor::<T>(neighbor, &iface_cx.caps, tx_token);
             }
-            None => return Err(None),
+            Err(None) => self.common.stats().on_link_send_dropped(),
+        }
+    }
+
+    fn resolve_ether_or_generate_neighbor(
+        &self,
+        pkt: &Packet,
+        iface_cx: &mut Context,
+    ) -> Result<EthernetRepr, Option<NeighborPacket>> {
+        // Resolve the next-hop IP address. Multicast packets are sent to the corresponding
+        // multicast Ethernet addresses, so no routes are needed.
+        let next_hop_ip = match pkt.ip_repr().dst_addr() {
+            dst_addr if dst_addr.is_multicast() => dst_addr,
+            dst_addr => iface_cx.route(&dst_addr, iface_cx.now()).ok_or(None)?,
         };
 
         // Resolve the next-hop Ethernet address.
-        let next_hop_ether = if next_hop_ip.is_broadcast() {
-            EthernetAddress::BROADCAST
-        } else if let Some(next_hop_ether) = self.arp_table.lock().get(&next_hop_ip) {
-            *next_hop_ether
-        } else {
-            // If the next-hop Ethernet address cannot be resolved, we drop the original packet and
-            // send an ARP packet instead. The upper layer should be responsible for detecting the
-            // packet loss and retrying later to see if the Ethernet address is ready.
-            return Err(Some(ArpRepr::EthernetIpv4 {
-                operation: ArpOperation::Request,
-                source_hardware_addr: self.ether_addr,
-                source_protocol_addr: iface_cx.ipv4_addr().unwrap_or(Ipv4Address::UNSPECIFIED),
-                target_hardware_addr: EthernetAddress::BROADCAST,
-                target_protocol_addr: next_hop_ip,
-            }));
+        let (next_hop_ether, ethertype) = match next_hop_ip {
+            IpAddress::Ipv4(next_hop_ip) => (
+                self.resolve_ipv4_ether(next_hop_ip, iface_cx)
+                    .map_err(|arp| Some(NeighborPacket::Arp(arp)))?,
+                EthernetProtocol::Ipv4,
+            ),
+            IpAddress::Ipv6(next_hop_ip) => {
+                let IpAddress::Ipv6(src_addr) = pkt.ip_repr().src_addr() else {
+                    return Err(None);
+                };
+                (
+                    self.resolve_ipv6_ether(next_hop_ip, src_addr)
+                        .map_err(Some)?,
+                    EthernetProtocol::Ipv6,
+                )
+            }
         };
 
         Ok(EthernetRepr {
             src_addr: self.ether_addr,
             dst_addr: next_hop_ether,
-            ethertype: EthernetProtocol::Ipv4,
+            ethertype,
+        })
+    }
+
+    fn resolve_ipv4_ether(
+        &self,
+        next_hop_ip: Ipv4Address,
+        iface_cx: &Context,
+    ) -> Result<EthernetAddress, ArpRepr> {
+        if next_hop_ip.is_broadcast() {
+            return Ok(EthernetAddress::BROADCAST);
+        }
+        if next_hop_ip.is_multicast() {
+            return Ok(ipv4_multicast_ether(&next_hop_ip));
+        }
+
+        if let Some(next_hop_ether) = self.arp_table.lock().get(&next_hop_ip) {
+            return Ok(*next_hop_ether);
+        }
+
+        // If the next-hop Ethernet address cannot be resolved, we drop the original packet and
+        // send an ARP packet instead. The upper layer should be responsible for detecting the
+        // packet loss and retrying later to see if the Ethernet address is ready.
+        Err(ArpRepr::EthernetIpv4 {
+            operation: ArpOperation::Request,
+            source_hardware_addr: self.ether_addr,
+            source_protocol_addr: iface_cx.ipv4_addr().unwrap_or(Ipv4Address::UNSPECIFIED),
+            target_hardware_addr: EthernetAddress::BROADCAST,
+            target_protocol_addr: next_hop_ip,
+        })
+    }
+
+    fn resolve_ipv6_ether(
+        &self,
+        next_hop_ip: Ipv6Address,
+        src_addr: Ipv6Address,
+    ) -> Result<EthernetAddress, NeighborPacket> {
+        if next_hop_ip.is_multicast() {
+            return Ok(ipv6_multicast_ether(&next_hop_ip));
+        }
+
+        if let Some(next_hop_ether) = self.ndisc_table.lock().get(&next_hop_ip) {
+            return Ok(*next_hop_ether);
+        }
+
+        // Like ARP, we drop the original packet and send a neighbor solicitation instead. The
+        // solicitation is sent to the solicited-node multicast address of the next hop. See
+        // <https://datatracker.ietf.org/doc/html/rfc4861#section-7.2.2>.
+        let dst_addr = solicited_node_addr(&next_hop_ip);
+        Err(NeighborPacket::Ndisc {
+            dst_ether: ipv6_multicast_ether(&dst_addr),
+            src_addr,
+            dst_addr,
+            repr: NdiscRepr::NeighborSolicit {
+                target_addr: next_hop_ip,
+                lladdr: Some(RawHardwareAddress::from_bytes(self.ether_addr.as_bytes())),
+            },
         })
     }
 
     /// Consumes the token and emits an IP packet.
-    fn emit_ip<T: TxToken>(
+    ///
+    /// If the TCP or UDP checksum is not computed according to `caps`, the checksum is left to
+    /// the device. If `segment_size` is not `None`, the TCP packet is split into segments by the
+    /// device, which also computes their checksums.
+    fn emit_ip<T: OffloadDevice + ?Sized>(
+        &self,
         ether_repr: &EthernetRepr,
         ip_pkt: &Packet,
+        segment_size: Option<u16>,
         caps: &DeviceCapabilities,
-        tx_token: T,
+        tx_token: T::TxToken<'_>,
     ) {
-        tx_token.consume(
-            ether_repr.buffer_len() + ip_pkt.ip_repr().buffer_len(),
-            |buffer| {
-                let mut frame = EthernetFrame::new_unchecked(buffer);
-                ether_repr.emit(&mut frame);
-
-                let ip_repr = ip_pkt.ip_repr();
-                ip_repr.emit(frame.payload_mut(), &caps.checksum);
-                ip_pkt.emit_payload(
-                    &ip_repr,
-                    &mut frame.payload_mut()[ip_repr.header_len()..],
-                    caps,
-                );
+        let len = ether_repr.buffer_len() + ip_pkt.ip_repr().buffer_len();
+        self.common.stats().on_link_send(len);
+
+        let mut caps = caps.clone();
+        if segment_size.is_some() {
+            caps.checksum.tcp = Checksum::None;
+        }
+
+        // Raw payloads are emitted as is, so their checksums are never left to the device.
+        let offload = TxOffload {
+            checksum: match ip_pkt.payload() {
+                IpPayload::Tcp(_) => !caps.checksum.tcp.tx(),
+                IpPayload::Udp(..) => !caps.checksum.udp.tx(),
+                _ => false,
             },
-        );
+            segment_size,
+        };
+
+        T::consume_with_offload(tx_token, len, offload, |buffer| {
+            let mut frame = EthernetFrame::new_unchecked(buffer);
+            ether_repr.emit(&mut frame);
+
+            let ip_repr = ip_pkt.ip_repr();
+            ip_repr.emit(frame.payload_mut(), &caps.checksum);
+            ip_pkt.emit_payload(
+                &ip_repr,
+                &mut frame.payload_mut()[ip_repr.header_len()..],
+                &caps,
+            );
+
+            self.common
+                .tap_frame(frame.into_inner(), FrameType::Outgoing, None);
+        });
+    }
+
+    /// Consumes the token and emits a packet of the neighbor discovery protocols.
+    fn emit_neighbor<T: OffloadDevice + ?Sized>(
+        &self,
+        neighbor: NeighborPacket,
+        caps: &DeviceCapabilities,
+        tx_token: T::TxToken<'_>,
+    ) {
+        let (dst_ether, src_addr, dst_addr, repr) = match neighbor {
+            NeighborPacket::Arp(arp_repr) => {
+                self.emit_arp(&arp_repr, tx_token);
+                return;
+            }
+            NeighborPacket::Ndisc {
+                dst_ether,
+                src_addr,
+                dst_addr,
+                repr,
+            } => (dst_ether, src_addr, dst_addr, repr),
+        };
+
+        let ether_repr = EthernetRepr {
+            src_addr: self.ether_addr,
+            dst_addr: dst_ether,
+            ethertype: EthernetProtocol::Ipv6,
+        };
+        let icmp_repr = Icmpv6Repr::Ndisc(repr);
+        let ip_repr = IpRepr::Ipv6(Ipv6Repr {
+            src_addr,
+            dst_addr,
+            next_header: IpProtocol::Icmpv6,
+            payload_len: icmp_repr.buffer_len(),
+            hop_limit: NDISC_HOP_LIMIT,
+        });
+        let pkt = Packet::new(ip_repr, IpPayload::Icmpv6(icmp_repr));
+
+        self.emit_ip::<T>(&ether_repr, &pkt, None, caps, tx_token);
     }
 
     /// Consumes the token and emits an ARP packet.
-    fn emit_arp<T: TxToken>(arp_repr: &ArpRepr, tx_token: T) {
+    fn emit_arp<T: TxToken>(&self, arp_repr: &ArpRepr, tx_token: T) {
         let ether_repr = match arp_repr {
             ArpRepr::EthernetIpv4 {
                 source_hardware_addr,
@@ -291,12 +601,96 @@ impl<D, E: Ext> EtherIface<D, E> {
             _ => return,
         };
 
-        tx_token.consume(ether_repr.buffer_len() + arp_repr.buffer_len(), |buffer| {
+        let len = ether_repr.buffer_len() + arp_repr.buffer_len();
+        self.common.stats().on_link_send(len);
+
+        tx_token.consume(len, |buffer| {
             let mut frame = EthernetFrame::new_unchecked(buffer);
             ether_repr.emit(&mut frame);
 
             let mut pkt = ArpPacket::new_unchecked(frame.payload_mut());
             arp_repr.emit(&mut pkt);
+
+            self.common
+                .tap_frame(frame.into_inner(), FrameType::Outgoing, None);
         });
     }
 }
+
+/// The hop limit of NDP packets.
+///
+/// NDP packets with other hop limits must be discarded, since they may have been forwarded by
+/// routers. See <https://datatracker.ietf.org/doc/html/rfc4861#section-7.1.1>.
+const NDISC_HOP_LIMIT: u8 = 255;
+
+/// Parses a neighbor solicitation or a neighbor advertisement in the IPv6 packet.
+///
+/// Other NDP packets (e.g., router advertisements) are left to the IP layer.
+fn parse_neighbor_ndisc<'pkt>(
+    pkt: &Ipv6Packet<&'pkt [u8]>,
+    checksum_caps: &ChecksumCapabilities,
+) -> Option<(Ipv6Repr, NdiscRepr<'pkt>)> {
+    let ipv6_repr = Ipv6Repr::parse(pkt).ok()?;
+    if ipv6_repr.next_header != IpProtocol::Icmpv6 || ipv6_repr.hop_limit != NDISC_HOP_LIMIT {
+        return None;
+    }
+
+    let icmp_pkt = Icmpv6Packet::new_checked(pkt.payload()).ok()?;
+    let Icmpv6Repr::Ndisc(ndisc_repr) = Icmpv6Repr::parse(
+        &ipv6_repr.src_addr,
+        &ipv6_repr.dst_addr,
+        &icmp_pkt,
+        checksum_caps,
+    )
+    .ok()?
+    else {
+        return None;
+    };
+
+    match ndisc_repr {
+        NdiscRepr::NeighborSolicit { .. } | NdiscRepr::NeighborAdvert { .. } => {
+            Some((ipv6_repr, ndisc_repr))
+        }
+        _ => None,
+    }
+}
+
+/// Parses the link-layer address in an NDP option as an Ethernet address.
+fn parse_ether_addr(lladdr: &RawHardwareAddress) -> Option<EthernetAddress> {
+    let bytes = <[u8; 6]>::try_from(lladdr.as_bytes()).ok()?;
+    let ether_addr = EthernetAddress(bytes);
+    ether_addr.is_unicast().then_some(ether_addr)
+}
+
+/// Returns the solicited-node multicast address of the IPv6 address.
+///
+/// Reference: <https://datatracker.ietf.org/doc/html/rfc4291#section-2.7.1>
+fn solicited_node_addr(addr: &Ipv6Address) -> Ipv6Address {
+    let octets = addr.octets();
+    Ipv6Address::new(
+        0xff02,
+        0,
+        0,
+        0,
+        0,
+        1,
+        0xff00 | u16::from(octets[13]),
+        u16::from_be_bytes([octets[14], octets[15]]),
+    )
+}
+
+/// Returns the Ethernet address that the IPv4 multicast address is mapped to.
+///
+/// Reference: <https://datatracker.ietf.org/doc/html/rfc1112#section-6.4>
+fn ipv4_multicast_ether(addr: &Ipv4Address) -> EthernetAddress {
+    let octets = addr.octets();
+    EthernetAddress([0x01, 0x00, 0x5e, octets[1] & 0x7f, octets[2], octets[3]])
+}
+
+/// Returns the Ethernet address that the IPv6 multicast address is mapped to.
+///
+/// Reference: <https://datatracker.ietf.org/doc/html/rfc2464#section-7>
+fn ipv6_multicast_ether(addr: &Ipv6Address) -> EthernetAddress {
+    let octets = addr.octets();
+    EthernetAddress([0x33, 0x33, octets[12], octets[13], octets[14], octets[15]])
+}
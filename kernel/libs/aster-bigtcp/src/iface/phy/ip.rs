@@ -5,7 +5,7 @@ use alloc::{ffi::CString, sync::Arc};
 use smoltcp::{
     iface::Config,
     phy::{Device, TxToken},
-    wire::{self, Ipv4Cidr, Ipv4Packet, Ipv6Cidr, Ipv6Packet},
+    wire::{self, EthernetAddress, Ipv4Cidr, Ipv6Cidr},
 };
 
 use crate::{
@@ -25,10 +25,10 @@ pub struct IpIface<D, E: Ext> {
 }
 
 impl<D: WithDevice, E: Ext> IpIface<D, E> {
-    // TODO: Support interfaces with multiple IPv4/IPv6 addresses or without IPv4 addresses.
+    // TODO: Support interfaces with multiple IPv4/IPv6 addresses.
     pub fn new(
         driver: D,
-        ip_cidr: Ipv4Cidr,
+        ip_cidr: Option<Ipv4Cidr>,
         ipv6_cidr: Option<Ipv6Cidr>,
         name: CString,
         sched_poll: E::ScheduleNextPoll,
@@ -42,7 +42,9 @@ impl<D: WithDevice, E: Ext> IpIface<D, E> {
             let mut interface = smoltcp::iface::Interface::new(config, device, now);
             interface.update_ip_addrs(|ip_addrs| {
                 debug_assert!(ip_addrs.is_empty());
-                ip_addrs.push(wire::IpCidr::Ipv4(ip_cidr)).unwrap();
+                if let Some(ip_cidr) = ip_cidr {
+                    ip_addrs.push(wire::IpCidr::Ipv4(ip_cidr)).unwrap();
+                }
                 if let Some(ipv6_cidr) = ipv6_cidr {
                     ip_addrs.push(wire::IpCidr::Ipv6(ipv6_cidr)).unwrap();
                 }
@@ -67,24 +69,34 @@ impl<D: WithDevice + 'static, E: Ext> Iface<E> for IpIface<D, E> {
         self.driver.with(|device| {
             let next_poll = self.common.poll(
                 device,
+                None,
                 |data, _iface_cx, tx_token| {
-                    if data.is_empty() {
+                    let stats = self.common.stats();
+                    stats.on_link_recv(data.len());
+
+                    // Ignore all incoming packets if the iface is down.
+                    if !self.common.is_up() {
+                        stats.on_link_recv_dropped();
                         return None;
                     }
-                    let version = data[0] >> 4;
 
-                    if version == 4 {
-                        let pkt = Ipv4Packet::new_checked(data).ok()?;
-                        Some((IpPacket::Ipv4(pkt), tx_token))
-                    } else if version == 6 {
-                        let pkt = Ipv6Packet::new_checked(data).ok()?;
-                        Some((IpPacket::Ipv6(pkt), tx_token))
-                    } else {
-                        None
-                    }
+                    let Some(pkt) = IpPacket::new_checked(data) else {
+                        stats.on_link_recv_dropped();
+                        return None;
+                    };
+                    Some((pkt, tx_token))
                 },
-                |pkt, iface_cx, tx_token| {
+                |pkt, _segment_size, iface_cx, tx_token| {
+                    let stats = self.common.stats();
+
+                    // Drop all outgoing packets if the iface is down.
+                    if !self.common.is_up() {
+                        stats.on_link_send_dropped();
+                        return;
+                    }
+
                     let ip_repr = pkt.ip_repr();
+                    stats.on_link_send(ip_repr.buffer_len());
                     tx_token.consume(ip_repr.buffer_len(), |buffer| {
                         ip_repr.emit(&mut buffer[..], &iface_cx.checksum_caps());
                         pkt.emit_payload(
@@ -99,8 +111,7 @@ impl<D: WithDevice + 'static, E: Ext> Iface<E> for IpIface<D, E> {
         });
     }
 
-    fn mtu(&self) -> usize {
-        self.driver
-            .with(|device| device.capabilities().max_transmission_unit)
+    fn ether_addr(&self) -> Option<EthernetAddress> {
+        None
     }
 }
@@ -9,22 +9,36 @@ use smoltcp::{
     },
     phy::{ChecksumCapabilities, Device, RxToken, TxToken},
     wire::{
-        IPV4_HEADER_LEN, IPV4_MIN_MTU, Icmpv4DstUnreachable, Icmpv4Repr, IpAddress, IpProtocol,
-        IpRepr, Ipv4Address, Ipv4Packet, Ipv4Repr, Ipv6Packet, Ipv6Repr, TcpControl, TcpPacket,
-        TcpRepr, UdpPacket, UdpRepr,
+        IPV4_HEADER_LEN, IPV4_MIN_MTU, Icmpv4DstUnreachable, Icmpv4Message, Icmpv4Packet,
+        Icmpv4Repr, Icmpv6DstUnreachable, Icmpv6Message, Icmpv6Packet, Icmpv6Repr, IpAddress,
+        IpCidr, IpEndpoint, IpProtocol, IpRepr, IpVersion, Ipv4Address, Ipv4Packet, Ipv4Repr,
+        Ipv6Packet, Ipv6Repr, TcpControl, TcpPacket, TcpRepr, UDP_HEADER_LEN, UdpPacket, UdpRepr,
     },
 };
 
-use super::{common::IpPacket, poll_iface::PollableIfaceMut};
+use super::{
+    common::IpPacket,
+    filter::{ActiveFilter, FilterHook},
+    multicast::{MulticastGroups, Report, parse_igmp_query, parse_mld_query},
+    poll_iface::PollableIfaceMut,
+    stats::IfaceStats,
+    tso::TsoBatch,
+};
 use crate::{
     ext::Ext,
-    socket::{TcpConnectionBg, TcpProcessResult},
+    socket::{TcpConnectionBg, TcpDispatchResult, TcpProcessResult, UdpIcmpError},
     socket_table::{ConnectionKey, ListenerKey, SocketTable},
 };
 
 pub(super) struct PollContext<'a, E: Ext> {
     iface: PollableIfaceMut<'a, E>,
+    ip_addrs: &'a [IpCidr],
+    multicast: &'a MulticastGroups,
     sockets: &'a SocketTable<E>,
+    filter: Option<&'a ActiveFilter<'a>>,
+    stats: &'a IfaceStats,
+    /// The maximum length of the TCP packets that the device can split into segments (TSO).
+    max_tso_len: Option<usize>,
     actions: &'a mut Vec<SocketTableAction<E>>,
 }
 
@@ -40,12 +54,22 @@ pub(super) enum SocketTableAction<E: Ext> {
 impl<'a, E: Ext> PollContext<'a, E> {
     pub(super) fn new(
         iface: PollableIfaceMut<'a, E>,
+        ip_addrs: &'a [IpCidr],
+        multicast: &'a MulticastGroups,
         sockets: &'a SocketTable<E>,
+        filter: Option<&'a ActiveFilter<'a>>,
+        stats: &'a IfaceStats,
+        max_tso_len: Option<usize>,
         actions: &'a mut Vec<SocketTableAction<E>>,
     ) -> Self {
         Self {
             iface,
+            ip_addrs,
+            multicast,
             sockets,
+            filter,
+            stats,
+            max_tso_len,
             actions,
         }
     }
@@ -70,7 +94,7 @@ impl<E: Ext> PollContext<'_, E> {
                 D::TxToken<'tx>,
                 Option<(IpPacket<'pkt>, D::TxToken<'tx>)>,
             >,
-        Q: FnMut(&Packet, &mut Context, D::TxToken<'_>),
+        Q: FnMut(&Packet, Option<u16>, &mut Context, D::TxToken<'_>),
     {
         while let Some((rx_token, tx_token)) = device.receive(self.iface.context().now()) {
             rx_token.consume(|data| {
@@ -79,13 +103,32 @@ impl<E: Ext> PollContext<'_, E> {
                 else {
                     return;
                 };
+                self.stats.on_ip_recv(match ip_packet {
+                    IpPacket::Ipv4(_) => IpVersion::Ipv4,
+                    IpPacket::Ipv6(_) => IpVersion::Ipv6,
+                });
+
+                // Incoming packets traverse the input hooks before being processed.
+                let filtered_data;
+                let ip_packet = if let Some(filter) = self.filter {
+                    let Some(data) = filter.filter_received(&ip_packet) else {
+                        return;
+                    };
+                    filtered_data = data;
+                    let Some(ip_packet) = IpPacket::new_checked(&filtered_data) else {
+                        return;
+                    };
+                    ip_packet
+                } else {
+                    ip_packet
+                };
 
                 let reply = match ip_packet {
                     IpPacket::Ipv4(p) => self.parse_and_process_ipv4(p),
                     IpPacket::Ipv6(p) => self.parse_and_process_ipv6(p),
                 };
                 let Some(reply) = reply else { return };
-                dispatch_phy(&reply, self.iface.context_mut(), tx_token);
+                dispatch_phy(&reply, None, self.iface.context_mut(), tx_token);
             });
         }
     }
@@ -97,7 +140,14 @@ impl<E: Ext> PollContext<'_, E> {
         // Parse the IP header. Ignore the packet if the header is ill-formed.
         let repr = Ipv4Repr::parse(&pkt, &self.iface.context().checksum_caps()).ok()?;
 
-        if !repr.dst_addr.is_broadcast() && !self.is_unicast_local(IpAddress::Ipv4(repr.dst_addr)) {
+        // Multicast packets are accepted only if the iface has joined the multicast group.
+        if repr.dst_addr.is_multicast() {
+            if !self.multicast.contains(&IpAddress::Ipv4(repr.dst_addr)) {
+                return None;
+            }
+        } else if !repr.dst_addr.is_broadcast()
+            && !self.is_unicast_local(IpAddress::Ipv4(repr.dst_addr))
+        {
             return self.generate_icmp_unreachable(
                 &IpRepr::Ipv4(repr),
                 pkt.payload(),
@@ -105,16 +155,14 @@ impl<E: Ext> PollContext<'_, E> {
             );
         }
 
+        let traffic_class = (pkt.dscp() << 2) | pkt.ecn();
         let checksum_caps = self.iface.context().checksum_caps();
-        match repr.next_header {
-            IpProtocol::Tcp => {
-                self.parse_and_process_tcp(&IpRepr::Ipv4(repr), pkt.payload(), &checksum_caps)
-            }
-            IpProtocol::Udp => {
-                self.parse_and_process_udp(&IpRepr::Ipv4(repr), pkt.payload(), &checksum_caps)
-            }
-            _ => None,
-        }
+        self.process_ip_payload(
+            &IpRepr::Ipv4(repr),
+            traffic_class,
+            pkt.payload(),
+            &checksum_caps,
+        )
     }
 
     fn parse_and_process_ipv6<'pkt>(
@@ -124,18 +172,63 @@ impl<E: Ext> PollContext<'_, E> {
         // Parse the IPv6 header. Ignore the packet if the header is ill-formed.
         let repr = Ipv6Repr::parse(&pkt).ok()?;
 
-        if !self.is_unicast_local(IpAddress::Ipv6(repr.dst_addr)) {
+        // Multicast packets are accepted only if the iface has joined the multicast group. Note
+        // that the iface always joins the all-nodes group (e.g., for router advertisements).
+        if repr.dst_addr.is_multicast() {
+            if !self.multicast.contains(&IpAddress::Ipv6(repr.dst_addr)) {
+                return None;
+            }
+        } else if !self.is_unicast_local(IpAddress::Ipv6(repr.dst_addr)) {
             // TODO: Generate an IPv6 ICMP unreachable message.
             return None;
         }
 
+        let traffic_class = pkt.traffic_class();
         let checksum_caps = self.iface.context().checksum_caps();
-        match repr.next_header {
-            IpProtocol::Tcp => {
-                self.parse_and_process_tcp(&IpRepr::Ipv6(repr), pkt.payload(), &checksum_caps)
+        self.process_ip_payload(
+            &IpRepr::Ipv6(repr),
+            traffic_class,
+            pkt.payload(),
+            &checksum_caps,
+        )
+    }
+
+    fn process_ip_payload<'pkt>(
+        &mut self,
+        ip_repr: &IpRepr,
+        traffic_class: u8,
+        ip_payload: &'pkt [u8],
+        checksum_caps: &ChecksumCapabilities,
+    ) -> Option<Packet<'pkt>> {
+        // TCP and UDP packets are counted when they are processed, since packets sent to local
+        // addresses may be processed without going through this method.
+        if !matches!(ip_repr.next_header(), IpProtocol::Tcp | IpProtocol::Udp) {
+            self.stats.on_ip_deliver(ip_repr.version());
+        }
+
+        // Raw IP sockets receive a copy of the packet before the packet is handled by the
+        // protocol.
+        self.process_raw(ip_repr, ip_payload);
+
+        match (ip_repr, ip_repr.next_header()) {
+            (_, IpProtocol::Tcp) => self.parse_and_process_tcp(ip_repr, ip_payload, checksum_caps),
+            (_, IpProtocol::Udp) => {
+                self.parse_and_process_udp(ip_repr, traffic_class, ip_payload, checksum_caps)
             }
-            IpProtocol::Udp => {
-                self.parse_and_process_udp(&IpRepr::Ipv6(repr), pkt.payload(), &checksum_caps)
+            (IpRepr::Ipv4(_), IpProtocol::Icmp) | (IpRepr::Ipv6(_), IpProtocol::Icmpv6) => {
+                self.parse_and_process_icmp(ip_repr, ip_payload, checksum_caps)
+            }
+            (IpRepr::Ipv4(_), IpProtocol::Igmp) => {
+                if let Some(group) = parse_igmp_query(ip_payload) {
+                    self.multicast.on_query(group);
+                }
+                None
+            }
+            (IpRepr::Ipv6(ipv6_repr), IpProtocol::HopByHop) => {
+                if let Some(group) = parse_mld_query(ipv6_repr, ip_payload) {
+                    self.multicast.on_query(group);
+                }
+                None
             }
             _ => None,
         }
@@ -164,26 +257,56 @@ impl<E: Ext> PollContext<'_, E> {
         )
         .ok()?;
 
-        self.process_tcp_until_outgoing(ip_repr, &tcp_repr)
+        let (reply_ip_repr, reply_tcp_repr) = self.process_tcp(ip_repr, &tcp_repr)?;
+        self.process_tcp_until_outgoing(reply_ip_repr, reply_tcp_repr)
             .map(|(ip_repr, tcp_repr)| Packet::new(ip_repr, IpPayload::Tcp(tcp_repr)))
     }
 
+    /// Processes a TCP packet if it is sent to a local address, then processes the reply if it is
+    /// also sent to a local address, and so on.
+    ///
+    /// The first packet that is not sent to a local address is returned.
     fn process_tcp_until_outgoing(
+        &mut self,
+        mut ip_repr: IpRepr,
+        mut tcp_repr: TcpRepr<'static>,
+    ) -> Option<(IpRepr, TcpRepr<'static>)> {
+        while self.is_unicast_local(ip_repr.dst_addr()) {
+            self.count_local(&ip_repr);
+            (ip_repr, tcp_repr) = self.process_local_tcp(&ip_repr, &tcp_repr)?;
+        }
+
+        Some((ip_repr, tcp_repr))
+    }
+
+    /// Processes a TCP packet sent to a local address.
+    fn process_local_tcp(
         &mut self,
         ip_repr: &IpRepr,
         tcp_repr: &TcpRepr,
     ) -> Option<(IpRepr, TcpRepr<'static>)> {
-        let (mut ip_repr, mut tcp_repr) = self.process_tcp(ip_repr, tcp_repr)?;
+        let Some(filter) = self.filter else {
+            return self.process_tcp(ip_repr, tcp_repr);
+        };
 
-        loop {
-            if !self.is_unicast_local(ip_repr.dst_addr()) {
-                return Some((ip_repr, tcp_repr));
-            }
+        let (ip_repr, ip_payload) = filter.filter(FilterHook::LOCAL_PATH, ip_repr, |payload| {
+            tcp_repr.emit(
+                &mut TcpPacket::new_unchecked(payload),
+                &ip_repr.src_addr(),
+                &ip_repr.dst_addr(),
+                &ChecksumCapabilities::ignored(),
+            )
+        })?;
+        let tcp_pkt = TcpPacket::new_checked(ip_payload.as_slice()).ok()?;
+        let tcp_repr = TcpRepr::parse(
+            &tcp_pkt,
+            &ip_repr.src_addr(),
+            &ip_repr.dst_addr(),
+            &ChecksumCapabilities::ignored(),
+        )
+        .ok()?;
 
-            let (new_ip_repr, new_tcp_repr) = self.process_tcp(&ip_repr, &tcp_repr)?;
-            ip_repr = new_ip_repr;
-            tcp_repr = new_tcp_repr;
-        }
+        self.process_tcp(&ip_repr, &tcp_repr)
     }
 
     fn process_tcp(
@@ -191,6 +314,8 @@ impl<E: Ext> PollContext<'_, E> {
         ip_repr: &IpRepr,
         tcp_repr: &TcpRepr,
     ) -> Option<(IpRepr, TcpRepr<'static>)> {
+        self.stats.on_tcp_recv(ip_repr);
+
         // Process packets belonging to existing connections first.
         // Note that we must do this first because SYN packets may match existing TIME-WAIT
         // sockets. See comments in `TcpConnectionBg::process` for details.
@@ -277,6 +402,7 @@ impl<E: Ext> PollContext<'_, E> {
     fn parse_and_process_udp<'pkt>(
         &mut self,
         ip_repr: &IpRepr,
+        traffic_class: u8,
         ip_payload: &'pkt [u8],
         checksum_caps: &ChecksumCapabilities,
     ) -> Option<Packet<'pkt>> {
@@ -290,7 +416,11 @@ impl<E: Ext> PollContext<'_, E> {
         )
         .ok()?;
 
-        if !self.process_udp(ip_repr, &udp_repr, udp_pkt.payload()) {
+        if !self.process_udp(ip_repr, traffic_class, &udp_repr, udp_pkt.payload()) {
+            if self.is_unicast_local(ip_repr.src_addr()) {
+                self.report_local_port_unreachable(ip_repr, &udp_repr, udp_pkt.payload());
+                return None;
+            }
             return self.generate_icmp_unreachable(
                 ip_repr,
                 ip_payload,
@@ -301,7 +431,13 @@ impl<E: Ext> PollContext<'_, E> {
         None
     }
 
-    fn process_udp(&mut self, ip_repr: &IpRepr, udp_repr: &UdpRepr, udp_payload: &[u8]) -> bool {
+    fn process_udp(
+        &mut self,
+        ip_repr: &IpRepr,
+        traffic_class: u8,
+        udp_repr: &UdpRepr,
+        udp_payload: &[u8],
+    ) -> bool {
         let mut processed = false;
 
         for socket in self.sockets.udp_socket_iter() {
@@ -309,15 +445,258 @@ impl<E: Ext> PollContext<'_, E> {
                 continue;
             }
 
-            processed |= socket.process(self.iface.context_mut(), ip_repr, udp_repr, udp_payload);
+            processed |= socket.process(
+                self.iface.context_mut(),
+                ip_repr,
+                traffic_class,
+                udp_repr,
+                udp_payload,
+            );
             if processed && ip_repr.dst_addr().is_unicast() {
                 break;
             }
         }
 
+        self.stats.on_udp_recv(ip_repr, processed);
+
         processed
     }
 
+    /// Reports that the destination port is unreachable to the local UDP socket that sent the
+    /// packet.
+    ///
+    /// This is done instead of generating an ICMP message, which would be sent to a local address.
+    fn report_local_port_unreachable(
+        &self,
+        ip_repr: &IpRepr,
+        udp_repr: &UdpRepr,
+        udp_payload: &[u8],
+    ) {
+        if !ip_repr.dst_addr().is_unicast() {
+            return;
+        }
+
+        let (icmp_type, icmp_code) = match ip_repr {
+            IpRepr::Ipv4(_) => (
+                u8::from(Icmpv4Message::DstUnreachable),
+                u8::from(Icmpv4DstUnreachable::PortUnreachable),
+            ),
+            IpRepr::Ipv6(_) => (
+                u8::from(Icmpv6Message::DstUnreachable),
+                u8::from(Icmpv6DstUnreachable::PortUnreachable),
+            ),
+        };
+
+        let local_endpoint = IpEndpoint::new(ip_repr.src_addr(), udp_repr.src_port);
+        self.process_udp_icmp_error(&local_endpoint, || UdpIcmpError {
+            offender: ip_repr.dst_addr(),
+            icmp_type,
+            icmp_code,
+            remote_endpoint: IpEndpoint::new(ip_repr.dst_addr(), udp_repr.dst_port),
+            payload: udp_payload.to_vec(),
+        });
+    }
+
+    /// Reports an ICMP error to the UDP socket that sent the packet causing the error.
+    ///
+    /// `header` and `data` are the IP header and the (possibly truncated) IP payload of the packet
+    /// causing the error, which are quoted in the ICMP message.
+    fn process_udp_icmp_error_message(
+        &self,
+        offender: IpAddress,
+        (icmp_type, icmp_code): (u8, u8),
+        header: &IpRepr,
+        data: &[u8],
+    ) {
+        if header.next_header() != IpProtocol::Udp || data.len() < UDP_HEADER_LEN {
+            return;
+        }
+
+        let udp_pkt = UdpPacket::new_unchecked(data);
+        let local_endpoint = IpEndpoint::new(header.src_addr(), udp_pkt.src_port());
+        self.process_udp_icmp_error(&local_endpoint, || UdpIcmpError {
+            offender,
+            icmp_type,
+            icmp_code,
+            remote_endpoint: IpEndpoint::new(header.dst_addr(), udp_pkt.dst_port()),
+            payload: data[UDP_HEADER_LEN..].to_vec(),
+        });
+    }
+
+    fn process_udp_icmp_error<F>(&self, local_endpoint: &IpEndpoint, new_error: F)
+    where
+        F: FnOnce() -> UdpIcmpError,
+    {
+        let Some(socket) = self.sockets.udp_socket_iter().find(|socket| {
+            socket.can_process(local_endpoint.port) && socket.is_bound_to(local_endpoint)
+        }) else {
+            return;
+        };
+
+        socket.process_icmp_error(new_error);
+    }
+
+    fn process_raw(&mut self, ip_repr: &IpRepr, ip_payload: &[u8]) {
+        for socket in self.sockets.raw_socket_iter() {
+            socket.process(self.iface.context_mut(), ip_repr, ip_payload);
+        }
+    }
+
+    fn parse_and_process_icmp<'pkt>(
+        &self,
+        ip_repr: &IpRepr,
+        ip_payload: &'pkt [u8],
+        checksum_caps: &ChecksumCapabilities,
+    ) -> Option<Packet<'pkt>> {
+        // Only unicast echo requests are answered. Like Linux, echo requests sent to broadcast
+        // addresses are ignored.
+        if !ip_repr.src_addr().is_unicast() || !ip_repr.dst_addr().is_unicast() {
+            return None;
+        }
+
+        // ICMP errors are reported to the UDP sockets that caused them. Other ICMP messages are
+        // only delivered to raw IP sockets, which has already been done.
+        match ip_repr {
+            IpRepr::Ipv4(ipv4_repr) => {
+                // Parse the ICMP header. Ignore the packet if the header is ill-formed.
+                let icmp_pkt = Icmpv4Packet::new_checked(ip_payload).ok()?;
+                let icmp_type_code = (u8::from(icmp_pkt.msg_type()), icmp_pkt.msg_code());
+                let icmp_repr = Icmpv4Repr::parse(&icmp_pkt, checksum_caps).ok()?;
+                let (ident, seq_no, data) = match icmp_repr {
+                    Icmpv4Repr::EchoRequest {
+                        ident,
+                        seq_no,
+                        data,
+                    } => (ident, seq_no, data),
+                    Icmpv4Repr::DstUnreachable { header, data, .. }
+                    | Icmpv4Repr::TimeExceeded { header, data, .. } => {
+                        self.process_udp_icmp_error_message(
+                            IpAddress::Ipv4(ipv4_repr.src_addr),
+                            icmp_type_code,
+                            &IpRepr::Ipv4(header),
+                            data,
+                        );
+                        return None;
+                    }
+                    _ => return None,
+                };
+
+                let icmp_repr = Icmpv4Repr::EchoReply {
+                    ident,
+                    seq_no,
+                    data,
+                };
+                Some(Packet::new_ipv4(
+                    Ipv4Repr {
+                        src_addr: ipv4_repr.dst_addr,
+                        dst_addr: ipv4_repr.src_addr,
+                        next_header: IpProtocol::Icmp,
+                        payload_len: icmp_repr.buffer_len(),
+                        hop_limit: 64,
+                    },
+                    IpPayload::Icmpv4(icmp_repr),
+                ))
+            }
+            IpRepr::Ipv6(ipv6_repr) => {
+                // Parse the ICMPv6 header. Ignore the packet if the header is ill-formed.
+                let icmp_pkt = Icmpv6Packet::new_checked(ip_payload).ok()?;
+                let icmp_type_code = (u8::from(icmp_pkt.msg_type()), icmp_pkt.msg_code());
+                let icmp_repr = Icmpv6Repr::parse(
+                    &ipv6_repr.src_addr,
+                    &ipv6_repr.dst_addr,
+                    &icmp_pkt,
+                    checksum_caps,
+                )
+                .ok()?;
+                let (ident, seq_no, data) = match icmp_repr {
+                    Icmpv6Repr::EchoRequest {
+                        ident,
+                        seq_no,
+                        data,
+                    } => (ident, seq_no, data),
+                    Icmpv6Repr::DstUnreachable { header, data, .. }
+                    | Icmpv6Repr::TimeExceeded { header, data, .. } => {
+                        self.process_udp_icmp_error_message(
+                            IpAddress::Ipv6(ipv6_repr.src_addr),
+                            icmp_type_code,
+                            &IpRepr::Ipv6(header),
+                            data,
+                        );
+                        return None;
+                    }
+                    _ => return None,
+                };
+
+                let icmp_repr = Icmpv6Repr::EchoReply {
+                    ident,
+                    seq_no,
+                    data,
+                };
+                Some(Packet::new(
+                    IpRepr::Ipv6(Ipv6Repr {
+                        src_addr: ipv6_repr.dst_addr,
+                        dst_addr: ipv6_repr.src_addr,
+                        next_header: IpProtocol::Icmpv6,
+                        payload_len: icmp_repr.buffer_len(),
+                        hop_limit: 64,
+                    }),
+                    IpPayload::Icmpv6(icmp_repr),
+                ))
+            }
+        }
+    }
+
+    /// Processes a packet sent to a local address, then processes the reply if it is also sent
+    /// to a local address, and so on.
+    ///
+    /// The first reply that is not sent to a local address is returned.
+    fn process_ip_until_outgoing(
+        &mut self,
+        mut ip_repr: IpRepr,
+        mut ip_payload: Vec<u8>,
+    ) -> Option<(IpRepr, Vec<u8>)> {
+        (ip_repr, ip_payload) = self.filter_local(ip_repr, ip_payload)?;
+
+        loop {
+            // The traffic class of locally generated packets is always zero.
+            let reply = self.process_ip_payload(
+                &ip_repr,
+                0,
+                &ip_payload,
+                &ChecksumCapabilities::ignored(),
+            )?;
+
+            let reply_ip_repr = reply.ip_repr();
+            let mut reply_ip_payload = vec![0; reply_ip_repr.payload_len()];
+            reply.emit_payload(
+                &reply_ip_repr,
+                &mut reply_ip_payload,
+                &self.iface.context().caps,
+            );
+
+            if !self.is_unicast_local(reply_ip_repr.dst_addr()) {
+                return Some((reply_ip_repr, reply_ip_payload));
+            }
+
+            self.count_local(&reply_ip_repr);
+            (ip_repr, ip_payload) = self.filter_local(reply_ip_repr, reply_ip_payload)?;
+        }
+    }
+
+    /// Passes a packet sent to a local address to the packet filter, if any.
+    ///
+    /// Returns the IP header and the IP payload of the (possibly modified) packet, or `None` if
+    /// the packet is dropped.
+    fn filter_local(&self, ip_repr: IpRepr, ip_payload: Vec<u8>) -> Option<(IpRepr, Vec<u8>)> {
+        let Some(filter) = self.filter else {
+            return Some((ip_repr, ip_payload));
+        };
+
+        filter.filter(FilterHook::LOCAL_PATH, &ip_repr, |payload| {
+            payload.copy_from_slice(&ip_payload)
+        })
+    }
+
     fn generate_icmp_unreachable<'pkt>(
         &self,
         ip_repr: &IpRepr,
@@ -330,11 +709,8 @@ impl<E: Ext> PollContext<'_, E> {
 
         if self.is_unicast_local(ip_repr.src_addr()) {
             // In this case, the generating ICMP message will have a local IP address as the
-            // destination. However, since we don't have the ability to handle ICMP messages, we'll
-            // just skip the generation.
-            //
-            // TODO: Generate the ICMP message here once we're able to handle incoming ICMP
-            // messages.
+            // destination. The errors for UDP sockets have been reported by
+            // `report_local_port_unreachable`, so we'll just skip the generation.
             return None;
         }
 
@@ -365,23 +741,38 @@ impl<E: Ext> PollContext<'_, E> {
         ))
     }
 
+    /// Returns whether a multicast packet sent via the iface should be looped back and processed
+    /// locally.
+    ///
+    /// The packet is looped back if the iface has joined the multicast group, unless the device
+    /// already loops it back.
+    fn should_loop_back_multicast(&self, dst_addr: IpAddress) -> bool {
+        dst_addr.is_multicast()
+            && !self.multicast.is_looped_back_by_device()
+            && self.multicast.contains(&dst_addr)
+    }
+
+    /// Updates the statistics for a packet sent to a local address.
+    ///
+    /// The packet does not go through the device, so it is counted as both transmitted and
+    /// received here.
+    fn count_local(&self, ip_repr: &IpRepr) {
+        let len = ip_repr.buffer_len();
+
+        self.stats.on_ip_send(ip_repr);
+        self.stats.on_link_send(len);
+        self.stats.on_link_recv(len);
+        self.stats.on_ip_recv(ip_repr.version());
+    }
+
     /// Returns whether the destination address is the unicast address of a local interface.
     ///
     /// Note: "local" means that the IP address belongs to the local interface, not to be confused
     /// with the localhost IP (127.0.0.1).
     fn is_unicast_local(&self, dst_addr: IpAddress) -> bool {
-        match dst_addr {
-            IpAddress::Ipv4(dst_addr) => self
-                .iface
-                .context()
-                .ipv4_addr()
-                .is_some_and(|addr| addr == dst_addr),
-            IpAddress::Ipv6(dst_addr) => self
-                .iface
-                .context()
-                .ipv6_addr()
-                .is_some_and(|addr| addr == dst_addr),
-        }
+        self.ip_addrs
+            .iter()
+            .any(|ip_cidr| ip_cidr.address() == dst_addr)
     }
 }
 
@@ -389,7 +780,7 @@ impl<E: Ext> PollContext<'_, E> {
     pub(super) fn poll_egress<D, Q>(&mut self, device: &mut D, dispatch_phy: &mut Q)
     where
         D: Device + ?Sized,
-        Q: FnMut(&Packet, &mut Context, D::TxToken<'_>),
+        Q: FnMut(&Packet, Option<u16>, &mut Context, D::TxToken<'_>),
     {
         while let Some(tx_token) = device.transmit(self.iface.context().now()) {
             if !self.dispatch_ip(tx_token, dispatch_phy) {
@@ -401,7 +792,7 @@ impl<E: Ext> PollContext<'_, E> {
     fn dispatch_ip<T, Q>(&mut self, tx_token: T, dispatch_phy: &mut Q) -> bool
     where
         T: TxToken,
-        Q: FnMut(&Packet, &mut Context, T),
+        Q: FnMut(&Packet, Option<u16>, &mut Context, T),
     {
         let (did_something_tcp, tx_token) = self.dispatch_tcp(tx_token, dispatch_phy);
 
@@ -409,15 +800,21 @@ impl<E: Ext> PollContext<'_, E> {
             return did_something_tcp;
         };
 
-        let (did_something_udp, _tx_token) = self.dispatch_udp(tx_token, dispatch_phy);
+        let (did_something_udp, tx_token) = self.dispatch_udp(tx_token, dispatch_phy);
+
+        let Some(tx_token) = tx_token else {
+            return did_something_tcp || did_something_udp;
+        };
+
+        let (did_something_raw, _tx_token) = self.dispatch_raw(tx_token, dispatch_phy);
 
-        did_something_tcp || did_something_udp
+        did_something_tcp || did_something_udp || did_something_raw
     }
 
     fn dispatch_tcp<T, Q>(&mut self, tx_token: T, dispatch_phy: &mut Q) -> (bool, Option<T>)
     where
         T: TxToken,
-        Q: FnMut(&Packet, &mut Context, T),
+        Q: FnMut(&Packet, Option<u16>, &mut Context, T),
     {
         let mut tx_token = Some(tx_token);
         let mut did_something = false;
@@ -432,22 +829,51 @@ impl<E: Ext> PollContext<'_, E> {
             did_something = true;
 
             let mut deferred = None;
+            let mut tso_batch: Option<TsoBatch> = None;
 
             let (reply, became_dead) =
                 TcpConnectionBg::dispatch(&socket, &mut self.iface, |iface, ip_repr, tcp_repr| {
-                    let mut this = PollContext::new(iface, self.sockets, self.actions);
+                    let mut this = PollContext::new(
+                        iface,
+                        self.ip_addrs,
+                        self.multicast,
+                        self.sockets,
+                        self.filter,
+                        self.stats,
+                        self.max_tso_len,
+                        self.actions,
+                    );
 
                     if !this.is_unicast_local(ip_repr.dst_addr()) {
+                        // Consecutive segments are coalesced if the device can split them again.
+                        if let Some(batch) = tso_batch.as_mut() {
+                            return if batch.append(ip_repr, tcp_repr) {
+                                TcpDispatchResult::Held
+                            } else {
+                                TcpDispatchResult::Refused
+                            };
+                        }
+                        if let Some(max_tso_len) = this.max_tso_len {
+                            tso_batch = TsoBatch::new(ip_repr, tcp_repr, max_tso_len);
+                            if tso_batch.is_some() {
+                                return TcpDispatchResult::Held;
+                            }
+                        }
+
                         dispatch_phy(
                             &Packet::new(ip_repr.clone(), IpPayload::Tcp(*tcp_repr)),
+                            None,
                             this.iface.context_mut(),
                             tx_token.take().unwrap(),
                         );
-                        return None;
+                        return TcpDispatchResult::Dispatched(None);
                     }
+                    this.count_local(ip_repr);
 
-                    if !socket.can_process(tcp_repr.dst_port) {
-                        return this.process_tcp(ip_repr, tcp_repr);
+                    // If there is a packet filter, the packet is always copied so that it can be
+                    // passed to the filter.
+                    if !socket.can_process(tcp_repr.dst_port) && this.filter.is_none() {
+                        return TcpDispatchResult::Dispatched(this.process_tcp(ip_repr, tcp_repr));
                     }
 
                     // We cannot call `process_tcp` now because it may cause deadlocks. We will copy
@@ -463,9 +889,22 @@ impl<E: Ext> PollContext<'_, E> {
                         data
                     }));
 
-                    None
+                    TcpDispatchResult::Dispatched(None)
                 });
 
+            if let Some(batch) = tso_batch {
+                debug_assert!(deferred.is_none() && reply.is_none());
+                self.stats.on_tcp_send_coalesced(batch.num_segments() - 1);
+                batch.with_packet(|pkt| {
+                    dispatch_phy(
+                        pkt,
+                        batch.segment_size(),
+                        self.iface.context_mut(),
+                        tx_token.take().unwrap(),
+                    )
+                });
+            }
+
             if *became_dead {
                 self.actions
                     .push(SocketTableAction::DelTcpConn(*socket.connection_key()));
@@ -474,27 +913,28 @@ impl<E: Ext> PollContext<'_, E> {
             match (deferred, reply) {
                 (None, None) => (),
                 (Some((ip_repr, ip_payload)), None) => {
-                    if let Some(reply) = self.parse_and_process_tcp(
-                        &ip_repr,
-                        &ip_payload,
-                        &ChecksumCapabilities::ignored(),
-                    ) {
-                        dispatch_phy(&reply, self.iface.context_mut(), tx_token.take().unwrap());
+                    if let Some((ip_repr, ip_payload)) = self.filter_local(ip_repr, ip_payload)
+                        && let Some(reply) = self.parse_and_process_tcp(
+                            &ip_repr,
+                            &ip_payload,
+                            &ChecksumCapabilities::ignored(),
+                        )
+                    {
+                        dispatch_phy(
+                            &reply,
+                            None,
+                            self.iface.context_mut(),
+                            tx_token.take().unwrap(),
+                        );
                     }
                 }
-                (None, Some((ip_repr, tcp_repr))) if !self.is_unicast_local(ip_repr.dst_addr()) => {
-                    dispatch_phy(
-                        &Packet::new(ip_repr, IpPayload::Tcp(tcp_repr)),
-                        self.iface.context_mut(),
-                        tx_token.take().unwrap(),
-                    );
-                }
                 (None, Some((ip_repr, tcp_repr))) => {
                     if let Some((new_ip_repr, new_tcp_repr)) =
-                        self.process_tcp_until_outgoing(&ip_repr, &tcp_repr)
+                        self.process_tcp_until_outgoing(ip_repr, tcp_repr)
                     {
                         dispatch_phy(
                             &Packet::new(new_ip_repr, IpPayload::Tcp(new_tcp_repr)),
+                            None,
                             self.iface.context_mut(),
                             tx_token.take().unwrap(),
                         );
@@ -514,7 +954,7 @@ impl<E: Ext> PollContext<'_, E> {
     fn dispatch_udp<T, Q>(&mut self, tx_token: T, dispatch_phy: &mut Q) -> (bool, Option<T>)
     where
         T: TxToken,
-        Q: FnMut(&Packet, &mut Context, T),
+        Q: FnMut(&Packet, Option<u16>, &mut Context, T),
     {
         let mut tx_token = Some(tx_token);
         let mut did_something = false;
@@ -535,23 +975,44 @@ impl<E: Ext> PollContext<'_, E> {
             let (cx, pending) = self.iface.inner_mut();
             socket.dispatch(cx, |cx, ip_repr, udp_repr, udp_payload| {
                 let iface = PollableIfaceMut::new(cx, pending);
-                let mut this = PollContext::new(iface, self.sockets, &mut actions);
-
-                if ip_repr.dst_addr().is_broadcast() || !this.is_unicast_local(ip_repr.dst_addr()) {
+                let mut this = PollContext::new(
+                    iface,
+                    self.ip_addrs,
+                    self.multicast,
+                    self.sockets,
+                    self.filter,
+                    self.stats,
+                    self.max_tso_len,
+                    &mut actions,
+                );
+
+                let dst_addr = ip_repr.dst_addr();
+                if dst_addr.is_broadcast() || !this.is_unicast_local(dst_addr) {
                     dispatch_phy(
                         &Packet::new(ip_repr.clone(), IpPayload::Udp(*udp_repr, udp_payload)),
+                        None,
                         this.iface.context_mut(),
                         tx_token.take().unwrap(),
                     );
-                    if !ip_repr.dst_addr().is_broadcast() {
+                    if !dst_addr.is_broadcast()
+                        && !(socket.is_multicast_loop_enabled()
+                            && this.should_loop_back_multicast(dst_addr))
+                    {
                         return;
                     }
+                    // The looped-back copy has been counted as sent.
+                    this.stats.on_ip_recv(ip_repr.version());
+                } else {
+                    this.count_local(ip_repr);
                 }
 
-                if !socket.can_process(udp_repr.dst_port) {
-                    // TODO: Generate the ICMP message here once we're able to handle incoming ICMP
-                    // messages.
-                    let _ = this.process_udp(ip_repr, udp_repr, udp_payload);
+                // The traffic class of locally generated packets is always zero. If there is a
+                // packet filter, the packet is always copied so that it can be passed to the
+                // filter.
+                if !socket.can_process(udp_repr.dst_port) && this.filter.is_none() {
+                    if !this.process_udp(ip_repr, 0, udp_repr, udp_payload) {
+                        this.report_local_port_unreachable(ip_repr, udp_repr, udp_payload);
+                    }
                     return;
                 }
 
@@ -572,13 +1033,20 @@ impl<E: Ext> PollContext<'_, E> {
             });
 
             if let Some((ip_repr, ip_payload)) = deferred
+                && let Some((ip_repr, ip_payload)) = self.filter_local(ip_repr, ip_payload)
                 && let Some(reply) = self.parse_and_process_udp(
                     &ip_repr,
+                    0,
                     &ip_payload,
                     &ChecksumCapabilities::ignored(),
                 )
             {
-                dispatch_phy(&reply, self.iface.context_mut(), tx_token.take().unwrap());
+                dispatch_phy(
+                    &reply,
+                    None,
+                    self.iface.context_mut(),
+                    tx_token.take().unwrap(),
+                );
             }
 
             if tx_token.is_none() {
@@ -593,4 +1061,110 @@ impl<E: Ext> PollContext<'_, E> {
 
         (did_something, tx_token)
     }
+    fn dispatch_raw<T, Q>(&mut self, tx_token: T, dispatch_phy: &mut Q) -> (bool, Option<T>)
+    where
+        T: TxToken,
+        Q: FnMut(&Packet, Option<u16>, &mut Context, T),
+    {
+        let mut tx_token = Some(tx_token);
+        let mut did_something = false;
+
+        let mut actions = Vec::new();
+
+        for socket in self.sockets.raw_socket_iter() {
+            if !socket.need_dispatch() {
+                continue;
+            }
+
+            did_something = true;
+
+            let mut deferred = None;
+
+            let (cx, pending) = self.iface.inner_mut();
+            socket.dispatch(cx, |cx, ip_repr, ip_payload| {
+                let iface = PollableIfaceMut::new(cx, pending);
+                let mut this = PollContext::new(
+                    iface,
+                    self.ip_addrs,
+                    self.multicast,
+                    self.sockets,
+                    self.filter,
+                    self.stats,
+                    self.max_tso_len,
+                    &mut actions,
+                );
+
+                let dst_addr = ip_repr.dst_addr();
+                if dst_addr.is_broadcast() || !this.is_unicast_local(dst_addr) {
+                    dispatch_phy(
+                        &Packet::new(ip_repr.clone(), IpPayload::Raw(ip_payload)),
+                        None,
+                        this.iface.context_mut(),
+                        tx_token.take().unwrap(),
+                    );
+                    if !dst_addr.is_broadcast() && !this.should_loop_back_multicast(dst_addr) {
+                        return;
+                    }
+                    // The looped-back copy has been counted as sent.
+                    this.stats.on_ip_recv(ip_repr.version());
+                } else {
+                    this.count_local(ip_repr);
+                }
+
+                // We cannot process the packet now because it may cause deadlocks (the packet
+                // may be received by the socket itself). We will copy the packet and process it
+                // after releasing the socket lock.
+                deferred = Some((ip_repr.clone(), ip_payload.to_vec()));
+            });
+
+            if let Some((ip_repr, ip_payload)) = deferred
+                && let Some((reply_ip_repr, reply_ip_payload)) =
+                    self.process_ip_until_outgoing(ip_repr, ip_payload)
+                && let Some(tx_token) = tx_token.take()
+            {
+                dispatch_phy(
+                    &Packet::new(reply_ip_repr, IpPayload::Raw(&reply_ip_payload)),
+                    None,
+                    self.iface.context_mut(),
+                    tx_token,
+                );
+            }
+
+            if tx_token.is_none() {
+                break;
+            }
+        }
+
+        // `actions` should be empty, because the packets are not processed in the closure above.
+        debug_assert!(actions.is_empty());
+
+        (did_something, tx_token)
+    }
+
+    /// Sends membership reports of multicast groups.
+    ///
+    /// Reports that cannot be sent due to the lack of transmit buffers are dropped.
+    pub(super) fn dispatch_reports<D, Q>(
+        &mut self,
+        reports: Vec<Report>,
+        device: &mut D,
+        dispatch_phy: &mut Q,
+    ) where
+        D: Device + ?Sized,
+        Q: FnMut(&Packet, Option<u16>, &mut Context, D::TxToken<'_>),
+    {
+        for report in reports {
+            let Some(tx_token) = device.transmit(self.iface.context().now()) else {
+                break;
+            };
+
+            let (ip_repr, ip_payload) = report.build(self.ip_addrs);
+            dispatch_phy(
+                &Packet::new(ip_repr, IpPayload::Raw(&ip_payload)),
+                None,
+                self.iface.context_mut(),
+                tx_token,
+            );
+        }
+    }
 }
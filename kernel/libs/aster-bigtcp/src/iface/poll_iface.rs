@@ -6,7 +6,13 @@ use core::{
     sync::atomic::{AtomicU64, Ordering},
 };
 
+use smoltcp::{
+    iface::Route,
+    wire::{IpAddress, IpCidr},
+};
+
 use crate::{
+    errors::iface::AddrError,
     ext::Ext,
     socket::{NeedIfacePoll, TcpConnectionBg},
 };
@@ -35,25 +41,53 @@ impl<E: Ext> PollableIface<E> {
         }
     }
 
-    pub(super) fn ipv4_addr(&self) -> Option<smoltcp::wire::Ipv4Address> {
-        self.interface.ipv4_addr()
+    pub(super) fn ip_addrs(&self) -> &[IpCidr] {
+        self.interface.ip_addrs()
     }
 
-    pub(super) fn ipv6_addr(&self) -> Option<smoltcp::wire::Ipv6Address> {
-        self.interface.ip_addrs().iter().find_map(|cidr| {
-            if let smoltcp::wire::IpCidr::Ipv6(ipv6_cidr) = cidr {
-                Some(ipv6_cidr.address())
-            } else {
-                None
+    pub(super) fn add_ip_addr(&mut self, ip_cidr: IpCidr) -> Result<(), AddrError> {
+        let mut result = Ok(());
+        self.interface.update_ip_addrs(|ip_addrs| {
+            if ip_addrs.push(ip_cidr).is_err() {
+                result = Err(AddrError::TooMany);
             }
-        })
+        });
+        result
     }
 
-    pub(super) fn prefix_len(&self) -> Option<u8> {
-        self.interface
-            .ip_addrs()
-            .first()
-            .map(|ip_addr| ip_addr.prefix_len())
+    pub(super) fn remove_ip_addr(&mut self, ip_cidr: &IpCidr) -> Result<(), AddrError> {
+        let mut result = Err(AddrError::NotFound);
+        self.interface.update_ip_addrs(|ip_addrs| {
+            if let Some(pos) = ip_addrs.iter().position(|other| other == ip_cidr) {
+                ip_addrs.remove(pos);
+                result = Ok(());
+            }
+        });
+        result
+    }
+
+    /// Replaces the routes that go through gateways.
+    ///
+    /// Each route is described by its destination and the address of its gateway. Routes to the
+    /// subnets of the iface's addresses do not need to be added here.
+    //
+    // TODO: Support link-scope routes to destinations outside the subnets of the iface's
+    // addresses. `smoltcp` does not support them for Ethernet devices.
+    pub(super) fn set_gateway_routes(&mut self, routes: &[(IpCidr, IpAddress)]) {
+        self.interface.routes_mut().update(|storage| {
+            storage.clear();
+            for (cidr, via_router) in routes {
+                let route = Route {
+                    cidr: *cidr,
+                    via_router: *via_router,
+                    preferred_until: None,
+                    expires_at: None,
+                };
+                if storage.push(route).is_err() {
+                    break;
+                }
+            }
+        });
     }
 
     /// Returns the next poll time.
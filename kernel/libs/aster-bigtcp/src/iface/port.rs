@@ -34,6 +34,16 @@ impl BindPortConfig {
         }
     }
 
+    /// Creates a new configuration for a raw IP socket.
+    ///
+    /// The protocol number is used as the port, which can always be reused.
+    pub(super) fn new_raw(addr: IpAddress, protocol: u8) -> Self {
+        Self {
+            addr,
+            kind: PortKind::CanReuse(protocol as u16),
+        }
+    }
+
     /// Creates a new configuration for reusing the port of a listening socket.
     pub fn new_backlog(endpoint: IpEndpoint) -> Self {
         Self {
@@ -0,0 +1,212 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use smoltcp::wire::{IpProtocol, IpRepr, IpVersion};
+
+/// The statistics of the link-layer frames received or transmitted by an iface.
+///
+/// The statistics follow the definitions of the corresponding fields in Linux's
+/// `rtnl_link_stats64`. Packets sent to local addresses do not go through the device, but they
+/// are still counted as transmitted and received by the iface, like the loopback device in Linux.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LinkStats {
+    /// The total number of frames received.
+    pub rx_packets: u64,
+    /// The total number of bytes received.
+    pub rx_bytes: u64,
+    /// The total number of frames received but dropped because they cannot be handled.
+    pub rx_dropped: u64,
+    /// The total number of multicast (including broadcast) frames received.
+    pub rx_multicast: u64,
+    /// The total number of frames transmitted.
+    pub tx_packets: u64,
+    /// The total number of bytes transmitted.
+    pub tx_bytes: u64,
+    /// The total number of outgoing packets dropped before they are transmitted.
+    pub tx_dropped: u64,
+}
+
+/// The statistics of the IP, TCP, and UDP packets processed by an iface.
+///
+/// The statistics follow the definitions of the corresponding counters in Linux's
+/// `/proc/net/snmp`. Like Linux, the IP and UDP counters only cover IPv4 packets, while the TCP
+/// counters cover both IPv4 and IPv6 packets.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ProtocolStats {
+    /// The total number of IP packets received.
+    pub ip_in_receives: u64,
+    /// The total number of IP packets delivered to the upper-layer protocols.
+    pub ip_in_delivers: u64,
+    /// The total number of IP packets sent.
+    pub ip_out_requests: u64,
+    /// The total number of TCP segments received.
+    pub tcp_in_segs: u64,
+    /// The total number of TCP segments sent.
+    pub tcp_out_segs: u64,
+    /// The total number of UDP datagrams delivered to sockets.
+    pub udp_in_datagrams: u64,
+    /// The total number of unicast UDP datagrams received for ports without sockets.
+    pub udp_no_ports: u64,
+    /// The total number of UDP datagrams sent.
+    pub udp_out_datagrams: u64,
+}
+
+/// A collector of [`LinkStats`] and [`ProtocolStats`].
+///
+/// The counters are updated without locks, so that they can be updated from all the paths that
+/// packets go through.
+#[derive(Default)]
+pub(super) struct IfaceStats {
+    rx_packets: Counter,
+    rx_bytes: Counter,
+    rx_dropped: Counter,
+    rx_multicast: Counter,
+    tx_packets: Counter,
+    tx_bytes: Counter,
+    tx_dropped: Counter,
+
+    ip_in_receives: Counter,
+    ip_in_delivers: Counter,
+    ip_out_requests: Counter,
+    tcp_in_segs: Counter,
+    tcp_out_segs: Counter,
+    udp_in_datagrams: Counter,
+    udp_no_ports: Counter,
+    udp_out_datagrams: Counter,
+}
+
+impl IfaceStats {
+    pub(super) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the link-layer statistics.
+    pub(super) fn link_stats(&self) -> LinkStats {
+        LinkStats {
+            rx_packets: self.rx_packets.get(),
+            rx_bytes: self.rx_bytes.get(),
+            rx_dropped: self.rx_dropped.get(),
+            rx_multicast: self.rx_multicast.get(),
+            tx_packets: self.tx_packets.get(),
+            tx_bytes: self.tx_bytes.get(),
+            tx_dropped: self.tx_dropped.get(),
+        }
+    }
+
+    /// Returns the protocol statistics.
+    pub(super) fn protocol_stats(&self) -> ProtocolStats {
+        ProtocolStats {
+            ip_in_receives: self.ip_in_receives.get(),
+            ip_in_delivers: self.ip_in_delivers.get(),
+            ip_out_requests: self.ip_out_requests.get(),
+            tcp_in_segs: self.tcp_in_segs.get(),
+            tcp_out_segs: self.tcp_out_segs.get(),
+            udp_in_datagrams: self.udp_in_datagrams.get(),
+            udp_no_ports: self.udp_no_ports.get(),
+            udp_out_datagrams: self.udp_out_datagrams.get(),
+        }
+    }
+
+    /// Updates the statistics after a frame of `len` bytes is received.
+    pub(super) fn on_link_recv(&self, len: usize) {
+        self.rx_packets.inc();
+        self.rx_bytes.add(len);
+    }
+
+    /// Updates the statistics after a received frame is found to be multicast or broadcast.
+    pub(super) fn on_link_recv_multicast(&self) {
+        self.rx_multicast.inc();
+    }
+
+    /// Updates the statistics after a received frame is dropped.
+    pub(super) fn on_link_recv_dropped(&self) {
+        self.rx_dropped.inc();
+    }
+
+    /// Updates the statistics after a frame of `len` bytes is transmitted.
+    pub(super) fn on_link_send(&self, len: usize) {
+        self.tx_packets.inc();
+        self.tx_bytes.add(len);
+    }
+
+    /// Updates the statistics after an outgoing packet is dropped.
+    pub(super) fn on_link_send_dropped(&self) {
+        self.tx_dropped.inc();
+    }
+
+    /// Updates the statistics after an IP packet is received.
+    pub(super) fn on_ip_recv(&self, version: IpVersion) {
+        if version == IpVersion::Ipv4 {
+            self.ip_in_receives.inc();
+        }
+    }
+
+    /// Updates the statistics after a received IP packet is delivered to an upper-layer protocol.
+    pub(super) fn on_ip_deliver(&self, version: IpVersion) {
+        if version == IpVersion::Ipv4 {
+            self.ip_in_delivers.inc();
+        }
+    }
+
+    /// Updates the statistics after an IP packet is sent.
+    pub(super) fn on_ip_send(&self, ip_repr: &IpRepr) {
+        let is_ipv4 = ip_repr.version() == IpVersion::Ipv4;
+        if is_ipv4 {
+            self.ip_out_requests.inc();
+        }
+
+        match ip_repr.next_header() {
+            IpProtocol::Tcp => self.tcp_out_segs.inc(),
+            IpProtocol::Udp if is_ipv4 => self.udp_out_datagrams.inc(),
+            _ => (),
+        }
+    }
+
+    /// Updates the statistics after `num_segs` TCP segments are coalesced into a sent packet,
+    /// which the device will split into segments again.
+    ///
+    /// The sent packet itself has been counted as one segment by [`Self::on_ip_send`].
+    pub(super) fn on_tcp_send_coalesced(&self, num_segs: usize) {
+        self.tcp_out_segs.add(num_segs);
+    }
+
+    /// Updates the statistics after a TCP segment is delivered to the TCP layer.
+    pub(super) fn on_tcp_recv(&self, ip_repr: &IpRepr) {
+        self.on_ip_deliver(ip_repr.version());
+        self.tcp_in_segs.inc();
+    }
+
+    /// Updates the statistics after a UDP datagram is delivered to the UDP layer.
+    ///
+    /// `has_socket` indicates whether the datagram is received by any socket.
+    pub(super) fn on_udp_recv(&self, ip_repr: &IpRepr, has_socket: bool) {
+        if ip_repr.version() != IpVersion::Ipv4 {
+            return;
+        }
+
+        self.ip_in_delivers.inc();
+        if has_socket {
+            self.udp_in_datagrams.inc();
+        } else if ip_repr.dst_addr().is_unicast() {
+            self.udp_no_ports.inc();
+        }
+    }
+}
+
+#[derive(Default)]
+struct Counter(AtomicU64);
+
+impl Counter {
+    fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    fn inc(&self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn add(&self, n: usize) {
+        self.0.fetch_add(n as u64, Ordering::Relaxed);
+    }
+}
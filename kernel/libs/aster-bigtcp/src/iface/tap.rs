@@ -0,0 +1,135 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::{collections::vec_deque::VecDeque, sync::Arc, vec::Vec};
+
+/// The type of a link-layer frame, as seen by an iface.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/if_packet.h#L26>.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FrameType {
+    /// The frame is sent to us.
+    Host = 0,
+    /// The frame is sent to the broadcast address.
+    Broadcast = 1,
+    /// The frame is sent to a multicast address.
+    Multicast = 2,
+    /// The frame is sent to another host.
+    ///
+    /// Such frames are only tapped if the iface is in the promiscuous mode.
+    OtherHost = 3,
+    /// The frame is sent by us.
+    Outgoing = 4,
+}
+
+/// An observer of the link-layer frames that are received or transmitted by an iface.
+///
+/// This can be used to implement packet sockets (i.e., `AF_PACKET`).
+pub trait FrameTap: Send + Sync {
+    /// Called when a frame is received or transmitted by the iface.
+    ///
+    /// The frame starts with its link-layer header. The method is called with some locks of the
+    /// iface held, so it must not poll the iface or send frames.
+    fn on_frame(&self, frame: &[u8], iface_index: u32, frame_type: FrameType);
+}
+
+pub(super) struct TapTable {
+    taps: Vec<Arc<dyn FrameTap>>,
+    /// The number of requests to put the iface in the promiscuous mode.
+    promiscuity: usize,
+}
+
+impl TapTable {
+    pub(super) const fn new() -> Self {
+        Self {
+            taps: Vec::new(),
+            promiscuity: 0,
+        }
+    }
+
+    pub(super) fn register(&mut self, tap: Arc<dyn FrameTap>) {
+        self.taps.push(tap);
+    }
+
+    pub(super) fn unregister(&mut self, tap: &Arc<dyn FrameTap>) {
+        if let Some(pos) = self.taps.iter().position(|t| Arc::ptr_eq(t, tap)) {
+            self.taps.swap_remove(pos);
+        }
+    }
+
+    /// Increases the promiscuity and returns whether the iface enters the promiscuous mode.
+    pub(super) fn inc_promiscuity(&mut self) -> bool {
+        self.promiscuity += 1;
+        self.promiscuity == 1
+    }
+
+    /// Decreases the promiscuity and returns whether the iface leaves the promiscuous mode.
+    pub(super) fn dec_promiscuity(&mut self) -> bool {
+        debug_assert!(self.promiscuity > 0);
+        self.promiscuity -= 1;
+        self.promiscuity == 0
+    }
+
+    pub(super) fn is_promiscuous(&self) -> bool {
+        self.promiscuity > 0
+    }
+
+    /// Delivers a frame to all the taps except `sender`.
+    pub(super) fn deliver(
+        &self,
+        frame: &[u8],
+        iface_index: u32,
+        frame_type: FrameType,
+        sender: Option<&Arc<dyn FrameTap>>,
+    ) {
+        // Without the promiscuous mode, such frames should have been filtered out by the
+        // hardware.
+        if frame_type == FrameType::OtherHost && self.promiscuity == 0 {
+            return;
+        }
+
+        for tap in self.taps.iter() {
+            if sender.is_some_and(|sender| Arc::ptr_eq(tap, sender)) {
+                continue;
+            }
+            tap.on_frame(frame, iface_index, frame_type);
+        }
+    }
+}
+
+/// Frames that are sent by the users of the iface and are waiting to be transmitted.
+pub(super) struct PendingFrames {
+    frames: VecDeque<Vec<u8>>,
+}
+
+/// The maximum number of pending frames.
+///
+/// This resembles the default transmit queue length in Linux.
+const MAX_PENDING_FRAMES: usize = 1000;
+
+impl PendingFrames {
+    pub(super) const fn new() -> Self {
+        Self {
+            frames: VecDeque::new(),
+        }
+    }
+
+    /// Pushes a frame to the queue. Returns `false` if the queue is full.
+    #[must_use]
+    pub(super) fn push(&mut self, frame: Vec<u8>) -> bool {
+        if self.frames.len() >= MAX_PENDING_FRAMES {
+            return false;
+        }
+
+        self.frames.push_back(frame);
+        true
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub(super) fn pop(&mut self) -> Option<Vec<u8>> {
+        self.frames.pop_front()
+    }
+}
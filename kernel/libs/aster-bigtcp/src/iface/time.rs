@@ -2,7 +2,7 @@
 
 use ostd::timer::Jiffies;
 
-pub(super) fn get_network_timestamp() -> smoltcp::time::Instant {
+pub(crate) fn get_network_timestamp() -> smoltcp::time::Instant {
     let millis = Jiffies::elapsed().as_duration().as_millis();
     smoltcp::time::Instant::from_millis(millis as i64)
 }
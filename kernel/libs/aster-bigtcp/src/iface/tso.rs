@@ -0,0 +1,162 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::{vec, vec::Vec};
+use core::ops::Range;
+
+use smoltcp::{
+    iface::packet::{IpPayload, Packet},
+    phy::ChecksumCapabilities,
+    wire::{IpRepr, TcpControl, TcpPacket, TcpRepr},
+};
+
+/// A batch of consecutive TCP segments that will be coalesced into one packet.
+///
+/// The device splits the coalesced packet into segments again, replicating its headers in each
+/// segment (i.e., TCP segmentation offload, or TSO). So the headers of the segments must be the
+/// same except for the sequence numbers and the PSH flags. All segments except the last one must
+/// have the same length, which is the segment size that the device uses to split the packet.
+pub(super) struct TsoBatch {
+    ip_repr: IpRepr,
+    /// The TCP header of the first segment followed by the payloads of all segments.
+    tcp_packet: Vec<u8>,
+    tcp_header_len: usize,
+    segment_size: usize,
+    num_segments: usize,
+    /// The maximum length of the coalesced IP packet.
+    max_len: usize,
+}
+
+impl TsoBatch {
+    /// Starts a batch with the first segment.
+    ///
+    /// Returns `None` if the segment cannot be coalesced with other segments.
+    pub(super) fn new(ip_repr: &IpRepr, tcp_repr: &TcpRepr, max_len: usize) -> Option<Self> {
+        if !is_data_segment(tcp_repr) || ip_repr.buffer_len() >= max_len {
+            return None;
+        }
+
+        let mut tcp_packet = vec![0; tcp_repr.buffer_len()];
+        emit_tcp(ip_repr, tcp_repr, &mut tcp_packet);
+
+        Some(Self {
+            ip_repr: ip_repr.clone(),
+            tcp_packet,
+            tcp_header_len: tcp_repr.header_len(),
+            segment_size: tcp_repr.payload.len(),
+            num_segments: 1,
+            max_len,
+        })
+    }
+
+    /// Appends the segment to the batch.
+    ///
+    /// Returns `false` if the segment cannot be coalesced with the segments in the batch.
+    pub(super) fn append(&mut self, ip_repr: &IpRepr, tcp_repr: &TcpRepr) -> bool {
+        let payload_len = self.tcp_packet.len() - self.tcp_header_len;
+
+        // Only the last segment can be shorter than the segment size.
+        if !is_data_segment(tcp_repr)
+            || payload_len != self.segment_size * self.num_segments
+            || tcp_repr.payload.len() > self.segment_size
+            || self.ip_repr.header_len() + self.tcp_packet.len() + tcp_repr.payload.len()
+                > self.max_len
+        {
+            return false;
+        }
+
+        let mut first_ip_repr = self.ip_repr.clone();
+        first_ip_repr.set_payload_len(ip_repr.payload_len());
+        if *ip_repr != first_ip_repr {
+            return false;
+        }
+
+        let first_tcp_packet = TcpPacket::new_unchecked(self.tcp_packet.as_slice());
+        if tcp_repr.seq_number != first_tcp_packet.seq_number() + payload_len
+            || tcp_repr.header_len() != self.tcp_header_len
+        {
+            return false;
+        }
+
+        // Compare the headers except for the sequence numbers and the PSH flags. The checksums
+        // are not computed, so they are always zero.
+        let mut tcp_header = [0; TCP_MAX_HEADER_LEN];
+        let tcp_header = &mut tcp_header[..self.tcp_header_len];
+        emit_tcp(
+            ip_repr,
+            &TcpRepr {
+                payload: &[],
+                ..*tcp_repr
+            },
+            tcp_header,
+        );
+        let first_tcp_header = &self.tcp_packet[..self.tcp_header_len];
+        if tcp_header[..SEQ_NUM_RANGE.start] != first_tcp_header[..SEQ_NUM_RANGE.start]
+            || tcp_header[SEQ_NUM_RANGE.end..FLAGS_OFFSET]
+                != first_tcp_header[SEQ_NUM_RANGE.end..FLAGS_OFFSET]
+            || tcp_header[FLAGS_OFFSET] | FLAG_PSH != first_tcp_header[FLAGS_OFFSET] | FLAG_PSH
+            || tcp_header[FLAGS_OFFSET + 1..] != first_tcp_header[FLAGS_OFFSET + 1..]
+        {
+            return false;
+        }
+
+        self.tcp_packet.extend_from_slice(tcp_repr.payload);
+        if tcp_repr.control == TcpControl::Psh {
+            self.tcp_packet[FLAGS_OFFSET] |= FLAG_PSH;
+        }
+        self.num_segments += 1;
+
+        true
+    }
+
+    /// Returns the number of segments in the batch.
+    pub(super) fn num_segments(&self) -> usize {
+        self.num_segments
+    }
+
+    /// Returns the segment size, if the device should split the coalesced packet.
+    pub(super) fn segment_size(&self) -> Option<u16> {
+        if self.num_segments > 1 {
+            Some(self.segment_size as u16)
+        } else {
+            None
+        }
+    }
+
+    /// Calls the closure with the coalesced packet.
+    pub(super) fn with_packet<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&Packet) -> R,
+    {
+        let mut ip_repr = self.ip_repr.clone();
+        ip_repr.set_payload_len(self.tcp_packet.len());
+
+        let tcp_repr = TcpRepr::parse(
+            &TcpPacket::new_unchecked(self.tcp_packet.as_slice()),
+            &ip_repr.src_addr(),
+            &ip_repr.dst_addr(),
+            &ChecksumCapabilities::ignored(),
+        )
+        .unwrap();
+
+        f(&Packet::new(ip_repr, IpPayload::Tcp(tcp_repr)))
+    }
+}
+
+/// Returns whether the segment carries only data, so it can be coalesced with other segments.
+fn is_data_segment(tcp_repr: &TcpRepr) -> bool {
+    !tcp_repr.payload.is_empty() && matches!(tcp_repr.control, TcpControl::None | TcpControl::Psh)
+}
+
+fn emit_tcp(ip_repr: &IpRepr, tcp_repr: &TcpRepr, buffer: &mut [u8]) {
+    tcp_repr.emit(
+        &mut TcpPacket::new_unchecked(buffer),
+        &ip_repr.src_addr(),
+        &ip_repr.dst_addr(),
+        &ChecksumCapabilities::ignored(),
+    );
+}
+
+const TCP_MAX_HEADER_LEN: usize = 60;
+const SEQ_NUM_RANGE: Range<usize> = 4..8;
+const FLAGS_OFFSET: usize = 13;
+const FLAG_PSH: u8 = 0x08;
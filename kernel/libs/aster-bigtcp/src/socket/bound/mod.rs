@@ -1,14 +1,17 @@
 // SPDX-License-Identifier: MPL-2.0
 
 mod common;
+mod raw;
 mod tcp_conn;
 mod tcp_listen;
 mod udp;
 
 pub use common::NeedIfacePoll;
+pub(crate) use raw::RawIpSocketBg;
+pub use raw::{RawIpMetadata, RawIpSocket};
 pub use tcp_conn::{ConnectState, RawTcpSocketExt, TcpConnection};
-pub(crate) use tcp_conn::{TcpConnectionBg, TcpProcessResult};
+pub(crate) use tcp_conn::{TcpConnectionBg, TcpDispatchResult, TcpProcessResult};
 pub use tcp_listen::TcpListener;
 pub(crate) use tcp_listen::TcpListenerBg;
-pub use udp::UdpSocket;
+pub use udp::{UdpIcmpError, UdpRecvInfo, UdpSocket};
 pub(crate) use udp::UdpSocketBg;
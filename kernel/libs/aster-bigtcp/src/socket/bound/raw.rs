@@ -0,0 +1,368 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use alloc::{boxed::Box, sync::Arc};
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use aster_softirq::BottomHalfDisabled;
+use ostd::sync::SpinLock;
+use smoltcp::{
+    iface::Context,
+    phy::ChecksumCapabilities,
+    wire::{
+        IPV4_HEADER_LEN, IPV6_HEADER_LEN, Icmpv4Message, Icmpv4Packet, Icmpv6Message, Icmpv6Packet,
+        IpAddress, IpProtocol, IpRepr, Ipv4Packet, Ipv4Repr, Ipv6Packet, Ipv6Repr,
+    },
+};
+
+use super::common::{Inner, Socket, SocketBg};
+use crate::{
+    errors::raw::{RecvError, SendError},
+    ext::Ext,
+    iface::BoundRawPort,
+    socket::{
+        event::SocketEvents,
+        unbound::{RawRawSocket, new_raw_socket},
+    },
+};
+
+pub type RawIpSocket<E> = Socket<RawIpSocketInner, E>;
+
+/// States needed by [`RawIpSocketBg`].
+pub struct RawIpSocketInner {
+    socket: SpinLock<Box<RawRawSocket>, BottomHalfDisabled>,
+    ip_protocol: IpProtocol,
+    /// The identifier of ICMP echo messages if this is a ping socket.
+    echo_ident: Option<u16>,
+    need_dispatch: AtomicBool,
+}
+
+/// The protocol number of `IPPROTO_RAW`.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/in.h#L80>.
+const IPPROTO_RAW: u8 = 255;
+
+impl<E: Ext> Inner<E> for RawIpSocketInner {
+    type BoundPort = BoundRawPort<E>;
+    type Observer = E::RawEventObserver;
+
+    fn on_drop(this: &Arc<SocketBg<Self, E>>) {
+        // A raw IP socket can be removed immediately.
+        this.bound.iface().common().remove_raw_socket(this);
+    }
+}
+
+impl RawIpSocketInner {
+    /// Returns whether an incoming packet matches the protocol and the identifier of the socket.
+    fn accepts(&self, ip_repr: &IpRepr, ip_payload: &[u8]) -> bool {
+        // An `IPPROTO_RAW` socket is send-only.
+        if u8::from(self.ip_protocol) == IPPROTO_RAW || ip_repr.next_header() != self.ip_protocol {
+            return false;
+        }
+
+        // A ping socket only receives echo replies with its identifier.
+        let Some(ident) = self.echo_ident else {
+            return true;
+        };
+        match ip_repr {
+            IpRepr::Ipv4(_) => Icmpv4Packet::new_checked(ip_payload).is_ok_and(|packet| {
+                packet.msg_type() == Icmpv4Message::EchoReply && packet.echo_ident() == ident
+            }),
+            IpRepr::Ipv6(_) => Icmpv6Packet::new_checked(ip_payload).is_ok_and(|packet| {
+                packet.msg_type() == Icmpv6Message::EchoReply && packet.echo_ident() == ident
+            }),
+        }
+    }
+
+    /// Fills in the ICMP fields that are maintained by the kernel.
+    ///
+    /// Ping sockets own the identifier of the echo requests and always compute the checksum. So
+    /// do ICMPv6 raw sockets for the checksum (see
+    /// <https://datatracker.ietf.org/doc/html/rfc3542#section-3.1>).
+    fn fill_icmp_fields(&self, ip_repr: &IpRepr, ip_payload: &mut [u8]) {
+        match ip_repr {
+            IpRepr::Ipv4(_) => {
+                let Some(ident) = self.echo_ident else {
+                    return;
+                };
+                let Ok(mut packet) = Icmpv4Packet::new_checked(ip_payload) else {
+                    return;
+                };
+                packet.set_echo_ident(ident);
+                packet.fill_checksum();
+            }
+            IpRepr::Ipv6(ipv6_repr) => {
+                if self.ip_protocol != IpProtocol::Icmpv6 {
+                    return;
+                }
+                let Ok(mut packet) = Icmpv6Packet::new_checked(ip_payload) else {
+                    return;
+                };
+                if let Some(ident) = self.echo_ident {
+                    packet.set_echo_ident(ident);
+                }
+                packet.fill_checksum(&ipv6_repr.src_addr, &ipv6_repr.dst_addr);
+            }
+        }
+    }
+}
+
+pub(crate) type RawIpSocketBg<E> = SocketBg<RawIpSocketInner, E>;
+
+impl<E: Ext> RawIpSocketBg<E> {
+    /// Tries to process an incoming packet.
+    ///
+    /// Unlike UDP packets, an incoming packet can be processed by multiple raw IP sockets.
+    pub(crate) fn process(&self, cx: &mut Context, ip_repr: &IpRepr, ip_payload: &[u8]) {
+        if !self.inner.accepts(ip_repr, ip_payload) {
+            return;
+        }
+
+        let mut socket = self.inner.socket.lock();
+
+        if !socket.accepts(ip_repr) {
+            return;
+        }
+
+        socket.process(cx, ip_repr, ip_payload);
+
+        self.notify_events(SocketEvents::CAN_RECV);
+    }
+
+    /// Tries to generate an outgoing packet and dispatches the generated packet.
+    pub(crate) fn dispatch<D>(&self, cx: &mut Context, dispatch: D)
+    where
+        D: FnOnce(&mut Context, &IpRepr, &[u8]),
+    {
+        let mut socket = self.inner.socket.lock();
+
+        socket
+            .dispatch(cx, |cx, (ip_repr, ip_payload)| {
+                dispatch(cx, &ip_repr, ip_payload);
+                Ok::<(), ()>(())
+            })
+            .unwrap();
+
+        // Like UDP, dequeuing a packet means that we can queue more packets.
+        self.notify_events(SocketEvents::CAN_SEND);
+
+        self.inner
+            .need_dispatch
+            .store(socket.send_queue() > 0, Ordering::Relaxed);
+    }
+
+    /// Returns whether the socket _may_ generate an outgoing packet.
+    ///
+    /// The check is intended to be lock-free and fast, but may have false positives.
+    pub(crate) fn need_dispatch(&self) -> bool {
+        self.inner.need_dispatch.load(Ordering::Relaxed)
+    }
+}
+
+/// The metadata of a packet received by a raw IP socket.
+#[derive(Clone, Copy, Debug)]
+pub struct RawIpMetadata {
+    /// The source address of the packet.
+    pub remote_addr: IpAddress,
+    /// The destination address of the packet.
+    pub local_addr: IpAddress,
+    /// The length of the IP header at the start of the packet.
+    pub header_len: usize,
+}
+
+impl<E: Ext> RawIpSocket<E> {
+    /// Creates a raw IP socket of the specified protocol.
+    ///
+    /// An `IPPROTO_RAW` socket receives no packets, but it can send packets of any protocol via
+    /// [`Self::send_hdrincl`].
+    ///
+    /// Polling the iface is _not_ required after this method succeeds.
+    pub fn new_raw(
+        bound: BoundRawPort<E>,
+        ip_protocol: IpProtocol,
+        observer: E::RawEventObserver,
+    ) -> Self {
+        Self::new_with(bound, ip_protocol, None, observer)
+    }
+
+    /// Creates a ping socket, which sends ICMP echo requests and receives ICMP echo replies.
+    ///
+    /// The port of the [`BoundRawPort`] is used as the identifier of the ICMP echo messages.
+    ///
+    /// Polling the iface is _not_ required after this method succeeds.
+    pub fn new_ping(bound: BoundRawPort<E>, observer: E::RawEventObserver) -> Self {
+        let ip_protocol = match bound.addr() {
+            IpAddress::Ipv4(_) => IpProtocol::Icmp,
+            IpAddress::Ipv6(_) => IpProtocol::Icmpv6,
+        };
+        let echo_ident = bound.port();
+        Self::new_with(bound, ip_protocol, Some(echo_ident), observer)
+    }
+
+    fn new_with(
+        bound: BoundRawPort<E>,
+        ip_protocol: IpProtocol,
+        echo_ident: Option<u16>,
+        observer: E::RawEventObserver,
+    ) -> Self {
+        let socket = {
+            // An `IPPROTO_RAW` socket can send packets of any protocol.
+            let protocol_filter = (u8::from(ip_protocol) != IPPROTO_RAW).then_some(ip_protocol);
+            new_raw_socket(bound.addr().version(), protocol_filter)
+        };
+
+        let inner = RawIpSocketInner {
+            socket: SpinLock::new(socket),
+            ip_protocol,
+            echo_ident,
+            need_dispatch: AtomicBool::new(false),
+        };
+
+        let socket = Self::new(bound, inner);
+        socket.init_observer(observer);
+        socket
+            .iface()
+            .common()
+            .register_raw_socket(socket.inner().clone());
+
+        socket
+    }
+
+    /// Sends a packet whose IP header is generated by the socket.
+    ///
+    /// The source address of the packet is the bound address.
+    ///
+    /// Polling the iface is _always_ required after this method succeeds.
+    pub fn send(
+        &self,
+        ip_payload: &[u8],
+        remote_addr: IpAddress,
+        hop_limit: u8,
+    ) -> Result<(), SendError> {
+        let ip_repr = match (*self.0.bound.addr(), remote_addr) {
+            (IpAddress::Ipv4(src_addr), IpAddress::Ipv4(dst_addr)) => IpRepr::Ipv4(Ipv4Repr {
+                src_addr,
+                dst_addr,
+                next_header: self.0.inner.ip_protocol,
+                payload_len: ip_payload.len(),
+                hop_limit,
+            }),
+            (IpAddress::Ipv6(src_addr), IpAddress::Ipv6(dst_addr)) => IpRepr::Ipv6(Ipv6Repr {
+                src_addr,
+                dst_addr,
+                next_header: self.0.inner.ip_protocol,
+                payload_len: ip_payload.len(),
+                hop_limit,
+            }),
+            _ => return Err(SendError::Unaddressable),
+        };
+        let header_len = ip_repr.header_len();
+
+        let mut socket = self.0.inner.socket.lock();
+
+        if header_len + ip_payload.len() > socket.payload_send_capacity() {
+            return Err(SendError::TooLarge);
+        }
+
+        let buffer = socket.send(header_len + ip_payload.len())?;
+        ip_repr.emit(&mut buffer[..header_len], &ChecksumCapabilities::default());
+        buffer[header_len..].copy_from_slice(ip_payload);
+        self.0
+            .inner
+            .fill_icmp_fields(&ip_repr, &mut buffer[header_len..]);
+
+        self.0
+            .inner
+            .need_dispatch
+            .store(socket.send_queue() > 0, Ordering::Relaxed);
+
+        Ok(())
+    }
+
+    /// Sends a packet that starts with an IPv4 header supplied by the caller.
+    ///
+    /// Like Linux, the total length and the checksum in the header are always filled in, and the
+    /// source address is filled in if it is unspecified.
+    ///
+    /// Polling the iface is _always_ required after this method succeeds.
+    pub fn send_hdrincl(&self, packet: &[u8]) -> Result<(), SendError> {
+        let IpAddress::Ipv4(local_addr) = *self.0.bound.addr() else {
+            return Err(SendError::Unaddressable);
+        };
+
+        if packet.len() < IPV4_HEADER_LEN {
+            return Err(SendError::Malformed);
+        }
+        let header = Ipv4Packet::new_unchecked(packet);
+        if header.version() != 4
+            || (header.header_len() as usize) < IPV4_HEADER_LEN
+            || (header.header_len() as usize) > packet.len()
+        {
+            return Err(SendError::Malformed);
+        }
+
+        let mut socket = self.0.inner.socket.lock();
+
+        if packet.len() > usize::from(u16::MAX) || packet.len() > socket.payload_send_capacity() {
+            return Err(SendError::TooLarge);
+        }
+
+        let buffer = socket.send(packet.len())?;
+        buffer.copy_from_slice(packet);
+        let mut ipv4_packet = Ipv4Packet::new_unchecked(buffer);
+        ipv4_packet.set_total_len(packet.len() as u16);
+        if ipv4_packet.src_addr().is_unspecified() {
+            ipv4_packet.set_src_addr(local_addr);
+        }
+        ipv4_packet.fill_checksum();
+
+        self.0
+            .inner
+            .need_dispatch
+            .store(socket.send_queue() > 0, Ordering::Relaxed);
+
+        Ok(())
+    }
+
+    /// Receives a packet, which starts with its IP header.
+    ///
+    /// Polling the iface is _not_ required after this method succeeds.
+    pub fn recv<F, R>(&self, f: F) -> Result<R, RecvError>
+    where
+        F: FnOnce(&[u8], RawIpMetadata) -> R,
+    {
+        let mut socket = self.0.inner.socket.lock();
+
+        let packet = socket.recv()?;
+        let meta = match self.0.bound.addr() {
+            IpAddress::Ipv4(_) => {
+                let ipv4_packet = Ipv4Packet::new_unchecked(packet);
+                RawIpMetadata {
+                    remote_addr: IpAddress::Ipv4(ipv4_packet.src_addr()),
+                    local_addr: IpAddress::Ipv4(ipv4_packet.dst_addr()),
+                    header_len: ipv4_packet.header_len() as usize,
+                }
+            }
+            IpAddress::Ipv6(_) => {
+                let ipv6_packet = Ipv6Packet::new_unchecked(packet);
+                RawIpMetadata {
+                    remote_addr: IpAddress::Ipv6(ipv6_packet.src_addr()),
+                    local_addr: IpAddress::Ipv6(ipv6_packet.dst_addr()),
+                    header_len: IPV6_HEADER_LEN,
+                }
+            }
+        };
+        let result = f(packet, meta);
+
+        Ok(result)
+    }
+
+    /// Returns whether there are packets to receive.
+    pub fn can_recv(&self) -> bool {
+        self.0.inner.socket.lock().can_recv()
+    }
+
+    /// Returns whether there is space to queue more packets to send.
+    pub fn can_send(&self) -> bool {
+        self.0.inner.socket.lock().can_send()
+    }
+}
@@ -13,7 +13,6 @@ use aster_softirq::BottomHalfDisabled;
 use ostd::sync::{SpinLock, SpinLockGuard};
 use smoltcp::{
     socket::{PollAt, tcp::State},
-    time::Duration,
     wire::{IpEndpoint, IpRepr, TcpControl, TcpRepr},
 };
 
@@ -23,12 +22,16 @@ use super::{
 };
 use crate::{
     define_boolean_value,
-    errors::tcp::{ConnectError, IoError, RecvError, SendError},
+    errors::tcp::{ConnError, ConnectError, IoError, RecvError, SendError},
     ext::Ext,
     iface::{BoundTcpPort, PollKey, PollableIfaceMut},
     socket::{
+        congestion::{CongestionControl, CongestionInfo, CongestionState},
         event::SocketEvents,
+        info::TcpConnectionInfo,
+        keep_alive::{KeepAliveConfig, KeepAliveState},
         option::{RawTcpOption, RawTcpSetOption},
+        stats::{StatsCollector, TcpStats},
         unbound::{RawTcpSocket, new_tcp_socket},
     },
     socket_table::ConnectionKey,
@@ -49,8 +52,11 @@ pub struct RawTcpSocketExt<E: Ext> {
     has_connected: bool,
     /// Indicates if the receiving side of this socket is shut down by the user.
     is_recv_shut: bool,
-    /// Indicates if the socket is closed by a RST packet.
-    is_rst_closed: bool,
+    /// The error that closed the socket abnormally.
+    conn_error: Option<ConnError>,
+    congestion: CongestionState,
+    keep_alive: KeepAliveState,
+    stats: StatsCollector,
 }
 
 impl<E: Ext> Deref for RawTcpSocketExt<E> {
@@ -102,12 +108,38 @@ impl<E: Ext> RawTcpSocketExt<E> {
         self.is_recv_shut
     }
 
-    /// Checks if the socket is closed by a RST packet.
+    /// Returns the error that closed the socket abnormally.
     ///
-    /// Note that the flag is automatically cleared when it is read by
-    /// [`TcpConnection::clear_rst_closed`], [`TcpConnection::send`], or [`TcpConnection::recv`].
-    pub fn is_rst_closed(&self) -> bool {
-        self.is_rst_closed
+    /// The socket can be closed abnormally by a RST packet or by a keep-alive timeout. Note that
+    /// the error is automatically cleared when it is read by [`TcpConnection::clear_conn_error`],
+    /// [`TcpConnection::send`], or [`TcpConnection::recv`].
+    pub fn conn_error(&self) -> Option<ConnError> {
+        self.conn_error
+    }
+
+    /// Returns the congestion control algorithm.
+    pub fn congestion_control(&self) -> CongestionControl {
+        self.congestion.control()
+    }
+
+    /// Returns the congestion information.
+    pub fn congestion_info(&self) -> CongestionInfo {
+        self.congestion.info()
+    }
+
+    /// Returns the keep-alive configuration.
+    pub fn keep_alive_config(&self) -> Option<KeepAliveConfig> {
+        self.keep_alive.config()
+    }
+
+    /// Returns the number of keep-alive probes that have not been answered.
+    pub fn keep_alive_probes(&self) -> u8 {
+        self.keep_alive.probes_out()
+    }
+
+    /// Returns the statistics.
+    pub fn stats(&self) -> TcpStats {
+        self.stats.stats()
     }
 }
 
@@ -138,7 +170,7 @@ impl<E: Ext> RawTcpSocketExt<E> {
                 // Strictly speaking, the socket isn't closed by an incoming RST packet in this
                 // situation. Instead, we reset the connection and _send_ an outgoing RST packet.
                 // However, Linux reports `ECONNRESET`, so we have to follow Linux.
-                self.is_rst_closed = true;
+                self.conn_error = Some(ConnError::Reset);
                 self.abort();
             }
             self.check_dead(this)
@@ -148,7 +180,7 @@ impl<E: Ext> RawTcpSocketExt<E> {
 
         let events = if self.state() != old_state {
             if self.state() == State::Closed && is_rst {
-                self.is_rst_closed = true;
+                self.conn_error = Some(ConnError::Reset);
             }
             self.on_new_state(this)
         } else {
@@ -158,6 +190,29 @@ impl<E: Ext> RawTcpSocketExt<E> {
         (events, became_dead)
     }
 
+    /// Processes an incoming packet and updates the congestion state, the keep-alive state, and
+    /// the statistics accordingly.
+    fn process_and_track(
+        &mut self,
+        iface: &mut PollableIfaceMut<E>,
+        ip_repr: &IpRepr,
+        tcp_repr: &TcpRepr,
+    ) -> Option<(IpRepr, TcpRepr<'static>)> {
+        let mut clamped_tcp_repr = *tcp_repr;
+        self.congestion.clamp_window(&mut clamped_tcp_repr);
+
+        self.keep_alive.before_recv(&mut self.socket);
+        let reply = self
+            .socket
+            .process(iface.context_mut(), ip_repr, &clamped_tcp_repr);
+
+        let now = iface.context().now();
+        self.congestion.on_recv(now, tcp_repr);
+        self.stats.on_recv(now, tcp_repr);
+
+        reply
+    }
+
     fn on_new_state(&mut self, this: &Arc<TcpConnectionBg<E>>) -> SocketEvents {
         let may_send = self.may_send();
 
@@ -221,6 +276,9 @@ impl<E: Ext> TcpConnectionInner<E> {
     pub(super) fn new(
         socket: Box<RawTcpSocket>,
         listener: Option<Arc<TcpListenerBg<E>>>,
+        congestion: CongestionState,
+        keep_alive: KeepAliveState,
+        stats: StatsCollector,
         weak_self: &Weak<TcpConnectionBg<E>>,
     ) -> Self {
         let connection_key = {
@@ -237,7 +295,10 @@ impl<E: Ext> TcpConnectionInner<E> {
             listener,
             has_connected: false,
             is_recv_shut: false,
-            is_rst_closed: false,
+            conn_error: None,
+            congestion,
+            keep_alive,
+            stats,
         };
 
         TcpConnectionInner {
@@ -325,8 +386,19 @@ impl<E: Ext> TcpConnection<E> {
             socket
         };
 
-        let connection =
-            Self::new_cyclic(bound, |weak| TcpConnectionInner::new(socket, None, weak));
+        let congestion = CongestionState::new(option.congestion_control);
+        let keep_alive = KeepAliveState::new(option.keep_alive);
+
+        let connection = Self::new_cyclic(bound, |weak| {
+            TcpConnectionInner::new(
+                socket,
+                None,
+                congestion,
+                keep_alive,
+                StatsCollector::new(),
+                weak,
+            )
+        });
         interface.update_next_poll_at_ms(&connection.0, PollAt::Now);
         connection.init_observer(observer);
 
@@ -384,9 +456,8 @@ impl<E: Ext> TcpConnection<E> {
 
         let mut socket = self.0.inner.lock();
 
-        if socket.is_rst_closed {
-            socket.is_rst_closed = false;
-            return Err(IoError::Socket(SendError::ConnReset));
+        if let Some(err) = socket.conn_error.take() {
+            return Err(IoError::Socket(err.into()));
         }
 
         let mut total_sent_bytes = 0;
@@ -486,9 +557,9 @@ impl<E: Ext> TcpConnection<E> {
             });
 
             let copy_result = match recv_result {
-                Err(_) if socket.is_rst_closed && total_recv_bytes == 0 => {
-                    socket.is_rst_closed = false;
-                    return Err(IoError::Socket(RecvError::ConnReset));
+                Err(_) if socket.conn_error.is_some() && total_recv_bytes == 0 => {
+                    let err = socket.conn_error.take().unwrap();
+                    return Err(IoError::Socket(err.into()));
                 }
                 Err(_) if total_recv_bytes > 0 => break total_recv_bytes,
                 res => res?,
@@ -517,16 +588,13 @@ impl<E: Ext> TcpConnection<E> {
         Ok((result, need_poll))
     }
 
-    /// Checks if the socket is closed by a RST packet and clears the flag.
+    /// Takes the error that closed the socket abnormally.
     ///
-    /// This flag is set when the socket is closed by a RST packet, and cleared when the connection
-    /// reset error is reported via one of the [`Self::send`], [`Self::recv`], or this method.
-    pub fn clear_rst_closed(&self) -> bool {
+    /// The error is set when the socket is closed by a RST packet or by a keep-alive timeout, and
+    /// cleared when it is reported via one of the [`Self::send`], [`Self::recv`], or this method.
+    pub fn clear_conn_error(&self) -> Option<ConnError> {
         let mut socket = self.0.inner.lock();
-
-        let is_rst = socket.is_rst_closed;
-        socket.is_rst_closed = false;
-        is_rst
+        socket.conn_error.take()
     }
 
     /// Shuts down the sending half of the connection.
@@ -620,11 +688,16 @@ impl<E: Ext> TcpConnection<E> {
 }
 
 impl<E: Ext> RawTcpSetOption for TcpConnection<E> {
-    fn set_keep_alive(&self, interval: Option<Duration>) -> NeedIfacePoll {
+    fn set_keep_alive(&self, config: Option<KeepAliveConfig>) -> NeedIfacePoll {
         let mut iface = self.iface().common().interface();
         let mut socket = self.0.inner.lock();
 
-        socket.set_keep_alive(interval);
+        let RawTcpSocketExt {
+            socket: raw_socket,
+            keep_alive,
+            ..
+        } = &mut *socket;
+        keep_alive.set_config(raw_socket, config);
 
         let poll_at = socket.poll_at(iface.context_mut());
         iface.update_next_poll_at_ms(&self.0, poll_at)
@@ -634,6 +707,11 @@ impl<E: Ext> RawTcpSetOption for TcpConnection<E> {
         let mut socket = self.0.inner.lock();
         socket.set_nagle_enabled(enabled);
     }
+
+    fn set_congestion_control(&self, control: CongestionControl) {
+        let mut socket = self.0.inner.lock();
+        socket.congestion.set_control(control);
+    }
 }
 
 impl<E: Ext> TcpConnectionBg<E> {
@@ -644,6 +722,22 @@ impl<E: Ext> TcpConnectionBg<E> {
     pub(crate) const fn connection_key(&self) -> &ConnectionKey {
         &self.inner.connection_key
     }
+
+    /// Calls `f` with the information about the connection.
+    pub(crate) fn with_info<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(TcpConnectionInfo<'_, E>) -> R,
+    {
+        let socket = self.inner.socket.lock();
+        let key = self.connection_key();
+
+        f(TcpConnectionInfo {
+            local_endpoint: key.local_endpoint(),
+            remote_endpoint: key.remote_endpoint(),
+            socket: &socket,
+            observer: self.observer(),
+        })
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -653,6 +747,21 @@ pub(crate) enum TcpProcessResult {
     ProcessedWithReply(IpRepr, TcpRepr<'static>),
 }
 
+/// The fate of an outgoing packet generated by [`TcpConnectionBg::dispatch`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) enum TcpDispatchResult {
+    /// The packet has been dispatched, possibly with a reply to process.
+    Dispatched(Option<(IpRepr, TcpRepr<'static>)>),
+    /// The packet has been held to be coalesced with the following packets, so more packets
+    /// should be generated.
+    Held,
+    /// The packet cannot be coalesced with the held packets.
+    ///
+    /// The socket is left as if the packet had never been generated, so it will generate the
+    /// packet again in the next dispatch.
+    Refused,
+}
+
 impl<E: Ext> TcpConnectionBg<E> {
     /// Tries to process an incoming packet and returns whether the packet is processed.
     pub(crate) fn process(
@@ -710,7 +819,7 @@ impl<E: Ext> TcpConnectionBg<E> {
         // to be queued.
         let mut events = SocketEvents::CAN_RECV | SocketEvents::CAN_SEND;
 
-        let result = match socket.process(iface.context_mut(), ip_repr, tcp_repr) {
+        let result = match socket.process_and_track(iface, ip_repr, tcp_repr) {
             None => TcpProcessResult::Processed,
             Some((ip_repr, tcp_repr)) => TcpProcessResult::ProcessedWithReply(ip_repr, tcp_repr),
         };
@@ -727,14 +836,17 @@ impl<E: Ext> TcpConnectionBg<E> {
         (result, became_dead)
     }
 
-    /// Tries to generate an outgoing packet and dispatches the generated packet.
+    /// Tries to generate outgoing packets and dispatches the generated packets.
+    ///
+    /// More packets are generated as long as `dispatch` holds the generated ones (see
+    /// [`TcpDispatchResult`]).
     pub(crate) fn dispatch<D>(
         self: &Arc<Self>,
         iface: &mut PollableIfaceMut<E>,
-        dispatch: D,
+        mut dispatch: D,
     ) -> (Option<(IpRepr, TcpRepr<'static>)>, TcpConnBecameDead)
     where
-        D: FnOnce(PollableIfaceMut<E>, &IpRepr, &TcpRepr) -> Option<(IpRepr, TcpRepr<'static>)>,
+        D: FnMut(PollableIfaceMut<E>, &IpRepr, &TcpRepr) -> TcpDispatchResult,
     {
         let mut socket = self.inner.lock();
 
@@ -745,12 +857,47 @@ impl<E: Ext> TcpConnectionBg<E> {
 
         let mut reply = None;
         let (cx, pending) = iface.inner_mut();
-        socket
-            .dispatch(cx, |cx, (ip_repr, tcp_repr)| {
-                reply = dispatch(PollableIfaceMut::new(cx, pending), &ip_repr, &tcp_repr);
-                Ok::<(), ()>(())
-            })
-            .unwrap();
+        let RawTcpSocketExt {
+            socket: raw_socket,
+            conn_error,
+            congestion,
+            keep_alive,
+            stats,
+            ..
+        } = &mut *socket;
+
+        if keep_alive.before_dispatch(raw_socket, cx.now()) {
+            // Like Linux, reset the connection if the peer does not answer the keep-alive probes.
+            *conn_error = Some(ConnError::TimedOut);
+            raw_socket.abort();
+        }
+
+        loop {
+            let mut result = None;
+            // If the packet is refused, the error makes the socket forget the packet.
+            let _ = raw_socket.dispatch(cx, |cx, (ip_repr, tcp_repr)| {
+                let res = dispatch(PollableIfaceMut::new(cx, pending), &ip_repr, &tcp_repr);
+                if res == TcpDispatchResult::Refused {
+                    return Err(());
+                }
+
+                let now = cx.now();
+                keep_alive.on_send(now, stats.is_keep_alive(&tcp_repr));
+                congestion.on_send(now, &tcp_repr);
+                stats.on_send(now, &tcp_repr);
+                result = Some(res);
+                Ok(())
+            });
+
+            match result {
+                Some(TcpDispatchResult::Dispatched(res)) => {
+                    reply = res;
+                    break;
+                }
+                Some(TcpDispatchResult::Held) => (),
+                Some(TcpDispatchResult::Refused) | None => break,
+            }
+        }
 
         // `dispatch` can return a packet in response to the generated packet. If the socket
         // accepts the packet, we can process it directly.
@@ -760,7 +907,7 @@ impl<E: Ext> TcpConnectionBg<E> {
             }
             is_rst |= tcp_repr.control == TcpControl::Rst;
             events |= SocketEvents::CAN_RECV | SocketEvents::CAN_SEND;
-            reply = socket.process(iface.context_mut(), ip_repr, tcp_repr);
+            reply = socket.process_and_track(iface, ip_repr, tcp_repr);
         }
 
         let (state_events, became_dead) =
@@ -6,7 +6,6 @@ use aster_softirq::BottomHalfDisabled;
 use ostd::sync::SpinLock;
 use smoltcp::{
     socket::PollAt,
-    time::Duration,
     wire::{IpEndpoint, IpRepr, TcpRepr},
 };
 
@@ -19,7 +18,11 @@ use crate::{
     ext::Ext,
     iface::{BindPortConfig, BoundTcpPort, PollableIfaceMut},
     socket::{
+        congestion::{CongestionControl, CongestionState},
+        info::TcpListenerInfo,
+        keep_alive::{KeepAliveConfig, KeepAliveState},
         option::{RawTcpOption, RawTcpSetOption},
+        stats::StatsCollector,
         unbound::{RawTcpSocket, new_tcp_socket},
     },
     socket_table::{ConnectionKey, ListenerKey},
@@ -30,6 +33,10 @@ pub type TcpListener<E> = Socket<TcpListenerInner<E>, E>;
 pub struct TcpBacklog<E: Ext> {
     socket: Box<RawTcpSocket>,
     max_conn: usize,
+    /// The congestion control algorithm used by new connections.
+    congestion_control: CongestionControl,
+    /// The keep-alive configuration used by new connections.
+    keep_alive: Option<KeepAliveConfig>,
     pub(super) connecting: BTreeMap<ConnectionKey, TcpConnection<E>>,
     pub(super) connected: Vec<TcpConnection<E>>,
 }
@@ -101,6 +108,8 @@ impl<E: Ext> TcpListener<E> {
             let backlog = TcpBacklog {
                 socket,
                 max_conn,
+                congestion_control: option.congestion_control,
+                keep_alive: option.keep_alive,
                 connecting: BTreeMap::new(),
                 connected: Vec::new(),
             };
@@ -170,9 +179,12 @@ impl<E: Ext> TcpListener<E> {
 }
 
 impl<E: Ext> RawTcpSetOption for TcpListener<E> {
-    fn set_keep_alive(&self, interval: Option<Duration>) -> NeedIfacePoll {
+    fn set_keep_alive(&self, config: Option<KeepAliveConfig>) -> NeedIfacePoll {
         let mut backlog = self.0.inner.backlog.lock();
-        backlog.socket.set_keep_alive(interval);
+        backlog.keep_alive = config;
+        backlog
+            .socket
+            .set_keep_alive(config.map(|config| config.idle));
 
         NeedIfacePoll::FALSE
     }
@@ -181,12 +193,28 @@ impl<E: Ext> RawTcpSetOption for TcpListener<E> {
         let mut backlog = self.0.inner.backlog.lock();
         backlog.socket.set_nagle_enabled(enabled);
     }
+
+    fn set_congestion_control(&self, control: CongestionControl) {
+        let mut backlog = self.0.inner.backlog.lock();
+        backlog.congestion_control = control;
+    }
 }
 
 impl<E: Ext> TcpListenerBg<E> {
     pub(crate) const fn listener_key(&self) -> &ListenerKey {
         &self.inner.listener_key
     }
+
+    pub(crate) fn info(&self) -> TcpListenerInfo<'_, E> {
+        let backlog = self.inner.backlog.lock();
+
+        TcpListenerInfo {
+            local_endpoint: self.bound.endpoint(),
+            num_connected: backlog.connected.len(),
+            max_conn: backlog.max_conn,
+            observer: self.observer(),
+        }
+    }
 }
 
 impl<E: Ext> TcpListenerBg<E> {
@@ -225,6 +253,19 @@ impl<E: Ext> TcpListenerBg<E> {
             return (result, None);
         }
 
+        // The SYN packet carries the options that are needed by congestion control.
+        let now = iface.context().now();
+        let congestion = {
+            let mut congestion = CongestionState::new(backlog.congestion_control);
+            congestion.on_recv(now, tcp_repr);
+            congestion
+        };
+        let stats = {
+            let mut stats = StatsCollector::new();
+            stats.on_recv(now, tcp_repr);
+            stats
+        };
+
         let new_socket = {
             let mut socket = new_tcp_socket();
             RawTcpOption::inherit(&backlog.socket, &mut socket);
@@ -241,6 +282,9 @@ impl<E: Ext> TcpListenerBg<E> {
                 TcpConnectionInner::new(
                     core::mem::replace(&mut backlog.socket, new_socket),
                     Some(self.clone()),
+                    congestion,
+                    KeepAliveState::new(backlog.keep_alive),
+                    stats,
                     weak,
                 )
             },
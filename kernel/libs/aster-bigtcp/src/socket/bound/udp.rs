@@ -1,14 +1,17 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use alloc::{boxed::Box, sync::Arc};
-use core::sync::atomic::{AtomicBool, Ordering};
+use alloc::{boxed::Box, collections::vec_deque::VecDeque, sync::Arc, vec::Vec};
+use core::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
 
 use aster_softirq::BottomHalfDisabled;
 use ostd::sync::SpinLock;
 use smoltcp::{
     iface::Context,
     socket::udp::UdpMetadata,
-    wire::{IpRepr, UdpRepr},
+    wire::{IpAddress, IpEndpoint, IpRepr, UdpRepr},
 };
 
 use super::common::{Inner, Socket, SocketBg};
@@ -16,7 +19,13 @@ use crate::{
     errors::udp::SendError,
     ext::Ext,
     iface::BoundUdpPort,
-    socket::{RawUdpSocket, event::SocketEvents, unbound::new_udp_socket},
+    socket::{
+        RawUdpSocket,
+        event::SocketEvents,
+        info::UdpSocketInfo,
+        option::UdpMulticastOption,
+        unbound::{UDP_METADATA_LEN, new_udp_socket},
+    },
 };
 
 pub type UdpSocket<E> = Socket<UdpSocketInner, E>;
@@ -25,8 +34,51 @@ pub type UdpSocket<E> = Socket<UdpSocketInner, E>;
 pub struct UdpSocketInner {
     socket: SpinLock<Box<RawUdpSocket>, BottomHalfDisabled>,
     need_dispatch: AtomicBool,
+    multicast: SpinLock<UdpMulticastOption, BottomHalfDisabled>,
+    /// The information about the received packets, in the same order as the packets in the
+    /// receive buffer.
+    recv_infos: SpinLock<VecDeque<UdpRecvInfo>, BottomHalfDisabled>,
+    /// The ICMP errors to receive, or `None` if error reporting is disabled.
+    errors: SpinLock<Option<VecDeque<UdpIcmpError>>, BottomHalfDisabled>,
+    /// The latest ICMP error, which is kept regardless of whether error reporting is enabled.
+    ///
+    /// Lock order: `errors` first, `pending_error` second.
+    pending_error: SpinLock<Option<UdpIcmpError>, BottomHalfDisabled>,
+}
+
+/// The information about a packet received by a UDP socket.
+///
+/// This supplements [`UdpMetadata`] with the fields that smoltcp does not record.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UdpRecvInfo {
+    /// The hop limit (or TTL) of the packet.
+    pub hop_limit: u8,
+    /// The traffic class (or TOS) of the packet.
+    pub traffic_class: u8,
+    /// The time when the packet is received, measured since boot.
+    pub timestamp: Duration,
 }
 
+/// An ICMP error reported to a UDP socket.
+#[derive(Clone, Debug)]
+pub struct UdpIcmpError {
+    /// The address of the node that reports the error.
+    pub offender: IpAddress,
+    /// The type of the ICMP message.
+    pub icmp_type: u8,
+    /// The code of the ICMP message.
+    pub icmp_code: u8,
+    /// The destination endpoint of the packet that causes the error.
+    pub remote_endpoint: IpEndpoint,
+    /// The payload of the packet that causes the error, which may be truncated.
+    pub payload: Vec<u8>,
+}
+
+/// The maximum number of ICMP errors that can be queued on a UDP socket.
+///
+/// Linux limits the error queue by the receive buffer size instead.
+const MAX_ICMP_ERRORS: usize = 64;
+
 impl<E: Ext> Inner<E> for UdpSocketInner {
     type BoundPort = BoundUdpPort<E>;
     type Observer = E::UdpEventObserver;
@@ -47,6 +99,7 @@ impl<E: Ext> UdpSocketBg<E> {
         &self,
         cx: &mut Context,
         ip_repr: &IpRepr,
+        traffic_class: u8,
         udp_repr: &UdpRepr,
         udp_payload: &[u8],
     ) -> bool {
@@ -56,6 +109,15 @@ impl<E: Ext> UdpSocketBg<E> {
             return false;
         }
 
+        let dst_addr = ip_repr.dst_addr();
+        if dst_addr.is_multicast()
+            && let Some(groups) = self.inner.multicast.lock().groups.as_ref()
+            && !groups.contains(&dst_addr)
+        {
+            return false;
+        }
+
+        let old_recv_queue = socket.recv_queue();
         socket.process(
             cx,
             smoltcp::phy::PacketMeta::default(),
@@ -64,6 +126,24 @@ impl<E: Ext> UdpSocketBg<E> {
             udp_payload,
         );
 
+        // smoltcp silently drops the packet if the receive buffer is full. For empty packets,
+        // whether they are dropped cannot be observed, so they are assumed to be queued unless
+        // the metadata buffer is full.
+        let mut recv_infos = self.inner.recv_infos.lock();
+        let is_queued = if udp_payload.is_empty() {
+            recv_infos.len() < UDP_METADATA_LEN
+        } else {
+            socket.recv_queue() > old_recv_queue
+        };
+        if is_queued {
+            recv_infos.push_back(UdpRecvInfo {
+                hop_limit: ip_repr.hop_limit(),
+                traffic_class,
+                timestamp: Duration::from_micros(cx.now().total_micros() as u64),
+            });
+        }
+        drop(recv_infos);
+
         self.notify_events(SocketEvents::CAN_RECV);
 
         true
@@ -77,7 +157,14 @@ impl<E: Ext> UdpSocketBg<E> {
         let mut socket = self.inner.socket.lock();
 
         socket
-            .dispatch(cx, |cx, _meta, (ip_repr, udp_repr, udp_payload)| {
+            .dispatch(cx, |cx, _meta, (mut ip_repr, udp_repr, udp_payload)| {
+                if ip_repr.dst_addr().is_multicast() {
+                    let hop_limit = self.inner.multicast.lock().hop_limit;
+                    match &mut ip_repr {
+                        IpRepr::Ipv4(ipv4_repr) => ipv4_repr.hop_limit = hop_limit,
+                        IpRepr::Ipv6(ipv6_repr) => ipv6_repr.hop_limit = hop_limit,
+                    }
+                }
                 dispatch(cx, &ip_repr, &udp_repr, udp_payload);
                 Ok::<(), ()>(())
             })
@@ -97,6 +184,49 @@ impl<E: Ext> UdpSocketBg<E> {
     pub(crate) fn need_dispatch(&self) -> bool {
         self.inner.need_dispatch.load(Ordering::Relaxed)
     }
+
+    /// Returns whether outgoing multicast packets should be looped back to local sockets.
+    pub(crate) fn is_multicast_loop_enabled(&self) -> bool {
+        self.inner.multicast.lock().is_loop_enabled
+    }
+
+    pub(crate) fn info(&self) -> UdpSocketInfo<'_, E> {
+        let socket = self.inner.socket.lock();
+
+        UdpSocketInfo {
+            local_endpoint: self.bound.endpoint(),
+            recv_queue: socket.recv_queue(),
+            send_queue: socket.send_queue(),
+            observer: self.observer(),
+        }
+    }
+
+    /// Returns whether the socket is bound to the local endpoint.
+    pub(crate) fn is_bound_to(&self, endpoint: &IpEndpoint) -> bool {
+        self.bound.endpoint() == *endpoint
+    }
+
+    /// Records an ICMP error caused by a packet sent from the socket.
+    ///
+    /// The error becomes the pending error. It is also queued if error reporting is enabled.
+    pub(crate) fn process_icmp_error<F>(&self, new_error: F)
+    where
+        F: FnOnce() -> UdpIcmpError,
+    {
+        let error = new_error();
+
+        let mut errors = self.inner.errors.lock();
+        if let Some(errors) = errors.as_mut()
+            && errors.len() < MAX_ICMP_ERRORS
+        {
+            errors.push_back(error.clone());
+        }
+        drop(errors);
+
+        *self.inner.pending_error.lock() = Some(error);
+
+        self.notify_events(SocketEvents::ERROR);
+    }
 }
 
 impl<E: Ext> UdpSocket<E> {
@@ -122,6 +252,10 @@ impl<E: Ext> UdpSocket<E> {
         let inner = UdpSocketInner {
             socket: SpinLock::new(socket),
             need_dispatch: AtomicBool::new(false),
+            multicast: SpinLock::new(UdpMulticastOption::new()),
+            recv_infos: SpinLock::new(VecDeque::new()),
+            errors: SpinLock::new(None),
+            pending_error: SpinLock::new(None),
         };
 
         let socket = Self::new(bound, inner);
@@ -168,16 +302,84 @@ impl<E: Ext> UdpSocket<E> {
     /// Polling the iface is _not_ required after this method succeeds.
     pub fn recv<F, R>(&self, f: F) -> Result<R, smoltcp::socket::udp::RecvError>
     where
-        F: FnOnce(&[u8], UdpMetadata) -> R,
+        F: FnOnce(&[u8], UdpMetadata, UdpRecvInfo) -> R,
     {
         let mut socket = self.0.inner.socket.lock();
 
         let (data, meta) = socket.recv()?;
-        let result = f(data, meta);
+        let info = self
+            .0
+            .inner
+            .recv_infos
+            .lock()
+            .pop_front()
+            .unwrap_or_default();
+        let result = f(data, meta, info);
 
         Ok(result)
     }
 
+    /// Receives an ICMP error.
+    ///
+    /// Like Linux, the pending error is replaced by the next error in the queue, if any.
+    ///
+    /// Polling the iface is _not_ required after this method succeeds.
+    pub fn recv_icmp_error(&self) -> Option<UdpIcmpError> {
+        let mut errors = self.0.inner.errors.lock();
+        let errors = errors.as_mut()?;
+
+        let error = errors.pop_front()?;
+        *self.0.inner.pending_error.lock() = errors.front().cloned();
+
+        Some(error)
+    }
+
+    /// Returns whether there are ICMP errors to receive.
+    pub fn has_icmp_errors(&self) -> bool {
+        self.0
+            .inner
+            .errors
+            .lock()
+            .as_ref()
+            .is_some_and(|errors| !errors.is_empty())
+    }
+
+    /// Returns whether reporting ICMP errors is enabled.
+    pub fn is_icmp_error_enabled(&self) -> bool {
+        self.0.inner.errors.lock().is_some()
+    }
+
+    /// Calls `f` with the pending ICMP error, which is the latest one that has not been cleared.
+    ///
+    /// `f` may clear the pending error by taking it.
+    ///
+    /// Polling the iface is _not_ required after this method succeeds.
+    pub fn pending_icmp_error_with<R>(&self, f: impl FnOnce(&mut Option<UdpIcmpError>) -> R) -> R {
+        f(&mut self.0.inner.pending_error.lock())
+    }
+
+    /// Enables or disables reporting ICMP errors.
+    ///
+    /// Disabling error reporting discards all the queued errors.
+    ///
+    /// Polling the iface is _not_ required after this method succeeds.
+    pub fn set_icmp_error_enabled(&self, is_enabled: bool) {
+        let mut errors = self.0.inner.errors.lock();
+
+        match (errors.is_some(), is_enabled) {
+            (false, true) => *errors = Some(VecDeque::new()),
+            (true, false) => *errors = None,
+            _ => (),
+        }
+    }
+
+    /// Sets the multicast options.
+    ///
+    /// Polling the iface is _not_ required after this method succeeds.
+    pub fn set_multicast_option(&self, option: UdpMulticastOption) {
+        *self.0.inner.multicast.lock() = option;
+    }
+
     /// Calls `f` with an immutable reference to the associated [`RawUdpSocket`].
     //
     // NOTE: If a mutable reference is required, add a method above that correctly updates the next
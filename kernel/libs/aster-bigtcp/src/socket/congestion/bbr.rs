@@ -0,0 +1,261 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use smoltcp::time::{Duration, Instant};
+
+use super::{AckSample, Controller, INITIAL_WINDOW_SEGMENTS, LossKind};
+
+/// The BBR congestion control algorithm (version 1).
+///
+/// BBR builds a model of the network path from the delivery rate and the round-trip time, and
+/// sets the congestion window to a multiple of the estimated bandwidth-delay product (BDP).
+///
+/// Since smoltcp does not support pacing, only the congestion window is controlled. The pacing
+/// gains are approximated by scaling the congestion window instead.
+///
+/// Reference: <https://datatracker.ietf.org/doc/html/draft-cardwell-iccrg-bbr-congestion-control-00>
+pub(super) struct Bbr {
+    window: usize,
+    mode: Mode,
+    /// The maximum delivery rates of the recent rounds, in bytes per second.
+    bw_samples: [u64; BW_FILTER_ROUNDS],
+    /// The number of round trips so far.
+    round_count: usize,
+    /// The current round ends when this number of bytes are delivered.
+    next_round_delivered: u64,
+    /// The time and the number of delivered bytes when the current round starts.
+    round_start: Option<(Instant, u64)>,
+    /// The bandwidth when the bandwidth was last found to grow.
+    full_bw: u64,
+    /// The number of rounds without significant bandwidth growth.
+    full_bw_rounds: usize,
+    is_full_bw_reached: bool,
+    /// The minimum round-trip time and the time when it is measured.
+    min_rtt: Option<(u64, Instant)>,
+    /// The index of the current gain cycle phase in the `ProbeBw` mode.
+    cycle_index: usize,
+    /// The time when the `ProbeRtt` mode ends.
+    probe_rtt_done: Option<Instant>,
+    /// The congestion window saved before the loss recovery or the `ProbeRtt` mode.
+    prior_window: usize,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Mode {
+    /// Grows the window rapidly to find the bottleneck bandwidth.
+    Startup,
+    /// Drains the queue created in the `Startup` mode.
+    Drain,
+    /// Cycles the gain to probe for more bandwidth.
+    ProbeBw,
+    /// Reduces the window to probe for the minimum round-trip time.
+    ProbeRtt,
+}
+
+/// The number of rounds in which the maximum bandwidth is kept.
+const BW_FILTER_ROUNDS: usize = 10;
+/// The period in which the minimum round-trip time is kept.
+const MIN_RTT_EXPIRY: Duration = Duration::from_secs(10);
+/// The time spent in the `ProbeRtt` mode.
+const PROBE_RTT_DURATION: Duration = Duration::from_millis(200);
+/// The minimum congestion window in segments.
+const MIN_WINDOW_SEGMENTS: usize = 4;
+
+/// The gain in the `Startup` mode (`2 / ln(2)`), in thousandths.
+const HIGH_GAIN_MILLIS: usize = 2885;
+/// The base gain of the congestion window in the `ProbeBw` mode, in thousandths.
+const WINDOW_GAIN_MILLIS: usize = 2000;
+/// The gains of the gain cycle in the `ProbeBw` mode, in quarters.
+const CYCLE_GAIN_QUARTERS: [usize; 8] = [5, 3, 4, 4, 4, 4, 4, 4];
+
+/// The bandwidth growth factor that is considered significant, in quarters.
+const FULL_BW_GROWTH_QUARTERS: u64 = 5;
+/// The number of rounds without significant growth after which the bandwidth is considered full.
+const FULL_BW_ROUNDS: usize = 3;
+
+impl Bbr {
+    pub(super) fn new(window: usize) -> Self {
+        Self {
+            window,
+            mode: Mode::Startup,
+            bw_samples: [0; BW_FILTER_ROUNDS],
+            round_count: 0,
+            next_round_delivered: 0,
+            round_start: None,
+            full_bw: 0,
+            full_bw_rounds: 0,
+            is_full_bw_reached: false,
+            min_rtt: None,
+            cycle_index: 0,
+            probe_rtt_done: None,
+            prior_window: window,
+        }
+    }
+
+    /// Returns the estimated bottleneck bandwidth, in bytes per second.
+    fn max_bw(&self) -> u64 {
+        self.bw_samples.iter().copied().max().unwrap()
+    }
+
+    /// Returns the estimated bandwidth-delay product in bytes.
+    fn bdp(&self, mss: usize) -> usize {
+        let bw = self.max_bw();
+        let Some((min_rtt_us, _)) = self.min_rtt.filter(|_| bw > 0) else {
+            // Like Linux, use the initial window if there is no valid estimation.
+            return INITIAL_WINDOW_SEGMENTS * mss;
+        };
+
+        // The clock has a granularity of milliseconds, so a zero round-trip time is possible.
+        let min_rtt_us = min_rtt_us.max(1000);
+        (bw as u128 * min_rtt_us as u128 / 1_000_000).min(usize::MAX as u128) as usize
+    }
+
+    /// Returns the gain of the congestion window, in thousandths.
+    fn window_gain_millis(&self) -> usize {
+        match self.mode {
+            Mode::Startup | Mode::Drain => HIGH_GAIN_MILLIS,
+            Mode::ProbeBw => WINDOW_GAIN_MILLIS * CYCLE_GAIN_QUARTERS[self.cycle_index] / 4,
+            Mode::ProbeRtt => 1000,
+        }
+    }
+
+    fn update_round(&mut self, sample: &AckSample) -> bool {
+        if sample.delivered < self.next_round_delivered {
+            return false;
+        }
+
+        if let Some((start, start_delivered)) = self.round_start {
+            let interval_us = (sample.now - start).total_micros();
+            if interval_us > 0 {
+                let bw = (sample.delivered - start_delivered) * 1_000_000 / interval_us;
+                self.bw_samples[self.round_count % BW_FILTER_ROUNDS] = bw;
+            }
+        }
+
+        self.round_count += 1;
+        self.bw_samples[self.round_count % BW_FILTER_ROUNDS] = 0;
+        self.next_round_delivered = sample.delivered + sample.in_flight as u64;
+        self.round_start = Some((sample.now, sample.delivered));
+
+        true
+    }
+
+    fn check_full_bw(&mut self) {
+        if self.is_full_bw_reached {
+            return;
+        }
+
+        let bw = self.max_bw();
+        if bw * 4 >= self.full_bw * FULL_BW_GROWTH_QUARTERS {
+            self.full_bw = bw;
+            self.full_bw_rounds = 0;
+            return;
+        }
+
+        self.full_bw_rounds += 1;
+        if self.full_bw_rounds >= FULL_BW_ROUNDS {
+            self.is_full_bw_reached = true;
+        }
+    }
+
+    fn update_min_rtt(&mut self, sample: &AckSample) -> bool {
+        let is_expired = self
+            .min_rtt
+            .is_some_and(|(_, stamp)| sample.now - stamp > MIN_RTT_EXPIRY);
+
+        if let Some(rtt_us) = sample.rtt_us
+            && (is_expired
+                || self
+                    .min_rtt
+                    .is_none_or(|(min_rtt_us, _)| rtt_us <= min_rtt_us))
+        {
+            self.min_rtt = Some((rtt_us, sample.now));
+        }
+
+        is_expired
+    }
+}
+
+impl Controller for Bbr {
+    fn window(&self) -> usize {
+        self.window
+    }
+
+    fn ssthresh(&self) -> usize {
+        // BBR does not use the slow start threshold.
+        usize::MAX
+    }
+
+    fn on_ack(&mut self, mss: usize, sample: &AckSample) {
+        let min_window = MIN_WINDOW_SEGMENTS * mss;
+
+        let is_round_start = self.update_round(sample);
+        if is_round_start {
+            self.check_full_bw();
+            if self.mode == Mode::ProbeBw {
+                self.cycle_index = (self.cycle_index + 1) % CYCLE_GAIN_QUARTERS.len();
+            }
+        }
+        let is_min_rtt_expired = self.update_min_rtt(sample);
+
+        if self.mode == Mode::Startup && self.is_full_bw_reached {
+            self.mode = Mode::Drain;
+        }
+        if self.mode == Mode::Drain && sample.in_flight <= self.bdp(mss) {
+            self.mode = Mode::ProbeBw;
+            self.cycle_index = 0;
+        }
+
+        if is_min_rtt_expired && self.mode != Mode::ProbeRtt {
+            self.mode = Mode::ProbeRtt;
+            self.prior_window = self.window;
+            self.probe_rtt_done = Some(sample.now + PROBE_RTT_DURATION);
+        }
+        if self.mode == Mode::ProbeRtt {
+            if self
+                .probe_rtt_done
+                .is_some_and(|done| sample.now >= done && is_round_start)
+            {
+                if let Some((_, ref mut stamp)) = self.min_rtt {
+                    *stamp = sample.now;
+                }
+                self.probe_rtt_done = None;
+                self.mode = if self.is_full_bw_reached {
+                    Mode::ProbeBw
+                } else {
+                    Mode::Startup
+                };
+                self.window = self.window.max(self.prior_window);
+            } else {
+                self.window = min_window;
+            }
+            return;
+        }
+
+        if sample.is_in_recovery {
+            // Packet conservation: Send one segment for each acknowledged segment.
+            self.window = (sample.in_flight + sample.acked).max(min_window);
+            return;
+        }
+
+        let target = (self.bdp(mss) * self.window_gain_millis() / 1000).max(min_window);
+        if self.is_full_bw_reached {
+            self.window = (self.window + sample.acked).min(target);
+        } else if self.window < target || sample.delivered < (INITIAL_WINDOW_SEGMENTS * mss) as u64
+        {
+            self.window += sample.acked;
+        }
+        self.window = self.window.max(min_window);
+    }
+
+    fn on_loss(&mut self, mss: usize, kind: LossKind, in_flight: usize) {
+        self.prior_window = self.window;
+        self.window = match kind {
+            LossKind::FastRetransmit => in_flight.max(MIN_WINDOW_SEGMENTS * mss),
+            LossKind::Timeout => mss,
+        };
+    }
+
+    fn on_recovery_end(&mut self) {
+        self.window = self.window.max(self.prior_window);
+    }
+}
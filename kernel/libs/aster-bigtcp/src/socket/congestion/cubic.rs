@@ -0,0 +1,187 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use smoltcp::time::Instant;
+
+use super::{AckSample, Controller, LossKind, reno::slow_start};
+
+/// The CUBIC congestion control algorithm.
+///
+/// All computations are done with integers because floating-point arithmetic is not available in
+/// the kernel.
+///
+/// Reference: <https://datatracker.ietf.org/doc/html/rfc9438>
+pub(super) struct Cubic {
+    window: usize,
+    ssthresh: usize,
+    /// The congestion window just before the last window reduction (`W_max`).
+    window_max: usize,
+    /// The current congestion avoidance stage.
+    epoch: Option<Epoch>,
+    /// The number of acknowledged bytes since the window was last grown.
+    acked_cubic: usize,
+    /// The number of acknowledged bytes since the Reno-friendly window was last grown.
+    acked_est: usize,
+}
+
+/// A congestion avoidance stage.
+struct Epoch {
+    /// The time when the stage starts.
+    start: Instant,
+    /// The time period that the window takes to grow to `origin`, in milliseconds (`K`).
+    k_ms: u64,
+    /// The window at the plateau of the cubic function.
+    origin: usize,
+    /// The window estimated for the Reno-friendly region (`W_est`).
+    window_est: usize,
+}
+
+/// The multiplicative decrease factor (`β_cubic`), in tenths.
+const BETA_TENTHS: usize = 7;
+
+/// The constant that determines the aggressiveness of the window growth (`C`), in tenths.
+const C_TENTHS: u64 = 4;
+
+impl Cubic {
+    pub(super) fn new(window: usize, ssthresh: usize) -> Self {
+        Self {
+            window,
+            ssthresh,
+            window_max: 0,
+            epoch: None,
+            acked_cubic: 0,
+            acked_est: 0,
+        }
+    }
+
+    fn new_epoch(&self, now: Instant, mss: usize) -> Epoch {
+        let (k_ms, origin) = if self.window < self.window_max {
+            // K = cbrt((W_max - cwnd) / C), where the windows are in segments and K is in
+            // seconds. Here we compute K in milliseconds.
+            let diff = (self.window_max - self.window) as u128;
+            let k_cubed = diff * 10 * 1_000_000_000 / (C_TENTHS as u128 * mss as u128);
+            (cbrt(k_cubed.min(u64::MAX as u128) as u64), self.window_max)
+        } else {
+            (0, self.window)
+        };
+
+        Epoch {
+            start: now,
+            k_ms,
+            origin,
+            window_est: self.window,
+        }
+    }
+}
+
+impl Epoch {
+    /// Computes `W_cubic(t)` in bytes, where `t` is in milliseconds.
+    fn cubic_window(&self, t_ms: u64, mss: usize) -> usize {
+        // Limit the offset to avoid overflows. The window will be limited by other factors way
+        // before the offset reaches the limit.
+        const MAX_OFFSET_MS: u64 = 1_000_000;
+
+        let offset_ms = t_ms.abs_diff(self.k_ms).min(MAX_OFFSET_MS) as u128;
+        // C * (t - K)^3, where the time is converted from milliseconds to seconds.
+        let delta =
+            offset_ms.pow(3) * C_TENTHS as u128 * mss as u128 / (10 * 1_000_000_000) as u128;
+        let delta = delta.min(usize::MAX as u128) as usize;
+
+        if t_ms >= self.k_ms {
+            self.origin.saturating_add(delta)
+        } else {
+            self.origin.saturating_sub(delta)
+        }
+    }
+}
+
+impl Controller for Cubic {
+    fn window(&self) -> usize {
+        self.window
+    }
+
+    fn ssthresh(&self) -> usize {
+        self.ssthresh
+    }
+
+    fn on_ack(&mut self, mss: usize, sample: &AckSample) {
+        if sample.is_in_recovery || !sample.is_window_limited(self.window) {
+            return;
+        }
+
+        let acked = slow_start(&mut self.window, self.ssthresh, sample.acked);
+        if acked == 0 {
+            return;
+        }
+
+        let mut epoch = self
+            .epoch
+            .take()
+            .unwrap_or_else(|| self.new_epoch(sample.now, mss));
+
+        // The target is the window after one round-trip time.
+        let t_ms = (sample.now - epoch.start).total_millis() + sample.srtt_us.unwrap_or(0) / 1000;
+        let target = epoch
+            .cubic_window(t_ms, mss)
+            .clamp(self.window, self.window + self.window / 2);
+
+        // Grow the window by `(target - cwnd) / cwnd` segments for each acknowledged segment. In
+        // the concave region where the target is reached, grow slowly by `0.01` segments.
+        let acked_per_segment = if target > self.window {
+            (self.window * mss / (target - self.window)).max(1)
+        } else {
+            self.window * 100
+        };
+        self.acked_cubic += acked;
+        if self.acked_cubic >= acked_per_segment {
+            self.window += mss * (self.acked_cubic / acked_per_segment);
+            self.acked_cubic %= acked_per_segment;
+        }
+
+        // In the Reno-friendly region, grow the estimated window by
+        // `3 * (1 - β) / (1 + β) = 9 / 17` segments per round-trip time.
+        let acked_per_segment = epoch.window_est * 17 / 9;
+        self.acked_est += acked;
+        if self.acked_est >= acked_per_segment {
+            epoch.window_est += mss * (self.acked_est / acked_per_segment);
+            self.acked_est %= acked_per_segment;
+        }
+        self.window = self.window.max(epoch.window_est);
+
+        self.epoch = Some(epoch);
+    }
+
+    fn on_loss(&mut self, mss: usize, kind: LossKind, _in_flight: usize) {
+        // Fast convergence: If the window is reduced again before reaching `W_max`, release more
+        // bandwidth to new flows.
+        self.window_max = if self.window < self.window_max {
+            self.window * (10 + BETA_TENTHS) / 20
+        } else {
+            self.window
+        };
+
+        self.ssthresh = (self.window * BETA_TENTHS / 10).max(mss * 2);
+        self.window = match kind {
+            LossKind::FastRetransmit => self.ssthresh,
+            LossKind::Timeout => mss,
+        };
+
+        self.epoch = None;
+        self.acked_cubic = 0;
+        self.acked_est = 0;
+    }
+}
+
+/// Computes the integer cube root.
+fn cbrt(mut x: u64) -> u64 {
+    // Reference: Hacker's Delight, Section 11-2.
+    let mut y = 0u64;
+    for s in (0..=63).rev().step_by(3) {
+        y *= 2;
+        let b = 3 * y * (y + 1) + 1;
+        if (x >> s) >= b {
+            x -= b << s;
+            y += 1;
+        }
+    }
+    y
+}
@@ -0,0 +1,478 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! TCP congestion control.
+//!
+//! smoltcp does not implement congestion control. Instead of modifying smoltcp, we keep track of
+//! the sequence numbers of outgoing and incoming segments and feed the congestion events into a
+//! pluggable [`Controller`]. The resulting congestion window is enforced by clamping the receive
+//! window advertised by the peer, so that smoltcp never keeps more than a congestion window of data
+//! in flight.
+
+mod bbr;
+mod cubic;
+mod reno;
+
+use alloc::boxed::Box;
+
+use smoltcp::{
+    time::{Duration, Instant},
+    wire::{TcpControl, TcpRepr, TcpSeqNumber},
+};
+
+use self::{bbr::Bbr, cubic::Cubic, reno::Reno};
+
+/// A TCP congestion control algorithm.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CongestionControl {
+    /// TCP Reno.
+    ///
+    /// Reference: <https://datatracker.ietf.org/doc/html/rfc5681>
+    Reno,
+    /// CUBIC.
+    ///
+    /// Reference: <https://datatracker.ietf.org/doc/html/rfc9438>
+    Cubic,
+    /// BBR (version 1).
+    ///
+    /// Reference: <https://datatracker.ietf.org/doc/html/draft-cardwell-iccrg-bbr-congestion-control-00>
+    Bbr,
+}
+
+impl CongestionControl {
+    /// All available congestion control algorithms.
+    pub const ALL: [Self; 3] = [Self::Reno, Self::Cubic, Self::Bbr];
+
+    /// Returns the name of the algorithm.
+    ///
+    /// The names are the same as the ones used by Linux.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Reno => "reno",
+            Self::Cubic => "cubic",
+            Self::Bbr => "bbr",
+        }
+    }
+
+    /// Looks up the algorithm with the specified name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|control| control.name() == name)
+    }
+
+    fn new_controller(self, window: usize, ssthresh: usize) -> Box<dyn Controller> {
+        match self {
+            Self::Reno => Box::new(Reno::new(window, ssthresh)),
+            Self::Cubic => Box::new(Cubic::new(window, ssthresh)),
+            Self::Bbr => Box::new(Bbr::new(window)),
+        }
+    }
+}
+
+/// The loss recovery state of a TCP connection.
+///
+/// This corresponds to the `tcp_ca_state` in Linux.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RecoveryState {
+    /// No losses or duplicate acknowledgments are detected.
+    Open,
+    /// Some duplicate acknowledgments are received.
+    Disorder,
+    /// Recovering from a loss detected by duplicate acknowledgments.
+    Recovery,
+    /// Recovering from a retransmission timeout.
+    Loss,
+}
+
+/// The congestion information of a TCP connection.
+#[derive(Clone, Copy, Debug)]
+pub struct CongestionInfo {
+    /// The congestion control algorithm.
+    pub control: CongestionControl,
+    /// The loss recovery state.
+    pub recovery_state: RecoveryState,
+    /// The maximum segment size used to send segments.
+    pub mss: usize,
+    /// The congestion window in bytes.
+    pub window: usize,
+    /// The slow start threshold in bytes.
+    ///
+    /// This is `usize::MAX` if the slow start threshold has not been set.
+    pub ssthresh: usize,
+    /// The smoothed round-trip time.
+    pub srtt: Option<Duration>,
+    /// The round-trip time variation.
+    pub rttvar: Option<Duration>,
+    /// The minimum round-trip time.
+    pub min_rtt: Option<Duration>,
+    /// The total number of retransmitted segments.
+    pub retransmits: usize,
+}
+
+/// A congestion controller that decides the congestion window.
+trait Controller: Send {
+    /// Returns the congestion window in bytes.
+    fn window(&self) -> usize;
+
+    /// Returns the slow start threshold in bytes.
+    fn ssthresh(&self) -> usize;
+
+    /// Updates the congestion window when new data are acknowledged.
+    fn on_ack(&mut self, mss: usize, sample: &AckSample);
+
+    /// Updates the congestion window when a loss is detected.
+    fn on_loss(&mut self, mss: usize, kind: LossKind, in_flight: usize);
+
+    /// Updates the congestion window when all data in flight at the time of the loss are
+    /// acknowledged.
+    fn on_recovery_end(&mut self) {}
+}
+
+/// The information about an acknowledgment of new data.
+struct AckSample {
+    now: Instant,
+    /// The number of newly acknowledged bytes.
+    acked: usize,
+    /// The number of bytes in flight before the acknowledgment.
+    prior_in_flight: usize,
+    /// The number of bytes in flight after the acknowledgment.
+    in_flight: usize,
+    /// The total number of bytes delivered to the peer.
+    delivered: u64,
+    /// The round-trip time measured by the acknowledgment, in microseconds.
+    rtt_us: Option<u64>,
+    /// The smoothed round-trip time, in microseconds.
+    srtt_us: Option<u64>,
+    /// Whether the connection is recovering from a loss.
+    is_in_recovery: bool,
+}
+
+impl AckSample {
+    /// Checks whether the congestion window limits the sending rate.
+    ///
+    /// If the application does not send enough data to fill the congestion window, growing the
+    /// congestion window makes no sense because the window is not validated by the network.
+    fn is_window_limited(&self, window: usize) -> bool {
+        self.prior_in_flight * 2 >= window
+    }
+}
+
+/// The way how a loss is detected.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum LossKind {
+    /// The loss is detected by duplicate acknowledgments.
+    FastRetransmit,
+    /// The loss is detected by a retransmission timeout.
+    Timeout,
+}
+
+/// The initial congestion window in segments.
+///
+/// Reference: <https://datatracker.ietf.org/doc/html/rfc6928>
+const INITIAL_WINDOW_SEGMENTS: usize = 10;
+
+/// The default maximum segment size if the peer does not specify one.
+///
+/// Reference: <https://datatracker.ietf.org/doc/html/rfc9293#section-3.7.1>
+const DEFAULT_MSS: usize = 536;
+
+/// The number of duplicate acknowledgments that trigger a fast retransmission.
+const DUP_ACK_THRESHOLD: usize = 3;
+
+/// The congestion state of a TCP connection.
+pub(crate) struct CongestionState {
+    control: CongestionControl,
+    controller: Box<dyn Controller>,
+    /// The maximum segment size advertised by the peer.
+    mss: usize,
+    /// The window scale advertised by the peer.
+    remote_win_shift: u8,
+    /// Whether the SYN segment of the peer has been seen.
+    has_remote_syn: bool,
+    /// The sequence numbers of the sent data.
+    seq: Option<SentSeq>,
+    /// The number of consecutive duplicate acknowledgments.
+    dup_acks: usize,
+    /// The ongoing loss recovery.
+    recovery: Option<Recovery>,
+    /// The segment that is being timed to measure the round-trip time.
+    rtt_probe: Option<(TcpSeqNumber, Instant)>,
+    rtt: RttEstimator,
+    /// The total number of bytes delivered to the peer.
+    delivered: u64,
+    /// The total number of retransmitted segments.
+    retransmits: usize,
+}
+
+#[derive(Clone, Copy)]
+struct SentSeq {
+    /// The oldest unacknowledged sequence number (`SND.UNA`).
+    una: TcpSeqNumber,
+    /// The highest sequence number that has been sent, plus one.
+    max: TcpSeqNumber,
+}
+
+impl SentSeq {
+    fn in_flight(&self) -> usize {
+        self.max - self.una
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Recovery {
+    /// The recovery ends when all data up to this sequence number are acknowledged.
+    point: TcpSeqNumber,
+    kind: LossKind,
+}
+
+impl CongestionState {
+    pub(crate) fn new(control: CongestionControl) -> Self {
+        Self {
+            control,
+            controller: control.new_controller(INITIAL_WINDOW_SEGMENTS * DEFAULT_MSS, usize::MAX),
+            mss: DEFAULT_MSS,
+            remote_win_shift: 0,
+            has_remote_syn: false,
+            seq: None,
+            dup_acks: 0,
+            recovery: None,
+            rtt_probe: None,
+            rtt: RttEstimator::new(),
+            delivered: 0,
+            retransmits: 0,
+        }
+    }
+
+    /// Returns the congestion control algorithm.
+    pub(crate) fn control(&self) -> CongestionControl {
+        self.control
+    }
+
+    /// Switches to another congestion control algorithm.
+    ///
+    /// Like Linux, the congestion window and the slow start threshold are kept.
+    pub(crate) fn set_control(&mut self, control: CongestionControl) {
+        if self.control == control {
+            return;
+        }
+
+        self.control = control;
+        self.controller =
+            control.new_controller(self.controller.window(), self.controller.ssthresh());
+    }
+
+    /// Returns the congestion information.
+    pub(crate) fn info(&self) -> CongestionInfo {
+        let recovery_state = match self.recovery {
+            Some(Recovery {
+                kind: LossKind::FastRetransmit,
+                ..
+            }) => RecoveryState::Recovery,
+            Some(Recovery {
+                kind: LossKind::Timeout,
+                ..
+            }) => RecoveryState::Loss,
+            None if self.dup_acks > 0 => RecoveryState::Disorder,
+            None => RecoveryState::Open,
+        };
+
+        CongestionInfo {
+            control: self.control,
+            recovery_state,
+            mss: self.mss,
+            window: self.controller.window(),
+            ssthresh: self.controller.ssthresh(),
+            srtt: self.rtt.srtt_us.map(Duration::from_micros),
+            rttvar: self.rtt.rttvar_us.map(Duration::from_micros),
+            min_rtt: self.rtt.min_rtt_us.map(Duration::from_micros),
+            retransmits: self.retransmits,
+        }
+    }
+
+    /// Limits the receive window advertised by an incoming segment to the congestion window.
+    ///
+    /// This method must be called before the segment is processed by smoltcp.
+    pub(crate) fn clamp_window(&self, tcp_repr: &mut TcpRepr) {
+        // The window in a SYN segment is never scaled. It will be updated by later segments.
+        if tcp_repr.control == TcpControl::Syn || tcp_repr.ack_number.is_none() {
+            return;
+        }
+
+        // The window cannot be zero. Otherwise, smoltcp will start sending zero window probes.
+        let window =
+            (self.controller.window() >> self.remote_win_shift).clamp(1, u16::MAX as usize);
+        tcp_repr.window_len = tcp_repr.window_len.min(window as u16);
+    }
+
+    /// Updates the congestion state after an incoming segment is processed by smoltcp.
+    pub(crate) fn on_recv(&mut self, now: Instant, tcp_repr: &TcpRepr) {
+        if tcp_repr.control == TcpControl::Syn && !self.has_remote_syn {
+            self.on_remote_syn(tcp_repr);
+        }
+
+        if tcp_repr.control == TcpControl::Rst {
+            return;
+        }
+        let Some(ack) = tcp_repr.ack_number else {
+            return;
+        };
+
+        let seq = self.seq.get_or_insert(SentSeq { una: ack, max: ack });
+        // Ignore acknowledgments of old data or data that are never sent.
+        if ack < seq.una || ack > seq.max {
+            return;
+        }
+
+        let prior_in_flight = seq.in_flight();
+
+        if ack == seq.una {
+            // Like smoltcp, any acknowledgment that carries no data and acknowledges nothing is
+            // considered a duplicate acknowledgment if some data are in flight.
+            if tcp_repr.payload.is_empty() && prior_in_flight > 0 {
+                self.dup_acks += 1;
+                if self.dup_acks == DUP_ACK_THRESHOLD && self.recovery.is_none() {
+                    self.enter_recovery(LossKind::FastRetransmit);
+                }
+            }
+            return;
+        }
+
+        let acked = ack - seq.una;
+        seq.una = ack;
+        let in_flight = seq.in_flight();
+
+        self.dup_acks = 0;
+        self.delivered += acked as u64;
+
+        let rtt_us = match self.rtt_probe {
+            Some((end, sent_at)) if ack >= end => {
+                self.rtt_probe = None;
+                let rtt_us = (now - sent_at).total_micros();
+                self.rtt.sample(rtt_us);
+                Some(rtt_us)
+            }
+            _ => None,
+        };
+
+        if let Some(recovery) = self.recovery
+            && ack >= recovery.point
+        {
+            self.recovery = None;
+            self.controller.on_recovery_end();
+        }
+
+        let sample = AckSample {
+            now,
+            acked,
+            prior_in_flight,
+            in_flight,
+            delivered: self.delivered,
+            rtt_us,
+            srtt_us: self.rtt.srtt_us,
+            is_in_recovery: self.recovery.is_some(),
+        };
+        self.controller.on_ack(self.mss, &sample);
+    }
+
+    /// Updates the congestion state when an outgoing segment is generated by smoltcp.
+    pub(crate) fn on_send(&mut self, now: Instant, tcp_repr: &TcpRepr) {
+        if tcp_repr.control == TcpControl::Rst {
+            return;
+        }
+
+        let start = tcp_repr.seq_number;
+        let end = start + tcp_repr.segment_len();
+
+        let seq = self.seq.get_or_insert(SentSeq {
+            una: start,
+            max: start,
+        });
+        // Keep-alive segments carry data that have already been acknowledged.
+        if start == end || start < seq.una {
+            return;
+        }
+
+        let old_max = seq.max;
+        if end > old_max {
+            seq.max = end;
+        }
+
+        if start >= old_max {
+            // New data. Time it if no other segment is being timed.
+            if self.rtt_probe.is_none() {
+                self.rtt_probe = Some((end, now));
+            }
+            return;
+        }
+
+        // This is a retransmission. Following Karn's algorithm, the retransmitted segment cannot
+        // be used to measure the round-trip time.
+        self.rtt_probe = None;
+        self.retransmits += 1;
+
+        // smoltcp retransmits data either because of duplicate acknowledgments, which have been
+        // handled in `on_recv`, or because of a retransmission timeout.
+        if self.recovery.is_none() {
+            self.enter_recovery(LossKind::Timeout);
+        }
+    }
+
+    fn on_remote_syn(&mut self, tcp_repr: &TcpRepr) {
+        self.has_remote_syn = true;
+        self.mss = tcp_repr
+            .max_seg_size
+            .map_or(DEFAULT_MSS, |mss| mss as usize);
+        self.remote_win_shift = tcp_repr.window_scale.unwrap_or(0);
+        self.controller = self
+            .control
+            .new_controller(INITIAL_WINDOW_SEGMENTS * self.mss, usize::MAX);
+    }
+
+    fn enter_recovery(&mut self, kind: LossKind) {
+        let Some(seq) = self.seq else {
+            return;
+        };
+
+        self.recovery = Some(Recovery {
+            point: seq.max,
+            kind,
+        });
+        self.controller.on_loss(self.mss, kind, seq.in_flight());
+    }
+}
+
+/// An estimator of the round-trip time.
+///
+/// Reference: <https://datatracker.ietf.org/doc/html/rfc6298#section-2>
+struct RttEstimator {
+    srtt_us: Option<u64>,
+    rttvar_us: Option<u64>,
+    min_rtt_us: Option<u64>,
+}
+
+impl RttEstimator {
+    const fn new() -> Self {
+        Self {
+            srtt_us: None,
+            rttvar_us: None,
+            min_rtt_us: None,
+        }
+    }
+
+    fn sample(&mut self, rtt_us: u64) {
+        match (self.srtt_us, self.rttvar_us) {
+            (Some(srtt_us), Some(rttvar_us)) => {
+                let delta_us = srtt_us.abs_diff(rtt_us);
+                self.rttvar_us = Some((rttvar_us * 3 + delta_us) / 4);
+                self.srtt_us = Some((srtt_us * 7 + rtt_us) / 8);
+            }
+            _ => {
+                self.srtt_us = Some(rtt_us);
+                self.rttvar_us = Some(rtt_us / 2);
+            }
+        }
+
+        self.min_rtt_us = Some(
+            self.min_rtt_us
+                .map_or(rtt_us, |min_rtt_us| min_rtt_us.min(rtt_us)),
+        );
+    }
+}
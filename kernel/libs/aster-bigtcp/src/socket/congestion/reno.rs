@@ -0,0 +1,75 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::{AckSample, Controller, LossKind};
+
+/// The TCP Reno congestion control algorithm.
+///
+/// Reference: <https://datatracker.ietf.org/doc/html/rfc5681#section-3.1>
+pub(super) struct Reno {
+    window: usize,
+    ssthresh: usize,
+    /// The number of bytes acknowledged in congestion avoidance since the window was last grown.
+    acked_in_avoidance: usize,
+}
+
+impl Reno {
+    pub(super) fn new(window: usize, ssthresh: usize) -> Self {
+        Self {
+            window,
+            ssthresh,
+            acked_in_avoidance: 0,
+        }
+    }
+}
+
+impl Controller for Reno {
+    fn window(&self) -> usize {
+        self.window
+    }
+
+    fn ssthresh(&self) -> usize {
+        self.ssthresh
+    }
+
+    fn on_ack(&mut self, mss: usize, sample: &AckSample) {
+        if sample.is_in_recovery || !sample.is_window_limited(self.window) {
+            return;
+        }
+
+        let acked = slow_start(&mut self.window, self.ssthresh, sample.acked);
+        if acked == 0 {
+            return;
+        }
+
+        // In congestion avoidance, the window grows by one segment per round-trip time.
+        self.acked_in_avoidance += acked;
+        if self.acked_in_avoidance >= self.window {
+            self.acked_in_avoidance -= self.window;
+            self.window += mss;
+        }
+    }
+
+    fn on_loss(&mut self, mss: usize, kind: LossKind, _in_flight: usize) {
+        // Like Linux, the slow start threshold is computed from the congestion window instead of
+        // the amount of data in flight.
+        self.ssthresh = (self.window / 2).max(mss * 2);
+        self.window = match kind {
+            LossKind::FastRetransmit => self.ssthresh,
+            LossKind::Timeout => mss,
+        };
+        self.acked_in_avoidance = 0;
+    }
+}
+
+/// Grows the window in slow start and returns the number of acknowledged bytes that are left for
+/// congestion avoidance.
+pub(super) fn slow_start(window: &mut usize, ssthresh: usize, acked: usize) -> usize {
+    if *window >= ssthresh {
+        return acked;
+    }
+
+    let new_window = window.saturating_add(acked).min(ssthresh);
+    let left = acked - (new_window - *window);
+    *window = new_window;
+    left
+}
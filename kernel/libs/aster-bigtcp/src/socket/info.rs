@@ -0,0 +1,47 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Information about the sockets bound to an iface.
+//!
+//! The information allows the sockets to be reported to user space (e.g., via `/proc/net/tcp`)
+//! without going through their owners.
+
+use smoltcp::wire::IpEndpoint;
+
+use super::RawTcpSocketExt;
+use crate::ext::Ext;
+
+/// Information about a TCP socket.
+pub enum TcpSocketInfo<'a, E: Ext> {
+    Listener(TcpListenerInfo<'a, E>),
+    Connection(TcpConnectionInfo<'a, E>),
+}
+
+/// Information about a TCP listener.
+pub struct TcpListenerInfo<'a, E: Ext> {
+    pub local_endpoint: IpEndpoint,
+    /// The number of established connections that have not been accepted.
+    pub num_connected: usize,
+    /// The maximum number of established connections that have not been accepted.
+    pub max_conn: usize,
+    pub observer: Option<&'a E::TcpEventObserver>,
+}
+
+/// Information about a TCP connection.
+pub struct TcpConnectionInfo<'a, E: Ext> {
+    pub local_endpoint: IpEndpoint,
+    pub remote_endpoint: IpEndpoint,
+    /// The underlying socket, which can be queried for the state and the statistics.
+    pub socket: &'a RawTcpSocketExt<E>,
+    /// The observer, which is absent if the connection has not been accepted.
+    pub observer: Option<&'a E::TcpEventObserver>,
+}
+
+/// Information about a UDP socket.
+pub struct UdpSocketInfo<'a, E: Ext> {
+    pub local_endpoint: IpEndpoint,
+    /// The number of bytes in the receive buffer.
+    pub recv_queue: usize,
+    /// The number of bytes in the send buffer.
+    pub send_queue: usize,
+    pub observer: Option<&'a E::UdpEventObserver>,
+}
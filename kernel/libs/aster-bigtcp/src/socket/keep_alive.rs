@@ -0,0 +1,120 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! TCP keep-alive.
+//!
+//! smoltcp sends a keep-alive probe whenever nothing has been sent for a fixed interval, but it
+//! never gives up if the peer stops responding. We switch the smoltcp interval between the idle
+//! time and the probe interval, count the probes that are not answered, and abort the connection
+//! after too many probes are lost.
+
+use smoltcp::time::{Duration, Instant};
+
+use super::unbound::RawTcpSocket;
+
+/// The keep-alive configuration of a TCP connection.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct KeepAliveConfig {
+    /// The time that the connection stays idle before the first probe is sent.
+    pub idle: Duration,
+    /// The interval between two probes.
+    pub interval: Duration,
+    /// The number of unanswered probes after which the connection is dropped.
+    pub count: u8,
+}
+
+/// The keep-alive state of a TCP connection.
+pub(crate) struct KeepAliveState {
+    config: Option<KeepAliveConfig>,
+    /// The number of probes sent since the peer was last heard from.
+    probes_out: u8,
+    /// The time when the last segment was sent.
+    last_send: Option<Instant>,
+}
+
+impl KeepAliveState {
+    pub(crate) const fn new(config: Option<KeepAliveConfig>) -> Self {
+        Self {
+            config,
+            probes_out: 0,
+            last_send: None,
+        }
+    }
+
+    /// Returns the keep-alive configuration.
+    pub(crate) fn config(&self) -> Option<KeepAliveConfig> {
+        self.config
+    }
+
+    /// Returns the number of probes that have not been answered.
+    pub(crate) fn probes_out(&self) -> u8 {
+        self.probes_out
+    }
+
+    /// Updates the keep-alive configuration.
+    pub(crate) fn set_config(
+        &mut self,
+        socket: &mut RawTcpSocket,
+        config: Option<KeepAliveConfig>,
+    ) {
+        self.config = config;
+        self.probes_out = 0;
+        socket.set_keep_alive(config.map(|config| config.idle));
+    }
+
+    /// Updates the keep-alive state before smoltcp generates outgoing segments.
+    ///
+    /// This method returns whether the connection should be aborted because the peer has not
+    /// answered the probes.
+    pub(crate) fn before_dispatch(&mut self, socket: &mut RawTcpSocket, now: Instant) -> bool {
+        let (Some(config), Some(last_send)) = (self.config, self.last_send) else {
+            return false;
+        };
+
+        if self.probes_out >= config.count {
+            if now < last_send + config.interval {
+                return false;
+            }
+            self.probes_out = 0;
+            return true;
+        }
+
+        // smoltcp rewinds its keep-alive timer with the current interval after sending a probe. So
+        // if a probe is about to be sent, we need to switch to the probe interval in advance.
+        let interval = if self.probes_out > 0 || now >= last_send + config.idle {
+            config.interval
+        } else {
+            config.idle
+        };
+        if socket.keep_alive() != Some(interval) {
+            socket.set_keep_alive(Some(interval));
+        }
+
+        false
+    }
+
+    /// Updates the keep-alive state when an outgoing segment is generated by smoltcp.
+    pub(crate) fn on_send(&mut self, now: Instant, is_probe: bool) {
+        self.last_send = Some(now);
+
+        if is_probe && self.config.is_some() {
+            self.probes_out = self.probes_out.saturating_add(1);
+        }
+    }
+
+    /// Updates the keep-alive state before an incoming segment is processed by smoltcp.
+    ///
+    /// Any incoming segment shows that the peer is alive, so the probing stops. smoltcp rewinds
+    /// its keep-alive timer with the current interval when processing the segment, so we need to
+    /// switch back to the idle time in advance. Then the next probe will be sent after the idle
+    /// time.
+    pub(crate) fn before_recv(&mut self, socket: &mut RawTcpSocket) {
+        self.probes_out = 0;
+
+        let Some(config) = self.config else {
+            return;
+        };
+        if socket.keep_alive() != Some(config.idle) {
+            socket.set_keep_alive(Some(config.idle));
+        }
+    }
+}
@@ -1,16 +1,29 @@
 // SPDX-License-Identifier: MPL-2.0
 
 mod bound;
+mod congestion;
 mod event;
+mod info;
+mod keep_alive;
 mod option;
+mod stats;
 mod unbound;
 
 pub use bound::{
-    ConnectState, NeedIfacePoll, RawTcpSocketExt, TcpConnection, TcpListener, UdpSocket,
+    ConnectState, NeedIfacePoll, RawIpMetadata, RawIpSocket, RawTcpSocketExt, TcpConnection,
+    TcpListener, UdpIcmpError, UdpRecvInfo, UdpSocket,
 };
-pub(crate) use bound::{TcpConnectionBg, TcpListenerBg, TcpProcessResult, UdpSocketBg};
+pub(crate) use bound::{
+    RawIpSocketBg, TcpConnectionBg, TcpDispatchResult, TcpListenerBg, TcpProcessResult, UdpSocketBg,
+};
+pub use congestion::{CongestionControl, CongestionInfo, RecoveryState};
 pub use event::{SocketEventObserver, SocketEvents};
-pub use option::{RawTcpOption, RawTcpSetOption};
+pub use info::{TcpConnectionInfo, TcpListenerInfo, TcpSocketInfo, UdpSocketInfo};
+pub use keep_alive::KeepAliveConfig;
+pub use option::{RawTcpOption, RawTcpSetOption, UdpMulticastOption};
+pub use smoltcp::socket::{tcp::State as TcpState, udp::UdpMetadata};
+pub use stats::TcpStats;
 pub use unbound::{
-    RawUdpSocket, TCP_RECV_BUF_LEN, TCP_SEND_BUF_LEN, UDP_RECV_PAYLOAD_LEN, UDP_SEND_PAYLOAD_LEN,
+    RAW_RECV_PAYLOAD_LEN, RAW_SEND_PAYLOAD_LEN, RawUdpSocket, TCP_RECV_BUF_LEN, TCP_SEND_BUF_LEN,
+    UDP_RECV_PAYLOAD_LEN, UDP_SEND_PAYLOAD_LEN,
 };
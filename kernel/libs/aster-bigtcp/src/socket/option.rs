@@ -1,33 +1,42 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use smoltcp::time::Duration;
+use alloc::vec::Vec;
 
-use super::{NeedIfacePoll, unbound::RawTcpSocket};
+use smoltcp::wire::IpAddress;
+
+use super::{CongestionControl, KeepAliveConfig, NeedIfacePoll, unbound::RawTcpSocket};
 
 /// A trait defines setting socket options on a raw socket.
 pub trait RawTcpSetOption {
-    /// Sets the keep alive interval.
+    /// Sets the keep-alive configuration.
     ///
     /// Polling the iface _may_ be required after this method succeeds.
-    fn set_keep_alive(&self, interval: Option<Duration>) -> NeedIfacePoll;
+    fn set_keep_alive(&self, config: Option<KeepAliveConfig>) -> NeedIfacePoll;
 
     /// Enables or disables Nagle’s Algorithm.
     ///
     /// Polling the iface is _not_ required after this method succeeds.
     fn set_nagle_enabled(&self, enabled: bool);
+
+    /// Sets the congestion control algorithm.
+    ///
+    /// Polling the iface is _not_ required after this method succeeds.
+    fn set_congestion_control(&self, control: CongestionControl);
 }
 
 /// Socket options on a raw socket.
 pub struct RawTcpOption {
-    /// The keep alive interval.
-    pub keep_alive: Option<Duration>,
+    /// The keep-alive configuration.
+    pub keep_alive: Option<KeepAliveConfig>,
     /// Whether Nagle's algorithm is enabled.
     pub is_nagle_enabled: bool,
+    /// The congestion control algorithm.
+    pub congestion_control: CongestionControl,
 }
 
 impl RawTcpOption {
     pub(super) fn apply(&self, socket: &mut RawTcpSocket) {
-        socket.set_keep_alive(self.keep_alive);
+        socket.set_keep_alive(self.keep_alive.map(|config| config.idle));
         socket.set_nagle_enabled(self.is_nagle_enabled);
     }
 
@@ -36,3 +45,34 @@ impl RawTcpOption {
         to.set_nagle_enabled(from.nagle_enabled());
     }
 }
+
+/// Multicast options on a UDP socket.
+#[derive(Clone, Debug)]
+pub struct UdpMulticastOption {
+    /// The hop limit (or TTL) of outgoing multicast packets.
+    pub hop_limit: u8,
+    /// Whether outgoing multicast packets are looped back to local sockets.
+    pub is_loop_enabled: bool,
+    /// The multicast groups from which incoming multicast packets are accepted.
+    ///
+    /// If this is `None`, incoming multicast packets from all groups that the iface has joined
+    /// are accepted.
+    pub groups: Option<Vec<IpAddress>>,
+}
+
+impl UdpMulticastOption {
+    /// Creates the default multicast options.
+    pub const fn new() -> Self {
+        Self {
+            hop_limit: 1,
+            is_loop_enabled: true,
+            groups: None,
+        }
+    }
+}
+
+impl Default for UdpMulticastOption {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,164 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use smoltcp::{
+    time::Instant,
+    wire::{TcpControl, TcpRepr, TcpSeqNumber},
+};
+
+/// The statistics of a TCP connection.
+///
+/// The statistics follow the definitions of the corresponding fields in Linux's `tcp_info`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TcpStats {
+    /// The total number of segments received.
+    pub segs_in: u64,
+    /// The total number of segments sent.
+    pub segs_out: u64,
+    /// The total number of segments received that carry data.
+    pub data_segs_in: u64,
+    /// The total number of segments sent that carry data.
+    pub data_segs_out: u64,
+    /// The total number of data bytes sent, including retransmissions.
+    pub bytes_sent: u64,
+    /// The total number of data bytes retransmitted.
+    pub bytes_retrans: u64,
+    /// The total number of bytes acknowledged by the peer.
+    pub bytes_acked: u64,
+    /// The total number of bytes received in order.
+    pub bytes_received: u64,
+    /// The number of segments retransmitted since the last acknowledgment of new data.
+    pub unrecovered_retrans: u32,
+    /// The time when data were last sent.
+    pub last_data_sent: Option<Instant>,
+    /// The time when data were last received.
+    pub last_data_recv: Option<Instant>,
+    /// The time when an acknowledgment was last received.
+    pub last_ack_recv: Option<Instant>,
+}
+
+/// A collector of [`TcpStats`].
+pub(crate) struct StatsCollector {
+    stats: TcpStats,
+    /// The oldest unacknowledged sequence number and the highest sequence number sent, plus one.
+    snd: Option<(TcpSeqNumber, TcpSeqNumber)>,
+    /// The first sequence number of the peer's data and the next sequence number to receive.
+    rcv: Option<(TcpSeqNumber, TcpSeqNumber)>,
+}
+
+impl StatsCollector {
+    pub(crate) fn new() -> Self {
+        Self {
+            stats: TcpStats::default(),
+            snd: None,
+            rcv: None,
+        }
+    }
+
+    /// Returns the statistics.
+    pub(crate) fn stats(&self) -> TcpStats {
+        self.stats
+    }
+
+    /// Checks whether an outgoing segment is a keep-alive probe.
+    ///
+    /// A keep-alive probe carries data that have already been acknowledged by the peer.
+    pub(crate) fn is_keep_alive(&self, tcp_repr: &TcpRepr) -> bool {
+        let Some((una, _)) = self.snd else {
+            return false;
+        };
+
+        tcp_repr.control == TcpControl::None
+            && !tcp_repr.payload.is_empty()
+            && tcp_repr.seq_number + tcp_repr.payload.len() <= una
+    }
+
+    /// Updates the statistics after an incoming segment is processed by smoltcp.
+    pub(crate) fn on_recv(&mut self, now: Instant, tcp_repr: &TcpRepr) {
+        let stats = &mut self.stats;
+
+        stats.segs_in += 1;
+        if !tcp_repr.payload.is_empty() {
+            stats.data_segs_in += 1;
+            stats.last_data_recv = Some(now);
+        }
+
+        if tcp_repr.control == TcpControl::Rst {
+            return;
+        }
+
+        if let Some(ack) = tcp_repr.ack_number {
+            stats.last_ack_recv = Some(now);
+
+            if let Some((ref mut una, max)) = self.snd
+                && ack > *una
+                && ack <= max
+            {
+                stats.bytes_acked += (ack - *una) as u64;
+                stats.unrecovered_retrans = 0;
+                *una = ack;
+            }
+        }
+
+        if tcp_repr.control == TcpControl::Syn {
+            // The SYN flag is not counted as received data.
+            if self.rcv.is_none() {
+                let start = tcp_repr.seq_number + 1;
+                self.rcv = Some((start, start));
+            }
+            return;
+        }
+
+        // Like Linux, the FIN flag is counted as received data.
+        if let Some((start, ref mut next)) = self.rcv {
+            let end = tcp_repr.seq_number + tcp_repr.segment_len();
+            if tcp_repr.seq_number <= *next && end > *next {
+                *next = end;
+                stats.bytes_received = (*next - start) as u64;
+            }
+        }
+    }
+
+    /// Updates the statistics when an outgoing segment is generated by smoltcp.
+    pub(crate) fn on_send(&mut self, now: Instant, tcp_repr: &TcpRepr) {
+        if tcp_repr.control == TcpControl::Rst {
+            return;
+        }
+
+        let is_keep_alive = self.is_keep_alive(tcp_repr);
+        let stats = &mut self.stats;
+
+        stats.segs_out += 1;
+
+        // The acknowledgment number may cover data that are received out of order.
+        if let Some((start, next)) = self.rcv.as_mut()
+            && let Some(ack) = tcp_repr.ack_number
+            && ack > *next
+        {
+            *next = ack;
+            stats.bytes_received = (*next - *start) as u64;
+        }
+
+        if is_keep_alive {
+            return;
+        }
+
+        let start = tcp_repr.seq_number;
+        let end = start + tcp_repr.segment_len();
+        let (_, max) = self.snd.get_or_insert((start, start));
+
+        let payload_len = tcp_repr.payload.len();
+        if payload_len > 0 {
+            stats.data_segs_out += 1;
+            stats.bytes_sent += payload_len as u64;
+            stats.last_data_sent = Some(now);
+        }
+
+        if start < *max && start < end {
+            stats.bytes_retrans += payload_len.min(*max - start) as u64;
+            stats.unrecovered_retrans = stats.unrecovered_retrans.saturating_add(1);
+        }
+        if end > *max {
+            *max = end;
+        }
+    }
+}
@@ -2,8 +2,11 @@
 
 use alloc::{boxed::Box, vec};
 
+use smoltcp::wire::{IpProtocol, IpVersion};
+
 pub(super) type RawTcpSocket = smoltcp::socket::tcp::Socket<'static>;
 pub type RawUdpSocket = smoltcp::socket::udp::Socket<'static>;
+pub(super) type RawRawSocket = smoltcp::socket::raw::Socket<'static>;
 
 pub(super) fn new_tcp_socket() -> Box<RawTcpSocket> {
     let raw_tcp_socket = {
@@ -30,6 +33,25 @@ pub(super) fn new_udp_socket() -> Box<RawUdpSocket> {
     Box::new(raw_udp_socket)
 }
 
+pub(super) fn new_raw_socket(
+    ip_version: IpVersion,
+    ip_protocol: Option<IpProtocol>,
+) -> Box<RawRawSocket> {
+    let raw_raw_socket = {
+        let metadata = smoltcp::socket::raw::PacketMetadata::EMPTY;
+        let rx_buffer = smoltcp::socket::raw::PacketBuffer::new(
+            vec![metadata; RAW_METADATA_LEN],
+            vec![0u8; RAW_RECV_PAYLOAD_LEN],
+        );
+        let tx_buffer = smoltcp::socket::raw::PacketBuffer::new(
+            vec![metadata; RAW_METADATA_LEN],
+            vec![0u8; RAW_SEND_PAYLOAD_LEN],
+        );
+        RawRawSocket::new(Some(ip_version), ip_protocol, rx_buffer, tx_buffer)
+    };
+    Box::new(raw_raw_socket)
+}
+
 // TCP socket buffer sizes:
 //
 // According to
@@ -49,4 +71,9 @@ pub const TCP_SEND_BUF_LEN: usize = 65536 * 2;
 // UDP socket buffer sizes:
 pub const UDP_SEND_PAYLOAD_LEN: usize = 65536;
 pub const UDP_RECV_PAYLOAD_LEN: usize = 65536;
-const UDP_METADATA_LEN: usize = 256;
+pub(super) const UDP_METADATA_LEN: usize = 256;
+
+// Raw socket buffer sizes (including the IP headers):
+pub const RAW_SEND_PAYLOAD_LEN: usize = 65536;
+pub const RAW_RECV_PAYLOAD_LEN: usize = 65536;
+const RAW_METADATA_LEN: usize = 256;
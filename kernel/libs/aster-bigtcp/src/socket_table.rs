@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: MPL-2.0
 
-//! This module defines the socket table, which manages all TCP and UDP sockets,
+//! This module defines the socket table, which manages all TCP, UDP, and raw IP sockets,
 //! for efficiently inserting, looking up, and removing sockets.
 
 use alloc::{boxed::Box, sync::Arc, vec::Vec};
@@ -12,7 +12,7 @@ use smoltcp::wire::{IpAddress, IpEndpoint, IpListenEndpoint};
 
 use crate::{
     ext::Ext,
-    socket::{TcpConnectionBg, TcpListenerBg, UdpSocketBg},
+    socket::{RawIpSocketBg, TcpConnectionBg, TcpListenerBg, UdpSocketBg},
     wire::PortNum,
 };
 
@@ -83,6 +83,14 @@ impl ConnectionKey {
     pub(crate) const fn hash(&self) -> SocketHash {
         self.hash
     }
+
+    pub(crate) const fn local_endpoint(&self) -> IpEndpoint {
+        IpEndpoint::new(self.local_addr, self.local_port)
+    }
+
+    pub(crate) const fn remote_endpoint(&self) -> IpEndpoint {
+        IpEndpoint::new(self.remote_addr, self.remote_port)
+    }
 }
 
 impl From<(IpEndpoint, IpEndpoint)> for ConnectionKey {
@@ -147,7 +155,7 @@ const fn hash_addr_port(addr: IpAddress, port: PortNum) -> SocketHash {
     }
 }
 
-/// The socket table manages TCP and UDP sockets.
+/// The socket table manages TCP, UDP, and raw IP sockets.
 ///
 /// Unlike the Linux inet hashtable, which is shared across a single network namespace,
 /// this table is currently limited to a single interface.
@@ -167,6 +175,9 @@ pub(crate) struct SocketTable<E: Ext> {
     // Note that multiple UDP sockets can be bound to the same address,
     // so we cannot use (addr, port) as a _unique_ key for UDP sockets.
     udp_sockets: Vec<Arc<UdpSocketBg<E>>>,
+    // Raw IP sockets are not keyed by addresses or ports. Every incoming packet is offered to all
+    // of them.
+    raw_sockets: Vec<Arc<RawIpSocketBg<E>>>,
 }
 
 // On Linux, the number of buckets is determined at runtime based on the available memory.
@@ -192,10 +203,13 @@ impl<E: Ext> SocketTable<E> {
 
         let udp_sockets = Vec::new();
 
+        let raw_sockets = Vec::new();
+
         Self {
             listener_buckets,
             connection_buckets,
             udp_sockets,
+            raw_sockets,
         }
     }
 
@@ -261,6 +275,16 @@ impl<E: Ext> SocketTable<E> {
         self.udp_sockets.push(udp_socket);
     }
 
+    pub(crate) fn insert_raw_socket(&mut self, raw_socket: Arc<RawIpSocketBg<E>>) {
+        debug_assert!(
+            !self
+                .raw_sockets
+                .iter()
+                .any(|socket| Arc::ptr_eq(socket, &raw_socket))
+        );
+        self.raw_sockets.push(raw_socket);
+    }
+
     pub(crate) fn lookup_listener(&self, key: &ListenerKey) -> Option<&Arc<TcpListenerBg<E>>> {
         let bucket = {
             let hash = key.hash();
@@ -290,6 +314,18 @@ impl<E: Ext> SocketTable<E> {
             .find(|connection| connection.connection_key() == key)
     }
 
+    pub(crate) fn listener_iter(&self) -> impl Iterator<Item = &Arc<TcpListenerBg<E>>> {
+        self.listener_buckets
+            .iter()
+            .flat_map(|bucket| bucket.listeners.iter())
+    }
+
+    pub(crate) fn connection_iter(&self) -> impl Iterator<Item = &Arc<TcpConnectionBg<E>>> {
+        self.connection_buckets
+            .iter()
+            .flat_map(|bucket| bucket.connections.iter())
+    }
+
     pub(crate) fn remove_listener(&mut self, key: &ListenerKey) -> Option<Arc<TcpListenerBg<E>>> {
         let bucket = {
             let hash = key.hash();
@@ -339,6 +375,21 @@ impl<E: Ext> SocketTable<E> {
     pub(crate) fn udp_socket_iter(&self) -> impl Iterator<Item = &Arc<UdpSocketBg<E>>> {
         self.udp_sockets.iter()
     }
+
+    pub(crate) fn remove_raw_socket(
+        &mut self,
+        socket: &Arc<RawIpSocketBg<E>>,
+    ) -> Option<Arc<RawIpSocketBg<E>>> {
+        let index = self
+            .raw_sockets
+            .iter()
+            .position(|raw_socket| Arc::ptr_eq(raw_socket, socket))?;
+        Some(self.raw_sockets.swap_remove(index))
+    }
+
+    pub(crate) fn raw_socket_iter(&self) -> impl Iterator<Item = &Arc<RawIpSocketBg<E>>> {
+        self.raw_sockets.iter()
+    }
 }
 
 impl<E: Ext> Default for SocketTable<E> {
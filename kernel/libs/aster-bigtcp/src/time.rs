@@ -1,3 +1,8 @@
 // SPDX-License-Identifier: MPL-2.0
 
 pub use smoltcp::time::{Duration, Instant};
+
+/// Returns the current time of the network stack.
+pub fn now() -> Instant {
+    crate::iface::get_network_timestamp()
+}
@@ -1,7 +1,26 @@
 // SPDX-License-Identifier: MPL-2.0
 
 pub use smoltcp::wire::{
-    EthernetAddress, IpAddress, IpCidr, IpEndpoint, Ipv4Address, Ipv4Cidr, Ipv6Address, Ipv6Cidr,
+    DHCP_CLIENT_PORT, DHCP_SERVER_PORT, DhcpMessageType, DhcpPacket, DhcpRepr, ETHERNET_HEADER_LEN,
+    EthernetAddress, EthernetFrame, IPV4_HEADER_LEN, IPV6_HEADER_LEN, Icmpv6Packet, IpAddress,
+    IpCidr, IpEndpoint, IpProtocol, Ipv4Address, Ipv4Cidr, Ipv6Address, Ipv6Cidr, Ipv6Packet,
+    NdiscPrefixInfoFlags, NdiscRepr, RawHardwareAddress, UDP_HEADER_LEN,
 };
 
 pub type PortNum = u16;
+
+/// The all-systems multicast address.
+///
+/// Reference: <https://datatracker.ietf.org/doc/html/rfc1112#section-4>
+pub const IPV4_ALL_SYSTEMS: Ipv4Address = Ipv4Address::new(224, 0, 0, 1);
+/// The all-routers multicast address.
+///
+/// Reference: <https://datatracker.ietf.org/doc/html/rfc2236#section-3>
+pub const IPV4_ALL_ROUTERS: Ipv4Address = Ipv4Address::new(224, 0, 0, 2);
+
+/// The link-local all-nodes multicast address.
+///
+/// Reference: <https://datatracker.ietf.org/doc/html/rfc4291#section-2.7.1>
+pub const IPV6_LINK_LOCAL_ALL_NODES: Ipv6Address = Ipv6Address::new(0xff02, 0, 0, 0, 0, 0, 0, 1);
+/// The link-local all-routers multicast address.
+pub const IPV6_LINK_LOCAL_ALL_ROUTERS: Ipv6Address = Ipv6Address::new(0xff02, 0, 0, 0, 0, 0, 0, 2);
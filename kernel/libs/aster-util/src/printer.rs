@@ -69,7 +69,10 @@ impl<'a, 'b> VmPrinter<'a, 'b> {
         Write::write_fmt(self, args).map_err(|_| VmPrinterError::PageFault)
     }
 
-    fn write_bytes(&mut self, bytes: &[u8]) -> core::fmt::Result {
+    /// Writes raw bytes to the underlying writer.
+    ///
+    /// Unlike [`Self::write_fmt`], the bytes do not have to be valid UTF-8.
+    pub fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), VmPrinterError> {
         if self.bytes_to_skip >= bytes.len() {
             self.bytes_to_skip -= bytes.len();
             return Ok(());
@@ -84,7 +87,7 @@ impl<'a, 'b> VmPrinter<'a, 'b> {
         let written_len = self
             .writer
             .write_fallible(&mut reader)
-            .map_err(|_| core::fmt::Error)?;
+            .map_err(|_| VmPrinterError::PageFault)?;
 
         self.bytes_written += written_len;
 
@@ -94,7 +97,7 @@ impl<'a, 'b> VmPrinter<'a, 'b> {
 
 impl Write for VmPrinter<'_, '_> {
     fn write_str(&mut self, s: &str) -> core::fmt::Result {
-        self.write_bytes(s.as_bytes())
+        self.write_bytes(s.as_bytes()).map_err(|_| core::fmt::Error)
     }
 }
 
@@ -104,7 +107,7 @@ impl<'a, 'b> From<&'a mut VmWriter<'b>> for VmPrinter<'a, 'b> {
     }
 }
 
-/// An error returned by [`VmPrinter::write_fmt`].
+/// An error returned by [`VmPrinter::write_fmt`] and [`VmPrinter::write_bytes`].
 pub enum VmPrinterError {
     /// Page fault occurred.
     PageFault,
@@ -156,4 +159,17 @@ mod test {
         assert_eq!(printer.bytes_written(), 0);
         assert_eq!(buf[0], 0);
     }
+
+    #[ktest]
+    fn write_raw_bytes() {
+        let mut buf = [0u8; 64];
+        let mut writer = VmWriter::from(buf.as_mut_bytes()).to_fallible();
+        let mut printer = VmPrinter::new_skip(&mut writer, 1);
+
+        let res = printer.write_bytes(b"@\xff\x80");
+        assert!(res.is_ok());
+
+        assert_eq!(printer.bytes_written(), 2);
+        assert_eq!(&buf[..2], b"\xff\x80");
+    }
 }
@@ -0,0 +1,49 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! LoongArch core dump ABI.
+
+use ostd::arch::cpu::context::UserContext;
+
+use super::cpu::SigContext;
+use crate::{prelude::*, process::posix_thread::SuppUserContext};
+
+/// The ELF machine type (`EM_LOONGARCH`) of core dumps.
+pub const ELF_MACHINE: u16 = 258;
+
+/// Mirror of Linux's `elf_gregset_t` for LoongArch.
+///
+/// The registers are laid out as `r0`-`r31`, `orig_a0`, `csr_era`, `csr_badv`, followed by
+/// reserved words.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.18/source/arch/loongarch/include/asm/elf.h>
+pub type ElfGregSet = [usize; 45];
+
+const ORIG_A0_INDEX: usize = 32;
+const CSR_ERA_INDEX: usize = 33;
+
+/// Builds the general-purpose registers saved in an `NT_PRSTATUS` note.
+pub fn elf_gregset(
+    user_ctx: &UserContext,
+    _supp_user_ctx: &SuppUserContext,
+    orig_syscall_ret: Option<usize>,
+) -> ElfGregSet {
+    let mut sig_context = SigContext::default();
+    sig_context.copy_user_regs_from(user_ctx);
+
+    let mut regs: ElfGregSet = FromZeros::new_zeroed();
+    // The signal context stores `pc` followed by `r0`-`r31`.
+    let gp_regs_bytes = &sig_context.as_bytes()[size_of::<usize>()..];
+    regs[..ORIG_A0_INDEX]
+        .as_mut_bytes()
+        .copy_from_slice(&gp_regs_bytes[..ORIG_A0_INDEX * size_of::<usize>()]);
+    regs[ORIG_A0_INDEX] = orig_syscall_ret.unwrap_or(0);
+    regs[CSR_ERA_INDEX] = sig_context.pc;
+    regs
+}
+
+/// Builds the content of an `NT_PRFPREG` note.
+///
+/// The FPU context is not saved on LoongArch yet, so no note is produced.
+pub fn elf_fpregset(_supp_user_ctx: &SuppUserContext) -> Option<Vec<u8>> {
+    None
+}
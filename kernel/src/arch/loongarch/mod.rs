@@ -1,5 +1,6 @@
 // SPDX-License-Identifier: MPL-2.0
 
+pub mod coredump;
 pub mod cpu;
 mod power;
 pub mod signal;
@@ -0,0 +1,45 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! RISC-V core dump ABI.
+
+use ostd::arch::cpu::context::{FpuContext, UserContext};
+
+use super::cpu::SigContext;
+use crate::{prelude::*, process::posix_thread::SuppUserContext};
+
+/// The ELF machine type (`EM_RISCV`) of core dumps.
+pub const ELF_MACHINE: u16 = 243;
+
+/// Mirror of Linux's `elf_gregset_t` for RISC-V.
+///
+/// The registers are laid out as `struct user_regs_struct`, i.e., `pc` followed by `x1`-`x31`.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.18/source/arch/riscv/include/uapi/asm/ptrace.h>
+pub type ElfGregSet = [usize; 32];
+
+/// Builds the general-purpose registers saved in an `NT_PRSTATUS` note.
+pub fn elf_gregset(
+    user_ctx: &UserContext,
+    _supp_user_ctx: &SuppUserContext,
+    _orig_syscall_ret: Option<usize>,
+) -> ElfGregSet {
+    // The signal context starts with the same layout as `struct user_regs_struct`.
+    let mut sig_context = SigContext::default();
+    sig_context.copy_user_regs_from(user_ctx);
+
+    let mut regs = ElfGregSet::default();
+    let len = regs.as_bytes().len();
+    regs.as_mut_bytes()
+        .copy_from_slice(&sig_context.as_bytes()[..len]);
+    regs
+}
+
+/// Builds the content of an `NT_PRFPREG` note.
+///
+/// Only the D extension, whose layout is `struct __riscv_d_ext_state`, is supported.
+pub fn elf_fpregset(supp_user_ctx: &SuppUserContext) -> Option<Vec<u8>> {
+    match supp_user_ctx.fpu().get() {
+        fpu_context @ FpuContext::D(_) => Some(fpu_context.as_bytes().to_vec()),
+        FpuContext::F(_) | FpuContext::Q(_) | FpuContext::None => None,
+    }
+}
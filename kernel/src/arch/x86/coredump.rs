@@ -0,0 +1,43 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! x86-64 core dump ABI.
+
+use ostd::arch::cpu::context::UserContext;
+
+use super::ptrace::CUserRegsStruct;
+use crate::{prelude::*, process::posix_thread::SuppUserContext};
+
+/// The ELF machine type (`EM_X86_64`) of core dumps.
+pub const ELF_MACHINE: u16 = 62;
+
+/// Mirror of Linux's `elf_gregset_t` for x86-64.
+pub type ElfGregSet = CUserRegsStruct;
+
+/// Builds the general-purpose registers saved in an `NT_PRSTATUS` note.
+///
+/// `orig_syscall_ret` should be `None` if the thread is not in a system call.
+pub fn elf_gregset(
+    user_ctx: &UserContext,
+    supp_user_ctx: &SuppUserContext,
+    orig_syscall_ret: Option<usize>,
+) -> ElfGregSet {
+    let mut regs = CUserRegsStruct::from_regs(
+        user_ctx.general_regs(),
+        supp_user_ctx.fs_base().get(),
+        supp_user_ctx.gs_base().get(),
+    );
+    regs.orig_rax = orig_syscall_ret.unwrap_or(usize::MAX);
+    regs
+}
+
+/// Builds the content of an `NT_PRFPREG` note.
+///
+/// The note contains the legacy region of the `FXSAVE` area (i.e., `struct user_i387_struct`).
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.18/source/arch/x86/include/asm/user_64.h>
+pub fn elf_fpregset(supp_user_ctx: &SuppUserContext) -> Option<Vec<u8>> {
+    const USER_I387_STRUCT_SIZE: usize = 512;
+
+    let fpu_context = supp_user_ctx.fpu().get();
+    Some(fpu_context.as_bytes()[..USER_I387_STRUCT_SIZE].to_vec())
+}
@@ -12,6 +12,7 @@ use super::registry::char::{MajorIdOwner, acquire_major};
 mod hwrng;
 #[cfg(all(target_arch = "x86_64", feature = "cvm_guest"))]
 pub mod tdxguest;
+mod tun;
 
 static MISC_MAJOR: Once<MajorIdOwner> = Once::new();
 
@@ -19,6 +20,7 @@ pub(super) fn init_in_first_kthread() {
     MISC_MAJOR.call_once(|| acquire_major(MajorId::new(10)).unwrap());
 
     hwrng::init_in_first_kthread();
+    tun::init_in_first_kthread();
 
     #[cfg(target_arch = "x86_64")]
     ostd::if_tdx_enabled!({
@@ -0,0 +1,211 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! TUN/TAP misc-device support.
+//!
+//! This module registers the `/dev/net/tun` character device. Each file opened from the device
+//! can be attached to a TUN/TAP network interface via the `TUNSETIFF` ioctl.
+
+use device_id::{DeviceId, MinorId};
+
+use crate::{
+    device::{Device, DeviceType, DevtmpfsInodeMeta, registry::char},
+    events::IoEvents,
+    fs::{
+        file::{PerOpenFileOps, StatusFlags, mkmod},
+        vfs::inode::FileOps,
+    },
+    net::iface::{IFNAMSIZ, TunFlags, TunInfo, TunQueue},
+    prelude::*,
+    process::{
+        posix_thread::AsPosixThread,
+        signal::{PollHandle, Pollable},
+    },
+    util::ioctl::{RawIoctl, dispatch_ioctl},
+};
+
+const TUN_MINOR: u32 = 200;
+
+mod ioctl_defs {
+    use super::CIfReq;
+    use crate::util::ioctl::{InData, MisencodedData, OutData, PassByVal, ioc};
+
+    // Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/if_tun.h>
+
+    pub(super) type SetIff      = ioc!(TUNSETIFF,      b'T', 202, MisencodedData<InData<i32>, CIfReq>);
+    pub(super) type SetPersist  = ioc!(TUNSETPERSIST,  b'T', 203, InData<i32, PassByVal>);
+    pub(super) type GetFeatures = ioc!(TUNGETFEATURES, b'T', 207, OutData<u32>);
+    pub(super) type GetIff      = ioc!(TUNGETIFF,      b'T', 210, MisencodedData<OutData<u32>, CIfReq>);
+    pub(super) type SetQueue    = ioc!(TUNSETQUEUE,    b'T', 217, MisencodedData<InData<i32>, CIfReq>);
+}
+
+/// The `struct ifreq` used by the TUN/TAP ioctls.
+///
+/// Only the `ifr_flags` member of the union is used.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/if.h#L234>
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct CIfReq {
+    name: [u8; IFNAMSIZ],
+    flags: u16,
+    _padding: [u8; 22],
+}
+
+impl From<TunInfo> for CIfReq {
+    fn from(info: TunInfo) -> Self {
+        let mut ifreq = CIfReq::new_zeroed();
+
+        let name = info.name.as_bytes();
+        let len = name.len().min(IFNAMSIZ - 1);
+        ifreq.name[..len].copy_from_slice(&name[..len]);
+        ifreq.flags = info.flags.bits();
+
+        ifreq
+    }
+}
+
+/// The `/dev/net/tun` device.
+#[derive(Debug)]
+struct TunCharDevice {
+    id: DeviceId,
+}
+
+impl TunCharDevice {
+    fn new() -> Arc<Self> {
+        let major = super::MISC_MAJOR.get().unwrap().get();
+        let minor = MinorId::new(TUN_MINOR);
+
+        let id = DeviceId::new(major, minor);
+        Arc::new(Self { id })
+    }
+}
+
+impl Device for TunCharDevice {
+    fn type_(&self) -> DeviceType {
+        DeviceType::Char
+    }
+
+    fn id(&self) -> DeviceId {
+        self.id
+    }
+
+    fn devtmpfs_meta(&self) -> Option<DevtmpfsInodeMeta<'_>> {
+        Some(DevtmpfsInodeMeta::with_mode("net/tun", mkmod!(a+rw)))
+    }
+
+    fn open(&self) -> Result<Box<dyn PerOpenFileOps>> {
+        Ok(Box::new(TunFile {
+            queue: TunQueue::new(),
+        }))
+    }
+}
+
+/// A file handle opened from `/dev/net/tun`.
+struct TunFile {
+    queue: Arc<TunQueue>,
+}
+
+impl Pollable for TunFile {
+    fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.queue
+            .pollee()
+            .poll_with(mask, poller, || self.queue.check_io_events())
+    }
+}
+
+impl FileOps for TunFile {
+    fn read_at(
+        &self,
+        _offset: usize,
+        writer: &mut VmWriter,
+        status_flags: StatusFlags,
+    ) -> Result<usize> {
+        if status_flags.contains(StatusFlags::O_NONBLOCK) {
+            self.queue.try_read(writer)
+        } else {
+            self.wait_events(IoEvents::IN, None, || self.queue.try_read(writer))
+        }
+    }
+
+    fn write_at(
+        &self,
+        _offset: usize,
+        reader: &mut VmReader,
+        _status_flags: StatusFlags,
+    ) -> Result<usize> {
+        self.queue.write(reader)
+    }
+}
+
+impl PerOpenFileOps for TunFile {
+    fn check_seekable(&self) -> Result<()> {
+        return_errno_with_message!(Errno::ESPIPE, "the inode is a TUN/TAP file");
+    }
+
+    fn is_offset_aware(&self) -> bool {
+        false
+    }
+
+    fn ioctl(&self, raw_ioctl: RawIoctl) -> Result<i32> {
+        use ioctl_defs::*;
+
+        let current = current_thread!();
+        let posix_thread = current.as_posix_thread().unwrap();
+
+        dispatch_ioctl!(match raw_ioctl {
+            cmd @ SetIff => {
+                let mut ifreq = cmd.read()?;
+                ifreq.name[IFNAMSIZ - 1] = 0;
+
+                let name = CStr::from_bytes_until_nul(&ifreq.name).unwrap();
+                let flags = TunFlags::from_bits_truncate(ifreq.flags);
+                let net_ns = posix_thread
+                    .ns_proxy()
+                    .lock()
+                    .as_ref()
+                    .unwrap()
+                    .net_ns()
+                    .clone();
+
+                let info = self.queue.set_iff(name, flags, &net_ns, posix_thread)?;
+                cmd.write(&CIfReq::from(info))?;
+            }
+            cmd @ GetIff => {
+                let info = self.queue.info()?;
+                cmd.write(&CIfReq::from(info))?;
+            }
+            cmd @ SetPersist => {
+                self.queue.set_persist(cmd.get() != 0)?;
+            }
+            cmd @ GetFeatures => {
+                cmd.write(&(TunFlags::supported_features().bits() as u32))?;
+            }
+            cmd @ SetQueue => {
+                let flags = TunFlags::from_bits_truncate(cmd.read()?.flags);
+                if flags.contains(TunFlags::IFF_ATTACH_QUEUE) {
+                    self.queue.attach_queue(posix_thread)?;
+                } else if flags.contains(TunFlags::IFF_DETACH_QUEUE) {
+                    self.queue.detach_queue()?;
+                } else {
+                    return_errno_with_message!(Errno::EINVAL, "the queue operation is invalid");
+                }
+            }
+            _ => return_errno_with_message!(
+                Errno::ENOTTY,
+                "the ioctl command is not supported by TUN/TAP files"
+            ),
+        });
+
+        Ok(0)
+    }
+}
+
+impl Drop for TunFile {
+    fn drop(&mut self) {
+        self.queue.release();
+    }
+}
+
+pub(super) fn init_in_first_kthread() {
+    char::register(TunCharDevice::new()).unwrap();
+}
@@ -0,0 +1,784 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::{fmt::Display, mem};
+
+use super::{
+    IORING_CQ_EVENTFD_DISABLED, IORING_MAX_CQ_ENTRIES, IORING_MAX_ENTRIES, IORING_SQ_CQ_OVERFLOW,
+    IoUringCqe, IoUringEnterFlags, IoUringFeatures, IoUringOp, IoUringParams, IoUringSetupFlags,
+    SqeFlags,
+    op::{Issue, Request, Wait, errno_to_res},
+    ring::Rings,
+};
+use crate::{
+    events::IoEvents,
+    fs::{
+        file::{
+            AccessMode, CreationFlags, FileLike, Mappable,
+            file_table::{FdFlags, RawFileDesc, get_file_fast},
+        },
+        pseudofs::AnonInodeFs,
+        vfs::path::Path,
+    },
+    prelude::*,
+    process::signal::{PollHandle, Pollable, Pollee, Poller},
+    syscall::EventFile,
+    time::clocks::MonotonicClock,
+    util::ioctl::RawIoctl,
+};
+
+/// A file-like object that represents an io_uring instance.
+///
+/// Lock order: `submission` -> `resources` -> `completion`.
+pub struct IoUringFile {
+    rings: Rings,
+    setup_flags: IoUringSetupFlags,
+    submission: Mutex<Submission>,
+    completion: SpinLock<Completion>,
+    resources: Mutex<Resources>,
+    pollee: Pollee,
+    /// The pseudo path associated with this io_uring file.
+    pseudo_path: Path,
+}
+
+/// The submission state, which is only accessed while requests are being submitted or performed.
+pub(super) struct Submission {
+    /// The SQ head, which is only written by the kernel.
+    sq_head: u32,
+    /// The chains of linked requests whose first requests are pending.
+    pub(super) pending: Vec<PendingChain>,
+    /// The number of completed requests, excluding timeouts.
+    pub(super) num_completed: u64,
+}
+
+/// A chain of linked requests whose first request is pending.
+pub(super) struct PendingChain {
+    pub(super) request: Request,
+    pub(super) wait: Wait,
+    pub(super) rest: VecDeque<Request>,
+}
+
+struct Completion {
+    /// The CQ tail, which is only written by the kernel.
+    cq_tail: u32,
+    /// The CQEs that cannot be placed in the CQ because it is full.
+    overflow: VecDeque<IoUringCqe>,
+}
+
+#[derive(Default)]
+struct Resources {
+    files: Option<Vec<Option<Arc<dyn FileLike>>>>,
+    buffers: Option<Vec<(Vaddr, usize)>>,
+    eventfd: Option<RegisteredEventfd>,
+}
+
+struct RegisteredEventfd {
+    file: Arc<dyn FileLike>,
+    is_async_only: bool,
+}
+
+/// The maximum number of registered files.
+const IORING_MAX_FIXED_FILES: usize = 1 << 20;
+/// The maximum number of registered buffers.
+const IORING_MAX_REG_BUFFERS: usize = 1 << 14;
+/// The maximum length of a registered buffer.
+const IORING_MAX_REG_BUFFER_LEN: usize = 1 << 30;
+
+/// The value that skips updating a registered file.
+const IORING_REGISTER_FILES_SKIP: RawFileDesc = -2;
+
+impl IoUringFile {
+    /// Creates a new io_uring instance with at least `entries` SQ entries.
+    ///
+    /// On success, `params` is updated with the actual parameters of the io_uring instance.
+    pub fn new(entries: u32, params: &mut IoUringParams) -> Result<Arc<Self>> {
+        let setup_flags = IoUringSetupFlags::from_bits(params.flags)
+            .ok_or_else(|| Error::with_message(Errno::EINVAL, "the setup flags are invalid"))?;
+
+        // TODO: Support the other setup flags, especially `IORING_SETUP_SQPOLL`.
+        const SUPPORTED_FLAGS: IoUringSetupFlags = IoUringSetupFlags::CQSIZE
+            .union(IoUringSetupFlags::CLAMP)
+            .union(IoUringSetupFlags::SUBMIT_ALL)
+            .union(IoUringSetupFlags::COOP_TASKRUN)
+            .union(IoUringSetupFlags::TASKRUN_FLAG)
+            .union(IoUringSetupFlags::SINGLE_ISSUER)
+            .union(IoUringSetupFlags::DEFER_TASKRUN)
+            .union(IoUringSetupFlags::NO_SQARRAY);
+        if !SUPPORTED_FLAGS.contains(setup_flags) {
+            return_errno_with_message!(Errno::EINVAL, "the setup flags are not supported");
+        }
+        if setup_flags.contains(IoUringSetupFlags::TASKRUN_FLAG)
+            && !setup_flags
+                .intersects(IoUringSetupFlags::COOP_TASKRUN | IoUringSetupFlags::DEFER_TASKRUN)
+        {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "IORING_SETUP_TASKRUN_FLAG requires IORING_SETUP_COOP_TASKRUN"
+            );
+        }
+        if setup_flags.contains(IoUringSetupFlags::DEFER_TASKRUN)
+            && !setup_flags.contains(IoUringSetupFlags::SINGLE_ISSUER)
+        {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "IORING_SETUP_DEFER_TASKRUN requires IORING_SETUP_SINGLE_ISSUER"
+            );
+        }
+
+        // Reference: <https://elixir.bootlin.com/linux/v6.18/source/io_uring/io_uring.c#L3747>
+        let is_clamped = setup_flags.contains(IoUringSetupFlags::CLAMP);
+        if entries == 0 {
+            return_errno_with_message!(Errno::EINVAL, "the number of entries is zero");
+        }
+        if entries > IORING_MAX_ENTRIES && !is_clamped {
+            return_errno_with_message!(Errno::EINVAL, "the number of entries is too large");
+        }
+        let sq_entries = entries.min(IORING_MAX_ENTRIES).next_power_of_two();
+
+        let cq_entries = if setup_flags.contains(IoUringSetupFlags::CQSIZE) {
+            if params.cq_entries == 0 {
+                return_errno_with_message!(Errno::EINVAL, "the number of CQ entries is zero");
+            }
+            if params.cq_entries > IORING_MAX_CQ_ENTRIES && !is_clamped {
+                return_errno_with_message!(Errno::EINVAL, "the number of CQ entries is too large");
+            }
+            let cq_entries = params
+                .cq_entries
+                .min(IORING_MAX_CQ_ENTRIES)
+                .next_power_of_two();
+            if cq_entries < sq_entries {
+                return_errno_with_message!(
+                    Errno::EINVAL,
+                    "the number of CQ entries is less than the number of SQ entries"
+                );
+            }
+            cq_entries
+        } else {
+            sq_entries * 2
+        };
+
+        let has_sq_array = !setup_flags.contains(IoUringSetupFlags::NO_SQARRAY);
+        let rings = Rings::new(sq_entries, cq_entries, has_sq_array)?;
+
+        params.sq_entries = sq_entries;
+        params.cq_entries = cq_entries;
+        params.features = (IoUringFeatures::NODROP
+            | IoUringFeatures::RW_CUR_POS
+            | IoUringFeatures::FAST_POLL
+            | IoUringFeatures::POLL_32BITS
+            | IoUringFeatures::CQE_SKIP
+            | IoUringFeatures::LINKED_FILE)
+            .bits();
+        params.sq_off = rings.sq_offsets();
+        params.cq_off = rings.cq_offsets();
+
+        let pseudo_path = AnonInodeFs::new_path(|_| "anon_inode:[io_uring]".to_string());
+
+        Ok(Arc::new(Self {
+            rings,
+            setup_flags,
+            submission: Mutex::new(Submission {
+                sq_head: 0,
+                pending: Vec::new(),
+                num_completed: 0,
+            }),
+            completion: SpinLock::new(Completion {
+                cq_tail: 0,
+                overflow: VecDeque::new(),
+            }),
+            resources: Mutex::new(Resources::default()),
+            pollee: Pollee::new(),
+            pseudo_path,
+        }))
+    }
+
+    /// Submits the requests in the SQ and waits for the completions.
+    ///
+    /// This method returns the number of submitted requests.
+    pub fn enter(
+        &self,
+        to_submit: u32,
+        min_complete: u32,
+        flags: IoUringEnterFlags,
+        ctx: &Context,
+    ) -> Result<usize> {
+        // TODO: Support extended arguments and registered rings.
+        if flags.intersects(IoUringEnterFlags::EXT_ARG | IoUringEnterFlags::REGISTERED_RING) {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "IORING_ENTER_EXT_ARG and IORING_ENTER_REGISTERED_RING are not supported"
+            );
+        }
+
+        let num_submitted = {
+            let mut submission = self.submission.lock();
+            self.flush_overflow();
+            let num_submitted = self.submit(&mut submission, to_submit, ctx);
+            self.run_pending(&mut submission, ctx);
+            num_submitted
+        };
+
+        if flags.contains(IoUringEnterFlags::GETEVENTS) && min_complete > 0 {
+            let min_complete = min_complete.min(self.rings.cq_entries());
+            // Like Linux, an error (e.g., `EINTR`) is reported only if no request is submitted.
+            if let Err(err) = self.wait_completions(min_complete as usize, ctx)
+                && num_submitted == 0
+            {
+                return Err(err);
+            }
+        }
+
+        Ok(num_submitted)
+    }
+
+    /// Consumes the SQEs in the SQ and issues the requests.
+    fn submit(&self, submission: &mut Submission, to_submit: u32, ctx: &Context) -> usize {
+        let num_available = self
+            .rings
+            .sq_tail()
+            .wrapping_sub(submission.sq_head)
+            .min(self.rings.sq_entries());
+        let num_to_submit = num_available.min(to_submit);
+
+        let mut num_submitted = 0;
+        // The requests in the current link.
+        let mut chain = VecDeque::new();
+        // Whether the current link has failed to be prepared.
+        let mut is_chain_failed = false;
+
+        while num_submitted < num_to_submit as usize {
+            let pos = submission.sq_head;
+            submission.sq_head = pos.wrapping_add(1);
+
+            let Some(sqe) = self.rings.read_sqe(pos) else {
+                // Like Linux, an SQE with an invalid index is dropped and stops the submission.
+                self.rings.inc_sq_dropped();
+                break;
+            };
+            num_submitted += 1;
+
+            let flags = SqeFlags::from_bits_truncate(sqe.flags);
+            let is_linked = flags.intersects(SqeFlags::IO_LINK | SqeFlags::IO_HARDLINK);
+
+            if is_chain_failed {
+                // The remaining requests in a failed link are canceled.
+                self.post_cqe(sqe.user_data, errno_to_res(Errno::ECANCELED), false);
+                submission.num_completed += 1;
+            } else {
+                match Request::new(sqe) {
+                    Ok(request) => {
+                        chain.push_back(request);
+                        if !is_linked {
+                            self.run_chain(submission, mem::take(&mut chain), false, ctx);
+                        }
+                    }
+                    Err(err) => {
+                        debug!("failed to prepare the io_uring request: {:?}", err);
+                        self.cancel_chain(submission, mem::take(&mut chain), false);
+                        self.post_cqe(sqe.user_data, errno_to_res(err.error()), false);
+                        submission.num_completed += 1;
+                        is_chain_failed = true;
+                    }
+                }
+            }
+
+            if is_chain_failed && !is_linked {
+                is_chain_failed = false;
+                // Like Linux, the submission stops at the failed request unless
+                // `IORING_SETUP_SUBMIT_ALL` is specified.
+                if !self.setup_flags.contains(IoUringSetupFlags::SUBMIT_ALL) {
+                    break;
+                }
+            }
+        }
+
+        // Like Linux, an incomplete link at the end of the submission is issued as is.
+        if !chain.is_empty() {
+            self.run_chain(submission, chain, false, ctx);
+        }
+
+        self.rings.set_sq_head(submission.sq_head);
+        self.pollee.notify(IoEvents::OUT);
+
+        num_submitted
+    }
+
+    /// Issues the requests in a chain one by one.
+    fn run_chain(
+        &self,
+        submission: &mut Submission,
+        mut chain: VecDeque<Request>,
+        is_async: bool,
+        ctx: &Context,
+    ) {
+        while let Some(request) = chain.pop_front() {
+            let res = match self.issue(&request, submission, ctx) {
+                Issue::Done(res) => res,
+                Issue::Wait(wait) => {
+                    submission.pending.push(PendingChain {
+                        request,
+                        wait,
+                        rest: chain,
+                    });
+                    return;
+                }
+            };
+
+            if !self.complete(submission, &request, res, is_async) {
+                self.cancel_chain(submission, chain, is_async);
+                return;
+            }
+        }
+    }
+
+    /// Retries the pending requests until no more progress can be made.
+    fn run_pending(&self, submission: &mut Submission, ctx: &Context) {
+        loop {
+            let mut has_progress = false;
+
+            let mut index = 0;
+            while index < submission.pending.len() {
+                let res = match &submission.pending[index].wait {
+                    Wait::Events(file, mask) => {
+                        if file.poll(*mask, None).is_empty() {
+                            index += 1;
+                            continue;
+                        }
+                        None
+                    }
+                    Wait::Timeout { deadline, target } => {
+                        if target.is_some_and(|target| submission.num_completed >= target) {
+                            Some(0)
+                        } else if MonotonicClock::get().read_time() >= *deadline {
+                            Some(errno_to_res(Errno::ETIME))
+                        } else {
+                            index += 1;
+                            continue;
+                        }
+                    }
+                };
+
+                has_progress = true;
+                let PendingChain { request, rest, .. } = submission.pending.remove(index);
+                match res {
+                    Some(res) => {
+                        if self.complete(submission, &request, res, true) {
+                            self.run_chain(submission, rest, true, ctx);
+                        } else {
+                            self.cancel_chain(submission, rest, true);
+                        }
+                    }
+                    None => {
+                        let mut chain = rest;
+                        chain.push_front(request);
+                        self.run_chain(submission, chain, true, ctx);
+                    }
+                }
+            }
+
+            if !has_progress {
+                break;
+            }
+        }
+    }
+
+    /// Completes a request with the result.
+    ///
+    /// This method returns whether the next request in the link should be issued.
+    pub(super) fn complete(
+        &self,
+        submission: &mut Submission,
+        request: &Request,
+        res: i32,
+        is_async: bool,
+    ) -> bool {
+        let flags = request.flags();
+        let is_failed = request.is_failed(res);
+
+        if is_failed || !flags.contains(SqeFlags::CQE_SKIP_SUCCESS) {
+            self.post_cqe(request.user_data(), res, is_async);
+        }
+        if request.op() != IoUringOp::Timeout {
+            submission.num_completed += 1;
+        }
+
+        !is_failed || flags.contains(SqeFlags::IO_HARDLINK)
+    }
+
+    /// Cancels the requests in a chain that will never be issued.
+    pub(super) fn cancel_chain(
+        &self,
+        submission: &mut Submission,
+        chain: VecDeque<Request>,
+        is_async: bool,
+    ) {
+        for request in chain {
+            self.complete(
+                submission,
+                &request,
+                errno_to_res(Errno::ECANCELED),
+                is_async,
+            );
+        }
+    }
+
+    /// Posts a CQE to the CQ, or to the overflow list if the CQ is full.
+    fn post_cqe(&self, user_data: u64, res: i32, is_async: bool) {
+        let cqe = IoUringCqe {
+            user_data,
+            res,
+            flags: 0,
+        };
+
+        {
+            let mut completion = self.completion.lock();
+            if completion.overflow.is_empty() && !self.is_cq_full(completion.cq_tail) {
+                self.rings.write_cqe(completion.cq_tail, &cqe);
+                completion.cq_tail = completion.cq_tail.wrapping_add(1);
+                self.rings.set_cq_tail(completion.cq_tail);
+            } else {
+                completion.overflow.push_back(cqe);
+                self.rings
+                    .set_sq_flags(self.rings.sq_flags() | IORING_SQ_CQ_OVERFLOW);
+            }
+        }
+
+        self.pollee.notify(IoEvents::IN);
+        self.signal_eventfd(is_async);
+    }
+
+    /// Moves the CQEs in the overflow list to the CQ, as long as there is free space.
+    fn flush_overflow(&self) {
+        let mut completion = self.completion.lock();
+        if completion.overflow.is_empty() {
+            return;
+        }
+
+        while !self.is_cq_full(completion.cq_tail)
+            && let Some(cqe) = completion.overflow.pop_front()
+        {
+            self.rings.write_cqe(completion.cq_tail, &cqe);
+            completion.cq_tail = completion.cq_tail.wrapping_add(1);
+        }
+        self.rings.set_cq_tail(completion.cq_tail);
+
+        if completion.overflow.is_empty() {
+            self.rings
+                .set_sq_flags(self.rings.sq_flags() & !IORING_SQ_CQ_OVERFLOW);
+        }
+    }
+
+    fn is_cq_full(&self, cq_tail: u32) -> bool {
+        cq_tail.wrapping_sub(self.rings.cq_head()) >= self.rings.cq_entries()
+    }
+
+    /// Returns the number of CQEs that have not been consumed by the user space.
+    fn num_ready(&self) -> usize {
+        let completion = self.completion.lock();
+        let num_in_cq = completion
+            .cq_tail
+            .wrapping_sub(self.rings.cq_head())
+            .min(self.rings.cq_entries());
+        num_in_cq as usize + completion.overflow.len()
+    }
+
+    fn signal_eventfd(&self, is_async: bool) {
+        if self.rings.cq_flags() & IORING_CQ_EVENTFD_DISABLED != 0 {
+            return;
+        }
+
+        let resources = self.resources.lock();
+        let Some(eventfd) = resources.eventfd.as_ref() else {
+            return;
+        };
+        if eventfd.is_async_only && !is_async {
+            return;
+        }
+        if let Some(event_file) = eventfd.file.downcast_ref::<EventFile>() {
+            event_file.signal();
+        }
+    }
+
+    /// Waits until there are at least `min_complete` CQEs that are not consumed.
+    fn wait_completions(&self, min_complete: usize, ctx: &Context) -> Result<()> {
+        loop {
+            let poller = {
+                let mut submission = self.submission.lock();
+                self.flush_overflow();
+                self.run_pending(&mut submission, ctx);
+                if self.num_ready() >= min_complete {
+                    return Ok(());
+                }
+
+                // Wait until the earliest timeout expires.
+                let now = MonotonicClock::get().read_time();
+                let timeout = submission
+                    .pending
+                    .iter()
+                    .filter_map(|chain| match &chain.wait {
+                        Wait::Timeout { deadline, .. } => Some(deadline.saturating_sub(now)),
+                        Wait::Events(..) => None,
+                    })
+                    .min();
+                let mut poller = Poller::new(timeout.as_ref());
+
+                // Wait until a new CQE is posted or a pending request can be retried.
+                self.pollee
+                    .register_poller(poller.as_handle_mut(), IoEvents::IN);
+                let mut is_any_ready = false;
+                for chain in submission.pending.iter() {
+                    if let Wait::Events(file, mask) = &chain.wait
+                        && !file.poll(*mask, Some(poller.as_handle_mut())).is_empty()
+                    {
+                        is_any_ready = true;
+                    }
+                }
+                if is_any_ready || self.num_ready() >= min_complete {
+                    continue;
+                }
+
+                poller
+            };
+
+            match poller.wait() {
+                Err(err) if err.error() == Errno::ETIME => (),
+                res => res?,
+            }
+        }
+    }
+
+    fn check_io_events(&self) -> IoEvents {
+        let mut events = IoEvents::empty();
+
+        if self.num_ready() > 0 {
+            events |= IoEvents::IN;
+        }
+
+        let num_queued = self.rings.sq_tail().wrapping_sub(self.rings.sq_head());
+        if num_queued < self.rings.sq_entries() {
+            events |= IoEvents::OUT;
+        }
+
+        events
+    }
+
+    /// Registers the files so that they can be used by the requests with `IOSQE_FIXED_FILE`.
+    ///
+    /// A file descriptor of `-1` leaves the slot empty, which can be updated later.
+    pub fn register_files(&self, fds: &[RawFileDesc], ctx: &Context) -> Result<()> {
+        if fds.is_empty() || fds.len() > IORING_MAX_FIXED_FILES {
+            return_errno_with_message!(Errno::EINVAL, "the number of files is invalid");
+        }
+
+        let files = fds
+            .iter()
+            .map(|&fd| {
+                if fd == -1 {
+                    Ok(None)
+                } else {
+                    get_registrable_file(fd, ctx).map(Some)
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut resources = self.resources.lock();
+        if resources.files.is_some() {
+            return_errno_with_message!(Errno::EBUSY, "the files are already registered");
+        }
+        resources.files = Some(files);
+
+        Ok(())
+    }
+
+    /// Unregisters the files.
+    pub fn unregister_files(&self) -> Result<()> {
+        if self.resources.lock().files.take().is_none() {
+            return_errno_with_message!(Errno::ENXIO, "no files are registered");
+        }
+        Ok(())
+    }
+
+    /// Updates the registered files starting from the slot at `offset`.
+    ///
+    /// This method returns the number of updated slots.
+    pub fn update_files(&self, offset: u32, fds: &[RawFileDesc], ctx: &Context) -> Result<usize> {
+        let mut resources = self.resources.lock();
+        let Some(files) = resources.files.as_mut() else {
+            return_errno_with_message!(Errno::ENXIO, "no files are registered");
+        };
+
+        let offset = offset as usize;
+        if offset
+            .checked_add(fds.len())
+            .is_none_or(|end| end > files.len())
+        {
+            return_errno_with_message!(Errno::EINVAL, "the slots are out of the registered files");
+        }
+
+        for (index, &fd) in fds.iter().enumerate() {
+            let slot = &mut files[offset + index];
+            match fd {
+                IORING_REGISTER_FILES_SKIP => (),
+                -1 => *slot = None,
+                // Like Linux, the slots before the invalid file descriptor remain updated.
+                fd => match get_registrable_file(fd, ctx) {
+                    Ok(file) => *slot = Some(file),
+                    Err(_) if index > 0 => return Ok(index),
+                    Err(err) => return Err(err),
+                },
+            }
+        }
+
+        Ok(fds.len())
+    }
+
+    /// Registers the buffers so that they can be used by `IORING_OP_READ_FIXED` and
+    /// `IORING_OP_WRITE_FIXED`.
+    pub fn register_buffers(&self, buffers: Vec<(Vaddr, usize)>) -> Result<()> {
+        if buffers.is_empty() || buffers.len() > IORING_MAX_REG_BUFFERS {
+            return_errno_with_message!(Errno::EINVAL, "the number of buffers is invalid");
+        }
+        for &(base, len) in buffers.iter() {
+            if (base == 0 && len != 0)
+                || len > IORING_MAX_REG_BUFFER_LEN
+                || base.checked_add(len).is_none()
+            {
+                return_errno_with_message!(Errno::EFAULT, "the buffer is invalid");
+            }
+        }
+
+        let mut resources = self.resources.lock();
+        if resources.buffers.is_some() {
+            return_errno_with_message!(Errno::EBUSY, "the buffers are already registered");
+        }
+        resources.buffers = Some(buffers);
+
+        Ok(())
+    }
+
+    /// Unregisters the buffers.
+    pub fn unregister_buffers(&self) -> Result<()> {
+        if self.resources.lock().buffers.take().is_none() {
+            return_errno_with_message!(Errno::ENXIO, "no buffers are registered");
+        }
+        Ok(())
+    }
+
+    /// Registers an eventfd that is signaled when CQEs are posted.
+    ///
+    /// If `is_async_only` is true, the eventfd is only signaled for the requests that are not
+    /// completed inline during the submission.
+    pub fn register_eventfd(
+        &self,
+        fd: RawFileDesc,
+        is_async_only: bool,
+        ctx: &Context,
+    ) -> Result<()> {
+        let file = {
+            let mut file_table = ctx.thread_local.borrow_file_table_mut();
+            get_file_fast!(&mut file_table, fd.try_into()?).into_owned()
+        };
+        if file.downcast_ref::<EventFile>().is_none() {
+            return_errno_with_message!(Errno::EINVAL, "the file is not an eventfd");
+        }
+
+        let mut resources = self.resources.lock();
+        if resources.eventfd.is_some() {
+            return_errno_with_message!(Errno::EBUSY, "an eventfd is already registered");
+        }
+        resources.eventfd = Some(RegisteredEventfd {
+            file,
+            is_async_only,
+        });
+
+        Ok(())
+    }
+
+    /// Unregisters the eventfd.
+    pub fn unregister_eventfd(&self) -> Result<()> {
+        if self.resources.lock().eventfd.take().is_none() {
+            return_errno_with_message!(Errno::ENXIO, "no eventfd is registered");
+        }
+        Ok(())
+    }
+
+    pub(super) fn fixed_file(&self, index: usize) -> Option<Arc<dyn FileLike>> {
+        self.resources.lock().files.as_ref()?.get(index)?.clone()
+    }
+
+    pub(super) fn fixed_buffer(&self, index: usize) -> Option<(Vaddr, usize)> {
+        self.resources.lock().buffers.as_ref()?.get(index).copied()
+    }
+}
+
+/// Gets the file that can be registered to an io_uring instance.
+fn get_registrable_file(fd: RawFileDesc, ctx: &Context) -> Result<Arc<dyn FileLike>> {
+    let mut file_table = ctx.thread_local.borrow_file_table_mut();
+    let file = get_file_fast!(&mut file_table, fd.try_into()?).into_owned();
+
+    // Like Linux, io_uring instances cannot be registered, which avoids reference cycles.
+    // Reference: <https://elixir.bootlin.com/linux/v6.18/source/io_uring/rsrc.c#L591>
+    if file.downcast_ref::<IoUringFile>().is_some() {
+        return_errno_with_message!(Errno::EBADF, "io_uring instances cannot be registered");
+    }
+
+    Ok(file)
+}
+
+impl Pollable for IoUringFile {
+    fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        // The user space consumes CQEs and places SQEs without notifying the pollee, so the cached
+        // events may be stale.
+        self.pollee.invalidate();
+        self.pollee
+            .poll_with(mask, poller, || self.check_io_events())
+    }
+}
+
+impl FileLike for IoUringFile {
+    fn ioctl(&self, _raw_ioctl: RawIoctl) -> Result<i32> {
+        return_errno_with_message!(Errno::ENOTTY, "io_uring files do not support ioctl");
+    }
+
+    fn mappable(&self) -> Result<Mappable> {
+        Ok(Mappable::Vmo(self.rings.vmo().clone()))
+    }
+
+    fn access_mode(&self) -> AccessMode {
+        // Reference: <https://elixir.bootlin.com/linux/v6.18/source/io_uring/io_uring.c#L3620>
+        AccessMode::O_RDWR
+    }
+
+    fn path(&self) -> &Path {
+        &self.pseudo_path
+    }
+
+    fn dump_proc_fdinfo(self: Arc<Self>, fd_flags: FdFlags) -> Box<dyn Display> {
+        struct FdInfo {
+            inner: Arc<IoUringFile>,
+            fd_flags: FdFlags,
+        }
+
+        impl Display for FdInfo {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                let mut flags = self.inner.status_flags().bits() | self.inner.access_mode() as u32;
+                if self.fd_flags.contains(FdFlags::CLOEXEC) {
+                    flags |= CreationFlags::O_CLOEXEC.bits();
+                }
+
+                writeln!(f, "pos:\t{}", 0)?;
+                writeln!(f, "flags:\t0{:o}", flags)?;
+                writeln!(f, "mnt_id:\t{}", AnonInodeFs::mount_node().id())?;
+                writeln!(f, "ino:\t{}", AnonInodeFs::shared_inode().ino())?;
+                writeln!(f, "SqMask:\t0x{:x}", self.inner.rings.sq_entries() - 1)?;
+                writeln!(f, "CqMask:\t0x{:x}", self.inner.rings.cq_entries() - 1)?;
+
+                Ok(())
+            }
+        }
+
+        Box::new(FdInfo {
+            inner: self,
+            fd_flags,
+        })
+    }
+}
@@ -0,0 +1,187 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The io_uring asynchronous I/O interface.
+//!
+//! An io_uring instance consists of a submission queue (SQ) and a completion queue (CQ), both of
+//! which are rings shared between the kernel and the user space. The user space places submission
+//! queue entries (SQEs) in the SQ and calls `io_uring_enter` to submit them. The kernel performs
+//! the requests and places completion queue entries (CQEs) in the CQ.
+//!
+//! Requests are performed in the context of the thread that calls `io_uring_enter`. A request that
+//! cannot be completed immediately (e.g., reading from an empty socket) is kept pending until its
+//! file is ready. Pending requests are retried whenever the user space calls `io_uring_enter`, and
+//! especially while it waits for completions with `IORING_ENTER_GETEVENTS`. This is similar to
+//! the Linux behavior with `IORING_SETUP_DEFER_TASKRUN`.
+//!
+//! Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/io_uring.h>
+
+mod file;
+mod op;
+mod ring;
+
+pub use file::IoUringFile;
+pub use ring::{CqRingOffsets, SqRingOffsets};
+
+use crate::prelude::*;
+
+/// The parameters of an io_uring instance.
+///
+/// This is `struct io_uring_params` in Linux.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+pub struct IoUringParams {
+    pub sq_entries: u32,
+    pub cq_entries: u32,
+    pub flags: u32,
+    pub sq_thread_cpu: u32,
+    pub sq_thread_idle: u32,
+    pub features: u32,
+    pub wq_fd: u32,
+    pub resv: [u32; 3],
+    pub sq_off: SqRingOffsets,
+    pub cq_off: CqRingOffsets,
+}
+
+/// A submission queue entry.
+///
+/// This is `struct io_uring_sqe` in Linux. The unions in the Linux definition are flattened to
+/// the names of their most common members.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub struct IoUringSqe {
+    pub opcode: u8,
+    pub flags: u8,
+    pub ioprio: u16,
+    pub fd: i32,
+    /// The file offset, or `addr2`.
+    pub off: u64,
+    pub addr: u64,
+    pub len: u32,
+    /// The opcode-specific flags (e.g., `rw_flags`, `poll32_events`, and `timeout_flags`).
+    pub op_flags: u32,
+    pub user_data: u64,
+    pub buf_index: u16,
+    pub personality: u16,
+    /// The index of the file to install, or `splice_fd_in`.
+    pub file_index: u32,
+    pub addr3: u64,
+    pub __pad2: u64,
+}
+
+/// A completion queue entry.
+///
+/// This is `struct io_uring_cqe` in Linux.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub struct IoUringCqe {
+    pub user_data: u64,
+    pub res: i32,
+    pub flags: u32,
+}
+
+/// The supported opcodes.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, TryFromInt)]
+pub enum IoUringOp {
+    Nop = 0,
+    Readv = 1,
+    Writev = 2,
+    Fsync = 3,
+    ReadFixed = 4,
+    WriteFixed = 5,
+    PollAdd = 6,
+    PollRemove = 7,
+    Timeout = 11,
+    TimeoutRemove = 12,
+    Accept = 13,
+    AsyncCancel = 14,
+    Connect = 16,
+    Openat = 18,
+    Close = 19,
+    Read = 22,
+    Write = 23,
+    Send = 26,
+    Recv = 27,
+}
+
+impl IoUringOp {
+    /// The last supported opcode.
+    pub const LAST: Self = Self::Recv;
+}
+
+bitflags! {
+    /// The flags of `io_uring_setup`.
+    pub struct IoUringSetupFlags: u32 {
+        const IOPOLL = 1 << 0;
+        const SQPOLL = 1 << 1;
+        const SQ_AFF = 1 << 2;
+        const CQSIZE = 1 << 3;
+        const CLAMP = 1 << 4;
+        const ATTACH_WQ = 1 << 5;
+        const R_DISABLED = 1 << 6;
+        const SUBMIT_ALL = 1 << 7;
+        const COOP_TASKRUN = 1 << 8;
+        const TASKRUN_FLAG = 1 << 9;
+        const SQE128 = 1 << 10;
+        const CQE32 = 1 << 11;
+        const SINGLE_ISSUER = 1 << 12;
+        const DEFER_TASKRUN = 1 << 13;
+        const NO_MMAP = 1 << 14;
+        const REGISTERED_FD_ONLY = 1 << 15;
+        const NO_SQARRAY = 1 << 16;
+    }
+}
+
+bitflags! {
+    /// The features reported by `io_uring_setup`.
+    pub struct IoUringFeatures: u32 {
+        const SINGLE_MMAP = 1 << 0;
+        const NODROP = 1 << 1;
+        const SUBMIT_STABLE = 1 << 2;
+        const RW_CUR_POS = 1 << 3;
+        const CUR_PERSONALITY = 1 << 4;
+        const FAST_POLL = 1 << 5;
+        const POLL_32BITS = 1 << 6;
+        const SQPOLL_NONFIXED = 1 << 7;
+        const EXT_ARG = 1 << 8;
+        const NATIVE_WORKERS = 1 << 9;
+        const RSRC_TAGS = 1 << 10;
+        const CQE_SKIP = 1 << 11;
+        const LINKED_FILE = 1 << 12;
+    }
+}
+
+bitflags! {
+    /// The flags of `io_uring_enter`.
+    pub struct IoUringEnterFlags: u32 {
+        const GETEVENTS = 1 << 0;
+        const SQ_WAKEUP = 1 << 1;
+        const SQ_WAIT = 1 << 2;
+        const EXT_ARG = 1 << 3;
+        const REGISTERED_RING = 1 << 4;
+    }
+}
+
+bitflags! {
+    /// The flags of an SQE.
+    struct SqeFlags: u8 {
+        const FIXED_FILE = 1 << 0;
+        const IO_DRAIN = 1 << 1;
+        const IO_LINK = 1 << 2;
+        const IO_HARDLINK = 1 << 3;
+        const ASYNC = 1 << 4;
+        const BUFFER_SELECT = 1 << 5;
+        const CQE_SKIP_SUCCESS = 1 << 6;
+    }
+}
+
+// The flags in the SQ ring that are written by the kernel.
+const IORING_SQ_CQ_OVERFLOW: u32 = 1 << 1;
+
+// The flags in the CQ ring that are written by the user space.
+const IORING_CQ_EVENTFD_DISABLED: u32 = 1 << 0;
+
+/// The maximum number of SQ entries.
+const IORING_MAX_ENTRIES: u32 = 32768;
+/// The maximum number of CQ entries.
+const IORING_MAX_CQ_ENTRIES: u32 = 2 * IORING_MAX_ENTRIES;
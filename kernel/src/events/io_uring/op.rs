@@ -0,0 +1,607 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The preparation and the execution of io_uring requests.
+
+use core::time::Duration;
+
+use super::{
+    IoUringOp, IoUringSqe, SqeFlags,
+    file::{IoUringFile, Submission},
+};
+use crate::{
+    events::IoEvents,
+    fs::{
+        self,
+        file::{
+            CreationFlags, FileLike, StatusFlags,
+            file_table::{FdFlags, RawFileDesc, get_file_fast},
+        },
+    },
+    net::socket::util::{MessageHeader, SendRecvFlags},
+    prelude::*,
+    syscall::do_openat,
+    time::{
+        clocks::{BootTimeClock, MonotonicClock, RealTimeClock},
+        timespec_t,
+    },
+    util::{
+        VmReaderArray, VmWriterArray,
+        net::{read_socket_addr_from_user, write_socket_addr_to_user},
+    },
+};
+
+/// The maximum number of bytes that can be read or written by a single request.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/linux/fs.h>
+const MAX_RW_COUNT: usize = (i32::MAX as usize) & !(PAGE_SIZE - 1);
+
+bitflags! {
+    /// The flags of read and write requests (`rw_flags`).
+    struct RwFlags: u32 {
+        const HIPRI = 1 << 0;
+        const DSYNC = 1 << 1;
+        const SYNC = 1 << 2;
+        const NOWAIT = 1 << 3;
+        const APPEND = 1 << 4;
+    }
+}
+
+bitflags! {
+    /// The flags of `IORING_OP_FSYNC` (`fsync_flags`).
+    struct FsyncFlags: u32 {
+        const DATASYNC = 1 << 0;
+    }
+}
+
+bitflags! {
+    /// The flags of `IORING_OP_TIMEOUT` (`timeout_flags`).
+    struct TimeoutFlags: u32 {
+        const ABS = 1 << 0;
+        const BOOTTIME = 1 << 2;
+        const REALTIME = 1 << 3;
+    }
+}
+
+/// A request that has passed the preparation checks.
+pub(super) struct Request {
+    op: IoUringOp,
+    sqe: IoUringSqe,
+}
+
+impl Request {
+    /// Prepares a request from an SQE.
+    pub(super) fn new(sqe: IoUringSqe) -> Result<Self> {
+        let op = IoUringOp::try_from(sqe.opcode)
+            .map_err(|_| Error::with_message(Errno::EINVAL, "the opcode is not supported"))?;
+        let flags = SqeFlags::from_bits(sqe.flags)
+            .ok_or_else(|| Error::with_message(Errno::EINVAL, "the SQE flags are invalid"))?;
+
+        // TODO: Support draining the previous requests and selecting the provided buffers.
+        if flags.intersects(SqeFlags::IO_DRAIN | SqeFlags::BUFFER_SELECT) {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "IOSQE_IO_DRAIN and IOSQE_BUFFER_SELECT are not supported"
+            );
+        }
+
+        match op {
+            IoUringOp::Read
+            | IoUringOp::Write
+            | IoUringOp::Readv
+            | IoUringOp::Writev
+            | IoUringOp::ReadFixed
+            | IoUringOp::WriteFixed => {
+                if RwFlags::from_bits(sqe.op_flags).is_none() {
+                    return_errno_with_message!(Errno::EOPNOTSUPP, "the RW flags are not supported");
+                }
+            }
+            IoUringOp::Fsync => {
+                if FsyncFlags::from_bits(sqe.op_flags).is_none() {
+                    return_errno_with_message!(Errno::EINVAL, "the fsync flags are invalid");
+                }
+            }
+            IoUringOp::PollAdd => {
+                // TODO: Support multishot polling.
+                if sqe.len != 0 {
+                    return_errno_with_message!(Errno::EINVAL, "multishot polling is not supported");
+                }
+            }
+            IoUringOp::Timeout => {
+                if sqe.len != 1 {
+                    return_errno_with_message!(Errno::EINVAL, "the timeout count is not one");
+                }
+                let flags = TimeoutFlags::from_bits(sqe.op_flags).ok_or_else(|| {
+                    Error::with_message(Errno::EINVAL, "the timeout flags are invalid")
+                })?;
+                if flags.contains(TimeoutFlags::BOOTTIME | TimeoutFlags::REALTIME) {
+                    return_errno_with_message!(Errno::EINVAL, "multiple clocks are specified");
+                }
+            }
+            IoUringOp::TimeoutRemove | IoUringOp::PollRemove | IoUringOp::AsyncCancel => {
+                // TODO: Support updating timeouts and polling events.
+                if sqe.op_flags != 0 {
+                    return_errno_with_message!(Errno::EINVAL, "the flags are not supported");
+                }
+            }
+            IoUringOp::Accept => {
+                let supported_flags =
+                    StatusFlags::O_NONBLOCK.bits() | CreationFlags::O_CLOEXEC.bits();
+                if sqe.op_flags & !supported_flags != 0 {
+                    return_errno_with_message!(Errno::EINVAL, "the accept flags are invalid");
+                }
+                // TODO: Support installing the accepted socket as a fixed file.
+                if sqe.file_index != 0 {
+                    return_errno_with_message!(Errno::EINVAL, "fixed files cannot be installed");
+                }
+            }
+            IoUringOp::Openat | IoUringOp::Close => {
+                if flags.contains(SqeFlags::FIXED_FILE) {
+                    return_errno_with_message!(
+                        Errno::EBADF,
+                        "fixed files cannot be opened or closed"
+                    );
+                }
+                if sqe.file_index != 0 {
+                    return_errno_with_message!(
+                        Errno::EINVAL,
+                        "fixed files cannot be opened or closed"
+                    );
+                }
+            }
+            IoUringOp::Send | IoUringOp::Recv => {
+                // TODO: Support the flags of `IORING_OP_SEND` and `IORING_OP_RECV` (e.g., zero-copy
+                // and multishot receiving), which are passed in the `ioprio` field.
+                if sqe.ioprio != 0 {
+                    return_errno_with_message!(
+                        Errno::EINVAL,
+                        "the send/recv flags are not supported"
+                    );
+                }
+            }
+            IoUringOp::Nop | IoUringOp::Connect => {}
+        }
+
+        Ok(Self { op, sqe })
+    }
+
+    pub(super) fn op(&self) -> IoUringOp {
+        self.op
+    }
+
+    pub(super) fn flags(&self) -> SqeFlags {
+        SqeFlags::from_bits_truncate(self.sqe.flags)
+    }
+
+    pub(super) fn user_data(&self) -> u64 {
+        self.sqe.user_data
+    }
+
+    /// Returns whether the result means that the request has failed.
+    ///
+    /// A failed request breaks the link that it belongs to, unless it is hard-linked.
+    pub(super) fn is_failed(&self, res: i32) -> bool {
+        if res < 0 {
+            return true;
+        }
+
+        // Like Linux, short reads and writes are also considered failures.
+        // Reference: <https://elixir.bootlin.com/linux/v6.18/source/io_uring/rw.c#L549>
+        matches!(
+            self.op,
+            IoUringOp::Read | IoUringOp::Write | IoUringOp::ReadFixed | IoUringOp::WriteFixed
+        ) && (res as u32) < self.sqe.len
+    }
+}
+
+/// The outcome of issuing a request.
+pub(super) enum Issue {
+    /// The request is completed with the result.
+    Done(i32),
+    /// The request cannot be completed now and should be retried later.
+    Wait(Wait),
+}
+
+/// The condition that a pending request waits for.
+pub(super) enum Wait {
+    /// Waits until the file has some of the events.
+    Events(Arc<dyn FileLike>, IoEvents),
+    /// Waits until the deadline (measured against `CLOCK_MONOTONIC`) is reached or the number of
+    /// completed requests reaches the target.
+    Timeout {
+        deadline: Duration,
+        target: Option<u64>,
+    },
+}
+
+/// Converts an error code to the result of a CQE.
+pub(super) fn errno_to_res(errno: Errno) -> i32 {
+    -(errno as i32)
+}
+
+impl IoUringFile {
+    /// Issues a request.
+    pub(super) fn issue(
+        &self,
+        request: &Request,
+        submission: &mut Submission,
+        ctx: &Context,
+    ) -> Issue {
+        self.try_issue(request, submission, ctx)
+            .unwrap_or_else(|err| Issue::Done(errno_to_res(err.error())))
+    }
+
+    fn try_issue(
+        &self,
+        request: &Request,
+        submission: &mut Submission,
+        ctx: &Context,
+    ) -> Result<Issue> {
+        let sqe = &request.sqe;
+
+        match request.op {
+            IoUringOp::Nop => Ok(Issue::Done(0)),
+            IoUringOp::Timeout => issue_timeout(sqe, submission, ctx),
+            IoUringOp::TimeoutRemove => self.cancel(submission, sqe.addr, Some(IoUringOp::Timeout)),
+            IoUringOp::PollRemove => self.cancel(submission, sqe.addr, Some(IoUringOp::PollAdd)),
+            IoUringOp::AsyncCancel => self.cancel(submission, sqe.addr, None),
+            IoUringOp::Openat => {
+                let fd = do_openat(sqe.fd, sqe.addr as Vaddr, sqe.op_flags, sqe.len as u16, ctx)?;
+                Ok(Issue::Done(fd.into()))
+            }
+            IoUringOp::Close => {
+                close_file(sqe.fd, ctx)?;
+                Ok(Issue::Done(0))
+            }
+            _ => {
+                let file = self.get_file(request, ctx)?;
+                self.issue_on_file(request, file, ctx)
+            }
+        }
+    }
+
+    /// Cancels the pending request whose user data is `user_data`.
+    fn cancel(
+        &self,
+        submission: &mut Submission,
+        user_data: u64,
+        op: Option<IoUringOp>,
+    ) -> Result<Issue> {
+        let Some(index) = submission.pending.iter().position(|chain| {
+            chain.request.user_data() == user_data && op.is_none_or(|op| chain.request.op == op)
+        }) else {
+            return_errno_with_message!(Errno::ENOENT, "the request to cancel is not found");
+        };
+
+        let chain = submission.pending.remove(index);
+        self.complete(
+            submission,
+            &chain.request,
+            errno_to_res(Errno::ECANCELED),
+            false,
+        );
+        self.cancel_chain(submission, chain.rest, false);
+
+        Ok(Issue::Done(0))
+    }
+
+    /// Gets the file that the request operates on.
+    fn get_file(&self, request: &Request, ctx: &Context) -> Result<Arc<dyn FileLike>> {
+        let fd = request.sqe.fd;
+
+        if request.flags().contains(SqeFlags::FIXED_FILE) {
+            return usize::try_from(fd)
+                .ok()
+                .and_then(|index| self.fixed_file(index))
+                .ok_or_else(|| Error::with_message(Errno::EBADF, "the fixed file does not exist"));
+        }
+
+        let mut file_table = ctx.thread_local.borrow_file_table_mut();
+        let file = get_file_fast!(&mut file_table, fd.try_into()?).into_owned();
+        Ok(file)
+    }
+
+    fn issue_on_file(
+        &self,
+        request: &Request,
+        file: Arc<dyn FileLike>,
+        ctx: &Context,
+    ) -> Result<Issue> {
+        let sqe = &request.sqe;
+
+        if request.op == IoUringOp::PollAdd {
+            let mask = IoEvents::from_bits_truncate(sqe.op_flags);
+            let events = file.poll(mask, None);
+            if events.is_empty() {
+                return Ok(Issue::Wait(Wait::Events(file, mask)));
+            }
+            return Ok(Issue::Done(events.bits() as i32));
+        }
+
+        // Requests on blocking files are not performed until the files are ready. This prevents
+        // the requests from blocking the thread that calls `io_uring_enter`. Like Linux, requests
+        // on non-blocking files fail with `EAGAIN` instead.
+        let rw_flags = RwFlags::from_bits_truncate(sqe.op_flags);
+        let msg_flags = SendRecvFlags::from_bits_truncate(sqe.op_flags as i32);
+        let (events, is_nonblocking) = match request.op {
+            IoUringOp::Read | IoUringOp::Readv | IoUringOp::ReadFixed => {
+                (IoEvents::IN, rw_flags.contains(RwFlags::NOWAIT))
+            }
+            IoUringOp::Write | IoUringOp::Writev | IoUringOp::WriteFixed => {
+                (IoEvents::OUT, rw_flags.contains(RwFlags::NOWAIT))
+            }
+            IoUringOp::Recv => (
+                IoEvents::IN,
+                msg_flags.contains(SendRecvFlags::MSG_DONTWAIT),
+            ),
+            IoUringOp::Send => (
+                IoEvents::OUT,
+                msg_flags.contains(SendRecvFlags::MSG_DONTWAIT),
+            ),
+            IoUringOp::Accept => (IoEvents::IN, false),
+            // FIXME: Connecting to a remote address may block the thread.
+            _ => (IoEvents::empty(), true),
+        };
+        let is_nonblocking =
+            is_nonblocking || file.status_flags().contains(StatusFlags::O_NONBLOCK);
+        if !is_nonblocking && file.poll(events, None).is_empty() {
+            return Ok(Issue::Wait(Wait::Events(file, events)));
+        }
+
+        let user_space = ctx.user_space();
+        let len = (sqe.len as usize).min(MAX_RW_COUNT);
+        let res = match request.op {
+            IoUringOp::Read => {
+                let writer = user_space.writer(sqe.addr as Vaddr, len)?;
+                read_file(&file, sqe.off, &mut [writer])
+            }
+            IoUringOp::Write => {
+                let reader = user_space.reader(sqe.addr as Vaddr, len)?;
+                write_file(&file, sqe.off, &mut [reader])
+            }
+            IoUringOp::ReadFixed => {
+                self.check_fixed_buffer(sqe)?;
+                let writer = user_space.writer(sqe.addr as Vaddr, len)?;
+                read_file(&file, sqe.off, &mut [writer])
+            }
+            IoUringOp::WriteFixed => {
+                self.check_fixed_buffer(sqe)?;
+                let reader = user_space.reader(sqe.addr as Vaddr, len)?;
+                write_file(&file, sqe.off, &mut [reader])
+            }
+            IoUringOp::Readv => {
+                let mut writer_array = VmWriterArray::from_user_io_vecs(
+                    &user_space,
+                    sqe.addr as Vaddr,
+                    sqe.len as usize,
+                )?;
+                read_file(&file, sqe.off, writer_array.writers_mut())
+            }
+            IoUringOp::Writev => {
+                let mut reader_array = VmReaderArray::from_user_io_vecs(
+                    &user_space,
+                    sqe.addr as Vaddr,
+                    sqe.len as usize,
+                )?;
+                write_file(&file, sqe.off, reader_array.readers_mut())
+            }
+            IoUringOp::Fsync => {
+                let path = file.as_inode_handle_or_err()?.path();
+                if FsyncFlags::from_bits_truncate(sqe.op_flags).contains(FsyncFlags::DATASYNC) {
+                    path.sync_data()?;
+                } else {
+                    path.sync_all()?;
+                }
+                Ok(0)
+            }
+            IoUringOp::Accept => accept(&file, sqe, ctx),
+            IoUringOp::Connect => {
+                let socket_addr = read_socket_addr_from_user(sqe.addr as Vaddr, sqe.off as usize)?;
+                file.as_socket_or_err()?.connect(socket_addr)?;
+                Ok(0)
+            }
+            IoUringOp::Send => {
+                let mut reader = user_space.reader(sqe.addr as Vaddr, len)?;
+                let message_header = MessageHeader::new(None, Vec::new());
+                file.as_socket_or_err()?
+                    .sendmsg(&mut reader, message_header, msg_flags)
+            }
+            IoUringOp::Recv => {
+                let mut writer = user_space.writer(sqe.addr as Vaddr, len)?;
+                file.as_socket_or_err()?
+                    .recvmsg(&mut writer, msg_flags)
+                    .map(|(len, _)| len)
+            }
+            _ => unreachable!(),
+        }?;
+
+        // The length is always less than `MAX_RW_COUNT`, except for vectored I/O with very large
+        // buffers, where the result has to be truncated.
+        Ok(Issue::Done(i32::try_from(res).unwrap_or(i32::MAX)))
+    }
+
+    /// Checks that the buffer of a request is in the registered buffer.
+    fn check_fixed_buffer(&self, sqe: &IoUringSqe) -> Result<()> {
+        let Some((base, len)) = self.fixed_buffer(sqe.buf_index as usize) else {
+            return_errno_with_message!(Errno::EFAULT, "the fixed buffer does not exist");
+        };
+
+        let start = sqe.addr as usize;
+        let end = start.checked_add(sqe.len as usize);
+        if start < base || end.is_none_or(|end| end > base + len) {
+            return_errno_with_message!(Errno::EFAULT, "the buffer is out of the registered buffer");
+        }
+
+        Ok(())
+    }
+}
+
+fn issue_timeout(sqe: &IoUringSqe, submission: &Submission, ctx: &Context) -> Result<Issue> {
+    let flags = TimeoutFlags::from_bits_truncate(sqe.op_flags);
+    let timespec = ctx.user_space().read_val::<timespec_t>(sqe.addr as Vaddr)?;
+    let timeout = Duration::try_from(timespec)?;
+
+    let now = MonotonicClock::get().read_time();
+    let deadline = if flags.contains(TimeoutFlags::ABS) {
+        // Convert the absolute time to the monotonic clock, so all the timeouts can be compared
+        // with each other.
+        let clock_now = if flags.contains(TimeoutFlags::BOOTTIME) {
+            BootTimeClock::get().read_time()
+        } else if flags.contains(TimeoutFlags::REALTIME) {
+            RealTimeClock::get().read_time()
+        } else {
+            now
+        };
+        now.saturating_add(timeout.saturating_sub(clock_now))
+    } else {
+        now.saturating_add(timeout)
+    };
+
+    // A timeout with a non-zero count also completes after the number of requests completes.
+    let target = (sqe.off != 0).then(|| submission.num_completed.saturating_add(sqe.off));
+
+    Ok(Issue::Wait(Wait::Timeout { deadline, target }))
+}
+
+fn close_file(fd: RawFileDesc, ctx: &Context) -> Result<()> {
+    let fd = fd.try_into()?;
+
+    let file = {
+        let file_table = ctx.thread_local.borrow_file_table();
+        let mut file_table_locked = file_table.unwrap().write();
+        // Like Linux, io_uring instances cannot be closed by io_uring requests.
+        // Reference: <https://elixir.bootlin.com/linux/v6.18/source/io_uring/openclose.c#L247>
+        if file_table_locked
+            .get_file(fd)?
+            .downcast_ref::<IoUringFile>()
+            .is_some()
+        {
+            return_errno_with_message!(Errno::EBADF, "io_uring instances cannot be closed");
+        }
+        file_table_locked.close_file(fd).unwrap()
+    };
+
+    fs::vfs::notify::on_close(&file);
+
+    // Cleanup work needs to be done in the `Drop` impl.
+    drop(file);
+
+    Ok(())
+}
+
+fn accept(file: &Arc<dyn FileLike>, sqe: &IoUringSqe, ctx: &Context) -> Result<usize> {
+    let (connected_socket, socket_addr) = file.as_socket_or_err()?.accept()?;
+
+    if sqe.op_flags & StatusFlags::O_NONBLOCK.bits() != 0 {
+        connected_socket.set_status_flags(StatusFlags::O_NONBLOCK)?;
+    }
+
+    let fd_flags = if sqe.op_flags & CreationFlags::O_CLOEXEC.bits() != 0 {
+        FdFlags::CLOEXEC
+    } else {
+        FdFlags::empty()
+    };
+
+    // The address and its length are passed in `addr` and `addr2` (i.e., `off`), respectively.
+    if sqe.addr != 0 {
+        write_socket_addr_to_user(&socket_addr, sqe.addr as Vaddr, sqe.off as Vaddr)?;
+    }
+
+    let fd = {
+        let file_table = ctx.thread_local.borrow_file_table();
+        let mut file_table_locked = file_table.unwrap().write();
+        file_table_locked.insert(connected_socket, fd_flags)
+    };
+
+    Ok(i32::from(fd) as usize)
+}
+
+/// Parses the file offset of a read or write request.
+///
+/// An offset of `-1` means that the current file offset is used.
+fn parse_offset(offset: u64) -> Result<Option<usize>> {
+    match offset.cast_signed() {
+        -1 => Ok(None),
+        offset if offset < 0 => {
+            return_errno_with_message!(Errno::EINVAL, "the offset is negative")
+        }
+        offset => Ok(Some(offset as usize)),
+    }
+}
+
+fn read_file(file: &Arc<dyn FileLike>, offset: u64, writers: &mut [VmWriter]) -> Result<usize> {
+    let mut offset = parse_offset(offset)?;
+    let mut total_len = 0;
+
+    for writer in writers.iter_mut() {
+        let res = match offset {
+            // Like Linux, the offset is ignored for non-seekable files.
+            Some(pos) => match file.read_at(pos, writer) {
+                Err(err) if err.error() == Errno::ESPIPE => {
+                    offset = None;
+                    file.read(writer)
+                }
+                res => res,
+            },
+            None => file.read(writer),
+        };
+
+        match res {
+            Ok(read_len) => {
+                total_len += read_len;
+                if let Some(pos) = offset.as_mut() {
+                    *pos += read_len;
+                }
+            }
+            Err(_) if total_len > 0 => break,
+            Err(err) => return Err(err),
+        }
+        if writer.has_avail() {
+            break;
+        }
+    }
+
+    if total_len > 0 {
+        fs::vfs::notify::on_access(file);
+    }
+
+    Ok(total_len)
+}
+
+fn write_file(file: &Arc<dyn FileLike>, offset: u64, readers: &mut [VmReader]) -> Result<usize> {
+    let mut offset = parse_offset(offset)?;
+    let mut total_len = 0;
+
+    for reader in readers.iter_mut() {
+        let res = match offset {
+            // Like Linux, the offset is ignored for non-seekable files.
+            Some(pos) => match file.write_at(pos, reader) {
+                Err(err) if err.error() == Errno::ESPIPE => {
+                    offset = None;
+                    file.write(reader)
+                }
+                res => res,
+            },
+            None => file.write(reader),
+        };
+
+        match res {
+            Ok(write_len) => {
+                total_len += write_len;
+                if let Some(pos) = offset.as_mut() {
+                    *pos += write_len;
+                }
+            }
+            Err(_) if total_len > 0 => break,
+            Err(err) => return Err(err),
+        }
+        if reader.has_remain() {
+            break;
+        }
+    }
+
+    if total_len > 0 {
+        fs::vfs::notify::on_modify(file);
+    }
+
+    Ok(total_len)
+}
@@ -0,0 +1,305 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The rings shared between the kernel and the user space.
+//!
+//! An io_uring instance exposes three memory regions, which the user space maps with `mmap` at
+//! the fixed offsets below:
+//!  - the submission queue (SQ) ring, which holds the head, the tail, and (optionally) the array
+//!    of indices into the SQE array;
+//!  - the completion queue (CQ) ring, which holds the head, the tail, and the CQEs;
+//!  - the SQE array, which holds the submission queue entries.
+//!
+//! All three regions live in a single sparse VMO, so that a mapping offset is directly a VMO
+//! offset. The pages of the regions are committed eagerly when the rings are created, which
+//! allows the kernel to access the rings without going through the page-fault path.
+
+use core::sync::atomic::{Ordering, fence};
+
+use align_ext::AlignExt;
+use ostd::mm::{VmIo, VmIoOnce};
+
+use super::{IoUringCqe, IoUringSqe};
+use crate::{
+    prelude::*,
+    vm::page_cache::{CachePage, Vmo, VmoOptions},
+};
+
+/// The mapping offset of the SQ ring.
+pub const IORING_OFF_SQ_RING: usize = 0;
+/// The mapping offset of the CQ ring.
+pub const IORING_OFF_CQ_RING: usize = 0x8000000;
+/// The mapping offset of the SQE array.
+pub const IORING_OFF_SQES: usize = 0x10000000;
+
+/// The offsets of the fields in the SQ ring.
+///
+/// This is `struct io_sqring_offsets` in Linux.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+pub struct SqRingOffsets {
+    pub head: u32,
+    pub tail: u32,
+    pub ring_mask: u32,
+    pub ring_entries: u32,
+    pub flags: u32,
+    pub dropped: u32,
+    pub array: u32,
+    pub resv1: u32,
+    pub user_addr: u64,
+}
+
+/// The offsets of the fields in the CQ ring.
+///
+/// This is `struct io_cqring_offsets` in Linux.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+pub struct CqRingOffsets {
+    pub head: u32,
+    pub tail: u32,
+    pub ring_mask: u32,
+    pub ring_entries: u32,
+    pub overflow: u32,
+    pub cqes: u32,
+    pub flags: u32,
+    pub resv1: u32,
+    pub user_addr: u64,
+}
+
+// The layout of the SQ ring.
+const SQ_HEAD: usize = 0;
+const SQ_TAIL: usize = 4;
+const SQ_RING_MASK: usize = 8;
+const SQ_RING_ENTRIES: usize = 12;
+const SQ_FLAGS: usize = 16;
+const SQ_DROPPED: usize = 20;
+const SQ_ARRAY: usize = 64;
+
+// The layout of the CQ ring.
+const CQ_HEAD: usize = 0;
+const CQ_TAIL: usize = 4;
+const CQ_RING_MASK: usize = 8;
+const CQ_RING_ENTRIES: usize = 12;
+const CQ_OVERFLOW: usize = 16;
+const CQ_FLAGS: usize = 20;
+const CQ_CQES: usize = 64;
+
+/// The rings of an io_uring instance.
+pub(super) struct Rings {
+    vmo: Arc<Vmo>,
+    sq_ring: Region,
+    cq_ring: Region,
+    sqes: Region,
+    sq_entries: u32,
+    cq_entries: u32,
+    has_sq_array: bool,
+}
+
+impl Rings {
+    /// Allocates the rings.
+    ///
+    /// The numbers of entries must be powers of two.
+    pub(super) fn new(sq_entries: u32, cq_entries: u32, has_sq_array: bool) -> Result<Self> {
+        debug_assert!(sq_entries.is_power_of_two());
+        debug_assert!(cq_entries.is_power_of_two());
+
+        let sq_ring_size = if has_sq_array {
+            SQ_ARRAY + sq_entries as usize * size_of::<u32>()
+        } else {
+            SQ_ARRAY
+        };
+        let cq_ring_size = CQ_CQES + cq_entries as usize * size_of::<IoUringCqe>();
+        let sqes_size = sq_entries as usize * size_of::<IoUringSqe>();
+
+        let vmo = VmoOptions::new(IORING_OFF_SQES + sqes_size).alloc()?;
+        let sq_ring = Region::commit(&vmo, IORING_OFF_SQ_RING, sq_ring_size)?;
+        let cq_ring = Region::commit(&vmo, IORING_OFF_CQ_RING, cq_ring_size)?;
+        let sqes = Region::commit(&vmo, IORING_OFF_SQES, sqes_size)?;
+
+        sq_ring.write_once(SQ_RING_MASK, sq_entries - 1);
+        sq_ring.write_once(SQ_RING_ENTRIES, sq_entries);
+        cq_ring.write_once(CQ_RING_MASK, cq_entries - 1);
+        cq_ring.write_once(CQ_RING_ENTRIES, cq_entries);
+
+        Ok(Self {
+            vmo,
+            sq_ring,
+            cq_ring,
+            sqes,
+            sq_entries,
+            cq_entries,
+            has_sq_array,
+        })
+    }
+
+    /// Returns the VMO that backs all the rings.
+    pub(super) fn vmo(&self) -> &Arc<Vmo> {
+        &self.vmo
+    }
+
+    pub(super) fn sq_entries(&self) -> u32 {
+        self.sq_entries
+    }
+
+    pub(super) fn cq_entries(&self) -> u32 {
+        self.cq_entries
+    }
+
+    pub(super) fn sq_offsets(&self) -> SqRingOffsets {
+        SqRingOffsets {
+            head: SQ_HEAD as u32,
+            tail: SQ_TAIL as u32,
+            ring_mask: SQ_RING_MASK as u32,
+            ring_entries: SQ_RING_ENTRIES as u32,
+            flags: SQ_FLAGS as u32,
+            dropped: SQ_DROPPED as u32,
+            array: if self.has_sq_array {
+                SQ_ARRAY as u32
+            } else {
+                0
+            },
+            ..Default::default()
+        }
+    }
+
+    pub(super) fn cq_offsets(&self) -> CqRingOffsets {
+        CqRingOffsets {
+            head: CQ_HEAD as u32,
+            tail: CQ_TAIL as u32,
+            ring_mask: CQ_RING_MASK as u32,
+            ring_entries: CQ_RING_ENTRIES as u32,
+            overflow: CQ_OVERFLOW as u32,
+            cqes: CQ_CQES as u32,
+            flags: CQ_FLAGS as u32,
+            ..Default::default()
+        }
+    }
+
+    /// Reads the SQ tail, which is written by the user space.
+    pub(super) fn sq_tail(&self) -> u32 {
+        let tail = self.sq_ring.read_once(SQ_TAIL);
+        // Pairs with the release store of the tail in the user space, so that the SQEs are
+        // visible before they are read.
+        fence(Ordering::Acquire);
+        tail
+    }
+
+    pub(super) fn sq_head(&self) -> u32 {
+        self.sq_ring.read_once(SQ_HEAD)
+    }
+
+    /// Publishes the SQ head, which tells the user space that the SQEs are consumed.
+    pub(super) fn set_sq_head(&self, head: u32) {
+        fence(Ordering::Release);
+        self.sq_ring.write_once(SQ_HEAD, head);
+    }
+
+    pub(super) fn sq_flags(&self) -> u32 {
+        self.sq_ring.read_once(SQ_FLAGS)
+    }
+
+    pub(super) fn set_sq_flags(&self, flags: u32) {
+        self.sq_ring.write_once(SQ_FLAGS, flags);
+    }
+
+    /// Records that an SQE with an invalid index has been skipped.
+    pub(super) fn inc_sq_dropped(&self) {
+        let dropped: u32 = self.sq_ring.read_once(SQ_DROPPED);
+        self.sq_ring.write_once(SQ_DROPPED, dropped.wrapping_add(1));
+    }
+
+    /// Reads the SQE at the SQ position `pos`.
+    ///
+    /// This method returns `None` if the user space has placed an invalid index in the SQ array.
+    pub(super) fn read_sqe(&self, pos: u32) -> Option<IoUringSqe> {
+        let index = if self.has_sq_array {
+            let slot = (pos & (self.sq_entries - 1)) as usize;
+            self.sq_ring.read_once(SQ_ARRAY + slot * size_of::<u32>())
+        } else {
+            pos & (self.sq_entries - 1)
+        };
+        if index >= self.sq_entries {
+            return None;
+        }
+
+        Some(self.sqes.read_val(index as usize * size_of::<IoUringSqe>()))
+    }
+
+    /// Reads the CQ head, which is written by the user space.
+    pub(super) fn cq_head(&self) -> u32 {
+        let head = self.cq_ring.read_once(CQ_HEAD);
+        // Pairs with the release store of the head in the user space, so that the CQEs are no
+        // longer accessed by the user space before they are overwritten.
+        fence(Ordering::Acquire);
+        head
+    }
+
+    /// Writes the CQE at the CQ position `pos`.
+    ///
+    /// The CQE will not be visible to the user space until the CQ tail is updated.
+    pub(super) fn write_cqe(&self, pos: u32, cqe: &IoUringCqe) {
+        let slot = (pos & (self.cq_entries - 1)) as usize;
+        self.cq_ring
+            .write_val(CQ_CQES + slot * size_of::<IoUringCqe>(), cqe);
+    }
+
+    /// Publishes the CQ tail, which tells the user space that the CQEs are available.
+    pub(super) fn set_cq_tail(&self, tail: u32) {
+        fence(Ordering::Release);
+        self.cq_ring.write_once(CQ_TAIL, tail);
+    }
+
+    pub(super) fn cq_flags(&self) -> u32 {
+        self.cq_ring.read_once(CQ_FLAGS)
+    }
+}
+
+/// A region of the rings with committed pages.
+///
+/// The region always starts at a page boundary. Therefore, naturally aligned fields never cross
+/// page boundaries.
+struct Region {
+    pages: Vec<CachePage>,
+}
+
+impl Region {
+    fn commit(vmo: &Vmo, offset: usize, size: usize) -> Result<Self> {
+        let start_idx = offset / PAGE_SIZE;
+        let end_idx = (offset + size).align_up(PAGE_SIZE) / PAGE_SIZE;
+
+        let pages = (start_idx..end_idx)
+            .map(|idx| vmo.commit_on(idx))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { pages })
+    }
+
+    fn read_once(&self, offset: usize) -> u32 {
+        // The offset is aligned and in bounds, so this will not fail.
+        self.pages[offset / PAGE_SIZE]
+            .read_once(offset % PAGE_SIZE)
+            .unwrap()
+    }
+
+    fn write_once(&self, offset: usize, val: u32) {
+        // The offset is aligned and in bounds, so this will not fail.
+        self.pages[offset / PAGE_SIZE]
+            .write_once(offset % PAGE_SIZE, &val)
+            .unwrap();
+    }
+
+    fn read_val<T: Pod>(&self, offset: usize) -> T {
+        debug_assert!((offset % PAGE_SIZE) + size_of::<T>() <= PAGE_SIZE);
+        // The value does not cross page boundaries, so this will not fail.
+        self.pages[offset / PAGE_SIZE]
+            .read_val(offset % PAGE_SIZE)
+            .unwrap()
+    }
+
+    fn write_val<T: Pod>(&self, offset: usize, val: &T) {
+        debug_assert!((offset % PAGE_SIZE) + size_of::<T>() <= PAGE_SIZE);
+        // The value does not cross page boundaries, so this will not fail.
+        self.pages[offset / PAGE_SIZE]
+            .write_val(offset % PAGE_SIZE, val)
+            .unwrap();
+    }
+}
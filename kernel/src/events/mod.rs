@@ -4,6 +4,7 @@ mod epoll;
 #[expect(clippy::module_inception)]
 mod events;
 mod io_events;
+mod io_uring;
 mod observer;
 mod subject;
 
@@ -11,6 +12,7 @@ pub use self::{
     epoll::{EpollCtl, EpollEvent, EpollFile, EpollFlags},
     events::{Events, EventsFilter},
     io_events::IoEvents,
+    io_uring::{IoUringEnterFlags, IoUringFile, IoUringOp, IoUringParams},
     observer::Observer,
     subject::SyncSubject,
 };
@@ -185,6 +185,7 @@ impl FileTable {
         // Reference: <https://man7.org/linux/man-pages/man2/fcntl_locking.2.html>
         if let Ok(inode_handle) = removed_entry.file.as_inode_handle_or_err() {
             inode_handle.release_range_locks();
+            inode_handle.flush();
         }
         Some(removed_entry.file)
     }
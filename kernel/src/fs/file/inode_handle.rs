@@ -186,6 +186,15 @@ impl InodeHandle {
         range_lock_list.set_lock(lock, is_nonblocking)
     }
 
+    /// Notifies the opened file that one of its file descriptors has been closed.
+    ///
+    /// See [`PerOpenFileOps::flush`].
+    pub fn flush(&self) {
+        if let Some(open_file) = self.open_file.as_ref() {
+            open_file.flush();
+        }
+    }
+
     pub fn release_range_locks(&self) {
         let range_lock = RangeLockItem::new(
             RangeLockType::Unlock,
@@ -571,6 +580,13 @@ pub trait PerOpenFileOps: Pollable + FileOps + Any + Send + Sync + 'static {
     fn ioctl(&self, _raw_ioctl: RawIoctl) -> Result<i32> {
         return_errno_with_message!(Errno::ENOTTY, "ioctl is not supported");
     }
+
+    /// Releases the per-process state when a file descriptor of the file is closed.
+    ///
+    /// Unlike dropping the file, this is called for every file descriptor that is closed, even if
+    /// other file descriptors still refer to the same opened file. This is similar to the `flush`
+    /// operation in Linux.
+    fn flush(&self) {}
 }
 
 fn do_seek_util(offset: &Mutex<usize>, pos: SeekFrom, end: Option<usize>) -> Result<usize> {
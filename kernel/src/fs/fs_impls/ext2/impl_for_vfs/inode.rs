@@ -268,7 +268,11 @@ impl Inode for Ext2Inode {
     }
 
     fn get_xattr(&self, name: XattrName, value_writer: &mut VmWriter) -> Result<usize> {
-        self.check_permission(Permission::MAY_READ)?;
+        // Like Linux, reading `security.*` xattrs does not require the read permission. Their
+        // access is controlled by the security mechanisms (e.g., file capabilities) instead.
+        if name.namespace() != XattrNamespace::Security {
+            self.check_permission(Permission::MAY_READ)?;
+        }
         self.get_xattr(name, value_writer)
     }
 
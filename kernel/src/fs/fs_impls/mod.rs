@@ -9,6 +9,7 @@ pub mod configfs;
 pub mod devpts;
 pub mod exfat;
 pub mod ext2;
+pub mod mqueue;
 pub mod overlayfs;
 pub mod procfs;
 pub mod pseudofs;
@@ -24,6 +25,7 @@ pub(super) fn init() {
     configfs::init();
     ramfs::init();
     tmpfs::init();
+    mqueue::init();
     devpts::init();
     pseudofs::init();
 
@@ -0,0 +1,532 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The POSIX message queue file system (mqueue).
+//!
+//! Every IPC namespace owns an mqueue instance, whose root directory holds the
+//! message queues created by `mq_open`. The instance can also be mounted (usually at
+//! `/dev/mqueue`) so that the queues can be listed, inspected, and removed with
+//! ordinary file operations.
+
+use core::{
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use aster_util::slot_vec::SlotVec;
+
+use self::queue::MqueueInode;
+pub use self::queue::{MqAttr, MqNotifyMethod, MqueueFile, NOTIFY_COOKIE_LEN};
+use crate::{
+    fs::{
+        file::{CreationFlags, InodeHandle, InodeMode, InodeType, OpenArgs, StatusFlags, mkmod},
+        pseudofs::AnonDeviceId,
+        utils::{DirEntryVecExt, DirentVisitor, NAME_MAX},
+        vfs::{
+            file_system::{FileSystem, FsEventSubscriberStats, SuperBlock},
+            inode::{Extension, FileOps, Inode, Metadata, MknodType, RevalidationPolicy},
+            path::{Mount, Path, PathResolver},
+            registry::{FsCreationCtx, FsProperties, FsType},
+        },
+    },
+    prelude::*,
+    process::{Gid, Uid, credentials::capabilities::CapSet, posix_thread::AsPosixThread},
+    time::clocks::RealTimeCoarseClock,
+};
+
+mod queue;
+
+// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/magic.h>
+const MQUEUE_MAGIC: u64 = 0x19800202;
+const BLOCK_SIZE: usize = PAGE_SIZE;
+
+const ROOT_INO: u64 = 1;
+
+// The following limits are derived from the default values in Linux.
+// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/linux/ipc_namespace.h>
+
+/// Maximum number of message queues in an mqueue instance.
+const QUEUES_MAX: usize = 256;
+/// Maximum number of messages in a queue created by unprivileged users.
+const MSG_MAX: i64 = 10;
+/// Maximum size in bytes of a message in a queue created by unprivileged users.
+const MSGSIZE_MAX: i64 = 8192;
+/// Default maximum number of messages in a queue.
+const MSG_DEFAULT: i64 = 10;
+/// Default maximum size in bytes of a message in a queue.
+const MSGSIZE_DEFAULT: i64 = 8192;
+/// Maximum number of messages in a queue created with `CAP_SYS_RESOURCE`.
+const HARD_MSGMAX: i64 = 65536;
+/// Maximum size in bytes of a message in a queue created with `CAP_SYS_RESOURCE`.
+const HARD_MSGSIZEMAX: i64 = 16 * 1024 * 1024;
+
+/// An mqueue file system instance.
+pub struct MqueueFs {
+    _anon_device_id: AnonDeviceId,
+    sb: SuperBlock,
+    root: Arc<RootInode>,
+    /// The next inode number to allocate.
+    next_ino: AtomicU64,
+    /// The number of message queues in this instance.
+    num_queues: AtomicUsize,
+    fs_event_subscriber_stats: FsEventSubscriberStats,
+}
+
+impl MqueueFs {
+    fn new() -> Result<Arc<Self>> {
+        let anon_device_id = AnonDeviceId::acquire().ok_or_else(|| {
+            Error::with_message(Errno::ENOMEM, "no device ID is available for mqueue")
+        })?;
+        let sb = SuperBlock::new(MQUEUE_MAGIC, BLOCK_SIZE, NAME_MAX, anon_device_id.id());
+        Ok(Arc::new_cyclic(|weak_self| Self {
+            _anon_device_id: anon_device_id,
+            sb: sb.clone(),
+            root: RootInode::new(weak_self.clone(), &sb),
+            next_ino: AtomicU64::new(ROOT_INO + 1),
+            num_queues: AtomicUsize::new(0),
+            fs_event_subscriber_stats: FsEventSubscriberStats::new(),
+        }))
+    }
+
+    /// Creates the internal mount of a new mqueue instance.
+    ///
+    /// Each IPC namespace holds such a mount, through which `mq_open` and `mq_unlink`
+    /// find the message queues regardless of whether and where the instance is mounted.
+    pub fn new_kern_mount() -> Result<Arc<Mount>> {
+        Mount::new_pseudo(Self::new()?)
+    }
+
+    fn alloc_ino(&self) -> u64 {
+        self.next_ino.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Creates a message queue named `name` in the root directory.
+    fn create_queue(&self, name: &str, mode: InodeMode, attr: &MqAttr) -> Result<Arc<dyn Inode>> {
+        let has_sys_resource = has_sys_resource();
+
+        if self
+            .num_queues
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |num_queues| {
+                (num_queues < QUEUES_MAX || has_sys_resource).then_some(num_queues + 1)
+            })
+            .is_err()
+        {
+            return_errno_with_message!(Errno::ENOSPC, "too many message queues");
+        }
+
+        let result = self.root.add_queue(name, || {
+            let (uid, gid) = current_fs_ids();
+            MqueueInode::new(self.alloc_ino(), mode, uid, gid, attr, &self.root.fs)
+        });
+        if result.is_err() {
+            self.num_queues.fetch_sub(1, Ordering::Relaxed);
+        }
+        result
+    }
+}
+
+impl FileSystem for MqueueFs {
+    fn name(&self) -> &'static str {
+        "mqueue"
+    }
+
+    fn sync(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn root_inode(&self) -> Arc<dyn Inode> {
+        self.root.clone()
+    }
+
+    fn sb(&self) -> SuperBlock {
+        self.sb.clone()
+    }
+
+    fn fs_event_subscriber_stats(&self) -> &FsEventSubscriberStats {
+        &self.fs_event_subscriber_stats
+    }
+}
+
+struct MqueueFsType;
+
+impl FsType for MqueueFsType {
+    fn name(&self) -> &'static str {
+        "mqueue"
+    }
+
+    fn properties(&self) -> FsProperties {
+        FsProperties::empty()
+    }
+
+    fn create(&self, fs_creation_ctx: &FsCreationCtx) -> Result<Arc<dyn FileSystem>> {
+        // Like Linux, mounting mqueue exposes the instance of the current IPC namespace.
+        let ns_proxy = fs_creation_ctx.task_ctx().thread_local.borrow_ns_proxy();
+        let ipc_ns = ns_proxy.unwrap().ipc_ns();
+        Ok(ipc_ns.mqueue_mount().fs().clone())
+    }
+
+    fn sysnode(&self) -> Option<Arc<dyn aster_systree::SysNode>> {
+        None
+    }
+}
+
+pub(super) fn init() {
+    crate::fs::vfs::registry::register(&MqueueFsType).unwrap();
+}
+
+/// Opens the message queue named `name` in the mqueue instance of `mount`.
+///
+/// If the queue does not exist and `O_CREAT` is specified in `open_args`, a new queue
+/// is created with `attr`, or with the default attributes if `attr` is `None`.
+pub fn open(
+    mount: &Arc<Mount>,
+    name: &str,
+    open_args: &OpenArgs,
+    attr: Option<&MqAttr>,
+    path_resolver: &PathResolver,
+) -> Result<InodeHandle> {
+    check_name(name)?;
+
+    let fs = mount.fs().downcast_ref::<MqueueFs>().unwrap();
+    let root = Path::new_fs_root(mount.clone());
+    let creation_flags = open_args.creation_flags;
+
+    let is_created = match fs.root.lookup(name) {
+        Ok(_) if creation_flags.contains(CreationFlags::O_CREAT | CreationFlags::O_EXCL) => {
+            return_errno_with_message!(Errno::EEXIST, "the message queue already exists");
+        }
+        Ok(_) => false,
+        Err(err) if err.error() == Errno::ENOENT => {
+            if !creation_flags.contains(CreationFlags::O_CREAT) {
+                return_errno_with_message!(Errno::ENOENT, "the message queue does not exist");
+            }
+
+            let attr = match attr {
+                Some(attr) => {
+                    attr.check_limits(has_sys_resource())?;
+                    *attr
+                }
+                None => MqAttr::new_default(),
+            };
+            match fs.create_queue(name, open_args.inode_mode, &attr) {
+                Ok(_) => true,
+                // Another thread has created the queue in the meantime.
+                Err(err)
+                    if err.error() == Errno::EEXIST
+                        && !creation_flags.contains(CreationFlags::O_EXCL) =>
+                {
+                    false
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        Err(err) => return Err(err),
+    };
+
+    let path = path_resolver.lookup_at_path(&root, name)?;
+    let status_flags = open_args.status_flags & StatusFlags::O_NONBLOCK;
+    if is_created {
+        // The access mode of a newly created queue is not checked.
+        InodeHandle::new_unchecked_access(path, open_args.access_mode, status_flags)
+    } else {
+        InodeHandle::new(path, open_args.access_mode, status_flags)
+    }
+}
+
+/// Removes the message queue named `name` from the mqueue instance of `mount`.
+///
+/// The queue itself is destroyed once all the file descriptors referring to it are closed.
+pub fn unlink(mount: &Arc<Mount>, name: &str) -> Result<()> {
+    check_name(name)?;
+
+    Path::new_fs_root(mount.clone()).unlink(name)
+}
+
+/// Checks whether `name` is a valid message queue name.
+///
+/// The leading slash of the name given to `mq_open` is stripped by the C library, so
+/// the name here cannot contain any slash.
+fn check_name(name: &str) -> Result<()> {
+    if name.is_empty() || name == "." || name == ".." || name.contains('/') {
+        return_errno_with_message!(Errno::EACCES, "the message queue name is invalid");
+    }
+    if name.len() > NAME_MAX {
+        return_errno_with_message!(Errno::ENAMETOOLONG, "the message queue name is too long");
+    }
+
+    Ok(())
+}
+
+/// Returns whether the current thread has the `CAP_SYS_RESOURCE` capability.
+fn has_sys_resource() -> bool {
+    current_thread!()
+        .as_posix_thread()
+        .unwrap()
+        .credentials()
+        .effective_capset()
+        .contains(CapSet::SYS_RESOURCE)
+}
+
+/// Returns the file system user ID and group ID of the current thread.
+fn current_fs_ids() -> (Uid, Gid) {
+    let credentials = current_thread!().as_posix_thread().unwrap().credentials();
+    (credentials.fsuid(), credentials.fsgid())
+}
+
+/// The root directory of an mqueue instance.
+struct RootInode {
+    queues: RwLock<SlotVec<(String, Arc<dyn Inode>)>>,
+    metadata: RwLock<Metadata>,
+    extension: Extension,
+    fs: Weak<MqueueFs>,
+}
+
+impl RootInode {
+    fn new(fs: Weak<MqueueFs>, sb: &SuperBlock) -> Arc<Self> {
+        Arc::new(Self {
+            queues: RwLock::new(SlotVec::new()),
+            metadata: RwLock::new(Metadata::new_dir(
+                ROOT_INO,
+                mkmod!(a+rwx) | InodeMode::S_ISVTX,
+                BLOCK_SIZE,
+                sb.container_dev_id,
+            )),
+            extension: Extension::new(),
+            fs,
+        })
+    }
+
+    /// Adds a queue created by `new_queue` under `name`.
+    fn add_queue<F>(&self, name: &str, new_queue: F) -> Result<Arc<dyn Inode>>
+    where
+        F: FnOnce() -> Arc<dyn Inode>,
+    {
+        let mut queues = self.queues.write();
+        if queues.find_entry_by_name(name).is_some() {
+            return_errno_with_message!(Errno::EEXIST, "the message queue already exists");
+        }
+
+        let queue = queues.put_entry_if_not_found(name, new_queue).clone();
+        drop(queues);
+
+        let mut metadata = self.metadata.write();
+        let now = RealTimeCoarseClock::get().read_time();
+        metadata.last_modify_at = now;
+        metadata.last_meta_change_at = now;
+
+        Ok(queue)
+    }
+}
+
+impl FileOps for RootInode {
+    fn read_at(
+        &self,
+        _offset: usize,
+        _writer: &mut VmWriter,
+        _status_flags: StatusFlags,
+    ) -> Result<usize> {
+        Err(Error::new(Errno::EISDIR))
+    }
+
+    fn write_at(
+        &self,
+        _offset: usize,
+        _reader: &mut VmReader,
+        _status_flags: StatusFlags,
+    ) -> Result<usize> {
+        Err(Error::new(Errno::EISDIR))
+    }
+
+    fn readdir_at(&self, offset: usize, visitor: &mut dyn DirentVisitor) -> Result<usize> {
+        let try_readdir = |offset: &mut usize, visitor: &mut dyn DirentVisitor| -> Result<()> {
+            // Read the 2 special entries.
+            if *offset == 0 {
+                visitor.visit(".", self.ino(), self.type_(), *offset)?;
+                *offset += 1;
+            }
+            if *offset == 1 {
+                visitor.visit("..", self.ino(), self.type_(), *offset)?;
+                *offset += 1;
+            }
+
+            // Read the queues.
+            let queues = self.queues.read();
+            let start_offset = *offset;
+            for (idx, (name, node)) in queues
+                .idxes_and_items()
+                .map(|(idx, (name, node))| (idx + 2, (name, node)))
+                .skip_while(|(idx, _)| idx < &start_offset)
+            {
+                visitor.visit(name.as_ref(), node.ino(), node.type_(), idx)?;
+                *offset = idx + 1;
+            }
+            Ok(())
+        };
+
+        let mut iterate_offset = offset;
+        match try_readdir(&mut iterate_offset, visitor) {
+            Err(e) if offset == iterate_offset => Err(e),
+            _ => Ok(iterate_offset - offset),
+        }
+    }
+}
+
+impl Inode for RootInode {
+    fn size(&self) -> usize {
+        self.metadata.read().size
+    }
+
+    fn resize(&self, _new_size: usize) -> Result<()> {
+        Err(Error::new(Errno::EISDIR))
+    }
+
+    fn metadata(&self) -> Metadata {
+        *self.metadata.read()
+    }
+
+    fn extension(&self) -> &Extension {
+        &self.extension
+    }
+
+    fn ino(&self) -> u64 {
+        self.metadata.read().ino
+    }
+
+    fn type_(&self) -> InodeType {
+        self.metadata.read().type_
+    }
+
+    fn mode(&self) -> Result<InodeMode> {
+        Ok(self.metadata.read().mode)
+    }
+
+    fn set_mode(&self, mode: InodeMode) -> Result<()> {
+        self.metadata.write().mode = mode;
+        Ok(())
+    }
+
+    fn owner(&self) -> Result<Uid> {
+        Ok(self.metadata.read().uid)
+    }
+
+    fn set_owner(&self, uid: Uid) -> Result<()> {
+        self.metadata.write().uid = uid;
+        Ok(())
+    }
+
+    fn group(&self) -> Result<Gid> {
+        Ok(self.metadata.read().gid)
+    }
+
+    fn set_group(&self, gid: Gid) -> Result<()> {
+        self.metadata.write().gid = gid;
+        Ok(())
+    }
+
+    fn atime(&self) -> Duration {
+        self.metadata.read().last_access_at
+    }
+
+    fn set_atime(&self, time: Duration) {
+        self.metadata.write().last_access_at = time;
+    }
+
+    fn mtime(&self) -> Duration {
+        self.metadata.read().last_modify_at
+    }
+
+    fn set_mtime(&self, time: Duration) {
+        self.metadata.write().last_modify_at = time;
+    }
+
+    fn ctime(&self) -> Duration {
+        self.metadata.read().last_meta_change_at
+    }
+
+    fn set_ctime(&self, time: Duration) {
+        self.metadata.write().last_meta_change_at = time;
+    }
+
+    fn create(&self, name: &str, type_: InodeType, mode: InodeMode) -> Result<Arc<dyn Inode>> {
+        if type_ != InodeType::File {
+            return_errno_with_message!(
+                Errno::EPERM,
+                "only message queues can be created in mqueue"
+            );
+        }
+
+        // Queues created by `open` have the default attributes.
+        let fs = self.fs.upgrade().unwrap();
+        fs.create_queue(name, mode, &MqAttr::new_default())
+    }
+
+    fn mknod(&self, _name: &str, _mode: InodeMode, _type_: MknodType) -> Result<Arc<dyn Inode>> {
+        return_errno_with_message!(Errno::EPERM, "mknod is not supported in mqueue");
+    }
+
+    fn link(&self, _old: &Arc<dyn Inode>, _name: &str) -> Result<()> {
+        return_errno_with_message!(Errno::EPERM, "hard links are not supported in mqueue");
+    }
+
+    fn unlink(&self, name: &str) -> Result<()> {
+        let mut queues = self.queues.write();
+        let Some((_, queue)) = queues.remove_entry_by_name(name) else {
+            return_errno_with_message!(Errno::ENOENT, "the message queue does not exist");
+        };
+        drop(queues);
+
+        queue.downcast_ref::<MqueueInode>().unwrap().mark_unlinked();
+        self.fs
+            .upgrade()
+            .unwrap()
+            .num_queues
+            .fetch_sub(1, Ordering::Relaxed);
+
+        Ok(())
+    }
+
+    fn rmdir(&self, _name: &str) -> Result<()> {
+        return_errno_with_message!(Errno::EPERM, "directories are not supported in mqueue");
+    }
+
+    fn lookup(&self, name: &str) -> Result<Arc<dyn Inode>> {
+        let inode = match name {
+            "." | ".." => self.fs().root_inode(),
+            queue => self
+                .queues
+                .read()
+                .find_entry_by_name(queue)
+                .cloned()
+                .ok_or(Error::with_message(
+                    Errno::ENOENT,
+                    "the message queue does not exist",
+                ))?,
+        };
+        Ok(inode)
+    }
+
+    fn rename(&self, _old_name: &str, _target: &Arc<dyn Inode>, _new_name: &str) -> Result<()> {
+        return_errno_with_message!(Errno::EPERM, "message queues cannot be renamed");
+    }
+
+    fn fs(&self) -> Arc<dyn FileSystem> {
+        self.fs.upgrade().unwrap()
+    }
+
+    fn revalidation_policy(&self) -> RevalidationPolicy {
+        RevalidationPolicy::REVALIDATE_EXISTS | RevalidationPolicy::REVALIDATE_ABSENT
+    }
+
+    fn revalidate_exists(&self, name: &str, child: &dyn Inode) -> bool {
+        // The same instance can be reached through the internal mount and any number of
+        // user mounts, each with its own dentry cache. Queues created or removed through
+        // one of them must be visible through the others.
+        self.queues
+            .read()
+            .find_entry_by_name(name)
+            .is_some_and(|queue| queue.ino() == child.ino())
+    }
+
+    fn revalidate_absent(&self, name: &str) -> bool {
+        self.queues.read().find_entry_by_name(name).is_none()
+    }
+}
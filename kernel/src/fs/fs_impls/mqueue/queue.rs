@@ -0,0 +1,661 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::time::Duration;
+
+use aster_util::printer::VmPrinter;
+use ostd::sync::WaitQueue;
+
+use super::{
+    BLOCK_SIZE, HARD_MSGMAX, HARD_MSGSIZEMAX, MSG_DEFAULT, MSG_MAX, MSGSIZE_DEFAULT, MSGSIZE_MAX,
+    MqueueFs,
+};
+use crate::{
+    events::IoEvents,
+    fs::{
+        file::{AccessMode, InodeMode, InodeType, PerOpenFileOps, StatusFlags},
+        vfs::{
+            file_system::FileSystem,
+            inode::{Extension, FileOps, Inode, Metadata},
+        },
+    },
+    net::socket::netlink::RawMessageReceiver,
+    prelude::*,
+    process::{
+        Gid, Process, Uid,
+        signal::{
+            PollHandle, Pollable, Pollee,
+            c_types::{SigNotify, siginfo_t, sigval_t},
+            constants::SI_MESGQ,
+            sig_num::SigNum,
+            signals::raw::RawSignal,
+        },
+    },
+    time::{clocks::RealTimeCoarseClock, wait::ManagedTimeout},
+};
+
+/// The size reported for the file of a message queue.
+// Reference: <https://elixir.bootlin.com/linux/v6.18/source/ipc/mqueue.c>
+const FILENT_SIZE: usize = 80;
+
+/// The attributes of a message queue (i.e., `struct mq_attr`).
+// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/mqueue.h>
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+pub struct MqAttr {
+    /// The flags of the open message queue description (`0` or `O_NONBLOCK`).
+    pub mq_flags: i64,
+    /// The maximum number of messages in the queue.
+    pub mq_maxmsg: i64,
+    /// The maximum size in bytes of a message.
+    pub mq_msgsize: i64,
+    /// The number of messages currently in the queue.
+    pub mq_curmsgs: i64,
+    _reserved: [i64; 4],
+}
+
+impl MqAttr {
+    /// Returns the attributes of a queue created without explicit attributes.
+    pub(super) fn new_default() -> Self {
+        Self {
+            mq_maxmsg: MSG_DEFAULT,
+            mq_msgsize: MSGSIZE_DEFAULT,
+            ..Self::default()
+        }
+    }
+
+    /// Checks whether a queue can be created with the attributes.
+    ///
+    /// Only `mq_maxmsg` and `mq_msgsize` are checked, since the other fields are ignored
+    /// when creating a queue.
+    pub(super) fn check_limits(&self, has_sys_resource: bool) -> Result<()> {
+        if self.mq_maxmsg <= 0 || self.mq_msgsize <= 0 {
+            return_errno_with_message!(Errno::EINVAL, "the queue attributes are not positive");
+        }
+
+        let (msg_max, msgsize_max) = if has_sys_resource {
+            (HARD_MSGMAX, HARD_MSGSIZEMAX)
+        } else {
+            (MSG_MAX, MSGSIZE_MAX)
+        };
+        if self.mq_maxmsg > msg_max || self.mq_msgsize > msgsize_max {
+            return_errno_with_message!(Errno::EINVAL, "the queue attributes exceed the limits");
+        }
+
+        Ok(())
+    }
+}
+
+/// The length of the cookie sent to the netlink socket for `SIGEV_THREAD`.
+// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/mqueue.h>
+pub const NOTIFY_COOKIE_LEN: usize = 32;
+
+/// The last byte of the cookie, which indicates why the cookie is sent.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug)]
+enum NotifyCookieType {
+    /// A message has arrived at the empty queue.
+    WokenUp = 1,
+    /// The registration has been removed.
+    Removed = 2,
+}
+
+/// The way to notify the registered process of a new message.
+#[derive(Clone)]
+pub enum MqNotifyMethod {
+    /// No notification is delivered (`SIGEV_NONE`).
+    None,
+    /// A signal is sent with the value (`SIGEV_SIGNAL`).
+    Signal(SigNum, sigval_t),
+    /// A cookie is sent to a netlink socket (`SIGEV_THREAD`).
+    ///
+    /// The C libraries receive the cookie in a helper thread, which then starts a new thread to
+    /// call the notification function.
+    Thread(Arc<dyn RawMessageReceiver>, [u8; NOTIFY_COOKIE_LEN]),
+}
+
+impl MqNotifyMethod {
+    fn sigev_notify(&self) -> SigNotify {
+        match self {
+            Self::None => SigNotify::SIGEV_NONE,
+            Self::Signal(..) => SigNotify::SIGEV_SIGNAL,
+            Self::Thread(..) => SigNotify::SIGEV_THREAD,
+        }
+    }
+
+    /// Sends the cookie to the netlink socket if the method is `SIGEV_THREAD`.
+    fn send_cookie(&self, cookie_type: NotifyCookieType) {
+        let Self::Thread(receiver, cookie) = self else {
+            return;
+        };
+
+        let mut cookie = *cookie;
+        cookie[NOTIFY_COOKIE_LEN - 1] = cookie_type as u8;
+        receiver.enqueue_raw(cookie.to_vec());
+    }
+}
+
+/// A process registered via `mq_notify`.
+struct Notification {
+    process: Weak<Process>,
+    method: MqNotifyMethod,
+}
+
+impl Notification {
+    /// Returns the registered process, or `None` if the process has exited.
+    fn process(&self) -> Option<Arc<Process>> {
+        self.process
+            .upgrade()
+            .filter(|process| !process.status().is_zombie())
+    }
+}
+
+/// A POSIX message queue.
+pub(super) struct MqueueInode {
+    /// The maximum number of messages in the queue.
+    max_msgs: usize,
+    /// The maximum size in bytes of a message.
+    max_msgsize: usize,
+    inner: Mutex<MqueueInner>,
+    /// The threads waiting for free space to send messages.
+    send_wait_queue: WaitQueue,
+    /// The threads waiting for messages to receive.
+    recv_wait_queue: WaitQueue,
+    pollee: Pollee,
+    metadata: RwLock<Metadata>,
+    extension: Extension,
+    fs: Weak<MqueueFs>,
+    this: Weak<MqueueInode>,
+}
+
+struct MqueueInner {
+    /// The messages in the queue, grouped by priority and kept in the order they were sent.
+    messages: BTreeMap<u32, VecDeque<Vec<u8>>>,
+    /// The total number of messages in the queue.
+    num_msgs: usize,
+    /// The total number of bytes of all messages in the queue.
+    num_bytes: usize,
+    /// The number of threads blocked in receiving messages.
+    num_waiting_receivers: usize,
+    /// The process to notify when a message arrives at the empty queue.
+    notification: Option<Notification>,
+}
+
+impl MqueueInner {
+    /// Returns the registered notification, discarding it if the process has exited.
+    fn notification(&mut self) -> Option<(&Notification, Arc<Process>)> {
+        let Some(process) = self.notification.as_ref()?.process() else {
+            self.notification = None;
+            return None;
+        };
+        Some((self.notification.as_ref().unwrap(), process))
+    }
+}
+
+impl MqueueInode {
+    pub(super) fn new(
+        ino: u64,
+        mode: InodeMode,
+        uid: Uid,
+        gid: Gid,
+        attr: &MqAttr,
+        fs: &Weak<MqueueFs>,
+    ) -> Arc<Self> {
+        let container_dev_id = fs.upgrade().unwrap().sb.container_dev_id;
+        let mut metadata = Metadata::new_file(ino, mode, BLOCK_SIZE, container_dev_id);
+        metadata.size = FILENT_SIZE;
+        metadata.uid = uid;
+        metadata.gid = gid;
+
+        Arc::new_cyclic(|weak_self| Self {
+            max_msgs: attr.mq_maxmsg as usize,
+            max_msgsize: attr.mq_msgsize as usize,
+            inner: Mutex::new(MqueueInner {
+                messages: BTreeMap::new(),
+                num_msgs: 0,
+                num_bytes: 0,
+                num_waiting_receivers: 0,
+                notification: None,
+            }),
+            send_wait_queue: WaitQueue::new(),
+            recv_wait_queue: WaitQueue::new(),
+            pollee: Pollee::new(),
+            metadata: RwLock::new(metadata),
+            extension: Extension::new(),
+            fs: fs.clone(),
+            this: weak_self.clone(),
+        })
+    }
+
+    /// Marks the queue as removed from the root directory.
+    ///
+    /// The queue stays usable through the file descriptors that are still open.
+    pub(super) fn mark_unlinked(&self) {
+        let mut metadata = self.metadata.write();
+        metadata.nr_hard_links = 0;
+        metadata.last_meta_change_at = RealTimeCoarseClock::get().read_time();
+    }
+
+    fn check_io_events(&self) -> IoEvents {
+        let inner = self.inner.lock();
+
+        let mut events = IoEvents::empty();
+        if inner.num_msgs > 0 {
+            events |= IoEvents::IN | IoEvents::RDNORM;
+        }
+        if inner.num_msgs < self.max_msgs {
+            events |= IoEvents::OUT | IoEvents::WRNORM;
+        }
+        events
+    }
+
+    fn update_times(&self) {
+        let mut metadata = self.metadata.write();
+        let now = RealTimeCoarseClock::get().read_time();
+        metadata.last_access_at = now;
+        metadata.last_modify_at = now;
+        metadata.last_meta_change_at = now;
+    }
+
+    fn send(
+        &self,
+        data: Vec<u8>,
+        priority: u32,
+        is_nonblocking: bool,
+        timeout: Option<ManagedTimeout>,
+        sender: &Context,
+    ) -> Result<()> {
+        if data.len() > self.max_msgsize {
+            return_errno_with_message!(Errno::EMSGSIZE, "the message is too long");
+        }
+
+        let try_lock_space = || {
+            let inner = self.inner.lock();
+            (inner.num_msgs < self.max_msgs).then_some(inner)
+        };
+
+        let mut inner = if is_nonblocking {
+            try_lock_space()
+                .ok_or_else(|| Error::with_message(Errno::EAGAIN, "the message queue is full"))?
+        } else {
+            self.send_wait_queue
+                .pause_until_or_timeout(try_lock_space, timeout)?
+        };
+
+        inner.num_bytes += data.len();
+        inner.num_msgs += 1;
+        inner.messages.entry(priority).or_default().push_back(data);
+
+        // Like Linux, the registered process is notified only if no thread is waiting to
+        // receive the message. The registration is removed once the notification is sent.
+        let notification = if inner.num_msgs == 1 && inner.num_waiting_receivers == 0 {
+            inner
+                .notification()
+                .map(|(notification, process)| (notification.method.clone(), process))
+        } else {
+            None
+        };
+        if notification.is_some() {
+            inner.notification = None;
+        }
+        drop(inner);
+
+        self.update_times();
+        self.recv_wait_queue.wake_all();
+        self.pollee.notify(IoEvents::IN | IoEvents::RDNORM);
+
+        match notification {
+            Some((MqNotifyMethod::Signal(num, value), process)) => {
+                let mut info = siginfo_t::new(num, SI_MESGQ);
+                info.set_pid_uid_by(sender, process.pid_ns());
+                info.set_value(value);
+                process.enqueue_signal(Box::new(RawSignal::new(info)));
+            }
+            Some((method, _)) => method.send_cookie(NotifyCookieType::WokenUp),
+            None => (),
+        }
+
+        Ok(())
+    }
+
+    fn receive(
+        &self,
+        max_len: usize,
+        is_nonblocking: bool,
+        timeout: Option<ManagedTimeout>,
+    ) -> Result<(Vec<u8>, u32)> {
+        if max_len < self.max_msgsize {
+            return_errno_with_message!(Errno::EMSGSIZE, "the buffer is too small");
+        }
+
+        let try_lock_message = || {
+            let inner = self.inner.lock();
+            (inner.num_msgs > 0).then_some(inner)
+        };
+
+        let mut inner = if is_nonblocking {
+            try_lock_message()
+                .ok_or_else(|| Error::with_message(Errno::EAGAIN, "the message queue is empty"))?
+        } else {
+            self.inner.lock().num_waiting_receivers += 1;
+            let result = self
+                .recv_wait_queue
+                .pause_until_or_timeout(try_lock_message, timeout);
+            match result {
+                Ok(mut inner) => {
+                    inner.num_waiting_receivers -= 1;
+                    inner
+                }
+                Err(err) => {
+                    self.inner.lock().num_waiting_receivers -= 1;
+                    return Err(err);
+                }
+            }
+        };
+
+        // Messages with higher priorities are received first.
+        let mut entry = inner.messages.last_entry().unwrap();
+        let priority = *entry.key();
+        let data = entry.get_mut().pop_front().unwrap();
+        if entry.get().is_empty() {
+            entry.remove();
+        }
+        inner.num_bytes -= data.len();
+        inner.num_msgs -= 1;
+        drop(inner);
+
+        self.update_times();
+        self.send_wait_queue.wake_all();
+        self.pollee.notify(IoEvents::OUT | IoEvents::WRNORM);
+
+        Ok((data, priority))
+    }
+
+    fn set_notification(
+        &self,
+        method: Option<MqNotifyMethod>,
+        process: &Arc<Process>,
+    ) -> Result<()> {
+        let mut inner = self.inner.lock();
+        let owner = inner.notification().map(|(_, owner)| owner);
+
+        match method {
+            // Like Linux, the registration is silently kept if the current process is not the
+            // registered one.
+            None => {
+                if owner.is_some_and(|owner| Arc::ptr_eq(&owner, process))
+                    && let Some(notification) = inner.notification.take()
+                {
+                    notification.method.send_cookie(NotifyCookieType::Removed);
+                }
+            }
+            Some(_) if owner.is_some() => {
+                return_errno_with_message!(
+                    Errno::EBUSY,
+                    "a process is already registered for notification"
+                );
+            }
+            Some(method) => {
+                inner.notification = Some(Notification {
+                    process: Arc::downgrade(process),
+                    method,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    fn attr(&self) -> MqAttr {
+        MqAttr {
+            mq_maxmsg: self.max_msgs as i64,
+            mq_msgsize: self.max_msgsize as i64,
+            mq_curmsgs: self.inner.lock().num_msgs as i64,
+            ..MqAttr::default()
+        }
+    }
+
+    /// Returns the status line shown when reading the file of the queue.
+    fn status(&self) -> String {
+        let mut inner = self.inner.lock();
+
+        let (sigev_notify, signo, pid) = match inner.notification() {
+            Some((notification, process)) => {
+                let signo = match notification.method {
+                    MqNotifyMethod::Signal(num, _) => num.as_u8() as i32,
+                    MqNotifyMethod::None | MqNotifyMethod::Thread(..) => 0,
+                };
+                (
+                    notification.method.sigev_notify() as i32,
+                    signo,
+                    process.pid(),
+                )
+            }
+            None => (0, 0, 0),
+        };
+
+        format!(
+            "QSIZE:{:<10} NOTIFY:{:<5} SIGNO:{:<5} NOTIFY_PID:{:<6}\n",
+            inner.num_bytes, sigev_notify, signo, pid
+        )
+    }
+}
+
+impl FileOps for MqueueInode {
+    fn read_at(
+        &self,
+        _offset: usize,
+        _writer: &mut VmWriter,
+        _status_flags: StatusFlags,
+    ) -> Result<usize> {
+        // Reads are served by `MqueueFile`.
+        return_errno_with_message!(Errno::EINVAL, "the message queue is not opened");
+    }
+
+    fn write_at(
+        &self,
+        _offset: usize,
+        _reader: &mut VmReader,
+        _status_flags: StatusFlags,
+    ) -> Result<usize> {
+        return_errno_with_message!(Errno::EINVAL, "message queues cannot be written");
+    }
+}
+
+impl Inode for MqueueInode {
+    fn size(&self) -> usize {
+        self.metadata.read().size
+    }
+
+    fn resize(&self, _new_size: usize) -> Result<()> {
+        return_errno_with_message!(Errno::EINVAL, "message queues cannot be resized");
+    }
+
+    fn metadata(&self) -> Metadata {
+        *self.metadata.read()
+    }
+
+    fn extension(&self) -> &Extension {
+        &self.extension
+    }
+
+    fn ino(&self) -> u64 {
+        self.metadata.read().ino
+    }
+
+    fn type_(&self) -> InodeType {
+        self.metadata.read().type_
+    }
+
+    fn mode(&self) -> Result<InodeMode> {
+        Ok(self.metadata.read().mode)
+    }
+
+    fn set_mode(&self, mode: InodeMode) -> Result<()> {
+        self.metadata.write().mode = mode;
+        Ok(())
+    }
+
+    fn owner(&self) -> Result<Uid> {
+        Ok(self.metadata.read().uid)
+    }
+
+    fn set_owner(&self, uid: Uid) -> Result<()> {
+        self.metadata.write().uid = uid;
+        Ok(())
+    }
+
+    fn group(&self) -> Result<Gid> {
+        Ok(self.metadata.read().gid)
+    }
+
+    fn set_group(&self, gid: Gid) -> Result<()> {
+        self.metadata.write().gid = gid;
+        Ok(())
+    }
+
+    fn atime(&self) -> Duration {
+        self.metadata.read().last_access_at
+    }
+
+    fn set_atime(&self, time: Duration) {
+        self.metadata.write().last_access_at = time;
+    }
+
+    fn mtime(&self) -> Duration {
+        self.metadata.read().last_modify_at
+    }
+
+    fn set_mtime(&self, time: Duration) {
+        self.metadata.write().last_modify_at = time;
+    }
+
+    fn ctime(&self) -> Duration {
+        self.metadata.read().last_meta_change_at
+    }
+
+    fn set_ctime(&self, time: Duration) {
+        self.metadata.write().last_meta_change_at = time;
+    }
+
+    fn open(
+        &self,
+        _access_mode: AccessMode,
+        _status_flags: StatusFlags,
+    ) -> Option<Result<Box<dyn PerOpenFileOps>>> {
+        let queue = self.this.upgrade().unwrap();
+        Some(Ok(Box::new(MqueueFile { queue })))
+    }
+
+    fn fs(&self) -> Arc<dyn FileSystem> {
+        self.fs.upgrade().unwrap()
+    }
+}
+
+/// An opened POSIX message queue.
+///
+/// The file is obtained either from `mq_open` or by opening the queue in a mounted
+/// mqueue instance. Reading the file gives the status of the queue.
+pub struct MqueueFile {
+    queue: Arc<MqueueInode>,
+}
+
+impl MqueueFile {
+    /// Sends a message with the priority to the queue.
+    ///
+    /// If the queue is full, this method will block until there is free space or the
+    /// timeout expires, unless `is_nonblocking` is true.
+    pub fn send(
+        &self,
+        data: Vec<u8>,
+        priority: u32,
+        is_nonblocking: bool,
+        timeout: Option<ManagedTimeout>,
+        ctx: &Context,
+    ) -> Result<()> {
+        self.queue
+            .send(data, priority, is_nonblocking, timeout, ctx)
+    }
+
+    /// Receives the oldest message with the highest priority from the queue.
+    ///
+    /// `max_len` must not be less than the maximum size of a message in the queue.
+    ///
+    /// If the queue is empty, this method will block until a message arrives or the
+    /// timeout expires, unless `is_nonblocking` is true.
+    pub fn receive(
+        &self,
+        max_len: usize,
+        is_nonblocking: bool,
+        timeout: Option<ManagedTimeout>,
+    ) -> Result<(Vec<u8>, u32)> {
+        self.queue.receive(max_len, is_nonblocking, timeout)
+    }
+
+    /// Registers (`Some`) or unregisters (`None`) the current process for notification
+    /// of a new message arriving at the empty queue.
+    ///
+    /// Only one process can be registered at a time. Unregistering does nothing if the
+    /// current process is not the registered one.
+    pub fn set_notification(&self, method: Option<MqNotifyMethod>, ctx: &Context) -> Result<()> {
+        self.queue.set_notification(method, &ctx.process)
+    }
+
+    /// Returns the attributes of the queue.
+    ///
+    /// The `mq_flags` field is always zero, since the flags belong to the open file.
+    pub fn attr(&self) -> MqAttr {
+        self.queue.attr()
+    }
+}
+
+impl Pollable for MqueueFile {
+    fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.queue
+            .pollee
+            .poll_with(mask, poller, || self.queue.check_io_events())
+    }
+}
+
+impl FileOps for MqueueFile {
+    fn read_at(
+        &self,
+        offset: usize,
+        writer: &mut VmWriter,
+        _status_flags: StatusFlags,
+    ) -> Result<usize> {
+        let mut printer = VmPrinter::new_skip(writer, offset);
+        write!(printer, "{}", self.queue.status())?;
+
+        Ok(printer.bytes_written())
+    }
+
+    fn write_at(
+        &self,
+        _offset: usize,
+        _reader: &mut VmReader,
+        _status_flags: StatusFlags,
+    ) -> Result<usize> {
+        return_errno_with_message!(Errno::EINVAL, "message queues cannot be written");
+    }
+}
+
+impl PerOpenFileOps for MqueueFile {
+    fn check_seekable(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn is_offset_aware(&self) -> bool {
+        true
+    }
+
+    fn flush(&self) {
+        // Closing any file descriptor of the queue removes the registration made by the current
+        // process, even if the registration was made through another file descriptor. This
+        // matches `mqueue_flush_file` in Linux.
+        let Some(process) = Process::current() else {
+            return;
+        };
+        // Removing a registration never fails.
+        let _ = self.queue.set_notification(None, &process);
+    }
+}
@@ -14,6 +14,7 @@ use self::{
     loadavg::LoadAvgFileOps,
     meminfo::MemInfoFileOps,
     mounts::MountsSymOps,
+    net::NetSymOps,
     pid::{PidDirOps, TidDirOps},
     self_::SelfSymOps,
     sys::SysDirOps,
@@ -35,7 +36,7 @@ use crate::{
     },
     prelude::*,
     process::{
-        Pid,
+        Pid, PidNamespace,
         pid_table::{self, PidEntryType},
     },
 };
@@ -46,6 +47,7 @@ mod filesystems;
 mod loadavg;
 mod meminfo;
 mod mounts;
+mod net;
 mod pid;
 mod self_;
 mod stat;
@@ -79,13 +81,13 @@ struct ProcFs {
 }
 
 impl ProcFs {
-    pub(self) fn new() -> Arc<Self> {
+    pub(self) fn new(pid_ns: Arc<PidNamespace>) -> Arc<Self> {
         let anon_device_id = AnonDeviceId::acquire().expect("no device ID is available for procfs");
         let sb = SuperBlock::new(PROC_MAGIC, BLOCK_SIZE, NAME_MAX, anon_device_id.id());
         Arc::new_cyclic(|weak_fs| Self {
             _anon_device_id: anon_device_id,
             sb: sb.clone(),
-            root: RootDirOps::new_inode(weak_fs.clone(), &sb),
+            root: RootDirOps::new_inode(pid_ns, weak_fs.clone(), &sb),
             inode_allocator: AtomicU64::new(PROC_ROOT_INO + 1),
             fs_event_subscriber_stats: FsEventSubscriberStats::new(),
         })
@@ -129,8 +131,10 @@ impl FsType for ProcFsType {
         FsProperties::empty()
     }
 
-    fn create(&self, _fs_creation_ctx: &FsCreationCtx) -> Result<Arc<dyn FileSystem>> {
-        Ok(ProcFs::new())
+    fn create(&self, fs_creation_ctx: &FsCreationCtx) -> Result<Arc<dyn FileSystem>> {
+        // The IDs in the procfs are shown in the PID namespace of the mounting process.
+        let pid_ns = fs_creation_ctx.task_ctx().process.pid_ns().clone();
+        Ok(ProcFs::new(pid_ns))
     }
 
     fn sysnode(&self) -> Option<Arc<dyn aster_systree::SysNode>> {
@@ -139,13 +143,26 @@ impl FsType for ProcFsType {
 }
 
 /// Represents the inode at `/proc`.
-struct RootDirOps;
+struct RootDirOps {
+    pid_ns: Arc<PidNamespace>,
+}
 
 impl RootDirOps {
-    pub fn new_inode(fs: Weak<ProcFs>, sb: &SuperBlock) -> Arc<dyn Inode> {
+    pub fn new_inode(
+        pid_ns: Arc<PidNamespace>,
+        fs: Weak<ProcFs>,
+        sb: &SuperBlock,
+    ) -> Arc<dyn Inode> {
         // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/proc/root.c#L368>
         let fs: Weak<dyn FileSystem> = fs;
-        ProcDir::new_root(Self, fs, PROC_ROOT_INO, sb, mkmod!(a+rx))
+        ProcDir::new_root(Self { pid_ns }, fs, PROC_ROOT_INO, sb, mkmod!(a+rx))
+    }
+
+    /// Returns the PID namespace of the procfs whose root directory is `root_dir`.
+    pub(self) fn pid_ns_of(root_dir: &Weak<dyn Inode>) -> Arc<PidNamespace> {
+        let root_dir = root_dir.upgrade().unwrap();
+        let root_dir = root_dir.downcast_ref::<ProcDir<RootDirOps>>().unwrap();
+        root_dir.inner().pid_ns.clone()
     }
 
     const STATIC_ENTRIES: &'static [StaticEntry] = &[
@@ -159,6 +176,7 @@ impl RootDirOps {
         ("loadavg", InodeType::File, LoadAvgFileOps::new_inode),
         ("meminfo", InodeType::File, MemInfoFileOps::new_inode),
         ("mounts", InodeType::SymLink, MountsSymOps::new_inode),
+        ("net", InodeType::SymLink, NetSymOps::new_inode),
         ("self", InodeType::SymLink, SelfSymOps::new_inode),
         ("stat", InodeType::File, StatFileOps::new_inode),
         ("sys", InodeType::Dir, SysDirOps::new_inode),
@@ -174,7 +192,9 @@ impl RootDirOps {
 
 impl ProcDirOps for RootDirOps {
     fn lookup_child(&self, this_dir: &ProcDir<Self>, name: &str) -> Result<Arc<dyn Inode>> {
-        if let Ok(pid) = name.parse::<Pid>() {
+        if let Ok(pid) = name.parse::<Pid>()
+            && let Some(pid) = self.pid_ns.global_id(pid)
+        {
             let pid_entry = {
                 let pid_table = pid_table::pid_table_mut();
                 pid_table.get_entry(pid)
@@ -182,12 +202,13 @@ impl ProcDirOps for RootDirOps {
             if let Some(pid_entry) = pid_entry
                 && let Some(type_) = pid_entry.type_()
             {
+                let pid_ns = self.pid_ns.clone();
                 return Ok(match type_ {
                     PidEntryType::Process => {
-                        PidDirOps::new_inode(pid_entry, this_dir.this_weak().clone())
+                        PidDirOps::new_inode(pid_entry, pid_ns, this_dir.this_weak().clone())
                     }
                     PidEntryType::Thread => {
-                        TidDirOps::new_inode(pid_entry, this_dir.this_weak().clone())
+                        TidDirOps::new_inode(pid_entry, pid_ns, this_dir.this_weak().clone())
                     }
                 });
             }
@@ -216,8 +237,9 @@ impl ProcDirOps for RootDirOps {
         let process_pids = {
             let pid_table = pid_table::pid_table_mut();
             pid_table
-                .iter_processes()
-                .filter_map(|process| usize::try_from(process.pid()).ok())
+                .processes_in_ns(&self.pid_ns)
+                .into_iter()
+                .filter_map(|(pid, _)| usize::try_from(pid).ok())
                 .collect::<Vec<_>>()
         };
 
@@ -263,6 +285,9 @@ impl ProcDirOps for RootDirOps {
         let Ok(pid) = name.parse::<Pid>() else {
             return true;
         };
+        let Some(pid) = self.pid_ns.global_id(pid) else {
+            return true;
+        };
 
         let pid_entry = {
             let pid_table = pid_table::pid_table_mut();
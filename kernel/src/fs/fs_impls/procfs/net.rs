@@ -0,0 +1,26 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::{
+    fs::{
+        file::mkmod,
+        procfs::template::{ProcSym, ProcSymOps},
+        vfs::inode::{Inode, SymbolicLink},
+    },
+    prelude::*,
+};
+
+/// Represents the inode at `/proc/net`.
+pub struct NetSymOps;
+
+impl NetSymOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/proc/proc_net.c#L374>
+        ProcSym::new(Self, parent, mkmod!(a+rwx))
+    }
+}
+
+impl ProcSymOps for NetSymOps {
+    fn read_link(&self) -> Result<SymbolicLink> {
+        Ok(SymbolicLink::Plain("self/net".to_string()))
+    }
+}
@@ -10,15 +10,19 @@ use super::{
 use crate::{
     fs::{
         file::{InodeType, mkmod},
-        procfs::pid::task::TaskDirOps,
+        procfs::pid::{task::TaskDirOps, timens_offsets::TimensOffsetsFileOps},
         vfs::inode::{Inode, RevalidationPolicy},
     },
     prelude::*,
-    process::pid_table::{PidEntry, PidEntryType},
+    process::{
+        PidNamespace,
+        pid_table::{PidEntry, PidEntryType},
+    },
     thread::Thread,
 };
 
 mod task;
+mod timens_offsets;
 pub(super) use task::TidDirOps;
 
 /// Represents the inode at `/proc/[pid]`.
@@ -30,8 +34,12 @@ pub struct PidDirOps(
 );
 
 impl PidDirOps {
-    pub fn new_inode(pid_entry: Arc<PidEntry>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
-        let this = Self(TidDirOps::new(pid_entry));
+    pub fn new_inode(
+        pid_entry: Arc<PidEntry>,
+        pid_ns: Arc<PidNamespace>,
+        parent: Weak<dyn Inode>,
+    ) -> Arc<dyn Inode> {
+        let this = Self(TidDirOps::new(pid_entry, pid_ns));
         // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/proc/base.c#L3493>
         ProcDir::new(this, parent, mkmod!(a+rx))
     }
@@ -40,6 +48,10 @@ impl PidDirOps {
         self.0.pid_entry()
     }
 
+    pub(super) fn pid_ns(&self) -> &Arc<PidNamespace> {
+        self.0.pid_ns()
+    }
+
     pub(super) fn tid_dir_ops(&self) -> &TidDirOps {
         &self.0
     }
@@ -51,6 +63,11 @@ impl PidDirOps {
             InodeType::File,
             task::stat::StatFileOps::new_process_inode,
         ),
+        (
+            "timens_offsets",
+            InodeType::File,
+            TimensOffsetsFileOps::new_inode,
+        ),
     ];
 }
 
@@ -11,7 +11,7 @@ use crate::{
                 comm::CommFileOps, environ::EnvironFileOps, exe::ExeSymOps, fd::FdDirOps,
                 gid_map::GidMapFileOps, maps::MapsFileOps, mem::MemFileOps,
                 mountinfo::MountInfoFileOps, mounts::MountsFileOps, mountstats::MountStatsFileOps,
-                ns::NsDirOps, oom_score_adj::OomScoreAdjFileOps, stat::StatFileOps,
+                net::NetDirOps, ns::NsDirOps, oom_score_adj::OomScoreAdjFileOps, stat::StatFileOps,
                 status::StatusFileOps, uid_map::UidMapFileOps,
             },
             template::{
@@ -23,7 +23,7 @@ use crate::{
         vfs::inode::{Inode, RevalidationPolicy},
     },
     prelude::*,
-    process::{Process, pid_table, pid_table::PidEntry, posix_thread::AsPosixThread},
+    process::{PidNamespace, Process, pid_table, pid_table::PidEntry, posix_thread::AsPosixThread},
     thread::{Thread, Tid},
 };
 
@@ -40,6 +40,7 @@ mod mem;
 mod mountinfo;
 mod mounts;
 mod mountstats;
+mod net;
 mod ns;
 mod oom_score_adj;
 pub(super) mod stat;
@@ -47,16 +48,23 @@ mod status;
 mod uid_map;
 
 /// Represents the inode at `/proc/[pid]/task`.
-pub struct TaskDirOps(Arc<PidEntry>);
+pub struct TaskDirOps {
+    pid_entry: Arc<PidEntry>,
+    pid_ns: Arc<PidNamespace>,
+}
 
 impl TaskDirOps {
     pub fn new_inode(dir: &PidDirOps, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        let this = Self {
+            pid_entry: dir.pid_entry().clone(),
+            pid_ns: dir.pid_ns().clone(),
+        };
         // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/proc/base.c#L3316>
-        ProcDir::new(Self(dir.pid_entry().clone()), parent, mkmod!(a+rx))
+        ProcDir::new(this, parent, mkmod!(a+rx))
     }
 
     fn process(&self) -> Option<Arc<Process>> {
-        self.0.process_of_thread()
+        self.pid_entry.process_of_thread()
     }
 }
 
@@ -64,16 +72,22 @@ impl TaskDirOps {
 #[derive(Clone)]
 pub struct TidDirOps {
     pid_entry: Arc<PidEntry>,
+    /// The PID namespace in which the IDs are shown.
+    pid_ns: Arc<PidNamespace>,
 }
 
 impl TidDirOps {
-    pub fn new(pid_entry: Arc<PidEntry>) -> Self {
-        Self { pid_entry }
+    pub fn new(pid_entry: Arc<PidEntry>, pid_ns: Arc<PidNamespace>) -> Self {
+        Self { pid_entry, pid_ns }
     }
 
-    pub fn new_inode(pid_entry: Arc<PidEntry>, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+    pub fn new_inode(
+        pid_entry: Arc<PidEntry>,
+        pid_ns: Arc<PidNamespace>,
+        parent: Weak<dyn Inode>,
+    ) -> Arc<dyn Inode> {
         ProcDir::new(
-            Self { pid_entry },
+            Self { pid_entry, pid_ns },
             parent,
             // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/proc/base.c#L3796>
             mkmod!(a+rx),
@@ -84,6 +98,10 @@ impl TidDirOps {
         &self.pid_entry
     }
 
+    pub(super) fn pid_ns(&self) -> &Arc<PidNamespace> {
+        &self.pid_ns
+    }
+
     pub(super) fn process(&self) -> Option<Arc<Process>> {
         self.pid_entry.process_of_thread()
     }
@@ -116,6 +134,7 @@ impl TidDirOps {
         ("mem", InodeType::File, MemFileOps::new_inode),
         ("mountinfo", InodeType::File, MountInfoFileOps::new_inode),
         ("mountstats", InodeType::File, MountStatsFileOps::new_inode),
+        ("net", InodeType::Dir, NetDirOps::new_inode),
         ("ns", InodeType::Dir, NsDirOps::new_inode),
         (
             "oom_score_adj",
@@ -185,11 +204,15 @@ impl TidDirOps {
 
 impl ProcDirOps for TaskDirOps {
     fn owner_thread(&self) -> Option<Arc<Thread>> {
-        self.0.thread()
+        self.pid_entry.thread()
     }
 
     fn lookup_child(&self, this_dir: &ProcDir<Self>, name: &str) -> Result<Arc<dyn Inode>> {
-        let Ok(tid) = name.parse::<Tid>() else {
+        let Some(tid) = name
+            .parse::<Tid>()
+            .ok()
+            .and_then(|tid| self.pid_ns.global_id(tid))
+        else {
             return_errno_with_message!(Errno::ENOENT, "the name is not a valid TID");
         };
 
@@ -227,6 +250,7 @@ impl ProcDirOps for TaskDirOps {
 
         Ok(TidDirOps::new_inode(
             pid_entry,
+            self.pid_ns.clone(),
             this_dir.this_weak().clone(),
         ))
     }
@@ -245,7 +269,9 @@ impl ProcDirOps for TaskDirOps {
             .lock()
             .as_slice()
             .iter()
-            .filter_map(|task| usize::try_from(task.as_posix_thread().unwrap().tid()).ok())
+            .map(|task| self.pid_ns.local_id(task.as_posix_thread().unwrap().tid()))
+            .filter(|tid| *tid != 0)
+            .filter_map(|tid| usize::try_from(tid).ok())
             .collect::<Vec<_>>();
 
         visit_readdir_entries(
@@ -280,6 +306,9 @@ impl ProcDirOps for TaskDirOps {
         let Ok(tid) = name.parse::<Tid>() else {
             return true;
         };
+        let Some(tid) = self.pid_ns.global_id(tid) else {
+            return true;
+        };
 
         let Some(process) = self.process() else {
             return true;
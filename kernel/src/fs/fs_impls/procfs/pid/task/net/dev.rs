@@ -0,0 +1,98 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_bigtcp::iface::LinkStats;
+use aster_util::printer::VmPrinter;
+
+use super::net_ns_of;
+use crate::{
+    fs::{
+        file::mkmod,
+        procfs::{
+            pid::task::TidDirOps,
+            template::{ProcFile, ProcFileOps},
+        },
+        vfs::inode::Inode,
+    },
+    prelude::*,
+    thread::Thread,
+};
+
+/// Represents the inode at `/proc/[pid]/task/[tid]/net/dev` (and also `/proc/[pid]/net/dev`).
+pub struct DevFileOps(TidDirOps);
+
+impl DevFileOps {
+    pub fn new_inode(dir: &TidDirOps, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/net/core/net-procfs.c#L333>
+        ProcFile::new(Self(dir.clone()), parent, mkmod!(a+r))
+    }
+}
+
+impl ProcFileOps for DevFileOps {
+    fn owner_thread(&self) -> Option<Arc<Thread>> {
+        self.0.thread()
+    }
+
+    fn read_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        let net_ns = net_ns_of(&self.0)?;
+
+        let mut printer = VmPrinter::new_skip(writer, offset);
+
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/net/core/net-procfs.c#L79>
+        writeln!(
+            printer,
+            "Inter-|   Receive                            \
+             |  Transmit"
+        )?;
+        writeln!(
+            printer,
+            " face |bytes    packets errs drop fifo frame compressed multicast\
+             |bytes    packets errs drop fifo colls carrier compressed"
+        )?;
+
+        for iface in net_ns.ifaces() {
+            // The errors and the other counters that are not collected by the iface are reported
+            // as zeros.
+            let LinkStats {
+                rx_packets,
+                rx_bytes,
+                rx_dropped,
+                rx_multicast,
+                tx_packets,
+                tx_bytes,
+                tx_dropped,
+            } = iface.link_stats();
+            let stats: [u64; STAT_WIDTHS.len()] = [
+                rx_bytes,
+                rx_packets,
+                0,
+                rx_dropped,
+                0,
+                0,
+                0,
+                rx_multicast,
+                tx_bytes,
+                tx_packets,
+                0,
+                tx_dropped,
+                0,
+                0,
+                0,
+                0,
+            ];
+
+            write!(printer, "{:>6}:", iface.name().to_string_lossy())?;
+            for (i, (value, width)) in stats.iter().zip(STAT_WIDTHS).enumerate() {
+                let sep = if i == 0 { "" } else { " " };
+                write!(printer, "{}{:>width$}", sep, value, width = width)?;
+            }
+            writeln!(printer)?;
+        }
+
+        Ok(printer.bytes_written())
+    }
+}
+
+/// The widths of the receive and transmit statistics columns.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/net/core/net-procfs.c#L49>
+const STAT_WIDTHS: [usize; 16] = [8, 7, 4, 4, 4, 5, 10, 9, 8, 7, 4, 4, 4, 5, 7, 10];
@@ -0,0 +1,246 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::fmt::{self, Display};
+
+use aster_bigtcp::wire::{IpAddress, IpEndpoint};
+use aster_util::printer::VmPrinter;
+use ostd::timer::TIMER_FREQ;
+
+use super::net_ns_of;
+use crate::{
+    fs::{
+        file::mkmod,
+        procfs::{
+            pid::task::TidDirOps,
+            template::{ProcFile, ProcFileOps},
+        },
+        vfs::inode::Inode,
+    },
+    net::socket::ip::{
+        InetSocketEntry, stream_options::TCP_INFINITE_SSTHRESH, tcp_socket_entries,
+        udp_socket_entries,
+    },
+    prelude::*,
+    thread::Thread,
+};
+
+/// Represents the inodes at `/proc/[pid]/task/[tid]/net/{tcp,tcp6,udp,udp6}` (and also
+/// `/proc/[pid]/net/{tcp,tcp6,udp,udp6}`).
+pub struct InetFileOps {
+    dir: TidDirOps,
+    protocol: InetProtocol,
+    is_ipv6: bool,
+}
+
+#[derive(Clone, Copy)]
+enum InetProtocol {
+    Tcp,
+    Udp,
+}
+
+impl InetFileOps {
+    pub fn new_tcp_inode(dir: &TidDirOps, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/net/ipv4/tcp_ipv4.c#L3012>
+        Self::new_inode_with(dir.clone(), InetProtocol::Tcp, false, parent)
+    }
+
+    pub fn new_tcp6_inode(dir: &TidDirOps, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/net/ipv6/tcp_ipv6.c#L2203>
+        Self::new_inode_with(dir.clone(), InetProtocol::Tcp, true, parent)
+    }
+
+    pub fn new_udp_inode(dir: &TidDirOps, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/net/ipv4/udp.c#L3522>
+        Self::new_inode_with(dir.clone(), InetProtocol::Udp, false, parent)
+    }
+
+    pub fn new_udp6_inode(dir: &TidDirOps, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/net/ipv6/udp.c#L1849>
+        Self::new_inode_with(dir.clone(), InetProtocol::Udp, true, parent)
+    }
+
+    fn new_inode_with(
+        dir: TidDirOps,
+        protocol: InetProtocol,
+        is_ipv6: bool,
+        parent: Weak<dyn Inode>,
+    ) -> Arc<dyn Inode> {
+        ProcFile::new(
+            Self {
+                dir,
+                protocol,
+                is_ipv6,
+            },
+            parent,
+            mkmod!(a+r),
+        )
+    }
+}
+
+impl ProcFileOps for InetFileOps {
+    fn owner_thread(&self) -> Option<Arc<Thread>> {
+        self.dir.thread()
+    }
+
+    fn read_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        let net_ns = net_ns_of(&self.dir)?;
+
+        let entries = match self.protocol {
+            InetProtocol::Tcp => tcp_socket_entries(&net_ns),
+            InetProtocol::Udp => udp_socket_entries(&net_ns),
+        };
+
+        let mut printer = VmPrinter::new_skip(writer, offset);
+
+        // The headers are printed by `tcp4_seq_show`, `tcp6_seq_show`, `udp4_seq_show`, and
+        // `udp6_seq_show` in Linux.
+        match (self.protocol, self.is_ipv6) {
+            (InetProtocol::Tcp, false) => writeln!(
+                printer,
+                "{:<149}",
+                "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   \
+                 uid  timeout inode"
+            )?,
+            (InetProtocol::Tcp, true) => writeln!(
+                printer,
+                "  sl  local_address                         remote_address                        \
+                 st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode"
+            )?,
+            (InetProtocol::Udp, false) => writeln!(
+                printer,
+                "{:<127}",
+                "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   \
+                 uid  timeout inode ref pointer drops"
+            )?,
+            (InetProtocol::Udp, true) => writeln!(
+                printer,
+                "  sl  local_address                         remote_address                        \
+                 st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode ref pointer drops"
+            )?,
+        }
+
+        let entries = entries
+            .iter()
+            .filter(|entry| entry.is_ipv6() == self.is_ipv6);
+        for (index, entry) in entries.enumerate() {
+            let line = match self.protocol {
+                InetProtocol::Tcp => format_tcp_entry(index, entry),
+                InetProtocol::Udp => format_udp_entry(index, entry),
+            };
+
+            // The IPv4 lines are padded to a fixed width.
+            match (self.protocol, self.is_ipv6) {
+                (InetProtocol::Tcp, false) => writeln!(printer, "{:<149}", line)?,
+                (InetProtocol::Udp, false) => writeln!(printer, "{:<127}", line)?,
+                (_, true) => writeln!(printer, "{}", line)?,
+            }
+        }
+
+        Ok(printer.bytes_written())
+    }
+}
+
+/// Formats a TCP socket.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/net/ipv4/tcp_ipv4.c#L2875>
+fn format_tcp_entry(index: usize, entry: &InetSocketEntry) -> String {
+    let (ino, uid) = ino_and_uid(entry);
+    let tcp_info = entry.tcp_info.unwrap_or_default();
+
+    // Listeners have nothing to send. Their receive queues are the pending connections.
+    let tx_queue = if entry.is_listening() {
+        0
+    } else {
+        entry.send_queue
+    };
+
+    let ssthresh = if entry.is_listening() {
+        // This is the maximum length of the TCP Fast Open queue, which is not supported.
+        0
+    } else if tcp_info.snd_ssthresh() >= TCP_INFINITE_SSTHRESH {
+        -1
+    } else {
+        tcp_info.snd_ssthresh() as i64
+    };
+
+    // TODO: Report the pending timers.
+    format!(
+        "{:4}: {} {} {:02X} {:08X}:{:08X} {:02X}:{:08X} {:08X} {:5} {:8} {} {} {:016x} \
+         {} {} {} {} {}",
+        index,
+        ProcEndpoint(&entry.local_endpoint),
+        ProcEndpoint(&entry.remote_endpoint),
+        entry.state,
+        tx_queue as u32,
+        entry.recv_queue as u32,
+        0,
+        0,
+        tcp_info.retransmits(),
+        uid,
+        tcp_info.probes(),
+        ino,
+        1,
+        0,
+        micros_to_clock_ticks(tcp_info.rto()),
+        0,
+        0,
+        tcp_info.snd_cwnd(),
+        ssthresh,
+    )
+}
+
+/// Formats a UDP socket.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/net/ipv4/udp.c#L3458>
+fn format_udp_entry(index: usize, entry: &InetSocketEntry) -> String {
+    let (ino, uid) = ino_and_uid(entry);
+
+    format!(
+        "{:5}: {} {} {:02X} {:08X}:{:08X} {:02X}:{:08X} {:08X} {:5} {:8} {} {} {:016x} {}",
+        index,
+        ProcEndpoint(&entry.local_endpoint),
+        ProcEndpoint(&entry.remote_endpoint),
+        entry.state,
+        entry.send_queue as u32,
+        entry.recv_queue as u32,
+        0,
+        0,
+        0,
+        uid,
+        0,
+        ino,
+        1,
+        0,
+        0,
+    )
+}
+
+fn ino_and_uid(entry: &InetSocketEntry) -> (u64, u32) {
+    entry
+        .inode
+        .map_or((0, 0), |inode| (inode.ino(), inode.uid().into()))
+}
+
+fn micros_to_clock_ticks(micros: u32) -> u64 {
+    u64::from(micros) * TIMER_FREQ / 1_000_000
+}
+
+/// An endpoint formatted in the way of `/proc/net/{tcp,tcp6,udp,udp6}`.
+///
+/// The address is printed as 32-bit words in the native byte order and the port is printed in
+/// hexadecimal.
+struct ProcEndpoint<'a>(&'a IpEndpoint);
+
+impl Display for ProcEndpoint<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.addr {
+            IpAddress::Ipv4(addr) => write!(f, "{:08X}", u32::from_ne_bytes(addr.octets()))?,
+            IpAddress::Ipv6(addr) => {
+                for word in addr.octets().chunks_exact(4) {
+                    write!(f, "{:08X}", u32::from_ne_bytes(word.try_into().unwrap()))?;
+                }
+            }
+        }
+        write!(f, ":{:04X}", self.0.port)
+    }
+}
@@ -0,0 +1,90 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use self::{
+    dev::DevFileOps, inet::InetFileOps, route::RouteFileOps, snmp::SnmpFileOps, unix::UnixFileOps,
+};
+use super::TidDirOps;
+use crate::{
+    fs::{
+        file::{InodeType, mkmod},
+        procfs::{
+            StaticEntryWithOps,
+            template::{
+                ProcDir, ProcDirOps, ReaddirEntry, listed_entries_from_table,
+                lookup_child_from_table, visit_listed_entries,
+            },
+        },
+        vfs::inode::Inode,
+    },
+    net::net_ns::NetNamespace,
+    prelude::*,
+    process::posix_thread::AsPosixThread,
+    thread::Thread,
+};
+
+mod dev;
+mod inet;
+mod route;
+mod snmp;
+mod unix;
+
+/// Represents the inode at `/proc/[pid]/task/[tid]/net` (and also `/proc/[pid]/net`).
+pub struct NetDirOps(TidDirOps);
+
+impl NetDirOps {
+    pub fn new_inode(dir: &TidDirOps, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/proc/proc_net.c#L307>
+        ProcDir::new(Self(dir.clone()), parent, mkmod!(a+rx))
+    }
+
+    const STATIC_ENTRIES: &'static [StaticEntryWithOps<TidDirOps>] = &[
+        ("dev", InodeType::File, DevFileOps::new_inode),
+        ("route", InodeType::File, RouteFileOps::new_inode),
+        ("snmp", InodeType::File, SnmpFileOps::new_inode),
+        ("tcp", InodeType::File, InetFileOps::new_tcp_inode),
+        ("tcp6", InodeType::File, InetFileOps::new_tcp6_inode),
+        ("udp", InodeType::File, InetFileOps::new_udp_inode),
+        ("udp6", InodeType::File, InetFileOps::new_udp6_inode),
+        ("unix", InodeType::File, UnixFileOps::new_inode),
+    ];
+}
+
+impl ProcDirOps for NetDirOps {
+    fn owner_thread(&self) -> Option<Arc<Thread>> {
+        self.0.thread()
+    }
+
+    fn lookup_child(&self, this_dir: &ProcDir<Self>, name: &str) -> Result<Arc<dyn Inode>> {
+        if let Some(child) = lookup_child_from_table(name, Self::STATIC_ENTRIES, |f| {
+            (f)(&self.0, this_dir.this_weak().clone())
+        }) {
+            return Ok(child);
+        }
+
+        return_errno_with_message!(Errno::ENOENT, "the file does not exist");
+    }
+
+    fn visit_entries_from_offset<'a, F>(&'a self, offset: usize, visit_fn: F) -> Result<()>
+    where
+        F: FnMut(ReaddirEntry<'a>) -> Result<()>,
+    {
+        visit_listed_entries(
+            offset,
+            listed_entries_from_table(Self::STATIC_ENTRIES),
+            visit_fn,
+        )
+    }
+}
+
+/// Returns the network namespace of the thread.
+fn net_ns_of(dir: &TidDirOps) -> Result<Arc<NetNamespace>> {
+    let Some(thread) = dir.thread() else {
+        return_errno_with_message!(Errno::ESRCH, "the thread does not exist");
+    };
+
+    let ns_proxy_guard = thread.as_posix_thread().unwrap().ns_proxy().lock();
+    let ns_proxy = ns_proxy_guard
+        .as_ref()
+        .ok_or_else(|| Error::with_message(Errno::ESRCH, "the thread has exited"))?;
+    Ok(ns_proxy.net_ns().clone())
+}
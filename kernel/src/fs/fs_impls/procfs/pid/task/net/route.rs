@@ -0,0 +1,97 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_bigtcp::wire::{IpAddress, IpCidr};
+use aster_util::printer::VmPrinter;
+
+use super::net_ns_of;
+use crate::{
+    fs::{
+        file::mkmod,
+        procfs::{
+            pid::task::TidDirOps,
+            template::{ProcFile, ProcFileOps},
+        },
+        vfs::inode::Inode,
+    },
+    prelude::*,
+    thread::Thread,
+};
+
+/// Represents the inode at `/proc/[pid]/task/[tid]/net/route` (and also `/proc/[pid]/net/route`).
+pub struct RouteFileOps(TidDirOps);
+
+impl RouteFileOps {
+    pub fn new_inode(dir: &TidDirOps, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/net/ipv4/fib_trie.c#L3024>
+        ProcFile::new(Self(dir.clone()), parent, mkmod!(a+r))
+    }
+}
+
+impl ProcFileOps for RouteFileOps {
+    fn owner_thread(&self) -> Option<Arc<Thread>> {
+        self.0.thread()
+    }
+
+    fn read_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        let net_ns = net_ns_of(&self.0)?;
+        let ifaces = net_ns.ifaces();
+
+        let mut printer = VmPrinter::new_skip(writer, offset);
+
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/net/ipv4/fib_trie.c#L2967>
+        writeln!(
+            printer,
+            "{:<127}",
+            "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT"
+        )?;
+
+        // Only IPv4 routes are reported here.
+        for route in net_ns.routes() {
+            let IpCidr::Ipv4(dst) = route.dst else {
+                continue;
+            };
+            let Some(iface) = ifaces
+                .iter()
+                .find(|iface| iface.index() == route.iface_index)
+            else {
+                continue;
+            };
+
+            let gateway = match route.gateway {
+                Some(IpAddress::Ipv4(gateway)) => Some(gateway),
+                _ => None,
+            };
+            let flags = if gateway.is_some() {
+                RTF_UP | RTF_GATEWAY
+            } else {
+                RTF_UP
+            };
+            let mask = u32::MAX
+                .checked_shl(32 - u32::from(dst.prefix_len()))
+                .unwrap_or(0);
+
+            // The addresses are printed as 32-bit words in the native byte order.
+            let line = format!(
+                "{}\t{:08X}\t{:08X}\t{:04X}\t{}\t{}\t{}\t{:08X}\t{}\t{}\t{}",
+                iface.name().to_string_lossy(),
+                u32::from_ne_bytes(dst.address().octets()),
+                gateway.map_or(0, |gateway| u32::from_ne_bytes(gateway.octets())),
+                flags,
+                0,
+                0,
+                route.metric,
+                u32::from_ne_bytes(mask.to_be_bytes()),
+                0,
+                0,
+                0,
+            );
+            writeln!(printer, "{:<127}", line)?;
+        }
+
+        Ok(printer.bytes_written())
+    }
+}
+
+// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/include/uapi/linux/route.h#L51>
+const RTF_UP: u16 = 0x0001;
+const RTF_GATEWAY: u16 = 0x0002;
@@ -0,0 +1,222 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_bigtcp::iface::ProtocolStats;
+use aster_util::printer::VmPrinter;
+
+use super::net_ns_of;
+use crate::{
+    fs::{
+        file::mkmod,
+        procfs::{
+            pid::task::TidDirOps,
+            template::{ProcFile, ProcFileOps},
+        },
+        vfs::inode::Inode,
+    },
+    net::socket::ip::{
+        stream_options::{TCP_CLOSE_WAIT, TCP_ESTABLISHED},
+        tcp_socket_entries,
+    },
+    prelude::*,
+    thread::Thread,
+};
+
+/// Represents the inode at `/proc/[pid]/task/[tid]/net/snmp` (and also `/proc/[pid]/net/snmp`).
+pub struct SnmpFileOps(TidDirOps);
+
+impl SnmpFileOps {
+    pub fn new_inode(dir: &TidDirOps, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/net/ipv4/proc.c#L542>
+        ProcFile::new(Self(dir.clone()), parent, mkmod!(a+r))
+    }
+}
+
+impl ProcFileOps for SnmpFileOps {
+    fn owner_thread(&self) -> Option<Arc<Thread>> {
+        self.0.thread()
+    }
+
+    fn read_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        let net_ns = net_ns_of(&self.0)?;
+
+        let curr_estab = tcp_socket_entries(&net_ns)
+            .iter()
+            .filter(|entry| entry.state == TCP_ESTABLISHED || entry.state == TCP_CLOSE_WAIT)
+            .count();
+
+        // The statistics are collected by each iface, so they are summed up here.
+        let mut stats = ProtocolStats::default();
+        for iface in net_ns.ifaces() {
+            let iface_stats = iface.protocol_stats();
+            stats.ip_in_receives += iface_stats.ip_in_receives;
+            stats.ip_in_delivers += iface_stats.ip_in_delivers;
+            stats.ip_out_requests += iface_stats.ip_out_requests;
+            stats.tcp_in_segs += iface_stats.tcp_in_segs;
+            stats.tcp_out_segs += iface_stats.tcp_out_segs;
+            stats.udp_in_datagrams += iface_stats.udp_in_datagrams;
+            stats.udp_no_ports += iface_stats.udp_no_ports;
+            stats.udp_out_datagrams += iface_stats.udp_out_datagrams;
+        }
+
+        // The counters that are not collected by the network stack are reported as zeros.
+        let mut ip_values = [0i64; IP_FIELDS.len()];
+        ip_values[0] = IP_FORWARDING_DISABLED;
+        ip_values[1] = IP_DEFAULT_TTL;
+        ip_values[2] = stats.ip_in_receives as i64;
+        ip_values[8] = stats.ip_in_delivers as i64;
+        ip_values[9] = stats.ip_out_requests as i64;
+        // Packets are never fragmented, so every packet is transmitted as requested.
+        ip_values[19] = stats.ip_out_requests as i64;
+
+        let mut tcp_values = [0i64; TCP_FIELDS.len()];
+        tcp_values[0] = TCP_RTO_ALGORITHM_OTHER;
+        tcp_values[1] = TCP_RTO_MIN_MS;
+        tcp_values[2] = TCP_RTO_MAX_MS;
+        tcp_values[3] = TCP_MAX_CONN_DYNAMIC;
+        tcp_values[8] = curr_estab as i64;
+        tcp_values[9] = stats.tcp_in_segs as i64;
+        tcp_values[10] = stats.tcp_out_segs as i64;
+
+        let mut udp_values = [0i64; UDP_FIELDS.len()];
+        udp_values[0] = stats.udp_in_datagrams as i64;
+        udp_values[1] = stats.udp_no_ports as i64;
+        udp_values[3] = stats.udp_out_datagrams as i64;
+
+        let mut printer = VmPrinter::new_skip(writer, offset);
+
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/net/ipv4/proc.c#L397>
+        write_section(&mut printer, "Ip", IP_FIELDS, &ip_values)?;
+        write_section(&mut printer, "Icmp", ICMP_FIELDS, &[0; ICMP_FIELDS.len()])?;
+        write_section(&mut printer, "Tcp", TCP_FIELDS, &tcp_values)?;
+        write_section(&mut printer, "Udp", UDP_FIELDS, &udp_values)?;
+        write_section(&mut printer, "UdpLite", UDP_FIELDS, &[0; UDP_FIELDS.len()])?;
+
+        Ok(printer.bytes_written())
+    }
+}
+
+/// Writes the header line and the value line of a section.
+fn write_section(
+    printer: &mut VmPrinter,
+    name: &str,
+    fields: &[&str],
+    values: &[i64],
+) -> Result<()> {
+    write!(printer, "{}:", name)?;
+    for field in fields {
+        write!(printer, " {}", field)?;
+    }
+    writeln!(printer)?;
+
+    write!(printer, "{}:", name)?;
+    for value in values {
+        write!(printer, " {}", value)?;
+    }
+    writeln!(printer)?;
+
+    Ok(())
+}
+
+// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/net/ipv4/proc.c#L96>
+const IP_FIELDS: &[&str] = &[
+    "Forwarding",
+    "DefaultTTL",
+    "InReceives",
+    "InHdrErrors",
+    "InAddrErrors",
+    "ForwDatagrams",
+    "InUnknownProtos",
+    "InDiscards",
+    "InDelivers",
+    "OutRequests",
+    "OutDiscards",
+    "OutNoRoutes",
+    "ReasmTimeout",
+    "ReasmReqds",
+    "ReasmOKs",
+    "ReasmFails",
+    "FragOKs",
+    "FragFails",
+    "FragCreates",
+    "OutTransmits",
+];
+
+// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/net/ipv4/proc.c#L333>
+const ICMP_FIELDS: &[&str] = &[
+    "InMsgs",
+    "InErrors",
+    "InCsumErrors",
+    "InDestUnreachs",
+    "InTimeExcds",
+    "InParmProbs",
+    "InSrcQuenchs",
+    "InRedirects",
+    "InEchos",
+    "InEchoReps",
+    "InTimestamps",
+    "InTimestampReps",
+    "InAddrMasks",
+    "InAddrMaskReps",
+    "OutMsgs",
+    "OutErrors",
+    "OutRateLimitGlobal",
+    "OutRateLimitHost",
+    "OutDestUnreachs",
+    "OutTimeExcds",
+    "OutParmProbs",
+    "OutSrcQuenchs",
+    "OutRedirects",
+    "OutEchos",
+    "OutEchoReps",
+    "OutTimestamps",
+    "OutTimestampReps",
+    "OutAddrMasks",
+    "OutAddrMaskReps",
+];
+
+// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/net/ipv4/proc.c#L144>
+const TCP_FIELDS: &[&str] = &[
+    "RtoAlgorithm",
+    "RtoMin",
+    "RtoMax",
+    "MaxConn",
+    "ActiveOpens",
+    "PassiveOpens",
+    "AttemptFails",
+    "EstabResets",
+    "CurrEstab",
+    "InSegs",
+    "OutSegs",
+    "RetransSegs",
+    "InErrs",
+    "OutRsts",
+    "InCsumErrors",
+];
+
+// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/net/ipv4/proc.c#L162>
+const UDP_FIELDS: &[&str] = &[
+    "InDatagrams",
+    "NoPorts",
+    "InErrors",
+    "OutDatagrams",
+    "RcvbufErrors",
+    "SndbufErrors",
+    "InCsumErrors",
+    "IgnoredMulti",
+    "MemErrors",
+];
+
+/// The value of `Forwarding` if IP forwarding is disabled.
+///
+/// Reference: <https://datatracker.ietf.org/doc/html/rfc1213#page-26>
+const IP_FORWARDING_DISABLED: i64 = 2;
+const IP_DEFAULT_TTL: i64 = 64;
+
+/// The value of `RtoAlgorithm` that Linux always reports.
+///
+/// Reference: <https://datatracker.ietf.org/doc/html/rfc1213#page-46>
+const TCP_RTO_ALGORITHM_OTHER: i64 = 1;
+const TCP_RTO_MIN_MS: i64 = 200;
+const TCP_RTO_MAX_MS: i64 = 120_000;
+/// The value of `MaxConn` if the maximum number of connections is dynamic.
+const TCP_MAX_CONN_DYNAMIC: i64 = -1;
@@ -0,0 +1,99 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_util::printer::VmPrinter;
+
+use super::net_ns_of;
+use crate::{
+    fs::{
+        file::mkmod,
+        procfs::{
+            pid::task::TidDirOps,
+            template::{ProcFile, ProcFileOps},
+        },
+        vfs::inode::Inode,
+    },
+    net::socket::unix::{UnixSocketAddr, unix_socket_entries},
+    prelude::*,
+    thread::Thread,
+};
+
+/// Represents the inode at `/proc/[pid]/task/[tid]/net/unix` (and also `/proc/[pid]/net/unix`).
+pub struct UnixFileOps(TidDirOps);
+
+impl UnixFileOps {
+    pub fn new_inode(dir: &TidDirOps, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/net/unix/af_unix.c#L3727>
+        ProcFile::new(Self(dir.clone()), parent, mkmod!(a+r))
+    }
+}
+
+impl ProcFileOps for UnixFileOps {
+    fn owner_thread(&self) -> Option<Arc<Thread>> {
+        self.0.thread()
+    }
+
+    fn read_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        let net_ns = net_ns_of(&self.0)?;
+        let entries = unix_socket_entries(&net_ns);
+
+        let mut printer = VmPrinter::new_skip(writer, offset);
+
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/net/unix/af_unix.c#L3446>
+        writeln!(
+            printer,
+            "Num       RefCount Protocol Flags    Type St Inode Path"
+        )?;
+
+        for entry in entries {
+            let flags = if entry.is_listening() {
+                SO_ACCEPTCON
+            } else {
+                0
+            };
+            let socket_state = if entry.is_connected() {
+                SS_CONNECTED
+            } else {
+                SS_UNCONNECTED
+            };
+
+            write!(
+                printer,
+                "{:016x}: {:08X} {:08X} {:08X} {:04X} {:02X} {:5}",
+                0,
+                1,
+                0,
+                flags,
+                entry.sock_type as u16,
+                socket_state,
+                entry.ino()
+            )?;
+
+            match &entry.addr {
+                UnixSocketAddr::Unnamed => (),
+                UnixSocketAddr::Path(path) => write!(printer, " {}", path)?,
+                UnixSocketAddr::Abstract(name) => {
+                    // Like Linux, the raw bytes are printed, except that null bytes are printed
+                    // as `@`, like the leading one.
+                    let name: Vec<u8> = name
+                        .iter()
+                        .map(|byte| if *byte == 0 { b'@' } else { *byte })
+                        .collect();
+                    write!(printer, " @")?;
+                    printer.write_bytes(&name)?;
+                }
+            }
+            writeln!(printer)?;
+        }
+
+        Ok(printer.bytes_written())
+    }
+}
+
+/// The flag of listening sockets, which is called `__SO_ACCEPTCON` in Linux.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/include/uapi/linux/net.h#L52>
+const SO_ACCEPTCON: u32 = 1 << 16;
+
+// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/include/uapi/linux/net.h#L48>
+const SS_UNCONNECTED: u8 = 1;
+const SS_CONNECTED: u8 = 3;
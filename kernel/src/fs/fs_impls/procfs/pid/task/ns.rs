@@ -20,10 +20,11 @@ use crate::{
         },
     },
     ipc::IpcNamespace,
-    net::uts_ns::UtsNamespace,
+    net::{net_ns::NetNamespace, uts_ns::UtsNamespace},
     prelude::*,
-    process::{NsProxy, UserNamespace, posix_thread::AsPosixThread},
+    process::{NsProxy, PidNamespace, UserNamespace, posix_thread::AsPosixThread},
     thread::Thread,
+    time::time_ns::TimeNamespace,
 };
 
 /// Represents the inode at `/proc/[pid]/task/[tid]/ns` (and also `/proc/[pid]/ns`).
@@ -52,13 +53,30 @@ enum NsProxyEntry {
     Ipc,
     /// The mount namespace.
     Mnt,
+    /// The network namespace.
+    Net,
+    /// The PID namespace for children.
+    PidForChildren,
+    /// The time namespace.
+    Time,
+    /// The time namespace for children.
+    TimeForChildren,
     /// The UTS namespace.
     Uts,
 }
 
 impl NsProxyEntry {
     /// All supported `NsProxy`-backed namespace entries.
-    const ALL: &[Self] = &[Self::Cgroup, Self::Ipc, Self::Mnt, Self::Uts];
+    const ALL: &[Self] = &[
+        Self::Cgroup,
+        Self::Ipc,
+        Self::Mnt,
+        Self::Net,
+        Self::PidForChildren,
+        Self::Time,
+        Self::TimeForChildren,
+        Self::Uts,
+    ];
 
     /// Returns the filename of this namespace entry under `/proc/[pid]/ns/`.
     fn as_str(self) -> &'static str {
@@ -66,6 +84,10 @@ impl NsProxyEntry {
             Self::Cgroup => "cgroup",
             Self::Ipc => "ipc",
             Self::Mnt => "mnt",
+            Self::Net => "net",
+            Self::PidForChildren => "pid_for_children",
+            Self::Time => "time",
+            Self::TimeForChildren => "time_for_children",
             Self::Uts => "uts",
         }
     }
@@ -76,6 +98,10 @@ impl NsProxyEntry {
             "cgroup" => Some(Self::Cgroup),
             "ipc" => Some(Self::Ipc),
             "mnt" => Some(Self::Mnt),
+            "net" => Some(Self::Net),
+            "pid_for_children" => Some(Self::PidForChildren),
+            "time" => Some(Self::Time),
+            "time_for_children" => Some(Self::TimeForChildren),
             "uts" => Some(Self::Uts),
             _ => None,
         }
@@ -104,6 +130,26 @@ impl NsProxyEntry {
                 ns_proxy.mnt_ns().get_path(),
                 parent,
             ),
+            Self::Net => NsSymOps::<NetNamespace>::new_inode(
+                dir.clone(),
+                ns_proxy.net_ns().get_path(),
+                parent,
+            ),
+            Self::PidForChildren => NsSymOps::<PidNamespace>::new_inode(
+                dir.clone(),
+                ns_proxy.pid_ns_for_children().get_path(),
+                parent,
+            ),
+            Self::Time => NsSymOps::<TimeNamespace>::new_inode(
+                dir.clone(),
+                ns_proxy.time_ns().get_path(),
+                parent,
+            ),
+            Self::TimeForChildren => NsSymOps::<TimeNamespace>::new_inode(
+                dir.clone(),
+                ns_proxy.time_ns_for_children().get_path(),
+                parent,
+            ),
             Self::Uts => NsSymOps::<UtsNamespace>::new_inode(
                 dir.clone(),
                 ns_proxy.uts_ns().get_path(),
@@ -126,6 +172,15 @@ fn cached_ns_path(inode: &dyn Inode) -> Option<&Path> {
     if let Some(sym) = inode.downcast_ref::<NsSymlink<MountNamespace>>() {
         return Some(&sym.inner().ns_path);
     }
+    if let Some(sym) = inode.downcast_ref::<NsSymlink<NetNamespace>>() {
+        return Some(&sym.inner().ns_path);
+    }
+    if let Some(sym) = inode.downcast_ref::<NsSymlink<PidNamespace>>() {
+        return Some(&sym.inner().ns_path);
+    }
+    if let Some(sym) = inode.downcast_ref::<NsSymlink<TimeNamespace>>() {
+        return Some(&sym.inner().ns_path);
+    }
     if let Some(sym) = inode.downcast_ref::<NsSymlink<UserNamespace>>() {
         return Some(&sym.inner().ns_path);
     }
@@ -155,6 +210,18 @@ impl ProcDirOps for NsDirOps {
             ));
         }
 
+        if name == "pid" {
+            let Some(process) = self.dir.process() else {
+                return_errno_with_message!(Errno::ESRCH, "the process does not exist");
+            };
+
+            return Ok(NsSymOps::<PidNamespace>::new_inode(
+                self.dir.clone(),
+                process.pid_ns().get_path(),
+                this_dir.this_weak().clone(),
+            ));
+        }
+
         // Validate the name and get the current namespace path.
         let entry = NsProxyEntry::from_str(name)
             .ok_or_else(|| Error::with_message(Errno::ENOENT, "the file does not exist"))?;
@@ -194,9 +261,11 @@ impl ProcDirOps for NsDirOps {
                     .map(|entry| ListedEntry::new(entry.as_str(), InodeType::SymLink))
             });
 
-        let user_entry = Some(ListedEntry::new("user", InodeType::SymLink)).into_iter();
+        let process_entries = ["pid", "user"]
+            .into_iter()
+            .map(|name| ListedEntry::new(name, InodeType::SymLink));
 
-        visit_listed_entries(offset, ns_proxy_entries.chain(user_entry), visit_fn)
+        visit_listed_entries(offset, ns_proxy_entries.chain(process_entries), visit_fn)
     }
 
     fn revalidation_policy(&self) -> RevalidationPolicy {
@@ -205,7 +274,7 @@ impl ProcDirOps for NsDirOps {
         RevalidationPolicy::REVALIDATE_EXISTS
     }
 
-    fn revalidate_exists(&self, _name: &str, child: &dyn Inode) -> bool {
+    fn revalidate_exists(&self, name: &str, child: &dyn Inode) -> bool {
         let Some(cached_path) = cached_ns_path(child) else {
             return false;
         };
@@ -218,6 +287,15 @@ impl ProcDirOps for NsDirOps {
             return cached_path == &user_ns.get_path();
         }
 
+        // Both "pid" and "pid_for_children" refer to PID namespaces, but only the former is backed
+        // by the process.
+        if name == "pid" {
+            let Some(process) = self.dir.process() else {
+                return false;
+            };
+            return cached_path == &process.pid_ns().get_path();
+        }
+
         let Some(thread) = self.dir.thread() else {
             return false;
         };
@@ -242,6 +320,24 @@ impl ProcDirOps for NsDirOps {
             return cached_path == &ns_proxy.ipc_ns().get_path();
         }
 
+        if child.downcast_ref::<NsSymlink<NetNamespace>>().is_some() {
+            return cached_path == &ns_proxy.net_ns().get_path();
+        }
+
+        if child.downcast_ref::<NsSymlink<PidNamespace>>().is_some() {
+            return cached_path == &ns_proxy.pid_ns_for_children().get_path();
+        }
+
+        // Both "time" and "time_for_children" refer to time namespaces.
+        if child.downcast_ref::<NsSymlink<TimeNamespace>>().is_some() {
+            let time_ns = if name == "time" {
+                ns_proxy.time_ns()
+            } else {
+                ns_proxy.time_ns_for_children()
+            };
+            return cached_path == &time_ns.get_path();
+        }
+
         // TODO: Support additional namespace types.
         false
     }
@@ -130,7 +130,8 @@ impl ProcFileOps for StatFileOps {
         //
         // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/proc/array.c#L467-L681>
 
-        let pid = posix_thread.tid();
+        let pid_ns = self.dir.pid_ns();
+        let pid = pid_ns.local_id(posix_thread.tid());
 
         let comm = posix_thread
             .thread_name()
@@ -149,9 +150,9 @@ impl ProcFileOps for StatFileOps {
                 SleepingState::StopByPtrace => 't',
             }
         };
-        let ppid = process.parent().pid();
-        let pgrp = process.pgid();
-        let session = process.sid();
+        let ppid = pid_ns.local_id(process.parent().pid());
+        let pgrp = pid_ns.local_id(process.pgid());
+        let session = pid_ns.local_id(process.sid());
 
         let (tty_nr, tpgid) = if let Some(terminal) = process.terminal() {
             (
@@ -159,7 +160,7 @@ impl ProcFileOps for StatFileOps {
                 terminal
                     .job_control()
                     .foreground()
-                    .map(|pgrp| pgrp.pgid() as i64)
+                    .map(|pgrp| pid_ns.local_id(pgrp.pgid()) as i64)
                     .unwrap_or(-1),
             )
         } else {
@@ -10,10 +10,7 @@ use crate::{
         vfs::inode::Inode,
     },
     prelude::*,
-    process::{
-        credentials::AMBIENT_CAPSET,
-        posix_thread::{AsPosixThread, SleepingState},
-    },
+    process::posix_thread::{AsPosixThread, SleepingState},
     thread::Thread,
     vm::vmar::RssType,
 };
@@ -33,6 +30,10 @@ use crate::{
 /// - Gid:    Real, effective, saved set, and filesystem GIDs.
 /// - FDSize: The number of file descriptor slots currently allocated.
 /// - Groups: Supplementary group IDs.
+/// - NStgid: Thread group IDs in the PID namespaces, from the outermost visible one.
+/// - NSpid:  Thread IDs in the PID namespaces, from the outermost visible one.
+/// - NSpgid: Process group IDs in the PID namespaces, from the outermost visible one.
+/// - NSsid:  Session IDs in the PID namespaces, from the outermost visible one.
 /// - VmPeak: Peak virtual memory size.
 /// - VmSize: Current virtual memory size.
 /// - VmLck:  Locked memory size.
@@ -57,7 +58,9 @@ use crate::{
 /// - CapEff: Effective capabilities.
 /// - CapBnd: Bounding set.
 /// - CapAmb: Ambient capabilities.
+/// - NoNewPrivs: Whether the `no_new_privs` attribute is set.
 /// - Seccomp: Seccomp mode.
+/// - Seccomp_filters: Number of attached seccomp filters.
 /// - Cpus_allowed: CPUs allowed for this process.
 /// - Cpus_allowed_list: List of CPUs allowed for this process.
 /// - Mems_allowed: Memory nodes allowed for this process.
@@ -113,15 +116,24 @@ impl ProcFileOps for StatusFileOps {
         };
         writeln!(printer, "State:\t{}", state)?;
 
-        writeln!(printer, "Tgid:\t{}", process.pid())?;
-        writeln!(printer, "Pid:\t{}", posix_thread.tid())?;
-        writeln!(printer, "PPid:\t{}", process.parent().pid())?;
+        let procfs_pid_ns = self.0.pid_ns();
+        writeln!(printer, "Tgid:\t{}", procfs_pid_ns.local_id(process.pid()))?;
+        writeln!(
+            printer,
+            "Pid:\t{}",
+            procfs_pid_ns.local_id(posix_thread.tid())
+        )?;
+        writeln!(
+            printer,
+            "PPid:\t{}",
+            procfs_pid_ns.local_id(process.parent().pid())
+        )?;
         writeln!(
             printer,
             "TracerPid:\t{}",
             posix_thread
                 .tracer()
-                .map(|tracer| tracer.as_posix_thread().unwrap().tid())
+                .map(|tracer| procfs_pid_ns.local_id(tracer.as_posix_thread().unwrap().tid()))
                 .unwrap_or(0)
         )?;
 
@@ -153,6 +165,20 @@ impl ProcFileOps for StatusFileOps {
                 .unwrap_or(0)
         )?;
 
+        let pid_ns = process.pid_ns();
+        for (name, id) in [
+            ("NStgid", process.pid()),
+            ("NSpid", posix_thread.tid()),
+            ("NSpgid", process.pgid()),
+            ("NSsid", process.sid()),
+        ] {
+            write!(printer, "{}:", name)?;
+            for local_id in pid_ns.local_ids_from(procfs_pid_ns, id) {
+                write!(printer, "\t{}", local_id)?;
+            }
+            writeln!(printer)?;
+        }
+
         if let Some(vmar_ref) = process.lock_vmar().as_ref() {
             let vsize = vmar_ref.get_mappings_total_size();
             let anon = vmar_ref.get_rss_counter(RssType::Anon) * (PAGE_SIZE / 1024);
@@ -191,7 +217,23 @@ impl ProcFileOps for StatusFileOps {
             "CapBnd:\t{:016x}",
             credentials.bounding_capset().bits()
         )?;
-        writeln!(printer, "CapAmb:\t{:016x}", AMBIENT_CAPSET.bits())?;
+        writeln!(
+            printer,
+            "CapAmb:\t{:016x}",
+            credentials.ambient_capset().bits()
+        )?;
+
+        writeln!(
+            printer,
+            "NoNewPrivs:\t{}",
+            posix_thread.no_new_privs() as u8
+        )?;
+        writeln!(printer, "Seccomp:\t{}", posix_thread.seccomp_mode() as u8)?;
+        writeln!(
+            printer,
+            "Seccomp_filters:\t{}",
+            posix_thread.num_seccomp_filters()
+        )?;
 
         Ok(printer.bytes_written())
     }
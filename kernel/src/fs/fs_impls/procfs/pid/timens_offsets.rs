@@ -0,0 +1,137 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_util::printer::VmPrinter;
+
+use super::{PidDirOps, TidDirOps};
+use crate::{
+    fs::{
+        file::mkmod,
+        procfs::template::{ProcFile, ProcFileOps},
+        vfs::inode::Inode,
+    },
+    prelude::*,
+    process::posix_thread::AsPosixThread,
+    syscall::ClockId,
+    thread::Thread,
+    time::time_ns::{ClockOffset, TimeNamespace},
+};
+
+/// Represents the inode at `/proc/[pid]/timens_offsets`.
+///
+/// The file shows the clock offsets of the time namespace for children of the process. The
+/// offsets can be changed until a thread enters the time namespace.
+pub struct TimensOffsetsFileOps(TidDirOps);
+
+impl TimensOffsetsFileOps {
+    pub fn new_inode(dir: &PidDirOps, parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.18/source/fs/proc/base.c>
+        ProcFile::new(Self(dir.tid_dir_ops().clone()), parent, mkmod!(a+r, u+w))
+    }
+
+    fn time_ns_for_children(&self) -> Result<Arc<TimeNamespace>> {
+        let Some(thread) = self.0.thread() else {
+            return_errno_with_message!(Errno::ESRCH, "the process does not exist");
+        };
+
+        let ns_proxy = thread.as_posix_thread().unwrap().ns_proxy().lock();
+        let Some(ns_proxy) = ns_proxy.as_ref() else {
+            return_errno_with_message!(Errno::ESRCH, "the process has exited");
+        };
+        Ok(ns_proxy.time_ns_for_children().clone())
+    }
+}
+
+impl ProcFileOps for TimensOffsetsFileOps {
+    fn owner_thread(&self) -> Option<Arc<Thread>> {
+        self.0.thread()
+    }
+
+    fn read_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        let offsets = self.time_ns_for_children()?.offsets();
+
+        let mut printer = VmPrinter::new_skip(writer, offset);
+        for (name, offset) in [
+            ("monotonic", offsets.monotonic()),
+            ("boottime", offsets.boottime()),
+        ] {
+            writeln!(
+                printer,
+                "{:<10} {:>10} {:>9}",
+                name,
+                offset.secs(),
+                offset.nanos()
+            )?;
+        }
+
+        Ok(printer.bytes_written())
+    }
+
+    fn write_at(&self, _offset: usize, reader: &mut VmReader) -> Result<usize> {
+        let (cstr, read_bytes) = reader.read_cstring_until_end(PAGE_SIZE)?;
+        let input = cstr
+            .to_str()
+            .map_err(|_| Error::with_message(Errno::EINVAL, "the input is not valid UTF-8"))?;
+
+        let (offsets, parsed_len) = parse_offsets(input)?;
+
+        let time_ns = self.time_ns_for_children()?;
+        time_ns.set_offsets(&offsets, current_thread!().as_posix_thread().unwrap())?;
+
+        Ok(parsed_len.unwrap_or(read_bytes))
+    }
+}
+
+/// The maximum number of offsets that can be written at once.
+const MAX_NR_OFFSETS: usize = 2;
+
+/// Parses lines in the form of `<clock> <secs> <nanos>`.
+///
+/// Only the first [`MAX_NR_OFFSETS`] lines are parsed. If there are more lines, the number of
+/// bytes parsed is returned along with the offsets.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/fs/proc/base.c>
+fn parse_offsets(input: &str) -> Result<(Vec<(ClockId, ClockOffset)>, Option<usize>)> {
+    let mut offsets = Vec::with_capacity(MAX_NR_OFFSETS);
+
+    let mut pos = 0;
+    loop {
+        let rest = &input[pos..];
+        let (line, next_pos) = match rest.find('\n') {
+            Some(len) if len + 1 < rest.len() => (&rest[..len], Some(pos + len + 1)),
+            Some(len) => (&rest[..len], None),
+            None => (rest, None),
+        };
+
+        offsets.push(parse_offset(line)?);
+
+        let Some(next_pos) = next_pos else {
+            return Ok((offsets, None));
+        };
+        if offsets.len() == MAX_NR_OFFSETS {
+            return Ok((offsets, Some(next_pos)));
+        }
+        pos = next_pos;
+    }
+}
+
+fn parse_offset(line: &str) -> Result<(ClockId, ClockOffset)> {
+    let mut fields = line.split_whitespace();
+    let (Some(clock), Some(secs), Some(nanos)) = (fields.next(), fields.next(), fields.next())
+    else {
+        return_errno_with_message!(Errno::EINVAL, "the offset is incomplete");
+    };
+
+    let clock_id = match clock {
+        "monotonic" | "1" => ClockId::CLOCK_MONOTONIC,
+        "boottime" | "7" => ClockId::CLOCK_BOOTTIME,
+        _ => return_errno_with_message!(Errno::EINVAL, "the clock is invalid"),
+    };
+    let secs = secs
+        .parse::<i64>()
+        .map_err(|_| Error::with_message(Errno::EINVAL, "the seconds are invalid"))?;
+    let nanos = nanos
+        .parse::<u32>()
+        .map_err(|_| Error::with_message(Errno::EINVAL, "the nanoseconds are invalid"))?;
+
+    Ok((clock_id, ClockOffset::new(secs, nanos)?))
+}
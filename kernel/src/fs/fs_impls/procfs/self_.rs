@@ -3,24 +3,39 @@
 use crate::{
     fs::{
         file::mkmod,
-        procfs::template::{ProcSym, ProcSymOps},
+        procfs::{
+            RootDirOps,
+            template::{ProcSym, ProcSymOps},
+        },
         vfs::inode::{Inode, SymbolicLink},
     },
     prelude::*,
+    process::PidNamespace,
 };
 
 /// Represents the inode at `/proc/self`.
-pub struct SelfSymOps;
+pub struct SelfSymOps {
+    pid_ns: Arc<PidNamespace>,
+}
 
 impl SelfSymOps {
     pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        let pid_ns = RootDirOps::pid_ns_of(&parent);
         // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/proc/self.c#L50>
-        ProcSym::new(Self, parent, mkmod!(a+rwx))
+        ProcSym::new(Self { pid_ns }, parent, mkmod!(a+rwx))
     }
 }
 
 impl ProcSymOps for SelfSymOps {
     fn read_link(&self) -> Result<SymbolicLink> {
-        Ok(SymbolicLink::Plain(current!().pid().to_string()))
+        let pid = self.pid_ns.local_id(current!().pid());
+        if pid == 0 {
+            return_errno_with_message!(
+                Errno::ENOENT,
+                "the current process is not visible in the PID namespace"
+            );
+        }
+
+        Ok(SymbolicLink::Plain(pid.to_string()))
     }
 }
@@ -0,0 +1,48 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_util::printer::VmPrinter;
+
+use crate::{
+    fs::{
+        file::mkmod,
+        procfs::template::{ProcFile, ProcFileOps},
+        vfs::inode::Inode,
+    },
+    prelude::*,
+    process::{CORENAME_MAX_SIZE, core_pattern, set_core_pattern},
+};
+
+/// Represents the inode at `/proc/sys/kernel/core_pattern`.
+pub struct CorePatternFileOps;
+
+impl CorePatternFileOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.18/source/fs/coredump.c>
+        ProcFile::new(Self, parent, mkmod!(a+r, u+w))
+    }
+}
+
+impl ProcFileOps for CorePatternFileOps {
+    fn read_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        let mut printer = VmPrinter::new_skip(writer, offset);
+
+        writeln!(printer, "{}", core_pattern())?;
+
+        Ok(printer.bytes_written())
+    }
+
+    fn write_at(&self, _offset: usize, reader: &mut VmReader) -> Result<usize> {
+        let (cstr, read_bytes) = reader.read_cstring_until_end(CORENAME_MAX_SIZE - 1)?;
+        let pattern = cstr.to_str().map_err(|_| {
+            Error::with_message(Errno::EINVAL, "the core pattern is not valid UTF-8")
+        })?;
+
+        set_core_pattern(pattern);
+
+        // Like Linux, the bytes that do not fit are silently discarded.
+        let remain = reader.remain();
+        reader.skip(remain);
+
+        Ok(read_bytes + remain)
+    }
+}
@@ -0,0 +1,74 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_util::printer::VmPrinter;
+use ostd::task::Task;
+
+use crate::{
+    fs::{
+        file::mkmod,
+        procfs::template::{ProcFile, ProcFileOps, read_i32_from},
+        vfs::inode::Inode,
+    },
+    ipc::IpcNamespace,
+    prelude::*,
+};
+
+/// Represents the inode of an integer limit in the IPC namespace of the current thread.
+///
+/// The files include:
+/// - `/proc/sys/kernel/msgmax`, the maximum size in bytes of a message;
+/// - `/proc/sys/kernel/msgmnb`, the default maximum number of bytes in a message queue.
+pub struct IpcLimitFileOps {
+    get: fn(&IpcNamespace) -> usize,
+    set: fn(&IpcNamespace, usize),
+}
+
+impl IpcLimitFileOps {
+    pub fn new_msgmax_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        Self::new_inode(parent, IpcNamespace::msg_max, IpcNamespace::set_msg_max)
+    }
+
+    pub fn new_msgmnb_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        Self::new_inode(parent, IpcNamespace::msg_mnb, IpcNamespace::set_msg_mnb)
+    }
+
+    fn new_inode(
+        parent: Weak<dyn Inode>,
+        get: fn(&IpcNamespace) -> usize,
+        set: fn(&IpcNamespace, usize),
+    ) -> Arc<dyn Inode> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/ipc/ipc_sysctl.c>
+        ProcFile::new(Self { get, set }, parent, mkmod!(a+r, u+w))
+    }
+}
+
+impl ProcFileOps for IpcLimitFileOps {
+    fn read_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        let current_task = Task::current().unwrap();
+        let thread_local = current_task.as_thread_local().unwrap();
+        let ns_proxy = thread_local.borrow_ns_proxy();
+        let ipc_ns = ns_proxy.unwrap().ipc_ns();
+
+        let mut printer = VmPrinter::new_skip(writer, offset);
+
+        writeln!(printer, "{}", (self.get)(ipc_ns))?;
+
+        Ok(printer.bytes_written())
+    }
+
+    fn write_at(&self, _offset: usize, reader: &mut VmReader) -> Result<usize> {
+        let (val, read_bytes) = read_i32_from(reader)?;
+        if val < 0 {
+            return_errno_with_message!(Errno::EINVAL, "the value must not be negative");
+        }
+
+        let current_task = Task::current().unwrap();
+        let thread_local = current_task.as_thread_local().unwrap();
+        let ns_proxy = thread_local.borrow_ns_proxy();
+        let ipc_ns = ns_proxy.unwrap().ipc_ns();
+
+        (self.set)(ipc_ns, val as usize);
+
+        Ok(read_bytes)
+    }
+}
@@ -0,0 +1,76 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::{
+    fs::{
+        file::{InodeType, mkmod},
+        procfs::{
+            ProcDir, StaticEntry,
+            sys::net::ipv4::{
+                ping_group_range::PingGroupRangeFileOps,
+                tcp_available_congestion_control::TcpAvailableCongestionControlFileOps,
+                tcp_congestion_control::TcpCongestionControlFileOps,
+            },
+            template::{
+                ProcDirOps, ReaddirEntry, listed_entries_from_table, lookup_child_from_table,
+                visit_listed_entries,
+            },
+        },
+        vfs::inode::Inode,
+    },
+    prelude::*,
+};
+
+mod ping_group_range;
+mod tcp_available_congestion_control;
+mod tcp_congestion_control;
+
+/// Represents the inode at `/proc/sys/net/ipv4`.
+pub struct Ipv4DirOps;
+
+impl Ipv4DirOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/ipv4/sysctl_net_ipv4.c>
+        ProcDir::new(Self, parent, mkmod!(a+rx))
+    }
+
+    const STATIC_ENTRIES: &'static [StaticEntry] = &[
+        (
+            "ping_group_range",
+            InodeType::File,
+            PingGroupRangeFileOps::new_inode,
+        ),
+        (
+            "tcp_available_congestion_control",
+            InodeType::File,
+            TcpAvailableCongestionControlFileOps::new_inode,
+        ),
+        (
+            "tcp_congestion_control",
+            InodeType::File,
+            TcpCongestionControlFileOps::new_inode,
+        ),
+    ];
+}
+
+impl ProcDirOps for Ipv4DirOps {
+    fn lookup_child(&self, this_dir: &ProcDir<Self>, name: &str) -> Result<Arc<dyn Inode>> {
+        if let Some(child) = lookup_child_from_table(name, Self::STATIC_ENTRIES, |f| {
+            (f)(this_dir.this_weak().clone())
+        }) {
+            return Ok(child);
+        }
+
+        return_errno_with_message!(Errno::ENOENT, "the file does not exist");
+    }
+
+    fn visit_entries_from_offset<'a, F>(&'a self, offset: usize, visit_fn: F) -> Result<()>
+    where
+        F: FnMut(ReaddirEntry<'a>) -> Result<()>,
+    {
+        visit_listed_entries(
+            offset,
+            listed_entries_from_table(Self::STATIC_ENTRIES),
+            visit_fn,
+        )
+    }
+}
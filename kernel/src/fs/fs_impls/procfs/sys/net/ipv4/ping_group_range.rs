@@ -0,0 +1,81 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_util::printer::VmPrinter;
+use ostd::task::Task;
+
+use crate::{
+    fs::{
+        file::mkmod,
+        procfs::template::{ProcFile, ProcFileOps},
+        vfs::inode::Inode,
+    },
+    prelude::*,
+    process::Gid,
+};
+
+/// Represents the inode at `/proc/sys/net/ipv4/ping_group_range`.
+///
+/// The file shows the range of groups that are allowed to create ping sockets in the network
+/// namespace of the current thread.
+pub struct PingGroupRangeFileOps;
+
+impl PingGroupRangeFileOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/ipv4/sysctl_net_ipv4.c>
+        ProcFile::new(Self, parent, mkmod!(a+r, u+w))
+    }
+}
+
+/// The maximum length of the content to write, which holds two integers.
+const MAX_WRITE_LEN: usize = 32;
+
+impl ProcFileOps for PingGroupRangeFileOps {
+    fn read_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        let current_task = Task::current().unwrap();
+        let thread_local = current_task.as_thread_local().unwrap();
+        let ns_proxy = thread_local.borrow_ns_proxy();
+        let net_ns = ns_proxy.unwrap().net_ns();
+
+        let (low, high) = net_ns.ping_group_range();
+
+        let mut printer = VmPrinter::new_skip(writer, offset);
+
+        writeln!(printer, "{}\t{}", u32::from(low), u32::from(high))?;
+
+        Ok(printer.bytes_written())
+    }
+
+    fn write_at(&self, _offset: usize, reader: &mut VmReader) -> Result<usize> {
+        let (cstr, read_bytes) = reader.read_cstring_until_end(MAX_WRITE_LEN)?;
+
+        let current_task = Task::current().unwrap();
+        let thread_local = current_task.as_thread_local().unwrap();
+        let ns_proxy = thread_local.borrow_ns_proxy();
+        let net_ns = ns_proxy.unwrap().net_ns();
+
+        let (low, high) = cstr
+            .to_str()
+            .ok()
+            .and_then(|content| parse_range(content, net_ns.ping_group_range()))
+            .ok_or_else(|| Error::with_message(Errno::EINVAL, "the range is not valid"))?;
+
+        net_ns.set_ping_group_range(low, high);
+
+        Ok(read_bytes)
+    }
+}
+
+/// Parses the range from at most two non-negative integers separated by whitespace.
+///
+/// Like Linux, the bounds that are not specified remain unchanged, and the extra integers are
+/// ignored.
+fn parse_range(content: &str, (low, high): (Gid, Gid)) -> Option<(Gid, Gid)> {
+    let mut bounds = [low, high];
+    for (bound, value) in bounds.iter_mut().zip(content.split_ascii_whitespace()) {
+        let value = value.parse::<i32>().ok().filter(|value| *value >= 0)?;
+        *bound = Gid::new(value as u32);
+    }
+
+    let [low, high] = bounds;
+    Some((low, high))
+}
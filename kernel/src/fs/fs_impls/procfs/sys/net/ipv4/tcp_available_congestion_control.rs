@@ -0,0 +1,36 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_bigtcp::socket::CongestionControl;
+use aster_util::printer::VmPrinter;
+
+use crate::{
+    fs::{
+        file::mkmod,
+        procfs::template::{ProcFile, ProcFileOps},
+        vfs::inode::Inode,
+    },
+    prelude::*,
+};
+
+/// Represents the inode at `/proc/sys/net/ipv4/tcp_available_congestion_control`.
+///
+/// The file lists the names of all available TCP congestion control algorithms.
+pub struct TcpAvailableCongestionControlFileOps;
+
+impl TcpAvailableCongestionControlFileOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/ipv4/sysctl_net_ipv4.c>
+        ProcFile::new(Self, parent, mkmod!(a+r))
+    }
+}
+
+impl ProcFileOps for TcpAvailableCongestionControlFileOps {
+    fn read_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        let mut printer = VmPrinter::new_skip(writer, offset);
+
+        let names = CongestionControl::ALL.map(CongestionControl::name);
+        writeln!(printer, "{}", names.join(" "))?;
+
+        Ok(printer.bytes_written())
+    }
+}
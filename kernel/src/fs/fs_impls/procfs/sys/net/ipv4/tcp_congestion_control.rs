@@ -0,0 +1,72 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_bigtcp::socket::CongestionControl;
+use aster_util::printer::VmPrinter;
+use ostd::task::Task;
+
+use crate::{
+    fs::{
+        file::mkmod,
+        procfs::template::{ProcFile, ProcFileOps},
+        vfs::inode::Inode,
+    },
+    prelude::*,
+};
+
+/// Represents the inode at `/proc/sys/net/ipv4/tcp_congestion_control`.
+///
+/// The file shows the default congestion control algorithm of new TCP sockets in the network
+/// namespace of the current thread.
+pub struct TcpCongestionControlFileOps;
+
+impl TcpCongestionControlFileOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/ipv4/sysctl_net_ipv4.c>
+        ProcFile::new(Self, parent, mkmod!(a+r, u+w))
+    }
+}
+
+/// The maximum length of the content to write, which holds an algorithm name.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/net/tcp.h>
+const MAX_WRITE_LEN: usize = 16;
+
+impl ProcFileOps for TcpCongestionControlFileOps {
+    fn read_at(&self, offset: usize, writer: &mut VmWriter) -> Result<usize> {
+        let current_task = Task::current().unwrap();
+        let thread_local = current_task.as_thread_local().unwrap();
+        let ns_proxy = thread_local.borrow_ns_proxy();
+        let net_ns = ns_proxy.unwrap().net_ns();
+
+        let control = net_ns.tcp_congestion_control();
+
+        let mut printer = VmPrinter::new_skip(writer, offset);
+
+        writeln!(printer, "{}", control.name())?;
+
+        Ok(printer.bytes_written())
+    }
+
+    fn write_at(&self, _offset: usize, reader: &mut VmReader) -> Result<usize> {
+        let (cstr, read_bytes) = reader.read_cstring_until_end(MAX_WRITE_LEN)?;
+
+        // Like Linux, the content is truncated at the first newline character.
+        let control = cstr
+            .to_str()
+            .ok()
+            .and_then(|content| content.split('\n').next())
+            .and_then(CongestionControl::from_name)
+            .ok_or_else(|| {
+                Error::with_message(Errno::ENOENT, "unknown congestion control algorithm")
+            })?;
+
+        let current_task = Task::current().unwrap();
+        let thread_local = current_task.as_thread_local().unwrap();
+        let ns_proxy = thread_local.borrow_ns_proxy();
+        let net_ns = ns_proxy.unwrap().net_ns();
+
+        net_ns.set_tcp_congestion_control(control);
+
+        Ok(read_bytes)
+    }
+}
@@ -0,0 +1,55 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::{
+    fs::{
+        file::{InodeType, mkmod},
+        procfs::{
+            ProcDir, StaticEntry,
+            sys::net::ipv4::Ipv4DirOps,
+            template::{
+                ProcDirOps, ReaddirEntry, listed_entries_from_table, lookup_child_from_table,
+                visit_listed_entries,
+            },
+        },
+        vfs::inode::Inode,
+    },
+    prelude::*,
+};
+
+mod ipv4;
+
+/// Represents the inode at `/proc/sys/net`.
+pub struct NetDirOps;
+
+impl NetDirOps {
+    pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/sysctl_net.c>
+        ProcDir::new(Self, parent, mkmod!(a+rx))
+    }
+
+    const STATIC_ENTRIES: &'static [StaticEntry] =
+        &[("ipv4", InodeType::Dir, Ipv4DirOps::new_inode)];
+}
+
+impl ProcDirOps for NetDirOps {
+    fn lookup_child(&self, this_dir: &ProcDir<Self>, name: &str) -> Result<Arc<dyn Inode>> {
+        if let Some(child) = lookup_child_from_table(name, Self::STATIC_ENTRIES, |f| {
+            (f)(this_dir.this_weak().clone())
+        }) {
+            return Ok(child);
+        }
+
+        return_errno_with_message!(Errno::ENOENT, "the file does not exist");
+    }
+
+    fn visit_entries_from_offset<'a, F>(&'a self, offset: usize, visit_fn: F) -> Result<()>
+    where
+        F: FnMut(ReaddirEntry<'a>) -> Result<()>,
+    {
+        visit_listed_entries(
+            offset,
+            listed_entries_from_table(Self::STATIC_ENTRIES),
+            visit_fn,
+        )
+    }
+}
@@ -5,27 +5,42 @@ use alloc::format;
 use crate::{
     fs::{
         file::mkmod,
-        procfs::template::{ProcSym, ProcSymOps},
+        procfs::{
+            RootDirOps,
+            template::{ProcSym, ProcSymOps},
+        },
         vfs::inode::{Inode, SymbolicLink},
     },
     prelude::*,
-    process::posix_thread::AsPosixThread,
+    process::{PidNamespace, posix_thread::AsPosixThread},
 };
 
 /// Represents the inode at `/proc/self-thread`.
-pub struct ThreadSelfSymOps;
+pub struct ThreadSelfSymOps {
+    pid_ns: Arc<PidNamespace>,
+}
 
 impl ThreadSelfSymOps {
     pub fn new_inode(parent: Weak<dyn Inode>) -> Arc<dyn Inode> {
+        let pid_ns = RootDirOps::pid_ns_of(&parent);
         // Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/fs/proc/thread_self.c#L50>
-        ProcSym::new(Self, parent, mkmod!(a+rwx))
+        ProcSym::new(Self { pid_ns }, parent, mkmod!(a+rwx))
     }
 }
 
 impl ProcSymOps for ThreadSelfSymOps {
     fn read_link(&self) -> Result<SymbolicLink> {
-        let pid = current!().pid();
-        let tid = current_thread!().as_posix_thread().unwrap().tid();
+        let pid = self.pid_ns.local_id(current!().pid());
+        let tid = self
+            .pid_ns
+            .local_id(current_thread!().as_posix_thread().unwrap().tid());
+        if pid == 0 || tid == 0 {
+            return_errno_with_message!(
+                Errno::ENOENT,
+                "the current thread is not visible in the PID namespace"
+            );
+        }
+
         Ok(SymbolicLink::Plain(format!("{}/task/{}", pid, tid)))
     }
 }
@@ -15,7 +15,8 @@ use crate::{
         },
     },
     prelude::*,
-    process::{Gid, Uid},
+    process::{Gid, Uid, posix_thread::AsPosixThread},
+    thread::Thread,
 };
 
 pub(super) fn init() {
@@ -37,11 +38,22 @@ impl SockFs {
 
     /// Creates a pseudo `Path` for a socket.
     pub fn new_path() -> Path {
+        // Like Linux, the socket inode is owned by the creator of the socket.
+        // Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/socket.c>
+        let (uid, gid) = Thread::current()
+            .and_then(|thread| {
+                thread
+                    .as_posix_thread()
+                    .map(|posix_thread| posix_thread.credentials())
+            })
+            .map(|credentials| (credentials.fsuid(), credentials.fsgid()))
+            .unwrap_or((Uid::new_root(), Gid::new_root()));
+
         let socket_inode = Arc::new(Self::singleton().alloc_inode(
             PseudoInodeType::Socket,
             mkmod!(a+rwx),
-            Uid::new_root(),
-            Gid::new_root(),
+            uid,
+            gid,
         ));
 
         Path::new_pseudo(Self::mount_node().clone(), socket_inode, |inode| {
@@ -1366,7 +1366,11 @@ impl Inode for RamInode {
     fn get_xattr(&self, name: XattrName, value_writer: &mut VmWriter) -> Result<usize> {
         RamXattr::check_file_type_for_xattr(self.typ)
             .map_err(|_| Error::with_message(Errno::ENODATA, "no available xattrs"))?;
-        self.check_permission(Permission::MAY_READ)?;
+        // Like Linux, reading `security.*` xattrs does not require the read permission. Their
+        // access is controlled by the security mechanisms (e.g., file capabilities) instead.
+        if name.namespace() != XattrNamespace::Security {
+            self.check_permission(Permission::MAY_READ)?;
+        }
         self.xattr.get(name, value_writer)
     }
 
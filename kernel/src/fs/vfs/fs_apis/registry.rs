@@ -80,6 +80,11 @@ impl<'a> FsCreationCtx<'a> {
         self.args
     }
 
+    /// Returns the context of the task that creates the filesystem.
+    pub(in crate::fs) fn task_ctx(&self) -> &Context<'a> {
+        self.task_ctx
+    }
+
     /// Resolves the mount source into a block device.
     pub(in crate::fs) fn resolve_block_device(&self) -> Result<Arc<dyn BlockDevice>> {
         let source = self
@@ -44,6 +44,31 @@ impl<T> IpcIds<T> {
         Ok(op(object))
     }
 
+    /// Calls `op` with all the objects, ordered by their IDs.
+    pub(super) fn with_all<R, F>(&self, op: F) -> R
+    where
+        F: FnOnce(&BTreeMap<IpcId, T>) -> R,
+    {
+        op(&self.objects.read())
+    }
+
+    /// Retains only the objects for which `f` returns `true`.
+    pub(super) fn retain<F>(&self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let mut objects = self.objects.write();
+
+        objects.retain(|id, object| {
+            if f(object) {
+                return true;
+            }
+
+            self.id_allocator.lock().free(id.get() as usize);
+            false
+        });
+    }
+
     /// Removes the object identified by `id`.
     pub(super) fn remove<F>(&self, id: IpcId, may_remove: F) -> Result<()>
     where
@@ -71,7 +96,41 @@ impl<T> IpcIds<T> {
         F: FnOnce(IpcId) -> Result<T>,
     {
         let mut objects = self.objects.write();
+        self.insert_auto_locked(&mut objects, new_object_fn)
+    }
+
+    /// Finds the object that matches `pred` and calls `found_fn` with it, or inserts a new object
+    /// with an automatically allocated ID if there is no such object.
+    ///
+    /// The lookup and the insertion are performed atomically.
+    pub(super) fn find_or_insert_auto<P, F, N>(
+        &self,
+        mut pred: P,
+        found_fn: F,
+        new_object_fn: N,
+    ) -> Result<IpcId>
+    where
+        P: FnMut(&T) -> bool,
+        F: FnOnce(IpcId, &T) -> Result<IpcId>,
+        N: FnOnce(IpcId) -> Result<T>,
+    {
+        let mut objects = self.objects.write();
+
+        if let Some((id, object)) = objects.iter().find(|(_, object)| pred(object)) {
+            return found_fn(*id, object);
+        }
+
+        self.insert_auto_locked(&mut objects, new_object_fn)
+    }
 
+    fn insert_auto_locked<F>(
+        &self,
+        objects: &mut BTreeMap<IpcId, T>,
+        new_object_fn: F,
+    ) -> Result<IpcId>
+    where
+        F: FnOnce(IpcId) -> Result<T>,
+    {
         let Some(id) = self
             .id_allocator
             .lock()
@@ -2,44 +2,65 @@
 
 //! Defines the IPC namespace abstraction.
 //!
-//! An IPC namespace isolates System V IPC resources from other namespaces.
-//! It currently manages semaphore sets only, while message queues and shared
-//! memory remain to be added.
+//! An IPC namespace isolates System V IPC resources and POSIX message queues
+//! from other namespaces. It manages message queues, semaphore sets, and shared
+//! memory segments.
 //!
-//! Each namespace stores its semaphore sets in a per-namespace map keyed by
-//! IPC key and uses a dedicated ID allocator to assign semaphore identifiers.
+//! Each namespace stores each kind of System V IPC object in a per-namespace map
+//! and uses a dedicated ID allocator to assign the object identifiers. POSIX
+//! message queues live in a per-namespace mqueue file system instance.
+
+use core::sync::atomic::{AtomicUsize, Ordering};
 
 use aster_rights::ReadOp;
 use spin::Once;
 
 use super::{
-    IPC_PRIVATE, IpcFlags, IpcId, IpcKey,
+    IPC_PRIVATE, IpcFlags, IpcId, IpcKey, IpcPermission, PermissionMode,
     ipc_ids::IpcIds,
-    semaphore::system_v::{
-        PermissionMode,
-        sem_set::{SEMMNI, SemaphoreSet},
-    },
+    msg::{MSGMAX, MSGMNB, MSGMNI, MsgInfo, MsgQueue, MsqidDs},
+    semaphore::system_v::sem_set::{SEMMNI, SemaphoreSet},
+    shm::{SHMMAX, SHMMIN, SHMMNI, ShmInfo, ShmSegment},
 };
 use crate::{
-    fs::pseudofs::{NsCommonOps, NsType, StashedDentry},
+    fs::{
+        mqueue::MqueueFs,
+        pseudofs::{NsCommonOps, NsType, StashedDentry},
+        vfs::path::Mount,
+    },
     prelude::*,
     process::{
         Credentials, UserNamespace, credentials::capabilities::CapSet, posix_thread::PosixThread,
     },
+    vm::page_cache::Vmo,
 };
 
 /// The IPC namespace.
 ///
 /// An IPC namespace isolates System V IPC objects
-/// (semaphores, message queues, shared memory).
+/// (semaphores, message queues, shared memory)
+/// and POSIX message queues.
 /// Each namespace maintains its own independent set
 /// of IPC resources and identifier allocator.
 ///
 /// Lock ordering:
-/// `sem_ids` -> `SemaphoreSet::inner`.
+/// `msg_ids` -> `MsgQueue::inner`,
+/// `sem_ids` -> `SemaphoreSet::inner`,
+/// `shm_ids` -> `ShmSegment::inner`,
+/// `shm_ids` -> `Vmar::inner`.
 pub struct IpcNamespace {
+    /// Message queues within this namespace.
+    msg_ids: IpcIds<Arc<MsgQueue>>,
+    /// Maximum size in bytes of a message (i.e., `/proc/sys/kernel/msgmax`).
+    msg_max: AtomicUsize,
+    /// Default maximum number of bytes in a message queue (i.e., `/proc/sys/kernel/msgmnb`).
+    msg_mnb: AtomicUsize,
     /// Semaphore sets within this namespace.
     sem_ids: IpcIds<SemaphoreSet>,
+    /// Shared memory segments within this namespace.
+    shm_ids: IpcIds<ShmSegment>,
+    /// The internal mount of the mqueue instance holding POSIX message queues.
+    mqueue_mount: Arc<Mount>,
     /// Owner user namespace.
     owner: Arc<UserNamespace>,
     /// Stashed dentry for nsfs.
@@ -53,24 +74,42 @@ impl IpcNamespace {
 
         INIT.call_once(|| {
             let owner = UserNamespace::get_init_singleton().clone();
-            Self::new(owner)
+            Self::new(owner).unwrap()
         })
     }
 
-    fn new(owner: Arc<UserNamespace>) -> Arc<Self> {
+    fn new(owner: Arc<UserNamespace>) -> Result<Arc<Self>> {
+        const MAX_MSG_ID: IpcId = {
+            assert!(MSGMNI <= u32::MAX as usize);
+            IpcId::new(MSGMNI as u32)
+        };
+
         const MAX_SEM_ID: IpcId = {
             assert!(SEMMNI <= u32::MAX as usize);
             IpcId::new(SEMMNI as u32)
         };
 
+        const MAX_SHM_ID: IpcId = {
+            assert!(SHMMNI <= u32::MAX as usize);
+            IpcId::new(SHMMNI as u32)
+        };
+
+        let msg_ids = IpcIds::new(MAX_MSG_ID);
         let sem_ids = IpcIds::new(MAX_SEM_ID);
+        let shm_ids = IpcIds::new(MAX_SHM_ID);
+        let mqueue_mount = MqueueFs::new_kern_mount()?;
         let stashed_dentry = StashedDentry::new();
 
-        Arc::new(Self {
+        Ok(Arc::new(Self {
+            msg_ids,
+            msg_max: AtomicUsize::new(MSGMAX),
+            msg_mnb: AtomicUsize::new(MSGMNB),
             sem_ids,
+            shm_ids,
+            mqueue_mount,
             owner,
             stashed_dentry,
-        })
+        }))
     }
 
     /// Clones a new IPC namespace from `self`.
@@ -82,7 +121,152 @@ impl IpcNamespace {
         posix_thread: &PosixThread,
     ) -> Result<Arc<Self>> {
         owner.check_cap(CapSet::SYS_ADMIN, posix_thread)?;
-        Ok(Self::new(owner))
+        Self::new(owner)
+    }
+
+    /// Returns the message queue identified by `msqid`.
+    pub fn get_msg_queue(
+        &self,
+        msqid: IpcId,
+        required_perm: PermissionMode,
+        posix_thread: &PosixThread,
+    ) -> Result<Arc<MsgQueue>> {
+        self.msg_ids.with(msqid, |queue| {
+            queue.with_permission(|perm| self.check_access(perm, required_perm, posix_thread))?;
+            Ok(queue.clone())
+        })?
+    }
+
+    /// Updates the message queue identified by `msqid` according to `msqid_ds` (i.e., `IPC_SET`).
+    pub fn set_msg_queue(
+        &self,
+        msqid: IpcId,
+        msqid_ds: &MsqidDs,
+        posix_thread: &PosixThread,
+    ) -> Result<()> {
+        self.msg_ids.with(msqid, |queue| {
+            queue.with_permission(|perm| self.check_owner(perm, posix_thread))?;
+
+            // Setting the queue size beyond `msgmnb` requires `CAP_SYS_RESOURCE`.
+            if msqid_ds.max_bytes() > self.msg_mnb() {
+                self.owner.check_cap(CapSet::SYS_RESOURCE, posix_thread)?;
+            }
+
+            queue.set_from(msqid_ds);
+            Ok(())
+        })?
+    }
+
+    /// Returns the existing message queue or creates a new one.
+    pub fn get_or_create_msg_queue(
+        &self,
+        key: IpcKey,
+        flags: IpcFlags,
+        mode: u16,
+        posix_thread: &PosixThread,
+    ) -> Result<IpcId> {
+        let credentials = posix_thread.credentials();
+        let new_queue = |_| {
+            if !flags.contains(IpcFlags::IPC_CREAT) && key != IPC_PRIVATE {
+                return_errno_with_message!(Errno::ENOENT, "the key does not exist");
+            }
+            Ok(Arc::new(MsgQueue::new(
+                key,
+                mode,
+                self.msg_mnb(),
+                &credentials,
+            )))
+        };
+
+        if key == IPC_PRIVATE {
+            return self.msg_ids.insert_auto(new_queue);
+        }
+
+        self.msg_ids.find_or_insert_auto(
+            |queue| queue.key() == key,
+            |msqid, queue| {
+                if flags.contains(IpcFlags::IPC_CREAT | IpcFlags::IPC_EXCL) {
+                    return_errno_with_message!(
+                        Errno::EEXIST,
+                        "the message queue already exists with IPC_EXCL"
+                    );
+                }
+
+                // All the permission classes in `mode` are requested.
+                // Reference: <https://elixir.bootlin.com/linux/v6.18/source/ipc/util.c#L543>.
+                let required_perm =
+                    PermissionMode::from_bits_truncate((mode >> 6) | (mode >> 3) | mode);
+                queue
+                    .with_permission(|perm| self.check_access(perm, required_perm, posix_thread))?;
+
+                Ok(msqid)
+            },
+            new_queue,
+        )
+    }
+
+    /// Removes the message queue identified by `msqid`.
+    ///
+    /// All the threads waiting on the queue will fail with [`Errno::EIDRM`].
+    pub fn remove_msg_queue(&self, msqid: IpcId, posix_thread: &PosixThread) -> Result<()> {
+        self.msg_ids.remove(msqid, |queue| {
+            queue.with_permission(|perm| self.check_owner(perm, posix_thread))?;
+            queue.mark_removed();
+            Ok(())
+        })
+    }
+
+    /// Returns the message queue usage and the maximum ID in use.
+    pub fn msg_info(&self) -> (MsgInfo, Option<IpcId>) {
+        self.msg_ids.with_all(|queues| {
+            let (num_msgs, num_bytes) = queues
+                .values()
+                .map(|queue| queue.usage())
+                .fold((0, 0), |(msgs, bytes), (queue_msgs, queue_bytes)| {
+                    (msgs + queue_msgs, bytes + queue_bytes)
+                });
+            let max_id = queues.keys().next_back().copied();
+
+            let msg_info = MsgInfo::usage(
+                self.msg_max(),
+                self.msg_mnb(),
+                queues.len(),
+                num_msgs,
+                num_bytes,
+            );
+            (msg_info, max_id)
+        })
+    }
+
+    /// Returns the limits of message queues and the maximum ID in use.
+    pub fn msg_limits(&self) -> (MsgInfo, Option<IpcId>) {
+        let max_id = self
+            .msg_ids
+            .with_all(|queues| queues.keys().next_back().copied());
+
+        (MsgInfo::limits(self.msg_max(), self.msg_mnb()), max_id)
+    }
+
+    /// Returns the maximum size in bytes of a message.
+    pub fn msg_max(&self) -> usize {
+        self.msg_max.load(Ordering::Relaxed)
+    }
+
+    /// Sets the maximum size in bytes of a message.
+    pub fn set_msg_max(&self, msg_max: usize) {
+        self.msg_max.store(msg_max, Ordering::Relaxed);
+    }
+
+    /// Returns the default maximum number of bytes in a message queue.
+    pub fn msg_mnb(&self) -> usize {
+        self.msg_mnb.load(Ordering::Relaxed)
+    }
+
+    /// Sets the default maximum number of bytes in a message queue.
+    ///
+    /// This only affects the message queues created afterwards.
+    pub fn set_msg_mnb(&self, msg_mnb: usize) {
+        self.msg_mnb.store(msg_mnb, Ordering::Relaxed);
     }
 
     /// Calls `op` with the semaphore set identified by `semid`.
@@ -188,6 +372,204 @@ impl IpcNamespace {
         self.sem_ids
             .insert_auto(|_| SemaphoreSet::new(IPC_PRIVATE, num_sems, mode, &credentials))
     }
+
+    /// Calls `op` with the shared memory segment identified by `shmid`.
+    pub fn with_shm<T, F>(
+        &self,
+        shmid: IpcId,
+        required_perm: PermissionMode,
+        posix_thread: &PosixThread,
+        op: F,
+    ) -> Result<T>
+    where
+        F: FnOnce(&ShmSegment) -> Result<T>,
+    {
+        self.shm_ids.with(shmid, |shm| {
+            self.check_shm_access(shm, required_perm, posix_thread)?;
+            op(shm)
+        })?
+    }
+
+    /// Calls `op` with the shared memory segment identified by `shmid` if the current thread is
+    /// privileged to change or remove the segment.
+    pub fn with_shm_as_owner<T, F>(
+        &self,
+        shmid: IpcId,
+        posix_thread: &PosixThread,
+        op: F,
+    ) -> Result<T>
+    where
+        F: FnOnce(&ShmSegment) -> Result<T>,
+    {
+        self.shm_ids.with(shmid, |shm| {
+            shm.with_permission(|perm| self.check_owner(perm, posix_thread))?;
+            op(shm)
+        })?
+    }
+
+    /// Calls `op` with the shared memory segment backed by `vmo`.
+    pub fn with_shm_by_vmo<T, F>(&self, vmo: &Arc<Vmo>, op: F) -> Result<T>
+    where
+        F: FnOnce(&ShmSegment) -> Result<T>,
+    {
+        self.shm_ids.with_all(|shms| {
+            let Some(shm) = shms.values().find(|shm| Arc::ptr_eq(shm.vmo(), vmo)) else {
+                return_errno_with_message!(
+                    Errno::EINVAL,
+                    "the VMO does not back a shared memory segment"
+                );
+            };
+            op(shm)
+        })
+    }
+
+    /// Returns the existing shared memory segment or creates a new one.
+    pub fn get_or_create_shm(
+        self: &Arc<Self>,
+        key: IpcKey,
+        size: usize,
+        flags: IpcFlags,
+        mode: u16,
+        ctx: &Context,
+    ) -> Result<IpcId> {
+        self.reap_destroyed_shms();
+
+        let credentials = ctx.posix_thread.credentials();
+        let new_shm = |_| {
+            if !flags.contains(IpcFlags::IPC_CREAT) && key != IPC_PRIVATE {
+                return_errno_with_message!(Errno::ENOENT, "the key does not exist");
+            }
+            if !(SHMMIN..=SHMMAX).contains(&size) {
+                return_errno_with_message!(Errno::EINVAL, "the segment size is out of range");
+            }
+            ShmSegment::new(
+                key,
+                size,
+                mode,
+                &credentials,
+                ctx.process.pid(),
+                Arc::downgrade(self),
+            )
+        };
+
+        if key == IPC_PRIVATE {
+            return self.shm_ids.insert_auto(new_shm);
+        }
+
+        self.shm_ids.find_or_insert_auto(
+            |shm| shm.key() == key,
+            |shmid, shm| {
+                if flags.contains(IpcFlags::IPC_CREAT | IpcFlags::IPC_EXCL) {
+                    return_errno_with_message!(
+                        Errno::EEXIST,
+                        "the shared memory segment already exists with IPC_EXCL"
+                    );
+                }
+
+                if shm.size() < size {
+                    return_errno_with_message!(Errno::EINVAL, "the segment is too small");
+                }
+
+                // All the permission classes in `mode` are requested.
+                // Reference: <https://elixir.bootlin.com/linux/v6.18/source/ipc/util.c#L543>.
+                let required_perm =
+                    PermissionMode::from_bits_truncate((mode >> 6) | (mode >> 3) | mode);
+                self.check_shm_access(shm, required_perm, ctx.posix_thread)?;
+
+                Ok(shmid)
+            },
+            new_shm,
+        )
+    }
+
+    /// Marks the shared memory segment identified by `shmid` to be destroyed.
+    ///
+    /// The segment will be removed once it is no longer attached.
+    pub fn destroy_shm(&self, shmid: IpcId, posix_thread: &PosixThread) -> Result<()> {
+        self.with_shm_as_owner(shmid, posix_thread, |shm| {
+            shm.mark_destroyed();
+            Ok(())
+        })?;
+
+        self.reap_destroyed_shms();
+
+        Ok(())
+    }
+
+    /// Removes the destroyed shared memory segments that are no longer attached.
+    ///
+    /// This method is called when the last mapping of a segment goes away, including the cases
+    /// where the segment is detached by `munmap`, `exit`, or `execve`. Since it is called
+    /// asynchronously in these cases, it is also called before the segments are counted.
+    pub fn reap_destroyed_shms(&self) {
+        self.shm_ids.retain(|shm| !shm.is_reapable());
+    }
+
+    /// Returns the shared memory usage and the maximum ID in use.
+    pub fn shm_info(&self) -> (ShmInfo, Option<IpcId>) {
+        self.reap_destroyed_shms();
+
+        self.shm_ids.with_all(|shms| {
+            let total_pages = shms
+                .values()
+                .map(|shm| shm.size().div_ceil(PAGE_SIZE))
+                .sum();
+            let max_id = shms.keys().next_back().copied();
+
+            (ShmInfo::new(shms.len(), total_pages), max_id)
+        })
+    }
+
+    /// Returns the internal mount of the mqueue instance in this namespace.
+    pub fn mqueue_mount(&self) -> &Arc<Mount> {
+        &self.mqueue_mount
+    }
+
+    fn check_shm_access(
+        &self,
+        shm: &ShmSegment,
+        required_perm: PermissionMode,
+        posix_thread: &PosixThread,
+    ) -> Result<()> {
+        shm.with_permission(|perm| self.check_access(perm, required_perm, posix_thread))
+    }
+
+    /// Checks whether the thread is granted `required_perm` to the IPC object with `permission`.
+    fn check_access(
+        &self,
+        permission: &IpcPermission,
+        required_perm: PermissionMode,
+        posix_thread: &PosixThread,
+    ) -> Result<()> {
+        if required_perm.is_empty() {
+            return Ok(());
+        }
+
+        let credentials = posix_thread.credentials();
+        if permission.is_access_granted(required_perm, &credentials) {
+            return Ok(());
+        }
+
+        self.owner
+            .check_cap(CapSet::IPC_OWNER, posix_thread)
+            .map_err(|_| {
+                Error::with_message(
+                    Errno::EACCES,
+                    "the thread does not have permission to access the IPC object",
+                )
+            })
+    }
+
+    /// Checks whether the thread is privileged to change or remove the IPC object with
+    /// `permission`.
+    fn check_owner(&self, permission: &IpcPermission, posix_thread: &PosixThread) -> Result<()> {
+        let credentials = posix_thread.credentials();
+        if permission.is_owner_or_creator(&credentials) {
+            return Ok(());
+        }
+
+        self.owner.check_cap(CapSet::SYS_ADMIN, posix_thread)
+    }
 }
 
 impl NsCommonOps for IpcNamespace {
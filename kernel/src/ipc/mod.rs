@@ -1,13 +1,17 @@
 // SPDX-License-Identifier: MPL-2.0
 
+use aster_rights::ReadOp;
+
 use crate::{
     prelude::*,
-    process::{Gid, Uid},
+    process::{Credentials, Gid, Uid},
 };
 
 mod ipc_ids;
 mod ipc_ns;
+pub mod msg;
 pub mod semaphore;
+pub mod shm;
 
 pub use ipc_ids::IpcId;
 pub use ipc_ns::IpcNamespace;
@@ -36,6 +40,15 @@ bitflags! {
     }
 }
 
+bitflags! {
+    pub struct PermissionMode: u16{
+        const ALTER  = 0o002;
+        const WRITE  = 0o002;
+        const READ   = 0o004;
+        const EXEC   = 0o001;
+    }
+}
+
 // TODO: Add support for the commented-out commands below
 #[expect(non_camel_case_types)]
 #[repr(i32)]
@@ -99,7 +112,63 @@ impl IpcPermission {
         self.mode
     }
 
-    pub(self) fn new_sem_perm(key: IpcKey, uid: Uid, gid: Gid, mode: u16) -> Self {
+    /// Returns whether the credentials are granted the `required` access by the permission mode.
+    ///
+    /// This only checks the mode bits. The caller should additionally consider the
+    /// `CAP_IPC_OWNER` capability if this method returns `false`.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18/source/ipc/util.c#L536>.
+    pub fn is_access_granted(
+        &self,
+        required: PermissionMode,
+        credentials: &Credentials<ReadOp>,
+    ) -> bool {
+        let euid = credentials.euid();
+        let granted = if euid == self.uid || euid == self.cuid {
+            self.mode >> 6
+        } else if self.is_in_group(credentials) {
+            self.mode >> 3
+        } else {
+            self.mode
+        };
+
+        required.bits() & !granted & 0o7 == 0
+    }
+
+    /// Returns whether the credentials belong to the owner or the creator.
+    ///
+    /// The owner and the creator can change the permission and remove the IPC object. The caller
+    /// should additionally consider the `CAP_SYS_ADMIN` capability if this method returns `false`.
+    pub fn is_owner_or_creator(&self, credentials: &Credentials<ReadOp>) -> bool {
+        let euid = credentials.euid();
+        euid == self.uid || euid == self.cuid
+    }
+
+    fn is_in_group(&self, credentials: &Credentials<ReadOp>) -> bool {
+        let fsgid = credentials.fsgid();
+        if fsgid == self.gid || fsgid == self.cguid {
+            return true;
+        }
+
+        let groups = credentials.groups();
+        groups.contains(&self.gid) || groups.contains(&self.cguid)
+    }
+
+    /// Sets the owner and the lower 9 bits of the permission mode.
+    ///
+    /// This is used to handle `IPC_SET`.
+    pub(self) fn set_owner_and_mode(&mut self, uid: Uid, gid: Gid, mode: u16) {
+        self.uid = uid;
+        self.gid = gid;
+        self.mode = (self.mode & !0o777) | (mode & 0o777);
+    }
+
+    /// Makes the key private so that the IPC object can no longer be looked up by its key.
+    pub(self) fn make_key_private(&mut self) {
+        self.key = IPC_PRIVATE;
+    }
+
+    pub(self) fn new(key: IpcKey, uid: Uid, gid: Gid, mode: u16) -> Self {
         Self {
             key,
             uid,
@@ -109,4 +178,35 @@ impl IpcPermission {
             mode,
         }
     }
+
+    /// Returns the permission in the layout of the userspace `ipc64_perm` structure.
+    pub(self) fn to_c_perm(&self) -> IpcPerm {
+        IpcPerm {
+            key: self.key.cast_unsigned(),
+            uid: self.uid.into(),
+            gid: self.gid.into(),
+            cuid: self.cuid.into(),
+            cgid: self.cguid.into(),
+            mode: self.mode,
+            ..IpcPerm::default()
+        }
+    }
+}
+
+// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/asm-generic/ipcbuf.h#L22>.
+#[padding_struct]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+struct IpcPerm {
+    key: u32,
+    uid: u32,
+    gid: u32,
+    cuid: u32,
+    cgid: u32,
+    mode: u16,
+    _pad1: u16,
+    seq: u16,
+    _pad2: u16,
+    _unused1: u64,
+    _unused2: u64,
 }
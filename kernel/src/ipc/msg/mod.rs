@@ -0,0 +1,165 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! System V message queues.
+
+pub use self::queue::{Message, MsgQueue};
+use super::IpcPerm;
+use crate::prelude::*;
+
+mod queue;
+
+// The following constant values are derived from the default values in Linux.
+
+/// Maximum number of message queues.
+pub const MSGMNI: usize = 32000;
+/// Default maximum size in bytes of a message.
+///
+/// The limit can be changed in `/proc/sys/kernel/msgmax`.
+pub const MSGMAX: usize = 8192;
+/// Default maximum number of bytes in a message queue.
+///
+/// The limit can be changed in `/proc/sys/kernel/msgmnb`.
+pub const MSGMNB: usize = 16384;
+/// The size of the message pool in KiB.
+const MSGPOOL: usize = MSGMNI * MSGMNB / 1024;
+/// The number of entries in the message map.
+const MSGMAP: usize = MSGMNB;
+/// The size of a message segment.
+const MSGSSZ: usize = 16;
+/// The number of message segments.
+const MSGSEG: usize = 0xffff;
+/// Maximum number of messages in the system.
+const MSGTQL: usize = MSGMNB;
+
+bitflags! {
+    pub struct MsgFlags: u32 {
+        /// Return an error instead of blocking.
+        const IPC_NOWAIT = 0o4000;
+        /// Truncate the message if it is too long.
+        const MSG_NOERROR = 0o10000;
+        /// Receive the first message whose type is not equal to `msgtyp`.
+        const MSG_EXCEPT = 0o20000;
+        /// Copy the message at the position `msgtyp` without removing it.
+        const MSG_COPY = 0o40000;
+    }
+}
+
+/// The criteria to select a message to receive.
+#[derive(Clone, Copy, Debug)]
+pub enum MsgSelector {
+    /// The first message in the queue.
+    Any,
+    /// The first message whose type is equal to the value.
+    Equal(i64),
+    /// The first message whose type is not equal to the value.
+    NotEqual(i64),
+    /// The first message with the lowest type that is less than or equal to the value.
+    LessOrEqual(i64),
+}
+
+impl MsgSelector {
+    /// Creates the selector from `msgtyp` and `msgflg` of `msgrcv`.
+    pub fn new(msgtyp: i64, flags: MsgFlags) -> Self {
+        match msgtyp {
+            0 => Self::Any,
+            // `-i64::MIN` overflows, so Linux uses `i64::MAX` instead.
+            i64::MIN => Self::LessOrEqual(i64::MAX),
+            ..0 => Self::LessOrEqual(-msgtyp),
+            _ if flags.contains(MsgFlags::MSG_EXCEPT) => Self::NotEqual(msgtyp),
+            _ => Self::Equal(msgtyp),
+        }
+    }
+}
+
+// TODO: Add support for the commented-out commands below
+#[expect(non_camel_case_types)]
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, TryFromInt)]
+pub enum MsgControlCmd {
+    IPC_RMID = 0,
+    IPC_SET = 1,
+    IPC_STAT = 2,
+    IPC_INFO = 3,
+
+    // MSG_STAT = 11,
+    MSG_INFO = 12,
+    // MSG_STAT_ANY = 13,
+}
+
+// In Linux, all 64-bit architectures adopt the same layout of `msqid64_ds`.
+// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/asm-generic/msgbuf.h>.
+#[padding_struct]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+pub struct MsqidDs {
+    msg_perm: IpcPerm,
+    msg_stime: u64,
+    msg_rtime: u64,
+    msg_ctime: u64,
+    msg_cbytes: u64,
+    msg_qnum: u64,
+    msg_qbytes: u64,
+    msg_lspid: u32,
+    msg_lrpid: u32,
+    _unused4: u64,
+    _unused5: u64,
+}
+
+impl MsqidDs {
+    /// Returns the maximum number of bytes allowed in the queue.
+    pub fn max_bytes(&self) -> usize {
+        self.msg_qbytes as usize
+    }
+}
+
+// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/msg.h>.
+#[padding_struct]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+pub struct MsgInfo {
+    msgpool: i32,
+    msgmap: i32,
+    msgmax: i32,
+    msgmnb: i32,
+    msgmni: i32,
+    msgssz: i32,
+    msgtql: i32,
+    msgseg: u16,
+}
+
+impl MsgInfo {
+    /// Returns the limits of message queues for `IPC_INFO`.
+    pub(super) fn limits(msg_max: usize, msg_mnb: usize) -> Self {
+        Self {
+            msgpool: MSGPOOL as i32,
+            msgmap: MSGMAP as i32,
+            msgmax: msg_max as i32,
+            msgmnb: msg_mnb as i32,
+            msgmni: MSGMNI as i32,
+            msgssz: MSGSSZ as i32,
+            msgtql: MSGTQL as i32,
+            msgseg: MSGSEG as u16,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the limits together with the resource consumption of message queues for
+    /// `MSG_INFO`.
+    ///
+    /// In this case, Linux reuses the fields to report the number of queues (`msgpool`), the
+    /// total number of messages (`msgmap`), and the total number of bytes (`msgtql`).
+    pub(super) fn usage(
+        msg_max: usize,
+        msg_mnb: usize,
+        num_queues: usize,
+        num_msgs: usize,
+        num_bytes: usize,
+    ) -> Self {
+        Self {
+            msgpool: num_queues as i32,
+            msgmap: num_msgs as i32,
+            msgtql: num_bytes as i32,
+            ..Self::limits(msg_max, msg_mnb)
+        }
+    }
+}
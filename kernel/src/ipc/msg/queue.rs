@@ -0,0 +1,295 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_rights::ReadOp;
+use ostd::sync::WaitQueue;
+
+use super::{MsgFlags, MsgSelector, MsqidDs};
+use crate::{
+    ipc::{IpcKey, IpcPermission},
+    prelude::*,
+    process::{Credentials, Gid, Pid, PidNamespace, Uid, signal::Pause},
+    time::clocks::RealTimeCoarseClock,
+};
+
+/// A message in a System V message queue.
+#[derive(Debug)]
+pub struct Message {
+    /// The type of the message, which is always positive.
+    mtype: i64,
+    /// The content of the message.
+    data: Vec<u8>,
+}
+
+impl Message {
+    pub fn new(mtype: i64, data: Vec<u8>) -> Self {
+        debug_assert!(mtype > 0);
+        Self { mtype, data }
+    }
+
+    /// Returns the type of the message.
+    pub fn mtype(&self) -> i64 {
+        self.mtype
+    }
+
+    /// Returns the content of the message.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    fn matches(&self, selector: MsgSelector) -> bool {
+        match selector {
+            MsgSelector::Any => true,
+            MsgSelector::Equal(mtype) => self.mtype == mtype,
+            MsgSelector::NotEqual(mtype) => self.mtype != mtype,
+            MsgSelector::LessOrEqual(mtype) => self.mtype <= mtype,
+        }
+    }
+}
+
+/// A System V message queue.
+#[derive(Debug)]
+pub struct MsgQueue {
+    /// Inner
+    inner: Mutex<MsgQueueInner>,
+    /// The threads waiting for free space to send messages.
+    send_wait_queue: WaitQueue,
+    /// The threads waiting for messages to receive.
+    recv_wait_queue: WaitQueue,
+}
+
+#[derive(Debug)]
+struct MsgQueueInner {
+    /// Queue permission
+    permission: IpcPermission,
+    /// Whether the queue is removed via `IPC_RMID`.
+    is_removed: bool,
+    /// The messages in the queue, in the order they were sent.
+    messages: VecDeque<Message>,
+    /// The total number of bytes of all messages in the queue.
+    num_bytes: usize,
+    /// The maximum number of bytes allowed in the queue.
+    max_bytes: usize,
+    /// Last `msgsnd` time
+    stime: u64,
+    /// Last `msgrcv` time
+    rtime: u64,
+    /// Creation time or last modification via `msgctl`
+    ctime: u64,
+    /// The global PID of the process that last sent a message.
+    lspid: Pid,
+    /// The global PID of the process that last received a message.
+    lrpid: Pid,
+}
+
+impl MsgQueueInner {
+    /// Returns whether a message of `size` bytes can be sent without exceeding the limits.
+    ///
+    /// Like Linux, the maximum number of bytes also limits the number of messages, so that
+    /// zero-length messages cannot fill the queue indefinitely.
+    fn can_fit(&self, size: usize) -> bool {
+        self.num_bytes + size <= self.max_bytes && self.messages.len() < self.max_bytes
+    }
+}
+
+impl MsgQueue {
+    pub(in crate::ipc) fn new(
+        key: IpcKey,
+        mode: u16,
+        max_bytes: usize,
+        credentials: &Credentials<ReadOp>,
+    ) -> Self {
+        let permission = IpcPermission::new(key, credentials.euid(), credentials.egid(), mode);
+
+        Self {
+            inner: Mutex::new(MsgQueueInner {
+                permission,
+                is_removed: false,
+                messages: VecDeque::new(),
+                num_bytes: 0,
+                max_bytes,
+                stime: 0,
+                rtime: 0,
+                ctime: RealTimeCoarseClock::get().read_time().as_secs(),
+                lspid: 0,
+                lrpid: 0,
+            }),
+            send_wait_queue: WaitQueue::new(),
+            recv_wait_queue: WaitQueue::new(),
+        }
+    }
+
+    /// Returns the key of the queue.
+    pub fn key(&self) -> IpcKey {
+        self.inner.lock().permission.key()
+    }
+
+    /// Returns the number of messages and the number of bytes in the queue.
+    pub fn usage(&self) -> (usize, usize) {
+        let inner = self.inner.lock();
+        (inner.messages.len(), inner.num_bytes)
+    }
+
+    /// Calls `op` with the permission of the queue.
+    pub fn with_permission<T, F>(&self, op: F) -> T
+    where
+        F: FnOnce(&IpcPermission) -> T,
+    {
+        op(&self.inner.lock().permission)
+    }
+
+    /// Sends `message` to the queue.
+    ///
+    /// If the queue is full, this method will block until there is enough space, unless
+    /// `IPC_NOWAIT` is specified in `flags`.
+    pub fn send(&self, message: Message, flags: MsgFlags, pid: Pid) -> Result<()> {
+        let size = message.data.len();
+
+        let try_lock_space = || {
+            let inner = self.inner.lock();
+            if inner.is_removed {
+                return Some(Err(Error::with_message(
+                    Errno::EIDRM,
+                    "the message queue is removed",
+                )));
+            }
+            inner.can_fit(size).then_some(Ok(inner))
+        };
+
+        let mut inner = if flags.contains(MsgFlags::IPC_NOWAIT) {
+            try_lock_space().unwrap_or_else(|| {
+                Err(Error::with_message(
+                    Errno::EAGAIN,
+                    "the message queue is full",
+                ))
+            })?
+        } else {
+            self.send_wait_queue.pause_until(try_lock_space)??
+        };
+
+        inner.messages.push_back(message);
+        inner.num_bytes += size;
+        inner.stime = RealTimeCoarseClock::get().read_time().as_secs();
+        inner.lspid = pid;
+        drop(inner);
+
+        // Receivers may wait for messages of different types, so all of them are woken up.
+        self.recv_wait_queue.wake_all();
+
+        Ok(())
+    }
+
+    /// Receives a message selected by `selector` from the queue.
+    ///
+    /// If the selected message is longer than `max_size`, the message will be truncated if
+    /// `MSG_NOERROR` is specified in `flags`, or an error will be returned otherwise.
+    ///
+    /// If there is no such message, this method will block until one arrives, unless
+    /// `IPC_NOWAIT` is specified in `flags`.
+    pub fn recv(
+        &self,
+        max_size: usize,
+        selector: MsgSelector,
+        flags: MsgFlags,
+        pid: Pid,
+    ) -> Result<Message> {
+        let try_find_message = || {
+            let inner = self.inner.lock();
+            if inner.is_removed {
+                return Some(Err(Error::with_message(
+                    Errno::EIDRM,
+                    "the message queue is removed",
+                )));
+            }
+            Self::find_message(&inner.messages, selector).map(|index| Ok((inner, index)))
+        };
+
+        let (mut inner, index) = if flags.contains(MsgFlags::IPC_NOWAIT) {
+            try_find_message().unwrap_or_else(|| {
+                Err(Error::with_message(
+                    Errno::ENOMSG,
+                    "no message of the desired type is in the queue",
+                ))
+            })?
+        } else {
+            self.recv_wait_queue.pause_until(try_find_message)??
+        };
+
+        if inner.messages[index].data.len() > max_size && !flags.contains(MsgFlags::MSG_NOERROR) {
+            return_errno_with_message!(Errno::E2BIG, "the message is too long");
+        }
+
+        let mut message = inner.messages.remove(index).unwrap();
+        inner.num_bytes -= message.data.len();
+        inner.rtime = RealTimeCoarseClock::get().read_time().as_secs();
+        inner.lrpid = pid;
+        drop(inner);
+
+        self.send_wait_queue.wake_all();
+
+        message.data.truncate(max_size);
+        Ok(message)
+    }
+
+    fn find_message(messages: &VecDeque<Message>, selector: MsgSelector) -> Option<usize> {
+        if let MsgSelector::LessOrEqual(_) = selector {
+            // Find the first message with the lowest type.
+            return messages
+                .iter()
+                .enumerate()
+                .filter(|(_, message)| message.matches(selector))
+                .min_by_key(|(_, message)| message.mtype)
+                .map(|(index, _)| index);
+        }
+
+        messages
+            .iter()
+            .position(|message| message.matches(selector))
+    }
+
+    /// Marks the queue as removed and wakes up all the waiting threads.
+    pub(in crate::ipc) fn mark_removed(&self) {
+        self.inner.lock().is_removed = true;
+
+        self.send_wait_queue.wake_all();
+        self.recv_wait_queue.wake_all();
+    }
+
+    /// Updates the owner, the permission mode, and the maximum number of bytes according to
+    /// `msqid_ds` (i.e., `IPC_SET`).
+    ///
+    /// The caller should check whether the new maximum number of bytes is allowed.
+    pub fn set_from(&self, msqid_ds: &MsqidDs) {
+        let perm = &msqid_ds.msg_perm;
+
+        let mut inner = self.inner.lock();
+        inner
+            .permission
+            .set_owner_and_mode(Uid::new(perm.uid), Gid::new(perm.gid), perm.mode);
+        inner.max_bytes = msqid_ds.max_bytes();
+        inner.ctime = RealTimeCoarseClock::get().read_time().as_secs();
+        drop(inner);
+
+        // The queue may have more space now.
+        self.send_wait_queue.wake_all();
+    }
+
+    /// Returns the status of the queue (i.e., `IPC_STAT`).
+    ///
+    /// The PIDs are translated into `pid_ns`, which should be the PID namespace of the caller.
+    pub fn msqid_ds(&self, pid_ns: &PidNamespace) -> MsqidDs {
+        let inner = self.inner.lock();
+
+        MsqidDs {
+            msg_perm: inner.permission.to_c_perm(),
+            msg_stime: inner.stime,
+            msg_rtime: inner.rtime,
+            msg_ctime: inner.ctime,
+            msg_cbytes: inner.num_bytes as u64,
+            msg_qnum: inner.messages.len() as u64,
+            msg_qbytes: inner.max_bytes as u64,
+            msg_lspid: pid_ns.local_id(inner.lspid),
+            msg_lrpid: pid_ns.local_id(inner.lrpid),
+            ..MsqidDs::default()
+        }
+    }
+}
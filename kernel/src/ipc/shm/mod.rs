@@ -0,0 +1,292 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! System V shared memory.
+
+use align_ext::AlignExt;
+
+pub use self::segment::ShmSegment;
+use super::{IpcId, IpcNamespace, IpcPerm, PermissionMode};
+use crate::{
+    prelude::*,
+    vm::{
+        perms::VmPerms,
+        vmar::{VMAR_CAP_ADDR, VMAR_LOWEST_ADDR, VmarMapOffset},
+    },
+};
+
+mod segment;
+
+// The following constant values are derived from the default values in Linux.
+
+/// Maximum number of shared memory segments.
+pub const SHMMNI: usize = 4096;
+/// Maximum size in bytes of a shared memory segment.
+pub const SHMMAX: usize = usize::MAX - (1 << 24);
+/// Minimum size in bytes of a shared memory segment.
+pub const SHMMIN: usize = 1;
+/// Maximum number of shared memory pages in all segments.
+pub const SHMALL: usize = usize::MAX - (1 << 24);
+/// Maximum number of shared memory segments that a process can attach.
+pub const SHMSEG: usize = SHMMNI;
+/// The boundary that attach addresses are rounded down to with `SHM_RND`.
+pub const SHMLBA: usize = PAGE_SIZE;
+
+/// The mode bit that indicates the segment will be destroyed.
+const SHM_DEST: u16 = 0o1000;
+
+bitflags! {
+    pub struct ShmFlags: u32 {
+        /// Attach the segment for read-only access.
+        const SHM_RDONLY = 0o10000;
+        /// Round the attach address down to a multiple of `SHMLBA`.
+        const SHM_RND = 0o20000;
+        /// Replace any existing mapping in the attach range.
+        const SHM_REMAP = 0o40000;
+        /// Allow the segment to be executed.
+        const SHM_EXEC = 0o100000;
+    }
+}
+
+// TODO: Add support for the commented-out commands below
+#[expect(non_camel_case_types)]
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, TryFromInt)]
+pub enum ShmControlCmd {
+    IPC_RMID = 0,
+    IPC_SET = 1,
+    IPC_STAT = 2,
+    IPC_INFO = 3,
+
+    // SHM_LOCK = 11,
+    // SHM_UNLOCK = 12,
+    // SHM_STAT = 13,
+    SHM_INFO = 14,
+    // SHM_STAT_ANY = 15,
+}
+
+// In Linux, all 64-bit architectures adopt the same layout of `shmid_ds`.
+// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/asm-generic/shmbuf.h#L27>.
+#[padding_struct]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+pub struct ShmidDs {
+    shm_perm: IpcPerm,
+    shm_segsz: u64,
+    shm_atime: u64,
+    shm_dtime: u64,
+    shm_ctime: u64,
+    shm_cpid: u32,
+    shm_lpid: u32,
+    shm_nattch: u64,
+    _unused4: u64,
+    _unused5: u64,
+}
+
+// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/asm-generic/shmbuf.h#L43>.
+#[padding_struct]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+pub struct ShmInfo64 {
+    shmmax: u64,
+    shmmin: u64,
+    shmmni: u64,
+    shmseg: u64,
+    shmall: u64,
+    _unused1: u64,
+    _unused2: u64,
+    _unused3: u64,
+    _unused4: u64,
+}
+
+impl ShmInfo64 {
+    /// Returns the system-wide limits of shared memory.
+    pub fn limits() -> Self {
+        Self {
+            shmmax: SHMMAX as u64,
+            shmmin: SHMMIN as u64,
+            shmmni: SHMMNI as u64,
+            shmseg: SHMSEG as u64,
+            shmall: SHMALL as u64,
+            ..Self::default()
+        }
+    }
+}
+
+// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/shm.h#L86>.
+#[padding_struct]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+pub struct ShmInfo {
+    used_ids: i32,
+    shm_tot: u64,
+    shm_rss: u64,
+    shm_swp: u64,
+    swap_attempts: u64,
+    swap_successes: u64,
+}
+
+impl ShmInfo {
+    pub(super) fn new(used_ids: usize, total_pages: usize) -> Self {
+        Self {
+            used_ids: used_ids as i32,
+            shm_tot: total_pages as u64,
+            // Pages are never swapped out. Pages that have not been committed are also counted as
+            // resident, so this is an upper bound.
+            shm_rss: total_pages as u64,
+            ..Self::default()
+        }
+    }
+}
+
+/// Attaches the shared memory segment identified by `shmid` to the address space of the current
+/// process.
+///
+/// On success, the address of the attached segment is returned.
+pub fn shm_attach(
+    shmid: IpcId,
+    addr: Vaddr,
+    flags: ShmFlags,
+    ipc_ns: &IpcNamespace,
+    ctx: &Context,
+) -> Result<Vaddr> {
+    let map_offset = if addr == 0 {
+        if flags.contains(ShmFlags::SHM_REMAP) {
+            return_errno_with_message!(Errno::EINVAL, "SHM_REMAP requires an attach address");
+        }
+        VmarMapOffset::Any
+    } else {
+        let mut addr = addr;
+        if !addr.is_multiple_of(SHMLBA) {
+            if !flags.contains(ShmFlags::SHM_RND) {
+                return_errno_with_message!(Errno::EINVAL, "the attach address is not aligned");
+            }
+            addr = addr.align_down(SHMLBA);
+        }
+        if addr < VMAR_LOWEST_ADDR {
+            return_errno_with_message!(Errno::EINVAL, "the attach address is too low");
+        }
+
+        if flags.contains(ShmFlags::SHM_REMAP) {
+            VmarMapOffset::FixedReplace(addr)
+        } else {
+            VmarMapOffset::FixedNoReplace(addr)
+        }
+    };
+
+    let (perms, required_perm) = if flags.contains(ShmFlags::SHM_RDONLY) {
+        (VmPerms::READ, PermissionMode::READ)
+    } else {
+        (
+            VmPerms::READ | VmPerms::WRITE,
+            PermissionMode::READ | PermissionMode::WRITE,
+        )
+    };
+    let (perms, required_perm) = if flags.contains(ShmFlags::SHM_EXEC) {
+        (perms | VmPerms::EXEC, required_perm | PermissionMode::EXEC)
+    } else {
+        (perms, required_perm)
+    };
+
+    let mut may_perms = VmPerms::ALL_MAY_PERMS;
+    if flags.contains(ShmFlags::SHM_RDONLY) {
+        may_perms.remove(VmPerms::MAY_WRITE);
+    }
+
+    // Map the segment while the segment is alive in the namespace, so that it cannot be reaped
+    // before the new mapping is counted as an attach.
+    ipc_ns.with_shm(shmid, required_perm, ctx.posix_thread, |shm| {
+        let map_size = shm.size().align_up(PAGE_SIZE);
+
+        let user_space = ctx.user_space();
+        let vmar = user_space.vmar();
+
+        if let VmarMapOffset::FixedReplace(addr) | VmarMapOffset::FixedNoReplace(addr) = map_offset
+        {
+            if VMAR_CAP_ADDR
+                .checked_sub(map_size)
+                .is_none_or(|max_addr| addr > max_addr)
+            {
+                return_errno_with_message!(Errno::EINVAL, "the attach address is too high");
+            }
+            if matches!(map_offset, VmarMapOffset::FixedNoReplace(_))
+                && vmar.query(addr..addr + map_size).iter().next().is_some()
+            {
+                return_errno_with_message!(
+                    Errno::EINVAL,
+                    "the attach range overlaps with existing mappings"
+                );
+            }
+        }
+
+        let addr = vmar
+            .new_map(map_size, perms)?
+            .may_perms(may_perms)
+            .offset(map_offset)
+            .vmo(shm.vmo().clone())
+            .tracker(shm.tracker())
+            .is_shared(true)
+            .build()?;
+
+        shm.on_attach(ctx.process.pid());
+
+        Ok(addr)
+    })
+}
+
+/// Detaches the shared memory segment attached at `addr` from the address space of the current
+/// process.
+pub fn shm_detach(addr: Vaddr, ipc_ns: &IpcNamespace, ctx: &Context) -> Result<()> {
+    if !addr.is_multiple_of(PAGE_SIZE) {
+        return_errno_with_message!(Errno::EINVAL, "the detach address is not aligned");
+    }
+
+    let user_space = ctx.user_space();
+    let vmar = user_space.vmar();
+
+    let Some(vmo) = vmar
+        .query(addr..addr + PAGE_SIZE)
+        .iter()
+        .find_map(|vm_mapping| {
+            if vm_mapping.map_to_addr() != addr {
+                return None;
+            }
+            let (vmo, offset) = vm_mapping.backing_vmo()?;
+            (offset == 0).then(|| vmo.clone())
+        })
+    else {
+        return_errno_with_message!(
+            Errno::EINVAL,
+            "no shared memory segment is attached at the address"
+        );
+    };
+
+    ipc_ns.with_shm_by_vmo(&vmo, |shm| {
+        let segment_range = addr..addr + shm.size().align_up(PAGE_SIZE);
+
+        // Collect the ranges first because the mappings cannot be removed while querying.
+        let ranges = vmar
+            .query(segment_range.clone())
+            .iter()
+            .filter(|vm_mapping| {
+                vm_mapping
+                    .backing_vmo()
+                    .is_some_and(|(mapping_vmo, offset)| {
+                        Arc::ptr_eq(mapping_vmo, &vmo) && vm_mapping.map_to_addr() - addr == offset
+                    })
+            })
+            .map(|vm_mapping| vm_mapping.map_to_addr()..vm_mapping.map_end().min(segment_range.end))
+            .collect::<Vec<_>>();
+        for range in ranges {
+            vmar.remove_mapping(range)?;
+        }
+
+        shm.on_detach(ctx.process.pid());
+
+        Ok(())
+    })?;
+
+    // Free the segment now if it has been destroyed, so that it is gone when `shmdt` returns.
+    ipc_ns.reap_destroyed_shms();
+
+    Ok(())
+}
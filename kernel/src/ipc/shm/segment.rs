@@ -0,0 +1,226 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use align_ext::AlignExt;
+use aster_rights::ReadOp;
+
+use super::{SHM_DEST, ShmidDs};
+use crate::{
+    ipc::{IpcKey, IpcNamespace, IpcPermission},
+    prelude::*,
+    process::{Credentials, Gid, Pid, PidNamespace, Uid},
+    thread::work_queue::{self, WorkPriority},
+    time::clocks::RealTimeCoarseClock,
+    vm::{
+        page_cache::{Vmo, VmoOptions},
+        vmar::MappingTracker,
+    },
+};
+
+/// A System V shared memory segment.
+///
+/// The memory of the segment is provided by a VMO, which is mapped into the
+/// address spaces of the attaching processes.
+#[derive(Debug)]
+pub struct ShmSegment {
+    /// The size of the segment in bytes, as specified in `shmget`.
+    size: usize,
+    /// The VMO that backs the segment.
+    vmo: Arc<Vmo>,
+    /// The tracker that counts the mappings of the segment.
+    attaches: Arc<ShmAttaches>,
+    /// The global PID of the creator.
+    cpid: Pid,
+    /// Inner
+    inner: Mutex<ShmInner>,
+}
+
+#[derive(Debug)]
+struct ShmInner {
+    /// Segment permission
+    permission: IpcPermission,
+    /// Whether the segment is marked to be destroyed via `IPC_RMID`.
+    ///
+    /// A destroyed segment is removed after the last process detaches from it.
+    is_destroyed: bool,
+    /// Last `shmat` time
+    atime: u64,
+    /// Last `shmdt` time
+    dtime: u64,
+    /// Creation time or last modification via `shmctl`
+    ctime: u64,
+    /// The global PID of the process that last attached or detached the segment.
+    lpid: Pid,
+}
+
+impl ShmSegment {
+    pub(in crate::ipc) fn new(
+        key: IpcKey,
+        size: usize,
+        mode: u16,
+        credentials: &Credentials<ReadOp>,
+        cpid: Pid,
+        ipc_ns: Weak<IpcNamespace>,
+    ) -> Result<Self> {
+        let vmo = VmoOptions::new(size.align_up(PAGE_SIZE)).alloc()?;
+        let permission = IpcPermission::new(key, credentials.euid(), credentials.egid(), mode);
+
+        Ok(Self {
+            size,
+            vmo,
+            attaches: Arc::new(ShmAttaches {
+                num_attaches: AtomicUsize::new(0),
+                ipc_ns,
+            }),
+            cpid,
+            inner: Mutex::new(ShmInner {
+                permission,
+                is_destroyed: false,
+                atime: 0,
+                dtime: 0,
+                ctime: RealTimeCoarseClock::get().read_time().as_secs(),
+                lpid: 0,
+            }),
+        })
+    }
+
+    /// Returns the size of the segment in bytes.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the VMO that backs the segment.
+    pub fn vmo(&self) -> &Arc<Vmo> {
+        &self.vmo
+    }
+
+    /// Returns the tracker that must be set for the mappings created by `shmat`.
+    pub(super) fn tracker(&self) -> Arc<dyn MappingTracker> {
+        self.attaches.clone()
+    }
+
+    /// Returns the number of current attaches.
+    ///
+    /// Like Linux, each mapping of the segment counts as one attach, including the mappings that
+    /// are inherited via `fork` or split from a mapping created by `shmat`.
+    pub fn num_attaches(&self) -> usize {
+        self.attaches.num_attaches.load(Ordering::Relaxed)
+    }
+
+    /// Returns the key of the segment.
+    ///
+    /// A destroyed segment always has a private key.
+    pub fn key(&self) -> IpcKey {
+        self.inner.lock().permission.key()
+    }
+
+    /// Returns whether the segment is marked to be destroyed.
+    pub fn is_destroyed(&self) -> bool {
+        self.inner.lock().is_destroyed
+    }
+
+    /// Returns whether the segment can be freed since it is destroyed and no longer attached.
+    pub(in crate::ipc) fn is_reapable(&self) -> bool {
+        self.is_destroyed() && self.num_attaches() == 0
+    }
+
+    /// Calls `op` with the permission of the segment.
+    pub fn with_permission<T, F>(&self, op: F) -> T
+    where
+        F: FnOnce(&IpcPermission) -> T,
+    {
+        op(&self.inner.lock().permission)
+    }
+
+    /// Marks the segment to be destroyed.
+    ///
+    /// The key of the segment becomes private, so `shmget` will no longer find this segment.
+    pub(in crate::ipc) fn mark_destroyed(&self) {
+        let mut inner = self.inner.lock();
+        inner.is_destroyed = true;
+        inner.permission.make_key_private();
+    }
+
+    /// Updates the owner and the permission mode according to `shmid_ds` (i.e., `IPC_SET`).
+    pub fn set_from(&self, shmid_ds: &ShmidDs) {
+        let perm = &shmid_ds.shm_perm;
+
+        let mut inner = self.inner.lock();
+        inner
+            .permission
+            .set_owner_and_mode(Uid::new(perm.uid), Gid::new(perm.gid), perm.mode);
+        inner.ctime = RealTimeCoarseClock::get().read_time().as_secs();
+    }
+
+    /// Records that the segment is attached by the process `pid`.
+    pub(super) fn on_attach(&self, pid: Pid) {
+        let mut inner = self.inner.lock();
+        inner.atime = RealTimeCoarseClock::get().read_time().as_secs();
+        inner.lpid = pid;
+    }
+
+    /// Records that the segment is detached by the process `pid`.
+    pub(super) fn on_detach(&self, pid: Pid) {
+        let mut inner = self.inner.lock();
+        inner.dtime = RealTimeCoarseClock::get().read_time().as_secs();
+        inner.lpid = pid;
+    }
+
+    /// Returns the status of the segment (i.e., `IPC_STAT`).
+    ///
+    /// The PIDs are translated into `pid_ns`, which should be the PID namespace of the caller.
+    pub fn shmid_ds(&self, pid_ns: &PidNamespace) -> ShmidDs {
+        let inner = self.inner.lock();
+
+        let mut shm_perm = inner.permission.to_c_perm();
+        if inner.is_destroyed {
+            shm_perm.mode |= SHM_DEST;
+        }
+
+        ShmidDs {
+            shm_perm,
+            shm_segsz: self.size as u64,
+            shm_atime: inner.atime,
+            shm_dtime: inner.dtime,
+            shm_ctime: inner.ctime,
+            shm_cpid: pid_ns.local_id(self.cpid),
+            shm_lpid: pid_ns.local_id(inner.lpid),
+            shm_nattch: self.num_attaches() as u64,
+            ..ShmidDs::default()
+        }
+    }
+}
+
+/// The attaches of a [`ShmSegment`].
+#[derive(Debug)]
+struct ShmAttaches {
+    num_attaches: AtomicUsize,
+    /// The IPC namespace that the segment belongs to.
+    ipc_ns: Weak<IpcNamespace>,
+}
+
+impl MappingTracker for ShmAttaches {
+    fn on_map(&self) {
+        self.num_attaches.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn on_unmap(&self) {
+        if self.num_attaches.fetch_sub(1, Ordering::Relaxed) != 1 {
+            return;
+        }
+
+        // The last mapping may go away when the process exits or executes a new program, so the
+        // segment must be freed here if it has been destroyed. The VMAR is locked now, so this is
+        // deferred to avoid locking the IPC namespace after the VMAR.
+        let ipc_ns = self.ipc_ns.clone();
+        work_queue::submit_work_func(
+            move || {
+                if let Some(ipc_ns) = ipc_ns.upgrade() {
+                    ipc_ns.reap_destroyed_shms();
+                }
+            },
+            WorkPriority::Normal,
+        );
+    }
+}
@@ -0,0 +1,428 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Software Ethernet bridges.
+//!
+//! A bridge connects several Ethernet interfaces, called its ports, into one broadcast domain. The
+//! frames received by a port are not delivered to the port's own interface. Instead, the bridge
+//! forwards them to other ports or delivers them to the interface of the bridge itself. The bridge
+//! learns which port each Ethernet address is behind from the source addresses of the received
+//! frames, so that unicast frames are only forwarded to the ports where their destinations are.
+//!
+//! The spanning tree protocol (STP) is not supported. Like a Linux bridge with STP disabled, a port
+//! starts forwarding frames as soon as it is attached.
+//!
+//! Currently, only the ends of veth pairs can be attached to bridges.
+
+use core::time::Duration;
+
+use aster_bigtcp::{
+    device::{
+        Device, DeviceCapabilities, FilterDevice, Medium, NotifyDevice, OffloadDevice, RxToken,
+        TxToken, WithDevice,
+    },
+    iface::FilterVerdict,
+    time::Instant,
+    wire::{ETHERNET_HEADER_LEN, EthernetAddress, EthernetFrame},
+};
+use aster_softirq::BottomHalfDisabled;
+use ostd::timer::Jiffies;
+
+use super::{
+    Iface, poll::spawn_background_poll_thread, util::new_virtual_ether_iface, veth::VethEnd,
+};
+use crate::{
+    net::net_ns::NetNamespace,
+    prelude::*,
+    thread::work_queue::{WorkPriority, submit_work_item, work_item::WorkItem},
+};
+
+/// The maximum number of ports of a bridge.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/bridge/br_private.h>
+const BR_MAX_PORTS: usize = 1 << 10;
+
+/// The time after which a learned address is forgotten if no frames are received from it.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/linux/if_bridge.h>
+const BR_DEFAULT_AGEING_TIME: Duration = Duration::from_secs(300);
+
+/// The MTU of bridges.
+const BRIDGE_MTU: usize = 1500;
+
+/// The maximum number of frames waiting to be forwarded or to be received by the interface.
+const BRIDGE_QUEUE_SIZE: usize = 1000;
+
+/// A software Ethernet bridge.
+pub(super) struct Bridge {
+    iface: Arc<Iface>,
+    ether_addr: EthernetAddress,
+    state: SpinLock<BridgeState, BottomHalfDisabled>,
+    /// The frames received by the ports and waiting to be forwarded.
+    ingress_frames: SpinLock<VecDeque<(Arc<VethEnd>, Vec<u8>)>, BottomHalfDisabled>,
+    /// The frames waiting to be received by the interface of the bridge.
+    local_frames: SpinLock<VecDeque<Vec<u8>>, BottomHalfDisabled>,
+    /// The work item that forwards the frames received by the ports.
+    forward_work: Arc<WorkItem>,
+    /// The network namespace that the interface is in.
+    ///
+    /// If the network namespace is destroyed, the interface will have been deleted as well.
+    net_ns: Mutex<Weak<NetNamespace>>,
+}
+
+struct BridgeState {
+    ports: Vec<Arc<VethEnd>>,
+    /// The forwarding database, which maps the learned addresses to the ports.
+    fdb: BTreeMap<EthernetAddress, FdbEntry>,
+}
+
+struct FdbEntry {
+    /// The index of the port's interface.
+    port: u32,
+    /// The time when a frame from the address was last received.
+    updated: Duration,
+}
+
+/// The link layer of a bridge.
+///
+/// This is the device driver used by the interface of the bridge.
+struct BridgeLink {
+    bridge: Weak<Bridge>,
+}
+
+struct BridgeDriver(Arc<SpinLock<BridgeLink, BottomHalfDisabled>>);
+
+impl WithDevice for BridgeDriver {
+    type Device = BridgeLink;
+
+    fn with<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut Self::Device) -> R,
+    {
+        let mut link = self.0.lock();
+        f(&mut link)
+    }
+}
+
+impl Bridge {
+    /// Creates a bridge without ports.
+    ///
+    /// The bridge is added to `net_ns`. Its name is generated from the template as in
+    /// [`NetNamespace::add_iface`].
+    pub(super) fn new(name_template: &str, net_ns: &Arc<NetNamespace>) -> Result<Arc<Self>> {
+        let link = Arc::new(SpinLock::new(BridgeLink {
+            bridge: Weak::new(),
+        }));
+
+        let iface = net_ns.add_iface(name_template, |name| {
+            new_virtual_ether_iface(BridgeDriver(link.clone()), name)
+        })?;
+        spawn_background_poll_thread(iface.clone());
+        let ether_addr = iface.ether_addr().unwrap();
+
+        let bridge = Arc::new_cyclic(|weak_bridge: &Weak<Self>| {
+            let weak_bridge = weak_bridge.clone();
+            let forward_work = WorkItem::new(Box::new(move || {
+                if let Some(bridge) = weak_bridge.upgrade() {
+                    bridge.forward_ingress_frames();
+                }
+            }));
+
+            Self {
+                iface,
+                ether_addr,
+                state: SpinLock::new(BridgeState {
+                    ports: Vec::new(),
+                    fdb: BTreeMap::new(),
+                }),
+                ingress_frames: SpinLock::new(VecDeque::new()),
+                local_frames: SpinLock::new(VecDeque::new()),
+                forward_work,
+                net_ns: Mutex::new(Arc::downgrade(net_ns)),
+            }
+        });
+        link.lock().bridge = Arc::downgrade(&bridge);
+
+        Ok(bridge)
+    }
+
+    pub(super) fn iface(&self) -> &Arc<Iface> {
+        &self.iface
+    }
+
+    /// Attaches a port to the bridge.
+    ///
+    /// The port must not be attached to any bridge.
+    pub(super) fn add_port(self: &Arc<Self>, port: &Arc<VethEnd>) -> Result<()> {
+        debug_assert!(port.master().is_none());
+
+        let mut state = self.state.lock();
+        if state.ports.len() >= BR_MAX_PORTS {
+            return_errno_with_message!(Errno::EXFULL, "the bridge has too many ports");
+        }
+        state.ports.push(port.clone());
+        port.set_master(Some(Arc::downgrade(self)));
+
+        Ok(())
+    }
+
+    /// Detaches a port from the bridge.
+    ///
+    /// The addresses learned from the port are forgotten.
+    pub(super) fn remove_port(&self, port: &VethEnd) {
+        let mut state = self.state.lock();
+        let Some(pos) = state
+            .ports
+            .iter()
+            .position(|other| core::ptr::eq(other.as_ref(), port))
+        else {
+            return;
+        };
+        state.ports.remove(pos);
+        port.set_master(None);
+
+        let port_index = port.iface().index();
+        state.fdb.retain(|_, entry| entry.port != port_index);
+    }
+
+    /// Returns the ports of the bridge.
+    pub(super) fn ports(&self) -> Vec<Arc<VethEnd>> {
+        self.state.lock().ports.clone()
+    }
+
+    /// Updates the network namespace after the interface is moved.
+    pub(super) fn set_net_ns(&self, net_ns: &Arc<NetNamespace>) {
+        *self.net_ns.lock() = Arc::downgrade(net_ns);
+    }
+
+    /// Removes the interface from the system after detaching all the ports.
+    ///
+    /// Returns the network namespace that the interface is removed from, or `None` if the network
+    /// namespace is being destroyed.
+    pub(super) fn remove(&self) -> Option<Arc<NetNamespace>> {
+        for port in self.ports() {
+            self.remove_port(&port);
+        }
+
+        self.iface.sched_poll().stop();
+
+        let net_ns = self.net_ns.lock().upgrade()?;
+        net_ns.remove_iface(&self.iface);
+
+        Some(net_ns)
+    }
+
+    /// Receives a frame from a port.
+    ///
+    /// The frame will be forwarded later in a work item.
+    pub(super) fn receive(&self, port: &Arc<VethEnd>, frame: Vec<u8>) {
+        // Like Linux, the ports are disabled while the bridge is down.
+        if !self.iface.is_up() {
+            return;
+        }
+
+        let mut ingress_frames = self.ingress_frames.lock();
+        if ingress_frames.len() >= BRIDGE_QUEUE_SIZE {
+            return;
+        }
+        ingress_frames.push_back((port.clone(), frame));
+        drop(ingress_frames);
+
+        submit_work_item(self.forward_work.clone(), WorkPriority::High);
+    }
+
+    fn forward_ingress_frames(&self) {
+        let mut has_local_frames = false;
+
+        loop {
+            let Some((port, frame)) = self.ingress_frames.lock().pop_front() else {
+                break;
+            };
+            has_local_frames |= self.forward(Some(&port), frame);
+        }
+
+        if has_local_frames {
+            self.iface.poll();
+        }
+    }
+
+    /// Forwards a frame received by a port, or transmitted by the interface of the bridge if
+    /// `in_port` is `None`.
+    ///
+    /// Like Linux with `br_netfilter`, the IP packets forwarded between the ports traverse the
+    /// forward hook of the packet filter, where the interface of the bridge is both the input and
+    /// the output interface.
+    ///
+    /// Returns whether the frame is delivered to the interface of the bridge.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/bridge/br_input.c> (`br_handle_frame_finish`)
+    fn forward(&self, in_port: Option<&Arc<VethEnd>>, mut frame: Vec<u8>) -> bool {
+        let Ok(ether_frame) = EthernetFrame::new_checked(frame.as_slice()) else {
+            return false;
+        };
+        let src_addr = ether_frame.src_addr();
+        let dst_addr = ether_frame.dst_addr();
+
+        let now = Jiffies::elapsed().as_duration();
+        let is_in_port =
+            |port: &Arc<VethEnd>| in_port.is_some_and(|in_port| Arc::ptr_eq(port, in_port));
+
+        let (out_ports, is_local) = {
+            let mut state = self.state.lock();
+
+            if let Some(in_port) = in_port {
+                // The port may have been detached after receiving the frame.
+                if !state.ports.iter().any(|port| Arc::ptr_eq(port, in_port)) {
+                    return false;
+                }
+
+                if src_addr.is_unicast() {
+                    let entry = FdbEntry {
+                        port: in_port.iface().index(),
+                        updated: now,
+                    };
+                    state.fdb.insert(src_addr, entry);
+                }
+            }
+
+            if dst_addr == self.ether_addr {
+                (Vec::new(), in_port.is_some())
+            } else if !dst_addr.is_unicast() {
+                // Broadcast and multicast frames are flooded to all other ports.
+                let out_ports = state
+                    .ports
+                    .iter()
+                    .filter(|port| !is_in_port(port))
+                    .cloned()
+                    .collect();
+                (out_ports, in_port.is_some())
+            } else if let Some(out_port) = state.lookup(&dst_addr, now) {
+                if is_in_port(&out_port) {
+                    (Vec::new(), false)
+                } else {
+                    (vec![out_port], false)
+                }
+            } else {
+                // Unicast frames to unknown destinations are flooded as well.
+                let out_ports = state
+                    .ports
+                    .iter()
+                    .filter(|port| !is_in_port(port))
+                    .cloned()
+                    .collect();
+                (out_ports, false)
+            }
+        };
+
+        if in_port.is_some() && !out_ports.is_empty() {
+            // The packet filter may rewrite the packet, which must not affect the frame delivered
+            // to the interface of the bridge.
+            let mut out_frame = if is_local {
+                frame.clone()
+            } else {
+                core::mem::take(&mut frame)
+            };
+            if self.iface.filter_forwarded_frame(&mut out_frame) == FilterVerdict::Accept {
+                for out_port in out_ports.iter() {
+                    out_port.transmit(&out_frame);
+                }
+            }
+        } else {
+            // The frames transmitted by the interface of the bridge have traversed the output
+            // hooks.
+            for out_port in out_ports.iter() {
+                out_port.transmit(&frame);
+            }
+        }
+
+        if !is_local {
+            return false;
+        }
+
+        let mut local_frames = self.local_frames.lock();
+        if local_frames.len() >= BRIDGE_QUEUE_SIZE {
+            return false;
+        }
+        local_frames.push_back(frame);
+
+        true
+    }
+}
+
+impl BridgeState {
+    /// Looks up the port where the address is.
+    ///
+    /// The entry is removed if it has expired.
+    fn lookup(&mut self, addr: &EthernetAddress, now: Duration) -> Option<Arc<VethEnd>> {
+        let entry = self.fdb.get(addr)?;
+        if now.saturating_sub(entry.updated) > BR_DEFAULT_AGEING_TIME {
+            self.fdb.remove(addr);
+            return None;
+        }
+
+        let port_index = entry.port;
+        self.ports
+            .iter()
+            .find(|port| port.iface().index() == port_index)
+            .cloned()
+    }
+}
+
+impl Device for BridgeLink {
+    type RxToken<'a> = BridgeRxToken;
+    type TxToken<'a> = BridgeTxToken<'a>;
+
+    fn receive(&mut self, _timestamp: Instant) -> Option<(Self::RxToken<'_>, Self::TxToken<'_>)> {
+        let frame = self.bridge.upgrade()?.local_frames.lock().pop_front()?;
+        Some((BridgeRxToken(frame), BridgeTxToken(self)))
+    }
+
+    fn transmit(&mut self, _timestamp: Instant) -> Option<Self::TxToken<'_>> {
+        Some(BridgeTxToken(self))
+    }
+
+    fn capabilities(&self) -> DeviceCapabilities {
+        let mut caps = DeviceCapabilities::default();
+        caps.medium = Medium::Ethernet;
+        caps.max_transmission_unit = BRIDGE_MTU + ETHERNET_HEADER_LEN;
+        caps.max_burst_size = None;
+        caps
+    }
+}
+
+impl NotifyDevice for BridgeLink {
+    fn notify_poll_end(&mut self) {}
+}
+
+impl OffloadDevice for BridgeLink {}
+
+impl FilterDevice for BridgeLink {
+    // The ports can receive arbitrary frames, so they are always filtered by the iface itself.
+    fn set_rx_filter(&mut self, _is_promisc: bool, _multicast_addrs: &[EthernetAddress]) {}
+}
+
+struct BridgeRxToken(Vec<u8>);
+
+impl RxToken for BridgeRxToken {
+    fn consume<R, F>(self, f: F) -> R
+    where
+        F: FnOnce(&[u8]) -> R,
+    {
+        f(&self.0)
+    }
+}
+
+struct BridgeTxToken<'a>(&'a BridgeLink);
+
+impl TxToken for BridgeTxToken<'_> {
+    fn consume<R, F>(self, len: usize, f: F) -> R
+    where
+        F: FnOnce(&mut [u8]) -> R,
+    {
+        let mut frame = vec![0u8; len];
+        let res = f(&mut frame);
+        if let Some(bridge) = self.0.bridge.upgrade() {
+            bridge.forward(None, frame);
+        }
+        res
+    }
+}
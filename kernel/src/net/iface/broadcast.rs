@@ -2,41 +2,33 @@
 
 use core::net::Ipv4Addr;
 
-use aster_bigtcp::wire::{IpAddress, IpEndpoint};
-use spin::Once;
+use aster_bigtcp::wire::{IpAddress, IpCidr, IpEndpoint};
 
-use crate::{net::iface::iter_all_ifaces, prelude::*};
+use crate::net::net_ns::NetNamespace;
 
-/// All known broadcast addresses.
-// FIXME: This information should be maintained in the routing table,
-// since a broadcast address might change if an interface's IP
-// or netmask changes, or if an interface is added/removed.
-static BROADCAST_ADDRS: Once<BTreeSet<Ipv4Addr>> = Once::new();
-
-pub(super) fn init() {
-    BROADCAST_ADDRS.call_once(|| {
-        let mut broadcast_addrs = BTreeSet::new();
-        // 255.255.255.255 is always included.
-        broadcast_addrs.insert(Ipv4Addr::BROADCAST);
-
-        for iface in iter_all_ifaces() {
-            let Some(broadcast_addr) = iface.broadcast_addr() else {
-                continue;
-            };
-
-            broadcast_addrs.insert(broadcast_addr);
-        }
-        broadcast_addrs
-    });
-}
-
-/// Determines if a given IP endpoint's address is a known broadcast address.
+/// Determines if a given IP endpoint's address is a known broadcast address in the network
+/// namespace.
 ///
 /// IPv6 has no broadcast; multicast (`ff00::/8`) handles fan-out instead and
 /// is intentionally not covered by this function.
-pub fn is_broadcast_endpoint(endpoint: &IpEndpoint) -> bool {
+//
+// FIXME: This information should be maintained in the routing table,
+// since a broadcast address might change if an interface's IP
+// or netmask changes.
+pub fn is_broadcast_endpoint(endpoint: &IpEndpoint, net_ns: &NetNamespace) -> bool {
     let IpAddress::Ipv4(ipv4_addr) = &endpoint.addr else {
         return false;
     };
-    BROADCAST_ADDRS.get().unwrap().contains(ipv4_addr)
+
+    // 255.255.255.255 is always included.
+    if *ipv4_addr == Ipv4Addr::BROADCAST {
+        return true;
+    }
+
+    net_ns.ifaces().iter().any(|iface| {
+        iface.ip_addrs().iter().any(|ip_cidr| match ip_cidr {
+            IpCidr::Ipv4(ipv4_cidr) => ipv4_cidr.broadcast() == Some(*ipv4_addr),
+            IpCidr::Ipv6(_) => false,
+        })
+    })
 }
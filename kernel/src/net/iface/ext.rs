@@ -10,4 +10,5 @@ impl aster_bigtcp::ext::Ext for BigtcpExt {
 
     type TcpEventObserver = StreamObserver;
     type UdpEventObserver = DatagramObserver;
+    type RawEventObserver = DatagramObserver;
 }
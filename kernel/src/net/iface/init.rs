@@ -1,64 +1,24 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use core::slice::Iter;
-
 use aster_bigtcp::{
     device::WithDevice,
     iface::{InterfaceFlags, InterfaceType},
 };
-use aster_softirq::BottomHalfDisabled;
-use spin::Once;
 
 use super::{Iface, poll::poll_ifaces};
 use crate::{
-    net::iface::{broadcast, sched::PollScheduler},
+    net::{iface::sched::PollScheduler, net_ns::NetNamespace},
     prelude::*,
 };
 
-static IFACES: Once<Vec<Arc<Iface>>> = Once::new();
-
-pub fn loopback_iface() -> &'static Arc<Iface> {
-    &IFACES.get().unwrap()[0]
-}
-
-pub fn virtio_iface() -> Option<&'static Arc<Iface>> {
-    IFACES.get().unwrap().get(1)
-}
-
-pub fn iter_all_ifaces() -> Iter<'static, Arc<Iface>> {
-    IFACES.get().unwrap().iter()
-}
-
 // TODO: Support multiple network devices and avoid the hardcoded device name.
 const VIRTIO_DEVICE_NAME: &str = aster_virtio::device::network::DEVICE_NAME;
 
 pub fn init() {
-    IFACES.call_once(|| {
-        let mut ifaces = Vec::with_capacity(2);
-
-        // Initialize loopback before virtio
-        // to ensure the loopback interface index is ahead of virtio.
-        ifaces.push(new_loopback());
-
-        if let Some(iface_virtio) = new_virtio() {
-            ifaces.push(iface_virtio);
-        }
-
-        ifaces
-    });
-
-    if let Some(iface_virtio) = virtio_iface() {
-        let callback = || iface_virtio.poll();
-        aster_network::register_recv_callback(VIRTIO_DEVICE_NAME, callback);
-        aster_network::register_send_callback(VIRTIO_DEVICE_NAME, callback);
-    }
-
-    broadcast::init();
-
-    poll_ifaces();
+    poll_ifaces(NetNamespace::get_init_singleton());
 }
 
-fn new_loopback() -> Arc<Iface> {
+pub(in crate::net) fn new_loopback() -> Arc<Iface> {
     use aster_bigtcp::{
         device::{Loopback, Medium},
         iface::IpIface,
@@ -93,7 +53,7 @@ fn new_loopback() -> Arc<Iface> {
 
     IpIface::new(
         Wrapper(Mutex::new(Loopback::new(Medium::Ip))),
-        Ipv4Cidr::new(LOOPBACK_ADDRESS, LOOPBACK_ADDRESS_PREFIX_LEN),
+        Some(Ipv4Cidr::new(LOOPBACK_ADDRESS, LOOPBACK_ADDRESS_PREFIX_LEN)),
         Some(Ipv6Cidr::new(
             LOOPBACK_IPV6_ADDRESS,
             LOOPBACK_IPV6_PREFIX_LEN,
@@ -105,32 +65,24 @@ fn new_loopback() -> Arc<Iface> {
     ) as Arc<Iface>
 }
 
-fn new_virtio() -> Option<Arc<Iface>> {
-    use aster_bigtcp::{
-        iface::EtherIface,
-        wire::{EthernetAddress, Ipv4Address, Ipv4Cidr},
-    };
-    use aster_network::AnyNetworkDevice;
-
-    const VIRTIO_ADDRESS: Ipv4Address = Ipv4Address::new(10, 0, 2, 15);
-    const VIRTIO_ADDRESS_PREFIX_LEN: u8 = 24; // mask: 255.255.255.0
-    const VIRTIO_GATEWAY: Ipv4Address = Ipv4Address::new(10, 0, 2, 2);
+pub(in crate::net) fn new_virtio() -> Option<Arc<Iface>> {
+    use aster_bigtcp::{iface::EtherIface, wire::EthernetAddress};
+    use aster_network::{AnyNetworkDevice, DeviceHandle};
 
     let virtio_net = aster_network::get_device(VIRTIO_DEVICE_NAME)?;
 
-    let ether_addr = virtio_net.lock().mac_addr().0;
+    let ether_addr = virtio_net.mac_addr().0;
 
-    struct Wrapper(Arc<SpinLock<dyn AnyNetworkDevice, BottomHalfDisabled>>);
+    struct Wrapper(Arc<dyn AnyNetworkDevice>);
 
     impl WithDevice for Wrapper {
-        type Device = dyn AnyNetworkDevice;
+        type Device = DeviceHandle;
 
         fn with<F, R>(&self, f: F) -> R
         where
             F: FnOnce(&mut Self::Device) -> R,
         {
-            let mut device = self.0.lock();
-            f(&mut *device)
+            f(&mut DeviceHandle::new(self.0.clone()))
         }
     }
 
@@ -142,13 +94,22 @@ fn new_virtio() -> Option<Arc<Iface>> {
         | InterfaceFlags::MULTICAST
         | InterfaceFlags::LOWER_UP;
 
-    Some(EtherIface::new(
+    let iface = EtherIface::new(
         Wrapper(virtio_net),
         EthernetAddress(ether_addr),
-        Ipv4Cidr::new(VIRTIO_ADDRESS, VIRTIO_ADDRESS_PREFIX_LEN),
-        VIRTIO_GATEWAY,
+        // The addresses are configured later according to the `ip=` kernel parameter.
+        None,
         CString::new("eth0").unwrap(),
         PollScheduler::new(),
         flags,
-    ))
+    ) as Arc<Iface>;
+
+    let callback = {
+        let iface = iface.clone();
+        move || iface.poll()
+    };
+    aster_network::register_recv_callback(VIRTIO_DEVICE_NAME, callback.clone());
+    aster_network::register_send_callback(VIRTIO_DEVICE_NAME, callback);
+
+    Some(iface)
 }
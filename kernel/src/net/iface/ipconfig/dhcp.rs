@@ -0,0 +1,301 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! A minimal DHCPv4 client.
+//!
+//! Like the DHCP client in Linux's IP autoconfiguration, the client only runs once at boot.
+//! The lease is never renewed.
+//!
+//! Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/ipv4/ipconfig.c>
+
+use core::time::Duration;
+
+use aster_bigtcp::{
+    errors::udp::{RecvError, SendError},
+    iface::BindPortConfig,
+    socket::UdpMetadata,
+    wire::{
+        DHCP_CLIENT_PORT, DHCP_SERVER_PORT, DhcpMessageType, DhcpPacket, DhcpRepr, EthernetAddress,
+        IpAddress, IpEndpoint, Ipv4Address, Ipv4Cidr,
+    },
+};
+
+use super::default_ipv4_prefix_len;
+use crate::{
+    events::IoEvents,
+    net::{
+        iface::{Iface, UdpSocket},
+        socket::ip::DatagramObserver,
+    },
+    prelude::*,
+    process::signal::{PollHandle, Pollable, Pollee},
+    util::random::getrandom,
+};
+
+/// The number of times that a message is sent before giving up.
+const SEND_RETRIES: usize = 6;
+/// The initial time to wait for a reply.
+const BASE_TIMEOUT: Duration = Duration::from_secs(2);
+/// The maximum time to wait for a reply.
+const MAX_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The minimum length of a DHCP message.
+///
+/// Some servers (e.g., the one in QEMU user networking) drop the messages that are shorter
+/// than the minimum BOOTP message.
+///
+/// Reference: <https://datatracker.ietf.org/doc/html/rfc951#section-3>
+const MIN_MESSAGE_LEN: usize = 548;
+
+/// The options that the client asks the server to supply.
+const PARAMETER_REQUEST_LIST: &[u8] = &[
+    1, // Subnet mask
+    3, // Router
+];
+
+/// The address and the router assigned by a DHCP server.
+pub(super) struct DhcpLease {
+    pub(super) addr: Ipv4Cidr,
+    pub(super) router: Option<Ipv4Address>,
+}
+
+/// Obtains a lease for the interface.
+///
+/// This method blocks until the server acknowledges a lease or all the retries fail.
+pub(super) fn request_lease(iface: &Arc<Iface>) -> Result<DhcpLease> {
+    let Some(ether_addr) = iface.ether_addr() else {
+        return_errno_with_message!(Errno::EINVAL, "DHCP requires an Ethernet interface");
+    };
+    let client = DhcpClient::new(iface.clone(), ether_addr)?;
+
+    let mut offer = None;
+    let mut timeout = BASE_TIMEOUT;
+    let mut retries = 0;
+
+    // Reference: <https://datatracker.ietf.org/doc/html/rfc2131#section-3.1>
+    while retries < SEND_RETRIES {
+        client.send(offer.as_ref())?;
+
+        let reply = match client.wait_events(IoEvents::IN, Some(&timeout), || {
+            client.try_recv(offer.as_ref())
+        }) {
+            Ok(reply) => reply,
+            Err(err) if err.error() == Errno::ETIME => {
+                retries += 1;
+                timeout = (timeout * 2).min(MAX_TIMEOUT);
+                continue;
+            }
+            Err(err) => return Err(err),
+        };
+
+        match reply.message_type {
+            DhcpMessageType::Offer => offer = Some(reply),
+            DhcpMessageType::Ack => {
+                let prefix_len = reply
+                    .subnet_mask
+                    .or(offer.and_then(|offer| offer.subnet_mask))
+                    .and_then(|mask| Ipv4Cidr::from_netmask(reply.addr, mask).ok())
+                    .map(|cidr| cidr.prefix_len())
+                    .or_else(|| default_ipv4_prefix_len(reply.addr))
+                    .ok_or_else(|| {
+                        Error::with_message(Errno::EINVAL, "the subnet mask cannot be determined")
+                    })?;
+                return Ok(DhcpLease {
+                    addr: Ipv4Cidr::new(reply.addr, prefix_len),
+                    router: reply.router,
+                });
+            }
+            // The offered address is no longer available. Start over.
+            _ => {
+                offer = None;
+                retries += 1;
+            }
+        }
+    }
+
+    return_errno_with_message!(Errno::ETIMEDOUT, "no DHCP servers replied");
+}
+
+struct DhcpClient {
+    iface: Arc<Iface>,
+    socket: UdpSocket,
+    pollee: Pollee,
+    ether_addr: EthernetAddress,
+    transaction_id: u32,
+}
+
+/// The fields that the client cares about in a reply from the server.
+#[derive(Clone, Copy)]
+struct DhcpReply {
+    message_type: DhcpMessageType,
+    addr: Ipv4Address,
+    server_id: Ipv4Address,
+    subnet_mask: Option<Ipv4Address>,
+    router: Option<Ipv4Address>,
+}
+
+impl DhcpClient {
+    fn new(iface: Arc<Iface>, ether_addr: EthernetAddress) -> Result<Self> {
+        let pollee = Pollee::new();
+
+        let endpoint = IpEndpoint::new(IpAddress::Ipv4(Ipv4Address::UNSPECIFIED), DHCP_CLIENT_PORT);
+        let bound_port = iface.bind_udp(BindPortConfig::new(endpoint, false))?;
+        let socket = match UdpSocket::new_bind(bound_port, DatagramObserver::new(pollee.clone())) {
+            Ok(socket) => socket,
+            Err((_, err)) => {
+                unreachable!("`new_bind` fails with {:?}, which should not happen", err)
+            }
+        };
+
+        let transaction_id = {
+            let mut bytes = [0; 4];
+            getrandom(&mut bytes);
+            u32::from_ne_bytes(bytes)
+        };
+
+        Ok(Self {
+            iface,
+            socket,
+            pollee,
+            ether_addr,
+            transaction_id,
+        })
+    }
+
+    /// Sends a DHCPDISCOVER message, or a DHCPREQUEST message if an offer has been selected.
+    fn send(&self, offer: Option<&DhcpReply>) -> Result<()> {
+        let repr = DhcpRepr {
+            message_type: if offer.is_some() {
+                DhcpMessageType::Request
+            } else {
+                DhcpMessageType::Discover
+            },
+            transaction_id: self.transaction_id,
+            secs: 0,
+            client_hardware_address: self.ether_addr,
+            client_ip: Ipv4Address::UNSPECIFIED,
+            your_ip: Ipv4Address::UNSPECIFIED,
+            server_ip: Ipv4Address::UNSPECIFIED,
+            router: None,
+            subnet_mask: None,
+            relay_agent_ip: Ipv4Address::UNSPECIFIED,
+            // Unicast replies cannot be received before the address is configured.
+            broadcast: true,
+            requested_ip: offer.map(|offer| offer.addr),
+            client_identifier: Some(self.ether_addr),
+            server_identifier: offer.map(|offer| offer.server_id),
+            parameter_request_list: Some(PARAMETER_REQUEST_LIST),
+            dns_servers: None,
+            max_size: None,
+            lease_duration: None,
+            renew_duration: None,
+            rebind_duration: None,
+            additional_options: &[],
+        };
+
+        let mut meta = UdpMetadata::from(IpEndpoint::new(
+            IpAddress::Ipv4(Ipv4Address::BROADCAST),
+            DHCP_SERVER_PORT,
+        ));
+        // The messages are sent from `0.0.0.0` because no address is configured yet.
+        meta.local_address = Some(IpAddress::Ipv4(Ipv4Address::UNSPECIFIED));
+
+        let size = repr.buffer_len().max(MIN_MESSAGE_LEN);
+        let result = self.socket.send(size, meta, |buffer| {
+            // The padding must be zeros (i.e., the PAD option).
+            buffer.fill(0);
+            repr.emit(&mut DhcpPacket::new_unchecked(buffer))
+                .expect("the buffer should be large enough for the DHCP message");
+        });
+        match result {
+            Ok(()) => (),
+            Err(SendError::BufferFull) => {
+                return_errno_with_message!(Errno::EAGAIN, "the send buffer is full")
+            }
+            Err(err @ (SendError::TooLarge | SendError::Unaddressable)) => {
+                unreachable!("sending a DHCP message should never fail with {:?}", err)
+            }
+        }
+
+        self.iface.poll();
+
+        Ok(())
+    }
+
+    /// Receives the next reply that matches the current state.
+    ///
+    /// Before an offer is selected, only DHCPOFFER messages are accepted. After that, only
+    /// DHCPACK and DHCPNAK messages from the selected server are accepted.
+    fn try_recv(&self, offer: Option<&DhcpReply>) -> Result<DhcpReply> {
+        loop {
+            let reply = match self.socket.recv(|data, _, _| self.parse_reply(data)) {
+                Ok(reply) => reply,
+                Err(RecvError::Exhausted) => {
+                    return_errno_with_message!(Errno::EAGAIN, "no DHCP replies are received")
+                }
+                Err(RecvError::Truncated) => {
+                    unreachable!("`recv` should never fail with `RecvError::Truncated`")
+                }
+            };
+            self.pollee.invalidate();
+
+            let Some(reply) = reply else {
+                continue;
+            };
+            let is_expected = match offer {
+                None => reply.message_type == DhcpMessageType::Offer,
+                Some(offer) => {
+                    matches!(
+                        reply.message_type,
+                        DhcpMessageType::Ack | DhcpMessageType::Nak
+                    ) && reply.server_id == offer.server_id
+                }
+            };
+            if is_expected {
+                return Ok(reply);
+            }
+        }
+    }
+
+    fn parse_reply(&self, data: &[u8]) -> Option<DhcpReply> {
+        let packet = DhcpPacket::new_checked(data).ok()?;
+        let repr = DhcpRepr::parse(&packet).ok()?;
+
+        if repr.transaction_id != self.transaction_id
+            || repr.client_hardware_address != self.ether_addr
+        {
+            return None;
+        }
+        // Servers must include their identifiers in DHCPOFFER, DHCPACK, and DHCPNAK messages.
+        // See <https://datatracker.ietf.org/doc/html/rfc2131#section-4.3.1>.
+        let server_id = repr.server_identifier?;
+        let addr = repr.your_ip;
+        if repr.message_type != DhcpMessageType::Nak
+            && (addr.is_unspecified() || addr.is_broadcast() || addr.is_multicast())
+        {
+            return None;
+        }
+
+        Some(DhcpReply {
+            message_type: repr.message_type,
+            addr,
+            server_id,
+            subnet_mask: repr.subnet_mask,
+            router: repr.router,
+        })
+    }
+
+    fn check_io_events(&self) -> IoEvents {
+        if self.socket.raw_with(|socket| socket.can_recv()) {
+            IoEvents::IN
+        } else {
+            IoEvents::empty()
+        }
+    }
+}
+
+impl Pollable for DhcpClient {
+    fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.pollee
+            .poll_with(mask, poller, || self.check_io_events())
+    }
+}
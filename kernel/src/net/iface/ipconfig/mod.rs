@@ -0,0 +1,262 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Boot-time configuration of the network interfaces.
+//!
+//! The IPv4 configuration is controlled by the `ip=` kernel parameter, which follows the syntax
+//! of Linux:
+//!
+//! ```text
+//! ip=<client-ip>:<server-ip>:<gw-ip>:<netmask>:<hostname>:<device>:<autoconf>
+//! ip=dhcp
+//! ip=off
+//! ```
+//!
+//! If the client address is specified, the device is configured statically. Otherwise, the
+//! address and the gateway are obtained via DHCP. The server address, the hostname, and the
+//! fields after `<autoconf>` (e.g., the DNS servers) are accepted but ignored.
+//!
+//! If the parameter is absent, the device is configured with the address that QEMU user
+//! networking assigns, so the default setup works without waiting for DHCP.
+//!
+//! The IPv6 configuration needs no parameters. Each Ethernet interface always gets a link-local
+//! address, and its global address and default route are configured via SLAAC.
+//!
+//! Reference: <https://elixir.bootlin.com/linux/v6.18/source/Documentation/admin-guide/nfs/nfsroot.rst>
+
+mod dhcp;
+mod slaac;
+
+use core::str::FromStr;
+
+use aster_bigtcp::{
+    iface::InterfaceFlags,
+    wire::{IpAddress, IpCidr, Ipv4Address, Ipv4Cidr, Ipv6Address, Ipv6Cidr},
+};
+use spin::Once;
+
+use super::Iface;
+use crate::{
+    net::{
+        net_ns::NetNamespace,
+        route::{IP6_RT_PRIO_USER, RT_SCOPE_UNIVERSE, RTPROT_BOOT, Route},
+    },
+    prelude::*,
+};
+
+static IP_CONFIG: Once<IpConfig> = Once::new();
+aster_cmdline::define_kv_param!("ip", IP_CONFIG);
+
+/// The address that QEMU user networking assigns.
+const QEMU_ADDRESS: Ipv4Address = Ipv4Address::new(10, 0, 2, 15);
+const QEMU_ADDRESS_PREFIX_LEN: u8 = 24; // mask: 255.255.255.0
+/// The gateway of QEMU user networking.
+const QEMU_GATEWAY: Ipv4Address = Ipv4Address::new(10, 0, 2, 2);
+
+/// The IPv4 configuration specified by the `ip=` kernel parameter.
+#[derive(Debug)]
+enum IpConfig {
+    /// No device is configured.
+    Off,
+    /// The device is configured with the specified address and gateway.
+    Static {
+        addr: Ipv4Cidr,
+        gateway: Option<Ipv4Address>,
+        device: Option<String>,
+    },
+    /// The device is configured via DHCP.
+    Dhcp { device: Option<String> },
+}
+
+impl FromStr for IpConfig {
+    type Err = Error;
+
+    // Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/ipv4/ipconfig.c> (`ip_auto_config_setup`)
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "off" | "none" => return Ok(Self::Off),
+            "on" | "any" | "dhcp" => return Ok(Self::Dhcp { device: None }),
+            _ => (),
+        }
+
+        let parse_addr = |field: &str| {
+            field
+                .parse::<Ipv4Address>()
+                .map_err(|_| Error::with_message(Errno::EINVAL, "the IPv4 address is invalid"))
+        };
+
+        let mut fields = s.split(':');
+        let mut next_field = || fields.next().filter(|field| !field.is_empty());
+
+        let client = next_field().map(parse_addr).transpose()?;
+        let _server = next_field();
+        let gateway = next_field().map(parse_addr).transpose()?;
+        let netmask = next_field().map(parse_addr).transpose()?;
+        let _hostname = next_field();
+        let device = next_field().map(String::from);
+        let autoconf = next_field();
+
+        let is_dhcp_enabled = match autoconf {
+            None | Some("on" | "any" | "dhcp") => true,
+            Some("off" | "none" | "static") => false,
+            Some(_) => return_errno_with_message!(
+                Errno::EINVAL,
+                "the autoconfiguration protocol is not supported"
+            ),
+        };
+
+        let Some(client) = client else {
+            if !is_dhcp_enabled {
+                return_errno_with_message!(
+                    Errno::EINVAL,
+                    "the client address is required if autoconfiguration is disabled"
+                );
+            }
+            return Ok(Self::Dhcp { device });
+        };
+
+        let prefix_len = match netmask {
+            Some(netmask) => Ipv4Cidr::from_netmask(client, netmask)
+                .map_err(|_| Error::with_message(Errno::EINVAL, "the netmask is invalid"))?
+                .prefix_len(),
+            None => default_ipv4_prefix_len(client).ok_or_else(|| {
+                Error::with_message(Errno::EINVAL, "the netmask cannot be guessed")
+            })?,
+        };
+
+        Ok(Self::Static {
+            addr: Ipv4Cidr::new(client, prefix_len),
+            gateway,
+            device,
+        })
+    }
+}
+
+/// Configures the interfaces of the initial network namespace without any network traffic.
+///
+/// This includes the link-local IPv6 addresses and the static IPv4 configuration.
+pub(in crate::net) fn init_static(net_ns: &NetNamespace) {
+    for iface in net_ns.ifaces() {
+        if let Some(ether_addr) = iface.ether_addr() {
+            slaac::add_link_local_addr(net_ns, &iface, ether_addr);
+        }
+    }
+
+    let (addr, gateway, device) = match IP_CONFIG.get() {
+        None => (
+            Ipv4Cidr::new(QEMU_ADDRESS, QEMU_ADDRESS_PREFIX_LEN),
+            Some(QEMU_GATEWAY),
+            None,
+        ),
+        Some(IpConfig::Static {
+            addr,
+            gateway,
+            device,
+        }) => (*addr, *gateway, device.as_deref()),
+        Some(IpConfig::Off | IpConfig::Dhcp { .. }) => return,
+    };
+
+    let Some(iface) = select_iface(net_ns, device) else {
+        return;
+    };
+    if let Err(err) = configure_ipv4(net_ns, &iface, addr, gateway) {
+        warn!("failed to configure {:?}: {:?}", iface.name(), err);
+    }
+}
+
+/// Configures the interfaces of the initial network namespace with DHCP and SLAAC.
+///
+/// Like Linux, the boot process waits for DHCP to finish. SLAAC runs in the background.
+pub(in crate::net) fn init_dynamic_in_first_kthread() {
+    let net_ns = NetNamespace::get_init_singleton();
+
+    for iface in net_ns.ifaces() {
+        if let Some(ether_addr) = iface.ether_addr() {
+            slaac::spawn_autoconf_thread(net_ns.clone(), iface, ether_addr);
+        }
+    }
+
+    let Some(IpConfig::Dhcp { device }) = IP_CONFIG.get() else {
+        return;
+    };
+    let Some(iface) = select_iface(net_ns, device.as_deref()) else {
+        return;
+    };
+
+    let res = dhcp::request_lease(&iface)
+        .and_then(|lease| configure_ipv4(net_ns, &iface, lease.addr, lease.router));
+    match res {
+        Ok(()) => info!("{:?} is configured via DHCP", iface.name()),
+        Err(err) => warn!("failed to configure {:?} via DHCP: {:?}", iface.name(), err),
+    }
+}
+
+/// Selects the interface by the name, or the first non-loopback interface if no name is given.
+fn select_iface(net_ns: &NetNamespace, device: Option<&str>) -> Option<Arc<Iface>> {
+    let iface = net_ns.ifaces().into_iter().find(|iface| match device {
+        Some(device) => iface.name().to_bytes() == device.as_bytes(),
+        None => !iface.flags().contains(InterfaceFlags::LOOPBACK),
+    });
+
+    if iface.is_none()
+        && let Some(device) = device
+    {
+        warn!("the network device {:?} does not exist", device);
+    }
+
+    iface
+}
+
+fn configure_ipv4(
+    net_ns: &NetNamespace,
+    iface: &Arc<Iface>,
+    addr: Ipv4Cidr,
+    gateway: Option<Ipv4Address>,
+) -> Result<()> {
+    net_ns.add_iface_addr(iface, IpCidr::Ipv4(addr), true)?;
+
+    if let Some(gateway) = gateway {
+        add_default_route(net_ns, iface, IpAddress::Ipv4(gateway), RTPROT_BOOT);
+    }
+
+    Ok(())
+}
+
+/// Adds the default route via the gateway.
+fn add_default_route(net_ns: &NetNamespace, iface: &Arc<Iface>, gateway: IpAddress, protocol: u8) {
+    let (dst, metric) = match gateway {
+        IpAddress::Ipv4(_) => (IpCidr::Ipv4(Ipv4Cidr::new(Ipv4Address::UNSPECIFIED, 0)), 0),
+        IpAddress::Ipv6(_) => (
+            IpCidr::Ipv6(Ipv6Cidr::new(Ipv6Address::UNSPECIFIED, 0)),
+            IP6_RT_PRIO_USER,
+        ),
+    };
+
+    let default_route = Route {
+        dst,
+        gateway: Some(gateway),
+        iface_index: iface.index(),
+        pref_src: None,
+        metric,
+        protocol,
+        scope: RT_SCOPE_UNIVERSE,
+    };
+
+    net_ns
+        .modify_routes(|route_table| {
+            route_table.insert(default_route);
+            Ok(())
+        })
+        .unwrap();
+}
+
+/// Returns the prefix length of the address class, which is used if no netmask is given.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/ipv4/ipconfig.c> (`ic_defaults`)
+fn default_ipv4_prefix_len(addr: Ipv4Address) -> Option<u8> {
+    match addr.octets()[0] {
+        0..=127 => Some(8),
+        128..=191 => Some(16),
+        192..=223 => Some(24),
+        _ => None,
+    }
+}
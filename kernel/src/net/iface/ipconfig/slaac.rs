@@ -0,0 +1,279 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! IPv6 stateless address autoconfiguration (SLAAC).
+//!
+//! Reference: <https://datatracker.ietf.org/doc/html/rfc4862>
+
+use core::time::Duration;
+
+use aster_bigtcp::{
+    errors::raw::RecvError,
+    socket::RawIpMetadata,
+    wire::{
+        EthernetAddress, IPV6_LINK_LOCAL_ALL_ROUTERS, Icmpv6Packet, IpAddress, IpCidr, IpProtocol,
+        Ipv6Address, Ipv6Cidr, Ipv6Packet, NdiscPrefixInfoFlags, NdiscRepr, RawHardwareAddress,
+    },
+};
+
+use super::add_default_route;
+use crate::{
+    events::IoEvents,
+    net::{
+        iface::{Iface, RawIpSocket},
+        net_ns::NetNamespace,
+        route::RTPROT_RA,
+        socket::ip::DatagramObserver,
+    },
+    prelude::*,
+    process::signal::{PollHandle, Pollable, Pollee},
+    thread::kernel_thread::ThreadOptions,
+};
+
+/// The maximum number of router solicitations to send.
+///
+/// Reference: <https://datatracker.ietf.org/doc/html/rfc4861#section-10>
+const MAX_RTR_SOLICITATIONS: usize = 3;
+/// The interval between router solicitations.
+const RTR_SOLICITATION_INTERVAL: Duration = Duration::from_secs(4);
+
+/// The hop limit of NDP messages, which proves that they come from the same link.
+///
+/// Reference: <https://datatracker.ietf.org/doc/html/rfc4861#section-6.1.2>
+const NDISC_HOP_LIMIT: u8 = 255;
+
+/// The prefix length that is required to form an address with an interface identifier.
+///
+/// Reference: <https://datatracker.ietf.org/doc/html/rfc4291#section-2.5.1>
+const IFACE_ID_PREFIX_LEN: u8 = 64;
+
+/// The link-local prefix (i.e., `fe80::/64`).
+const LINK_LOCAL_PREFIX: Ipv6Address = Ipv6Address::new(0xfe80, 0, 0, 0, 0, 0, 0, 0);
+
+/// Assigns the link-local address to the interface.
+pub(super) fn add_link_local_addr(
+    net_ns: &NetNamespace,
+    iface: &Arc<Iface>,
+    ether_addr: EthernetAddress,
+) {
+    let addr = Ipv6Cidr::new(
+        iface_addr(&LINK_LOCAL_PREFIX, ether_addr),
+        IFACE_ID_PREFIX_LEN,
+    );
+    if let Err(err) = net_ns.add_iface_addr(iface, IpCidr::Ipv6(addr), true) {
+        warn!(
+            "failed to add the link-local address to {:?}: {:?}",
+            iface.name(),
+            err
+        );
+    }
+}
+
+/// Spawns a thread to configure the interface with router advertisements.
+///
+/// The thread solicits router advertisements. For the first advertisement received, a global
+/// address is formed from the advertised prefix, and the router becomes a default router.
+//
+// TODO: Perform duplicate address detection, process unsolicited router advertisements, and
+// expire the addresses and the routes according to their lifetimes.
+pub(super) fn spawn_autoconf_thread(
+    net_ns: Arc<NetNamespace>,
+    iface: Arc<Iface>,
+    ether_addr: EthernetAddress,
+) {
+    let task_fn = move || {
+        if let Err(err) = autoconf(&net_ns, &iface, ether_addr) {
+            info!("SLAAC on {:?} failed: {:?}", iface.name(), err);
+        }
+    };
+
+    ThreadOptions::new(task_fn).spawn();
+}
+
+fn autoconf(net_ns: &NetNamespace, iface: &Arc<Iface>, ether_addr: EthernetAddress) -> Result<()> {
+    let solicitor = RouterSolicitor::new(iface.clone(), ether_addr)?;
+
+    for _ in 0..MAX_RTR_SOLICITATIONS {
+        solicitor.send()?;
+
+        let advert =
+            match solicitor.wait_events(IoEvents::IN, Some(&RTR_SOLICITATION_INTERVAL), || {
+                solicitor.try_recv()
+            }) {
+                Ok(advert) => advert,
+                Err(err) if err.error() == Errno::ETIME => continue,
+                Err(err) => return Err(err),
+            };
+
+        if let Some(prefix) = advert.prefix {
+            let addr = Ipv6Cidr::new(iface_addr(&prefix, ether_addr), IFACE_ID_PREFIX_LEN);
+            net_ns.add_iface_addr(iface, IpCidr::Ipv6(addr), advert.is_on_link)?;
+        }
+        if advert.is_default_router {
+            add_default_route(net_ns, iface, IpAddress::Ipv6(advert.router), RTPROT_RA);
+        }
+
+        return Ok(());
+    }
+
+    return_errno_with_message!(Errno::ETIMEDOUT, "no routers replied");
+}
+
+struct RouterSolicitor {
+    iface: Arc<Iface>,
+    socket: RawIpSocket,
+    pollee: Pollee,
+    ether_addr: EthernetAddress,
+}
+
+/// The fields that SLAAC cares about in a router advertisement.
+struct RouterAdvert {
+    router: Ipv6Address,
+    is_default_router: bool,
+    /// The prefix to form an address, if any.
+    prefix: Option<Ipv6Address>,
+    is_on_link: bool,
+}
+
+impl RouterSolicitor {
+    fn new(iface: Arc<Iface>, ether_addr: EthernetAddress) -> Result<Self> {
+        let pollee = Pollee::new();
+
+        let link_local_addr = iface_addr(&LINK_LOCAL_PREFIX, ether_addr);
+        let bound_port =
+            iface.bind_raw(IpAddress::Ipv6(link_local_addr), IpProtocol::Icmpv6.into())?;
+        let socket = RawIpSocket::new_raw(
+            bound_port,
+            IpProtocol::Icmpv6,
+            DatagramObserver::new(pollee.clone()),
+        );
+
+        Ok(Self {
+            iface,
+            socket,
+            pollee,
+            ether_addr,
+        })
+    }
+
+    fn send(&self) -> Result<()> {
+        let repr = NdiscRepr::RouterSolicit {
+            lladdr: Some(RawHardwareAddress::from_bytes(self.ether_addr.as_bytes())),
+        };
+        let mut payload = vec![0; repr.buffer_len()];
+        repr.emit(&mut Icmpv6Packet::new_unchecked(&mut payload[..]));
+
+        // The checksum is filled in by the socket.
+        self.socket
+            .send(
+                &payload,
+                IpAddress::Ipv6(IPV6_LINK_LOCAL_ALL_ROUTERS),
+                NDISC_HOP_LIMIT,
+            )
+            .map_err(|_| {
+                Error::with_message(Errno::ENOBUFS, "failed to send the router solicitation")
+            })?;
+
+        self.iface.poll();
+
+        Ok(())
+    }
+
+    fn try_recv(&self) -> Result<RouterAdvert> {
+        loop {
+            let advert = match self.socket.recv(parse_router_advert) {
+                Ok(advert) => advert,
+                Err(RecvError::Exhausted) => {
+                    return_errno_with_message!(
+                        Errno::EAGAIN,
+                        "no router advertisements are received"
+                    )
+                }
+                Err(RecvError::Truncated) => {
+                    unreachable!("`recv` should never fail with `RecvError::Truncated`")
+                }
+            };
+            self.pollee.invalidate();
+
+            if let Some(advert) = advert {
+                return Ok(advert);
+            }
+        }
+    }
+
+    fn check_io_events(&self) -> IoEvents {
+        if self.socket.can_recv() {
+            IoEvents::IN
+        } else {
+            IoEvents::empty()
+        }
+    }
+}
+
+impl Pollable for RouterSolicitor {
+    fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.pollee
+            .poll_with(mask, poller, || self.check_io_events())
+    }
+}
+
+/// Parses the router advertisement in the ICMPv6 packet.
+///
+/// Reference: <https://datatracker.ietf.org/doc/html/rfc4861#section-6.1.2>
+fn parse_router_advert(packet: &[u8], meta: RawIpMetadata) -> Option<RouterAdvert> {
+    let ipv6_packet = Ipv6Packet::new_checked(packet).ok()?;
+    let IpAddress::Ipv6(router) = meta.remote_addr else {
+        return None;
+    };
+    if ipv6_packet.hop_limit() != NDISC_HOP_LIMIT || !router.is_unicast_link_local() {
+        return None;
+    }
+
+    let icmp_packet = Icmpv6Packet::new_checked(&packet[meta.header_len..]).ok()?;
+    if !icmp_packet.verify_checksum(&router, &ipv6_packet.dst_addr()) {
+        return None;
+    }
+    let NdiscRepr::RouterAdvert {
+        router_lifetime,
+        prefix_info,
+        ..
+    } = NdiscRepr::parse(&icmp_packet).ok()?
+    else {
+        return None;
+    };
+
+    // Reference: <https://datatracker.ietf.org/doc/html/rfc4862#section-5.5.3>
+    let prefix_info = prefix_info.filter(|info| {
+        info.flags.contains(NdiscPrefixInfoFlags::ADDRCONF)
+            && info.prefix_len == IFACE_ID_PREFIX_LEN
+            && info.valid_lifetime.total_millis() > 0
+            && !info.prefix.is_unicast_link_local()
+    });
+
+    Some(RouterAdvert {
+        router,
+        is_default_router: router_lifetime.total_millis() > 0,
+        prefix: prefix_info.map(|info| info.prefix),
+        is_on_link: prefix_info
+            .is_some_and(|info| info.flags.contains(NdiscPrefixInfoFlags::ON_LINK)),
+    })
+}
+
+/// Forms an address from the prefix and the interface identifier derived from the Ethernet
+/// address (i.e., the modified EUI-64 format).
+///
+/// Reference: <https://datatracker.ietf.org/doc/html/rfc4291#appendix-A>
+fn iface_addr(prefix: &Ipv6Address, ether_addr: EthernetAddress) -> Ipv6Address {
+    let mut octets = prefix.octets();
+    let mac = ether_addr.as_bytes();
+    octets[8..].copy_from_slice(&[
+        mac[0] ^ 0x02,
+        mac[1],
+        mac[2],
+        0xff,
+        0xfe,
+        mac[3],
+        mac[4],
+        mac[5],
+    ]);
+    Ipv6Address::from(octets)
+}
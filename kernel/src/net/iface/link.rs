@@ -0,0 +1,288 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Virtual links created via netlink.
+//!
+//! All the virtual links are recorded in a global registry. The lock of the registry serializes
+//! the creation, deletion, and configuration of the links, like the RTNL lock in Linux.
+
+use super::{Iface, bridge::Bridge, tun, veth::VethEnd};
+use crate::{
+    net::{net_ns::NetNamespace, socket::netlink},
+    prelude::*,
+};
+
+/// The kind of a virtual link.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+    Veth,
+    Bridge,
+}
+
+impl LinkKind {
+    /// Parses the kind from its name (i.e., `IFLA_INFO_KIND`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "veth" => Some(Self::Veth),
+            "bridge" => Some(Self::Bridge),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the kind.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Veth => "veth",
+            Self::Bridge => "bridge",
+        }
+    }
+}
+
+/// The information about a virtual link.
+pub struct LinkInfo {
+    pub kind: LinkKind,
+    /// The index of the peer interface for veth pairs.
+    pub peer_index: Option<u32>,
+    /// The index of the bridge that the interface is attached to.
+    pub master_index: Option<u32>,
+}
+
+#[derive(Clone)]
+enum VirtualLink {
+    Veth(Arc<VethEnd>),
+    Bridge(Arc<Bridge>),
+}
+
+impl VirtualLink {
+    fn iface(&self) -> &Arc<Iface> {
+        match self {
+            Self::Veth(end) => end.iface(),
+            Self::Bridge(bridge) => bridge.iface(),
+        }
+    }
+}
+
+static LINKS: Mutex<Vec<VirtualLink>> = Mutex::new(Vec::new());
+
+fn find_link(links: &[VirtualLink], iface: &Arc<Iface>) -> Option<VirtualLink> {
+    links
+        .iter()
+        .find(|link| Arc::ptr_eq(link.iface(), iface))
+        .cloned()
+}
+
+/// Creates a veth pair.
+///
+/// Returns the interfaces of the two ends.
+pub(in crate::net) fn new_veth_pair(
+    name_template: &str,
+    net_ns: &Arc<NetNamespace>,
+    peer_name_template: &str,
+    peer_net_ns: &Arc<NetNamespace>,
+) -> Result<(Arc<Iface>, Arc<Iface>)> {
+    let mut links = LINKS.lock();
+
+    let (end, peer) = VethEnd::new_pair(name_template, net_ns, peer_name_template, peer_net_ns)?;
+    let ifaces = (end.iface().clone(), peer.iface().clone());
+    links.push(VirtualLink::Veth(end));
+    links.push(VirtualLink::Veth(peer));
+
+    Ok(ifaces)
+}
+
+/// Creates a bridge.
+pub(in crate::net) fn new_bridge(
+    name_template: &str,
+    net_ns: &Arc<NetNamespace>,
+) -> Result<Arc<Iface>> {
+    let mut links = LINKS.lock();
+
+    let bridge = Bridge::new(name_template, net_ns)?;
+    let iface = bridge.iface().clone();
+    links.push(VirtualLink::Bridge(bridge));
+
+    Ok(iface)
+}
+
+/// Deletes a virtual link.
+///
+/// Deleting an end of a veth pair also deletes the other end. Returns the deleted interfaces and
+/// the network namespaces that they were in.
+pub(in crate::net) fn delete_link(
+    iface: &Arc<Iface>,
+) -> Result<Vec<(Arc<Iface>, Arc<NetNamespace>)>> {
+    let mut links = LINKS.lock();
+
+    let Some(link) = find_link(&links, iface) else {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "the interface cannot be deleted");
+    };
+
+    Ok(remove_link(&mut links, link))
+}
+
+/// Deletes the virtual links among the interfaces of a network namespace that is being destroyed.
+///
+/// The TUN/TAP devices are deleted as well. Returns the interfaces that are not deleted, and the
+/// interfaces that are deleted with the virtual links in other network namespaces (i.e., the
+/// peers of veth ends) along with their network namespaces.
+pub(in crate::net) fn delete_netns_links(
+    ifaces: Vec<Arc<Iface>>,
+) -> (Vec<Arc<Iface>>, Vec<(Arc<Iface>, Arc<NetNamespace>)>) {
+    let mut links = LINKS.lock();
+
+    let (virtual_ifaces, other_ifaces): (Vec<_>, Vec<_>) = ifaces
+        .into_iter()
+        .filter(|iface| !tun::delete_device(iface))
+        .partition(|iface| find_link(&links, iface).is_some());
+
+    let mut deleted = Vec::new();
+    for iface in virtual_ifaces {
+        // The link may have been deleted with its peer.
+        if let Some(link) = find_link(&links, &iface) {
+            deleted.append(&mut remove_link(&mut links, link));
+        }
+    }
+
+    (other_ifaces, deleted)
+}
+
+/// Removes a virtual link from the system.
+///
+/// The interfaces in the network namespaces that are being destroyed are not returned.
+fn remove_link(
+    links: &mut Vec<VirtualLink>,
+    link: VirtualLink,
+) -> Vec<(Arc<Iface>, Arc<NetNamespace>)> {
+    let removed = match link {
+        VirtualLink::Veth(end) => {
+            let peer = end.peer();
+            let mut removed = vec![(end.iface().clone(), end.remove())];
+            if let Some(peer) = peer {
+                removed.push((peer.iface().clone(), peer.remove()));
+            }
+            removed
+        }
+        VirtualLink::Bridge(bridge) => vec![(bridge.iface().clone(), bridge.remove())],
+    };
+
+    links.retain(|link| {
+        !removed
+            .iter()
+            .any(|(iface, _)| Arc::ptr_eq(link.iface(), iface))
+    });
+
+    removed
+        .into_iter()
+        .filter_map(|(iface, net_ns)| Some((iface, net_ns?)))
+        .collect()
+}
+
+/// Attaches the interface to a bridge, or detaches it if `master` is `None`.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/bridge/br_if.c> (`br_add_if`)
+pub(in crate::net) fn set_master(iface: &Arc<Iface>, master: Option<&Arc<Iface>>) -> Result<()> {
+    let links = LINKS.lock();
+
+    let Some(master) = master else {
+        if let Some(VirtualLink::Veth(end)) = find_link(&links, iface)
+            && let Some(bridge) = end.master()
+        {
+            bridge.remove_port(&end);
+        }
+        return Ok(());
+    };
+
+    let Some(VirtualLink::Bridge(bridge)) = find_link(&links, master) else {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "the master interface is not a bridge");
+    };
+
+    let port = match find_link(&links, iface) {
+        Some(VirtualLink::Veth(end)) => end,
+        Some(VirtualLink::Bridge(_)) => {
+            return_errno_with_message!(Errno::ELOOP, "a bridge cannot be a bridge port")
+        }
+        None if iface.ether_addr().is_none() => {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "only Ethernet interfaces can be bridge ports"
+            )
+        }
+        None => {
+            return_errno_with_message!(
+                Errno::EOPNOTSUPP,
+                "only veth interfaces can be bridge ports currently"
+            )
+        }
+    };
+
+    // Like Linux, the interface is detached from the old bridge before being attached to the new
+    // one.
+    if let Some(old_bridge) = port.master() {
+        if Arc::ptr_eq(&old_bridge, &bridge) {
+            return Ok(());
+        }
+        old_bridge.remove_port(&port);
+    }
+
+    bridge.add_port(&port)
+}
+
+/// Returns the information about the interface if it is a virtual link.
+pub(in crate::net) fn link_info(iface: &Arc<Iface>) -> Option<LinkInfo> {
+    let link = find_link(&LINKS.lock(), iface)?;
+
+    let info = match link {
+        VirtualLink::Veth(end) => LinkInfo {
+            kind: LinkKind::Veth,
+            peer_index: end.peer().map(|peer| peer.iface().index()),
+            master_index: end.master().map(|bridge| bridge.iface().index()),
+        },
+        VirtualLink::Bridge(_) => LinkInfo {
+            kind: LinkKind::Bridge,
+            peer_index: None,
+            master_index: None,
+        },
+    };
+
+    Some(info)
+}
+
+/// Returns whether the interface cannot be moved to another network namespace.
+///
+/// Like Linux, bridges are local to their network namespaces.
+pub(in crate::net) fn is_netns_local(iface: &Arc<Iface>) -> bool {
+    matches!(
+        find_link(&LINKS.lock(), iface),
+        Some(VirtualLink::Bridge(_))
+    )
+}
+
+/// Updates the network namespace of the interface after it is moved.
+///
+/// If the interface is a bridge port, it is detached from the bridge because the bridge stays in
+/// the original network namespace. Like Linux, the interface is reported as deleted in the
+/// original network namespace and as new in the target network namespace.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/core/dev.c>
+pub(in crate::net) fn notify_iface_moved(
+    iface: &Arc<Iface>,
+    old_net_ns: &NetNamespace,
+    new_net_ns: &Arc<NetNamespace>,
+) {
+    tun::notify_iface_moved(iface, new_net_ns);
+
+    let links = LINKS.lock();
+    match find_link(&links, iface) {
+        Some(VirtualLink::Veth(end)) => {
+            end.set_net_ns(new_net_ns);
+            if let Some(bridge) = end.master() {
+                bridge.remove_port(&end);
+            }
+        }
+        Some(VirtualLink::Bridge(bridge)) => bridge.set_net_ns(new_net_ns),
+        None => (),
+    }
+    drop(links);
+
+    netlink::notify_del_link(old_net_ns, iface);
+    netlink::notify_new_link(new_net_ns, iface);
+}
@@ -1,21 +1,42 @@
 // SPDX-License-Identifier: MPL-2.0
 
+mod bridge;
 mod broadcast;
 mod ext;
 mod init;
+mod ipconfig;
+mod link;
 mod poll;
 mod sched;
+mod tun;
+mod util;
+mod veth;
 
 pub use broadcast::is_broadcast_endpoint;
-pub use init::{init, iter_all_ifaces, loopback_iface, virtio_iface};
-pub(super) use poll::init_in_first_kthread;
+pub use init::init;
+pub(super) use init::{new_loopback, new_virtio};
+pub(super) use ipconfig::{init_dynamic_in_first_kthread, init_static};
+pub use link::{LinkInfo, LinkKind};
+pub(super) use link::{
+    delete_link, is_netns_local, link_info, new_bridge, new_veth_pair, notify_iface_moved,
+    set_master,
+};
+pub(super) use poll::{init_in_first_kthread, spawn_background_poll_thread};
+pub use tun::{TunFlags, TunInfo, TunQueue};
+
+/// The maximum length of interface names, including the trailing null byte.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/if.h>
+pub const IFNAMSIZ: usize = 16;
 
 pub type Iface = dyn aster_bigtcp::iface::Iface<ext::BigtcpExt>;
 pub type BoundTcpPort = aster_bigtcp::iface::BoundTcpPort<ext::BigtcpExt>;
 pub type BoundUdpPort = aster_bigtcp::iface::BoundUdpPort<ext::BigtcpExt>;
+pub type BoundRawPort = aster_bigtcp::iface::BoundRawPort<ext::BigtcpExt>;
 
 pub type RawTcpSocketExt = aster_bigtcp::socket::RawTcpSocketExt<ext::BigtcpExt>;
 
 pub type TcpConnection = aster_bigtcp::socket::TcpConnection<ext::BigtcpExt>;
 pub type TcpListener = aster_bigtcp::socket::TcpListener<ext::BigtcpExt>;
 pub type UdpSocket = aster_bigtcp::socket::UdpSocket<ext::BigtcpExt>;
+pub type RawIpSocket = aster_bigtcp::socket::RawIpSocket<ext::BigtcpExt>;
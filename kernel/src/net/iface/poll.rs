@@ -5,37 +5,44 @@ use core::time::Duration;
 
 use ostd::{debug, timer::Jiffies};
 
-use super::{Iface, iter_all_ifaces};
+use super::Iface;
 use crate::{
+    net::net_ns::NetNamespace,
     sched::{Nice, SchedPolicy},
     thread::kernel_thread::ThreadOptions,
     time::wait::WaitTimeout,
 };
 
 pub fn init_in_first_kthread() {
-    for iface in iter_all_ifaces() {
+    for iface in NetNamespace::get_init_singleton().ifaces() {
         spawn_background_poll_thread(iface.clone());
     }
 }
 
-pub(super) fn poll_ifaces() {
-    for iface in iter_all_ifaces() {
+pub(super) fn poll_ifaces(net_ns: &NetNamespace) {
+    for iface in net_ns.ifaces() {
         iface.poll();
     }
 }
 
-fn spawn_background_poll_thread(iface: Arc<Iface>) {
+pub(in crate::net) fn spawn_background_poll_thread(iface: Arc<Iface>) {
     let task_fn = move || {
         debug!("spawn background poll thread for {:?}", iface.name());
 
         let sched_poll = iface.sched_poll();
         let wait_queue = sched_poll.polling_wait_queue();
+        let should_exit = || sched_poll.is_stopped() && !iface.has_tcp_connections();
 
         loop {
-            let next_poll_at_ms = if let Some(next_poll_at_ms) = sched_poll.next_poll_at_ms() {
-                next_poll_at_ms
-            } else {
-                wait_queue.wait_until(|| sched_poll.next_poll_at_ms())
+            let Some(next_poll_at_ms) = wait_queue.wait_until(|| {
+                if should_exit() {
+                    Some(None)
+                } else {
+                    sched_poll.next_poll_at_ms().map(Some)
+                }
+            }) else {
+                debug!("stop background poll thread for {:?}", iface.name());
+                break;
             };
 
             let now_as_ms = Jiffies::elapsed().as_duration().as_millis() as u64;
@@ -55,9 +62,15 @@ fn spawn_background_poll_thread(iface: Arc<Iface>) {
 
             let duration = Duration::from_millis(next_poll_at_ms - now_as_ms);
             let _ = wait_queue.wait_until_or_timeout(
-                // If `sched_poll.next_poll_at_ms()` changes to an earlier time, we will end the
-                // waiting.
-                || (sched_poll.next_poll_at_ms()? < next_poll_at_ms).then_some(()),
+                // If `sched_poll.next_poll_at_ms()` changes to an earlier time or the thread should
+                // exit, we will end the waiting.
+                || {
+                    (should_exit()
+                        || sched_poll
+                            .next_poll_at_ms()
+                            .is_some_and(|millis| millis < next_poll_at_ms))
+                    .then_some(())
+                },
                 &duration,
             );
         }
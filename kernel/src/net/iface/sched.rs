@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use core::sync::atomic::{AtomicU64, Ordering};
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 
 use aster_bigtcp::iface::ScheduleNextPoll;
 use ostd::sync::WaitQueue;
@@ -11,6 +11,8 @@ pub struct PollScheduler {
     next_poll_at_ms: AtomicU64,
     /// The wait queue that the background polling thread will sleep on.
     polling_wait_queue: WaitQueue,
+    /// Whether the background polling thread should exit.
+    is_stopped: AtomicBool,
 }
 
 impl PollScheduler {
@@ -18,6 +20,7 @@ impl PollScheduler {
         Self {
             next_poll_at_ms: AtomicU64::new(0),
             polling_wait_queue: WaitQueue::new(),
+            is_stopped: AtomicBool::new(false),
         }
     }
 
@@ -29,12 +32,31 @@ impl PollScheduler {
     pub(super) fn polling_wait_queue(&self) -> &WaitQueue {
         &self.polling_wait_queue
     }
+
+    pub(super) fn is_stopped(&self) -> bool {
+        self.is_stopped.load(Ordering::Relaxed)
+    }
+
+    /// Stops the background polling thread.
+    ///
+    /// This should be called when the interface is no longer reachable. The thread keeps polling
+    /// the interface until all the TCP connections bound to it are released, so that the
+    /// connections that are being closed can finish.
+    pub(in crate::net) fn stop(&self) {
+        self.is_stopped.store(true, Ordering::Relaxed);
+        self.polling_wait_queue.wake_all();
+    }
 }
 
 impl ScheduleNextPoll for PollScheduler {
     fn schedule_next_poll(&self, poll_at: Option<u64>) {
         let Some(new_instant) = poll_at else {
             self.next_poll_at_ms.store(0, Ordering::Relaxed);
+            // The stopped background polling thread may be waiting for the last TCP connection to
+            // be released.
+            if self.is_stopped() {
+                self.polling_wait_queue.wake_all();
+            }
             return;
         };
 
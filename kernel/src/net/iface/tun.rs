@@ -0,0 +1,754 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! TUN/TAP devices.
+//!
+//! A TUN/TAP device is a virtual interface whose packets are exchanged with the userspace instead
+//! of a physical network. A TUN device exchanges IP packets, while a TAP device exchanges Ethernet
+//! frames.
+//!
+//! The userspace attaches files opened from `/dev/net/tun` to a device. Each attached file serves
+//! as a queue of the device. The packets transmitted via the interface are distributed among the
+//! queues, and the packets written to any queue are received by the interface.
+
+use aster_bigtcp::{
+    device::{
+        Device, DeviceCapabilities, FilterDevice, Medium, NotifyDevice, OffloadDevice, RxToken,
+        TxToken, WithDevice,
+    },
+    iface::{InterfaceFlags, InterfaceType, IpIface},
+    time::Instant,
+    wire::{ETHERNET_HEADER_LEN, EthernetAddress},
+};
+use aster_softirq::BottomHalfDisabled;
+
+use super::{
+    Iface,
+    poll::spawn_background_poll_thread,
+    sched::PollScheduler,
+    util::{new_virtual_ether_iface, virtual_iface_flags},
+};
+use crate::{
+    events::IoEvents,
+    net::net_ns::NetNamespace,
+    prelude::*,
+    process::{credentials::capabilities::CapSet, posix_thread::PosixThread, signal::Pollee},
+};
+
+bitflags! {
+    /// The flags of TUN/TAP devices.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/if_tun.h#L65>
+    pub struct TunFlags: u16 {
+        const IFF_TUN          = 0x0001;
+        const IFF_TAP          = 0x0002;
+        const IFF_NAPI         = 0x0010;
+        const IFF_NAPI_FRAGS   = 0x0020;
+        const IFF_NO_CARRIER   = 0x0040;
+        const IFF_NO_PI        = 0x1000;
+        /// This flag has no effect.
+        const IFF_ONE_QUEUE    = 0x2000;
+        const IFF_VNET_HDR     = 0x4000;
+        const IFF_TUN_EXCL     = 0x8000;
+        const IFF_MULTI_QUEUE  = 0x0100;
+        const IFF_ATTACH_QUEUE = 0x0200;
+        const IFF_DETACH_QUEUE = 0x0400;
+        const IFF_PERSIST      = 0x0800;
+    }
+}
+
+impl TunFlags {
+    /// The flags that are supported and can be changed by `TUNSETIFF`.
+    const FEATURES: Self = Self::IFF_NO_PI
+        .union(Self::IFF_ONE_QUEUE)
+        .union(Self::IFF_MULTI_QUEUE);
+
+    /// Returns the flags that are reported by `TUNGETFEATURES`.
+    pub fn supported_features() -> Self {
+        Self::IFF_TUN | Self::IFF_TAP | Self::FEATURES
+    }
+}
+
+/// The packet information that precedes each packet if [`TunFlags::IFF_NO_PI`] is not set.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/if_tun.h#L100>
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct CTunPi {
+    flags: u16,
+    /// The link-layer protocol in network byte order.
+    proto: u16,
+}
+
+/// The flag in [`CTunPi`] that indicates that the packet is truncated.
+const TUN_PKT_STRIP: u16 = 0x0001;
+
+/// The maximum number of queues of a device.
+const MAX_TAP_QUEUES: usize = 256;
+
+/// The maximum number of packets waiting to be read in a queue.
+///
+/// This resembles the default transmit queue length of TUN/TAP devices in Linux.
+const TUN_READQ_SIZE: usize = 500;
+
+/// The MTU of TUN/TAP devices.
+const TUN_MTU: usize = 1500;
+
+/// The maximum size of packets written by the userspace.
+const MAX_PACKET_LEN: usize = 65535;
+
+/// The TUN/TAP devices that exist.
+static TUN_DEVICES: Mutex<Vec<Arc<TunDevice>>> = Mutex::new(Vec::new());
+
+/// A TUN/TAP device.
+struct TunDevice {
+    iface: Arc<Iface>,
+    link: Arc<SpinLock<TunLink, BottomHalfDisabled>>,
+    state: Mutex<TunState>,
+    /// The network namespace that the interface is in.
+    ///
+    /// If the network namespace is destroyed, the device will have been deleted as well.
+    net_ns: Mutex<Weak<NetNamespace>>,
+}
+
+struct TunState {
+    flags: TunFlags,
+    /// The number of queues that are detached by `TUNSETQUEUE`.
+    num_detached: usize,
+    is_deleted: bool,
+}
+
+/// The link layer of a TUN/TAP device.
+///
+/// This is the device driver used by the interface.
+struct TunLink {
+    medium: Medium,
+    /// The packets written by the userspace and waiting to be received by the interface.
+    rx_packets: VecDeque<Vec<u8>>,
+    /// The attached queues.
+    queues: Vec<Arc<TunQueue>>,
+}
+
+struct TunDriver(Arc<SpinLock<TunLink, BottomHalfDisabled>>);
+
+impl WithDevice for TunDriver {
+    type Device = TunLink;
+
+    fn with<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut Self::Device) -> R,
+    {
+        let mut link = self.0.lock();
+        f(&mut link)
+    }
+}
+
+impl TunDevice {
+    fn new(flags: TunFlags, name_template: &str, net_ns: &Arc<NetNamespace>) -> Result<Arc<Self>> {
+        let medium = if flags.contains(TunFlags::IFF_TAP) {
+            Medium::Ethernet
+        } else {
+            Medium::Ip
+        };
+        let link = Arc::new(SpinLock::new(TunLink {
+            medium,
+            rx_packets: VecDeque::new(),
+            queues: Vec::new(),
+        }));
+
+        let iface = net_ns.add_iface(name_template, |name| {
+            let driver = TunDriver(link.clone());
+            match medium {
+                Medium::Ip => new_tun_iface(driver, name),
+                _ => new_virtual_ether_iface(driver, name),
+            }
+        })?;
+        spawn_background_poll_thread(iface.clone());
+
+        Ok(Arc::new(Self {
+            iface,
+            link,
+            state: Mutex::new(TunState {
+                flags: flags & (TunFlags::IFF_TUN | TunFlags::IFF_TAP | TunFlags::FEATURES),
+                num_detached: 0,
+                is_deleted: false,
+            }),
+            net_ns: Mutex::new(Arc::downgrade(net_ns)),
+        }))
+    }
+
+    fn flags(&self) -> TunFlags {
+        self.state.lock().flags
+    }
+
+    /// Attaches a queue to the device.
+    fn attach(&self, queue: &Arc<TunQueue>, was_detached: bool) -> Result<()> {
+        let mut state = self.state.lock();
+        if state.is_deleted {
+            return_errno_with_message!(Errno::ENODEV, "the device has been deleted");
+        }
+
+        let mut link = self.link.lock();
+        if !state.flags.contains(TunFlags::IFF_MULTI_QUEUE) && !link.queues.is_empty() {
+            return_errno_with_message!(Errno::EBUSY, "the device has been attached to a file");
+        }
+        if link.queues.len() >= MAX_TAP_QUEUES {
+            return_errno_with_message!(Errno::E2BIG, "the device has too many queues");
+        }
+        link.queues.push(queue.clone());
+
+        if was_detached {
+            state.num_detached -= 1;
+        }
+
+        Ok(())
+    }
+
+    /// Detaches a queue from the device.
+    ///
+    /// If `is_closing` is false, the queue is detached by `TUNSETQUEUE` and can be attached
+    /// again later.
+    fn detach(&self, queue: &Arc<TunQueue>, is_closing: bool) {
+        let mut state = self.state.lock();
+
+        let mut link = self.link.lock();
+        if let Some(pos) = link.queues.iter().position(|q| Arc::ptr_eq(q, queue)) {
+            link.queues.remove(pos);
+        }
+        drop(link);
+
+        if !is_closing {
+            state.num_detached += 1;
+        }
+
+        self.delete_if_unused(state);
+    }
+
+    /// Forgets a queue that is detached by `TUNSETQUEUE` and is now closing.
+    fn forget_detached(&self) {
+        let mut state = self.state.lock();
+        state.num_detached -= 1;
+
+        self.delete_if_unused(state);
+    }
+
+    /// Deletes the device if it is not persistent and no queues are attached or detached.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18/source/drivers/net/tun.c> (`__tun_detach`)
+    fn delete_if_unused(&self, mut state: MutexGuard<TunState>) {
+        if state.num_detached != 0
+            || state.flags.contains(TunFlags::IFF_PERSIST)
+            || state.is_deleted
+            || !self.link.lock().queues.is_empty()
+        {
+            return;
+        }
+
+        state.is_deleted = true;
+        drop(state);
+
+        self.delete();
+    }
+
+    /// Removes the interface from the system.
+    fn delete(&self) {
+        TUN_DEVICES
+            .lock()
+            .retain(|device| !core::ptr::eq(device.as_ref(), self));
+
+        self.iface.sched_poll().stop();
+
+        if let Some(net_ns) = self.net_ns.lock().upgrade() {
+            net_ns.remove_iface(&self.iface);
+        }
+    }
+
+    /// Receives a packet written by the userspace.
+    fn receive(&self, reader: &mut VmReader) -> Result<usize> {
+        let flags = self.flags();
+        let total_len = reader.remain();
+
+        if !flags.contains(TunFlags::IFF_NO_PI) {
+            if reader.remain() < size_of::<CTunPi>() {
+                return_errno_with_message!(
+                    Errno::EINVAL,
+                    "the packet is too short to contain the packet information"
+                );
+            }
+            // TODO: Use the protocol in the packet information to receive the packet.
+            reader.read_val::<CTunPi>()?;
+        }
+
+        let len = reader.remain();
+        if len > MAX_PACKET_LEN {
+            return_errno_with_message!(Errno::EINVAL, "the packet is too large");
+        }
+        let mut packet = vec![0u8; len];
+        reader.read_fallible(&mut VmWriter::from(packet.as_mut_slice()))?;
+
+        if flags.contains(TunFlags::IFF_TAP) {
+            if len < ETHERNET_HEADER_LEN {
+                return_errno_with_message!(Errno::EINVAL, "the Ethernet frame is too short");
+            }
+        } else if flags.contains(TunFlags::IFF_NO_PI)
+            && !matches!(packet.first().map(|byte| byte >> 4), Some(4 | 6))
+        {
+            // Reference: <https://elixir.bootlin.com/linux/v6.18/source/drivers/net/tun.c> (`tun_get_user`)
+            return_errno_with_message!(Errno::EINVAL, "the IP version is invalid");
+        }
+
+        self.link.lock().rx_packets.push_back(packet);
+        self.iface.poll();
+
+        Ok(total_len)
+    }
+}
+
+/// Creates the interface of a TUN device.
+fn new_tun_iface(driver: TunDriver, name: CString) -> Arc<Iface> {
+    let flags = virtual_iface_flags(InterfaceFlags::POINTOPOINT | InterfaceFlags::NOARP);
+
+    IpIface::new(
+        driver,
+        None,
+        None,
+        name,
+        PollScheduler::new(),
+        InterfaceType::NONE,
+        flags,
+    ) as Arc<Iface>
+}
+
+impl TunLink {
+    /// Transmits a packet to one of the attached queues.
+    fn transmit(&self, packet: Vec<u8>) {
+        let queue = match self.queues.as_slice() {
+            // Like Linux, the packet is dropped if no queue is attached.
+            [] => return,
+            [queue] => queue,
+            queues => &queues[flow_hash(self.medium, &packet) as usize % queues.len()],
+        };
+        queue.push(packet);
+    }
+}
+
+/// Computes the hash of the flow that the packet belongs to.
+///
+/// The packets of the same flow are always transmitted to the same queue.
+fn flow_hash(medium: Medium, packet: &[u8]) -> u32 {
+    let ip_packet = match medium {
+        Medium::Ethernet => packet.get(ETHERNET_HEADER_LEN..).unwrap_or_default(),
+        _ => packet,
+    };
+    // Hash the source and destination addresses.
+    let addrs = match ip_packet.first().map(|byte| byte >> 4) {
+        Some(4) => ip_packet.get(12..20),
+        Some(6) => ip_packet.get(8..40),
+        _ => None,
+    };
+
+    // FNV-1a hash.
+    addrs
+        .unwrap_or_default()
+        .iter()
+        .fold(0x811c9dc5, |hash, byte| {
+            (hash ^ (*byte as u32)).wrapping_mul(0x01000193)
+        })
+}
+
+impl Device for TunLink {
+    type RxToken<'a> = TunRxToken;
+    type TxToken<'a> = TunTxToken<'a>;
+
+    fn receive(&mut self, _timestamp: Instant) -> Option<(Self::RxToken<'_>, Self::TxToken<'_>)> {
+        let packet = self.rx_packets.pop_front()?;
+        Some((TunRxToken(packet), TunTxToken(self)))
+    }
+
+    fn transmit(&mut self, _timestamp: Instant) -> Option<Self::TxToken<'_>> {
+        Some(TunTxToken(self))
+    }
+
+    fn capabilities(&self) -> DeviceCapabilities {
+        let mut caps = DeviceCapabilities::default();
+        caps.medium = self.medium;
+        caps.max_transmission_unit = match self.medium {
+            Medium::Ethernet => TUN_MTU + ETHERNET_HEADER_LEN,
+            _ => TUN_MTU,
+        };
+        caps.max_burst_size = None;
+        caps
+    }
+}
+
+impl NotifyDevice for TunLink {
+    fn notify_poll_end(&mut self) {}
+}
+
+impl OffloadDevice for TunLink {}
+
+impl FilterDevice for TunLink {
+    // The userspace can write arbitrary frames, so they are always filtered by the iface itself.
+    fn set_rx_filter(&mut self, _is_promisc: bool, _multicast_addrs: &[EthernetAddress]) {}
+}
+
+struct TunRxToken(Vec<u8>);
+
+impl RxToken for TunRxToken {
+    fn consume<R, F>(self, f: F) -> R
+    where
+        F: FnOnce(&[u8]) -> R,
+    {
+        f(&self.0)
+    }
+}
+
+struct TunTxToken<'a>(&'a TunLink);
+
+impl TxToken for TunTxToken<'_> {
+    fn consume<R, F>(self, len: usize, f: F) -> R
+    where
+        F: FnOnce(&mut [u8]) -> R,
+    {
+        let mut packet = vec![0u8; len];
+        let res = f(&mut packet);
+        self.0.transmit(packet);
+        res
+    }
+}
+
+/// A queue of a TUN/TAP device.
+///
+/// Each file opened from `/dev/net/tun` owns a queue. The queue is not attached to any device
+/// until `TUNSETIFF` succeeds.
+pub struct TunQueue {
+    device: Mutex<QueueDevice>,
+    packets: SpinLock<VecDeque<Vec<u8>>, BottomHalfDisabled>,
+    pollee: Pollee,
+}
+
+enum QueueDevice {
+    None,
+    Attached(Arc<TunDevice>),
+    /// The queue is detached by `TUNSETQUEUE` and can be attached again.
+    Detached(Arc<TunDevice>),
+}
+
+/// The information of the device that a queue is attached to.
+pub struct TunInfo {
+    pub name: CString,
+    pub flags: TunFlags,
+}
+
+impl TunQueue {
+    /// Creates a new queue that is not attached to any device.
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            device: Mutex::new(QueueDevice::None),
+            packets: SpinLock::new(VecDeque::new()),
+            pollee: Pollee::new(),
+        })
+    }
+
+    /// Attaches the queue to the device with the specified name, creating the device if it does
+    /// not exist.
+    ///
+    /// If the name is empty or contains `%d`, a new device will always be created.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18/source/drivers/net/tun.c> (`tun_set_iff`)
+    pub fn set_iff(
+        self: &Arc<Self>,
+        name: &CStr,
+        flags: TunFlags,
+        net_ns: &Arc<NetNamespace>,
+        posix_thread: &PosixThread,
+    ) -> Result<TunInfo> {
+        let mut queue_device = self.device.lock();
+        if !matches!(&*queue_device, QueueDevice::None) {
+            return_errno_with_message!(Errno::EEXIST, "the file has been attached to a device");
+        }
+
+        // Like Linux, `IFF_TUN` takes precedence if both types are specified.
+        let is_tun = flags.contains(TunFlags::IFF_TUN);
+        if !is_tun && !flags.contains(TunFlags::IFF_TAP) {
+            return_errno_with_message!(Errno::EINVAL, "the device type is invalid");
+        }
+        if flags.intersects(TunFlags::IFF_VNET_HDR | TunFlags::IFF_NAPI | TunFlags::IFF_NAPI_FRAGS)
+        {
+            return_errno_with_message!(Errno::EINVAL, "the device flags are not supported");
+        }
+
+        let name = name
+            .to_str()
+            .map_err(|_| Error::with_message(Errno::EINVAL, "the device name is invalid"))?;
+
+        let existing_iface = (!name.is_empty() && !name.contains('%'))
+            .then(|| {
+                net_ns
+                    .ifaces()
+                    .into_iter()
+                    .find(|iface| iface.name().to_bytes() == name.as_bytes())
+            })
+            .flatten();
+
+        let device = if let Some(iface) = existing_iface {
+            if flags.contains(TunFlags::IFF_TUN_EXCL) {
+                return_errno_with_message!(Errno::EBUSY, "the device already exists");
+            }
+
+            let Some(device) = find_device(&iface) else {
+                return_errno_with_message!(Errno::EINVAL, "the device is not a TUN/TAP device");
+            };
+            let device_flags = device.flags();
+            if device_flags.contains(TunFlags::IFF_TUN) != is_tun {
+                return_errno_with_message!(Errno::EINVAL, "the device type does not match");
+            }
+            if device_flags.contains(TunFlags::IFF_MULTI_QUEUE)
+                != flags.contains(TunFlags::IFF_MULTI_QUEUE)
+            {
+                return_errno_with_message!(Errno::EINVAL, "the multi-queue flag does not match");
+            }
+
+            // TODO: Support `TUNSETOWNER` and `TUNSETGROUP`, which allow the owner to attach
+            // files without the capability.
+            net_ns.check_cap(CapSet::NET_ADMIN, posix_thread)?;
+
+            device.attach(self, false)?;
+
+            let mut state = device.state.lock();
+            state.flags = (state.flags - TunFlags::FEATURES) | (flags & TunFlags::FEATURES);
+
+            device
+        } else {
+            net_ns.check_cap(CapSet::NET_ADMIN, posix_thread)?;
+
+            let name_template = match name {
+                "" if is_tun => "tun%d",
+                "" => "tap%d",
+                name => name,
+            };
+            let device = TunDevice::new(flags, name_template, net_ns)?;
+            TUN_DEVICES.lock().push(device.clone());
+
+            device.attach(self, false)?;
+
+            device
+        };
+
+        let info = TunInfo {
+            name: device.iface.name(),
+            flags: device.flags(),
+        };
+        *queue_device = QueueDevice::Attached(device);
+        self.pollee.notify(IoEvents::OUT);
+
+        Ok(info)
+    }
+
+    /// Attaches the queue to the device that it is detached from.
+    pub fn attach_queue(self: &Arc<Self>, posix_thread: &PosixThread) -> Result<()> {
+        let mut queue_device = self.device.lock();
+        let QueueDevice::Detached(device) = &*queue_device else {
+            return_errno_with_message!(Errno::EINVAL, "the file is not detached");
+        };
+
+        let Some(net_ns) = device.net_ns.lock().upgrade() else {
+            return_errno_with_message!(Errno::ENODEV, "the device has been deleted");
+        };
+        net_ns.check_cap(CapSet::NET_ADMIN, posix_thread)?;
+
+        device.attach(self, true)?;
+
+        let device = device.clone();
+        *queue_device = QueueDevice::Attached(device);
+        self.pollee.notify(IoEvents::OUT);
+
+        Ok(())
+    }
+
+    /// Detaches the queue from the multi-queue device that it is attached to.
+    pub fn detach_queue(self: &Arc<Self>) -> Result<()> {
+        let mut queue_device = self.device.lock();
+        let QueueDevice::Attached(device) = &*queue_device else {
+            return_errno_with_message!(Errno::EINVAL, "the file is not attached");
+        };
+        if !device.flags().contains(TunFlags::IFF_MULTI_QUEUE) {
+            return_errno_with_message!(Errno::EINVAL, "the device is not a multi-queue device");
+        }
+
+        device.detach(self, false);
+        self.packets.lock().clear();
+        self.pollee.invalidate();
+
+        let device = device.clone();
+        *queue_device = QueueDevice::Detached(device);
+
+        Ok(())
+    }
+
+    /// Detaches the queue from its device because the file is closing.
+    pub fn release(self: &Arc<Self>) {
+        let queue_device = core::mem::replace(&mut *self.device.lock(), QueueDevice::None);
+        match queue_device {
+            QueueDevice::None => (),
+            QueueDevice::Attached(device) => device.detach(self, true),
+            QueueDevice::Detached(device) => device.forget_detached(),
+        }
+    }
+
+    /// Returns the information of the attached device.
+    pub fn info(&self) -> Result<TunInfo> {
+        let device = self.attached_device()?;
+        Ok(TunInfo {
+            name: device.iface.name(),
+            flags: device.flags(),
+        })
+    }
+
+    /// Sets whether the attached device persists after all its queues are closed.
+    pub fn set_persist(&self, persist: bool) -> Result<()> {
+        let device = self.attached_device()?;
+        device
+            .state
+            .lock()
+            .flags
+            .set(TunFlags::IFF_PERSIST, persist);
+        Ok(())
+    }
+
+    /// Reads a packet transmitted via the interface.
+    ///
+    /// If the buffer is too small, the packet will be truncated.
+    pub fn try_read(&self, writer: &mut VmWriter) -> Result<usize> {
+        let device = self.attached_device()?;
+        let flags = device.flags();
+
+        if !flags.contains(TunFlags::IFF_NO_PI) && writer.avail() < size_of::<CTunPi>() {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the buffer is too small to contain the packet information"
+            );
+        }
+
+        let Some(packet) = self.packets.lock().pop_front() else {
+            return_errno_with_message!(Errno::EAGAIN, "no packets are available");
+        };
+        self.pollee.invalidate();
+
+        let mut copied_len = 0;
+        if !flags.contains(TunFlags::IFF_NO_PI) {
+            let proto = if flags.contains(TunFlags::IFF_TAP) {
+                u16::from_be_bytes([packet[12], packet[13]])
+            } else if packet[0] >> 4 == 6 {
+                ETH_P_IPV6
+            } else {
+                ETH_P_IP
+            };
+            let pi = CTunPi {
+                flags: if writer.avail() < size_of::<CTunPi>() + packet.len() {
+                    TUN_PKT_STRIP
+                } else {
+                    0
+                },
+                proto: proto.to_be(),
+            };
+            writer.write_val(&pi)?;
+            copied_len += size_of::<CTunPi>();
+        }
+
+        copied_len += writer.write_fallible(&mut VmReader::from(packet.as_slice()))?;
+
+        Ok(copied_len)
+    }
+
+    /// Writes a packet to be received by the interface.
+    pub fn write(&self, reader: &mut VmReader) -> Result<usize> {
+        let device = self.attached_device()?;
+        device.receive(reader)
+    }
+
+    /// Returns the I/O events of the queue.
+    pub fn check_io_events(&self) -> IoEvents {
+        if self.attached_device().is_err() {
+            return IoEvents::ERR;
+        }
+
+        let mut events = IoEvents::OUT;
+        if !self.packets.lock().is_empty() {
+            events |= IoEvents::IN;
+        }
+        events
+    }
+
+    pub fn pollee(&self) -> &Pollee {
+        &self.pollee
+    }
+
+    /// Returns the device that the queue belongs to.
+    ///
+    /// Like Linux, a detached queue still belongs to its device. It can be used for I/O, but it
+    /// will not receive packets transmitted via the interface.
+    fn attached_device(&self) -> Result<Arc<TunDevice>> {
+        let device = match &*self.device.lock() {
+            QueueDevice::Attached(device) | QueueDevice::Detached(device) => device.clone(),
+            QueueDevice::None => {
+                return_errno_with_message!(Errno::EBADFD, "the file is not attached")
+            }
+        };
+
+        // The device can be deleted while queues are attached if its network namespace is
+        // destroyed.
+        if device.state.lock().is_deleted {
+            return_errno_with_message!(Errno::EBADFD, "the device has been deleted");
+        }
+
+        Ok(device)
+    }
+
+    fn push(&self, packet: Vec<u8>) {
+        let mut packets = self.packets.lock();
+        // Like Linux, the packet is dropped if the queue is full.
+        if packets.len() >= TUN_READQ_SIZE {
+            return;
+        }
+        packets.push_back(packet);
+        drop(packets);
+
+        self.pollee.notify(IoEvents::IN);
+    }
+}
+
+const ETH_P_IP: u16 = 0x0800;
+const ETH_P_IPV6: u16 = 0x86DD;
+
+fn find_device(iface: &Arc<Iface>) -> Option<Arc<TunDevice>> {
+    TUN_DEVICES
+        .lock()
+        .iter()
+        .find(|device| Arc::ptr_eq(&device.iface, iface))
+        .cloned()
+}
+
+/// Deletes the TUN/TAP device of the interface because its network namespace is being destroyed.
+///
+/// Returns `false` if the interface does not belong to a TUN/TAP device.
+pub(super) fn delete_device(iface: &Arc<Iface>) -> bool {
+    let Some(device) = find_device(iface) else {
+        return false;
+    };
+
+    let mut state = device.state.lock();
+    if !state.is_deleted {
+        state.is_deleted = true;
+        drop(state);
+        device.delete();
+    }
+
+    true
+}
+
+/// Updates the network namespace of the interface if it belongs to a TUN/TAP device.
+pub(in crate::net) fn notify_iface_moved(iface: &Arc<Iface>, net_ns: &Arc<NetNamespace>) {
+    if let Some(device) = find_device(iface) {
+        *device.net_ns.lock() = Arc::downgrade(net_ns);
+    }
+}
@@ -0,0 +1,52 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_bigtcp::{
+    device::{FilterDevice, NotifyDevice, OffloadDevice, WithDevice},
+    iface::{EtherIface, InterfaceFlags},
+    wire::EthernetAddress,
+};
+
+use super::{Iface, sched::PollScheduler};
+use crate::{prelude::*, util::random::getrandom};
+
+/// Returns the flags of a newly created virtual interface, including `extra_flags`.
+///
+/// Like Linux, virtual interfaces are down when created, so `InterfaceFlags::UP` is not set.
+/// It should be set later when the userspace brings up the interface.
+pub(super) fn virtual_iface_flags(extra_flags: InterfaceFlags) -> InterfaceFlags {
+    // FIXME: These flags are currently hardcoded.
+    // In the future, we should set appropriate values.
+    InterfaceFlags::RUNNING | InterfaceFlags::MULTICAST | InterfaceFlags::LOWER_UP | extra_flags
+}
+
+/// Creates the interface of a virtual Ethernet device (e.g., a veth end, a bridge, or a TAP
+/// device).
+///
+/// Like Linux, the interface has a random locally administered address. It is down when created
+/// (see [`virtual_iface_flags`]).
+pub(super) fn new_virtual_ether_iface<D>(driver: D, name: CString) -> Arc<Iface>
+where
+    D: WithDevice + 'static,
+    D::Device: NotifyDevice + FilterDevice + OffloadDevice,
+{
+    EtherIface::new(
+        driver,
+        random_ether_addr(),
+        None,
+        name,
+        PollScheduler::new(),
+        virtual_iface_flags(InterfaceFlags::BROADCAST),
+    ) as Arc<Iface>
+}
+
+/// Generates a random locally administered Ethernet address.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/linux/etherdevice.h> (`eth_random_addr`)
+fn random_ether_addr() -> EthernetAddress {
+    let mut ether_addr = [0u8; 6];
+    getrandom(&mut ether_addr);
+    ether_addr[0] &= 0xfe; // Clear the multicast bit.
+    ether_addr[0] |= 0x02; // Set the locally administered bit.
+
+    EthernetAddress(ether_addr)
+}
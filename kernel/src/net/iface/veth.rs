@@ -0,0 +1,275 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Virtual Ethernet (veth) devices.
+//!
+//! veth devices are created in pairs. The frames transmitted via one end of a pair are received by
+//! the other end, so a pair works like an Ethernet cable between two interfaces. The two ends can
+//! be in different network namespaces, which makes veth pairs useful for connecting containers.
+//!
+//! The frames are received asynchronously in work items. Receiving a frame synchronously would
+//! poll the peer interface while the transmitting interface is still being polled, which may
+//! deadlock or recurse without bound if the peer responds immediately.
+
+use aster_bigtcp::{
+    device::{
+        Device, DeviceCapabilities, FilterDevice, Medium, NotifyDevice, OffloadDevice, RxToken,
+        TxToken, WithDevice,
+    },
+    time::Instant,
+    wire::{ETHERNET_HEADER_LEN, EthernetAddress},
+};
+use aster_softirq::BottomHalfDisabled;
+
+use super::{
+    Iface, bridge::Bridge, poll::spawn_background_poll_thread, util::new_virtual_ether_iface,
+};
+use crate::{
+    net::net_ns::NetNamespace,
+    prelude::*,
+    thread::work_queue::{WorkPriority, submit_work_item, work_item::WorkItem},
+};
+
+/// The MTU of veth devices.
+const VETH_MTU: usize = 1500;
+
+/// The maximum number of frames waiting to be received by an end.
+///
+/// This resembles the default transmit queue length of veth devices in Linux.
+const VETH_RXQ_SIZE: usize = 1000;
+
+/// An end of a veth pair.
+pub(super) struct VethEnd {
+    iface: Arc<Iface>,
+    link: Arc<SpinLock<VethLink, BottomHalfDisabled>>,
+    /// The frames transmitted by the peer and waiting to be received by the interface.
+    rx_frames: SpinLock<VecDeque<Vec<u8>>, BottomHalfDisabled>,
+    /// The work item that polls the interface.
+    poll_work: Arc<WorkItem>,
+    /// The bridge that the end is attached to as a port.
+    master: SpinLock<Option<Weak<Bridge>>, BottomHalfDisabled>,
+    /// The network namespace that the interface is in.
+    ///
+    /// If the network namespace is destroyed, the interface will have been deleted as well.
+    net_ns: Mutex<Weak<NetNamespace>>,
+}
+
+/// The link layer of an end of a veth pair.
+///
+/// This is the device driver used by the interface.
+struct VethLink {
+    end: Weak<VethEnd>,
+    peer: Weak<VethEnd>,
+}
+
+struct VethDriver(Arc<SpinLock<VethLink, BottomHalfDisabled>>);
+
+impl WithDevice for VethDriver {
+    type Device = VethLink;
+
+    fn with<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut Self::Device) -> R,
+    {
+        let mut link = self.0.lock();
+        f(&mut link)
+    }
+}
+
+impl VethEnd {
+    /// Creates a veth pair.
+    ///
+    /// The ends are added to `net_ns` and `peer_net_ns`, respectively. Their names are generated
+    /// from the templates as in [`NetNamespace::add_iface`].
+    pub(super) fn new_pair(
+        name_template: &str,
+        net_ns: &Arc<NetNamespace>,
+        peer_name_template: &str,
+        peer_net_ns: &Arc<NetNamespace>,
+    ) -> Result<(Arc<Self>, Arc<Self>)> {
+        let end = Self::new(name_template, net_ns)?;
+        let peer = match Self::new(peer_name_template, peer_net_ns) {
+            Ok(peer) => peer,
+            Err(err) => {
+                end.remove();
+                return Err(err);
+            }
+        };
+
+        end.link.lock().peer = Arc::downgrade(&peer);
+        peer.link.lock().peer = Arc::downgrade(&end);
+
+        Ok((end, peer))
+    }
+
+    fn new(name_template: &str, net_ns: &Arc<NetNamespace>) -> Result<Arc<Self>> {
+        let link = Arc::new(SpinLock::new(VethLink {
+            end: Weak::new(),
+            peer: Weak::new(),
+        }));
+
+        let iface = net_ns.add_iface(name_template, |name| {
+            new_virtual_ether_iface(VethDriver(link.clone()), name)
+        })?;
+        spawn_background_poll_thread(iface.clone());
+
+        let end = Arc::new_cyclic(|weak_end: &Weak<Self>| {
+            let weak_end = weak_end.clone();
+            let poll_work = WorkItem::new(Box::new(move || {
+                if let Some(end) = weak_end.upgrade() {
+                    end.iface.poll();
+                }
+            }));
+
+            Self {
+                iface,
+                link: link.clone(),
+                rx_frames: SpinLock::new(VecDeque::new()),
+                poll_work,
+                master: SpinLock::new(None),
+                net_ns: Mutex::new(Arc::downgrade(net_ns)),
+            }
+        });
+        link.lock().end = Arc::downgrade(&end);
+
+        Ok(end)
+    }
+
+    pub(super) fn iface(&self) -> &Arc<Iface> {
+        &self.iface
+    }
+
+    /// Returns the other end of the pair.
+    pub(super) fn peer(&self) -> Option<Arc<Self>> {
+        self.link.lock().peer.upgrade()
+    }
+
+    /// Returns the bridge that the end is attached to.
+    pub(super) fn master(&self) -> Option<Arc<Bridge>> {
+        self.master.lock().as_ref().and_then(Weak::upgrade)
+    }
+
+    /// Sets the bridge that the end is attached to.
+    ///
+    /// This method should only be called by the bridge when attaching or detaching the port.
+    pub(super) fn set_master(&self, master: Option<Weak<Bridge>>) {
+        *self.master.lock() = master;
+    }
+
+    /// Updates the network namespace after the interface is moved.
+    pub(super) fn set_net_ns(&self, net_ns: &Arc<NetNamespace>) {
+        *self.net_ns.lock() = Arc::downgrade(net_ns);
+    }
+
+    /// Removes the interface from the system.
+    ///
+    /// Returns the network namespace that the interface is removed from, or `None` if the network
+    /// namespace is being destroyed.
+    pub(super) fn remove(&self) -> Option<Arc<NetNamespace>> {
+        if let Some(bridge) = self.master() {
+            bridge.remove_port(self);
+        }
+
+        self.iface.sched_poll().stop();
+
+        let net_ns = self.net_ns.lock().upgrade()?;
+        net_ns.remove_iface(&self.iface);
+
+        Some(net_ns)
+    }
+
+    /// Transmits a frame forwarded by the bridge that the end is attached to.
+    pub(super) fn transmit(&self, frame: &[u8]) {
+        // The frame is dropped if it cannot be sent (e.g., if the interface is down).
+        if self.iface.send_frame(frame, None).is_ok() {
+            self.poll_later();
+        }
+    }
+
+    /// Receives a frame transmitted by the peer.
+    fn receive(self: &Arc<Self>, frame: Vec<u8>) {
+        // Like Linux, the frame is dropped if the interface is down.
+        if !self.iface.is_up() {
+            return;
+        }
+
+        // The frames received by a bridge port are forwarded by the bridge.
+        if let Some(bridge) = self.master() {
+            bridge.receive(self, frame);
+            return;
+        }
+
+        let mut rx_frames = self.rx_frames.lock();
+        // Like Linux, the frame is dropped if the queue is full.
+        if rx_frames.len() >= VETH_RXQ_SIZE {
+            return;
+        }
+        rx_frames.push_back(frame);
+        drop(rx_frames);
+
+        self.poll_later();
+    }
+
+    fn poll_later(&self) {
+        submit_work_item(self.poll_work.clone(), WorkPriority::High);
+    }
+}
+
+impl Device for VethLink {
+    type RxToken<'a> = VethRxToken;
+    type TxToken<'a> = VethTxToken<'a>;
+
+    fn receive(&mut self, _timestamp: Instant) -> Option<(Self::RxToken<'_>, Self::TxToken<'_>)> {
+        let frame = self.end.upgrade()?.rx_frames.lock().pop_front()?;
+        Some((VethRxToken(frame), VethTxToken(self)))
+    }
+
+    fn transmit(&mut self, _timestamp: Instant) -> Option<Self::TxToken<'_>> {
+        Some(VethTxToken(self))
+    }
+
+    fn capabilities(&self) -> DeviceCapabilities {
+        let mut caps = DeviceCapabilities::default();
+        caps.medium = Medium::Ethernet;
+        caps.max_transmission_unit = VETH_MTU + ETHERNET_HEADER_LEN;
+        caps.max_burst_size = None;
+        caps
+    }
+}
+
+impl NotifyDevice for VethLink {
+    fn notify_poll_end(&mut self) {}
+}
+
+impl OffloadDevice for VethLink {}
+
+impl FilterDevice for VethLink {
+    // The peer can transmit arbitrary frames, so they are always filtered by the iface itself.
+    fn set_rx_filter(&mut self, _is_promisc: bool, _multicast_addrs: &[EthernetAddress]) {}
+}
+
+struct VethRxToken(Vec<u8>);
+
+impl RxToken for VethRxToken {
+    fn consume<R, F>(self, f: F) -> R
+    where
+        F: FnOnce(&[u8]) -> R,
+    {
+        f(&self.0)
+    }
+}
+
+struct VethTxToken<'a>(&'a VethLink);
+
+impl TxToken for VethTxToken<'_> {
+    fn consume<R, F>(self, len: usize, f: F) -> R
+    where
+        F: FnOnce(&mut [u8]) -> R,
+    {
+        let mut frame = vec![0u8; len];
+        let res = f(&mut frame);
+        if let Some(peer) = self.0.peer.upgrade() {
+            peer.receive(frame);
+        }
+        res
+    }
+}
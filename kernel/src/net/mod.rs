@@ -1,16 +1,19 @@
 // SPDX-License-Identifier: MPL-2.0
 
 pub mod iface;
+pub mod net_ns;
+pub mod netfilter;
+pub mod route;
 pub mod socket;
 pub mod uts_ns;
 
 pub fn init() {
     iface::init();
-    socket::netlink::init();
     socket::vsock::init();
 }
 
 /// Lazy init should be called after spawning init thread.
 pub fn init_in_first_kthread() {
     iface::init_in_first_kthread();
+    iface::init_dynamic_in_first_kthread();
 }
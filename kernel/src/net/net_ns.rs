@@ -0,0 +1,739 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_bigtcp::{
+    errors::iface::AddrError,
+    iface::InterfaceFlags,
+    socket::CongestionControl,
+    wire::{IpAddress, IpCidr},
+};
+use spin::Once;
+
+use crate::{
+    fs::pseudofs::{NsCommonOps, NsType, StashedDentry},
+    net::{
+        iface::{self, IFNAMSIZ, Iface},
+        netfilter::Netfilter,
+        route::{Route, RouteTable},
+        socket::netlink::{self, NetlinkSocketTable},
+    },
+    prelude::*,
+    process::{Gid, UserNamespace, credentials::capabilities::CapSet, posix_thread::PosixThread},
+};
+
+/// The network namespace.
+///
+/// A network namespace provides an isolated network stack. It owns a set of network interfaces,
+/// which includes its own loopback interface, and the netlink sockets bound in it. The sockets
+/// bound to an interface are tracked by the interface itself, so the ports are isolated between
+/// network namespaces as well.
+///
+/// An interface other than the loopback interface can be moved to another network namespace.
+/// When a network namespace is destroyed, its virtual interfaces (e.g., veth pairs) are deleted,
+/// and the other interfaces are moved back to the initial network namespace.
+///
+/// Each network namespace also has its own routing table. The lock of the routing table also
+/// serializes the changes of interface addresses and states, so that the routes are always
+/// consistent with the interfaces.
+///
+/// The packets received or transmitted by the interfaces are filtered by the packet filter of the
+/// network namespace that the interfaces belong to.
+pub struct NetNamespace {
+    loopback_iface: Arc<Iface>,
+    /// All the interfaces in the namespace, ordered by their indexes.
+    ifaces: RwLock<Vec<Arc<Iface>>>,
+    // Lock order: `route_table` -> `ifaces`
+    route_table: RwLock<RouteTable>,
+    netfilter: Arc<Netfilter>,
+    netlink_socket_table: NetlinkSocketTable,
+    /// The range of groups that are allowed to create ping sockets (i.e., the
+    /// `net.ipv4.ping_group_range` sysctl).
+    ping_group_range: SpinLock<(Gid, Gid)>,
+    /// The default congestion control algorithm of new TCP sockets (i.e., the
+    /// `net.ipv4.tcp_congestion_control` sysctl).
+    tcp_congestion_control: SpinLock<CongestionControl>,
+    owner: Arc<UserNamespace>,
+    stashed_dentry: StashedDentry,
+}
+
+impl NetNamespace {
+    /// Returns a reference to the singleton initial network namespace.
+    pub fn get_init_singleton() -> &'static Arc<NetNamespace> {
+        static INIT: Once<Arc<NetNamespace>> = Once::new();
+
+        INIT.call_once(|| {
+            // Initialize loopback before virtio
+            // to ensure the loopback interface index is ahead of virtio.
+            let loopback_iface = iface::new_loopback();
+
+            let virtio_iface = iface::new_virtio();
+
+            let mut ifaces = vec![loopback_iface.clone()];
+            if let Some(virtio_iface) = virtio_iface.as_ref() {
+                ifaces.push(virtio_iface.clone());
+            }
+
+            let owner = UserNamespace::get_init_singleton().clone();
+            let net_ns = Self::new(loopback_iface, ifaces, owner);
+
+            iface::init_static(&net_ns);
+
+            net_ns
+        })
+    }
+
+    fn new(
+        loopback_iface: Arc<Iface>,
+        ifaces: Vec<Arc<Iface>>,
+        owner: Arc<UserNamespace>,
+    ) -> Arc<Self> {
+        let mut route_table = RouteTable::new();
+        for iface in ifaces.iter() {
+            add_prefix_routes(&mut route_table, iface);
+        }
+
+        let netfilter = Netfilter::new();
+        for iface in ifaces.iter() {
+            iface.set_packet_filter(Some(netfilter.clone()));
+        }
+
+        Arc::new(Self {
+            loopback_iface,
+            ifaces: RwLock::new(ifaces),
+            route_table: RwLock::new(route_table),
+            netfilter,
+            netlink_socket_table: NetlinkSocketTable::new(),
+            ping_group_range: SpinLock::new(DEFAULT_PING_GROUP_RANGE),
+            tcp_congestion_control: SpinLock::new(DEFAULT_TCP_CONGESTION_CONTROL),
+            owner,
+            stashed_dentry: StashedDentry::new(),
+        })
+    }
+
+    /// Creates a new network namespace.
+    ///
+    /// The new network namespace contains only a new loopback interface.
+    pub fn new_clone(
+        &self,
+        owner: Arc<UserNamespace>,
+        posix_thread: &PosixThread,
+    ) -> Result<Arc<Self>> {
+        owner.check_cap(CapSet::SYS_ADMIN, posix_thread)?;
+
+        let loopback_iface = iface::new_loopback();
+        iface::spawn_background_poll_thread(loopback_iface.clone());
+
+        let net_ns = Self::new(loopback_iface.clone(), vec![loopback_iface], owner);
+
+        // Like Linux, the default congestion control algorithm is inherited from the initial
+        // network namespace.
+        let init_net_ns = Self::get_init_singleton();
+        net_ns.set_tcp_congestion_control(init_net_ns.tcp_congestion_control());
+
+        Ok(net_ns)
+    }
+
+    /// Creates a new network namespace without spawning the background polling thread.
+    #[cfg(ktest)]
+    pub(in crate::net) fn new_for_ktest() -> Arc<Self> {
+        let loopback_iface = iface::new_loopback();
+        let owner = UserNamespace::get_init_singleton().clone();
+        Self::new(loopback_iface.clone(), vec![loopback_iface], owner)
+    }
+
+    /// Returns the loopback interface of the namespace.
+    pub fn loopback_iface(&self) -> &Arc<Iface> {
+        &self.loopback_iface
+    }
+
+    /// Returns all the interfaces in the namespace, ordered by their indexes.
+    pub fn ifaces(&self) -> Vec<Arc<Iface>> {
+        self.ifaces.read().clone()
+    }
+
+    /// Adds a new interface to the namespace.
+    ///
+    /// The name of the interface is generated from `name_template`. If the template contains
+    /// `%d`, it will be replaced by the smallest number that makes the name unused. The interface
+    /// is then created by `new_iface` with the generated name.
+    ///
+    /// This method will fail with `EEXIST` if the name is already used by another interface.
+    pub(in crate::net) fn add_iface<F>(
+        &self,
+        name_template: &str,
+        new_iface: F,
+    ) -> Result<Arc<Iface>>
+    where
+        F: FnOnce(CString) -> Arc<Iface>,
+    {
+        let mut ifaces = self.ifaces.write();
+
+        let name = alloc_iface_name(&ifaces, name_template)?;
+        let iface = new_iface(name);
+        iface.set_packet_filter(Some(self.netfilter.clone()));
+        insert_iface(&mut ifaces, iface.clone());
+
+        Ok(iface)
+    }
+
+    /// Renames the interface.
+    ///
+    /// The new name is generated from `name_template` in the same way as [`Self::add_iface`].
+    pub(in crate::net) fn rename_iface(
+        &self,
+        iface: &Arc<Iface>,
+        name_template: &str,
+    ) -> Result<()> {
+        let ifaces = self.ifaces.write();
+
+        if name_template.as_bytes() == iface.name().to_bytes() {
+            return Ok(());
+        }
+
+        let name = alloc_iface_name(&ifaces, name_template)?;
+        iface.set_name(name);
+
+        Ok(())
+    }
+
+    /// Removes the interface from the namespace.
+    ///
+    /// Returns `false` if the interface is not in the namespace.
+    pub(in crate::net) fn remove_iface(&self, iface: &Arc<Iface>) -> bool {
+        let mut ifaces = self.ifaces.write();
+
+        let Some(pos) = ifaces.iter().position(|other| Arc::ptr_eq(other, iface)) else {
+            return false;
+        };
+        ifaces.remove(pos);
+        drop(ifaces);
+
+        self.remove_iface_routes(iface);
+
+        true
+    }
+
+    /// Moves the interface with the specified index to the target network namespace.
+    ///
+    /// The addresses and the routes of the interface are flushed.
+    ///
+    /// This method will fail with `EPERM` if the caller does not have the NET_ADMIN capability
+    /// in the owner user namespaces of both network namespaces, or with `EBUSY` if some sockets
+    /// are bound to the interface.
+    pub fn move_iface_to(
+        &self,
+        index: u32,
+        target: &Arc<NetNamespace>,
+        posix_thread: &PosixThread,
+    ) -> Result<()> {
+        self.owner.check_cap(CapSet::NET_ADMIN, posix_thread)?;
+        target.owner.check_cap(CapSet::NET_ADMIN, posix_thread)?;
+
+        if index == self.loopback_iface.index() {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the loopback interface cannot be moved to another network namespace"
+            );
+        }
+        if core::ptr::eq(self, target.as_ref()) {
+            return Ok(());
+        }
+        // Like the loopback interface, some other interfaces (e.g., bridges) are local to their
+        // network namespaces.
+        let iface = self
+            .ifaces
+            .read()
+            .iter()
+            .find(|iface| iface.index() == index)
+            .cloned();
+        if let Some(iface) = iface {
+            if iface::is_netns_local(&iface) {
+                return_errno_with_message!(
+                    Errno::EINVAL,
+                    "the interface cannot be moved to another network namespace"
+                );
+            }
+            // Check the name in advance, so the addresses are not flushed in vain. It is checked
+            // again below in case of races.
+            if target
+                .ifaces
+                .read()
+                .iter()
+                .any(|target_iface| target_iface.name() == iface.name())
+            {
+                return_errno_with_message!(
+                    Errno::EEXIST,
+                    "an interface with the same name exists in the target network namespace"
+                );
+            }
+        }
+
+        let (iface, ip_addrs) = {
+            let mut route_table = self.route_table.write();
+            let mut ifaces = self.ifaces.write();
+            let Some(pos) = ifaces.iter().position(|iface| iface.index() == index) else {
+                return_errno_with_message!(Errno::ENODEV, "the interface does not exist");
+            };
+            // The sockets bound to the interface belong to this network namespace. They should
+            // not be reachable via the interface after it is moved.
+            if ifaces[pos].has_bound_ports() {
+                return_errno_with_message!(Errno::EBUSY, "some sockets are bound to the interface");
+            }
+            let iface = ifaces.remove(pos);
+            drop(ifaces);
+
+            // Like Linux, the addresses and the routes are flushed. They are meaningful only in
+            // the old network namespace.
+            //
+            // Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/core/dev.c>
+            route_table.remove_all(|route| route.iface_index == iface.index());
+            iface.set_gateway_routes(&[]);
+            let ip_addrs = flush_ip_addrs(&iface);
+
+            (iface, ip_addrs)
+        };
+        for ip_cidr in ip_addrs.iter() {
+            netlink::notify_del_addr(self, &iface, ip_cidr);
+        }
+
+        // Two locks are never held at the same time to avoid deadlocks when two interfaces are
+        // moved in opposite directions concurrently.
+        let mut target_ifaces = target.ifaces.write();
+        if target_ifaces
+            .iter()
+            .any(|target_iface| target_iface.name() == iface.name())
+        {
+            drop(target_ifaces);
+            insert_iface(&mut self.ifaces.write(), iface);
+            return_errno_with_message!(
+                Errno::EEXIST,
+                "an interface with the same name exists in the target network namespace"
+            );
+        }
+        iface.set_packet_filter(Some(target.netfilter.clone()));
+        insert_iface(&mut target_ifaces, iface.clone());
+        drop(target_ifaces);
+
+        iface::notify_iface_moved(&iface, self, target);
+
+        Ok(())
+    }
+
+    /// Returns all the routes in the routing table.
+    pub fn routes(&self) -> Vec<Route> {
+        self.route_table.read().routes().to_vec()
+    }
+
+    /// Modifies the routing table with `f`.
+    ///
+    /// After the modification, the routes via gateways are pushed to the interfaces.
+    pub(in crate::net) fn modify_routes<F, R>(&self, f: F) -> Result<R>
+    where
+        F: FnOnce(&mut RouteTable) -> Result<R>,
+    {
+        let mut route_table = self.route_table.write();
+        let res = f(&mut route_table);
+
+        for iface in self.ifaces.read().iter() {
+            sync_gateway_routes(&route_table, iface);
+        }
+
+        res
+    }
+
+    /// Adds an IP address to the interface.
+    ///
+    /// If `add_prefix_route` is true, a prefix route for the address will also be added to the
+    /// routing table, so that the subnet of the address is directly reachable.
+    pub(in crate::net) fn add_iface_addr(
+        &self,
+        iface: &Arc<Iface>,
+        ip_cidr: IpCidr,
+        add_prefix_route: bool,
+    ) -> Result<()> {
+        let mut route_table = self.route_table.write();
+
+        match iface.add_ip_addr(ip_cidr) {
+            Ok(()) => (),
+            Err(AddrError::Exists) => {
+                return_errno_with_message!(Errno::EEXIST, "the address already exists")
+            }
+            Err(AddrError::TooMany) => {
+                return_errno_with_message!(Errno::ENOSPC, "the interface has too many addresses")
+            }
+            Err(AddrError::NotFound) => unreachable!(),
+        }
+
+        if add_prefix_route && iface.is_up() && !iface.flags().contains(InterfaceFlags::LOOPBACK) {
+            route_table.add_prefix_route(&ip_cidr, iface.index());
+        }
+
+        Ok(())
+    }
+
+    /// Removes an IP address from the interface.
+    ///
+    /// The routes that use the address as the preferred source address are also removed.
+    pub(in crate::net) fn remove_iface_addr(
+        &self,
+        iface: &Arc<Iface>,
+        ip_cidr: &IpCidr,
+    ) -> Result<()> {
+        let mut route_table = self.route_table.write();
+
+        if iface.remove_ip_addr(ip_cidr).is_err() {
+            return_errno_with_message!(Errno::EADDRNOTAVAIL, "the address does not exist");
+        }
+
+        let addr = ip_cidr.address();
+        route_table
+            .remove_all(|route| route.iface_index == iface.index() && route.pref_src == Some(addr));
+        sync_gateway_routes(&route_table, iface);
+
+        Ok(())
+    }
+
+    /// Brings the interface up or down.
+    ///
+    /// When the interface goes down, all its routes are removed. When the interface goes up, its
+    /// prefix routes are added back. This method returns whether the state has changed.
+    pub(in crate::net) fn set_iface_up(&self, iface: &Arc<Iface>, is_up: bool) -> bool {
+        let mut route_table = self.route_table.write();
+
+        if !iface.set_up(is_up) {
+            return false;
+        }
+
+        if is_up {
+            add_prefix_routes(&mut route_table, iface);
+        } else {
+            route_table.remove_all(|route| route.iface_index == iface.index());
+        }
+        sync_gateway_routes(&route_table, iface);
+
+        true
+    }
+
+    /// Selects the output interface and the source address to reach the destination.
+    ///
+    /// This method fails with `ENETUNREACH` if there is no route to the destination, or with
+    /// `EADDRNOTAVAIL` if no source address is available.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/ipv4/route.c> (`ip_route_output_key_hash_rcu`)
+    pub(in crate::net) fn route_output(&self, dst: &IpAddress) -> Result<(Arc<Iface>, IpAddress)> {
+        let route_table = self.route_table.read();
+        let ifaces = self.ifaces.read();
+
+        // Local addresses are reachable via the interfaces that own them.
+        if let Some(iface) = ifaces
+            .iter()
+            .find(|iface| iface.is_up() && iface.has_ip_addr(*dst))
+        {
+            return Ok((iface.clone(), *dst));
+        }
+
+        // The subnets of the loopback addresses (e.g., 127.0.0.0/8) are reachable via the
+        // loopback interface. In Linux, they are in the local routing table.
+        let loopback_iface = &self.loopback_iface;
+        if loopback_iface.is_up()
+            && loopback_iface
+                .ip_addrs()
+                .iter()
+                .any(|ip_cidr| ip_cidr.contains_addr(dst))
+        {
+            let src_addr = select_src_addr(&ifaces, loopback_iface, dst, None)?;
+            return Ok((loopback_iface.clone(), src_addr));
+        }
+
+        let Some((route, iface)) = route_table.lookup(dst, |route| {
+            ifaces
+                .iter()
+                .find(|iface| iface.index() == route.iface_index && iface.is_up())
+        }) else {
+            return_errno_with_message!(Errno::ENETUNREACH, "no route to the destination");
+        };
+
+        let src_addr = select_src_addr(&ifaces, iface, dst, Some(route))?;
+        Ok((iface.clone(), src_addr))
+    }
+
+    fn remove_iface_routes(&self, iface: &Arc<Iface>) {
+        let mut route_table = self.route_table.write();
+        route_table.remove_all(|route| route.iface_index == iface.index());
+        iface.set_gateway_routes(&[]);
+    }
+
+    /// Checks whether the thread has the required capability in the owner user namespace.
+    pub fn check_cap(&self, required: CapSet, posix_thread: &PosixThread) -> Result<()> {
+        self.owner.check_cap(required, posix_thread)
+    }
+
+    /// Returns the range of groups that are allowed to create ping sockets.
+    ///
+    /// The range is inclusive. If the lower bound is greater than the upper bound, no groups are
+    /// allowed.
+    pub fn ping_group_range(&self) -> (Gid, Gid) {
+        *self.ping_group_range.lock()
+    }
+
+    /// Sets the range of groups that are allowed to create ping sockets.
+    ///
+    /// Like Linux, if the lower bound is greater than the upper bound, the range is reset to the
+    /// default empty range.
+    pub fn set_ping_group_range(&self, low: Gid, high: Gid) {
+        let range = if low <= high {
+            (low, high)
+        } else {
+            DEFAULT_PING_GROUP_RANGE
+        };
+        *self.ping_group_range.lock() = range;
+    }
+
+    /// Returns the default congestion control algorithm of new TCP sockets.
+    pub fn tcp_congestion_control(&self) -> CongestionControl {
+        *self.tcp_congestion_control.lock()
+    }
+
+    /// Sets the default congestion control algorithm of new TCP sockets.
+    pub fn set_tcp_congestion_control(&self, control: CongestionControl) {
+        *self.tcp_congestion_control.lock() = control;
+    }
+
+    /// Returns the packet filter of the namespace.
+    pub(in crate::net) fn netfilter(&self) -> &Arc<Netfilter> {
+        &self.netfilter
+    }
+
+    /// Returns the netlink sockets bound in the namespace.
+    pub(in crate::net) fn netlink_socket_table(&self) -> &NetlinkSocketTable {
+        &self.netlink_socket_table
+    }
+}
+
+/// The default range of groups that are allowed to create ping sockets, which is empty.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/ipv4/sysctl_net_ipv4.c>
+const DEFAULT_PING_GROUP_RANGE: (Gid, Gid) = (Gid::new(1), Gid::new(0));
+
+/// The default congestion control algorithm of new TCP sockets.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/ipv4/Kconfig>
+const DEFAULT_TCP_CONGESTION_CONTROL: CongestionControl = CongestionControl::Cubic;
+
+/// Generates an unused interface name from the template.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/core/dev.c> (`__dev_alloc_name`)
+fn alloc_iface_name(ifaces: &[Arc<Iface>], name_template: &str) -> Result<CString> {
+    /// The maximum number that can replace `%d` in the template.
+    const MAX_NAME_NUM: usize = 32768;
+
+    let is_valid_name = |name: &str| {
+        !name.is_empty()
+            && name.len() < IFNAMSIZ
+            && name != "."
+            && name != ".."
+            && !name
+                .chars()
+                .any(|c| c == '/' || c == ':' || c.is_whitespace())
+    };
+    let is_used_name = |name: &str| {
+        ifaces
+            .iter()
+            .any(|iface| iface.name().to_bytes() == name.as_bytes())
+    };
+
+    let Some((prefix, suffix)) = name_template.split_once("%d") else {
+        if !is_valid_name(name_template) || name_template.contains('%') {
+            return_errno_with_message!(Errno::EINVAL, "the interface name is invalid");
+        }
+        if is_used_name(name_template) {
+            return_errno_with_message!(Errno::EEXIST, "the interface name is already used");
+        }
+        return Ok(CString::new(name_template).unwrap());
+    };
+    if prefix.contains('%') || suffix.contains('%') {
+        return_errno_with_message!(Errno::EINVAL, "the interface name template is invalid");
+    }
+
+    for num in 0..MAX_NAME_NUM {
+        let name = format!("{}{}{}", prefix, num, suffix);
+        if !is_valid_name(&name) {
+            return_errno_with_message!(Errno::EINVAL, "the interface name is invalid");
+        }
+        if !is_used_name(&name) {
+            return Ok(CString::new(name).unwrap());
+        }
+    }
+
+    return_errno_with_message!(Errno::ENFILE, "no interface name is available");
+}
+
+/// Adds the prefix routes for all the addresses of the interface if the interface is up.
+fn add_prefix_routes(route_table: &mut RouteTable, iface: &Arc<Iface>) {
+    if !iface.is_up() || iface.flags().contains(InterfaceFlags::LOOPBACK) {
+        return;
+    }
+
+    for ip_cidr in iface.ip_addrs().iter() {
+        route_table.add_prefix_route(ip_cidr, iface.index());
+    }
+}
+
+/// Removes all the addresses of the interface and returns them.
+fn flush_ip_addrs(iface: &Arc<Iface>) -> Arc<[IpCidr]> {
+    let ip_addrs = iface.ip_addrs();
+    for ip_cidr in ip_addrs.iter() {
+        let _ = iface.remove_ip_addr(ip_cidr);
+    }
+    ip_addrs
+}
+
+/// Pushes the routes of the interface that go through gateways to the interface.
+fn sync_gateway_routes(route_table: &RouteTable, iface: &Arc<Iface>) {
+    let gateway_routes: Vec<_> = route_table
+        .routes()
+        .iter()
+        .filter(|route| route.iface_index == iface.index())
+        .filter_map(|route| Some((route.dst, route.gateway?)))
+        .collect();
+    iface.set_gateway_routes(&gateway_routes);
+}
+
+/// Selects the source address to reach the destination via the interface.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/ipv4/devinet.c> (`inet_select_addr`)
+fn select_src_addr(
+    ifaces: &[Arc<Iface>],
+    iface: &Arc<Iface>,
+    dst: &IpAddress,
+    route: Option<&Route>,
+) -> Result<IpAddress> {
+    if let Some(pref_src) = route.and_then(|route| route.pref_src) {
+        return Ok(pref_src);
+    }
+
+    let next_hop = route.and_then(|route| route.gateway).unwrap_or(*dst);
+    let is_same_family = |ip_cidr: &&IpCidr| {
+        matches!(
+            (ip_cidr, dst),
+            (IpCidr::Ipv4(_), IpAddress::Ipv4(_)) | (IpCidr::Ipv6(_), IpAddress::Ipv6(_))
+        )
+    };
+
+    // Link-local IPv6 addresses are only preferred for link-local destinations.
+    let is_narrower_scope = |ip_cidr: &&IpCidr| match (ip_cidr, dst) {
+        (IpCidr::Ipv6(ipv6_cidr), IpAddress::Ipv6(dst)) => {
+            ipv6_cidr.address().is_unicast_link_local() && !dst.is_unicast_link_local()
+        }
+        _ => false,
+    };
+
+    // Prefer the addresses whose subnets contain the next hop.
+    if let Some(ip_cidr) = iface
+        .ip_addrs()
+        .iter()
+        .filter(is_same_family)
+        .min_by_key(|ip_cidr| {
+            (
+                is_narrower_scope(ip_cidr),
+                !ip_cidr.contains_addr(&next_hop),
+            )
+        })
+    {
+        return Ok(ip_cidr.address());
+    }
+
+    // Like Linux, fall back to the addresses of other interfaces, excluding the loopback ones.
+    if let Some(ip_cidr) = ifaces
+        .iter()
+        .filter(|other| !other.flags().contains(InterfaceFlags::LOOPBACK))
+        .find_map(|other| other.ip_addrs().iter().find(is_same_family).copied())
+    {
+        return Ok(ip_cidr.address());
+    }
+
+    return_errno_with_message!(
+        Errno::EADDRNOTAVAIL,
+        "no address is available to reach the destination"
+    );
+}
+
+/// Inserts the interface into the list while keeping the list ordered by the indexes.
+fn insert_iface(ifaces: &mut Vec<Arc<Iface>>, iface: Arc<Iface>) {
+    let pos = ifaces.partition_point(|other| other.index() < iface.index());
+    ifaces.insert(pos, iface);
+}
+
+impl Drop for NetNamespace {
+    fn drop(&mut self) {
+        // The loopback interface is kept alive by its background polling thread until the TCP
+        // connections that are still being closed (e.g., in the TIME-WAIT state) are released.
+        self.loopback_iface.sched_poll().stop();
+
+        let ifaces: Vec<_> = self
+            .ifaces
+            .write()
+            .drain(..)
+            .filter(|iface| !Arc::ptr_eq(iface, &self.loopback_iface))
+            .collect();
+        if ifaces.is_empty() {
+            return;
+        }
+
+        // Like Linux, the virtual interfaces are deleted, and the other interfaces are moved back
+        // to the initial network namespace.
+        //
+        // Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/core/dev.c>
+        let (moved_ifaces, deleted_ifaces) = iface::delete_netns_links(ifaces);
+        for (iface, net_ns) in deleted_ifaces.iter() {
+            netlink::notify_del_link(net_ns, iface);
+        }
+        if moved_ifaces.is_empty() {
+            return;
+        }
+
+        let init_ns = Self::get_init_singleton();
+        let mut init_ifaces = init_ns.ifaces.write();
+        for iface in moved_ifaces.iter() {
+            // If the name is used, the interface is renamed to `dev<index>`, or to `dev%d` if
+            // that name is used as well.
+            if init_ifaces.iter().any(|other| other.name() == iface.name()) {
+                let name = alloc_iface_name(&init_ifaces, &format!("dev{}", iface.index()))
+                    .or_else(|_| alloc_iface_name(&init_ifaces, "dev%d"));
+                match name {
+                    Ok(name) => iface.set_name(name),
+                    Err(_) => warn!(
+                        "failed to rename the interface moved back to the initial network namespace"
+                    ),
+                }
+            }
+            iface.set_packet_filter(Some(init_ns.netfilter.clone()));
+            iface.set_gateway_routes(&[]);
+            flush_ip_addrs(iface);
+            insert_iface(&mut init_ifaces, iface.clone());
+        }
+        drop(init_ifaces);
+
+        for iface in moved_ifaces.iter() {
+            iface::notify_iface_moved(iface, self, init_ns);
+        }
+    }
+}
+
+impl NsCommonOps for NetNamespace {
+    const TYPE: NsType = NsType::Net;
+
+    fn owner_user_ns(&self) -> Option<&Arc<UserNamespace>> {
+        Some(&self.owner)
+    }
+
+    fn parent(&self) -> Result<&Arc<Self>> {
+        return_errno_with_message!(
+            Errno::EINVAL,
+            "a network namespace does not have a parent namespace"
+        );
+    }
+
+    fn stashed_dentry(&self) -> &StashedDentry {
+        &self.stashed_dentry
+    }
+}
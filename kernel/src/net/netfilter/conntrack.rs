@@ -0,0 +1,635 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Connection tracking.
+//!
+//! Each tracked connection has two tuples, one for each direction. The tuple of the reply
+//! direction is the inverse of the tuple of the original direction, unless the connection is
+//! translated by NAT (see [`super::nat`]).
+//!
+//! The packets sent to local addresses may be looped back by the device (e.g., the packets sent
+//! to 127.0.0.2). Such packets are passed to the filter twice, first when they are transmitted
+//! and again when they are received, and they have already been translated when they are received.
+//! So the translated tuples are also recorded, so that the second lookup finds the same connection.
+
+use core::time::Duration;
+
+use aster_softirq::BottomHalfDisabled;
+use ostd::timer::Jiffies;
+
+use super::packet::{IPPROTO_ICMP, IPPROTO_ICMPV6, IPPROTO_TCP, IPPROTO_UDP, IpVersion, Packet};
+use crate::prelude::*;
+
+/// The maximum number of tracked connections in a network namespace.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/netfilter/nf_conntrack_core.c>
+const MAX_CONNS: usize = 65536;
+
+/// The interval between two scans for expired connections.
+const GC_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Timeouts of tracked connections.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/netfilter/nf_conntrack_proto_tcp.c>
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/netfilter/nf_conntrack_proto_udp.c>
+const TCP_TIMEOUT_ESTABLISHED: Duration = Duration::from_secs(5 * 24 * 60 * 60);
+const TCP_TIMEOUT_UNREPLIED: Duration = Duration::from_secs(120);
+const TCP_TIMEOUT_CLOSING: Duration = Duration::from_secs(120);
+const TCP_TIMEOUT_CLOSE: Duration = Duration::from_secs(10);
+const UDP_TIMEOUT_UNREPLIED: Duration = Duration::from_secs(30);
+const UDP_TIMEOUT_REPLIED: Duration = Duration::from_secs(120);
+const ICMP_TIMEOUT: Duration = Duration::from_secs(30);
+const GENERIC_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// The direction of a packet in a connection.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/netfilter/nf_conntrack_tuple_common.h>
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Original = 0,
+    Reply = 1,
+}
+
+impl Direction {
+    pub(super) fn inverse(self) -> Self {
+        match self {
+            Self::Original => Self::Reply,
+            Self::Reply => Self::Original,
+        }
+    }
+}
+
+bitflags! {
+    /// The status of a tracked connection.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/netfilter/nf_conntrack_common.h>
+    pub struct CtStatus: u32 {
+        const EXPECTED = 1 << 0;
+        const SEEN_REPLY = 1 << 1;
+        const ASSURED = 1 << 2;
+        const CONFIRMED = 1 << 3;
+        const SRC_NAT = 1 << 4;
+        const DST_NAT = 1 << 5;
+        const SRC_NAT_DONE = 1 << 7;
+        const DST_NAT_DONE = 1 << 8;
+    }
+}
+
+bitflags! {
+    /// The state of a packet with respect to its connection, as matched by `ct state`.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/netfilter/nf_conntrack_common.h>
+    pub struct CtStateBits: u32 {
+        const INVALID = 1 << 0;
+        const ESTABLISHED = 1 << 1;
+        const RELATED = 1 << 2;
+        const NEW = 1 << 3;
+        const UNTRACKED = 1 << 6;
+    }
+}
+
+/// The tuple that identifies a direction of a connection.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Tuple {
+    pub version: IpVersion,
+    /// The source address. IPv4 addresses only occupy the first 4 bytes.
+    pub src: [u8; 16],
+    /// The destination address. IPv4 addresses only occupy the first 4 bytes.
+    pub dst: [u8; 16],
+    pub proto: u8,
+    /// The source port, or the identifier of ICMP echo messages.
+    pub src_id: u16,
+    /// The destination port, or the type and the code of ICMP echo messages.
+    pub dst_id: u16,
+}
+
+impl Tuple {
+    /// Extracts the tuple from a packet.
+    fn from_packet(pkt: &Packet) -> Result<Self, Untracked> {
+        let mut tuple = Self {
+            version: pkt.version(),
+            src: [0; 16],
+            dst: [0; 16],
+            proto: pkt.l4_proto(),
+            src_id: 0,
+            dst_id: 0,
+        };
+        let addr_len = pkt.version().addr_len();
+        tuple.src[..addr_len].copy_from_slice(pkt.src_addr());
+        tuple.dst[..addr_len].copy_from_slice(pkt.dst_addr());
+
+        match tuple.proto {
+            IPPROTO_TCP | IPPROTO_UDP => {
+                // TODO: Support IP fragments, which are reassembled before being tracked in
+                // Linux.
+                let (src_port, dst_port) = pkt.ports().ok_or(Untracked::Invalid)?;
+                tuple.src_id = src_port;
+                tuple.dst_id = dst_port;
+            }
+            IPPROTO_ICMP | IPPROTO_ICMPV6 => {
+                let (type_, code, id) = pkt.icmp_header().ok_or(Untracked::Invalid)?;
+                match icmp_kind(pkt.version(), type_) {
+                    IcmpKind::Request | IcmpKind::Reply => {
+                        tuple.src_id = id;
+                        tuple.dst_id = u16::from_be_bytes([type_, code]);
+                    }
+                    IcmpKind::Error => return Err(Untracked::IcmpError),
+                    IcmpKind::Untracked => return Err(Untracked::Untracked),
+                    IcmpKind::Other => return Err(Untracked::Invalid),
+                }
+            }
+            _ => (),
+        }
+
+        Ok(tuple)
+    }
+
+    /// Returns the source address, with its actual length.
+    pub fn src_addr(&self) -> &[u8] {
+        &self.src[..self.version.addr_len()]
+    }
+
+    /// Returns the destination address, with its actual length.
+    pub fn dst_addr(&self) -> &[u8] {
+        &self.dst[..self.version.addr_len()]
+    }
+
+    /// Returns the tuple of the opposite direction.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/netfilter/nf_conntrack_core.c>
+    pub fn invert(&self) -> Self {
+        let (src_id, dst_id) = if matches!(self.proto, IPPROTO_ICMP | IPPROTO_ICMPV6) {
+            let [type_, code] = self.dst_id.to_be_bytes();
+            let type_ = invert_icmp_type(self.version, type_).unwrap_or(type_);
+            (self.src_id, u16::from_be_bytes([type_, code]))
+        } else {
+            (self.dst_id, self.src_id)
+        };
+
+        Self {
+            version: self.version,
+            src: self.dst,
+            dst: self.src,
+            proto: self.proto,
+            src_id,
+            dst_id,
+        }
+    }
+}
+
+/// The reason why a packet is not associated with a connection.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Untracked {
+    /// The packet cannot be tracked (e.g., a malformed packet).
+    Invalid,
+    /// The packet does not need to be tracked (e.g., an IPv6 neighbor discovery message).
+    Untracked,
+    /// The packet is an ICMP error message, which is related to the connection of the packet
+    /// that it embeds.
+    IcmpError,
+}
+
+enum IcmpKind {
+    Request,
+    Reply,
+    Error,
+    Untracked,
+    Other,
+}
+
+/// Classifies the ICMP or ICMPv6 message type.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/netfilter/nf_conntrack_proto_icmp.c>
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/netfilter/nf_conntrack_proto_icmpv6.c>
+fn icmp_kind(version: IpVersion, type_: u8) -> IcmpKind {
+    match (version, type_) {
+        // Echo, timestamp, information, and address mask requests
+        (IpVersion::V4, 8 | 13 | 15 | 17) | (IpVersion::V6, 128) => IcmpKind::Request,
+        (IpVersion::V4, 0 | 14 | 16 | 18) | (IpVersion::V6, 129) => IcmpKind::Reply,
+        // Destination unreachable, source quench, redirect, time exceeded, and parameter problem
+        (IpVersion::V4, 3 | 4 | 5 | 11 | 12) | (IpVersion::V6, 1..=4) => IcmpKind::Error,
+        // Multicast listener and neighbor discovery messages
+        (IpVersion::V6, 130..=137 | 143) => IcmpKind::Untracked,
+        _ => IcmpKind::Other,
+    }
+}
+
+fn invert_icmp_type(version: IpVersion, type_: u8) -> Option<u8> {
+    let inverted = match (version, type_) {
+        (IpVersion::V4, 8) => 0,
+        (IpVersion::V4, 0) => 8,
+        (IpVersion::V4, 13) => 14,
+        (IpVersion::V4, 14) => 13,
+        (IpVersion::V4, 15) => 16,
+        (IpVersion::V4, 16) => 15,
+        (IpVersion::V4, 17) => 18,
+        (IpVersion::V4, 18) => 17,
+        (IpVersion::V6, 128) => 129,
+        (IpVersion::V6, 129) => 128,
+        _ => return None,
+    };
+    Some(inverted)
+}
+
+/// A tracked connection.
+#[derive(Clone, Debug)]
+pub struct Conn {
+    /// The tuples of the original direction and the reply direction.
+    tuples: [Tuple; 2],
+    pub(super) status: CtStatus,
+    tcp: TcpState,
+    expires: Duration,
+}
+
+/// The simplified state of a TCP connection.
+///
+/// Unlike Linux, the sequence numbers are not tracked.
+#[derive(Clone, Copy, Debug, Default)]
+struct TcpState {
+    fin_seen: [bool; 2],
+    rst_seen: bool,
+}
+
+impl TcpState {
+    fn is_closing(&self) -> bool {
+        self.rst_seen || self.fin_seen.iter().any(|seen| *seen)
+    }
+}
+
+const TCP_FLAG_FIN: u8 = 0x01;
+const TCP_FLAG_SYN: u8 = 0x02;
+const TCP_FLAG_RST: u8 = 0x04;
+const TCP_FLAG_ACK: u8 = 0x10;
+
+impl Conn {
+    fn new(tuple: Tuple) -> Self {
+        Self {
+            tuples: [tuple, tuple.invert()],
+            status: CtStatus::empty(),
+            tcp: TcpState::default(),
+            expires: Duration::ZERO,
+        }
+    }
+
+    /// Returns the tuple of the direction.
+    pub fn tuple(&self, dir: Direction) -> &Tuple {
+        &self.tuples[dir as usize]
+    }
+
+    pub(super) fn set_reply_tuple(&mut self, tuple: Tuple) {
+        self.tuples[Direction::Reply as usize] = tuple;
+    }
+
+    /// Returns the tuples under which the connection is indexed.
+    fn keys(&self) -> [(Tuple, Direction); 4] {
+        let [orig, reply] = self.tuples;
+        [
+            (orig, Direction::Original),
+            (reply, Direction::Reply),
+            (reply.invert(), Direction::Original),
+            (orig.invert(), Direction::Reply),
+        ]
+    }
+
+    /// Updates the state of the connection after seeing a packet.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/netfilter/nf_conntrack_proto_tcp.c>
+    fn update(&mut self, pkt: &Packet, dir: Direction, now: Duration) {
+        if dir == Direction::Reply {
+            self.status |= CtStatus::SEEN_REPLY;
+        } else if self.status.contains(CtStatus::SEEN_REPLY) {
+            self.status |= CtStatus::ASSURED;
+        }
+
+        let timeout = match self.tuples[0].proto {
+            IPPROTO_TCP => {
+                let flags = tcp_flags(pkt);
+                if flags & TCP_FLAG_RST != 0 {
+                    self.tcp.rst_seen = true;
+                }
+                if flags & TCP_FLAG_FIN != 0 {
+                    self.tcp.fin_seen[dir as usize] = true;
+                }
+
+                if self.tcp.rst_seen {
+                    TCP_TIMEOUT_CLOSE
+                } else if self.tcp.is_closing() {
+                    TCP_TIMEOUT_CLOSING
+                } else if self.status.contains(CtStatus::SEEN_REPLY) {
+                    TCP_TIMEOUT_ESTABLISHED
+                } else {
+                    TCP_TIMEOUT_UNREPLIED
+                }
+            }
+            IPPROTO_UDP if self.status.contains(CtStatus::SEEN_REPLY) => UDP_TIMEOUT_REPLIED,
+            IPPROTO_UDP => UDP_TIMEOUT_UNREPLIED,
+            IPPROTO_ICMP | IPPROTO_ICMPV6 => ICMP_TIMEOUT,
+            _ => GENERIC_TIMEOUT,
+        };
+        self.expires = now + timeout;
+    }
+}
+
+fn tcp_flags(pkt: &Packet) -> u8 {
+    pkt.transport_header()
+        .and_then(|header| header.get(13))
+        .copied()
+        .unwrap_or(0)
+}
+
+/// The connection tracking information of a packet.
+#[derive(Clone, Debug)]
+pub enum PacketCt {
+    /// The packet is not tracked because it is invalid.
+    Invalid,
+    /// The packet is intentionally not tracked.
+    Untracked,
+    /// The packet belongs to or is related to a connection.
+    Tracked(TrackedCt),
+}
+
+/// The connection that a packet belongs to or is related to.
+#[derive(Clone, Debug)]
+pub struct TrackedCt {
+    /// The ID of the connection, or `None` if the connection is new and not confirmed yet.
+    id: Option<u64>,
+    pub(super) conn: Conn,
+    pub(super) dir: Direction,
+    pub(super) is_related: bool,
+    /// Whether the connection is modified (e.g., a NAT binding is set up) and should be written
+    /// back.
+    pub(super) is_dirty: bool,
+}
+
+impl PacketCt {
+    /// Returns the bits of the state, as matched by `ct state`.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/netfilter/nft_ct.c>
+    pub fn state_bits(&self) -> CtStateBits {
+        match self {
+            Self::Invalid => CtStateBits::INVALID,
+            Self::Untracked => CtStateBits::UNTRACKED,
+            Self::Tracked(ct) if ct.is_related => CtStateBits::RELATED,
+            Self::Tracked(ct) if ct.is_new() => CtStateBits::NEW,
+            Self::Tracked(_) => CtStateBits::ESTABLISHED,
+        }
+    }
+
+    pub fn tracked(&self) -> Option<&TrackedCt> {
+        match self {
+            Self::Tracked(ct) => Some(ct),
+            _ => None,
+        }
+    }
+}
+
+impl TrackedCt {
+    /// Returns whether the packet starts a new connection.
+    ///
+    /// Like Linux, the connection is considered new until a reply is seen.
+    pub(super) fn is_new(&self) -> bool {
+        !self.is_related
+            && self.dir == Direction::Original
+            && !self.conn.status.contains(CtStatus::SEEN_REPLY)
+    }
+
+    pub fn conn(&self) -> &Conn {
+        &self.conn
+    }
+
+    pub fn dir(&self) -> Direction {
+        self.dir
+    }
+}
+
+/// The table of tracked connections in a network namespace.
+pub(super) struct ConnTrack {
+    table: SpinLock<ConnTable, BottomHalfDisabled>,
+}
+
+struct ConnTable {
+    conns: BTreeMap<u64, Conn>,
+    /// The connections and the directions indexed by the tuples.
+    index: BTreeMap<Tuple, (u64, Direction)>,
+    next_id: u64,
+    next_gc: Duration,
+}
+
+impl ConnTrack {
+    pub(super) fn new() -> Self {
+        Self {
+            table: SpinLock::new(ConnTable {
+                conns: BTreeMap::new(),
+                index: BTreeMap::new(),
+                next_id: 1,
+                next_gc: Duration::ZERO,
+            }),
+        }
+    }
+
+    /// Finds the connection of a packet, or starts tracking a new connection.
+    ///
+    /// The state of the found connection is updated. A new connection is not recorded until it
+    /// is confirmed by [`Self::confirm`].
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/netfilter/nf_conntrack_core.c>
+    pub(super) fn resolve(&self, pkt: &Packet) -> PacketCt {
+        let now = Jiffies::elapsed().as_duration();
+
+        let tuple = match Tuple::from_packet(pkt) {
+            Ok(tuple) => tuple,
+            Err(Untracked::Invalid) => return PacketCt::Invalid,
+            Err(Untracked::Untracked) => return PacketCt::Untracked,
+            Err(Untracked::IcmpError) => return self.resolve_icmp_error(pkt, now),
+        };
+
+        let mut table = self.table.lock();
+        table.gc_if_needed(now);
+
+        if let Some((id, dir)) = table.find(&tuple, now) {
+            let conn = table.conns.get_mut(&id).unwrap();
+
+            // Like Linux, a SYN in the original direction reopens a closing TCP connection.
+            let flags = tcp_flags(pkt);
+            if tuple.proto == IPPROTO_TCP
+                && dir == Direction::Original
+                && conn.tcp.is_closing()
+                && flags & (TCP_FLAG_SYN | TCP_FLAG_ACK) == TCP_FLAG_SYN
+            {
+                table.remove(id);
+            } else {
+                conn.update(pkt, dir, now);
+                return PacketCt::Tracked(TrackedCt {
+                    id: Some(id),
+                    conn: conn.clone(),
+                    dir,
+                    is_related: false,
+                    is_dirty: false,
+                });
+            }
+        }
+        drop(table);
+
+        // Like Linux, TCP connections can be picked up in the middle, but not by RST packets.
+        // ICMP connections can only be started by requests.
+        let can_start = match tuple.proto {
+            IPPROTO_TCP => tcp_flags(pkt) & TCP_FLAG_RST == 0,
+            IPPROTO_ICMP | IPPROTO_ICMPV6 => {
+                let [type_, _] = tuple.dst_id.to_be_bytes();
+                matches!(icmp_kind(tuple.version, type_), IcmpKind::Request)
+            }
+            _ => true,
+        };
+        if !can_start {
+            return PacketCt::Invalid;
+        }
+
+        let mut conn = Conn::new(tuple);
+        conn.update(pkt, Direction::Original, now);
+        PacketCt::Tracked(TrackedCt {
+            id: None,
+            conn,
+            dir: Direction::Original,
+            is_related: false,
+            is_dirty: false,
+        })
+    }
+
+    /// Finds the connection that an ICMP error message is related to.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/netfilter/nf_conntrack_proto_icmp.c>
+    fn resolve_icmp_error(&self, pkt: &Packet, now: Duration) -> PacketCt {
+        let Some(inner) = pkt.icmp_inner_packet() else {
+            return PacketCt::Invalid;
+        };
+        let mut inner = inner.to_vec();
+        let Some(inner_pkt) = Packet::parse(&mut inner) else {
+            return PacketCt::Invalid;
+        };
+        if inner_pkt.version() != pkt.version() {
+            return PacketCt::Invalid;
+        }
+        let Ok(inner_tuple) = Tuple::from_packet(&inner_pkt) else {
+            return PacketCt::Invalid;
+        };
+
+        // The embedded packet was sent in the opposite direction of the error message.
+        let mut table = self.table.lock();
+        let Some((id, dir)) = table.find(&inner_tuple.invert(), now) else {
+            return PacketCt::Invalid;
+        };
+
+        PacketCt::Tracked(TrackedCt {
+            id: Some(id),
+            conn: table.conns[&id].clone(),
+            dir,
+            is_related: true,
+            is_dirty: false,
+        })
+    }
+
+    /// Returns whether a tuple is used by a connection other than `ct`.
+    pub(super) fn is_taken(&self, tuple: &Tuple, ct: &TrackedCt) -> bool {
+        let now = Jiffies::elapsed().as_duration();
+        let mut table = self.table.lock();
+        table
+            .find(tuple, now)
+            .is_some_and(|(id, _)| Some(id) != ct.id)
+    }
+
+    /// Confirms the connection of an accepted packet.
+    ///
+    /// A new connection is recorded, and the changes of an existing connection are written back.
+    /// Returns `false` if the new connection cannot be recorded because the table is full.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/netfilter/nf_conntrack_core.c>
+    pub(super) fn confirm(&self, ct: TrackedCt) -> bool {
+        let mut table = self.table.lock();
+
+        let Some(id) = ct.id else {
+            // Another packet of the same connection may have been confirmed concurrently.
+            if table.index.contains_key(ct.conn.tuple(Direction::Original)) {
+                return true;
+            }
+            if table.conns.len() >= MAX_CONNS {
+                table.gc(Jiffies::elapsed().as_duration());
+                if table.conns.len() >= MAX_CONNS {
+                    warn!("the connection tracking table is full, dropping the packet");
+                    return false;
+                }
+            }
+
+            let mut conn = ct.conn;
+            conn.status |= CtStatus::CONFIRMED;
+            table.insert(conn);
+            return true;
+        };
+
+        if !ct.is_dirty || !table.conns.contains_key(&id) {
+            return true;
+        }
+
+        // The connection is still recorded, so only the tuples and the status need to be
+        // written back. The other states are updated in place.
+        let mut conn = table.remove(id).unwrap();
+        conn.tuples = ct.conn.tuples;
+        conn.status |= ct.conn.status;
+        table.insert_with_id(id, conn);
+        true
+    }
+}
+
+impl ConnTable {
+    fn find(&mut self, tuple: &Tuple, now: Duration) -> Option<(u64, Direction)> {
+        let (id, dir) = *self.index.get(tuple)?;
+        if self.conns[&id].expires <= now {
+            self.remove(id);
+            return None;
+        }
+        Some((id, dir))
+    }
+
+    fn insert(&mut self, conn: Conn) {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.insert_with_id(id, conn);
+    }
+
+    fn insert_with_id(&mut self, id: u64, conn: Conn) {
+        for (key, dir) in conn.keys() {
+            self.index.entry(key).or_insert((id, dir));
+        }
+        self.conns.insert(id, conn);
+    }
+
+    fn remove(&mut self, id: u64) -> Option<Conn> {
+        let conn = self.conns.remove(&id)?;
+        for (key, _) in conn.keys() {
+            if self.index.get(&key).is_some_and(|(other, _)| *other == id) {
+                self.index.remove(&key);
+            }
+        }
+        Some(conn)
+    }
+
+    fn gc_if_needed(&mut self, now: Duration) {
+        if now >= self.next_gc {
+            self.gc(now);
+        }
+    }
+
+    /// Removes the expired connections.
+    fn gc(&mut self, now: Duration) {
+        let expired: Vec<_> = self
+            .conns
+            .iter()
+            .filter(|(_, conn)| conn.expires <= now)
+            .map(|(id, _)| *id)
+            .collect();
+        for id in expired {
+            self.remove(id);
+        }
+
+        self.next_gc = now + GC_INTERVAL;
+    }
+}
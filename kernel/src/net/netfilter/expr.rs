@@ -0,0 +1,708 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Expressions of nftables rules.
+//!
+//! A rule is a list of expressions that are evaluated in order. Expressions exchange data via
+//! registers: some expressions load data from the packet into registers (e.g., [`Expr::Payload`]),
+//! and others compare the data in registers (e.g., [`Expr::Cmp`]). An expression can also set the
+//! verdict, which stops the evaluation of the rule.
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use aster_bigtcp::{
+    iface::{FilterHook, FilterIface},
+    wire::IpAddress,
+};
+
+use super::{
+    conntrack::{Direction, PacketCt},
+    nat::NatRange,
+    packet::{IpVersion, NFPROTO_INET, NFPROTO_IPV4, Packet},
+};
+use crate::{net::iface::IFNAMSIZ, prelude::*};
+
+/// The size of the data registers in bytes.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/net/netfilter/nf_tables.h>
+const REGS_SIZE: usize = 80;
+
+/// The size of a 32-bit register in bytes.
+const REG32_SIZE: usize = 4;
+
+/// A data register.
+///
+/// User space refers to the registers either as 128-bit registers (`NFT_REG_1` to `NFT_REG_4`)
+/// or as 32-bit registers (`NFT_REG32_00` to `NFT_REG32_15`), which overlap with each other.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Register {
+    /// The index of the first 32-bit slot.
+    slot: usize,
+}
+
+impl Register {
+    const NFT_REG_1: u32 = 1;
+    const NFT_REG_4: u32 = 4;
+    const NFT_REG32_00: u32 = 8;
+    const NFT_REG32_15: u32 = 23;
+
+    /// Parses the register number given by user space.
+    ///
+    /// `len` is the length of the data that is loaded from or stored to the register, which
+    /// must fit in the registers.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/netfilter/nf_tables_api.c>
+    pub fn new(raw: u32, len: usize) -> Result<Self> {
+        let slot = match raw {
+            Self::NFT_REG_1..=Self::NFT_REG_4 => raw as usize * 4,
+            Self::NFT_REG32_00..=Self::NFT_REG32_15 => raw as usize - 4,
+            _ => return_errno_with_message!(Errno::EINVAL, "the register is invalid"),
+        };
+
+        if len == 0 {
+            return_errno_with_message!(Errno::EINVAL, "the data length is zero");
+        }
+        if slot * REG32_SIZE + len > REGS_SIZE {
+            return_errno_with_message!(Errno::ERANGE, "the data does not fit in the registers");
+        }
+
+        Ok(Self { slot })
+    }
+
+    /// Returns the register number that is reported to user space.
+    pub fn to_raw(self) -> u32 {
+        if self.slot % 4 == 0 {
+            (self.slot / 4) as u32
+        } else {
+            self.slot as u32 + 4
+        }
+    }
+
+    fn offset(self) -> usize {
+        self.slot * REG32_SIZE
+    }
+}
+
+/// A verdict, which decides how the evaluation continues.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/netfilter/nf_tables.h>
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    /// Continues with the next expression.
+    Continue,
+    /// Stops evaluating the current rule and continues with the next rule.
+    Break,
+    /// Accepts the packet at the current hook.
+    Accept,
+    /// Drops the packet.
+    Drop,
+    /// Continues with the chain, and returns to the next rule after the chain ends.
+    Jump(String),
+    /// Continues with the chain without returning.
+    Goto(String),
+    /// Returns to the chain that jumped to the current chain.
+    Return,
+}
+
+impl Verdict {
+    pub const NFT_CONTINUE: i32 = -1;
+    pub const NFT_BREAK: i32 = -2;
+    pub const NFT_JUMP: i32 = -3;
+    pub const NFT_GOTO: i32 = -4;
+    pub const NFT_RETURN: i32 = -5;
+    pub const NF_DROP: i32 = 0;
+    pub const NF_ACCEPT: i32 = 1;
+
+    /// Returns the verdict code that is reported to user space.
+    pub fn code(&self) -> i32 {
+        match self {
+            Self::Continue => Self::NFT_CONTINUE,
+            Self::Break => Self::NFT_BREAK,
+            Self::Accept => Self::NF_ACCEPT,
+            Self::Drop => Self::NF_DROP,
+            Self::Jump(_) => Self::NFT_JUMP,
+            Self::Goto(_) => Self::NFT_GOTO,
+            Self::Return => Self::NFT_RETURN,
+        }
+    }
+
+    /// Returns the chain that the verdict jumps to.
+    pub fn chain(&self) -> Option<&str> {
+        match self {
+            Self::Jump(chain) | Self::Goto(chain) => Some(chain),
+            _ => None,
+        }
+    }
+}
+
+/// An expression of a rule.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/netfilter/nf_tables.h>
+#[derive(Debug)]
+pub enum Expr {
+    /// Loads a constant value into a register (i.e., the `immediate` expression).
+    Immediate { dreg: Register, value: Vec<u8> },
+    /// Sets the verdict (i.e., the `immediate` expression with the verdict register).
+    Verdict(Verdict),
+    /// Compares the data in a register with a constant value.
+    Cmp {
+        sreg: Register,
+        op: CmpOp,
+        data: Vec<u8>,
+    },
+    /// Checks whether the data in a register is in an inclusive range.
+    Range {
+        sreg: Register,
+        op: RangeOp,
+        from: Vec<u8>,
+        to: Vec<u8>,
+    },
+    /// Computes `(data & mask) ^ xor` for the data in a register.
+    Bitwise {
+        sreg: Register,
+        dreg: Register,
+        mask: Vec<u8>,
+        xor: Vec<u8>,
+    },
+    /// Loads the data at an offset of a header of the packet into a register.
+    Payload {
+        dreg: Register,
+        base: PayloadBase,
+        offset: u32,
+        len: u32,
+    },
+    /// Loads the metadata of the packet into a register.
+    Meta { dreg: Register, key: MetaKey },
+    /// Loads the connection tracking information of the packet into a register.
+    Ct {
+        dreg: Register,
+        key: CtKey,
+        dir: Option<Direction>,
+    },
+    /// Counts the packets and the bytes.
+    Counter(Arc<Counter>),
+    /// Sets up a source or destination NAT binding.
+    Nat(NatExpr),
+    /// Sets up a source NAT binding to the address of the output iface.
+    Masq {
+        ports: Option<(Register, Register)>,
+        flags: u32,
+    },
+    /// Sets up a destination NAT binding to the address of the local host.
+    Redir {
+        ports: Option<(Register, Register)>,
+        flags: u32,
+    },
+}
+
+/// The comparison operator of [`Expr::Cmp`].
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, TryFromInt)]
+pub enum CmpOp {
+    Eq = 0,
+    Neq = 1,
+    Lt = 2,
+    Lte = 3,
+    Gt = 4,
+    Gte = 5,
+}
+
+/// The operator of [`Expr::Range`].
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, TryFromInt)]
+pub enum RangeOp {
+    Eq = 0,
+    Neq = 1,
+}
+
+/// The header that the offset of [`Expr::Payload`] is relative to.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, TryFromInt)]
+pub enum PayloadBase {
+    LinkLayer = 0,
+    Network = 1,
+    Transport = 2,
+}
+
+/// The metadata loaded by [`Expr::Meta`].
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, TryFromInt)]
+pub enum MetaKey {
+    Len = 0,
+    Protocol = 1,
+    Mark = 3,
+    Iif = 4,
+    Oif = 5,
+    IifName = 6,
+    OifName = 7,
+    NfProto = 15,
+    L4Proto = 16,
+}
+
+impl MetaKey {
+    /// Returns the length of the loaded data.
+    pub fn data_len(self) -> usize {
+        match self {
+            Self::Len | Self::Mark | Self::Iif | Self::Oif => 4,
+            Self::Protocol => 2,
+            Self::IifName | Self::OifName => IFNAMSIZ,
+            Self::NfProto | Self::L4Proto => 1,
+        }
+    }
+}
+
+/// The connection tracking information loaded by [`Expr::Ct`].
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, TryFromInt)]
+pub enum CtKey {
+    State = 0,
+    Direction = 1,
+    Status = 2,
+    Mark = 3,
+    L3Protocol = 7,
+    Src = 8,
+    Dst = 9,
+    Protocol = 10,
+    ProtoSrc = 11,
+    ProtoDst = 12,
+    SrcIp = 19,
+    DstIp = 20,
+    SrcIp6 = 21,
+    DstIp6 = 22,
+}
+
+impl CtKey {
+    /// Returns the length of the loaded data in a table of the family.
+    pub fn data_len(self, family: u8) -> usize {
+        match self {
+            Self::State | Self::Status | Self::Mark => 4,
+            Self::Direction | Self::L3Protocol | Self::Protocol => 1,
+            Self::ProtoSrc | Self::ProtoDst => 2,
+            Self::Src | Self::Dst if family == NFPROTO_IPV4 => 4,
+            Self::Src | Self::Dst => 16,
+            Self::SrcIp | Self::DstIp => 4,
+            Self::SrcIp6 | Self::DstIp6 => 16,
+        }
+    }
+
+    /// Returns whether the key refers to an address or a port in a tuple, so that the direction
+    /// must be specified.
+    pub fn needs_direction(self) -> bool {
+        !matches!(
+            self,
+            Self::State
+                | Self::Direction
+                | Self::Status
+                | Self::Mark
+                | Self::L3Protocol
+                | Self::Protocol
+        )
+    }
+
+    /// Returns whether the key refers to the connection rather than a tuple, so that the
+    /// direction must not be specified.
+    pub fn rejects_direction(self) -> bool {
+        matches!(
+            self,
+            Self::State | Self::Direction | Self::Status | Self::Mark
+        )
+    }
+}
+
+/// The counter of [`Expr::Counter`].
+#[derive(Debug)]
+pub struct Counter {
+    packets: AtomicU64,
+    bytes: AtomicU64,
+}
+
+impl Counter {
+    pub fn new(packets: u64, bytes: u64) -> Self {
+        Self {
+            packets: AtomicU64::new(packets),
+            bytes: AtomicU64::new(bytes),
+        }
+    }
+
+    pub fn packets(&self) -> u64 {
+        self.packets.load(Ordering::Relaxed)
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.bytes.load(Ordering::Relaxed)
+    }
+}
+
+/// The NAT type of [`NatExpr`].
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, TryFromInt)]
+pub enum NatType {
+    Snat = 0,
+    Dnat = 1,
+}
+
+/// The `nat` expression.
+#[derive(Debug)]
+pub struct NatExpr {
+    pub type_: NatType,
+    /// The address family (i.e., `NFPROTO_IPV4` or `NFPROTO_IPV6`).
+    pub family: u8,
+    /// The registers that contain the minimum and the maximum addresses.
+    pub addrs: Option<(Register, Register)>,
+    /// The registers that contain the minimum and the maximum ports.
+    pub ports: Option<(Register, Register)>,
+    pub flags: u32,
+}
+
+impl Expr {
+    /// Returns the name of the expression that is reported to user space.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Immediate { .. } | Self::Verdict(_) => "immediate",
+            Self::Cmp { .. } => "cmp",
+            Self::Range { .. } => "range",
+            Self::Bitwise { .. } => "bitwise",
+            Self::Payload { .. } => "payload",
+            Self::Meta { .. } => "meta",
+            Self::Ct { .. } => "ct",
+            Self::Counter(_) => "counter",
+            Self::Nat(_) => "nat",
+            Self::Masq { .. } => "masq",
+            Self::Redir { .. } => "redir",
+        }
+    }
+
+    /// Returns whether the expression sets up NAT bindings, so that it can only be used in NAT
+    /// chains.
+    pub(super) fn is_nat(&self) -> bool {
+        matches!(self, Self::Nat(_) | Self::Masq { .. } | Self::Redir { .. })
+    }
+
+    /// Returns whether the expression can be used in a chain at the hook.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/netfilter/nft_nat.c>
+    pub(super) fn is_valid_at(&self, hook: FilterHook) -> bool {
+        let is_src_hook = matches!(hook, FilterHook::PostRouting | FilterHook::LocalIn);
+        let is_dst_hook = matches!(hook, FilterHook::PreRouting | FilterHook::LocalOut);
+        match self {
+            Self::Nat(NatExpr {
+                type_: NatType::Snat,
+                ..
+            })
+            | Self::Masq { .. } => is_src_hook,
+            Self::Nat(NatExpr {
+                type_: NatType::Dnat,
+                ..
+            })
+            | Self::Redir { .. } => is_dst_hook,
+            _ => true,
+        }
+    }
+
+    /// Evaluates the expression.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/netfilter/nf_tables_core.c>
+    pub(super) fn eval<'a>(&'a self, regs: &mut Registers<'a>, ctx: &mut EvalContext) {
+        match self {
+            Self::Immediate { dreg, value } => regs.store(*dreg, value),
+            Self::Verdict(verdict) => regs.verdict = verdict,
+            Self::Cmp { sreg, op, data } => {
+                let ordering = regs.load(*sreg, data.len()).cmp(data.as_slice());
+                let is_match = match op {
+                    CmpOp::Eq => ordering.is_eq(),
+                    CmpOp::Neq => ordering.is_ne(),
+                    CmpOp::Lt => ordering.is_lt(),
+                    CmpOp::Lte => ordering.is_le(),
+                    CmpOp::Gt => ordering.is_gt(),
+                    CmpOp::Gte => ordering.is_ge(),
+                };
+                if !is_match {
+                    regs.verdict = &Verdict::Break;
+                }
+            }
+            Self::Range { sreg, op, from, to } => {
+                let data = regs.load(*sreg, from.len());
+                let is_in_range = data >= from.as_slice() && data <= to.as_slice();
+                if is_in_range != (*op == RangeOp::Eq) {
+                    regs.verdict = &Verdict::Break;
+                }
+            }
+            Self::Bitwise {
+                sreg,
+                dreg,
+                mask,
+                xor,
+            } => {
+                let mut data = regs.load(*sreg, mask.len()).to_vec();
+                for (byte, (mask, xor)) in data.iter_mut().zip(mask.iter().zip(xor.iter())) {
+                    *byte = (*byte & mask) ^ xor;
+                }
+                regs.store(*dreg, &data);
+            }
+            Self::Payload {
+                dreg,
+                base,
+                offset,
+                len,
+            } => {
+                let header = match base {
+                    // The packets that traverse the hooks do not contain link-layer headers.
+                    PayloadBase::LinkLayer => None,
+                    PayloadBase::Network => Some(ctx.pkt.network_header()),
+                    PayloadBase::Transport => ctx.pkt.transport_header(),
+                };
+                let start = *offset as usize;
+                match header.and_then(|header| header.get(start..start + *len as usize)) {
+                    Some(data) => regs.store(*dreg, data),
+                    None => regs.verdict = &Verdict::Break,
+                }
+            }
+            Self::Meta { dreg, key } => match ctx.meta(*key) {
+                Some(value) => regs.store(*dreg, value.as_bytes()),
+                None => regs.verdict = &Verdict::Break,
+            },
+            Self::Ct { dreg, key, dir } => match ctx.ct(*key, *dir) {
+                Some(value) => regs.store(*dreg, value.as_bytes()),
+                None => regs.verdict = &Verdict::Break,
+            },
+            Self::Counter(counter) => {
+                counter.packets.fetch_add(1, Ordering::Relaxed);
+                counter
+                    .bytes
+                    .fetch_add(ctx.pkt.len() as u64, Ordering::Relaxed);
+            }
+            Self::Nat(nat) => {
+                if nat.family != NFPROTO_INET && nat.family != ctx.pkt.version().nfproto() {
+                    return;
+                }
+                let addr_len = ctx.pkt.version().addr_len();
+                let addrs = nat.addrs.map(|(min, max)| {
+                    (
+                        to_addr(regs.load(min, addr_len)),
+                        to_addr(regs.load(max, addr_len)),
+                    )
+                });
+                let ports = nat.ports.map(|ports| regs.load_ports(ports));
+                regs.verdict = ctx.set_nat_range(NatRange { addrs, ports });
+            }
+            Self::Masq { ports, .. } => {
+                let ports = ports.map(|ports| regs.load_ports(ports));
+                regs.verdict = match ctx.iface_addr() {
+                    Some(addr) => ctx.set_nat_range(NatRange {
+                        addrs: Some((addr, addr)),
+                        ports,
+                    }),
+                    None => &Verdict::Drop,
+                };
+            }
+            Self::Redir { ports, .. } => {
+                let ports = ports.map(|ports| regs.load_ports(ports));
+                let addr = if ctx.hook == FilterHook::LocalOut {
+                    Some(loopback_addr(ctx.pkt.version()))
+                } else {
+                    ctx.iface_addr()
+                };
+                regs.verdict = match addr {
+                    Some(addr) => ctx.set_nat_range(NatRange {
+                        addrs: Some((addr, addr)),
+                        ports,
+                    }),
+                    None => &Verdict::Drop,
+                };
+            }
+        }
+    }
+}
+
+fn to_addr(bytes: &[u8]) -> [u8; 16] {
+    let mut addr = [0u8; 16];
+    addr[..bytes.len()].copy_from_slice(bytes);
+    addr
+}
+
+fn loopback_addr(version: IpVersion) -> [u8; 16] {
+    match version {
+        IpVersion::V4 => to_addr(&[127, 0, 0, 1]),
+        IpVersion::V6 => to_addr(&1u128.to_be_bytes()),
+    }
+}
+
+/// The registers used to evaluate a chain.
+pub(super) struct Registers<'a> {
+    pub(super) verdict: &'a Verdict,
+    data: [u8; REGS_SIZE],
+}
+
+impl Registers<'_> {
+    pub(super) fn new() -> Self {
+        Self {
+            verdict: &Verdict::Continue,
+            data: [0; REGS_SIZE],
+        }
+    }
+
+    fn load(&self, reg: Register, len: usize) -> &[u8] {
+        &self.data[reg.offset()..reg.offset() + len]
+    }
+
+    /// Stores the data into the register.
+    ///
+    /// Like Linux, the remaining bytes in the last 32-bit register are zeroed.
+    fn store(&mut self, reg: Register, data: &[u8]) {
+        let start = reg.offset();
+        let end = (start + data.len().next_multiple_of(REG32_SIZE)).min(REGS_SIZE);
+        self.data[start..end].fill(0);
+        self.data[start..start + data.len()].copy_from_slice(data);
+    }
+
+    fn load_ports(&self, (min, max): (Register, Register)) -> (u16, u16) {
+        let load_port = |reg| {
+            let bytes = self.load(reg, 2);
+            u16::from_be_bytes([bytes[0], bytes[1]])
+        };
+        (load_port(min), load_port(max))
+    }
+}
+
+/// The context in which the expressions are evaluated.
+pub(super) struct EvalContext<'a, 'b> {
+    pub(super) pkt: &'a Packet<'b>,
+    pub(super) ct: &'a PacketCt,
+    pub(super) hook: FilterHook,
+    pub(super) iface: &'a FilterIface<'a>,
+    /// The family of the table that the chain belongs to.
+    pub(super) family: u8,
+    /// The state of the NAT binding that is being set up.
+    pub(super) nat: NatState,
+}
+
+/// The state of the NAT binding that is being set up by the evaluated chain.
+pub(super) enum NatState {
+    /// The chain is not a NAT chain, so NAT expressions are ignored.
+    Disallowed,
+    /// The chain is a NAT chain, but no NAT expressions have been evaluated.
+    Pending,
+    /// A NAT expression has set up the binding.
+    Done(NatRange),
+}
+
+impl EvalContext<'_, '_> {
+    fn set_nat_range(&mut self, range: NatRange) -> &'static Verdict {
+        if matches!(self.nat, NatState::Disallowed) {
+            return &Verdict::Break;
+        }
+        self.nat = NatState::Done(range);
+        &Verdict::Accept
+    }
+
+    /// Returns the first address of the iface that is in the same family as the packet.
+    fn iface_addr(&self) -> Option<[u8; 16]> {
+        let version = self.pkt.version();
+        self.iface
+            .ip_addrs
+            .iter()
+            .find_map(|ip_cidr| match (ip_cidr.address(), version) {
+                (IpAddress::Ipv4(addr), IpVersion::V4) => Some(to_addr(&addr.octets())),
+                (IpAddress::Ipv6(addr), IpVersion::V6) => Some(to_addr(&addr.octets())),
+                _ => None,
+            })
+    }
+
+    /// Returns the metadata of the packet, or `None` if it is unavailable at the hook.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/netfilter/nft_meta.c>
+    fn meta(&self, key: MetaKey) -> Option<Value> {
+        // The forward hook has both an input and an output interface.
+        let is_input = matches!(
+            self.hook,
+            FilterHook::PreRouting | FilterHook::LocalIn | FilterHook::Forward
+        );
+        let is_output = matches!(
+            self.hook,
+            FilterHook::Forward | FilterHook::LocalOut | FilterHook::PostRouting
+        );
+
+        let value = match key {
+            MetaKey::Len => Value::new(&(self.pkt.len() as u32).to_ne_bytes()),
+            MetaKey::Protocol => {
+                let ethertype: u16 = match self.pkt.version() {
+                    IpVersion::V4 => 0x0800,
+                    IpVersion::V6 => 0x86dd,
+                };
+                Value::new(&ethertype.to_be_bytes())
+            }
+            // TODO: Support packet marks.
+            MetaKey::Mark => Value::new(&0u32.to_ne_bytes()),
+            MetaKey::Iif if is_input => Value::new(&self.iface.index.to_ne_bytes()),
+            MetaKey::Oif if is_output => Value::new(&self.iface.index.to_ne_bytes()),
+            MetaKey::IifName if is_input => self.iface_name(),
+            MetaKey::OifName if is_output => self.iface_name(),
+            MetaKey::Iif | MetaKey::Oif | MetaKey::IifName | MetaKey::OifName => return None,
+            MetaKey::NfProto => Value::new(&[self.pkt.version().nfproto()]),
+            MetaKey::L4Proto => Value::new(&[self.pkt.l4_proto()]),
+        };
+        Some(value)
+    }
+
+    fn iface_name(&self) -> Value {
+        let mut name = [0u8; IFNAMSIZ];
+        let bytes = self.iface.name.to_bytes();
+        let len = bytes.len().min(IFNAMSIZ - 1);
+        name[..len].copy_from_slice(&bytes[..len]);
+        Value::new(&name)
+    }
+
+    /// Returns the connection tracking information, or `None` if it is unavailable.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/netfilter/nft_ct.c>
+    fn ct(&self, key: CtKey, dir: Option<Direction>) -> Option<Value> {
+        if key == CtKey::State {
+            return Some(Value::new(&self.ct.state_bits().bits().to_ne_bytes()));
+        }
+
+        let ct = self.ct.tracked()?;
+        let conn = ct.conn();
+        let tuple = conn.tuple(dir.unwrap_or(ct.dir()));
+        let is_v4 = tuple.version == IpVersion::V4;
+
+        let value = match key {
+            CtKey::State => unreachable!(),
+            CtKey::Direction => Value::new(&[ct.dir() as u8]),
+            CtKey::Status => Value::new(&conn.status.bits().to_ne_bytes()),
+            // TODO: Support connection marks.
+            CtKey::Mark => Value::new(&0u32.to_ne_bytes()),
+            CtKey::L3Protocol => Value::new(&[tuple.version.nfproto()]),
+            CtKey::Src => Value::new(&tuple.src[..key.data_len(self.family)]),
+            CtKey::Dst => Value::new(&tuple.dst[..key.data_len(self.family)]),
+            CtKey::Protocol => Value::new(&[tuple.proto]),
+            CtKey::ProtoSrc => Value::new(&tuple.src_id.to_be_bytes()),
+            CtKey::ProtoDst => Value::new(&tuple.dst_id.to_be_bytes()),
+            CtKey::SrcIp if is_v4 => Value::new(tuple.src_addr()),
+            CtKey::DstIp if is_v4 => Value::new(tuple.dst_addr()),
+            CtKey::SrcIp6 if !is_v4 => Value::new(tuple.src_addr()),
+            CtKey::DstIp6 if !is_v4 => Value::new(tuple.dst_addr()),
+            CtKey::SrcIp | CtKey::DstIp | CtKey::SrcIp6 | CtKey::DstIp6 => return None,
+        };
+        Some(value)
+    }
+}
+
+/// A value of the metadata or the connection tracking information.
+struct Value {
+    buf: [u8; 16],
+    len: usize,
+}
+
+impl Value {
+    fn new(bytes: &[u8]) -> Self {
+        let mut buf = [0u8; 16];
+        buf[..bytes.len()].copy_from_slice(bytes);
+        Self {
+            buf,
+            len: bytes.len(),
+        }
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+}
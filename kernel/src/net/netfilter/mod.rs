@@ -0,0 +1,293 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Packet filtering and network address translation (NAT).
+//!
+//! This module implements the packet filter of a network namespace, which is similar to the
+//! netfilter framework and nftables in Linux. The packets received or transmitted by the ifaces
+//! traverse the hooks (see [`FilterHook`]), where they are evaluated by the base chains of the
+//! [`Ruleset`] attached to the hooks. The connections of the packets are tracked, so that the
+//! rules can match the connection states, and the connections can be translated by NAT.
+//!
+//! The ruleset is configured by user space via `NETLINK_NETFILTER` sockets, which modify the
+//! ruleset in transactions (see [`Transaction`]).
+//!
+//! Unlike Linux, packets are never forwarded between ifaces, so the forward hook is never
+//! traversed. In addition, the packets are not routed again after their destinations are
+//! translated, so DNAT cannot redirect outgoing packets to another iface.
+
+mod conntrack;
+mod expr;
+mod nat;
+mod packet;
+mod ruleset;
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use aster_bigtcp::iface::{FilterHook, FilterIface, FilterVerdict, PacketFilter};
+use aster_softirq::BottomHalfDisabled;
+pub use conntrack::Direction;
+use conntrack::{ConnTrack, PacketCt};
+pub use expr::{
+    CmpOp, Counter, CtKey, Expr, MetaKey, NatExpr, NatType, PayloadBase, RangeOp, Register, Verdict,
+};
+use expr::{EvalContext, NatState};
+use nat::ManipType;
+use packet::Packet;
+pub use packet::{NFPROTO_INET, NFPROTO_IPV4, NFPROTO_IPV6, NFPROTO_UNSPEC};
+pub use ruleset::{
+    BaseChain, Chain, ChainPolicy, ChainType, Rule, RulePosition, Ruleset, Table, TableFlags,
+};
+
+use crate::prelude::*;
+
+/// The priorities of the NAT steps at the hooks where the destination or the source is
+/// translated.
+///
+/// The base chains with lower priorities are evaluated before the translation.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/netfilter_ipv4.h>
+const NF_IP_PRI_NAT_DST: i32 = -100;
+const NF_IP_PRI_NAT_SRC: i32 = 100;
+
+/// The packet filter of a network namespace.
+pub struct Netfilter {
+    ruleset: SpinLock<Arc<Ruleset>, BottomHalfDisabled>,
+    /// Whether any base chains are attached to the hooks.
+    is_active: AtomicBool,
+    /// The generation of the ruleset, which is increased by each commit.
+    ///
+    /// Like Linux, the generation is never zero, because user space uses zero to skip the
+    /// generation check. The lock also serializes the transactions.
+    generation: Mutex<u32>,
+    conntrack: ConnTrack,
+}
+
+impl Netfilter {
+    pub(super) fn new() -> Arc<Self> {
+        Arc::new(Self {
+            ruleset: SpinLock::new(Arc::new(Ruleset::new())),
+            is_active: AtomicBool::new(false),
+            generation: Mutex::new(1),
+            conntrack: ConnTrack::new(),
+        })
+    }
+
+    /// Returns the current ruleset.
+    pub fn ruleset(&self) -> Arc<Ruleset> {
+        self.ruleset.lock().clone()
+    }
+
+    /// Returns the current generation of the ruleset.
+    pub fn generation(&self) -> u32 {
+        *self.generation.lock()
+    }
+
+    /// Starts a transaction to modify the ruleset.
+    ///
+    /// The modifications are not visible until the transaction is committed. Other
+    /// transactions are blocked until the transaction is committed or dropped.
+    pub fn begin(&self) -> Transaction<'_> {
+        let generation = self.generation.lock();
+        let ruleset = Ruleset::clone(&self.ruleset.lock());
+
+        Transaction {
+            netfilter: self,
+            generation,
+            ruleset,
+            has_changes: false,
+        }
+    }
+
+    /// Evaluates the base chains attached to the hook.
+    fn traverse_hook(
+        &self,
+        ruleset: &Ruleset,
+        hook: FilterHook,
+        pkt: &mut Packet,
+        ct: &mut PacketCt,
+        iface: &FilterIface,
+    ) -> FilterVerdict {
+        let manip = ManipType::of_hook(hook);
+        let nat_priority = match manip {
+            Some(ManipType::Dst) => NF_IP_PRI_NAT_DST,
+            Some(ManipType::Src) => NF_IP_PRI_NAT_SRC,
+            None => i32::MAX,
+        };
+        let mut is_nat_done = manip.is_none();
+
+        for entry in ruleset.base_chains(pkt.version(), hook) {
+            // NAT chains are only evaluated in the NAT step.
+            if entry.type_ == ChainType::Nat {
+                continue;
+            }
+
+            if !is_nat_done && entry.priority >= nat_priority {
+                is_nat_done = true;
+                let verdict = self.do_nat(ruleset, hook, manip.unwrap(), pkt, ct, iface);
+                if verdict == FilterVerdict::Drop {
+                    return FilterVerdict::Drop;
+                }
+            }
+
+            let mut ctx = EvalContext {
+                pkt,
+                ct,
+                hook,
+                iface,
+                family: NFPROTO_INET,
+                nat: NatState::Disallowed,
+            };
+            if ruleset.eval_chain(entry, &mut ctx) == FilterVerdict::Drop {
+                return FilterVerdict::Drop;
+            }
+        }
+
+        if !is_nat_done {
+            return self.do_nat(ruleset, hook, manip.unwrap(), pkt, ct, iface);
+        }
+
+        FilterVerdict::Accept
+    }
+
+    /// Sets up the NAT binding if needed, and translates the packet.
+    ///
+    /// The NAT chains are evaluated in the order of their priorities to set up the binding of a
+    /// new connection, until a NAT expression sets up the binding. If no NAT expressions are
+    /// evaluated, a null binding is set up, which keeps the addresses and the ports unless they
+    /// clash with other connections.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/netfilter/nf_nat_proto.c>
+    fn do_nat(
+        &self,
+        ruleset: &Ruleset,
+        hook: FilterHook,
+        manip: ManipType,
+        pkt: &mut Packet,
+        ct: &mut PacketCt,
+        iface: &FilterIface,
+    ) -> FilterVerdict {
+        let needs_binding = match ct.tracked() {
+            Some(tracked) => !tracked.is_related && !nat::is_initialized(tracked, manip),
+            None => return FilterVerdict::Accept,
+        };
+
+        if needs_binding {
+            let mut range = None;
+
+            if ct.tracked().unwrap().is_new() {
+                let nat_chains = ruleset
+                    .base_chains(pkt.version(), hook)
+                    .iter()
+                    .filter(|entry| entry.type_ == ChainType::Nat);
+                for entry in nat_chains {
+                    let mut ctx = EvalContext {
+                        pkt,
+                        ct,
+                        hook,
+                        iface,
+                        family: NFPROTO_INET,
+                        nat: NatState::Pending,
+                    };
+                    if ruleset.eval_chain(entry, &mut ctx) == FilterVerdict::Drop {
+                        return FilterVerdict::Drop;
+                    }
+                    if let NatState::Done(nat_range) = ctx.nat {
+                        range = Some(nat_range);
+                        break;
+                    }
+                }
+            }
+
+            let PacketCt::Tracked(tracked) = ct else {
+                unreachable!();
+            };
+            nat::setup_binding(&self.conntrack, tracked, manip, range.as_ref());
+        }
+
+        let PacketCt::Tracked(tracked) = ct else {
+            unreachable!();
+        };
+        nat::translate_packet(pkt, tracked, manip);
+
+        FilterVerdict::Accept
+    }
+}
+
+impl PacketFilter for Netfilter {
+    fn is_active(&self) -> bool {
+        self.is_active.load(Ordering::Relaxed)
+    }
+
+    fn filter(&self, hooks: &[FilterHook], pkt: &mut [u8], iface: &FilterIface) -> FilterVerdict {
+        let ruleset = self.ruleset();
+
+        // Malformed packets are left to the network stack, which will drop them.
+        let Some(mut pkt) = Packet::parse(pkt) else {
+            return FilterVerdict::Accept;
+        };
+        let mut ct = self.conntrack.resolve(&pkt);
+
+        for hook in hooks.iter() {
+            let verdict = self.traverse_hook(&ruleset, *hook, &mut pkt, &mut ct, iface);
+            if verdict == FilterVerdict::Drop {
+                return FilterVerdict::Drop;
+            }
+        }
+
+        if let PacketCt::Tracked(tracked) = ct
+            && !self.conntrack.confirm(tracked)
+        {
+            return FilterVerdict::Drop;
+        }
+
+        FilterVerdict::Accept
+    }
+}
+
+/// A transaction that modifies the ruleset.
+///
+/// The modifications are made to a copy of the ruleset, which replaces the current ruleset
+/// atomically when the transaction is committed. If the transaction is dropped without being
+/// committed, the modifications are discarded.
+pub struct Transaction<'a> {
+    netfilter: &'a Netfilter,
+    generation: MutexGuard<'a, u32>,
+    ruleset: Ruleset,
+    has_changes: bool,
+}
+
+impl Transaction<'_> {
+    /// Returns the generation of the ruleset that the transaction is based on.
+    pub fn generation(&self) -> u32 {
+        *self.generation
+    }
+
+    /// Returns the ruleset with the modifications made so far.
+    pub fn ruleset(&self) -> &Ruleset {
+        &self.ruleset
+    }
+
+    /// Returns the ruleset for modification.
+    pub fn ruleset_mut(&mut self) -> &mut Ruleset {
+        self.has_changes = true;
+        &mut self.ruleset
+    }
+
+    /// Commits the transaction and returns the new generation.
+    ///
+    /// The generation is not increased if the transaction does not modify the ruleset.
+    pub fn commit(mut self) -> u32 {
+        if !self.has_changes {
+            return *self.generation;
+        }
+
+        self.ruleset.rebuild_hooks();
+        let is_active = self.ruleset.has_base_chains();
+
+        *self.netfilter.ruleset.lock() = Arc::new(self.ruleset);
+        self.netfilter.is_active.store(is_active, Ordering::Relaxed);
+
+        *self.generation = self.generation.checked_add(1).unwrap_or(1);
+        *self.generation
+    }
+}
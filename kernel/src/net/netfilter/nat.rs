@@ -0,0 +1,263 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Network address translation (NAT).
+//!
+//! A connection is translated by setting up a binding for each type of manipulation: the source
+//! NAT binding is set up when its first packet traverses [`FilterHook::PostRouting`] or
+//! [`FilterHook::LocalIn`], and the destination NAT binding is set up when its first packet
+//! traverses [`FilterHook::PreRouting`] or [`FilterHook::LocalOut`]. Once set up, the bindings
+//! alter the reply tuple of the connection, and all the packets of the connection are rewritten
+//! according to the tuples at these hooks.
+
+use aster_bigtcp::iface::FilterHook;
+
+use super::{
+    conntrack::{ConnTrack, CtStatus, Direction, TrackedCt, Tuple},
+    packet::{IPPROTO_ICMP, IPPROTO_ICMPV6, IPPROTO_TCP, IPPROTO_UDP, Packet},
+};
+
+/// The type of a NAT manipulation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ManipType {
+    /// Source NAT, which rewrites the source address and port.
+    Src,
+    /// Destination NAT, which rewrites the destination address and port.
+    Dst,
+}
+
+impl ManipType {
+    /// Returns the type of the manipulation done at the hook.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/net/netfilter/nf_nat.h>
+    pub(super) fn of_hook(hook: FilterHook) -> Option<Self> {
+        match hook {
+            FilterHook::PreRouting | FilterHook::LocalOut => Some(Self::Dst),
+            FilterHook::PostRouting | FilterHook::LocalIn => Some(Self::Src),
+            FilterHook::Forward => None,
+        }
+    }
+
+    fn inverse(self) -> Self {
+        match self {
+            Self::Src => Self::Dst,
+            Self::Dst => Self::Src,
+        }
+    }
+
+    fn status_bit(self) -> CtStatus {
+        match self {
+            Self::Src => CtStatus::SRC_NAT,
+            Self::Dst => CtStatus::DST_NAT,
+        }
+    }
+
+    fn done_bit(self) -> CtStatus {
+        match self {
+            Self::Src => CtStatus::SRC_NAT_DONE,
+            Self::Dst => CtStatus::DST_NAT_DONE,
+        }
+    }
+}
+
+/// The range of the addresses and the ports that a connection can be translated to.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct NatRange {
+    /// The inclusive range of the addresses, or `None` if the addresses are kept.
+    pub(super) addrs: Option<([u8; 16], [u8; 16])>,
+    /// The inclusive range of the ports, or `None` if the ports are kept if possible.
+    pub(super) ports: Option<(u16, u16)>,
+}
+
+/// Returns whether the binding of the manipulation type has been set up.
+pub(super) fn is_initialized(ct: &TrackedCt, manip: ManipType) -> bool {
+    ct.conn.status.contains(manip.done_bit())
+}
+
+/// Sets up the binding of the manipulation type for a connection.
+///
+/// If `range` is `None`, a null binding is set up, which only changes the ports if they clash
+/// with another connection.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/netfilter/nf_nat_core.c>
+pub(super) fn setup_binding(
+    conntrack: &ConnTrack,
+    ct: &mut TrackedCt,
+    manip: ManipType,
+    range: Option<&NatRange>,
+) {
+    // The tuple of the original direction, with the existing manipulations applied.
+    let curr = ct.conn.tuple(Direction::Reply).invert();
+
+    let mut new = curr;
+    if let Some((min, max)) = range.and_then(|range| range.addrs) {
+        let addr = match manip {
+            ManipType::Src => &mut new.src,
+            ManipType::Dst => &mut new.dst,
+        };
+        *addr = select_addr(&curr, &min, &max);
+    }
+    select_unique_id(
+        conntrack,
+        ct,
+        &mut new,
+        manip,
+        range.and_then(|range| range.ports),
+    );
+
+    ct.conn.set_reply_tuple(new.invert());
+    if new != curr {
+        ct.conn.status |= manip.status_bit();
+    }
+    ct.conn.status |= manip.done_bit();
+    ct.is_dirty = true;
+}
+
+/// Selects an address in the range.
+///
+/// Like Linux, the address is selected by hashing the addresses of the connection, so that the
+/// connections between the same hosts are translated to the same address.
+fn select_addr(tuple: &Tuple, min: &[u8; 16], max: &[u8; 16]) -> [u8; 16] {
+    let addr_len = tuple.version.addr_len();
+    let to_int = |addr: &[u8; 16]| {
+        let mut bytes = [0u8; 16];
+        bytes[16 - addr_len..].copy_from_slice(&addr[..addr_len]);
+        u128::from_be_bytes(bytes)
+    };
+
+    let min_val = to_int(min);
+    let max_val = to_int(max);
+    if min_val >= max_val {
+        return *min;
+    }
+
+    let hash = u128::from(hash_bytes(tuple.src_addr()) ^ hash_bytes(tuple.dst_addr()));
+    let addr_val = match (max_val - min_val).checked_add(1) {
+        Some(range_size) => min_val + hash % range_size,
+        None => hash,
+    };
+
+    let mut addr = [0u8; 16];
+    addr[..addr_len].copy_from_slice(&addr_val.to_be_bytes()[16 - addr_len..]);
+    addr
+}
+
+/// Selects the port (or the ICMP identifier) that makes the tuple unique.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/netfilter/nf_nat_proto.c>
+fn select_unique_id(
+    conntrack: &ConnTrack,
+    ct: &TrackedCt,
+    tuple: &mut Tuple,
+    manip: ManipType,
+    ports: Option<(u16, u16)>,
+) {
+    /// The maximum number of ports to try.
+    const MAX_ATTEMPTS: u32 = 128;
+
+    let is_dst_port = match (tuple.proto, manip) {
+        (IPPROTO_TCP | IPPROTO_UDP, ManipType::Src) => false,
+        (IPPROTO_TCP | IPPROTO_UDP, ManipType::Dst) => true,
+        (IPPROTO_ICMP | IPPROTO_ICMPV6, ManipType::Src) => false,
+        _ => return,
+    };
+    let curr = if is_dst_port {
+        tuple.dst_id
+    } else {
+        tuple.src_id
+    };
+
+    let in_range = ports.is_none_or(|(min, max)| (min..=max).contains(&curr));
+    if in_range && !conntrack.is_taken(&tuple.invert(), ct) {
+        return;
+    }
+
+    let (min, max) = match ports {
+        Some(ports) => ports,
+        // Like Linux, privileged source ports are translated to privileged ports.
+        None if manip == ManipType::Src && curr < 512 => (1, 511),
+        None if manip == ManipType::Src && curr < 1024 => (600, 1023),
+        None => (1024, u16::MAX),
+    };
+    let range_size = u32::from(max.saturating_sub(min)) + 1;
+    let offset = hash_bytes(tuple.src_addr()) ^ hash_bytes(tuple.dst_addr()) ^ u64::from(curr);
+
+    for attempt in 0..range_size.min(MAX_ATTEMPTS) {
+        let new_id = min + (offset.wrapping_add(u64::from(attempt)) % u64::from(range_size)) as u16;
+        if is_dst_port {
+            tuple.dst_id = new_id;
+        } else {
+            tuple.src_id = new_id;
+        }
+        if !conntrack.is_taken(&tuple.invert(), ct) {
+            return;
+        }
+    }
+}
+
+/// Hashes the bytes with the FNV-1a algorithm.
+fn hash_bytes(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Rewrites a packet according to the binding of the manipulation type.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/netfilter/nf_nat_core.c>
+pub(super) fn translate_packet(pkt: &mut Packet, ct: &TrackedCt, manip: ManipType) {
+    // The source NAT of the original direction is the destination NAT of the reply direction,
+    // and vice versa.
+    let mut status_bit = manip.status_bit();
+    if ct.dir == Direction::Reply {
+        status_bit ^= CtStatus::SRC_NAT | CtStatus::DST_NAT;
+    }
+    if !ct.conn.status.contains(status_bit) {
+        return;
+    }
+
+    // The packet should look like a packet of the opposite direction after being inverted.
+    let target = ct.conn.tuple(ct.dir.inverse()).invert();
+
+    if ct.is_related {
+        translate_icmp_error(pkt, ct, &target, manip);
+    } else {
+        manip_packet(pkt, &target, manip);
+    }
+}
+
+/// Rewrites an ICMP error message that is related to a translated connection.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/netfilter/nf_nat_proto.c>
+fn translate_icmp_error(pkt: &mut Packet, ct: &TrackedCt, target: &Tuple, manip: ManipType) {
+    // The embedded packet should look like the packet that caused the error. So it is rewritten
+    // with the opposite manipulation.
+    let inner_target = ct.conn.tuple(ct.dir.inverse());
+    let Some(mut inner) = pkt.icmp_inner_packet_mut() else {
+        return;
+    };
+    manip_packet(&mut inner, inner_target, manip.inverse());
+    pkt.recompute_icmp_checksum();
+
+    // The ICMP header does not contain ports, so only the addresses are rewritten.
+    match manip {
+        ManipType::Src => pkt.set_src_addr(target.src_addr()),
+        ManipType::Dst => pkt.set_dst_addr(target.dst_addr()),
+    }
+}
+
+/// Rewrites the packet to match the tuple.
+fn manip_packet(pkt: &mut Packet, target: &Tuple, manip: ManipType) {
+    match manip {
+        ManipType::Src => {
+            pkt.set_src_addr(target.src_addr());
+            pkt.set_src_port(target.src_id);
+        }
+        ManipType::Dst => {
+            pkt.set_dst_addr(target.dst_addr());
+            pkt.set_dst_port(target.dst_id);
+        }
+    }
+
+    // The identifier of ICMP echo messages is the same in both directions.
+    pkt.set_icmp_id(target.src_id);
+}
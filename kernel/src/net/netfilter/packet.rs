@@ -0,0 +1,412 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Parsing and rewriting of the IP packets that traverse the hooks.
+
+/// The IP version of a packet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IpVersion {
+    V4,
+    V6,
+}
+
+impl IpVersion {
+    /// Returns the netfilter protocol family (i.e., `NFPROTO_IPV4` or `NFPROTO_IPV6`).
+    pub fn nfproto(self) -> u8 {
+        match self {
+            Self::V4 => NFPROTO_IPV4,
+            Self::V6 => NFPROTO_IPV6,
+        }
+    }
+
+    /// Returns the length of the addresses.
+    pub fn addr_len(self) -> usize {
+        match self {
+            Self::V4 => 4,
+            Self::V6 => 16,
+        }
+    }
+}
+
+/// Netfilter protocol families.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/netfilter.h>
+pub const NFPROTO_UNSPEC: u8 = 0;
+pub const NFPROTO_INET: u8 = 1;
+pub const NFPROTO_IPV4: u8 = 2;
+pub const NFPROTO_IPV6: u8 = 10;
+
+/// IP protocol numbers.
+///
+/// Reference: <https://www.iana.org/assignments/protocol-numbers/protocol-numbers.xhtml>
+pub const IPPROTO_ICMP: u8 = 1;
+pub const IPPROTO_TCP: u8 = 6;
+pub const IPPROTO_UDP: u8 = 17;
+pub const IPPROTO_ICMPV6: u8 = 58;
+
+const IPV4_HEADER_LEN: usize = 20;
+const IPV6_HEADER_LEN: usize = 40;
+
+/// An IP packet that is being filtered.
+///
+/// The packet starts with its IP header. The addresses and the ports (or the identifiers of ICMP
+/// echo messages) can be rewritten in place, and the checksums are updated incrementally.
+pub(super) struct Packet<'a> {
+    data: &'a mut [u8],
+    version: IpVersion,
+    l4_proto: u8,
+    l4_offset: usize,
+    /// Whether the packet is a fragment other than the first one, so that it does not contain
+    /// the transport header.
+    is_later_fragment: bool,
+}
+
+impl<'a> Packet<'a> {
+    /// Parses the IP header of a packet.
+    ///
+    /// Returns `None` if the IP header is malformed.
+    pub(super) fn parse(data: &'a mut [u8]) -> Option<Self> {
+        let (version, l4_proto, l4_offset, is_later_fragment) = match data.first()? >> 4 {
+            4 => {
+                if data.len() < IPV4_HEADER_LEN {
+                    return None;
+                }
+                let header_len = usize::from(data[0] & 0x0f) * 4;
+                if header_len < IPV4_HEADER_LEN || header_len > data.len() {
+                    return None;
+                }
+                let frag_offset = u16::from_be_bytes([data[6], data[7]]) & 0x1fff;
+                (IpVersion::V4, data[9], header_len, frag_offset != 0)
+            }
+            6 => {
+                if data.len() < IPV6_HEADER_LEN {
+                    return None;
+                }
+                let (l4_proto, l4_offset, is_later_fragment) = skip_ipv6_ext_headers(data);
+                (IpVersion::V6, l4_proto, l4_offset, is_later_fragment)
+            }
+            _ => return None,
+        };
+
+        Some(Self {
+            data,
+            version,
+            l4_proto,
+            l4_offset,
+            is_later_fragment,
+        })
+    }
+
+    pub(super) fn version(&self) -> IpVersion {
+        self.version
+    }
+
+    /// Returns the length of the packet, including the IP header.
+    pub(super) fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns the protocol of the transport header.
+    pub(super) fn l4_proto(&self) -> u8 {
+        self.l4_proto
+    }
+
+    /// Returns the packet starting with its IP header.
+    pub(super) fn network_header(&self) -> &[u8] {
+        self.data
+    }
+
+    /// Returns the packet starting with its transport header.
+    ///
+    /// Returns `None` if the packet does not contain the transport header.
+    pub(super) fn transport_header(&self) -> Option<&[u8]> {
+        if self.is_later_fragment {
+            return None;
+        }
+        self.data.get(self.l4_offset..)
+    }
+
+    /// Returns the source address.
+    pub(super) fn src_addr(&self) -> &[u8] {
+        match self.version {
+            IpVersion::V4 => &self.data[12..16],
+            IpVersion::V6 => &self.data[8..24],
+        }
+    }
+
+    /// Returns the destination address.
+    pub(super) fn dst_addr(&self) -> &[u8] {
+        match self.version {
+            IpVersion::V4 => &self.data[16..20],
+            IpVersion::V6 => &self.data[24..40],
+        }
+    }
+
+    /// Returns the source port and the destination port of TCP or UDP packets.
+    pub(super) fn ports(&self) -> Option<(u16, u16)> {
+        if !matches!(self.l4_proto, IPPROTO_TCP | IPPROTO_UDP) {
+            return None;
+        }
+
+        let header = self.transport_header()?.get(..4)?;
+        Some((
+            u16::from_be_bytes([header[0], header[1]]),
+            u16::from_be_bytes([header[2], header[3]]),
+        ))
+    }
+
+    /// Returns the type, the code, and the identifier of ICMP or ICMPv6 packets.
+    ///
+    /// The identifier is only meaningful for echo messages.
+    pub(super) fn icmp_header(&self) -> Option<(u8, u8, u16)> {
+        if !self.is_icmp() {
+            return None;
+        }
+
+        let header = self.transport_header()?.get(..8)?;
+        Some((
+            header[0],
+            header[1],
+            u16::from_be_bytes([header[4], header[5]]),
+        ))
+    }
+
+    /// Returns the packet embedded in ICMP or ICMPv6 error messages.
+    pub(super) fn icmp_inner_packet(&self) -> Option<&[u8]> {
+        if !self.is_icmp() {
+            return None;
+        }
+
+        self.transport_header()?.get(8..)
+    }
+
+    fn is_icmp(&self) -> bool {
+        matches!(
+            (self.version, self.l4_proto),
+            (IpVersion::V4, IPPROTO_ICMP) | (IpVersion::V6, IPPROTO_ICMPV6)
+        )
+    }
+
+    /// Rewrites the source address.
+    pub(super) fn set_src_addr(&mut self, addr: &[u8]) {
+        let offset = match self.version {
+            IpVersion::V4 => 12,
+            IpVersion::V6 => 8,
+        };
+        self.set_addr(offset, addr);
+    }
+
+    /// Rewrites the destination address.
+    pub(super) fn set_dst_addr(&mut self, addr: &[u8]) {
+        let offset = match self.version {
+            IpVersion::V4 => 16,
+            IpVersion::V6 => 24,
+        };
+        self.set_addr(offset, addr);
+    }
+
+    fn set_addr(&mut self, offset: usize, addr: &[u8]) {
+        let len = self.version.addr_len();
+        debug_assert_eq!(addr.len(), len);
+
+        let mut old = [0u8; 16];
+        old[..len].copy_from_slice(&self.data[offset..offset + len]);
+        let old = &old[..len];
+        if old == addr {
+            return;
+        }
+        self.data[offset..offset + len].copy_from_slice(addr);
+
+        if self.version == IpVersion::V4 {
+            update_checksum(self.data, IPV4_CHECKSUM_OFFSET, old, addr);
+        }
+
+        // The pseudo-header that includes the addresses is covered by the checksums of TCP, UDP,
+        // and ICMPv6, but not by the checksum of ICMP.
+        if let Some((csum_offset, is_udp)) = self.l4_checksum_offset()
+            && (self.version == IpVersion::V6 || self.l4_proto != IPPROTO_ICMP)
+        {
+            self.update_l4_checksum(csum_offset, is_udp, old, addr);
+        }
+    }
+
+    /// Rewrites the source port of TCP or UDP packets.
+    pub(super) fn set_src_port(&mut self, port: u16) {
+        if matches!(self.l4_proto, IPPROTO_TCP | IPPROTO_UDP) {
+            self.set_l4_field(0, port);
+        }
+    }
+
+    /// Rewrites the destination port of TCP or UDP packets.
+    pub(super) fn set_dst_port(&mut self, port: u16) {
+        if matches!(self.l4_proto, IPPROTO_TCP | IPPROTO_UDP) {
+            self.set_l4_field(2, port);
+        }
+    }
+
+    /// Rewrites the identifier of ICMP or ICMPv6 packets.
+    pub(super) fn set_icmp_id(&mut self, id: u16) {
+        if self.is_icmp() {
+            self.set_l4_field(4, id);
+        }
+    }
+
+    fn set_l4_field(&mut self, offset_in_l4: usize, value: u16) {
+        let Some(old) = self
+            .transport_header()
+            .and_then(|header| header.get(offset_in_l4..offset_in_l4 + 2))
+        else {
+            return;
+        };
+        let old = [old[0], old[1]];
+        let new = value.to_be_bytes();
+        if old == new {
+            return;
+        }
+
+        let offset = self.l4_offset + offset_in_l4;
+        self.data[offset..offset + 2].copy_from_slice(&new);
+
+        // The embedded packets in ICMP error messages may be truncated, so the checksums may be
+        // absent.
+        if let Some((csum_offset, is_udp)) = self.l4_checksum_offset() {
+            self.update_l4_checksum(csum_offset, is_udp, &old, &new);
+        }
+    }
+
+    /// Returns the packet embedded in ICMP or ICMPv6 error messages, which can be rewritten.
+    pub(super) fn icmp_inner_packet_mut(&mut self) -> Option<Packet<'_>> {
+        if !self.is_icmp() || self.is_later_fragment {
+            return None;
+        }
+
+        let inner = self.data.get_mut(self.l4_offset + 8..)?;
+        Packet::parse(inner)
+    }
+
+    /// Recomputes the checksum of ICMP or ICMPv6 packets from scratch.
+    pub(super) fn recompute_icmp_checksum(&mut self) {
+        let Some((csum_offset, _)) = self.l4_checksum_offset() else {
+            return;
+        };
+        self.data[csum_offset..csum_offset + 2].fill(0);
+
+        let l4_len = self.data.len() - self.l4_offset;
+        let mut sum = sum_words(&self.data[self.l4_offset..]);
+        if self.version == IpVersion::V6 {
+            sum += sum_words(self.src_addr());
+            sum += sum_words(self.dst_addr());
+            sum += sum_words(&(l4_len as u32).to_be_bytes());
+            sum += u32::from(IPPROTO_ICMPV6);
+        }
+
+        let checksum = !fold_checksum(sum);
+        self.data[csum_offset..csum_offset + 2].copy_from_slice(&checksum.to_be_bytes());
+    }
+
+    /// Returns the offset of the transport checksum and whether the transport protocol is UDP.
+    ///
+    /// Returns `None` if the transport header is absent or truncated, or if its protocol is not
+    /// supported.
+    fn l4_checksum_offset(&self) -> Option<(usize, bool)> {
+        let (offset_in_l4, header_len, is_udp) = match self.l4_proto {
+            IPPROTO_TCP => (16, 20, false),
+            IPPROTO_UDP => (6, 8, true),
+            IPPROTO_ICMP | IPPROTO_ICMPV6 if self.is_icmp() => (2, 8, false),
+            _ => return None,
+        };
+
+        if self.transport_header()?.len() < header_len {
+            return None;
+        }
+        Some((self.l4_offset + offset_in_l4, is_udp))
+    }
+
+    fn update_l4_checksum(&mut self, csum_offset: usize, is_udp: bool, old: &[u8], new: &[u8]) {
+        let is_csum_zero = self.data[csum_offset] == 0 && self.data[csum_offset + 1] == 0;
+
+        // A zero UDP checksum means that the checksum is not computed over IPv4.
+        if is_udp && is_csum_zero && self.version == IpVersion::V4 {
+            return;
+        }
+
+        update_checksum(self.data, csum_offset, old, new);
+
+        // A computed UDP checksum of zero is transmitted as all ones.
+        if is_udp && self.data[csum_offset] == 0 && self.data[csum_offset + 1] == 0 {
+            self.data[csum_offset..csum_offset + 2].copy_from_slice(&[0xff, 0xff]);
+        }
+    }
+}
+
+const IPV4_CHECKSUM_OFFSET: usize = 10;
+
+/// Skips the IPv6 extension headers.
+///
+/// Returns the protocol and the offset of the transport header, and whether the packet is a
+/// fragment other than the first one.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/ipv6/exthdrs_core.c>
+fn skip_ipv6_ext_headers(data: &[u8]) -> (u8, usize, bool) {
+    const IPPROTO_HOPOPTS: u8 = 0;
+    const IPPROTO_ROUTING: u8 = 43;
+    const IPPROTO_FRAGMENT: u8 = 44;
+    const IPPROTO_AH: u8 = 51;
+    const IPPROTO_DSTOPTS: u8 = 60;
+
+    let mut next_header = data[6];
+    let mut offset = IPV6_HEADER_LEN;
+
+    loop {
+        let Some(header) = data.get(offset..offset + 8) else {
+            return (next_header, offset, false);
+        };
+
+        let header_len = match next_header {
+            IPPROTO_HOPOPTS | IPPROTO_ROUTING | IPPROTO_DSTOPTS => (usize::from(header[1]) + 1) * 8,
+            IPPROTO_AH => (usize::from(header[1]) + 2) * 4,
+            IPPROTO_FRAGMENT => {
+                let frag_offset = u16::from_be_bytes([header[2], header[3]]) & !0x7;
+                if frag_offset != 0 {
+                    return (header[0], offset + 8, true);
+                }
+                8
+            }
+            _ => return (next_header, offset, false),
+        };
+
+        next_header = header[0];
+        offset += header_len;
+    }
+}
+
+/// Updates the Internet checksum at `offset` after `old` is replaced by `new` in the checksummed
+/// data.
+///
+/// Reference: <https://datatracker.ietf.org/doc/html/rfc1624>
+fn update_checksum(data: &mut [u8], offset: usize, old: &[u8], new: &[u8]) {
+    let checksum = u16::from_be_bytes([data[offset], data[offset + 1]]);
+
+    // HC' = ~(~HC + ~m + m')
+    let mut sum = u32::from(!checksum);
+    for (old, new) in old.chunks_exact(2).zip(new.chunks_exact(2)) {
+        sum += u32::from(!u16::from_be_bytes([old[0], old[1]]));
+        sum += u32::from(u16::from_be_bytes([new[0], new[1]]));
+    }
+
+    data[offset..offset + 2].copy_from_slice(&(!fold_checksum(sum)).to_be_bytes());
+}
+
+/// Sums the 16-bit words in the data, padding the last byte with zero if necessary.
+fn sum_words(data: &[u8]) -> u32 {
+    data.chunks(2)
+        .map(|word| u32::from(u16::from_be_bytes([word[0], *word.get(1).unwrap_or(&0)])))
+        .sum()
+}
+
+/// Folds the 32-bit sum into the 16-bit one's complement sum.
+fn fold_checksum(mut sum: u32) -> u16 {
+    while sum >> 16 != 0 {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    sum as u16
+}
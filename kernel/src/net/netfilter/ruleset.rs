@@ -0,0 +1,736 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The ruleset of nftables.
+//!
+//! A ruleset consists of tables, which consist of chains, which in turn consist of rules. A base
+//! chain is attached to a hook, and the packets that traverse the hook are evaluated by the base
+//! chains attached to it in the order of their priorities. A regular chain can only be reached
+//! by jumping from another chain in the same table.
+
+use aster_bigtcp::iface::{FilterHook, FilterVerdict};
+
+use super::{
+    expr::{EvalContext, Expr, Registers, Verdict},
+    packet::{IpVersion, NFPROTO_INET, NFPROTO_IPV4, NFPROTO_IPV6},
+};
+use crate::prelude::*;
+
+/// The maximum length of table and chain names, including the trailing null byte.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/netfilter/nf_tables.h>
+const NFT_NAME_MAXLEN: usize = 256;
+
+/// The maximum depth of nested jumps.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/net/netfilter/nf_tables.h>
+const NFT_JUMP_STACK_SIZE: usize = 16;
+
+/// The number of hooks that base chains can be attached to.
+const NUM_HOOKS: usize = 5;
+
+/// The ruleset of a network namespace.
+#[derive(Clone)]
+pub struct Ruleset {
+    tables: Vec<Table>,
+    next_table_handle: u64,
+    /// The base chains attached to each hook for each IP version, sorted by their priorities.
+    ///
+    /// This is only updated by [`Self::rebuild_hooks`] when the ruleset is committed.
+    hooks: [Vec<HookEntry>; 2 * NUM_HOOKS],
+}
+
+/// A base chain attached to a hook.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct HookEntry {
+    pub(super) priority: i32,
+    pub(super) type_: ChainType,
+    table: usize,
+    chain: usize,
+}
+
+bitflags! {
+    /// The flags of a table.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/netfilter/nf_tables.h>
+    pub struct TableFlags: u32 {
+        /// The base chains of the table are not attached to hooks.
+        const DORMANT = 1 << 0;
+        /// The table is owned by the netlink socket that creates it.
+        const OWNER = 1 << 1;
+    }
+}
+
+/// A table, which is a container of chains in an address family.
+#[derive(Clone)]
+pub struct Table {
+    name: String,
+    family: u8,
+    flags: TableFlags,
+    handle: u64,
+    userdata: Option<Vec<u8>>,
+    chains: Vec<Chain>,
+    /// The last handle allocated to the chains and rules in the table.
+    last_handle: u64,
+}
+
+/// A chain, which is a list of rules.
+#[derive(Clone)]
+pub struct Chain {
+    name: String,
+    handle: u64,
+    base: Option<BaseChain>,
+    userdata: Option<Vec<u8>>,
+    rules: Vec<Arc<Rule>>,
+}
+
+/// The information of a base chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BaseChain {
+    pub hook: FilterHook,
+    pub priority: i32,
+    pub type_: ChainType,
+    pub policy: ChainPolicy,
+}
+
+/// The type of a base chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChainType {
+    /// The chain filters packets.
+    Filter,
+    /// The chain sets up NAT bindings for new connections.
+    Nat,
+}
+
+impl ChainType {
+    /// Parses the type name given by user space.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/netfilter/nf_tables_api.c>
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "filter" => Ok(Self::Filter),
+            "nat" => Ok(Self::Nat),
+            "route" => {
+                return_errno_with_message!(Errno::EOPNOTSUPP, "route chains are not supported")
+            }
+            _ => return_errno_with_message!(Errno::ENOENT, "the chain type does not exist"),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Filter => "filter",
+            Self::Nat => "nat",
+        }
+    }
+}
+
+/// The verdict of a base chain when no rules decide the verdict.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChainPolicy {
+    Accept,
+    Drop,
+}
+
+impl ChainPolicy {
+    /// Parses the verdict code given by user space.
+    pub fn from_code(code: i32) -> Result<Self> {
+        match code {
+            Verdict::NF_ACCEPT => Ok(Self::Accept),
+            Verdict::NF_DROP => Ok(Self::Drop),
+            _ => return_errno_with_message!(Errno::EINVAL, "the chain policy is invalid"),
+        }
+    }
+
+    pub fn code(self) -> i32 {
+        match self {
+            Self::Accept => Verdict::NF_ACCEPT,
+            Self::Drop => Verdict::NF_DROP,
+        }
+    }
+}
+
+/// A rule, which is a list of expressions.
+#[derive(Debug)]
+pub struct Rule {
+    handle: u64,
+    exprs: Vec<Expr>,
+    userdata: Option<Vec<u8>>,
+}
+
+/// The position where a new rule is inserted in a chain.
+#[derive(Clone, Copy, Debug)]
+pub enum RulePosition {
+    /// Replaces the rule with the handle.
+    Replace(u64),
+    /// Inserts the rule before the rule with the handle, or at the beginning of the chain.
+    Before(Option<u64>),
+    /// Inserts the rule after the rule with the handle, or at the end of the chain.
+    After(Option<u64>),
+}
+
+impl Ruleset {
+    pub(super) fn new() -> Self {
+        Self {
+            tables: Vec::new(),
+            next_table_handle: 1,
+            hooks: Default::default(),
+        }
+    }
+
+    /// Returns all the tables, in the order in which they are created.
+    pub fn tables(&self) -> &[Table] {
+        &self.tables
+    }
+
+    /// Finds the table by its family and its name.
+    pub fn table(&self, family: u8, name: &str) -> Result<&Table> {
+        self.tables
+            .iter()
+            .find(|table| table.family == family && table.name == name)
+            .ok_or_else(|| Error::with_message(Errno::ENOENT, "the table does not exist"))
+    }
+
+    /// Finds the table by its family and its name for modification.
+    pub fn table_mut(&mut self, family: u8, name: &str) -> Result<&mut Table> {
+        self.tables
+            .iter_mut()
+            .find(|table| table.family == family && table.name == name)
+            .ok_or_else(|| Error::with_message(Errno::ENOENT, "the table does not exist"))
+    }
+
+    /// Adds a new table.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/netfilter/nf_tables_api.c>
+    pub fn add_table(
+        &mut self,
+        family: u8,
+        name: String,
+        flags: TableFlags,
+        userdata: Option<Vec<u8>>,
+    ) -> Result<&Table> {
+        if !matches!(family, NFPROTO_INET | NFPROTO_IPV4 | NFPROTO_IPV6) {
+            return_errno_with_message!(Errno::EAFNOSUPPORT, "the table family is not supported");
+        }
+        check_name(&name)?;
+        check_table_flags(flags)?;
+        if self.table(family, &name).is_ok() {
+            return_errno_with_message!(Errno::EEXIST, "the table already exists");
+        }
+
+        let handle = self.next_table_handle;
+        self.next_table_handle += 1;
+
+        self.tables.push(Table {
+            name,
+            family,
+            flags,
+            handle,
+            userdata,
+            chains: Vec::new(),
+            last_handle: 0,
+        });
+        Ok(self.tables.last().unwrap())
+    }
+
+    /// Removes the table, along with all its chains and rules.
+    pub fn remove_table(&mut self, family: u8, name: &str) -> Result<Table> {
+        let Some(pos) = self
+            .tables
+            .iter()
+            .position(|table| table.family == family && table.name == name)
+        else {
+            return_errno_with_message!(Errno::ENOENT, "the table does not exist");
+        };
+        Ok(self.tables.remove(pos))
+    }
+
+    /// Rebuilds the lists of the base chains attached to the hooks.
+    pub(super) fn rebuild_hooks(&mut self) {
+        for entries in self.hooks.iter_mut() {
+            entries.clear();
+        }
+
+        for (table_index, table) in self.tables.iter().enumerate() {
+            if table.flags.contains(TableFlags::DORMANT) {
+                continue;
+            }
+
+            for (chain_index, chain) in table.chains.iter().enumerate() {
+                let Some(base) = chain.base.as_ref() else {
+                    continue;
+                };
+                let entry = HookEntry {
+                    priority: base.priority,
+                    type_: base.type_,
+                    table: table_index,
+                    chain: chain_index,
+                };
+
+                for version in [IpVersion::V4, IpVersion::V6] {
+                    if table.family == NFPROTO_INET || table.family == version.nfproto() {
+                        self.hooks[hook_slot(version, base.hook)].push(entry);
+                    }
+                }
+            }
+        }
+
+        // The sort is stable, so the chains with the same priority are evaluated in the order in
+        // which they are created.
+        for entries in self.hooks.iter_mut() {
+            entries.sort_by_key(|entry| entry.priority);
+        }
+    }
+
+    /// Returns whether any base chains are attached to the hooks.
+    pub(super) fn has_base_chains(&self) -> bool {
+        self.hooks.iter().any(|entries| !entries.is_empty())
+    }
+
+    /// Returns the base chains attached to the hook, sorted by their priorities.
+    pub(super) fn base_chains(&self, version: IpVersion, hook: FilterHook) -> &[HookEntry] {
+        &self.hooks[hook_slot(version, hook)]
+    }
+
+    /// Evaluates a base chain and returns the verdict.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/netfilter/nf_tables_core.c>
+    pub(super) fn eval_chain(&self, entry: &HookEntry, ctx: &mut EvalContext) -> FilterVerdict {
+        let table = &self.tables[entry.table];
+        ctx.family = table.family;
+
+        let mut regs = Registers::new();
+        let mut stack = [(0, 0); NFT_JUMP_STACK_SIZE];
+        let mut depth = 0;
+
+        let mut chain_index = entry.chain;
+        let mut rule_index = 0;
+
+        loop {
+            let rules = &table.chains[chain_index].rules;
+
+            let mut verdict = &Verdict::Continue;
+            while let Some(rule) = rules.get(rule_index) {
+                rule_index += 1;
+
+                regs.verdict = &Verdict::Continue;
+                for expr in rule.exprs.iter() {
+                    expr.eval(&mut regs, ctx);
+                    if *regs.verdict != Verdict::Continue {
+                        break;
+                    }
+                }
+
+                if !matches!(regs.verdict, Verdict::Continue | Verdict::Break) {
+                    verdict = regs.verdict;
+                    break;
+                }
+            }
+
+            match verdict {
+                Verdict::Accept => return FilterVerdict::Accept,
+                Verdict::Drop => return FilterVerdict::Drop,
+                Verdict::Jump(name) | Verdict::Goto(name) => {
+                    let Some(target) = table.chain_index(name) else {
+                        return FilterVerdict::Drop;
+                    };
+                    if matches!(verdict, Verdict::Jump(_)) {
+                        if depth == NFT_JUMP_STACK_SIZE {
+                            return FilterVerdict::Drop;
+                        }
+                        stack[depth] = (chain_index, rule_index);
+                        depth += 1;
+                    }
+                    chain_index = target;
+                    rule_index = 0;
+                }
+                Verdict::Continue | Verdict::Break | Verdict::Return => {
+                    if depth > 0 {
+                        depth -= 1;
+                        (chain_index, rule_index) = stack[depth];
+                        continue;
+                    }
+
+                    let policy = table.chains[entry.chain].base.as_ref().unwrap().policy;
+                    return match policy {
+                        ChainPolicy::Accept => FilterVerdict::Accept,
+                        ChainPolicy::Drop => FilterVerdict::Drop,
+                    };
+                }
+            }
+        }
+    }
+}
+
+fn hook_slot(version: IpVersion, hook: FilterHook) -> usize {
+    let version_index = match version {
+        IpVersion::V4 => 0,
+        IpVersion::V6 => 1,
+    };
+    version_index * NUM_HOOKS + hook as usize
+}
+
+fn check_name(name: &str) -> Result<()> {
+    if name.is_empty() {
+        return_errno_with_message!(Errno::EINVAL, "the name is empty");
+    }
+    if name.len() >= NFT_NAME_MAXLEN {
+        return_errno_with_message!(Errno::ENAMETOOLONG, "the name is too long");
+    }
+    Ok(())
+}
+
+fn check_table_flags(flags: TableFlags) -> Result<()> {
+    if flags.contains(TableFlags::OWNER) {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "owned tables are not supported");
+    }
+    Ok(())
+}
+
+impl Table {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn family(&self) -> u8 {
+        self.family
+    }
+
+    pub fn flags(&self) -> TableFlags {
+        self.flags
+    }
+
+    pub fn handle(&self) -> u64 {
+        self.handle
+    }
+
+    pub fn userdata(&self) -> Option<&[u8]> {
+        self.userdata.as_deref()
+    }
+
+    /// Returns all the chains, in the order in which they are created.
+    pub fn chains(&self) -> &[Chain] {
+        &self.chains
+    }
+
+    /// Returns the number of the objects in the table.
+    pub fn use_count(&self) -> u32 {
+        self.chains.len() as u32
+    }
+
+    /// Sets the flags of the table (e.g., to make the table dormant).
+    pub fn set_flags(&mut self, flags: TableFlags) -> Result<()> {
+        check_table_flags(flags)?;
+        self.flags = flags;
+        Ok(())
+    }
+
+    fn alloc_handle(&mut self) -> u64 {
+        self.last_handle += 1;
+        self.last_handle
+    }
+
+    fn chain_index(&self, name: &str) -> Option<usize> {
+        self.chains.iter().position(|chain| chain.name == name)
+    }
+
+    /// Finds the chain by its name.
+    pub fn chain(&self, name: &str) -> Result<&Chain> {
+        self.chain_index(name)
+            .map(|index| &self.chains[index])
+            .ok_or_else(|| Error::with_message(Errno::ENOENT, "the chain does not exist"))
+    }
+
+    /// Finds the chain by its handle.
+    pub fn chain_by_handle(&self, handle: u64) -> Result<&Chain> {
+        self.chains
+            .iter()
+            .find(|chain| chain.handle == handle)
+            .ok_or_else(|| Error::with_message(Errno::ENOENT, "the chain does not exist"))
+    }
+
+    /// Returns the number of the jumps to the chain.
+    pub fn chain_use_count(&self, name: &str) -> u32 {
+        self.chains
+            .iter()
+            .flat_map(|chain| chain.jump_targets())
+            .filter(|target| *target == name)
+            .count() as u32
+    }
+
+    /// Adds a new chain.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/netfilter/nf_tables_api.c>
+    pub fn add_chain(
+        &mut self,
+        name: String,
+        base: Option<BaseChain>,
+        userdata: Option<Vec<u8>>,
+    ) -> Result<&Chain> {
+        check_name(&name)?;
+        if self.chain_index(&name).is_some() {
+            return_errno_with_message!(Errno::EEXIST, "the chain already exists");
+        }
+
+        // Like Linux, NAT chains cannot be attached to the forward hook.
+        if let Some(base) = base.as_ref()
+            && base.type_ == ChainType::Nat
+            && base.hook == FilterHook::Forward
+        {
+            return_errno_with_message!(
+                Errno::EOPNOTSUPP,
+                "NAT chains cannot be attached to the forward hook"
+            );
+        }
+
+        let handle = self.alloc_handle();
+        self.chains.push(Chain {
+            name,
+            handle,
+            base,
+            userdata,
+            rules: Vec::new(),
+        });
+        Ok(self.chains.last().unwrap())
+    }
+
+    /// Updates an existing chain.
+    ///
+    /// The hook, the priority, and the type of a base chain cannot be changed, so `base` must
+    /// match the existing ones if it is specified. Only the policy can be changed.
+    pub fn update_chain(
+        &mut self,
+        name: &str,
+        base: Option<BaseChain>,
+        policy: Option<ChainPolicy>,
+    ) -> Result<&Chain> {
+        let index = self
+            .chain_index(name)
+            .ok_or_else(|| Error::with_message(Errno::ENOENT, "the chain does not exist"))?;
+        let chain = &mut self.chains[index];
+
+        if let Some(new_base) = base {
+            let Some(old_base) = chain.base.as_ref() else {
+                return_errno_with_message!(Errno::EEXIST, "the chain is not a base chain");
+            };
+            if old_base.type_ != new_base.type_ {
+                return_errno_with_message!(Errno::EEXIST, "the chain has a different type");
+            }
+            if old_base.hook != new_base.hook || old_base.priority != new_base.priority {
+                return_errno_with_message!(
+                    Errno::EOPNOTSUPP,
+                    "the hook of a base chain cannot be changed"
+                );
+            }
+        }
+
+        if let Some(policy) = policy {
+            let Some(base) = chain.base.as_mut() else {
+                return_errno_with_message!(
+                    Errno::EOPNOTSUPP,
+                    "the policy of a regular chain cannot be set"
+                );
+            };
+            base.policy = policy;
+        }
+
+        Ok(chain)
+    }
+
+    /// Removes the chain, along with all its rules.
+    ///
+    /// This method fails with `EBUSY` if other chains jump to the chain.
+    pub fn remove_chain(&mut self, name: &str) -> Result<Chain> {
+        let index = self
+            .chain_index(name)
+            .ok_or_else(|| Error::with_message(Errno::ENOENT, "the chain does not exist"))?;
+        if self.chains.iter().enumerate().any(|(other, chain)| {
+            other != index && chain.jump_targets().any(|target| target == name)
+        }) {
+            return_errno_with_message!(Errno::EBUSY, "the chain is in use");
+        }
+
+        Ok(self.chains.remove(index))
+    }
+
+    /// Adds a new rule to the chain.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/netfilter/nf_tables_api.c>
+    pub fn add_rule(
+        &mut self,
+        chain: &str,
+        exprs: Vec<Expr>,
+        userdata: Option<Vec<u8>>,
+        position: RulePosition,
+    ) -> Result<Arc<Rule>> {
+        let index = self
+            .chain_index(chain)
+            .ok_or_else(|| Error::with_message(Errno::ENOENT, "the chain does not exist"))?;
+        self.check_exprs(index, &exprs)?;
+
+        let find_rule = |handle: u64| {
+            self.chains[index]
+                .rules
+                .iter()
+                .position(|rule| rule.handle == handle)
+                .ok_or_else(|| Error::with_message(Errno::ENOENT, "the rule does not exist"))
+        };
+        let (pos, is_replace) = match position {
+            RulePosition::Replace(handle) => (find_rule(handle)?, true),
+            RulePosition::Before(Some(handle)) => (find_rule(handle)?, false),
+            RulePosition::Before(None) => (0, false),
+            RulePosition::After(Some(handle)) => (find_rule(handle)? + 1, false),
+            RulePosition::After(None) => (self.chains[index].rules.len(), false),
+        };
+
+        let handle = match position {
+            RulePosition::Replace(handle) => handle,
+            _ => self.alloc_handle(),
+        };
+        let rule = Arc::new(Rule {
+            handle,
+            exprs,
+            userdata,
+        });
+
+        let rules = &mut self.chains[index].rules;
+        if is_replace {
+            rules[pos] = rule.clone();
+        } else {
+            rules.insert(pos, rule.clone());
+        }
+        Ok(rule)
+    }
+
+    /// Checks whether the expressions can be used in the chain.
+    fn check_exprs(&self, chain_index: usize, exprs: &[Expr]) -> Result<()> {
+        let chain = &self.chains[chain_index];
+
+        for expr in exprs.iter() {
+            if expr.is_nat()
+                && let Some(base) = chain.base.as_ref()
+                && (base.type_ != ChainType::Nat || !expr.is_valid_at(base.hook))
+            {
+                return_errno_with_message!(
+                    Errno::EOPNOTSUPP,
+                    "the NAT expression cannot be used in the chain"
+                );
+            }
+
+            let Expr::Verdict(verdict) = expr else {
+                continue;
+            };
+            let Some(target) = verdict.chain() else {
+                continue;
+            };
+            let target = self.chain(target)?;
+            if target.base.is_some() {
+                return_errno_with_message!(Errno::EOPNOTSUPP, "cannot jump to a base chain");
+            }
+            if target.name == chain.name || self.can_reach(&target.name, &chain.name) {
+                return_errno_with_message!(Errno::ELOOP, "the jump creates a loop");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns whether the chain `to` can be reached from the chain `from` via jumps.
+    fn can_reach(&self, from: &str, to: &str) -> bool {
+        let mut visited = BTreeSet::new();
+        let mut pending = vec![from];
+
+        while let Some(name) = pending.pop() {
+            if !visited.insert(name) {
+                continue;
+            }
+            let Ok(chain) = self.chain(name) else {
+                continue;
+            };
+            for target in chain.jump_targets() {
+                if target == to {
+                    return true;
+                }
+                pending.push(target);
+            }
+        }
+
+        false
+    }
+
+    /// Removes the rule from the chain.
+    pub fn remove_rule(&mut self, chain: &str, handle: u64) -> Result<Arc<Rule>> {
+        let index = self
+            .chain_index(chain)
+            .ok_or_else(|| Error::with_message(Errno::ENOENT, "the chain does not exist"))?;
+        let rules = &mut self.chains[index].rules;
+        let Some(pos) = rules.iter().position(|rule| rule.handle == handle) else {
+            return_errno_with_message!(Errno::ENOENT, "the rule does not exist");
+        };
+        Ok(rules.remove(pos))
+    }
+
+    /// Removes all the rules from the chain.
+    pub fn flush_chain(&mut self, chain: &str) -> Result<Vec<Arc<Rule>>> {
+        let index = self
+            .chain_index(chain)
+            .ok_or_else(|| Error::with_message(Errno::ENOENT, "the chain does not exist"))?;
+        Ok(core::mem::take(&mut self.chains[index].rules))
+    }
+}
+
+impl Chain {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn handle(&self) -> u64 {
+        self.handle
+    }
+
+    /// Returns the information of the base chain, or `None` if the chain is a regular chain.
+    pub fn base(&self) -> Option<&BaseChain> {
+        self.base.as_ref()
+    }
+
+    pub fn userdata(&self) -> Option<&[u8]> {
+        self.userdata.as_deref()
+    }
+
+    /// Returns all the rules, in the order in which they are evaluated.
+    pub fn rules(&self) -> &[Arc<Rule>] {
+        &self.rules
+    }
+
+    /// Finds the rule by its handle.
+    pub fn rule(&self, handle: u64) -> Result<&Arc<Rule>> {
+        self.rules
+            .iter()
+            .find(|rule| rule.handle == handle)
+            .ok_or_else(|| Error::with_message(Errno::ENOENT, "the rule does not exist"))
+    }
+
+    /// Returns the chains that the rules jump to.
+    fn jump_targets(&self) -> impl Iterator<Item = &str> {
+        self.rules
+            .iter()
+            .flat_map(|rule| rule.exprs.iter())
+            .filter_map(|expr| match expr {
+                Expr::Verdict(verdict) => verdict.chain(),
+                _ => None,
+            })
+    }
+}
+
+impl Rule {
+    pub fn handle(&self) -> u64 {
+        self.handle
+    }
+
+    pub fn exprs(&self) -> &[Expr] {
+        &self.exprs
+    }
+
+    pub fn userdata(&self) -> Option<&[u8]> {
+        self.userdata.as_deref()
+    }
+}
@@ -0,0 +1,203 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The routing table.
+//!
+//! Each network namespace has its own routing table (see [`NetNamespace`]), which corresponds to
+//! the main routing table (`RT_TABLE_MAIN`) in Linux. The routes are used to select the output
+//! interface and the source address for outgoing packets.
+//!
+//! [`NetNamespace`]: super::net_ns::NetNamespace
+
+use core::cmp::Reverse;
+
+use aster_bigtcp::wire::{IpAddress, IpCidr, Ipv4Address, Ipv4Cidr, Ipv6Address, Ipv6Cidr};
+
+use crate::prelude::*;
+
+/// A route in the routing table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Route {
+    /// The destination subnet.
+    pub dst: IpCidr,
+    /// The gateway, or `None` if the destination is directly reachable.
+    pub gateway: Option<IpAddress>,
+    /// The index of the output interface.
+    pub iface_index: u32,
+    /// The preferred source address.
+    pub pref_src: Option<IpAddress>,
+    /// The priority of the route. Routes with lower metrics are preferred.
+    pub metric: u32,
+    /// The origin of the route (e.g., [`RTPROT_KERNEL`]).
+    pub protocol: u8,
+    /// The distance to the destination (e.g., [`RT_SCOPE_LINK`]).
+    pub scope: u8,
+}
+
+/// The route is installed by the kernel.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/rtnetlink.h#L288>
+pub const RTPROT_KERNEL: u8 = 2;
+/// The route is installed during boot.
+pub const RTPROT_BOOT: u8 = 3;
+/// The route is learned from router advertisements.
+pub const RTPROT_RA: u8 = 9;
+
+/// The destination is anywhere.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/rtnetlink.h#L330>
+pub const RT_SCOPE_UNIVERSE: u8 = 0;
+/// The destination is on the directly attached link.
+pub const RT_SCOPE_LINK: u8 = 253;
+
+/// The default metric of IPv6 routes.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/net/ip6_route.h>
+pub const IP6_RT_PRIO_USER: u32 = 1024;
+/// The metric of IPv6 prefix routes added by the kernel.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/net/addrconf.h>
+const IP6_RT_PRIO_ADDRCONF: u32 = 256;
+
+impl Route {
+    /// Creates the prefix route for an address of an interface.
+    ///
+    /// A prefix route makes the subnet of the address directly reachable via the interface.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/ipv4/fib_frontend.c> (`fib_add_ifaddr`)
+    pub(super) fn new_prefix(ip_cidr: &IpCidr, iface_index: u32) -> Self {
+        let (metric, scope) = match ip_cidr {
+            IpCidr::Ipv4(_) => (0, RT_SCOPE_LINK),
+            IpCidr::Ipv6(_) => (IP6_RT_PRIO_ADDRCONF, RT_SCOPE_UNIVERSE),
+        };
+
+        Self {
+            dst: network_of(ip_cidr),
+            gateway: None,
+            iface_index,
+            pref_src: Some(ip_cidr.address()),
+            metric,
+            protocol: RTPROT_KERNEL,
+            scope,
+        }
+    }
+
+    /// Returns whether the route has the same key as the other route.
+    ///
+    /// The routing table cannot contain two routes with the same key.
+    pub fn has_same_key(&self, other: &Route) -> bool {
+        self.dst == other.dst && self.metric == other.metric
+    }
+}
+
+/// Returns the subnet that contains the address.
+pub fn network_of(ip_cidr: &IpCidr) -> IpCidr {
+    match ip_cidr {
+        IpCidr::Ipv4(ipv4_cidr) => {
+            let prefix_len = ipv4_cidr.prefix_len();
+            let mask = u32::MAX.checked_shl(32 - prefix_len as u32).unwrap_or(0);
+            let network = Ipv4Address::from_bits(ipv4_cidr.address().to_bits() & mask);
+            IpCidr::Ipv4(Ipv4Cidr::new(network, prefix_len))
+        }
+        IpCidr::Ipv6(ipv6_cidr) => {
+            let prefix_len = ipv6_cidr.prefix_len();
+            let mask = u128::MAX.checked_shl(128 - prefix_len as u32).unwrap_or(0);
+            let network = Ipv6Address::from_bits(ipv6_cidr.address().to_bits() & mask);
+            IpCidr::Ipv6(Ipv6Cidr::new(network, prefix_len))
+        }
+    }
+}
+
+/// Returns whether a prefix route should be created for the address.
+///
+/// Addresses with full-length prefixes (e.g., `/32` for IPv4) do not need prefix routes.
+pub(super) fn needs_prefix_route(ip_cidr: &IpCidr) -> bool {
+    match ip_cidr {
+        IpCidr::Ipv4(ipv4_cidr) => ipv4_cidr.prefix_len() < 32,
+        IpCidr::Ipv6(ipv6_cidr) => ipv6_cidr.prefix_len() < 128,
+    }
+}
+
+/// A routing table.
+pub struct RouteTable {
+    routes: Vec<Route>,
+}
+
+impl RouteTable {
+    /// Creates an empty routing table.
+    pub(super) const fn new() -> Self {
+        Self { routes: Vec::new() }
+    }
+
+    /// Returns all the routes in the table.
+    pub fn routes(&self) -> &[Route] {
+        &self.routes
+    }
+
+    /// Finds the route that has the same key as `route`.
+    pub fn find_same_key(&self, route: &Route) -> Option<&Route> {
+        self.routes.iter().find(|other| other.has_same_key(route))
+    }
+
+    /// Inserts a route, replacing the route with the same key if it exists.
+    pub fn insert(&mut self, route: Route) {
+        if let Some(other) = self
+            .routes
+            .iter_mut()
+            .find(|other| other.has_same_key(&route))
+        {
+            *other = route;
+        } else {
+            self.routes.push(route);
+        }
+    }
+
+    /// Removes the first route that satisfies the predicate.
+    pub fn remove_first<F>(&mut self, mut predicate: F) -> Option<Route>
+    where
+        F: FnMut(&Route) -> bool,
+    {
+        let pos = self.routes.iter().position(|route| predicate(route))?;
+        Some(self.routes.remove(pos))
+    }
+
+    /// Removes all the routes that satisfy the predicate.
+    pub(super) fn remove_all<F>(&mut self, mut predicate: F)
+    where
+        F: FnMut(&Route) -> bool,
+    {
+        self.routes.retain(|route| !predicate(route));
+    }
+
+    /// Adds the prefix route for an address of an interface, if it does not exist.
+    pub(super) fn add_prefix_route(&mut self, ip_cidr: &IpCidr, iface_index: u32) {
+        if !needs_prefix_route(ip_cidr) {
+            return;
+        }
+
+        let route = Route::new_prefix(ip_cidr, iface_index);
+        if self
+            .routes
+            .iter()
+            .any(|other| other.has_same_key(&route) && other.iface_index == iface_index)
+        {
+            return;
+        }
+        self.routes.push(route);
+    }
+
+    /// Looks up the best route to the destination.
+    ///
+    /// Among the routes that contain the destination and that are accepted by `filter_map`, the
+    /// route with the longest prefix is selected. If there is a tie, the route with the lowest
+    /// metric is selected.
+    pub(super) fn lookup<F, T>(&self, dst: &IpAddress, mut filter_map: F) -> Option<(&Route, T)>
+    where
+        F: FnMut(&Route) -> Option<T>,
+    {
+        self.routes
+            .iter()
+            .filter(|route| route.dst.contains_addr(dst))
+            .filter_map(|route| filter_map(route).map(|value| (route, value)))
+            .min_by_key(|(route, _)| (Reverse(route.dst.prefix_len()), route.metric))
+    }
+}
@@ -56,6 +56,84 @@ impl IpAddressFamily {
             IpAddressFamily::IPv6 => UNSPECIFIED_LOCAL_ENDPOINT_V6,
         }
     }
+
+    /// Converts a socket address to the local endpoint to which a socket of this family binds.
+    ///
+    /// For IPv6 sockets, an IPv4-mapped IPv6 address is converted to an IPv4 endpoint unless
+    /// `v6only` is true.
+    pub(super) fn local_endpoint_from(
+        &self,
+        socket_addr: SocketAddr,
+        v6only: bool,
+    ) -> Result<IpEndpoint> {
+        match (self, socket_addr) {
+            (IpAddressFamily::IPv4, SocketAddr::IPv4(addr, port)) => {
+                Ok(IpEndpoint::new(addr.into(), port))
+            }
+            (IpAddressFamily::IPv6, SocketAddr::IPv6(addr, port)) => match addr.to_ipv4_mapped() {
+                None => Ok(IpEndpoint::new(addr.into(), port)),
+                Some(_) if v6only => return_errno_with_message!(
+                    Errno::EINVAL,
+                    "IPv4-mapped addresses cannot be bound to IPv6-only sockets"
+                ),
+                Some(ipv4_addr) => Ok(IpEndpoint::new(ipv4_addr.into(), port)),
+            },
+            // Linux checks the address length first, and `sockaddr_in` is shorter than the
+            // minimum length of `sockaddr_in6`.
+            (IpAddressFamily::IPv6, SocketAddr::IPv4(..)) => {
+                return_errno_with_message!(Errno::EINVAL, "the socket address is too short")
+            }
+            _ => return_errno_with_message!(
+                Errno::EAFNOSUPPORT,
+                "the protocol family does not match the address family"
+            ),
+        }
+    }
+
+    /// Converts a socket address to the remote endpoint to which a socket of this family sends.
+    ///
+    /// For IPv6 sockets, an IPv4 address or an IPv4-mapped IPv6 address is converted to an IPv4
+    /// endpoint unless `v6only` is true.
+    //
+    // Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/ipv6/udp.c>
+    pub(super) fn remote_endpoint_from(
+        &self,
+        socket_addr: SocketAddr,
+        v6only: bool,
+    ) -> Result<IpEndpoint> {
+        match (self, socket_addr) {
+            (IpAddressFamily::IPv4, SocketAddr::IPv4(addr, port)) => {
+                Ok(IpEndpoint::new(addr.into(), port))
+            }
+            (IpAddressFamily::IPv6, SocketAddr::IPv6(addr, port)) => match addr.to_ipv4_mapped() {
+                None => Ok(IpEndpoint::new(addr.into(), port)),
+                Some(_) if v6only => return_errno_with_message!(
+                    Errno::ENETUNREACH,
+                    "IPv4-mapped addresses are not reachable from IPv6-only sockets"
+                ),
+                Some(ipv4_addr) => Ok(IpEndpoint::new(ipv4_addr.into(), port)),
+            },
+            (IpAddressFamily::IPv6, SocketAddr::IPv4(addr, port)) if !v6only => {
+                Ok(IpEndpoint::new(addr.into(), port))
+            }
+            _ => return_errno_with_message!(
+                Errno::EAFNOSUPPORT,
+                "the protocol family does not match the address family"
+            ),
+        }
+    }
+
+    /// Converts an endpoint to the socket address reported to the user space.
+    ///
+    /// For IPv6 sockets, an IPv4 endpoint is reported as an IPv4-mapped IPv6 address.
+    pub(super) fn socket_addr_from(&self, endpoint: IpEndpoint) -> SocketAddr {
+        match (self, endpoint.addr) {
+            (IpAddressFamily::IPv6, IpAddress::Ipv4(addr)) => {
+                SocketAddr::IPv6(addr.to_ipv6_mapped(), endpoint.port)
+            }
+            _ => endpoint.into(),
+        }
+    }
 }
 
 // Note: This does not handle IPv4-mapped IPv6 addresses. When `IPV6_V6ONLY` is set,
@@ -7,87 +7,44 @@ use aster_bigtcp::{
 };
 
 use crate::{
-    net::{
-        iface::{Iface, iter_all_ifaces, loopback_iface, virtio_iface},
-        socket::util::check_port_privilege,
-    },
+    net::{iface::Iface, net_ns::NetNamespace, socket::util::check_port_privilege},
     prelude::*,
 };
 
-fn get_iface_to_bind(ip_addr: &IpAddress) -> Option<Arc<Iface>> {
-    match *ip_addr {
-        IpAddress::Ipv4(ipv4_addr) => iter_all_ifaces()
-            .find(|iface| iface.ipv4_addr().is_some_and(|addr| addr == ipv4_addr))
-            .map(Clone::clone),
-        IpAddress::Ipv6(ipv6_addr) => iter_all_ifaces()
-            .find(|iface| iface.ipv6_addr().is_some_and(|addr| addr == ipv6_addr))
-            .map(Clone::clone),
-    }
-}
-
-/// Get a suitable iface to deal with sendto/connect request if the socket is not bound to an iface.
-/// If the remote address is the same as that of some iface, we will use the iface.
-/// Otherwise, we will use a default interface.
-fn get_ephemeral_iface(remote_ip_addr: &IpAddress) -> Arc<Iface> {
-    match remote_ip_addr {
-        IpAddress::Ipv4(remote_ipv4_addr) => {
-            if let Some(iface) = iter_all_ifaces().find(|iface| {
-                iface
-                    .ipv4_addr()
-                    .is_some_and(|addr| addr == *remote_ipv4_addr)
-            }) {
-                return iface.clone();
-            }
-
-            // FIXME: Instead of hardcoding the rules here, we should choose the
-            // default interface according to the routing table.
-            if let Some(virtio_iface) = virtio_iface() {
-                virtio_iface.clone()
-            } else {
-                loopback_iface().clone()
-            }
-        }
-        IpAddress::Ipv6(remote_ipv6_addr) => {
-            if let Some(iface) = iter_all_ifaces().find(|iface| {
-                iface
-                    .ipv6_addr()
-                    .is_some_and(|addr| addr == *remote_ipv6_addr)
-            }) {
-                return iface.clone();
-            }
-
-            // Fall back to an interface with an IPv6 address.
-            // Prefer virtio over loopback for external traffic.
-            if let Some(virtio_iface) = virtio_iface()
-                && virtio_iface.ipv6_addr().is_some()
-            {
-                return virtio_iface.clone();
-            }
-
-            loopback_iface().clone()
-        }
-    }
+fn get_iface_to_bind(ip_addr: &IpAddress, net_ns: &NetNamespace) -> Option<Arc<Iface>> {
+    net_ns
+        .ifaces()
+        .into_iter()
+        .find(|iface| iface.has_ip_addr(*ip_addr))
 }
 
 pub(super) fn resolve_bind_iface_and_config(
     endpoint: &IpEndpoint,
     can_reuse: bool,
+    net_ns: &NetNamespace,
 ) -> Result<(Arc<Iface>, BindPortConfig)> {
     check_port_privilege(endpoint.port)?;
 
-    let iface = match get_iface_to_bind(&endpoint.addr) {
-        Some(iface) => iface,
+    let iface = resolve_bind_iface(&endpoint.addr, net_ns)?;
+    let bind_port_config = BindPortConfig::new(*endpoint, can_reuse);
+
+    Ok((iface, bind_port_config))
+}
+
+/// Resolves the iface to which a socket binds.
+///
+/// Unlike [`resolve_bind_iface_and_config`], this does not check the port privilege, so it is
+/// suitable for sockets whose "ports" are not real ports (e.g., raw IP sockets).
+pub(super) fn resolve_bind_iface(ip_addr: &IpAddress, net_ns: &NetNamespace) -> Result<Arc<Iface>> {
+    match get_iface_to_bind(ip_addr, net_ns) {
+        Some(iface) => Ok(iface),
         None => {
             return_errno_with_message!(
                 Errno::EADDRNOTAVAIL,
                 "the address is not available from the local machine"
             );
         }
-    };
-
-    let bind_port_config = BindPortConfig::new(*endpoint, can_reuse);
-
-    Ok((iface, bind_port_config))
+    }
 }
 
 impl From<BindError> for Error {
@@ -103,16 +60,14 @@ impl From<BindError> for Error {
     }
 }
 
-pub(super) fn get_ephemeral_endpoint(remote_endpoint: &IpEndpoint) -> Option<IpEndpoint> {
-    let iface = get_ephemeral_iface(&remote_endpoint.addr);
-    match remote_endpoint.addr {
-        IpAddress::Ipv4(_) => {
-            let ip_addr = iface.ipv4_addr()?;
-            Some(IpEndpoint::new(IpAddress::Ipv4(ip_addr), 0))
-        }
-        IpAddress::Ipv6(_) => {
-            let ipv6_addr = iface.ipv6_addr()?;
-            Some(IpEndpoint::new(IpAddress::Ipv6(ipv6_addr), 0))
-        }
-    }
+/// Gets a suitable local endpoint to deal with sendto/connect requests if the socket is not bound.
+///
+/// The IP address of the endpoint is the source address selected by the routing table, and the
+/// port is left unspecified.
+pub(super) fn get_ephemeral_endpoint(
+    remote_endpoint: &IpEndpoint,
+    net_ns: &NetNamespace,
+) -> Result<IpEndpoint> {
+    let (_, src_addr) = net_ns.route_output(&remote_endpoint.addr)?;
+    Ok(IpEndpoint::new(src_addr, 0))
 }
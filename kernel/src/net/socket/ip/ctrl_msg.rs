@@ -0,0 +1,387 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::time::Duration;
+
+use aster_bigtcp::{
+    socket::UdpIcmpError,
+    wire::{IpAddress, Ipv4Address, Ipv6Address},
+};
+use ostd::timer::Jiffies;
+
+use crate::{
+    net::socket::util::{CControlHeader, options::TimestampFormat},
+    prelude::*,
+    time::{Clock, clocks::RealTimeClock, timespec_t, timeval_t},
+    util::net::{CSocketAddrInet, CSocketAddrInet6, CSocketOptionLevel},
+};
+
+#[derive(Debug)]
+pub struct IpControlMessage(Message);
+
+#[derive(Debug)]
+enum Message {
+    IpPktInfo(CInPktInfo),
+    IpTtl(i32),
+    IpTos(u8),
+    IpRecvErr(CIpRecvErr),
+    Ipv6PktInfo(CIpv6PktInfo),
+    Ipv6RecvErr(CIpv6RecvErr),
+    Timestamp(timeval_t),
+    TimestampNs(timespec_t),
+    UdpSegment(u16),
+    TcpInq(i32),
+}
+
+impl IpControlMessage {
+    /// Creates an `IP_PKTINFO` message with the interface index, the local address, and the
+    /// destination address.
+    pub(super) fn new_ip_pktinfo(
+        ifindex: u32,
+        local_addr: Ipv4Address,
+        dst_addr: Ipv4Address,
+    ) -> Self {
+        Self(Message::IpPktInfo(CInPktInfo {
+            ifindex: ifindex as i32,
+            spec_dst: local_addr.octets(),
+            addr: dst_addr.octets(),
+        }))
+    }
+
+    /// Creates an `IP_TTL` message with the TTL of the received packet.
+    pub(super) fn new_ip_ttl(ttl: u8) -> Self {
+        Self(Message::IpTtl(ttl as i32))
+    }
+
+    /// Creates an `IP_TOS` message with the TOS of the received packet.
+    pub(super) fn new_ip_tos(tos: u8) -> Self {
+        Self(Message::IpTos(tos))
+    }
+
+    /// Creates a `TCP_CM_INQ` message with the number of bytes that are available to receive.
+    pub(super) fn new_tcp_inq(inq: i32) -> Self {
+        Self(Message::TcpInq(inq))
+    }
+
+    /// Creates an `IPV6_PKTINFO` message with the destination address and the interface index.
+    pub(super) fn new_ipv6_pktinfo(addr: Ipv6Address, ifindex: u32) -> Self {
+        Self(Message::Ipv6PktInfo(CIpv6PktInfo {
+            addr: addr.octets(),
+            ifindex,
+        }))
+    }
+
+    /// Creates an `SO_TIMESTAMP` or `SO_TIMESTAMPNS` message with the time when the packet was
+    /// received.
+    ///
+    /// The time is measured since boot and is converted to the real time.
+    pub(super) fn new_timestamp(recv_time: Duration, format: TimestampFormat) -> Self {
+        let elapsed = Jiffies::elapsed().as_duration().saturating_sub(recv_time);
+        let timestamp = RealTimeClock::get().read_time().saturating_sub(elapsed);
+
+        match format {
+            TimestampFormat::Timeval => Self(Message::Timestamp(timestamp.into())),
+            TimestampFormat::Timespec => Self(Message::TimestampNs(timestamp.into())),
+        }
+    }
+
+    /// Creates an `IP_RECVERR` or `IPV6_RECVERR` message that describes the ICMP error.
+    pub(super) fn new_recverr(error: &UdpIcmpError) -> Self {
+        match error.offender {
+            IpAddress::Ipv4(offender) => Self(Message::IpRecvErr(CIpRecvErr {
+                ee: CSockExtendedErr::new(
+                    icmp_err_convert(error.icmp_type, error.icmp_code).0,
+                    SO_EE_ORIGIN_ICMP,
+                    error.icmp_type,
+                    error.icmp_code,
+                ),
+                offender: CSocketAddrInet::from((offender, 0)),
+            })),
+            IpAddress::Ipv6(offender) => Self(Message::Ipv6RecvErr(CIpv6RecvErr {
+                ee: CSockExtendedErr::new(
+                    icmpv6_err_convert(error.icmp_type, error.icmp_code).0,
+                    SO_EE_ORIGIN_ICMP6,
+                    error.icmp_type,
+                    error.icmp_code,
+                ),
+                offender: CSocketAddrInet6::from((offender, 0)),
+            })),
+        }
+    }
+
+    /// Returns the source address and the interface index specified by `IP_PKTINFO` or
+    /// `IPV6_PKTINFO`.
+    ///
+    /// The source address may be unspecified, and the interface index may be zero.
+    pub(super) fn pktinfo(&self) -> Option<(IpAddress, u32)> {
+        match &self.0 {
+            Message::IpPktInfo(pktinfo) => Some((
+                IpAddress::Ipv4(Ipv4Address::from(pktinfo.spec_dst)),
+                pktinfo.ifindex as u32,
+            )),
+            Message::Ipv6PktInfo(pktinfo) => Some((
+                IpAddress::Ipv6(Ipv6Address::from(pktinfo.addr)),
+                pktinfo.ifindex,
+            )),
+            _ => None,
+        }
+    }
+
+    /// Returns the segment size specified by `UDP_SEGMENT`.
+    pub(super) fn udp_segment(&self) -> Option<u16> {
+        match &self.0 {
+            Message::UdpSegment(segment_size) => Some(*segment_size),
+            _ => None,
+        }
+    }
+
+    pub fn read_from(header: &CControlHeader, reader: &mut VmReader) -> Result<Option<Self>> {
+        use CSocketOptionLevel::{SOL_IP, SOL_IPV6, SOL_UDP};
+
+        let message = match (header.level(), header.type_()) {
+            (Some(SOL_IP), IP_PKTINFO) => Message::IpPktInfo(read_payload_from(header, reader)?),
+            (Some(SOL_IPV6), IPV6_PKTINFO) => {
+                Message::Ipv6PktInfo(read_payload_from(header, reader)?)
+            }
+            (Some(SOL_UDP), UDP_SEGMENT) => Message::UdpSegment(read_payload_from(header, reader)?),
+            _ => {
+                warn!("unsupported control message type in {:?}", header);
+                reader.skip(header.payload_len());
+                return Ok(None);
+            }
+        };
+
+        Ok(Some(Self(message)))
+    }
+
+    pub fn write_to(&self, writer: &mut VmWriter) -> Result<CControlHeader> {
+        use CSocketOptionLevel::{SOL_IP, SOL_IPV6, SOL_SOCKET, SOL_TCP};
+
+        match &self.0 {
+            Message::IpPktInfo(pktinfo) => {
+                write_payload_to(SOL_IP, IP_PKTINFO, pktinfo.as_bytes(), writer)
+            }
+            Message::IpTtl(ttl) => write_payload_to(SOL_IP, IP_TTL, ttl.as_bytes(), writer),
+            Message::IpTos(tos) => write_payload_to(SOL_IP, IP_TOS, tos.as_bytes(), writer),
+            Message::IpRecvErr(recverr) => {
+                write_payload_to(SOL_IP, IP_RECVERR, recverr.as_bytes(), writer)
+            }
+            Message::Ipv6PktInfo(pktinfo) => {
+                write_payload_to(SOL_IPV6, IPV6_PKTINFO, pktinfo.as_bytes(), writer)
+            }
+            Message::Ipv6RecvErr(recverr) => {
+                write_payload_to(SOL_IPV6, IPV6_RECVERR, recverr.as_bytes(), writer)
+            }
+            Message::Timestamp(timeval) => {
+                write_payload_to(SOL_SOCKET, SO_TIMESTAMP_OLD, timeval.as_bytes(), writer)
+            }
+            Message::TimestampNs(timespec) => {
+                write_payload_to(SOL_SOCKET, SO_TIMESTAMPNS_OLD, timespec.as_bytes(), writer)
+            }
+            Message::UdpSegment(_) => {
+                unreachable!("`UDP_SEGMENT` messages are never received")
+            }
+            Message::TcpInq(inq) => write_payload_to(SOL_TCP, TCP_CM_INQ, inq.as_bytes(), writer),
+        }
+    }
+}
+
+fn read_payload_from<T: Pod>(header: &CControlHeader, reader: &mut VmReader) -> Result<T> {
+    if header.payload_len() != size_of::<T>() {
+        return_errno_with_message!(Errno::EINVAL, "the size of the control message is invalid");
+    }
+
+    Ok(reader.read_val::<T>()?)
+}
+
+fn write_payload_to(
+    level: CSocketOptionLevel,
+    type_: i32,
+    payload: &[u8],
+    writer: &mut VmWriter,
+) -> Result<CControlHeader> {
+    let payload_len = payload
+        .len()
+        .min(CControlHeader::payload_len_from_total(writer.avail())?);
+    if payload_len != payload.len() {
+        warn!("setting MSG_CTRUNC is not supported");
+    }
+
+    let header = CControlHeader::new(level, type_, payload_len);
+    writer.write_val(&header)?;
+    writer.write_fallible(&mut VmReader::from(&payload[..payload_len]))?;
+
+    Ok(header)
+}
+
+// Control message types.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/in.h#L94>,
+// <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/in6.h#L228>,
+// <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/udp.h#L30>,
+// <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/tcp.h>, and
+// <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/asm-generic/socket.h>.
+
+const IP_TOS: i32 = 1;
+const IP_TTL: i32 = 2;
+const IP_PKTINFO: i32 = 8;
+const IP_RECVERR: i32 = 11;
+
+const IPV6_RECVERR: i32 = 25;
+const IPV6_PKTINFO: i32 = 50;
+
+const UDP_SEGMENT: i32 = 103;
+
+const TCP_CM_INQ: i32 = 36;
+
+const SO_TIMESTAMP_OLD: i32 = 29;
+const SO_TIMESTAMPNS_OLD: i32 = 35;
+
+/// `struct in_pktinfo` in Linux.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/in.h>.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct CInPktInfo {
+    ifindex: i32,
+    spec_dst: [u8; 4],
+    addr: [u8; 4],
+}
+
+/// `struct in6_pktinfo` in Linux.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/ipv6.h#L22>.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct CIpv6PktInfo {
+    addr: [u8; 16],
+    ifindex: u32,
+}
+
+/// `struct sock_extended_err` in Linux.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/errqueue.h>.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct CSockExtendedErr {
+    errno: u32,
+    origin: u8,
+    type_: u8,
+    code: u8,
+    pad: u8,
+    info: u32,
+    data: u32,
+}
+
+const SO_EE_ORIGIN_ICMP: u8 = 2;
+const SO_EE_ORIGIN_ICMP6: u8 = 3;
+
+impl CSockExtendedErr {
+    fn new(errno: Errno, origin: u8, type_: u8, code: u8) -> Self {
+        Self {
+            errno: errno as u32,
+            origin,
+            type_,
+            code,
+            pad: 0,
+            info: 0,
+            data: 0,
+        }
+    }
+}
+
+/// The payload of `IP_RECVERR` messages, which is followed by the address of the offender.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/ipv4/ip_sockglue.c>.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct CIpRecvErr {
+    ee: CSockExtendedErr,
+    offender: CSocketAddrInet,
+}
+
+/// The payload of `IPV6_RECVERR` messages, which is followed by the address of the offender.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/ipv6/datagram.c>.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct CIpv6RecvErr {
+    ee: CSockExtendedErr,
+    offender: CSocketAddrInet6,
+}
+
+/// Converts an ICMP error reported to a UDP socket to the error code.
+///
+/// Returns the error code and whether the error is a hard error. Hard errors are reported to
+/// connected sockets even if `IP_RECVERR` or `IPV6_RECVERR` is not set.
+pub(super) fn icmp_error_to_errno(error: &UdpIcmpError) -> (Errno, bool) {
+    match error.offender {
+        IpAddress::Ipv4(_) => icmp_err_convert(error.icmp_type, error.icmp_code),
+        IpAddress::Ipv6(_) => icmpv6_err_convert(error.icmp_type, error.icmp_code),
+    }
+}
+
+/// Converts an ICMP error to the error code and whether it is a hard error.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/ipv4/icmp.c> and
+/// <https://elixir.bootlin.com/linux/v6.18/source/net/ipv4/udp.c>.
+fn icmp_err_convert(icmp_type: u8, icmp_code: u8) -> (Errno, bool) {
+    const ICMP_DEST_UNREACH: u8 = 3;
+    const ICMP_PARAMETERPROB: u8 = 12;
+
+    const DEST_UNREACH_ERRNOS: [(Errno, bool); 16] = [
+        (Errno::ENETUNREACH, false),  // ICMP_NET_UNREACH
+        (Errno::EHOSTUNREACH, false), // ICMP_HOST_UNREACH
+        (Errno::ENOPROTOOPT, true),   // ICMP_PROT_UNREACH
+        (Errno::ECONNREFUSED, true),  // ICMP_PORT_UNREACH
+        (Errno::EMSGSIZE, true),      // ICMP_FRAG_NEEDED
+        (Errno::EOPNOTSUPP, false),   // ICMP_SR_FAILED
+        (Errno::ENETUNREACH, true),   // ICMP_NET_UNKNOWN
+        (Errno::EHOSTDOWN, true),     // ICMP_HOST_UNKNOWN
+        (Errno::ENONET, true),        // ICMP_HOST_ISOLATED
+        (Errno::ENETUNREACH, true),   // ICMP_NET_ANO
+        (Errno::EHOSTUNREACH, true),  // ICMP_HOST_ANO
+        (Errno::ENETUNREACH, false),  // ICMP_NET_UNR_TOS
+        (Errno::EHOSTUNREACH, false), // ICMP_HOST_UNR_TOS
+        (Errno::EHOSTUNREACH, true),  // ICMP_PKT_FILTERED
+        (Errno::EHOSTUNREACH, true),  // ICMP_PREC_VIOLATION
+        (Errno::EHOSTUNREACH, true),  // ICMP_PREC_CUTOFF
+    ];
+
+    match icmp_type {
+        ICMP_DEST_UNREACH => DEST_UNREACH_ERRNOS
+            .get(icmp_code as usize)
+            .copied()
+            .unwrap_or((Errno::EHOSTUNREACH, false)),
+        ICMP_PARAMETERPROB => (Errno::EPROTO, true),
+        _ => (Errno::EHOSTUNREACH, false),
+    }
+}
+
+/// Converts an ICMPv6 error to the error code and whether it is a hard error.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/ipv6/icmp.c>.
+fn icmpv6_err_convert(icmp_type: u8, icmp_code: u8) -> (Errno, bool) {
+    const ICMPV6_DEST_UNREACH: u8 = 1;
+    const ICMPV6_PKT_TOOBIG: u8 = 2;
+    const ICMPV6_PARAMPROB: u8 = 4;
+
+    const DEST_UNREACH_ERRNOS: [(Errno, bool); 7] = [
+        (Errno::ENETUNREACH, false),  // ICMPV6_NOROUTE
+        (Errno::EACCES, true),        // ICMPV6_ADM_PROHIBITED
+        (Errno::EHOSTUNREACH, false), // ICMPV6_NOT_NEIGHBOUR
+        (Errno::EHOSTUNREACH, false), // ICMPV6_ADDR_UNREACH
+        (Errno::ECONNREFUSED, true),  // ICMPV6_PORT_UNREACH
+        (Errno::EACCES, true),        // ICMPV6_POLICY_FAIL
+        (Errno::EACCES, true),        // ICMPV6_REJECT_ROUTE
+    ];
+
+    match icmp_type {
+        ICMPV6_DEST_UNREACH => DEST_UNREACH_ERRNOS
+            .get(icmp_code as usize)
+            .copied()
+            .unwrap_or((Errno::EPROTO, true)),
+        ICMPV6_PKT_TOOBIG => (Errno::EMSGSIZE, false),
+        ICMPV6_PARAMPROB => (Errno::EPROTO, true),
+        _ => (Errno::EHOSTUNREACH, false),
+    }
+}
@@ -2,19 +2,28 @@
 
 use aster_bigtcp::{
     errors::udp::{RecvError, SendError},
-    wire::IpEndpoint,
+    socket::{UdpIcmpError, UdpMulticastOption, UdpRecvInfo},
+    wire::{IPV4_HEADER_LEN, IPV6_HEADER_LEN, IpAddress, IpEndpoint, UDP_HEADER_LEN},
 };
 
 use crate::{
     events::IoEvents,
     net::{
         iface::{BoundUdpPort, Iface, UdpSocket},
-        socket::util::{SendRecvFlags, datagram_common},
+        socket::{
+            ip::{IpAddressFamily, ctrl_msg::icmp_error_to_errno},
+            util::{SendRecvFlags, datagram_common},
+        },
     },
     prelude::*,
     util::{MultiRead, MultiWrite},
 };
 
+/// The maximum number of segments that can be sent with `UDP_SEGMENT`.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/linux/udp.h>.
+const UDP_MAX_SEGMENTS: usize = 1 << 7;
+
 pub(super) struct BoundDatagram {
     bound_socket: UdpSocket,
     remote_endpoint: Option<IpEndpoint>,
@@ -35,40 +44,45 @@ impl BoundDatagram {
     pub(super) fn bound_port(&self) -> &BoundUdpPort {
         self.bound_socket.bound_port()
     }
-}
-
-impl datagram_common::Bound for BoundDatagram {
-    type Endpoint = IpEndpoint;
 
-    fn local_endpoint(&self) -> Self::Endpoint {
-        self.bound_socket.local_endpoint().unwrap()
-    }
-
-    fn remote_endpoint(&self) -> Option<&Self::Endpoint> {
-        self.remote_endpoint.as_ref()
+    pub(super) fn set_multicast_option(&self, option: UdpMulticastOption) {
+        self.bound_socket.set_multicast_option(option);
     }
 
-    fn set_remote_endpoint(&mut self, endpoint: &Self::Endpoint) {
-        self.remote_endpoint = Some(*endpoint)
+    pub(super) fn set_icmp_error_enabled(&self, is_enabled: bool) {
+        self.bound_socket.set_icmp_error_enabled(is_enabled);
     }
 
-    fn try_recv(
+    /// Receives a datagram.
+    ///
+    /// On success, this method returns the number of received bytes, the source endpoint, the
+    /// destination address, and other information of the datagram.
+    pub(super) fn try_recv_with_dst(
         &self,
         writer: &mut dyn MultiWrite,
         _flags: SendRecvFlags,
-    ) -> Result<(usize, Self::Endpoint)> {
-        let result = self.bound_socket.recv(|packet, udp_metadata| {
+    ) -> Result<(usize, IpEndpoint, IpAddress, UdpRecvInfo)> {
+        let result = self.bound_socket.recv(|packet, udp_metadata, recv_info| {
             let copied_res = writer
                 .write(&mut VmReader::from(packet))
                 .map_err(Into::into);
             let endpoint = udp_metadata.endpoint;
-            (copied_res, endpoint)
+            let dst_addr = udp_metadata.local_address;
+            (copied_res, endpoint, dst_addr, recv_info)
         });
 
         match result {
-            Ok((Ok(res), endpoint)) => Ok((res, endpoint)),
-            Ok((Err(e), _)) => Err(e),
+            Ok((Ok(res), endpoint, dst_addr, recv_info)) => {
+                let dst_addr =
+                    dst_addr.unwrap_or_else(|| self.bound_socket.local_endpoint().unwrap().addr);
+                Ok((res, endpoint, dst_addr, recv_info))
+            }
+            Ok((Err(e), _, _, _)) => Err(e),
             Err(RecvError::Exhausted) => {
+                // Like Linux, the pending error is reported only if there are no packets.
+                if let Some(error) = self.test_and_clear_error() {
+                    return Err(error);
+                }
                 return_errno_with_message!(Errno::EAGAIN, "the receive buffer is empty")
             }
             Err(RecvError::Truncated) => {
@@ -77,24 +91,86 @@ impl datagram_common::Bound for BoundDatagram {
         }
     }
 
-    fn try_send(
+    /// Receives an ICMP error from the error queue.
+    ///
+    /// On success, this method returns the number of received bytes of the payload that causes
+    /// the error, and the error itself.
+    pub(super) fn try_recv_error(
+        &self,
+        writer: &mut dyn MultiWrite,
+    ) -> Result<(usize, UdpIcmpError)> {
+        let Some(error) = self.bound_socket.recv_icmp_error() else {
+            return_errno_with_message!(Errno::EAGAIN, "the error queue is empty");
+        };
+
+        let copied_bytes = writer.write(&mut VmReader::from(error.payload.as_slice()))?;
+        Ok((copied_bytes, error))
+    }
+
+    /// Sends a datagram, or multiple datagrams if the segment size is specified.
+    ///
+    /// On success, this method returns the total number of sent bytes.
+    pub(super) fn try_send_segments(
         &self,
         reader: &mut dyn MultiRead,
-        remote: &Self::Endpoint,
-        _flags: SendRecvFlags,
+        remote: &IpEndpoint,
+        segment_size: Option<u16>,
+        flags: SendRecvFlags,
     ) -> Result<usize> {
-        let result = self
-            .bound_socket
-            .send(reader.sum_lens(), *remote, |socket_buffer| {
-                // FIXME: If copy failed, we should not send any packet.
-                // But current smoltcp API seems not to support this behavior.
-                reader
-                    .read(&mut VmWriter::from(socket_buffer))
-                    .inspect_err(|e| {
-                        warn!("unexpected UDP packet {e:#?} will be sent");
-                    })
-                    .map_err(Into::into)
-            });
+        let Some(segment_size) = segment_size.filter(|size| *size != 0) else {
+            return datagram_common::Bound::try_send(self, reader, remote, flags);
+        };
+        let segment_size = segment_size as usize;
+
+        let total_len = reader.sum_lens();
+        if total_len > segment_size * UDP_MAX_SEGMENTS {
+            return_errno_with_message!(Errno::EINVAL, "too many segments are required");
+        }
+
+        let header_len = match self.family() {
+            IpAddressFamily::IPv4 => IPV4_HEADER_LEN,
+            IpAddressFamily::IPv6 => IPV6_HEADER_LEN,
+        } + UDP_HEADER_LEN;
+        if header_len + segment_size > self.iface().mtu() {
+            return_errno_with_message!(Errno::EINVAL, "the segment size exceeds the MTU");
+        }
+
+        let mut sent_bytes = 0;
+        loop {
+            let len = (total_len - sent_bytes).min(segment_size);
+            // FIXME: Linux sends all the segments atomically. Here, if the send buffer becomes
+            // full in the middle, only the first few segments are sent.
+            match self.try_send_len(reader, remote, len) {
+                Ok(len) => sent_bytes += len,
+                Err(_) if sent_bytes > 0 => break,
+                Err(err) => return Err(err),
+            }
+
+            if sent_bytes >= total_len {
+                break;
+            }
+        }
+
+        Ok(sent_bytes)
+    }
+
+    /// Sends a datagram with `len` bytes from the reader.
+    fn try_send_len(
+        &self,
+        reader: &mut dyn MultiRead,
+        remote: &IpEndpoint,
+        len: usize,
+    ) -> Result<usize> {
+        let result = self.bound_socket.send(len, *remote, |socket_buffer| {
+            // FIXME: If copy failed, we should not send any packet.
+            // But current smoltcp API seems not to support this behavior.
+            reader
+                .read(&mut VmWriter::from(socket_buffer))
+                .inspect_err(|e| {
+                    warn!("unexpected UDP packet {e:#?} will be sent");
+                })
+                .map_err(Into::into)
+        });
 
         match result {
             Ok(inner) => inner,
@@ -110,8 +186,83 @@ impl datagram_common::Bound for BoundDatagram {
         }
     }
 
+    /// Tests and clears the pending error that should be reported via `SO_ERROR`.
+    pub(super) fn test_and_clear_error(&self) -> Option<Error> {
+        let is_icmp_error_enabled = self.bound_socket.is_icmp_error_enabled();
+        self.bound_socket.pending_icmp_error_with(|pending_error| {
+            let errno = self.socket_errno(&pending_error.take()?, is_icmp_error_enabled)?;
+            Some(Error::with_message(errno, "an ICMP error is received"))
+        })
+    }
+
+    /// Returns whether there is a pending error that should be reported via `SO_ERROR`.
+    fn has_error(&self) -> bool {
+        let is_icmp_error_enabled = self.bound_socket.is_icmp_error_enabled();
+        self.bound_socket.pending_icmp_error_with(|pending_error| {
+            pending_error
+                .as_ref()
+                .is_some_and(|error| self.socket_errno(error, is_icmp_error_enabled).is_some())
+        })
+    }
+
+    /// Returns the error code to report the ICMP error via `SO_ERROR`, if it should be reported.
+    ///
+    /// Like Linux, all errors are reported if `IP_RECVERR` or `IPV6_RECVERR` is set. Otherwise,
+    /// only the hard errors caused by the packets sent to the connected peer are reported.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/ipv4/udp.c>.
+    fn socket_errno(&self, error: &UdpIcmpError, is_icmp_error_enabled: bool) -> Option<Errno> {
+        let (errno, is_hard) = icmp_error_to_errno(error);
+        if is_icmp_error_enabled || (is_hard && self.remote_endpoint == Some(error.remote_endpoint))
+        {
+            Some(errno)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the address family of the local endpoint.
+    pub(super) fn family(&self) -> IpAddressFamily {
+        IpAddressFamily::from(self.bound_socket.local_endpoint().unwrap().addr)
+    }
+}
+
+impl datagram_common::Bound for BoundDatagram {
+    type Endpoint = IpEndpoint;
+
+    fn local_endpoint(&self) -> Self::Endpoint {
+        self.bound_socket.local_endpoint().unwrap()
+    }
+
+    fn remote_endpoint(&self) -> Option<&Self::Endpoint> {
+        self.remote_endpoint.as_ref()
+    }
+
+    fn set_remote_endpoint(&mut self, endpoint: &Self::Endpoint) {
+        self.remote_endpoint = Some(*endpoint)
+    }
+
+    fn try_recv(
+        &self,
+        writer: &mut dyn MultiWrite,
+        flags: SendRecvFlags,
+    ) -> Result<(usize, Self::Endpoint)> {
+        self.try_recv_with_dst(writer, flags)
+            .map(|(recv_bytes, remote_endpoint, _, _)| (recv_bytes, remote_endpoint))
+    }
+
+    fn try_send(
+        &self,
+        reader: &mut dyn MultiRead,
+        remote: &Self::Endpoint,
+        _flags: SendRecvFlags,
+    ) -> Result<usize> {
+        let len = reader.sum_lens();
+        self.try_send_len(reader, remote, len)
+    }
+
     fn check_io_events(&self) -> IoEvents {
-        self.bound_socket.raw_with(|socket| {
+        let mut events = self.bound_socket.raw_with(|socket| {
             let mut events = IoEvents::empty();
 
             if socket.can_recv() {
@@ -123,6 +274,12 @@ impl datagram_common::Bound for BoundDatagram {
             }
 
             events
-        })
+        });
+
+        if self.bound_socket.has_icmp_errors() || self.has_error() {
+            events |= IoEvents::ERR;
+        }
+
+        events
     }
 }
@@ -0,0 +1,192 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_bigtcp::{
+    socket::UdpRecvInfo,
+    wire::{IpAddress, IpEndpoint},
+};
+
+use super::{DatagramSocket, OptionSet, bound::BoundDatagram};
+use crate::{
+    net::{
+        iface::Iface,
+        socket::{
+            ip::{IpControlMessage, addr::IpAddressFamily},
+            util::{ControlMessage, datagram_common::Bound},
+        },
+    },
+    prelude::*,
+};
+
+/// The options specified by the control messages of `sendmsg`.
+#[derive(Default)]
+pub(super) struct SendControl {
+    /// The source address specified by `IP_PKTINFO` or `IPV6_PKTINFO`.
+    src_addr: Option<IpAddress>,
+    /// The outgoing interface specified by `IP_PKTINFO` or `IPV6_PKTINFO`.
+    iface: Option<Arc<Iface>>,
+    /// The segment size specified by `UDP_SEGMENT`.
+    segment_size: Option<u16>,
+}
+
+impl SendControl {
+    /// Selects the local endpoint to send packets if the socket is not bound.
+    ///
+    /// If the source address or the outgoing interface is specified, this method returns the
+    /// endpoint with the address. Otherwise, this method returns `None`.
+    pub(super) fn select_local_endpoint(&self, remote_endpoint: &IpEndpoint) -> Option<IpEndpoint> {
+        if let Some(src_addr) = self.src_addr {
+            return Some(IpEndpoint::new(src_addr, 0));
+        }
+
+        let src_addr = self
+            .iface
+            .as_ref()?
+            .ip_addrs()
+            .iter()
+            .map(|ip_cidr| ip_cidr.address())
+            .find(|addr| addr.version() == remote_endpoint.addr.version())?;
+        Some(IpEndpoint::new(src_addr, 0))
+    }
+
+    /// Checks whether the packets can be sent from the bound socket as specified.
+    pub(super) fn check_bound(&self, bound_datagram: &BoundDatagram) {
+        // TODO: Support sending packets from another local address or via another interface
+        // without binding the socket to them. This requires a wildcard bind.
+        let is_src_changed = self
+            .src_addr
+            .is_some_and(|src_addr| src_addr != bound_datagram.local_endpoint().addr);
+        let is_iface_changed = self
+            .iface
+            .as_ref()
+            .is_some_and(|iface| iface.index() != bound_datagram.iface().index());
+        if is_src_changed || is_iface_changed {
+            warn!("changing the source address of a bound socket is not supported");
+        }
+    }
+
+    pub(super) fn segment_size(&self) -> Option<u16> {
+        self.segment_size
+    }
+}
+
+impl DatagramSocket {
+    /// Parses the control messages of `sendmsg`.
+    pub(super) fn parse_send_control(
+        &self,
+        control_messages: &[ControlMessage],
+    ) -> Result<SendControl> {
+        let mut send_control = SendControl::default();
+
+        for control_message in control_messages.iter() {
+            let ControlMessage::Ip(message) = control_message else {
+                warn!("unsupported control message {:?}", control_message);
+                continue;
+            };
+
+            if let Some((src_addr, ifindex)) = message.pktinfo() {
+                // Like Linux, `IPV6_PKTINFO` is ignored for IPv4 sockets.
+                if self.family == IpAddressFamily::IPv4 && !matches!(src_addr, IpAddress::Ipv4(_)) {
+                    continue;
+                }
+
+                send_control.iface = if ifindex != 0 {
+                    Some(self.find_iface(ifindex).ok_or_else(|| {
+                        Error::with_message(Errno::ENODEV, "the interface does not exist")
+                    })?)
+                } else {
+                    None
+                };
+
+                let src_addr = match src_addr {
+                    IpAddress::Ipv6(addr) => addr
+                        .to_ipv4_mapped()
+                        .map_or(IpAddress::Ipv6(addr), IpAddress::Ipv4),
+                    IpAddress::Ipv4(addr) => IpAddress::Ipv4(addr),
+                };
+                send_control.src_addr = if !src_addr.is_unspecified() {
+                    if !self
+                        .net_ns
+                        .ifaces()
+                        .iter()
+                        .any(|iface| iface.has_ip_addr(src_addr))
+                    {
+                        return_errno_with_message!(
+                            Errno::ENETUNREACH,
+                            "the source address is not a local address"
+                        );
+                    }
+                    Some(src_addr)
+                } else {
+                    None
+                };
+            }
+
+            if let Some(segment_size) = message.udp_segment() {
+                send_control.segment_size = Some(segment_size);
+            }
+        }
+
+        Ok(send_control)
+    }
+
+    /// Creates the control messages of `recvmsg` for a received datagram.
+    pub(super) fn new_recv_control(
+        &self,
+        options: &OptionSet,
+        iface: &Iface,
+        dst_addr: IpAddress,
+        recv_info: &UdpRecvInfo,
+    ) -> Vec<ControlMessage> {
+        let mut control_messages = Vec::new();
+
+        if let Some(format) = options.socket.timestamp() {
+            let message = IpControlMessage::new_timestamp(recv_info.timestamp, format);
+            control_messages.push(ControlMessage::Ip(message));
+        }
+
+        if self.family == IpAddressFamily::IPv6 && options.ipv6.recvpktinfo() {
+            let dst_addr = match dst_addr {
+                IpAddress::Ipv4(addr) => addr.to_ipv6_mapped(),
+                IpAddress::Ipv6(addr) => addr,
+            };
+            let message = IpControlMessage::new_ipv6_pktinfo(dst_addr, iface.index());
+            control_messages.push(ControlMessage::Ip(message));
+        }
+
+        // The IP-level control messages are generated only for IPv4 packets.
+        let IpAddress::Ipv4(dst_addr) = dst_addr else {
+            return control_messages;
+        };
+
+        if options.ip.pktinfo() {
+            // For broadcast and multicast packets, the local address is the primary address of
+            // the interface.
+            let local_addr = if iface.has_ip_addr(IpAddress::Ipv4(dst_addr)) {
+                dst_addr
+            } else {
+                iface
+                    .ip_addrs()
+                    .iter()
+                    .find_map(|ip_cidr| match ip_cidr.address() {
+                        IpAddress::Ipv4(addr) => Some(addr),
+                        IpAddress::Ipv6(_) => None,
+                    })
+                    .unwrap_or(dst_addr)
+            };
+            let message = IpControlMessage::new_ip_pktinfo(iface.index(), local_addr, dst_addr);
+            control_messages.push(ControlMessage::Ip(message));
+        }
+
+        if options.ip.recvttl() {
+            let message = IpControlMessage::new_ip_ttl(recv_info.hop_limit);
+            control_messages.push(ControlMessage::Ip(message));
+        }
+
+        if options.ip.recvtos() {
+            let message = IpControlMessage::new_ip_tos(recv_info.traffic_class);
+            control_messages.push(ControlMessage::Ip(message));
+        }
+
+        control_messages
+    }
+}
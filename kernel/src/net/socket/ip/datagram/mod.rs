@@ -4,22 +4,28 @@ use core::sync::atomic::{AtomicBool, Ordering};
 
 use aster_bigtcp::wire::IpEndpoint;
 use bound::BoundDatagram;
+use control::SendControl;
+use multicast::Membership;
 use unbound::{BindOptions, UnboundDatagram};
 
-use super::addr::UNSPECIFIED_LOCAL_ENDPOINT;
+use super::{IpControlMessage, addr::IpAddressFamily};
 use crate::{
     events::IoEvents,
     fs::{pseudofs::SockFs, vfs::path::Path},
     net::{
         iface::is_broadcast_endpoint,
+        net_ns::NetNamespace,
         socket::{
             Socket,
-            ip::options::{IpOptionSet, SetIpLevelOption},
+            ip::{
+                ipv6_options::{Ipv6OptionSet, SetIpv6LevelOption},
+                options::{IpOptionSet, SetIpLevelOption},
+            },
             options::{Error as SocketError, SocketOption, macros::sock_option_mut},
             private::SocketPrivate,
             util::{
-                MessageHeader, SendRecvFlags, SocketAddr,
-                datagram_common::{Bound, Inner, select_remote_and_bind},
+                ControlMessage, MessageHeader, SendRecvFlags, SocketAddr, SocketInode,
+                datagram_common::{Inner, select_remote_and_bind},
                 options::{GetSocketLevelOption, SetSocketLevelOption, SocketOptionSet},
             },
         },
@@ -30,13 +36,18 @@ use crate::{
 };
 
 mod bound;
+mod control;
+mod multicast;
 pub(super) mod observer;
 mod unbound;
 
 pub struct DatagramSocket {
-    // Lock order: `inner` first, `options` second
+    // Lock order: `inner` first, `options` second, `memberships` third
     inner: RwMutex<Inner<UnboundDatagram, BoundDatagram>>,
     options: RwLock<OptionSet>,
+    memberships: Mutex<Vec<Membership>>,
+    family: IpAddressFamily,
+    net_ns: Arc<NetNamespace>,
 
     is_nonblocking: AtomicBool,
     pollee: Pollee,
@@ -47,6 +58,7 @@ pub struct DatagramSocket {
 struct OptionSet {
     socket: SocketOptionSet,
     ip: IpOptionSet,
+    ipv6: Ipv6OptionSet,
     // TODO: UDP option set
 }
 
@@ -54,19 +66,28 @@ impl OptionSet {
     fn new() -> Self {
         let socket = SocketOptionSet::new_udp();
         let ip = IpOptionSet::new_udp();
-        OptionSet { socket, ip }
+        let ipv6 = Ipv6OptionSet::new_udp();
+        OptionSet { socket, ip, ipv6 }
     }
 }
 
 impl DatagramSocket {
-    pub fn new(is_nonblocking: bool) -> Arc<Self> {
-        let unbound_datagram = UnboundDatagram::new();
+    pub fn new(
+        is_nonblocking: bool,
+        family: IpAddressFamily,
+        net_ns: Arc<NetNamespace>,
+    ) -> Arc<Self> {
+        let pseudo_path = SockFs::new_path();
+        let unbound_datagram = UnboundDatagram::new(net_ns.clone(), SocketInode::of(&pseudo_path));
         Arc::new(Self {
             inner: RwMutex::new(Inner::Unbound(unbound_datagram)),
             options: RwLock::new(OptionSet::new()),
+            memberships: Mutex::new(Vec::new()),
+            family,
+            net_ns,
             is_nonblocking: AtomicBool::new(is_nonblocking),
             pollee: Pollee::new(),
-            pseudo_path: SockFs::new_path(),
+            pseudo_path,
         })
     }
 
@@ -74,21 +95,52 @@ impl DatagramSocket {
         &self,
         writer: &mut dyn MultiWrite,
         flags: SendRecvFlags,
-    ) -> Result<(usize, SocketAddr)> {
-        let recv_bytes = self
-            .inner
-            .read()
-            .try_recv(writer, flags)
-            .map(|(recv_bytes, remote_endpoint)| (recv_bytes, remote_endpoint.into()))?;
+    ) -> Result<(usize, MessageHeader)> {
+        let inner = self.inner.read();
+        let Inner::Bound(bound_datagram) = &*inner else {
+            return_errno_with_message!(Errno::EAGAIN, "the socket is not bound");
+        };
+
+        // The pending error may be cleared even if the receiving fails.
+        let result = bound_datagram.try_recv_with_dst(writer, flags);
+        let iface = bound_datagram.iface().clone();
+        drop(inner);
+        self.pollee.invalidate();
+        let (recv_bytes, remote_endpoint, dst_addr, recv_info) = result?;
+
+        let control_messages =
+            self.new_recv_control(&self.options.read(), &iface, dst_addr, &recv_info);
+
+        let peer_addr = self.family.socket_addr_from(remote_endpoint);
+        Ok((
+            recv_bytes,
+            MessageHeader::new(Some(peer_addr), control_messages),
+        ))
+    }
+
+    fn try_recv_error(&self, writer: &mut dyn MultiWrite) -> Result<(usize, MessageHeader)> {
+        let inner = self.inner.read();
+        let Inner::Bound(bound_datagram) = &*inner else {
+            return_errno_with_message!(Errno::EAGAIN, "the error queue is empty");
+        };
+
+        let (recv_bytes, error) = bound_datagram.try_recv_error(writer)?;
+        drop(inner);
         self.pollee.invalidate();
 
-        Ok(recv_bytes)
+        let recverr = IpControlMessage::new_recverr(&error);
+        let remote_addr = self.family.socket_addr_from(error.remote_endpoint);
+        Ok((
+            recv_bytes,
+            MessageHeader::new(Some(remote_addr), vec![ControlMessage::Ip(recverr)]),
+        ))
     }
 
     fn try_send(
         &self,
         reader: &mut dyn MultiRead,
         remote: Option<&IpEndpoint>,
+        send_control: &SendControl,
         flags: SendRecvFlags,
     ) -> Result<usize> {
         let (sent_bytes, iface_to_poll) = select_remote_and_bind(
@@ -101,12 +153,44 @@ impl DatagramSocket {
                         "the destination address is not specified",
                     )
                 })?;
-                self.inner
-                    .write()
-                    .bind_ephemeral(remote_endpoint, &self.pollee)
+
+                let mut inner = self.inner.write();
+                let options = self.options.read();
+
+                // Packets are sent from the address or via the interface specified by
+                // `IP_PKTINFO` or `IPV6_PKTINFO`, if any. Multicast packets are sent via the
+                // interface specified by `IP_MULTICAST_IF` or `IPV6_MULTICAST_IF`, if any.
+                if let Inner::Unbound(_) = &*inner
+                    && let Some(endpoint) = send_control
+                        .select_local_endpoint(remote_endpoint)
+                        .or_else(|| self.select_multicast_endpoint(remote_endpoint, &options))
+                {
+                    inner.bind(&endpoint, &self.pollee, BindOptions { can_reuse: false })?;
+                } else {
+                    inner.bind_ephemeral(remote_endpoint, &self.pollee)?;
+                }
+
+                self.update_bound_options(&inner, &options);
+                Ok(())
             },
             |bound_datagram, remote_endpoint| {
-                let sent_bytes = bound_datagram.try_send(reader, remote_endpoint, flags)?;
+                // TODO: Support sending IPv4 packets and IPv6 packets via the same socket. This
+                // requires binding the socket to both IPv4 and IPv6 unspecified addresses.
+                if bound_datagram.family() != IpAddressFamily::from(remote_endpoint.addr) {
+                    return_errno_with_message!(
+                        Errno::ENETUNREACH,
+                        "the destination address family does not match the bound address family"
+                    );
+                }
+
+                send_control.check_bound(bound_datagram);
+
+                let sent_bytes = bound_datagram.try_send_segments(
+                    reader,
+                    remote_endpoint,
+                    send_control.segment_size(),
+                    flags,
+                )?;
                 let iface_to_poll = bound_datagram.iface().clone();
                 Ok((sent_bytes, iface_to_poll))
             },
@@ -117,6 +201,34 @@ impl DatagramSocket {
 
         Ok(sent_bytes)
     }
+
+    fn test_and_clear_error(&self) -> Option<Error> {
+        let error = match &*self.inner.read() {
+            Inner::Bound(bound_datagram) => bound_datagram.test_and_clear_error(),
+            Inner::Unbound(_) => None,
+        };
+        self.pollee.invalidate();
+        error
+    }
+
+    /// Updates the options that take effect on the bound socket.
+    fn update_bound_options(
+        &self,
+        inner: &Inner<UnboundDatagram, BoundDatagram>,
+        options: &OptionSet,
+    ) {
+        self.update_multicast_option(inner, options);
+
+        let Inner::Bound(bound_datagram) = inner else {
+            return;
+        };
+
+        let recverr = match bound_datagram.family() {
+            IpAddressFamily::IPv4 => options.ip.recverr(),
+            IpAddressFamily::IPv6 => options.ipv6.recverr(),
+        };
+        bound_datagram.set_icmp_error_enabled(recverr);
+    }
 }
 
 impl Pollable for DatagramSocket {
@@ -138,25 +250,46 @@ impl SocketPrivate for DatagramSocket {
 
 impl Socket for DatagramSocket {
     fn bind(&self, socket_addr: SocketAddr) -> Result<()> {
-        let endpoint = socket_addr.try_into()?;
-        let can_reuse = self.options.read().socket.reuse_addr();
+        let (can_reuse, v6only) = {
+            let options = self.options.read();
+            (options.socket.reuse_addr(), options.ipv6.v6only())
+        };
+        let endpoint = self.family.local_endpoint_from(socket_addr, v6only)?;
 
-        self.inner
-            .write()
-            .bind(&endpoint, &self.pollee, BindOptions { can_reuse })
+        let mut inner = self.inner.write();
+        inner.bind(&endpoint, &self.pollee, BindOptions { can_reuse })?;
+
+        self.update_bound_options(&inner, &self.options.read());
+        Ok(())
     }
 
     fn connect(&self, socket_addr: SocketAddr) -> Result<()> {
-        let endpoint = socket_addr.try_into()?;
-        let can_broadcast = self.options.read().socket.broadcast();
-        if !can_broadcast && is_broadcast_endpoint(&endpoint) {
+        let (can_broadcast, v6only) = {
+            let options = self.options.read();
+            (options.socket.broadcast(), options.ipv6.v6only())
+        };
+        let endpoint = self.family.remote_endpoint_from(socket_addr, v6only)?;
+        if !can_broadcast && is_broadcast_endpoint(&endpoint, &self.net_ns) {
             return_errno_with_message!(
                 Errno::EACCES,
                 "connecting to a broadcast address without SO_BROADCAST is not allowed"
             );
         }
 
-        self.inner.write().connect(&endpoint, &self.pollee)
+        let mut inner = self.inner.write();
+        if let Inner::Bound(bound_datagram) = &*inner
+            && bound_datagram.family() != IpAddressFamily::from(endpoint.addr)
+        {
+            return_errno_with_message!(
+                Errno::ENETUNREACH,
+                "the remote address family does not match the bound address family"
+            );
+        }
+
+        inner.connect(&endpoint, &self.pollee)?;
+
+        self.update_bound_options(&inner, &self.options.read());
+        Ok(())
     }
 
     fn addr(&self) -> Result<SocketAddr> {
@@ -164,9 +297,9 @@ impl Socket for DatagramSocket {
             .inner
             .read()
             .addr()
-            .unwrap_or(UNSPECIFIED_LOCAL_ENDPOINT);
+            .unwrap_or_else(|| self.family.unspecified_endpoint());
 
-        Ok(endpoint.into())
+        Ok(self.family.socket_addr_from(endpoint))
     }
 
     fn peer_addr(&self) -> Result<SocketAddr> {
@@ -175,7 +308,7 @@ impl Socket for DatagramSocket {
                 Error::with_message(Errno::ENOTCONN, "the socket is not connected")
             })?;
 
-        Ok(endpoint.into())
+        Ok(self.family.socket_addr_from(endpoint))
     }
 
     fn sendmsg(
@@ -194,14 +327,18 @@ impl Socket for DatagramSocket {
             control_messages,
         } = message_header;
 
+        let (can_broadcast, v6only) = {
+            let options = self.options.read();
+            (options.socket.broadcast(), options.ipv6.v6only())
+        };
+
         let endpoint = match addr {
-            Some(addr) => Some(addr.try_into()?),
+            Some(addr) => Some(self.family.remote_endpoint_from(addr, v6only)?),
             None => None,
         };
 
         if let Some(endpoint) = endpoint.as_ref() {
-            let can_broadcast = self.options.read().socket.broadcast();
-            if !can_broadcast && is_broadcast_endpoint(endpoint) {
+            if !can_broadcast && is_broadcast_endpoint(endpoint, &self.net_ns) {
                 return_errno_with_message!(
                     Errno::EACCES,
                     "sending to a broadcast address without SO_BROADCAST is not allowed"
@@ -209,13 +346,15 @@ impl Socket for DatagramSocket {
             }
         }
 
-        if !control_messages.is_empty() {
-            // TODO: Support sending control message
-            warn!("sending control message is not supported");
+        let send_control = self.parse_send_control(&control_messages)?;
+
+        // Like Linux, the pending error is reported before sending.
+        if let Some(error) = self.test_and_clear_error() {
+            return Err(error);
         }
 
         // TODO: Block if the send buffer is full
-        self.try_send(reader, endpoint.as_ref(), flags)
+        self.try_send(reader, endpoint.as_ref(), &send_control, flags)
     }
 
     fn recvmsg(
@@ -228,21 +367,18 @@ impl Socket for DatagramSocket {
             warn!("unsupported flags: {:?}", flags);
         }
 
-        let (received_bytes, peer_addr) =
-            self.block_on(IoEvents::IN, || self.try_recv(writer, flags))?;
-
-        // TODO: Receive control message
-
-        let message_header = MessageHeader::new(Some(peer_addr), Vec::new());
+        // Receiving errors from the error queue never blocks.
+        if flags.contains(SendRecvFlags::MSG_ERRQUEUE) {
+            return self.try_recv_error(writer);
+        }
 
-        Ok((received_bytes, message_header))
+        self.block_on(IoEvents::IN, || self.try_recv(writer, flags))
     }
 
     fn get_option(&self, option: &mut dyn SocketOption) -> Result<()> {
         sock_option_mut!(match option {
             socket_errors @ SocketError => {
-                // TODO: Support socket errors for UDP sockets
-                socket_errors.set(None);
+                socket_errors.set(self.test_and_clear_error());
                 return Ok(());
             }
             _ => (),
@@ -258,22 +394,55 @@ impl Socket for DatagramSocket {
         }
 
         // Deal with IP-level options
-        options.ip.get_option(option)
+        match options.ip.get_option(option) {
+            Err(err)
+                if err.error() == Errno::ENOPROTOOPT && self.family == IpAddressFamily::IPv6 => {}
+            res => return res,
+        }
+
+        // Deal with IPv6-level options
+        options.ipv6.get_option(option)
     }
 
     fn set_option(&self, option: &dyn SocketOption) -> Result<()> {
         let inner = self.inner.read();
         let mut options = self.options.write();
 
+        // Deal with multicast group memberships
+        match self.set_membership(option) {
+            Err(err) if err.error() == Errno::ENOPROTOOPT => (),
+            Err(err) => return Err(err),
+            Ok(iface_to_poll) => {
+                self.update_bound_options(&inner, &options);
+
+                drop(inner);
+                drop(options);
+
+                iface_to_poll.poll();
+                return Ok(());
+            }
+        }
+        self.check_multicast_if(option)?;
+
         // Deal with socket-level options
         let need_iface_poll = match options.socket.set_option(option, &*inner) {
             Err(err) if err.error() == Errno::ENOPROTOOPT => {
                 // Deal with IP-level options
-                options.ip.set_option(option, &*inner)?
+                match options.ip.set_option(option, &*inner) {
+                    Err(err)
+                        if err.error() == Errno::ENOPROTOOPT
+                            && self.family == IpAddressFamily::IPv6 =>
+                    {
+                        // Deal with IPv6-level options
+                        options.ipv6.set_option(option, &*inner)?
+                    }
+                    res => res?,
+                }
             }
             Err(err) => return Err(err),
             Ok(need_iface_poll) => need_iface_poll,
         };
+        self.update_bound_options(&inner, &options);
 
         let iface_to_poll = need_iface_poll
             .then(|| match &*inner {
@@ -297,6 +466,15 @@ impl Socket for DatagramSocket {
     }
 }
 
+impl Drop for DatagramSocket {
+    fn drop(&mut self) {
+        for membership in self.memberships.get_mut().drain(..) {
+            let iface = membership.leave();
+            iface.poll();
+        }
+    }
+}
+
 impl GetSocketLevelOption for Inner<UnboundDatagram, BoundDatagram> {
     fn is_listening(&self) -> bool {
         false
@@ -321,3 +499,16 @@ impl SetIpLevelOption for Inner<UnboundDatagram, BoundDatagram> {
         );
     }
 }
+
+impl SetIpv6LevelOption for Inner<UnboundDatagram, BoundDatagram> {
+    fn set_v6only(&self, _v6only: bool) -> Result<()> {
+        if let Inner::Bound(_) = self {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "IPV6_V6ONLY cannot be changed after the socket is bound"
+            );
+        }
+
+        Ok(())
+    }
+}
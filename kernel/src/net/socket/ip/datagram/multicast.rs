@@ -0,0 +1,331 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_bigtcp::{
+    socket::UdpMulticastOption,
+    wire::{IpAddress, IpEndpoint},
+};
+
+use super::{DatagramSocket, OptionSet, bound::BoundDatagram, unbound::UnboundDatagram};
+use crate::{
+    net::{
+        iface::Iface,
+        socket::{
+            ip::{
+                addr::IpAddressFamily,
+                ipv6_options::{self, Ipv6Mreq},
+                options::{self, IpMreqn, IpMulticastIf},
+            },
+            options::{SocketOption, macros::sock_option_ref},
+            util::datagram_common::Inner,
+        },
+    },
+    prelude::*,
+};
+
+/// A multicast group that a datagram socket has joined.
+pub(super) struct Membership {
+    iface: Arc<Iface>,
+    group: IpAddress,
+}
+
+impl Membership {
+    /// Leaves the multicast group and returns the iface that needs polling.
+    pub(super) fn leave(self) -> Arc<Iface> {
+        self.iface.leave_multicast_group(self.group);
+        self.iface
+    }
+}
+
+/// The maximum number of IPv4 multicast groups that a socket can join.
+///
+/// This resembles the default value of `net.ipv4.igmp_max_memberships` in Linux.
+const IP_MAX_MEMBERSHIPS: usize = 20;
+
+impl DatagramSocket {
+    /// Validates the interface specified by `IP_MULTICAST_IF` or `IPV6_MULTICAST_IF`.
+    ///
+    /// The option itself will be stored by the option sets.
+    pub(super) fn check_multicast_if(&self, option: &dyn SocketOption) -> Result<()> {
+        sock_option_ref!(match option {
+            ip_multicast_if @ options::MulticastIf => {
+                let multicast_if = ip_multicast_if.get().unwrap();
+                if !multicast_if.is_unspecified() && self.find_ipv4_iface(multicast_if).is_none() {
+                    return_errno_with_message!(
+                        Errno::EADDRNOTAVAIL,
+                        "the multicast interface does not exist"
+                    );
+                }
+            }
+            ipv6_multicast_if @ ipv6_options::MulticastIf => {
+                let ifindex = *ipv6_multicast_if.get().unwrap();
+                if self.family == IpAddressFamily::IPv6
+                    && ifindex != 0
+                    && self.find_iface(ifindex).is_none()
+                {
+                    return_errno_with_message!(
+                        Errno::ENODEV,
+                        "the multicast interface does not exist"
+                    );
+                }
+            }
+            _ => (),
+        });
+
+        Ok(())
+    }
+
+    /// Joins or leaves a multicast group.
+    ///
+    /// This method returns the iface that needs polling, or fails with `ENOPROTOOPT` if the option
+    /// is not a membership option.
+    pub(super) fn set_membership(&self, option: &dyn SocketOption) -> Result<Arc<Iface>> {
+        sock_option_ref!(match option {
+            add_membership @ options::AddMembership => {
+                let mreqn = add_membership.get().unwrap();
+                return self.add_ipv4_membership(mreqn);
+            }
+            drop_membership @ options::DropMembership => {
+                let mreqn = drop_membership.get().unwrap();
+                return self.drop_ipv4_membership(mreqn);
+            }
+            _ => (),
+        });
+
+        if self.family != IpAddressFamily::IPv6 {
+            return_errno_with_message!(Errno::ENOPROTOOPT, "the socket option is unknown");
+        }
+
+        sock_option_ref!(match option {
+            join_group @ ipv6_options::JoinGroup => {
+                let mreq = join_group.get().unwrap();
+                self.add_ipv6_membership(mreq)
+            }
+            leave_group @ ipv6_options::LeaveGroup => {
+                let mreq = leave_group.get().unwrap();
+                self.drop_ipv6_membership(mreq)
+            }
+            _ => return_errno_with_message!(Errno::ENOPROTOOPT, "the socket option is unknown"),
+        })
+    }
+
+    fn add_ipv4_membership(&self, mreqn: &IpMreqn) -> Result<Arc<Iface>> {
+        if !mreqn.multiaddr.is_multicast() {
+            return_errno_with_message!(Errno::EINVAL, "the group is not a multicast address");
+        }
+
+        let iface = self.find_ipv4_membership_iface(mreqn)?;
+        let group = IpAddress::Ipv4(mreqn.multiaddr);
+
+        self.add_membership(iface, group)
+    }
+
+    fn drop_ipv4_membership(&self, mreqn: &IpMreqn) -> Result<Arc<Iface>> {
+        let group = IpAddress::Ipv4(mreqn.multiaddr);
+
+        // Like Linux, if the interface is not specified, the membership is found by the group
+        // alone.
+        let ifindex = if mreqn.ifindex == 0 && mreqn.address.is_unspecified() {
+            None
+        } else {
+            Some(self.find_ipv4_membership_iface(mreqn)?.index())
+        };
+
+        self.drop_membership(group, ifindex)
+    }
+
+    fn add_ipv6_membership(&self, mreq: &Ipv6Mreq) -> Result<Arc<Iface>> {
+        if !mreq.multiaddr.is_multicast() {
+            return_errno_with_message!(Errno::EINVAL, "the group is not a multicast address");
+        }
+
+        let group = IpAddress::Ipv6(mreq.multiaddr);
+        let iface = if mreq.ifindex != 0 {
+            self.find_iface(mreq.ifindex)
+        } else {
+            self.net_ns
+                .route_output(&group)
+                .ok()
+                .map(|(iface, _)| iface)
+        };
+        let Some(iface) = iface else {
+            return_errno_with_message!(Errno::ENODEV, "the interface does not exist");
+        };
+
+        self.add_membership(iface, group)
+    }
+
+    fn drop_ipv6_membership(&self, mreq: &Ipv6Mreq) -> Result<Arc<Iface>> {
+        let group = IpAddress::Ipv6(mreq.multiaddr);
+        let ifindex = (mreq.ifindex != 0).then_some(mreq.ifindex);
+
+        self.drop_membership(group, ifindex)
+    }
+
+    fn add_membership(&self, iface: Arc<Iface>, group: IpAddress) -> Result<Arc<Iface>> {
+        let mut memberships = self.memberships.lock();
+
+        if memberships.iter().any(|membership| {
+            membership.group == group && membership.iface.index() == iface.index()
+        }) {
+            return_errno_with_message!(Errno::EADDRINUSE, "the group has already been joined");
+        }
+
+        if let IpAddress::Ipv4(_) = group {
+            let num_ipv4_memberships = memberships
+                .iter()
+                .filter(|membership| matches!(membership.group, IpAddress::Ipv4(_)))
+                .count();
+            if num_ipv4_memberships >= IP_MAX_MEMBERSHIPS {
+                return_errno_with_message!(Errno::ENOBUFS, "too many groups have been joined");
+            }
+        }
+
+        iface.join_multicast_group(group);
+        memberships.push(Membership {
+            iface: iface.clone(),
+            group,
+        });
+
+        Ok(iface)
+    }
+
+    fn drop_membership(&self, group: IpAddress, ifindex: Option<u32>) -> Result<Arc<Iface>> {
+        let mut memberships = self.memberships.lock();
+
+        let Some(pos) = memberships.iter().position(|membership| {
+            membership.group == group
+                && ifindex.is_none_or(|ifindex| membership.iface.index() == ifindex)
+        }) else {
+            return_errno_with_message!(Errno::EADDRNOTAVAIL, "the group has not been joined");
+        };
+
+        Ok(memberships.swap_remove(pos).leave())
+    }
+
+    /// Finds the iface on which an IPv4 multicast group is joined or left.
+    ///
+    /// The iface is specified by the interface index, the local address, or the routing table,
+    /// in that order of preference.
+    fn find_ipv4_membership_iface(&self, mreqn: &IpMreqn) -> Result<Arc<Iface>> {
+        let multicast_if = IpMulticastIf {
+            addr: mreqn.address,
+            ifindex: mreqn.ifindex,
+        };
+
+        let iface = if !multicast_if.is_unspecified() {
+            self.find_ipv4_iface(&multicast_if)
+        } else {
+            let group = IpAddress::Ipv4(mreqn.multiaddr);
+            self.net_ns
+                .route_output(&group)
+                .ok()
+                .map(|(iface, _)| iface)
+        };
+
+        iface.ok_or_else(|| Error::with_message(Errno::ENODEV, "the interface does not exist"))
+    }
+
+    fn find_ipv4_iface(&self, multicast_if: &IpMulticastIf) -> Option<Arc<Iface>> {
+        if multicast_if.ifindex != 0 {
+            return self.find_iface(multicast_if.ifindex);
+        }
+
+        self.net_ns
+            .ifaces()
+            .into_iter()
+            .find(|iface| iface.has_ip_addr(IpAddress::Ipv4(multicast_if.addr)))
+    }
+
+    pub(super) fn find_iface(&self, ifindex: u32) -> Option<Arc<Iface>> {
+        self.net_ns
+            .ifaces()
+            .into_iter()
+            .find(|iface| iface.index() == ifindex)
+    }
+
+    /// Selects the local endpoint to send multicast packets if the socket is not bound.
+    ///
+    /// If the outgoing interface is specified by `IP_MULTICAST_IF` or `IPV6_MULTICAST_IF`, an
+    /// address of the interface is selected. Otherwise, this method returns `None` and the local
+    /// endpoint is selected by routing.
+    pub(super) fn select_multicast_endpoint(
+        &self,
+        remote_endpoint: &IpEndpoint,
+        options: &OptionSet,
+    ) -> Option<IpEndpoint> {
+        if !remote_endpoint.addr.is_multicast() {
+            return None;
+        }
+
+        let ifindex = match remote_endpoint.addr {
+            IpAddress::Ipv4(_) => {
+                let multicast_if = options.ip.multicast_if();
+                if multicast_if.ifindex == 0 {
+                    // The interface is specified by its local address, or not specified at all.
+                    return (!multicast_if.addr.is_unspecified())
+                        .then(|| IpEndpoint::new(IpAddress::Ipv4(multicast_if.addr), 0));
+                }
+                multicast_if.ifindex
+            }
+            IpAddress::Ipv6(_) => options.ipv6.multicast_if(),
+        };
+        if ifindex == 0 {
+            return None;
+        }
+
+        let iface = self.find_iface(ifindex)?;
+        let src_addr = iface
+            .ip_addrs()
+            .iter()
+            .map(|ip_cidr| ip_cidr.address())
+            .find(|addr| addr.version() == remote_endpoint.addr.version())?;
+
+        Some(IpEndpoint::new(src_addr, 0))
+    }
+
+    /// Updates the multicast options of the bound UDP socket.
+    ///
+    /// This method should be called whenever the socket is bound, the options are changed, or
+    /// the memberships are changed.
+    pub(super) fn update_multicast_option(
+        &self,
+        inner: &Inner<UnboundDatagram, BoundDatagram>,
+        options: &OptionSet,
+    ) {
+        let Inner::Bound(bound_datagram) = inner else {
+            return;
+        };
+
+        let (hop_limit, is_loop_enabled, is_multicast_all) = match bound_datagram.family() {
+            IpAddressFamily::IPv4 => (
+                options.ip.multicast_ttl(),
+                options.ip.multicast_loop(),
+                options.ip.multicast_all(),
+            ),
+            IpAddressFamily::IPv6 => (
+                options.ipv6.multicast_hops(),
+                options.ipv6.multicast_loop(),
+                options.ipv6.multicast_all(),
+            ),
+        };
+
+        // If `IP_MULTICAST_ALL` is disabled, only the packets sent to the groups that the socket
+        // has joined are received. Otherwise, the packets sent to all the groups that the iface
+        // has joined are received.
+        let groups = (!is_multicast_all).then(|| {
+            let iface_index = bound_datagram.iface().index();
+            self.memberships
+                .lock()
+                .iter()
+                .filter(|membership| membership.iface.index() == iface_index)
+                .map(|membership| membership.group)
+                .collect()
+        });
+
+        bound_datagram.set_multicast_option(UdpMulticastOption {
+            hop_limit,
+            is_loop_enabled,
+            groups,
+        });
+    }
+}
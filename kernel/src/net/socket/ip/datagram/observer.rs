@@ -2,13 +2,33 @@
 
 use aster_bigtcp::socket::{SocketEventObserver, SocketEvents};
 
-use crate::{events::IoEvents, process::signal::Pollee};
+use crate::{events::IoEvents, net::socket::util::SocketInode, process::signal::Pollee};
 
-pub struct DatagramObserver(Pollee);
+pub struct DatagramObserver {
+    pollee: Pollee,
+    inode: Option<SocketInode>,
+}
 
 impl DatagramObserver {
-    pub(super) fn new(pollee: Pollee) -> Self {
-        Self(pollee)
+    /// Creates an observer for a socket that has no inode (e.g., a socket used by the kernel).
+    pub(in crate::net) fn new(pollee: Pollee) -> Self {
+        Self {
+            pollee,
+            inode: None,
+        }
+    }
+
+    /// Creates an observer for a socket whose inode is `inode`.
+    pub(in crate::net) fn new_with_inode(pollee: Pollee, inode: SocketInode) -> Self {
+        Self {
+            pollee,
+            inode: Some(inode),
+        }
+    }
+
+    /// Returns the inode of the socket that observes the events.
+    pub(in crate::net) fn inode(&self) -> Option<SocketInode> {
+        self.inode
     }
 }
 
@@ -24,6 +44,10 @@ impl SocketEventObserver for DatagramObserver {
             io_events |= IoEvents::OUT;
         }
 
-        self.0.notify(io_events);
+        if events.contains(SocketEvents::ERROR) {
+            io_events |= IoEvents::ERR;
+        }
+
+        self.pollee.notify(io_events);
     }
 }
@@ -7,9 +7,10 @@ use crate::{
     events::IoEvents,
     net::{
         iface::BoundUdpPort,
+        net_ns::NetNamespace,
         socket::{
             ip::common::{get_ephemeral_endpoint, resolve_bind_iface_and_config},
-            util::datagram_common,
+            util::{SocketInode, datagram_common},
         },
     },
     prelude::*,
@@ -17,12 +18,13 @@ use crate::{
 };
 
 pub(super) struct UnboundDatagram {
-    _private: (),
+    net_ns: Arc<NetNamespace>,
+    inode: SocketInode,
 }
 
 impl UnboundDatagram {
-    pub(super) fn new() -> Self {
-        Self { _private: () }
+    pub(super) fn new(net_ns: Arc<NetNamespace>, inode: SocketInode) -> Self {
+        Self { net_ns, inode }
     }
 }
 
@@ -42,15 +44,15 @@ impl datagram_common::Unbound for UnboundDatagram {
         pollee: &Pollee,
         options: BindOptions,
     ) -> Result<Self::Bound> {
-        let bound_port = bind_port(endpoint, options.can_reuse)?;
+        let bound_port = bind_port(endpoint, options.can_reuse, &self.net_ns)?;
 
-        let bound_socket =
-            match UdpSocket::new_bind(bound_port, DatagramObserver::new(pollee.clone())) {
-                Ok(bound_socket) => bound_socket,
-                Err((_, err)) => {
-                    unreachable!("`new_bind` fails with {:?}, which should not happen", err)
-                }
-            };
+        let observer = DatagramObserver::new_with_inode(pollee.clone(), self.inode);
+        let bound_socket = match UdpSocket::new_bind(bound_port, observer) {
+            Ok(bound_socket) => bound_socket,
+            Err((_, err)) => {
+                unreachable!("`new_bind` fails with {:?}, which should not happen", err)
+            }
+        };
 
         Ok(BoundDatagram::new(bound_socket))
     }
@@ -60,12 +62,7 @@ impl datagram_common::Unbound for UnboundDatagram {
         remote_endpoint: &Self::Endpoint,
         pollee: &Pollee,
     ) -> Result<Self::Bound> {
-        let endpoint = get_ephemeral_endpoint(remote_endpoint).ok_or_else(|| {
-            Error::with_message(
-                Errno::EADDRNOTAVAIL,
-                "no interface has an address for the specified family",
-            )
-        })?;
+        let endpoint = get_ephemeral_endpoint(remote_endpoint, &self.net_ns)?;
         self.bind(&endpoint, pollee, BindOptions { can_reuse: false })
     }
 
@@ -74,7 +71,11 @@ impl datagram_common::Unbound for UnboundDatagram {
     }
 }
 
-fn bind_port(endpoint: &IpEndpoint, can_reuse: bool) -> Result<BoundUdpPort> {
-    let (iface, config) = resolve_bind_iface_and_config(endpoint, can_reuse)?;
+fn bind_port(
+    endpoint: &IpEndpoint,
+    can_reuse: bool,
+    net_ns: &NetNamespace,
+) -> Result<BoundUdpPort> {
+    let (iface, config) = resolve_bind_iface_and_config(endpoint, can_reuse, net_ns)?;
     Ok(iface.bind_udp(config)?)
 }
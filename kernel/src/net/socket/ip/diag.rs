@@ -0,0 +1,126 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Reporting of TCP and UDP sockets to user space.
+//!
+//! The sockets are found in the socket tables of the interfaces in a network namespace. They are
+//! reported via `/proc/net/{tcp,tcp6,udp,udp6}` and `NETLINK_SOCK_DIAG`.
+
+use aster_bigtcp::{
+    socket::{CongestionControl, TcpSocketInfo},
+    wire::{IpAddress, IpEndpoint, Ipv4Address, Ipv6Address},
+};
+
+use super::stream_options::{TCP_CLOSE, TCP_LISTEN, TcpInfo};
+use crate::{
+    net::{net_ns::NetNamespace, socket::util::SocketInode},
+    prelude::*,
+};
+
+/// A TCP or UDP socket.
+#[derive(Clone, Debug)]
+pub struct InetSocketEntry {
+    pub local_endpoint: IpEndpoint,
+    /// The remote endpoint, which is unspecified if the socket is not connected.
+    pub remote_endpoint: IpEndpoint,
+    /// The TCP state (e.g., `TCP_ESTABLISHED`), which is also used by UDP sockets.
+    pub state: u8,
+    /// The number of bytes to receive, or the number of connections to accept for listeners.
+    pub recv_queue: usize,
+    /// The number of bytes to send, or the maximum number of connections to accept for
+    /// listeners.
+    pub send_queue: usize,
+    /// The inode, which is absent if the socket has not been accepted or is used by the kernel.
+    pub inode: Option<SocketInode>,
+    /// The TCP information, which is present only for TCP sockets.
+    pub tcp_info: Option<TcpInfo>,
+    /// The congestion control algorithm, which is present only for TCP connections.
+    pub congestion: Option<CongestionControl>,
+}
+
+impl InetSocketEntry {
+    /// Returns whether the socket is an IPv6 socket.
+    pub fn is_ipv6(&self) -> bool {
+        matches!(self.local_endpoint.addr, IpAddress::Ipv6(_))
+    }
+
+    /// Returns whether the socket is a TCP listener.
+    pub fn is_listening(&self) -> bool {
+        self.state == TCP_LISTEN
+    }
+}
+
+/// Returns the TCP sockets in the network namespace.
+pub fn tcp_socket_entries(net_ns: &NetNamespace) -> Vec<InetSocketEntry> {
+    let now = aster_bigtcp::time::now();
+    let mut entries = Vec::new();
+
+    for iface in net_ns.ifaces() {
+        iface.visit_tcp_sockets(|info| {
+            let entry = match info {
+                TcpSocketInfo::Listener(listener) => {
+                    let tcp_info = TcpInfo::new_unconnected(true);
+                    InetSocketEntry {
+                        local_endpoint: listener.local_endpoint,
+                        remote_endpoint: unspecified_endpoint(&listener.local_endpoint),
+                        state: tcp_info.state(),
+                        recv_queue: listener.num_connected,
+                        send_queue: listener.max_conn,
+                        inode: listener.observer.map(|observer| observer.inode()),
+                        tcp_info: Some(tcp_info),
+                        congestion: None,
+                    }
+                }
+                TcpSocketInfo::Connection(connection) => {
+                    let socket = connection.socket;
+                    let tcp_info = TcpInfo::new_connection(socket, now);
+                    InetSocketEntry {
+                        local_endpoint: connection.local_endpoint,
+                        remote_endpoint: connection.remote_endpoint,
+                        state: tcp_info.state(),
+                        recv_queue: socket.recv_queue(),
+                        send_queue: socket.send_queue(),
+                        inode: connection.observer.map(|observer| observer.inode()),
+                        tcp_info: Some(tcp_info),
+                        congestion: Some(socket.congestion_control()),
+                    }
+                }
+            };
+            entries.push(entry);
+        });
+    }
+
+    entries
+}
+
+/// Returns the UDP sockets in the network namespace.
+pub fn udp_socket_entries(net_ns: &NetNamespace) -> Vec<InetSocketEntry> {
+    let mut entries = Vec::new();
+
+    for iface in net_ns.ifaces() {
+        iface.visit_udp_sockets(|info| {
+            // TODO: Report the remote endpoints of connected UDP sockets. They are only known by
+            // the sockets' owners, so all UDP sockets are reported as unconnected for now.
+            entries.push(InetSocketEntry {
+                local_endpoint: info.local_endpoint,
+                remote_endpoint: unspecified_endpoint(&info.local_endpoint),
+                state: TCP_CLOSE,
+                recv_queue: info.recv_queue,
+                send_queue: info.send_queue,
+                inode: info.observer.and_then(|observer| observer.inode()),
+                tcp_info: None,
+                congestion: None,
+            });
+        });
+    }
+
+    entries
+}
+
+/// Returns the unspecified endpoint in the same address family as `endpoint`.
+fn unspecified_endpoint(endpoint: &IpEndpoint) -> IpEndpoint {
+    let addr = match endpoint.addr {
+        IpAddress::Ipv4(_) => IpAddress::Ipv4(Ipv4Address::UNSPECIFIED),
+        IpAddress::Ipv6(_) => IpAddress::Ipv6(Ipv6Address::UNSPECIFIED),
+    };
+    IpEndpoint::new(addr, 0)
+}
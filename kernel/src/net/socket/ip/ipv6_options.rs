@@ -0,0 +1,217 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_bigtcp::{socket::NeedIfacePoll, wire::Ipv6Address};
+
+use crate::{
+    net::socket::options::{
+        SocketOption,
+        macros::{impl_socket_options, sock_option_mut, sock_option_ref},
+    },
+    prelude::*,
+};
+
+/// IPv6-level socket options.
+#[derive(Clone, Copy, CopyGetters, Debug, Setters)]
+#[get_copy = "pub"]
+#[set = "pub"]
+pub(super) struct Ipv6OptionSet {
+    v6only: bool,
+    unicast_hops: Ipv6Hops,
+    recvpktinfo: bool,
+    recverr: bool,
+    multicast_hops: u8,
+    multicast_loop: bool,
+    multicast_all: bool,
+    multicast_if: u32,
+}
+
+const DEFAULT_HOP_LIMIT: u8 = 64;
+const DEFAULT_MULTICAST_HOP_LIMIT: u8 = 1;
+
+impl Ipv6OptionSet {
+    pub(super) const fn new_udp() -> Self {
+        Self {
+            v6only: false,
+            unicast_hops: Ipv6Hops(None),
+            recvpktinfo: false,
+            recverr: false,
+            multicast_hops: DEFAULT_MULTICAST_HOP_LIMIT,
+            multicast_loop: true,
+            multicast_all: true,
+            multicast_if: 0,
+        }
+    }
+
+    pub(super) const fn new_raw() -> Self {
+        Self {
+            v6only: false,
+            unicast_hops: Ipv6Hops(None),
+            recvpktinfo: false,
+            recverr: false,
+            multicast_hops: DEFAULT_MULTICAST_HOP_LIMIT,
+            multicast_loop: true,
+            multicast_all: true,
+            multicast_if: 0,
+        }
+    }
+
+    pub(super) fn get_option(&self, option: &mut dyn SocketOption) -> Result<()> {
+        sock_option_mut!(match option {
+            ipv6_v6only @ V6Only => {
+                let v6only = self.v6only();
+                ipv6_v6only.set(v6only);
+            }
+            ipv6_unicast_hops @ UnicastHops => {
+                let unicast_hops = self.unicast_hops();
+                ipv6_unicast_hops.set(unicast_hops);
+            }
+            ipv6_recvpktinfo @ RecvPktInfo => {
+                let recvpktinfo = self.recvpktinfo();
+                ipv6_recvpktinfo.set(recvpktinfo);
+            }
+            ipv6_recverr @ Recverr => {
+                let recverr = self.recverr();
+                ipv6_recverr.set(recverr);
+            }
+            ipv6_multicast_hops @ MulticastHops => {
+                let multicast_hops = self.multicast_hops();
+                ipv6_multicast_hops.set(Ipv6MulticastHops(multicast_hops));
+            }
+            ipv6_multicast_loop @ MulticastLoop => {
+                let multicast_loop = self.multicast_loop();
+                ipv6_multicast_loop.set(multicast_loop);
+            }
+            ipv6_multicast_all @ MulticastAll => {
+                let multicast_all = self.multicast_all();
+                ipv6_multicast_all.set(multicast_all);
+            }
+            ipv6_multicast_if @ MulticastIf => {
+                let multicast_if = self.multicast_if();
+                ipv6_multicast_if.set(multicast_if);
+            }
+            _ => return_errno_with_message!(Errno::ENOPROTOOPT, "the socket option is unknown"),
+        });
+
+        Ok(())
+    }
+
+    pub(super) fn set_option(
+        &mut self,
+        option: &dyn SocketOption,
+        socket: &dyn SetIpv6LevelOption,
+    ) -> Result<NeedIfacePoll> {
+        sock_option_ref!(match option {
+            ipv6_v6only @ V6Only => {
+                let v6only = ipv6_v6only.get().unwrap();
+                socket.set_v6only(*v6only)?;
+                self.set_v6only(*v6only);
+            }
+            ipv6_unicast_hops @ UnicastHops => {
+                let unicast_hops = ipv6_unicast_hops.get().unwrap();
+                self.set_unicast_hops(*unicast_hops);
+            }
+            ipv6_recvpktinfo @ RecvPktInfo => {
+                let recvpktinfo = ipv6_recvpktinfo.get().unwrap();
+                self.set_recvpktinfo(*recvpktinfo);
+            }
+            ipv6_recverr @ Recverr => {
+                let recverr = ipv6_recverr.get().unwrap();
+                self.set_recverr(*recverr);
+            }
+            ipv6_multicast_hops @ MulticastHops => {
+                let multicast_hops = ipv6_multicast_hops.get().unwrap();
+                self.set_multicast_hops(multicast_hops.0);
+            }
+            ipv6_multicast_loop @ MulticastLoop => {
+                let multicast_loop = ipv6_multicast_loop.get().unwrap();
+                self.set_multicast_loop(*multicast_loop);
+            }
+            ipv6_multicast_all @ MulticastAll => {
+                let multicast_all = ipv6_multicast_all.get().unwrap();
+                self.set_multicast_all(*multicast_all);
+            }
+            ipv6_multicast_if @ MulticastIf => {
+                let multicast_if = ipv6_multicast_if.get().unwrap();
+                self.set_multicast_if(*multicast_if);
+            }
+            _ => return_errno_with_message!(
+                Errno::ENOPROTOOPT,
+                "the socket option to be set is unknown"
+            ),
+        });
+
+        Ok(NeedIfacePoll::FALSE)
+    }
+}
+
+impl_socket_options!(
+    pub struct V6Only(bool);
+    pub struct UnicastHops(Ipv6Hops);
+    pub struct RecvPktInfo(bool);
+    pub struct Recverr(bool);
+    pub struct MulticastHops(Ipv6MulticastHops);
+    pub struct MulticastLoop(bool);
+    pub struct MulticastAll(bool);
+    pub struct MulticastIf(u32);
+    pub struct JoinGroup(Ipv6Mreq);
+    pub struct LeaveGroup(Ipv6Mreq);
+);
+
+/// The hop limit of IPv6 packets.
+///
+/// Unlike [`IpTtl`], zero is a valid hop limit.
+///
+/// [`IpTtl`]: super::options::IpTtl
+#[derive(Clone, Copy, Debug)]
+pub struct Ipv6Hops(Option<u8>);
+
+impl Ipv6Hops {
+    pub const fn new(val: Option<u8>) -> Self {
+        Self(val)
+    }
+
+    pub const fn get(&self) -> u8 {
+        if let Some(val) = self.0 {
+            val
+        } else {
+            DEFAULT_HOP_LIMIT
+        }
+    }
+}
+
+/// The hop limit of outgoing multicast IPv6 packets.
+///
+/// Like [`Ipv6Hops`], zero is a valid hop limit.
+#[derive(Clone, Copy, Debug)]
+pub struct Ipv6MulticastHops(u8);
+
+impl Ipv6MulticastHops {
+    /// Creates the hop limit from the value set by the user space.
+    ///
+    /// Like Linux, `-1` means the default hop limit. This method fails with `EINVAL` if the
+    /// value is out of range.
+    pub fn new(val: i32) -> Result<Self> {
+        match val {
+            -1 => Ok(Self(DEFAULT_MULTICAST_HOP_LIMIT)),
+            0..=255 => Ok(Self(val as u8)),
+            _ => return_errno_with_message!(Errno::EINVAL, "invalid hop limit value"),
+        }
+    }
+
+    pub const fn get(&self) -> u8 {
+        self.0
+    }
+}
+
+/// A request to join or leave an IPv6 multicast group.
+#[derive(Clone, Copy, Debug)]
+pub struct Ipv6Mreq {
+    /// The address of the multicast group.
+    pub multiaddr: Ipv6Address,
+    /// The interface index, or zero.
+    pub ifindex: u32,
+}
+
+pub(super) trait SetIpv6LevelOption {
+    fn set_v6only(&self, _v6only: bool) -> Result<()>;
+}
@@ -2,12 +2,19 @@
 
 mod addr;
 mod common;
+mod ctrl_msg;
 mod datagram;
+mod diag;
+pub mod ipv6_options;
 pub mod options;
+mod raw;
 mod stream;
 
 pub use addr::IpAddressFamily;
+pub(super) use ctrl_msg::IpControlMessage;
 pub use datagram::DatagramSocket;
 pub(in crate::net) use datagram::observer::DatagramObserver;
+pub use diag::{InetSocketEntry, tcp_socket_entries, udp_socket_entries};
+pub use raw::RawSocket;
 pub(in crate::net) use stream::observer::StreamObserver;
 pub use stream::{StreamSocket, options as stream_options};
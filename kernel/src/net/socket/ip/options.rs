@@ -2,7 +2,7 @@
 
 use core::num::NonZeroU8;
 
-use aster_bigtcp::socket::NeedIfacePoll;
+use aster_bigtcp::{socket::NeedIfacePoll, wire::Ipv4Address};
 
 use crate::{
     net::socket::options::{
@@ -21,9 +21,17 @@ pub(super) struct IpOptionSet {
     ttl: IpTtl,
     hdrincl: bool,
     recverr: bool,
+    pktinfo: bool,
+    recvttl: bool,
+    recvtos: bool,
+    multicast_ttl: u8,
+    multicast_loop: bool,
+    multicast_all: bool,
+    multicast_if: IpMulticastIf,
 }
 
 const DEFAULT_TTL: u8 = 64;
+const DEFAULT_MULTICAST_TTL: u8 = 1;
 pub(super) const INET_ECN_MASK: u8 = 3;
 
 impl IpOptionSet {
@@ -33,6 +41,13 @@ impl IpOptionSet {
             ttl: IpTtl(None),
             hdrincl: false,
             recverr: false,
+            pktinfo: false,
+            recvttl: false,
+            recvtos: false,
+            multicast_ttl: DEFAULT_MULTICAST_TTL,
+            multicast_loop: true,
+            multicast_all: true,
+            multicast_if: IpMulticastIf::UNSPECIFIED,
         }
     }
 
@@ -42,6 +57,32 @@ impl IpOptionSet {
             ttl: IpTtl(None),
             hdrincl: false,
             recverr: false,
+            pktinfo: false,
+            recvttl: false,
+            recvtos: false,
+            multicast_ttl: DEFAULT_MULTICAST_TTL,
+            multicast_loop: true,
+            multicast_all: true,
+            multicast_if: IpMulticastIf::UNSPECIFIED,
+        }
+    }
+
+    /// Returns the default IP-level options for raw IP sockets and ping sockets.
+    ///
+    /// `IP_HDRINCL` is enabled by default for `IPPROTO_RAW` sockets.
+    pub(super) const fn new_raw(hdrincl: bool) -> Self {
+        Self {
+            tos: 0,
+            ttl: IpTtl(None),
+            hdrincl,
+            recverr: false,
+            pktinfo: false,
+            recvttl: false,
+            recvtos: false,
+            multicast_ttl: DEFAULT_MULTICAST_TTL,
+            multicast_loop: true,
+            multicast_all: true,
+            multicast_if: IpMulticastIf::UNSPECIFIED,
         }
     }
 
@@ -63,6 +104,34 @@ impl IpOptionSet {
                 let recverr = self.recverr();
                 ip_recverr.set(recverr);
             }
+            ip_pktinfo @ PktInfo => {
+                let pktinfo = self.pktinfo();
+                ip_pktinfo.set(pktinfo);
+            }
+            ip_recvttl @ RecvTtl => {
+                let recvttl = self.recvttl();
+                ip_recvttl.set(recvttl);
+            }
+            ip_recvtos @ RecvTos => {
+                let recvtos = self.recvtos();
+                ip_recvtos.set(recvtos);
+            }
+            ip_multicast_ttl @ MulticastTtl => {
+                let multicast_ttl = self.multicast_ttl();
+                ip_multicast_ttl.set(IpMulticastTtl(multicast_ttl));
+            }
+            ip_multicast_loop @ MulticastLoop => {
+                let multicast_loop = self.multicast_loop();
+                ip_multicast_loop.set(IpMulticastFlag(multicast_loop));
+            }
+            ip_multicast_all @ MulticastAll => {
+                let multicast_all = self.multicast_all();
+                ip_multicast_all.set(IpMulticastFlag(multicast_all));
+            }
+            ip_multicast_if @ MulticastIf => {
+                let multicast_if = self.multicast_if();
+                ip_multicast_if.set(multicast_if);
+            }
             _ => return_errno_with_message!(Errno::ENOPROTOOPT, "the socket option is unknown"),
         });
 
@@ -95,6 +164,34 @@ impl IpOptionSet {
                 let recverr = ip_recverr.get().unwrap();
                 self.set_recverr(*recverr);
             }
+            ip_pktinfo @ PktInfo => {
+                let pktinfo = ip_pktinfo.get().unwrap();
+                self.set_pktinfo(*pktinfo);
+            }
+            ip_recvttl @ RecvTtl => {
+                let recvttl = ip_recvttl.get().unwrap();
+                self.set_recvttl(*recvttl);
+            }
+            ip_recvtos @ RecvTos => {
+                let recvtos = ip_recvtos.get().unwrap();
+                self.set_recvtos(*recvtos);
+            }
+            ip_multicast_ttl @ MulticastTtl => {
+                let multicast_ttl = ip_multicast_ttl.get().unwrap();
+                self.set_multicast_ttl(multicast_ttl.0);
+            }
+            ip_multicast_loop @ MulticastLoop => {
+                let multicast_loop = ip_multicast_loop.get().unwrap();
+                self.set_multicast_loop(multicast_loop.0);
+            }
+            ip_multicast_all @ MulticastAll => {
+                let multicast_all = ip_multicast_all.get().unwrap();
+                self.set_multicast_all(multicast_all.0);
+            }
+            ip_multicast_if @ MulticastIf => {
+                let multicast_if = ip_multicast_if.get().unwrap();
+                self.set_multicast_if(*multicast_if);
+            }
             _ => return_errno_with_message!(
                 Errno::ENOPROTOOPT,
                 "the socket option to be set is unknown"
@@ -110,6 +207,15 @@ impl_socket_options!(
     pub struct Ttl(IpTtl);
     pub struct Hdrincl(bool);
     pub struct Recverr(bool);
+    pub struct PktInfo(bool);
+    pub struct RecvTtl(bool);
+    pub struct RecvTos(bool);
+    pub struct MulticastTtl(IpMulticastTtl);
+    pub struct MulticastLoop(IpMulticastFlag);
+    pub struct MulticastAll(IpMulticastFlag);
+    pub struct MulticastIf(IpMulticastIf);
+    pub struct AddMembership(IpMreqn);
+    pub struct DropMembership(IpMreqn);
 );
 
 #[derive(Clone, Copy, Debug)]
@@ -129,6 +235,80 @@ impl IpTtl {
     }
 }
 
+/// The TTL of outgoing multicast packets.
+///
+/// Unlike [`IpTtl`], zero is a valid TTL, which keeps the packets on the local host.
+#[derive(Clone, Copy, Debug)]
+pub struct IpMulticastTtl(u8);
+
+impl IpMulticastTtl {
+    /// Creates the TTL from the value set by the user space.
+    ///
+    /// Like Linux, `-1` means the default TTL. This method fails with `EINVAL` if the value is
+    /// out of range.
+    pub fn new(val: i32) -> Result<Self> {
+        match val {
+            -1 => Ok(Self(DEFAULT_MULTICAST_TTL)),
+            0..=255 => Ok(Self(val as u8)),
+            _ => return_errno_with_message!(Errno::EINVAL, "invalid multicast ttl value"),
+        }
+    }
+
+    pub const fn get(&self) -> u8 {
+        self.0
+    }
+}
+
+/// A boolean value of the IP-level multicast options (e.g., `IP_MULTICAST_LOOP`).
+///
+/// Unlike [`bool`], the value can also be set from a single byte in the user space.
+#[derive(Clone, Copy, Debug)]
+pub struct IpMulticastFlag(bool);
+
+impl IpMulticastFlag {
+    pub const fn new(val: bool) -> Self {
+        Self(val)
+    }
+
+    pub const fn get(&self) -> bool {
+        self.0
+    }
+}
+
+/// The interface for outgoing multicast packets, specified by `IP_MULTICAST_IF`.
+#[derive(Clone, Copy, Debug)]
+pub struct IpMulticastIf {
+    /// The local address of the interface, or the unspecified address.
+    pub addr: Ipv4Address,
+    /// The interface index, or zero.
+    pub ifindex: u32,
+}
+
+impl IpMulticastIf {
+    /// The unspecified interface, which means that the interface is selected by routing.
+    pub const UNSPECIFIED: Self = Self {
+        addr: Ipv4Address::UNSPECIFIED,
+        ifindex: 0,
+    };
+
+    pub fn is_unspecified(&self) -> bool {
+        self.addr.is_unspecified() && self.ifindex == 0
+    }
+}
+
+/// A request to join or leave an IPv4 multicast group.
+///
+/// The request can be read from either `struct ip_mreq` or `struct ip_mreqn` in the user space.
+#[derive(Clone, Copy, Debug)]
+pub struct IpMreqn {
+    /// The address of the multicast group.
+    pub multiaddr: Ipv4Address,
+    /// The local address of the interface, or the unspecified address.
+    pub address: Ipv4Address,
+    /// The interface index, or zero.
+    pub ifindex: u32,
+}
+
 pub(super) trait SetIpLevelOption {
     fn set_hdrincl(&self, _hdrincl: bool) -> Result<()>;
 }
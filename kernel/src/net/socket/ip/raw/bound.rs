@@ -0,0 +1,224 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_bigtcp::{
+    errors::raw::{RecvError, SendError},
+    socket::RAW_SEND_PAYLOAD_LEN,
+    wire::IpEndpoint,
+};
+
+use super::RawSocketKind;
+use crate::{
+    events::IoEvents,
+    net::{
+        iface::{BoundRawPort, Iface, RawIpSocket},
+        socket::{
+            ip::IpAddressFamily,
+            util::{SendRecvFlags, datagram_common},
+        },
+    },
+    prelude::*,
+    util::{MultiRead, MultiWrite},
+};
+
+pub(super) struct BoundRaw {
+    bound_socket: RawIpSocket,
+    kind: RawSocketKind,
+    remote_endpoint: Option<IpEndpoint>,
+}
+
+/// The IP-level options that affect how a packet is sent.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct SendOptions {
+    /// Whether the packet starts with an IP header supplied by the user (i.e., `IP_HDRINCL`).
+    pub(super) hdrincl: bool,
+    /// The TTL or the hop limit of the packet.
+    pub(super) hop_limit: u8,
+}
+
+impl Default for SendOptions {
+    fn default() -> Self {
+        Self {
+            hdrincl: false,
+            hop_limit: DEFAULT_HOP_LIMIT,
+        }
+    }
+}
+
+const DEFAULT_HOP_LIMIT: u8 = 64;
+
+impl BoundRaw {
+    pub(super) fn new(bound_socket: RawIpSocket, kind: RawSocketKind) -> Self {
+        Self {
+            bound_socket,
+            kind,
+            remote_endpoint: None,
+        }
+    }
+
+    pub(super) fn iface(&self) -> &Arc<Iface> {
+        self.bound_socket.iface()
+    }
+
+    pub(super) fn bound_port(&self) -> &BoundRawPort {
+        self.bound_socket.bound_port()
+    }
+
+    pub(super) fn kind(&self) -> RawSocketKind {
+        self.kind
+    }
+
+    /// Returns the address family of the local endpoint.
+    pub(super) fn family(&self) -> IpAddressFamily {
+        IpAddressFamily::from(self.bound_socket.local_endpoint().unwrap().addr)
+    }
+
+    /// Sends a packet with the specified options.
+    pub(super) fn try_send_with(
+        &self,
+        reader: &mut dyn MultiRead,
+        remote: &IpEndpoint,
+        options: SendOptions,
+    ) -> Result<usize> {
+        let len = reader.sum_lens();
+        if len > RAW_SEND_PAYLOAD_LEN {
+            return_errno_with_message!(Errno::EMSGSIZE, "the message is too large");
+        }
+
+        let mut packet = vec![0u8; len];
+        reader.read(&mut VmWriter::from(packet.as_mut_slice()))?;
+
+        if self.kind == RawSocketKind::Ping {
+            check_echo_request(&packet, self.family())?;
+        }
+
+        let result = if options.hdrincl {
+            if self.family() == IpAddressFamily::IPv6 {
+                return_errno_with_message!(
+                    Errno::EOPNOTSUPP,
+                    "sending IPv6 packets with the IP header included is not supported"
+                );
+            }
+            self.bound_socket.send_hdrincl(&packet)
+        } else {
+            self.bound_socket
+                .send(&packet, remote.addr, options.hop_limit)
+        };
+
+        match result {
+            Ok(()) => Ok(len),
+            Err(SendError::TooLarge) => {
+                return_errno_with_message!(Errno::EMSGSIZE, "the message is too large");
+            }
+            Err(SendError::Unaddressable) => {
+                return_errno_with_message!(Errno::EINVAL, "the destination address is invalid");
+            }
+            Err(SendError::BufferFull) => {
+                return_errno_with_message!(Errno::EAGAIN, "the send buffer is full");
+            }
+            Err(SendError::Malformed) => {
+                return_errno_with_message!(Errno::EINVAL, "the IP header is invalid");
+            }
+        }
+    }
+}
+
+/// Checks that the packet to be sent via a ping socket is an ICMP echo request.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/ipv4/ping.c>
+fn check_echo_request(packet: &[u8], family: IpAddressFamily) -> Result<()> {
+    /// The length of the ICMP header.
+    const ICMP_HEADER_LEN: usize = 8;
+    /// The type of ICMP echo requests.
+    const ICMP_ECHO: u8 = 8;
+    /// The type of ICMPv6 echo requests.
+    const ICMPV6_ECHO_REQUEST: u8 = 128;
+
+    if packet.len() < ICMP_HEADER_LEN {
+        return_errno_with_message!(Errno::EINVAL, "the ICMP message is too short");
+    }
+
+    let echo_request_type = match family {
+        IpAddressFamily::IPv4 => ICMP_ECHO,
+        IpAddressFamily::IPv6 => ICMPV6_ECHO_REQUEST,
+    };
+    if packet[0] != echo_request_type || packet[1] != 0 {
+        return_errno_with_message!(
+            Errno::EINVAL,
+            "only ICMP echo requests can be sent via ping sockets"
+        );
+    }
+
+    Ok(())
+}
+
+impl datagram_common::Bound for BoundRaw {
+    type Endpoint = IpEndpoint;
+
+    fn local_endpoint(&self) -> Self::Endpoint {
+        self.bound_socket.local_endpoint().unwrap()
+    }
+
+    fn remote_endpoint(&self) -> Option<&Self::Endpoint> {
+        self.remote_endpoint.as_ref()
+    }
+
+    fn set_remote_endpoint(&mut self, endpoint: &Self::Endpoint) {
+        self.remote_endpoint = Some(*endpoint)
+    }
+
+    fn try_recv(
+        &self,
+        writer: &mut dyn MultiWrite,
+        _flags: SendRecvFlags,
+    ) -> Result<(usize, Self::Endpoint)> {
+        // Like Linux, the IP header is delivered to IPv4 raw IP sockets but not to IPv6 raw IP
+        // sockets or ping sockets.
+        let strip_header =
+            self.kind == RawSocketKind::Ping || self.family() == IpAddressFamily::IPv6;
+
+        let result = self.bound_socket.recv(|packet, raw_metadata| {
+            let data = if strip_header {
+                &packet[raw_metadata.header_len..]
+            } else {
+                packet
+            };
+            let copied_res = writer.write(&mut VmReader::from(data)).map_err(Into::into);
+            (copied_res, raw_metadata.remote_addr)
+        });
+
+        match result {
+            // The port is always zero for raw IP sockets and ping sockets.
+            Ok((Ok(res), remote_addr)) => Ok((res, IpEndpoint::new(remote_addr, 0))),
+            Ok((Err(e), _)) => Err(e),
+            Err(RecvError::Exhausted) => {
+                return_errno_with_message!(Errno::EAGAIN, "the receive buffer is empty")
+            }
+            Err(RecvError::Truncated) => {
+                unreachable!("`recv` should never fail with `RecvError::Truncated`")
+            }
+        }
+    }
+
+    fn try_send(
+        &self,
+        reader: &mut dyn MultiRead,
+        remote: &Self::Endpoint,
+        _flags: SendRecvFlags,
+    ) -> Result<usize> {
+        self.try_send_with(reader, remote, SendOptions::default())
+    }
+
+    fn check_io_events(&self) -> IoEvents {
+        let mut events = IoEvents::empty();
+
+        if self.bound_socket.can_recv() {
+            events |= IoEvents::IN;
+        }
+
+        if self.bound_socket.can_send() {
+            events |= IoEvents::OUT;
+        }
+
+        events
+    }
+}
@@ -0,0 +1,469 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use aster_bigtcp::wire::IpEndpoint;
+use bound::{BoundRaw, SendOptions};
+use unbound::{BindOptions, UnboundRaw};
+
+use super::addr::IpAddressFamily;
+use crate::{
+    events::IoEvents,
+    fs::{pseudofs::SockFs, vfs::path::Path},
+    net::{
+        iface::is_broadcast_endpoint,
+        net_ns::NetNamespace,
+        socket::{
+            Socket,
+            ip::{
+                ipv6_options::{Ipv6OptionSet, SetIpv6LevelOption},
+                options::{IpOptionSet, SetIpLevelOption},
+            },
+            options::{Error as SocketError, SocketOption, macros::sock_option_mut},
+            private::SocketPrivate,
+            util::{
+                MessageHeader, SendRecvFlags, SocketAddr,
+                datagram_common::{Inner, select_remote_and_bind},
+                options::{GetSocketLevelOption, SetSocketLevelOption, SocketOptionSet},
+            },
+        },
+    },
+    prelude::*,
+    process::{
+        credentials::capabilities::CapSet,
+        posix_thread::PosixThread,
+        signal::{PollHandle, Pollable, Pollee},
+    },
+    util::{MultiRead, MultiWrite},
+};
+
+mod bound;
+mod unbound;
+
+/// A raw IP socket or a ping socket.
+///
+/// A raw IP socket (i.e., `SOCK_RAW`) sends and receives IP packets of a specific protocol. A ping
+/// socket (i.e., `SOCK_DGRAM` with `IPPROTO_ICMP` or `IPPROTO_ICMPV6`) is an unprivileged
+/// alternative that only sends ICMP echo requests and receives the matching echo replies.
+//
+// TODO: An unbound socket should receive packets from all the interfaces. Currently, a socket
+// receives packets only after it is bound, either explicitly or implicitly by sending a packet.
+pub struct RawSocket {
+    // Lock order: `inner` first, `options` second
+    inner: RwMutex<Inner<UnboundRaw, BoundRaw>>,
+    options: RwLock<OptionSet>,
+    kind: RawSocketKind,
+    family: IpAddressFamily,
+    net_ns: Arc<NetNamespace>,
+
+    is_nonblocking: AtomicBool,
+    pollee: Pollee,
+    pseudo_path: Path,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum RawSocketKind {
+    /// A raw IP socket of the specified protocol.
+    Raw(u8),
+    /// A ping socket.
+    Ping,
+}
+
+#[derive(Clone, Debug)]
+struct OptionSet {
+    socket: SocketOptionSet,
+    ip: IpOptionSet,
+    ipv6: Ipv6OptionSet,
+}
+
+/// The protocol number of `IPPROTO_RAW`.
+const IPPROTO_RAW: u8 = 255;
+
+impl OptionSet {
+    fn new(kind: RawSocketKind) -> Self {
+        let socket = SocketOptionSet::new_raw();
+        // Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/ipv4/af_inet.c>
+        let ip = IpOptionSet::new_raw(kind == RawSocketKind::Raw(IPPROTO_RAW));
+        let ipv6 = Ipv6OptionSet::new_raw();
+        OptionSet { socket, ip, ipv6 }
+    }
+}
+
+impl RawSocket {
+    /// Creates a raw IP socket of the specified protocol.
+    ///
+    /// This method will fail with [`EPERM`] if the thread does not have the NET_RAW capability.
+    ///
+    /// [`EPERM`]: crate::error::Errno::EPERM
+    pub fn new_raw(
+        is_nonblocking: bool,
+        family: IpAddressFamily,
+        protocol: u8,
+        net_ns: Arc<NetNamespace>,
+        posix_thread: &PosixThread,
+    ) -> Result<Arc<Self>> {
+        net_ns.check_cap(CapSet::NET_RAW, posix_thread)?;
+
+        Ok(Self::new(
+            is_nonblocking,
+            family,
+            RawSocketKind::Raw(protocol),
+            net_ns,
+        ))
+    }
+
+    /// Creates a ping socket.
+    ///
+    /// This method will fail with [`EACCES`] if neither the effective group nor the supplementary
+    /// groups of the thread are in the `net.ipv4.ping_group_range` sysctl.
+    ///
+    /// [`EACCES`]: crate::error::Errno::EACCES
+    //
+    // Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/ipv4/ping.c>
+    pub fn new_ping(
+        is_nonblocking: bool,
+        family: IpAddressFamily,
+        net_ns: Arc<NetNamespace>,
+        posix_thread: &PosixThread,
+    ) -> Result<Arc<Self>> {
+        let (low, high) = net_ns.ping_group_range();
+        let credentials = posix_thread.credentials();
+        let is_allowed = |gid| low <= gid && gid <= high;
+        if !is_allowed(credentials.egid()) && !credentials.groups().iter().copied().any(is_allowed)
+        {
+            return_errno_with_message!(
+                Errno::EACCES,
+                "the group is not allowed to create ping sockets"
+            );
+        }
+
+        Ok(Self::new(
+            is_nonblocking,
+            family,
+            RawSocketKind::Ping,
+            net_ns,
+        ))
+    }
+
+    fn new(
+        is_nonblocking: bool,
+        family: IpAddressFamily,
+        kind: RawSocketKind,
+        net_ns: Arc<NetNamespace>,
+    ) -> Arc<Self> {
+        let unbound_raw = UnboundRaw::new(kind, net_ns.clone());
+        Arc::new(Self {
+            inner: RwMutex::new(Inner::Unbound(unbound_raw)),
+            options: RwLock::new(OptionSet::new(kind)),
+            kind,
+            family,
+            net_ns,
+            is_nonblocking: AtomicBool::new(is_nonblocking),
+            pollee: Pollee::new(),
+            pseudo_path: SockFs::new_path(),
+        })
+    }
+
+    fn try_recv(
+        &self,
+        writer: &mut dyn MultiWrite,
+        flags: SendRecvFlags,
+    ) -> Result<(usize, MessageHeader)> {
+        let (recv_bytes, remote_endpoint) = self.inner.read().try_recv(writer, flags)?;
+        self.pollee.invalidate();
+
+        let peer_addr = self.family.socket_addr_from(remote_endpoint);
+        Ok((recv_bytes, MessageHeader::new(Some(peer_addr), Vec::new())))
+    }
+
+    fn try_send(&self, reader: &mut dyn MultiRead, remote: Option<&IpEndpoint>) -> Result<usize> {
+        let send_options = {
+            let options = self.options.read();
+            let hop_limit = match self.family {
+                IpAddressFamily::IPv4 => options.ip.ttl().get(),
+                IpAddressFamily::IPv6 => options.ipv6.unicast_hops().get(),
+            };
+            SendOptions {
+                hdrincl: options.ip.hdrincl(),
+                hop_limit,
+            }
+        };
+
+        let (sent_bytes, iface_to_poll) = select_remote_and_bind(
+            &self.inner,
+            remote,
+            || {
+                let remote_endpoint = remote.ok_or_else(|| {
+                    Error::with_message(
+                        Errno::EDESTADDRREQ,
+                        "the destination address is not specified",
+                    )
+                })?;
+                self.inner
+                    .write()
+                    .bind_ephemeral(remote_endpoint, &self.pollee)
+            },
+            |bound_raw, remote_endpoint| {
+                if bound_raw.family() != IpAddressFamily::from(remote_endpoint.addr) {
+                    return_errno_with_message!(
+                        Errno::ENETUNREACH,
+                        "the destination address family does not match the bound address family"
+                    );
+                }
+
+                let sent_bytes = bound_raw.try_send_with(reader, remote_endpoint, send_options)?;
+                let iface_to_poll = bound_raw.iface().clone();
+                Ok((sent_bytes, iface_to_poll))
+            },
+        )?;
+
+        self.pollee.invalidate();
+        iface_to_poll.poll();
+
+        Ok(sent_bytes)
+    }
+
+    /// Returns the port reported to the user space when the socket is not bound.
+    ///
+    /// Like Linux, the protocol number is reported as the port for raw IP sockets.
+    fn unbound_port(&self) -> u16 {
+        match self.kind {
+            RawSocketKind::Raw(protocol) => protocol as u16,
+            RawSocketKind::Ping => 0,
+        }
+    }
+}
+
+impl Pollable for RawSocket {
+    fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.pollee
+            .poll_with(mask, poller, || self.inner.read().check_io_events())
+    }
+}
+
+impl SocketPrivate for RawSocket {
+    fn is_nonblocking(&self) -> bool {
+        self.is_nonblocking.load(Ordering::Relaxed)
+    }
+
+    fn set_nonblocking(&self, is_nonblocking: bool) {
+        self.is_nonblocking.store(is_nonblocking, Ordering::Relaxed);
+    }
+}
+
+// IPv4-mapped IPv6 addresses are never accepted because an IPv6 raw IP socket (or ping socket)
+// cannot send or receive IPv4 packets. So `v6only` is always true when converting addresses.
+impl Socket for RawSocket {
+    fn bind(&self, socket_addr: SocketAddr) -> Result<()> {
+        let can_reuse = self.options.read().socket.reuse_addr();
+        let endpoint = self.family.local_endpoint_from(socket_addr, true)?;
+
+        self.inner
+            .write()
+            .bind(&endpoint, &self.pollee, BindOptions { can_reuse })
+    }
+
+    fn connect(&self, socket_addr: SocketAddr) -> Result<()> {
+        let can_broadcast = self.options.read().socket.broadcast();
+        let endpoint = self.family.remote_endpoint_from(socket_addr, true)?;
+        if !can_broadcast && is_broadcast_endpoint(&endpoint, &self.net_ns) {
+            return_errno_with_message!(
+                Errno::EACCES,
+                "connecting to a broadcast address without SO_BROADCAST is not allowed"
+            );
+        }
+
+        self.inner.write().connect(&endpoint, &self.pollee)
+    }
+
+    fn addr(&self) -> Result<SocketAddr> {
+        let endpoint = self.inner.read().addr().unwrap_or_else(|| {
+            let mut endpoint = self.family.unspecified_endpoint();
+            endpoint.port = self.unbound_port();
+            endpoint
+        });
+
+        Ok(self.family.socket_addr_from(endpoint))
+    }
+
+    fn peer_addr(&self) -> Result<SocketAddr> {
+        let endpoint =
+            *self.inner.read().peer_addr().ok_or_else(|| {
+                Error::with_message(Errno::ENOTCONN, "the socket is not connected")
+            })?;
+
+        Ok(self.family.socket_addr_from(endpoint))
+    }
+
+    fn sendmsg(
+        &self,
+        reader: &mut dyn MultiRead,
+        message_header: MessageHeader,
+        flags: SendRecvFlags,
+    ) -> Result<usize> {
+        // TODO: Deal with flags
+        if !flags.is_all_supported() {
+            warn!("unsupported flags: {:?}", flags);
+        }
+
+        let MessageHeader {
+            addr,
+            control_messages,
+        } = message_header;
+
+        let endpoint = match addr {
+            Some(addr) => Some(self.family.remote_endpoint_from(addr, true)?),
+            None => None,
+        };
+
+        if let Some(endpoint) = endpoint.as_ref() {
+            let can_broadcast = self.options.read().socket.broadcast();
+            if !can_broadcast && is_broadcast_endpoint(endpoint, &self.net_ns) {
+                return_errno_with_message!(
+                    Errno::EACCES,
+                    "sending to a broadcast address without SO_BROADCAST is not allowed"
+                );
+            }
+        }
+
+        if !control_messages.is_empty() {
+            // TODO: Support sending control message
+            warn!("sending control message is not supported");
+        }
+
+        // TODO: Block if the send buffer is full
+        self.try_send(reader, endpoint.as_ref())
+    }
+
+    fn recvmsg(
+        &self,
+        writer: &mut dyn MultiWrite,
+        flags: SendRecvFlags,
+    ) -> Result<(usize, MessageHeader)> {
+        // TODO: Deal with flags
+        if !flags.is_all_supported() {
+            warn!("unsupported flags: {:?}", flags);
+        }
+
+        self.block_on(IoEvents::IN, || self.try_recv(writer, flags))
+    }
+
+    fn get_option(&self, option: &mut dyn SocketOption) -> Result<()> {
+        sock_option_mut!(match option {
+            socket_errors @ SocketError => {
+                // TODO: Support socket errors for raw IP sockets
+                socket_errors.set(None);
+                return Ok(());
+            }
+            _ => (),
+        });
+
+        let inner = self.inner.read();
+        let options = self.options.read();
+
+        // Deal with socket-level options
+        match options.socket.get_option(option, &*inner) {
+            Err(err) if err.error() == Errno::ENOPROTOOPT => (),
+            res => return res,
+        }
+
+        // Deal with IP-level options
+        match options.ip.get_option(option) {
+            Err(err)
+                if err.error() == Errno::ENOPROTOOPT && self.family == IpAddressFamily::IPv6 => {}
+            res => return res,
+        }
+
+        // Deal with IPv6-level options
+        options.ipv6.get_option(option)
+    }
+
+    fn set_option(&self, option: &dyn SocketOption) -> Result<()> {
+        let inner = self.inner.read();
+        let mut options = self.options.write();
+
+        // Deal with socket-level options
+        let need_iface_poll = match options.socket.set_option(option, &*inner) {
+            Err(err) if err.error() == Errno::ENOPROTOOPT => {
+                // Deal with IP-level options
+                match options.ip.set_option(option, self) {
+                    Err(err)
+                        if err.error() == Errno::ENOPROTOOPT
+                            && self.family == IpAddressFamily::IPv6 =>
+                    {
+                        // Deal with IPv6-level options
+                        options.ipv6.set_option(option, &*inner)?
+                    }
+                    res => res?,
+                }
+            }
+            Err(err) => return Err(err),
+            Ok(need_iface_poll) => need_iface_poll,
+        };
+
+        let iface_to_poll = need_iface_poll
+            .then(|| match &*inner {
+                Inner::Unbound(_) => None,
+                Inner::Bound(bound_raw) => Some(bound_raw.iface().clone()),
+            })
+            .flatten();
+
+        drop(inner);
+        drop(options);
+
+        if let Some(iface) = iface_to_poll {
+            iface.poll();
+        }
+
+        Ok(())
+    }
+
+    fn pseudo_path(&self) -> &Path {
+        &self.pseudo_path
+    }
+}
+
+impl GetSocketLevelOption for Inner<UnboundRaw, BoundRaw> {
+    fn is_listening(&self) -> bool {
+        false
+    }
+}
+
+impl SetSocketLevelOption for Inner<UnboundRaw, BoundRaw> {
+    fn set_reuse_addr(&self, reuse_addr: bool) {
+        let Inner::Bound(bound) = self else {
+            return;
+        };
+
+        // The "ports" of raw IP sockets can always be reused.
+        if bound.kind() == RawSocketKind::Ping {
+            bound.bound_port().set_can_reuse(reuse_addr);
+        }
+    }
+}
+
+impl SetIpLevelOption for RawSocket {
+    fn set_hdrincl(&self, _hdrincl: bool) -> Result<()> {
+        if self.kind == RawSocketKind::Ping || self.family == IpAddressFamily::IPv6 {
+            return_errno_with_message!(
+                Errno::ENOPROTOOPT,
+                "IP_HDRINCL can only be set on IPv4 raw IP sockets"
+            );
+        }
+
+        Ok(())
+    }
+}
+
+impl SetIpv6LevelOption for Inner<UnboundRaw, BoundRaw> {
+    fn set_v6only(&self, _v6only: bool) -> Result<()> {
+        if let Inner::Bound(_) = self {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "IPV6_V6ONLY cannot be changed after the socket is bound"
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_bigtcp::{
+    iface::BindPortConfig,
+    wire::{IpEndpoint, IpProtocol},
+};
+
+use super::{RawSocketKind, bound::BoundRaw};
+use crate::{
+    events::IoEvents,
+    net::{
+        iface::RawIpSocket,
+        net_ns::NetNamespace,
+        socket::{
+            ip::{
+                DatagramObserver,
+                common::{get_ephemeral_endpoint, resolve_bind_iface},
+            },
+            util::datagram_common,
+        },
+    },
+    prelude::*,
+    process::signal::Pollee,
+};
+
+pub(super) struct UnboundRaw {
+    kind: RawSocketKind,
+    net_ns: Arc<NetNamespace>,
+}
+
+impl UnboundRaw {
+    pub(super) fn new(kind: RawSocketKind, net_ns: Arc<NetNamespace>) -> Self {
+        Self { kind, net_ns }
+    }
+}
+
+pub(super) struct BindOptions {
+    pub(super) can_reuse: bool,
+}
+
+impl datagram_common::Unbound for UnboundRaw {
+    type Endpoint = IpEndpoint;
+    type BindOptions = BindOptions;
+
+    type Bound = BoundRaw;
+
+    fn bind(
+        &mut self,
+        endpoint: &Self::Endpoint,
+        pollee: &Pollee,
+        options: BindOptions,
+    ) -> Result<Self::Bound> {
+        let iface = resolve_bind_iface(&endpoint.addr, &self.net_ns)?;
+        let observer = DatagramObserver::new(pollee.clone());
+
+        let bound_socket = match self.kind {
+            // Like Linux, the port is ignored when binding a raw IP socket.
+            RawSocketKind::Raw(protocol) => {
+                let bound_port = iface.bind_raw(endpoint.addr, protocol)?;
+                RawIpSocket::new_raw(bound_port, IpProtocol::from(protocol), observer)
+            }
+            // The port is used as the identifier of the ICMP echo messages.
+            RawSocketKind::Ping => {
+                let config = BindPortConfig::new(*endpoint, options.can_reuse);
+                let bound_port = iface.bind_ping(config)?;
+                RawIpSocket::new_ping(bound_port, observer)
+            }
+        };
+
+        Ok(BoundRaw::new(bound_socket, self.kind))
+    }
+
+    fn bind_ephemeral(
+        &mut self,
+        remote_endpoint: &Self::Endpoint,
+        pollee: &Pollee,
+    ) -> Result<Self::Bound> {
+        let endpoint = get_ephemeral_endpoint(remote_endpoint, &self.net_ns)?;
+        self.bind(&endpoint, pollee, BindOptions { can_reuse: false })
+    }
+
+    fn check_io_events(&self) -> IoEvents {
+        IoEvents::OUT
+    }
+}
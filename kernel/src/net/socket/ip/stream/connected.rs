@@ -1,7 +1,7 @@
 // SPDX-License-Identifier: MPL-2.0
 
 use aster_bigtcp::{
-    errors::tcp::{IoError, RecvError, SendError},
+    errors::tcp::{ConnError, IoError, RecvError, SendError},
     socket::{NeedIfacePoll, RawTcpSetOption},
     wire::IpEndpoint,
 };
@@ -92,6 +92,9 @@ impl ConnectedStream {
             Err(IoError::Socket(RecvError::ConnReset)) => {
                 return_errno_with_message!(Errno::ECONNRESET, "the connection is reset")
             }
+            Err(IoError::Socket(RecvError::TimedOut)) => {
+                return_errno_with_message!(Errno::ETIMEDOUT, "the connection is timed out")
+            }
         }
     }
 
@@ -116,6 +119,9 @@ impl ConnectedStream {
             Err(IoError::Socket(SendError::ConnReset)) => {
                 return_errno_with_message!(Errno::ECONNRESET, "the connection is reset");
             }
+            Err(IoError::Socket(SendError::TimedOut)) => {
+                return_errno_with_message!(Errno::ETIMEDOUT, "the connection is timed out");
+            }
         }
     }
 
@@ -174,8 +180,8 @@ impl ConnectedStream {
                 events |= IoEvents::HUP;
             }
 
-            // If the connection is reset, add an ERR event.
-            if socket.is_rst_closed() {
+            // If the connection is reset or timed out, add an ERR event.
+            if socket.conn_error().is_some() {
                 events |= IoEvents::ERR;
             }
 
@@ -184,14 +190,13 @@ impl ConnectedStream {
     }
 
     pub(super) fn test_and_clear_error(&self) -> Option<Error> {
-        if self.tcp_conn.clear_rst_closed() {
-            Some(Error::with_message(
-                Errno::ECONNRESET,
-                "the connection is reset",
-            ))
-        } else {
-            None
-        }
+        let error = match self.tcp_conn.clear_conn_error()? {
+            ConnError::Reset => Error::with_message(Errno::ECONNRESET, "the connection is reset"),
+            ConnError::TimedOut => {
+                Error::with_message(Errno::ETIMEDOUT, "the connection is timed out")
+            }
+        };
+        Some(error)
     }
 
     pub(super) fn set_raw_option<R>(
@@ -10,7 +10,7 @@ use super::{connected::ConnectedStream, init::InitStream, observer::StreamObserv
 use crate::{
     events::IoEvents,
     net::{
-        iface::{BoundTcpPort, Iface, TcpConnection},
+        iface::{BoundTcpPort, Iface, RawTcpSocketExt, TcpConnection},
         socket::ip::IpAddressFamily,
     },
     prelude::*,
@@ -120,6 +120,10 @@ impl ConnectingStream {
         set_option(&self.tcp_conn)
     }
 
+    pub(super) fn raw_with<R>(&self, f: impl FnOnce(&RawTcpSocketExt) -> R) -> R {
+        self.tcp_conn.raw_with(f)
+    }
+
     pub(super) fn into_connection(self) -> TcpConnection {
         self.tcp_conn
     }
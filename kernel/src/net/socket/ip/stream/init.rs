@@ -12,6 +12,7 @@ use crate::{
     events::IoEvents,
     net::{
         iface::BoundTcpPort,
+        net_ns::NetNamespace,
         socket::{
             ip::{
                 addr::IpAddressFamily,
@@ -85,7 +86,12 @@ impl InitStream {
         self.family
     }
 
-    pub(super) fn bind(&mut self, endpoint: &IpEndpoint, can_reuse: bool) -> Result<()> {
+    pub(super) fn bind(
+        &mut self,
+        endpoint: &IpEndpoint,
+        can_reuse: bool,
+        net_ns: &NetNamespace,
+    ) -> Result<()> {
         if self.bound_port.is_some() {
             return_errno_with_message!(Errno::EINVAL, "the socket is already bound to an address");
         }
@@ -99,7 +105,7 @@ impl InitStream {
             );
         }
 
-        self.bound_port = Some(bind_port(endpoint, can_reuse)?);
+        self.bound_port = Some(bind_port(endpoint, can_reuse, net_ns)?);
 
         Ok(())
     }
@@ -114,6 +120,7 @@ impl InitStream {
         option: &RawTcpOption,
         can_reuse: bool,
         observer: StreamObserver,
+        net_ns: &NetNamespace,
     ) -> Result<ConnectingStream, (Error, Self)> {
         debug_assert!(
             self.is_connect_done,
@@ -135,19 +142,11 @@ impl InitStream {
         let bound_port = if let Some(bound_port) = self.bound_port {
             bound_port
         } else {
-            let endpoint = match get_ephemeral_endpoint(remote_endpoint) {
-                Some(ep) => ep,
-                None => {
-                    return Err((
-                        Error::with_message(
-                            Errno::EADDRNOTAVAIL,
-                            "no interface has an address for the specified family",
-                        ),
-                        self,
-                    ));
-                }
+            let endpoint = match get_ephemeral_endpoint(remote_endpoint, net_ns) {
+                Ok(endpoint) => endpoint,
+                Err(err) => return Err((err, self)),
             };
-            match bind_port(&endpoint, can_reuse) {
+            match bind_port(&endpoint, can_reuse, net_ns) {
                 Ok(bound_port) => bound_port,
                 Err(err) => return Err((err, self)),
             }
@@ -273,7 +272,11 @@ impl InitStream {
     }
 }
 
-fn bind_port(endpoint: &IpEndpoint, can_reuse: bool) -> Result<BoundTcpPort> {
-    let (iface, config) = resolve_bind_iface_and_config(endpoint, can_reuse)?;
+fn bind_port(
+    endpoint: &IpEndpoint,
+    can_reuse: bool,
+    net_ns: &NetNamespace,
+) -> Result<BoundTcpPort> {
+    let (iface, config) = resolve_bind_iface_and_config(endpoint, can_reuse, net_ns)?;
     Ok(iface.bind_tcp(config)?)
 }
@@ -1,10 +1,12 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use core::sync::atomic::{AtomicBool, Ordering};
+use core::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
 
 use aster_bigtcp::{
     socket::{NeedIfacePoll, RawTcpOption, RawTcpSetOption},
-    time::Duration,
     wire::IpEndpoint,
 };
 use connected::{ConnectedStream, close_and_linger};
@@ -13,14 +15,15 @@ use init::InitStream;
 use listen::ListenStream;
 use observer::StreamObserver;
 use options::{
-    Congestion, DeferAccept, Inq, KeepCnt, KeepIdle, KeepIntvl, MaxSegment, NoDelay, SynCnt,
-    UserTimeout, WindowClamp,
+    Congestion, DeferAccept, Info, Inq, KeepCnt, KeepIdle, KeepIntvl, MaxSegment, NoDelay, SynCnt,
+    TcpInfo, UserTimeout, WindowClamp,
 };
 use ostd::sync::{PreemptDisabled, RwLockReadGuard, RwLockWriteGuard};
 use takeable::Takeable;
 use util::{Retrans, TcpOptionSet};
 
 use super::{
+    IpControlMessage,
     addr::IpAddressFamily,
     options::{IpOptionSet, SetIpLevelOption},
 };
@@ -28,7 +31,8 @@ use crate::{
     events::IoEvents,
     fs::{file::FileLike, pseudofs::SockFs, vfs::path::Path},
     net::{
-        iface::Iface,
+        iface::{Iface, RawTcpSocketExt},
+        net_ns::NetNamespace,
         socket::{
             Socket,
             options::{
@@ -37,7 +41,8 @@ use crate::{
             },
             private::SocketPrivate,
             util::{
-                MessageHeader, SendRecvFlags, SockShutdownCmd, SocketAddr,
+                ControlMessage, MessageHeader, SendRecvFlags, SockShutdownCmd, SocketAddr,
+                SocketInode,
                 options::{GetSocketLevelOption, SetSocketLevelOption, SocketOptionSet},
             },
         },
@@ -61,6 +66,7 @@ pub struct StreamSocket {
     // and other locks in `aster-bigtcp`), which will break the atomic mode.
     state: RwLock<Takeable<State>>,
     options: RwLock<OptionSet>,
+    net_ns: Arc<NetNamespace>,
 
     is_nonblocking: AtomicBool,
     pollee: Pollee,
@@ -94,28 +100,43 @@ impl OptionSet {
     }
 
     fn raw(&self) -> RawTcpOption {
-        let keep_alive_interval = Duration::from_secs(self.tcp.keep_intvl() as u64);
-
         RawTcpOption {
-            keep_alive: self.socket.keep_alive().then_some(keep_alive_interval),
+            keep_alive: self
+                .socket
+                .keep_alive()
+                .then(|| self.tcp.keep_alive_config()),
             is_nagle_enabled: !self.tcp.no_delay(),
+            congestion_control: self.tcp.congestion(),
         }
     }
 }
 
 impl StreamSocket {
-    pub fn new(is_nonblocking: bool, family: IpAddressFamily) -> Arc<Self> {
+    pub fn new(
+        is_nonblocking: bool,
+        family: IpAddressFamily,
+        net_ns: Arc<NetNamespace>,
+    ) -> Arc<Self> {
         let init_stream = InitStream::new(family);
+
+        let mut options = OptionSet::new();
+        options.tcp.set_congestion(net_ns.tcp_congestion_control());
+
         Arc::new(Self {
             state: RwLock::new(Takeable::new(State::Init(init_stream))),
-            options: RwLock::new(OptionSet::new()),
+            options: RwLock::new(options),
+            net_ns,
             is_nonblocking: AtomicBool::new(is_nonblocking),
             pollee: Pollee::new(),
             pseudo_path: SockFs::new_path(),
         })
     }
 
-    fn new_accepted(connected_stream: ConnectedStream, listener_options: &OptionSet) -> Arc<Self> {
+    fn new_accepted(
+        connected_stream: ConnectedStream,
+        listener_options: &OptionSet,
+        net_ns: Arc<NetNamespace>,
+    ) -> Arc<Self> {
         let options = connected_stream.raw_with(|raw_tcp_socket| {
             let mut options = OptionSet::new();
 
@@ -129,33 +150,45 @@ impl StreamSocket {
             // `listener_options` would give the accepted socket "new" options
             // while its raw socket still has the "old" ones.
 
-            if let Some(interval) = raw_tcp_socket.keep_alive() {
+            if let Some(config) = raw_tcp_socket.keep_alive_config() {
                 options.socket.set_keep_alive(true);
-                options.tcp.set_keep_intvl(interval.secs() as u32);
+                options.tcp.set_keep_idle(config.idle.secs() as u32);
+                options.tcp.set_keep_intvl(config.interval.secs() as u32);
+                options.tcp.set_keep_cnt(config.count);
             } else {
-                options
-                    .tcp
-                    .set_keep_intvl(listener_options.tcp.keep_intvl());
+                let listener_tcp = &listener_options.tcp;
+                options.tcp.set_keep_idle(listener_tcp.keep_idle());
+                options.tcp.set_keep_intvl(listener_tcp.keep_intvl());
+                options.tcp.set_keep_cnt(listener_tcp.keep_cnt());
             }
 
             if !raw_tcp_socket.nagle_enabled() {
                 options.tcp.set_no_delay(true);
             }
 
+            options
+                .tcp
+                .set_congestion(raw_tcp_socket.congestion_control());
+
             // TODO: Update other options for a newly-accepted socket
 
             options
         });
 
         let pollee = Pollee::new();
-        connected_stream.init_observer(StreamObserver::new(pollee.clone()));
+        let pseudo_path = SockFs::new_path();
+        connected_stream.init_observer(StreamObserver::new(
+            pollee.clone(),
+            SocketInode::of(&pseudo_path),
+        ));
 
         Arc::new(Self {
             options: RwLock::new(options),
             state: RwLock::new(Takeable::new(State::Connected(connected_stream))),
+            net_ns,
             is_nonblocking: AtomicBool::new(false),
             pollee,
-            pseudo_path: SockFs::new_path(),
+            pseudo_path,
         })
     }
 
@@ -259,7 +292,8 @@ impl StreamSocket {
                 remote_endpoint,
                 &raw_option,
                 options.socket.reuse_addr(),
-                StreamObserver::new(self.pollee.clone()),
+                StreamObserver::new(self.pollee.clone(), SocketInode::of(&self.pseudo_path)),
+                &self.net_ns,
             ) {
                 Ok(connecting_stream) => {
                     let iface_to_poll = connecting_stream.iface().clone();
@@ -322,7 +356,8 @@ impl StreamSocket {
         let accepted = listen_stream.try_accept().map(|connected_stream| {
             let remote_endpoint = connected_stream.remote_endpoint();
             let listener_options = self.options.read();
-            let accepted_socket = Self::new_accepted(connected_stream, &listener_options);
+            let accepted_socket =
+                Self::new_accepted(connected_stream, &listener_options, self.net_ns.clone());
             (accepted_socket as _, remote_endpoint.into())
         });
         let iface_to_poll = listen_stream.iface().clone();
@@ -416,6 +451,50 @@ impl StreamSocket {
         }
     }
 
+    /// Creates the control messages of `recvmsg`.
+    ///
+    /// Like Linux, only `SO_TIMESTAMP`, `SO_TIMESTAMPNS`, and `TCP_CM_INQ` messages are generated
+    /// for TCP sockets. The IP-level messages (e.g., `IP_PKTINFO`) are never generated because the
+    /// received bytes may come from different packets.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/ipv4/tcp.c>
+    fn new_recv_control(&self) -> Vec<ControlMessage> {
+        let state = self.read_updated_state();
+        let State::Connected(connected_stream) = state.as_ref() else {
+            return Vec::new();
+        };
+        let options = self.options.read();
+
+        let (last_data_recv, inq) = connected_stream.raw_with(|socket| {
+            let inq = socket.recv_queue();
+            // If no more data can be received, a positive value is reported to indicate the end
+            // of the stream.
+            let inq = if inq == 0 && !socket.may_recv_new() {
+                1
+            } else {
+                inq.min(i32::MAX as usize) as i32
+            };
+            (socket.stats().last_data_recv, inq)
+        });
+
+        let mut control_messages = Vec::new();
+
+        if let Some(format) = options.socket.timestamp()
+            && let Some(last_data_recv) = last_data_recv
+        {
+            let recv_time = Duration::from_micros(last_data_recv.total_micros() as u64);
+            let message = IpControlMessage::new_timestamp(recv_time, format);
+            control_messages.push(ControlMessage::Ip(message));
+        }
+
+        if options.tcp.receive_inq() {
+            let message = IpControlMessage::new_tcp_inq(inq);
+            control_messages.push(ControlMessage::Ip(message));
+        }
+
+        control_messages
+    }
+
     fn test_and_clear_error(&self) -> Option<Error> {
         let state = self.read_updated_state();
 
@@ -456,7 +535,7 @@ impl Socket for StreamSocket {
         };
 
         let can_reuse = self.options.read().socket.reuse_addr();
-        init_stream.bind(&endpoint, can_reuse)
+        init_stream.bind(&endpoint, can_reuse, &self.net_ns)
     }
 
     fn connect(&self, socket_addr: SocketAddr) -> Result<()> {
@@ -495,7 +574,7 @@ impl Socket for StreamSocket {
             let listen_stream = match init_stream.listen(
                 backlog,
                 &raw_option,
-                StreamObserver::new(self.pollee.clone()),
+                StreamObserver::new(self.pollee.clone(), SocketInode::of(&self.pseudo_path)),
             ) {
                 Ok(listen_stream) => listen_stream,
                 Err((err, init_stream)) => {
@@ -560,7 +639,7 @@ impl Socket for StreamSocket {
     fn sendmsg(
         &self,
         reader: &mut dyn MultiRead,
-        message_header: MessageHeader,
+        _message_header: MessageHeader,
         flags: SendRecvFlags,
     ) -> Result<usize> {
         // TODO: Deal with flags
@@ -568,18 +647,15 @@ impl Socket for StreamSocket {
             warn!("unsupported flags: {:?}", flags);
         }
 
-        let MessageHeader {
-            control_messages, ..
-        } = message_header;
-
         // According to the Linux man pages, `EISCONN` _may_ be returned when the destination
         // address is specified for a connection-mode socket. In practice, the destination address
         // is simply ignored. We follow the same behavior as the Linux implementation to ignore it.
 
-        if !control_messages.is_empty() {
-            // TODO: Support sending control message
-            warn!("sending control message is not supported");
-        }
+        // Like Linux, the control messages have no effect on TCP sockets. Only the socket-level
+        // ones are processed, and the supported ones (i.e., `SCM_RIGHTS` and `SCM_CREDENTIALS`)
+        // are simply accepted. The ones at other levels (e.g., `IP_PKTINFO`) are ignored.
+        //
+        // Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/core/sock.c>
 
         self.block_on(IoEvents::OUT, || self.try_send(reader, flags))
 
@@ -598,11 +674,11 @@ impl Socket for StreamSocket {
 
         let (received_bytes, _) = self.block_on(IoEvents::IN, || self.try_recv(writer, flags))?;
 
-        // TODO: Receive control message
+        let control_messages = self.new_recv_control();
 
         // According to <https://elixir.bootlin.com/linux/v6.0.9/source/net/ipv4/tcp.c#L2645>,
         // peer address is ignored for connected socket.
-        let message_header = MessageHeader::new(None, Vec::new());
+        let message_header = MessageHeader::new(None, control_messages);
 
         Ok((received_bytes, message_header))
     }
@@ -691,6 +767,10 @@ impl Socket for StreamSocket {
                 let inq = options.tcp.receive_inq();
                 tcp_inq.set(inq);
             }
+            tcp_info @ Info => {
+                let info = state.tcp_info();
+                tcp_info.set(info);
+            }
             _ => return_errno_with_message!(
                 Errno::ENOPROTOOPT,
                 "the socket option to get is unknown"
@@ -779,7 +859,7 @@ fn do_tcp_setsockopt(
             }
             options.tcp.set_keep_idle(*keepidle);
 
-            // TODO: Track when the socket becomes idle to actually support keep idle.
+            return Ok(state.set_keep_alive(options.socket.keep_alive(), &options.tcp));
         }
         tcp_keep_intvl @ KeepIntvl => {
             // Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/net/tcp.h#L168>
@@ -795,7 +875,7 @@ fn do_tcp_setsockopt(
             }
             options.tcp.set_keep_intvl(*keepintvl);
 
-            return Ok(state.set_keep_alive(options.socket.keep_alive(), *keepintvl));
+            return Ok(state.set_keep_alive(options.socket.keep_alive(), &options.tcp));
         }
         tcp_keep_cnt @ KeepCnt => {
             // Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/net/tcp.h#L169>
@@ -808,7 +888,7 @@ fn do_tcp_setsockopt(
             }
             options.tcp.set_keep_cnt(*keepcnt);
 
-            // TODO: Track the keepalive count in the underlying TCP socket.
+            return Ok(state.set_keep_alive(options.socket.keep_alive(), &options.tcp));
         }
         tcp_syn_cnt @ SynCnt => {
             const MAX_TCP_SYN_CNT: u8 = 127;
@@ -839,6 +919,9 @@ fn do_tcp_setsockopt(
         tcp_congestion @ Congestion => {
             let congestion = tcp_congestion.get().unwrap();
             options.tcp.set_congestion(*congestion);
+            state.set_raw_option(|raw_socket: &dyn RawTcpSetOption| {
+                raw_socket.set_congestion_control(*congestion)
+            });
         }
         tcp_user_timeout @ UserTimeout => {
             let user_timeout = tcp_user_timeout.get().unwrap();
@@ -874,6 +957,19 @@ impl State {
         }
     }
 
+    fn tcp_info(&self) -> TcpInfo {
+        let now = aster_bigtcp::time::now();
+        let new_connection_info =
+            |raw_socket: &RawTcpSocketExt| TcpInfo::new_connection(raw_socket, now);
+
+        match self {
+            State::Init(_) => TcpInfo::new_unconnected(false),
+            State::Connecting(connecting_stream) => connecting_stream.raw_with(new_connection_info),
+            State::Connected(connected_stream) => connected_stream.raw_with(new_connection_info),
+            State::Listen(_) => TcpInfo::new_unconnected(true),
+        }
+    }
+
     fn iface(&self) -> Option<&Arc<Iface>> {
         match self {
             State::Init(_) => None,
@@ -902,10 +998,14 @@ impl State {
         bound_port.set_can_reuse(reuse_addr);
     }
 
-    fn set_keep_alive(&self, is_keep_alive_enabled: bool, keep_intvl: u32) -> NeedIfacePoll {
-        let interval = is_keep_alive_enabled.then(|| Duration::from_secs(keep_intvl as u64));
+    fn set_keep_alive(
+        &self,
+        is_keep_alive_enabled: bool,
+        tcp_options: &TcpOptionSet,
+    ) -> NeedIfacePoll {
+        let config = is_keep_alive_enabled.then(|| tcp_options.keep_alive_config());
 
-        let set_keepalive = |raw_socket: &dyn RawTcpSetOption| raw_socket.set_keep_alive(interval);
+        let set_keepalive = |raw_socket: &dyn RawTcpSetOption| raw_socket.set_keep_alive(config);
 
         self.set_raw_option(set_keepalive)
             .unwrap_or(NeedIfacePoll::FALSE)
@@ -924,7 +1024,7 @@ impl SetSocketLevelOption for (&State, &TcpOptionSet) {
     }
 
     fn set_keep_alive(&self, keep_alive: bool) -> NeedIfacePoll {
-        self.0.set_keep_alive(keep_alive, self.1.keep_intvl())
+        self.0.set_keep_alive(keep_alive, self.1)
     }
 }
 
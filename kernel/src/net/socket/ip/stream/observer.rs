@@ -2,14 +2,22 @@
 
 use aster_bigtcp::socket::{SocketEventObserver, SocketEvents};
 
-use crate::{events::IoEvents, process::signal::Pollee};
+use crate::{events::IoEvents, net::socket::util::SocketInode, process::signal::Pollee};
 
 #[derive(Clone)]
-pub struct StreamObserver(Pollee);
+pub struct StreamObserver {
+    pollee: Pollee,
+    inode: SocketInode,
+}
 
 impl StreamObserver {
-    pub(super) fn new(pollee: Pollee) -> Self {
-        Self(pollee)
+    pub(super) fn new(pollee: Pollee, inode: SocketInode) -> Self {
+        Self { pollee, inode }
+    }
+
+    /// Returns the inode of the socket that observes the events.
+    pub(in crate::net) fn inode(&self) -> SocketInode {
+        self.inode
     }
 }
 
@@ -39,6 +47,6 @@ impl SocketEventObserver for StreamObserver {
             io_events |= IoEvents::HUP | IoEvents::ERR;
         }
 
-        self.0.notify(io_events);
+        self.pollee.notify(io_events);
     }
 }
@@ -1,6 +1,16 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use crate::{net::socket::options::macros::impl_socket_options, prelude::*};
+pub use aster_bigtcp::socket::CongestionControl;
+use aster_bigtcp::{
+    socket::{RecoveryState, TcpState},
+    time::{Duration, Instant},
+};
+
+use super::util::{DEFAULT_MAXSEG, TCP_RTO_MAX, TCP_RTO_MIN, TCP_TIMEOUT_INIT};
+use crate::{
+    net::{iface::RawTcpSocketExt, socket::options::macros::impl_socket_options},
+    prelude::*,
+};
 
 impl_socket_options!(
     pub struct NoDelay(bool);
@@ -14,32 +24,237 @@ impl_socket_options!(
     pub struct Congestion(CongestionControl);
     pub struct UserTimeout(u32);
     pub struct Inq(bool);
+    pub struct Info(TcpInfo);
 );
 
-#[derive(Clone, Copy, Debug)]
-pub enum CongestionControl {
-    Reno,
-    Cubic,
+/// The TCP connection information reported by `TCP_INFO`.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/tcp.h>
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+pub struct TcpInfo {
+    pub(super) state: u8,
+    pub(super) ca_state: u8,
+    pub(super) retransmits: u8,
+    pub(super) probes: u8,
+    pub(super) backoff: u8,
+    pub(super) options: u8,
+    /// The bit fields `tcpi_snd_wscale : 4` and `tcpi_rcv_wscale : 4`.
+    pub(super) wscale: u8,
+    /// The bit fields `tcpi_delivery_rate_app_limited : 1` and `tcpi_fastopen_client_fail : 2`.
+    pub(super) app_limited_fastopen: u8,
+
+    pub(super) rto: u32,
+    pub(super) ato: u32,
+    pub(super) snd_mss: u32,
+    pub(super) rcv_mss: u32,
+
+    pub(super) unacked: u32,
+    pub(super) sacked: u32,
+    pub(super) lost: u32,
+    pub(super) retrans: u32,
+    pub(super) fackets: u32,
+
+    pub(super) last_data_sent: u32,
+    pub(super) last_ack_sent: u32,
+    pub(super) last_data_recv: u32,
+    pub(super) last_ack_recv: u32,
+
+    pub(super) pmtu: u32,
+    pub(super) rcv_ssthresh: u32,
+    pub(super) rtt: u32,
+    pub(super) rttvar: u32,
+    pub(super) snd_ssthresh: u32,
+    pub(super) snd_cwnd: u32,
+    pub(super) advmss: u32,
+    pub(super) reordering: u32,
+
+    pub(super) rcv_rtt: u32,
+    pub(super) rcv_space: u32,
+
+    pub(super) total_retrans: u32,
+
+    pub(super) pacing_rate: u64,
+    pub(super) max_pacing_rate: u64,
+    pub(super) bytes_acked: u64,
+    pub(super) bytes_received: u64,
+    pub(super) segs_out: u32,
+    pub(super) segs_in: u32,
+
+    pub(super) notsent_bytes: u32,
+    pub(super) min_rtt: u32,
+    pub(super) data_segs_in: u32,
+    pub(super) data_segs_out: u32,
+
+    pub(super) delivery_rate: u64,
+
+    pub(super) busy_time: u64,
+    pub(super) rwnd_limited: u64,
+    pub(super) sndbuf_limited: u64,
+
+    pub(super) delivered: u32,
+    pub(super) delivered_ce: u32,
+
+    pub(super) bytes_sent: u64,
+    pub(super) bytes_retrans: u64,
+    pub(super) dsack_dups: u32,
+    pub(super) reord_seen: u32,
+
+    pub(super) rcv_ooopack: u32,
+
+    pub(super) snd_wnd: u32,
+    pub(super) rcv_wnd: u32,
+
+    pub(super) rehash: u32,
+
+    pub(super) total_rto: u16,
+    pub(super) total_rto_recoveries: u16,
+    pub(super) total_rto_time: u32,
 }
 
-impl CongestionControl {
-    const RENO: &'static str = "reno";
-    const CUBIC: &'static str = "cubic";
+impl TcpInfo {
+    /// Creates the information of a socket that is not connected.
+    pub(in crate::net::socket::ip) fn new_unconnected(is_listening: bool) -> Self {
+        // Reference: <https://elixir.bootlin.com/linux/v6.13/source/net/ipv4/tcp.c>
+        const TCP_INIT_CWND: u32 = 10;
 
-    pub fn new(name: &str) -> Result<Self> {
-        let congestion = match name {
-            Self::RENO => Self::Reno,
-            Self::CUBIC => Self::Cubic,
-            _ => return_errno_with_message!(Errno::ENOENT, "unsupported congestion name"),
+        Self {
+            state: if is_listening { TCP_LISTEN } else { TCP_CLOSE },
+            rto: to_micros(TCP_TIMEOUT_INIT),
+            snd_mss: DEFAULT_MAXSEG,
+            snd_ssthresh: TCP_INFINITE_SSTHRESH,
+            snd_cwnd: TCP_INIT_CWND,
+            min_rtt: u32::MAX,
+            ..Default::default()
+        }
+    }
+
+    /// Creates the information of a connection.
+    pub(in crate::net::socket::ip) fn new_connection(
+        raw_socket: &RawTcpSocketExt,
+        now: Instant,
+    ) -> Self {
+        let congestion = raw_socket.congestion_info();
+        let stats = raw_socket.stats();
+
+        let mss = congestion.mss.max(1);
+        let to_segments = |bytes: usize| (bytes / mss).min(TCP_INFINITE_SSTHRESH as usize) as u32;
+        let to_millis_since = |time: Option<Instant>| {
+            time.map_or(0, |time| {
+                (now - time).total_millis().min(u32::MAX as u64) as u32
+            })
         };
 
-        Ok(congestion)
-    }
+        // Reference: <https://datatracker.ietf.org/doc/html/rfc6298#section-2>
+        let rto = match (congestion.srtt, congestion.rttvar) {
+            (Some(srtt), Some(rttvar)) => (srtt + (rttvar * 4).max(TCP_RTO_MIN)).min(TCP_RTO_MAX),
+            _ => TCP_TIMEOUT_INIT,
+        };
 
-    pub fn name(&self) -> &'static str {
-        match self {
-            Self::Reno => Self::RENO,
-            Self::Cubic => Self::CUBIC,
+        Self {
+            state: tcp_state_to_c(raw_socket.state()),
+            ca_state: match congestion.recovery_state {
+                RecoveryState::Open => TCP_CA_OPEN,
+                RecoveryState::Disorder => TCP_CA_DISORDER,
+                RecoveryState::Recovery => TCP_CA_RECOVERY,
+                RecoveryState::Loss => TCP_CA_LOSS,
+            },
+            retransmits: stats.unrecovered_retrans.min(u8::MAX as u32) as u8,
+            probes: raw_socket.keep_alive_probes(),
+            rto: to_micros(rto),
+            snd_mss: mss as u32,
+            last_data_sent: to_millis_since(stats.last_data_sent),
+            last_data_recv: to_millis_since(stats.last_data_recv),
+            last_ack_recv: to_millis_since(stats.last_ack_recv),
+            rtt: congestion.srtt.map_or(0, to_micros),
+            rttvar: congestion.rttvar.map_or(0, to_micros),
+            snd_ssthresh: to_segments(congestion.ssthresh),
+            snd_cwnd: to_segments(congestion.window),
+            total_retrans: congestion.retransmits as u32,
+            bytes_acked: stats.bytes_acked,
+            bytes_received: stats.bytes_received,
+            segs_out: stats.segs_out as u32,
+            segs_in: stats.segs_in as u32,
+            min_rtt: congestion.min_rtt.map_or(u32::MAX, to_micros),
+            data_segs_in: stats.data_segs_in as u32,
+            data_segs_out: stats.data_segs_out as u32,
+            bytes_sent: stats.bytes_sent,
+            bytes_retrans: stats.bytes_retrans,
+            ..Default::default()
         }
     }
+
+    /// Returns the TCP state (e.g., [`TCP_ESTABLISHED`]).
+    pub fn state(&self) -> u8 {
+        self.state
+    }
+
+    /// Returns the number of unrecovered retransmissions.
+    pub fn retransmits(&self) -> u8 {
+        self.retransmits
+    }
+
+    /// Returns the number of unanswered keep-alive probes.
+    pub fn probes(&self) -> u8 {
+        self.probes
+    }
+
+    /// Returns the retransmission timeout in microseconds.
+    pub fn rto(&self) -> u32 {
+        self.rto
+    }
+
+    /// Returns the congestion window in segments.
+    pub fn snd_cwnd(&self) -> u32 {
+        self.snd_cwnd
+    }
+
+    /// Returns the slow start threshold in segments.
+    ///
+    /// The threshold is [`TCP_INFINITE_SSTHRESH`] if the connection is in the initial slow start.
+    pub fn snd_ssthresh(&self) -> u32 {
+        self.snd_ssthresh
+    }
+}
+
+fn to_micros(duration: Duration) -> u32 {
+    duration.total_micros().min(u32::MAX as u64) as u32
+}
+
+// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/net/tcp_states.h>
+pub const TCP_ESTABLISHED: u8 = 1;
+pub const TCP_SYN_SENT: u8 = 2;
+pub const TCP_SYN_RECV: u8 = 3;
+pub const TCP_FIN_WAIT1: u8 = 4;
+pub const TCP_FIN_WAIT2: u8 = 5;
+pub const TCP_TIME_WAIT: u8 = 6;
+pub const TCP_CLOSE: u8 = 7;
+pub const TCP_CLOSE_WAIT: u8 = 8;
+pub const TCP_LAST_ACK: u8 = 9;
+pub const TCP_LISTEN: u8 = 10;
+pub const TCP_CLOSING: u8 = 11;
+
+fn tcp_state_to_c(state: TcpState) -> u8 {
+    match state {
+        TcpState::Closed => TCP_CLOSE,
+        TcpState::Listen => TCP_LISTEN,
+        TcpState::SynSent => TCP_SYN_SENT,
+        TcpState::SynReceived => TCP_SYN_RECV,
+        TcpState::Established => TCP_ESTABLISHED,
+        TcpState::FinWait1 => TCP_FIN_WAIT1,
+        TcpState::FinWait2 => TCP_FIN_WAIT2,
+        TcpState::CloseWait => TCP_CLOSE_WAIT,
+        TcpState::Closing => TCP_CLOSING,
+        TcpState::LastAck => TCP_LAST_ACK,
+        TcpState::TimeWait => TCP_TIME_WAIT,
+    }
 }
+
+// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/tcp.h>
+const TCP_CA_OPEN: u8 = 0;
+const TCP_CA_DISORDER: u8 = 1;
+const TCP_CA_RECOVERY: u8 = 3;
+const TCP_CA_LOSS: u8 = 4;
+
+// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/net/tcp.h>
+pub const TCP_INFINITE_SSTHRESH: u32 = 0x7fff_ffff;
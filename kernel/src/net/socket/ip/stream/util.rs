@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use aster_bigtcp::time::Duration;
+use aster_bigtcp::{socket::KeepAliveConfig, time::Duration};
 
 use super::options::CongestionControl;
 use crate::prelude::*;
@@ -41,11 +41,20 @@ impl TcpOptionSet {
             syn_cnt: DEFAULT_SYN_CNT,
             defer_accept: Retrans(0),
             window_clamp: DEFAULT_WINDOW_CLAMP,
-            congestion: CongestionControl::Reno,
+            congestion: CongestionControl::Cubic,
             user_timeout: 0,
             receive_inq: false,
         }
     }
+
+    /// Returns the keep-alive configuration that is used when `SO_KEEPALIVE` is enabled.
+    pub(super) fn keep_alive_config(&self) -> KeepAliveConfig {
+        KeepAliveConfig {
+            idle: Duration::from_secs(self.keep_idle as u64),
+            interval: Duration::from_secs(self.keep_intvl as u64),
+            count: self.keep_cnt,
+        }
+    }
 }
 
 impl Default for TcpOptionSet {
@@ -55,8 +64,9 @@ impl Default for TcpOptionSet {
 }
 
 /// Initial RTO value
-const TCP_TIMEOUT_INIT: Duration = Duration::from_secs(1);
-const TCP_RTO_MAX: Duration = Duration::from_secs(120);
+pub(super) const TCP_TIMEOUT_INIT: Duration = Duration::from_secs(1);
+pub(super) const TCP_RTO_MIN: Duration = Duration::from_millis(200);
+pub(super) const TCP_RTO_MAX: Duration = Duration::from_secs(120);
 
 /// The number of retransmits.
 #[derive(Clone, Copy, Debug)]
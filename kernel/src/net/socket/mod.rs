@@ -18,6 +18,7 @@ use crate::{
 pub mod ip;
 pub mod netlink;
 pub mod options;
+pub mod packet;
 pub mod unix;
 pub mod util;
 pub mod vsock;
@@ -5,22 +5,29 @@ use core::sync::atomic::{AtomicBool, Ordering};
 pub(super) use bound::BoundNetlink;
 use unbound::UnboundNetlink;
 
-use super::{GroupIdSet, NetlinkSocketAddr};
+use super::{GroupIdSet, NetlinkSocketAddr, addr::MAX_GROUPS};
 use crate::{
     events::IoEvents,
     fs::{pseudofs::SockFs, vfs::path::Path},
-    net::socket::{
-        Socket,
-        netlink::{AddMembership, DropMembership, table::SupportedNetlinkProtocol},
-        options::{
-            Error as SocketError, SocketOption,
-            macros::{sock_option_mut, sock_option_ref},
-        },
-        private::SocketPrivate,
-        util::{
-            MessageHeader, SendRecvFlags, SocketAddr,
-            datagram_common::{Bound, Inner, select_remote_and_bind},
-            options::{GetSocketLevelOption, SetSocketLevelOption, SocketOptionSet},
+    net::{
+        net_ns::NetNamespace,
+        socket::{
+            Socket,
+            netlink::{
+                AddMembership, DropMembership,
+                receiver::{MessageReceiver, QueueableMessage, RawMessageReceiver},
+                table::SupportedNetlinkProtocol,
+            },
+            options::{
+                Error as SocketError, SocketOption,
+                macros::{sock_option_mut, sock_option_ref},
+            },
+            private::SocketPrivate,
+            util::{
+                MessageHeader, SendRecvFlags, SocketAddr,
+                datagram_common::{Bound, Inner, select_remote_and_bind},
+                options::{GetSocketLevelOption, SetSocketLevelOption, SocketOptionSet},
+            },
         },
     },
     prelude::*,
@@ -57,8 +64,8 @@ impl<P: SupportedNetlinkProtocol> NetlinkSocket<P>
 where
     BoundNetlink<P::Message>: Bound<Endpoint = NetlinkSocketAddr>,
 {
-    pub fn new(is_nonblocking: bool) -> Arc<Self> {
-        let unbound = UnboundNetlink::new();
+    pub fn new(is_nonblocking: bool, net_ns: Arc<NetNamespace>) -> Arc<Self> {
+        let unbound = UnboundNetlink::new(net_ns);
         Arc::new(Self {
             inner: RwMutex::new(Inner::Unbound(unbound)),
             options: RwLock::new(OptionSet::new()),
@@ -89,6 +96,25 @@ where
         Ok(sent_bytes)
     }
 
+    /// Returns the receiver of the messages in arbitrary formats.
+    ///
+    /// If the socket is not bound, it will be bound to an ephemeral port first.
+    pub(super) fn raw_receiver(&self) -> Result<Arc<dyn RawMessageReceiver>>
+    where
+        P::Message: QueueableMessage,
+    {
+        self.inner
+            .write()
+            .bind_ephemeral(&NetlinkSocketAddr::new_unspecified(), &self.pollee)?;
+
+        let Inner::Bound(bound) = &*self.inner.read() else {
+            unreachable!("`bind_ephemeral` succeeds so the socket cannot be unbound");
+        };
+        let receiver = MessageReceiver::new(bound.receive_queue.clone(), self.pollee.clone());
+
+        Ok(Arc::new(receiver))
+    }
+
     // FIXME: This method is marked as `pub(super)` because it's invoked during kernel mode testing.
     pub(super) fn try_recv(
         &self,
@@ -278,18 +304,33 @@ impl<P: SupportedNetlinkProtocol> Inner<UnboundNetlink<P>, BoundNetlink<P::Messa
     }
 }
 
+/// Converts a group number in `NETLINK_ADD_MEMBERSHIP` or `NETLINK_DROP_MEMBERSHIP` to a group
+/// set.
+///
+/// Unlike the groups in socket addresses, which are bitmasks, the groups in these socket options
+/// are numbers starting from one.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/net/netlink/af_netlink.c#L1659>.
+fn group_to_group_id_set(group: u32) -> Result<GroupIdSet> {
+    if group == 0 || group > MAX_GROUPS {
+        return_errno_with_message!(Errno::EINVAL, "the netlink group is invalid");
+    }
+
+    Ok(GroupIdSet::new(1 << (group - 1)))
+}
+
 fn do_netlink_setsockopt<P: SupportedNetlinkProtocol>(
     option: &dyn SocketOption,
     inner: &mut Inner<UnboundNetlink<P>, BoundNetlink<P::Message>>,
 ) -> Result<()> {
     sock_option_ref!(match option {
         add_membership @ AddMembership => {
-            let groups = add_membership.get().unwrap();
-            inner.add_groups(GroupIdSet::new(*groups));
+            let group = add_membership.get().unwrap();
+            inner.add_groups(group_to_group_id_set(*group)?);
         }
         drop_membership @ DropMembership => {
-            let groups = drop_membership.get().unwrap();
-            inner.drop_groups(GroupIdSet::new(*groups));
+            let group = drop_membership.get().unwrap();
+            inner.drop_groups(group_to_group_id_set(*group)?);
         }
         _ =>
             return_errno_with_message!(Errno::ENOPROTOOPT, "the socket option to be set is unknown"),
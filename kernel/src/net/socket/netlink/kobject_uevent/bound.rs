@@ -6,7 +6,7 @@ use super::message::UeventMessage;
 use crate::{
     events::IoEvents,
     net::socket::{
-        netlink::{NetlinkSocketAddr, common::BoundNetlink},
+        netlink::{NetlinkSocketAddr, common::BoundNetlink, receiver::QueuedMessage},
         util::{SendRecvFlags, datagram_common},
     },
     prelude::*,
@@ -73,7 +73,10 @@ impl datagram_common::Bound for BoundNetlinkUevent {
             let len = response_len.min(writer.sum_lens());
             response.write_to(writer)?;
 
-            let remote = *response.src_addr();
+            let remote = match response {
+                QueuedMessage::Protocol(uevent) => *uevent.src_addr(),
+                QueuedMessage::Raw(_) => NetlinkSocketAddr::new_unspecified(),
+            };
 
             let should_dequeue = !flags.contains(SendRecvFlags::MSG_PEEK);
             Ok((should_dequeue, (len, remote)))
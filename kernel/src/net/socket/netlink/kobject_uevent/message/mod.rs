@@ -40,20 +40,19 @@ impl UeventMessage {
     pub(super) fn src_addr(&self) -> &NetlinkSocketAddr {
         &self.src_addr
     }
-
-    /// Writes the uevent to the given `writer`.
-    pub(super) fn write_to(&self, writer: &mut dyn MultiWrite) -> Result<()> {
-        let _nbytes = writer.write(&mut VmReader::from(self.uevent.as_bytes()))?;
-        // `_nbytes` may be smaller than the message size. We ignore it to truncate the message.
-
-        Ok(())
-    }
 }
 
 impl QueueableMessage for UeventMessage {
     fn total_len(&self) -> usize {
         self.uevent.len()
     }
+
+    fn write_to(&self, writer: &mut dyn MultiWrite) -> Result<()> {
+        let _nbytes = writer.write(&mut VmReader::from(self.uevent.as_bytes()))?;
+        // `_nbytes` may be smaller than the message size. We ignore it to truncate the message.
+
+        Ok(())
+    }
 }
 
 impl MulticastMessage for UeventMessage {}
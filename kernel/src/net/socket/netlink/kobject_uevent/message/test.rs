@@ -6,20 +6,23 @@ use core::str::FromStr;
 use ostd::prelude::*;
 
 use crate::{
-    net::socket::{
-        Socket,
-        netlink::{
-            GroupIdSet, NetlinkSocketAddr, NetlinkUeventSocket,
-            kobject_uevent::{
-                UeventMessage,
-                message::{
-                    syn_uevent::{SyntheticUevent, Uuid},
-                    uevent::Uevent,
+    net::{
+        net_ns::NetNamespace,
+        socket::{
+            Socket,
+            netlink::{
+                GroupIdSet, NetlinkSocketAddr, NetlinkUeventSocket,
+                kobject_uevent::{
+                    UeventMessage,
+                    message::{
+                        syn_uevent::{SyntheticUevent, Uuid},
+                        uevent::Uevent,
+                    },
                 },
+                table::{NetlinkUeventProtocol, SupportedNetlinkProtocol},
             },
-            table::{NetlinkUeventProtocol, SupportedNetlinkProtocol},
+            util::{SendRecvFlags, SocketAddr},
         },
-        util::{SendRecvFlags, SocketAddr},
     },
     prelude::*,
 };
@@ -50,10 +53,10 @@ fn synthetic_uevent() {
 
 #[ktest]
 fn multicast_synthetic_uevent() {
-    crate::net::socket::netlink::init();
+    let net_ns = NetNamespace::new_for_ktest();
 
     // Creates a new netlink uevent socket and joins the group for kobject uevents.
-    let socket = NetlinkUeventSocket::new(true);
+    let socket = NetlinkUeventSocket::new(true, net_ns.clone());
     let socket_addr = SocketAddr::Netlink(NetlinkSocketAddr::new(100, GroupIdSet::new(0x1)));
     socket.bind(socket_addr).unwrap();
 
@@ -79,7 +82,7 @@ fn multicast_synthetic_uevent() {
     };
     let uevent_message =
         UeventMessage::new(uevent, NetlinkSocketAddr::new(0, GroupIdSet::new(0x1)));
-    NetlinkUeventProtocol::multicast(GroupIdSet::new(0x1), uevent_message).unwrap();
+    NetlinkUeventProtocol::multicast(&net_ns, GroupIdSet::new(0x1), uevent_message).unwrap();
 
     let (len, _) = socket
         .try_recv(&mut writer, SendRecvFlags::empty())
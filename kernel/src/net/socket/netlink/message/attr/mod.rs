@@ -201,3 +201,38 @@ pub trait Attribute: Debug + Send + Sync {
         Ok(())
     }
 }
+
+/// Reads the raw payload of an attribute.
+pub fn read_payload(reader: &mut dyn MultiRead, payload_len: usize) -> Result<Vec<u8>> {
+    let mut payload = vec![0u8; payload_len];
+    let nbytes = reader.read(&mut VmWriter::from(payload.as_mut_slice()))?;
+    if nbytes != payload_len {
+        return_errno_with_message!(Errno::EINVAL, "the reader length is too small");
+    }
+
+    Ok(payload)
+}
+
+/// Parses the nested attributes in a payload.
+pub fn parse_nested_attrs<A: Attribute>(payload: &[u8]) -> Result<Vec<A>> {
+    let mut reader = VmReader::from(payload).to_fallible();
+
+    match A::read_all_from(&mut reader, payload.len())? {
+        ContinueRead::Parsed(attrs) => Ok(attrs),
+        ContinueRead::Skipped => Ok(Vec::new()),
+        ContinueRead::SkippedErr(err) => Err(err),
+    }
+}
+
+/// Encodes the attributes into a payload of an attribute that nests them.
+pub fn encode_nested_attrs<A: Attribute>(attrs: &[A]) -> Vec<u8> {
+    let len = attrs.iter().map(|attr| attr.total_len_with_padding()).sum();
+    let mut payload = vec![0u8; len];
+
+    let mut writer = VmWriter::from(payload.as_mut_slice()).to_fallible();
+    for attr in attrs {
+        attr.write_to(&mut writer).unwrap();
+    }
+
+    payload
+}
@@ -9,13 +9,15 @@ mod attr;
 mod result;
 mod segment;
 
-pub(super) use attr::{Attribute, CAttrHeader, noattr::NoAttr};
+pub(super) use attr::{
+    Attribute, CAttrHeader, encode_nested_attrs, noattr::NoAttr, parse_nested_attrs, read_payload,
+};
 pub(super) use result::ContinueRead;
 pub(super) use segment::{
     CSegmentType, SegmentBody,
     ack::{DoneSegment, ErrorSegment},
     common::SegmentCommon,
-    header::{CMsgSegHdr, GetRequestFlags, SegHdrCommonFlags},
+    header::{CMsgSegHdr, DeleteRequestFlags, GetRequestFlags, NewRequestFlags, SegHdrCommonFlags},
 };
 
 use super::receiver::QueueableMessage;
@@ -28,7 +30,7 @@ use crate::{
 ///
 /// A netlink message can be transmitted to and from user space using a single send/receive syscall.
 /// It consists of one or more [`ProtocolSegment`]s.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Message<T> {
     segments: Vec<T>,
 }
@@ -39,18 +41,8 @@ impl<T> Message<T> {
     }
 }
 
-impl<T: ProtocolSegment> Message<T> {
-    // We do not provide a `read_from` method here. Netlink sockets should use `T::read_from` to
-    // read the request segments one by one instead.
-
-    pub(super) fn write_to(&self, writer: &mut dyn MultiWrite) -> Result<()> {
-        for segment in self.segments.iter() {
-            segment.write_to(writer)?;
-        }
-
-        Ok(())
-    }
-}
+// We do not provide a `read_from` method for `Message`. Netlink sockets should use `T::read_from`
+// to read the request segments one by one instead.
 
 impl<T: ProtocolSegment> QueueableMessage for Message<T> {
     fn total_len(&self) -> usize {
@@ -59,6 +51,14 @@ impl<T: ProtocolSegment> QueueableMessage for Message<T> {
             .map(|segment| segment.header().len as usize)
             .sum()
     }
+
+    fn write_to(&self, writer: &mut dyn MultiWrite) -> Result<()> {
+        for segment in self.segments.iter() {
+            segment.write_to(writer)?;
+        }
+
+        Ok(())
+    }
 }
 
 pub trait ProtocolSegment: Sized {
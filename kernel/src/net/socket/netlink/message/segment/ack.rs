@@ -59,6 +59,13 @@ impl SegmentBody for ErrorSegmentBody {
     type CType = ErrorSegmentBody;
 }
 
+impl ErrorSegmentBody {
+    /// Returns the header of the request that the segment responds to.
+    pub fn request_header(&self) -> &CMsgSegHdr {
+        &self.request_header
+    }
+}
+
 impl ErrorSegment {
     pub fn new_from_request(request_header: &CMsgSegHdr, error: Option<Error>) -> Self {
         let header = CMsgSegHdr {
@@ -7,7 +7,7 @@ use crate::{
     util::{MultiRead, MultiWrite},
 };
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct SegmentCommon<Body, Attr> {
     header: CMsgSegHdr,
     body: Body,
@@ -29,6 +29,10 @@ impl<Body, Attr> SegmentCommon<Body, Attr> {
         &self.body
     }
 
+    pub fn body_mut(&mut self) -> &mut Body {
+        &mut self.body
+    }
+
     pub fn attrs(&self) -> &Vec<Attr> {
         &self.attrs
     }
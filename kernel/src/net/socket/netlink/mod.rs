@@ -41,18 +41,44 @@ mod addr;
 mod common;
 mod kobject_uevent;
 mod message;
+mod netfilter;
 mod options;
 mod receiver;
 mod route;
+mod sock_diag;
 mod table;
 
 pub use addr::{GroupIdSet, NetlinkSocketAddr};
 pub use kobject_uevent::NetlinkUeventSocket;
+pub use netfilter::NetlinkNetfilterSocket;
 pub use options::{AddMembership, DropMembership};
 pub(super) use receiver::NETLINK_DEFAULT_BUF_SIZE;
+pub use receiver::RawMessageReceiver;
 pub use route::NetlinkRouteSocket;
+pub(in crate::net) use route::{notify_del_addr, notify_del_link, notify_new_link};
+pub use sock_diag::NetlinkSockDiagSocket;
+pub(in crate::net) use table::NetlinkSocketTable;
 pub use table::{StandardNetlinkProtocol, is_valid_protocol};
 
-pub(in crate::net) fn init() {
-    table::init();
+use crate::{fs::file::FileLike, prelude::*};
+
+/// Returns the receiver of the messages in arbitrary formats if the file is a netlink socket.
+///
+/// If the socket is not bound, it will be bound to an ephemeral port first.
+pub fn raw_receiver_of(file: &dyn FileLike) -> Result<Arc<dyn RawMessageReceiver>> {
+    if let Some(socket) = file.downcast_ref::<NetlinkRouteSocket>() {
+        return socket.raw_receiver();
+    }
+    if let Some(socket) = file.downcast_ref::<NetlinkUeventSocket>() {
+        return socket.raw_receiver();
+    }
+    if let Some(socket) = file.downcast_ref::<NetlinkNetfilterSocket>() {
+        return socket.raw_receiver();
+    }
+    if let Some(socket) = file.downcast_ref::<NetlinkSockDiagSocket>() {
+        return socket.raw_receiver();
+    }
+
+    file.as_socket_or_err()?;
+    return_errno_with_message!(Errno::EINVAL, "the socket is not a netlink socket");
 }
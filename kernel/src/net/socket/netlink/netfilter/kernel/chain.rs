@@ -0,0 +1,389 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Handle chain-related requests.
+
+use aster_bigtcp::iface::FilterHook;
+
+use super::{
+    Batch,
+    rule::rule_to_segment,
+    util::{finish_dump, is_dump, new_segment, parse_name, require_attr},
+};
+use crate::{
+    net::{
+        netfilter::{BaseChain, Chain, ChainPolicy, ChainType, NFPROTO_UNSPEC, Netfilter, Table},
+        socket::netlink::{
+            message::{CMsgSegHdr, DeleteRequestFlags, NewRequestFlags, ProtocolSegment},
+            netfilter::message::{NfAttr, NfSegment, NfnlSegment, NftMsgType, find_attr},
+        },
+    },
+    prelude::*,
+};
+
+/// Chain attributes.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/netfilter/nf_tables.h>
+#[expect(non_camel_case_types)]
+#[expect(clippy::upper_case_acronyms)]
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+enum ChainAttrClass {
+    UNSPEC = 0,
+    TABLE = 1,
+    HANDLE = 2,
+    NAME = 3,
+    HOOK = 4,
+    POLICY = 5,
+    USE = 6,
+    TYPE = 7,
+    COUNTERS = 8,
+    PAD = 9,
+    FLAGS = 10,
+    ID = 11,
+    USERDATA = 12,
+}
+
+/// Hook attributes, which are nested in [`ChainAttrClass::HOOK`].
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/netfilter/nf_tables.h>
+#[expect(non_camel_case_types)]
+#[expect(clippy::upper_case_acronyms)]
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+enum HookAttrClass {
+    UNSPEC = 0,
+    HOOKNUM = 1,
+    PRIORITY = 2,
+    DEV = 3,
+    DEVS = 4,
+}
+
+bitflags! {
+    /// The flags of a chain.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/netfilter/nf_tables.h>
+    struct ChainFlags: u32 {
+        /// The chain is attached to a hook.
+        const BASE = 1 << 0;
+        /// The chain is offloaded to the hardware.
+        const HW_OFFLOAD = 1 << 1;
+        /// The chain is bound to a rule (i.e., an anonymous chain).
+        const BINDING = 1 << 2;
+    }
+}
+
+pub(super) fn do_new_chain(request: &NfSegment, batch: &mut Batch) -> Result<()> {
+    let attrs = request.attrs();
+    let family = request.body().family;
+    let table_name = parse_name(require_attr(attrs, ChainAttrClass::TABLE as u16)?)?;
+    let table = batch.transaction().ruleset().table(family, table_name)?;
+
+    // Finds the existing chain, if any.
+    let name_attr = find_attr(attrs, ChainAttrClass::NAME as u16);
+    let id_attr = find_attr(attrs, ChainAttrClass::ID as u16);
+    let existing = if let Some(attr) = find_attr(attrs, ChainAttrClass::HANDLE as u16) {
+        Some(table.chain_by_handle(attr.as_be64()?)?)
+    } else if let Some(attr) = name_attr {
+        table.chain(parse_name(attr)?).ok()
+    } else if id_attr.is_none() {
+        return_errno_with_message!(Errno::EINVAL, "the chain is not specified");
+    } else {
+        None
+    };
+
+    let hook_attr = find_attr(attrs, ChainAttrClass::HOOK as u16);
+
+    let policy = match find_attr(attrs, ChainAttrClass::POLICY as u16) {
+        Some(attr) => {
+            let is_base = match existing {
+                Some(chain) => chain.base().is_some(),
+                None => hook_attr.is_some(),
+            };
+            if !is_base {
+                return_errno_with_message!(
+                    Errno::EOPNOTSUPP,
+                    "the policy of a regular chain cannot be set"
+                );
+            }
+            Some(ChainPolicy::from_code(attr.as_be32()? as i32)?)
+        }
+        None => None,
+    };
+
+    let flags = match find_attr(attrs, ChainAttrClass::FLAGS as u16) {
+        Some(attr) => {
+            let flags = ChainFlags::from_bits(attr.as_be32()?).ok_or_else(|| {
+                Error::with_message(Errno::EOPNOTSUPP, "the chain flags are not supported")
+            })?;
+            if flags.intersects(ChainFlags::HW_OFFLOAD | ChainFlags::BINDING) {
+                return_errno_with_message!(
+                    Errno::EOPNOTSUPP,
+                    "offloaded or bound chains are not supported"
+                );
+            }
+            flags
+        }
+        None => ChainFlags::empty(),
+    };
+
+    let base = match hook_attr {
+        Some(attr) => {
+            let type_attr = find_attr(attrs, ChainAttrClass::TYPE as u16);
+            Some(parse_hook(attr, type_attr, policy)?)
+        }
+        None => None,
+    };
+
+    let request_flags = NewRequestFlags::from_bits_truncate(request.header().flags);
+
+    if let Some(chain) = existing {
+        if request_flags.contains(NewRequestFlags::EXCL) {
+            return_errno_with_message!(Errno::EEXIST, "the chain already exists");
+        }
+        if request_flags.contains(NewRequestFlags::REPLACE) {
+            return_errno_with_message!(Errno::EOPNOTSUPP, "chains cannot be replaced");
+        }
+        if let Some(attr) = name_attr
+            && parse_name(attr)? != chain.name()
+        {
+            return_errno_with_message!(Errno::EOPNOTSUPP, "chains cannot be renamed");
+        }
+
+        let name = chain.name().to_string();
+        let table = batch
+            .transaction_mut()
+            .ruleset_mut()
+            .table_mut(family, table_name)?;
+        let chain = table.update_chain(&name, base, policy)?;
+        let segment = chain_to_segment(request.header(), NftMsgType::NEWCHAIN, table, chain, 0);
+        batch.push_notification(request.header(), segment);
+        return Ok(());
+    }
+
+    let Some(name_attr) = name_attr else {
+        return_errno_with_message!(Errno::EINVAL, "the name of the new chain is missing");
+    };
+    let name = parse_name(name_attr)?;
+    if flags.contains(ChainFlags::BASE) && base.is_none() {
+        return_errno_with_message!(Errno::EINVAL, "the base chain has no hook");
+    }
+    let userdata =
+        find_attr(attrs, ChainAttrClass::USERDATA as u16).map(|attr| attr.payload().to_vec());
+
+    let table = batch
+        .transaction_mut()
+        .ruleset_mut()
+        .table_mut(family, table_name)?;
+    let chain = table.add_chain(name.to_string(), base, userdata)?;
+    let segment = chain_to_segment(request.header(), NftMsgType::NEWCHAIN, table, chain, 0);
+
+    if let Some(attr) = id_attr {
+        batch.add_chain_id(family, table_name, attr.as_be32()?, name);
+    }
+    batch.push_notification(request.header(), segment);
+
+    Ok(())
+}
+
+/// Parses the hook of a base chain.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/netfilter/nf_tables_api.c>
+fn parse_hook(
+    hook_attr: &NfAttr,
+    type_attr: Option<&NfAttr>,
+    policy: Option<ChainPolicy>,
+) -> Result<BaseChain> {
+    let attrs = hook_attr.as_nested()?;
+
+    let hook = match require_attr(&attrs, HookAttrClass::HOOKNUM as u16)?.as_be32()? {
+        0 => FilterHook::PreRouting,
+        1 => FilterHook::LocalIn,
+        2 => FilterHook::Forward,
+        3 => FilterHook::LocalOut,
+        4 => FilterHook::PostRouting,
+        _ => return_errno_with_message!(Errno::EOPNOTSUPP, "the hook is not supported"),
+    };
+    let priority = require_attr(&attrs, HookAttrClass::PRIORITY as u16)?.as_be32()? as i32;
+
+    if find_attr(&attrs, HookAttrClass::DEV as u16).is_some()
+        || find_attr(&attrs, HookAttrClass::DEVS as u16).is_some()
+    {
+        return_errno_with_message!(
+            Errno::EOPNOTSUPP,
+            "hooks bound to devices are not supported"
+        );
+    }
+
+    let type_ = match type_attr {
+        Some(attr) => ChainType::from_name(parse_name(attr)?)?,
+        None => ChainType::Filter,
+    };
+
+    Ok(BaseChain {
+        hook,
+        priority,
+        type_,
+        policy: policy.unwrap_or(ChainPolicy::Accept),
+    })
+}
+
+/// Deletes a chain.
+///
+/// If `is_destroy` is true, it is not an error if the chain does not exist.
+pub(super) fn do_del_chain(request: &NfSegment, batch: &mut Batch, is_destroy: bool) -> Result<()> {
+    let attrs = request.attrs();
+    let family = request.body().family;
+    let table_name = parse_name(require_attr(attrs, ChainAttrClass::TABLE as u16)?)?;
+
+    if find_attr(attrs, ChainAttrClass::HOOK as u16).is_some() {
+        return_errno_with_message!(
+            Errno::EOPNOTSUPP,
+            "deleting the devices of a chain is not supported"
+        );
+    }
+
+    let table = batch.transaction().ruleset().table(family, table_name)?;
+    let result = if let Some(attr) = find_attr(attrs, ChainAttrClass::HANDLE as u16) {
+        table.chain_by_handle(attr.as_be64()?)
+    } else if let Some(attr) = find_attr(attrs, ChainAttrClass::NAME as u16) {
+        table.chain(parse_name(attr)?)
+    } else {
+        return_errno_with_message!(Errno::EINVAL, "the chain is not specified");
+    };
+    let chain = match result {
+        Ok(chain) => chain,
+        Err(error) if is_destroy && error.error() == Errno::ENOENT => return Ok(()),
+        Err(error) => return Err(error),
+    };
+
+    let request_flags = DeleteRequestFlags::from_bits_truncate(request.header().flags);
+    if request_flags.contains(DeleteRequestFlags::NONREC) && !chain.rules().is_empty() {
+        return_errno_with_message!(Errno::EBUSY, "the chain is not empty");
+    }
+
+    let name = chain.name().to_string();
+    let table = batch
+        .transaction_mut()
+        .ruleset_mut()
+        .table_mut(family, table_name)?;
+    let chain = table.remove_chain(&name)?;
+
+    let header = request.header();
+    let mut segments = chain
+        .rules()
+        .iter()
+        .map(|rule| rule_to_segment(header, NftMsgType::DELRULE, table, &chain, rule, 0))
+        .collect::<Vec<_>>();
+    segments.push(chain_to_segment(
+        header,
+        NftMsgType::DELCHAIN,
+        table,
+        &chain,
+        0,
+    ));
+    for segment in segments {
+        batch.push_notification(header, segment);
+    }
+
+    Ok(())
+}
+
+pub(super) fn do_get_chain(request: &NfSegment, netfilter: &Netfilter) -> Result<Vec<NfnlSegment>> {
+    let header = request.header();
+    let attrs = request.attrs();
+    let family = request.body().family;
+    let generation = netfilter.generation();
+    let ruleset = netfilter.ruleset();
+
+    if !is_dump(header) {
+        let table_name = parse_name(require_attr(attrs, ChainAttrClass::TABLE as u16)?)?;
+        let name = parse_name(require_attr(attrs, ChainAttrClass::NAME as u16)?)?;
+        let table = ruleset.table(family, table_name)?;
+        let chain = table.chain(name)?;
+        return Ok(vec![chain_to_segment(
+            header,
+            NftMsgType::NEWCHAIN,
+            table,
+            chain,
+            generation,
+        )]);
+    }
+
+    let table_name = find_attr(attrs, ChainAttrClass::TABLE as u16)
+        .map(parse_name)
+        .transpose()?;
+
+    let mut response_segments = Vec::new();
+    for table in ruleset.tables() {
+        if family != NFPROTO_UNSPEC && table.family() != family {
+            continue;
+        }
+        if table_name.is_some_and(|name| table.name() != name) {
+            continue;
+        }
+        for chain in table.chains() {
+            response_segments.push(chain_to_segment(
+                header,
+                NftMsgType::NEWCHAIN,
+                table,
+                chain,
+                generation,
+            ));
+        }
+    }
+    finish_dump(header, &mut response_segments);
+
+    Ok(response_segments)
+}
+
+/// Describes the chain in a segment.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/netfilter/nf_tables_api.c>
+pub(super) fn chain_to_segment(
+    request_header: &CMsgSegHdr,
+    type_: NftMsgType,
+    table: &Table,
+    chain: &Chain,
+    generation: u32,
+) -> NfnlSegment {
+    let mut attrs = vec![
+        NfAttr::new_str(ChainAttrClass::TABLE as u16, table.name()),
+        NfAttr::new_str(ChainAttrClass::NAME as u16, chain.name()),
+        NfAttr::new_be64(ChainAttrClass::HANDLE as u16, chain.handle()),
+    ];
+
+    // Like Linux, the deletion events only identify the chain.
+    if type_ != NftMsgType::DELCHAIN {
+        if let Some(base) = chain.base() {
+            let hook_attrs = [
+                NfAttr::new_be32(HookAttrClass::HOOKNUM as u16, base.hook as u32),
+                NfAttr::new_be32(HookAttrClass::PRIORITY as u16, base.priority as u32),
+            ];
+            attrs.push(NfAttr::new_nested(ChainAttrClass::HOOK as u16, &hook_attrs));
+            attrs.push(NfAttr::new_be32(
+                ChainAttrClass::POLICY as u16,
+                base.policy.code() as u32,
+            ));
+            attrs.push(NfAttr::new_str(
+                ChainAttrClass::TYPE as u16,
+                base.type_.name(),
+            ));
+            attrs.push(NfAttr::new_be32(
+                ChainAttrClass::FLAGS as u16,
+                ChainFlags::BASE.bits(),
+            ));
+        }
+        attrs.push(NfAttr::new_be32(
+            ChainAttrClass::USE as u16,
+            table.chain_use_count(chain.name()),
+        ));
+        if let Some(userdata) = chain.userdata() {
+            attrs.push(NfAttr::new(
+                ChainAttrClass::USERDATA as u16,
+                userdata.to_vec(),
+            ));
+        }
+    }
+
+    new_segment(request_header, type_, table.family(), generation, attrs)
+}
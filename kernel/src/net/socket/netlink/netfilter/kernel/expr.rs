@@ -0,0 +1,807 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Parse and describe the expressions of rules.
+//!
+//! Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/netfilter/nf_tables_api.c>
+
+use super::util::{parse_name, require_attr};
+use crate::{
+    net::{
+        netfilter::{
+            CmpOp, Counter, CtKey, Direction, Expr, MetaKey, NFPROTO_INET, NFPROTO_IPV4,
+            NFPROTO_IPV6, NatExpr, NatType, PayloadBase, RangeOp, Register, Verdict,
+        },
+        socket::netlink::netfilter::message::{NfAttr, find_attr},
+    },
+    prelude::*,
+};
+
+/// The maximum number of expressions in a rule.
+const NFT_RULE_MAXEXPRS: usize = 128;
+
+/// The register that holds the verdict.
+const NFT_REG_VERDICT: u32 = 0;
+
+/// The maximum length of the values in expressions.
+const NFT_DATA_MAXLEN: usize = 16;
+
+/// The length of a port in a register.
+const PORT_LEN: usize = 2;
+
+/// The NAT range flags that are supported, including those only reported to user space.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/netfilter/nf_nat.h>
+const NF_NAT_RANGE_MAP_IPS: u32 = 1 << 0;
+const NF_NAT_RANGE_PROTO_SPECIFIED: u32 = 1 << 1;
+const NF_NAT_RANGE_PROTO_OFFSET: u32 = 1 << 5;
+const NF_NAT_RANGE_NETMAP: u32 = 1 << 6;
+const NF_NAT_RANGE_MASK: u32 = (1 << 7) - 1;
+
+/// List attributes.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/netfilter/nf_tables.h>
+#[expect(non_camel_case_types)]
+#[expect(clippy::upper_case_acronyms)]
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+enum ListAttrClass {
+    UNSPEC = 0,
+    ELEM = 1,
+}
+
+/// Expression attributes.
+#[expect(non_camel_case_types)]
+#[expect(clippy::upper_case_acronyms)]
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+enum ExprAttrClass {
+    UNSPEC = 0,
+    NAME = 1,
+    DATA = 2,
+}
+
+/// Data attributes.
+#[expect(non_camel_case_types)]
+#[expect(clippy::upper_case_acronyms)]
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+enum DataAttrClass {
+    UNSPEC = 0,
+    VALUE = 1,
+    VERDICT = 2,
+}
+
+/// Verdict attributes.
+#[expect(non_camel_case_types)]
+#[expect(clippy::upper_case_acronyms)]
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+enum VerdictAttrClass {
+    UNSPEC = 0,
+    CODE = 1,
+    CHAIN = 2,
+    CHAIN_ID = 3,
+}
+
+/// Attributes of the `immediate` expression.
+#[expect(non_camel_case_types)]
+#[expect(clippy::upper_case_acronyms)]
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+enum ImmediateAttrClass {
+    UNSPEC = 0,
+    DREG = 1,
+    DATA = 2,
+}
+
+/// Attributes of the `cmp` expression.
+#[expect(non_camel_case_types)]
+#[expect(clippy::upper_case_acronyms)]
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+enum CmpAttrClass {
+    UNSPEC = 0,
+    SREG = 1,
+    OP = 2,
+    DATA = 3,
+}
+
+/// Attributes of the `range` expression.
+#[expect(non_camel_case_types)]
+#[expect(clippy::upper_case_acronyms)]
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+enum RangeAttrClass {
+    UNSPEC = 0,
+    SREG = 1,
+    OP = 2,
+    FROM_DATA = 3,
+    TO_DATA = 4,
+}
+
+/// Attributes of the `bitwise` expression.
+#[expect(non_camel_case_types)]
+#[expect(clippy::upper_case_acronyms)]
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+enum BitwiseAttrClass {
+    UNSPEC = 0,
+    SREG = 1,
+    DREG = 2,
+    LEN = 3,
+    MASK = 4,
+    XOR = 5,
+    OP = 6,
+    DATA = 7,
+}
+
+/// The only supported operation of the `bitwise` expression (i.e., `NFT_BITWISE_MASK_XOR`).
+const NFT_BITWISE_BOOL: u32 = 0;
+
+/// Attributes of the `payload` expression.
+#[expect(non_camel_case_types)]
+#[expect(clippy::upper_case_acronyms)]
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+enum PayloadAttrClass {
+    UNSPEC = 0,
+    DREG = 1,
+    BASE = 2,
+    OFFSET = 3,
+    LEN = 4,
+    SREG = 5,
+    CSUM_TYPE = 6,
+    CSUM_OFFSET = 7,
+    CSUM_FLAGS = 8,
+}
+
+/// Attributes of the `meta` expression.
+#[expect(non_camel_case_types)]
+#[expect(clippy::upper_case_acronyms)]
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+enum MetaAttrClass {
+    UNSPEC = 0,
+    DREG = 1,
+    KEY = 2,
+    SREG = 3,
+}
+
+/// Attributes of the `ct` expression.
+#[expect(non_camel_case_types)]
+#[expect(clippy::upper_case_acronyms)]
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+enum CtAttrClass {
+    UNSPEC = 0,
+    DREG = 1,
+    KEY = 2,
+    DIRECTION = 3,
+    SREG = 4,
+}
+
+/// Attributes of the `counter` expression.
+#[expect(non_camel_case_types)]
+#[expect(clippy::upper_case_acronyms)]
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+enum CounterAttrClass {
+    UNSPEC = 0,
+    BYTES = 1,
+    PACKETS = 2,
+    PAD = 3,
+}
+
+/// Attributes of the `nat` expression.
+#[expect(non_camel_case_types)]
+#[expect(clippy::upper_case_acronyms)]
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+enum NatAttrClass {
+    UNSPEC = 0,
+    TYPE = 1,
+    FAMILY = 2,
+    REG_ADDR_MIN = 3,
+    REG_ADDR_MAX = 4,
+    REG_PROTO_MIN = 5,
+    REG_PROTO_MAX = 6,
+    FLAGS = 7,
+}
+
+/// Attributes of the `masq` expression.
+#[expect(non_camel_case_types)]
+#[expect(clippy::upper_case_acronyms)]
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+enum MasqAttrClass {
+    UNSPEC = 0,
+    FLAGS = 1,
+    REG_PROTO_MIN = 2,
+    REG_PROTO_MAX = 3,
+}
+
+/// Attributes of the `redir` expression.
+#[expect(non_camel_case_types)]
+#[expect(clippy::upper_case_acronyms)]
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+enum RedirAttrClass {
+    UNSPEC = 0,
+    REG_PROTO_MIN = 1,
+    REG_PROTO_MAX = 2,
+    FLAGS = 3,
+}
+
+/// The context to parse the expressions of a rule.
+pub(super) struct ExprContext<'a> {
+    /// The family of the table that contains the rule.
+    pub(super) family: u8,
+    /// Finds the chain added in the current batch by its ID.
+    pub(super) chain_by_id: &'a dyn Fn(u32) -> Result<String>,
+}
+
+/// Parses the list of expressions.
+pub(super) fn parse_exprs(attr: &NfAttr, ctx: &ExprContext) -> Result<Vec<Expr>> {
+    let elems = attr.as_nested()?;
+    if elems.len() > NFT_RULE_MAXEXPRS {
+        return_errno_with_message!(Errno::EINVAL, "there are too many expressions");
+    }
+
+    elems
+        .iter()
+        .map(|elem| {
+            if elem.class() != ListAttrClass::ELEM as u16 {
+                return_errno_with_message!(Errno::EINVAL, "the list element is invalid");
+            }
+            parse_expr(elem, ctx)
+        })
+        .collect()
+}
+
+fn parse_expr(elem: &NfAttr, ctx: &ExprContext) -> Result<Expr> {
+    let attrs = elem.as_nested()?;
+    let name = parse_name(require_attr(&attrs, ExprAttrClass::NAME as u16)?)?;
+    let attrs = match find_attr(&attrs, ExprAttrClass::DATA as u16) {
+        Some(attr) => attr.as_nested()?,
+        None => Vec::new(),
+    };
+
+    match name {
+        "immediate" => parse_immediate(&attrs, ctx),
+        "cmp" => parse_cmp(&attrs),
+        "range" => parse_range(&attrs),
+        "bitwise" => parse_bitwise(&attrs),
+        "payload" => parse_payload(&attrs),
+        "meta" => parse_meta(&attrs),
+        "ct" => parse_ct(&attrs, ctx),
+        "counter" => parse_counter(&attrs),
+        "nat" => parse_nat(&attrs, ctx),
+        "masq" => parse_masq(&attrs),
+        "redir" => parse_redir(&attrs),
+        // Like Linux, unknown expressions are reported as missing rather than unsupported.
+        _ => return_errno_with_message!(Errno::ENOENT, "the expression does not exist"),
+    }
+}
+
+fn parse_immediate(attrs: &[NfAttr], ctx: &ExprContext) -> Result<Expr> {
+    let dreg = require_attr(attrs, ImmediateAttrClass::DREG as u16)?.as_be32()?;
+    let data = require_attr(attrs, ImmediateAttrClass::DATA as u16)?;
+
+    if dreg == NFT_REG_VERDICT {
+        return Ok(Expr::Verdict(parse_verdict(data, ctx)?));
+    }
+
+    let value = parse_value(data, NFT_DATA_MAXLEN)?;
+    let dreg = Register::new(dreg, value.len())?;
+    Ok(Expr::Immediate { dreg, value })
+}
+
+fn parse_cmp(attrs: &[NfAttr]) -> Result<Expr> {
+    let data = parse_value(
+        require_attr(attrs, CmpAttrClass::DATA as u16)?,
+        NFT_DATA_MAXLEN,
+    )?;
+    let sreg = Register::new(
+        require_attr(attrs, CmpAttrClass::SREG as u16)?.as_be32()?,
+        data.len(),
+    )?;
+    let op = CmpOp::try_from(require_attr(attrs, CmpAttrClass::OP as u16)?.as_be32()?)?;
+
+    Ok(Expr::Cmp { sreg, op, data })
+}
+
+fn parse_range(attrs: &[NfAttr]) -> Result<Expr> {
+    let from = parse_value(
+        require_attr(attrs, RangeAttrClass::FROM_DATA as u16)?,
+        NFT_DATA_MAXLEN,
+    )?;
+    let to = parse_value(
+        require_attr(attrs, RangeAttrClass::TO_DATA as u16)?,
+        NFT_DATA_MAXLEN,
+    )?;
+    if from.len() != to.len() {
+        return_errno_with_message!(Errno::EINVAL, "the bounds of the range mismatch");
+    }
+    let sreg = Register::new(
+        require_attr(attrs, RangeAttrClass::SREG as u16)?.as_be32()?,
+        from.len(),
+    )?;
+    let op = RangeOp::try_from(require_attr(attrs, RangeAttrClass::OP as u16)?.as_be32()?)?;
+
+    Ok(Expr::Range { sreg, op, from, to })
+}
+
+fn parse_bitwise(attrs: &[NfAttr]) -> Result<Expr> {
+    let len = require_attr(attrs, BitwiseAttrClass::LEN as u16)?.as_be32()?;
+    if len > u8::MAX as u32 {
+        return_errno_with_message!(Errno::ERANGE, "the data length is too large");
+    }
+    let len = len as usize;
+    let sreg = Register::new(
+        require_attr(attrs, BitwiseAttrClass::SREG as u16)?.as_be32()?,
+        len,
+    )?;
+    let dreg = Register::new(
+        require_attr(attrs, BitwiseAttrClass::DREG as u16)?.as_be32()?,
+        len,
+    )?;
+
+    if let Some(attr) = find_attr(attrs, BitwiseAttrClass::OP as u16)
+        && attr.as_be32()? != NFT_BITWISE_BOOL
+    {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "the bitwise operation is not supported");
+    }
+    if find_attr(attrs, BitwiseAttrClass::DATA as u16).is_some() {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "the bitwise operation is not supported");
+    }
+
+    let mask = parse_value(
+        require_attr(attrs, BitwiseAttrClass::MASK as u16)?,
+        NFT_DATA_MAXLEN,
+    )?;
+    let xor = parse_value(
+        require_attr(attrs, BitwiseAttrClass::XOR as u16)?,
+        NFT_DATA_MAXLEN,
+    )?;
+    if mask.len() != len || xor.len() != len {
+        return_errno_with_message!(
+            Errno::EINVAL,
+            "the mask or the XOR value has a wrong length"
+        );
+    }
+
+    Ok(Expr::Bitwise {
+        sreg,
+        dreg,
+        mask,
+        xor,
+    })
+}
+
+fn parse_payload(attrs: &[NfAttr]) -> Result<Expr> {
+    if find_attr(attrs, PayloadAttrClass::SREG as u16).is_some()
+        || find_attr(attrs, PayloadAttrClass::CSUM_TYPE as u16).is_some()
+        || find_attr(attrs, PayloadAttrClass::CSUM_OFFSET as u16).is_some()
+        || find_attr(attrs, PayloadAttrClass::CSUM_FLAGS as u16).is_some()
+    {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "mangling packets is not supported");
+    }
+
+    let base =
+        PayloadBase::try_from(require_attr(attrs, PayloadAttrClass::BASE as u16)?.as_be32()?)
+            .map_err(|_| {
+                Error::with_message(Errno::EOPNOTSUPP, "the payload base is not supported")
+            })?;
+    let offset = require_attr(attrs, PayloadAttrClass::OFFSET as u16)?.as_be32()?;
+    let len = require_attr(attrs, PayloadAttrClass::LEN as u16)?.as_be32()?;
+    if offset > u8::MAX as u32 || len > u8::MAX as u32 {
+        return_errno_with_message!(Errno::ERANGE, "the offset or the length is too large");
+    }
+    let dreg = Register::new(
+        require_attr(attrs, PayloadAttrClass::DREG as u16)?.as_be32()?,
+        len as usize,
+    )?;
+
+    Ok(Expr::Payload {
+        dreg,
+        base,
+        offset,
+        len,
+    })
+}
+
+fn parse_meta(attrs: &[NfAttr]) -> Result<Expr> {
+    if find_attr(attrs, MetaAttrClass::SREG as u16).is_some() {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "setting the metadata is not supported");
+    }
+
+    let key = MetaKey::try_from(require_attr(attrs, MetaAttrClass::KEY as u16)?.as_be32()?)
+        .map_err(|_| Error::with_message(Errno::EOPNOTSUPP, "the meta key is not supported"))?;
+    let dreg = Register::new(
+        require_attr(attrs, MetaAttrClass::DREG as u16)?.as_be32()?,
+        key.data_len(),
+    )?;
+
+    Ok(Expr::Meta { dreg, key })
+}
+
+fn parse_ct(attrs: &[NfAttr], ctx: &ExprContext) -> Result<Expr> {
+    if find_attr(attrs, CtAttrClass::SREG as u16).is_some() {
+        return_errno_with_message!(
+            Errno::EOPNOTSUPP,
+            "setting the connection tracking information is not supported"
+        );
+    }
+
+    let key = CtKey::try_from(require_attr(attrs, CtAttrClass::KEY as u16)?.as_be32()?)
+        .map_err(|_| Error::with_message(Errno::EOPNOTSUPP, "the ct key is not supported"))?;
+
+    let dir = match find_attr(attrs, CtAttrClass::DIRECTION as u16) {
+        Some(attr) => match attr.as_u8()? {
+            0 => Some(Direction::Original),
+            1 => Some(Direction::Reply),
+            _ => return_errno_with_message!(Errno::EINVAL, "the direction is invalid"),
+        },
+        None => None,
+    };
+    if dir.is_some() && key.rejects_direction() {
+        return_errno_with_message!(Errno::EINVAL, "the ct key does not take a direction");
+    }
+    if dir.is_none() && key.needs_direction() {
+        return_errno_with_message!(Errno::EINVAL, "the ct key requires a direction");
+    }
+
+    let dreg = Register::new(
+        require_attr(attrs, CtAttrClass::DREG as u16)?.as_be32()?,
+        key.data_len(ctx.family),
+    )?;
+
+    Ok(Expr::Ct { dreg, key, dir })
+}
+
+fn parse_counter(attrs: &[NfAttr]) -> Result<Expr> {
+    let bytes = match find_attr(attrs, CounterAttrClass::BYTES as u16) {
+        Some(attr) => attr.as_be64()?,
+        None => 0,
+    };
+    let packets = match find_attr(attrs, CounterAttrClass::PACKETS as u16) {
+        Some(attr) => attr.as_be64()?,
+        None => 0,
+    };
+
+    Ok(Expr::Counter(Arc::new(Counter::new(packets, bytes))))
+}
+
+fn parse_nat(attrs: &[NfAttr], ctx: &ExprContext) -> Result<Expr> {
+    let type_ = NatType::try_from(require_attr(attrs, NatAttrClass::TYPE as u16)?.as_be32()?)
+        .map_err(|_| Error::with_message(Errno::EOPNOTSUPP, "the NAT type is not supported"))?;
+
+    let family = require_attr(attrs, NatAttrClass::FAMILY as u16)?.as_be32()?;
+    if ctx.family != NFPROTO_INET && ctx.family as u32 != family {
+        return_errno_with_message!(
+            Errno::EOPNOTSUPP,
+            "the NAT family does not match the table family"
+        );
+    }
+    let addr_len = if family == NFPROTO_IPV4 as u32 {
+        Some(4)
+    } else if family == NFPROTO_IPV6 as u32 {
+        Some(16)
+    } else {
+        None
+    };
+
+    let mut flags = 0;
+
+    let addrs = match find_attr(attrs, NatAttrClass::REG_ADDR_MIN as u16) {
+        Some(min) => {
+            let Some(addr_len) = addr_len else {
+                return_errno_with_message!(Errno::EAFNOSUPPORT, "the NAT family is not supported");
+            };
+            flags |= NF_NAT_RANGE_MAP_IPS;
+            Some(parse_reg_range(
+                min,
+                find_attr(attrs, NatAttrClass::REG_ADDR_MAX as u16),
+                addr_len,
+            )?)
+        }
+        None => None,
+    };
+    let ports = match find_attr(attrs, NatAttrClass::REG_PROTO_MIN as u16) {
+        Some(min) => {
+            flags |= NF_NAT_RANGE_PROTO_SPECIFIED;
+            Some(parse_reg_range(
+                min,
+                find_attr(attrs, NatAttrClass::REG_PROTO_MAX as u16),
+                PORT_LEN,
+            )?)
+        }
+        None => None,
+    };
+
+    if let Some(attr) = find_attr(attrs, NatAttrClass::FLAGS as u16) {
+        let extra_flags = attr.as_be32()?;
+        if extra_flags & !NF_NAT_RANGE_MASK != 0 {
+            return_errno_with_message!(Errno::EOPNOTSUPP, "the NAT flags are invalid");
+        }
+        if extra_flags & (NF_NAT_RANGE_NETMAP | NF_NAT_RANGE_PROTO_OFFSET) != 0 {
+            return_errno_with_message!(
+                Errno::EOPNOTSUPP,
+                "mapping prefixes or port offsets is not supported"
+            );
+        }
+        flags |= extra_flags;
+    }
+
+    Ok(Expr::Nat(NatExpr {
+        type_,
+        family: family as u8,
+        addrs,
+        ports,
+        flags,
+    }))
+}
+
+fn parse_masq(attrs: &[NfAttr]) -> Result<Expr> {
+    let flags = parse_nat_flags(find_attr(attrs, MasqAttrClass::FLAGS as u16))?;
+    let ports = match find_attr(attrs, MasqAttrClass::REG_PROTO_MIN as u16) {
+        Some(min) => Some(parse_reg_range(
+            min,
+            find_attr(attrs, MasqAttrClass::REG_PROTO_MAX as u16),
+            PORT_LEN,
+        )?),
+        None => None,
+    };
+
+    Ok(Expr::Masq { ports, flags })
+}
+
+fn parse_redir(attrs: &[NfAttr]) -> Result<Expr> {
+    let mut flags = parse_nat_flags(find_attr(attrs, RedirAttrClass::FLAGS as u16))?;
+    let ports = match find_attr(attrs, RedirAttrClass::REG_PROTO_MIN as u16) {
+        Some(min) => {
+            flags |= NF_NAT_RANGE_PROTO_SPECIFIED;
+            Some(parse_reg_range(
+                min,
+                find_attr(attrs, RedirAttrClass::REG_PROTO_MAX as u16),
+                PORT_LEN,
+            )?)
+        }
+        None => None,
+    };
+
+    Ok(Expr::Redir { ports, flags })
+}
+
+fn parse_nat_flags(attr: Option<&NfAttr>) -> Result<u32> {
+    let Some(attr) = attr else {
+        return Ok(0);
+    };
+
+    let flags = attr.as_be32()?;
+    if flags & !NF_NAT_RANGE_MASK != 0 {
+        return_errno_with_message!(Errno::EINVAL, "the NAT flags are invalid");
+    }
+    Ok(flags)
+}
+
+/// Parses the registers that contain the minimum and the maximum values of a range.
+///
+/// The maximum value is the same as the minimum value if its register is not specified.
+fn parse_reg_range(min: &NfAttr, max: Option<&NfAttr>, len: usize) -> Result<(Register, Register)> {
+    let min = Register::new(min.as_be32()?, len)?;
+    let max = match max {
+        Some(attr) => Register::new(attr.as_be32()?, len)?,
+        None => min,
+    };
+    Ok((min, max))
+}
+
+/// Parses the data that contains a value.
+fn parse_value(attr: &NfAttr, max_len: usize) -> Result<Vec<u8>> {
+    let attrs = attr.as_nested()?;
+    if find_attr(&attrs, DataAttrClass::VERDICT as u16).is_some() {
+        return_errno_with_message!(Errno::EINVAL, "the data is not a value");
+    }
+
+    let value = require_attr(&attrs, DataAttrClass::VALUE as u16)?.payload();
+    if value.is_empty() {
+        return_errno_with_message!(Errno::EINVAL, "the value is empty");
+    }
+    if value.len() > max_len {
+        return_errno_with_message!(Errno::EOVERFLOW, "the value is too long");
+    }
+    Ok(value.to_vec())
+}
+
+/// Parses the data that contains a verdict.
+fn parse_verdict(attr: &NfAttr, ctx: &ExprContext) -> Result<Verdict> {
+    let attrs = attr.as_nested()?;
+    let attrs = require_attr(&attrs, DataAttrClass::VERDICT as u16)?.as_nested()?;
+    let code = require_attr(&attrs, VerdictAttrClass::CODE as u16)?.as_be32()? as i32;
+
+    let verdict = match code {
+        Verdict::NF_ACCEPT => Verdict::Accept,
+        Verdict::NF_DROP => Verdict::Drop,
+        Verdict::NFT_CONTINUE => Verdict::Continue,
+        Verdict::NFT_BREAK => Verdict::Break,
+        Verdict::NFT_RETURN => Verdict::Return,
+        Verdict::NFT_JUMP | Verdict::NFT_GOTO => {
+            let chain = if let Some(attr) = find_attr(&attrs, VerdictAttrClass::CHAIN as u16) {
+                parse_name(attr)?.to_string()
+            } else if let Some(attr) = find_attr(&attrs, VerdictAttrClass::CHAIN_ID as u16) {
+                (ctx.chain_by_id)(attr.as_be32()?)?
+            } else {
+                return_errno_with_message!(Errno::EINVAL, "the target chain is not specified");
+            };
+            if code == Verdict::NFT_JUMP {
+                Verdict::Jump(chain)
+            } else {
+                Verdict::Goto(chain)
+            }
+        }
+        _ => return_errno_with_message!(Errno::EINVAL, "the verdict is invalid"),
+    };
+
+    Ok(verdict)
+}
+
+/// Describes the list of expressions in an attribute.
+pub(super) fn exprs_to_attr(class: u16, exprs: &[Expr]) -> NfAttr {
+    let elems = exprs.iter().map(expr_to_attr).collect::<Vec<_>>();
+    NfAttr::new_nested(class, &elems)
+}
+
+fn expr_to_attr(expr: &Expr) -> NfAttr {
+    let attrs = match expr {
+        Expr::Immediate { dreg, value } => vec![
+            NfAttr::new_be32(ImmediateAttrClass::DREG as u16, dreg.to_raw()),
+            value_to_attr(ImmediateAttrClass::DATA as u16, value),
+        ],
+        Expr::Verdict(verdict) => {
+            let mut verdict_attrs = vec![NfAttr::new_be32(
+                VerdictAttrClass::CODE as u16,
+                verdict.code() as u32,
+            )];
+            if let Some(chain) = verdict.chain() {
+                verdict_attrs.push(NfAttr::new_str(VerdictAttrClass::CHAIN as u16, chain));
+            }
+            let data_attrs = [NfAttr::new_nested(
+                DataAttrClass::VERDICT as u16,
+                &verdict_attrs,
+            )];
+            vec![
+                NfAttr::new_be32(ImmediateAttrClass::DREG as u16, NFT_REG_VERDICT),
+                NfAttr::new_nested(ImmediateAttrClass::DATA as u16, &data_attrs),
+            ]
+        }
+        Expr::Cmp { sreg, op, data } => vec![
+            NfAttr::new_be32(CmpAttrClass::SREG as u16, sreg.to_raw()),
+            NfAttr::new_be32(CmpAttrClass::OP as u16, *op as u32),
+            value_to_attr(CmpAttrClass::DATA as u16, data),
+        ],
+        Expr::Range { sreg, op, from, to } => vec![
+            NfAttr::new_be32(RangeAttrClass::SREG as u16, sreg.to_raw()),
+            NfAttr::new_be32(RangeAttrClass::OP as u16, *op as u32),
+            value_to_attr(RangeAttrClass::FROM_DATA as u16, from),
+            value_to_attr(RangeAttrClass::TO_DATA as u16, to),
+        ],
+        Expr::Bitwise {
+            sreg,
+            dreg,
+            mask,
+            xor,
+        } => vec![
+            NfAttr::new_be32(BitwiseAttrClass::SREG as u16, sreg.to_raw()),
+            NfAttr::new_be32(BitwiseAttrClass::DREG as u16, dreg.to_raw()),
+            NfAttr::new_be32(BitwiseAttrClass::LEN as u16, mask.len() as u32),
+            NfAttr::new_be32(BitwiseAttrClass::OP as u16, NFT_BITWISE_BOOL),
+            value_to_attr(BitwiseAttrClass::MASK as u16, mask),
+            value_to_attr(BitwiseAttrClass::XOR as u16, xor),
+        ],
+        Expr::Payload {
+            dreg,
+            base,
+            offset,
+            len,
+        } => vec![
+            NfAttr::new_be32(PayloadAttrClass::DREG as u16, dreg.to_raw()),
+            NfAttr::new_be32(PayloadAttrClass::BASE as u16, *base as u32),
+            NfAttr::new_be32(PayloadAttrClass::OFFSET as u16, *offset),
+            NfAttr::new_be32(PayloadAttrClass::LEN as u16, *len),
+        ],
+        Expr::Meta { dreg, key } => vec![
+            NfAttr::new_be32(MetaAttrClass::KEY as u16, *key as u32),
+            NfAttr::new_be32(MetaAttrClass::DREG as u16, dreg.to_raw()),
+        ],
+        Expr::Ct { dreg, key, dir } => {
+            let mut attrs = vec![
+                NfAttr::new_be32(CtAttrClass::KEY as u16, *key as u32),
+                NfAttr::new_be32(CtAttrClass::DREG as u16, dreg.to_raw()),
+            ];
+            if let Some(dir) = dir {
+                attrs.push(NfAttr::new(CtAttrClass::DIRECTION as u16, vec![*dir as u8]));
+            }
+            attrs
+        }
+        Expr::Counter(counter) => vec![
+            NfAttr::new_be64(CounterAttrClass::BYTES as u16, counter.bytes()),
+            NfAttr::new_be64(CounterAttrClass::PACKETS as u16, counter.packets()),
+        ],
+        Expr::Nat(nat) => {
+            let mut attrs = vec![
+                NfAttr::new_be32(NatAttrClass::TYPE as u16, nat.type_ as u32),
+                NfAttr::new_be32(NatAttrClass::FAMILY as u16, nat.family as u32),
+            ];
+            if let Some((min, max)) = nat.addrs {
+                attrs.push(NfAttr::new_be32(
+                    NatAttrClass::REG_ADDR_MIN as u16,
+                    min.to_raw(),
+                ));
+                attrs.push(NfAttr::new_be32(
+                    NatAttrClass::REG_ADDR_MAX as u16,
+                    max.to_raw(),
+                ));
+            }
+            if let Some((min, max)) = nat.ports {
+                attrs.push(NfAttr::new_be32(
+                    NatAttrClass::REG_PROTO_MIN as u16,
+                    min.to_raw(),
+                ));
+                attrs.push(NfAttr::new_be32(
+                    NatAttrClass::REG_PROTO_MAX as u16,
+                    max.to_raw(),
+                ));
+            }
+            if nat.flags != 0 {
+                attrs.push(NfAttr::new_be32(NatAttrClass::FLAGS as u16, nat.flags));
+            }
+            attrs
+        }
+        Expr::Masq { ports, flags } => {
+            let mut attrs = Vec::new();
+            if *flags != 0 {
+                attrs.push(NfAttr::new_be32(MasqAttrClass::FLAGS as u16, *flags));
+            }
+            if let Some((min, max)) = ports {
+                attrs.push(NfAttr::new_be32(
+                    MasqAttrClass::REG_PROTO_MIN as u16,
+                    min.to_raw(),
+                ));
+                attrs.push(NfAttr::new_be32(
+                    MasqAttrClass::REG_PROTO_MAX as u16,
+                    max.to_raw(),
+                ));
+            }
+            attrs
+        }
+        Expr::Redir { ports, flags } => {
+            let mut attrs = Vec::new();
+            if let Some((min, max)) = ports {
+                attrs.push(NfAttr::new_be32(
+                    RedirAttrClass::REG_PROTO_MIN as u16,
+                    min.to_raw(),
+                ));
+                attrs.push(NfAttr::new_be32(
+                    RedirAttrClass::REG_PROTO_MAX as u16,
+                    max.to_raw(),
+                ));
+            }
+            if *flags != 0 {
+                attrs.push(NfAttr::new_be32(RedirAttrClass::FLAGS as u16, *flags));
+            }
+            attrs
+        }
+    };
+
+    let elem_attrs = [
+        NfAttr::new_str(ExprAttrClass::NAME as u16, expr.name()),
+        NfAttr::new_nested(ExprAttrClass::DATA as u16, &attrs),
+    ];
+    NfAttr::new_nested(ListAttrClass::ELEM as u16, &elem_attrs)
+}
+
+fn value_to_attr(class: u16, value: &[u8]) -> NfAttr {
+    let data_attrs = [NfAttr::new(DataAttrClass::VALUE as u16, value.to_vec())];
+    NfAttr::new_nested(class, &data_attrs)
+}
@@ -0,0 +1,438 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! This module defines the kernel socket,
+//! which is responsible for handling requests from user space.
+//!
+//! Requests that modify the ruleset must be sent in a batch, which starts with a batch begin
+//! segment and ends with a batch end segment in a single message. The modifications of a batch
+//! are made in a transaction, which is committed only if all the requests in the batch succeed.
+//!
+//! Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/netfilter/nfnetlink.c>
+
+use core::marker::PhantomData;
+
+use util::{
+    ack_segment, finish_dump, is_dump, needs_ack, needs_echo, new_segment, notify, unicast,
+};
+
+use super::message::{NFNL_SUBSYS_NFTABLES, NfAttr, NfSegment, NfnlSegment, NftMsgType, find_attr};
+use crate::{
+    net::{
+        net_ns::NetNamespace,
+        netfilter::{NFPROTO_UNSPEC, Netfilter, Transaction},
+        socket::netlink::{
+            addr::PortNum,
+            message::{CMsgSegHdr, ErrorSegment, ProtocolSegment},
+        },
+    },
+    prelude::*,
+    process::{credentials::capabilities::CapSet, posix_thread::AsPosixThread},
+};
+
+mod chain;
+mod expr;
+mod rule;
+mod table;
+mod util;
+
+pub(super) struct NetlinkNetfilterKernelSocket {
+    _private: PhantomData<()>,
+}
+
+impl NetlinkNetfilterKernelSocket {
+    const fn new() -> Self {
+        Self {
+            _private: PhantomData,
+        }
+    }
+
+    /// Handles the segments in a message sent from user space.
+    ///
+    /// Like Linux, the segments form a batch if the first segment begins a batch. Otherwise,
+    /// the segments are handled one by one.
+    pub(super) fn handle_requests(
+        &self,
+        net_ns: &Arc<NetNamespace>,
+        requests: Vec<core::result::Result<NfnlSegment, ErrorSegment>>,
+        dst_port: PortNum,
+    ) {
+        debug!("netlink netfilter requests: {:?}", requests);
+
+        let Some(first_header) = requests.first().map(|request| match request {
+            Ok(segment) => *segment.header(),
+            Err(err_segment) => *err_segment.body().request_header(),
+        }) else {
+            return;
+        };
+
+        // Like Linux, all requests (including GET requests) require the `CAP_NET_ADMIN`
+        // capability.
+        if let Err(error) = check_permission(net_ns) {
+            let err_segment = ErrorSegment::new_from_request(&first_header, Some(error));
+            unicast(net_ns, dst_port, vec![NfnlSegment::Error(err_segment)]);
+            return;
+        }
+
+        if let Some(Ok(NfnlSegment::BatchBegin(_))) = requests.first() {
+            self.handle_batch(net_ns, requests, dst_port);
+            return;
+        }
+
+        for request in requests {
+            let response_segments = match request {
+                Ok(NfnlSegment::Nftables(segment)) => handle_get_request(net_ns, &segment),
+                Ok(segment) => {
+                    let error = Error::with_message(
+                        Errno::EINVAL,
+                        "the batch delimiter does not begin the message",
+                    );
+                    let err_segment = ErrorSegment::new_from_request(segment.header(), Some(error));
+                    vec![NfnlSegment::Error(err_segment)]
+                }
+                Err(err_segment) => vec![NfnlSegment::Error(err_segment)],
+            };
+            unicast(net_ns, dst_port, response_segments);
+        }
+    }
+
+    /// Handles a batch of requests that modify the ruleset.
+    fn handle_batch(
+        &self,
+        net_ns: &Arc<NetNamespace>,
+        requests: Vec<core::result::Result<NfnlSegment, ErrorSegment>>,
+        dst_port: PortNum,
+    ) {
+        let mut requests = requests.into_iter();
+        let Some(Ok(NfnlSegment::BatchBegin(begin))) = requests.next() else {
+            unreachable!("the batch should start with a batch begin segment");
+        };
+        let begin_header = *begin.header();
+
+        let mut batch = match Batch::begin(net_ns.netfilter(), &begin) {
+            Ok(batch) => batch,
+            Err(error) => {
+                let err_segment = ErrorSegment::new_from_request(&begin_header, Some(error));
+                unicast(net_ns, dst_port, vec![NfnlSegment::Error(err_segment)]);
+                return;
+            }
+        };
+
+        let mut response_segments = Vec::new();
+        if needs_ack(&begin_header) {
+            response_segments.push(ack_segment(&begin_header));
+        }
+
+        let mut has_error = false;
+        let mut is_done = false;
+
+        for request in requests {
+            let segment = match request {
+                Ok(NfnlSegment::Nftables(segment)) => segment,
+                Ok(NfnlSegment::BatchEnd(end)) => {
+                    if needs_ack(end.header()) {
+                        response_segments.push(ack_segment(end.header()));
+                    }
+                    is_done = true;
+                    break;
+                }
+                Ok(segment) => {
+                    // Like Linux, a malformed batch is aborted without reporting the results of
+                    // the previous requests.
+                    let error =
+                        Error::with_message(Errno::EINVAL, "the batch begins more than once");
+                    let err_segment = ErrorSegment::new_from_request(segment.header(), Some(error));
+                    response_segments = vec![NfnlSegment::Error(err_segment)];
+                    has_error = true;
+                    break;
+                }
+                Err(err_segment) => {
+                    response_segments.push(NfnlSegment::Error(err_segment));
+                    has_error = true;
+                    continue;
+                }
+            };
+
+            match batch.apply(&segment) {
+                Ok(()) if needs_ack(segment.header()) => {
+                    response_segments.push(ack_segment(segment.header()));
+                }
+                Ok(()) => (),
+                Err(error) => {
+                    let err_segment = ErrorSegment::new_from_request(segment.header(), Some(error));
+                    response_segments.push(NfnlSegment::Error(err_segment));
+                    has_error = true;
+                }
+            }
+        }
+
+        // The transaction is aborted if it is dropped without being committed.
+        if is_done && !has_error {
+            batch.commit(net_ns, &begin_header, dst_port);
+        }
+
+        unicast(net_ns, dst_port, response_segments);
+    }
+}
+
+/// Handles a request that does not modify the ruleset.
+fn handle_get_request(net_ns: &NetNamespace, request: &NfSegment) -> Vec<NfnlSegment> {
+    let header = request.header();
+    let netfilter = net_ns.netfilter();
+
+    let result = NftMsgType::from_seg_type(header.type_).and_then(|type_| match type_ {
+        NftMsgType::GETGEN => Ok(vec![gen_segment(header, netfilter.generation())]),
+        NftMsgType::GETTABLE => table::do_get_table(request, netfilter),
+        NftMsgType::GETCHAIN => chain::do_get_chain(request, netfilter),
+        NftMsgType::GETRULE => rule::do_get_rule(request, netfilter),
+        // Sets, stateful objects, and flowtables are not supported, so there are no such objects.
+        NftMsgType::GETSET
+        | NftMsgType::GETSETELEM
+        | NftMsgType::GETOBJ
+        | NftMsgType::GETFLOWTABLE
+            if is_dump(header) =>
+        {
+            let mut segments = Vec::new();
+            finish_dump(header, &mut segments);
+            Ok(segments)
+        }
+        NftMsgType::GETSET
+        | NftMsgType::GETSETELEM
+        | NftMsgType::GETOBJ
+        | NftMsgType::GETFLOWTABLE => {
+            return_errno_with_message!(Errno::ENOENT, "the object does not exist")
+        }
+        NftMsgType::GETRULE_RESET | NftMsgType::GETOBJ_RESET | NftMsgType::GETSETELEM_RESET => {
+            return_errno_with_message!(Errno::EOPNOTSUPP, "resetting objects is not supported")
+        }
+        _ => return_errno_with_message!(Errno::EINVAL, "the request must be sent in a batch"),
+    });
+
+    match result {
+        Ok(mut segments) => {
+            // The DONE segment of a dump request acts as the ACK segment.
+            if !is_dump(header) && needs_ack(header) {
+                segments.push(ack_segment(header));
+            }
+            segments
+        }
+        Err(error) => {
+            let err_segment = ErrorSegment::new_from_request(header, Some(error));
+            vec![NfnlSegment::Error(err_segment)]
+        }
+    }
+}
+
+/// Generation attributes.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/netfilter/nf_tables.h>
+#[expect(non_camel_case_types)]
+#[expect(clippy::upper_case_acronyms)]
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+enum GenAttrClass {
+    UNSPEC = 0,
+    ID = 1,
+    PROC_PID = 2,
+    PROC_NAME = 3,
+}
+
+/// Returns the segment that reports the generation of the ruleset.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/netfilter/nf_tables_api.c>
+fn gen_segment(request_header: &CMsgSegHdr, generation: u32) -> NfnlSegment {
+    let current = current_thread!();
+    let posix_thread = current.as_posix_thread().unwrap();
+    let proc_name = posix_thread
+        .thread_name()
+        .lock()
+        .name()
+        .to_bytes_with_nul()
+        .to_vec();
+
+    let attrs = vec![
+        NfAttr::new_be32(GenAttrClass::ID as u16, generation),
+        NfAttr::new_be32(GenAttrClass::PROC_PID as u16, current!().pid()),
+        NfAttr::new(GenAttrClass::PROC_NAME as u16, proc_name),
+    ];
+
+    new_segment(
+        request_header,
+        NftMsgType::NEWGEN,
+        NFPROTO_UNSPEC,
+        generation,
+        attrs,
+    )
+}
+
+/// Batch attributes, which are in the batch begin segment.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/netfilter/nfnetlink.h>
+#[expect(non_camel_case_types)]
+#[expect(clippy::upper_case_acronyms)]
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+enum BatchAttrClass {
+    UNSPEC = 0,
+    GENID = 1,
+}
+
+/// A batch of requests that modify the ruleset.
+pub(super) struct Batch<'a> {
+    transaction: Transaction<'a>,
+    /// The chains added in the batch, which can be referred to by their IDs.
+    chain_ids: Vec<ChainId>,
+    /// The notifications to send after the batch is committed.
+    notifications: Vec<Notification>,
+}
+
+struct ChainId {
+    family: u8,
+    table: String,
+    id: u32,
+    chain: String,
+}
+
+struct Notification {
+    segment: NfnlSegment,
+    is_echo: bool,
+}
+
+impl<'a> Batch<'a> {
+    /// Begins a batch as requested by the batch begin segment.
+    fn begin(netfilter: &'a Netfilter, begin: &NfSegment) -> Result<Self> {
+        // Like Linux, old versions of `nft` that use the host byte order are also accepted.
+        let res_id = begin.body().res_id;
+        if res_id != NFNL_SUBSYS_NFTABLES && res_id.swap_bytes() != NFNL_SUBSYS_NFTABLES {
+            return_errno_with_message!(
+                Errno::EOPNOTSUPP,
+                "batches of the netfilter subsystem are not supported"
+            );
+        }
+
+        let transaction = netfilter.begin();
+
+        // A zero generation ID skips the check.
+        if let Some(attr) = find_attr(begin.attrs(), BatchAttrClass::GENID as u16) {
+            let generation = attr.as_be32()?;
+            if generation != 0 && generation != transaction.generation() {
+                return_errno_with_message!(Errno::ERESTART, "the ruleset has been changed");
+            }
+        }
+
+        Ok(Self {
+            transaction,
+            chain_ids: Vec::new(),
+            notifications: Vec::new(),
+        })
+    }
+
+    /// Applies a request in the batch.
+    fn apply(&mut self, request: &NfSegment) -> Result<()> {
+        match NftMsgType::from_seg_type(request.header().type_)? {
+            NftMsgType::NEWTABLE => table::do_new_table(request, self),
+            NftMsgType::DELTABLE => table::do_del_table(request, self, false),
+            NftMsgType::DESTROYTABLE => table::do_del_table(request, self, true),
+            NftMsgType::NEWCHAIN => chain::do_new_chain(request, self),
+            NftMsgType::DELCHAIN => chain::do_del_chain(request, self, false),
+            NftMsgType::DESTROYCHAIN => chain::do_del_chain(request, self, true),
+            NftMsgType::NEWRULE => rule::do_new_rule(request, self),
+            NftMsgType::DELRULE => rule::do_del_rule(request, self, false),
+            NftMsgType::DESTROYRULE => rule::do_del_rule(request, self, true),
+            NftMsgType::NEWSET
+            | NftMsgType::DELSET
+            | NftMsgType::DESTROYSET
+            | NftMsgType::NEWSETELEM
+            | NftMsgType::DELSETELEM
+            | NftMsgType::DESTROYSETELEM
+            | NftMsgType::NEWOBJ
+            | NftMsgType::DELOBJ
+            | NftMsgType::DESTROYOBJ
+            | NftMsgType::NEWFLOWTABLE
+            | NftMsgType::DELFLOWTABLE
+            | NftMsgType::DESTROYFLOWTABLE => return_errno_with_message!(
+                Errno::EOPNOTSUPP,
+                "sets, stateful objects, and flowtables are not supported"
+            ),
+            _ => return_errno_with_message!(Errno::EINVAL, "the request cannot be sent in a batch"),
+        }
+    }
+
+    /// Commits the batch and sends the notifications.
+    fn commit(self, net_ns: &NetNamespace, begin_header: &CMsgSegHdr, dst_port: PortNum) {
+        let old_generation = self.transaction.generation();
+        let generation = self.transaction.commit();
+        if generation == old_generation {
+            return;
+        }
+
+        for mut notification in self.notifications {
+            if let NfnlSegment::Nftables(segment) = &mut notification.segment {
+                segment.body_mut().res_id = generation as u16;
+            }
+            let echo_port = notification.is_echo.then_some(dst_port);
+            notify(net_ns, notification.segment, echo_port);
+        }
+
+        let echo_port = needs_echo(begin_header).then_some(dst_port);
+        notify(net_ns, gen_segment(begin_header, generation), echo_port);
+    }
+
+    pub(super) fn transaction(&self) -> &Transaction<'a> {
+        &self.transaction
+    }
+
+    pub(super) fn transaction_mut(&mut self) -> &mut Transaction<'a> {
+        &mut self.transaction
+    }
+
+    /// Records a chain added in the batch with its ID.
+    pub(super) fn add_chain_id(&mut self, family: u8, table: &str, id: u32, chain: &str) {
+        self.chain_ids.push(ChainId {
+            family,
+            table: table.to_string(),
+            id,
+            chain: chain.to_string(),
+        });
+    }
+
+    /// Finds the chain added in the batch by its ID.
+    pub(super) fn chain_by_id(&self, family: u8, table: &str, id: u32) -> Result<&str> {
+        self.chain_ids
+            .iter()
+            .rev()
+            .find(|chain_id| {
+                chain_id.family == family && chain_id.table == table && chain_id.id == id
+            })
+            .map(|chain_id| chain_id.chain.as_str())
+            .ok_or_else(|| Error::with_message(Errno::ENOENT, "the chain does not exist"))
+    }
+
+    /// Queues a notification that is sent after the batch is committed.
+    ///
+    /// The resource ID in the segment is replaced with the new generation.
+    pub(super) fn push_notification(&mut self, request_header: &CMsgSegHdr, segment: NfnlSegment) {
+        self.notifications.push(Notification {
+            segment,
+            is_echo: needs_echo(request_header),
+        });
+    }
+}
+
+/// Checks whether the current thread is allowed to make the requests.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/netfilter/nfnetlink.c>
+fn check_permission(net_ns: &NetNamespace) -> Result<()> {
+    let current = current_thread!();
+    net_ns.check_cap(CapSet::NET_ADMIN, current.as_posix_thread().unwrap())
+}
+
+/// The kernel socket of `NETLINK_NETFILTER`.
+///
+/// The ruleset is kept in `NetNamespace::netfilter` of the requesting socket, and a batch of
+/// changes lives only while the batch is being handled. So one kernel socket serves all network
+/// namespaces.
+static NETLINK_NETFILTER_KERNEL: NetlinkNetfilterKernelSocket = NetlinkNetfilterKernelSocket::new();
+
+pub(super) fn get_netlink_netfilter_kernel() -> &'static NetlinkNetfilterKernelSocket {
+    &NETLINK_NETFILTER_KERNEL
+}
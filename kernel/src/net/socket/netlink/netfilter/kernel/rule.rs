@@ -0,0 +1,279 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Handle rule-related requests.
+
+use super::{
+    Batch,
+    expr::{ExprContext, exprs_to_attr, parse_exprs},
+    util::{finish_dump, is_dump, new_segment, parse_name, require_attr},
+};
+use crate::{
+    net::{
+        netfilter::{Chain, NFPROTO_UNSPEC, Netfilter, Rule, RulePosition, Table},
+        socket::netlink::{
+            message::{CMsgSegHdr, NewRequestFlags, ProtocolSegment},
+            netfilter::message::{NfAttr, NfSegment, NfnlSegment, NftMsgType, find_attr},
+        },
+    },
+    prelude::*,
+};
+
+/// Rule attributes.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/netfilter/nf_tables.h>
+#[expect(non_camel_case_types)]
+#[expect(clippy::upper_case_acronyms)]
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+enum RuleAttrClass {
+    UNSPEC = 0,
+    TABLE = 1,
+    CHAIN = 2,
+    HANDLE = 3,
+    EXPRESSIONS = 4,
+    COMPAT = 5,
+    POSITION = 6,
+    USERDATA = 7,
+    PAD = 8,
+    ID = 9,
+    POSITION_ID = 10,
+    CHAIN_ID = 11,
+}
+
+pub(super) fn do_new_rule(request: &NfSegment, batch: &mut Batch) -> Result<()> {
+    let attrs = request.attrs();
+    let family = request.body().family;
+    let table_name = parse_name(require_attr(attrs, RuleAttrClass::TABLE as u16)?)?;
+    let table = batch.transaction().ruleset().table(family, table_name)?;
+
+    let chain_name = if let Some(attr) = find_attr(attrs, RuleAttrClass::CHAIN as u16) {
+        table.chain(parse_name(attr)?)?.name().to_string()
+    } else if let Some(attr) = find_attr(attrs, RuleAttrClass::CHAIN_ID as u16) {
+        batch
+            .chain_by_id(family, table_name, attr.as_be32()?)?
+            .to_string()
+    } else {
+        return_errno_with_message!(Errno::EINVAL, "the chain is not specified");
+    };
+    let chain = table.chain(&chain_name)?;
+
+    let request_flags = NewRequestFlags::from_bits_truncate(request.header().flags);
+
+    let position = if let Some(attr) = find_attr(attrs, RuleAttrClass::HANDLE as u16) {
+        let handle = chain.rule(attr.as_be64()?)?.handle();
+        if request_flags.contains(NewRequestFlags::EXCL) {
+            return_errno_with_message!(Errno::EEXIST, "the rule already exists");
+        }
+        if !request_flags.contains(NewRequestFlags::REPLACE) {
+            return_errno_with_message!(Errno::EOPNOTSUPP, "the rule can only be replaced");
+        }
+        RulePosition::Replace(handle)
+    } else {
+        if !request_flags.contains(NewRequestFlags::CREATE)
+            || request_flags.contains(NewRequestFlags::REPLACE)
+        {
+            return_errno_with_message!(Errno::EINVAL, "the rule must be created");
+        }
+        if find_attr(attrs, RuleAttrClass::POSITION_ID as u16).is_some() {
+            return_errno_with_message!(
+                Errno::EOPNOTSUPP,
+                "referring to rules by their IDs is not supported"
+            );
+        }
+        let pos = find_attr(attrs, RuleAttrClass::POSITION as u16)
+            .map(|attr| attr.as_be64())
+            .transpose()?;
+        if request_flags.contains(NewRequestFlags::APPEND) {
+            RulePosition::After(pos)
+        } else {
+            RulePosition::Before(pos)
+        }
+    };
+
+    let exprs = match find_attr(attrs, RuleAttrClass::EXPRESSIONS as u16) {
+        Some(attr) => {
+            let chain_by_id = |id| {
+                batch
+                    .chain_by_id(family, table_name, id)
+                    .map(str::to_string)
+            };
+            let ctx = ExprContext {
+                family,
+                chain_by_id: &chain_by_id,
+            };
+            parse_exprs(attr, &ctx)?
+        }
+        None => Vec::new(),
+    };
+    let userdata =
+        find_attr(attrs, RuleAttrClass::USERDATA as u16).map(|attr| attr.payload().to_vec());
+
+    let table = batch
+        .transaction_mut()
+        .ruleset_mut()
+        .table_mut(family, table_name)?;
+    let rule = table.add_rule(&chain_name, exprs, userdata, position)?;
+    let chain = table.chain(&chain_name)?;
+    let segment = rule_to_segment(
+        request.header(),
+        NftMsgType::NEWRULE,
+        table,
+        chain,
+        &rule,
+        0,
+    );
+    batch.push_notification(request.header(), segment);
+
+    Ok(())
+}
+
+/// Deletes a rule, or all the rules in a chain or in a table if the rule is not specified.
+///
+/// If `is_destroy` is true, it is not an error if the rule does not exist.
+pub(super) fn do_del_rule(request: &NfSegment, batch: &mut Batch, is_destroy: bool) -> Result<()> {
+    let attrs = request.attrs();
+    let family = request.body().family;
+    let table_name = parse_name(require_attr(attrs, RuleAttrClass::TABLE as u16)?)?;
+    let table = batch.transaction().ruleset().table(family, table_name)?;
+
+    if find_attr(attrs, RuleAttrClass::ID as u16).is_some() {
+        return_errno_with_message!(
+            Errno::EOPNOTSUPP,
+            "referring to rules by their IDs is not supported"
+        );
+    }
+
+    let chain_names = match find_attr(attrs, RuleAttrClass::CHAIN as u16) {
+        Some(attr) => match table.chain(parse_name(attr)?) {
+            Ok(chain) => vec![chain.name().to_string()],
+            Err(error) if is_destroy && error.error() == Errno::ENOENT => return Ok(()),
+            Err(error) => return Err(error),
+        },
+        None if find_attr(attrs, RuleAttrClass::HANDLE as u16).is_some() => {
+            return_errno_with_message!(Errno::EINVAL, "the chain is not specified");
+        }
+        None => table
+            .chains()
+            .iter()
+            .map(|chain| chain.name().to_string())
+            .collect(),
+    };
+
+    let header = request.header();
+    let table = batch
+        .transaction_mut()
+        .ruleset_mut()
+        .table_mut(family, table_name)?;
+
+    let mut segments = Vec::new();
+    for chain_name in chain_names.iter() {
+        let rules = if let Some(attr) = find_attr(attrs, RuleAttrClass::HANDLE as u16) {
+            match table.remove_rule(chain_name, attr.as_be64()?) {
+                Ok(rule) => vec![rule],
+                Err(error) if is_destroy && error.error() == Errno::ENOENT => Vec::new(),
+                Err(error) => return Err(error),
+            }
+        } else {
+            table.flush_chain(chain_name)?
+        };
+
+        let chain = table.chain(chain_name)?;
+        segments.extend(
+            rules
+                .iter()
+                .map(|rule| rule_to_segment(header, NftMsgType::DELRULE, table, chain, rule, 0)),
+        );
+    }
+    for segment in segments {
+        batch.push_notification(header, segment);
+    }
+
+    Ok(())
+}
+
+pub(super) fn do_get_rule(request: &NfSegment, netfilter: &Netfilter) -> Result<Vec<NfnlSegment>> {
+    let header = request.header();
+    let attrs = request.attrs();
+    let family = request.body().family;
+    let generation = netfilter.generation();
+    let ruleset = netfilter.ruleset();
+
+    if !is_dump(header) {
+        let table_name = parse_name(require_attr(attrs, RuleAttrClass::TABLE as u16)?)?;
+        let chain_name = parse_name(require_attr(attrs, RuleAttrClass::CHAIN as u16)?)?;
+        let handle = require_attr(attrs, RuleAttrClass::HANDLE as u16)?.as_be64()?;
+        let table = ruleset.table(family, table_name)?;
+        let chain = table.chain(chain_name)?;
+        let rule = chain.rule(handle)?;
+        return Ok(vec![rule_to_segment(
+            header,
+            NftMsgType::NEWRULE,
+            table,
+            chain,
+            rule,
+            generation,
+        )]);
+    }
+
+    let table_name = find_attr(attrs, RuleAttrClass::TABLE as u16)
+        .map(parse_name)
+        .transpose()?;
+    let chain_name = find_attr(attrs, RuleAttrClass::CHAIN as u16)
+        .map(parse_name)
+        .transpose()?;
+
+    let mut response_segments = Vec::new();
+    for table in ruleset.tables() {
+        if family != NFPROTO_UNSPEC && table.family() != family {
+            continue;
+        }
+        if table_name.is_some_and(|name| table.name() != name) {
+            continue;
+        }
+        for chain in table.chains() {
+            if chain_name.is_some_and(|name| chain.name() != name) {
+                continue;
+            }
+            for rule in chain.rules() {
+                response_segments.push(rule_to_segment(
+                    header,
+                    NftMsgType::NEWRULE,
+                    table,
+                    chain,
+                    rule,
+                    generation,
+                ));
+            }
+        }
+    }
+    finish_dump(header, &mut response_segments);
+
+    Ok(response_segments)
+}
+
+/// Describes the rule in a segment.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/netfilter/nf_tables_api.c>
+pub(super) fn rule_to_segment(
+    request_header: &CMsgSegHdr,
+    type_: NftMsgType,
+    table: &Table,
+    chain: &Chain,
+    rule: &Rule,
+    generation: u32,
+) -> NfnlSegment {
+    let mut attrs = vec![
+        NfAttr::new_str(RuleAttrClass::TABLE as u16, table.name()),
+        NfAttr::new_str(RuleAttrClass::CHAIN as u16, chain.name()),
+        NfAttr::new_be64(RuleAttrClass::HANDLE as u16, rule.handle()),
+        exprs_to_attr(RuleAttrClass::EXPRESSIONS as u16, rule.exprs()),
+    ];
+    if let Some(userdata) = rule.userdata() {
+        attrs.push(NfAttr::new(
+            RuleAttrClass::USERDATA as u16,
+            userdata.to_vec(),
+        ));
+    }
+
+    new_segment(request_header, type_, table.family(), generation, attrs)
+}
@@ -0,0 +1,230 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Handle table-related requests.
+
+use super::{
+    Batch,
+    chain::chain_to_segment,
+    rule::rule_to_segment,
+    util::{finish_dump, is_dump, new_segment, parse_name, require_attr},
+};
+use crate::{
+    net::{
+        netfilter::{NFPROTO_UNSPEC, Netfilter, Table, TableFlags},
+        socket::netlink::{
+            message::{CMsgSegHdr, DeleteRequestFlags, NewRequestFlags, ProtocolSegment},
+            netfilter::message::{NfAttr, NfSegment, NfnlSegment, NftMsgType, find_attr},
+        },
+    },
+    prelude::*,
+};
+
+/// Table attributes.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/netfilter/nf_tables.h>
+#[expect(non_camel_case_types)]
+#[expect(clippy::upper_case_acronyms)]
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+enum TableAttrClass {
+    UNSPEC = 0,
+    NAME = 1,
+    FLAGS = 2,
+    USE = 3,
+    HANDLE = 4,
+    PAD = 5,
+    USERDATA = 6,
+    OWNER = 7,
+}
+
+pub(super) fn do_new_table(request: &NfSegment, batch: &mut Batch) -> Result<()> {
+    let attrs = request.attrs();
+    let family = request.body().family;
+    let name = parse_name(require_attr(attrs, TableAttrClass::NAME as u16)?)?;
+
+    let flags = match find_attr(attrs, TableAttrClass::FLAGS as u16) {
+        Some(attr) => TableFlags::from_bits(attr.as_be32()?).ok_or_else(|| {
+            Error::with_message(Errno::EOPNOTSUPP, "the table flags are not supported")
+        })?,
+        None => TableFlags::empty(),
+    };
+    let userdata = find_attr(attrs, TableAttrClass::USERDATA as u16).map(|attr| attr.payload());
+
+    let request_flags = NewRequestFlags::from_bits_truncate(request.header().flags);
+
+    if let Ok(table) = batch.transaction().ruleset().table(family, name) {
+        if request_flags.contains(NewRequestFlags::EXCL) {
+            return_errno_with_message!(Errno::EEXIST, "the table already exists");
+        }
+        if request_flags.contains(NewRequestFlags::REPLACE) {
+            return_errno_with_message!(Errno::EOPNOTSUPP, "tables cannot be replaced");
+        }
+        if table.flags() == flags {
+            return Ok(());
+        }
+
+        let table = batch
+            .transaction_mut()
+            .ruleset_mut()
+            .table_mut(family, name)?;
+        table.set_flags(flags)?;
+        let segment = table_to_segment(request.header(), NftMsgType::NEWTABLE, table, 0);
+        batch.push_notification(request.header(), segment);
+        return Ok(());
+    }
+
+    let table = batch.transaction_mut().ruleset_mut().add_table(
+        family,
+        name.to_string(),
+        flags,
+        userdata.map(|userdata| userdata.to_vec()),
+    )?;
+    let segment = table_to_segment(request.header(), NftMsgType::NEWTABLE, table, 0);
+    batch.push_notification(request.header(), segment);
+
+    Ok(())
+}
+
+/// Deletes a table, or all the tables if neither the name nor the handle is specified.
+///
+/// If `is_destroy` is true, it is not an error if the table does not exist.
+pub(super) fn do_del_table(request: &NfSegment, batch: &mut Batch, is_destroy: bool) -> Result<()> {
+    let attrs = request.attrs();
+    let family = request.body().family;
+    let name_attr = find_attr(attrs, TableAttrClass::NAME as u16);
+    let handle_attr = find_attr(attrs, TableAttrClass::HANDLE as u16);
+
+    // Like Linux, all the tables of the family are flushed if the table is not specified.
+    if family == NFPROTO_UNSPEC || (name_attr.is_none() && handle_attr.is_none()) {
+        let name = name_attr.map(parse_name).transpose()?;
+        let targets = batch
+            .transaction()
+            .ruleset()
+            .tables()
+            .iter()
+            .filter(|table| family == NFPROTO_UNSPEC || table.family() == family)
+            .filter(|table| name.is_none_or(|name| table.name() == name))
+            .map(|table| (table.family(), table.name().to_string()))
+            .collect::<Vec<_>>();
+
+        for (family, name) in targets {
+            delete_table(request.header(), batch, family, &name)?;
+        }
+        return Ok(());
+    }
+
+    let ruleset = batch.transaction().ruleset();
+    let result = if let Some(attr) = handle_attr {
+        let handle = attr.as_be64()?;
+        ruleset
+            .tables()
+            .iter()
+            .find(|table| table.family() == family && table.handle() == handle)
+            .ok_or_else(|| Error::with_message(Errno::ENOENT, "the table does not exist"))
+    } else {
+        ruleset.table(family, parse_name(name_attr.unwrap())?)
+    };
+    let table = match result {
+        Ok(table) => table,
+        Err(error) if is_destroy && error.error() == Errno::ENOENT => return Ok(()),
+        Err(error) => return Err(error),
+    };
+
+    let request_flags = DeleteRequestFlags::from_bits_truncate(request.header().flags);
+    if request_flags.contains(DeleteRequestFlags::NONREC) && table.use_count() > 0 {
+        return_errno_with_message!(Errno::EBUSY, "the table is not empty");
+    }
+
+    let name = table.name().to_string();
+    delete_table(request.header(), batch, family, &name)
+}
+
+fn delete_table(
+    request_header: &CMsgSegHdr,
+    batch: &mut Batch,
+    family: u8,
+    name: &str,
+) -> Result<()> {
+    let table = batch
+        .transaction_mut()
+        .ruleset_mut()
+        .remove_table(family, name)?;
+
+    // Like Linux, the deletion of the objects in the table is notified first.
+    for chain in table.chains() {
+        for rule in chain.rules() {
+            let segment =
+                rule_to_segment(request_header, NftMsgType::DELRULE, &table, chain, rule, 0);
+            batch.push_notification(request_header, segment);
+        }
+        let segment = chain_to_segment(request_header, NftMsgType::DELCHAIN, &table, chain, 0);
+        batch.push_notification(request_header, segment);
+    }
+    let segment = table_to_segment(request_header, NftMsgType::DELTABLE, &table, 0);
+    batch.push_notification(request_header, segment);
+
+    Ok(())
+}
+
+pub(super) fn do_get_table(request: &NfSegment, netfilter: &Netfilter) -> Result<Vec<NfnlSegment>> {
+    let header = request.header();
+    let family = request.body().family;
+    let generation = netfilter.generation();
+    let ruleset = netfilter.ruleset();
+
+    if !is_dump(header) {
+        let name = parse_name(require_attr(request.attrs(), TableAttrClass::NAME as u16)?)?;
+        let table = ruleset.table(family, name)?;
+        return Ok(vec![table_to_segment(
+            header,
+            NftMsgType::NEWTABLE,
+            table,
+            generation,
+        )]);
+    }
+
+    let mut response_segments = ruleset
+        .tables()
+        .iter()
+        .filter(|table| family == NFPROTO_UNSPEC || table.family() == family)
+        .map(|table| table_to_segment(header, NftMsgType::NEWTABLE, table, generation))
+        .collect::<Vec<_>>();
+    finish_dump(header, &mut response_segments);
+
+    Ok(response_segments)
+}
+
+/// Describes the table in a segment.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/netfilter/nf_tables_api.c>
+fn table_to_segment(
+    request_header: &CMsgSegHdr,
+    type_: NftMsgType,
+    table: &Table,
+    generation: u32,
+) -> NfnlSegment {
+    let mut attrs = vec![
+        NfAttr::new_str(TableAttrClass::NAME as u16, table.name()),
+        NfAttr::new_be64(TableAttrClass::HANDLE as u16, table.handle()),
+    ];
+
+    // Like Linux, the deletion events only identify the table.
+    if type_ != NftMsgType::DELTABLE {
+        attrs.push(NfAttr::new_be32(
+            TableAttrClass::FLAGS as u16,
+            table.flags().bits(),
+        ));
+        attrs.push(NfAttr::new_be32(
+            TableAttrClass::USE as u16,
+            table.use_count(),
+        ));
+        if let Some(userdata) = table.userdata() {
+            attrs.push(NfAttr::new(
+                TableAttrClass::USERDATA as u16,
+                userdata.to_vec(),
+            ));
+        }
+    }
+
+    new_segment(request_header, type_, table.family(), generation, attrs)
+}
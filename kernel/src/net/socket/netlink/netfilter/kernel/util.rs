@@ -0,0 +1,133 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::{
+    net::{
+        net_ns::NetNamespace,
+        socket::netlink::{
+            GroupIdSet,
+            addr::PortNum,
+            message::{
+                CMsgSegHdr, DoneSegment, ErrorSegment, GetRequestFlags, ProtocolSegment,
+                SegHdrCommonFlags,
+            },
+            netfilter::message::{
+                NFNETLINK_V0, NfAttr, NfGenMsgBody, NfSegment, NfnlMessage, NfnlSegment,
+                NftMsgType, find_attr,
+            },
+            table::{NetlinkNetfilterProtocol, SupportedNetlinkProtocol},
+        },
+    },
+    prelude::*,
+};
+
+/// The maximum length of table, chain, and expression names, excluding the trailing null byte.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/netfilter/nf_tables.h>
+const NFT_NAME_MAXLEN: usize = 256 - 1;
+
+/// The multicast group of the notifications of nftables.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/netfilter/nfnetlink.h>
+const NFNLGRP_NFTABLES: u32 = 7;
+
+/// Returns the attribute of the class, or fails with `EINVAL` if it is missing.
+pub(super) fn require_attr(attrs: &[NfAttr], class: u16) -> Result<&NfAttr> {
+    find_attr(attrs, class)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "a required attribute is missing"))
+}
+
+/// Parses the name of a table, a chain, or an expression.
+pub(super) fn parse_name(attr: &NfAttr) -> Result<&str> {
+    attr.as_str(NFT_NAME_MAXLEN)
+}
+
+/// Returns whether the GET request asks for all the objects.
+pub(super) fn is_dump(request_header: &CMsgSegHdr) -> bool {
+    let flags = GetRequestFlags::from_bits_truncate(request_header.flags);
+    flags.contains(GetRequestFlags::DUMP)
+}
+
+/// Creates a response segment to the request.
+///
+/// Like Linux, the resource ID in the body is the lower 16 bits of the generation.
+pub(super) fn new_segment(
+    request_header: &CMsgSegHdr,
+    type_: NftMsgType,
+    family: u8,
+    generation: u32,
+    attrs: Vec<NfAttr>,
+) -> NfnlSegment {
+    let header = CMsgSegHdr {
+        len: 0,
+        type_: type_.to_seg_type(),
+        flags: SegHdrCommonFlags::empty().bits(),
+        seq: request_header.seq,
+        pid: request_header.pid,
+    };
+    let body = NfGenMsgBody {
+        family,
+        version: NFNETLINK_V0,
+        res_id: generation as u16,
+    };
+
+    NfnlSegment::Nftables(NfSegment::new(header, body, attrs))
+}
+
+/// Finishes a response message to a dump request.
+pub(super) fn finish_dump(request_header: &CMsgSegHdr, response_segments: &mut Vec<NfnlSegment>) {
+    let done_segment = DoneSegment::new_from_request(request_header, None);
+    response_segments.push(NfnlSegment::Done(done_segment));
+
+    for segment in response_segments.iter_mut() {
+        let header = segment.header_mut();
+        let mut flags = SegHdrCommonFlags::from_bits_truncate(header.flags);
+        flags |= SegHdrCommonFlags::MULTI;
+        header.flags = flags.bits();
+    }
+}
+
+/// Returns the ACK segment to a successful request.
+pub(super) fn ack_segment(request_header: &CMsgSegHdr) -> NfnlSegment {
+    NfnlSegment::Error(ErrorSegment::new_from_request(request_header, None))
+}
+
+/// Returns whether the request asks for an ACK segment.
+pub(super) fn needs_ack(request_header: &CMsgSegHdr) -> bool {
+    let flags = SegHdrCommonFlags::from_bits_truncate(request_header.flags);
+    flags.contains(SegHdrCommonFlags::ACK)
+}
+
+/// Returns whether the request asks for the notifications to be echoed.
+pub(super) fn needs_echo(request_header: &CMsgSegHdr) -> bool {
+    let flags = SegHdrCommonFlags::from_bits_truncate(request_header.flags);
+    flags.contains(SegHdrCommonFlags::ECHO)
+}
+
+/// Sends a unicast message to the socket bound to the port.
+pub(super) fn unicast(net_ns: &NetNamespace, dst_port: PortNum, segments: Vec<NfnlSegment>) {
+    if segments.is_empty() {
+        return;
+    }
+
+    let message = NfnlMessage::new(segments);
+
+    debug!("netlink netfilter response: {:?}", message);
+
+    NetlinkNetfilterProtocol::unicast(net_ns, dst_port, message).unwrap();
+}
+
+/// Notifies the sockets in the multicast group of a change.
+///
+/// If `echo_port` is specified, the notification is also sent to the socket that makes the
+/// change.
+pub(super) fn notify(net_ns: &NetNamespace, segment: NfnlSegment, echo_port: Option<PortNum>) {
+    let groups = GroupIdSet::new(1 << (NFNLGRP_NFTABLES - 1));
+    let message = NfnlMessage::new(vec![segment]);
+
+    debug!("netlink netfilter notification: {:?}", message);
+
+    if let Some(port) = echo_port {
+        NetlinkNetfilterProtocol::unicast(net_ns, port, message.clone()).unwrap();
+    }
+    NetlinkNetfilterProtocol::multicast(net_ns, groups, message).unwrap();
+}
@@ -0,0 +1,137 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Netlink netfilter attributes.
+//!
+//! The nftables subsystem uses deeply nested attributes whose classes depend on the enclosing
+//! attributes (e.g., the attributes of an expression depend on the name of the expression).
+//! Therefore, the attributes are kept in their raw form, and are interpreted by the handlers of
+//! the requests.
+
+use crate::{
+    net::socket::netlink::message::{
+        Attribute, CAttrHeader, ContinueRead, encode_nested_attrs, parse_nested_attrs, read_payload,
+    },
+    prelude::*,
+    util::MultiRead,
+};
+
+/// The flag in the attribute type that indicates the payload contains nested attributes.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/netlink.h>
+const NLA_F_NESTED: u16 = 1 << 15;
+
+/// A raw netlink netfilter attribute.
+#[derive(Clone, Debug)]
+pub struct NfAttr {
+    type_: u16,
+    payload: Vec<u8>,
+}
+
+impl NfAttr {
+    pub fn new(type_: u16, payload: Vec<u8>) -> Self {
+        Self { type_, payload }
+    }
+
+    /// Creates an attribute that contains a 32-bit integer in network byte order.
+    pub fn new_be32(type_: u16, value: u32) -> Self {
+        Self::new(type_, value.to_be_bytes().to_vec())
+    }
+
+    /// Creates an attribute that contains a 64-bit integer in network byte order.
+    pub fn new_be64(type_: u16, value: u64) -> Self {
+        Self::new(type_, value.to_be_bytes().to_vec())
+    }
+
+    /// Creates an attribute that contains a null-terminated string.
+    pub fn new_str(type_: u16, value: &str) -> Self {
+        let mut payload = Vec::with_capacity(value.len() + 1);
+        payload.extend_from_slice(value.as_bytes());
+        payload.push(0);
+        Self::new(type_, payload)
+    }
+
+    /// Creates an attribute that contains nested attributes.
+    pub fn new_nested(type_: u16, attrs: &[NfAttr]) -> Self {
+        Self::new(type_ | NLA_F_NESTED, encode_nested_attrs(attrs))
+    }
+
+    /// Returns the class of the attribute, without the flags.
+    pub fn class(&self) -> u16 {
+        self.type_ & !NLA_F_NESTED
+    }
+
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    /// Interprets the payload as a 32-bit integer in network byte order.
+    pub fn as_be32(&self) -> Result<u32> {
+        let bytes = self.payload.as_slice().try_into().map_err(|_| {
+            Error::with_message(Errno::EINVAL, "the attribute is not a 32-bit integer")
+        })?;
+        Ok(u32::from_be_bytes(bytes))
+    }
+
+    /// Interprets the payload as a 64-bit integer in network byte order.
+    pub fn as_be64(&self) -> Result<u64> {
+        let bytes = self.payload.as_slice().try_into().map_err(|_| {
+            Error::with_message(Errno::EINVAL, "the attribute is not a 64-bit integer")
+        })?;
+        Ok(u64::from_be_bytes(bytes))
+    }
+
+    /// Interprets the payload as an 8-bit integer.
+    pub fn as_u8(&self) -> Result<u8> {
+        match self.payload.as_slice() {
+            [value] => Ok(*value),
+            _ => return_errno_with_message!(Errno::EINVAL, "the attribute is not an 8-bit integer"),
+        }
+    }
+
+    /// Interprets the payload as a string.
+    ///
+    /// Like Linux, the string ends at the first null byte, if any. It fails with `ERANGE` if the
+    /// string is longer than `max_len`.
+    pub fn as_str(&self, max_len: usize) -> Result<&str> {
+        let bytes = match self.payload.iter().position(|byte| *byte == 0) {
+            Some(len) => &self.payload[..len],
+            None => &self.payload,
+        };
+        if bytes.len() > max_len {
+            return_errno_with_message!(Errno::ERANGE, "the string attribute is too long");
+        }
+        core::str::from_utf8(bytes)
+            .map_err(|_| Error::with_message(Errno::EINVAL, "the string attribute is invalid"))
+    }
+
+    /// Parses the payload as nested attributes.
+    pub fn as_nested(&self) -> Result<Vec<NfAttr>> {
+        parse_nested_attrs(&self.payload)
+    }
+}
+
+impl Attribute for NfAttr {
+    fn type_(&self) -> u16 {
+        self.type_
+    }
+
+    fn payload_as_bytes(&self) -> &[u8] {
+        &self.payload
+    }
+
+    fn read_from(header: &CAttrHeader, reader: &mut dyn MultiRead) -> Result<ContinueRead<Self>>
+    where
+        Self: Sized,
+    {
+        let payload = read_payload(reader, header.payload_len())?;
+
+        Ok(ContinueRead::Parsed(Self::new(header.type_(), payload)))
+    }
+}
+
+/// Finds the attribute of the class.
+///
+/// Like Linux, the last one takes effect if the class appears multiple times.
+pub fn find_attr(attrs: &[NfAttr], class: u16) -> Option<&NfAttr> {
+    attrs.iter().rev().find(|attr| attr.class() == class)
+}
@@ -0,0 +1,205 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::attr::NfAttr;
+use crate::{
+    net::socket::netlink::message::{
+        CMsgSegHdr, ContinueRead, DoneSegment, ErrorSegment, ProtocolSegment, SegmentBody,
+        SegmentCommon,
+    },
+    prelude::*,
+    util::{MultiRead, MultiWrite},
+};
+
+/// The netlink netfilter segment, which is the basic unit of a netlink netfilter message.
+#[derive(Clone, Debug)]
+pub enum NfnlSegment {
+    /// The segment that begins a batch.
+    BatchBegin(NfSegment),
+    /// The segment that ends a batch.
+    BatchEnd(NfSegment),
+    /// A segment of the nftables subsystem.
+    Nftables(NfSegment),
+    Done(DoneSegment),
+    Error(ErrorSegment),
+}
+
+pub type NfSegment = SegmentCommon<NfGenMsgBody, NfAttr>;
+
+impl SegmentBody for NfGenMsgBody {
+    type CType = CNfGenMsg;
+}
+
+/// `nfgenmsg` in Linux.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/netfilter/nfnetlink.h>
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub struct CNfGenMsg {
+    /// The address family
+    pub family: u8,
+    /// The version of the nfnetlink protocol
+    pub version: u8,
+    /// The resource ID, in network byte order
+    pub res_id: u16,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct NfGenMsgBody {
+    pub family: u8,
+    pub version: u8,
+    pub res_id: u16,
+}
+
+impl TryFrom<CNfGenMsg> for NfGenMsgBody {
+    type Error = Error;
+
+    fn try_from(value: CNfGenMsg) -> Result<Self> {
+        Ok(Self {
+            family: value.family,
+            version: value.version,
+            res_id: u16::from_be(value.res_id),
+        })
+    }
+}
+
+impl From<NfGenMsgBody> for CNfGenMsg {
+    fn from(value: NfGenMsgBody) -> Self {
+        Self {
+            family: value.family,
+            version: value.version,
+            res_id: value.res_id.to_be(),
+        }
+    }
+}
+
+/// The message type that begins a batch.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/netfilter/nfnetlink.h>
+pub const NFNL_MSG_BATCH_BEGIN: u16 = 16;
+/// The message type that ends a batch.
+pub const NFNL_MSG_BATCH_END: u16 = 17;
+
+/// The ID of the nftables subsystem, which is the upper byte of the message types.
+pub const NFNL_SUBSYS_NFTABLES: u16 = 10;
+
+/// The version of the nfnetlink protocol.
+pub const NFNETLINK_V0: u8 = 0;
+
+/// The message types of the nftables subsystem.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/netfilter/nf_tables.h>
+#[expect(non_camel_case_types)]
+#[expect(clippy::upper_case_acronyms)]
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+pub enum NftMsgType {
+    NEWTABLE = 0,
+    GETTABLE = 1,
+    DELTABLE = 2,
+    NEWCHAIN = 3,
+    GETCHAIN = 4,
+    DELCHAIN = 5,
+    NEWRULE = 6,
+    GETRULE = 7,
+    DELRULE = 8,
+    NEWSET = 9,
+    GETSET = 10,
+    DELSET = 11,
+    NEWSETELEM = 12,
+    GETSETELEM = 13,
+    DELSETELEM = 14,
+    NEWGEN = 15,
+    GETGEN = 16,
+    TRACE = 17,
+    NEWOBJ = 18,
+    GETOBJ = 19,
+    DELOBJ = 20,
+    GETOBJ_RESET = 21,
+    NEWFLOWTABLE = 22,
+    GETFLOWTABLE = 23,
+    DELFLOWTABLE = 24,
+    GETRULE_RESET = 25,
+    DESTROYTABLE = 26,
+    DESTROYCHAIN = 27,
+    DESTROYRULE = 28,
+    DESTROYSET = 29,
+    DESTROYSETELEM = 30,
+    DESTROYOBJ = 31,
+    DESTROYFLOWTABLE = 32,
+    GETSETELEM_RESET = 33,
+}
+
+impl NftMsgType {
+    /// Returns the message type in the segment header.
+    pub fn to_seg_type(self) -> u16 {
+        (NFNL_SUBSYS_NFTABLES << 8) | self as u16
+    }
+
+    /// Parses the message type in the segment header.
+    pub fn from_seg_type(seg_type: u16) -> Result<Self> {
+        if seg_type >> 8 != NFNL_SUBSYS_NFTABLES {
+            return_errno_with_message!(Errno::EINVAL, "the message is not an nftables message");
+        }
+        Ok(Self::try_from(seg_type & 0xff)?)
+    }
+}
+
+impl ProtocolSegment for NfnlSegment {
+    fn header(&self) -> &CMsgSegHdr {
+        match self {
+            Self::BatchBegin(segment) | Self::BatchEnd(segment) | Self::Nftables(segment) => {
+                segment.header()
+            }
+            Self::Done(done_segment) => done_segment.header(),
+            Self::Error(error_segment) => error_segment.header(),
+        }
+    }
+
+    fn header_mut(&mut self) -> &mut CMsgSegHdr {
+        match self {
+            Self::BatchBegin(segment) | Self::BatchEnd(segment) | Self::Nftables(segment) => {
+                segment.header_mut()
+            }
+            Self::Done(done_segment) => done_segment.header_mut(),
+            Self::Error(error_segment) => error_segment.header_mut(),
+        }
+    }
+
+    fn read_from(reader: &mut dyn MultiRead) -> Result<ContinueRead<Self, ErrorSegment>> {
+        let header = reader
+            .read_val_opt::<CMsgSegHdr>()?
+            .ok_or_else(|| Error::with_message(Errno::EINVAL, "the reader length is too small"))?;
+
+        let segment = match header.type_ {
+            NFNL_MSG_BATCH_BEGIN => {
+                NfSegment::read_from(&header, reader)?.map(NfnlSegment::BatchBegin)
+            }
+            NFNL_MSG_BATCH_END => NfSegment::read_from(&header, reader)?.map(NfnlSegment::BatchEnd),
+            type_ if type_ >> 8 == NFNL_SUBSYS_NFTABLES => {
+                NfSegment::read_from(&header, reader)?.map(NfnlSegment::Nftables)
+            }
+            _ => {
+                let payload_len = header.calc_payload_len_with_padding(reader)?;
+                reader.skip_some(payload_len);
+                ContinueRead::skipped_with_error(
+                    Errno::EOPNOTSUPP,
+                    "the netfilter subsystem is not supported",
+                )
+            }
+        };
+
+        Ok(segment.map_err(|error| ErrorSegment::new_from_request(&header, Some(error))))
+    }
+
+    fn write_to(&self, writer: &mut dyn MultiWrite) -> Result<()> {
+        match self {
+            Self::Nftables(segment) => segment.write_to(writer)?,
+            Self::Done(done_segment) => done_segment.write_to(writer)?,
+            Self::Error(error_segment) => error_segment.write_to(writer)?,
+            Self::BatchBegin(_) | Self::BatchEnd(_) => {
+                unreachable!("kernel should not write batch delimiters to user space");
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,16 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Netlink Netfilter Socket.
+//!
+//! Only the nftables subsystem is supported, which allows user space (e.g., `nft`) to configure
+//! the packet filter of the network namespace.
+
+pub(super) use message::NfnlMessage;
+
+use crate::net::socket::netlink::{common::NetlinkSocket, table::NetlinkNetfilterProtocol};
+
+mod bound;
+mod kernel;
+mod message;
+
+pub type NetlinkNetfilterSocket = NetlinkSocket<NetlinkNetfilterProtocol>;
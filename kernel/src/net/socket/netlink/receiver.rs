@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use crate::{events::IoEvents, prelude::*, process::signal::Pollee};
+use crate::{events::IoEvents, prelude::*, process::signal::Pollee, util::MultiWrite};
 
 pub struct MessageReceiver<Message> {
     message_queue: Arc<Mutex<MessageQueue<Message>>>,
@@ -8,11 +8,22 @@ pub struct MessageReceiver<Message> {
 }
 
 pub(super) struct MessageQueue<Message> {
-    messages: VecDeque<Message>,
+    messages: VecDeque<QueuedMessage<Message>>,
     total_length: usize,
     error: Option<Error>,
 }
 
+/// A message in the [`MessageQueue`].
+pub(super) enum QueuedMessage<Message> {
+    /// A message in the format of the netlink protocol.
+    Protocol(Message),
+    /// A message in an arbitrary format.
+    ///
+    /// Such messages are not sent by the netlink protocol, but by other kernel components that use
+    /// netlink sockets for notifications (e.g., POSIX message queues).
+    Raw(Vec<u8>),
+}
+
 impl<Message> MessageQueue<Message> {
     /// Creates a pair of a [`MessageQueue`] and a [`MessageReceiver`].
     pub(super) fn new_pair(pollee: Pollee) -> (Arc<Mutex<Self>>, MessageReceiver<Message>) {
@@ -21,10 +32,7 @@ impl<Message> MessageQueue<Message> {
             total_length: 0,
             error: None,
         }));
-        let receiver = MessageReceiver {
-            message_queue: queue.clone(),
-            pollee,
-        };
+        let receiver = MessageReceiver::new(queue.clone(), pollee);
         (queue, receiver)
     }
 
@@ -46,6 +54,31 @@ impl<Message> MessageQueue<Message> {
 pub trait QueueableMessage {
     /// Counts and returns the length of the message.
     fn total_len(&self) -> usize;
+
+    /// Writes the message to the given `writer`.
+    fn write_to(&self, writer: &mut dyn MultiWrite) -> Result<()>;
+}
+
+impl<Message: QueueableMessage> QueuedMessage<Message> {
+    fn total_len(&self) -> usize {
+        match self {
+            QueuedMessage::Protocol(message) => message.total_len(),
+            QueuedMessage::Raw(bytes) => bytes.len(),
+        }
+    }
+
+    /// Writes the message to the given `writer`.
+    pub(super) fn write_to(&self, writer: &mut dyn MultiWrite) -> Result<()> {
+        match self {
+            QueuedMessage::Protocol(message) => message.write_to(writer),
+            QueuedMessage::Raw(bytes) => {
+                let _nbytes = writer.write(&mut VmReader::from(bytes.as_slice()))?;
+                // `_nbytes` may be smaller than the message size. We ignore it to truncate the
+                // message.
+                Ok(())
+            }
+        }
+    }
 }
 
 impl<Message: QueueableMessage> MessageQueue<Message> {
@@ -58,7 +91,7 @@ impl<Message: QueueableMessage> MessageQueue<Message> {
     /// returned. In this case, the closure will not be executed.
     pub(super) fn dequeue_if<F, R>(&mut self, f: F) -> Result<R>
     where
-        F: FnOnce(&Message, usize) -> Result<(bool, R)>,
+        F: FnOnce(&QueuedMessage<Message>, usize) -> Result<(bool, R)>,
     {
         if let Some(error) = self.error.take() {
             return Err(error);
@@ -80,7 +113,7 @@ impl<Message: QueueableMessage> MessageQueue<Message> {
 
     /// Tries to enqueue a new message. Returns `false` if the buffer is full.
     #[must_use]
-    pub(self) fn enqueue(&mut self, message: Message) -> bool {
+    pub(self) fn enqueue(&mut self, message: QueuedMessage<Message>) -> bool {
         let length = message.total_len();
 
         // Currently, we don't support sending netlink messages between user spaces, so only the
@@ -101,8 +134,21 @@ impl<Message: QueueableMessage> MessageQueue<Message> {
     }
 }
 
+impl<Message> MessageReceiver<Message> {
+    pub(super) fn new(message_queue: Arc<Mutex<MessageQueue<Message>>>, pollee: Pollee) -> Self {
+        Self {
+            message_queue,
+            pollee,
+        }
+    }
+}
+
 impl<Message: QueueableMessage> MessageReceiver<Message> {
     pub(super) fn enqueue_message(&self, message: Message) {
+        self.enqueue(QueuedMessage::Protocol(message));
+    }
+
+    fn enqueue(&self, message: QueuedMessage<Message>) {
         let is_ok = self.message_queue.lock().enqueue(message);
         if is_ok {
             self.pollee.notify(IoEvents::IN);
@@ -112,4 +158,19 @@ impl<Message: QueueableMessage> MessageReceiver<Message> {
     }
 }
 
+/// A receiver of messages in arbitrary formats.
+///
+/// Each netlink socket can receive such messages, regardless of its netlink protocol. See
+/// [`QueuedMessage::Raw`] for details.
+pub trait RawMessageReceiver: Send + Sync {
+    /// Enqueues a message in an arbitrary format.
+    fn enqueue_raw(&self, message: Vec<u8>);
+}
+
+impl<Message: QueueableMessage + Send> RawMessageReceiver for MessageReceiver<Message> {
+    fn enqueue_raw(&self, message: Vec<u8>) {
+        self.enqueue(QueuedMessage::Raw(message));
+    }
+}
+
 pub(in crate::net) const NETLINK_DEFAULT_BUF_SIZE: usize = 65536;
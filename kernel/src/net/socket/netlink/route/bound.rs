@@ -61,6 +61,7 @@ impl datagram_common::Bound for BoundNetlinkRoute {
         let sum_lens = reader.sum_lens();
 
         let local_port = self.handle.port();
+        let net_ns = self.handle.net_ns();
         let rtnl_kernel = get_netlink_route_kernel();
 
         loop {
@@ -70,7 +71,7 @@ impl datagram_common::Bound for BoundNetlinkRoute {
                 // There is at least a valid segment header, so we can create an error segment to
                 // report any errors found while parsing the segment body or attributes.
                 Ok(ContinueRead::SkippedErr(err_segment)) => {
-                    rtnl_kernel.report_error(err_segment, local_port);
+                    rtnl_kernel.report_error(net_ns, err_segment, local_port);
                     continue;
                 }
                 // EFAULT indicates an error occurred while copying data from user space,
@@ -91,7 +92,7 @@ impl datagram_common::Bound for BoundNetlinkRoute {
                 header.pid = local_port;
             }
 
-            rtnl_kernel.handle_request(&segment, local_port);
+            rtnl_kernel.handle_request(net_ns, &segment, local_port);
         }
 
         Ok(sum_lens)
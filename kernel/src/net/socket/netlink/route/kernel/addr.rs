@@ -2,17 +2,20 @@
 
 //! Handle address-related requests.
 
-use alloc::borrow::ToOwned;
 use core::num::NonZeroU32;
 
-use super::util::finish_response;
+use aster_bigtcp::wire::{IpAddress, IpCidr};
+
+use super::util::{RtnlGroup, ack_response, finish_response, notify, response_header};
 use crate::{
     net::{
-        iface::{Iface, iter_all_ifaces},
+        iface::Iface,
+        net_ns::NetNamespace,
         socket::netlink::{
-            message::{CMsgSegHdr, CSegmentType, GetRequestFlags, SegHdrCommonFlags},
+            message::{CMsgSegHdr, CSegmentType, GetRequestFlags, NewRequestFlags},
             route::message::{
-                AddrAttr, AddrMessageFlags, AddrSegment, AddrSegmentBody, RtScope, RtnlSegment,
+                AddrAttr, AddrMessageFlags, AddrSegment, AddrSegmentBody, IpAddrBytes, RtScope,
+                RtnlSegment,
             },
         },
     },
@@ -20,7 +23,10 @@ use crate::{
     util::net::CSocketAddrFamily,
 };
 
-pub(super) fn do_get_addr(request_segment: &AddrSegment) -> Result<Vec<RtnlSegment>> {
+pub(super) fn do_get_addr(
+    request_segment: &AddrSegment,
+    net_ns: &NetNamespace,
+) -> Result<Vec<RtnlSegment>> {
     let dump_all = {
         let flags = GetRequestFlags::from_bits_truncate(request_segment.header().flags);
         flags.contains(GetRequestFlags::DUMP)
@@ -29,9 +35,31 @@ pub(super) fn do_get_addr(request_segment: &AddrSegment) -> Result<Vec<RtnlSegme
         return_errno_with_message!(Errno::EOPNOTSUPP, "GETADDR only supports dump requests");
     }
 
-    let mut response_segments: Vec<RtnlSegment> = iter_all_ifaces()
-        // GETADDR only supports dump mode, so we're going to report all addresses.
-        .filter_map(|iface| iface_to_new_addr(request_segment.header(), iface))
+    let family = request_segment.body().family;
+
+    let mut response_segments: Vec<RtnlSegment> = net_ns
+        .ifaces()
+        .iter()
+        // GETADDR only supports dump mode, so we're going to report all addresses of the
+        // requested family. Unknown families are treated as `AF_UNSPEC`.
+        .flat_map(|iface| {
+            iface
+                .ip_addrs()
+                .iter()
+                .filter(|ip_cidr| match ip_cidr {
+                    IpCidr::Ipv4(_) => family != CSocketAddrFamily::AF_INET6 as i32,
+                    IpCidr::Ipv6(_) => family != CSocketAddrFamily::AF_INET as i32,
+                })
+                .map(|ip_cidr| {
+                    new_addr_segment(
+                        request_segment.header(),
+                        CSegmentType::NEWADDR,
+                        iface,
+                        ip_cidr,
+                    )
+                })
+                .collect::<Vec<_>>()
+        })
         .map(RtnlSegment::NewAddr)
         .collect();
 
@@ -40,30 +68,198 @@ pub(super) fn do_get_addr(request_segment: &AddrSegment) -> Result<Vec<RtnlSegme
     Ok(response_segments)
 }
 
-fn iface_to_new_addr(request_header: &CMsgSegHdr, iface: &Arc<Iface>) -> Option<AddrSegment> {
-    let ipv4_addr = iface.ipv4_addr()?;
+pub(super) fn do_new_addr(
+    request_segment: &AddrSegment,
+    net_ns: &NetNamespace,
+) -> Result<Vec<RtnlSegment>> {
+    let iface = find_iface(request_segment, net_ns)?;
+    let ip_cidr = parse_ip_cidr(request_segment)?;
+
+    let flags = NewRequestFlags::from_bits_truncate(request_segment.header().flags);
+    let add_prefix_route = !addr_flags(request_segment).contains(AddrMessageFlags::NOPREFIXROUTE);
+
+    if iface.ip_addrs().contains(&ip_cidr) {
+        // Reference: <https://elixir.bootlin.com/linux/v6.13/source/net/ipv4/devinet.c#L1003>.
+        if flags.contains(NewRequestFlags::EXCL) || !flags.contains(NewRequestFlags::REPLACE) {
+            return_errno_with_message!(Errno::EEXIST, "the address already exists");
+        }
+        return Ok(ack_response(request_segment.header()));
+    }
+
+    net_ns.add_iface_addr(&iface, ip_cidr, add_prefix_route)?;
+
+    let segment = new_addr_segment(
+        request_segment.header(),
+        CSegmentType::NEWADDR,
+        &iface,
+        &ip_cidr,
+    );
+    notify(net_ns, addr_group(&ip_cidr), RtnlSegment::NewAddr(segment));
+
+    Ok(ack_response(request_segment.header()))
+}
+
+pub(super) fn do_del_addr(
+    request_segment: &AddrSegment,
+    net_ns: &NetNamespace,
+) -> Result<Vec<RtnlSegment>> {
+    let iface = find_iface(request_segment, net_ns)?;
+    let ip_cidr = parse_ip_cidr(request_segment)?;
+
+    net_ns.remove_iface_addr(&iface, &ip_cidr)?;
+
+    let segment = new_addr_segment(
+        request_segment.header(),
+        CSegmentType::DELADDR,
+        &iface,
+        &ip_cidr,
+    );
+    notify(net_ns, addr_group(&ip_cidr), RtnlSegment::DelAddr(segment));
+
+    Ok(ack_response(request_segment.header()))
+}
+
+/// Notifies the sockets in the network namespace that the address is removed from the interface.
+///
+/// This is used for the changes that are not requested via netlink, such as flushing the
+/// addresses of an interface that is moved to another network namespace.
+pub(in crate::net) fn notify_del_addr(net_ns: &NetNamespace, iface: &Arc<Iface>, ip_cidr: &IpCidr) {
+    let segment = new_addr_segment(
+        &CMsgSegHdr::new_zeroed(),
+        CSegmentType::DELADDR,
+        iface,
+        ip_cidr,
+    );
+    notify(net_ns, addr_group(ip_cidr), RtnlSegment::DelAddr(segment));
+}
+
+/// Finds the interface specified by the index in the request.
+fn find_iface(request_segment: &AddrSegment, net_ns: &NetNamespace) -> Result<Arc<Iface>> {
+    let Some(index) = request_segment.body().index else {
+        return_errno_with_message!(Errno::ENODEV, "the interface index is not specified");
+    };
+
+    net_ns
+        .ifaces()
+        .into_iter()
+        .find(|iface| iface.index() == index.get())
+        .ok_or_else(|| Error::with_message(Errno::ENODEV, "the interface does not exist"))
+}
+
+/// Parses the address and the prefix length in the request.
+///
+/// Like Linux, `IFA_LOCAL` is preferred over `IFA_ADDRESS` because they are different only for
+/// point-to-point interfaces, where `IFA_ADDRESS` is the address of the peer.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/net/ipv4/devinet.c#L880>.
+fn parse_ip_cidr(request_segment: &AddrSegment) -> Result<IpCidr> {
+    let body = request_segment.body();
+
+    let mut local = None;
+    let mut address = None;
+    for attr in request_segment.attrs() {
+        match attr {
+            AddrAttr::Local(addr) => local = Some(*addr),
+            AddrAttr::Address(addr) => address = Some(*addr),
+            _ => (),
+        }
+    }
+    let Some(addr) = local.or(address) else {
+        return_errno_with_message!(Errno::EINVAL, "the address is not specified");
+    };
+
+    let (addr, max_prefix_len) = match (body.family, addr) {
+        (family, IpAddrBytes::V4(_)) if family == CSocketAddrFamily::AF_INET as i32 => {
+            (IpAddress::from(addr), 32)
+        }
+        (family, IpAddrBytes::V6(_)) if family == CSocketAddrFamily::AF_INET6 as i32 => {
+            (IpAddress::from(addr), 128)
+        }
+        (family, _)
+            if family == CSocketAddrFamily::AF_INET as i32
+                || family == CSocketAddrFamily::AF_INET6 as i32 =>
+        {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the address does not match the address family"
+            );
+        }
+        _ => return_errno_with_message!(Errno::EOPNOTSUPP, "the address family is not supported"),
+    };
+    if body.prefix_len > max_prefix_len {
+        return_errno_with_message!(Errno::EINVAL, "the prefix length is invalid");
+    }
+
+    Ok(IpCidr::new(addr, body.prefix_len))
+}
+
+/// Returns the address flags in the request.
+///
+/// The `IFA_FLAGS` attribute, if present, takes precedence over the flags in the header, since
+/// the latter can only hold the lower eight bits.
+fn addr_flags(request_segment: &AddrSegment) -> AddrMessageFlags {
+    request_segment
+        .attrs()
+        .iter()
+        .find_map(|attr| {
+            if let AddrAttr::Flags(flags) = attr {
+                Some(AddrMessageFlags::from_bits_truncate(*flags))
+            } else {
+                None
+            }
+        })
+        .unwrap_or(request_segment.body().flags)
+}
 
-    let header = CMsgSegHdr {
-        len: 0,
-        type_: CSegmentType::NEWADDR as _,
-        flags: SegHdrCommonFlags::empty().bits(),
-        seq: request_header.seq,
-        pid: request_header.pid,
+fn addr_group(ip_cidr: &IpCidr) -> RtnlGroup {
+    match ip_cidr {
+        IpCidr::Ipv4(_) => RtnlGroup::IPV4_IFADDR,
+        IpCidr::Ipv6(_) => RtnlGroup::IPV6_IFADDR,
+    }
+}
+
+fn new_addr_segment(
+    request_header: &CMsgSegHdr,
+    type_: CSegmentType,
+    iface: &Arc<Iface>,
+    ip_cidr: &IpCidr,
+) -> AddrSegment {
+    let header = response_header(request_header, type_);
+
+    let (family, scope) = match ip_cidr {
+        IpCidr::Ipv4(ipv4_cidr) if ipv4_cidr.address().is_loopback() => {
+            (CSocketAddrFamily::AF_INET, RtScope::HOST)
+        }
+        IpCidr::Ipv4(_) => (CSocketAddrFamily::AF_INET, RtScope::UNIVERSE),
+        IpCidr::Ipv6(ipv6_cidr) if ipv6_cidr.address().is_loopback() => {
+            (CSocketAddrFamily::AF_INET6, RtScope::HOST)
+        }
+        IpCidr::Ipv6(ipv6_cidr) if ipv6_cidr.address().is_unicast_link_local() => {
+            (CSocketAddrFamily::AF_INET6, RtScope::LINK)
+        }
+        IpCidr::Ipv6(_) => (CSocketAddrFamily::AF_INET6, RtScope::UNIVERSE),
     };
 
     let addr_message = AddrSegmentBody {
-        family: CSocketAddrFamily::AF_INET as _,
-        prefix_len: iface.prefix_len().unwrap(),
+        family: family as _,
+        prefix_len: ip_cidr.prefix_len(),
         flags: AddrMessageFlags::PERMANENT,
-        scope: RtScope::HOST,
+        scope,
         index: NonZeroU32::new(iface.index()),
     };
 
-    let attrs = vec![
-        AddrAttr::Address(ipv4_addr.octets()),
-        AddrAttr::Label(iface.name().to_owned()),
-        AddrAttr::Local(ipv4_addr.octets()),
-    ];
+    let addr = IpAddrBytes::from(ip_cidr.address());
+    let attrs = match ip_cidr {
+        IpCidr::Ipv4(ipv4_cidr) => {
+            let mut attrs = vec![AddrAttr::Address(addr), AddrAttr::Local(addr)];
+            if let Some(broadcast) = ipv4_cidr.broadcast() {
+                attrs.push(AddrAttr::Broadcast(IpAddrBytes::V4(broadcast.octets())));
+            }
+            attrs.push(AddrAttr::Label(iface.name()));
+            attrs
+        }
+        IpCidr::Ipv6(_) => vec![AddrAttr::Address(addr)],
+    };
 
-    Some(AddrSegment::new(header, addr_message, attrs))
+    AddrSegment::new(header, addr_message, attrs)
 }
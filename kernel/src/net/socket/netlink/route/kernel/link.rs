@@ -2,32 +2,44 @@
 
 //! Handle link-related requests.
 
-use alloc::borrow::ToOwned;
 use core::num::NonZero;
 
-use aster_bigtcp::iface::InterfaceType;
+use aster_bigtcp::iface::{InterfaceFlags, InterfaceType};
 
-use super::util::finish_response;
+use super::util::{RtnlGroup, ack_response, finish_response, notify};
 use crate::{
+    fs::{file::InodeHandle, pseudofs::NsFile},
     net::{
-        iface::{Iface, iter_all_ifaces},
+        iface::{self, Iface, LinkKind},
+        net_ns::NetNamespace,
         socket::netlink::{
-            message::{CMsgSegHdr, CSegmentType, GetRequestFlags, SegHdrCommonFlags},
-            route::message::{LinkAttr, LinkSegment, LinkSegmentBody, RtnlSegment},
+            message::{
+                CMsgSegHdr, CSegmentType, GetRequestFlags, NewRequestFlags, SegHdrCommonFlags,
+                encode_nested_attrs, parse_nested_attrs,
+            },
+            route::message::{
+                LinkAttr, LinkInfoAttr, LinkSegment, LinkSegmentBody, RtnlSegment, VethInfoAttr,
+            },
         },
     },
     prelude::*,
+    process::{credentials::capabilities::CapSet, pid_table, posix_thread::AsPosixThread},
     util::net::CSocketAddrFamily,
 };
 
-pub(super) fn do_get_link(request_segment: &LinkSegment) -> Result<Vec<RtnlSegment>> {
+pub(super) fn do_get_link(
+    request_segment: &LinkSegment,
+    net_ns: &NetNamespace,
+) -> Result<Vec<RtnlSegment>> {
     let filter_by = FilterBy::from_request(request_segment)?;
 
-    let mut response_segments: Vec<RtnlSegment> = iter_all_ifaces()
+    let mut response_segments: Vec<RtnlSegment> = net_ns
+        .ifaces()
+        .iter()
         // Filter to include only requested links.
         .filter(|iface| match &filter_by {
             FilterBy::Index(index) => *index == iface.index(),
-            FilterBy::Name(name) => *name == iface.name(),
+            FilterBy::Name(name) => *name == iface.name().as_c_str(),
             FilterBy::Dump => true,
         })
         .map(|iface| iface_to_new_link(request_segment.header(), iface))
@@ -88,13 +100,450 @@ impl<'a> FilterBy<'a> {
     }
 }
 
+pub(super) fn do_new_link(
+    request_segment: &LinkSegment,
+    net_ns: &Arc<NetNamespace>,
+) -> Result<Vec<RtnlSegment>> {
+    let flags = NewRequestFlags::from_bits_truncate(request_segment.header().flags);
+
+    let Some(iface) = find_link(request_segment, net_ns) else {
+        if flags.contains(NewRequestFlags::CREATE) {
+            return create_link(request_segment, net_ns);
+        }
+        return_errno_with_message!(Errno::ENODEV, "no link found");
+    };
+
+    if flags.contains(NewRequestFlags::EXCL) {
+        return_errno_with_message!(Errno::EEXIST, "the link already exists");
+    }
+    if flags.contains(NewRequestFlags::REPLACE) {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "replacing links is not supported");
+    }
+
+    set_link(request_segment, &iface, net_ns)
+}
+
+pub(super) fn do_set_link(
+    request_segment: &LinkSegment,
+    net_ns: &NetNamespace,
+) -> Result<Vec<RtnlSegment>> {
+    let iface = find_specified_link(request_segment, net_ns)?;
+
+    set_link(request_segment, &iface, net_ns)
+}
+
+pub(super) fn do_del_link(
+    request_segment: &LinkSegment,
+    net_ns: &NetNamespace,
+) -> Result<Vec<RtnlSegment>> {
+    let iface = find_specified_link(request_segment, net_ns)?;
+
+    // Deleting an end of a veth pair also deletes the other end, which may be in another network
+    // namespace.
+    for (iface, net_ns) in iface::delete_link(&iface)? {
+        let mut segment = iface_to_new_link(request_segment.header(), &iface);
+        segment.header_mut().type_ = CSegmentType::DELLINK as _;
+        notify(&net_ns, RtnlGroup::LINK, RtnlSegment::DelLink(segment));
+    }
+
+    Ok(ack_response(request_segment.header()))
+}
+
+/// Notifies the sockets in the network namespace that the link is added to it.
+///
+/// This is used for the changes that are not requested via netlink, such as moving links when a
+/// network namespace is destroyed.
+pub(in crate::net) fn notify_new_link(net_ns: &NetNamespace, iface: &Arc<Iface>) {
+    let segment = iface_to_new_link(&CMsgSegHdr::new_zeroed(), iface);
+    notify(net_ns, RtnlGroup::LINK, RtnlSegment::NewLink(segment));
+}
+
+/// Notifies the sockets in the network namespace that the link is removed from it.
+///
+/// See [`notify_new_link`] for when it is used.
+pub(in crate::net) fn notify_del_link(net_ns: &NetNamespace, iface: &Arc<Iface>) {
+    let mut segment = iface_to_new_link(&CMsgSegHdr::new_zeroed(), iface);
+    segment.header_mut().type_ = CSegmentType::DELLINK as _;
+    notify(net_ns, RtnlGroup::LINK, RtnlSegment::DelLink(segment));
+}
+
+/// Finds the link specified by the index or the name.
+///
+/// This method will fail with `EINVAL` if neither the index nor the name is specified.
+fn find_specified_link(request_segment: &LinkSegment, net_ns: &NetNamespace) -> Result<Arc<Iface>> {
+    let has_name = request_segment
+        .attrs()
+        .iter()
+        .any(|attr| matches!(attr, LinkAttr::Name(_)));
+    if request_segment.body().index.is_none() && !has_name {
+        return_errno_with_message!(
+            Errno::EINVAL,
+            "either interface name or index should be specified"
+        );
+    }
+
+    find_link(request_segment, net_ns)
+        .ok_or_else(|| Error::with_message(Errno::ENODEV, "no link found"))
+}
+
+/// Finds the link specified by the index or, if the index is absent, by the name.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/net/core/rtnetlink.c#L3289>.
+fn find_link(request_segment: &LinkSegment, net_ns: &NetNamespace) -> Option<Arc<Iface>> {
+    let ifaces = net_ns.ifaces();
+
+    if let Some(index) = request_segment.body().index {
+        return ifaces
+            .into_iter()
+            .find(|iface| iface.index() == index.get());
+    }
+
+    let name = request_segment.attrs().iter().find_map(|attr| {
+        if let LinkAttr::Name(name) = attr {
+            Some(name.as_c_str())
+        } else {
+            None
+        }
+    })?;
+    ifaces
+        .into_iter()
+        .find(|iface| iface.name().as_c_str() == name)
+}
+
+fn set_link(
+    request_segment: &LinkSegment,
+    iface: &Arc<Iface>,
+    net_ns: &NetNamespace,
+) -> Result<Vec<RtnlSegment>> {
+    let mut target_ns = None;
+    let mut new_name = None;
+    let mut new_mtu = None;
+    let mut new_master = None;
+
+    for attr in request_segment.attrs() {
+        match attr {
+            LinkAttr::Name(name) => new_name = Some(name),
+            // TODO: Check whether the kind in `IFLA_LINKINFO` matches the link.
+            LinkAttr::ExtMask(_) | LinkAttr::Link(_) | LinkAttr::LinkInfo(_) => (),
+            LinkAttr::NetNsPid(pid) => target_ns = Some(get_net_ns_by_pid(*pid)?),
+            LinkAttr::NetNsFd(fd) => target_ns = Some(get_net_ns_by_fd(*fd)?),
+            LinkAttr::Mtu(mtu) => new_mtu = Some(*mtu),
+            LinkAttr::Master(index) => new_master = Some(*index),
+            LinkAttr::TxqLen(_) | LinkAttr::LinkMode(_) => {
+                return_errno_with_message!(
+                    Errno::EOPNOTSUPP,
+                    "changing the link attribute is not supported"
+                );
+            }
+        }
+    }
+
+    // The changes are applied in the same order as Linux.
+    // Reference: <https://elixir.bootlin.com/linux/v6.13/source/net/core/rtnetlink.c#L2877>.
+
+    let net_ns = if let Some(target_ns) = target_ns.as_ref() {
+        let current = current_thread!();
+        net_ns.move_iface_to(iface.index(), target_ns, current.as_posix_thread().unwrap())?;
+        target_ns.as_ref()
+    } else {
+        net_ns
+    };
+
+    if let Some(mtu) = new_mtu
+        && iface.set_mtu(mtu as usize).is_err()
+    {
+        return_errno_with_message!(Errno::EINVAL, "the MTU is invalid");
+    }
+
+    // The name is used to find the link if the index is absent, so it should not be treated as
+    // the new name in that case.
+    if let Some(name) = new_name
+        && request_segment.body().index.is_some()
+        && *name != iface.name()
+    {
+        let Ok(name) = name.to_str() else {
+            return_errno_with_message!(Errno::EINVAL, "the interface name is invalid");
+        };
+        net_ns.rename_iface(iface, name)?;
+    }
+
+    change_flags(request_segment.body(), iface, net_ns);
+
+    if let Some(master) = new_master {
+        set_master(iface, master, net_ns)?;
+    }
+
+    let segment = iface_to_new_link(request_segment.header(), iface);
+    notify(net_ns, RtnlGroup::LINK, RtnlSegment::NewLink(segment));
+
+    Ok(ack_response(request_segment.header()))
+}
+
+/// Changes the flags of the link as specified in `ifinfomsg`.
+fn change_flags(body: &LinkSegmentBody, iface: &Arc<Iface>, net_ns: &NetNamespace) {
+    if body.flags.is_empty() && body.change.is_empty() {
+        return;
+    }
+
+    // Reference: <https://elixir.bootlin.com/linux/v6.13/source/net/core/rtnetlink.c#L3324>.
+    let flags = if body.change.is_empty() {
+        body.flags
+    } else {
+        (body.flags & body.change) | (iface.flags() & !body.change)
+    };
+    // TODO: Support changing other flags (e.g., `IFF_PROMISC`).
+    net_ns.set_iface_up(iface, flags.contains(InterfaceFlags::UP));
+}
+
+/// Attaches the link to the bridge with the specified index, or detaches it if the index is zero.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/net/core/rtnetlink.c>.
+fn set_master(iface: &Arc<Iface>, master_index: u32, net_ns: &NetNamespace) -> Result<()> {
+    if master_index == 0 {
+        return iface::set_master(iface, None);
+    }
+
+    let Some(master) = net_ns
+        .ifaces()
+        .into_iter()
+        .find(|iface| iface.index() == master_index)
+    else {
+        return_errno_with_message!(Errno::EINVAL, "the master link does not exist");
+    };
+
+    iface::set_master(iface, Some(&master))
+}
+
+/// Creates a new link.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/net/core/rtnetlink.c>.
+fn create_link(
+    request_segment: &LinkSegment,
+    net_ns: &Arc<NetNamespace>,
+) -> Result<Vec<RtnlSegment>> {
+    if request_segment.body().index.is_some() {
+        return_errno_with_message!(
+            Errno::EOPNOTSUPP,
+            "creating links with specified indexes is not supported"
+        );
+    }
+
+    let mut link_info = None;
+    let mut master = None;
+    for attr in request_segment.attrs() {
+        match attr {
+            LinkAttr::LinkInfo(payload) => link_info = Some(payload),
+            LinkAttr::Master(index) => master = Some(*index),
+            _ => (),
+        }
+    }
+
+    let mut kind = None;
+    let mut kind_data = None;
+    if let Some(link_info) = link_info {
+        for attr in parse_nested_attrs::<LinkInfoAttr>(link_info)? {
+            match attr {
+                LinkInfoAttr::Kind(name) => kind = Some(name),
+                LinkInfoAttr::Data(data) => kind_data = Some(data),
+            }
+        }
+    }
+    let Some(kind) = kind
+        .as_ref()
+        .and_then(|name| name.to_str().ok())
+        .and_then(LinkKind::from_name)
+    else {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "the link kind is not supported");
+    };
+
+    let config = NewLinkConfig::from_attrs(request_segment.attrs(), net_ns)?;
+    let body = request_segment.body();
+
+    let new_links = match kind {
+        LinkKind::Veth => {
+            let mut peer = None;
+            if let Some(data) = kind_data.as_ref() {
+                for attr in parse_nested_attrs::<VethInfoAttr>(data)? {
+                    let VethInfoAttr::Peer(peer_body, peer_attrs) = attr;
+                    peer = Some((peer_body, peer_attrs));
+                }
+            }
+
+            let (peer_body, peer_attrs) = peer.unzip();
+            let peer_config =
+                NewLinkConfig::from_attrs(peer_attrs.as_deref().unwrap_or_default(), net_ns)?;
+
+            let (iface, peer_iface) = iface::new_veth_pair(
+                &config.name_template(kind)?,
+                &config.net_ns,
+                &peer_config.name_template(kind)?,
+                &peer_config.net_ns,
+            )?;
+            vec![
+                (iface, config, Some(*body)),
+                (peer_iface, peer_config, peer_body),
+            ]
+        }
+        LinkKind::Bridge => {
+            let iface = iface::new_bridge(&config.name_template(kind)?, &config.net_ns)?;
+            vec![(iface, config, Some(*body))]
+        }
+    };
+
+    // Like Linux, the link is deleted if it cannot be configured as requested.
+    let configure = || -> Result<()> {
+        for (iface, config, body) in new_links.iter() {
+            config.apply(iface, body.as_ref())?;
+        }
+        if let Some(master) = master {
+            let (iface, config, _) = &new_links[0];
+            set_master(iface, master, &config.net_ns)?;
+        }
+        Ok(())
+    };
+    if let Err(err) = configure() {
+        let _ = iface::delete_link(&new_links[0].0);
+        return Err(err);
+    }
+
+    for (iface, config, _) in new_links.iter() {
+        let segment = iface_to_new_link(request_segment.header(), iface);
+        notify(
+            &config.net_ns,
+            RtnlGroup::LINK,
+            RtnlSegment::NewLink(segment),
+        );
+    }
+
+    Ok(ack_response(request_segment.header()))
+}
+
+/// The configuration of a new link.
+struct NewLinkConfig<'a> {
+    name: Option<&'a CStr>,
+    net_ns: Arc<NetNamespace>,
+    mtu: Option<u32>,
+}
+
+impl<'a> NewLinkConfig<'a> {
+    /// Parses the configuration from the link attributes.
+    ///
+    /// The link will be created in `net_ns` unless another network namespace is specified.
+    fn from_attrs(attrs: &'a [LinkAttr], net_ns: &Arc<NetNamespace>) -> Result<Self> {
+        let mut config = Self {
+            name: None,
+            net_ns: net_ns.clone(),
+            mtu: None,
+        };
+
+        for attr in attrs {
+            match attr {
+                LinkAttr::Name(name) => config.name = Some(name.as_c_str()),
+                LinkAttr::NetNsPid(pid) => config.net_ns = get_net_ns_by_pid(*pid)?,
+                LinkAttr::NetNsFd(fd) => config.net_ns = get_net_ns_by_fd(*fd)?,
+                LinkAttr::Mtu(mtu) => config.mtu = Some(*mtu),
+                LinkAttr::ExtMask(_)
+                | LinkAttr::Master(_)
+                | LinkAttr::Link(_)
+                | LinkAttr::LinkInfo(_) => (),
+                LinkAttr::TxqLen(_) | LinkAttr::LinkMode(_) => {
+                    return_errno_with_message!(
+                        Errno::EOPNOTSUPP,
+                        "setting the link attribute is not supported"
+                    );
+                }
+            }
+        }
+
+        if !Arc::ptr_eq(&config.net_ns, net_ns) {
+            let current = current_thread!();
+            config
+                .net_ns
+                .check_cap(CapSet::NET_ADMIN, current.as_posix_thread().unwrap())?;
+        }
+
+        Ok(config)
+    }
+
+    /// Returns the name template of the link.
+    ///
+    /// Like Linux, the default name is the kind followed by a number (e.g., `veth0`).
+    fn name_template(&self, kind: LinkKind) -> Result<String> {
+        let Some(name) = self.name else {
+            return Ok(format!("{}%d", kind.name()));
+        };
+
+        let Ok(name) = name.to_str() else {
+            return_errno_with_message!(Errno::EINVAL, "the interface name is invalid");
+        };
+        Ok(name.to_string())
+    }
+
+    /// Applies the configuration to the new link.
+    ///
+    /// The flags are changed as specified in `body`, if any.
+    fn apply(&self, iface: &Arc<Iface>, body: Option<&LinkSegmentBody>) -> Result<()> {
+        if let Some(mtu) = self.mtu
+            && iface.set_mtu(mtu as usize).is_err()
+        {
+            return_errno_with_message!(Errno::EINVAL, "the MTU is invalid");
+        }
+
+        if let Some(body) = body {
+            change_flags(body, iface, &self.net_ns);
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the network namespace of the process with the specified ID.
+fn get_net_ns_by_pid(pid: u32) -> Result<Arc<NetNamespace>> {
+    let process = current!()
+        .pid_ns()
+        .global_id(pid)
+        .and_then(|pid| pid_table::pid_table_mut().get_process(pid))
+        .ok_or_else(|| Error::with_message(Errno::ESRCH, "the process does not exist"))?;
+
+    let main_thread = process.main_thread();
+    let ns_proxy = main_thread.as_posix_thread().unwrap().ns_proxy().lock();
+    let Some(ns_proxy) = ns_proxy.as_ref() else {
+        return_errno_with_message!(Errno::ESRCH, "the process has exited");
+    };
+
+    Ok(ns_proxy.net_ns().clone())
+}
+
+/// Returns the network namespace referred to by the file descriptor.
+fn get_net_ns_by_fd(fd: u32) -> Result<Arc<NetNamespace>> {
+    // The thread-local file table may have been borrowed by the system call (e.g., `sendto`), so
+    // the file table in the POSIX thread is used instead.
+    let file = {
+        let current = current_thread!();
+        let file_table = current.as_posix_thread().unwrap().file_table().lock();
+        let file_table = file_table.as_ref().unwrap().read();
+        file_table.get_file(fd.cast_signed())?.clone()
+    };
+
+    let ns_file = file
+        .downcast_ref::<InodeHandle>()
+        .map(|inode_handle| inode_handle.downcast_open_file::<NsFile<NetNamespace>>())
+        .transpose()?
+        .flatten()
+        .ok_or_else(|| {
+            Error::with_message(Errno::EINVAL, "the file is not a network namespace file")
+        })?;
+
+    Ok(ns_file.ns().clone())
+}
+
 // The below functions starting with `validate_` should only be enabled in strict mode.
 // Reference: <https://docs.kernel.org/userspace-api/netlink/intro.html#strict-checking>.
 
 fn validate_getlink_request(body: &LinkSegmentBody) -> Result<()> {
     // FIXME: The Linux implementation also checks the `padding` and `change` fields,
-    // but these fields are lost during the conversion of a `CIfInfoMsg` to `LinkSegmentBody`.
-    // We should consider including the `change` field in `LinkSegmentBody`.
+    // but the `padding` field is lost during the conversion of a `CIfInfoMsg` to
+    // `LinkSegmentBody`.
     // Reference: <https://elixir.bootlin.com/linux/v6.13/source/net/core/rtnetlink.c#L4043>.
     if !body.flags.is_empty() || body.type_ != InterfaceType::NETROM {
         return_errno_with_message!(Errno::EINVAL, "the flags or the type is not valid");
@@ -135,12 +584,26 @@ fn iface_to_new_link(request_header: &CMsgSegHdr, iface: &Arc<Iface>) -> LinkSeg
         type_: iface.type_(),
         index: NonZero::new(iface.index()),
         flags: iface.flags(),
+        change: InterfaceFlags::empty(),
     };
 
-    let attrs = vec![
-        LinkAttr::Name(iface.name().to_owned()),
+    let mut attrs = vec![
+        LinkAttr::Name(iface.name()),
         LinkAttr::Mtu(iface.mtu() as u32),
     ];
 
+    if let Some(link_info) = iface::link_info(iface) {
+        if let Some(master_index) = link_info.master_index {
+            attrs.push(LinkAttr::Master(master_index));
+        }
+        if let Some(peer_index) = link_info.peer_index {
+            attrs.push(LinkAttr::Link(peer_index));
+        }
+        let kind = CString::new(link_info.kind.name()).unwrap();
+        attrs.push(LinkAttr::LinkInfo(encode_nested_attrs(&[
+            LinkInfoAttr::Kind(kind),
+        ])));
+    }
+
     LinkSegment::new(header, link_message, attrs)
 }
@@ -7,18 +7,26 @@ use core::marker::PhantomData;
 
 use super::message::{RtnlMessage, RtnlSegment};
 use crate::{
-    net::socket::netlink::{
-        addr::PortNum,
-        message::{ErrorSegment, ProtocolSegment},
-        table::{NetlinkRouteProtocol, SupportedNetlinkProtocol},
+    net::{
+        net_ns::NetNamespace,
+        socket::netlink::{
+            addr::PortNum,
+            message::{ErrorSegment, ProtocolSegment},
+            table::{NetlinkRouteProtocol, SupportedNetlinkProtocol},
+        },
     },
     prelude::*,
+    process::{credentials::capabilities::CapSet, posix_thread::AsPosixThread},
 };
 
 mod addr;
 mod link;
+mod route;
 mod util;
 
+pub(super) use addr::notify_del_addr;
+pub(super) use link::{notify_del_link, notify_new_link};
+
 pub(super) struct NetlinkRouteKernelSocket {
     _private: PhantomData<()>,
 }
@@ -30,47 +38,88 @@ impl NetlinkRouteKernelSocket {
         }
     }
 
-    pub(super) fn handle_request(&self, request: &RtnlSegment, dst_port: PortNum) {
+    pub(super) fn handle_request(
+        &self,
+        net_ns: &Arc<NetNamespace>,
+        request: &RtnlSegment,
+        dst_port: PortNum,
+    ) {
         debug!("netlink route request: {:?}", request);
 
         let request_header = request.header();
 
-        let response_segments = match request {
-            RtnlSegment::GetLink(request_segment) => link::do_get_link(request_segment),
-            RtnlSegment::GetAddr(request_segment) => addr::do_get_addr(request_segment),
+        let response_segments = check_permission(net_ns, request).and_then(|()| match request {
+            RtnlSegment::NewLink(request_segment) => link::do_new_link(request_segment, net_ns),
+            RtnlSegment::DelLink(request_segment) => link::do_del_link(request_segment, net_ns),
+            RtnlSegment::GetLink(request_segment) => link::do_get_link(request_segment, net_ns),
+            RtnlSegment::SetLink(request_segment) => link::do_set_link(request_segment, net_ns),
+            RtnlSegment::NewAddr(request_segment) => addr::do_new_addr(request_segment, net_ns),
+            RtnlSegment::DelAddr(request_segment) => addr::do_del_addr(request_segment, net_ns),
+            RtnlSegment::GetAddr(request_segment) => addr::do_get_addr(request_segment, net_ns),
+            RtnlSegment::NewRoute(request_segment) => route::do_new_route(request_segment, net_ns),
+            RtnlSegment::DelRoute(request_segment) => route::do_del_route(request_segment, net_ns),
+            RtnlSegment::GetRoute(request_segment) => route::do_get_route(request_segment, net_ns),
             _ => Err(Error::with_message(
                 Errno::EOPNOTSUPP,
                 "the netlink route request is not supported",
             )),
-        };
+        });
 
         let response = match response_segments {
+            // Nothing to reply, e.g., a successful SET request without the ACK flag.
+            Ok(segments) if segments.is_empty() => return,
             Ok(segments) => RtnlMessage::new(segments),
             Err(error) => {
                 // TODO: Deal with the `NetlinkMessageCommonFlags::ACK` flag.
                 // Should we return `ErrorSegment` if ACK flag does not exist?
                 // Reference: <https://docs.kernel.org/userspace-api/netlink/intro.html#netlink-message-types>.
                 let err_segment = ErrorSegment::new_from_request(request_header, Some(error));
-                self.report_error(err_segment, dst_port);
+                self.report_error(net_ns, err_segment, dst_port);
                 return;
             }
         };
 
         debug!("netlink route response: {:?}", response);
 
-        NetlinkRouteProtocol::unicast(dst_port, response).unwrap();
+        NetlinkRouteProtocol::unicast(net_ns, dst_port, response).unwrap();
     }
 
-    pub(super) fn report_error(&self, err_segment: ErrorSegment, dst_port: PortNum) {
+    pub(super) fn report_error(
+        &self,
+        net_ns: &NetNamespace,
+        err_segment: ErrorSegment,
+        dst_port: PortNum,
+    ) {
         let response = RtnlMessage::new(vec![RtnlSegment::Error(err_segment)]);
 
         debug!("netlink route error: {:?}", response);
 
-        NetlinkRouteProtocol::unicast(dst_port, response).unwrap();
+        NetlinkRouteProtocol::unicast(net_ns, dst_port, response).unwrap();
+    }
+}
+
+/// Checks whether the current thread is allowed to make the request.
+///
+/// Like Linux, requests other than GET requests require the `CAP_NET_ADMIN` capability.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/net/core/rtnetlink.c#L6680>.
+fn check_permission(net_ns: &NetNamespace, request: &RtnlSegment) -> Result<()> {
+    if matches!(
+        request,
+        RtnlSegment::GetLink(_) | RtnlSegment::GetAddr(_) | RtnlSegment::GetRoute(_)
+    ) {
+        return Ok(());
     }
+
+    let current = current_thread!();
+    net_ns.check_cap(CapSet::NET_ADMIN, current.as_posix_thread().unwrap())
 }
 
-/// FIXME: NETLINK_ROUTE_KERNEL should be a per-network namespace socket
+/// The kernel socket of `NETLINK_ROUTE`.
+///
+/// The links, addresses, and routes are looked up in the network namespace of the requesting
+/// socket, and the notifications go to the sockets in the same namespace. Since nothing is kept
+/// here, one kernel socket serves all network namespaces.
 static NETLINK_ROUTE_KERNEL: NetlinkRouteKernelSocket = NetlinkRouteKernelSocket::new();
 
 pub(super) fn get_netlink_route_kernel() -> &'static NetlinkRouteKernelSocket {
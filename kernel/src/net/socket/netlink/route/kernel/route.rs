@@ -0,0 +1,313 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Handle route-related requests.
+
+use aster_bigtcp::wire::{IpAddress, IpCidr, Ipv4Address, Ipv6Address};
+
+use super::util::{RtnlGroup, ack_response, finish_response, notify, response_header};
+use crate::{
+    net::{
+        iface::Iface,
+        net_ns::NetNamespace,
+        route::{IP6_RT_PRIO_USER, Route, network_of},
+        socket::netlink::{
+            message::{CMsgSegHdr, CSegmentType, GetRequestFlags, NewRequestFlags},
+            route::message::{
+                IpAddrBytes, RT_TABLE_MAIN, RT_TABLE_UNSPEC, RouteAttr, RouteSegment,
+                RouteSegmentBody, RouteType, RtScope, RtnlSegment,
+            },
+        },
+    },
+    prelude::*,
+    util::net::CSocketAddrFamily,
+};
+
+pub(super) fn do_get_route(
+    request_segment: &RouteSegment,
+    net_ns: &NetNamespace,
+) -> Result<Vec<RtnlSegment>> {
+    let dump_all = {
+        let flags = GetRequestFlags::from_bits_truncate(request_segment.header().flags);
+        flags.contains(GetRequestFlags::DUMP)
+    };
+    if !dump_all {
+        // TODO: Support looking up the route to a specific destination (i.e., `ip route get`).
+        return_errno_with_message!(Errno::EOPNOTSUPP, "GETROUTE only supports dump requests");
+    }
+
+    let family = request_segment.body().family;
+
+    let mut response_segments: Vec<RtnlSegment> = net_ns
+        .routes()
+        .iter()
+        // Unknown families are treated as `AF_UNSPEC`.
+        .filter(|route| match route.dst {
+            IpCidr::Ipv4(_) => family != CSocketAddrFamily::AF_INET6 as i32,
+            IpCidr::Ipv6(_) => family != CSocketAddrFamily::AF_INET as i32,
+        })
+        .map(|route| new_route_segment(request_segment.header(), CSegmentType::NEWROUTE, route))
+        .map(RtnlSegment::NewRoute)
+        .collect();
+
+    finish_response(request_segment.header(), dump_all, &mut response_segments);
+
+    Ok(response_segments)
+}
+
+pub(super) fn do_new_route(
+    request_segment: &RouteSegment,
+    net_ns: &NetNamespace,
+) -> Result<Vec<RtnlSegment>> {
+    let body = request_segment.body();
+    let request = RouteRequest::parse(request_segment)?;
+
+    if !matches!(body.type_, RouteType::UNICAST | RouteType::UNSPEC) {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "only unicast routes are supported");
+    }
+
+    let ifaces = net_ns.ifaces();
+
+    let oif = request
+        .oif
+        .map(|oif| {
+            ifaces
+                .iter()
+                .find(|iface| iface.index() == oif)
+                .ok_or_else(|| Error::with_message(Errno::ENODEV, "the interface does not exist"))
+        })
+        .transpose()?;
+
+    // Reference: <https://elixir.bootlin.com/linux/v6.13/source/net/ipv4/fib_semantics.c#L1242>.
+    let iface = match (request.gateway, oif) {
+        (Some(gateway), oif) => {
+            let is_on_link = |iface: &&Arc<Iface>| {
+                iface.is_up()
+                    && iface
+                        .ip_addrs()
+                        .iter()
+                        .any(|ip_cidr| ip_cidr.contains_addr(&gateway))
+            };
+            let Some(iface) = ifaces
+                .iter()
+                .filter(|iface| oif.is_none_or(|oif| Arc::ptr_eq(*iface, oif)))
+                .find(is_on_link)
+            else {
+                return_errno_with_message!(Errno::ENETUNREACH, "the gateway is unreachable");
+            };
+            iface
+        }
+        (None, Some(oif)) => oif,
+        (None, None) => {
+            return_errno_with_message!(Errno::ENODEV, "the output interface is not specified")
+        }
+    };
+    if !iface.is_up() {
+        return_errno_with_message!(Errno::ENETDOWN, "the output interface is down");
+    }
+
+    if let Some(pref_src) = request.pref_src
+        && !ifaces.iter().any(|iface| iface.has_ip_addr(pref_src))
+    {
+        return_errno_with_message!(Errno::EINVAL, "the preferred source address is not local");
+    }
+
+    let (metric, scope) = match request.dst {
+        IpCidr::Ipv4(_) => (request.metric.unwrap_or(0), body.scope),
+        // IPv6 routes do not have scopes.
+        IpCidr::Ipv6(_) => (
+            request.metric.unwrap_or(IP6_RT_PRIO_USER),
+            RtScope::UNIVERSE,
+        ),
+    };
+
+    let route = Route {
+        dst: request.dst,
+        gateway: request.gateway,
+        iface_index: iface.index(),
+        pref_src: request.pref_src,
+        metric,
+        protocol: body.protocol,
+        scope: scope as u8,
+    };
+
+    let flags = NewRequestFlags::from_bits_truncate(request_segment.header().flags);
+    net_ns.modify_routes(|route_table| {
+        // Reference: <https://elixir.bootlin.com/linux/v6.13/source/net/ipv4/fib_trie.c#L1231>.
+        if route_table.find_same_key(&route).is_some() {
+            if flags.contains(NewRequestFlags::EXCL) || !flags.contains(NewRequestFlags::REPLACE) {
+                return_errno_with_message!(Errno::EEXIST, "the route already exists");
+            }
+        } else if !flags.contains(NewRequestFlags::CREATE) {
+            return_errno_with_message!(Errno::ENOENT, "the route does not exist");
+        }
+
+        route_table.insert(route.clone());
+        Ok(())
+    })?;
+
+    let segment = new_route_segment(request_segment.header(), CSegmentType::NEWROUTE, &route);
+    notify(net_ns, route_group(&route), RtnlSegment::NewRoute(segment));
+
+    Ok(ack_response(request_segment.header()))
+}
+
+pub(super) fn do_del_route(
+    request_segment: &RouteSegment,
+    net_ns: &NetNamespace,
+) -> Result<Vec<RtnlSegment>> {
+    let body = request_segment.body();
+    let request = RouteRequest::parse(request_segment)?;
+
+    // Reference: <https://elixir.bootlin.com/linux/v6.13/source/net/ipv4/fib_trie.c#L1700>.
+    let is_matched = |route: &Route| {
+        route.dst == request.dst
+            && request.oif.is_none_or(|oif| route.iface_index == oif)
+            && request
+                .gateway
+                .is_none_or(|gateway| route.gateway == Some(gateway))
+            && request
+                .pref_src
+                .is_none_or(|pref_src| route.pref_src == Some(pref_src))
+            && request.metric.is_none_or(|metric| route.metric == metric)
+            && (body.protocol == 0 || route.protocol == body.protocol)
+            && (body.scope == RtScope::NOWHERE
+                || matches!(request.dst, IpCidr::Ipv6(_))
+                || route.scope == body.scope as u8)
+    };
+
+    let route = net_ns.modify_routes(|route_table| {
+        route_table
+            .remove_first(is_matched)
+            .ok_or_else(|| Error::with_message(Errno::ESRCH, "the route does not exist"))
+    })?;
+
+    let segment = new_route_segment(request_segment.header(), CSegmentType::DELROUTE, &route);
+    notify(net_ns, route_group(&route), RtnlSegment::DelRoute(segment));
+
+    Ok(ack_response(request_segment.header()))
+}
+
+/// The route described by a NEWROUTE or DELROUTE request.
+struct RouteRequest {
+    dst: IpCidr,
+    oif: Option<u32>,
+    gateway: Option<IpAddress>,
+    pref_src: Option<IpAddress>,
+    metric: Option<u32>,
+}
+
+impl RouteRequest {
+    fn parse(request_segment: &RouteSegment) -> Result<Self> {
+        let body = request_segment.body();
+
+        let mut table = body.table as u32;
+        let mut dst = None;
+        let mut oif = None;
+        let mut gateway = None;
+        let mut pref_src = None;
+        let mut metric = None;
+        for attr in request_segment.attrs() {
+            match attr {
+                RouteAttr::Table(id) => table = *id,
+                RouteAttr::Dst(addr) => dst = Some(parse_addr(body, *addr)?),
+                RouteAttr::Oif(index) => oif = Some(*index),
+                RouteAttr::Gateway(addr) => gateway = Some(parse_addr(body, *addr)?),
+                RouteAttr::PrefSrc(addr) => pref_src = Some(parse_addr(body, *addr)?),
+                RouteAttr::Priority(priority) => metric = Some(*priority).filter(|p| *p != 0),
+            }
+        }
+
+        if table != RT_TABLE_UNSPEC as u32 && table != RT_TABLE_MAIN as u32 {
+            return_errno_with_message!(
+                Errno::EOPNOTSUPP,
+                "only the main routing table is supported"
+            );
+        }
+
+        let (unspecified, max_prefix_len) = if body.family == CSocketAddrFamily::AF_INET as i32 {
+            (IpAddress::Ipv4(Ipv4Address::UNSPECIFIED), 32)
+        } else if body.family == CSocketAddrFamily::AF_INET6 as i32 {
+            (IpAddress::Ipv6(Ipv6Address::UNSPECIFIED), 128)
+        } else {
+            return_errno_with_message!(Errno::EOPNOTSUPP, "the address family is not supported");
+        };
+        if body.dst_len > max_prefix_len {
+            return_errno_with_message!(Errno::EINVAL, "the prefix length is invalid");
+        }
+
+        let dst = IpCidr::new(dst.unwrap_or(unspecified), body.dst_len);
+        let network = network_of(&dst);
+        // Like Linux, IPv4 destinations must not have host bits, while IPv6 destinations are
+        // silently masked.
+        if matches!(dst, IpCidr::Ipv4(_)) && network != dst {
+            return_errno_with_message!(Errno::EINVAL, "the prefix is invalid for the length");
+        }
+
+        Ok(Self {
+            dst: network,
+            oif,
+            gateway,
+            pref_src,
+            metric,
+        })
+    }
+}
+
+/// Parses an address in the request, which must match the address family of the request.
+fn parse_addr(body: &RouteSegmentBody, addr: IpAddrBytes) -> Result<IpAddress> {
+    match addr {
+        IpAddrBytes::V4(_) if body.family == CSocketAddrFamily::AF_INET as i32 => Ok(addr.into()),
+        IpAddrBytes::V6(_) if body.family == CSocketAddrFamily::AF_INET6 as i32 => Ok(addr.into()),
+        _ => return_errno_with_message!(
+            Errno::EINVAL,
+            "the address does not match the address family"
+        ),
+    }
+}
+
+fn route_group(route: &Route) -> RtnlGroup {
+    match route.dst {
+        IpCidr::Ipv4(_) => RtnlGroup::IPV4_ROUTE,
+        IpCidr::Ipv6(_) => RtnlGroup::IPV6_ROUTE,
+    }
+}
+
+fn new_route_segment(
+    request_header: &CMsgSegHdr,
+    type_: CSegmentType,
+    route: &Route,
+) -> RouteSegment {
+    let header = response_header(request_header, type_);
+
+    let family = match route.dst {
+        IpCidr::Ipv4(_) => CSocketAddrFamily::AF_INET,
+        IpCidr::Ipv6(_) => CSocketAddrFamily::AF_INET6,
+    };
+
+    let route_message = RouteSegmentBody {
+        family: family as _,
+        dst_len: route.dst.prefix_len(),
+        src_len: 0,
+        tos: 0,
+        table: RT_TABLE_MAIN,
+        protocol: route.protocol,
+        scope: RtScope::try_from(route.scope).unwrap(),
+        type_: RouteType::UNICAST,
+        flags: 0,
+    };
+
+    let mut attrs = vec![RouteAttr::Table(RT_TABLE_MAIN as u32)];
+    if route.dst.prefix_len() > 0 {
+        attrs.push(RouteAttr::Dst(IpAddrBytes::from(route.dst.address())));
+    }
+    attrs.push(RouteAttr::Priority(route.metric));
+    if let Some(pref_src) = route.pref_src {
+        attrs.push(RouteAttr::PrefSrc(IpAddrBytes::from(pref_src)));
+    }
+    if let Some(gateway) = route.gateway {
+        attrs.push(RouteAttr::Gateway(IpAddrBytes::from(gateway)));
+    }
+    attrs.push(RouteAttr::Oif(route.iface_index));
+
+    RouteSegment::new(header, route_message, attrs)
+}
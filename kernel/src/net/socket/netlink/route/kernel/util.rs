@@ -1,9 +1,17 @@
 // SPDX-License-Identifier: MPL-2.0
 
 use crate::{
-    net::socket::netlink::{
-        message::{CMsgSegHdr, DoneSegment, ProtocolSegment, SegHdrCommonFlags},
-        route::message::RtnlSegment,
+    net::{
+        net_ns::NetNamespace,
+        socket::netlink::{
+            GroupIdSet,
+            message::{
+                CMsgSegHdr, CSegmentType, DoneSegment, ErrorSegment, ProtocolSegment,
+                SegHdrCommonFlags,
+            },
+            route::message::{RtnlMessage, RtnlSegment},
+            table::{NetlinkRouteProtocol, SupportedNetlinkProtocol},
+        },
     },
     prelude::*,
 };
@@ -37,3 +45,52 @@ fn add_multi_flag(response_segments: &mut [RtnlSegment]) {
         header.flags = flags.bits();
     }
 }
+
+/// Returns the response to a successful request that does not return data.
+///
+/// The response contains an ACK segment only if the request asks for it.
+pub fn ack_response(request_header: &CMsgSegHdr) -> Vec<RtnlSegment> {
+    let flags = SegHdrCommonFlags::from_bits_truncate(request_header.flags);
+    if !flags.contains(SegHdrCommonFlags::ACK) {
+        return Vec::new();
+    }
+
+    let ack_segment = ErrorSegment::new_from_request(request_header, None);
+    vec![RtnlSegment::Error(ack_segment)]
+}
+
+/// Returns the header of a response segment to the request.
+pub fn response_header(request_header: &CMsgSegHdr, type_: CSegmentType) -> CMsgSegHdr {
+    CMsgSegHdr {
+        len: 0,
+        type_: type_ as _,
+        flags: SegHdrCommonFlags::empty().bits(),
+        seq: request_header.seq,
+        pid: request_header.pid,
+    }
+}
+
+/// Multicast groups of netlink route sockets.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/rtnetlink.h#L731>.
+#[expect(non_camel_case_types)]
+#[expect(clippy::upper_case_acronyms)]
+#[repr(u32)]
+#[derive(Clone, Copy, Debug)]
+pub enum RtnlGroup {
+    LINK = 1,
+    IPV4_IFADDR = 5,
+    IPV4_ROUTE = 7,
+    IPV6_IFADDR = 9,
+    IPV6_ROUTE = 11,
+}
+
+/// Notifies the sockets in the multicast group of a change.
+pub fn notify(net_ns: &NetNamespace, group: RtnlGroup, segment: RtnlSegment) {
+    let groups = GroupIdSet::new(1 << (group as u32 - 1));
+    let message = RtnlMessage::new(vec![segment]);
+
+    debug!("netlink route notification: {:?}", message);
+
+    NetlinkRouteProtocol::multicast(net_ns, groups, message).unwrap();
+}
@@ -1,5 +1,6 @@
 // SPDX-License-Identifier: MPL-2.0
 
+use super::{IFNAME_SIZE, IpAddrBytes};
 use crate::{
     net::socket::netlink::message::{Attribute, CAttrHeader, ContinueRead},
     prelude::*,
@@ -27,11 +28,14 @@ enum AddrAttrClass {
     TARGET_NETNSID = 10,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum AddrAttr {
-    Address([u8; 4]),
-    Local([u8; 4]),
+    Address(IpAddrBytes),
+    Local(IpAddrBytes),
     Label(CString),
+    Broadcast(IpAddrBytes),
+    Flags(u32),
+    RtPriority(u32),
 }
 
 impl AddrAttr {
@@ -40,6 +44,9 @@ impl AddrAttr {
             AddrAttr::Address(_) => AddrAttrClass::ADDRESS,
             AddrAttr::Local(_) => AddrAttrClass::LOCAL,
             AddrAttr::Label(_) => AddrAttrClass::LABEL,
+            AddrAttr::Broadcast(_) => AddrAttrClass::BROADCAST,
+            AddrAttr::Flags(_) => AddrAttrClass::FLAGS,
+            AddrAttr::RtPriority(_) => AddrAttrClass::RT_PRIORITY,
         }
     }
 }
@@ -51,9 +58,12 @@ impl Attribute for AddrAttr {
 
     fn payload_as_bytes(&self) -> &[u8] {
         match self {
-            AddrAttr::Address(address) => address,
-            AddrAttr::Local(local) => local,
+            AddrAttr::Address(address) => address.as_bytes(),
+            AddrAttr::Local(local) => local.as_bytes(),
             AddrAttr::Label(label) => label.as_bytes_with_nul(),
+            AddrAttr::Broadcast(broadcast) => broadcast.as_bytes(),
+            AddrAttr::Flags(flags) => flags.as_bytes(),
+            AddrAttr::RtPriority(priority) => priority.as_bytes(),
         }
     }
 
@@ -62,26 +72,63 @@ impl Attribute for AddrAttr {
         Self: Sized,
     {
         let payload_len = header.payload_len();
-        reader.skip_some(payload_len);
 
-        // GETADDR only supports dump requests. These requests do not have any attributes.
-        // According to the Linux behavior, we should just ignore all the attributes.
+        let Ok(class) = AddrAttrClass::try_from(header.type_()) else {
+            // Unknown attributes should be ignored.
+            // Reference: <https://docs.kernel.org/userspace-api/netlink/intro.html#unknown-attributes>.
+            reader.skip_some(payload_len);
+            return Ok(ContinueRead::Skipped);
+        };
 
-        Ok(ContinueRead::Skipped)
-    }
+        let res = match (class, payload_len) {
+            (AddrAttrClass::ADDRESS | AddrAttrClass::LOCAL | AddrAttrClass::BROADCAST, _) => {
+                let Some(addr) = IpAddrBytes::read_from(reader, payload_len)? else {
+                    return Ok(ContinueRead::skipped_with_error(
+                        Errno::EINVAL,
+                        "the address attribute is invalid",
+                    ));
+                };
+                match class {
+                    AddrAttrClass::ADDRESS => Self::Address(addr),
+                    AddrAttrClass::LOCAL => Self::Local(addr),
+                    _ => Self::Broadcast(addr),
+                }
+            }
+            (AddrAttrClass::LABEL, 1..=IFNAME_SIZE) => {
+                let (label, label_len) =
+                    reader.read_cstring_until_end(IFNAME_SIZE.min(payload_len))?;
+                if label_len != payload_len {
+                    reader.skip_some(payload_len - label_len);
+                }
+                if label.as_bytes().len() == IFNAME_SIZE {
+                    return Ok(ContinueRead::skipped_with_error(
+                        Errno::EINVAL,
+                        "the address label is too long",
+                    ));
+                }
+                Self::Label(label)
+            }
+            (AddrAttrClass::FLAGS, 4) => Self::Flags(reader.read_val_opt::<u32>()?.unwrap()),
+            (AddrAttrClass::RT_PRIORITY, 4) => {
+                Self::RtPriority(reader.read_val_opt::<u32>()?.unwrap())
+            }
 
-    fn read_all_from(
-        reader: &mut dyn MultiRead,
-        total_len: usize,
-    ) -> Result<ContinueRead<Vec<Self>>>
-    where
-        Self: Sized,
-    {
-        reader.skip_some(total_len);
+            (AddrAttrClass::LABEL | AddrAttrClass::FLAGS | AddrAttrClass::RT_PRIORITY, _) => {
+                warn!("address attribute `{:?}` contains invalid payload", class);
+                reader.skip_some(payload_len);
+                return Ok(ContinueRead::skipped_with_error(
+                    Errno::EINVAL,
+                    "the address attribute is invalid",
+                ));
+            }
 
-        // GETADDR only supports dump requests. These requests do not have any attributes.
-        // According to the Linux behavior, we should just ignore all the attributes.
+            (_, _) => {
+                warn!("address attribute `{:?}` is not supported", class);
+                reader.skip_some(payload_len);
+                return Ok(ContinueRead::Skipped);
+            }
+        };
 
-        Ok(ContinueRead::Skipped)
+        Ok(ContinueRead::Parsed(res))
     }
 }
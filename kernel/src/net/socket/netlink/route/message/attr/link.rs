@@ -2,7 +2,10 @@
 
 use super::IFNAME_SIZE;
 use crate::{
-    net::socket::netlink::message::{Attribute, CAttrHeader, ContinueRead},
+    net::socket::netlink::{
+        message::{Attribute, CAttrHeader, ContinueRead, read_payload},
+        route::message::segment::link::{CIfinfoMsg, LinkSegmentBody},
+    },
     prelude::*,
     util::MultiRead,
 };
@@ -82,13 +85,21 @@ enum LinkAttrClass {
     PARENT_DEV_BUS_NAME = 57,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum LinkAttr {
     Name(CString),
     Mtu(u32),
     TxqLen(u32),
     LinkMode(u8),
+    NetNsPid(u32),
+    NetNsFd(u32),
     ExtMask(RtExtFilter),
+    /// The index of the bridge that the interface is attached to.
+    Master(u32),
+    /// The index of the interface that the interface is linked to (e.g., the veth peer).
+    Link(u32),
+    /// The payload of the nested [`LinkInfoAttr`]s.
+    LinkInfo(Vec<u8>),
 }
 
 impl LinkAttr {
@@ -98,7 +109,12 @@ impl LinkAttr {
             LinkAttr::Mtu(_) => LinkAttrClass::MTU,
             LinkAttr::TxqLen(_) => LinkAttrClass::TXQLEN,
             LinkAttr::LinkMode(_) => LinkAttrClass::LINKMODE,
+            LinkAttr::NetNsPid(_) => LinkAttrClass::NET_NS_PID,
+            LinkAttr::NetNsFd(_) => LinkAttrClass::NET_NS_FD,
             LinkAttr::ExtMask(_) => LinkAttrClass::EXT_MASK,
+            LinkAttr::Master(_) => LinkAttrClass::MASTER,
+            LinkAttr::Link(_) => LinkAttrClass::LINK,
+            LinkAttr::LinkInfo(_) => LinkAttrClass::LINKINFO,
         }
     }
 }
@@ -114,7 +130,12 @@ impl Attribute for LinkAttr {
             LinkAttr::Mtu(mtu) => mtu.as_bytes(),
             LinkAttr::TxqLen(txq_len) => txq_len.as_bytes(),
             LinkAttr::LinkMode(link_mode) => link_mode.as_bytes(),
+            LinkAttr::NetNsPid(pid) => pid.as_bytes(),
+            LinkAttr::NetNsFd(fd) => fd.as_bytes(),
             LinkAttr::ExtMask(ext_filter) => ext_filter.as_bytes(),
+            LinkAttr::Master(index) => index.as_bytes(),
+            LinkAttr::Link(index) => index.as_bytes(),
+            LinkAttr::LinkInfo(payload) => payload.as_slice(),
         }
     }
 
@@ -150,17 +171,26 @@ impl Attribute for LinkAttr {
             (LinkAttrClass::MTU, 4) => Self::Mtu(reader.read_val_opt::<u32>()?.unwrap()),
             (LinkAttrClass::TXQLEN, 4) => Self::TxqLen(reader.read_val_opt::<u32>()?.unwrap()),
             (LinkAttrClass::LINKMODE, 1) => Self::LinkMode(reader.read_val_opt::<u8>()?.unwrap()),
+            (LinkAttrClass::NET_NS_PID, 4) => {
+                Self::NetNsPid(reader.read_val_opt::<u32>()?.unwrap())
+            }
+            (LinkAttrClass::NET_NS_FD, 4) => Self::NetNsFd(reader.read_val_opt::<u32>()?.unwrap()),
             (LinkAttrClass::EXT_MASK, 4) => {
                 const { assert!(size_of::<RtExtFilter>() == 4) };
                 Self::ExtMask(reader.read_val_opt::<RtExtFilter>()?.unwrap())
             }
+            (LinkAttrClass::MASTER, 4) => Self::Master(reader.read_val_opt::<u32>()?.unwrap()),
+            (LinkAttrClass::LINKINFO, _) => Self::LinkInfo(read_payload(reader, payload_len)?),
 
             (
                 LinkAttrClass::IFNAME
                 | LinkAttrClass::MTU
                 | LinkAttrClass::TXQLEN
                 | LinkAttrClass::LINKMODE
-                | LinkAttrClass::EXT_MASK,
+                | LinkAttrClass::NET_NS_PID
+                | LinkAttrClass::NET_NS_FD
+                | LinkAttrClass::EXT_MASK
+                | LinkAttrClass::MASTER,
                 _,
             ) => {
                 warn!("link attribute `{:?}` contains invalid payload", class);
@@ -186,6 +216,170 @@ impl Attribute for LinkAttr {
     }
 }
 
+/// Link information attributes, which are nested in [`LinkAttr::LinkInfo`].
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/if_link.h>.
+#[expect(non_camel_case_types)]
+#[expect(clippy::upper_case_acronyms)]
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+enum LinkInfoAttrClass {
+    UNSPEC = 0,
+    KIND = 1,
+    DATA = 2,
+    XSTATS = 3,
+    SLAVE_KIND = 4,
+    SLAVE_DATA = 5,
+}
+
+#[derive(Clone, Debug)]
+pub enum LinkInfoAttr {
+    /// The kind of the link (e.g., "veth" or "bridge").
+    Kind(CString),
+    /// The kind-specific payload, whose format depends on [`LinkInfoAttr::Kind`].
+    Data(Vec<u8>),
+}
+
+impl LinkInfoAttr {
+    fn class(&self) -> LinkInfoAttrClass {
+        match self {
+            LinkInfoAttr::Kind(_) => LinkInfoAttrClass::KIND,
+            LinkInfoAttr::Data(_) => LinkInfoAttrClass::DATA,
+        }
+    }
+}
+
+impl Attribute for LinkInfoAttr {
+    fn type_(&self) -> u16 {
+        self.class() as u16
+    }
+
+    fn payload_as_bytes(&self) -> &[u8] {
+        match self {
+            LinkInfoAttr::Kind(kind) => kind.as_bytes_with_nul(),
+            LinkInfoAttr::Data(payload) => payload.as_slice(),
+        }
+    }
+
+    fn read_from(header: &CAttrHeader, reader: &mut dyn MultiRead) -> Result<ContinueRead<Self>>
+    where
+        Self: Sized,
+    {
+        let payload_len = header.payload_len();
+
+        let Ok(class) = LinkInfoAttrClass::try_from(header.type_()) else {
+            reader.skip_some(payload_len);
+            return Ok(ContinueRead::Skipped);
+        };
+
+        let res = match (class, payload_len) {
+            (LinkInfoAttrClass::KIND, 1..) => {
+                let (kind, kind_len) = reader.read_cstring_until_end(payload_len)?;
+                if kind_len != payload_len {
+                    reader.skip_some(payload_len - kind_len);
+                }
+                Self::Kind(kind)
+            }
+            (LinkInfoAttrClass::DATA, _) => Self::Data(read_payload(reader, payload_len)?),
+
+            (LinkInfoAttrClass::KIND, _) => {
+                warn!("link info attribute `{:?}` contains invalid payload", class);
+                reader.skip_some(payload_len);
+                return Ok(ContinueRead::skipped_with_error(
+                    Errno::EINVAL,
+                    "the link info attribute is invalid",
+                ));
+            }
+
+            (_, _) => {
+                warn!("link info attribute `{:?}` is not supported", class);
+                reader.skip_some(payload_len);
+                return Ok(ContinueRead::Skipped);
+            }
+        };
+
+        Ok(ContinueRead::Parsed(res))
+    }
+}
+
+/// veth attributes, which are nested in [`LinkInfoAttr::Data`].
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/veth.h>.
+#[expect(non_camel_case_types)]
+#[expect(clippy::upper_case_acronyms)]
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+enum VethInfoAttrClass {
+    UNSPEC = 0,
+    PEER = 1,
+}
+
+#[derive(Clone, Debug)]
+pub enum VethInfoAttr {
+    /// The `ifinfomsg` and the link attributes of the peer.
+    Peer(LinkSegmentBody, Vec<LinkAttr>),
+}
+
+impl Attribute for VethInfoAttr {
+    fn type_(&self) -> u16 {
+        match self {
+            VethInfoAttr::Peer(..) => VethInfoAttrClass::PEER as u16,
+        }
+    }
+
+    fn payload_as_bytes(&self) -> &[u8] {
+        unreachable!("veth attributes should not be written to user space")
+    }
+
+    fn read_from(header: &CAttrHeader, reader: &mut dyn MultiRead) -> Result<ContinueRead<Self>>
+    where
+        Self: Sized,
+    {
+        let payload_len = header.payload_len();
+
+        let Ok(class) = VethInfoAttrClass::try_from(header.type_()) else {
+            reader.skip_some(payload_len);
+            return Ok(ContinueRead::Skipped);
+        };
+
+        let res = match (class, payload_len) {
+            (VethInfoAttrClass::PEER, len) if len >= size_of::<CIfinfoMsg>() => {
+                let c_body = reader.read_val_opt::<CIfinfoMsg>()?.unwrap();
+                let attrs_len = payload_len - size_of::<CIfinfoMsg>();
+                let Ok(body) = LinkSegmentBody::try_from(c_body) else {
+                    reader.skip_some(attrs_len);
+                    return Ok(ContinueRead::skipped_with_error(
+                        Errno::EINVAL,
+                        "the veth peer information is invalid",
+                    ));
+                };
+                match LinkAttr::read_all_from(reader, attrs_len)? {
+                    ContinueRead::Parsed(attrs) => Self::Peer(body, attrs),
+                    ContinueRead::Skipped => Self::Peer(body, Vec::new()),
+                    ContinueRead::SkippedErr(err) => return Ok(ContinueRead::SkippedErr(err)),
+                }
+            }
+
+            (VethInfoAttrClass::PEER, _) => {
+                warn!("veth attribute `{:?}` contains invalid payload", class);
+                reader.skip_some(payload_len);
+                return Ok(ContinueRead::skipped_with_error(
+                    Errno::EINVAL,
+                    "the veth attribute is invalid",
+                ));
+            }
+
+            (_, _) => {
+                warn!("veth attribute `{:?}` is not supported", class);
+                reader.skip_some(payload_len);
+                return Ok(ContinueRead::Skipped);
+            }
+        };
+
+        Ok(ContinueRead::Parsed(res))
+    }
+}
+
 bitflags! {
     /// New extended info filters for [`NlLinkAttr::ExtMask`].
     ///
@@ -1,7 +1,65 @@
 // SPDX-License-Identifier: MPL-2.0
 
+use aster_bigtcp::wire::{IpAddress, Ipv4Address, Ipv6Address};
+
+use crate::{prelude::*, util::MultiRead};
+
 pub mod addr;
 pub mod link;
+pub mod route;
 
 /// The size limit for interface names.
 const IFNAME_SIZE: usize = 16;
+
+/// An IPv4 or IPv6 address in its binary form.
+///
+/// The family of the address is determined by the payload length of the attribute.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum IpAddrBytes {
+    V4([u8; 4]),
+    V6([u8; 16]),
+}
+
+impl IpAddrBytes {
+    /// Reads the address from the attribute payload.
+    ///
+    /// Returns `None` if the payload length is neither 4 nor 16. In that case, the payload is
+    /// still skipped.
+    fn read_from(reader: &mut dyn MultiRead, payload_len: usize) -> Result<Option<Self>> {
+        let addr = match payload_len {
+            4 => Self::V4(reader.read_val_opt::<[u8; 4]>()?.unwrap()),
+            16 => Self::V6(reader.read_val_opt::<[u8; 16]>()?.unwrap()),
+            _ => {
+                reader.skip_some(payload_len);
+                return Ok(None);
+            }
+        };
+
+        Ok(Some(addr))
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Self::V4(bytes) => bytes,
+            Self::V6(bytes) => bytes,
+        }
+    }
+}
+
+impl From<IpAddress> for IpAddrBytes {
+    fn from(value: IpAddress) -> Self {
+        match value {
+            IpAddress::Ipv4(ipv4_addr) => Self::V4(ipv4_addr.octets()),
+            IpAddress::Ipv6(ipv6_addr) => Self::V6(ipv6_addr.octets()),
+        }
+    }
+}
+
+impl From<IpAddrBytes> for IpAddress {
+    fn from(value: IpAddrBytes) -> Self {
+        match value {
+            IpAddrBytes::V4(bytes) => IpAddress::Ipv4(Ipv4Address::from(bytes)),
+            IpAddrBytes::V6(bytes) => IpAddress::Ipv6(Ipv6Address::from(bytes)),
+        }
+    }
+}
@@ -8,14 +8,22 @@
 mod attr;
 mod segment;
 
-pub(super) use attr::{addr::AddrAttr, link::LinkAttr};
+pub(super) use attr::{
+    IpAddrBytes,
+    addr::AddrAttr,
+    link::{LinkAttr, LinkInfoAttr, VethInfoAttr},
+    route::RouteAttr,
+};
 pub(super) use segment::{
     RtnlSegment,
     addr::{AddrMessageFlags, AddrSegment, AddrSegmentBody, RtScope},
     link::{LinkSegment, LinkSegmentBody},
+    route::{RT_TABLE_MAIN, RT_TABLE_UNSPEC, RouteSegment, RouteSegmentBody, RouteType},
 };
 
-use crate::net::socket::netlink::message::Message;
+use crate::net::socket::netlink::{message::Message, table::MulticastMessage};
 
 /// A netlink route message.
 pub(in crate::net::socket::netlink) type RtnlMessage = Message<RtnlSegment>;
+
+impl MulticastMessage for RtnlMessage {}
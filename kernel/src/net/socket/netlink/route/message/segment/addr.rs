@@ -105,7 +105,7 @@ bitflags! {
 /// Reference: <https://elixir.bootlin.com/linux/v6.13/source/include/uapi/linux/rtnetlink.h#L320>.
 #[expect(clippy::upper_case_acronyms)]
 #[repr(u8)]
-#[derive(Clone, Copy, Debug, TryFromInt)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
 pub enum RtScope {
     UNIVERSE = 0,
     // User defined values
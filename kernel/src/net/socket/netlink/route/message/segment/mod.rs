@@ -38,6 +38,7 @@ pub mod route;
 
 use addr::AddrSegment;
 use link::LinkSegment;
+use route::RouteSegment;
 
 use crate::{
     net::socket::netlink::message::{
@@ -48,12 +49,18 @@ use crate::{
 };
 
 /// The netlink route segment, which is the basic unit of a netlink route message.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum RtnlSegment {
     NewLink(LinkSegment),
+    DelLink(LinkSegment),
     GetLink(LinkSegment),
+    SetLink(LinkSegment),
     NewAddr(AddrSegment),
+    DelAddr(AddrSegment),
     GetAddr(AddrSegment),
+    NewRoute(RouteSegment),
+    DelRoute(RouteSegment),
+    GetRoute(RouteSegment),
     Done(DoneSegment),
     Error(ErrorSegment),
 }
@@ -61,12 +68,16 @@ pub enum RtnlSegment {
 impl ProtocolSegment for RtnlSegment {
     fn header(&self) -> &CMsgSegHdr {
         match self {
-            RtnlSegment::NewLink(link_segment) | RtnlSegment::GetLink(link_segment) => {
-                link_segment.header()
-            }
-            RtnlSegment::NewAddr(addr_segment) | RtnlSegment::GetAddr(addr_segment) => {
-                addr_segment.header()
-            }
+            RtnlSegment::NewLink(link_segment)
+            | RtnlSegment::DelLink(link_segment)
+            | RtnlSegment::GetLink(link_segment)
+            | RtnlSegment::SetLink(link_segment) => link_segment.header(),
+            RtnlSegment::NewAddr(addr_segment)
+            | RtnlSegment::DelAddr(addr_segment)
+            | RtnlSegment::GetAddr(addr_segment) => addr_segment.header(),
+            RtnlSegment::NewRoute(route_segment)
+            | RtnlSegment::DelRoute(route_segment)
+            | RtnlSegment::GetRoute(route_segment) => route_segment.header(),
             RtnlSegment::Done(done_segment) => done_segment.header(),
             RtnlSegment::Error(error_segment) => error_segment.header(),
         }
@@ -74,12 +85,16 @@ impl ProtocolSegment for RtnlSegment {
 
     fn header_mut(&mut self) -> &mut CMsgSegHdr {
         match self {
-            RtnlSegment::NewLink(link_segment) | RtnlSegment::GetLink(link_segment) => {
-                link_segment.header_mut()
-            }
-            RtnlSegment::NewAddr(addr_segment) | RtnlSegment::GetAddr(addr_segment) => {
-                addr_segment.header_mut()
-            }
+            RtnlSegment::NewLink(link_segment)
+            | RtnlSegment::DelLink(link_segment)
+            | RtnlSegment::GetLink(link_segment)
+            | RtnlSegment::SetLink(link_segment) => link_segment.header_mut(),
+            RtnlSegment::NewAddr(addr_segment)
+            | RtnlSegment::DelAddr(addr_segment)
+            | RtnlSegment::GetAddr(addr_segment) => addr_segment.header_mut(),
+            RtnlSegment::NewRoute(route_segment)
+            | RtnlSegment::DelRoute(route_segment)
+            | RtnlSegment::GetRoute(route_segment) => route_segment.header_mut(),
             RtnlSegment::Done(done_segment) => done_segment.header_mut(),
             RtnlSegment::Error(error_segment) => error_segment.header_mut(),
         }
@@ -91,12 +106,36 @@ impl ProtocolSegment for RtnlSegment {
             .ok_or_else(|| Error::with_message(Errno::EINVAL, "the reader length is too small"))?;
 
         let segment = match CSegmentType::try_from(header.type_) {
+            Ok(CSegmentType::NEWLINK) => {
+                LinkSegment::read_from(&header, reader)?.map(RtnlSegment::NewLink)
+            }
+            Ok(CSegmentType::DELLINK) => {
+                LinkSegment::read_from(&header, reader)?.map(RtnlSegment::DelLink)
+            }
             Ok(CSegmentType::GETLINK) => {
                 LinkSegment::read_from(&header, reader)?.map(RtnlSegment::GetLink)
             }
+            Ok(CSegmentType::SETLINK) => {
+                LinkSegment::read_from(&header, reader)?.map(RtnlSegment::SetLink)
+            }
+            Ok(CSegmentType::NEWADDR) => {
+                AddrSegment::read_from(&header, reader)?.map(RtnlSegment::NewAddr)
+            }
+            Ok(CSegmentType::DELADDR) => {
+                AddrSegment::read_from(&header, reader)?.map(RtnlSegment::DelAddr)
+            }
             Ok(CSegmentType::GETADDR) => {
                 AddrSegment::read_from(&header, reader)?.map(RtnlSegment::GetAddr)
             }
+            Ok(CSegmentType::NEWROUTE) => {
+                RouteSegment::read_from(&header, reader)?.map(RtnlSegment::NewRoute)
+            }
+            Ok(CSegmentType::DELROUTE) => {
+                RouteSegment::read_from(&header, reader)?.map(RtnlSegment::DelRoute)
+            }
+            Ok(CSegmentType::GETROUTE) => {
+                RouteSegment::read_from(&header, reader)?.map(RtnlSegment::GetRoute)
+            }
             _ => {
                 let payload_len = header.calc_payload_len_with_padding(reader)?;
                 reader.skip_some(payload_len);
@@ -112,12 +151,22 @@ impl ProtocolSegment for RtnlSegment {
 
     fn write_to(&self, writer: &mut dyn MultiWrite) -> Result<()> {
         match self {
-            RtnlSegment::NewLink(link_segment) => link_segment.write_to(writer)?,
-            RtnlSegment::NewAddr(addr_segment) => addr_segment.write_to(writer)?,
+            RtnlSegment::NewLink(link_segment) | RtnlSegment::DelLink(link_segment) => {
+                link_segment.write_to(writer)?
+            }
+            RtnlSegment::NewAddr(addr_segment) | RtnlSegment::DelAddr(addr_segment) => {
+                addr_segment.write_to(writer)?
+            }
+            RtnlSegment::NewRoute(route_segment) | RtnlSegment::DelRoute(route_segment) => {
+                route_segment.write_to(writer)?
+            }
             RtnlSegment::Done(done_segment) => done_segment.write_to(writer)?,
             RtnlSegment::Error(error_segment) => error_segment.write_to(writer)?,
-            RtnlSegment::GetAddr(_) | RtnlSegment::GetLink(_) => {
-                unreachable!("kernel should not write get requests to user space");
+            RtnlSegment::GetAddr(_)
+            | RtnlSegment::GetRoute(_)
+            | RtnlSegment::GetLink(_)
+            | RtnlSegment::SetLink(_) => {
+                unreachable!("kernel should not write get or set requests to user space");
             }
         }
         Ok(())
@@ -2,6 +2,7 @@
 
 //! Netlink Route Socket.
 
+pub(super) use kernel::{notify_del_addr, notify_del_link, notify_new_link};
 pub(super) use message::RtnlMessage;
 
 use crate::net::socket::netlink::{common::NetlinkSocket, table::NetlinkRouteProtocol};
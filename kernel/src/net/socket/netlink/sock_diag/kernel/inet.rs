@@ -0,0 +1,168 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Handling of the requests for TCP and UDP sockets.
+//!
+//! Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/net/ipv4/inet_diag.c>
+
+use aster_bigtcp::wire::IpAddress;
+
+use super::util::{finish_dump, is_dump, matches_states, response_header};
+use crate::{
+    net::{
+        net_ns::NetNamespace,
+        socket::{
+            ip::{InetSocketEntry, tcp_socket_entries, udp_socket_entries},
+            netlink::{
+                message::CMsgSegHdr,
+                sock_diag::message::{
+                    INET_DIAG_NOCOOKIE, InetDiagAttr, InetDiagAttrClass, InetDiagMsgBody,
+                    InetDiagMsgSegment, InetDiagReqSegment, InetDiagSockId, SockDiagSegment,
+                },
+            },
+        },
+    },
+    prelude::*,
+    util::net::{CSocketAddrFamily, Protocol},
+};
+
+pub(super) fn do_get_sockets(
+    request_segment: &InetDiagReqSegment,
+    net_ns: &NetNamespace,
+) -> Result<Vec<SockDiagSegment>> {
+    let request_header = request_segment.header();
+    let request = request_segment.body();
+
+    let dump_all = is_dump(request_header);
+
+    let entries = match Protocol::try_from(i32::from(request.protocol)) {
+        Ok(Protocol::IPPROTO_TCP) => tcp_socket_entries(net_ns),
+        Ok(Protocol::IPPROTO_UDP) => udp_socket_entries(net_ns),
+        // Like Linux, dumping the sockets of an unsupported protocol reports nothing.
+        _ if dump_all => Vec::new(),
+        _ => return_errno_with_message!(Errno::ENOENT, "the protocol is not supported"),
+    };
+    let is_ipv6 = request.family == CSocketAddrFamily::AF_INET6 as u8;
+    let mut entries = entries
+        .into_iter()
+        .filter(|entry| entry.is_ipv6() == is_ipv6);
+
+    if !dump_all {
+        // Like Linux, the states are not checked when looking up a single socket.
+        let Some(entry) = entries.find(|entry| matches_id(entry, &request.id)) else {
+            return_errno_with_message!(Errno::ENOENT, "the socket does not exist");
+        };
+        if request.id.cookie != INET_DIAG_NOCOOKIE && request.id.cookie != cookie_of(&entry) {
+            return_errno_with_message!(Errno::ESTALE, "the socket has been replaced");
+        }
+        return Ok(vec![new_inet_segment(request_header, request.ext, &entry)]);
+    }
+
+    let mut response_segments: Vec<SockDiagSegment> = entries
+        .filter(|entry| matches_states(request.states, entry.state))
+        .map(|entry| new_inet_segment(request_header, request.ext, &entry))
+        .collect();
+
+    finish_dump(request_header, &mut response_segments);
+
+    Ok(response_segments)
+}
+
+fn new_inet_segment(
+    request_header: &CMsgSegHdr,
+    ext: u8,
+    entry: &InetSocketEntry,
+) -> SockDiagSegment {
+    let family = if entry.is_ipv6() {
+        CSocketAddrFamily::AF_INET6
+    } else {
+        CSocketAddrFamily::AF_INET
+    };
+    let (ino, uid) = entry
+        .inode
+        .map_or((0, 0), |inode| (inode.ino(), inode.uid().into()));
+
+    // TODO: Report the pending timers.
+    let body = InetDiagMsgBody {
+        family: family as u8,
+        state: entry.state,
+        timer: 0,
+        retrans: entry.tcp_info.map_or(0, |tcp_info| tcp_info.retransmits()),
+        id: sock_id_of(entry),
+        expires: 0,
+        rqueue: entry.recv_queue as u32,
+        wqueue: entry.send_queue as u32,
+        uid,
+        inode: ino as u32,
+    };
+
+    let mut attrs = Vec::new();
+    if ext & InetDiagAttrClass::INFO.ext_bit() != 0
+        && let Some(tcp_info) = entry.tcp_info
+    {
+        attrs.push(InetDiagAttr::Info(tcp_info));
+    }
+    if ext & InetDiagAttrClass::CONG.ext_bit() != 0
+        && let Some(congestion) = entry.congestion
+    {
+        attrs.push(InetDiagAttr::Cong(CString::new(congestion.name()).unwrap()));
+    }
+
+    let segment = InetDiagMsgSegment::new(response_header(request_header), body, attrs);
+    SockDiagSegment::InetResponse(segment)
+}
+
+fn sock_id_of(entry: &InetSocketEntry) -> InetDiagSockId {
+    InetDiagSockId {
+        sport: entry.local_endpoint.port,
+        dport: entry.remote_endpoint.port,
+        src: addr_to_bytes(&entry.local_endpoint.addr),
+        dst: addr_to_bytes(&entry.remote_endpoint.addr),
+        // TODO: Report the interface if the socket is bound to a device.
+        if_: 0,
+        cookie: cookie_of(entry),
+    }
+}
+
+/// Returns the cookie of the socket.
+///
+/// The inode number is used as the cookie because it uniquely identifies the socket.
+fn cookie_of(entry: &InetSocketEntry) -> u64 {
+    entry.inode.map_or(0, |inode| inode.ino())
+}
+
+/// Returns whether the socket matches the identifier in a request that looks up a single socket.
+///
+/// The source is the local endpoint, and the destination is the remote endpoint. Like Linux, a
+/// socket bound to the unspecified address matches any local address, and an unconnected socket
+/// matches any remote endpoint.
+fn matches_id(entry: &InetSocketEntry, id: &InetDiagSockId) -> bool {
+    let local = &entry.local_endpoint;
+    let matches_local = local.port == id.sport
+        && (local.addr.is_unspecified() || addr_matches(&local.addr, &id.src));
+
+    let remote = &entry.remote_endpoint;
+    let matches_remote = remote.addr.is_unspecified()
+        || (remote.port == id.dport && addr_matches(&remote.addr, &id.dst));
+
+    matches_local && matches_remote
+}
+
+/// Returns whether the address equals the one in the format of [`InetDiagSockId`].
+fn addr_matches(addr: &IpAddress, bytes: &[u8; 16]) -> bool {
+    match addr {
+        IpAddress::Ipv4(addr) => addr.octets() == bytes[..4],
+        IpAddress::Ipv6(addr) => addr.octets() == *bytes,
+    }
+}
+
+/// Converts the address to the format of [`InetDiagSockId`].
+///
+/// IPv4 addresses occupy the first four bytes.
+fn addr_to_bytes(addr: &IpAddress) -> [u8; 16] {
+    let mut bytes = [0u8; 16];
+    match addr {
+        IpAddress::Ipv4(addr) => bytes[..4].copy_from_slice(&addr.octets()),
+        IpAddress::Ipv6(addr) => bytes.copy_from_slice(&addr.octets()),
+    }
+    bytes
+}
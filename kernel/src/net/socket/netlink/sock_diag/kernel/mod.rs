@@ -0,0 +1,98 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! This module defines the kernel socket,
+//! which is responsible for handling requests from user space.
+//!
+//! Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/net/core/sock_diag.c>
+
+use core::marker::PhantomData;
+
+use super::message::{SockDiagMessage, SockDiagSegment};
+use crate::{
+    net::{
+        net_ns::NetNamespace,
+        socket::netlink::{
+            addr::PortNum,
+            message::{ErrorSegment, ProtocolSegment},
+            table::{NetlinkSockDiagProtocol, SupportedNetlinkProtocol},
+        },
+    },
+    prelude::*,
+};
+
+mod inet;
+mod unix;
+mod util;
+
+pub(super) struct NetlinkSockDiagKernelSocket {
+    _private: PhantomData<()>,
+}
+
+impl NetlinkSockDiagKernelSocket {
+    const fn new() -> Self {
+        Self {
+            _private: PhantomData,
+        }
+    }
+
+    pub(super) fn handle_request(
+        &self,
+        net_ns: &Arc<NetNamespace>,
+        request: &SockDiagSegment,
+        dst_port: PortNum,
+    ) {
+        debug!("netlink sock_diag request: {:?}", request);
+
+        let request_header = request.header();
+
+        // Like Linux, no capabilities are required to query the sockets.
+        let response_segments = match request {
+            SockDiagSegment::InetRequest(request_segment) => {
+                inet::do_get_sockets(request_segment, net_ns)
+            }
+            SockDiagSegment::UnixRequest(request_segment) => {
+                unix::do_get_sockets(request_segment, net_ns)
+            }
+            _ => Err(Error::with_message(
+                Errno::EOPNOTSUPP,
+                "the netlink sock_diag request is not supported",
+            )),
+        };
+
+        let response = match response_segments {
+            Ok(segments) => SockDiagMessage::new(segments),
+            Err(error) => {
+                let err_segment = ErrorSegment::new_from_request(request_header, Some(error));
+                self.report_error(net_ns, err_segment, dst_port);
+                return;
+            }
+        };
+
+        debug!("netlink sock_diag response: {:?}", response);
+
+        NetlinkSockDiagProtocol::unicast(net_ns, dst_port, response).unwrap();
+    }
+
+    pub(super) fn report_error(
+        &self,
+        net_ns: &NetNamespace,
+        err_segment: ErrorSegment,
+        dst_port: PortNum,
+    ) {
+        let response = SockDiagMessage::new(vec![SockDiagSegment::Error(err_segment)]);
+
+        debug!("netlink sock_diag error: {:?}", response);
+
+        NetlinkSockDiagProtocol::unicast(net_ns, dst_port, response).unwrap();
+    }
+}
+
+/// The kernel socket of `NETLINK_SOCK_DIAG`.
+///
+/// Each request only reads the socket tables, which are filtered by the network namespace of the
+/// requesting socket. So one kernel socket serves all network namespaces.
+static NETLINK_SOCK_DIAG_KERNEL: NetlinkSockDiagKernelSocket = NetlinkSockDiagKernelSocket::new();
+
+pub(super) fn get_netlink_sock_diag_kernel() -> &'static NetlinkSockDiagKernelSocket {
+    &NETLINK_SOCK_DIAG_KERNEL
+}
@@ -0,0 +1,117 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Handling of the requests for UNIX domain sockets.
+//!
+//! Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/net/unix/diag.c>
+
+use super::util::{finish_dump, is_dump, matches_states, response_header};
+use crate::{
+    net::{
+        net_ns::NetNamespace,
+        socket::{
+            netlink::{
+                message::CMsgSegHdr,
+                sock_diag::message::{
+                    INET_DIAG_NOCOOKIE, SockDiagSegment, UnixDiagAttr, UnixDiagMsgBody,
+                    UnixDiagMsgSegment, UnixDiagReqSegment, UnixDiagShow,
+                },
+            },
+            unix::{UnixSocketAddr, UnixSocketEntry, unix_socket_entries},
+        },
+    },
+    prelude::*,
+};
+
+pub(super) fn do_get_sockets(
+    request_segment: &UnixDiagReqSegment,
+    net_ns: &NetNamespace,
+) -> Result<Vec<SockDiagSegment>> {
+    let request_header = request_segment.header();
+    let request = request_segment.body();
+
+    let entries = unix_socket_entries(net_ns);
+
+    if !is_dump(request_header) {
+        if request.ino == 0 {
+            return_errno_with_message!(Errno::EINVAL, "the inode number is not specified");
+        }
+
+        // Like Linux, the states are not checked when looking up a single socket.
+        let Some(entry) = entries
+            .iter()
+            .find(|entry| entry.ino() == u64::from(request.ino))
+        else {
+            return_errno_with_message!(Errno::ENOENT, "the socket does not exist");
+        };
+        if request.cookie != INET_DIAG_NOCOOKIE && request.cookie != entry.ino() {
+            return_errno_with_message!(Errno::ESTALE, "the socket has been replaced");
+        }
+        return Ok(vec![new_unix_segment(request_header, request.show, entry)]);
+    }
+
+    let mut response_segments: Vec<SockDiagSegment> = entries
+        .iter()
+        .filter(|entry| matches_states(request.states, entry.state))
+        .map(|entry| new_unix_segment(request_header, request.show, entry))
+        .collect();
+
+    finish_dump(request_header, &mut response_segments);
+
+    Ok(response_segments)
+}
+
+fn new_unix_segment(
+    request_header: &CMsgSegHdr,
+    show: UnixDiagShow,
+    entry: &UnixSocketEntry,
+) -> SockDiagSegment {
+    // The inode number is used as the cookie because it uniquely identifies the socket.
+    let body = UnixDiagMsgBody {
+        type_: entry.sock_type,
+        state: entry.state,
+        ino: entry.ino() as u32,
+        cookie: entry.ino(),
+    };
+
+    let mut attrs = Vec::new();
+    if show.contains(UnixDiagShow::NAME)
+        && let Some(name) = name_of(&entry.addr)
+    {
+        attrs.push(UnixDiagAttr::Name(name));
+    }
+    if show.contains(UnixDiagShow::PEER)
+        && let Some(peer_ino) = entry.peer_ino
+    {
+        attrs.push(UnixDiagAttr::Peer(peer_ino as u32));
+    }
+    if show.contains(UnixDiagShow::RQLEN) {
+        let lens = [entry.recv_queue as u32, entry.send_queue as u32];
+        attrs.push(UnixDiagAttr::RqLen(lens));
+    }
+    if show.contains(UnixDiagShow::UID) {
+        attrs.push(UnixDiagAttr::Uid(entry.uid().into()));
+    }
+    // TODO: Support other information, e.g., `UDIAG_SHOW_VFS` and `UDIAG_SHOW_ICONS`.
+
+    let segment = UnixDiagMsgSegment::new(response_header(request_header), body, attrs);
+    SockDiagSegment::UnixResponse(segment)
+}
+
+/// Returns the bound address in the format of `sun_path`, or `None` if the socket is unnamed.
+///
+/// Like Linux, a path name ends with a null byte, and an abstract name starts with a null byte.
+fn name_of(addr: &UnixSocketAddr) -> Option<Vec<u8>> {
+    let mut name = Vec::new();
+    match addr {
+        UnixSocketAddr::Unnamed => return None,
+        UnixSocketAddr::Path(path) => {
+            name.extend_from_slice(path.as_bytes());
+            name.push(0);
+        }
+        UnixSocketAddr::Abstract(abstract_name) => {
+            name.push(0);
+            name.extend_from_slice(abstract_name);
+        }
+    }
+    Some(name)
+}
@@ -0,0 +1,50 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::{
+    net::socket::netlink::{
+        message::{CMsgSegHdr, DoneSegment, GetRequestFlags, ProtocolSegment, SegHdrCommonFlags},
+        sock_diag::message::{SOCK_DIAG_BY_FAMILY, SockDiagSegment},
+    },
+    prelude::*,
+};
+
+/// Returns whether the request asks for all the matching sockets.
+///
+/// Otherwise, the request looks up a single socket.
+pub(super) fn is_dump(request_header: &CMsgSegHdr) -> bool {
+    let flags = GetRequestFlags::from_bits_truncate(request_header.flags);
+    flags.contains(GetRequestFlags::DUMP)
+}
+
+/// Returns whether the state is one of the states in the bitmap of the request.
+pub(super) fn matches_states(states: u32, state: u8) -> bool {
+    1u32.checked_shl(u32::from(state))
+        .is_some_and(|bit| states & bit != 0)
+}
+
+/// Returns the header of a response segment to the request.
+pub(super) fn response_header(request_header: &CMsgSegHdr) -> CMsgSegHdr {
+    CMsgSegHdr {
+        len: 0,
+        type_: SOCK_DIAG_BY_FAMILY,
+        flags: SegHdrCommonFlags::empty().bits(),
+        seq: request_header.seq,
+        pid: request_header.pid,
+    }
+}
+
+/// Finishes a response message to a dump request.
+pub(super) fn finish_dump(
+    request_header: &CMsgSegHdr,
+    response_segments: &mut Vec<SockDiagSegment>,
+) {
+    let done_segment = DoneSegment::new_from_request(request_header, None);
+    response_segments.push(SockDiagSegment::Done(done_segment));
+
+    for segment in response_segments.iter_mut() {
+        let header = segment.header_mut();
+        let mut flags = SegHdrCommonFlags::from_bits_truncate(header.flags);
+        flags |= SegHdrCommonFlags::MULTI;
+        header.flags = flags.bits();
+    }
+}
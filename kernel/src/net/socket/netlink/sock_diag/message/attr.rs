@@ -0,0 +1,149 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Netlink sock_diag attributes.
+//!
+//! The attributes only appear in the responses sent from the kernel. The attributes of the
+//! requests belong to different classes and are ignored.
+
+use crate::{
+    net::socket::{
+        ip::stream_options::TcpInfo,
+        netlink::message::{Attribute, CAttrHeader, ContinueRead},
+    },
+    prelude::*,
+    util::MultiRead,
+};
+
+/// Attributes of [`CInetDiagMsg`].
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/include/uapi/linux/inet_diag.h#L140>
+///
+/// [`CInetDiagMsg`]: super::segment::CInetDiagMsg
+#[expect(non_camel_case_types)]
+#[expect(clippy::upper_case_acronyms)]
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+pub enum InetDiagAttrClass {
+    NONE = 0,
+    MEMINFO = 1,
+    INFO = 2,
+    VEGASINFO = 3,
+    CONG = 4,
+    TOS = 5,
+    TCLASS = 6,
+    SKMEMINFO = 7,
+    SHUTDOWN = 8,
+}
+
+impl InetDiagAttrClass {
+    /// Returns the bit in the `ext` field of the requests that asks for the attribute.
+    pub fn ext_bit(self) -> u8 {
+        1 << (self as u8 - 1)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum InetDiagAttr {
+    /// The TCP information, i.e., `struct tcp_info`.
+    Info(TcpInfo),
+    /// The name of the congestion control algorithm.
+    Cong(CString),
+}
+
+impl InetDiagAttr {
+    fn class(&self) -> InetDiagAttrClass {
+        match self {
+            InetDiagAttr::Info(_) => InetDiagAttrClass::INFO,
+            InetDiagAttr::Cong(_) => InetDiagAttrClass::CONG,
+        }
+    }
+}
+
+impl Attribute for InetDiagAttr {
+    fn type_(&self) -> u16 {
+        self.class() as u16
+    }
+
+    fn payload_as_bytes(&self) -> &[u8] {
+        match self {
+            InetDiagAttr::Info(info) => info.as_bytes(),
+            InetDiagAttr::Cong(name) => name.as_bytes_with_nul(),
+        }
+    }
+
+    fn read_from(header: &CAttrHeader, reader: &mut dyn MultiRead) -> Result<ContinueRead<Self>>
+    where
+        Self: Sized,
+    {
+        // The attributes are never sent from user space.
+        reader.skip_some(header.payload_len());
+        Ok(ContinueRead::Skipped)
+    }
+}
+
+/// Attributes of [`CUnixDiagMsg`].
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/include/uapi/linux/unix_diag.h#L36>
+///
+/// [`CUnixDiagMsg`]: super::segment::CUnixDiagMsg
+#[expect(non_camel_case_types)]
+#[expect(clippy::upper_case_acronyms)]
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+enum UnixDiagAttrClass {
+    NAME = 0,
+    VFS = 1,
+    PEER = 2,
+    ICONS = 3,
+    RQLEN = 4,
+    MEMINFO = 5,
+    SHUTDOWN = 6,
+    UID = 7,
+}
+
+#[derive(Clone, Debug)]
+pub enum UnixDiagAttr {
+    /// The bound address, in the format of `sun_path`.
+    Name(Vec<u8>),
+    /// The inode number of the peer.
+    Peer(u32),
+    /// The lengths of the receive queue and the send queue, i.e., `struct unix_diag_rqlen`.
+    RqLen([u32; 2]),
+    /// The UID of the owner.
+    Uid(u32),
+}
+
+impl UnixDiagAttr {
+    fn class(&self) -> UnixDiagAttrClass {
+        match self {
+            UnixDiagAttr::Name(_) => UnixDiagAttrClass::NAME,
+            UnixDiagAttr::Peer(_) => UnixDiagAttrClass::PEER,
+            UnixDiagAttr::RqLen(_) => UnixDiagAttrClass::RQLEN,
+            UnixDiagAttr::Uid(_) => UnixDiagAttrClass::UID,
+        }
+    }
+}
+
+impl Attribute for UnixDiagAttr {
+    fn type_(&self) -> u16 {
+        self.class() as u16
+    }
+
+    fn payload_as_bytes(&self) -> &[u8] {
+        match self {
+            UnixDiagAttr::Name(name) => name.as_slice(),
+            UnixDiagAttr::Peer(ino) => ino.as_bytes(),
+            UnixDiagAttr::RqLen(lens) => lens.as_bytes(),
+            UnixDiagAttr::Uid(uid) => uid.as_bytes(),
+        }
+    }
+
+    fn read_from(header: &CAttrHeader, reader: &mut dyn MultiRead) -> Result<ContinueRead<Self>>
+    where
+        Self: Sized,
+    {
+        // The attributes are never sent from user space.
+        reader.skip_some(header.payload_len());
+        Ok(ContinueRead::Skipped)
+    }
+}
@@ -0,0 +1,21 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Netlink message types for the netlink sock_diag protocol.
+//!
+//! This module defines how to interpret messages sent from user space and how to write
+//! kernel messages back to user space.
+
+mod attr;
+mod segment;
+
+pub(super) use attr::{InetDiagAttr, InetDiagAttrClass, UnixDiagAttr};
+pub(super) use segment::{
+    INET_DIAG_NOCOOKIE, InetDiagMsgBody, InetDiagMsgSegment, InetDiagReqSegment, InetDiagSockId,
+    SOCK_DIAG_BY_FAMILY, SockDiagSegment, UnixDiagMsgBody, UnixDiagMsgSegment, UnixDiagReqSegment,
+    UnixDiagShow,
+};
+
+use crate::net::socket::netlink::message::Message;
+
+/// A netlink sock_diag message.
+pub(in crate::net::socket::netlink) type SockDiagMessage = Message<SockDiagSegment>;
@@ -0,0 +1,455 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::attr::{InetDiagAttr, UnixDiagAttr};
+use crate::{
+    net::socket::netlink::message::{
+        CMsgSegHdr, ContinueRead, DoneSegment, ErrorSegment, NoAttr, ProtocolSegment, SegmentBody,
+        SegmentCommon, read_payload,
+    },
+    prelude::*,
+    util::{
+        MultiRead, MultiWrite,
+        net::{CSocketAddrFamily, SockType},
+    },
+};
+
+/// The netlink sock_diag segment, which is the basic unit of a netlink sock_diag message.
+#[derive(Clone, Debug)]
+pub enum SockDiagSegment {
+    /// A request for TCP or UDP sockets.
+    InetRequest(InetDiagReqSegment),
+    /// A request for UNIX domain sockets.
+    UnixRequest(UnixDiagReqSegment),
+    /// A response that reports a TCP or UDP socket.
+    InetResponse(InetDiagMsgSegment),
+    /// A response that reports a UNIX domain socket.
+    UnixResponse(UnixDiagMsgSegment),
+    Done(DoneSegment),
+    Error(ErrorSegment),
+}
+
+// TODO: Support the bytecode filters (i.e., `INET_DIAG_REQ_BYTECODE`) in the attributes of the
+// requests. They are ignored for now, so more sockets than requested may be reported.
+pub type InetDiagReqSegment = SegmentCommon<InetDiagReqBody, NoAttr>;
+pub type UnixDiagReqSegment = SegmentCommon<UnixDiagReqBody, NoAttr>;
+pub type InetDiagMsgSegment = SegmentCommon<InetDiagMsgBody, InetDiagAttr>;
+pub type UnixDiagMsgSegment = SegmentCommon<UnixDiagMsgBody, UnixDiagAttr>;
+
+/// The message type of all requests and responses.
+///
+/// The old message types (i.e., `TCPDIAG_GETSOCK` and `DCCPDIAG_GETSOCK`) are not supported.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/include/uapi/linux/sock_diag.h#L8>
+pub const SOCK_DIAG_BY_FAMILY: u16 = 20;
+
+impl ProtocolSegment for SockDiagSegment {
+    fn header(&self) -> &CMsgSegHdr {
+        match self {
+            Self::InetRequest(segment) => segment.header(),
+            Self::UnixRequest(segment) => segment.header(),
+            Self::InetResponse(segment) => segment.header(),
+            Self::UnixResponse(segment) => segment.header(),
+            Self::Done(done_segment) => done_segment.header(),
+            Self::Error(error_segment) => error_segment.header(),
+        }
+    }
+
+    fn header_mut(&mut self) -> &mut CMsgSegHdr {
+        match self {
+            Self::InetRequest(segment) => segment.header_mut(),
+            Self::UnixRequest(segment) => segment.header_mut(),
+            Self::InetResponse(segment) => segment.header_mut(),
+            Self::UnixResponse(segment) => segment.header_mut(),
+            Self::Done(done_segment) => done_segment.header_mut(),
+            Self::Error(error_segment) => error_segment.header_mut(),
+        }
+    }
+
+    fn read_from(reader: &mut dyn MultiRead) -> Result<ContinueRead<Self, ErrorSegment>> {
+        let header = reader
+            .read_val_opt::<CMsgSegHdr>()?
+            .ok_or_else(|| Error::with_message(Errno::EINVAL, "the reader length is too small"))?;
+
+        // The type of the body depends on the address family, which is the first byte of the
+        // body. So the whole payload is read before the body is parsed.
+        let payload_len = header.calc_payload_len_with_padding(reader)?;
+        let payload = read_payload(reader, payload_len)?;
+        let mut payload_reader = VmReader::from(payload.as_slice()).to_fallible();
+
+        let segment = match (header.type_, payload.first()) {
+            (SOCK_DIAG_BY_FAMILY, Some(&family))
+                if family == CSocketAddrFamily::AF_INET as u8
+                    || family == CSocketAddrFamily::AF_INET6 as u8 =>
+            {
+                InetDiagReqSegment::read_from(&header, &mut payload_reader)?
+                    .map(SockDiagSegment::InetRequest)
+            }
+            (SOCK_DIAG_BY_FAMILY, Some(&family)) if family == CSocketAddrFamily::AF_UNIX as u8 => {
+                UnixDiagReqSegment::read_from(&header, &mut payload_reader)?
+                    .map(SockDiagSegment::UnixRequest)
+            }
+            (SOCK_DIAG_BY_FAMILY, Some(_)) => ContinueRead::skipped_with_error(
+                Errno::ENOENT,
+                "the address family is not supported",
+            ),
+            (SOCK_DIAG_BY_FAMILY, None) => {
+                ContinueRead::skipped_with_error(Errno::EINVAL, "the message length is too small")
+            }
+            _ => ContinueRead::skipped_with_error(
+                Errno::EINVAL,
+                "the sock_diag message type is not supported",
+            ),
+        };
+
+        Ok(segment.map_err(|error| ErrorSegment::new_from_request(&header, Some(error))))
+    }
+
+    fn write_to(&self, writer: &mut dyn MultiWrite) -> Result<()> {
+        match self {
+            Self::InetResponse(segment) => segment.write_to(writer)?,
+            Self::UnixResponse(segment) => segment.write_to(writer)?,
+            Self::Done(done_segment) => done_segment.write_to(writer)?,
+            Self::Error(error_segment) => error_segment.write_to(writer)?,
+            Self::InetRequest(_) | Self::UnixRequest(_) => {
+                unreachable!("kernel should not write requests to user space");
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SegmentBody for InetDiagReqBody {
+    type CType = CInetDiagReqV2;
+}
+
+impl SegmentBody for UnixDiagReqBody {
+    type CType = CUnixDiagReq;
+}
+
+impl SegmentBody for InetDiagMsgBody {
+    type CType = CInetDiagMsg;
+}
+
+impl SegmentBody for UnixDiagMsgBody {
+    type CType = CUnixDiagMsg;
+}
+
+/// `inet_diag_sockid` in Linux.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/include/uapi/linux/inet_diag.h#L14>
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub struct CInetDiagSockId {
+    /// The source port, in network byte order
+    pub sport: u16,
+    /// The destination port, in network byte order
+    pub dport: u16,
+    /// The source address, in network byte order
+    pub src: [u8; 16],
+    /// The destination address, in network byte order
+    pub dst: [u8; 16],
+    /// The index of the bound interface
+    pub if_: u32,
+    pub cookie: [u32; 2],
+}
+
+/// An identifier of a TCP or UDP socket.
+///
+/// IPv4 addresses occupy the first four bytes of the address fields.
+#[derive(Clone, Copy, Debug)]
+pub struct InetDiagSockId {
+    pub sport: u16,
+    pub dport: u16,
+    pub src: [u8; 16],
+    pub dst: [u8; 16],
+    pub if_: u32,
+    pub cookie: u64,
+}
+
+impl From<CInetDiagSockId> for InetDiagSockId {
+    fn from(value: CInetDiagSockId) -> Self {
+        Self {
+            sport: u16::from_be(value.sport),
+            dport: u16::from_be(value.dport),
+            src: value.src,
+            dst: value.dst,
+            if_: value.if_,
+            cookie: cookie_from_c(value.cookie),
+        }
+    }
+}
+
+impl From<InetDiagSockId> for CInetDiagSockId {
+    fn from(value: InetDiagSockId) -> Self {
+        Self {
+            sport: value.sport.to_be(),
+            dport: value.dport.to_be(),
+            src: value.src,
+            dst: value.dst,
+            if_: value.if_,
+            cookie: cookie_to_c(value.cookie),
+        }
+    }
+}
+
+/// `inet_diag_req_v2` in Linux.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/include/uapi/linux/inet_diag.h#L38>
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub struct CInetDiagReqV2 {
+    pub family: u8,
+    pub protocol: u8,
+    /// The extensions to report
+    pub ext: u8,
+    pub pad: u8,
+    /// The states of the sockets to report
+    pub states: u32,
+    pub id: CInetDiagSockId,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct InetDiagReqBody {
+    pub family: u8,
+    pub protocol: u8,
+    /// The extensions (i.e., the attributes) to report. The attribute of class `N` is reported
+    /// if bit `N - 1` is set.
+    pub ext: u8,
+    /// The states of the sockets to report. The sockets in state `N` are reported if bit `N` is
+    /// set.
+    pub states: u32,
+    pub id: InetDiagSockId,
+}
+
+impl TryFrom<CInetDiagReqV2> for InetDiagReqBody {
+    type Error = Error;
+
+    fn try_from(value: CInetDiagReqV2) -> Result<Self> {
+        Ok(Self {
+            family: value.family,
+            protocol: value.protocol,
+            ext: value.ext,
+            states: value.states,
+            id: value.id.into(),
+        })
+    }
+}
+
+impl From<InetDiagReqBody> for CInetDiagReqV2 {
+    fn from(value: InetDiagReqBody) -> Self {
+        Self {
+            family: value.family,
+            protocol: value.protocol,
+            ext: value.ext,
+            pad: 0,
+            states: value.states,
+            id: value.id.into(),
+        }
+    }
+}
+
+/// `inet_diag_msg` in Linux.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/include/uapi/linux/inet_diag.h#L119>
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub struct CInetDiagMsg {
+    pub family: u8,
+    pub state: u8,
+    /// The kind of the pending timer
+    pub timer: u8,
+    /// The number of retransmissions
+    pub retrans: u8,
+    pub id: CInetDiagSockId,
+    /// The time until the pending timer expires, in milliseconds
+    pub expires: u32,
+    pub rqueue: u32,
+    pub wqueue: u32,
+    pub uid: u32,
+    pub inode: u32,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct InetDiagMsgBody {
+    pub family: u8,
+    pub state: u8,
+    pub timer: u8,
+    pub retrans: u8,
+    pub id: InetDiagSockId,
+    pub expires: u32,
+    pub rqueue: u32,
+    pub wqueue: u32,
+    pub uid: u32,
+    pub inode: u32,
+}
+
+impl TryFrom<CInetDiagMsg> for InetDiagMsgBody {
+    type Error = Error;
+
+    fn try_from(value: CInetDiagMsg) -> Result<Self> {
+        Ok(Self {
+            family: value.family,
+            state: value.state,
+            timer: value.timer,
+            retrans: value.retrans,
+            id: value.id.into(),
+            expires: value.expires,
+            rqueue: value.rqueue,
+            wqueue: value.wqueue,
+            uid: value.uid,
+            inode: value.inode,
+        })
+    }
+}
+
+impl From<InetDiagMsgBody> for CInetDiagMsg {
+    fn from(value: InetDiagMsgBody) -> Self {
+        Self {
+            family: value.family,
+            state: value.state,
+            timer: value.timer,
+            retrans: value.retrans,
+            id: value.id.into(),
+            expires: value.expires,
+            rqueue: value.rqueue,
+            wqueue: value.wqueue,
+            uid: value.uid,
+            inode: value.inode,
+        }
+    }
+}
+
+/// `unix_diag_req` in Linux.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/include/uapi/linux/unix_diag.h#L7>
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub struct CUnixDiagReq {
+    pub family: u8,
+    pub protocol: u8,
+    pub pad: u16,
+    /// The states of the sockets to report
+    pub states: u32,
+    /// The inode number of the socket to look up
+    pub ino: u32,
+    /// The information to report
+    pub show: u32,
+    pub cookie: [u32; 2],
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct UnixDiagReqBody {
+    pub protocol: u8,
+    /// The states of the sockets to report. The sockets in state `N` are reported if bit `N` is
+    /// set.
+    pub states: u32,
+    pub ino: u32,
+    pub show: UnixDiagShow,
+    pub cookie: u64,
+}
+
+impl TryFrom<CUnixDiagReq> for UnixDiagReqBody {
+    type Error = Error;
+
+    fn try_from(value: CUnixDiagReq) -> Result<Self> {
+        Ok(Self {
+            protocol: value.protocol,
+            states: value.states,
+            ino: value.ino,
+            show: UnixDiagShow::from_bits_truncate(value.show),
+            cookie: cookie_from_c(value.cookie),
+        })
+    }
+}
+
+impl From<UnixDiagReqBody> for CUnixDiagReq {
+    fn from(value: UnixDiagReqBody) -> Self {
+        Self {
+            family: CSocketAddrFamily::AF_UNIX as u8,
+            protocol: value.protocol,
+            pad: 0,
+            states: value.states,
+            ino: value.ino,
+            show: value.show.bits(),
+            cookie: cookie_to_c(value.cookie),
+        }
+    }
+}
+
+bitflags! {
+    /// The information to report in [`CUnixDiagReq`].
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/include/uapi/linux/unix_diag.h#L17>
+    pub struct UnixDiagShow: u32 {
+        const NAME    = 0x01;
+        const VFS     = 0x02;
+        const PEER    = 0x04;
+        const ICONS   = 0x08;
+        const RQLEN   = 0x10;
+        const MEMINFO = 0x20;
+        const UID     = 0x40;
+    }
+}
+
+/// `unix_diag_msg` in Linux.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/include/uapi/linux/unix_diag.h#L25>
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub struct CUnixDiagMsg {
+    pub family: u8,
+    pub type_: u8,
+    pub state: u8,
+    pub pad: u8,
+    pub ino: u32,
+    pub cookie: [u32; 2],
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct UnixDiagMsgBody {
+    pub type_: SockType,
+    pub state: u8,
+    pub ino: u32,
+    pub cookie: u64,
+}
+
+impl TryFrom<CUnixDiagMsg> for UnixDiagMsgBody {
+    type Error = Error;
+
+    fn try_from(value: CUnixDiagMsg) -> Result<Self> {
+        Ok(Self {
+            type_: SockType::try_from(value.type_ as i32)?,
+            state: value.state,
+            ino: value.ino,
+            cookie: cookie_from_c(value.cookie),
+        })
+    }
+}
+
+impl From<UnixDiagMsgBody> for CUnixDiagMsg {
+    fn from(value: UnixDiagMsgBody) -> Self {
+        Self {
+            family: CSocketAddrFamily::AF_UNIX as u8,
+            type_: value.type_ as u8,
+            state: value.state,
+            pad: 0,
+            ino: value.ino,
+            cookie: cookie_to_c(value.cookie),
+        }
+    }
+}
+
+/// The cookie that matches any socket.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.5/source/include/uapi/linux/inet_diag.h#L24>
+pub const INET_DIAG_NOCOOKIE: u64 = u64::MAX;
+
+/// Converts a cookie from two 32-bit words, where the first one contains the lower bits.
+fn cookie_from_c(cookie: [u32; 2]) -> u64 {
+    u64::from(cookie[0]) | (u64::from(cookie[1]) << 32)
+}
+
+/// Converts a cookie to two 32-bit words, where the first one contains the lower bits.
+fn cookie_to_c(cookie: u64) -> [u32; 2] {
+    [cookie as u32, (cookie >> 32) as u32]
+}
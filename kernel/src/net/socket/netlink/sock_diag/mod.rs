@@ -0,0 +1,16 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Netlink sock_diag Socket.
+//!
+//! This protocol allows user space (e.g., `ss`) to query the sockets in the network namespace.
+//! Only TCP, UDP, and UNIX domain sockets can be queried.
+
+pub(super) use message::SockDiagMessage;
+
+use crate::net::socket::netlink::{common::NetlinkSocket, table::NetlinkSockDiagProtocol};
+
+mod bound;
+mod kernel;
+mod message;
+
+pub type NetlinkSockDiagSocket = NetlinkSocket<NetlinkSockDiagProtocol>;
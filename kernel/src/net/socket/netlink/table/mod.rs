@@ -2,16 +2,18 @@
 
 use multicast::MulticastGroup;
 pub(super) use multicast::MulticastMessage;
-use spin::Once;
 
 use super::{
     addr::{GroupIdSet, MAX_GROUPS, NetlinkProtocolId, NetlinkSocketAddr, PortNum},
     receiver::QueueableMessage,
 };
 use crate::{
-    net::socket::netlink::{
-        addr::UNSPECIFIED_PORT, kobject_uevent::UeventMessage, receiver::MessageReceiver,
-        route::RtnlMessage,
+    net::{
+        net_ns::NetNamespace,
+        socket::netlink::{
+            addr::UNSPECIFIED_PORT, kobject_uevent::UeventMessage, netfilter::NfnlMessage,
+            receiver::MessageReceiver, route::RtnlMessage, sock_diag::SockDiagMessage,
+        },
     },
     prelude::*,
     util::random::getrandom,
@@ -19,19 +21,21 @@ use crate::{
 
 mod multicast;
 
-static NETLINK_SOCKET_TABLE: Once<NetlinkSocketTable> = Once::new();
-
-/// All bound netlink sockets.
-struct NetlinkSocketTable {
+/// All bound netlink sockets in a network namespace.
+pub(in crate::net) struct NetlinkSocketTable {
     route: RwMutex<ProtocolSocketTable<RtnlMessage>>,
+    sock_diag: RwMutex<ProtocolSocketTable<SockDiagMessage>>,
     uevent: RwMutex<ProtocolSocketTable<UeventMessage>>,
+    netfilter: RwMutex<ProtocolSocketTable<NfnlMessage>>,
 }
 
 impl NetlinkSocketTable {
-    fn new() -> Self {
+    pub(in crate::net) fn new() -> Self {
         Self {
             route: RwMutex::new(ProtocolSocketTable::new()),
+            sock_diag: RwMutex::new(ProtocolSocketTable::new()),
             uevent: RwMutex::new(ProtocolSocketTable::new()),
+            netfilter: RwMutex::new(ProtocolSocketTable::new()),
         }
     }
 }
@@ -39,30 +43,34 @@ impl NetlinkSocketTable {
 pub trait SupportedNetlinkProtocol {
     type Message: 'static + Send;
 
-    fn socket_table() -> &'static RwMutex<ProtocolSocketTable<Self::Message>>;
+    fn socket_table(net_ns: &NetNamespace) -> &RwMutex<ProtocolSocketTable<Self::Message>>;
 
     fn bind(
+        net_ns: &Arc<NetNamespace>,
         addr: &NetlinkSocketAddr,
         receiver: MessageReceiver<Self::Message>,
     ) -> Result<BoundHandle<Self::Message>> {
-        let mut socket_table = Self::socket_table().write();
-        socket_table.bind(Self::socket_table(), addr, receiver)
+        let mut socket_table = Self::socket_table(net_ns).write();
+        socket_table.bind(net_ns, Self::socket_table, addr, receiver)
     }
 
-    fn unicast(dst_port: PortNum, message: Self::Message) -> Result<()>
+    fn unicast(net_ns: &NetNamespace, dst_port: PortNum, message: Self::Message) -> Result<()>
     where
         Self::Message: QueueableMessage,
     {
-        let socket_table = Self::socket_table().read();
+        let socket_table = Self::socket_table(net_ns).read();
         socket_table.unicast(dst_port, message)
     }
 
-    #[cfg_attr(not(ktest), expect(dead_code))]
-    fn multicast(dst_groups: GroupIdSet, message: Self::Message) -> Result<()>
+    fn multicast(
+        net_ns: &NetNamespace,
+        dst_groups: GroupIdSet,
+        message: Self::Message,
+    ) -> Result<()>
     where
         Self::Message: MulticastMessage,
     {
-        let socket_table = Self::socket_table().read();
+        let socket_table = Self::socket_table(net_ns).read();
         socket_table.multicast(dst_groups, message)
     }
 }
@@ -72,8 +80,18 @@ pub enum NetlinkRouteProtocol {}
 impl SupportedNetlinkProtocol for NetlinkRouteProtocol {
     type Message = RtnlMessage;
 
-    fn socket_table() -> &'static RwMutex<ProtocolSocketTable<Self::Message>> {
-        &NETLINK_SOCKET_TABLE.get().unwrap().route
+    fn socket_table(net_ns: &NetNamespace) -> &RwMutex<ProtocolSocketTable<Self::Message>> {
+        &net_ns.netlink_socket_table().route
+    }
+}
+
+pub enum NetlinkSockDiagProtocol {}
+
+impl SupportedNetlinkProtocol for NetlinkSockDiagProtocol {
+    type Message = SockDiagMessage;
+
+    fn socket_table(net_ns: &NetNamespace) -> &RwMutex<ProtocolSocketTable<Self::Message>> {
+        &net_ns.netlink_socket_table().sock_diag
     }
 }
 
@@ -82,8 +100,18 @@ pub enum NetlinkUeventProtocol {}
 impl SupportedNetlinkProtocol for NetlinkUeventProtocol {
     type Message = UeventMessage;
 
-    fn socket_table() -> &'static RwMutex<ProtocolSocketTable<Self::Message>> {
-        &NETLINK_SOCKET_TABLE.get().unwrap().uevent
+    fn socket_table(net_ns: &NetNamespace) -> &RwMutex<ProtocolSocketTable<Self::Message>> {
+        &net_ns.netlink_socket_table().uevent
+    }
+}
+
+pub enum NetlinkNetfilterProtocol {}
+
+impl SupportedNetlinkProtocol for NetlinkNetfilterProtocol {
+    type Message = NfnlMessage;
+
+    fn socket_table(net_ns: &NetNamespace) -> &RwMutex<ProtocolSocketTable<Self::Message>> {
+        &net_ns.netlink_socket_table().netfilter
     }
 }
 
@@ -119,7 +147,8 @@ impl<Message: 'static> ProtocolSocketTable<Message> {
     /// as specified in `addr.groups()`.
     fn bind(
         &mut self,
-        socket_table: &'static RwMutex<ProtocolSocketTable<Message>>,
+        net_ns: &Arc<NetNamespace>,
+        socket_table: SocketTableFn<Message>,
         addr: &NetlinkSocketAddr,
         receiver: MessageReceiver<Message>,
     ) -> Result<BoundHandle<Message>> {
@@ -145,7 +174,12 @@ impl<Message: 'static> ProtocolSocketTable<Message> {
             group.add_member(port);
         }
 
-        Ok(BoundHandle::new(socket_table, port, addr.groups()))
+        Ok(BoundHandle::new(
+            net_ns.clone(),
+            socket_table,
+            port,
+            addr.groups(),
+        ))
     }
 
     fn unicast(&self, dst_port: PortNum, message: Message) -> Result<()>
@@ -182,31 +216,46 @@ impl<Message: 'static> ProtocolSocketTable<Message> {
     }
 }
 
+/// A function that finds the socket table of a netlink protocol in a network namespace.
+type SocketTableFn<Message> = fn(&NetNamespace) -> &RwMutex<ProtocolSocketTable<Message>>;
+
 /// A bound netlink socket address.
 ///
 /// When dropping a `BoundHandle`,
 /// the port will be automatically released.
 pub struct BoundHandle<Message: 'static> {
-    socket_table: &'static RwMutex<ProtocolSocketTable<Message>>,
+    net_ns: Arc<NetNamespace>,
+    socket_table: SocketTableFn<Message>,
     port: PortNum,
     groups: GroupIdSet,
 }
 
 impl<Message: 'static> BoundHandle<Message> {
     fn new(
-        socket_table: &'static RwMutex<ProtocolSocketTable<Message>>,
+        net_ns: Arc<NetNamespace>,
+        socket_table: SocketTableFn<Message>,
         port: PortNum,
         groups: GroupIdSet,
     ) -> Self {
         debug_assert_ne!(port, UNSPECIFIED_PORT);
 
         Self {
+            net_ns,
             socket_table,
             port,
             groups,
         }
     }
 
+    fn socket_table(&self) -> &RwMutex<ProtocolSocketTable<Message>> {
+        (self.socket_table)(&self.net_ns)
+    }
+
+    /// Returns the network namespace where the socket is bound.
+    pub(super) fn net_ns(&self) -> &Arc<NetNamespace> {
+        &self.net_ns
+    }
+
     pub(super) const fn port(&self) -> PortNum {
         self.port
     }
@@ -216,7 +265,7 @@ impl<Message: 'static> BoundHandle<Message> {
     }
 
     pub(super) fn add_groups(&mut self, groups: GroupIdSet) {
-        let mut protocol_sockets = self.socket_table.write();
+        let mut protocol_sockets = self.socket_table().write();
 
         for group_id in groups.ids_iter() {
             let group = &mut protocol_sockets.multicast_groups[group_id as usize];
@@ -227,7 +276,7 @@ impl<Message: 'static> BoundHandle<Message> {
     }
 
     pub(super) fn drop_groups(&mut self, groups: GroupIdSet) {
-        let mut protocol_sockets = self.socket_table.write();
+        let mut protocol_sockets = self.socket_table().write();
 
         for group_id in groups.ids_iter() {
             let group = &mut protocol_sockets.multicast_groups[group_id as usize];
@@ -238,7 +287,7 @@ impl<Message: 'static> BoundHandle<Message> {
     }
 
     pub(super) fn bind_groups(&mut self, groups: GroupIdSet) {
-        let mut protocol_sockets = self.socket_table.write();
+        let mut protocol_sockets = self.socket_table().write();
 
         for group_id in self.groups.ids_iter() {
             let group = &mut protocol_sockets.multicast_groups[group_id as usize];
@@ -256,7 +305,7 @@ impl<Message: 'static> BoundHandle<Message> {
 
 impl<Message: 'static> Drop for BoundHandle<Message> {
     fn drop(&mut self) {
-        let mut protocol_sockets = self.socket_table.write();
+        let mut protocol_sockets = self.socket_table().write();
 
         protocol_sockets.unicast_sockets.remove(&self.port);
 
@@ -267,10 +316,6 @@ impl<Message: 'static> Drop for BoundHandle<Message> {
     }
 }
 
-pub(super) fn init() {
-    NETLINK_SOCKET_TABLE.call_once(NetlinkSocketTable::new);
-}
-
 /// Returns whether the `protocol` is valid.
 pub fn is_valid_protocol(protocol: NetlinkProtocolId) -> bool {
     protocol < MAX_ALLOWED_PROTOCOL_ID
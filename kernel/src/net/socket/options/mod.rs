@@ -2,7 +2,7 @@
 
 use macros::impl_socket_options;
 
-use super::util::LingerOption;
+use super::util::{LingerOption, SocketFilter};
 use crate::{net::socket::unix::CUserCred, prelude::*, process::Gid};
 
 pub(in crate::net) mod macros;
@@ -25,9 +25,14 @@ impl_socket_options!(
     pub struct Linger(LingerOption);
     pub struct ReusePort(bool);
     pub struct PassCred(bool);
+    pub struct Timestamp(bool);
+    pub struct TimestampNs(bool);
     pub struct PeerCred(CUserCred);
     pub struct AcceptConn(bool);
     pub struct SendBufForce(u32);
     pub struct RecvBufForce(u32);
     pub struct PeerGroups(Arc<[Gid]>);
+    pub struct AttachFilter(SocketFilter);
+    /// The option value is ignored.
+    pub struct DetachFilter(i32);
 );
@@ -0,0 +1,40 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::{net::socket::util::SocketAddr, prelude::*};
+
+/// The socket address of a packet socket.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/if_packet.h#L14>.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct LinkLayerSocketAddr {
+    /// The link-layer protocol (i.e., the EtherType) in host byte order.
+    pub protocol: u16,
+    /// The interface index, or zero for any interfaces.
+    pub ifindex: u32,
+    /// The ARP hardware type of the interface.
+    pub hatype: u16,
+    /// The type of the packet.
+    pub pkttype: u8,
+    /// The length of the valid bytes in [`Self::addr`].
+    pub halen: u8,
+    /// The link-layer address.
+    pub addr: [u8; 8],
+}
+
+impl TryFrom<SocketAddr> for LinkLayerSocketAddr {
+    type Error = Error;
+
+    fn try_from(value: SocketAddr) -> Result<Self> {
+        let SocketAddr::Packet(addr) = value else {
+            return_errno_with_message!(Errno::EINVAL, "the socket address is not link-layer");
+        };
+
+        Ok(addr)
+    }
+}
+
+impl From<LinkLayerSocketAddr> for SocketAddr {
+    fn from(value: LinkLayerSocketAddr) -> Self {
+        SocketAddr::Packet(value)
+    }
+}
@@ -0,0 +1,582 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! This module defines packet sockets (i.e., `AF_PACKET`).
+//!
+//! Packet sockets send and receive raw link-layer frames. A `SOCK_RAW` packet socket sees the
+//! frames with their link-layer headers, whereas a `SOCK_DGRAM` packet socket sees only the
+//! payloads, and the link-layer headers are built from the socket addresses when sending.
+//!
+//! A packet socket receives the frames of a specific protocol (i.e., the EtherType), or the frames
+//! of all protocols if the protocol is `ETH_P_ALL`. In the latter case, the frames sent by the
+//! local host are also received.
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use aster_bigtcp::{errors::link::SendError, iface::FrameTap, wire::ETHERNET_HEADER_LEN};
+use receiver::PacketReceiver;
+
+use crate::{
+    events::IoEvents,
+    fs::{pseudofs::SockFs, vfs::path::Path},
+    net::{
+        iface::Iface,
+        net_ns::NetNamespace,
+        socket::{
+            Socket,
+            options::{
+                AttachFilter, DetachFilter, Error as SocketError, SocketOption,
+                macros::{sock_option_mut, sock_option_ref},
+            },
+            private::SocketPrivate,
+            util::{
+                MessageHeader, SendRecvFlags, SocketAddr,
+                options::{GetSocketLevelOption, SetSocketLevelOption, SocketOptionSet},
+            },
+        },
+    },
+    prelude::*,
+    process::{
+        credentials::capabilities::CapSet,
+        posix_thread::PosixThread,
+        signal::{PollHandle, Pollable, Pollee},
+    },
+    util::{MultiRead, MultiWrite},
+};
+
+mod addr;
+mod options;
+mod receiver;
+
+pub use addr::LinkLayerSocketAddr;
+use options::PACKET_MR_PROMISC;
+pub use options::{AddMembership, CPacketMreq, DropMembership};
+
+/// A packet socket.
+pub struct PacketSocket {
+    kind: PacketSocketKind,
+    // Lock order: `state` first, `options` second
+    state: Mutex<State>,
+    options: RwLock<OptionSet>,
+    receiver: Arc<PacketReceiver>,
+    net_ns: Arc<NetNamespace>,
+
+    is_nonblocking: AtomicBool,
+    pollee: Pollee,
+    pseudo_path: Path,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum PacketSocketKind {
+    /// A `SOCK_RAW` packet socket.
+    Raw,
+    /// A `SOCK_DGRAM` packet socket.
+    Dgram,
+}
+
+struct State {
+    /// The protocol of the frames to receive and send, in host byte order.
+    protocol: u16,
+    /// The interface that the socket is bound to, or `None` for all interfaces.
+    bound_iface: Option<Arc<Iface>>,
+    /// The interfaces on which the receiver is registered as a tap.
+    tapped_ifaces: Vec<Arc<Iface>>,
+    memberships: Vec<Membership>,
+}
+
+struct Membership {
+    iface: Arc<Iface>,
+    mreq: CPacketMreq,
+    /// The number of times that the membership is added.
+    count: usize,
+}
+
+#[derive(Clone, Debug)]
+struct OptionSet {
+    socket: SocketOptionSet,
+}
+
+/// The protocol number that matches all protocols.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/if_ether.h#L133>.
+const ETH_P_ALL: u16 = 0x0003;
+
+/// The length of hardware addresses of the supported interfaces.
+const HARDWARE_ADDR_LEN: u8 = 6;
+
+/// The default size of the receive buffer.
+///
+/// This resembles the default value of `net.core.rmem_default` in Linux.
+pub(in crate::net) const PACKET_DEFAULT_BUF_SIZE: usize = 212992;
+
+impl PacketSocket {
+    /// Creates a `SOCK_RAW` packet socket.
+    ///
+    /// The protocol is the EtherType in host byte order.
+    ///
+    /// This method will fail with [`EPERM`] if the thread does not have the NET_RAW capability.
+    ///
+    /// [`EPERM`]: crate::error::Errno::EPERM
+    pub fn new_raw(
+        is_nonblocking: bool,
+        protocol: u16,
+        net_ns: Arc<NetNamespace>,
+        posix_thread: &PosixThread,
+    ) -> Result<Arc<Self>> {
+        Self::new(
+            is_nonblocking,
+            PacketSocketKind::Raw,
+            protocol,
+            net_ns,
+            posix_thread,
+        )
+    }
+
+    /// Creates a `SOCK_DGRAM` packet socket.
+    ///
+    /// The protocol is the EtherType in host byte order.
+    ///
+    /// This method will fail with [`EPERM`] if the thread does not have the NET_RAW capability.
+    ///
+    /// [`EPERM`]: crate::error::Errno::EPERM
+    pub fn new_dgram(
+        is_nonblocking: bool,
+        protocol: u16,
+        net_ns: Arc<NetNamespace>,
+        posix_thread: &PosixThread,
+    ) -> Result<Arc<Self>> {
+        Self::new(
+            is_nonblocking,
+            PacketSocketKind::Dgram,
+            protocol,
+            net_ns,
+            posix_thread,
+        )
+    }
+
+    fn new(
+        is_nonblocking: bool,
+        kind: PacketSocketKind,
+        protocol: u16,
+        net_ns: Arc<NetNamespace>,
+        posix_thread: &PosixThread,
+    ) -> Result<Arc<Self>> {
+        net_ns.check_cap(CapSet::NET_RAW, posix_thread)?;
+
+        let pollee = Pollee::new();
+        let receiver = Arc::new(PacketReceiver::new(kind, protocol, pollee.clone()));
+
+        let socket = Arc::new(Self {
+            kind,
+            state: Mutex::new(State {
+                protocol,
+                bound_iface: None,
+                tapped_ifaces: Vec::new(),
+                memberships: Vec::new(),
+            }),
+            options: RwLock::new(OptionSet {
+                socket: SocketOptionSet::new_packet(),
+            }),
+            receiver,
+            net_ns,
+            is_nonblocking: AtomicBool::new(is_nonblocking),
+            pollee,
+            pseudo_path: SockFs::new_path(),
+        });
+        socket.update_taps(&mut socket.state.lock());
+
+        Ok(socket)
+    }
+
+    fn tap(&self) -> Arc<dyn FrameTap> {
+        self.receiver.clone()
+    }
+
+    /// Registers the receiver as a tap on the interfaces that the socket receives frames from.
+    //
+    // TODO: An unbound socket should also receive frames from the interfaces that are added to the
+    // network namespace after the socket is created or bound.
+    fn update_taps(&self, state: &mut State) {
+        let tap = self.tap();
+
+        for iface in state.tapped_ifaces.drain(..) {
+            iface.unregister_tap(&tap);
+        }
+
+        self.receiver.set_protocol(state.protocol);
+
+        // Like Linux, a socket whose protocol is zero receives no frames.
+        if state.protocol == 0 {
+            return;
+        }
+
+        state.tapped_ifaces = match state.bound_iface.as_ref() {
+            Some(iface) => vec![iface.clone()],
+            None => self.net_ns.ifaces(),
+        };
+        for iface in state.tapped_ifaces.iter() {
+            iface.register_tap(tap.clone());
+        }
+    }
+
+    fn find_iface(&self, ifindex: u32) -> Option<Arc<Iface>> {
+        self.net_ns
+            .ifaces()
+            .into_iter()
+            .find(|iface| iface.index() == ifindex)
+    }
+
+    fn try_recv(
+        &self,
+        writer: &mut dyn MultiWrite,
+        flags: SendRecvFlags,
+    ) -> Result<(usize, MessageHeader)> {
+        let (recv_bytes, addr) = self.receiver.try_recv(writer, flags)?;
+        self.pollee.invalidate();
+
+        let message_header = MessageHeader::new(Some(addr.into()), Vec::new());
+        Ok((recv_bytes, message_header))
+    }
+
+    fn try_send(
+        &self,
+        reader: &mut dyn MultiRead,
+        remote: Option<&LinkLayerSocketAddr>,
+    ) -> Result<usize> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/packet/af_packet.c> (`packet_snd`)
+
+        let iface = match remote {
+            Some(remote) => self.find_iface(remote.ifindex),
+            None => self.state.lock().bound_iface.clone(),
+        };
+        let Some(iface) = iface else {
+            return_errno_with_message!(Errno::ENXIO, "the interface does not exist");
+        };
+
+        let header_len = match (self.kind, remote) {
+            (PacketSocketKind::Raw, _) => 0,
+            (PacketSocketKind::Dgram, Some(_)) => ETHERNET_HEADER_LEN,
+            (PacketSocketKind::Dgram, None) => {
+                return_errno_with_message!(
+                    Errno::EINVAL,
+                    "the destination address is required to build the link-layer header"
+                );
+            }
+        };
+
+        let len = reader.sum_lens();
+        if header_len + len > ETHERNET_HEADER_LEN + iface.mtu() {
+            return_errno_with_message!(Errno::EMSGSIZE, "the frame is too large");
+        }
+
+        let mut frame = vec![0u8; header_len + len];
+        reader.read(&mut VmWriter::from(&mut frame[header_len..]))?;
+
+        if let Some(remote) = remote
+            && header_len != 0
+        {
+            let Some(src_addr) = iface.ether_addr() else {
+                return_errno_with_message!(
+                    Errno::EOPNOTSUPP,
+                    "the interface does not support link-layer headers"
+                );
+            };
+            frame[0..6].copy_from_slice(&remote.addr[..6]);
+            frame[6..12].copy_from_slice(src_addr.as_bytes());
+            frame[12..14].copy_from_slice(&remote.protocol.to_be_bytes());
+        }
+
+        match iface.send_frame(&frame, Some(&self.tap())) {
+            Ok(()) => (),
+            Err(SendError::Unsupported) => {
+                return_errno_with_message!(
+                    Errno::EOPNOTSUPP,
+                    "the interface does not support sending link-layer frames"
+                );
+            }
+            Err(SendError::Down) => {
+                return_errno_with_message!(Errno::ENETDOWN, "the interface is down");
+            }
+            Err(SendError::BufferFull) => {
+                return_errno_with_message!(Errno::ENOBUFS, "the transmit queue is full");
+            }
+            Err(SendError::TooLarge) => {
+                return_errno_with_message!(Errno::EMSGSIZE, "the frame is too large");
+            }
+            Err(SendError::Malformed) => {
+                return_errno_with_message!(Errno::EINVAL, "the frame is too short");
+            }
+        }
+        iface.poll();
+
+        Ok(len)
+    }
+
+    fn add_membership(&self, mreq: &CPacketMreq) -> Result<()> {
+        let Some(iface) = self.find_iface(mreq.mr_ifindex as u32) else {
+            return_errno_with_message!(Errno::ENODEV, "the interface does not exist");
+        };
+        if mreq.mr_alen > HARDWARE_ADDR_LEN as u16 {
+            return_errno_with_message!(Errno::EINVAL, "the address length is too large");
+        }
+
+        let mut state = self.state.lock();
+
+        if let Some(membership) = state
+            .memberships
+            .iter_mut()
+            .find(|membership| is_same_mreq(&membership.mreq, mreq))
+        {
+            membership.count += 1;
+            return Ok(());
+        }
+
+        // TODO: Support the other membership types. `PACKET_MR_MULTICAST` and `PACKET_MR_ALLMULTI`
+        // require adding link-layer addresses to the receive filter of the device, but only the
+        // multicast groups joined by the iface are added now.
+        if mreq.mr_type == PACKET_MR_PROMISC {
+            iface.inc_promiscuity();
+        }
+        state.memberships.push(Membership {
+            iface,
+            mreq: *mreq,
+            count: 1,
+        });
+
+        Ok(())
+    }
+
+    fn drop_membership(&self, mreq: &CPacketMreq) {
+        let mut state = self.state.lock();
+
+        let Some(pos) = state
+            .memberships
+            .iter()
+            .position(|membership| is_same_mreq(&membership.mreq, mreq))
+        else {
+            return;
+        };
+
+        let membership = &mut state.memberships[pos];
+        membership.count -= 1;
+        if membership.count == 0 {
+            let membership = state.memberships.swap_remove(pos);
+            membership.leave();
+        }
+    }
+}
+
+impl Membership {
+    fn leave(self) {
+        if self.mreq.mr_type == PACKET_MR_PROMISC {
+            self.iface.dec_promiscuity();
+        }
+    }
+}
+
+fn is_same_mreq(lhs: &CPacketMreq, rhs: &CPacketMreq) -> bool {
+    let alen = lhs.mr_alen as usize;
+
+    lhs.mr_ifindex == rhs.mr_ifindex
+        && lhs.mr_type == rhs.mr_type
+        && lhs.mr_alen == rhs.mr_alen
+        && lhs.mr_address[..alen] == rhs.mr_address[..alen]
+}
+
+impl Drop for PacketSocket {
+    fn drop(&mut self) {
+        let tap = self.tap();
+        let state = self.state.get_mut();
+
+        for iface in state.tapped_ifaces.drain(..) {
+            iface.unregister_tap(&tap);
+        }
+        for membership in state.memberships.drain(..) {
+            membership.leave();
+        }
+    }
+}
+
+impl Pollable for PacketSocket {
+    fn poll(&self, mask: IoEvents, poller: Option<&mut PollHandle>) -> IoEvents {
+        self.pollee.poll_with(mask, poller, || {
+            let mut events = IoEvents::OUT;
+            if self.receiver.can_recv() {
+                events |= IoEvents::IN;
+            }
+            events
+        })
+    }
+}
+
+impl SocketPrivate for PacketSocket {
+    fn is_nonblocking(&self) -> bool {
+        self.is_nonblocking.load(Ordering::Relaxed)
+    }
+
+    fn set_nonblocking(&self, is_nonblocking: bool) {
+        self.is_nonblocking.store(is_nonblocking, Ordering::Relaxed);
+    }
+}
+
+impl Socket for PacketSocket {
+    fn bind(&self, socket_addr: SocketAddr) -> Result<()> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/packet/af_packet.c> (`packet_do_bind`)
+
+        let addr = LinkLayerSocketAddr::try_from(socket_addr)?;
+
+        let bound_iface = if addr.ifindex == 0 {
+            None
+        } else {
+            let Some(iface) = self.find_iface(addr.ifindex) else {
+                return_errno_with_message!(Errno::ENODEV, "the interface does not exist");
+            };
+            Some(iface)
+        };
+
+        let mut state = self.state.lock();
+        // Like Linux, a zero protocol keeps the current protocol.
+        if addr.protocol != 0 {
+            state.protocol = addr.protocol;
+        }
+        state.bound_iface = bound_iface;
+        self.update_taps(&mut state);
+
+        Ok(())
+    }
+
+    fn addr(&self) -> Result<SocketAddr> {
+        let state = self.state.lock();
+
+        let mut addr = LinkLayerSocketAddr {
+            protocol: state.protocol,
+            ..Default::default()
+        };
+        if let Some(iface) = state.bound_iface.as_ref() {
+            addr.ifindex = iface.index();
+            addr.hatype = iface.type_() as u16;
+            addr.halen = HARDWARE_ADDR_LEN;
+            if let Some(ether_addr) = iface.ether_addr() {
+                addr.addr[..6].copy_from_slice(ether_addr.as_bytes());
+            }
+        }
+
+        Ok(addr.into())
+    }
+
+    fn sendmsg(
+        &self,
+        reader: &mut dyn MultiRead,
+        message_header: MessageHeader,
+        flags: SendRecvFlags,
+    ) -> Result<usize> {
+        // TODO: Deal with flags
+        if !flags.is_all_supported() {
+            warn!("unsupported flags: {:?}", flags);
+        }
+
+        let MessageHeader {
+            addr,
+            control_messages,
+        } = message_header;
+
+        let remote = match addr {
+            Some(addr) => Some(LinkLayerSocketAddr::try_from(addr)?),
+            None => None,
+        };
+
+        if !control_messages.is_empty() {
+            // TODO: Support sending control message
+            warn!("sending control message is not supported");
+        }
+
+        // TODO: Block if the transmit queue is full
+        self.try_send(reader, remote.as_ref())
+    }
+
+    fn recvmsg(
+        &self,
+        writer: &mut dyn MultiWrite,
+        flags: SendRecvFlags,
+    ) -> Result<(usize, MessageHeader)> {
+        // TODO: Deal with other flags. Only MSG_PEEK and MSG_TRUNC are handled here.
+        let supported_flags = SendRecvFlags::MSG_PEEK | SendRecvFlags::MSG_TRUNC;
+        if !(flags - supported_flags).is_all_supported() {
+            warn!("unsupported flags: {:?}", flags);
+        }
+
+        self.block_on(IoEvents::IN, || self.try_recv(writer, flags))
+    }
+
+    fn get_option(&self, option: &mut dyn SocketOption) -> Result<()> {
+        sock_option_mut!(match option {
+            socket_errors @ SocketError => {
+                // TODO: Support socket errors for packet sockets
+                socket_errors.set(None);
+                return Ok(());
+            }
+            _ => (),
+        });
+
+        let options = self.options.read();
+
+        // Deal with socket-level options
+        options.socket.get_option(option, self)
+
+        // TODO: Deal with packet-level options
+    }
+
+    fn set_option(&self, option: &dyn SocketOption) -> Result<()> {
+        // Deal with socket filters
+        sock_option_ref!(match option {
+            attach_filter @ AttachFilter => {
+                let filter = attach_filter.get().unwrap();
+                self.receiver.replace_filter(Some(filter.clone()));
+                return Ok(());
+            }
+            _detach_filter @ DetachFilter => {
+                if self.receiver.replace_filter(None).is_none() {
+                    return_errno_with_message!(Errno::ENOENT, "no socket filter is attached");
+                }
+                return Ok(());
+            }
+            _ => (),
+        });
+
+        // Deal with socket-level options
+        match self.options.write().socket.set_option(option, self) {
+            Err(err) if err.error() == Errno::ENOPROTOOPT => (),
+            res => return res.map(|_need_iface_poll| ()),
+        }
+
+        // Deal with packet-level options
+        sock_option_ref!(match option {
+            add_membership @ AddMembership => {
+                let mreq = add_membership.get().unwrap();
+                self.add_membership(mreq)?;
+            }
+            drop_membership @ DropMembership => {
+                // Like Linux, dropping a membership that does not exist succeeds.
+                let mreq = drop_membership.get().unwrap();
+                self.drop_membership(mreq);
+            }
+            _ => return_errno_with_message!(
+                Errno::ENOPROTOOPT,
+                "the socket option to be set is unknown"
+            ),
+        });
+
+        Ok(())
+    }
+
+    fn pseudo_path(&self) -> &Path {
+        &self.pseudo_path
+    }
+}
+
+impl GetSocketLevelOption for PacketSocket {
+    fn is_listening(&self) -> bool {
+        false
+    }
+}
+
+impl SetSocketLevelOption for PacketSocket {}
@@ -0,0 +1,27 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::{net::socket::options::macros::impl_socket_options, prelude::*};
+
+impl_socket_options!(
+    pub struct AddMembership(CPacketMreq);
+    pub struct DropMembership(CPacketMreq);
+);
+
+/// A request to add or drop a membership of a packet socket.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/if_packet.h#L297>.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub struct CPacketMreq {
+    /// The interface index.
+    pub mr_ifindex: i32,
+    /// The membership type (e.g., [`PACKET_MR_PROMISC`]).
+    pub mr_type: u16,
+    /// The length of the valid bytes in [`Self::mr_address`].
+    pub mr_alen: u16,
+    /// The link-layer address.
+    pub mr_address: [u8; 8],
+}
+
+/// The membership type that puts the interface into the promiscuous mode.
+pub(super) const PACKET_MR_PROMISC: u16 = 1;
@@ -0,0 +1,188 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_bigtcp::{
+    iface::{FrameTap, FrameType, InterfaceType},
+    wire::ETHERNET_HEADER_LEN,
+};
+use ostd::sync::BottomHalfDisabled;
+
+use super::{ETH_P_ALL, LinkLayerSocketAddr, PACKET_DEFAULT_BUF_SIZE, PacketSocketKind};
+use crate::{
+    events::IoEvents,
+    net::socket::util::{FilterPacket, SendRecvFlags, SocketFilter},
+    prelude::*,
+    process::signal::Pollee,
+    util::MultiWrite,
+};
+
+/// The receiving side of a packet socket.
+///
+/// The receiver is registered as a tap on the interfaces that the socket receives frames from.
+pub(super) struct PacketReceiver {
+    kind: PacketSocketKind,
+    inner: SpinLock<ReceiverInner, BottomHalfDisabled>,
+    pollee: Pollee,
+}
+
+struct ReceiverInner {
+    /// The protocol of the frames to receive, in host byte order.
+    protocol: u16,
+    filter: Option<SocketFilter>,
+    queue: VecDeque<ReceivedFrame>,
+    /// The total length of the frames in the queue.
+    queued_len: usize,
+}
+
+struct ReceivedFrame {
+    data: Vec<u8>,
+    addr: LinkLayerSocketAddr,
+}
+
+impl PacketReceiver {
+    pub(super) fn new(kind: PacketSocketKind, protocol: u16, pollee: Pollee) -> Self {
+        Self {
+            kind,
+            inner: SpinLock::new(ReceiverInner {
+                protocol,
+                filter: None,
+                queue: VecDeque::new(),
+                queued_len: 0,
+            }),
+            pollee,
+        }
+    }
+
+    pub(super) fn set_protocol(&self, protocol: u16) {
+        self.inner.lock().protocol = protocol;
+    }
+
+    /// Replaces the socket filter and returns the old one.
+    pub(super) fn replace_filter(&self, filter: Option<SocketFilter>) -> Option<SocketFilter> {
+        core::mem::replace(&mut self.inner.lock().filter, filter)
+    }
+
+    pub(super) fn can_recv(&self) -> bool {
+        !self.inner.lock().queue.is_empty()
+    }
+
+    pub(super) fn try_recv(
+        &self,
+        writer: &mut dyn MultiWrite,
+        flags: SendRecvFlags,
+    ) -> Result<(usize, LinkLayerSocketAddr)> {
+        let mut inner = self.inner.lock();
+
+        let Some(frame) = inner.queue.front() else {
+            return_errno_with_message!(Errno::EAGAIN, "the receive buffer is empty");
+        };
+
+        let copied_len = writer.write(&mut VmReader::from(frame.data.as_slice()))?;
+        let frame_len = frame.data.len();
+        let addr = frame.addr;
+
+        if !flags.contains(SendRecvFlags::MSG_PEEK) {
+            inner.queue.pop_front();
+            inner.queued_len -= frame_len;
+        }
+
+        // Like Linux, the real length of the frame is returned if `MSG_TRUNC` is specified.
+        let recv_len = if flags.contains(SendRecvFlags::MSG_TRUNC) {
+            frame_len
+        } else {
+            copied_len
+        };
+
+        Ok((recv_len, addr))
+    }
+}
+
+impl FrameTap for PacketReceiver {
+    fn on_frame(&self, frame: &[u8], iface_index: u32, frame_type: FrameType) {
+        // Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/packet/af_packet.c> (`packet_rcv`)
+
+        if frame.len() < ETHERNET_HEADER_LEN {
+            return;
+        }
+        let protocol = frame_protocol(frame);
+
+        let mut inner = self.inner.lock();
+
+        // Outgoing frames are only delivered to the sockets that receive all the protocols.
+        let is_wanted = match inner.protocol {
+            0 => false,
+            ETH_P_ALL => true,
+            wanted => frame_type != FrameType::Outgoing && wanted == protocol,
+        };
+        if !is_wanted {
+            return;
+        }
+
+        let data_offset = match self.kind {
+            PacketSocketKind::Raw => 0,
+            PacketSocketKind::Dgram => ETHERNET_HEADER_LEN,
+        };
+        let mut data = &frame[data_offset..];
+
+        if let Some(filter) = inner.filter.as_ref() {
+            let packet = FilterPacket {
+                frame,
+                data_offset,
+                network_offset: ETHERNET_HEADER_LEN,
+                protocol,
+                pkttype: frame_type as u8,
+                ifindex: iface_index,
+                hatype: InterfaceType::ETHER as u16,
+            };
+            let snap_len = filter.run(&packet) as usize;
+            if snap_len == 0 {
+                return;
+            }
+            data = &data[..snap_len.min(data.len())];
+        }
+
+        if PACKET_DEFAULT_BUF_SIZE - inner.queued_len < data.len() {
+            return;
+        }
+
+        let mut addr = LinkLayerSocketAddr {
+            protocol,
+            ifindex: iface_index,
+            hatype: InterfaceType::ETHER as u16,
+            pkttype: frame_type as u8,
+            halen: 6,
+            addr: [0; 8],
+        };
+        // The source address is at offset 6 of the Ethernet header.
+        addr.addr[..6].copy_from_slice(&frame[6..12]);
+
+        inner.queued_len += data.len();
+        inner.queue.push_back(ReceivedFrame {
+            data: data.to_vec(),
+            addr,
+        });
+        drop(inner);
+
+        self.pollee.notify(IoEvents::IN);
+    }
+}
+
+/// Returns the link-layer protocol of an Ethernet frame in host byte order.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/ethernet/eth.c> (`eth_type_trans`)
+fn frame_protocol(frame: &[u8]) -> u16 {
+    /// The minimum value of EtherTypes. Smaller values are frame lengths of IEEE 802.3 frames.
+    const ETH_P_802_3_MIN: u16 = 0x0600;
+    /// The protocol of raw IEEE 802.3 frames (i.e., Novell IPX frames).
+    const ETH_P_802_3: u16 = 0x0001;
+    /// The protocol of IEEE 802.2 LLC frames.
+    const ETH_P_802_2: u16 = 0x0004;
+
+    let ether_type = u16::from_be_bytes([frame[12], frame[13]]);
+    if ether_type >= ETH_P_802_3_MIN {
+        ether_type
+    } else if frame[ETHERNET_HEADER_LEN..].starts_with(&[0xff, 0xff]) {
+        ETH_P_802_3
+    } else {
+        ETH_P_802_2
+    }
+}
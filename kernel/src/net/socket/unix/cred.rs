@@ -9,6 +9,10 @@ use crate::{
 };
 
 pub(super) struct SocketCred<R = ReadOp> {
+    /// The global PID.
+    ///
+    /// It is translated to the PID namespace of the current process when converted to a
+    /// [`CUserCred`].
     pid: Pid,
     cred: Credentials<R>,
 }
@@ -36,25 +40,34 @@ impl SocketCred<ReadDupOp> {
 
 impl<R: TRights> SocketCred<R> {
     /// Converts to a [`CUserCred`] with the PID and the _effective_ UID/GID.
+    ///
+    /// The PID is the one seen in the PID namespace of the current process, or zero if the
+    /// process is not visible there.
     #[require(R > Read)]
     pub(super) fn to_effective_c_cred(&self) -> CUserCred {
         CUserCred {
-            pid: self.pid,
+            pid: self.local_pid(),
             uid: self.cred.euid(),
             gid: self.cred.egid(),
         }
     }
 
     /// Converts to a [`CUserCred`] with the PID and the _real_ UID/GID.
+    ///
+    /// The PID is translated in the same way as [`Self::to_effective_c_cred`].
     #[require(R > Read)]
     pub(super) fn to_real_c_cred(&self) -> CUserCred {
         CUserCred {
-            pid: self.pid,
+            pid: self.local_pid(),
             uid: self.cred.ruid(),
             gid: self.cred.rgid(),
         }
     }
 
+    fn local_pid(&self) -> Pid {
+        current!().pid_ns().local_id(self.pid)
+    }
+
     #[require(R > Read)]
     pub(super) fn groups(&self) -> Arc<[Gid]> {
         self.cred.groups().iter().cloned().collect()
@@ -215,8 +215,10 @@ impl AuxiliaryData {
         let mut cred = None;
 
         for ctrl_msg in ctrl_msgs.into_iter() {
-            let ControlMessage::Unix(unix_ctrl_msg) = ctrl_msg;
             // TODO: What should we do if there are control messages of other protocols?
+            let ControlMessage::Unix(unix_ctrl_msg) = ctrl_msg else {
+                continue;
+            };
 
             match unix_ctrl_msg.0 {
                 Message::Files(FileMessage {
@@ -232,6 +234,7 @@ impl AuxiliaryData {
                 }
                 Message::Cred(CredMessage { cred: msg_cred }) => {
                     let cur_cred = SocketCred::<ReadOp>::new_current();
+                    // The PID is checked against the sender's PID in its own PID namespace.
                     if cur_cred.to_real_c_cred() != msg_cred {
                         // FIXME: Allow this if we're root or have the CAP_SYS_ADMIN capability.
                         return_errno_with_message!(
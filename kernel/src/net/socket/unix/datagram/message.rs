@@ -22,6 +22,8 @@ use crate::{
 
 pub(super) struct MessageQueue {
     addr: Once<UnixSocketAddr>,
+    /// The inode number of the socket that owns the queue.
+    ino: u64,
     inner: Mutex<Option<Inner>>,
     is_pass_cred: AtomicBool,
     pollee: Pollee,
@@ -101,6 +103,10 @@ impl MessageQueue {
         self.addr.get().cloned().unwrap_or(UnixSocketAddr::Unnamed)
     }
 
+    pub(super) fn ino(&self) -> u64 {
+        self.ino
+    }
+
     /// Blocks until the buffer is free and the `try_send` succeeds, or until interrupted.
     pub(super) fn block_send<F, R>(&self, mut try_send: F) -> Result<R>
     where
@@ -124,7 +130,7 @@ pub(super) struct MessageReceiver {
 }
 
 impl MessageReceiver {
-    pub(super) fn new() -> MessageReceiver {
+    pub(super) fn new(ino: u64) -> MessageReceiver {
         let inner = Inner {
             messages: VecDeque::new(),
             total_length: 0,
@@ -133,6 +139,7 @@ impl MessageReceiver {
 
         let queue = MessageQueue {
             addr: Once::new(),
+            ino,
             inner: Mutex::new(Some(inner)),
             pollee: Pollee::new(),
             send_wait_queue: WaitQueue::new(),
@@ -214,6 +221,12 @@ impl MessageReceiver {
         self.queue.addr()
     }
 
+    /// Returns the total length of the messages to receive.
+    pub(super) fn recv_queue_len(&self) -> usize {
+        let inner = self.queue.inner.lock();
+        inner.as_ref().unwrap().total_length
+    }
+
     pub(super) fn queue(&self) -> &Arc<MessageQueue> {
         &self.queue
     }
@@ -8,19 +8,28 @@ use super::message::{MessageQueue, MessageReceiver};
 use crate::{
     events::IoEvents,
     fs::{pseudofs::SockFs, vfs::path::Path},
-    net::socket::{
-        Socket,
-        options::{Error as SocketError, PeerCred, SocketOption, macros::sock_option_mut},
-        private::SocketPrivate,
-        unix::{CUserCred, UnixSocketAddr, cred::SocketCred, ctrl_msg::AuxiliaryData},
-        util::{
-            MessageHeader, SendRecvFlags, SockShutdownCmd, SocketAddr,
-            options::{GetSocketLevelOption, SetSocketLevelOption, SocketOptionSet},
+    net::{
+        net_ns::NetNamespace,
+        socket::{
+            Socket,
+            ip::stream_options::{TCP_CLOSE, TCP_ESTABLISHED},
+            options::{Error as SocketError, PeerCred, SocketOption, macros::sock_option_mut},
+            private::SocketPrivate,
+            unix::{
+                CUserCred, UnixSocketAddr, UnixSocketEntry,
+                cred::SocketCred,
+                ctrl_msg::AuxiliaryData,
+                diag::{UnixSocketDiag, register_socket, unregister_socket},
+            },
+            util::{
+                MessageHeader, SendRecvFlags, SockShutdownCmd, SocketAddr, SocketInode,
+                options::{GetSocketLevelOption, SetSocketLevelOption, SocketOptionSet},
+            },
         },
     },
     prelude::*,
     process::signal::{PollHandle, Pollable},
-    util::{MultiRead, MultiWrite},
+    util::{MultiRead, MultiWrite, net::SockType},
 };
 
 pub struct UnixDatagramSocket {
@@ -35,6 +44,7 @@ pub struct UnixDatagramSocket {
     is_nonblocking: AtomicBool,
     is_write_shutdown: AtomicBool,
     pseudo_path: Path,
+    net_ns: Arc<NetNamespace>,
 }
 
 #[derive(Clone, Debug)]
@@ -51,13 +61,13 @@ impl OptionSet {
 }
 
 impl UnixDatagramSocket {
-    pub fn new(is_nonblocking: bool) -> Arc<Self> {
-        Arc::new(Self::new_raw(is_nonblocking))
+    pub fn new(is_nonblocking: bool, net_ns: Arc<NetNamespace>) -> Arc<Self> {
+        Self::new_registered(Self::new_raw(is_nonblocking, net_ns))
     }
 
-    pub fn new_pair(is_nonblocking: bool) -> (Arc<Self>, Arc<Self>) {
-        let mut socket_a = Self::new_raw(is_nonblocking);
-        let mut socket_b = Self::new_raw(is_nonblocking);
+    pub fn new_pair(is_nonblocking: bool, net_ns: Arc<NetNamespace>) -> (Arc<Self>, Arc<Self>) {
+        let mut socket_a = Self::new_raw(is_nonblocking, net_ns.clone());
+        let mut socket_b = Self::new_raw(is_nonblocking, net_ns);
 
         let cred = SocketCred::<ReadDupOp>::new_current();
         socket_a.peer_cred = Some(cred.dup().restrict());
@@ -69,21 +79,40 @@ impl UnixDatagramSocket {
         *remote_queue_a = Some(socket_b.local_receiver.queue().clone());
         *remote_queue_b = Some(socket_a.local_receiver.queue().clone());
 
-        (Arc::new(socket_a), Arc::new(socket_b))
+        (
+            Self::new_registered(socket_a),
+            Self::new_registered(socket_b),
+        )
     }
 
-    fn new_raw(is_nonblocking: bool) -> Self {
+    fn new_raw(is_nonblocking: bool, net_ns: Arc<NetNamespace>) -> Self {
+        let pseudo_path = SockFs::new_path();
+
         Self {
-            local_receiver: MessageReceiver::new(),
+            local_receiver: MessageReceiver::new(pseudo_path.inode().ino()),
             remote_queue: RwLock::new(None),
             options: RwLock::new(OptionSet::new()),
             peer_cred: None,
             is_nonblocking: AtomicBool::new(is_nonblocking),
             is_write_shutdown: AtomicBool::new(false),
-            pseudo_path: SockFs::new_path(),
+            pseudo_path,
+            net_ns,
         }
     }
 
+    fn new_registered(socket: Self) -> Arc<Self> {
+        let socket = Arc::new(socket);
+        register_socket(
+            socket.ino(),
+            Arc::downgrade(&socket) as Weak<dyn UnixSocketDiag>,
+        );
+        socket
+    }
+
+    fn ino(&self) -> u64 {
+        self.local_receiver.queue().ino()
+    }
+
     fn do_send(
         &self,
         reader: &mut dyn MultiRead,
@@ -303,6 +332,37 @@ impl Socket for UnixDatagramSocket {
     }
 }
 
+impl UnixSocketDiag for UnixDatagramSocket {
+    fn net_ns(&self) -> &Arc<NetNamespace> {
+        &self.net_ns
+    }
+
+    fn entry(&self) -> UnixSocketEntry {
+        let peer_ino = self.remote_queue.read().as_ref().map(|queue| queue.ino());
+
+        UnixSocketEntry {
+            sock_type: SockType::SOCK_DGRAM,
+            state: if peer_ino.is_some() {
+                TCP_ESTABLISHED
+            } else {
+                TCP_CLOSE
+            },
+            inode: SocketInode::of(&self.pseudo_path),
+            addr: self.local_receiver.addr(),
+            peer_ino,
+            recv_queue: self.local_receiver.recv_queue_len(),
+            // The messages are sent to the receive queue of the peer directly.
+            send_queue: 0,
+        }
+    }
+}
+
+impl Drop for UnixDatagramSocket {
+    fn drop(&mut self) {
+        unregister_socket(self.ino());
+    }
+}
+
 fn do_unix_getsockopt(option: &mut dyn SocketOption, socket: &UnixDatagramSocket) -> Result<()> {
     sock_option_mut!(match option {
         socket_peer_cred @ PeerCred => {
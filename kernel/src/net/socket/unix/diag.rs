@@ -0,0 +1,106 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Reporting of UNIX domain sockets to user space.
+//!
+//! All live UNIX domain sockets are recorded in a global table, so that they can be reported via
+//! `/proc/net/unix` and `NETLINK_SOCK_DIAG`.
+
+use super::UnixSocketAddr;
+use crate::{
+    net::{
+        net_ns::NetNamespace,
+        socket::{
+            ip::stream_options::{TCP_ESTABLISHED, TCP_LISTEN},
+            util::SocketInode,
+        },
+    },
+    prelude::*,
+    process::Uid,
+    util::net::SockType,
+};
+
+/// A UNIX domain socket.
+#[derive(Clone, Debug)]
+pub struct UnixSocketEntry {
+    pub sock_type: SockType,
+    /// The TCP state (e.g., `TCP_ESTABLISHED`), which is also used by UNIX domain sockets.
+    pub state: u8,
+    pub inode: SocketInode,
+    /// The bound address, which is unnamed if the socket is not bound.
+    pub addr: UnixSocketAddr,
+    /// The inode number of the peer, which is absent if the socket is not connected.
+    pub peer_ino: Option<u64>,
+    /// The number of bytes to receive, or the number of connections to accept for listeners.
+    pub recv_queue: usize,
+    /// The number of bytes to send, or the maximum number of connections to accept for
+    /// listeners.
+    pub send_queue: usize,
+}
+
+impl UnixSocketEntry {
+    pub fn ino(&self) -> u64 {
+        self.inode.ino()
+    }
+
+    pub fn uid(&self) -> Uid {
+        self.inode.uid()
+    }
+
+    /// Returns whether the socket is listening.
+    pub fn is_listening(&self) -> bool {
+        self.state == TCP_LISTEN
+    }
+
+    /// Returns whether the socket is connected.
+    pub fn is_connected(&self) -> bool {
+        self.state == TCP_ESTABLISHED
+    }
+}
+
+/// Returns the live UNIX domain sockets in the network namespace, sorted by their inode numbers.
+pub fn unix_socket_entries(net_ns: &NetNamespace) -> Vec<UnixSocketEntry> {
+    // The sockets are collected first because querying them may sleep.
+    let sockets: Vec<_> = SOCKET_TABLE
+        .sockets
+        .read()
+        .values()
+        .filter_map(Weak::upgrade)
+        .filter(|socket| core::ptr::eq(socket.net_ns().as_ref(), net_ns))
+        .collect();
+
+    sockets.iter().map(|socket| socket.entry()).collect()
+}
+
+/// A UNIX domain socket that can be reported to user space.
+pub(super) trait UnixSocketDiag: Send + Sync {
+    /// Returns the network namespace where the socket is created.
+    fn net_ns(&self) -> &Arc<NetNamespace>;
+
+    fn entry(&self) -> UnixSocketEntry;
+}
+
+/// Records a new socket in the global table.
+pub(super) fn register_socket(ino: u64, socket: Weak<dyn UnixSocketDiag>) {
+    let old_socket = SOCKET_TABLE.sockets.write().insert(ino, socket);
+    debug_assert!(old_socket.is_none());
+}
+
+/// Removes a dying socket from the global table.
+pub(super) fn unregister_socket(ino: u64) {
+    let old_socket = SOCKET_TABLE.sockets.write().remove(&ino);
+    debug_assert!(old_socket.is_some());
+}
+
+static SOCKET_TABLE: SocketTable = SocketTable::new();
+
+struct SocketTable {
+    sockets: RwLock<BTreeMap<u64, Weak<dyn UnixSocketDiag>>>,
+}
+
+impl SocketTable {
+    const fn new() -> Self {
+        Self {
+            sockets: RwLock::new(BTreeMap::new()),
+        }
+    }
+}
@@ -45,6 +45,7 @@ impl Connected {
 
         let this_inner = Inner {
             addr: Once::new(),
+            ino: Once::new(),
             state,
             reader: Mutex::new(this_reader),
             writer: Mutex::new(this_writer),
@@ -55,6 +56,7 @@ impl Connected {
         };
         let peer_inner = Inner {
             addr: Once::new(),
+            ino: Once::new(),
             state: peer_state,
             reader: Mutex::new(peer_reader),
             writer: Mutex::new(peer_writer),
@@ -98,6 +100,26 @@ impl Connected {
             .unwrap_or(UnixSocketAddr::Unnamed)
     }
 
+    /// Records the inode number of the socket that owns this end.
+    pub(super) fn set_ino(&self, ino: u64) {
+        self.inner.this_end().ino.call_once(|| ino);
+    }
+
+    /// Returns the inode number of the socket that owns the peer end.
+    ///
+    /// This method returns `None` if the peer end has not been accepted.
+    pub(super) fn peer_ino(&self) -> Option<u64> {
+        self.inner.peer_end().ino.get().copied()
+    }
+
+    /// Returns the number of bytes to receive and the number of bytes to be received by the peer.
+    pub(super) fn queue_lens(&self) -> (usize, usize) {
+        let this_end = self.inner.this_end();
+        let recv_len = this_end.reader.lock().len();
+        let send_len = this_end.writer.lock().len();
+        (recv_len, send_len)
+    }
+
     pub(super) fn bind(&mut self, addr_to_bind: UnixSocketAddr) -> Result<()> {
         if self.addr.is_some() {
             return addr_to_bind.bind_unnamed();
@@ -365,6 +387,7 @@ impl Drop for Connected {
 
 struct Inner {
     addr: Once<UnixSocketAddr>,
+    ino: Once<u64>,
     state: EndpointState,
     // Lock order: `reader` -> `all_aux` & `all_aux` -> `writer`
     reader: Mutex<RbConsumer<u8>>,
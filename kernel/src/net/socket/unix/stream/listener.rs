@@ -14,14 +14,17 @@ use super::{
 use crate::{
     events::IoEvents,
     fs::file::FileLike,
-    net::socket::{
-        SocketAddr,
-        unix::{
-            addr::{UnixSocketAddrBound, UnixSocketAddrKey},
-            cred::SocketCred,
-            stream::socket::OptionSet,
+    net::{
+        net_ns::NetNamespace,
+        socket::{
+            SocketAddr,
+            unix::{
+                addr::{UnixSocketAddrBound, UnixSocketAddrKey},
+                cred::SocketCred,
+                stream::socket::OptionSet,
+            },
+            util::SockShutdownCmd,
         },
-        util::SockShutdownCmd,
     },
     prelude::*,
     process::signal::Pollee,
@@ -56,12 +59,13 @@ impl Listener {
     }
 
     pub(super) fn try_accept(&self, is_seqpacket: bool) -> Result<(Arc<dyn FileLike>, SocketAddr)> {
-        let connected = self.backlog.pop_incoming()?;
+        let (connected, net_ns) = self.backlog.pop_incoming()?;
 
         let peer_addr = connected.peer_addr().into();
         let options = OptionSet::new_accepted(connected.is_pass_cred());
 
-        let socket = UnixStreamSocket::new_connected(connected, options, false, is_seqpacket);
+        let socket =
+            UnixStreamSocket::new_connected(connected, options, false, is_seqpacket, net_ns);
         Ok((socket, peer_addr))
     }
 
@@ -98,6 +102,17 @@ impl Listener {
         self.backlog.check_io_events()
     }
 
+    /// Returns the number of pending connections and the maximum number of them.
+    pub(super) fn backlog_lens(&self) -> (usize, usize) {
+        let num_pending = self
+            .backlog
+            .incoming_conns
+            .lock()
+            .as_ref()
+            .map_or(0, |conns| conns.len());
+        (num_pending, self.backlog.backlog.load(Ordering::Relaxed))
+    }
+
     pub(super) fn cred(&self) -> &SocketCred<ReadDupOp> {
         &self.backlog.listener_cred
     }
@@ -167,7 +182,13 @@ pub(super) struct Backlog {
     addr: UnixSocketAddrBound,
     pollee: Pollee,
     backlog: AtomicUsize,
-    incoming_conns: SpinLock<Option<VecDeque<Connected>>>,
+    /// The pending connections and the network namespaces of the connecting sockets.
+    ///
+    /// Like Linux, an accepted socket belongs to the network namespace of the connecting socket,
+    /// which can differ from that of the listening socket if the address is a path name.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/unix/af_unix.c>
+    incoming_conns: SpinLock<Option<VecDeque<(Connected, Arc<NetNamespace>)>>>,
     connect_wait_queue: WaitQueue,
     listener_cred: SocketCred<ReadDupOp>,
     is_pass_cred: AtomicBool,
@@ -204,7 +225,7 @@ impl Backlog {
         &self.addr
     }
 
-    fn pop_incoming(&self) -> Result<Connected> {
+    fn pop_incoming(&self) -> Result<(Connected, Arc<NetNamespace>)> {
         let mut locked_incoming_conns = self.incoming_conns.lock();
 
         let Some(incoming_conns) = &mut *locked_incoming_conns else {
@@ -262,6 +283,7 @@ impl Backlog {
         pollee: Pollee,
         options: &OptionSet,
         is_seqpacket: bool,
+        net_ns: &Arc<NetNamespace>,
     ) -> Result<Connected, (Error, Init)> {
         if is_seqpacket != self.is_seqpacket {
             // FIXME: According to the Linux implementation, we should avoid this error by
@@ -308,7 +330,7 @@ impl Backlog {
             server_conn.set_pass_cred(true);
         }
 
-        incoming_conns.push_back(server_conn);
+        incoming_conns.push_back((server_conn, net_ns.clone()));
         self.pollee.notify(IoEvents::IN);
 
         Ok(client_conn)
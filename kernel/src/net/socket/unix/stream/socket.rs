@@ -13,16 +13,26 @@ use super::{
 use crate::{
     events::IoEvents,
     fs::{file::FileLike, pseudofs::SockFs, utils::EndpointState, vfs::path::Path},
-    net::socket::{
-        Socket,
-        options::{
-            Error as SocketError, PeerCred, PeerGroups, SocketOption, macros::sock_option_mut,
-        },
-        private::SocketPrivate,
-        unix::{CUserCred, UnixSocketAddr, cred::SocketCred, ctrl_msg::AuxiliaryData},
-        util::{
-            ControlMessage, MessageHeader, SendRecvFlags, SockShutdownCmd, SocketAddr,
-            options::{GetSocketLevelOption, SetSocketLevelOption, SocketOptionSet},
+    net::{
+        net_ns::NetNamespace,
+        socket::{
+            Socket,
+            ip::stream_options::{TCP_CLOSE, TCP_ESTABLISHED, TCP_LISTEN},
+            options::{
+                Error as SocketError, PeerCred, PeerGroups, SocketOption, macros::sock_option_mut,
+            },
+            private::SocketPrivate,
+            unix::{
+                CUserCred, UnixSocketAddr, UnixSocketEntry,
+                cred::SocketCred,
+                ctrl_msg::AuxiliaryData,
+                diag::{UnixSocketDiag, register_socket, unregister_socket},
+            },
+            util::{
+                ControlMessage, MessageHeader, SendRecvFlags, SockShutdownCmd, SocketAddr,
+                SocketInode,
+                options::{GetSocketLevelOption, SetSocketLevelOption, SocketOptionSet},
+            },
         },
     },
     prelude::*,
@@ -30,7 +40,7 @@ use crate::{
         Gid,
         signal::{PollHandle, Pollable, Pollee},
     },
-    util::{MultiRead, MultiWrite},
+    util::{MultiRead, MultiWrite, net::SockType},
 };
 
 pub struct UnixStreamSocket {
@@ -43,6 +53,7 @@ pub struct UnixStreamSocket {
 
     is_seqpacket: bool,
     pseudo_path: Path,
+    net_ns: Arc<NetNamespace>,
 }
 
 enum State {
@@ -163,22 +174,32 @@ impl OptionSet {
 }
 
 impl UnixStreamSocket {
-    pub fn new(is_nonblocking: bool, is_seqpacket: bool) -> Arc<Self> {
-        Self::new_init(Init::new(), is_nonblocking, is_seqpacket)
+    pub fn new(is_nonblocking: bool, is_seqpacket: bool, net_ns: Arc<NetNamespace>) -> Arc<Self> {
+        Self::new_init(Init::new(), is_nonblocking, is_seqpacket, net_ns)
     }
 
-    fn new_init(init: Init, is_nonblocking: bool, is_seqpacket: bool) -> Arc<Self> {
-        Arc::new(Self {
+    fn new_init(
+        init: Init,
+        is_nonblocking: bool,
+        is_seqpacket: bool,
+        net_ns: Arc<NetNamespace>,
+    ) -> Arc<Self> {
+        Self::new_registered(Self {
             state: RwMutex::new(Takeable::new(State::Init(init))),
             options: RwLock::new(OptionSet::new()),
             pollee: Pollee::new(),
             is_nonblocking: AtomicBool::new(is_nonblocking),
             is_seqpacket,
             pseudo_path: SockFs::new_path(),
+            net_ns,
         })
     }
 
-    pub fn new_pair(is_nonblocking: bool, is_seqpacket: bool) -> (Arc<Self>, Arc<Self>) {
+    pub fn new_pair(
+        is_nonblocking: bool,
+        is_seqpacket: bool,
+        net_ns: Arc<NetNamespace>,
+    ) -> (Arc<Self>, Arc<Self>) {
         let cred = SocketCred::<ReadDupOp>::new_current();
 
         let (conn_a, conn_b) = Connected::new_pair(
@@ -190,8 +211,20 @@ impl UnixStreamSocket {
             cred.restrict(),
         );
         (
-            Self::new_connected(conn_a, OptionSet::new(), is_nonblocking, is_seqpacket),
-            Self::new_connected(conn_b, OptionSet::new(), is_nonblocking, is_seqpacket),
+            Self::new_connected(
+                conn_a,
+                OptionSet::new(),
+                is_nonblocking,
+                is_seqpacket,
+                net_ns.clone(),
+            ),
+            Self::new_connected(
+                conn_b,
+                OptionSet::new(),
+                is_nonblocking,
+                is_seqpacket,
+                net_ns,
+            ),
         )
     }
 
@@ -200,18 +233,36 @@ impl UnixStreamSocket {
         options: OptionSet,
         is_nonblocking: bool,
         is_seqpacket: bool,
+        net_ns: Arc<NetNamespace>,
     ) -> Arc<Self> {
         let cloned_pollee = connected.cloned_pollee();
-        Arc::new(Self {
+        let pseudo_path = SockFs::new_path();
+        connected.set_ino(pseudo_path.inode().ino());
+
+        Self::new_registered(Self {
             state: RwMutex::new(Takeable::new(State::Connected(connected))),
             options: RwLock::new(options),
             pollee: cloned_pollee,
             is_nonblocking: AtomicBool::new(is_nonblocking),
             is_seqpacket,
-            pseudo_path: SockFs::new_path(),
+            pseudo_path,
+            net_ns,
         })
     }
 
+    fn new_registered(socket: Self) -> Arc<Self> {
+        let socket = Arc::new(socket);
+        register_socket(
+            socket.pseudo_path.inode().ino(),
+            Arc::downgrade(&socket) as Weak<dyn UnixSocketDiag>,
+        );
+        socket
+    }
+
+    fn ino(&self) -> u64 {
+        self.pseudo_path.inode().ino()
+    }
+
     fn try_send(
         &self,
         buf: &mut dyn MultiRead,
@@ -270,10 +321,12 @@ impl UnixStreamSocket {
                 self.pollee.clone(),
                 &self.options.read(),
                 self.is_seqpacket,
+                &self.net_ns,
             ) {
                 Ok(connected) => connected,
                 Err((err, init)) => return (State::Init(init), Err(err)),
             };
+            connected.set_ino(self.ino());
 
             (State::Connected(connected), Ok(()))
         })
@@ -519,6 +572,53 @@ impl Socket for UnixStreamSocket {
     }
 }
 
+impl UnixSocketDiag for UnixStreamSocket {
+    fn net_ns(&self) -> &Arc<NetNamespace> {
+        &self.net_ns
+    }
+
+    fn entry(&self) -> UnixSocketEntry {
+        let sock_type = if self.is_seqpacket {
+            SockType::SOCK_SEQPACKET
+        } else {
+            SockType::SOCK_STREAM
+        };
+
+        let state = self.state.read();
+        let (tcp_state, addr, peer_ino, (recv_queue, send_queue)) = match state.as_ref() {
+            State::Init(init) => (TCP_CLOSE, init.addr().cloned(), None, (0, 0)),
+            State::Listen(listener) => (
+                TCP_LISTEN,
+                Some(listener.addr().clone()),
+                None,
+                listener.backlog_lens(),
+            ),
+            State::Connected(connected) => (
+                TCP_ESTABLISHED,
+                connected.addr().cloned(),
+                connected.peer_ino(),
+                connected.queue_lens(),
+            ),
+        };
+
+        UnixSocketEntry {
+            sock_type,
+            state: tcp_state,
+            inode: SocketInode::of(&self.pseudo_path),
+            addr: addr.into(),
+            peer_ino,
+            recv_queue,
+            send_queue,
+        }
+    }
+}
+
+impl Drop for UnixStreamSocket {
+    fn drop(&mut self) {
+        unregister_socket(self.ino());
+    }
+}
+
 fn do_unix_getsockopt(option: &mut dyn SocketOption, state: &State) -> Result<()> {
     sock_option_mut!(match option {
         socket_peer_cred @ PeerCred => {
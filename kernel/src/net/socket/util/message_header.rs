@@ -3,7 +3,11 @@
 use align_ext::AlignExt;
 
 use super::SocketAddr;
-use crate::{net::socket::unix::UnixControlMessage, prelude::*, util::net::CSocketOptionLevel};
+use crate::{
+    net::socket::{ip::IpControlMessage, unix::UnixControlMessage},
+    prelude::*,
+    util::net::CSocketOptionLevel,
+};
 
 /// Message header used for sendmsg/recvmsg.
 #[derive(Debug)]
@@ -36,6 +40,7 @@ impl MessageHeader {
 #[derive(Debug)]
 pub enum ControlMessage {
     Unix(UnixControlMessage),
+    Ip(IpControlMessage),
 }
 
 impl ControlMessage {
@@ -90,6 +95,12 @@ impl ControlMessage {
                 let msg = UnixControlMessage::read_from(header, reader)?;
                 Ok(msg.map(Self::Unix))
             }
+            CSocketOptionLevel::SOL_IP
+            | CSocketOptionLevel::SOL_IPV6
+            | CSocketOptionLevel::SOL_UDP => {
+                let msg = IpControlMessage::read_from(header, reader)?;
+                Ok(msg.map(Self::Ip))
+            }
             _ => {
                 warn!("unsupported control message level in {:?}", header);
                 reader.skip(header.payload_len());
@@ -127,6 +138,7 @@ impl ControlMessage {
     fn write_to(&self, writer: &mut VmWriter) -> Result<CControlHeader> {
         match self {
             Self::Unix(msg) => msg.write_to(writer),
+            Self::Ip(msg) => msg.write_to(writer),
         }
     }
 }
@@ -8,6 +8,8 @@ mod port_privilege;
 mod send_recv_flags;
 mod shutdown_cmd;
 mod socket_addr;
+mod socket_filter;
+mod socket_inode;
 
 pub use linger_option::LingerOption;
 pub(super) use message_header::CControlHeader;
@@ -16,3 +18,6 @@ pub(super) use port_privilege::check_port_privilege;
 pub use send_recv_flags::SendRecvFlags;
 pub use shutdown_cmd::SockShutdownCmd;
 pub use socket_addr::SocketAddr;
+pub(super) use socket_filter::FilterPacket;
+pub use socket_filter::{BPF_MAXINSNS, CSockFilter, SocketFilter};
+pub use socket_inode::SocketInode;
@@ -3,7 +3,8 @@
 use core::ops::RangeInclusive;
 
 use aster_bigtcp::socket::{
-    NeedIfacePoll, TCP_RECV_BUF_LEN, TCP_SEND_BUF_LEN, UDP_RECV_PAYLOAD_LEN, UDP_SEND_PAYLOAD_LEN,
+    NeedIfacePoll, RAW_RECV_PAYLOAD_LEN, RAW_SEND_PAYLOAD_LEN, TCP_RECV_BUF_LEN, TCP_SEND_BUF_LEN,
+    UDP_RECV_PAYLOAD_LEN, UDP_SEND_PAYLOAD_LEN,
 };
 
 use super::LingerOption;
@@ -13,8 +14,10 @@ use crate::{
         options::{
             AcceptConn, Broadcast, KeepAlive, Linger, PassCred, PeerCred, PeerGroups, Priority,
             RecvBuf, RecvBufForce, ReuseAddr, ReusePort, SendBuf, SendBufForce, SocketOption,
+            Timestamp, TimestampNs,
             macros::{sock_option_mut, sock_option_ref},
         },
+        packet::PACKET_DEFAULT_BUF_SIZE,
         unix::{CUserCred, UNIX_DATAGRAM_DEFAULT_BUF_SIZE, UNIX_STREAM_DEFAULT_BUF_SIZE},
     },
     prelude::*,
@@ -34,6 +37,16 @@ pub struct SocketOptionSet {
     linger: LingerOption,
     reuse_port: bool,
     pass_cred: bool,
+    timestamp: Option<TimestampFormat>,
+}
+
+/// The format of the timestamps of received packets.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TimestampFormat {
+    /// `struct timeval`, enabled by `SO_TIMESTAMP`.
+    Timeval,
+    /// `struct timespec`, enabled by `SO_TIMESTAMPNS`.
+    Timespec,
 }
 
 impl Default for SocketOptionSet {
@@ -48,6 +61,7 @@ impl Default for SocketOptionSet {
             linger: LingerOption::default(),
             reuse_port: false,
             pass_cred: false,
+            timestamp: None,
         }
     }
 }
@@ -71,6 +85,15 @@ impl SocketOptionSet {
         }
     }
 
+    /// Returns the default socket level options for raw IP socket and ping socket.
+    pub(in crate::net) fn new_raw() -> Self {
+        Self {
+            send_buf: RAW_SEND_PAYLOAD_LEN as u32,
+            recv_buf: RAW_RECV_PAYLOAD_LEN as u32,
+            ..Default::default()
+        }
+    }
+
     /// Returns the default socket level options for unix stream socket.
     pub(in crate::net) fn new_unix_stream() -> Self {
         Self {
@@ -98,6 +121,15 @@ impl SocketOptionSet {
         }
     }
 
+    /// Returns the default socket level options for packet socket.
+    pub(in crate::net) fn new_packet() -> Self {
+        Self {
+            send_buf: PACKET_DEFAULT_BUF_SIZE as u32,
+            recv_buf: PACKET_DEFAULT_BUF_SIZE as u32,
+            ..Default::default()
+        }
+    }
+
     /// Gets socket-level options.
     ///
     /// Note that the socket error has to be handled separately. This method does not handle it
@@ -150,6 +182,14 @@ impl SocketOptionSet {
                 let peer_cred = CUserCred::new_invalid();
                 socket_peer_cred.set(peer_cred);
             }
+            socket_timestamp @ Timestamp => {
+                let timestamp = self.timestamp();
+                socket_timestamp.set(timestamp == Some(TimestampFormat::Timeval));
+            }
+            socket_timestamp_ns @ TimestampNs => {
+                let timestamp = self.timestamp();
+                socket_timestamp_ns.set(timestamp == Some(TimestampFormat::Timespec));
+            }
             socket_accept_conn @ AcceptConn => {
                 let is_listening = socket.is_listening();
                 socket_accept_conn.set(is_listening);
@@ -232,6 +272,16 @@ impl SocketOptionSet {
                 self.set_pass_cred(*pass_cred);
                 socket.set_pass_cred(*pass_cred);
             }
+            // Like Linux, disabling either option disables the timestamps in both formats.
+            // Currently, only UDP sockets generate the timestamps.
+            socket_timestamp @ Timestamp => {
+                let timestamp = socket_timestamp.get().unwrap();
+                self.set_timestamp(timestamp.then_some(TimestampFormat::Timeval));
+            }
+            socket_timestamp_ns @ TimestampNs => {
+                let timestamp_ns = socket_timestamp_ns.get().unwrap();
+                self.set_timestamp(timestamp_ns.then_some(TimestampFormat::Timespec));
+            }
             socket_sendbuf_force @ SendBufForce => {
                 check_current_privileged()?;
                 let send_buf = socket_sendbuf_force.get().unwrap();
@@ -0,0 +1,436 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Classic BPF socket filters (i.e., `SO_ATTACH_FILTER`).
+//!
+//! Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/core/filter.c>.
+
+use crate::prelude::*;
+
+/// A classic BPF instruction.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/filter.h#L24>.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub struct CSockFilter {
+    /// The opcode.
+    code: u16,
+    /// The jump offset if the condition is true.
+    jt: u8,
+    /// The jump offset if the condition is false.
+    jf: u8,
+    /// The generic multiuse field.
+    k: u32,
+}
+
+/// A validated classic BPF program attached to a socket.
+#[derive(Clone, Debug)]
+pub struct SocketFilter {
+    insns: Arc<[CSockFilter]>,
+}
+
+/// The maximum number of instructions in a program.
+pub const BPF_MAXINSNS: usize = 4096;
+
+/// The number of words in the scratch memory.
+const BPF_MEMWORDS: usize = 16;
+
+// Instruction classes
+const BPF_LD: u16 = 0x00;
+const BPF_LDX: u16 = 0x01;
+const BPF_ST: u16 = 0x02;
+const BPF_STX: u16 = 0x03;
+const BPF_ALU: u16 = 0x04;
+const BPF_JMP: u16 = 0x05;
+const BPF_RET: u16 = 0x06;
+const BPF_MISC: u16 = 0x07;
+
+// Load sizes
+const BPF_W: u16 = 0x00;
+const BPF_H: u16 = 0x08;
+const BPF_B: u16 = 0x10;
+
+// Load modes
+const BPF_IMM: u16 = 0x00;
+const BPF_ABS: u16 = 0x20;
+const BPF_IND: u16 = 0x40;
+const BPF_MEM: u16 = 0x60;
+const BPF_LEN: u16 = 0x80;
+const BPF_MSH: u16 = 0xa0;
+
+// ALU and jump operations
+const BPF_ADD: u16 = 0x00;
+const BPF_SUB: u16 = 0x10;
+const BPF_MUL: u16 = 0x20;
+const BPF_DIV: u16 = 0x30;
+const BPF_OR: u16 = 0x40;
+const BPF_AND: u16 = 0x50;
+const BPF_LSH: u16 = 0x60;
+const BPF_RSH: u16 = 0x70;
+const BPF_NEG: u16 = 0x80;
+const BPF_MOD: u16 = 0x90;
+const BPF_XOR: u16 = 0xa0;
+const BPF_JA: u16 = 0x00;
+const BPF_JEQ: u16 = 0x10;
+const BPF_JGT: u16 = 0x20;
+const BPF_JGE: u16 = 0x30;
+const BPF_JSET: u16 = 0x40;
+
+// Operand sources
+const BPF_K: u16 = 0x00;
+const BPF_X: u16 = 0x08;
+const BPF_A: u16 = 0x10;
+
+// Miscellaneous operations
+const BPF_TAX: u16 = 0x00;
+const BPF_TXA: u16 = 0x80;
+
+// Special offsets for loads
+const SKF_AD_OFF: u32 = -0x1000i32 as u32;
+const SKF_NET_OFF: u32 = -0x100000i32 as u32;
+const SKF_LL_OFF: u32 = -0x200000i32 as u32;
+
+// Ancillary data that can be loaded
+const SKF_AD_PROTOCOL: u32 = 0;
+const SKF_AD_PKTTYPE: u32 = 4;
+const SKF_AD_IFINDEX: u32 = 8;
+const SKF_AD_HATYPE: u32 = 28;
+const SKF_AD_VLAN_TAG: u32 = 44;
+const SKF_AD_VLAN_TAG_PRESENT: u32 = 48;
+
+const fn class(code: u16) -> u16 {
+    code & 0x07
+}
+
+impl SocketFilter {
+    /// Creates a socket filter after validating the program.
+    ///
+    /// This method fails with [`EINVAL`] if the program is invalid. The validation follows
+    /// `bpf_check_classic` in Linux.
+    ///
+    /// [`EINVAL`]: crate::error::Errno::EINVAL
+    pub fn new(insns: Vec<CSockFilter>) -> Result<Self> {
+        let len = insns.len();
+        if len == 0 || len > BPF_MAXINSNS {
+            return_errno_with_message!(Errno::EINVAL, "the BPF program length is invalid");
+        }
+
+        for (pc, insn) in insns.iter().enumerate() {
+            check_insn(insn, pc, len)?;
+        }
+
+        let last_code = insns[len - 1].code;
+        if last_code != BPF_RET | BPF_K && last_code != BPF_RET | BPF_A {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the BPF program does not end with a return instruction"
+            );
+        }
+
+        check_load_and_stores(&insns)?;
+
+        Ok(Self {
+            insns: insns.into(),
+        })
+    }
+
+    /// Runs the filter on a packet.
+    ///
+    /// Returns the number of bytes of the packet that should be kept. Zero means that the packet
+    /// should be dropped.
+    pub fn run(&self, packet: &FilterPacket) -> u32 {
+        let mut a: u32 = 0;
+        let mut x: u32 = 0;
+        let mut mem = [0u32; BPF_MEMWORDS];
+        let mut pc = 0;
+
+        // The validation guarantees that `pc` never goes out of bounds and that the program
+        // always terminates with a return instruction.
+        loop {
+            let CSockFilter { code, jt, jf, k } = self.insns[pc];
+            pc += 1;
+
+            match class(code) {
+                BPF_LD => {
+                    a = match code & 0xe0 {
+                        BPF_IMM => k,
+                        BPF_ABS if k >= SKF_AD_OFF => packet.load_ancillary(k - SKF_AD_OFF),
+                        BPF_ABS => match packet.load(code & 0x18, k) {
+                            Some(value) => value,
+                            None => return 0,
+                        },
+                        BPF_IND => match packet.load(code & 0x18, x.wrapping_add(k)) {
+                            Some(value) => value,
+                            None => return 0,
+                        },
+                        BPF_MEM => mem[k as usize],
+                        BPF_LEN => packet.len(),
+                        _ => unreachable!(),
+                    };
+                }
+                BPF_LDX => {
+                    x = match code & 0xe0 {
+                        BPF_IMM => k,
+                        BPF_MEM => mem[k as usize],
+                        BPF_LEN => packet.len(),
+                        BPF_MSH => match packet.load(BPF_B, k) {
+                            Some(value) => (value & 0xf) << 2,
+                            None => return 0,
+                        },
+                        _ => unreachable!(),
+                    };
+                }
+                BPF_ST => mem[k as usize] = a,
+                BPF_STX => mem[k as usize] = x,
+                BPF_ALU => {
+                    let operand = if code & BPF_X != 0 { x } else { k };
+                    a = match code & 0xf0 {
+                        BPF_ADD => a.wrapping_add(operand),
+                        BPF_SUB => a.wrapping_sub(operand),
+                        BPF_MUL => a.wrapping_mul(operand),
+                        BPF_DIV => match a.checked_div(operand) {
+                            Some(value) => value,
+                            None => return 0,
+                        },
+                        BPF_MOD => match a.checked_rem(operand) {
+                            Some(value) => value,
+                            None => return 0,
+                        },
+                        BPF_OR => a | operand,
+                        BPF_AND => a & operand,
+                        BPF_XOR => a ^ operand,
+                        BPF_LSH => a.checked_shl(operand).unwrap_or(0),
+                        BPF_RSH => a.checked_shr(operand).unwrap_or(0),
+                        BPF_NEG => a.wrapping_neg(),
+                        _ => unreachable!(),
+                    };
+                }
+                BPF_JMP => {
+                    let operand = if code & BPF_X != 0 { x } else { k };
+                    let cond = match code & 0xf0 {
+                        BPF_JA => {
+                            pc += k as usize;
+                            continue;
+                        }
+                        BPF_JEQ => a == operand,
+                        BPF_JGT => a > operand,
+                        BPF_JGE => a >= operand,
+                        BPF_JSET => a & operand != 0,
+                        _ => unreachable!(),
+                    };
+                    pc += if cond { jt as usize } else { jf as usize };
+                }
+                BPF_RET => {
+                    return if code & BPF_A != 0 { a } else { k };
+                }
+                BPF_MISC => {
+                    if code & 0xf8 == BPF_TXA {
+                        a = x;
+                    } else {
+                        x = a;
+                    }
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+}
+
+/// Checks whether a single instruction is valid.
+fn check_insn(insn: &CSockFilter, pc: usize, len: usize) -> Result<()> {
+    let CSockFilter { code, jt, jf, k } = *insn;
+
+    let is_valid_code = match class(code) {
+        BPF_LD => [
+            BPF_W | BPF_ABS,
+            BPF_H | BPF_ABS,
+            BPF_B | BPF_ABS,
+            BPF_W | BPF_IND,
+            BPF_H | BPF_IND,
+            BPF_B | BPF_IND,
+            BPF_W | BPF_IMM,
+            BPF_W | BPF_MEM,
+            BPF_W | BPF_LEN,
+        ]
+        .contains(&(code & !BPF_LD)),
+        BPF_LDX => [
+            BPF_W | BPF_IMM,
+            BPF_W | BPF_MEM,
+            BPF_W | BPF_LEN,
+            BPF_B | BPF_MSH,
+        ]
+        .contains(&(code & !BPF_LDX)),
+        BPF_ST | BPF_STX => code == BPF_ST || code == BPF_STX,
+        BPF_ALU => {
+            let op = code & 0xf0;
+            if op == BPF_NEG {
+                code == BPF_ALU | BPF_NEG
+            } else {
+                code & !(0xf0 | BPF_X) == BPF_ALU && op <= BPF_XOR
+            }
+        }
+        BPF_JMP => {
+            let op = code & 0xf0;
+            if op == BPF_JA {
+                code == BPF_JMP | BPF_JA
+            } else {
+                code & !(0xf0 | BPF_X) == BPF_JMP && op <= BPF_JSET
+            }
+        }
+        BPF_RET => code == BPF_RET | BPF_K || code == BPF_RET | BPF_A,
+        BPF_MISC => code == BPF_MISC | BPF_TAX || code == BPF_MISC | BPF_TXA,
+        _ => unreachable!(),
+    };
+    if !is_valid_code {
+        return_errno_with_message!(Errno::EINVAL, "the BPF instruction is invalid");
+    }
+
+    match class(code) {
+        BPF_ALU if code & BPF_X == 0 => match code & 0xf0 {
+            BPF_DIV | BPF_MOD if k == 0 => {
+                return_errno_with_message!(Errno::EINVAL, "the BPF program divides by zero");
+            }
+            BPF_LSH | BPF_RSH if k >= 32 => {
+                return_errno_with_message!(Errno::EINVAL, "the BPF shift amount is too large");
+            }
+            _ => (),
+        },
+        BPF_LD | BPF_LDX if code & 0xe0 == BPF_MEM => {
+            if k as usize >= BPF_MEMWORDS {
+                return_errno_with_message!(Errno::EINVAL, "the BPF memory index is invalid");
+            }
+        }
+        BPF_ST | BPF_STX => {
+            if k as usize >= BPF_MEMWORDS {
+                return_errno_with_message!(Errno::EINVAL, "the BPF memory index is invalid");
+            }
+        }
+        BPF_JMP if code == BPF_JMP | BPF_JA => {
+            if k as usize >= len - pc - 1 {
+                return_errno_with_message!(Errno::EINVAL, "the BPF jump target is invalid");
+            }
+        }
+        BPF_JMP => {
+            if pc + jt as usize + 1 >= len || pc + jf as usize + 1 >= len {
+                return_errno_with_message!(Errno::EINVAL, "the BPF jump target is invalid");
+            }
+        }
+        BPF_LD if code & 0xe0 == BPF_ABS && k >= SKF_AD_OFF => {
+            if !matches!(
+                k - SKF_AD_OFF,
+                SKF_AD_PROTOCOL
+                    | SKF_AD_PKTTYPE
+                    | SKF_AD_IFINDEX
+                    | SKF_AD_HATYPE
+                    | SKF_AD_VLAN_TAG
+                    | SKF_AD_VLAN_TAG_PRESENT
+            ) {
+                return_errno_with_message!(
+                    Errno::EINVAL,
+                    "the BPF ancillary operation is not supported"
+                );
+            }
+        }
+        _ => (),
+    }
+
+    Ok(())
+}
+
+/// Checks that the scratch memory is never read before it is written.
+fn check_load_and_stores(insns: &[CSockFilter]) -> Result<()> {
+    let mut masks = vec![u16::MAX; insns.len()];
+    let mut mem_valid: u16 = 0;
+
+    for (pc, insn) in insns.iter().enumerate() {
+        mem_valid &= masks[pc];
+
+        match class(insn.code) {
+            BPF_ST | BPF_STX => mem_valid |= 1 << insn.k,
+            BPF_LD | BPF_LDX if insn.code & 0xe0 == BPF_MEM => {
+                if mem_valid & (1 << insn.k) == 0 {
+                    return_errno_with_message!(
+                        Errno::EINVAL,
+                        "the BPF memory is read before it is written"
+                    );
+                }
+            }
+            BPF_JMP if insn.code == BPF_JMP | BPF_JA => {
+                masks[pc + 1 + insn.k as usize] &= mem_valid;
+                mem_valid = u16::MAX;
+            }
+            BPF_JMP => {
+                masks[pc + 1 + insn.jt as usize] &= mem_valid;
+                masks[pc + 1 + insn.jf as usize] &= mem_valid;
+                mem_valid = u16::MAX;
+            }
+            _ => (),
+        }
+    }
+
+    Ok(())
+}
+
+/// A packet to be examined by a [`SocketFilter`].
+pub struct FilterPacket<'a> {
+    /// The whole frame, starting with the link-layer header.
+    pub frame: &'a [u8],
+    /// The offset of the data that the filter sees, relative to the start of the frame.
+    pub data_offset: usize,
+    /// The offset of the network-layer header, relative to the start of the frame.
+    pub network_offset: usize,
+    /// The link-layer protocol in host byte order.
+    pub protocol: u16,
+    /// The packet type.
+    pub pkttype: u8,
+    /// The index of the interface.
+    pub ifindex: u32,
+    /// The ARP hardware type of the interface.
+    pub hatype: u16,
+}
+
+impl FilterPacket<'_> {
+    fn len(&self) -> u32 {
+        (self.frame.len() - self.data_offset) as u32
+    }
+
+    /// Loads a value of the specified size at the specified offset.
+    ///
+    /// Returns `None` if the offset is out of bounds.
+    fn load(&self, size: u16, offset: u32) -> Option<u32> {
+        let start = if offset >= SKF_AD_OFF {
+            return None;
+        } else if offset >= SKF_NET_OFF {
+            self.network_offset
+                .checked_add((offset - SKF_NET_OFF) as usize)?
+        } else if offset >= SKF_LL_OFF {
+            (offset - SKF_LL_OFF) as usize
+        } else {
+            self.data_offset.checked_add(offset as usize)?
+        };
+
+        let width = match size {
+            BPF_W => 4,
+            BPF_H => 2,
+            BPF_B => 1,
+            _ => unreachable!(),
+        };
+        let bytes = self.frame.get(start..start.checked_add(width)?)?;
+
+        Some(bytes.iter().fold(0, |acc, byte| (acc << 8) | *byte as u32))
+    }
+
+    /// Loads the ancillary data at the specified offset.
+    ///
+    /// The offset must have been validated by [`check_insn`].
+    fn load_ancillary(&self, offset: u32) -> u32 {
+        match offset {
+            SKF_AD_PROTOCOL => self.protocol as u32,
+            SKF_AD_PKTTYPE => self.pkttype as u32,
+            SKF_AD_IFINDEX => self.ifindex,
+            SKF_AD_HATYPE => self.hatype as u32,
+            // VLAN tags are not supported, so they are never present.
+            SKF_AD_VLAN_TAG | SKF_AD_VLAN_TAG_PRESENT => 0,
+            _ => unreachable!(),
+        }
+    }
+}
@@ -0,0 +1,30 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::{fs::vfs::path::Path, prelude::*, process::Uid};
+
+/// The inode number and the owner of a socket.
+///
+/// They identify the socket when it is reported to user space (e.g., via `/proc/net/tcp`).
+#[derive(Clone, Copy, Debug)]
+pub struct SocketInode {
+    ino: u64,
+    uid: Uid,
+}
+
+impl SocketInode {
+    /// Creates the identity of the socket whose pseudo path is `pseudo_path`.
+    pub fn of(pseudo_path: &Path) -> Self {
+        Self {
+            ino: pseudo_path.inode().ino(),
+            uid: pseudo_path.owner().unwrap_or(Uid::new_root()),
+        }
+    }
+
+    pub fn ino(&self) -> u64 {
+        self.ino
+    }
+
+    pub fn uid(&self) -> Uid {
+        self.uid
+    }
+}
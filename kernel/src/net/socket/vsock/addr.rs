@@ -29,6 +29,7 @@ impl From<VsockSocketAddr> for SocketAddr {
 }
 
 pub(super) const VMADDR_CID_ANY: u32 = u32::MAX;
+pub(super) const VMADDR_CID_LOCAL: u32 = 1;
 pub(super) const VMADDR_CID_HOST: u32 = 2;
 
 pub(super) const VMADDR_PORT_ANY: u32 = u32::MAX;
@@ -16,10 +16,12 @@
 //!
 
 mod addr;
+mod options;
 mod stream;
 mod transport;
 
 pub use addr::VsockSocketAddr;
+pub use options::{BufferMaxSize, BufferMinSize, BufferSize, ConnectTimeout};
 pub use stream::VsockStreamSocket;
 
 pub(in crate::net) fn init() {
@@ -0,0 +1,86 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::time::Duration;
+
+use super::transport::SocketConfig;
+use crate::{net::socket::options::macros::impl_socket_options, prelude::*, time::timeval_t};
+
+impl_socket_options!(
+    pub struct BufferSize(u64);
+    pub struct BufferMinSize(u64);
+    pub struct BufferMaxSize(u64);
+    pub struct ConnectTimeout(timeval_t);
+);
+
+/// The vsock-level socket options.
+#[derive(Clone, Copy, CopyGetters, Debug)]
+#[get_copy = "pub(super)"]
+pub(super) struct VsockOptionSet {
+    buffer_size: u64,
+    buffer_min_size: u64,
+    buffer_max_size: u64,
+    connect_timeout: Duration,
+}
+
+// Reference: <https://elixir.bootlin.com/linux/v6.16.8/source/net/vmw_vsock/af_vsock.c#L136>
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+const DEFAULT_BUFFER_SIZE: u64 = 256 * 1024;
+const DEFAULT_BUFFER_MAX_SIZE: u64 = 256 * 1024;
+const DEFAULT_BUFFER_MIN_SIZE: u64 = 128;
+
+impl VsockOptionSet {
+    pub(super) fn new() -> Self {
+        Self {
+            buffer_size: DEFAULT_BUFFER_SIZE,
+            buffer_min_size: DEFAULT_BUFFER_MIN_SIZE,
+            buffer_max_size: DEFAULT_BUFFER_MAX_SIZE,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+        }
+    }
+
+    /// Sets the buffer size, which is clamped to the minimum and maximum sizes.
+    pub(super) fn set_buffer_size(&mut self, buffer_size: u64) {
+        // Like Linux, the maximum size wins if the minimum size is larger than it.
+        self.buffer_size = buffer_size
+            .max(self.buffer_min_size)
+            .min(self.buffer_max_size);
+    }
+
+    pub(super) fn set_buffer_min_size(&mut self, buffer_min_size: u64) {
+        self.buffer_min_size = buffer_min_size;
+        self.set_buffer_size(self.buffer_size);
+    }
+
+    pub(super) fn set_buffer_max_size(&mut self, buffer_max_size: u64) {
+        self.buffer_max_size = buffer_max_size;
+        self.set_buffer_size(self.buffer_size);
+    }
+
+    /// Sets the connect timeout.
+    ///
+    /// A zero timeout restores the default timeout.
+    pub(super) fn set_connect_timeout(&mut self, timeout: timeval_t) -> Result<()> {
+        if timeout.sec < 0 || timeout.usec < 0 || timeout.usec >= 1_000_000 {
+            return_errno_with_message!(Errno::ERANGE, "the timeout is out of range");
+        }
+
+        let timeout = Duration::try_from(timeout)?;
+        self.connect_timeout = if timeout.is_zero() {
+            DEFAULT_CONNECT_TIMEOUT
+        } else {
+            timeout
+        };
+
+        Ok(())
+    }
+
+    /// Returns the configuration that the transport uses for new connections and listeners.
+    pub(super) fn socket_config(&self, is_seqpacket: bool) -> SocketConfig {
+        SocketConfig {
+            is_seqpacket,
+            // The buffer size is advertised to the peer as a 32-bit integer.
+            buf_size: self.buffer_size.min(u32::MAX as u64) as u32,
+            connect_timeout: self.connect_timeout,
+        }
+    }
+}
@@ -44,6 +44,10 @@ impl ConnectedStream {
         self.connection.shutdown(cmd)
     }
 
+    pub(super) fn set_buf_size(&self, buf_size: u32) {
+        self.connection.set_buf_size(buf_size);
+    }
+
     pub(super) fn local_addr(&self) -> VsockSocketAddr {
         self.connection.local_addr()
     }
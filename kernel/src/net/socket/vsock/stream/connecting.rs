@@ -5,7 +5,7 @@ use crate::{
     net::socket::vsock::{
         addr::VsockSocketAddr,
         stream::{ConnectedStream, InitStream},
-        transport::{BoundPort, ConnectResult, Connection},
+        transport::{BoundPort, ConnectResult, Connection, SocketConfig},
     },
     prelude::*,
     process::signal::Pollee,
@@ -25,10 +25,11 @@ impl ConnectingStream {
     pub(super) fn new(
         bound_port: BoundPort,
         remote_addr: VsockSocketAddr,
+        config: &SocketConfig,
         pollee: &Pollee,
     ) -> Result<Self, (Error, BoundPort)> {
         bound_port
-            .connect(remote_addr, pollee)
+            .connect(remote_addr, config, pollee)
             .map(|connection| Self { connection })
     }
 
@@ -36,6 +37,10 @@ impl ConnectingStream {
         self.connection.local_addr()
     }
 
+    pub(super) fn set_buf_size(&self, buf_size: u32) {
+        self.connection.set_buf_size(buf_size);
+    }
+
     pub(super) fn has_result(&self) -> bool {
         self.connection.has_connect_result()
     }
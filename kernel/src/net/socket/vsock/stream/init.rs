@@ -5,9 +5,9 @@ use crate::{
     net::socket::{
         util::{SockShutdownCmd, check_port_privilege},
         vsock::{
-            addr::{VMADDR_CID_HOST, VMADDR_PORT_ANY, VsockSocketAddr},
+            addr::{VMADDR_PORT_ANY, VsockSocketAddr},
             stream::{ConnectingStream, ListenStream},
-            transport::BoundPort,
+            transport::{BoundPort, SocketConfig, check_remote_addr},
         },
     },
     prelude::*,
@@ -71,13 +71,11 @@ impl InitStream {
     pub(super) fn connect(
         self,
         remote_addr: VsockSocketAddr,
+        config: &SocketConfig,
         pollee: &Pollee,
     ) -> Result<ConnectingStream, (Error, Self)> {
-        if remote_addr.cid != VMADDR_CID_HOST {
-            return Err((
-                Error::with_message(Errno::ENETUNREACH, "only the host vsock CID is supported"),
-                self,
-            ));
+        if let Err(error) = check_remote_addr(&remote_addr, config) {
+            return Err((error, self));
         }
         if remote_addr.port == VMADDR_PORT_ANY {
             return Err((
@@ -95,7 +93,7 @@ impl InitStream {
             }
         };
 
-        ConnectingStream::new(bound_port, remote_addr, pollee)
+        ConnectingStream::new(bound_port, remote_addr, config, pollee)
             .map_err(|(error, bound_port)| (error, Self::new_bound(bound_port)))
     }
 
@@ -106,6 +104,7 @@ impl InitStream {
     pub(super) fn listen(
         self,
         backlog: usize,
+        config: &SocketConfig,
         pollee: &Pollee,
     ) -> Result<ListenStream, (Error, Self)> {
         if !self.is_connect_done {
@@ -122,7 +121,7 @@ impl InitStream {
             ));
         };
 
-        ListenStream::new(bound_port, backlog, pollee)
+        ListenStream::new(bound_port, backlog, config, pollee)
             .map_err(|(error, bound_port)| (error, Self::new_bound(bound_port)))
     }
 
@@ -5,7 +5,7 @@ use crate::{
     net::socket::vsock::{
         addr::VsockSocketAddr,
         stream::ConnectedStream,
-        transport::{BoundPort, Listener},
+        transport::{BoundPort, Listener, SocketConfig},
     },
     prelude::*,
     process::signal::Pollee,
@@ -19,10 +19,11 @@ impl ListenStream {
     pub(super) fn new(
         bound_port: BoundPort,
         backlog: usize,
+        config: &SocketConfig,
         pollee: &Pollee,
     ) -> Result<Self, (Error, BoundPort)> {
         bound_port
-            .listen(backlog, pollee)
+            .listen(backlog, config, pollee)
             .map(|listener| Self { listener })
     }
 
@@ -36,6 +37,10 @@ impl ListenStream {
         self.listener.set_backlog(backlog);
     }
 
+    pub(super) fn set_buf_size(&self, buf_size: u32) {
+        self.listener.set_buf_size(buf_size);
+    }
+
     pub(super) fn local_addr(&self) -> VsockSocketAddr {
         self.listener.local_addr()
     }
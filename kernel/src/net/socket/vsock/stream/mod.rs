@@ -18,10 +18,16 @@ use crate::{
     fs::{file::FileLike, pseudofs::SockFs, vfs::path::Path},
     net::socket::{
         Socket,
-        options::{Error as SocketError, SocketOption, macros::sock_option_mut},
+        options::{
+            Error as SocketError, SocketOption,
+            macros::{sock_option_mut, sock_option_ref},
+        },
         private::SocketPrivate,
         util::{MessageHeader, SendRecvFlags, SockShutdownCmd, SocketAddr},
-        vsock::addr::{UNSPECIFIED_VSOCK_ADDR, VsockSocketAddr},
+        vsock::{
+            addr::{UNSPECIFIED_VSOCK_ADDR, VsockSocketAddr},
+            options::{BufferMaxSize, BufferMinSize, BufferSize, ConnectTimeout, VsockOptionSet},
+        },
     },
     prelude::*,
     process::signal::{PollHandle, Pollable, Pollee},
@@ -29,8 +35,12 @@ use crate::{
 };
 
 pub struct VsockStreamSocket {
+    // Lock order: `state` first, `options` second
     state: Mutex<Takeable<State>>,
+    options: Mutex<VsockOptionSet>,
     is_nonblocking: AtomicBool,
+    /// Whether the socket is a `SOCK_SEQPACKET` socket, which preserves message boundaries.
+    is_seqpacket: bool,
     // Note that for vsock, all pollee notifications and invalidations live in the transport module
     // (e.g., `super::transport`) rather than in this module.
     pollee: Pollee,
@@ -45,10 +55,12 @@ enum State {
 }
 
 impl VsockStreamSocket {
-    pub fn new(is_nonblocking: bool) -> Result<Arc<Self>> {
+    pub fn new(is_nonblocking: bool, is_seqpacket: bool) -> Result<Arc<Self>> {
         Ok(Arc::new(Self {
             state: Mutex::new(Takeable::new(State::Init(InitStream::new()))),
+            options: Mutex::new(VsockOptionSet::new()),
             is_nonblocking: AtomicBool::new(is_nonblocking),
+            is_seqpacket,
             pollee: Pollee::new(),
             pseudo_path: SockFs::new_path(),
         }))
@@ -97,7 +109,8 @@ impl VsockStreamSocket {
                 );
             }
 
-            match init_stream.connect(remote_addr, &self.pollee) {
+            let config = self.options.lock().socket_config(self.is_seqpacket);
+            match init_stream.connect(remote_addr, &config, &self.pollee) {
                 Ok(connecting_stream) if self.is_nonblocking() => (
                     State::Connecting(connecting_stream),
                     Some(Err(Error::with_message(
@@ -147,9 +160,12 @@ impl VsockStreamSocket {
         let peer_addr = connected.remote_addr().into();
         let pollee = connected.pollee().clone();
 
+        // Like Linux, the accepted socket inherits the vsock-level options of the listening socket.
         let accepted = Arc::new(Self {
             state: Mutex::new(Takeable::new(State::Connected(connected))),
+            options: Mutex::new(*self.options.lock()),
             is_nonblocking: AtomicBool::new(false),
+            is_seqpacket: self.is_seqpacket,
             pollee,
             pseudo_path: SockFs::new_path(),
         });
@@ -291,7 +307,8 @@ impl Socket for VsockStreamSocket {
                 }
             };
 
-            match init_stream.listen(backlog, &self.pollee) {
+            let config = self.options.lock().socket_config(self.is_seqpacket);
+            match init_stream.listen(backlog, &config, &self.pollee) {
                 Ok(listen_stream) => (State::Listen(listen_stream), Ok(())),
                 Err((error, init_stream)) => (State::Init(init_stream), Err(error)),
             }
@@ -341,8 +358,6 @@ impl Socket for VsockStreamSocket {
         Ok(connected_stream.remote_addr().into())
     }
 
-    // TODO: Support setting socket options
-
     fn get_option(&self, option: &mut dyn SocketOption) -> Result<()> {
         sock_option_mut!(match option {
             socket_errors @ SocketError => {
@@ -352,8 +367,68 @@ impl Socket for VsockStreamSocket {
             _ => {}
         });
 
-        // TODO: Support getting other socket options
-        return_errno_with_message!(Errno::EOPNOTSUPP, "the socket option to be get is unknown");
+        let options = self.options.lock();
+
+        // Deal with vsock-level options
+        sock_option_mut!(match option {
+            buffer_size @ BufferSize => {
+                buffer_size.set(options.buffer_size());
+            }
+            buffer_min_size @ BufferMinSize => {
+                buffer_min_size.set(options.buffer_min_size());
+            }
+            buffer_max_size @ BufferMaxSize => {
+                buffer_max_size.set(options.buffer_max_size());
+            }
+            connect_timeout @ ConnectTimeout => {
+                connect_timeout.set(options.connect_timeout().into());
+            }
+            // TODO: Support getting socket-level options
+            _ => return_errno_with_message!(
+                Errno::EOPNOTSUPP,
+                "the socket option to be get is unknown"
+            ),
+        });
+
+        Ok(())
+    }
+
+    fn set_option(&self, option: &dyn SocketOption) -> Result<()> {
+        let state = self.lock_updated_state();
+        let mut options = self.options.lock();
+
+        // Deal with vsock-level options
+        sock_option_ref!(match option {
+            buffer_size @ BufferSize => {
+                options.set_buffer_size(*buffer_size.get().unwrap());
+            }
+            buffer_min_size @ BufferMinSize => {
+                options.set_buffer_min_size(*buffer_min_size.get().unwrap());
+            }
+            buffer_max_size @ BufferMaxSize => {
+                options.set_buffer_max_size(*buffer_max_size.get().unwrap());
+            }
+            connect_timeout @ ConnectTimeout => {
+                // The new timeout only applies to later connection attempts.
+                return options.set_connect_timeout(*connect_timeout.get().unwrap());
+            }
+            // TODO: Support setting socket-level options
+            _ => return_errno_with_message!(
+                Errno::EOPNOTSUPP,
+                "the socket option to be set is unknown"
+            ),
+        });
+
+        // Advertise the new buffer size to the peer(s).
+        let buf_size = options.socket_config(self.is_seqpacket).buf_size;
+        match state.as_ref() {
+            State::Init(_) => (),
+            State::Connecting(connecting_stream) => connecting_stream.set_buf_size(buf_size),
+            State::Connected(connected_stream) => connected_stream.set_buf_size(buf_size),
+            State::Listen(listen_stream) => listen_stream.set_buf_size(buf_size),
+        }
+
+        Ok(())
     }
 
     fn sendmsg(
@@ -362,8 +437,13 @@ impl Socket for VsockStreamSocket {
         message_header: MessageHeader,
         flags: SendRecvFlags,
     ) -> Result<usize> {
-        // TODO: Deal with flags
-        if !flags.is_all_supported() {
+        // TODO: Deal with other flags. Only `MSG_EOR` is handled here for `SOCK_SEQPACKET`.
+        let supported_flags = if self.is_seqpacket {
+            SendRecvFlags::MSG_EOR
+        } else {
+            SendRecvFlags::empty()
+        };
+        if !(flags - supported_flags).is_all_supported() {
             warn!("unsupported flags: {:?}", flags);
         }
 
@@ -409,8 +489,13 @@ impl Socket for VsockStreamSocket {
         writer: &mut dyn MultiWrite,
         flags: SendRecvFlags,
     ) -> Result<(usize, MessageHeader)> {
-        // TODO: Deal with flags
-        if !flags.is_all_supported() {
+        // TODO: Deal with other flags. Only `MSG_TRUNC` is handled here for `SOCK_SEQPACKET`.
+        let supported_flags = if self.is_seqpacket {
+            SendRecvFlags::MSG_TRUNC
+        } else {
+            SendRecvFlags::empty()
+        };
+        if !(flags - supported_flags).is_all_supported() {
             warn!("unsupported flags: {:?}", flags);
         }
 
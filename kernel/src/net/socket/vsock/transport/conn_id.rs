@@ -2,7 +2,10 @@
 
 use aster_virtio::device::socket::header::VirtioVsockHdr;
 
-use crate::net::socket::vsock::{VsockSocketAddr, transport::BoundPort};
+use crate::net::socket::vsock::{
+    VsockSocketAddr,
+    transport::{BoundPort, packet::TransportKind},
+};
 
 #[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
 pub(super) struct ConnId {
@@ -13,9 +16,13 @@ pub(super) struct ConnId {
 }
 
 impl ConnId {
-    pub(super) fn from_port_and_remote(port: &BoundPort, remote: VsockSocketAddr) -> Self {
+    pub(super) fn from_port_and_remote(
+        port: &BoundPort,
+        remote: VsockSocketAddr,
+        transport: TransportKind,
+    ) -> Self {
         Self {
-            local_cid: port.vsock_space().guest_cid(),
+            local_cid: port.vsock_space().local_cid(transport),
             peer_cid: remote.cid as u64,
             local_port: port.port(),
             peer_port: remote.port,
@@ -2,6 +2,8 @@
 
 use core::sync::atomic::Ordering;
 
+use aster_virtio::device::socket::header::VirtioVsockType;
+
 use crate::{
     events::IoEvents,
     net::socket::vsock::{
@@ -52,7 +54,12 @@ impl Connection {
             state.shutdown.local_read_closed && state.shutdown.local_write_closed;
         let peer_fully_closed = state.shutdown.peer_read_closed && state.shutdown.peer_write_closed;
 
-        if !state.rx_queue.packets.is_empty() {
+        // For `SOCK_SEQPACKET` sockets, only complete messages can be received.
+        let can_recv = match self.inner.type_ {
+            VirtioVsockType::Stream => !state.rx_queue.packets.is_empty(),
+            VirtioVsockType::SeqPacket => state.rx_queue.num_messages != 0,
+        };
+        if can_recv {
             events |= IoEvents::IN;
         }
 
@@ -63,8 +70,12 @@ impl Connection {
         // Most sockets tend to report EPOLLOUT once the write side has been shut down. However,
         // the logic for vsock appears to be different.
         if !state.shutdown.local_write_closed {
-            if state.peer_credit() != 0
-                && self.inner.pending_tx_bytes.load(Ordering::Relaxed) < DEFAULT_TX_BUF_SIZE
+            // A message that has been blocked can only be sent as a whole, so there must be enough
+            // room for it. Otherwise, there must be room for at least one byte.
+            let wanted_room = state.credit.blocked_msg_len.max(1);
+            if state.peer_credit() >= wanted_room
+                && self.inner.pending_tx_bytes.load(Ordering::Relaxed) + wanted_room
+                    <= DEFAULT_TX_BUF_SIZE
             {
                 events |= IoEvents::OUT;
             }
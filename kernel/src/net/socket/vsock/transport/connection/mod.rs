@@ -23,16 +23,17 @@ mod utils;
 use core::sync::atomic::AtomicUsize;
 
 use aster_softirq::BottomHalfDisabled;
-use aster_virtio::device::socket::{
-    header::{VirtioVsockHdr, VirtioVsockOp, VirtioVsockShutdownFlags},
-    packet::RxPacket,
+use aster_virtio::device::socket::header::{
+    VirtioVsockHdr, VirtioVsockOp, VirtioVsockRwFlags, VirtioVsockShutdownFlags, VirtioVsockType,
 };
 use takeable::Takeable;
 
 use crate::{
     events::IoEvents,
     net::socket::vsock::transport::{
-        BoundPort, DEFAULT_CONNECT_TIMEOUT, DEFAULT_RX_BUF_SIZE, conn_id::ConnId,
+        BoundPort, SocketConfig,
+        conn_id::ConnId,
+        packet::{RecvPacket, TransportKind},
     },
     prelude::*,
     process::signal::Pollee,
@@ -54,6 +55,8 @@ impl Connection {
 
 pub(super) struct ConnectionInner {
     conn_id: ConnId,
+    transport: TransportKind,
+    type_: VirtioVsockType,
     bound_port: BoundPort,
     pollee: Pollee,
     state: SpinLock<ConnectionState, BottomHalfDisabled>,
@@ -118,18 +121,29 @@ enum Phase {
 }
 
 struct RxQueue {
-    packets: VecDeque<RxPacket>,
+    packets: VecDeque<RecvPacket>,
     used_bytes: usize,
     read_offset: usize,
+    /// The number of complete messages in the queue.
+    ///
+    /// This is only used by [`VirtioVsockType::SeqPacket`] connections.
+    num_messages: usize,
 }
 
 struct CreditState {
+    /// The size of the local receive buffer, which is advertised to the peer.
+    buf_alloc: u32,
     peer_buf_alloc: u32,
     peer_fwd_cnt: u32,
     local_fwd_cnt: u32,
     last_reported_fwd_cnt: u32,
     credit_request_pending: bool,
     tx_cnt: u32,
+    /// The length of the last message that could not be sent due to insufficient space.
+    ///
+    /// This is only used by [`VirtioVsockType::SeqPacket`] connections. Messages are sent
+    /// atomically, so [`IoEvents::OUT`] should not be reported until the message can be sent.
+    blocked_msg_len: usize,
 }
 
 struct ShutdownState {
@@ -149,15 +163,25 @@ impl ConnectionInner {
     pub(super) fn new_connecting(
         bound_port: BoundPort,
         conn_id: &ConnId,
+        transport: TransportKind,
+        config: &SocketConfig,
         pollee: Pollee,
     ) -> Arc<Self> {
         pollee.invalidate();
 
-        let this = Self::new(bound_port, conn_id, pollee, Phase::Connecting);
+        let this = Self::new(
+            bound_port,
+            conn_id,
+            transport,
+            config.type_(),
+            config.buf_size,
+            pollee,
+            Phase::Connecting,
+        );
 
         let mut state = this.state.lock();
         let _ = state.send_packet(&this, VirtioVsockOp::Request, 0);
-        state.arm_timeout(&this, DEFAULT_CONNECT_TIMEOUT);
+        state.arm_timeout(&this, config.connect_timeout);
         drop(state);
 
         this
@@ -166,9 +190,20 @@ impl ConnectionInner {
     pub(super) fn new_connected(
         bound_port: BoundPort,
         conn_id: &ConnId,
+        transport: TransportKind,
+        type_: VirtioVsockType,
+        buf_size: u32,
         header: &VirtioVsockHdr,
     ) -> Arc<Self> {
-        let this = Self::new(bound_port, conn_id, Pollee::new(), Phase::Connected);
+        let this = Self::new(
+            bound_port,
+            conn_id,
+            transport,
+            type_,
+            buf_size,
+            Pollee::new(),
+            Phase::Connected,
+        );
 
         let mut state = this.state.lock();
         state.update_peer_credit(&this, header);
@@ -178,7 +213,15 @@ impl ConnectionInner {
         this
     }
 
-    fn new(bound_port: BoundPort, conn_id: &ConnId, pollee: Pollee, phase: Phase) -> Arc<Self> {
+    fn new(
+        bound_port: BoundPort,
+        conn_id: &ConnId,
+        transport: TransportKind,
+        type_: VirtioVsockType,
+        buf_size: u32,
+        pollee: Pollee,
+        phase: Phase,
+    ) -> Arc<Self> {
         debug_assert_eq!(bound_port.port(), conn_id.local_port);
 
         let peer_fully_closed = phase != Phase::Connected;
@@ -190,14 +233,17 @@ impl ConnectionInner {
                 packets: VecDeque::new(),
                 used_bytes: 0,
                 read_offset: 0,
+                num_messages: 0,
             },
             credit: CreditState {
+                buf_alloc: buf_size,
                 peer_buf_alloc: 0,
                 peer_fwd_cnt: 0,
                 local_fwd_cnt: 0,
                 last_reported_fwd_cnt: 0,
                 credit_request_pending: false,
                 tx_cnt: 0,
+                blocked_msg_len: 0,
             },
             shutdown: ShutdownState {
                 local_read_closed: false,
@@ -210,6 +256,8 @@ impl ConnectionInner {
 
         Arc::new(Self {
             conn_id: *conn_id,
+            transport,
+            type_,
             bound_port,
             pollee,
             state: SpinLock::new(state),
@@ -221,6 +269,14 @@ impl ConnectionInner {
         self.conn_id
     }
 
+    pub(super) const fn transport(&self) -> TransportKind {
+        self.transport
+    }
+
+    pub(super) const fn type_(&self) -> VirtioVsockType {
+        self.type_
+    }
+
     pub(super) fn pollee(&self) -> &Pollee {
         &self.pollee
     }
@@ -295,7 +351,7 @@ impl ConnectionInner {
         should_remove
     }
 
-    pub(super) fn on_rw(&self, header: &VirtioVsockHdr, packet: RxPacket) -> Result<()> {
+    pub(super) fn on_rw(&self, header: &VirtioVsockHdr, packet: RecvPacket) -> Result<()> {
         let mut state = self.state.lock();
 
         if state.shutdown.peer_write_closed {
@@ -306,14 +362,20 @@ impl ConnectionInner {
         }
 
         let len = packet.payload_len();
-        if state.rx_queue.used_bytes + len > DEFAULT_RX_BUF_SIZE {
+        if state.rx_queue.used_bytes + len > state.credit.buf_alloc as usize {
             state.active_rst(self);
             return_errno_with_message!(Errno::ENOMEM, "the receive queue is full");
         }
 
         state.update_peer_credit(self, header);
 
-        if len != 0 {
+        // An empty packet can still mark the end of a message.
+        let is_eom = self.type_ == VirtioVsockType::SeqPacket && is_end_of_message(header);
+        if is_eom {
+            state.rx_queue.num_messages += 1;
+        }
+
+        if len != 0 || is_eom {
             state.rx_queue.used_bytes += len;
             state.rx_queue.packets.push_back(packet);
         }
@@ -459,3 +521,8 @@ impl ConnectionState {
         }
     }
 }
+
+/// Returns whether the packet is the last packet of a message.
+fn is_end_of_message(header: &VirtioVsockHdr) -> bool {
+    VirtioVsockRwFlags::from_bits_truncate(header.flags).contains(VirtioVsockRwFlags::SEQ_EOM)
+}
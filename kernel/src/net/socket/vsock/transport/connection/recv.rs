@@ -1,13 +1,14 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use aster_virtio::device::socket::{header::VirtioVsockOp, packet::RxPacket};
+use aster_virtio::device::socket::header::{VirtioVsockOp, VirtioVsockType};
 
 use crate::{
     net::socket::{
         util::SendRecvFlags,
         vsock::transport::{
-            CREDIT_UPDATE_THRESHOLD, Connection,
-            connection::{ConnectionInner, ConnectionState},
+            Connection,
+            connection::{ConnectionInner, ConnectionState, Phase, is_end_of_message},
+            packet::RecvPacket,
         },
     },
     prelude::*,
@@ -19,8 +20,12 @@ impl Connection {
     pub(in crate::net::socket::vsock) fn try_recv(
         &mut self,
         writer: &mut dyn MultiWrite,
-        _flags: SendRecvFlags,
+        flags: SendRecvFlags,
     ) -> Result<usize> {
+        if self.inner.type_ == VirtioVsockType::SeqPacket {
+            return self.try_recv_message(writer, flags);
+        }
+
         // We use a packet-pool approach here so a receive attempt either completes for the chosen
         // packets or leaves the receive queue unchanged.
         //
@@ -62,10 +67,58 @@ impl Connection {
 
         result
     }
+
+    /// Receives exactly one message, discarding the bytes that do not fit in `writer`.
+    fn try_recv_message(
+        &mut self,
+        writer: &mut dyn MultiWrite,
+        flags: SendRecvFlags,
+    ) -> Result<usize> {
+        let Some(message) = self.inner.state.lock().grab_message_to_recv(&self.inner)? else {
+            return Ok(0);
+        };
+
+        // See `try_recv` for why releasing the state lock does not cause race conditions.
+        let result = message.copy_to_userspace(writer);
+
+        let mut state = self.inner.state.lock();
+        let message_len = message.len;
+        if result.is_ok() {
+            state.finish_recv_message(&self.inner, message);
+        } else {
+            state.undo_pop_message(message);
+        }
+        drop(state);
+
+        self.inner.pollee.invalidate();
+
+        let copied_len = result?;
+
+        // Like Linux, the real length of the message is returned if `MSG_TRUNC` is specified.
+        if flags.contains(SendRecvFlags::MSG_TRUNC) {
+            Ok(message_len)
+        } else {
+            Ok(copied_len)
+        }
+    }
+
+    /// Sets the size of the receive buffer and advertises it to the peer.
+    pub(in crate::net::socket::vsock) fn set_buf_size(&self, buf_size: u32) {
+        let mut state = self.inner.state.lock();
+
+        state.credit.buf_alloc = buf_size;
+
+        if state.phase == Phase::Connected
+            && !state.shutdown.peer_write_closed
+            && !state.shutdown.local_read_closed
+        {
+            let _ = state.send_packet(&self.inner, VirtioVsockOp::CreditUpdate, 0);
+        }
+    }
 }
 
 struct PoppedRxPackets<'a> {
-    packets: &'a mut [Option<RxPacket>],
+    packets: &'a mut [Option<RecvPacket>],
     read_offset: usize,
 }
 
@@ -111,7 +164,7 @@ impl ConnectionState {
     fn grab_packets_to_recv<'a>(
         &mut self,
         conn: &ConnectionInner,
-        packet_pool: &'a mut [Option<RxPacket>],
+        packet_pool: &'a mut [Option<RecvPacket>],
         max_bytes: usize,
     ) -> Result<Option<PoppedRxPackets<'a>>> {
         if max_bytes != 0
@@ -131,7 +184,7 @@ impl ConnectionState {
 
     fn pop_rx_packets<'a>(
         &mut self,
-        packet_pool: &'a mut [Option<RxPacket>],
+        packet_pool: &'a mut [Option<RecvPacket>],
         mut max_bytes: usize,
     ) -> Option<PoppedRxPackets<'a>> {
         let mut read_offset = None;
@@ -200,7 +253,8 @@ impl ConnectionState {
             .credit
             .local_fwd_cnt
             .wrapping_sub(self.credit.last_reported_fwd_cnt);
-        if new_credit < CREDIT_UPDATE_THRESHOLD {
+        // Like Linux, report the credit once a quarter of the receive buffer has been consumed.
+        if new_credit < self.credit.buf_alloc / 4 {
             return;
         }
 
@@ -211,4 +265,77 @@ impl ConnectionState {
 
         let _ = self.send_packet(conn, VirtioVsockOp::CreditUpdate, 0);
     }
+
+    fn grab_message_to_recv(&mut self, conn: &ConnectionInner) -> Result<Option<PoppedMessage>> {
+        if self.rx_queue.num_messages != 0 {
+            return Ok(Some(self.pop_message()));
+        }
+
+        self.test_and_clear_error(conn)?;
+
+        if self.shutdown.local_read_closed || self.shutdown.peer_write_closed {
+            return Ok(None);
+        }
+
+        return_errno_with_message!(Errno::EAGAIN, "the receive buffer is empty");
+    }
+
+    fn pop_message(&mut self) -> PoppedMessage {
+        debug_assert_eq!(self.rx_queue.read_offset, 0);
+
+        let mut packets = Vec::new();
+        let mut len = 0;
+
+        loop {
+            let packet = self.rx_queue.packets.pop_front().unwrap();
+            let is_last = is_end_of_message(&packet.header());
+
+            len += packet.payload_len();
+            packets.push(packet);
+
+            if is_last {
+                break;
+            }
+        }
+
+        self.rx_queue.num_messages -= 1;
+
+        PoppedMessage { packets, len }
+    }
+
+    fn finish_recv_message(&mut self, conn: &ConnectionInner, message: PoppedMessage) {
+        self.rx_queue.used_bytes -= message.len;
+        self.credit.local_fwd_cnt = self.credit.local_fwd_cnt.wrapping_add(message.len as u32);
+
+        self.send_credit_update_header_if_needed(conn);
+    }
+
+    fn undo_pop_message(&mut self, message: PoppedMessage) {
+        for packet in message.packets.into_iter().rev() {
+            self.rx_queue.packets.push_front(packet);
+        }
+        self.rx_queue.num_messages += 1;
+    }
+}
+
+/// A message popped from the receive queue of a [`VirtioVsockType::SeqPacket`] connection.
+struct PoppedMessage {
+    packets: Vec<RecvPacket>,
+    /// The total payload length of the message.
+    len: usize,
+}
+
+impl PoppedMessage {
+    fn copy_to_userspace(&self, writer: &mut dyn MultiWrite) -> Result<usize> {
+        let mut total_write_len = 0;
+
+        for packet in self.packets.iter() {
+            if writer.is_empty() {
+                break;
+            }
+            total_write_len += writer.write(&mut packet.payload())?;
+        }
+
+        Ok(total_write_len)
+    }
 }
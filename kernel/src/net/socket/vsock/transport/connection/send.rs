@@ -3,7 +3,7 @@
 use core::sync::atomic::Ordering;
 
 use aster_virtio::device::socket::{
-    header::VirtioVsockOp,
+    header::{VirtioVsockOp, VirtioVsockRwFlags, VirtioVsockType},
     packet::{TxPacket, TxPacketBuilder},
     queue::TxCompletion,
 };
@@ -15,6 +15,8 @@ use crate::{
         vsock::transport::{
             Connection, DEFAULT_TX_BUF_SIZE,
             connection::{ConnectionInner, ConnectionState},
+            loopback,
+            packet::TransportKind,
         },
     },
     prelude::*,
@@ -26,11 +28,18 @@ impl Connection {
     ///
     /// The method respects both peer receive credit and the connection's pending-byte budget. It
     /// may return `EAGAIN` when either resource is exhausted.
+    ///
+    /// For [`VirtioVsockType::SeqPacket`] connections, the data in `reader` is sent as one message
+    /// or not sent at all.
     pub(in crate::net::socket::vsock) fn try_send(
         &mut self,
         reader: &mut dyn MultiRead,
-        _flags: SendRecvFlags,
+        flags: SendRecvFlags,
     ) -> Result<usize> {
+        if self.inner.type_ == VirtioVsockType::SeqPacket {
+            return self.try_send_message(reader, flags);
+        }
+
         // See the comments in `try_recv` to know why we use a packet-pool approach here.
         let mut packet_pool = [const { None }; 8];
 
@@ -47,13 +56,38 @@ impl Connection {
         // userspace.
         Self::copy_to_send_buffers(&mut packet_pool[..], reader, num_bytes)?;
 
-        self.build_and_send_tx_packets(&mut packet_pool[..])?;
+        self.build_and_send_tx_packets(&mut packet_pool[..], VirtioVsockRwFlags::empty())?;
 
         self.inner.pollee.invalidate();
 
         Ok(num_bytes)
     }
 
+    fn try_send_message(
+        &mut self,
+        reader: &mut dyn MultiRead,
+        flags: SendRecvFlags,
+    ) -> Result<usize> {
+        let msg_len = reader.sum_lens();
+
+        let mut packet_pool = self.alloc_message_buffers(msg_len)?;
+        if msg_len == 0 {
+            return Ok(0);
+        }
+
+        Self::copy_to_send_buffers(&mut packet_pool[..], reader, msg_len)?;
+
+        let mut rw_flags = VirtioVsockRwFlags::SEQ_EOM;
+        if flags.contains(SendRecvFlags::MSG_EOR) {
+            rw_flags |= VirtioVsockRwFlags::SEQ_EOR;
+        }
+        self.build_and_send_tx_packets(&mut packet_pool[..], rw_flags)?;
+
+        self.inner.pollee.invalidate();
+
+        Ok(msg_len)
+    }
+
     fn alloc_send_buffers(
         &mut self,
         packet_pool: &mut [Option<TxPacketBuilder>],
@@ -77,7 +111,7 @@ impl Connection {
             return_errno_with_message!(Errno::EAGAIN, "the pending queue is full");
         }
 
-        let credit_room = state.check_peer_credit(&self.inner)?;
+        let credit_room = state.check_peer_credit(&self.inner, 1)?;
         debug_assert_ne!(credit_room, 0);
 
         let max_bytes = max_bytes.min(pending_queue_room).min(credit_room);
@@ -96,6 +130,47 @@ impl Connection {
         Ok(num_bytes)
     }
 
+    fn alloc_message_buffers(&mut self, msg_len: usize) -> Result<Vec<Option<TxPacketBuilder>>> {
+        let mut state = self.inner.state.lock();
+
+        state.test_and_clear_error(&self.inner)?;
+
+        if state.shutdown.local_write_closed || state.shutdown.peer_read_closed {
+            return_errno_with_message!(Errno::EPIPE, "the connection is closed for writing");
+        }
+
+        if msg_len == 0 {
+            return Ok(Vec::new());
+        }
+
+        // Like Linux, a message that cannot fit in the peer's receive buffer is rejected.
+        if msg_len > state.credit.peer_buf_alloc as usize || msg_len > DEFAULT_TX_BUF_SIZE {
+            return_errno_with_message!(Errno::EMSGSIZE, "the message is too large");
+        }
+
+        // The message can only be sent as a whole. Record its length so that `IoEvents::OUT` is
+        // not reported before there is enough room for it.
+        state.credit.blocked_msg_len = msg_len;
+
+        let pending_queue_room =
+            DEFAULT_TX_BUF_SIZE - self.inner.pending_tx_bytes.load(Ordering::Relaxed);
+        if pending_queue_room < msg_len {
+            return_errno_with_message!(Errno::EAGAIN, "the pending queue is full");
+        }
+
+        state.check_peer_credit(&self.inner, msg_len)?;
+
+        state.credit.blocked_msg_len = 0;
+
+        let num_packets = msg_len.div_ceil(TxPacketBuilder::MAX_NBYTES);
+        let mut packet_pool = Vec::with_capacity(num_packets);
+        for _ in 0..num_packets {
+            packet_pool.push(Some(TxPacket::new_builder()?));
+        }
+
+        Ok(packet_pool)
+    }
+
     fn copy_to_send_buffers(
         packet_pool: &mut [Option<TxPacketBuilder>],
         reader: &mut dyn MultiRead,
@@ -121,26 +196,60 @@ impl Connection {
         Ok(())
     }
 
-    fn build_and_send_tx_packets(&self, packet_pool: &mut [Option<TxPacketBuilder>]) -> Result<()> {
+    /// Builds the TX packets and sends them.
+    ///
+    /// The last packet carries `last_flags`, which marks the end of a message.
+    fn build_and_send_tx_packets(
+        &self,
+        packet_pool: &mut [Option<TxPacketBuilder>],
+        last_flags: VirtioVsockRwFlags,
+    ) -> Result<()> {
         let mut state = self.inner.state.lock();
 
         if state.shutdown.local_write_closed || state.shutdown.peer_read_closed {
             return_errno_with_message!(Errno::EPIPE, "the connection is closed for writing");
         }
 
+        let num_packets = packet_pool.iter().take_while(|opt| opt.is_some()).count();
+        let packet_flags = |index: usize| {
+            if index + 1 == num_packets {
+                last_flags.bits()
+            } else {
+                0
+            }
+        };
+
+        // Packets sent via the loopback transport are delivered directly without going through a
+        // device, so they never wait in the pending queue.
+        if self.inner.transport == TransportKind::Loopback {
+            let mut num_bytes = 0;
+
+            for (index, packet_opt) in packet_pool[..num_packets].iter_mut().enumerate() {
+                let packet_builder = packet_opt.take().unwrap();
+
+                num_bytes += packet_builder.payload_len();
+                let packet = state.make_tx_packet(&self.inner, packet_builder, packet_flags(index));
+
+                // Lock order: socket state -> loopback packets
+                loopback::send_packet(packet);
+            }
+
+            state.consume_peer_credit(num_bytes);
+
+            return Ok(());
+        }
+
         let vsock_space = self.inner.bound_port.vsock_space();
         let mut tx = vsock_space.device().lock_tx();
 
         let mut num_bytes = 0;
         let mut num_bytes_in_pending = 0;
 
-        for packet_opt in packet_pool.iter_mut() {
-            let Some(packet_builder) = packet_opt.take() else {
-                break;
-            };
+        for (index, packet_opt) in packet_pool[..num_packets].iter_mut().enumerate() {
+            let packet_builder = packet_opt.take().unwrap();
 
             let nbytes = packet_builder.payload_len();
-            let packet = state.make_tx_packet(&self.inner, packet_builder);
+            let packet = state.make_tx_packet(&self.inner, packet_builder, packet_flags(index));
 
             match tx.try_send(packet) {
                 Ok(()) => (),
@@ -171,10 +280,11 @@ impl Connection {
 }
 
 impl ConnectionState {
-    fn check_peer_credit(&mut self, conn: &ConnectionInner) -> Result<usize> {
+    /// Returns the peer credit if it is at least `min_bytes`, otherwise requests more credit.
+    fn check_peer_credit(&mut self, conn: &ConnectionInner, min_bytes: usize) -> Result<usize> {
         let peer_free = self.peer_credit();
 
-        if peer_free != 0 {
+        if peer_free >= min_bytes {
             return Ok(peer_free);
         }
 
@@ -8,10 +8,7 @@ use aster_virtio::device::socket::{
 };
 
 use crate::{
-    net::socket::vsock::transport::{
-        DEFAULT_RX_BUF_SIZE,
-        connection::{ConnectionInner, ConnectionState, TimerState},
-    },
+    net::socket::vsock::transport::connection::{ConnectionInner, ConnectionState, TimerState},
     prelude::*,
 };
 
@@ -38,15 +35,20 @@ impl ConnectionState {
             conn.conn_id.local_port,
             conn.conn_id.peer_port,
             0,
+            conn.type_,
             op,
             flags,
-            DEFAULT_RX_BUF_SIZE as u32,
+            self.credit.buf_alloc,
             self.credit.local_fwd_cnt,
         );
 
-        // Lock order: socket state -> device TX
+        // Lock order: socket state -> device TX or loopback packets
 
-        if conn.bound_port.vsock_space().send_packet(&header) {
+        if conn
+            .bound_port
+            .vsock_space()
+            .send_packet(conn.transport, &header)
+        {
             self.credit.last_reported_fwd_cnt = self.credit.local_fwd_cnt;
             true
         } else {
@@ -58,6 +60,7 @@ impl ConnectionState {
         &mut self,
         conn: &ConnectionInner,
         packet_builder: TxPacketBuilder,
+        flags: u32,
     ) -> TxPacket {
         let header = VirtioVsockHdr::new(
             conn.conn_id.local_cid,
@@ -65,9 +68,10 @@ impl ConnectionState {
             conn.conn_id.local_port,
             conn.conn_id.peer_port,
             packet_builder.payload_len() as u32,
+            conn.type_,
             VirtioVsockOp::Rw,
-            0,
-            DEFAULT_RX_BUF_SIZE as u32,
+            flags,
+            self.credit.buf_alloc,
             self.credit.local_fwd_cnt,
         );
         self.credit.last_reported_fwd_cnt = self.credit.local_fwd_cnt;
@@ -1,14 +1,17 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use core::sync::atomic::{AtomicUsize, Ordering};
+use core::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
 
 use aster_softirq::BottomHalfDisabled;
+use aster_virtio::device::socket::header::VirtioVsockType;
 
 use crate::{
     events::IoEvents,
     net::socket::vsock::{
         VsockSocketAddr,
-        transport::{BoundPort, Connection, MAX_BACKLOG, connection::ConnectionInner},
+        transport::{
+            BoundPort, Connection, MAX_BACKLOG, SocketConfig, connection::ConnectionInner,
+        },
     },
     prelude::*,
     process::signal::Pollee,
@@ -34,6 +37,11 @@ impl Listener {
         self.inner.set_backlog(backlog);
     }
 
+    /// Updates the receive buffer size of the connections that will be accepted.
+    pub(in crate::net::socket::vsock) fn set_buf_size(&self, buf_size: u32) {
+        self.inner.buf_size.store(buf_size, Ordering::Relaxed);
+    }
+
     /// Returns the local listening address.
     pub(in crate::net::socket::vsock) fn local_addr(&self) -> VsockSocketAddr {
         self.inner.bound_port.local_addr()
@@ -54,6 +62,8 @@ impl Drop for Listener {
 
 pub(super) struct ListenerInner {
     bound_port: BoundPort,
+    type_: VirtioVsockType,
+    buf_size: AtomicU32,
     pollee: Pollee,
     backlog: AtomicUsize,
     num_conns: AtomicUsize,
@@ -61,11 +71,18 @@ pub(super) struct ListenerInner {
 }
 
 impl ListenerInner {
-    pub(super) fn new(bound_port: BoundPort, backlog: usize, pollee: Pollee) -> Arc<Self> {
+    pub(super) fn new(
+        bound_port: BoundPort,
+        backlog: usize,
+        config: &SocketConfig,
+        pollee: Pollee,
+    ) -> Arc<Self> {
         pollee.invalidate();
 
         Arc::new(Self {
             bound_port,
+            type_: config.type_(),
+            buf_size: AtomicU32::new(config.buf_size),
             pollee,
             backlog: AtomicUsize::new(backlog.min(MAX_BACKLOG)),
             num_conns: AtomicUsize::new(0),
@@ -125,4 +142,12 @@ impl ListenerInner {
     pub(super) fn bound_port(&self) -> &BoundPort {
         &self.bound_port
     }
+
+    pub(super) fn type_(&self) -> VirtioVsockType {
+        self.type_
+    }
+
+    pub(super) fn buf_size(&self) -> u32 {
+        self.buf_size.load(Ordering::Relaxed)
+    }
 }
@@ -0,0 +1,47 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The loopback transport.
+//!
+//! Like the `vsock_loopback` transport in Linux, packets sent to the local CID are queued and then
+//! processed in the bottom half, as if they were received from a device.
+//!
+//! Reference: <https://elixir.bootlin.com/linux/v6.16.8/source/net/vmw_vsock/vsock_loopback.c>
+
+use alloc::{collections::vec_deque::VecDeque, sync::Arc};
+
+use aster_softirq::{BottomHalfDisabled, Taskless};
+use aster_virtio::device::socket::packet::TxPacket;
+use ostd::sync::SpinLock;
+use spin::Once;
+
+use crate::net::socket::vsock::transport::space::vsock_space;
+
+static PENDING_PACKETS: SpinLock<VecDeque<TxPacket>, BottomHalfDisabled> =
+    SpinLock::new(VecDeque::new());
+
+static TASKLESS: Once<Arc<Taskless>> = Once::new();
+
+/// Queues a packet to be delivered to the local CID.
+///
+/// This method may be called while holding the socket state lock.
+pub(super) fn send_packet(packet: TxPacket) {
+    // Lock order: socket state -> loopback packets
+
+    PENDING_PACKETS.lock().push_back(packet);
+
+    TASKLESS.get().unwrap().schedule();
+}
+
+fn process_pending_packets() {
+    let packets = {
+        let mut pending = PENDING_PACKETS.lock();
+        core::mem::take(&mut *pending)
+    };
+
+    let vsock_space = vsock_space().unwrap();
+    vsock_space.process_loopback_rx(packets);
+}
+
+pub(super) fn init() {
+    TASKLESS.call_once(|| Taskless::new(process_pending_packets));
+}
@@ -2,11 +2,11 @@
 
 //! The virtio-vsock transport protocol.
 //!
-//! Built on top of the [_basic virtio-vsock device support_](`aster_virtio::device::socket`), this
-//! module manages connections and listeners, handles connection establishment, data transfer, and
-//! shutdown, and implements credit-based flow control plus I/O event checking and notification.
-//! The socket layer is expected to build on these APIs to provide the user-visible socket
-//! interface.
+//! Built on top of the [_basic virtio-vsock device support_](`aster_virtio::device::socket`) and a
+//! loopback transport that delivers packets back to the local CID, this module manages connections
+//! and listeners, handles connection establishment, data transfer, and shutdown, and implements
+//! credit-based flow control plus I/O event checking and notification. The socket layer is
+//! expected to build on these APIs to provide the user-visible socket interface.
 //!
 //! For a quick start, bind to a port by creating a [`BoundPort`] instance.
 //!  - To connect to a remote address, use the [`BoundPort::connect`] method and get a
@@ -21,28 +21,47 @@
 mod conn_id;
 mod connection;
 mod listener;
+mod loopback;
+mod packet;
 mod port;
 mod space;
 mod timer;
 
 use core::time::Duration;
 
+use aster_virtio::device::socket::header::VirtioVsockType;
 pub(super) use connection::{Connection, connect::ConnectResult};
 pub(super) use listener::Listener;
 pub(super) use port::BoundPort;
+pub(super) use space::check_remote_addr;
 
-// Reference: <https://elixir.bootlin.com/linux/v6.16.8/source/net/vmw_vsock/af_vsock.c#L136>
-const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
 // Reference: <https://elixir.bootlin.com/linux/v6.16.8/source/net/vmw_vsock/virtio_transport_common.c#L24>
 const DEFAULT_CLOSE_TIMEOUT: Duration = Duration::from_secs(8);
 // Reference: <https://elixir.bootlin.com/linux/v6.16.8/source/net/vmw_vsock/af_vsock.c#L138>
-const DEFAULT_RX_BUF_SIZE: usize = 256 * 1024;
-// Reference: <https://elixir.bootlin.com/linux/v6.16.8/source/net/vmw_vsock/af_vsock.c#L138>
 const DEFAULT_TX_BUF_SIZE: usize = 256 * 1024;
 // Reference: <https://elixir.bootlin.com/linux/v6.16.8/source/include/linux/socket.h#L298>
 const MAX_BACKLOG: usize = 4096;
 
-const CREDIT_UPDATE_THRESHOLD: u32 = (DEFAULT_RX_BUF_SIZE / 4) as u32;
+/// The socket-level settings that the transport needs to know.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct SocketConfig {
+    /// Whether the socket preserves message boundaries (i.e., `SOCK_SEQPACKET`).
+    pub(super) is_seqpacket: bool,
+    /// The size of the receive buffer advertised to the peer.
+    pub(super) buf_size: u32,
+    /// The time limit for establishing a connection.
+    pub(super) connect_timeout: Duration,
+}
+
+impl SocketConfig {
+    fn type_(&self) -> VirtioVsockType {
+        if self.is_seqpacket {
+            VirtioVsockType::SeqPacket
+        } else {
+            VirtioVsockType::Stream
+        }
+    }
+}
 
 fn process_rx_callback() {
     if let Ok(vsock_space) = space::vsock_space() {
@@ -56,17 +75,20 @@ fn process_event_callback() {
     }
 }
 
-/// Initializes the virtio-vsock transport when the default device is present.
+/// Initializes the vsock transports.
+///
+/// The loopback transport is always available, while the virtio-vsock transport is only available
+/// when the default device is present.
 pub(super) fn init() {
     use aster_virtio::device::socket::DEVICE_NAME;
 
-    let Some(device) = aster_virtio::device::socket::get_device(DEVICE_NAME) else {
-        return;
-    };
-
-    device.init_rx_callback(process_rx_callback);
-    device.init_event_callback(process_event_callback);
+    let device = aster_virtio::device::socket::get_device(DEVICE_NAME);
+    if let Some(device) = device.as_ref() {
+        device.init_rx_callback(process_rx_callback);
+        device.init_event_callback(process_event_callback);
+    }
     space::init(device);
 
+    loopback::init();
     timer::init();
 }
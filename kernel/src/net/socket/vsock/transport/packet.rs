@@ -0,0 +1,56 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use aster_virtio::device::socket::{
+    header::VirtioVsockHdr,
+    packet::{RxPacket, TxPacket},
+};
+use ostd::mm::{Infallible, VmReader};
+
+/// A transport through which packets are sent and received.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) enum TransportKind {
+    /// The virtio-vsock device, which connects the guest to the host.
+    Virtio,
+    /// The loopback transport, which connects local sockets to each other.
+    Loopback,
+}
+
+/// A packet received from a transport.
+pub(super) enum RecvPacket {
+    /// A packet received from the virtio-vsock device.
+    Virtio(RxPacket),
+    /// A packet sent via the loopback transport.
+    ///
+    /// The packet is delivered as is, so no copies are needed.
+    Loopback(TxPacket),
+}
+
+impl RecvPacket {
+    pub(super) fn header(&self) -> VirtioVsockHdr {
+        match self {
+            Self::Virtio(packet) => packet.header(),
+            Self::Loopback(packet) => packet.header(),
+        }
+    }
+
+    pub(super) fn payload_len(&self) -> usize {
+        match self {
+            Self::Virtio(packet) => packet.payload_len(),
+            Self::Loopback(packet) => packet.payload_len(),
+        }
+    }
+
+    pub(super) fn payload(&self) -> VmReader<'_, Infallible> {
+        match self {
+            Self::Virtio(packet) => packet.payload(),
+            Self::Loopback(packet) => packet.payload(),
+        }
+    }
+
+    pub(super) const fn transport(&self) -> TransportKind {
+        match self {
+            Self::Virtio(_) => TransportKind::Virtio,
+            Self::Loopback(_) => TransportKind::Loopback,
+        }
+    }
+}
@@ -5,9 +5,9 @@ use alloc::collections::btree_map::BTreeMap;
 use crate::{
     error::{Errno, Error, return_errno_with_message},
     net::socket::vsock::{
-        addr::{VMADDR_CID_ANY, VMADDR_PORT_ANY, VsockSocketAddr},
+        addr::{VMADDR_CID_ANY, VMADDR_CID_LOCAL, VMADDR_PORT_ANY, VsockSocketAddr},
         transport::{
-            Connection, Listener,
+            Connection, Listener, SocketConfig,
             space::{VsockSpace, vsock_space},
         },
     },
@@ -22,6 +22,8 @@ use crate::{
 #[derive(Debug)]
 pub(in crate::net::socket::vsock) struct BoundPort {
     port: u32,
+    /// The CID specified when binding, which may be [`VMADDR_CID_ANY`].
+    cid: u32,
 }
 
 pub(super) struct PortTable {
@@ -58,12 +60,15 @@ impl BoundPort {
         let vsock_space = vsock_space()?;
 
         let guest_cid = vsock_space.guest_cid();
-        if addr.cid != VMADDR_CID_ANY && addr.cid as u64 != guest_cid {
+        if addr.cid != VMADDR_CID_ANY
+            && addr.cid != VMADDR_CID_LOCAL
+            && addr.cid as u64 != guest_cid
+        {
             return_errno_with_message!(Errno::EADDRNOTAVAIL, "the vsock CID is not local");
         }
 
         if addr.port == VMADDR_PORT_ANY {
-            return Self::new_ephemeral();
+            return Self::new_ephemeral_with_cid(addr.cid);
         }
 
         let mut ports = vsock_space.lock_ports();
@@ -72,11 +77,18 @@ impl BoundPort {
             return_errno_with_message!(Errno::EADDRINUSE, "the vsock port is already in use");
         }
         *usage += 1;
-        Ok(Self { port: addr.port })
+        Ok(Self {
+            port: addr.port,
+            cid: addr.cid,
+        })
     }
 
     /// Allocates and returns a fresh ephemeral port lease.
     pub(in crate::net::socket::vsock) fn new_ephemeral() -> Result<Self> {
+        Self::new_ephemeral_with_cid(VMADDR_CID_ANY)
+    }
+
+    fn new_ephemeral_with_cid(cid: u32) -> Result<Self> {
         let vsock_space = vsock_space()?;
         let mut ports = vsock_space.lock_ports();
 
@@ -88,7 +100,10 @@ impl BoundPort {
             if *usage == 0 {
                 *usage += 1;
                 ports.next_ephemeral_port = PortTable::next_ephemeral_port_after(current_port);
-                return Ok(Self { port: current_port });
+                return Ok(Self {
+                    port: current_port,
+                    cid,
+                });
             }
 
             current_port = PortTable::next_ephemeral_port_after(current_port);
@@ -109,6 +124,7 @@ impl BoundPort {
         *usage += 1;
         BoundPort {
             port: bound_port.port,
+            cid: bound_port.cid,
         }
     }
 
@@ -119,10 +135,11 @@ impl BoundPort {
     pub(in crate::net::socket::vsock) fn connect(
         self,
         remote_addr: VsockSocketAddr,
+        config: &SocketConfig,
         pollee: &Pollee,
     ) -> Result<Connection, (Error, BoundPort)> {
         let vsock_space = self.vsock_space();
-        vsock_space.new_connection(self, remote_addr, pollee)
+        vsock_space.new_connection(self, remote_addr, config, pollee)
     }
 
     /// Starts listening on the leased port.
@@ -132,20 +149,32 @@ impl BoundPort {
     pub(in crate::net::socket::vsock) fn listen(
         self,
         backlog: usize,
+        config: &SocketConfig,
         pollee: &Pollee,
     ) -> Result<Listener, (Error, BoundPort)> {
         let vsock_space = self.vsock_space();
-        vsock_space.new_listener(self, backlog, pollee)
+        vsock_space.new_listener(self, backlog, config, pollee)
     }
 
     /// Returns the local address described by this lease.
     pub(in crate::net::socket::vsock) fn local_addr(&self) -> VsockSocketAddr {
+        let cid = if self.cid == VMADDR_CID_ANY {
+            self.vsock_space().guest_cid() as u32
+        } else {
+            self.cid
+        };
+
         VsockSocketAddr {
-            cid: self.vsock_space().guest_cid() as u32,
+            cid,
             port: self.port,
         }
     }
 
+    /// Returns whether packets addressed to `cid` can be delivered to the port.
+    pub(super) fn accepts_cid(&self, cid: u64) -> bool {
+        self.cid == VMADDR_CID_ANY || self.cid as u64 == cid
+    }
+
     pub(super) fn vsock_space(&self) -> &'static VsockSpace {
         // This won't fail because we've checked it in all constructors.
         vsock_space().unwrap()
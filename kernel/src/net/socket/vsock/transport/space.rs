@@ -3,8 +3,11 @@
 use aster_softirq::BottomHalfDisabled;
 use aster_virtio::device::socket::{
     device::SocketDevice,
-    header::{VirtioVsockHdr, VirtioVsockOp, VirtioVsockShutdownFlags, VirtioVsockType},
-    packet::{RxPacket, TxPacket},
+    header::{
+        VirtioVsockHdr, VirtioVsockOp, VirtioVsockRwFlags, VirtioVsockShutdownFlags,
+        VirtioVsockType,
+    },
+    packet::TxPacket,
 };
 use ostd::sync::PreemptDisabled;
 use spin::Once;
@@ -12,20 +15,26 @@ use spin::Once;
 use crate::{
     events::IoEvents,
     net::socket::vsock::{
-        addr::VsockSocketAddr,
+        addr::{VMADDR_CID_HOST, VMADDR_CID_LOCAL, VsockSocketAddr},
         transport::{
-            BoundPort, Connection, Listener, conn_id::ConnId, connection::ConnectionInner,
-            listener::ListenerInner, port::PortTable, timer::TimerEvent,
+            BoundPort, Connection, Listener, SocketConfig,
+            conn_id::ConnId,
+            connection::ConnectionInner,
+            listener::ListenerInner,
+            loopback,
+            packet::{RecvPacket, TransportKind},
+            port::PortTable,
+            timer::TimerEvent,
         },
     },
     prelude::*,
     process::signal::Pollee,
 };
 
-// We currently support only one vsock device.
-// TODO: Add support for multiple vsock devices and the loopback vsock device.
+// We currently support only one vsock device, in addition to the loopback transport.
+// TODO: Add support for multiple vsock devices.
 pub(super) struct VsockSpace {
-    device: Arc<SocketDevice>,
+    device: Option<Arc<SocketDevice>>,
     ports: SpinLock<PortTable>,
     sockets: SpinLock<SocketTable, BottomHalfDisabled>,
 }
@@ -36,7 +45,7 @@ struct SocketTable {
 }
 
 impl VsockSpace {
-    fn new(device: Arc<SocketDevice>) -> Self {
+    fn new(device: Option<Arc<SocketDevice>>) -> Self {
         Self {
             device,
             ports: SpinLock::new(PortTable::new()),
@@ -47,12 +56,53 @@ impl VsockSpace {
         }
     }
 
+    /// Returns the virtio-vsock device.
+    ///
+    /// This method should only be called for [`TransportKind::Virtio`] connections, which cannot
+    /// exist without the device.
     pub(super) fn device(&self) -> &SocketDevice {
-        &self.device
+        self.device.as_ref().unwrap()
     }
 
+    /// Returns the CID of the guest.
+    ///
+    /// Like Linux, this is [`VMADDR_CID_LOCAL`] if there is no virtio-vsock device.
     pub(super) fn guest_cid(&self) -> u64 {
-        self.device.guest_cid()
+        self.device
+            .as_ref()
+            .map_or(VMADDR_CID_LOCAL as u64, |device| device.guest_cid())
+    }
+
+    /// Returns the CID that the transport uses as the source of outgoing packets.
+    pub(super) fn local_cid(&self, transport: TransportKind) -> u64 {
+        match transport {
+            TransportKind::Virtio => self.guest_cid(),
+            TransportKind::Loopback => VMADDR_CID_LOCAL as u64,
+        }
+    }
+
+    /// Selects the transport that can reach `remote_cid`.
+    fn select_transport(&self, remote_cid: u32, config: &SocketConfig) -> Result<TransportKind> {
+        // Like Linux, connections to the guest's own CID are also handled by the loopback
+        // transport.
+        if remote_cid == VMADDR_CID_LOCAL || remote_cid as u64 == self.guest_cid() {
+            return Ok(TransportKind::Loopback);
+        }
+
+        let Some(device) = self.device.as_ref() else {
+            return_errno_with_message!(Errno::ENETUNREACH, "no virtio-vsock device is available");
+        };
+        if remote_cid != VMADDR_CID_HOST {
+            return_errno_with_message!(Errno::ENETUNREACH, "only the host vsock CID is supported");
+        }
+        if config.is_seqpacket && !device.supports_seqpacket() {
+            return_errno_with_message!(
+                Errno::ESOCKTNOSUPPORT,
+                "the virtio-vsock device does not support seqpacket sockets"
+            );
+        }
+
+        Ok(TransportKind::Virtio)
     }
 
     pub(super) fn lock_ports(&self) -> SpinLockGuard<'_, PortTable, PreemptDisabled> {
@@ -66,15 +116,21 @@ impl VsockSpace {
         &self,
         bound_port: BoundPort,
         remote_addr: VsockSocketAddr,
+        config: &SocketConfig,
         pollee: &Pollee,
     ) -> Result<Connection, (Error, BoundPort)> {
         use alloc::collections::btree_map::Entry;
 
         let mut sockets = self.sockets.lock();
 
-        // Note that we should query the guest CID (part of `from_port_and_remote`) after locking
-        // `sockets` to avoid race conditions with `process_transport_event`.
-        let conn_id = ConnId::from_port_and_remote(&bound_port, remote_addr);
+        // Note that we should query the guest CID (part of `select_transport` and
+        // `from_port_and_remote`) after locking `sockets` to avoid race conditions with
+        // `process_transport_event`.
+        let transport = match self.select_transport(remote_addr.cid, config) {
+            Ok(transport) => transport,
+            Err(error) => return Err((error, bound_port)),
+        };
+        let conn_id = ConnId::from_port_and_remote(&bound_port, remote_addr, transport);
         let Entry::Vacant(entry) = sockets.connections.entry(conn_id) else {
             return Err((
                 Error::with_message(Errno::EADDRINUSE, "the vsock connection already exists"),
@@ -82,7 +138,13 @@ impl VsockSpace {
             ));
         };
 
-        let inner = ConnectionInner::new_connecting(bound_port, &conn_id, pollee.clone());
+        let inner = ConnectionInner::new_connecting(
+            bound_port,
+            &conn_id,
+            transport,
+            config,
+            pollee.clone(),
+        );
         entry.insert(inner.clone());
 
         Ok(Connection::new(inner))
@@ -115,6 +177,7 @@ impl VsockSpace {
         &self,
         bound_port: BoundPort,
         backlog: usize,
+        config: &SocketConfig,
         pollee: &Pollee,
     ) -> Result<Listener, (Error, BoundPort)> {
         use alloc::collections::btree_map::Entry;
@@ -129,7 +192,7 @@ impl VsockSpace {
             ));
         };
 
-        let inner = ListenerInner::new(bound_port, backlog, pollee.clone());
+        let inner = ListenerInner::new(bound_port, backlog, config, pollee.clone());
         entry.insert(inner.clone());
 
         Ok(Listener::new(inner))
@@ -178,15 +241,29 @@ impl VsockSpace {
     pub(super) fn process_rx(&self) {
         // Lock order: device RX -> sockets -> socket state -> device TX
 
-        let mut rx = self.device.lock_rx();
+        let Some(device) = self.device.as_ref() else {
+            return;
+        };
+
+        let mut rx = device.lock_rx();
         let mut sockets = self.sockets.lock();
 
         while let Some(packet) = rx.recv() {
-            self.process_rx_packet(&mut sockets, packet);
+            self.process_rx_packet(&mut sockets, RecvPacket::Virtio(packet));
         }
     }
 
-    fn process_rx_packet(&self, sockets: &mut SocketTable, packet: RxPacket) {
+    pub(super) fn process_loopback_rx(&self, packets: VecDeque<TxPacket>) {
+        // Lock order: sockets -> socket state -> loopback packets
+
+        let mut sockets = self.sockets.lock();
+
+        for packet in packets.into_iter() {
+            self.process_rx_packet(&mut sockets, RecvPacket::Loopback(packet));
+        }
+    }
+
+    fn process_rx_packet(&self, sockets: &mut SocketTable, packet: RecvPacket) {
         use alloc::collections::btree_map::Entry;
 
         let header = packet.header();
@@ -207,29 +284,34 @@ impl VsockSpace {
         listeners: &BTreeMap<u32, Arc<ListenerInner>>,
         vacant_conn: alloc::collections::btree_map::VacantEntry<'_, ConnId, Arc<ConnectionInner>>,
         header: &VirtioVsockHdr,
-        packet: RxPacket,
+        packet: RecvPacket,
     ) {
-        if header.op() != Some(VirtioVsockOp::Request)
-            || !self.validate_rx_header(VirtioVsockOp::Request, header, &packet)
-        {
-            self.send_raw_rst(header);
-            return;
-        }
+        let transport = packet.transport();
 
         let dst_port = header.dst_port;
         let listener = if let Some(listener) = listeners.get(&dst_port)
+            && header.op() == Some(VirtioVsockOp::Request)
+            && self.validate_rx_header(VirtioVsockOp::Request, listener.type_(), header, &packet)
+            && listener.bound_port().accepts_cid(header.dst_cid)
             && !listener.is_full()
         {
             listener
         } else {
-            self.send_raw_rst(header);
+            self.send_raw_rst(transport, header);
             return;
         };
 
         let bound_port = BoundPort::new_shared(listener.bound_port());
         let conn_id = vacant_conn.key();
 
-        let inner = ConnectionInner::new_connected(bound_port, conn_id, header);
+        let inner = ConnectionInner::new_connected(
+            bound_port,
+            conn_id,
+            transport,
+            listener.type_(),
+            listener.buf_size(),
+            header,
+        );
         vacant_conn.insert(inner.clone());
 
         listener.push_incoming(inner.clone());
@@ -243,10 +325,13 @@ impl VsockSpace {
             Arc<ConnectionInner>,
         >,
         header: &VirtioVsockHdr,
-        packet: RxPacket,
+        packet: RecvPacket,
     ) {
+        let connection = occupied_conn.get();
+
         let op = if let Some(op) = header.op()
-            && self.validate_rx_header(op, header, &packet)
+            && packet.transport() == connection.transport()
+            && self.validate_rx_header(op, connection.type_(), header, &packet)
         {
             op
         } else {
@@ -254,8 +339,6 @@ impl VsockSpace {
             return;
         };
 
-        let connection = occupied_conn.get();
-
         let should_remove = match op {
             VirtioVsockOp::Request => {
                 connection.active_rst();
@@ -277,14 +360,13 @@ impl VsockSpace {
         }
     }
 
-    fn send_raw_rst(&self, header: &VirtioVsockHdr) {
+    fn send_raw_rst(&self, transport: TransportKind, header: &VirtioVsockHdr) {
         if header.op == VirtioVsockOp::Rst as u16 {
             // Do not send an RST packet in response to an RST packet. Otherwise, we may loop.
             return;
         }
 
-        // We do not use `VirtioVsockHdr::new` here because we want to specify the `type_` field. It
-        // may not be `VirtioVsockType::Stream`.
+        // We do not use `VirtioVsockHdr::new` here because the `type_` field may be invalid.
         let rst_header = VirtioVsockHdr {
             src_cid: header.dst_cid,
             dst_cid: header.src_cid,
@@ -297,7 +379,7 @@ impl VsockSpace {
             buf_alloc: 0,
             fwd_cnt: 0,
         };
-        let _ = self.send_packet(&rst_header);
+        let _ = self.send_packet(transport, &rst_header);
     }
 
     pub(super) fn process_transport_event(&self) {
@@ -310,14 +392,19 @@ impl VsockSpace {
         // fetched again. Existing listen sockets remain but their CID is updated to reflect the
         // current guest_cid."
 
-        let connections = core::mem::take(&mut sockets.connections);
-        for connection in connections.into_values() {
+        let connections = sockets
+            .connections
+            .extract_if(.., |_, connection| {
+                connection.transport() == TransportKind::Virtio
+            })
+            .collect::<Vec<_>>();
+        for (_, connection) in connections.into_iter() {
             connection.on_rst();
             Self::notify_removed_connection(connection);
         }
 
         // The reload of the guest CID is protectd by the `sockets` lock.
-        self.device.reload_guest_id();
+        self.device().reload_guest_id();
     }
 
     pub(super) fn process_timer_events(&self, events: Vec<TimerEvent>) {
@@ -369,16 +456,21 @@ impl VsockSpace {
     // TODO: This method may fail if memory allocation fails. For now, we will ignore the error in
     // most cases. If possible, we should find better ways to handle the error.
     #[must_use]
-    pub(super) fn send_packet(&self, header: &VirtioVsockHdr) -> bool {
+    pub(super) fn send_packet(&self, transport: TransportKind, header: &VirtioVsockHdr) -> bool {
         let Ok(builder) = TxPacket::new_builder() else {
             warn!("failed to allocate vsock packet: {:?}", header);
             return false;
         };
         let packet = builder.build(header);
 
+        if transport == TransportKind::Loopback {
+            loopback::send_packet(packet);
+            return true;
+        }
+
         // Lock order: socket state -> device TX
 
-        let mut tx = self.device.lock_tx();
+        let mut tx = self.device().lock_tx();
         match tx.try_send(packet) {
             Ok(()) => (),
             Err(pending) => {
@@ -395,14 +487,17 @@ impl VsockSpace {
     fn validate_rx_header(
         &self,
         op: VirtioVsockOp,
+        type_: VirtioVsockType,
         header: &VirtioVsockHdr,
-        packet: &RxPacket,
+        packet: &RecvPacket,
     ) -> bool {
-        if header.type_ != VirtioVsockType::Stream as u16 {
+        if header.type_ != type_ as u16 {
             return false;
         }
 
-        if header.dst_cid != self.guest_cid() {
+        // Packets from the loopback transport are sent by ourselves and can be addressed to either
+        // the local CID or the guest CID.
+        if packet.transport() == TransportKind::Virtio && header.dst_cid != self.guest_cid() {
             return false;
         }
 
@@ -420,6 +515,9 @@ impl VsockSpace {
             VirtioVsockOp::Shutdown => {
                 payload_len == 0 && VirtioVsockShutdownFlags::from_bits(header.flags).is_some()
             }
+            VirtioVsockOp::Rw if type_ == VirtioVsockType::SeqPacket => {
+                VirtioVsockRwFlags::from_bits(header.flags).is_some()
+            }
             VirtioVsockOp::Rw => header.flags == 0,
         }
     }
@@ -430,9 +528,19 @@ static VSOCK_SPACE: Once<VsockSpace> = Once::new();
 pub(super) fn vsock_space() -> Result<&'static VsockSpace> {
     VSOCK_SPACE
         .get()
-        .ok_or_else(|| Error::with_message(Errno::ENODEV, "no virtio-vsock device is available"))
+        .ok_or_else(|| Error::with_message(Errno::ENODEV, "the vsock transports are not available"))
+}
+
+/// Checks whether `remote_addr` can be reached by a socket with the given `config`.
+pub(in crate::net::socket::vsock) fn check_remote_addr(
+    remote_addr: &VsockSocketAddr,
+    config: &SocketConfig,
+) -> Result<()> {
+    vsock_space()?
+        .select_transport(remote_addr.cid, config)
+        .map(|_| ())
 }
 
-pub(super) fn init(device: Arc<SocketDevice>) {
+pub(super) fn init(device: Option<Arc<SocketDevice>>) {
     VSOCK_SPACE.call_once(move || VsockSpace::new(device));
 }
@@ -193,6 +193,12 @@ impl UtsName {
             }
         }
     };
+
+    /// Returns the node name (i.e., the host name).
+    pub fn nodename(&self) -> &CStr {
+        // The last byte is always zero because at most `UTS_FIELD_LEN - 1` bytes can be set.
+        CStr::from_bytes_until_nul(&self.nodename).unwrap()
+    }
 }
 
 impl NsCommonOps for UtsNamespace {
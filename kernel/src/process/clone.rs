@@ -26,9 +26,9 @@ use crate::{
     },
     prelude::*,
     process::{
-        NsProxy, UserNamespace,
+        NsProxy, PidNamespace, UserNamespace,
         pid_file::PidFile,
-        posix_thread::{PosixThread, ThreadLocal, allocate_posix_tid},
+        posix_thread::{PosixThread, ThreadLocal},
         stats::PROCESS_CREATION_COUNTER,
     },
     sched::Nice,
@@ -203,7 +203,7 @@ impl CloneArgs {
                 );
             }
 
-            if ctx.process.is_init_process() {
+            if ctx.process.is_pid_ns_init() {
                 return_errno_with_message!(
                     Errno::EINVAL,
                     "`CLONE_PARENT` cannot be used if the process is the init process"
@@ -226,6 +226,22 @@ impl CloneArgs {
                     "`CLONE_THREAD` cannot be used together with `CLONE_PIDFD` or `CLONE_NEWUSER`"
                 );
             }
+
+            // Threads in the same thread group must be in the same PID namespace.
+            if clone_flags.contains(CloneFlags::CLONE_NEWPID)
+                || !Arc::ptr_eq(
+                    ctx.thread_local
+                        .borrow_ns_proxy()
+                        .unwrap()
+                        .pid_ns_for_children(),
+                    ctx.process.pid_ns(),
+                )
+            {
+                return_errno_with_message!(
+                    Errno::EINVAL,
+                    "`CLONE_THREAD` cannot be used if the child would be in another PID namespace"
+                );
+            }
         }
 
         // Reject invalid argument combinations related to the CLONE_SIGHAND flag.
@@ -288,6 +304,8 @@ impl CloneFlags {
 
 /// Clone a child thread or child process.
 ///
+/// Returns the ID of the child in the PID namespace of the current process.
+///
 /// FIXME: currently, the child process or thread will be scheduled to run at once,
 /// but this may not be the expected behavior.
 pub fn clone_child(
@@ -303,7 +321,7 @@ pub fn clone_child(
         child_thread.run();
 
         let child_tid = child_thread.as_posix_thread().unwrap().tid();
-        Ok(child_tid)
+        Ok(ctx.process.pid_ns().local_id(child_tid))
     } else {
         // Hold the read lock before charge to ensure the cgroup of current process
         // won't change during the charge and the subsequent move operation.
@@ -360,7 +378,7 @@ pub fn clone_child(
         }
 
         let child_pid = child_process.pid();
-        Ok(child_pid)
+        Ok(ctx.process.pid_ns().local_id(child_pid))
     }
 }
 
@@ -436,7 +454,8 @@ fn clone_child_task(
     // Inherit the thread name.
     let thread_name = posix_thread.thread_name().lock().clone();
 
-    let child_tid = allocate_posix_tid();
+    let child_pid_ns = process.pid_ns();
+    let child_tid = child_pid_ns.allocate_tid()?;
     let child_task = {
         let credentials = {
             let credentials = ctx.posix_thread.credentials();
@@ -464,23 +483,33 @@ fn clone_child_task(
         }
 
         // Deal with SETTID/CLEARTID flags
-        clone_parent_settid(child_tid, clone_args.parent_tid, clone_flags)?;
+        clone_parent_settid(
+            child_pid_ns.local_id(child_tid),
+            clone_args.parent_tid,
+            clone_flags,
+        )
+        .inspect_err(|_| child_pid_ns.free_tid(child_tid))?;
         thread_builder = clone_child_cleartid(thread_builder, clone_args.child_tid, clone_flags);
         thread_builder = clone_child_settid(thread_builder, clone_args.child_tid, clone_flags);
 
         thread_builder.build()
     };
 
-    process
-        .tasks()
-        .lock()
-        .insert(child_task.clone())
-        .map_err(|_| {
-            Error::with_message(
-                Errno::EINTR,
-                "the process has exited or has already executed a new program",
-            )
-        })?;
+    let mut tasks = process.tasks().lock();
+    // Inherit the seccomp state with the lock held, so that the child thread
+    // cannot miss the filters synchronized to all threads in the process.
+    child_task
+        .as_posix_thread()
+        .unwrap()
+        .inherit_seccomp(posix_thread);
+    tasks.insert(child_task.clone()).map_err(|_| {
+        child_pid_ns.free_tid(child_tid);
+        Error::with_message(
+            Errno::EINTR,
+            "the process has exited or has already executed a new program",
+        )
+    })?;
+    drop(tasks);
 
     let child_thread = child_task.as_thread().unwrap();
     pid_table::pid_table_mut().insert_thread(child_tid, child_thread);
@@ -553,6 +582,13 @@ fn clone_child_process(
             .switch_to_mnt_ns(child_ns_proxy.mnt_ns())?;
     }
 
+    if !Arc::ptr_eq(
+        child_ns_proxy.time_ns(),
+        thread_local.borrow_ns_proxy().unwrap().time_ns(),
+    ) {
+        child_ns_proxy.time_ns().join_vdso(&child_vmar)?;
+    }
+
     // Inherit the parent's signal mask
     let child_sig_mask = posix_thread.sig_mask().into();
 
@@ -565,7 +601,9 @@ fn clone_child_process(
     // Inherit the parent's OOM score adjustment
     let child_oom_score_adj = process.oom_score_adj().load(Ordering::Relaxed);
 
-    let child_tid = allocate_posix_tid();
+    // The child lives in the PID namespace for children of the parent.
+    let child_pid_ns = child_ns_proxy.pid_ns_for_children().clone();
+    let child_tid = child_pid_ns.allocate_tid()?;
 
     let child = {
         let child_vmar_arc = child_vmar.clone_arc();
@@ -602,7 +640,12 @@ fn clone_child_process(
         }
 
         // Deal with SETTID/CLEARTID flags
-        clone_parent_settid(child_tid, clone_args.parent_tid, clone_flags)?;
+        clone_parent_settid(
+            process.pid_ns().local_id(child_tid),
+            clone_args.parent_tid,
+            clone_flags,
+        )
+        .inspect_err(|_| child_pid_ns.free_tid(child_tid))?;
         child_thread_builder =
             clone_child_cleartid(child_thread_builder, clone_args.child_tid, clone_flags);
         child_thread_builder =
@@ -616,11 +659,22 @@ fn clone_child_process(
             child_oom_score_adj,
             child_sig_dispositions,
             child_user_ns,
+            child_pid_ns.clone(),
             child_thread_builder,
         )
     };
 
-    clone_pidfd(ctx, &child, clone_flags, clone_args.pidfd)?;
+    {
+        let _tasks = process.tasks().lock();
+        let child_main_thread = child.main_thread();
+        child_main_thread
+            .as_posix_thread()
+            .unwrap()
+            .inherit_seccomp(posix_thread);
+    }
+
+    clone_pidfd(ctx, &child, clone_flags, clone_args.pidfd)
+        .inspect_err(|_| child_pid_ns.free_tid(child_tid))?;
 
     if let Some(sig) = clone_args.exit_signal {
         child.set_exit_signal(sig);
@@ -823,7 +877,16 @@ fn clone_ns_proxy(
     posix_thread: &PosixThread,
     clone_flags: CloneFlags,
 ) -> Result<Arc<NsProxy>> {
-    parent_ns_proxy.new_clone(user_ns, process, posix_thread, clone_flags)
+    let child_ns_proxy = parent_ns_proxy.new_clone(user_ns, process, posix_thread, clone_flags)?;
+
+    // A child that does not share the VMAR enters the time namespace for children.
+    //
+    // Reference: <https://elixir.bootlin.com/linux/v6.18/source/kernel/time/namespace.c>
+    if clone_flags.contains(CloneFlags::CLONE_VM) {
+        Ok(child_ns_proxy)
+    } else {
+        Ok(child_ns_proxy.with_time_ns_for_children())
+    }
 }
 
 #[expect(clippy::too_many_arguments)]
@@ -835,6 +898,7 @@ fn create_child_process(
     oom_score_adj: i16,
     sig_dispositions: Arc<Mutex<SigDispositions>>,
     user_ns: Arc<UserNamespace>,
+    pid_ns: Arc<PidNamespace>,
     thread_builder: PosixThreadBuilder,
 ) -> Arc<Process> {
     let child_proc = Process::new(
@@ -845,6 +909,7 @@ fn create_child_process(
         oom_score_adj,
         sig_dispositions,
         user_ns,
+        pid_ns,
     );
 
     let child_task = thread_builder.process(Arc::downgrade(&child_proc)).build();
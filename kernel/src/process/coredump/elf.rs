@@ -0,0 +1,392 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The ELF core file format.
+//!
+//! A core file consists of an ELF header, followed by a `PT_NOTE` segment that describes the
+//! process and its threads, and a `PT_LOAD` segment for each memory mapping.
+//!
+//! Reference: <https://elixir.bootlin.com/linux/v6.18/source/fs/binfmt_elf.c>
+
+use core::ops::Range;
+
+use align_ext::AlignExt;
+use ostd::arch::cpu::context::UserContext;
+
+use super::output::CoreWriter;
+use crate::{
+    arch::coredump::{ELF_MACHINE, ElfGregSet, elf_fpregset, elf_gregset},
+    prelude::*,
+    process::signal::{c_types::siginfo_t, sig_num::SigNum},
+    time::timeval_t,
+    vm::{perms::VmPerms, vmar::Vmar},
+};
+
+/// The information of a thread that is saved in a core dump.
+pub(super) struct ThreadCoreInfo {
+    prstatus: ElfPrStatus,
+    fpregset: Option<Vec<u8>>,
+}
+
+impl ThreadCoreInfo {
+    /// Collects the information of the current thread.
+    pub(super) fn new(sig_num: SigNum, ctx: &Context, user_ctx: &UserContext) -> Self {
+        let Context {
+            process,
+            posix_thread,
+            thread_local,
+            ..
+        } = ctx;
+        let pid_ns = process.pid_ns();
+        let supp_user_ctx = thread_local.supp_user_context();
+
+        // Like Linux, the main thread reports the CPU time of the whole process.
+        let prof_clock = if posix_thread.tid() == process.pid() {
+            process.prof_clock()
+        } else {
+            posix_thread.prof_clock()
+        };
+        let (children_utime, children_stime) = process.reaped_children_stats().lock().get();
+
+        let fpregset = elf_fpregset(supp_user_ctx);
+
+        let mut prstatus = ElfPrStatus::new_zeroed();
+        prstatus.pr_info.si_signo = sig_num.as_u8() as i32;
+        prstatus.pr_cursig = sig_num.as_u8() as i16;
+        prstatus.pr_sigpend = posix_thread.sig_queues().sig_pending().into();
+        prstatus.pr_sighold = posix_thread.sig_mask().into();
+        prstatus.pr_pid = pid_ns.local_id(posix_thread.tid()) as i32;
+        prstatus.pr_ppid = pid_ns.local_id(process.parent().pid()) as i32;
+        prstatus.pr_pgrp = pid_ns.local_id(process.pgid()) as i32;
+        prstatus.pr_sid = pid_ns.local_id(process.sid()) as i32;
+        prstatus.pr_utime = prof_clock.user_clock().read_time().into();
+        prstatus.pr_stime = prof_clock.kernel_clock().read_time().into();
+        prstatus.pr_cutime = children_utime.into();
+        prstatus.pr_cstime = children_stime.into();
+        prstatus.pr_reg = elf_gregset(user_ctx, supp_user_ctx, thread_local.orig_syscall_ret());
+        prstatus.pr_fpvalid = fpregset.is_some() as i32;
+
+        Self { prstatus, fpregset }
+    }
+}
+
+/// The information of a process that is saved in a core dump.
+pub(super) struct ProcessCoreInfo {
+    pub(super) psinfo: ElfPrPsInfo,
+    pub(super) siginfo: siginfo_t,
+    pub(super) auxv: Vec<u8>,
+    pub(super) files: Vec<FileMapping>,
+    pub(super) segments: Vec<Segment>,
+}
+
+/// A memory mapping that is backed by a file.
+pub(super) struct FileMapping {
+    pub(super) range: Range<Vaddr>,
+    pub(super) offset: usize,
+    pub(super) path: String,
+}
+
+/// A memory mapping that is dumped as a `PT_LOAD` segment.
+pub(super) struct Segment {
+    pub(super) range: Range<Vaddr>,
+    pub(super) perms: VmPerms,
+    /// The size of the leading part of the mapping whose content is dumped.
+    pub(super) dump_size: usize,
+}
+
+/// Writes a core dump in the ELF format.
+///
+/// The first thread in `threads` should be the one that triggers the core dump.
+pub(super) fn write_elf_core(
+    writer: &mut CoreWriter,
+    process: &ProcessCoreInfo,
+    threads: &[ThreadCoreInfo],
+    vmar: &Vmar,
+) -> Result<()> {
+    let notes = build_notes(process, threads);
+
+    let phnum = match u16::try_from(process.segments.len() + 1) {
+        Ok(phnum) if phnum != PN_XNUM => phnum,
+        _ => return_errno_with_message!(Errno::E2BIG, "there are too many segments to dump"),
+    };
+    let phdrs_offset = size_of::<Elf64Ehdr>();
+    let notes_offset = phdrs_offset + size_of::<Elf64Phdr>() * phnum as usize;
+    let data_offset = (notes_offset + notes.len()).align_up(PAGE_SIZE);
+
+    let ehdr = Elf64Ehdr {
+        e_ident: ELF_IDENT,
+        e_type: ET_CORE,
+        e_machine: ELF_MACHINE,
+        e_version: EV_CURRENT as u32,
+        e_entry: 0,
+        e_phoff: phdrs_offset as u64,
+        e_shoff: 0,
+        e_flags: 0,
+        e_ehsize: size_of::<Elf64Ehdr>() as u16,
+        e_phentsize: size_of::<Elf64Phdr>() as u16,
+        e_phnum: phnum,
+        e_shentsize: 0,
+        e_shnum: 0,
+        e_shstrndx: 0,
+    };
+    writer.write(ehdr.as_bytes())?;
+
+    let note_phdr = Elf64Phdr {
+        p_type: PT_NOTE,
+        p_flags: 0,
+        p_offset: notes_offset as u64,
+        p_vaddr: 0,
+        p_paddr: 0,
+        p_filesz: notes.len() as u64,
+        p_memsz: 0,
+        p_align: 4,
+    };
+    writer.write(note_phdr.as_bytes())?;
+
+    let mut offset = data_offset;
+    for segment in process.segments.iter() {
+        let load_phdr = Elf64Phdr {
+            p_type: PT_LOAD,
+            p_flags: segment_flags(segment.perms),
+            p_offset: offset as u64,
+            p_vaddr: segment.range.start as u64,
+            p_paddr: 0,
+            p_filesz: segment.dump_size as u64,
+            p_memsz: segment.range.len() as u64,
+            p_align: PAGE_SIZE as u64,
+        };
+        writer.write(load_phdr.as_bytes())?;
+        offset += segment.dump_size;
+    }
+
+    writer.write(&notes)?;
+    writer.write_zeros(data_offset - (notes_offset + notes.len()))?;
+
+    let mut page = vec![0u8; PAGE_SIZE];
+    for segment in process.segments.iter() {
+        let dump_range = segment.range.start..segment.range.start + segment.dump_size;
+        for page_addr in dump_range.step_by(PAGE_SIZE) {
+            let mut page_writer = VmWriter::from(page.as_mut_slice()).to_fallible();
+            // Like Linux, pages that cannot be read are dumped as zeros.
+            if vmar
+                .read_page_for_dump(page_addr, &mut page_writer)
+                .is_err()
+            {
+                page.fill(0);
+            }
+            writer.write(&page)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn build_notes(process: &ProcessCoreInfo, threads: &[ThreadCoreInfo]) -> Vec<u8> {
+    let mut notes = Vec::new();
+
+    for (index, thread) in threads.iter().enumerate() {
+        push_note(&mut notes, NT_PRSTATUS, thread.prstatus.as_bytes());
+
+        if index == 0 {
+            push_note(&mut notes, NT_PRPSINFO, process.psinfo.as_bytes());
+            push_note(&mut notes, NT_SIGINFO, process.siginfo.as_bytes());
+            push_note(&mut notes, NT_AUXV, &process.auxv);
+            push_note(&mut notes, NT_FILE, &build_file_note(&process.files));
+        }
+
+        if let Some(fpregset) = thread.fpregset.as_ref() {
+            push_note(&mut notes, NT_PRFPREG, fpregset);
+        }
+    }
+
+    notes
+}
+
+/// Builds the content of an `NT_FILE` note.
+///
+/// The note starts with the number of file mappings and the page size, followed by the
+/// `(start, end, offset in pages)` triple of each file mapping, and then the paths.
+fn build_file_note(files: &[FileMapping]) -> Vec<u8> {
+    let mut note = Vec::new();
+
+    note.extend_from_slice(&(files.len() as u64).to_le_bytes());
+    note.extend_from_slice(&(PAGE_SIZE as u64).to_le_bytes());
+    for file in files.iter() {
+        note.extend_from_slice(&(file.range.start as u64).to_le_bytes());
+        note.extend_from_slice(&(file.range.end as u64).to_le_bytes());
+        note.extend_from_slice(&((file.offset / PAGE_SIZE) as u64).to_le_bytes());
+    }
+    for file in files.iter() {
+        note.extend_from_slice(file.path.as_bytes());
+        note.push(0);
+    }
+
+    note
+}
+
+fn push_note(notes: &mut Vec<u8>, n_type: u32, desc: &[u8]) {
+    let nhdr = Elf64Nhdr {
+        n_namesz: NOTE_NAME.len() as u32,
+        n_descsz: desc.len() as u32,
+        n_type,
+    };
+    notes.extend_from_slice(nhdr.as_bytes());
+    notes.extend_from_slice(NOTE_NAME);
+    notes.resize(notes.len().align_up(4), 0);
+    notes.extend_from_slice(desc);
+    notes.resize(notes.len().align_up(4), 0);
+}
+
+fn segment_flags(perms: VmPerms) -> u32 {
+    let mut flags = 0;
+    if perms.contains(VmPerms::READ) {
+        flags |= PF_R;
+    }
+    if perms.contains(VmPerms::WRITE) {
+        flags |= PF_W;
+    }
+    if perms.contains(VmPerms::EXEC) {
+        flags |= PF_X;
+    }
+    flags
+}
+
+const ELF_IDENT: [u8; 16] = [
+    0x7f,
+    b'E',
+    b'L',
+    b'F',
+    ELFCLASS64,
+    ELFDATA2LSB,
+    EV_CURRENT,
+    ELFOSABI_NONE,
+    0,
+    0,
+    0,
+    0,
+    0,
+    0,
+    0,
+    0,
+];
+const ELFCLASS64: u8 = 2;
+const ELFDATA2LSB: u8 = 1;
+const EV_CURRENT: u8 = 1;
+const ELFOSABI_NONE: u8 = 0;
+
+const ET_CORE: u16 = 4;
+const PN_XNUM: u16 = 0xffff;
+
+const PT_LOAD: u32 = 1;
+const PT_NOTE: u32 = 4;
+
+const PF_X: u32 = 1;
+const PF_W: u32 = 2;
+const PF_R: u32 = 4;
+
+const NOTE_NAME: &[u8] = b"CORE\0";
+
+const NT_PRSTATUS: u32 = 1;
+const NT_PRFPREG: u32 = 2;
+const NT_PRPSINFO: u32 = 3;
+const NT_AUXV: u32 = 6;
+const NT_SIGINFO: u32 = 0x53494749;
+const NT_FILE: u32 = 0x46494c45;
+
+/// The ELF header.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/elf.h>
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct Elf64Ehdr {
+    e_ident: [u8; 16],
+    e_type: u16,
+    e_machine: u16,
+    e_version: u32,
+    e_entry: u64,
+    e_phoff: u64,
+    e_shoff: u64,
+    e_flags: u32,
+    e_ehsize: u16,
+    e_phentsize: u16,
+    e_phnum: u16,
+    e_shentsize: u16,
+    e_shnum: u16,
+    e_shstrndx: u16,
+}
+
+/// The program header.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct Elf64Phdr {
+    p_type: u32,
+    p_flags: u32,
+    p_offset: u64,
+    p_vaddr: u64,
+    p_paddr: u64,
+    p_filesz: u64,
+    p_memsz: u64,
+    p_align: u64,
+}
+
+/// The note header.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct Elf64Nhdr {
+    n_namesz: u32,
+    n_descsz: u32,
+    n_type: u32,
+}
+
+/// The content of an `NT_PRSTATUS` note.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/linux/elfcore.h>
+#[padding_struct]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct ElfPrStatus {
+    pr_info: ElfSigInfo,
+    pr_cursig: i16,
+    pr_sigpend: u64,
+    pr_sighold: u64,
+    pr_pid: i32,
+    pr_ppid: i32,
+    pr_pgrp: i32,
+    pr_sid: i32,
+    pr_utime: timeval_t,
+    pr_stime: timeval_t,
+    pr_cutime: timeval_t,
+    pr_cstime: timeval_t,
+    pr_reg: ElfGregSet,
+    pr_fpvalid: i32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct ElfSigInfo {
+    si_signo: i32,
+    si_code: i32,
+    si_errno: i32,
+}
+
+/// The content of an `NT_PRPSINFO` note.
+#[padding_struct]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub(super) struct ElfPrPsInfo {
+    pub(super) pr_state: i8,
+    pub(super) pr_sname: u8,
+    pub(super) pr_zomb: i8,
+    pub(super) pr_nice: i8,
+    pub(super) pr_flag: u64,
+    pub(super) pr_uid: u32,
+    pub(super) pr_gid: u32,
+    pub(super) pr_pid: i32,
+    pub(super) pr_ppid: i32,
+    pub(super) pr_pgrp: i32,
+    pub(super) pr_sid: i32,
+    pub(super) pr_fname: [u8; 16],
+    pub(super) pr_psargs: [u8; ELF_PRARGSZ],
+}
+
+/// The maximum length of the arguments in an `NT_PRPSINFO` note, including the trailing nul byte.
+pub(super) const ELF_PRARGSZ: usize = 80;
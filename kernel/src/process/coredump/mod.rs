@@ -0,0 +1,372 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Core dumps.
+//!
+//! When a process is terminated by a signal whose default action is to dump core, the thread
+//! that receives the signal (the dumper) kills the other threads in the process and waits for
+//! them to report their states. Then, it writes an ELF core file that captures the states of all
+//! threads and the memory mappings of the process.
+//!
+//! Reference: <https://man7.org/linux/man-pages/man5/core.5.html>
+
+mod elf;
+mod output;
+mod pattern;
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use ostd::{arch::cpu::context::UserContext, sync::WaitQueue};
+
+pub use self::pattern::{CORENAME_MAX_SIZE, core_pattern, set_core_pattern};
+use self::{
+    elf::{
+        ELF_PRARGSZ, ElfPrPsInfo, FileMapping, ProcessCoreInfo, Segment, ThreadCoreInfo,
+        write_elf_core,
+    },
+    output::{CoreWriter, create_core_file, create_core_pipe},
+    pattern::{CoreTarget, PatternArgs, expand_core_pattern},
+};
+use crate::{
+    fs::vfs::path::PathResolver,
+    prelude::*,
+    process::{
+        Dumpable, ResourceType, TermStatus,
+        posix_thread::{ContextPthreadAdminApi, sigkill_other_threads},
+        process_vm::InitStackReader,
+        signal::{c_types::siginfo_t, sig_mask::SigMask, sig_num::SigNum},
+    },
+    thread::AsThread,
+    time::clocks::RealTimeClock,
+    vm::{
+        perms::VmPerms,
+        vmar::{VMAR_CAP_ADDR, VMAR_LOWEST_ADDR, VmMapping, Vmar},
+    },
+};
+
+/// The state of an ongoing core dump.
+///
+/// While a core dump is ongoing, the state is installed in the [`TaskSet`] of the process, so
+/// that the other threads can report to the dumper before they exit.
+///
+/// [`TaskSet`]: crate::process::task_set::TaskSet
+pub(in crate::process) struct CoreState {
+    sig_num: SigNum,
+    num_other_threads: usize,
+    other_threads: Mutex<Vec<ThreadCoreInfo>>,
+    is_dumped: AtomicBool,
+    wait_queue: WaitQueue,
+}
+
+impl CoreState {
+    fn new(sig_num: SigNum, num_other_threads: usize) -> Self {
+        Self {
+            sig_num,
+            num_other_threads,
+            other_threads: Mutex::new(Vec::with_capacity(num_other_threads)),
+            is_dumped: AtomicBool::new(false),
+            wait_queue: WaitQueue::new(),
+        }
+    }
+
+    /// Reports the state of the current thread to the dumper, and waits until the core dump is
+    /// complete.
+    ///
+    /// This method should be called by a thread other than the dumper before it exits.
+    pub(in crate::process) fn report_and_wait(&self, ctx: &Context, user_ctx: &UserContext) {
+        let thread_info = ThreadCoreInfo::new(self.sig_num, ctx, user_ctx);
+        self.other_threads.lock().push(thread_info);
+        self.wait_queue.wake_all();
+
+        self.wait_queue
+            .wait_until(|| self.is_dumped.load(Ordering::Acquire).then_some(()));
+    }
+
+    fn wait_for_other_threads(&self) -> Vec<ThreadCoreInfo> {
+        self.wait_queue.wait_until(|| {
+            let mut other_threads = self.other_threads.lock();
+            (other_threads.len() == self.num_other_threads)
+                .then(|| core::mem::take(&mut *other_threads))
+        })
+    }
+
+    fn set_dumped(&self) {
+        self.is_dumped.store(true, Ordering::Release);
+        self.wait_queue.wake_all();
+    }
+}
+
+/// Dumps the core of the current process, which is being terminated by the signal.
+///
+/// This method kills the other threads in the process. It returns the termination status of the
+/// process, which tells whether the core has been dumped. The caller should then exit the
+/// process with the status.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.18/source/fs/coredump.c>
+pub(in crate::process) fn do_coredump(
+    sig_num: SigNum,
+    siginfo: siginfo_t,
+    ctx: &Context,
+    user_ctx: &UserContext,
+) -> TermStatus {
+    let killed = TermStatus::Killed(sig_num);
+
+    let dumpable = ctx.user_space().vmar().process_vm().dumpable();
+    if dumpable == Dumpable::Disable {
+        return killed;
+    }
+
+    let core_state = {
+        let mut tasks = ctx.process.tasks().lock();
+        if tasks.has_exited_group() || tasks.in_execve() {
+            // Another thread is exiting the process or executing a new program.
+            return killed;
+        }
+
+        let num_other_threads = tasks
+            .as_slice()
+            .iter()
+            .filter(|task| {
+                !core::ptr::eq(task.as_ref(), ctx.task) && !task.as_thread().unwrap().is_exited()
+            })
+            .count();
+        let core_state = Arc::new(CoreState::new(sig_num, num_other_threads));
+
+        tasks.set_core_state(core_state.clone());
+        sigkill_other_threads(ctx.task, &tasks);
+        tasks.set_exited_group();
+
+        core_state
+    };
+
+    let mut threads = vec![ThreadCoreInfo::new(sig_num, ctx, user_ctx)];
+    // Other signals should not interrupt the core dump.
+    ctx.set_sig_mask(SigMask::new_full());
+    threads.extend(core_state.wait_for_other_threads());
+
+    let term_status = match dump_core(sig_num, siginfo, dumpable, &threads, ctx) {
+        Ok(()) => TermStatus::CoreDumped(sig_num),
+        Err(err) => {
+            debug!("failed to dump the core: {:?}", err);
+            killed
+        }
+    };
+
+    let mut tasks = ctx.process.tasks().lock();
+    tasks.clear_core_state();
+    core_state.set_dumped();
+    // The process exit code is not set when the process exits, because the exit group has been
+    // initiated above.
+    ctx.process.status().set_exit_code(term_status.as_u32());
+
+    term_status
+}
+
+fn dump_core(
+    sig_num: SigNum,
+    siginfo: siginfo_t,
+    dumpable: Dumpable,
+    threads: &[ThreadCoreInfo],
+    ctx: &Context,
+) -> Result<()> {
+    let Context {
+        process,
+        posix_thread,
+        thread_local,
+        ..
+    } = ctx;
+
+    // The VMAR lock is only held while collecting the information. Otherwise, a core dump helper
+    // that reads the files in procfs (e.g., `/proc/[pid]/maps`) would deadlock with the dumper.
+    let (exe_path, process_info) = {
+        let vmar_guard = process.lock_vmar();
+        let Some(vmar) = vmar_guard.as_ref() else {
+            return_errno_with_message!(Errno::ESRCH, "the process has exited");
+        };
+        let init_stack_reader = vmar_guard.init_stack_reader().unwrap();
+
+        let fs_ref = thread_local.borrow_fs();
+        let path_resolver = fs_ref.resolver().read();
+        let exe_path = path_resolver
+            .make_abs_path(vmar.process_vm().executable_file())
+            .into_string();
+        let process_info =
+            collect_process_info(siginfo, ctx, vmar, &init_stack_reader, &path_resolver);
+
+        (exe_path, process_info)
+    };
+
+    let core_limit = process
+        .resource_limits()
+        .get_rlimit(ResourceType::RLIMIT_CORE)
+        .get_cur();
+    let credentials = posix_thread.credentials();
+    let pid_ns = process.pid_ns();
+
+    let target = {
+        let ns_proxy = thread_local.borrow_ns_proxy();
+        let hostname = ns_proxy
+            .unwrap()
+            .uts_ns()
+            .uts_name()
+            .nodename()
+            .to_string_lossy()
+            .into_owned();
+        let comm = posix_thread
+            .thread_name()
+            .lock()
+            .name()
+            .to_string_lossy()
+            .into_owned();
+        let args = PatternArgs {
+            local_pid: pid_ns.local_id(process.pid()),
+            global_pid: process.pid(),
+            local_tid: pid_ns.local_id(posix_thread.tid()),
+            global_tid: posix_thread.tid(),
+            uid: credentials.ruid(),
+            gid: credentials.rgid(),
+            dumpable,
+            sig_num,
+            timestamp: RealTimeClock::get().read_time().as_secs(),
+            hostname: &hostname,
+            comm: &comm,
+            exe_path: &exe_path,
+            core_limit,
+        };
+        expand_core_pattern(&args)
+    };
+
+    let mut writer = match target {
+        None => return_errno_with_message!(Errno::EINVAL, "the core pattern is empty"),
+        Some(CoreTarget::File(path_name)) => {
+            if core_limit < PAGE_SIZE as u64 {
+                return_errno_with_message!(Errno::EFBIG, "the core file size limit is too small");
+            }
+            CoreWriter::new(create_core_file(&path_name, dumpable, ctx)?, core_limit)
+        }
+        Some(CoreTarget::Pipe(argv)) => {
+            // A limit of one is set for the helper itself to avoid recursive core dumps.
+            if core_limit == 1 {
+                return_errno_with_message!(
+                    Errno::EPERM,
+                    "the core dump helper cannot dump its own core"
+                );
+            }
+            CoreWriter::new(create_core_pipe(argv)?, u64::MAX)
+        }
+    };
+
+    // The other threads are waiting for the core dump to complete, so the mappings collected above
+    // cannot be changed by them while the pages are dumped.
+    write_elf_core(&mut writer, &process_info, threads, ctx.user_space().vmar())
+}
+
+fn collect_process_info(
+    siginfo: siginfo_t,
+    ctx: &Context,
+    vmar: &Vmar,
+    init_stack_reader: &InitStackReader,
+    path_resolver: &PathResolver,
+) -> ProcessCoreInfo {
+    let Context {
+        process,
+        posix_thread,
+        ..
+    } = ctx;
+    let pid_ns = process.pid_ns();
+    let credentials = posix_thread.credentials();
+
+    let mut psinfo = ElfPrPsInfo::new_zeroed();
+    psinfo.pr_sname = b'R';
+    psinfo.pr_nice = process.nice().load(Ordering::Relaxed).into();
+    psinfo.pr_uid = credentials.ruid().into();
+    psinfo.pr_gid = credentials.rgid().into();
+    psinfo.pr_pid = pid_ns.local_id(process.pid()) as i32;
+    psinfo.pr_ppid = pid_ns.local_id(process.parent().pid()) as i32;
+    psinfo.pr_pgrp = pid_ns.local_id(process.pgid()) as i32;
+    psinfo.pr_sid = pid_ns.local_id(process.sid()) as i32;
+    {
+        let thread_name = posix_thread.thread_name().lock();
+        let name = thread_name.name().to_bytes();
+        let len = name.len().min(psinfo.pr_fname.len() - 1);
+        psinfo.pr_fname[..len].copy_from_slice(&name[..len]);
+    }
+    {
+        let mut writer = VmWriter::from(&mut psinfo.pr_psargs[..ELF_PRARGSZ - 1]).to_fallible();
+        let len = init_stack_reader.argv(0, &mut writer).unwrap_or(0);
+        // The arguments are separated by spaces instead of nul bytes.
+        for byte in psinfo.pr_psargs[..len].iter_mut() {
+            if *byte == 0 {
+                *byte = b' ';
+            }
+        }
+    }
+
+    let auxv = {
+        let mut auxv = vec![0u8; PAGE_SIZE];
+        let mut writer = VmWriter::from(auxv.as_mut_slice()).to_fallible();
+        let len = init_stack_reader.auxv(0, &mut writer).unwrap_or(0);
+        auxv.truncate(len);
+        auxv
+    };
+
+    let mut files = Vec::new();
+    let mut segments = Vec::new();
+    let query_guard = vmar.query(VMAR_LOWEST_ADDR..VMAR_CAP_ADDR);
+    for vm_mapping in query_guard.iter() {
+        let range = vm_mapping.map_to_addr()..vm_mapping.map_end();
+
+        if let Some(path) = vm_mapping.path() {
+            files.push(FileMapping {
+                range: range.clone(),
+                offset: vm_mapping.backing_vmo().map_or(0, |(_, offset)| offset),
+                path: path_resolver.make_abs_path(path).into_string(),
+            });
+        }
+
+        segments.push(Segment {
+            range,
+            perms: vm_mapping.perms(),
+            dump_size: dump_size(vm_mapping),
+        });
+    }
+
+    ProcessCoreInfo {
+        psinfo,
+        siginfo,
+        auxv,
+        files,
+        segments,
+    }
+}
+
+/// Returns the size of the leading part of the mapping whose content should be dumped.
+///
+/// Like Linux with the default `coredump_filter`, anonymous memory and private file-backed memory
+/// that may have been modified are dumped. For other file-backed memory, only the first page is
+/// dumped if it may contain an ELF header, which helps debuggers to identify the file.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.18/source/fs/coredump.c>
+fn dump_size(vm_mapping: &VmMapping) -> usize {
+    if vm_mapping.is_dont_dump() || vm_mapping.is_device() {
+        return 0;
+    }
+
+    let is_anonymous = vm_mapping.path().is_none();
+    if is_anonymous {
+        return vm_mapping.map_size();
+    }
+
+    let perms = vm_mapping.perms();
+    if !vm_mapping.is_shared() && perms.contains(VmPerms::MAY_WRITE) {
+        return vm_mapping.map_size();
+    }
+
+    let is_file_start = vm_mapping
+        .backing_vmo()
+        .is_some_and(|(_, offset)| offset == 0);
+    if is_file_start && perms.contains(VmPerms::READ) {
+        return PAGE_SIZE;
+    }
+
+    0
+}
@@ -0,0 +1,163 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::{
+    fs::{
+        self,
+        file::{
+            AccessMode, CreationFlags, FileLike, InodeHandle, InodeMode, InodeType, OpenArgs,
+            StatusFlags,
+        },
+        pipe::new_file_pair,
+        vfs::path::{FsPath, LookupResult},
+    },
+    prelude::*,
+    process::{
+        Dumpable, process::spawn_user_mode_helper, rlimit::new_resource_limits_for_core_dump_helper,
+    },
+};
+
+/// A writer that writes a core dump to a file, subject to a size limit.
+pub(super) struct CoreWriter {
+    file: Arc<dyn FileLike>,
+    limit: u64,
+    written: u64,
+}
+
+impl CoreWriter {
+    pub(super) fn new(file: Arc<dyn FileLike>, limit: u64) -> Self {
+        Self {
+            file,
+            limit,
+            written: 0,
+        }
+    }
+
+    /// Writes all the bytes.
+    ///
+    /// Like Linux, the core dump is aborted if it would exceed the size limit, instead of being
+    /// truncated.
+    pub(super) fn write(&mut self, mut bytes: &[u8]) -> Result<()> {
+        if self.written + bytes.len() as u64 > self.limit {
+            return_errno_with_message!(Errno::EFBIG, "the core dump exceeds the size limit");
+        }
+
+        while !bytes.is_empty() {
+            let mut reader = VmReader::from(bytes).to_fallible();
+            let len = self.file.write(&mut reader)?;
+            if len == 0 {
+                return_errno_with_message!(Errno::EIO, "the core dump cannot be written");
+            }
+            bytes = &bytes[len..];
+            self.written += len as u64;
+        }
+
+        Ok(())
+    }
+
+    /// Writes `len` zero bytes.
+    pub(super) fn write_zeros(&mut self, len: usize) -> Result<()> {
+        const ZEROS: [u8; 256] = [0; 256];
+
+        let mut remain = len;
+        while remain > 0 {
+            let len = remain.min(ZEROS.len());
+            self.write(&ZEROS[..len])?;
+            remain -= len;
+        }
+
+        Ok(())
+    }
+}
+
+/// Creates the core file at the path.
+///
+/// Like Linux, symbolic links are not followed. An existing file at the path is truncated and
+/// reused only if it is a regular file owned by the dumper and has no other hard links. If the
+/// process is dumped as root, an existing file is never reused. A new file is only accessible to
+/// its owner.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.18/source/fs/coredump.c>
+pub(super) fn create_core_file(
+    path_name: &str,
+    dumpable: Dumpable,
+    ctx: &Context,
+) -> Result<Arc<dyn FileLike>> {
+    let mut creation_flags = CreationFlags::O_CREAT | CreationFlags::O_NOFOLLOW;
+    if dumpable == Dumpable::Root {
+        creation_flags |= CreationFlags::O_EXCL;
+    }
+
+    let fs_ref = ctx.thread_local.borrow_fs();
+    let open_args = OpenArgs {
+        creation_flags,
+        status_flags: StatusFlags::empty(),
+        access_mode: AccessMode::O_WRONLY,
+        inode_mode: InodeMode::from_bits_truncate(0o600 & !fs_ref.umask().get()),
+    };
+
+    let lookup_res = fs_ref
+        .resolver()
+        .read()
+        .lookup_unresolved_no_follow(&FsPath::try_from(path_name)?)?;
+    let file = match lookup_res {
+        LookupResult::Resolved(path) => path.open(open_args)?,
+        LookupResult::AtParent(result) => {
+            if result.target_is_dir() {
+                return_errno_with_message!(Errno::EISDIR, "the core file is a directory");
+            }
+
+            let (dir_path, file_name) = result.into_parent_and_basename();
+            let file_path =
+                dir_path.new_fs_child(&file_name, InodeType::File, open_args.inode_mode)?;
+            fs::vfs::notify::on_create(&dir_path, || file_name.clone());
+
+            InodeHandle::new_unchecked_access(
+                file_path,
+                open_args.access_mode,
+                open_args.status_flags,
+            )?
+        }
+    };
+
+    // The owner or the mode of the file may have been changed by others, even if the file is
+    // newly created.
+    let metadata = file.path().metadata();
+    let fsuid = ctx.posix_thread.credentials().fsuid();
+    if metadata.type_ != InodeType::File
+        || metadata.nr_hard_links != 1
+        || metadata.uid != fsuid
+        || metadata.mode.bits() & 0o677 != 0o600
+    {
+        return_errno_with_message!(Errno::EPERM, "the core file cannot be written safely");
+    }
+    file.path().resize(0)?;
+
+    Ok(Arc::new(file))
+}
+
+/// Spawns a user mode helper and returns a pipe connected to its standard input.
+///
+/// The first argument in `argv` is the path of the helper.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.18/source/fs/coredump.c>
+pub(super) fn create_core_pipe(argv: Vec<CString>) -> Result<Arc<dyn FileLike>> {
+    let executable_path = argv[0]
+        .to_str()
+        .map_err(|_| Error::with_message(Errno::EINVAL, "the helper path is not valid UTF-8"))?
+        .to_string();
+    let envp = vec![
+        CString::new("HOME=/").unwrap(),
+        CString::new("PATH=/sbin:/bin:/usr/sbin:/usr/bin").unwrap(),
+    ];
+
+    let (reader, writer) = new_file_pair(StatusFlags::empty())?;
+    spawn_user_mode_helper(
+        &executable_path,
+        argv,
+        envp,
+        new_resource_limits_for_core_dump_helper(),
+        Some(reader),
+    )?;
+
+    Ok(writer)
+}
@@ -0,0 +1,172 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! The core pattern, which decides where core dumps are written.
+//!
+//! The pattern is exposed as `/proc/sys/kernel/core_pattern`. It is either a file name template
+//! (e.g., `core.%p`), or a pipe to a user mode helper if it starts with `|` (e.g.,
+//! `|/usr/bin/handler %p %s`).
+//!
+//! Reference: <https://man7.org/linux/man-pages/man5/core.5.html>
+
+use alloc::borrow::Cow;
+use core::fmt::Write;
+
+use crate::{
+    prelude::*,
+    process::{Dumpable, Gid, Pid, Uid, signal::sig_num::SigNum},
+};
+
+/// The maximum length of the core pattern, including the trailing nul byte.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/linux/binfmts.h>
+pub const CORENAME_MAX_SIZE: usize = 128;
+
+static CORE_PATTERN: Mutex<Cow<'static, str>> = Mutex::new(Cow::Borrowed("core"));
+
+/// Returns the core pattern.
+pub fn core_pattern() -> String {
+    CORE_PATTERN.lock().to_string()
+}
+
+/// Sets the core pattern.
+///
+/// Like Linux, the pattern is cut at the first newline character, and is truncated if it does not
+/// fit into [`CORENAME_MAX_SIZE`] bytes.
+pub fn set_core_pattern(pattern: &str) {
+    let mut pattern = pattern.split('\n').next().unwrap();
+    if pattern.len() >= CORENAME_MAX_SIZE {
+        let mut len = CORENAME_MAX_SIZE - 1;
+        while !pattern.is_char_boundary(len) {
+            len -= 1;
+        }
+        pattern = &pattern[..len];
+    }
+
+    *CORE_PATTERN.lock() = Cow::Owned(pattern.to_string());
+}
+
+/// The values that the `%` specifiers in the core pattern expand to.
+pub(super) struct PatternArgs<'a> {
+    /// `%p`: The PID of the dumped process in its PID namespace.
+    pub(super) local_pid: Pid,
+    /// `%P`: The PID of the dumped process in the initial PID namespace.
+    pub(super) global_pid: Pid,
+    /// `%i`: The TID of the dumping thread in its PID namespace.
+    pub(super) local_tid: Pid,
+    /// `%I`: The TID of the dumping thread in the initial PID namespace.
+    pub(super) global_tid: Pid,
+    /// `%u`: The real UID of the dumped process.
+    pub(super) uid: Uid,
+    /// `%g`: The real GID of the dumped process.
+    pub(super) gid: Gid,
+    /// `%d`: The dumpable attribute of the dumped process.
+    pub(super) dumpable: Dumpable,
+    /// `%s`: The number of the signal causing the dump.
+    pub(super) sig_num: SigNum,
+    /// `%t`: The time of the dump, expressed as seconds since the Epoch.
+    pub(super) timestamp: u64,
+    /// `%h`: The host name.
+    pub(super) hostname: &'a str,
+    /// `%e`: The name of the dumping thread.
+    pub(super) comm: &'a str,
+    /// `%E`: The path of the executable file.
+    pub(super) exe_path: &'a str,
+    /// `%c`: The soft limit of the core file size.
+    pub(super) core_limit: u64,
+}
+
+/// The target that a core dump is written to.
+pub(super) enum CoreTarget {
+    /// A file at the path.
+    File(String),
+    /// A pipe to a user mode helper spawned with the arguments.
+    ///
+    /// The first argument is the path of the helper.
+    Pipe(Vec<CString>),
+}
+
+/// Expands the core pattern with the arguments.
+///
+/// This method returns `None` if the expanded pattern is empty.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.18/source/fs/coredump.c>
+pub(super) fn expand_core_pattern(args: &PatternArgs) -> Option<CoreTarget> {
+    let pattern = core_pattern();
+    let (is_pipe, template) = match pattern.strip_prefix('|') {
+        Some(template) => (true, template.trim_start()),
+        None => (false, pattern.as_str()),
+    };
+
+    let mut argv = vec![String::new()];
+    let mut was_space = false;
+    let mut chars = template.chars();
+
+    while let Some(ch) = chars.next() {
+        // For a pipe, only the spaces in the pattern itself (not those in the expanded values)
+        // separate the arguments.
+        if is_pipe {
+            if ch.is_ascii_whitespace() {
+                was_space = true;
+                continue;
+            }
+            if was_space {
+                was_space = false;
+                argv.push(String::new());
+            }
+        }
+
+        let arg = argv.last_mut().unwrap();
+        if ch != '%' {
+            arg.push(ch);
+            continue;
+        }
+
+        // Writing to a `String` never fails.
+        let _ = match chars.next() {
+            Some('%') => write!(arg, "%"),
+            Some('p') => write!(arg, "{}", args.local_pid),
+            Some('P') => write!(arg, "{}", args.global_pid),
+            Some('i') => write!(arg, "{}", args.local_tid),
+            Some('I') => write!(arg, "{}", args.global_tid),
+            Some('u') => write!(arg, "{}", u32::from(args.uid)),
+            Some('g') => write!(arg, "{}", u32::from(args.gid)),
+            Some('d') => write!(arg, "{}", args.dumpable as u8),
+            Some('s') => write!(arg, "{}", args.sig_num.as_u8()),
+            Some('t') => write!(arg, "{}", args.timestamp),
+            Some('h') => write_escaped(arg, args.hostname),
+            Some('e') => write_escaped(arg, args.comm),
+            Some('E') => write_escaped(arg, args.exe_path),
+            Some('c') => write!(arg, "{}", args.core_limit),
+            // Unknown specifiers and a trailing `%` are dropped.
+            Some(_) | None => Ok(()),
+        };
+    }
+
+    if argv[0].is_empty() {
+        return None;
+    }
+
+    if !is_pipe {
+        return Some(CoreTarget::File(argv.pop().unwrap()));
+    }
+
+    let argv = argv
+        .into_iter()
+        .map(|arg| CString::new(arg.replace('\0', "")).unwrap())
+        .collect();
+    Some(CoreTarget::Pipe(argv))
+}
+
+/// Writes the value with `/` replaced by `!`, so that it cannot introduce directories.
+fn write_escaped(arg: &mut String, value: &str) -> core::fmt::Result {
+    let start = arg.len();
+    arg.extend(value.chars().map(|ch| if ch == '/' { '!' } else { ch }));
+
+    // The value cannot be a special directory entry either.
+    let escaped = &arg[start..];
+    if escaped == "." || escaped == ".." {
+        arg.replace_range(start..start + 1, "!");
+    }
+
+    Ok(())
+}
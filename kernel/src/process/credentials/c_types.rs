@@ -24,3 +24,29 @@ pub struct CUserCapData {
 }
 
 pub const LINUX_CAPABILITY_VERSION_3: u32 = 0x20080522;
+
+/// `struct vfs_ns_cap_data` in Linux.
+///
+/// This is the value of the `security.capability` extended attribute in revision 3. The values
+/// in earlier revisions are prefixes of it.
+///
+/// All the fields are stored in little-endian.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18.6/source/include/uapi/linux/capability.h>.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub struct CVfsNsCapData {
+    pub magic_etc: u32,
+    pub data: [CVfsCapDataItem; 2],
+    pub rootid: u32,
+}
+
+/// The `data` field in `struct vfs_ns_cap_data` in Linux.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18.6/source/include/uapi/linux/capability.h>.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub struct CVfsCapDataItem {
+    pub permitted: u32,
+    pub inheritable: u32,
+}
@@ -5,15 +5,13 @@ use core::sync::atomic::Ordering;
 use ostd::sync::{PreemptDisabled, RwLockReadGuard, RwLockWriteGuard};
 
 use super::{
-    Gid, SecureBits, Uid, group::AtomicGid, secure_bits::AtomicSecureBits, user::AtomicUid,
-};
-use crate::{
-    prelude::*,
-    process::credentials::{
-        AMBIENT_CAPSET,
-        capabilities::{AtomicCapSet, CapSet},
-    },
+    FileCaps, Gid, SecureBits, Uid,
+    capabilities::{AtomicCapSet, CapSet},
+    group::AtomicGid,
+    secure_bits::AtomicSecureBits,
+    user::AtomicUid,
 };
+use crate::prelude::*;
 
 #[derive(Debug)]
 pub(super) struct Credentials_ {
@@ -72,6 +70,11 @@ pub(super) struct Credentials_ {
     /// Capabilities that limit privileges granted during `execve()` and may be added to the
     /// inheritable set.
     bounding_capset: AtomicCapSet,
+    /// Capabilities that are preserved across an `execve()` of a program that is not privileged.
+    ///
+    /// No capability can be in the ambient set if it is not in both the permitted set and the
+    /// inheritable set.
+    ambient_capset: AtomicCapSet,
 
     /// Secure bits.
     securebits: AtomicSecureBits,
@@ -99,6 +102,7 @@ impl Credentials_ {
             permitted_capset: AtomicCapSet::new(capset),
             effective_capset: AtomicCapSet::new(capset),
             bounding_capset: AtomicCapSet::new(CapSet::all()),
+            ambient_capset: AtomicCapSet::new(CapSet::empty()),
             securebits: AtomicSecureBits::new(SecureBits::new_empty()),
         }
     }
@@ -185,34 +189,6 @@ impl Credentials_ {
 
     pub(super) fn set_suid(&self, suid: Uid) {
         self.set_resuid_unchecked(None, None, Some(suid));
-
-        // Begin to adjust capabilities.
-        // Reference: The "Transformation of capabilities during execve()" section and
-        // the "Capabilities and execution of programs by root" section in
-        // <https://man7.org/linux/man-pages/man7/capabilities.7.html>.
-
-        let (file_permitted, file_inheritable) =
-            if (self.euid().is_root() || self.ruid().is_root()) && !self.securebits().no_root() {
-                (CapSet::all(), CapSet::all())
-            } else {
-                // TODO: Get the file capabilities from the file system.
-                (CapSet::empty(), CapSet::empty())
-            };
-
-        let file_effective = if self.euid().is_root() && !self.securebits().no_root() {
-            CapSet::all()
-        } else {
-            // TODO: Get the file capabilities from the file system.
-            CapSet::empty()
-        };
-
-        let new_permitted = (self.inheritable_capset() & file_inheritable)
-            | (file_permitted & self.bounding_capset())
-            | AMBIENT_CAPSET;
-        let new_effective = (file_effective & new_permitted) | (!file_effective & AMBIENT_CAPSET);
-
-        self.set_permitted_capset(new_permitted);
-        self.set_effective_capset(new_effective);
     }
 
     // For `setreuid`, the real UID can *NOT* be set to the old saved-set user ID,
@@ -308,8 +284,10 @@ impl Credentials_ {
         if had_root && all_nonroot && !self.keep_capabilities() {
             self.set_permitted_capset(CapSet::empty());
             self.set_inheritable_capset(CapSet::empty());
-            // TODO: Clear ambient capabilities when we support it. Note that ambient capabilities
-            // should be cleared even if `keep_capabilities` is true.
+        }
+        if had_root && all_nonroot {
+            // Ambient capabilities are cleared even if `keep_capabilities` is true.
+            self.clear_ambient_capset();
         }
 
         if old_euid.is_root() && !new_euid.is_root() {
@@ -525,14 +503,20 @@ impl Credentials_ {
         self.bounding_capset.load(Ordering::Relaxed)
     }
 
+    pub(super) fn ambient_capset(&self) -> CapSet {
+        self.ambient_capset.load(Ordering::Relaxed)
+    }
+
     pub(super) fn set_inheritable_capset(&self, inheritable_capset: CapSet) {
         self.inheritable_capset
             .store(inheritable_capset, Ordering::Relaxed);
+        self.drop_ambient_capset_not_allowed();
     }
 
     pub(super) fn set_permitted_capset(&self, permitted_capset: CapSet) {
         self.permitted_capset
             .store(permitted_capset, Ordering::Relaxed);
+        self.drop_ambient_capset_not_allowed();
     }
 
     pub(super) fn set_effective_capset(&self, effective_capset: CapSet) {
@@ -545,6 +529,99 @@ impl Credentials_ {
             .store(bounding_capset, Ordering::Relaxed);
     }
 
+    pub(super) fn raise_ambient_capability(&self, capability: CapSet) -> Result<()> {
+        if !(self.permitted_capset() & self.inheritable_capset()).contains(capability) {
+            return_errno_with_message!(
+                Errno::EPERM,
+                "only capabilities in both the permitted and inheritable sets can be ambient"
+            );
+        }
+        if self.securebits().no_cap_ambient_raise() {
+            return_errno_with_message!(
+                Errno::EPERM,
+                "raising ambient capabilities is disallowed by the secure bits"
+            );
+        }
+
+        let new_ambient_capset = self.ambient_capset() | capability;
+        self.ambient_capset
+            .store(new_ambient_capset, Ordering::Relaxed);
+        Ok(())
+    }
+
+    pub(super) fn lower_ambient_capability(&self, capability: CapSet) {
+        let new_ambient_capset = self.ambient_capset() - capability;
+        self.ambient_capset
+            .store(new_ambient_capset, Ordering::Relaxed);
+    }
+
+    pub(super) fn clear_ambient_capset(&self) {
+        self.ambient_capset
+            .store(CapSet::empty(), Ordering::Relaxed);
+    }
+
+    /// Drops the ambient capabilities that are not in both the permitted and inheritable sets.
+    fn drop_ambient_capset_not_allowed(&self) {
+        let allowed_capset = self.permitted_capset() & self.inheritable_capset();
+        let new_ambient_capset = self.ambient_capset() & allowed_capset;
+        self.ambient_capset
+            .store(new_ambient_capset, Ordering::Relaxed);
+    }
+
+    pub(super) fn apply_exec_caps(&self, file_caps: Option<&FileCaps>, no_new_privs: bool) {
+        // Reference: The "Transformation of capabilities during execve()" section and
+        // the "Capabilities and execution of programs by root" section in
+        // <https://man7.org/linux/man-pages/man7/capabilities.7.html>, and
+        // <https://elixir.bootlin.com/linux/v6.18.6/source/security/commoncap.c>.
+
+        let old_permitted = self.permitted_capset();
+        let is_setid = self.euid() != self.ruid() || self.egid() != self.rgid();
+
+        let (mut new_permitted, mut is_file_effective) = match file_caps {
+            Some(file_caps) => (
+                file_caps.new_permitted(self.bounding_capset(), self.inheritable_capset()),
+                file_caps.is_effective(),
+            ),
+            None => (CapSet::empty(), false),
+        };
+
+        // A set-user-ID-root program with file capabilities does not get the privileges of the
+        // root user if the real UID is not root.
+        let is_suid_root_with_file_caps =
+            file_caps.is_some() && self.euid().is_root() && !self.ruid().is_root();
+        if !self.securebits().no_root() && !is_suid_root_with_file_caps {
+            if self.euid().is_root() || self.ruid().is_root() {
+                new_permitted = self.bounding_capset() | self.inheritable_capset();
+            }
+            if self.euid().is_root() {
+                is_file_effective = true;
+            }
+        }
+
+        if no_new_privs {
+            // No capabilities can be gained if `no_new_privs` is set.
+            new_permitted &= old_permitted;
+        }
+
+        let new_ambient = if file_caps.is_some() || is_setid {
+            CapSet::empty()
+        } else {
+            self.ambient_capset()
+        };
+        new_permitted |= new_ambient;
+        let new_effective = if is_file_effective {
+            new_permitted
+        } else {
+            new_ambient
+        };
+
+        self.permitted_capset
+            .store(new_permitted, Ordering::Relaxed);
+        self.effective_capset
+            .store(new_effective, Ordering::Relaxed);
+        self.ambient_capset.store(new_ambient, Ordering::Relaxed);
+    }
+
     pub(super) fn drop_bounding_capability(&self, capability: CapSet) -> Result<()> {
         if !self.effective_capset().contains(CapSet::SETPCAP) {
             return_errno_with_message!(
@@ -607,6 +684,7 @@ impl Clone for Credentials_ {
             permitted_capset: self.permitted_capset.clone(),
             effective_capset: self.effective_capset.clone(),
             bounding_capset: self.bounding_capset.clone(),
+            ambient_capset: self.ambient_capset.clone(),
             securebits: self.securebits.clone(),
         }
     }
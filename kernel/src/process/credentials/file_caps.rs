@@ -0,0 +1,157 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::{
+    Uid,
+    c_types::{CVfsCapDataItem, CVfsNsCapData},
+    capabilities::CapSet,
+};
+use crate::{
+    fs::vfs::{inode::Inode, xattr::XattrName},
+    prelude::*,
+    process::{UserNamespace, posix_thread::PosixThread},
+};
+
+/// The file capabilities of an executable file.
+///
+/// The file capabilities are stored in the `security.capability` extended attribute. They are
+/// used to compute the capabilities of a thread when it executes the file.
+///
+/// Reference: The "File capabilities" section in
+/// <https://man7.org/linux/man-pages/man7/capabilities.7.html>.
+#[derive(Clone, Copy, Debug)]
+pub struct FileCaps {
+    permitted: CapSet,
+    inheritable: CapSet,
+    is_effective: bool,
+}
+
+impl FileCaps {
+    /// The name of the extended attribute that stores the file capabilities.
+    pub const XATTR_NAME: &str = "security.capability";
+
+    /// Reads the file capabilities of the inode.
+    ///
+    /// This method returns `None` if the inode has no file capabilities, or if the root user ID
+    /// of the file capabilities is not the root user of `user_ns` or any of its ancestors. In the
+    /// latter case, the file capabilities are meant for processes in another user namespace.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18.6/source/security/commoncap.c>.
+    pub fn read_from(inode: &dyn Inode, user_ns: &UserNamespace) -> Result<Option<Self>> {
+        let xattr_name = XattrName::try_from_full_name(Self::XATTR_NAME).unwrap();
+
+        let mut buf = [0u8; size_of::<CVfsNsCapData>()];
+        let mut writer = VmWriter::from(buf.as_mut_slice()).to_fallible();
+        let len = match inode.get_xattr(xattr_name, &mut writer) {
+            Ok(len) => len,
+            Err(err) if matches!(err.error(), Errno::ENODATA | Errno::EOPNOTSUPP) => {
+                return Ok(None);
+            }
+            Err(err) => return Err(err),
+        };
+
+        // The bytes beyond `len` are zeros, so the data of an earlier revision can be parsed in
+        // the same way.
+        let data = CVfsNsCapData::from_bytes(&buf);
+        let (rootid, is_effective) = parse_header(&data, len)?;
+        if !user_ns.is_root_of_self_or_ancestor(rootid) {
+            return Ok(None);
+        }
+
+        let [lo, hi] = data.data.map(|item| CVfsCapDataItem {
+            permitted: u32::from_le(item.permitted),
+            inheritable: u32::from_le(item.inheritable),
+        });
+        Ok(Some(Self {
+            permitted: CapSet::from_lo_hi(lo.permitted, hi.permitted),
+            inheritable: CapSet::from_lo_hi(lo.inheritable, hi.inheritable),
+            is_effective,
+        }))
+    }
+
+    /// Checks whether `value` can be written to the `security.capability` extended attribute by
+    /// the thread.
+    ///
+    /// # Errors
+    ///
+    /// This method will fail with
+    /// - `EINVAL` if the value is malformed;
+    /// - `EPERM` if the thread does not have the SETFCAP capability in `user_ns`.
+    pub fn check_xattr_value(
+        value: &[u8],
+        user_ns: &UserNamespace,
+        posix_thread: &PosixThread,
+    ) -> Result<()> {
+        let mut buf = [0u8; size_of::<CVfsNsCapData>()];
+        let Some(prefix) = buf.get_mut(..value.len()) else {
+            return_errno_with_message!(Errno::EINVAL, "the file capabilities are too long");
+        };
+        prefix.copy_from_slice(value);
+
+        let data = CVfsNsCapData::from_bytes(&buf);
+        parse_header(&data, value.len())?;
+        // FIXME: Once support for creating new user namespaces is added, file capabilities of
+        // revision 2 written in a non-initial user namespace should be converted to revision 3
+        // with the root user ID of the namespace.
+
+        user_ns.check_cap(CapSet::SETFCAP, posix_thread)
+    }
+
+    /// Returns the permitted capabilities that the thread will have after executing the file.
+    ///
+    /// `bounding` and `inheritable` are the bounding set and the inheritable set of the thread.
+    /// The result does not take the privileges of the root user and the ambient set into
+    /// account.
+    pub(super) fn new_permitted(&self, bounding: CapSet, inheritable: CapSet) -> CapSet {
+        (self.permitted & bounding) | (self.inheritable & inheritable)
+    }
+
+    /// Checks whether all the permitted file capabilities can be granted to the thread.
+    ///
+    /// If the effective bit is set, the program is probably unaware of capabilities and assumes
+    /// that it has all the permitted file capabilities. So executing the program fails with
+    /// `EPERM` if some of them cannot be granted.
+    pub fn check_grantable(&self, bounding: CapSet, inheritable: CapSet) -> Result<()> {
+        let new_permitted = self.new_permitted(bounding, inheritable);
+        if self.is_effective && !new_permitted.contains(self.permitted) {
+            return_errno_with_message!(
+                Errno::EPERM,
+                "some file capabilities cannot be granted to the thread"
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Returns whether the effective bit is set.
+    pub(super) fn is_effective(&self) -> bool {
+        self.is_effective
+    }
+}
+
+const VFS_CAP_REVISION_MASK: u32 = 0xFF00_0000;
+const VFS_CAP_REVISION_1: u32 = 0x0100_0000;
+const VFS_CAP_REVISION_2: u32 = 0x0200_0000;
+const VFS_CAP_REVISION_3: u32 = 0x0300_0000;
+const VFS_CAP_FLAGS_EFFECTIVE: u32 = 0x0000_0001;
+
+const XATTR_CAPS_SZ_1: usize = size_of::<u32>() + size_of::<CVfsCapDataItem>();
+const XATTR_CAPS_SZ_2: usize = size_of::<u32>() + size_of::<CVfsCapDataItem>() * 2;
+const XATTR_CAPS_SZ_3: usize = size_of::<CVfsNsCapData>();
+
+/// Parses the header of the file capabilities of `len` bytes.
+///
+/// Returns the root user ID and whether the effective bit is set.
+fn parse_header(data: &CVfsNsCapData, len: usize) -> Result<(Uid, bool)> {
+    let magic_etc = u32::from_le(data.magic_etc);
+
+    let rootid = match (magic_etc & VFS_CAP_REVISION_MASK, len) {
+        (VFS_CAP_REVISION_1, XATTR_CAPS_SZ_1) | (VFS_CAP_REVISION_2, XATTR_CAPS_SZ_2) => {
+            Uid::new_root()
+        }
+        (VFS_CAP_REVISION_3, XATTR_CAPS_SZ_3) => Uid::new(u32::from_le(data.rootid)),
+        _ => return_errno_with_message!(Errno::EINVAL, "the file capabilities are malformed"),
+    };
+    let is_effective = magic_etc & VFS_CAP_FLAGS_EFFECTIVE != 0;
+
+    Ok((rootid, is_effective))
+}
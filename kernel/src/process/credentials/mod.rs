@@ -3,14 +3,15 @@
 pub mod c_types;
 pub mod capabilities;
 mod credentials_;
+mod file_caps;
 mod group;
 mod secure_bits;
 mod static_cap;
 mod user;
 
 use aster_rights::FullOp;
-use capabilities::CapSet;
 use credentials_::Credentials_;
+pub use file_caps::FileCaps;
 pub use group::Gid;
 pub use secure_bits::SecureBits;
 pub use user::Uid;
@@ -28,6 +29,3 @@ use crate::prelude::*;
 /// - Linux capabilities;
 /// - secure bits.
 pub struct Credentials<R = FullOp>(Arc<Credentials_>, R);
-
-// TODO: Support the ambient capability set.
-pub const AMBIENT_CAPSET: CapSet = CapSet::empty();
@@ -4,7 +4,9 @@ use aster_rights::{Dup, Read, TRights, Write};
 use aster_rights_proc::require;
 use ostd::sync::{PreemptDisabled, RwLockReadGuard, RwLockWriteGuard};
 
-use super::{Credentials, Gid, SecureBits, Uid, capabilities::CapSet, credentials_::Credentials_};
+use super::{
+    Credentials, FileCaps, Gid, SecureBits, Uid, capabilities::CapSet, credentials_::Credentials_,
+};
 use crate::prelude::*;
 
 impl<R: TRights> Credentials<R> {
@@ -299,8 +301,20 @@ impl<R: TRights> Credentials<R> {
         self.0.bounding_capset()
     }
 
+    /// Gets the capabilities that are preserved across an `execve()` of a program that is not
+    /// privileged.
+    ///
+    /// This method requires the `Read` right.
+    #[require(R > Read)]
+    pub fn ambient_capset(&self) -> CapSet {
+        self.0.ambient_capset()
+    }
+
     /// Sets the capabilities that child processes can inherit.
     ///
+    /// The capabilities that are no longer in the inheritable set are dropped from the ambient
+    /// set.
+    ///
     /// This method requires the `Write` right.
     #[require(R > Write)]
     pub fn set_inheritable_capset(&self, inheritable_capset: CapSet) {
@@ -309,6 +323,9 @@ impl<R: TRights> Credentials<R> {
 
     /// Sets the capabilities that a process can potentially be granted.
     ///
+    /// The capabilities that are no longer in the permitted set are dropped from the ambient
+    /// set.
+    ///
     /// This method requires the `Write` right.
     #[require(R > Write)]
     pub fn set_permitted_capset(&self, permitted_capset: CapSet) {
@@ -333,6 +350,47 @@ impl<R: TRights> Credentials<R> {
         self.0.drop_bounding_capability(capability)
     }
 
+    /// Adds one capability to the ambient capability set.
+    ///
+    /// If the capability is not in both the permitted and inheritable sets, or if the
+    /// [`SecureBits::NO_CAP_AMBIENT_RAISE`] is set, this method returns an error.
+    ///
+    /// This method requires the `Write` right.
+    #[require(R > Write)]
+    pub fn raise_ambient_capability(&self, capability: CapSet) -> Result<()> {
+        self.0.raise_ambient_capability(capability)
+    }
+
+    /// Removes one capability from the ambient capability set.
+    ///
+    /// This method requires the `Write` right.
+    #[require(R > Write)]
+    pub fn lower_ambient_capability(&self, capability: CapSet) {
+        self.0.lower_ambient_capability(capability);
+    }
+
+    /// Removes all capabilities from the ambient capability set.
+    ///
+    /// This method requires the `Write` right.
+    #[require(R > Write)]
+    pub fn clear_ambient_capset(&self) {
+        self.0.clear_ambient_capset();
+    }
+
+    /// Updates the capabilities for executing a new executable file.
+    ///
+    /// The UIDs and GIDs should have been updated according to the set-user-ID and set-group-ID
+    /// bits of the file before calling this method. `file_caps` are the file capabilities of the
+    /// file. If `no_new_privs` is set, no capabilities will be gained.
+    ///
+    /// This method should only be used when executing a new executable file.
+    ///
+    /// This method requires the `Write` right.
+    #[require(R > Write)]
+    pub fn apply_exec_caps(&self, file_caps: Option<&FileCaps>, no_new_privs: bool) {
+        self.0.apply_exec_caps(file_caps, no_new_privs);
+    }
+
     /// Gets the keep-capabilities flag.
     ///
     /// This method requires the `Read` right.
@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use aster_rights::ReadWriteOp;
+use aster_rights::{ReadOp, ReadWriteOp};
 #[cfg(target_arch = "x86_64")]
 use ostd::arch::cpu::context::{FsBase, GsBase};
 use ostd::{
@@ -12,10 +12,16 @@ use ostd::{
 
 use super::process_vm::activate_vmar;
 use crate::{
-    fs::vfs::{inode::Inode, path::Path},
+    fs::{
+        file::Permission,
+        vfs::{inode::Inode, path::Path},
+    },
     prelude::*,
     process::{
-        ContextUnshareAdminApi, Credentials, Process, pid_table,
+        ContextSetNsAdminApi, ContextUnshareAdminApi, Credentials, Dumpable, Gid, NsProxy, Process,
+        Uid,
+        credentials::{FileCaps, capabilities::CapSet},
+        pid_table,
         posix_thread::{
             AsPosixThread, ContextPthreadAdminApi, ThreadLocal, ThreadName, ptrace::PtraceEvent,
             sigkill_other_threads,
@@ -60,9 +66,38 @@ pub fn do_execve(
     let program_to_load =
         ProgramToLoad::build_from_file(elf_file.clone(), &path_resolver, argv, envp)?;
 
+    // The file capabilities are checked here, since failing to grant them aborts the execution.
+    let file_caps = FileCaps::read_from(elf_file.inode().as_ref(), &ctx.process.user_ns().lock())?;
+    if let Some(file_caps) = file_caps.as_ref() {
+        let credentials = ctx.posix_thread.credentials();
+        file_caps.check_grantable(
+            credentials.bounding_capset(),
+            credentials.inheritable_capset(),
+        )?;
+    }
+
     let new_vmar = VmarHandle::new(ProcessVm::new(elf_file.clone()));
+    // A program that cannot be read must not be dumped; otherwise, its content may be leaked via
+    // core dumps.
+    if elf_file
+        .inode()
+        .check_permission(Permission::MAY_READ)
+        .is_err()
+    {
+        new_vmar.process_vm().set_dumpable(Dumpable::Disable);
+    }
     let elf_load_info = program_to_load.load_to_vmar(&new_vmar, &path_resolver)?;
 
+    // The new program runs in the time namespace for children.
+    //
+    // Reference: <https://elixir.bootlin.com/linux/v6.18/source/kernel/nsproxy.c>
+    let new_ns_proxy = ctx
+        .thread_local
+        .borrow_ns_proxy()
+        .unwrap()
+        .with_time_ns_for_children();
+    new_ns_proxy.time_ns().join_vdso(&new_vmar)?;
+
     // Ensure no other thread is concurrently performing exit_group or execve.
     // If such an operation is in progress, return EAGAIN.
     let mut task_set = ctx.process.tasks().lock();
@@ -89,6 +124,8 @@ pub fn do_execve(
         elf_file,
         thread_name,
         new_vmar,
+        new_ns_proxy,
+        file_caps,
         &elf_load_info,
     );
 
@@ -149,6 +186,8 @@ fn do_execve_no_return(
     elf_file: Path,
     thread_name: ThreadName,
     new_vmar: VmarHandle,
+    new_ns_proxy: Arc<NsProxy>,
+    file_caps: Option<FileCaps>,
     elf_load_info: &ElfLoadInfo,
 ) -> Result<()> {
     let Context {
@@ -167,11 +206,32 @@ fn do_execve_no_return(
     // while holding the process VMAR lock.
     // This prevents race conditions when checking access permissions while opening
     // `/proc/[pid]/mem` or `/proc/[pid]/maps`.
+    let old_credentials = CredentialsSnapshot::new(posix_thread.credentials());
     let (vmar_guard, old_vmar) = activate_vmar(ctx, new_vmar);
-    apply_caps_from_exec(process, ctx.credentials_mut(), elf_file.inode())?;
+    apply_caps_from_exec(
+        process,
+        ctx.credentials_mut(),
+        elf_file.inode(),
+        file_caps.as_ref(),
+        posix_thread.no_new_privs(),
+    )?;
+    // Programs that run with different credentials must not be dumped, so that their memory
+    // cannot be inspected by the unprivileged user who started them.
+    //
+    // Reference: <https://elixir.bootlin.com/linux/v6.18/source/fs/exec.c>
+    // and <https://elixir.bootlin.com/linux/v6.18/source/kernel/cred.c>
+    if old_credentials.is_privileged_exec(&CredentialsSnapshot::new(posix_thread.credentials())) {
+        vmar_guard
+            .unwrap()
+            .process_vm()
+            .set_dumpable(Dumpable::Disable);
+    }
     drop(vmar_guard);
     drop(old_vmar);
 
+    // Enter the time namespace for children, whose vvar pages have been mapped in the new VMAR.
+    ctx.set_ns_proxy(new_ns_proxy);
+
     // After the program has been successfully loaded, the virtual memory of the current process
     // is initialized. Hence, it is necessary to clear the previously recorded robust list.
     *thread_local.robust_list().borrow_mut() = None;
@@ -305,14 +365,25 @@ fn set_cpu_context(
 
 /// Sets the UID and GID in the credentials according to the ELF inode.
 ///
-/// The capabilities will be updated accordingly.
+/// The capabilities will be updated accordingly, taking the file capabilities into account.
+///
+/// If `no_new_privs` is set, the `set_uid` and `set_gid` bits of the ELF inode are ignored, and no
+/// capabilities will be gained.
 fn apply_caps_from_exec(
     process: &Process,
     credentials: Credentials<ReadWriteOp>,
     elf_inode: &Arc<dyn Inode>,
+    file_caps: Option<&FileCaps>,
+    no_new_privs: bool,
 ) -> Result<()> {
-    set_uid_from_elf(process, &credentials, elf_inode)?;
-    set_gid_from_elf(process, &credentials, elf_inode)?;
+    if no_new_privs {
+        credentials.reset_suid();
+        credentials.reset_sgid();
+    } else {
+        set_uid_from_elf(process, &credentials, elf_inode)?;
+        set_gid_from_elf(process, &credentials, elf_inode)?;
+    }
+    credentials.apply_exec_caps(file_caps, no_new_privs);
     credentials.set_keep_capabilities(false)?;
 
     Ok(())
@@ -360,6 +431,45 @@ fn set_gid_from_elf(
     Ok(())
 }
 
+/// A snapshot of the credentials that decide whether a program can be dumped.
+struct CredentialsSnapshot {
+    ruid: Uid,
+    euid: Uid,
+    fsuid: Uid,
+    rgid: Gid,
+    egid: Gid,
+    fsgid: Gid,
+    permitted_capset: CapSet,
+}
+
+impl CredentialsSnapshot {
+    fn new(credentials: Credentials<ReadOp>) -> Self {
+        Self {
+            ruid: credentials.ruid(),
+            euid: credentials.euid(),
+            fsuid: credentials.fsuid(),
+            rgid: credentials.rgid(),
+            egid: credentials.egid(),
+            fsgid: credentials.fsgid(),
+            permitted_capset: credentials.permitted_capset(),
+        }
+    }
+
+    /// Returns whether the program is executed with privileges that the user does not have.
+    ///
+    /// This is the case if the old credentials are already in a set-user-ID or set-group-ID
+    /// state, or if the new credentials have changed the effective IDs or gained capabilities.
+    fn is_privileged_exec(&self, new: &Self) -> bool {
+        self.euid != self.ruid
+            || self.egid != self.rgid
+            || new.euid != self.euid
+            || new.fsuid != self.fsuid
+            || new.egid != self.egid
+            || new.fsgid != self.fsgid
+            || !self.permitted_capset.contains(new.permitted_capset)
+    }
+}
+
 fn reset_vfork_child(process: &Process) {
     if process.status().is_vfork_child() {
         // Resumes the parent process.
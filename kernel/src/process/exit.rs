@@ -2,10 +2,12 @@
 
 use core::sync::atomic::Ordering;
 
-use super::{INIT_PROCESS_PID, Pid, Process, pid_table};
+use super::{INIT_PROCESS_PID, Pid, Process, pid_table, wait::reap_zombie_children};
 use crate::{
-    events::IoEvents, fs::cgroupfs::CgroupMembership, prelude::*,
-    process::signal::signals::kernel::KernelSignal,
+    events::IoEvents,
+    fs::cgroupfs::CgroupMembership,
+    prelude::*,
+    process::signal::{constants::SIGKILL, signals::kernel::KernelSignal},
 };
 
 /// Exits the current POSIX process.
@@ -21,6 +23,10 @@ pub(super) fn exit_process(current_process: &Process) {
     // Drop fields in `Process`.
     drop_after!(current_process.lock_vmar().set_vmar(None));
 
+    // If the current process is the init process of a PID namespace, the other processes in the
+    // namespace cannot outlive it.
+    zap_pid_ns_processes(current_process);
+
     // Move the children to the reaper process and send them signals. The children should see a new
     // parent when they receive the signal.
     let children = move_children_to_reaper_process(current_process);
@@ -34,6 +40,7 @@ pub(super) fn exit_process(current_process: &Process) {
     // This must happen after `move_children_to_reaper_process`. See the comments in
     // `find_reaper_process` for details.
     current_process.status().set_zombie();
+    current_process.pid_ns().notify_process_exit();
 
     current_process.pidfile_pollee.notify(IoEvents::IN);
 
@@ -63,6 +70,42 @@ macro_rules! drop_after {
 
 pub(super) use drop_after;
 
+/// Kills the other processes in the PID namespace and waits for them to be reaped if the current
+/// process is the init process of a non-initial PID namespace.
+///
+/// After this point, no new processes can be created in the namespace. The children of the current
+/// process are reaped here, while the other processes are reaped by their parents outside the
+/// namespace.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.18/source/kernel/pid_namespace.c> (`zap_pid_ns_processes`)
+fn zap_pid_ns_processes(current_process: &Process) {
+    if current_process.is_init_process() || !current_process.is_pid_ns_init() {
+        return;
+    }
+
+    let pid_ns = current_process.pid_ns();
+    pid_ns.disable_allocation();
+
+    let processes = pid_table::pid_table_mut().processes_in_ns(pid_ns);
+    for (_, process) in processes {
+        if process.pid() != current_process.pid() {
+            process.enqueue_signal(Box::new(KernelSignal::new(SIGKILL)));
+        }
+    }
+
+    // The orphaned processes in the namespace are moved to the current process, which is not a
+    // zombie yet, so they are reaped here as well.
+    pid_ns.exit_wait_queue().wait_until(|| {
+        reap_zombie_children(current_process);
+
+        let processes = pid_table::pid_table_mut().processes_in_ns(pid_ns);
+        processes
+            .iter()
+            .all(|(_, process)| process.pid() == current_process.pid())
+            .then_some(())
+    });
+}
+
 /// Moves the children to a reaper process.
 ///
 /// Returns the moved children. Note that no new processes can become this process's children after
@@ -80,6 +123,24 @@ fn move_children_to_reaper_process(current_process: &Process) -> BTreeMap<Pid, A
         }
     }
 
+    // Fall back to the init process of the PID namespace. If the current process is the init
+    // process of the namespace, it has no children left after `zap_pid_ns_processes`, but the
+    // init process of the parent namespace is still the correct reaper.
+    let mut pid_ns = current_process.pid_ns();
+    if current_process.is_pid_ns_init() {
+        pid_ns = pid_ns.parent_ns().unwrap();
+    }
+    while let Some(parent_ns) = pid_ns.parent_ns() {
+        let reaper_process = pid_ns.child_reaper(&pid_table::pid_table_mut());
+        if let Some(reaper_process) = reaper_process
+            && let Ok(children) = move_process_children(current_process, &reaper_process)
+        {
+            reaper_process.children_wait_queue().wake_all();
+            return children;
+        }
+        pid_ns = parent_ns;
+    }
+
     let init_process = pid_table::pid_table_mut()
         .get_process(INIT_PROCESS_PID)
         .unwrap();
@@ -104,6 +165,11 @@ fn find_reaper_process(current_process: &Process) -> Option<Arc<Process>> {
             return Some(parent);
         }
 
+        // Orphaned processes never leave their PID namespaces to find subreapers.
+        if parent.is_pid_ns_init() {
+            return (!parent.status().is_zombie()).then_some(parent);
+        }
+
         if !parent.has_child_subreaper.load(Ordering::Acquire) {
             return None;
         }
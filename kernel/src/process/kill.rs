@@ -1,9 +1,15 @@
 // SPDX-License-Identifier: MPL-2.0
 
 use super::{
-    Pgid, Pid, Process, pid_table,
+    Pgid, Pid, Process,
+    namespace::pid_ns::INIT_LOCAL_PID,
+    pid_table,
     posix_thread::AsPosixThread,
-    signal::{constants::SIGCONT, sig_num::SigNum, signals::Signal},
+    signal::{
+        constants::{SIGCONT, SIGKILL, SIGSTOP},
+        sig_num::SigNum,
+        signals::Signal,
+    },
 };
 use crate::{
     prelude::*,
@@ -25,6 +31,10 @@ pub fn kill(pid: Pid, signal: Option<Box<dyn Signal>>, ctx: &Context) -> Result<
             return Ok(());
         };
 
+        if is_ignored_by_pid_ns_init(ctx.process.as_ref(), signal.num(), ctx) {
+            return Ok(());
+        }
+
         if !ctx.posix_thread.has_signal_blocked(signal.num()) {
             // Killing the current thread does not raise any permission issues.
             ctx.posix_thread.enqueue_signal(signal);
@@ -109,7 +119,9 @@ pub fn tgkill(
         return Ok(());
     }
 
-    if let Some(signal) = signal {
+    if let Some(signal) = signal
+        && !is_ignored_by_pid_ns_init(&target_posix_thread.process(), signal.num(), ctx)
+    {
         // We've checked the permission issues above.
         // FIXME: We should take some lock while checking the permission to avoid race conditions.
         target_posix_thread.enqueue_signal(signal);
@@ -121,13 +133,17 @@ pub fn tgkill(
 /// Sends a signal to all processes except current process and init process, using
 /// the current process as the sender.
 ///
+/// Only the processes in the PID namespace of the current process are considered, and the init
+/// process refers to the init process of that namespace.
+///
 /// The credentials of the current process will be checked to determine
 /// if it is authorized to send the signal to the target group.
 pub fn kill_all<S: Signal + Clone>(signal: Option<S>, ctx: &Context) -> Result<()> {
     let mut result = Ok(());
 
-    for process in pid_table::pid_table_mut().iter_processes() {
-        if Arc::ptr_eq(&ctx.process, &process) || process.is_init_process() {
+    let processes = pid_table::pid_table_mut().processes_in_ns(ctx.process.pid_ns());
+    for (pid, process) in processes {
+        if Arc::ptr_eq(&ctx.process, &process) || pid == INIT_LOCAL_PID {
             continue;
         }
 
@@ -149,13 +165,29 @@ fn kill_process(process: &Process, signal: Option<Box<dyn Signal>>, ctx: &Contex
     let target_main_thread = process.main_thread();
     check_signal_perm(target_main_thread.as_posix_thread().unwrap(), ctx, signum)?;
 
-    if let Some(signal) = signal {
+    if let Some(signal) = signal
+        && !is_ignored_by_pid_ns_init(process, signal.num(), ctx)
+    {
         process.enqueue_signal(signal);
     }
 
     Ok(())
 }
 
+/// Returns whether the signal sent by the current process should be dropped because the target
+/// process is the init process of a PID namespace.
+///
+/// Other signals with the default action are ignored by the init process upon delivery. But
+/// `SIGKILL` and `SIGSTOP` from the ancestor namespaces must be delivered, so those from the
+/// namespace itself (or from its descendant namespaces) are dropped here.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.18/source/kernel/signal.c>
+fn is_ignored_by_pid_ns_init(target: &Process, signum: SigNum, ctx: &Context) -> bool {
+    (signum == SIGKILL || signum == SIGSTOP)
+        && target.is_pid_ns_init()
+        && target.pid_ns().local_id(ctx.process.pid()) != 0
+}
+
 // Reference: <https://elixir.bootlin.com/linux/v6.17/source/kernel/signal.c#L799>.
 fn check_signal_perm(target: &PosixThread, ctx: &Context, signum: Option<SigNum>) -> Result<()> {
     let target_process = target.process();
@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: MPL-2.0
 
 mod clone;
+mod coredump;
 pub mod credentials;
 mod execve;
 mod exit;
@@ -24,11 +25,13 @@ mod term_status;
 mod wait;
 
 pub use clone::{CloneArgs, CloneFlags, clone_child};
+pub use coredump::{CORENAME_MAX_SIZE, core_pattern, set_core_pattern};
 pub use credentials::{Credentials, Gid, Uid};
 pub use execve::do_execve;
 pub use kill::{kill, kill_all, kill_group, tgkill};
 pub use namespace::{
     nsproxy::{ContextSetNsAdminApi, NsProxy, NsProxyBuilder, check_unsupported_ns_flags},
+    pid_ns::PidNamespace,
     unshare::ContextUnshareAdminApi,
     user_ns::UserNamespace,
 };
@@ -38,7 +41,7 @@ pub use process::{
     Session, Sid, Terminal, broadcast_signal_async, enqueue_signal_async, spawn_init_process,
 };
 pub use process_filter::ProcessFilter;
-pub use process_vm::{INIT_STACK_SIZE, LockedHeap, ProcessVm, VmarSnapshot};
+pub use process_vm::{Dumpable, INIT_STACK_SIZE, LockedHeap, ProcessVm, VmarSnapshot};
 pub use rlimit::ResourceType;
 pub use stats::collect_process_creation_count;
 pub use term_status::TermStatus;
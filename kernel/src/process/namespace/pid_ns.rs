@@ -0,0 +1,413 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::ops::Range;
+
+use ostd::sync::WaitQueue;
+use spin::Once;
+
+use crate::{
+    fs::pseudofs::{NsCommonOps, NsType, StashedDentry},
+    prelude::*,
+    process::{
+        Pid, Process, UserNamespace,
+        credentials::capabilities::CapSet,
+        pid_table::PidTable,
+        posix_thread::{PID_MAX, PosixThread, allocate_posix_tid},
+    },
+    thread::Tid,
+};
+
+/// The PID namespace.
+///
+/// PID namespaces form a tree. A thread is visible in the PID namespace where it is created and
+/// in all ancestor namespaces, and it has a distinct ID in each of them.
+///
+/// Inside the kernel, threads, processes, process groups, and sessions are always identified by
+/// their IDs in the initial PID namespace (i.e., the global IDs). Each non-initial PID namespace
+/// maintains a mapping between the global IDs and its local IDs, which is used to translate the
+/// IDs that cross the user-kernel boundary.
+pub struct PidNamespace {
+    level: u32,
+    parent: Option<Arc<PidNamespace>>,
+    owner: Arc<UserNamespace>,
+    inner: SpinLock<Inner>,
+    /// The wait queue for the init process to wait for the other processes to be reaped.
+    ///
+    /// It is woken up when a process in this namespace or a descendant namespace becomes a zombie
+    /// or is reaped.
+    exit_wait_queue: WaitQueue,
+    stashed_dentry: StashedDentry,
+}
+
+struct Inner {
+    /// The mapping from the local IDs to the global IDs.
+    local_to_global: BTreeMap<u32, u32>,
+    /// The mapping from the global IDs to the local IDs.
+    global_to_local: BTreeMap<u32, u32>,
+    /// The last allocated local ID.
+    last_id: u32,
+    /// Whether new IDs can be allocated in the namespace.
+    ///
+    /// This becomes `false` after the init process of the namespace exits.
+    is_allocatable: bool,
+}
+
+impl PidNamespace {
+    /// Returns a reference to the singleton initial PID namespace.
+    pub fn get_init_singleton() -> &'static Arc<PidNamespace> {
+        static INIT: Once<Arc<PidNamespace>> = Once::new();
+
+        INIT.call_once(|| {
+            Arc::new(Self {
+                level: 0,
+                parent: None,
+                owner: UserNamespace::get_init_singleton().clone(),
+                inner: SpinLock::new(Inner::new()),
+                exit_wait_queue: WaitQueue::new(),
+                stashed_dentry: StashedDentry::new(),
+            })
+        })
+    }
+
+    /// Creates a new PID namespace as a child of this namespace.
+    pub fn new_child(
+        self: &Arc<Self>,
+        owner: Arc<UserNamespace>,
+        posix_thread: &PosixThread,
+    ) -> Result<Arc<Self>> {
+        owner.check_cap(CapSet::SYS_ADMIN, posix_thread)?;
+
+        if self.level >= MAX_PID_NS_LEVEL {
+            return_errno_with_message!(
+                Errno::ENOSPC,
+                "the maximum nesting level of PID namespaces is reached"
+            );
+        }
+
+        Ok(Arc::new(Self {
+            level: self.level + 1,
+            parent: Some(self.clone()),
+            owner,
+            inner: SpinLock::new(Inner::new()),
+            exit_wait_queue: WaitQueue::new(),
+            stashed_dentry: StashedDentry::new(),
+        }))
+    }
+
+    /// Returns the nesting level of the namespace.
+    ///
+    /// The initial PID namespace has a level of zero.
+    pub fn level(&self) -> u32 {
+        self.level
+    }
+
+    /// Returns the parent namespace, or `None` if this is the initial PID namespace.
+    ///
+    /// Unlike [`NsCommonOps::parent`], this method does not check whether the parent namespace is
+    /// visible to the current process.
+    pub(in crate::process) fn parent_ns(&self) -> Option<&Arc<PidNamespace>> {
+        self.parent.as_ref()
+    }
+
+    /// Returns whether this namespace is the same as, or an ancestor of, the other namespace.
+    pub fn is_same_or_ancestor_of(&self, other: &PidNamespace) -> bool {
+        let mut ns = other;
+        while ns.level > self.level {
+            ns = ns.parent.as_ref().unwrap();
+        }
+        core::ptr::eq(ns, self)
+    }
+
+    /// Translates a global ID to the ID in this namespace.
+    ///
+    /// Returns zero if the ID is not visible in this namespace.
+    pub fn local_id(&self, global_id: u32) -> u32 {
+        if self.level == 0 {
+            return global_id;
+        }
+
+        self.inner
+            .lock()
+            .global_to_local
+            .get(&global_id)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Translates an ID in this namespace to the global ID.
+    ///
+    /// Returns `None` if no thread, process, process group, or session has the ID in this
+    /// namespace.
+    pub fn global_id(&self, local_id: u32) -> Option<u32> {
+        if self.level == 0 {
+            return Some(local_id);
+        }
+
+        self.inner.lock().local_to_global.get(&local_id).copied()
+    }
+
+    /// Returns the IDs in the namespaces from `ancestor` down to this namespace.
+    ///
+    /// This corresponds to the `NSpid` field and the similar fields in `/proc/[pid]/status`. If
+    /// `ancestor` is not an ancestor of this namespace, the IDs in all the ancestor namespaces
+    /// will be returned.
+    pub fn local_ids_from(&self, ancestor: &PidNamespace, global_id: u32) -> Vec<u32> {
+        let mut ids = Vec::new();
+
+        let mut ns = self;
+        loop {
+            ids.push(ns.local_id(global_id));
+            if core::ptr::eq(ns, ancestor) {
+                break;
+            }
+            let Some(parent) = ns.parent.as_deref() else {
+                break;
+            };
+            ns = parent;
+        }
+
+        ids.reverse();
+        ids
+    }
+
+    /// Returns the init process of the namespace.
+    ///
+    /// The init process is the process whose ID is 1 in this namespace. It reaps the orphaned
+    /// processes in the namespace. Returns `None` if the init process has not been created or has
+    /// been reaped.
+    pub fn child_reaper(&self, pid_table: &PidTable) -> Option<Arc<Process>> {
+        let pid = self.global_id(INIT_LOCAL_PID)?;
+        pid_table.get_process(pid)
+    }
+
+    /// Returns the pairs of the local IDs and the global IDs in the namespace, ordered by the
+    /// local IDs.
+    ///
+    /// This method must not be called on the initial PID namespace, which does not maintain such
+    /// a mapping.
+    pub(in crate::process) fn ids(&self) -> Vec<(u32, u32)> {
+        debug_assert!(self.level > 0);
+        self.inner
+            .lock()
+            .local_to_global
+            .iter()
+            .map(|(local_id, global_id)| (*local_id, *global_id))
+            .collect()
+    }
+
+    /// Allocates a new global TID and the corresponding IDs in this namespace and all ancestor
+    /// namespaces.
+    ///
+    /// The IDs are released by [`Self::free_tid`] when the TID is no longer in use.
+    ///
+    /// # Errors
+    ///
+    /// This method will return `ENOMEM` if the init process of this namespace has exited, or
+    /// `EAGAIN` if all the IDs in this namespace or an ancestor namespace are in use.
+    pub(in crate::process) fn allocate_tid(&self) -> Result<Tid> {
+        let tid = allocate_posix_tid();
+
+        let Some(parent) = self.parent.as_deref() else {
+            return Ok(tid);
+        };
+
+        {
+            let mut inner = self.inner.lock();
+            if !inner.is_allocatable {
+                return_errno_with_message!(
+                    Errno::ENOMEM,
+                    "the init process of the PID namespace has exited"
+                );
+            }
+            inner.insert(tid)?;
+        }
+
+        let mut ns = parent;
+        while let Some(parent) = ns.parent.as_deref() {
+            if let Err(err) = ns.inner.lock().insert(tid) {
+                // Release the IDs allocated in the descendant namespaces.
+                let mut descendant = self;
+                while !core::ptr::eq(descendant, ns) {
+                    descendant.inner.lock().remove(tid);
+                    descendant = descendant.parent.as_deref().unwrap();
+                }
+                return Err(err);
+            }
+            ns = parent;
+        }
+
+        Ok(tid)
+    }
+
+    /// Releases the IDs allocated by [`Self::allocate_tid`].
+    pub(in crate::process) fn free_tid(&self, tid: Tid) {
+        let mut ns = self;
+        while let Some(parent) = ns.parent.as_deref() {
+            ns.inner.lock().remove(tid);
+            ns = parent;
+        }
+    }
+
+    /// Wakes up the init processes of this namespace and all ancestor namespaces that are waiting
+    /// for the other processes to be reaped.
+    ///
+    /// This should be called when a process in this namespace becomes a zombie or is reaped.
+    pub(in crate::process) fn notify_process_exit(&self) {
+        let mut ns = Some(self);
+        while let Some(current) = ns {
+            current.exit_wait_queue.wake_all();
+            ns = current.parent.as_deref();
+        }
+    }
+
+    /// Returns the wait queue that is woken up by [`Self::notify_process_exit`].
+    pub(in crate::process) fn exit_wait_queue(&self) -> &WaitQueue {
+        &self.exit_wait_queue
+    }
+
+    /// Prevents new IDs from being allocated in the namespace.
+    ///
+    /// This should be called when the init process of the namespace exits.
+    pub(in crate::process) fn disable_allocation(&self) {
+        self.inner.lock().is_allocatable = false;
+    }
+}
+
+/// The ID of the init process in each PID namespace.
+pub(in crate::process) const INIT_LOCAL_PID: Pid = 1;
+
+/// The maximum nesting level of PID namespaces.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/linux/pid_namespace.h>
+const MAX_PID_NS_LEVEL: u32 = 32;
+
+/// The minimum ID to use when the allocation wraps around.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/linux/threads.h>
+const RESERVED_PIDS: u32 = 300;
+
+impl Inner {
+    fn new() -> Self {
+        Self {
+            local_to_global: BTreeMap::new(),
+            global_to_local: BTreeMap::new(),
+            last_id: 0,
+            is_allocatable: true,
+        }
+    }
+
+    /// Allocates a free local ID for the global ID.
+    ///
+    /// The IDs are allocated cyclically. After wrapping around, the allocation starts again from
+    /// [`RESERVED_PIDS`].
+    ///
+    /// # Errors
+    ///
+    /// This method will return `EAGAIN` if all the local IDs are in use.
+    fn insert(&mut self, global_id: u32) -> Result<()> {
+        let Some(local_id) = self
+            .first_free_id(self.last_id + 1..PID_MAX)
+            .or_else(|| self.first_free_id(RESERVED_PIDS..self.last_id + 1))
+        else {
+            return_errno_with_message!(Errno::EAGAIN, "all IDs in the PID namespace are in use");
+        };
+
+        self.last_id = local_id;
+        self.local_to_global.insert(local_id, global_id);
+        self.global_to_local.insert(global_id, local_id);
+
+        Ok(())
+    }
+
+    /// Returns the smallest local ID in the range that is not in use.
+    fn first_free_id(&self, range: Range<u32>) -> Option<u32> {
+        if range.is_empty() {
+            return None;
+        }
+
+        let mut candidate = range.start;
+        for local_id in self.local_to_global.range(range.clone()).map(|(id, _)| *id) {
+            if local_id != candidate {
+                break;
+            }
+            candidate += 1;
+        }
+
+        (candidate < range.end).then_some(candidate)
+    }
+
+    /// Releases the local ID of the global ID.
+    fn remove(&mut self, global_id: u32) {
+        if let Some(local_id) = self.global_to_local.remove(&global_id) {
+            self.local_to_global.remove(&local_id);
+        }
+    }
+}
+
+impl NsCommonOps for PidNamespace {
+    const TYPE: NsType = NsType::Pid;
+
+    fn owner_user_ns(&self) -> Option<&Arc<UserNamespace>> {
+        Some(&self.owner)
+    }
+
+    fn parent(&self) -> Result<&Arc<Self>> {
+        let Some(parent) = self.parent.as_ref() else {
+            return_errno_with_message!(Errno::EPERM, "the initial PID namespace has no parent");
+        };
+
+        // Reference: <https://elixir.bootlin.com/linux/v6.18/source/kernel/pid_namespace.c>
+        let current = current!();
+        if !current.pid_ns().is_same_or_ancestor_of(parent) {
+            return_errno_with_message!(
+                Errno::EPERM,
+                "the parent PID namespace is not visible to the current process"
+            );
+        }
+
+        Ok(parent)
+    }
+
+    fn stashed_dentry(&self) -> &StashedDentry {
+        &self.stashed_dentry
+    }
+}
+
+#[cfg(ktest)]
+mod test {
+    use ostd::prelude::*;
+
+    use super::*;
+
+    #[ktest]
+    fn insert_wraps_around() {
+        let mut inner = Inner::new();
+        inner.insert(100).unwrap();
+        inner.insert(101).unwrap();
+        assert_eq!(inner.global_to_local[&100], 1);
+        assert_eq!(inner.global_to_local[&101], 2);
+
+        // The IDs below `RESERVED_PIDS` are skipped after wrapping around.
+        inner.last_id = PID_MAX - 2;
+        inner.insert(102).unwrap();
+        inner.insert(103).unwrap();
+        assert_eq!(inner.global_to_local[&102], PID_MAX - 1);
+        assert_eq!(inner.global_to_local[&103], RESERVED_PIDS);
+
+        // The freed IDs below `RESERVED_PIDS` are not reused.
+        inner.remove(100);
+        inner.insert(104).unwrap();
+        assert_eq!(inner.global_to_local[&104], RESERVED_PIDS + 1);
+    }
+
+    #[ktest]
+    fn first_free_id() {
+        let mut inner = Inner::new();
+        for global_id in 100..103 {
+            inner.insert(global_id).unwrap();
+        }
+
+        assert_eq!(inner.first_free_id(1..10), Some(4));
+        assert_eq!(inner.first_free_id(2..4), None);
+    }
+}
@@ -50,6 +50,16 @@ impl UserNamespace {
         Ok(Uid::new_root())
     }
 
+    /// Returns whether the UID in the initial user namespace is mapped to the root user of this
+    /// namespace or any of its ancestors.
+    pub fn is_root_of_self_or_ancestor(&self, uid: Uid) -> bool {
+        // FIXME: Creating new user namespaces is not yet supported,
+        // so every UID is mapped to itself in the only user namespace.
+        // Once user namespace creation is implemented,
+        // this should walk up the ancestor chain and check the UID mappings.
+        uid.is_root()
+    }
+
     /// Returns whether this namespace is the same as, or an ancestor of, the other namespace.
     pub fn is_same_or_ancestor_of(self: &Arc<Self>, other: &Arc<Self>) -> bool {
         // FIXME: Creating new user namespaces is not yet supported,
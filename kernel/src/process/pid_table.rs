@@ -6,10 +6,13 @@
 //! This design is inspired by Linux's `struct pid`. Each [`PidEntry`] tracks
 //! the kernel objects that share the same numeric identifier, which eliminates
 //! the need for separate per-type lookup tables.
+//!
+//! The table is indexed by the IDs in the initial PID namespace. See
+//! [`PidNamespace`] for how the IDs in other PID namespaces are translated.
 
-use alloc::collections::btree_map::Entry;
+use alloc::collections::btree_map::{Entry, OccupiedEntry};
 
-use super::{Pgid, Pid, Process, ProcessGroup, Session, Sid};
+use super::{Pgid, Pid, PidNamespace, Process, ProcessGroup, Session, Sid};
 use crate::{
     prelude::*,
     process::posix_thread::AsPosixThread,
@@ -36,10 +39,27 @@ impl PidTable {
     }
 
     /// Returns the entry for the given ID, or creates a new one if absent.
-    fn get_or_create_entry(&mut self, id: u32) -> &Arc<PidEntry> {
+    ///
+    /// The new entry holds the IDs allocated in `pid_ns` and its ancestor namespaces, which will
+    /// be released when the entry is removed.
+    fn get_or_create_entry(&mut self, id: u32, pid_ns: &Arc<PidNamespace>) -> &Arc<PidEntry> {
         self.entries
             .entry(id)
-            .or_insert_with(|| Arc::new(PidEntry::new()))
+            .or_insert_with(|| Arc::new(PidEntry::new(pid_ns.clone())))
+    }
+
+    /// Returns the entry for the given process group ID or session ID, or creates a new one if
+    /// absent.
+    fn get_or_create_group_entry(&mut self, id: u32) -> &Arc<PidEntry> {
+        // The entry can only be absent for the bootstrap process group and session, which belong
+        // to the initial PID namespace. Otherwise, the ID must be the ID of a live process.
+        self.get_or_create_entry(id, PidNamespace::get_init_singleton())
+    }
+
+    /// Removes an entry that no longer tracks any live object.
+    fn remove_entry(map_entry: OccupiedEntry<'_, u32, Arc<PidEntry>>) {
+        let (id, pid_entry) = map_entry.remove_entry();
+        pid_entry.pid_ns.free_tid(id);
     }
 
     // ---- Thread operations ----
@@ -49,9 +69,11 @@ impl PidTable {
     /// This method requires the target entry not to track a process. A
     /// process's main thread must be inserted with [`Self::insert_process`].
     pub(super) fn insert_thread(&mut self, tid: Tid, thread: &Arc<Thread>) {
-        debug_assert_eq!(tid, thread.as_posix_thread().unwrap().tid());
+        let posix_thread = thread.as_posix_thread().unwrap();
+        debug_assert_eq!(tid, posix_thread.tid());
 
-        let mut entry = self.get_or_create_entry(tid).lock();
+        let pid_ns = posix_thread.process().pid_ns().clone();
+        let mut entry = self.get_or_create_entry(tid, &pid_ns).lock();
         debug_assert!(!entry.has_live_process());
 
         entry.set_thread(thread);
@@ -76,7 +98,7 @@ impl PidTable {
         };
 
         if should_remove {
-            map_entry.remove();
+            Self::remove_entry(map_entry);
         }
     }
 
@@ -99,7 +121,7 @@ impl PidTable {
         };
 
         if should_remove {
-            map_entry.remove();
+            Self::remove_entry(map_entry);
         }
 
         Some(thread)
@@ -107,9 +129,11 @@ impl PidTable {
 
     /// Replaces the live thread reference for a TID.
     pub(super) fn replace_thread(&mut self, tid: Tid, thread: &Arc<Thread>) {
-        debug_assert_eq!(tid, thread.as_posix_thread().unwrap().tid());
+        let posix_thread = thread.as_posix_thread().unwrap();
+        debug_assert_eq!(tid, posix_thread.tid());
 
-        let entry = self.get_or_create_entry(tid);
+        let pid_ns = posix_thread.process().pid_ns().clone();
+        let entry = self.get_or_create_entry(tid, &pid_ns);
         entry.lock().replace_thread(thread);
     }
 
@@ -134,7 +158,7 @@ impl PidTable {
         // `set_process` will assert the process slot is empty.
         self.process_count += 1;
 
-        let entry = self.get_or_create_entry(pid);
+        let entry = self.get_or_create_entry(pid, process.pid_ns());
         let mut entry = entry.lock();
         entry.set_process(process);
         entry.set_thread(&process.main_thread());
@@ -162,9 +186,11 @@ impl PidTable {
             pid_entry.is_empty()
         };
 
+        let pid_ns = map_entry.get().pid_ns.clone();
         if should_remove {
-            map_entry.remove();
+            Self::remove_entry(map_entry);
         }
+        pid_ns.notify_process_exit();
     }
 
     /// Gets a process by a PID.
@@ -181,6 +207,25 @@ impl PidTable {
             .filter_map(|entry| entry.lock().process())
     }
 
+    /// Returns the processes that are visible in the PID namespace, along with their IDs in the
+    /// namespace.
+    ///
+    /// The processes are ordered by their IDs in the namespace.
+    pub fn processes_in_ns(&self, pid_ns: &PidNamespace) -> Vec<(Pid, Arc<Process>)> {
+        if pid_ns.level() == 0 {
+            return self
+                .iter_processes()
+                .map(|process| (process.pid(), process))
+                .collect();
+        }
+
+        pid_ns
+            .ids()
+            .into_iter()
+            .filter_map(|(local_pid, pid)| Some((local_pid, self.get_process(pid)?)))
+            .collect()
+    }
+
     /// Returns the number of live processes.
     pub fn process_count(&self) -> usize {
         self.process_count
@@ -190,7 +235,7 @@ impl PidTable {
 
     /// Inserts a process group into the table.
     pub(super) fn insert_process_group(&mut self, pgid: Pgid, group: &Arc<ProcessGroup>) {
-        let entry = self.get_or_create_entry(pgid);
+        let entry = self.get_or_create_group_entry(pgid);
         entry.lock().set_process_group(group);
     }
 
@@ -208,7 +253,7 @@ impl PidTable {
         };
 
         if should_remove {
-            map_entry.remove();
+            Self::remove_entry(map_entry);
         }
     }
 
@@ -230,7 +275,7 @@ impl PidTable {
 
     /// Inserts a session into the table.
     pub(super) fn insert_session(&mut self, sid: Sid, session: &Arc<Session>) {
-        let entry = self.get_or_create_entry(sid);
+        let entry = self.get_or_create_group_entry(sid);
         entry.lock().set_session(session);
     }
 
@@ -248,7 +293,7 @@ impl PidTable {
         };
 
         if should_remove {
-            map_entry.remove();
+            Self::remove_entry(map_entry);
         }
     }
 
@@ -279,6 +324,8 @@ impl PidTable {
 /// a [`Process`], but at some intermediate moment has only an associated [`Thread`].
 pub struct PidEntry {
     inner: Mutex<PidEntryInner>,
+    /// The PID namespace where the ID is allocated.
+    pid_ns: Arc<PidNamespace>,
 }
 
 struct PidEntryInner {
@@ -303,9 +350,10 @@ pub enum PidEntryType {
 
 impl PidEntry {
     /// Creates a new empty `PidEntry`.
-    fn new() -> Self {
+    fn new(pid_ns: Arc<PidNamespace>) -> Self {
         Self {
             inner: Mutex::new(PidEntryInner::new()),
+            pid_ns,
         }
     }
 
@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use core::sync::atomic::{AtomicU32, AtomicU64};
+use core::sync::atomic::{AtomicBool, AtomicU32, AtomicU64};
 
 #[cfg(target_arch = "x86_64")]
 use ostd::arch::cpu::context::{FsBase, GsBase};
@@ -12,7 +12,7 @@ use ostd::{
 };
 use spin::Once;
 
-use super::{PosixThread, ThreadLocal};
+use super::{PosixThread, ThreadLocal, seccomp::SeccompState};
 use crate::{
     fs::{file::file_table::FileTable, thread_info::ThreadFsInfo},
     prelude::*,
@@ -204,6 +204,8 @@ impl PosixThreadBuilder {
                     default_timer_slack_ns: AtomicU64::new(default_timer_slack_ns),
                     tracee_status: Once::new(),
                     tracees: Once::new(),
+                    seccomp: SeccompState::new(),
+                    no_new_privs: AtomicBool::new(false),
                     exit_code: AtomicU32::new(0),
                     personality: AtomicU32::new(0),
                 }
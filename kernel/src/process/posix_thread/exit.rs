@@ -62,6 +62,17 @@ fn exit_internal(
 
     let is_last_thread = {
         let mut tasks = posix_process.tasks().lock();
+
+        // If another thread is dumping the core, report to it and wait for the core dump to
+        // complete before exiting.
+        while !current_thread.is_exited()
+            && let Some(core_state) = tasks.core_state().cloned()
+        {
+            drop(tasks);
+            core_state.report_and_wait(ctx, user_ctx);
+            tasks = posix_process.tasks().lock();
+        }
+
         let has_exited_group = tasks.has_exited_group();
         let in_evecve = tasks.in_execve();
 
@@ -100,7 +111,9 @@ fn exit_internal(
 
     wake_clear_ctid(thread_local);
 
-    wake_robust_list(thread_local, posix_thread.tid());
+    // The robust futexes store the TIDs seen by the user space.
+    let local_tid = posix_process.pid_ns().local_id(posix_thread.tid());
+    wake_robust_list(thread_local, local_tid);
 
     // According to Linux behavior, the main thread shouldn't be removed from the table until the
     // process is reaped by its parent.
@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use core::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use core::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
 
 use aster_rights::{ReadDupOp, ReadOp, ReadWriteOp};
 use ostd::{
@@ -20,7 +20,7 @@ use crate::{
     process::{
         ExitCode, Pid,
         namespace::nsproxy::NsProxy,
-        posix_thread::ptrace::TraceeStatus,
+        posix_thread::{ptrace::TraceeStatus, seccomp::SeccompState},
         signal::{PauseReason, PollHandle, sig_mask::SigMask},
     },
     thread::{Thread, Tid},
@@ -37,6 +37,7 @@ mod personality;
 mod posix_thread_ext;
 pub mod ptrace;
 mod robust_list;
+mod seccomp;
 mod thread_local;
 
 pub use builder::PosixThreadBuilder;
@@ -46,7 +47,11 @@ pub use name::{MAX_THREAD_NAME_LEN, ThreadName};
 pub use personality::Personality;
 pub use posix_thread_ext::AsPosixThread;
 pub use robust_list::RobustListHead;
-pub use thread_local::{AsThreadLocal, FileTableRefMut, ThreadLocal};
+pub use seccomp::{
+    SeccompFilterFlags, SeccompMode, attach_seccomp_filter, is_seccomp_action_available,
+    seccomp_allows_syscall, set_seccomp_strict,
+};
+pub use thread_local::{AsThreadLocal, FileTableRefMut, SuppUserContext, ThreadLocal};
 
 pub struct PosixThread {
     // Immutable part
@@ -102,6 +107,12 @@ pub struct PosixThread {
     /// Threads traced by this thread.
     tracees: Once<Mutex<BTreeMap<Tid, Arc<Thread>>>>,
 
+    // Seccomp
+    /// The seccomp mode and filters.
+    seccomp: SeccompState,
+    /// Whether `execve` is prevented from granting new privileges.
+    no_new_privs: AtomicBool,
+
     /// Exit code of this thread.
     exit_code: AtomicU32,
 
@@ -259,7 +270,8 @@ impl PosixThread {
     /// This method does not perform permission checks on user signals.
     /// Therefore, unless the caller can ensure that there are no permission issues,
     /// this method should be used to enqueue kernel signals or fault signals.
-    pub fn enqueue_signal(&self, signal: Box<dyn Signal>) {
+    pub fn enqueue_signal(&self, mut signal: Box<dyn Signal>) {
+        signal.translate_to_pid_ns(self.process().pid_ns());
         self.sig_queues.enqueue(signal);
         self.wake_signalled_waker();
     }
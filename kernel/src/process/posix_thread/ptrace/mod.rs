@@ -115,6 +115,23 @@ impl PosixThread {
         }
     }
 
+    /// Stops this thread at a seccomp event-stop if it is currently traced,
+    /// and the `PTRACE_O_TRACESECCOMP` option is enabled.
+    ///
+    /// Returns a [`PtraceStopResult`] indicating why this ptrace-stop ended.
+    pub(in crate::process) fn ptrace_stop_on_seccomp(
+        &self,
+        data: u16,
+        ctx: &Context,
+        user_ctx: &mut UserContext,
+    ) -> PtraceStopResult {
+        if let Some(status) = self.tracee_status.get() {
+            status.ptrace_stop_on_seccomp(data, ctx, user_ctx)
+        } else {
+            PtraceStopResult::NotTraced(None)
+        }
+    }
+
     /// Returns whether a clone-family ptrace event would be required for `clone_args`.
     pub(in crate::process) fn needs_ptrace_clone_stop(&self, clone_args: &CloneArgs) -> bool {
         self.tracee_status
@@ -295,12 +312,16 @@ impl PosixThread {
 
     /// Returns the tracee with the given tid, if it is being traced by this thread.
     ///
+    /// The tid is in the PID namespace of this thread.
+    ///
     /// # Errors
     ///
     /// Returns `ESRCH` if there is no tracee with the given tid.
     pub fn get_tracee(&self, tid: Tid) -> Result<Arc<Thread>> {
+        let tid = self.process().pid_ns().global_id(tid);
         self.tracees()
-            .and_then(|tracees| tracees.lock().get(&tid).cloned())
+            .zip(tid)
+            .and_then(|(tracees, tid)| tracees.lock().get(&tid).cloned())
             .ok_or_else(|| Error::with_message(Errno::ESRCH, "no such tracee"))
     }
 
@@ -463,6 +484,38 @@ impl TraceeStatus {
         self.do_ptrace_stop(state, tracer, signal, wait_status, None, ctx, user_ctx)
     }
 
+    fn ptrace_stop_on_seccomp(
+        &self,
+        data: u16,
+        ctx: &Context,
+        user_ctx: &mut UserContext,
+    ) -> PtraceStopResult {
+        let state = self.state.lock();
+
+        let Some(tracer) = state.tracer() else {
+            return PtraceStopResult::NotTraced(None);
+        };
+        let event = PtraceEvent::Seccomp(data);
+        if !state.options.contains(event.option()) {
+            return PtraceStopResult::NotTraced(None);
+        }
+
+        let siginfo = event.siginfo(ctx);
+        let signal = Box::new(RawSignal::new(siginfo));
+        let signal = DequeuedSignal::FromThread(signal);
+        let wait_status = PtraceWaitStatus::from_event(&event);
+
+        self.do_ptrace_stop(
+            state,
+            tracer,
+            signal,
+            wait_status,
+            Some(event),
+            ctx,
+            user_ctx,
+        )
+    }
+
     #[expect(clippy::too_many_arguments)]
     fn do_ptrace_stop(
         &self,
@@ -495,7 +548,7 @@ impl TraceeStatus {
         let tracer = tracer.as_posix_thread().unwrap();
         tracer.enqueue_signal(Box::new(RawSignal::new({
             let mut siginfo = siginfo_t::new(SIGCHLD, CLD_TRAPPED);
-            siginfo.set_pid_uid_by(ctx);
+            siginfo.set_pid_uid_by(ctx, tracer.process().pid_ns());
             siginfo
         })));
         tracer.process().children_wait_queue().wake_all();
@@ -591,7 +644,10 @@ impl TraceeStatus {
         self.check_ptrace_stopped(&state)?;
 
         if let Some(sig_num) = request.sig_num() {
-            let signal = Box::new(UserSignal::new_kill(sig_num, ctx));
+            // The injected signal is delivered without being queued, so the tracer's PID is
+            // translated here.
+            let mut signal = Box::new(UserSignal::new_kill(sig_num, ctx));
+            signal.translate_to_pid_ns(self.process().pid_ns());
             state.signal.inject(signal);
         } else {
             state.signal.clear();
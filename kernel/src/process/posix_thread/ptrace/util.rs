@@ -5,7 +5,7 @@
 use crate::{
     prelude::*,
     process::{
-        ExitCode, WaitOptions,
+        ExitCode, PidNamespace, WaitOptions,
         signal::{
             DequeuedSignal, c_types::siginfo_t, constants::SIGTRAP, sig_num::SigNum,
             signals::Signal,
@@ -142,6 +142,8 @@ bitflags! {
         const PTRACE_O_TRACEVFORKDONE = 1 << PtraceEvent::VforkDone(0).code();
         /// Stops the tracee at `exit`.
         const PTRACE_O_TRACEEXIT = 1 << PtraceEvent::Exit(0).code();
+        /// Stops the tracee when a seccomp filter returns `SECCOMP_RET_TRACE`.
+        const PTRACE_O_TRACESECCOMP = 1 << PtraceEvent::Seccomp(0).code();
         /// Send a `SIGKILL` signal to the tracee if the tracer exits.
         const PTRACE_O_EXITKILL = 1 << 20;
     }
@@ -162,6 +164,8 @@ pub enum PtraceEvent {
     VforkDone(Tid),
     /// An `exit` event with the tracee's exit code.
     Exit(ExitCode),
+    /// A seccomp event with the data of the `SECCOMP_RET_TRACE` filter return value.
+    Seccomp(u16),
 }
 
 impl PtraceEvent {
@@ -174,6 +178,7 @@ impl PtraceEvent {
             Self::Exec(_) => 4,
             Self::VforkDone(_) => 5,
             Self::Exit(_) => 6,
+            Self::Seccomp(_) => 7,
         }
     }
 
@@ -183,14 +188,18 @@ impl PtraceEvent {
     }
 
     /// Returns the message of this event.
-    pub const fn message(&self) -> usize {
+    ///
+    /// The thread IDs in the message are translated into `pid_ns`, which should be the PID
+    /// namespace of the tracer.
+    pub fn message(&self, pid_ns: &PidNamespace) -> usize {
         match self {
             Self::Fork(tid)
             | Self::Vfork(tid)
             | Self::Clone(tid)
             | Self::Exec(tid)
-            | Self::VforkDone(tid) => *tid as usize,
+            | Self::VforkDone(tid) => pid_ns.local_id(*tid) as usize,
             Self::Exit(exit_code) => *exit_code as usize,
+            Self::Seccomp(data) => *data as usize,
         }
     }
 
@@ -198,7 +207,7 @@ impl PtraceEvent {
     pub(super) fn siginfo(&self, ctx: &Context) -> siginfo_t {
         let code = PtraceWaitStatus::from_event(self).0;
         let mut siginfo = siginfo_t::new(SIGTRAP, code);
-        siginfo.set_pid_uid_by(ctx);
+        siginfo.set_pid_uid_by(ctx, ctx.process.pid_ns());
         siginfo
     }
 }
@@ -237,6 +246,6 @@ impl PtraceWaitStatus {
 pub(super) fn syscall_stop_siginfo(options: &PtraceOptions, ctx: &Context) -> siginfo_t {
     let code = PtraceWaitStatus::from_syscall(options).0;
     let mut siginfo = siginfo_t::new(SIGTRAP, code);
-    siginfo.set_pid_uid_by(ctx);
+    siginfo.set_pid_uid_by(ctx, ctx.process.pid_ns());
     siginfo
 }
@@ -0,0 +1,617 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Secure computing (seccomp) for POSIX threads.
+//!
+//! Reference: <https://elixir.bootlin.com/linux/v6.18/source/kernel/seccomp.c>
+
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use atomic_integer_wrapper::define_atomic_version_of_integer_like_type;
+use ostd::{arch::cpu::context::UserContext, user::UserContextApi};
+
+use super::{AsPosixThread, PosixThread, do_exit, do_exit_group, ptrace::PtraceStopResult};
+use crate::{
+    cpu::LinuxAbi,
+    prelude::*,
+    process::{
+        TermStatus,
+        coredump::do_coredump,
+        credentials::capabilities::CapSet,
+        signal::{
+            c_types::siginfo_t,
+            constants::{SIGKILL, SIGSYS, SYS_SECCOMP},
+            signals::raw::RawSignal,
+        },
+    },
+    thread::{AsThread, Tid},
+    util::bpf::{
+        BPF_A, BPF_ABS, BPF_ALU, BPF_IMM, BPF_JMP, BPF_K, BPF_LD, BPF_LDX, BPF_LEN, BPF_MEM,
+        BPF_MISC, BPF_RET, BPF_ST, BPF_STX, BPF_W, BpfInput, BpfProgram, CSockFilter, bpf_class,
+        bpf_mode, bpf_size,
+    },
+};
+
+/// The seccomp mode of a thread.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, TryFromInt)]
+pub enum SeccompMode {
+    /// No syscalls are filtered.
+    Disabled = 0,
+    /// Only `read`, `write`, `_exit`, and `sigreturn` are allowed.
+    Strict = 1,
+    /// Syscalls are filtered by the attached BPF programs.
+    Filter = 2,
+}
+
+define_atomic_version_of_integer_like_type!(SeccompMode, try_from = true, {
+    #[derive(Debug)]
+    struct AtomicSeccompMode(AtomicU8);
+});
+
+impl From<SeccompMode> for u8 {
+    fn from(value: SeccompMode) -> Self {
+        value as _
+    }
+}
+
+bitflags! {
+    /// The flags of `SECCOMP_SET_MODE_FILTER`.
+    pub struct SeccompFilterFlags: u32 {
+        /// Synchronizes the filters of all threads in the process.
+        const TSYNC = 1 << 0;
+        /// Logs all actions except `SECCOMP_RET_ALLOW`.
+        const LOG = 1 << 1;
+        /// Disables the speculative store bypass mitigation.
+        const SPEC_ALLOW = 1 << 2;
+        /// Creates a user-space notification listener.
+        const NEW_LISTENER = 1 << 3;
+        /// Reports `ESRCH` instead of a thread ID if `TSYNC` fails.
+        const TSYNC_ESRCH = 1 << 4;
+        /// Waits killably for the user-space notification listener.
+        const WAIT_KILLABLE_RECV = 1 << 5;
+    }
+}
+
+/// The seccomp state of a thread.
+pub(super) struct SeccompState {
+    mode: AtomicSeccompMode,
+    filter: SpinLock<Option<Arc<SeccompFilter>>>,
+}
+
+impl SeccompState {
+    pub(super) fn new() -> Self {
+        Self {
+            mode: AtomicSeccompMode::new(SeccompMode::Disabled),
+            filter: SpinLock::new(None),
+        }
+    }
+
+    /// Sets the filter, and then the mode, so that a thread that observes
+    /// [`SeccompMode::Filter`] always finds a filter.
+    fn set(&self, mode: SeccompMode, filter: Option<Arc<SeccompFilter>>) {
+        *self.filter.lock() = filter;
+        self.mode.store(mode, Ordering::Release);
+    }
+}
+
+/// A seccomp filter, which is linked with the filters attached before it.
+struct SeccompFilter {
+    prog: BpfProgram,
+    is_logging: bool,
+    prev: Option<Arc<SeccompFilter>>,
+}
+
+impl SeccompFilter {
+    /// Runs this filter and all filters attached before it.
+    ///
+    /// Returns the return value with the highest precedence and the filter that produced it. If
+    /// multiple filters return actions with the same precedence, the most recently attached one
+    /// wins.
+    fn run(&self, data: &SeccompData) -> (u32, &SeccompFilter) {
+        let mut ret = SECCOMP_RET_ALLOW;
+        let mut matched = self;
+
+        let mut filter = Some(self);
+        while let Some(current) = filter {
+            let current_ret = current.prog.run(data);
+            if action_precedence(current_ret) < action_precedence(ret) {
+                ret = current_ret;
+                matched = current;
+            }
+            filter = current.prev.as_deref();
+        }
+
+        (ret, matched)
+    }
+
+    /// Returns an iterator over this filter and all filters attached before it.
+    fn iter(self: &Arc<Self>) -> impl Iterator<Item = &Arc<SeccompFilter>> {
+        core::iter::successors(Some(self), |filter| filter.prev.as_ref())
+    }
+}
+
+/// Returns the precedence of the action in a filter return value.
+///
+/// A smaller value means a higher precedence.
+fn action_precedence(ret: u32) -> i32 {
+    (ret & SECCOMP_RET_ACTION_FULL) as i32
+}
+
+// Filter return values.
+// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/seccomp.h#L38>
+const SECCOMP_RET_KILL_PROCESS: u32 = 0x8000_0000;
+const SECCOMP_RET_KILL_THREAD: u32 = 0x0000_0000;
+const SECCOMP_RET_TRAP: u32 = 0x0003_0000;
+const SECCOMP_RET_ERRNO: u32 = 0x0005_0000;
+const SECCOMP_RET_USER_NOTIF: u32 = 0x7fc0_0000;
+const SECCOMP_RET_TRACE: u32 = 0x7ff0_0000;
+const SECCOMP_RET_LOG: u32 = 0x7ffc_0000;
+const SECCOMP_RET_ALLOW: u32 = 0x7fff_0000;
+
+const SECCOMP_RET_ACTION_FULL: u32 = 0xffff_0000;
+const SECCOMP_RET_DATA: u32 = 0x0000_ffff;
+
+/// The maximum number of instructions on a path through the filters, where each filter
+/// counts as four extra instructions.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/kernel/seccomp.c#L247>
+const MAX_INSNS_PER_PATH: usize = (1 << 18) / size_of::<CSockFilter>();
+
+/// The audit architecture reported in `seccomp_data`.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/audit.h#L384>
+#[cfg(target_arch = "x86_64")]
+const AUDIT_ARCH: u32 = 0xc000_003e;
+#[cfg(target_arch = "riscv64")]
+const AUDIT_ARCH: u32 = 0xc000_00f3;
+#[cfg(target_arch = "loongarch64")]
+const AUDIT_ARCH: u32 = 0xc000_0102;
+
+/// The syscalls allowed in [`SeccompMode::Strict`]: `read`, `write`, `exit`, and `rt_sigreturn`.
+#[cfg(target_arch = "x86_64")]
+const STRICT_MODE_SYSCALLS: [usize; 4] = [0, 1, 60, 15];
+#[cfg(not(target_arch = "x86_64"))]
+const STRICT_MODE_SYSCALLS: [usize; 4] = [63, 64, 93, 139];
+
+/// The input of seccomp filters (`struct seccomp_data` in Linux).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct SeccompData {
+    nr: i32,
+    arch: u32,
+    instruction_pointer: u64,
+    args: [u64; 6],
+}
+
+impl SeccompData {
+    fn new(user_ctx: &UserContext) -> Self {
+        Self {
+            nr: user_ctx.syscall_num() as i32,
+            arch: AUDIT_ARCH,
+            instruction_pointer: user_ctx.instruction_pointer() as u64,
+            args: user_ctx.syscall_args().map(|arg| arg as u64),
+        }
+    }
+}
+
+impl BpfInput for SeccompData {
+    fn size(&self) -> u32 {
+        size_of::<Self>() as u32
+    }
+
+    fn load(&self, offset: u32, size: u16) -> Option<u32> {
+        // Seccomp filters are checked to only load aligned words.
+        debug_assert_eq!(size, BPF_W);
+
+        let offset = offset as usize;
+        let bytes = self.as_bytes().get(offset..offset + size_of::<u32>())?;
+        Some(u32::from_ne_bytes(bytes.try_into().unwrap()))
+    }
+}
+
+impl PosixThread {
+    /// Returns the seccomp mode of this thread.
+    pub fn seccomp_mode(&self) -> SeccompMode {
+        self.seccomp.mode.load(Ordering::Acquire)
+    }
+
+    /// Returns the number of seccomp filters attached to this thread.
+    pub fn num_seccomp_filters(&self) -> usize {
+        self.seccomp
+            .filter
+            .lock()
+            .as_ref()
+            .map_or(0, |filter| filter.iter().count())
+    }
+
+    /// Returns whether the `no_new_privs` attribute of this thread is set.
+    pub fn no_new_privs(&self) -> bool {
+        self.no_new_privs.load(Ordering::Relaxed)
+    }
+
+    /// Sets the `no_new_privs` attribute of this thread.
+    ///
+    /// Once set, the attribute cannot be unset.
+    pub fn set_no_new_privs(&self) {
+        self.no_new_privs.store(true, Ordering::Relaxed);
+    }
+
+    /// Inherits the `no_new_privs` attribute and the seccomp state from the parent thread.
+    ///
+    /// This method should be called with the task set of the parent's process locked, so that
+    /// the new thread cannot miss the filters synchronized by `SECCOMP_FILTER_FLAG_TSYNC`.
+    pub(in crate::process) fn inherit_seccomp(&self, parent: &PosixThread) {
+        if parent.no_new_privs() {
+            self.set_no_new_privs();
+        }
+
+        let filter = parent.seccomp.filter.lock().clone();
+        self.seccomp.set(parent.seccomp_mode(), filter);
+    }
+}
+
+/// Sets the seccomp mode of the current thread to [`SeccompMode::Strict`].
+pub fn set_seccomp_strict(ctx: &Context) -> Result<()> {
+    let _tasks = ctx.process.tasks().lock();
+
+    check_mode_transition(ctx.posix_thread, SeccompMode::Strict)?;
+    ctx.posix_thread.seccomp.set(SeccompMode::Strict, None);
+
+    Ok(())
+}
+
+/// Attaches a seccomp filter to the current thread.
+///
+/// If `SeccompFilterFlags::TSYNC` is set, the filter is attached to all threads in the process.
+/// If a thread prevents the synchronization, the filter is not attached and the ID of that
+/// thread is returned.
+pub fn attach_seccomp_filter(
+    insns: Vec<CSockFilter>,
+    flags: SeccompFilterFlags,
+    ctx: &Context,
+) -> Result<Option<Tid>> {
+    let posix_thread = ctx.posix_thread;
+
+    // Like Linux, installing a filter requires either `no_new_privs` or `CAP_SYS_ADMIN`, so that
+    // an unprivileged filter cannot fool a set-user-ID program.
+    if !posix_thread.no_new_privs()
+        && !posix_thread
+            .credentials()
+            .effective_capset()
+            .contains(CapSet::SYS_ADMIN)
+    {
+        return_errno_with_message!(
+            Errno::EACCES,
+            "installing a seccomp filter requires `no_new_privs` or `CAP_SYS_ADMIN`"
+        );
+    }
+
+    check_filter_insns(&insns)?;
+    let prog = BpfProgram::new(insns)?;
+
+    // Hold the lock to serialize against other filter attachments and `clone`.
+    let tasks = ctx.process.tasks().lock();
+
+    check_mode_transition(posix_thread, SeccompMode::Filter)?;
+    let prev = posix_thread.seccomp.filter.lock().clone();
+
+    let total_insns = prev
+        .iter()
+        .flat_map(|prev| prev.iter())
+        .fold(prog.num_insns(), |total, filter| {
+            total + filter.prog.num_insns() + 4
+        });
+    if total_insns > MAX_INSNS_PER_PATH {
+        return_errno_with_message!(Errno::ENOMEM, "the seccomp filters are too long");
+    }
+
+    let is_syncing = flags.contains(SeccompFilterFlags::TSYNC);
+    if is_syncing {
+        let other_threads = tasks
+            .as_slice()
+            .iter()
+            .filter_map(|task| task.as_posix_thread())
+            .filter(|thread| !core::ptr::eq(*thread, posix_thread));
+        for thread in other_threads {
+            if can_sync_filter(thread, prev.as_ref()) {
+                continue;
+            }
+
+            if flags.contains(SeccompFilterFlags::TSYNC_ESRCH) {
+                return_errno_with_message!(
+                    Errno::ESRCH,
+                    "the seccomp filters cannot be synchronized"
+                );
+            }
+            return Ok(Some(thread.tid()));
+        }
+    }
+
+    let filter = Arc::new(SeccompFilter {
+        prog,
+        is_logging: flags.contains(SeccompFilterFlags::LOG),
+        prev,
+    });
+
+    if is_syncing {
+        let no_new_privs = posix_thread.no_new_privs();
+        for thread in tasks
+            .as_slice()
+            .iter()
+            .filter_map(|task| task.as_posix_thread())
+        {
+            if no_new_privs {
+                thread.set_no_new_privs();
+            }
+            thread
+                .seccomp
+                .set(SeccompMode::Filter, Some(filter.clone()));
+        }
+    } else {
+        posix_thread.seccomp.set(SeccompMode::Filter, Some(filter));
+    }
+
+    Ok(None)
+}
+
+/// Returns whether the filter return value has an action that is supported.
+pub fn is_seccomp_action_available(action: u32) -> bool {
+    matches!(
+        action,
+        SECCOMP_RET_KILL_PROCESS
+            | SECCOMP_RET_KILL_THREAD
+            | SECCOMP_RET_TRAP
+            | SECCOMP_RET_ERRNO
+            | SECCOMP_RET_TRACE
+            | SECCOMP_RET_LOG
+            | SECCOMP_RET_ALLOW
+    )
+}
+
+/// Checks that a thread in seccomp mode `current` can switch to seccomp mode `new`.
+fn check_mode_transition(posix_thread: &PosixThread, new: SeccompMode) -> Result<()> {
+    let current = posix_thread.seccomp_mode();
+    if current != SeccompMode::Disabled && current != new {
+        return_errno_with_message!(Errno::EINVAL, "the seccomp mode cannot be changed");
+    }
+
+    Ok(())
+}
+
+/// Returns whether the filters of `thread` can be replaced by a descendant of `filter`
+/// without dropping any filters of `thread`.
+fn can_sync_filter(thread: &PosixThread, filter: Option<&Arc<SeccompFilter>>) -> bool {
+    match thread.seccomp_mode() {
+        SeccompMode::Disabled => true,
+        SeccompMode::Strict => false,
+        SeccompMode::Filter => {
+            let Some(thread_filter) = thread.seccomp.filter.lock().clone() else {
+                return true;
+            };
+            filter.is_some_and(|filter| {
+                filter
+                    .iter()
+                    .any(|ancestor| Arc::ptr_eq(ancestor, &thread_filter))
+            })
+        }
+    }
+}
+
+/// Checks that the instructions are allowed in seccomp filters.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/kernel/seccomp.c#L278>
+fn check_filter_insns(insns: &[CSockFilter]) -> Result<()> {
+    for insn in insns {
+        let code = insn.code;
+        let is_allowed = match bpf_class(code) {
+            BPF_LD => match bpf_mode(code) {
+                BPF_ABS => {
+                    bpf_size(code) == BPF_W
+                        && insn.k % 4 == 0
+                        && (insn.k as usize) < size_of::<SeccompData>()
+                }
+                BPF_IMM | BPF_MEM | BPF_LEN => true,
+                _ => false,
+            },
+            BPF_LDX => matches!(bpf_mode(code), BPF_IMM | BPF_MEM | BPF_LEN),
+            // The source of the return value is encoded in the size bits.
+            BPF_RET => matches!(bpf_size(code), BPF_K | BPF_A),
+            BPF_ST | BPF_STX | BPF_ALU | BPF_JMP | BPF_MISC => true,
+            _ => false,
+        };
+
+        if !is_allowed {
+            return_errno_with_message!(
+                Errno::EINVAL,
+                "the instruction is not allowed in seccomp filters"
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks the syscall that the current thread is about to make against its seccomp state.
+///
+/// Returns `false` if the syscall should be skipped. This happens if the syscall is rejected,
+/// in which case the return value has been set in `user_ctx`, or if the thread is exiting.
+pub fn seccomp_allows_syscall(ctx: &Context, user_ctx: &mut UserContext) -> bool {
+    match ctx.posix_thread.seccomp_mode() {
+        SeccompMode::Disabled => true,
+        SeccompMode::Strict => {
+            if STRICT_MODE_SYSCALLS.contains(&user_ctx.syscall_num()) {
+                return true;
+            }
+
+            log_action(SECCOMP_RET_KILL_THREAD, &SeccompData::new(user_ctx), ctx);
+            // Like Linux, a strict-mode violation kills the thread with `SIGKILL`.
+            do_exit(TermStatus::Killed(SIGKILL), ctx, user_ctx);
+            false
+        }
+        SeccompMode::Filter => run_filters(ctx, user_ctx, false),
+    }
+}
+
+fn run_filters(ctx: &Context, user_ctx: &mut UserContext, is_recheck: bool) -> bool {
+    let filter = ctx.posix_thread.seccomp.filter.lock().clone().unwrap();
+    let data = SeccompData::new(user_ctx);
+
+    let (ret, matched) = filter.run(&data);
+    let action = ret & SECCOMP_RET_ACTION_FULL;
+    let action_data = ret & SECCOMP_RET_DATA;
+
+    match action {
+        SECCOMP_RET_ALLOW => true,
+        SECCOMP_RET_LOG => {
+            log_action(action, &data, ctx);
+            true
+        }
+        SECCOMP_RET_ERRNO => {
+            if matched.is_logging {
+                log_action(action, &data, ctx);
+            }
+            // Like Linux, the error number is capped at `MAX_ERRNO`.
+            let errno = action_data.min(4095) as isize;
+            user_ctx.set_syscall_ret((-errno) as usize);
+            false
+        }
+        SECCOMP_RET_TRAP => {
+            if matched.is_logging {
+                log_action(action, &data, ctx);
+            }
+            // Like Linux, the registers are left as they were at the syscall entry, so the
+            // signal handler can emulate the syscall.
+            let mut siginfo = siginfo_t::new(SIGSYS, SYS_SECCOMP);
+            siginfo.si_errno = action_data as i32;
+            siginfo.set_sigsys(user_ctx.instruction_pointer(), data.nr, data.arch);
+            ctx.posix_thread
+                .enqueue_signal(Box::new(RawSignal::new(siginfo)));
+            false
+        }
+        SECCOMP_RET_TRACE => {
+            // The tracer has seen the syscall. Do not stop again.
+            if is_recheck {
+                return true;
+            }
+            if matched.is_logging {
+                log_action(action, &data, ctx);
+            }
+
+            match ctx
+                .posix_thread
+                .ptrace_stop_on_seccomp(action_data as u16, ctx, user_ctx)
+            {
+                PtraceStopResult::Continued(_) => {}
+                PtraceStopResult::Interrupted => return false,
+                PtraceStopResult::NotTraced(_) => {
+                    user_ctx.set_syscall_ret((-(Errno::ENOSYS as isize)) as usize);
+                    return false;
+                }
+            }
+
+            // The tracer may have changed or skipped the syscall, so the filters must be run
+            // again.
+            apply_tracer_syscall_num(ctx, user_ctx) && run_filters(ctx, user_ctx, true)
+        }
+        SECCOMP_RET_USER_NOTIF => {
+            // TODO: Support user-space notification listeners. They cannot be created for now, so
+            // Linux also fails the syscall with `ENOSYS`.
+            if matched.is_logging {
+                log_action(action, &data, ctx);
+            }
+            user_ctx.set_syscall_ret((-(Errno::ENOSYS as isize)) as usize);
+            false
+        }
+        SECCOMP_RET_KILL_THREAD if !is_last_live_thread(ctx) => {
+            log_action(action, &data, ctx);
+            do_exit(TermStatus::Killed(SIGSYS), ctx, user_ctx);
+            false
+        }
+        // Like Linux, killing the last live thread kills the process, so the core is dumped as
+        // with `SECCOMP_RET_KILL_PROCESS`.
+        SECCOMP_RET_KILL_THREAD => {
+            log_action(action, &data, ctx);
+            kill_process_with_core(action_data, &data, ctx, user_ctx);
+            false
+        }
+        // Like Linux, unknown actions are treated as `SECCOMP_RET_KILL_PROCESS`.
+        _ => {
+            log_action(SECCOMP_RET_KILL_PROCESS, &data, ctx);
+            kill_process_with_core(action_data, &data, ctx, user_ctx);
+            false
+        }
+    }
+}
+
+/// Returns whether the current thread is the only thread in the process that has not exited.
+fn is_last_live_thread(ctx: &Context) -> bool {
+    let tasks = ctx.process.tasks().lock();
+    tasks
+        .as_slice()
+        .iter()
+        .all(|task| core::ptr::eq(task.as_ref(), ctx.task) || task.as_thread().unwrap().is_exited())
+}
+
+/// Kills the current process with `SIGSYS` after dumping its core.
+///
+/// Like a `SIGSYS` signal raised by `SECCOMP_RET_TRAP`, the core records the rejected syscall,
+/// and the signal action cannot prevent the core dump.
+fn kill_process_with_core(
+    action_data: u32,
+    data: &SeccompData,
+    ctx: &Context,
+    user_ctx: &UserContext,
+) {
+    let mut siginfo = siginfo_t::new(SIGSYS, SYS_SECCOMP);
+    siginfo.si_errno = action_data as i32;
+    siginfo.set_sigsys(user_ctx.instruction_pointer(), data.nr, data.arch);
+
+    let term_status = do_coredump(SIGSYS, siginfo, ctx, user_ctx);
+    do_exit_group(term_status, ctx, user_ctx);
+}
+
+/// Applies the syscall number that the tracer may have changed at a seccomp stop.
+///
+/// Returns `false` if the tracer skips the syscall by setting the number to `-1`.
+fn apply_tracer_syscall_num(ctx: &Context, user_ctx: &mut UserContext) -> bool {
+    // On x86-64, the tracer sets the syscall number in `orig_rax`, while the syscall
+    // is dispatched according to `rax`.
+    #[cfg(target_arch = "x86_64")]
+    let syscall_num = ctx.thread_local.orig_syscall_ret().unwrap();
+    #[cfg(not(target_arch = "x86_64"))]
+    let syscall_num = {
+        let _ = ctx;
+        user_ctx.syscall_num()
+    };
+
+    if (syscall_num as isize) < 0 {
+        return false;
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    user_ctx.set_syscall_ret(syscall_num);
+
+    true
+}
+
+fn log_action(action: u32, data: &SeccompData, ctx: &Context) {
+    let action = match action {
+        SECCOMP_RET_KILL_THREAD => "kill_thread",
+        SECCOMP_RET_TRAP => "trap",
+        SECCOMP_RET_ERRNO => "errno",
+        SECCOMP_RET_USER_NOTIF => "user_notif",
+        SECCOMP_RET_TRACE => "trace",
+        SECCOMP_RET_LOG => "log",
+        _ => "kill_process",
+    };
+
+    notice!(
+        "seccomp: pid={} tid={} arch={:#x} syscall={} ip={:#x} action={}",
+        ctx.process.pid(),
+        ctx.posix_thread.tid(),
+        data.arch,
+        data.nr,
+        data.instruction_pointer,
+        action,
+    );
+}
@@ -2,20 +2,21 @@
 
 //! This module defines functions related to spawning the init process.
 
-use ostd::{arch::cpu::context::UserContext, task::Task, user::UserContextApi};
+use ostd::{arch::cpu::context::UserContext, sync::RwArc, task::Task, user::UserContextApi};
 
 use super::{Process, Session};
 use crate::{
     fs::{
+        file::file_table::FileTable,
         thread_info::ThreadFsInfo,
         vfs::path::{FsPath, MountNamespace, Path},
     },
     prelude::*,
     process::{
-        Credentials, ProcessVm, UserNamespace, pid_table,
+        Credentials, PidNamespace, ProcessVm, UserNamespace, pid_table,
         posix_thread::{PosixThreadBuilder, ThreadName, allocate_posix_tid},
         program_loader::ProgramToLoad,
-        rlimit::new_resource_limits_for_init,
+        rlimit::{ResourceLimits, new_resource_limits_for_init},
         signal::sig_disposition::SigDispositions,
     },
     sched::Nice,
@@ -86,6 +87,29 @@ fn create_init_process(
     executable_path: &str,
     argv: Vec<CString>,
     envp: Vec<CString>,
+) -> Result<Arc<Process>> {
+    create_process_from_executable(
+        executable_path,
+        argv,
+        envp,
+        new_resource_limits_for_init(),
+        None,
+    )
+}
+
+/// Creates a process from the given executable file in the initial namespaces.
+///
+/// The process runs with the root credentials. If `file_table` is `None`, the process starts with
+/// an empty file table.
+///
+/// The caller is responsible for attaching the process to its parent and process group, and then
+/// scheduling it to run.
+pub(super) fn create_process_from_executable(
+    executable_path: &str,
+    argv: Vec<CString>,
+    envp: Vec<CString>,
+    resource_limits: ResourceLimits,
+    file_table: Option<RwArc<FileTable>>,
 ) -> Result<Arc<Process>> {
     let fs = {
         let fs_resolver = MountNamespace::get_init_singleton().new_path_resolver();
@@ -96,13 +120,13 @@ fn create_init_process(
 
     let pid = allocate_posix_tid();
     let vmar = VmarHandle::new(ProcessVm::new(elf_path.clone()));
-    let resource_limits = new_resource_limits_for_init();
     let nice = Nice::default();
     let oom_score_adj = 0;
     let sig_dispositions = Arc::new(Mutex::new(SigDispositions::default()));
     let user_ns = UserNamespace::get_init_singleton().clone();
+    let pid_ns = PidNamespace::get_init_singleton().clone();
 
-    let init_proc = Process::new(
+    let process = Process::new(
         pid,
         vmar.clone_arc(),
         resource_limits,
@@ -110,12 +134,13 @@ fn create_init_process(
         oom_score_adj,
         sig_dispositions,
         user_ns,
+        pid_ns,
     );
 
-    let init_task = create_init_task(pid, &init_proc, fs, vmar, elf_path, argv, envp)?;
-    init_proc.tasks().lock().insert(init_task).unwrap();
+    let main_task = create_main_task(pid, &process, fs, file_table, vmar, elf_path, argv, envp)?;
+    process.tasks().lock().insert(main_task).unwrap();
 
-    Ok(init_proc)
+    Ok(process)
 }
 
 fn set_bootstrap_session_and_group(process: &Arc<Process>) {
@@ -132,11 +157,12 @@ fn set_bootstrap_session_and_group(process: &Arc<Process>) {
     pid_table.insert_process(process.pid(), process);
 }
 
-/// Creates the init task from the given executable file.
-fn create_init_task(
+/// Creates the main task of a process from the given executable file.
+fn create_main_task(
     tid: Tid,
     process: &Arc<Process>,
     fs: ThreadFsInfo,
+    file_table: Option<RwArc<FileTable>>,
     vmar: VmarHandle,
     elf_path: Path,
     argv: Vec<CString>,
@@ -162,9 +188,12 @@ fn create_init_task(
 
     let thread_name = ThreadName::new_from_executable_path(&elf_abs_path);
 
-    let thread_builder =
+    let mut thread_builder =
         PosixThreadBuilder::new(tid, thread_name, Box::new(user_ctx), credentials, vmar)
             .process(Arc::downgrade(process))
             .fs(Arc::new(fs));
+    if let Some(file_table) = file_table {
+        thread_builder = thread_builder.file_table(file_table);
+    }
     Ok(thread_builder.build())
 }
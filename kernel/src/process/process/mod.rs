@@ -26,7 +26,8 @@ use crate::{
     fs::cgroupfs::CgroupNode,
     prelude::*,
     process::{
-        UserNamespace, WaitOptions,
+        PidNamespace, UserNamespace, WaitOptions,
+        namespace::pid_ns::INIT_LOCAL_PID,
         signal::{Pollee, sig_queues::SigQueues},
         status::StopWaitStatus,
     },
@@ -42,6 +43,7 @@ mod process_group;
 mod session;
 mod terminal;
 mod timer_manager;
+mod user_mode_helper;
 
 use atomic_integer_wrapper::define_atomic_version_of_integer_like_type;
 pub use init_proc::spawn_init_process;
@@ -53,6 +55,7 @@ use ostd::{
 pub use process_group::ProcessGroup;
 pub use session::Session;
 pub use terminal::Terminal;
+pub use user_mode_helper::spawn_user_mode_helper;
 
 /// Process ID.
 pub type Pid = u32;
@@ -154,6 +157,8 @@ pub struct Process {
     // Namespaces
     /// The user namespace
     user_ns: Mutex<Arc<UserNamespace>>,
+    /// The PID namespace
+    pid_ns: Arc<PidNamespace>,
 }
 
 impl Drop for Process {
@@ -234,6 +239,7 @@ impl Process {
         oom_score_adj: i16,
         sig_dispositions: Arc<Mutex<SigDispositions>>,
         user_ns: Arc<UserNamespace>,
+        pid_ns: Arc<PidNamespace>,
     ) -> Arc<Self> {
         // SIGCHID does not interrupt pauser. Child process will
         // resume paused parent when doing exit.
@@ -266,6 +272,7 @@ impl Process {
             prof_clock,
             start_time: Jiffies::elapsed(),
             user_ns: Mutex::new(user_ns),
+            pid_ns,
         })
     }
 
@@ -333,6 +340,14 @@ impl Process {
         self.parent.pid() == 0
     }
 
+    /// Returns whether the process is the init process of its PID namespace.
+    ///
+    /// Note that this method also returns `true` for the init process of the initial PID
+    /// namespace (see [`Self::is_init_process`]).
+    pub fn is_pid_ns_init(&self) -> bool {
+        self.pid_ns.local_id(self.pid) == INIT_LOCAL_PID
+    }
+
     pub(super) fn children(&self) -> &Mutex<Option<BTreeMap<Pid, Arc<Process>>>> {
         &self.children
     }
@@ -636,11 +651,12 @@ impl Process {
     /// This method does not perform permission checks on user signals.
     /// Therefore, unless the caller can ensure that there are no permission issues,
     /// this method should be used to enqueue kernel signals or fault signals.
-    pub fn enqueue_signal(&self, signal: Box<dyn Signal>) {
+    pub fn enqueue_signal(&self, mut signal: Box<dyn Signal>) {
         if self.status.is_zombie() {
             return;
         }
 
+        signal.translate_to_pid_ns(&self.pid_ns);
         self.sig_queues.enqueue(signal);
 
         for task in self.tasks.lock().as_slice() {
@@ -784,6 +800,13 @@ impl Process {
         &self.user_ns
     }
 
+    /// Returns the PID namespace of the process.
+    ///
+    /// The PID namespace is determined when the process is created and never changes.
+    pub fn pid_ns(&self) -> &Arc<PidNamespace> {
+        &self.pid_ns
+    }
+
     // ******************* cgroup ********************
 
     /// Returns a RCU read guard to the cgroup of the process.
@@ -0,0 +1,95 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! This module defines functions related to spawning user mode helpers.
+//!
+//! A user mode helper is a user space program that is spawned by the kernel on its own behalf,
+//! such as the program that receives core dumps from a pipe.
+
+use core::sync::atomic::Ordering;
+
+use ostd::sync::RwArc;
+
+use super::{INIT_PROCESS_PID, Process, init_proc::create_process_from_executable};
+use crate::{
+    fs::file::{
+        FileLike,
+        file_table::{FdFlags, FileTable},
+    },
+    prelude::*,
+    process::{pid_table, rlimit::ResourceLimits},
+};
+
+/// Creates and schedules a user mode helper to run.
+///
+/// The helper runs with the root credentials in the initial namespaces, and becomes a child of
+/// the init process, which will reap it after it exits.
+///
+/// If `stdin` is specified, it is installed as the standard input of the helper. Otherwise, the
+/// helper starts with no open files.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.18/source/kernel/umh.c>
+pub fn spawn_user_mode_helper(
+    executable_path: &str,
+    argv: Vec<CString>,
+    envp: Vec<CString>,
+    resource_limits: ResourceLimits,
+    stdin: Option<Arc<dyn FileLike>>,
+) -> Result<Arc<Process>> {
+    let file_table = {
+        let mut file_table = FileTable::new();
+        if let Some(stdin) = stdin {
+            let fd = file_table.insert(stdin, FdFlags::empty());
+            debug_assert_eq!(i32::from(fd), 0);
+        }
+        RwArc::new(file_table)
+    };
+
+    let process = create_process_from_executable(
+        executable_path,
+        argv,
+        envp,
+        resource_limits,
+        Some(file_table),
+    )?;
+
+    set_parent_and_group(&process)?;
+
+    process.run();
+
+    Ok(process)
+}
+
+/// Makes the init process the parent of the helper, and puts the helper in the process group of
+/// the init process.
+fn set_parent_and_group(process: &Arc<Process>) -> Result<()> {
+    let init_process = pid_table::pid_table_mut()
+        .get_process(INIT_PROCESS_PID)
+        .ok_or_else(|| Error::with_message(Errno::ESRCH, "the init process does not exist"))?;
+
+    // Lock order: children of process -> parent of process
+    let mut children_mut = init_process.children().lock();
+    let Some(children_mut) = children_mut.as_mut() else {
+        return_errno_with_message!(Errno::ESRCH, "the init process has exited");
+    };
+    process.parent().lock().set_process(&init_process);
+
+    if init_process.has_child_subreaper.load(Ordering::Acquire) {
+        process.has_child_subreaper.store(true, Ordering::Release);
+    }
+
+    // Lock order: children of process -> PID table -> group of process -> group inner
+    let mut pid_table = pid_table::pid_table_mut();
+
+    let process_group = {
+        let process_group_mut = init_process.process_group.lock();
+        let process_group = process_group_mut.as_ref().unwrap();
+        process_group.lock().insert_process(process);
+        process_group.clone()
+    };
+    *process.process_group.lock() = Some(process_group);
+
+    children_mut.insert(process.pid(), process.clone());
+    pid_table.insert_process(process.pid(), process);
+
+    Ok(())
+}
@@ -7,6 +7,9 @@ use crate::{
     process::PidFile,
 };
 
+/// A filter that selects processes by their IDs.
+///
+/// The IDs are in the PID namespace of the current process.
 #[derive(Clone, Debug)]
 pub enum ProcessFilter {
     Any,
@@ -63,7 +66,8 @@ impl ProcessFilter {
         } else if wait_pid == 0 {
             // "wait for any child process whose process group ID is equal to that of the calling
             // process at the time of the call to `waitpid()`"
-            let pgid = current!().pgid();
+            let current = current!();
+            let pgid = current.pid_ns().local_id(current.pgid());
             Ok(ProcessFilter::WithPgid(pgid))
         } else {
             // "wait for the child whose process ID is equal to the value of `pid`"
@@ -12,10 +12,14 @@
 mod heap;
 mod init_stack;
 
-use core::ops::Range;
 #[cfg(target_arch = "riscv64")]
-use core::sync::atomic::{AtomicUsize, Ordering};
+use core::sync::atomic::AtomicUsize;
+use core::{
+    ops::Range,
+    sync::atomic::{AtomicU8, Ordering},
+};
 
+use atomic_integer_wrapper::define_atomic_version_of_integer_like_type;
 use ostd::task::disable_preempt;
 
 pub use self::{
@@ -77,6 +81,8 @@ pub struct ProcessVm {
     data_range: SpinLock<Range<Vaddr>>,
     /// The executable file.
     executable_file: Path,
+    /// Whether the process can be dumped.
+    dumpable: AtomicDumpable,
     /// The base address for vDSO segment
     #[cfg(target_arch = "riscv64")]
     vdso_base: AtomicUsize,
@@ -91,6 +97,7 @@ impl ProcessVm {
             code_range: SpinLock::new(0..0),
             data_range: SpinLock::new(0..0),
             executable_file,
+            dumpable: AtomicDumpable::new(Dumpable::User),
             #[cfg(target_arch = "riscv64")]
             vdso_base: AtomicUsize::new(0),
         }
@@ -104,6 +111,7 @@ impl ProcessVm {
             code_range: SpinLock::new(process_vm.code_range.lock().clone()),
             data_range: SpinLock::new(process_vm.data_range.lock().clone()),
             executable_file: process_vm.executable_file.clone(),
+            dumpable: AtomicDumpable::new(process_vm.dumpable()),
             #[cfg(target_arch = "riscv64")]
             vdso_base: AtomicUsize::new(process_vm.vdso_base.load(Ordering::Relaxed)),
         }
@@ -134,6 +142,16 @@ impl ProcessVm {
         &self.executable_file
     }
 
+    /// Returns whether the process can be dumped.
+    pub fn dumpable(&self) -> Dumpable {
+        self.dumpable.load(Ordering::Relaxed)
+    }
+
+    /// Sets whether the process can be dumped.
+    pub fn set_dumpable(&self, dumpable: Dumpable) {
+        self.dumpable.store(dumpable, Ordering::Relaxed);
+    }
+
     /// Maps and writes the initial portion of the main stack of a process.
     pub(super) fn map_and_write_init_stack(
         &self,
@@ -179,6 +197,33 @@ impl ProcessVm {
     }
 }
 
+/// The dumpable attribute of a process.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/linux/sched/coredump.h>
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
+pub enum Dumpable {
+    /// The process is not dumpable (e.g., after executing a setuid program).
+    Disable = 0,
+    /// The process is dumped as the user of the process.
+    User = 1,
+    /// The process is dumped as root.
+    ///
+    /// This value cannot be set via `prctl(PR_SET_DUMPABLE)`.
+    Root = 2,
+}
+
+define_atomic_version_of_integer_like_type!(Dumpable, try_from = true, {
+    #[derive(Debug)]
+    struct AtomicDumpable(AtomicU8);
+});
+
+impl From<Dumpable> for u8 {
+    fn from(value: Dumpable) -> Self {
+        value as _
+    }
+}
+
 /// A guard to the [`Vmar`] used by a process.
 ///
 /// It is bound to a [`Process`] and can only be obtained from
@@ -223,3 +223,17 @@ pub(super) fn new_resource_limits_for_init() -> ResourceLimits {
         .set_raw_rlimit_unchecked(raw_rlimit);
     resource_limits
 }
+
+/// Creates resource limits for the user mode helper that receives core dumps.
+///
+/// The core file size limit is set to one, which prevents the helper from being dumped into
+/// another pipe recursively.
+//
+// Reference: <https://elixir.bootlin.com/linux/v6.18/source/fs/coredump.c>
+pub(super) fn new_resource_limits_for_core_dump_helper() -> ResourceLimits {
+    let resource_limits = new_resource_limits_for_init();
+    resource_limits
+        .get_rlimit(ResourceType::RLIMIT_CORE)
+        .set_raw_rlimit_unchecked(RawRLimit64 { cur: 1, max: 1 });
+    resource_limits
+}
@@ -10,7 +10,7 @@ use super::sig_num::SigNum;
 use crate::{
     arch::cpu::SigContext,
     prelude::*,
-    process::{Pid, Uid},
+    process::{Pid, PidNamespace, Uid},
 };
 
 pub type sigset_t = u64;
@@ -64,8 +64,16 @@ impl siginfo_t {
         self.siginfo_fields.common_mut().first = pid_uid;
     }
 
-    pub fn set_pid_uid_by(&mut self, ctx: &Context) {
-        self.set_pid_uid(ctx.process.pid(), ctx.posix_thread.credentials().ruid());
+    /// Sets the PID and UID of the current thread as the sender.
+    ///
+    /// The PID is translated into `pid_ns`, which should be the PID namespace of the receiver.
+    pub fn set_pid_uid_by(&mut self, ctx: &Context, pid_ns: &PidNamespace) {
+        let pid = pid_ns.local_id(ctx.process.pid());
+        self.set_pid_uid(pid, ctx.posix_thread.credentials().ruid());
+    }
+
+    pub fn set_value(&mut self, value: sigval_t) {
+        *self.siginfo_fields.common_mut().second.value_mut() = value;
     }
 
     pub fn set_status(&mut self, status: i32) {
@@ -77,6 +85,14 @@ impl siginfo_t {
             .status_mut() = status;
     }
 
+    pub fn set_sigsys(&mut self, call_addr: Vaddr, syscall: i32, arch: u32) {
+        *self.siginfo_fields.sigsys_mut() = siginfo_sigsys_t {
+            call_addr,
+            syscall,
+            arch,
+        };
+    }
+
     pub fn si_addr(&self) -> Vaddr {
         self.siginfo_fields.sigfault().addr
     }
@@ -89,6 +105,7 @@ union siginfo_fields_t {
     bytes: [u8; 128 - size_of::<i32>() * 4],
     common: siginfo_common_t,
     sigfault: siginfo_sigfault_t,
+    sigsys: siginfo_sigsys_t,
 }
 
 impl Default for siginfo_fields_t {
@@ -192,6 +209,14 @@ union siginfo_addr_bnd_t {
     upper: Vaddr, // *const c_void,
 }
 
+#[repr(C)]
+#[derive(Clone, Copy, Pod)]
+struct siginfo_sigsys_t {
+    call_addr: Vaddr, // *const c_void
+    syscall: i32,
+    arch: u32,
+}
+
 /// Reference: <https://elixir.bootlin.com/linux/v6.15.7/source/include/uapi/asm-generic/ucontext.h#L5>
 #[cfg(target_arch = "x86_64")]
 #[repr(C)]
@@ -35,8 +35,12 @@ use crate::{
     prelude::*,
     process::{
         TermStatus,
+        coredump::do_coredump,
         posix_thread::{ContextPthreadAdminApi, do_exit_group, ptrace::PtraceStopResult},
-        signal::{c_types::stack_t, constants::SIGKILL},
+        signal::{
+            c_types::stack_t,
+            constants::{SIGKILL, SIGSTOP},
+        },
     },
 };
 
@@ -178,17 +182,34 @@ pub fn handle_pending_signal(user_ctx: &mut UserContext, ctx: &Context) {
                 do_exit_group(TermStatus::Killed(SIGSEGV), ctx, user_ctx);
             }
         }
-        SigAction::Dfl if ctx.process.is_init_process() => {
+        SigAction::Dfl
+            if ctx.process.is_init_process()
+                || (ctx.process.is_pid_ns_init() && sig_num != SIGKILL && sig_num != SIGSTOP) =>
+        {
             // From Linux man pages "kill(2)":
             // "The only signals that can be sent to process ID 1, the init process, are those for
             // which init has explicitly installed signal handlers."
+            //
+            // The init process of a non-initial PID namespace can still be killed or stopped by
+            // processes in the ancestor namespaces. Such signals sent from the namespace itself
+            // are dropped by the sender.
         }
         SigAction::Dfl => {
             let sig_default_action = SigDefaultAction::from_signum(sig_num);
             debug!("sig_default_action = {:?}", sig_default_action);
 
             match sig_default_action {
-                SigDefaultAction::Core | SigDefaultAction::Term => {
+                SigDefaultAction::Core => {
+                    warn!(
+                        "PID {}: terminating on signal {} and dumping core",
+                        ctx.process.pid(),
+                        sig_num.sig_name()
+                    );
+                    // The signal terminates the current process after dumping the core.
+                    let term_status = do_coredump(sig_num, signal.to_info(), ctx, user_ctx);
+                    do_exit_group(term_status, ctx, user_ctx);
+                }
+                SigDefaultAction::Term => {
                     warn!(
                         "PID {}: terminating on signal {}",
                         ctx.process.pid(),
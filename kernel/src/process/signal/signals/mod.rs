@@ -8,10 +8,17 @@ pub mod user;
 use core::{any::Any, fmt::Debug};
 
 use super::{c_types::siginfo_t, sig_num::SigNum};
+use crate::process::PidNamespace;
 
 pub trait Signal: Send + Sync + Debug + Any {
     /// Returns the number of the signal.
     fn num(&self) -> SigNum;
     /// Returns the siginfo_t that gives more details about a signal.
     fn to_info(&self) -> siginfo_t;
+    /// Translates the PID of the sender into the PID namespace of the receiver.
+    ///
+    /// This is called when the signal is sent to the receiver. Like Linux, the PID becomes zero
+    /// if the sender is not visible in the receiver's namespace. Translating a signal again has
+    /// no effect, so a signal that is requeued keeps the PID seen by its first receiver.
+    fn translate_to_pid_ns(&mut self, _pid_ns: &PidNamespace) {}
 }
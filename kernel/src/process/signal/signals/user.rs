@@ -6,7 +6,7 @@ use super::Signal;
 use crate::{
     context::Context,
     process::{
-        Pid, Uid,
+        Pid, PidNamespace, Uid,
         signal::{
             c_types::siginfo_t,
             constants::{SI_QUEUE, SI_TKILL, SI_USER},
@@ -18,11 +18,20 @@ use crate::{
 #[derive(Clone, Copy, Debug)]
 pub struct UserSignal {
     num: SigNum,
-    pid: Pid,
+    pid: SenderPid,
     uid: Uid,
     kind: UserSignalKind,
 }
 
+/// The PID of the sender of a [`UserSignal`].
+#[derive(Clone, Copy, Debug)]
+enum SenderPid {
+    /// The global PID, before the signal is sent to the receiver.
+    Global(Pid),
+    /// The PID in the PID namespace of the receiver, or zero if the sender is not visible there.
+    Local(Pid),
+}
+
 #[derive(Clone, Copy, Debug)]
 pub enum UserSignalKind {
     Kill,
@@ -31,26 +40,23 @@ pub enum UserSignalKind {
 }
 
 impl UserSignal {
+    /// Creates a user signal sent by the process whose global PID is `pid`.
     pub fn new(num: SigNum, kind: UserSignalKind, pid: Pid, uid: Uid) -> Self {
         Self {
             num,
             kind,
-            pid,
+            pid: SenderPid::Global(pid),
             uid,
         }
     }
 
     pub fn new_kill(num: SigNum, ctx: &Context) -> Self {
-        Self {
+        Self::new(
             num,
-            kind: UserSignalKind::Kill,
-            pid: ctx.process.pid(),
-            uid: ctx.posix_thread.credentials().ruid(),
-        }
-    }
-
-    pub fn pid(&self) -> Pid {
-        self.pid
+            UserSignalKind::Kill,
+            ctx.process.pid(),
+            ctx.posix_thread.credentials().ruid(),
+        )
     }
 
     pub fn kind(&self) -> UserSignalKind {
@@ -70,9 +76,21 @@ impl Signal for UserSignal {
             UserSignalKind::Sigqueue => SI_QUEUE,
         };
 
+        let pid = match self.pid {
+            SenderPid::Local(pid) => pid,
+            // The signal has not been sent to a receiver yet. This should not happen.
+            SenderPid::Global(_) => 0,
+        };
+
         let mut info = siginfo_t::new(self.num, code);
-        info.set_pid_uid(self.pid, self.uid);
+        info.set_pid_uid(pid, self.uid);
 
         info
     }
+
+    fn translate_to_pid_ns(&mut self, pid_ns: &PidNamespace) {
+        if let SenderPid::Global(pid) = self.pid {
+            self.pid = SenderPid::Local(pid_ns.local_id(pid));
+        }
+    }
 }
@@ -6,6 +6,8 @@ use super::signal::sig_num::SigNum;
 pub enum TermStatus {
     Exited(u8),
     Killed(SigNum),
+    /// The process is killed by a signal and a core dump is produced.
+    CoreDumped(SigNum),
 }
 
 impl TermStatus {
@@ -14,6 +16,7 @@ impl TermStatus {
         match self {
             TermStatus::Exited(status) => (*status as u32) << 8,
             TermStatus::Killed(signum) => signum.as_u8() as u32,
+            TermStatus::CoreDumped(signum) => signum.as_u8() as u32 | 0x80,
         }
     }
 }
@@ -105,11 +105,19 @@ pub fn do_wait(
     Ok(zombie_child)
 }
 
-fn wait_filter(child_pid: Pid, child: &Arc<Process>, child_filter: &ProcessFilter) -> bool {
+fn wait_filter(
+    child_pid: Pid,
+    child: &Arc<Process>,
+    child_filter: &ProcessFilter,
+    ctx: &Context,
+) -> bool {
+    // The IDs in the filter are in the PID namespace of the current process.
+    let pid_ns = ctx.process.pid_ns();
+
     match &child_filter {
         ProcessFilter::Any => true,
-        ProcessFilter::WithPid(pid) => child_pid == *pid,
-        ProcessFilter::WithPgid(pgid) => child.pgid() == *pgid,
+        ProcessFilter::WithPid(pid) => pid_ns.local_id(child_pid) == *pid,
+        ProcessFilter::WithPgid(pgid) => pid_ns.local_id(child.pgid()) == *pgid,
         ProcessFilter::WithPidfd(pid_file) => match pid_file.process_opt() {
             Some(process) => Arc::ptr_eq(&process, child),
             None => false,
@@ -201,7 +209,7 @@ fn try_wait_tracees(
         let Some(process) = tracee.weak_process().upgrade() else {
             continue;
         };
-        if !wait_filter(tracee.tid(), &process, child_filter) {
+        if !wait_filter(tracee.tid(), &process, child_filter, ctx) {
             continue;
         }
 
@@ -296,7 +304,7 @@ fn try_wait_children(
     let mut fallback_result = WaitResult::NoMatch;
 
     for child in children_mut.values() {
-        if !wait_filter(child.pid(), child, child_filter) {
+        if !wait_filter(child.pid(), child, child_filter, ctx) {
             continue;
         }
 
@@ -335,6 +343,24 @@ fn try_wait_children(
     fallback_result
 }
 
+/// Reaps all the zombie children of the process.
+///
+/// This is used by the init process of a PID namespace to reap the processes in the namespace
+/// before it exits. Unlike `wait`, the parent is not notified of the reaped children.
+pub(super) fn reap_zombie_children(process: &Process) {
+    let mut children_lock = process.children().lock();
+    let children_mut = children_lock.as_mut().unwrap();
+
+    let zombie_pids: Vec<Pid> = children_mut
+        .iter()
+        .filter(|(_, child)| child.status().is_zombie())
+        .map(|(pid, _)| *pid)
+        .collect();
+    for pid in zombie_pids {
+        reap_zombie_child(pid, children_mut, process.reaped_children_stats());
+    }
+}
+
 /// Free zombie child with `child_pid`, returns the exit code of child process.
 fn reap_zombie_child(
     child_pid: Pid,
@@ -62,6 +62,7 @@ use super::{
     getxattr::{sys_fgetxattr, sys_getxattr, sys_lgetxattr},
     impl_syscall_nums_and_dispatch_fn,
     inotify::{sys_inotify_add_watch, sys_inotify_init, sys_inotify_init1, sys_inotify_rm_watch},
+    io_uring::{sys_io_uring_enter, sys_io_uring_register, sys_io_uring_setup},
     ioctl::sys_ioctl,
     kill::sys_kill,
     link::{sys_link, sys_linkat},
@@ -75,7 +76,15 @@ use super::{
     mmap::sys_mmap,
     mount::sys_mount,
     mprotect::sys_mprotect,
+    mqueue::{
+        sys_mq_getsetattr, sys_mq_notify, sys_mq_open, sys_mq_timedreceive, sys_mq_timedsend,
+        sys_mq_unlink,
+    },
     mremap::sys_mremap,
+    msgctl::sys_msgctl,
+    msgget::sys_msgget,
+    msgrcv::sys_msgrcv,
+    msgsnd::sys_msgsnd,
     msync::sys_msync,
     munmap::sys_munmap,
     nanosleep::{sys_clock_nanosleep, sys_nanosleep},
@@ -121,6 +130,7 @@ use super::{
     sched_setparam::sys_sched_setparam,
     sched_setscheduler::sys_sched_setscheduler,
     sched_yield::sys_sched_yield,
+    seccomp::sys_seccomp,
     select::sys_select,
     semctl::sys_semctl,
     semget::sys_semget,
@@ -150,6 +160,10 @@ use super::{
     setsockopt::sys_setsockopt,
     setuid::sys_setuid,
     setxattr::{sys_fsetxattr, sys_lsetxattr, sys_setxattr},
+    shmat::sys_shmat,
+    shmctl::sys_shmctl,
+    shmdt::sys_shmdt,
+    shmget::sys_shmget,
     shutdown::sys_shutdown,
     sigaltstack::sys_sigaltstack,
     signalfd::{sys_signalfd, sys_signalfd4},
@@ -209,6 +223,9 @@ impl_syscall_nums_and_dispatch_fn! {
     SYS_MSYNC = 26             => sys_msync(args[..3]);
     SYS_SCHED_YIELD = 24       => sys_sched_yield(args[..0]);
     SYS_MADVISE = 28           => sys_madvise(args[..3]);
+    SYS_SHMGET = 29            => sys_shmget(args[..3]);
+    SYS_SHMAT = 30             => sys_shmat(args[..3]);
+    SYS_SHMCTL = 31            => sys_shmctl(args[..3]);
     SYS_DUP = 32               => sys_dup(args[..1]);
     SYS_DUP2 = 33              => sys_dup2(args[..2]);
     SYS_PAUSE = 34             => sys_pause(args[..0]);
@@ -244,6 +261,11 @@ impl_syscall_nums_and_dispatch_fn! {
     SYS_SEMGET = 64            => sys_semget(args[..3]);
     SYS_SEMOP = 65             => sys_semop(args[..3]);
     SYS_SEMCTL = 66            => sys_semctl(args[..4]);
+    SYS_SHMDT = 67             => sys_shmdt(args[..1]);
+    SYS_MSGGET = 68            => sys_msgget(args[..2]);
+    SYS_MSGSND = 69            => sys_msgsnd(args[..4]);
+    SYS_MSGRCV = 70            => sys_msgrcv(args[..5]);
+    SYS_MSGCTL = 71            => sys_msgctl(args[..3]);
     SYS_FCNTL = 72             => sys_fcntl(args[..3]);
     SYS_FLOCK = 73             => sys_flock(args[..2]);
     SYS_FSYNC = 74             => sys_fsync(args[..1]);
@@ -359,6 +381,12 @@ impl_syscall_nums_and_dispatch_fn! {
     SYS_EPOLL_CTL = 233        => sys_epoll_ctl(args[..4]);
     SYS_TGKILL = 234           => sys_tgkill(args[..3]);
     SYS_UTIMES = 235           => sys_utimes(args[..2]);
+    SYS_MQ_OPEN = 240          => sys_mq_open(args[..4]);
+    SYS_MQ_UNLINK = 241        => sys_mq_unlink(args[..1]);
+    SYS_MQ_TIMEDSEND = 242     => sys_mq_timedsend(args[..5]);
+    SYS_MQ_TIMEDRECEIVE = 243  => sys_mq_timedreceive(args[..5]);
+    SYS_MQ_NOTIFY = 244        => sys_mq_notify(args[..2]);
+    SYS_MQ_GETSETATTR = 245    => sys_mq_getsetattr(args[..3]);
     SYS_WAITID = 247           => sys_waitid(args[..5]);
     SYS_IOPRIO_SET = 251       => sys_ioprio_set(args[..3]);
     SYS_IOPRIO_GET = 252       => sys_ioprio_get(args[..2]);
@@ -407,6 +435,7 @@ impl_syscall_nums_and_dispatch_fn! {
     SYS_SCHED_SETATTR = 314    => sys_sched_setattr(args[..3]);
     SYS_SCHED_GETATTR = 315    => sys_sched_getattr(args[..4]);
     SYS_RENAMEAT2 = 316        => sys_renameat2(args[..5]);
+    SYS_SECCOMP = 317          => sys_seccomp(args[..3]);
     SYS_GETRANDOM = 318        => sys_getrandom(args[..3]);
     SYS_MEMFD_CREATE = 319     => sys_memfd_create(args[..2]);
     SYS_EXECVEAT = 322         => sys_execveat(args[..5], &mut user_ctx);
@@ -414,6 +443,9 @@ impl_syscall_nums_and_dispatch_fn! {
     SYS_PWRITEV2 = 328         => sys_pwritev2(args[..6]);
     SYS_STATX = 332            => sys_statx(args[..5]);
     SYS_PIDFD_SEND_SIGNAL = 424 => sys_pidfd_send_signal(args[..4]);
+    SYS_IO_URING_SETUP = 425   => sys_io_uring_setup(args[..2]);
+    SYS_IO_URING_ENTER = 426   => sys_io_uring_enter(args[..6]);
+    SYS_IO_URING_REGISTER = 427 => sys_io_uring_register(args[..4]);
     SYS_PIDFD_OPEN = 434       => sys_pidfd_open(args[..2]);
     SYS_CLONE3 = 435           => sys_clone3(args[..2], &user_ctx);
     SYS_CLOSE_RANGE = 436      => sys_close_range(args[..3]);
@@ -112,22 +112,29 @@ pub enum DynamicClockType {
 pub fn read_clock(clockid: clockid_t, ctx: &Context) -> Result<Duration> {
     if clockid >= 0 {
         let clock_id = ClockId::try_from(clockid)?;
-        match clock_id {
-            ClockId::CLOCK_REALTIME => Ok(RealTimeClock::get().read_time()),
-            ClockId::CLOCK_MONOTONIC => Ok(MonotonicClock::get().read_time()),
-            ClockId::CLOCK_MONOTONIC_RAW => Ok(MonotonicRawClock::get().read_time()),
-            ClockId::CLOCK_REALTIME_COARSE => Ok(RealTimeCoarseClock::get().read_time()),
-            ClockId::CLOCK_MONOTONIC_COARSE => Ok(MonotonicCoarseClock::get().read_time()),
-            ClockId::CLOCK_BOOTTIME => Ok(BootTimeClock::get().read_time()),
-            ClockId::CLOCK_PROCESS_CPUTIME_ID => Ok(ctx.process.prof_clock().read_time()),
-            ClockId::CLOCK_THREAD_CPUTIME_ID => Ok(ctx.posix_thread.prof_clock().read_time()),
-        }
+        let time = match clock_id {
+            ClockId::CLOCK_REALTIME => RealTimeClock::get().read_time(),
+            ClockId::CLOCK_MONOTONIC => MonotonicClock::get().read_time(),
+            ClockId::CLOCK_MONOTONIC_RAW => MonotonicRawClock::get().read_time(),
+            ClockId::CLOCK_REALTIME_COARSE => RealTimeCoarseClock::get().read_time(),
+            ClockId::CLOCK_MONOTONIC_COARSE => MonotonicCoarseClock::get().read_time(),
+            ClockId::CLOCK_BOOTTIME => BootTimeClock::get().read_time(),
+            ClockId::CLOCK_PROCESS_CPUTIME_ID => ctx.process.prof_clock().read_time(),
+            ClockId::CLOCK_THREAD_CPUTIME_ID => ctx.posix_thread.prof_clock().read_time(),
+        };
+
+        // Some clocks are shifted in the time namespace of the current thread.
+        let ns_proxy = ctx.thread_local.borrow_ns_proxy();
+        Ok(ns_proxy.unwrap().time_ns().to_ns_time(clock_id, time))
     } else {
         let dynamic_clockid_info = DynamicClockIdInfo::try_from(clockid)?;
         match dynamic_clockid_info {
             DynamicClockIdInfo::Pid(pid, clock_type) => {
-                let process = pid_table::pid_table_mut()
-                    .get_process(pid)
+                let process = ctx
+                    .process
+                    .pid_ns()
+                    .global_id(pid)
+                    .and_then(|pid| pid_table::pid_table_mut().get_process(pid))
                     .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid clock ID"))?;
                 match clock_type {
                     DynamicClockType::Profiling => Ok(process.prof_clock().read_time()),
@@ -137,8 +144,11 @@ pub fn read_clock(clockid: clockid_t, ctx: &Context) -> Result<Duration> {
                 }
             }
             DynamicClockIdInfo::Tid(tid, clock_type) => {
-                let thread = pid_table::pid_table_mut()
-                    .get_thread(tid)
+                let thread = ctx
+                    .process
+                    .pid_ns()
+                    .global_id(tid)
+                    .and_then(|tid| pid_table::pid_table_mut().get_thread(tid))
                     .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid clock ID"))?;
                 let posix_thread = thread.as_posix_thread().unwrap();
                 match clock_type {
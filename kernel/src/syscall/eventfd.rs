@@ -67,7 +67,8 @@ bitflags! {
     }
 }
 
-struct EventFile {
+/// An eventfd file.
+pub struct EventFile {
     counter: Mutex<u64>,
     pollee: Pollee,
     flags: Mutex<Flags>,
@@ -159,6 +160,13 @@ impl EventFile {
 
         return_errno_with_message!(Errno::EINVAL, "new value exceeds MAX_COUNTER_VALUE");
     }
+
+    /// Signals the eventfd by adding one to the counter.
+    ///
+    /// Like Linux's `eventfd_signal`, the signal is lost if the counter would overflow.
+    pub fn signal(&self) {
+        let _ = self.add_counter_val(1);
+    }
 }
 
 impl Pollable for EventFile {
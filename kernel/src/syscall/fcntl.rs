@@ -133,6 +133,7 @@ fn handle_getown(fd: FileDesc, ctx: &Context) -> Result<SyscallReturn> {
     let mut file_table = ctx.thread_local.borrow_file_table_mut();
     file_table.read_with(|inner| {
         let pid = inner.get_entry(fd)?.owner().unwrap_or(0);
+        let pid = ctx.process.pid_ns().local_id(pid);
         Ok(SyscallReturn::Return(pid as _))
     })
 }
@@ -150,8 +151,10 @@ fn handle_setown(fd: FileDesc, arg: u64, ctx: &Context) -> Result<SyscallReturn>
         None
     } else {
         Some(
-            pid_table::pid_table_mut()
-                .get_process(pid)
+            ctx.process
+                .pid_ns()
+                .global_id(pid)
+                .and_then(|pid| pid_table::pid_table_mut().get_process(pid))
                 .ok_or(Error::with_message(
                     Errno::ESRCH,
                     "cannot set_owner with an invalid pid",
@@ -27,13 +27,13 @@ impl IoPrioWho {
             Self::IOPRIO_WHO_PROCESS => {
                 // In Linux, IOPRIO_WHO_PROCESS identifies a single thread by its TID
                 let target_tid = if who == 0 {
-                    ctx.posix_thread.tid()
+                    Some(ctx.posix_thread.tid())
                 } else {
-                    who
+                    ctx.process.pid_ns().global_id(who)
                 };
 
-                let thread = crate::process::pid_table::pid_table_mut()
-                    .get_thread(target_tid)
+                let thread = target_tid
+                    .and_then(|tid| crate::process::pid_table::pid_table_mut().get_thread(tid))
                     .ok_or_else(|| Error::new(Errno::ESRCH))?;
                 Ok(Self::Thread(thread))
             }
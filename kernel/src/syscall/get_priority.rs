@@ -80,12 +80,18 @@ impl PriorityTarget {
     pub(super) fn new(which: i32, who: u32, ctx: &Context) -> Result<Self> {
         let which = Which::try_from(which)
             .map_err(|_| Error::with_message(Errno::EINVAL, "invalid which value"))?;
+        let global_id = |id: u32| {
+            ctx.process
+                .pid_ns()
+                .global_id(id)
+                .ok_or_else(|| Error::new(Errno::ESRCH))
+        };
         Ok(match which {
             Which::PRIO_PROCESS => {
                 let pid = if who == 0 {
                     ctx.process.pid()
                 } else {
-                    who as Pid
+                    global_id(who as Pid)?
                 };
                 Self::Process(pid)
             }
@@ -93,7 +99,7 @@ impl PriorityTarget {
                 let pgid = if who == 0 {
                     ctx.process.pgid()
                 } else {
-                    who as Pgid
+                    global_id(who as Pgid)?
                 };
                 Self::ProcessGroup(pgid)
             }
@@ -9,17 +9,21 @@ use crate::{
 pub fn sys_getpgid(pid: Pid, ctx: &Context) -> Result<SyscallReturn> {
     debug!("pid = {}", pid);
 
+    let pid_ns = ctx.process.pid_ns();
+
     // The documentation quoted below is from
     // <https://www.man7.org/linux/man-pages/man2/getpgid.2.html>.
 
     // "If `pid` is equal to 0, getpgid() shall return the process group ID of the calling
     // process."
     if pid == 0 {
-        return Ok(SyscallReturn::Return(ctx.process.pgid() as _));
+        let pgid = pid_ns.local_id(ctx.process.pgid());
+        return Ok(SyscallReturn::Return(pgid as _));
     }
 
-    let process = pid_table::pid_table_mut()
-        .get_process(pid)
+    let process = pid_ns
+        .global_id(pid)
+        .and_then(|pid| pid_table::pid_table_mut().get_process(pid))
         .ok_or(Error::with_message(
             Errno::ESRCH,
             "the process to get the PGID does not exist",
@@ -29,5 +33,5 @@ pub fn sys_getpgid(pid: Pid, ctx: &Context) -> Result<SyscallReturn> {
     // session than the current process. Linux does not perform this check by default, but some
     // strict security policies (e.g. SELinux) may do so.
 
-    Ok(SyscallReturn::Return(process.pgid() as _))
+    Ok(SyscallReturn::Return(pid_ns.local_id(process.pgid()) as _))
 }
@@ -4,7 +4,7 @@ use super::SyscallReturn;
 use crate::prelude::*;
 
 pub fn sys_getpid(ctx: &Context) -> Result<SyscallReturn> {
-    let pid = ctx.process.pid();
+    let pid = ctx.process.pid_ns().local_id(ctx.process.pid());
     debug!("pid = {}", pid);
     Ok(SyscallReturn::Return(pid as _))
 }
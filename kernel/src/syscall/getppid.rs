@@ -4,5 +4,8 @@ use super::SyscallReturn;
 use crate::prelude::*;
 
 pub fn sys_getppid(ctx: &Context) -> Result<SyscallReturn> {
-    Ok(SyscallReturn::Return(ctx.process.parent().pid() as _))
+    // The parent process is invisible (and zero is returned) if the current process is the init
+    // process of a PID namespace.
+    let ppid = ctx.process.pid_ns().local_id(ctx.process.parent().pid());
+    Ok(SyscallReturn::Return(ppid as _))
 }
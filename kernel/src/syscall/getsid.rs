@@ -9,16 +9,20 @@ use crate::{
 pub fn sys_getsid(pid: Pid, ctx: &Context) -> Result<SyscallReturn> {
     debug!("pid = {}", pid);
 
+    let pid_ns = ctx.process.pid_ns();
+
     // The documentation quoted below is from
     // <https://www.man7.org/linux/man-pages/man2/getsid.2.html>.
 
     // "If `pid` is 0, getsid() returns the session ID of the calling process."
     if pid == 0 {
-        return Ok(SyscallReturn::Return(ctx.process.sid() as _));
+        let sid = pid_ns.local_id(ctx.process.sid());
+        return Ok(SyscallReturn::Return(sid as _));
     }
 
-    let process = pid_table::pid_table_mut()
-        .get_process(pid)
+    let process = pid_ns
+        .global_id(pid)
+        .and_then(|pid| pid_table::pid_table_mut().get_process(pid))
         .ok_or(Error::with_message(
             Errno::ESRCH,
             "the process to get the SID does not exist",
@@ -28,5 +32,5 @@ pub fn sys_getsid(pid: Pid, ctx: &Context) -> Result<SyscallReturn> {
     // session than the current process. Linux does not perform this check by default, but some
     // strict security policies (e.g. SELinux) may do so.
 
-    Ok(SyscallReturn::Return(process.sid() as _))
+    Ok(SyscallReturn::Return(pid_ns.local_id(process.sid()) as _))
 }
@@ -0,0 +1,281 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use ostd::mm::VmIo;
+
+use super::SyscallReturn;
+use crate::{
+    events::{IoUringEnterFlags, IoUringFile, IoUringOp, IoUringParams},
+    fs::file::{
+        FileLike,
+        file_table::{FdFlags, RawFileDesc, get_file_fast},
+    },
+    prelude::*,
+    process::{posix_thread::ContextPthreadAdminApi, signal::sig_mask::SigMask},
+};
+
+pub fn sys_io_uring_setup(
+    entries: u32,
+    params_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let mut params = ctx.user_space().read_val::<IoUringParams>(params_addr)?;
+    debug!("entries = {}, params = {:?}", entries, params);
+
+    if params.resv.iter().any(|&resv| resv != 0) {
+        return_errno_with_message!(Errno::EINVAL, "the reserved fields are not zero");
+    }
+
+    let io_uring = IoUringFile::new(entries, &mut params)?;
+    ctx.user_space().write_val(params_addr, &params)?;
+
+    // Like Linux, the file descriptor is always close-on-exec.
+    let file_table = ctx.thread_local.borrow_file_table();
+    let fd = file_table
+        .unwrap()
+        .write()
+        .insert(io_uring, FdFlags::CLOEXEC);
+
+    Ok(SyscallReturn::Return(fd.into()))
+}
+
+pub fn sys_io_uring_enter(
+    fd: RawFileDesc,
+    to_submit: u32,
+    min_complete: u32,
+    flags: u32,
+    sigmask_addr: Vaddr,
+    sigmask_size: usize,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!(
+        "fd = {}, to_submit = {}, min_complete = {}, flags = {:#x}, sigmask_addr = {:#x}, sigmask_size = {}",
+        fd, to_submit, min_complete, flags, sigmask_addr, sigmask_size
+    );
+
+    let flags = IoUringEnterFlags::from_bits(flags)
+        .ok_or_else(|| Error::with_message(Errno::EINVAL, "the enter flags are invalid"))?;
+
+    let file = get_io_uring_file(fd, ctx)?;
+    let io_uring = file.downcast_ref::<IoUringFile>().unwrap();
+
+    if flags.contains(IoUringEnterFlags::GETEVENTS) && sigmask_addr != 0 {
+        if sigmask_size != size_of::<SigMask>() {
+            return_errno_with_message!(Errno::EINVAL, "invalid sigmask size");
+        }
+
+        let sigmask = ctx.user_space().read_val::<SigMask>(sigmask_addr)?;
+        ctx.save_and_set_sig_mask(sigmask);
+    }
+
+    let num_submitted = io_uring.enter(to_submit, min_complete, flags, ctx)?;
+
+    Ok(SyscallReturn::Return(num_submitted as _))
+}
+
+pub fn sys_io_uring_register(
+    fd: RawFileDesc,
+    opcode: u32,
+    arg: Vaddr,
+    nr_args: u32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!(
+        "fd = {}, opcode = {}, arg = {:#x}, nr_args = {}",
+        fd, opcode, arg, nr_args
+    );
+
+    let file = get_io_uring_file(fd, ctx)?;
+    let io_uring = file.downcast_ref::<IoUringFile>().unwrap();
+
+    let opcode = RegisterOp::try_from(opcode)
+        .map_err(|_| Error::with_message(Errno::EINVAL, "the register opcode is not supported"))?;
+    let user_space = ctx.user_space();
+
+    let res = match opcode {
+        RegisterOp::RegisterBuffers => {
+            let buffers = read_array::<c_iovec>(arg, nr_args, ctx)?
+                .into_iter()
+                .map(|iovec| (iovec.iov_base, iovec.iov_len))
+                .collect();
+            io_uring.register_buffers(buffers)?;
+            0
+        }
+        RegisterOp::UnregisterBuffers => {
+            check_no_args(arg, nr_args)?;
+            io_uring.unregister_buffers()?;
+            0
+        }
+        RegisterOp::RegisterFiles => {
+            let fds = read_array::<RawFileDesc>(arg, nr_args, ctx)?;
+            io_uring.register_files(&fds, ctx)?;
+            0
+        }
+        RegisterOp::UnregisterFiles => {
+            check_no_args(arg, nr_args)?;
+            io_uring.unregister_files()?;
+            0
+        }
+        RegisterOp::RegisterFilesUpdate => {
+            let update = user_space.read_val::<c_io_uring_files_update>(arg)?;
+            if update.resv != 0 {
+                return_errno_with_message!(Errno::EINVAL, "the reserved field is not zero");
+            }
+            let fds = read_array::<RawFileDesc>(update.fds as Vaddr, nr_args, ctx)?;
+            io_uring.update_files(update.offset, &fds, ctx)?
+        }
+        RegisterOp::RegisterEventfd | RegisterOp::RegisterEventfdAsync => {
+            if nr_args != 1 {
+                return_errno_with_message!(Errno::EINVAL, "the number of arguments is not one");
+            }
+            let eventfd = user_space.read_val::<RawFileDesc>(arg)?;
+            let is_async_only = opcode == RegisterOp::RegisterEventfdAsync;
+            io_uring.register_eventfd(eventfd, is_async_only, ctx)?;
+            0
+        }
+        RegisterOp::UnregisterEventfd => {
+            check_no_args(arg, nr_args)?;
+            io_uring.unregister_eventfd()?;
+            0
+        }
+        RegisterOp::RegisterProbe => {
+            write_probe(arg, nr_args, ctx)?;
+            0
+        }
+    };
+
+    Ok(SyscallReturn::Return(res as _))
+}
+
+fn get_io_uring_file(fd: RawFileDesc, ctx: &Context) -> Result<Arc<dyn FileLike>> {
+    let mut file_table = ctx.thread_local.borrow_file_table_mut();
+    let file = get_file_fast!(&mut file_table, fd.try_into()?).into_owned();
+
+    if file.downcast_ref::<IoUringFile>().is_none() {
+        return_errno_with_message!(Errno::EOPNOTSUPP, "the file is not an io_uring instance");
+    }
+
+    Ok(file)
+}
+
+fn check_no_args(arg: Vaddr, nr_args: u32) -> Result<()> {
+    if arg != 0 || nr_args != 0 {
+        return_errno_with_message!(Errno::EINVAL, "the arguments are not empty");
+    }
+    Ok(())
+}
+
+fn read_array<T: Pod>(addr: Vaddr, len: u32, ctx: &Context) -> Result<Vec<T>> {
+    if len == 0 {
+        return_errno_with_message!(Errno::EINVAL, "the array is empty");
+    }
+
+    let user_space = ctx.user_space();
+    (0..len as usize)
+        .map(|index| user_space.read_val::<T>(addr + index * size_of::<T>()))
+        .collect()
+}
+
+/// Writes the supported opcodes to the user space.
+fn write_probe(probe_addr: Vaddr, nr_args: u32, ctx: &Context) -> Result<()> {
+    const IO_URING_OP_SUPPORTED: u16 = 1 << 0;
+
+    // Reference: <https://elixir.bootlin.com/linux/v6.18/source/io_uring/register.c#L43>
+    if nr_args > 256 {
+        return_errno_with_message!(Errno::EINVAL, "the number of probed opcodes is too large");
+    }
+
+    let user_space = ctx.user_space();
+    let ops_addr = probe_addr + size_of::<c_io_uring_probe>();
+
+    // Like Linux, the probe structure must be zeroed by the user space.
+    let probe = user_space.read_val::<c_io_uring_probe>(probe_addr)?;
+    let ops = (0..nr_args as usize)
+        .map(|index| {
+            user_space.read_val::<c_io_uring_probe_op>(
+                ops_addr + index * size_of::<c_io_uring_probe_op>(),
+            )
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if probe.as_bytes().iter().any(|&byte| byte != 0)
+        || ops
+            .iter()
+            .any(|op| op.as_bytes().iter().any(|&byte| byte != 0))
+    {
+        return_errno_with_message!(Errno::EINVAL, "the probe structure is not zeroed");
+    }
+
+    let num_ops = (IoUringOp::LAST as u32 + 1).min(nr_args);
+    for index in 0..num_ops {
+        let op = index as u8;
+        let flags = if IoUringOp::try_from(op).is_ok() {
+            IO_URING_OP_SUPPORTED
+        } else {
+            0
+        };
+        let probe_op = c_io_uring_probe_op {
+            op,
+            flags,
+            ..Default::default()
+        };
+        user_space.write_val(
+            ops_addr + index as usize * size_of::<c_io_uring_probe_op>(),
+            &probe_op,
+        )?;
+    }
+
+    let probe = c_io_uring_probe {
+        last_op: IoUringOp::LAST as u8,
+        ops_len: num_ops as u8,
+        ..Default::default()
+    };
+    user_space.write_val(probe_addr, &probe)?;
+
+    Ok(())
+}
+
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, TryFromInt)]
+enum RegisterOp {
+    RegisterBuffers = 0,
+    UnregisterBuffers = 1,
+    RegisterFiles = 2,
+    UnregisterFiles = 3,
+    RegisterEventfd = 4,
+    UnregisterEventfd = 5,
+    RegisterFilesUpdate = 6,
+    RegisterEventfdAsync = 7,
+    RegisterProbe = 8,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct c_iovec {
+    iov_base: Vaddr,
+    iov_len: usize,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct c_io_uring_files_update {
+    offset: u32,
+    resv: u32,
+    fds: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+struct c_io_uring_probe {
+    last_op: u8,
+    ops_len: u8,
+    resv: u16,
+    resv2: [u32; 3],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod)]
+struct c_io_uring_probe_op {
+    op: u8,
+    resv: u8,
+    flags: u16,
+    resv2: u32,
+}
@@ -29,13 +29,22 @@ pub fn sys_kill(process_filter: u64, sig_num: u64, ctx: &Context) -> Result<Sysc
 
 fn do_sys_kill(filter: ProcessFilter, sig_num: Option<SigNum>, ctx: &Context) -> Result<()> {
     let signal = sig_num.map(|sig_num| UserSignal::new_kill(sig_num, ctx));
+    let pid_ns = ctx.process.pid_ns();
 
     match filter {
         ProcessFilter::Any => kill_all(signal, ctx)?,
         ProcessFilter::WithPid(pid) => {
+            let pid = pid_ns.global_id(pid).ok_or_else(|| {
+                Error::with_message(Errno::ESRCH, "the target process does not exist")
+            })?;
             kill(pid, signal.map(|s| Box::new(s) as Box<dyn Signal>), ctx)?
         }
-        ProcessFilter::WithPgid(pgid) => kill_group(pgid, signal, ctx)?,
+        ProcessFilter::WithPgid(pgid) => {
+            let pgid = pid_ns.global_id(pgid).ok_or_else(|| {
+                Error::with_message(Errno::ESRCH, "the target group does not exist")
+            })?;
+            kill_group(pgid, signal, ctx)?
+        }
         ProcessFilter::WithPidfd(_) => unreachable!(),
     }
     Ok(())
@@ -32,6 +32,12 @@ pub fn sys_madvise(addr: Vaddr, len: usize, behavior: i32, ctx: &Context) -> Res
         MadviseBehavior::MADV_DONTNEED => {
             vmar.discard_pages(addr_range)?;
         }
+        MadviseBehavior::MADV_DONTDUMP => {
+            vmar.set_dont_dump(addr_range, true)?;
+        }
+        MadviseBehavior::MADV_DODUMP => {
+            vmar.set_dont_dump(addr_range, false)?;
+        }
         _ if DUMMY_MADVISE.contains(&behavior) => {
             let query_guard = vmar.query(addr_range);
             if !query_guard.is_fully_mapped() {
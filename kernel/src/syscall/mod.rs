@@ -8,10 +8,12 @@
 )]
 
 pub use clock_gettime::ClockId;
+pub use eventfd::EventFile;
+pub use open::do_openat;
 use ostd::arch::cpu::context::UserContext;
 pub use timer_create::create_timer;
 
-use crate::{cpu::LinuxAbi, prelude::*};
+use crate::{cpu::LinuxAbi, prelude::*, process::posix_thread::seccomp_allows_syscall};
 
 #[cfg_attr(target_arch = "x86_64", path = "arch/x86.rs")]
 #[cfg_attr(target_arch = "riscv64", path = "arch/riscv.rs")]
@@ -75,6 +77,7 @@ mod gettimeofday;
 mod getuid;
 mod getxattr;
 mod inotify;
+mod io_uring;
 mod ioctl;
 mod kill;
 mod link;
@@ -88,7 +91,12 @@ mod mknod;
 mod mmap;
 mod mount;
 mod mprotect;
+mod mqueue;
 mod mremap;
+mod msgctl;
+mod msgget;
+mod msgrcv;
+mod msgsnd;
 mod msync;
 mod munmap;
 mod nanosleep;
@@ -134,6 +142,7 @@ mod sched_setattr;
 mod sched_setparam;
 mod sched_setscheduler;
 mod sched_yield;
+mod seccomp;
 mod select;
 mod semctl;
 mod semget;
@@ -163,6 +172,10 @@ mod setsid;
 mod setsockopt;
 mod setuid;
 mod setxattr;
+mod shmat;
+mod shmctl;
+mod shmdt;
+mod shmget;
 mod shutdown;
 mod sigaltstack;
 mod signalfd;
@@ -372,6 +385,10 @@ impl SyscallArgument {
 }
 
 pub fn handle_syscall(ctx: &Context, user_ctx: &mut UserContext) {
+    if !seccomp_allows_syscall(ctx, user_ctx) {
+        return;
+    }
+
     let syscall_frame = SyscallArgument::new_from_context(user_ctx);
     let syscall_return = arch::syscall_dispatch(
         syscall_frame.syscall_number,
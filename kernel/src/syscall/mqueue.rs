@@ -0,0 +1,303 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::time::Duration;
+
+use ostd::mm::VmIo;
+
+use super::SyscallReturn;
+use crate::{
+    fs::{
+        file::{
+            FileLike, InodeHandle, InodeMode, OpenArgs, StatusFlags,
+            file_table::{FdFlags, RawFileDesc, get_file_fast},
+        },
+        mqueue::{self, MqAttr, MqNotifyMethod, MqueueFile, NOTIFY_COOKIE_LEN},
+    },
+    net::socket::netlink,
+    prelude::*,
+    process::signal::{
+        c_types::{SigNotify, sigevent_t},
+        sig_num::SigNum,
+    },
+    syscall::constants::MAX_FILENAME_LEN,
+    time::{clocks::RealTimeClock, timer::Timeout, timespec_t, wait::ManagedTimeout},
+};
+
+/// The maximum priority of a message (exclusive).
+// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/mqueue.h>
+const MQ_PRIO_MAX: u32 = 32768;
+
+pub fn sys_mq_open(
+    name_addr: Vaddr,
+    flags: u32,
+    mode: u16,
+    attr_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let name = ctx.user_space().read_cstring(name_addr, MAX_FILENAME_LEN)?;
+    let attr = if attr_addr != 0 {
+        Some(ctx.user_space().read_val::<MqAttr>(attr_addr)?)
+    } else {
+        None
+    };
+    debug!(
+        "name = {:?}, flags = {:#o}, mode = {:#o}, attr = {:?}",
+        name, flags, mode, attr
+    );
+
+    let file_handle = {
+        let name = name.to_string_lossy();
+
+        let fs_ref = ctx.thread_local.borrow_fs();
+        let mask_mode = mode & !fs_ref.umask().get();
+        let open_args =
+            OpenArgs::from_flags_and_mode(flags, InodeMode::from_bits_truncate(mask_mode))?;
+
+        let ns_proxy = ctx.thread_local.borrow_ns_proxy();
+        let mqueue_mount = ns_proxy.unwrap().ipc_ns().mqueue_mount();
+
+        let path_resolver = fs_ref.resolver().read();
+        mqueue::open(
+            mqueue_mount,
+            name.as_ref(),
+            &open_args,
+            attr.as_ref(),
+            &path_resolver,
+        )?
+    };
+
+    // Like Linux, the file descriptor is always close-on-exec.
+    let file_table = ctx.thread_local.borrow_file_table();
+    let fd = file_table
+        .unwrap()
+        .write()
+        .insert(Arc::new(file_handle), FdFlags::CLOEXEC);
+
+    Ok(SyscallReturn::Return(fd.into()))
+}
+
+pub fn sys_mq_unlink(name_addr: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    let name = ctx.user_space().read_cstring(name_addr, MAX_FILENAME_LEN)?;
+    debug!("name = {:?}", name);
+
+    let ns_proxy = ctx.thread_local.borrow_ns_proxy();
+    let mqueue_mount = ns_proxy.unwrap().ipc_ns().mqueue_mount();
+    mqueue::unlink(mqueue_mount, name.to_string_lossy().as_ref())?;
+
+    Ok(SyscallReturn::Return(0))
+}
+
+pub fn sys_mq_timedsend(
+    mqdes: RawFileDesc,
+    msg_addr: Vaddr,
+    msg_len: usize,
+    msg_prio: u32,
+    abs_timeout_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!(
+        "mqdes = {}, msg_addr = {:#x}, msg_len = {}, msg_prio = {}, abs_timeout_addr = {:#x}",
+        mqdes, msg_addr, msg_len, msg_prio, abs_timeout_addr
+    );
+
+    if msg_prio >= MQ_PRIO_MAX {
+        return_errno_with_message!(Errno::EINVAL, "the message priority is too large");
+    }
+    let timeout = read_abs_timeout(abs_timeout_addr, ctx)?;
+
+    let mut file_table = ctx.thread_local.borrow_file_table_mut();
+    let file = get_file_fast!(&mut file_table, mqdes.try_into()?);
+    let mqueue_file = downcast_mqueue_file(&file)?;
+    if !file.access_mode().is_writable() {
+        return_errno_with_message!(Errno::EBADF, "the message queue is not opened for writing");
+    }
+    if msg_len > mqueue_file.attr().mq_msgsize as usize {
+        return_errno_with_message!(Errno::EMSGSIZE, "the message is too long");
+    }
+
+    let mut data = vec![0u8; msg_len];
+    ctx.user_space().read_bytes(msg_addr, &mut data)?;
+
+    let is_nonblocking = file.status_flags().contains(StatusFlags::O_NONBLOCK);
+    mqueue_file
+        .send(data, msg_prio, is_nonblocking, timeout, ctx)
+        .map_err(map_wait_error)?;
+
+    Ok(SyscallReturn::Return(0))
+}
+
+pub fn sys_mq_timedreceive(
+    mqdes: RawFileDesc,
+    msg_addr: Vaddr,
+    msg_len: usize,
+    msg_prio_addr: Vaddr,
+    abs_timeout_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!(
+        "mqdes = {}, msg_addr = {:#x}, msg_len = {}, msg_prio_addr = {:#x}, abs_timeout_addr = {:#x}",
+        mqdes, msg_addr, msg_len, msg_prio_addr, abs_timeout_addr
+    );
+
+    let timeout = read_abs_timeout(abs_timeout_addr, ctx)?;
+
+    let mut file_table = ctx.thread_local.borrow_file_table_mut();
+    let file = get_file_fast!(&mut file_table, mqdes.try_into()?);
+    let mqueue_file = downcast_mqueue_file(&file)?;
+    if !file.access_mode().is_readable() {
+        return_errno_with_message!(Errno::EBADF, "the message queue is not opened for reading");
+    }
+
+    let is_nonblocking = file.status_flags().contains(StatusFlags::O_NONBLOCK);
+    let (data, priority) = mqueue_file
+        .receive(msg_len, is_nonblocking, timeout)
+        .map_err(map_wait_error)?;
+
+    // Like Linux, the message is lost if it cannot be copied to the user space.
+    let user_space = ctx.user_space();
+    user_space.write_bytes(msg_addr, data.as_slice())?;
+    if msg_prio_addr != 0 {
+        user_space.write_val(msg_prio_addr, &priority)?;
+    }
+
+    Ok(SyscallReturn::Return(data.len() as _))
+}
+
+pub fn sys_mq_notify(
+    mqdes: RawFileDesc,
+    sigevent_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!("mqdes = {}, sigevent_addr = {:#x}", mqdes, sigevent_addr);
+
+    let method = if sigevent_addr != 0 {
+        let sigevent = ctx.user_space().read_val::<sigevent_t>(sigevent_addr)?;
+        Some(parse_notify_method(&sigevent, ctx)?)
+    } else {
+        None
+    };
+
+    let mut file_table = ctx.thread_local.borrow_file_table_mut();
+    let file = get_file_fast!(&mut file_table, mqdes.try_into()?);
+    let mqueue_file = downcast_mqueue_file(&file)?;
+    mqueue_file.set_notification(method, ctx)?;
+
+    Ok(SyscallReturn::Return(0))
+}
+
+pub fn sys_mq_getsetattr(
+    mqdes: RawFileDesc,
+    new_attr_addr: Vaddr,
+    old_attr_addr: Vaddr,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    debug!(
+        "mqdes = {}, new_attr_addr = {:#x}, old_attr_addr = {:#x}",
+        mqdes, new_attr_addr, old_attr_addr
+    );
+
+    let new_attr = if new_attr_addr != 0 {
+        let new_attr = ctx.user_space().read_val::<MqAttr>(new_attr_addr)?;
+        if new_attr.mq_flags & !(StatusFlags::O_NONBLOCK.bits() as i64) != 0 {
+            return_errno_with_message!(Errno::EINVAL, "the message queue flags are invalid");
+        }
+        Some(new_attr)
+    } else {
+        None
+    };
+
+    let mut file_table = ctx.thread_local.borrow_file_table_mut();
+    let file = get_file_fast!(&mut file_table, mqdes.try_into()?);
+    let mqueue_file = downcast_mqueue_file(&file)?;
+
+    let status_flags = file.status_flags();
+    let old_attr = MqAttr {
+        mq_flags: (status_flags & StatusFlags::O_NONBLOCK).bits() as i64,
+        ..mqueue_file.attr()
+    };
+
+    // Only `mq_flags` can be changed. The other fields are ignored.
+    if let Some(new_attr) = new_attr {
+        let new_flags = if new_attr.mq_flags != 0 {
+            status_flags | StatusFlags::O_NONBLOCK
+        } else {
+            status_flags - StatusFlags::O_NONBLOCK
+        };
+        file.set_status_flags(new_flags)?;
+    }
+
+    if old_attr_addr != 0 {
+        ctx.user_space().write_val(old_attr_addr, &old_attr)?;
+    }
+
+    Ok(SyscallReturn::Return(0))
+}
+
+fn downcast_mqueue_file(file: &Arc<dyn FileLike>) -> Result<&MqueueFile> {
+    file.downcast_ref::<InodeHandle>()
+        .map(|inode_handle| inode_handle.downcast_open_file::<MqueueFile>())
+        .transpose()?
+        .flatten()
+        .ok_or_else(|| Error::with_message(Errno::EBADF, "the file is not a message queue"))
+}
+
+/// Reads the absolute timeout measured against `CLOCK_REALTIME`.
+fn read_abs_timeout(
+    abs_timeout_addr: Vaddr,
+    ctx: &Context,
+) -> Result<Option<ManagedTimeout<'static>>> {
+    if abs_timeout_addr == 0 {
+        return Ok(None);
+    }
+
+    let timespec = ctx.user_space().read_val::<timespec_t>(abs_timeout_addr)?;
+    let abs_timeout = Duration::try_from(timespec)?;
+
+    Ok(Some(ManagedTimeout::new_with_manager(
+        Timeout::When(abs_timeout),
+        RealTimeClock::timer_manager(),
+    )))
+}
+
+fn map_wait_error(err: Error) -> Error {
+    match err.error() {
+        Errno::ETIME => Error::with_message(Errno::ETIMEDOUT, "the timeout has expired"),
+        Errno::EINTR => Error::new(Errno::ERESTARTSYS),
+        _ => err,
+    }
+}
+
+fn parse_notify_method(sigevent: &sigevent_t, ctx: &Context) -> Result<MqNotifyMethod> {
+    let method = match SigNotify::try_from(sigevent.sigev_notify) {
+        Ok(SigNotify::SIGEV_NONE) => MqNotifyMethod::None,
+        // Like Linux, signal number zero is valid, but no signal will be sent.
+        Ok(SigNotify::SIGEV_SIGNAL) if sigevent.sigev_signo == 0 => MqNotifyMethod::None,
+        Ok(SigNotify::SIGEV_SIGNAL) => {
+            let num = u8::try_from(sigevent.sigev_signo)
+                .ok()
+                .and_then(|signo| SigNum::try_from(signo).ok())
+                .ok_or_else(|| {
+                    Error::with_message(Errno::EINVAL, "the signal number is invalid")
+                })?;
+            MqNotifyMethod::Signal(num, sigevent.sigev_value)
+        }
+        // The C libraries implement `SIGEV_THREAD` by asking the kernel to send a cookie to a
+        // netlink socket, whose file descriptor is passed in `sigev_signo`.
+        Ok(SigNotify::SIGEV_THREAD) => {
+            let mut cookie = [0u8; NOTIFY_COOKIE_LEN];
+            ctx.user_space()
+                .read_bytes(sigevent.sigev_value.read_ptr(), &mut cookie)?;
+
+            let mut file_table = ctx.thread_local.borrow_file_table_mut();
+            let file = get_file_fast!(&mut file_table, sigevent.sigev_signo.try_into()?);
+            let receiver = netlink::raw_receiver_of(file.as_ref().as_ref())?;
+
+            MqNotifyMethod::Thread(receiver, cookie)
+        }
+        Ok(SigNotify::SIGEV_THREAD_ID) | Err(_) => {
+            return_errno_with_message!(Errno::EINVAL, "the notification method is invalid");
+        }
+    };
+
+    Ok(method)
+}
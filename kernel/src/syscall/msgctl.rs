@@ -0,0 +1,63 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use ostd::mm::VmIo;
+
+use super::SyscallReturn;
+use crate::{
+    ipc::{
+        IpcId, PermissionMode,
+        msg::{MsgControlCmd, MsqidDs},
+    },
+    prelude::*,
+};
+
+pub fn sys_msgctl(msqid: i32, cmd: i32, buf: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    let cmd = MsgControlCmd::try_from(cmd)?;
+
+    debug!(
+        "msgctl: msqid = {}, cmd = {:?}, buf = {:#x}",
+        msqid, cmd, buf
+    );
+
+    let ns_proxy = ctx.thread_local.borrow_ns_proxy();
+    let ipc_ns = ns_proxy.unwrap().ipc_ns();
+
+    // The commands below do not operate on a specific queue.
+    match cmd {
+        MsgControlCmd::IPC_INFO | MsgControlCmd::MSG_INFO => {
+            let (msg_info, max_id) = if matches!(cmd, MsgControlCmd::IPC_INFO) {
+                ipc_ns.msg_limits()
+            } else {
+                ipc_ns.msg_info()
+            };
+            ctx.user_space().write_val(buf, &msg_info)?;
+
+            return Ok(SyscallReturn::Return(
+                max_id.map_or(0, |max_id| max_id.get() as isize),
+            ));
+        }
+        _ => (),
+    }
+
+    let Ok(msqid) = IpcId::try_from(msqid.cast_unsigned()) else {
+        return_errno_with_message!(Errno::EINVAL, "non-positive message queue IDs are invalid");
+    };
+
+    match cmd {
+        MsgControlCmd::IPC_RMID => {
+            ipc_ns.remove_msg_queue(msqid, ctx.posix_thread)?;
+        }
+        MsgControlCmd::IPC_SET => {
+            let msqid_ds: MsqidDs = ctx.user_space().read_val(buf)?;
+            ipc_ns.set_msg_queue(msqid, &msqid_ds, ctx.posix_thread)?;
+        }
+        MsgControlCmd::IPC_STAT => {
+            let queue = ipc_ns.get_msg_queue(msqid, PermissionMode::READ, ctx.posix_thread)?;
+            ctx.user_space()
+                .write_val(buf, &queue.msqid_ds(ctx.process.pid_ns()))?;
+        }
+        MsgControlCmd::IPC_INFO | MsgControlCmd::MSG_INFO => unreachable!(),
+    }
+
+    Ok(SyscallReturn::Return(0))
+}
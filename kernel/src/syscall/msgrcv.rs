@@ -0,0 +1,54 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use ostd::mm::VmIo;
+
+use super::SyscallReturn;
+use crate::{
+    ipc::{
+        IpcId, PermissionMode,
+        msg::{MsgFlags, MsgSelector},
+    },
+    prelude::*,
+};
+
+pub fn sys_msgrcv(
+    msqid: i32,
+    msgp: Vaddr,
+    msgsz: usize,
+    msgtyp: i64,
+    msgflg: i32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let flags = MsgFlags::from_bits_truncate(msgflg.cast_unsigned());
+
+    debug!(
+        "msgrcv: msqid = {}, msgp = {:#x}, msgsz = {}, msgtyp = {}, flags = {:?}",
+        msqid, msgp, msgsz, msgtyp, flags
+    );
+
+    let Ok(msqid) = IpcId::try_from(msqid.cast_unsigned()) else {
+        return_errno_with_message!(Errno::EINVAL, "non-positive message queue IDs are invalid");
+    };
+    if msgsz.cast_signed() < 0 {
+        return_errno_with_message!(Errno::EINVAL, "the buffer size is negative");
+    }
+    if flags.contains(MsgFlags::MSG_COPY) {
+        // TODO: Support `MSG_COPY`, which is only available with `CONFIG_CHECKPOINT_RESTORE` in
+        // Linux.
+        return_errno_with_message!(Errno::ENOSYS, "MSG_COPY is not supported");
+    }
+    let selector = MsgSelector::new(msgtyp, flags);
+
+    let ns_proxy = ctx.thread_local.borrow_ns_proxy();
+    let ipc_ns = ns_proxy.unwrap().ipc_ns();
+
+    let queue = ipc_ns.get_msg_queue(msqid, PermissionMode::READ, ctx.posix_thread)?;
+    let message = queue.recv(msgsz, selector, flags, ctx.process.pid())?;
+
+    // Like Linux, the message is lost if it cannot be copied to the user space.
+    let user_space = ctx.user_space();
+    user_space.write_val(msgp, &message.mtype())?;
+    user_space.write_bytes(msgp + size_of::<i64>(), message.data())?;
+
+    Ok(SyscallReturn::Return(message.data().len() as isize))
+}
@@ -0,0 +1,52 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use ostd::mm::VmIo;
+
+use super::SyscallReturn;
+use crate::{
+    ipc::{
+        IpcId, PermissionMode,
+        msg::{Message, MsgFlags},
+    },
+    prelude::*,
+};
+
+pub fn sys_msgsnd(
+    msqid: i32,
+    msgp: Vaddr,
+    msgsz: usize,
+    msgflg: i32,
+    ctx: &Context,
+) -> Result<SyscallReturn> {
+    let flags = MsgFlags::from_bits_truncate(msgflg.cast_unsigned());
+
+    debug!(
+        "msgsnd: msqid = {}, msgp = {:#x}, msgsz = {}, flags = {:?}",
+        msqid, msgp, msgsz, flags
+    );
+
+    let user_space = ctx.user_space();
+    // The message buffer starts with the message type, which is followed by the message content.
+    let mtype = user_space.read_val::<i64>(msgp)?;
+
+    let ns_proxy = ctx.thread_local.borrow_ns_proxy();
+    let ipc_ns = ns_proxy.unwrap().ipc_ns();
+
+    if msgsz > ipc_ns.msg_max() {
+        return_errno_with_message!(Errno::EINVAL, "the message is too long");
+    }
+    let Ok(msqid) = IpcId::try_from(msqid.cast_unsigned()) else {
+        return_errno_with_message!(Errno::EINVAL, "non-positive message queue IDs are invalid");
+    };
+    if mtype < 1 {
+        return_errno_with_message!(Errno::EINVAL, "the message type must be positive");
+    }
+
+    let mut data = vec![0u8; msgsz];
+    user_space.read_bytes(msgp + size_of::<i64>(), &mut data)?;
+
+    let queue = ipc_ns.get_msg_queue(msqid, PermissionMode::WRITE, ctx.posix_thread)?;
+    queue.send(Message::new(mtype, data), flags, ctx.process.pid())?;
+
+    Ok(SyscallReturn::Return(0))
+}
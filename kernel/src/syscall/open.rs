@@ -7,7 +7,7 @@ use crate::{
         file::{
             AccessMode, CreationFlags, FileLike, InodeHandle, InodeMode, InodeType, OpenArgs,
             StatusFlags,
-            file_table::{FdFlags, RawFileDesc},
+            file_table::{FdFlags, FileDesc, RawFileDesc},
         },
         vfs::{
             inode::HardLinkability,
@@ -25,6 +25,24 @@ pub fn sys_openat(
     mode: u16,
     ctx: &Context,
 ) -> Result<SyscallReturn> {
+    let fd = do_openat(dirfd, path_addr, flags, mode, ctx).map_err(|err| match err.error() {
+        Errno::EINTR => Error::new(Errno::ERESTARTSYS),
+        _ => err,
+    })?;
+    Ok(SyscallReturn::Return(fd.into()))
+}
+
+/// Opens the file at the path in the user space and installs it in the file table.
+///
+/// Unlike [`sys_openat`], this method does not turn `EINTR` into `ERESTARTSYS`, so it can also be
+/// used for the requests that should not be restarted, such as io_uring requests.
+pub fn do_openat(
+    dirfd: RawFileDesc,
+    path_addr: Vaddr,
+    flags: u32,
+    mode: u16,
+    ctx: &Context,
+) -> Result<FileDesc> {
     let path = ctx.user_space().read_cstring(path_addr, MAX_FILENAME_LEN)?;
     debug!(
         "dirfd = {}, path = {:?}, flags = {}, mode = {}",
@@ -44,11 +62,7 @@ pub fn sys_openat(
             &fs_path,
             flags,
             InodeMode::from_bits_truncate(mask_mode),
-        )
-        .map_err(|err| match err.error() {
-            Errno::EINTR => Error::new(Errno::ERESTARTSYS),
-            _ => err,
-        })?
+        )?
     };
 
     let fd = {
@@ -64,7 +78,7 @@ pub fn sys_openat(
     };
     let file_like: Arc<dyn FileLike> = file_handle;
     fs::vfs::notify::on_open(&file_like);
-    Ok(SyscallReturn::Return(fd.into()))
+    Ok(fd)
 }
 
 pub fn sys_open(path_addr: Vaddr, flags: u32, mode: u16, ctx: &Context) -> Result<SyscallReturn> {
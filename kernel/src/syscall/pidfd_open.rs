@@ -19,8 +19,11 @@ pub fn sys_pidfd_open(pid: Pid, flags: u32, ctx: &Context) -> Result<SyscallRetu
         return_errno_with_message!(Errno::EINVAL, "all negative PIDs are not valid");
     }
 
-    let process = pid_table::pid_table_mut()
-        .get_process(pid)
+    let process = ctx
+        .process
+        .pid_ns()
+        .global_id(pid)
+        .and_then(|pid| pid_table::pid_table_mut().get_process(pid))
         .ok_or_else(|| Error::with_message(Errno::ESRCH, "the process does not exist"))?;
 
     let pid_fd = {
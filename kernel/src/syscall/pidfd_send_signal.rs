@@ -35,7 +35,6 @@ pub fn sys_pidfd_send_signal(
     );
 
     let siginfo = read_siginfo_from_user(info_ptr, sig_num, ctx)?;
-    let signal = RawSignal::new(siginfo);
 
     let target = get_target_from_pidfd(pidfd, flags, ctx)?;
 
@@ -45,44 +44,52 @@ pub fn sys_pidfd_send_signal(
         SignalTarget::ProcessGroup { pgid: _ } => false,
     };
 
-    if !is_self && (siginfo.si_code >= 0 || siginfo.si_code == SI_TKILL) {
-        return_errno_with_message!(
-            Errno::EPERM,
-            "signals with custom code can only be sent to the current thread/process"
-        );
+    match siginfo {
+        Some(siginfo) => {
+            if !is_self && (siginfo.si_code >= 0 || siginfo.si_code == SI_TKILL) {
+                return_errno_with_message!(
+                    Errno::EPERM,
+                    "signals with custom code can only be sent to the current thread/process"
+                );
+            }
+            send_signal(target, RawSignal::new(siginfo), ctx)?;
+        }
+        // If `info_ptr` is NULL, the kernel constructs a default `siginfo_t` structure
+        // whose fields match the values that are implicitly supplied when a signal is sent using the kill(2).
+        None => send_signal(target, UserSignal::new_kill(sig_num, ctx), ctx)?,
     }
 
+    Ok(SyscallReturn::Return(0))
+}
+
+fn send_signal<S: Signal + Clone>(target: SignalTarget, signal: S, ctx: &Context) -> Result<()> {
     match target {
         SignalTarget::Thread { tid, tgid } => {
             let signal = Some(Box::new(signal) as Box<dyn Signal>);
-            tgkill(tid, Some(tgid), signal, ctx)?;
-        }
-        SignalTarget::Process { pid } => {
-            kill(pid, Some(Box::new(signal) as Box<dyn Signal>), ctx)?;
-        }
-        SignalTarget::ProcessGroup { pgid } => {
-            kill_group(pgid, Some(signal), ctx)?;
+            tgkill(tid, Some(tgid), signal, ctx)
         }
+        SignalTarget::Process { pid } => kill(pid, Some(Box::new(signal) as Box<dyn Signal>), ctx),
+        SignalTarget::ProcessGroup { pgid } => kill_group(pgid, Some(signal), ctx),
     }
-
-    Ok(SyscallReturn::Return(0))
 }
 
-fn read_siginfo_from_user(info_ptr: Vaddr, sig_num: SigNum, ctx: &Context) -> Result<siginfo_t> {
-    if info_ptr != 0 {
-        let si = ctx.user_space().read_val::<siginfo_t>(info_ptr)?;
-        if si.si_signo != sig_num.as_u8() as i32 {
-            return_errno_with_message!(
-                Errno::EINVAL,
-                "`siginfo.si_signo` does not match the specified signal number"
-            );
-        }
-        Ok(si)
-    } else {
-        // If `info_ptr` is NULL, the kernel constructs a default `siginfo_t` structure
-        // whose fields match the values that are implicitly supplied when a signal is sent using the kill(2).
-        Ok(UserSignal::new_kill(sig_num, ctx).to_info())
+fn read_siginfo_from_user(
+    info_ptr: Vaddr,
+    sig_num: SigNum,
+    ctx: &Context,
+) -> Result<Option<siginfo_t>> {
+    if info_ptr == 0 {
+        return Ok(None);
+    }
+
+    let si = ctx.user_space().read_val::<siginfo_t>(info_ptr)?;
+    if si.si_signo != sig_num.as_u8() as i32 {
+        return_errno_with_message!(
+            Errno::EINVAL,
+            "`siginfo.si_signo` does not match the specified signal number"
+        );
     }
+    Ok(Some(si))
 }
 
 fn get_target_from_pidfd(
@@ -2,12 +2,16 @@
 
 use ostd::mm::VmIo;
 
-use super::SyscallReturn;
+use super::{SyscallReturn, seccomp::set_seccomp_filter};
 use crate::{
     prelude::*,
     process::{
+        Dumpable,
         credentials::{SecureBits, capabilities::CapSet},
-        posix_thread::{ContextPthreadAdminApi, MAX_THREAD_NAME_LEN},
+        posix_thread::{
+            ContextPthreadAdminApi, MAX_THREAD_NAME_LEN, SeccompFilterFlags, SeccompMode,
+            set_seccomp_strict,
+        },
         signal::sig_num::SigNum,
     },
 };
@@ -35,14 +39,14 @@ pub fn sys_prctl(
             ctx.user_space().write_val(write_to_addr, &write_val)?;
         }
         PrctlCmd::PR_GET_DUMPABLE => {
-            // TODO: When coredump is supported, return the actual value.
-            return Ok(SyscallReturn::Return(Dumpable::Disable as _));
+            let dumpable = ctx.user_space().vmar().process_vm().dumpable();
+            return Ok(SyscallReturn::Return(dumpable as _));
         }
         PrctlCmd::PR_SET_DUMPABLE(dumpable) => {
             if dumpable != Dumpable::Disable && dumpable != Dumpable::User {
                 return_errno_with_message!(Errno::EINVAL, "invalid dumpable attribute");
             }
-            // TODO: Implement coredump.
+            ctx.user_space().vmar().process_vm().set_dumpable(dumpable);
         }
         PrctlCmd::PR_GET_KEEPCAPS => {
             let keep_cap = {
@@ -74,6 +78,19 @@ pub fn sys_prctl(
             ctx.user_space()
                 .write_bytes(write_to_addr, thread_name.name().to_bytes_with_nul())?;
         }
+        PrctlCmd::PR_GET_SECCOMP => {
+            let mode = ctx.posix_thread.seccomp_mode();
+            return Ok(SyscallReturn::Return(mode as u8 as _));
+        }
+        PrctlCmd::PR_SET_SECCOMP(mode, filter_addr) => match mode {
+            SeccompMode::Strict => set_seccomp_strict(ctx)?,
+            SeccompMode::Filter => {
+                set_seccomp_filter(filter_addr, SeccompFilterFlags::empty(), ctx)?;
+            }
+            SeccompMode::Disabled => {
+                return_errno_with_message!(Errno::EINVAL, "the seccomp mode is invalid");
+            }
+        },
         PrctlCmd::PR_CAPBSET_READ(capability) => {
             let credentials = ctx.posix_thread.credentials();
             let is_in_bounding_set = credentials.bounding_capset().contains(capability);
@@ -121,6 +138,32 @@ pub fn sys_prctl(
             ctx.user_space()
                 .write_val(write_addr, &(process.is_child_subreaper() as u32))?;
         }
+        PrctlCmd::PR_SET_NO_NEW_PRIVS => {
+            ctx.posix_thread.set_no_new_privs();
+        }
+        PrctlCmd::PR_GET_NO_NEW_PRIVS => {
+            let no_new_privs = ctx.posix_thread.no_new_privs();
+            return Ok(SyscallReturn::Return(no_new_privs as _));
+        }
+        PrctlCmd::PR_CAP_AMBIENT(op) => match op {
+            CapAmbientOp::IsSet(capability) => {
+                let credentials = ctx.posix_thread.credentials();
+                let is_in_ambient_set = credentials.ambient_capset().contains(capability);
+                return Ok(SyscallReturn::Return(is_in_ambient_set as _));
+            }
+            CapAmbientOp::Raise(capability) => {
+                let credentials = ctx.credentials_mut();
+                credentials.raise_ambient_capability(capability)?;
+            }
+            CapAmbientOp::Lower(capability) => {
+                let credentials = ctx.credentials_mut();
+                credentials.lower_ambient_capability(capability);
+            }
+            CapAmbientOp::ClearAll => {
+                let credentials = ctx.credentials_mut();
+                credentials.clear_ambient_capset();
+            }
+        },
     }
 
     Ok(SyscallReturn::Return(0))
@@ -134,6 +177,8 @@ const PR_GET_KEEPCAPS: i32 = 7;
 const PR_SET_KEEPCAPS: i32 = 8;
 const PR_SET_NAME: i32 = 15;
 const PR_GET_NAME: i32 = 16;
+const PR_GET_SECCOMP: i32 = 21;
+const PR_SET_SECCOMP: i32 = 22;
 const PR_CAPBSET_READ: i32 = 23;
 const PR_CAPBSET_DROP: i32 = 24;
 const PR_GET_SECUREBITS: i32 = 27;
@@ -142,6 +187,14 @@ const PR_SET_TIMERSLACK: i32 = 29;
 const PR_GET_TIMERSLACK: i32 = 30;
 const PR_SET_CHILD_SUBREAPER: i32 = 36;
 const PR_GET_CHILD_SUBREAPER: i32 = 37;
+const PR_SET_NO_NEW_PRIVS: i32 = 38;
+const PR_GET_NO_NEW_PRIVS: i32 = 39;
+const PR_CAP_AMBIENT: i32 = 47;
+
+const PR_CAP_AMBIENT_IS_SET: u64 = 1;
+const PR_CAP_AMBIENT_RAISE: u64 = 2;
+const PR_CAP_AMBIENT_LOWER: u64 = 3;
+const PR_CAP_AMBIENT_CLEAR_ALL: u64 = 4;
 
 #[expect(non_camel_case_types)]
 #[derive(Clone, Copy, Debug)]
@@ -154,6 +207,8 @@ pub enum PrctlCmd {
     PR_SET_KEEPCAPS(u32),
     PR_SET_NAME(Vaddr),
     PR_GET_NAME(Vaddr),
+    PR_GET_SECCOMP,
+    PR_SET_SECCOMP(SeccompMode, Vaddr),
     PR_CAPBSET_READ(CapSet),
     PR_CAPBSET_DROP(CapSet),
     PR_GET_SECUREBITS,
@@ -162,18 +217,22 @@ pub enum PrctlCmd {
     PR_GET_TIMERSLACK,
     PR_SET_CHILD_SUBREAPER(bool),
     PR_GET_CHILD_SUBREAPER(Vaddr),
+    PR_SET_NO_NEW_PRIVS,
+    PR_GET_NO_NEW_PRIVS,
+    PR_CAP_AMBIENT(CapAmbientOp),
 }
 
-#[repr(u64)]
-#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromInt)]
-pub enum Dumpable {
-    Disable = 0, /* No setuid dumping */
-    User = 1,    /* Dump as user of process */
-    Root = 2,    /* Dump as root */
+/// The operations on the ambient capability set.
+#[derive(Clone, Copy, Debug)]
+pub enum CapAmbientOp {
+    IsSet(CapSet),
+    Raise(CapSet),
+    Lower(CapSet),
+    ClearAll,
 }
 
 impl PrctlCmd {
-    fn from_args(option: i32, arg2: u64, _arg3: u64, _arg4: u64, _arg5: u64) -> Result<PrctlCmd> {
+    fn from_args(option: i32, arg2: u64, arg3: u64, arg4: u64, arg5: u64) -> Result<PrctlCmd> {
         match option {
             PR_SET_PDEATHSIG => {
                 let signum = SigNum::try_from(arg2 as u8)?;
@@ -181,11 +240,27 @@ impl PrctlCmd {
             }
             PR_GET_PDEATHSIG => Ok(PrctlCmd::PR_GET_PDEATHSIG(arg2 as _)),
             PR_GET_DUMPABLE => Ok(PrctlCmd::PR_GET_DUMPABLE),
-            PR_SET_DUMPABLE => Ok(PrctlCmd::PR_SET_DUMPABLE(Dumpable::try_from(arg2)?)),
+            PR_SET_DUMPABLE => {
+                let dumpable = u8::try_from(arg2)
+                    .ok()
+                    .and_then(|dumpable| Dumpable::try_from(dumpable).ok())
+                    .ok_or_else(|| {
+                        Error::with_message(Errno::EINVAL, "invalid dumpable attribute")
+                    })?;
+                Ok(PrctlCmd::PR_SET_DUMPABLE(dumpable))
+            }
             PR_GET_KEEPCAPS => Ok(PrctlCmd::PR_GET_KEEPCAPS),
             PR_SET_KEEPCAPS => Ok(PrctlCmd::PR_SET_KEEPCAPS(arg2 as _)),
             PR_SET_NAME => Ok(PrctlCmd::PR_SET_NAME(arg2 as _)),
             PR_GET_NAME => Ok(PrctlCmd::PR_GET_NAME(arg2 as _)),
+            PR_GET_SECCOMP => Ok(PrctlCmd::PR_GET_SECCOMP),
+            PR_SET_SECCOMP => {
+                let mode = u8::try_from(arg2)
+                    .ok()
+                    .and_then(|mode| SeccompMode::try_from(mode).ok())
+                    .ok_or_else(|| Error::with_message(Errno::EINVAL, "invalid seccomp mode"))?;
+                Ok(PrctlCmd::PR_SET_SECCOMP(mode, arg3 as _))
+            }
             PR_CAPBSET_READ => Ok(PrctlCmd::PR_CAPBSET_READ(parse_capability(arg2)?)),
             PR_CAPBSET_DROP => Ok(PrctlCmd::PR_CAPBSET_DROP(parse_capability(arg2)?)),
             PR_GET_SECUREBITS => Ok(PrctlCmd::PR_GET_SECUREBITS),
@@ -196,6 +271,47 @@ impl PrctlCmd {
             PR_GET_TIMERSLACK => Ok(PrctlCmd::PR_GET_TIMERSLACK),
             PR_SET_CHILD_SUBREAPER => Ok(PrctlCmd::PR_SET_CHILD_SUBREAPER(arg2 > 0)),
             PR_GET_CHILD_SUBREAPER => Ok(PrctlCmd::PR_GET_CHILD_SUBREAPER(arg2 as _)),
+            PR_SET_NO_NEW_PRIVS => {
+                if arg2 != 1 || arg3 != 0 || arg4 != 0 || arg5 != 0 {
+                    return_errno_with_message!(Errno::EINVAL, "invalid no_new_privs arguments");
+                }
+                Ok(PrctlCmd::PR_SET_NO_NEW_PRIVS)
+            }
+            PR_GET_NO_NEW_PRIVS => {
+                if arg2 != 0 || arg3 != 0 || arg4 != 0 || arg5 != 0 {
+                    return_errno_with_message!(Errno::EINVAL, "invalid no_new_privs arguments");
+                }
+                Ok(PrctlCmd::PR_GET_NO_NEW_PRIVS)
+            }
+            PR_CAP_AMBIENT => {
+                if arg2 == PR_CAP_AMBIENT_CLEAR_ALL {
+                    if arg3 != 0 || arg4 != 0 || arg5 != 0 {
+                        return_errno_with_message!(
+                            Errno::EINVAL,
+                            "invalid ambient capability arguments"
+                        );
+                    }
+                    return Ok(PrctlCmd::PR_CAP_AMBIENT(CapAmbientOp::ClearAll));
+                }
+
+                let capability = parse_capability(arg3)?;
+                if arg4 != 0 || arg5 != 0 {
+                    return_errno_with_message!(
+                        Errno::EINVAL,
+                        "invalid ambient capability arguments"
+                    );
+                }
+                let op = match arg2 {
+                    PR_CAP_AMBIENT_IS_SET => CapAmbientOp::IsSet(capability),
+                    PR_CAP_AMBIENT_RAISE => CapAmbientOp::Raise(capability),
+                    PR_CAP_AMBIENT_LOWER => CapAmbientOp::Lower(capability),
+                    _ => return_errno_with_message!(
+                        Errno::EINVAL,
+                        "invalid ambient capability operation"
+                    ),
+                };
+                Ok(PrctlCmd::PR_CAP_AMBIENT(op))
+            }
             _ => {
                 debug!("prctl cmd number: {}", option);
                 return_errno_with_message!(Errno::EINVAL, "unsupported prctl command");
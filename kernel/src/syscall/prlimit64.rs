@@ -41,7 +41,16 @@ pub fn sys_prlimit64(
         Some(user_space.read_val(new_rlim_addr)?)
     };
 
-    let old_raw = if pid == 0 || pid == ctx.process.pid() {
+    let pid = if pid == 0 {
+        ctx.process.pid()
+    } else {
+        ctx.process
+            .pid_ns()
+            .global_id(pid)
+            .ok_or_else(|| Error::with_message(Errno::ESRCH, "the target process does not exist"))?
+    };
+
+    let old_raw = if pid == ctx.process.pid() {
         do_prlimit64(&ctx.process, resource, new_raw, ctx)?
     } else {
         let target_process = pid_table::pid_table_mut().get_process(pid).ok_or_else(|| {
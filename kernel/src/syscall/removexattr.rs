@@ -11,6 +11,7 @@ use crate::{
     fs,
     fs::file::file_table::{RawFileDesc, get_file_fast},
     prelude::*,
+    process::credentials::{FileCaps, capabilities::CapSet},
     syscall::constants::MAX_FILENAME_LEN,
 };
 
@@ -56,6 +57,12 @@ fn removexattr(
     let name_str = name_cstr.to_string_lossy();
     let xattr_name = parse_xattr_name(name_str.as_ref())?;
     check_xattr_namespace(xattr_name.namespace(), ctx)?;
+    if xattr_name.full_name() == FileCaps::XATTR_NAME {
+        ctx.process
+            .user_ns()
+            .lock()
+            .check_cap(CapSet::SETFCAP, ctx.posix_thread)?;
+    }
 
     match lookup_path_for_xattr(&file_ctx, ctx) {
         Ok(path) => {
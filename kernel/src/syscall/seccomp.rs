@@ -0,0 +1,80 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use ostd::mm::VmIo;
+
+use super::SyscallReturn;
+use crate::{
+    prelude::*,
+    process::posix_thread::{
+        SeccompFilterFlags, attach_seccomp_filter, is_seccomp_action_available, set_seccomp_strict,
+    },
+    thread::Tid,
+    util::bpf::CSockFprog,
+};
+
+pub fn sys_seccomp(op: u32, flags: u32, args: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    debug!("op = {}, flags = {:#x}, args = {:#x}", op, flags, args);
+
+    let op = SeccompOp::try_from(op)
+        .map_err(|_| Error::with_message(Errno::EINVAL, "the seccomp operation is invalid"))?;
+
+    let res = match op {
+        SeccompOp::SetModeStrict => {
+            if flags != 0 || args != 0 {
+                return_errno_with_message!(Errno::EINVAL, "the strict mode takes no arguments");
+            }
+            set_seccomp_strict(ctx)?;
+            0
+        }
+        SeccompOp::SetModeFilter => {
+            let flags = SeccompFilterFlags::from_bits(flags).ok_or_else(|| {
+                Error::with_message(Errno::EINVAL, "the filter flags are invalid")
+            })?;
+            if flags.intersects(
+                SeccompFilterFlags::NEW_LISTENER | SeccompFilterFlags::WAIT_KILLABLE_RECV,
+            ) {
+                return_errno_with_message!(
+                    Errno::EINVAL,
+                    "user-space notification listeners are not supported"
+                );
+            }
+            set_seccomp_filter(args, flags, ctx)?
+        }
+        SeccompOp::GetActionAvail => {
+            if flags != 0 {
+                return_errno_with_message!(Errno::EINVAL, "the flags are not zero");
+            }
+            let action = ctx.user_space().read_val::<u32>(args)?;
+            if !is_seccomp_action_available(action) {
+                return_errno_with_message!(Errno::EOPNOTSUPP, "the action is not available");
+            }
+            0
+        }
+    };
+
+    Ok(SyscallReturn::Return(res as _))
+}
+
+/// Attaches the seccomp filter in `fprog_addr` to the current thread.
+///
+/// Returns the ID of the thread that prevents the synchronization, or zero on success.
+pub(super) fn set_seccomp_filter(
+    fprog_addr: Vaddr,
+    flags: SeccompFilterFlags,
+    ctx: &Context,
+) -> Result<Tid> {
+    let fprog = ctx.user_space().read_val::<CSockFprog>(fprog_addr)?;
+    let insns = fprog.read_insns(ctx)?;
+
+    let failed_tid = attach_seccomp_filter(insns, flags, ctx)?;
+
+    Ok(failed_tid.map_or(0, |tid| ctx.process.pid_ns().local_id(tid)))
+}
+
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, TryFromInt)]
+enum SeccompOp {
+    SetModeStrict = 0,
+    SetModeFilter = 1,
+    GetActionAvail = 2,
+}
@@ -4,10 +4,7 @@ use ostd::mm::VmIo;
 
 use super::SyscallReturn;
 use crate::{
-    ipc::{
-        IpcControlCmd, IpcId,
-        semaphore::system_v::{PermissionMode, sem::Semaphore},
-    },
+    ipc::{IpcControlCmd, IpcId, PermissionMode, semaphore::system_v::sem::Semaphore},
     prelude::*,
     process::Pid,
 };
@@ -63,6 +60,7 @@ pub fn sys_semctl(
                 sem_set.get(semnum as usize, sem_pid)
             })?;
 
+            let pid = ctx.process.pid_ns().local_id(pid);
             return Ok(SyscallReturn::Return(pid as isize));
         }
         IpcControlCmd::SEM_GETVAL => {
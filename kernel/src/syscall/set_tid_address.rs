@@ -15,6 +15,6 @@ pub fn sys_set_tid_address(tidptr: Vaddr, ctx: &Context) -> Result<SyscallReturn
 
     ctx.thread_local.set_child_tid().set(clear_child_tid);
 
-    let tid = ctx.posix_thread.tid();
+    let tid = ctx.process.pid_ns().local_id(ctx.posix_thread.tid());
     Ok(SyscallReturn::Return(tid as _))
 }
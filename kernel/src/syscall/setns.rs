@@ -18,13 +18,14 @@ use crate::{
         vfs::path::MountNamespace,
     },
     ipc::IpcNamespace,
-    net::uts_ns::UtsNamespace,
+    net::{net_ns::NetNamespace, uts_ns::UtsNamespace},
     prelude::*,
     process::{
-        CloneFlags, ContextSetNsAdminApi, NsProxy, NsProxyBuilder, PidFile,
+        CloneFlags, ContextSetNsAdminApi, NsProxy, NsProxyBuilder, PidFile, PidNamespace,
         check_unsupported_ns_flags, credentials::capabilities::CapSet, posix_thread::AsPosixThread,
     },
     syscall::SyscallReturn,
+    time::time_ns::TimeNamespace,
 };
 
 pub fn sys_setns(fd: RawFileDesc, flags: u32, ctx: &Context) -> Result<SyscallReturn> {
@@ -44,8 +45,18 @@ pub fn sys_setns(fd: RawFileDesc, flags: u32, ctx: &Context) -> Result<SyscallRe
         build_proxy_from_ns_file(file.as_ref(), ns_type_flags, ctx)?
     };
 
+    let new_ns_proxy = Arc::new(new_ns_proxy);
+
+    // Make the vDSO use the clock offsets of the new time namespace.
+    if !Arc::ptr_eq(
+        new_ns_proxy.time_ns(),
+        ctx.thread_local.borrow_ns_proxy().unwrap().time_ns(),
+    ) {
+        new_ns_proxy.time_ns().join_vdso(ctx.user_space().vmar())?;
+    }
+
     // Install the newly created `NsProxy`.
-    ctx.set_ns_proxy(Arc::new(new_ns_proxy));
+    ctx.set_ns_proxy(new_ns_proxy);
 
     Ok(SyscallReturn::Return(0))
 }
@@ -99,6 +110,26 @@ fn build_proxy_from_pid_file(
         set_mnt_ns(&mut builder, target_ns, ctx)?;
     }
 
+    if flags.contains(CloneFlags::CLONE_NEWNET) {
+        let target_ns = target_proxy.net_ns();
+        set_net_ns(&mut builder, target_ns, ctx)?;
+    }
+
+    if flags.contains(CloneFlags::CLONE_NEWPID) {
+        let target_ns = target_thread
+            .as_posix_thread()
+            .unwrap()
+            .process()
+            .pid_ns()
+            .clone();
+        set_pid_ns(&mut builder, &target_ns, ctx)?;
+    }
+
+    if flags.contains(CloneFlags::CLONE_NEWTIME) {
+        let target_ns = target_proxy.time_ns();
+        set_time_ns(&mut builder, target_ns, ctx)?;
+    }
+
     if flags.contains(CloneFlags::CLONE_NEWUTS) {
         let target_ns = target_proxy.uts_ns();
         set_uts_ns(&mut builder, target_ns, ctx)?;
@@ -140,6 +171,15 @@ fn build_proxy_from_ns_file(
         || try_apply_ns_from_inode::<MountNamespace>(inode_handle, flags, |ns| {
             set_mnt_ns(&mut builder, &ns, ctx)
         })?
+        || try_apply_ns_from_inode::<NetNamespace>(inode_handle, flags, |ns| {
+            set_net_ns(&mut builder, &ns, ctx)
+        })?
+        || try_apply_ns_from_inode::<PidNamespace>(inode_handle, flags, |ns| {
+            set_pid_ns(&mut builder, &ns, ctx)
+        })?
+        || try_apply_ns_from_inode::<TimeNamespace>(inode_handle, flags, |ns| {
+            set_time_ns(&mut builder, &ns, ctx)
+        })?
         || try_apply_ns_from_inode::<UtsNamespace>(inode_handle, flags, |ns| {
             set_uts_ns(&mut builder, &ns, ctx)
         })?;
@@ -217,6 +257,65 @@ fn set_mnt_ns(
     Ok(())
 }
 
+fn set_net_ns(
+    builder: &mut NsProxyBuilder,
+    target_ns: &Arc<NetNamespace>,
+    ctx: &Context,
+) -> Result<()> {
+    check_set_ns_perms(target_ns, ctx)?;
+
+    builder.net_ns(target_ns.clone());
+
+    Ok(())
+}
+
+fn set_pid_ns(
+    builder: &mut NsProxyBuilder,
+    target_ns: &Arc<PidNamespace>,
+    ctx: &Context,
+) -> Result<()> {
+    check_set_ns_perms(target_ns, ctx)?;
+
+    // The new children cannot be placed in a PID namespace that is invisible to the current
+    // process.
+    if !ctx.process.pid_ns().is_same_or_ancestor_of(target_ns) {
+        return_errno_with_message!(
+            Errno::EINVAL,
+            "the PID namespace is not a descendant of the current PID namespace"
+        );
+    }
+
+    builder.pid_ns_for_children(target_ns.clone());
+
+    Ok(())
+}
+
+fn set_time_ns(
+    builder: &mut NsProxyBuilder,
+    target_ns: &Arc<TimeNamespace>,
+    ctx: &Context,
+) -> Result<()> {
+    // Reference: <https://elixir.bootlin.com/linux/v6.18/source/kernel/time/namespace.c>
+
+    // The time namespace determines the vvar pages mapped in the VMAR, so the VMAR must not be
+    // shared with other threads or processes.
+    if ctx.process.tasks().lock().as_slice().len() != 1
+        || ctx.user_space().vmar().has_multiple_handles()
+    {
+        return_errno_with_message!(
+            Errno::EUSERS,
+            "setting a time namespace is allowed only if the VMAR is not shared"
+        );
+    }
+
+    check_set_ns_perms(target_ns, ctx)?;
+
+    builder.time_ns(target_ns.clone());
+    builder.time_ns_for_children(target_ns.clone());
+
+    Ok(())
+}
+
 fn set_uts_ns(
     builder: &mut NsProxyBuilder,
     target_ns: &Arc<UtsNamespace>,
@@ -2,6 +2,8 @@
 
 use alloc::borrow::Cow;
 
+use ostd::mm::VmIo;
+
 use super::SyscallReturn;
 use crate::{
     fs,
@@ -18,7 +20,7 @@ use crate::{
         },
     },
     prelude::*,
-    process::credentials::capabilities::CapSet,
+    process::credentials::{FileCaps, capabilities::CapSet},
     syscall::constants::MAX_FILENAME_LEN,
 };
 
@@ -115,7 +117,18 @@ fn setxattr(
     if value_len > XATTR_VALUE_MAX_LEN {
         return_errno_with_message!(Errno::E2BIG, "xattr value too long");
     }
-    let mut value_reader = user_space.reader(value_ptr, value_len)?;
+    // The file capabilities are checked before being written, so they are read from the user
+    // space in advance.
+    let file_caps_value;
+    let mut value_reader = if xattr_name.full_name() == FileCaps::XATTR_NAME {
+        let mut value = vec![0u8; value_len];
+        user_space.read_bytes(value_ptr, &mut value)?;
+        FileCaps::check_xattr_value(&value, &ctx.process.user_ns().lock(), ctx.posix_thread)?;
+        file_caps_value = value;
+        VmReader::from(file_caps_value.as_slice()).to_fallible()
+    } else {
+        user_space.reader(value_ptr, value_len)?
+    };
 
     let path = lookup_path_for_xattr(&file_ctx, ctx)?;
     path.set_xattr(xattr_name, &mut value_reader, flags)?;
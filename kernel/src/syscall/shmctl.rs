@@ -0,0 +1,71 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use ostd::mm::VmIo;
+
+use super::SyscallReturn;
+use crate::{
+    ipc::{
+        IpcId, PermissionMode,
+        shm::{ShmControlCmd, ShmInfo64, ShmidDs},
+    },
+    prelude::*,
+};
+
+pub fn sys_shmctl(shmid: i32, cmd: i32, buf: Vaddr, ctx: &Context) -> Result<SyscallReturn> {
+    let cmd = ShmControlCmd::try_from(cmd)?;
+
+    debug!(
+        "shmctl: shmid = {}, cmd = {:?}, buf = {:#x}",
+        shmid, cmd, buf
+    );
+
+    let ns_proxy = ctx.thread_local.borrow_ns_proxy();
+    let ipc_ns = ns_proxy.unwrap().ipc_ns();
+
+    // The commands below do not operate on a specific segment.
+    match cmd {
+        ShmControlCmd::IPC_INFO => {
+            ctx.user_space().write_val(buf, &ShmInfo64::limits())?;
+
+            let (_, max_id) = ipc_ns.shm_info();
+            return Ok(SyscallReturn::Return(
+                max_id.map_or(0, |max_id| max_id.get() as isize),
+            ));
+        }
+        ShmControlCmd::SHM_INFO => {
+            let (shm_info, max_id) = ipc_ns.shm_info();
+            ctx.user_space().write_val(buf, &shm_info)?;
+
+            return Ok(SyscallReturn::Return(
+                max_id.map_or(0, |max_id| max_id.get() as isize),
+            ));
+        }
+        _ => (),
+    }
+
+    let Ok(shmid) = IpcId::try_from(shmid.cast_unsigned()) else {
+        return_errno_with_message!(Errno::EINVAL, "non-positive shared memory IDs are invalid");
+    };
+
+    match cmd {
+        ShmControlCmd::IPC_RMID => {
+            ipc_ns.destroy_shm(shmid, ctx.posix_thread)?;
+        }
+        ShmControlCmd::IPC_SET => {
+            let shmid_ds: ShmidDs = ctx.user_space().read_val(buf)?;
+            ipc_ns.with_shm_as_owner(shmid, ctx.posix_thread, |shm| {
+                shm.set_from(&shmid_ds);
+                Ok(())
+            })?;
+        }
+        ShmControlCmd::IPC_STAT => {
+            ipc_ns.with_shm(shmid, PermissionMode::READ, ctx.posix_thread, |shm| {
+                let shmid_ds = shm.shmid_ds(ctx.process.pid_ns());
+                Ok(ctx.user_space().write_val(buf, &shmid_ds)?)
+            })?;
+        }
+        ShmControlCmd::IPC_INFO | ShmControlCmd::SHM_INFO => unreachable!(),
+    }
+
+    Ok(SyscallReturn::Return(0))
+}
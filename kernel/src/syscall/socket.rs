@@ -4,10 +4,12 @@ use super::SyscallReturn;
 use crate::{
     fs::file::{FileLike, file_table::FdFlags},
     net::socket::{
-        ip::{DatagramSocket, IpAddressFamily, StreamSocket},
+        ip::{DatagramSocket, IpAddressFamily, RawSocket, StreamSocket},
         netlink::{
-            NetlinkRouteSocket, NetlinkUeventSocket, StandardNetlinkProtocol, is_valid_protocol,
+            NetlinkNetfilterSocket, NetlinkRouteSocket, NetlinkSockDiagSocket, NetlinkUeventSocket,
+            StandardNetlinkProtocol, is_valid_protocol,
         },
+        packet::PacketSocket,
         unix::{UnixDatagramSocket, UnixStreamSocket},
         vsock::VsockStreamSocket,
     },
@@ -25,15 +27,16 @@ pub fn sys_socket(domain: i32, type_: i32, protocol: i32, ctx: &Context) -> Resu
     );
 
     let is_nonblocking = sock_flags.contains(SockFlags::SOCK_NONBLOCK);
+    let net_ns = ctx.thread_local.borrow_ns_proxy().unwrap().net_ns().clone();
     let file_like = match (domain, sock_type) {
         (CSocketAddrFamily::AF_UNIX, SockType::SOCK_STREAM) => {
-            UnixStreamSocket::new(is_nonblocking, false) as Arc<dyn FileLike>
+            UnixStreamSocket::new(is_nonblocking, false, net_ns) as Arc<dyn FileLike>
         }
         (CSocketAddrFamily::AF_UNIX, SockType::SOCK_SEQPACKET) => {
-            UnixStreamSocket::new(is_nonblocking, true) as Arc<dyn FileLike>
+            UnixStreamSocket::new(is_nonblocking, true, net_ns) as Arc<dyn FileLike>
         }
         (CSocketAddrFamily::AF_UNIX, SockType::SOCK_RAW | SockType::SOCK_DGRAM) => {
-            UnixDatagramSocket::new(is_nonblocking) as Arc<dyn FileLike>
+            UnixDatagramSocket::new(is_nonblocking, net_ns) as Arc<dyn FileLike>
         }
         (CSocketAddrFamily::AF_INET | CSocketAddrFamily::AF_INET6, SockType::SOCK_STREAM) => {
             let protocol = Protocol::try_from(protocol)?;
@@ -45,30 +48,77 @@ pub fn sys_socket(domain: i32, type_: i32, protocol: i32, ctx: &Context) -> Resu
                         CSocketAddrFamily::AF_INET6 => IpAddressFamily::IPv6,
                         _ => unreachable!(),
                     };
-                    StreamSocket::new(is_nonblocking, family) as Arc<dyn FileLike>
+                    StreamSocket::new(is_nonblocking, family, net_ns) as Arc<dyn FileLike>
                 }
                 _ => return_errno_with_message!(Errno::EAFNOSUPPORT, "unsupported protocol"),
             }
         }
-        (CSocketAddrFamily::AF_INET, SockType::SOCK_DGRAM) => {
+        (CSocketAddrFamily::AF_INET | CSocketAddrFamily::AF_INET6, SockType::SOCK_DGRAM) => {
             let protocol = Protocol::try_from(protocol)?;
             debug!("protocol = {:?}", protocol);
             match protocol {
                 Protocol::IPPROTO_IP | Protocol::IPPROTO_UDP => {
-                    DatagramSocket::new(is_nonblocking) as Arc<dyn FileLike>
+                    let family = match domain {
+                        CSocketAddrFamily::AF_INET => IpAddressFamily::IPv4,
+                        CSocketAddrFamily::AF_INET6 => IpAddressFamily::IPv6,
+                        _ => unreachable!(),
+                    };
+                    DatagramSocket::new(is_nonblocking, family, net_ns) as Arc<dyn FileLike>
+                }
+                Protocol::IPPROTO_ICMP if domain == CSocketAddrFamily::AF_INET => {
+                    RawSocket::new_ping(
+                        is_nonblocking,
+                        IpAddressFamily::IPv4,
+                        net_ns,
+                        ctx.posix_thread,
+                    )? as Arc<dyn FileLike>
+                }
+                Protocol::IPPROTO_ICMPV6 if domain == CSocketAddrFamily::AF_INET6 => {
+                    RawSocket::new_ping(
+                        is_nonblocking,
+                        IpAddressFamily::IPv6,
+                        net_ns,
+                        ctx.posix_thread,
+                    )? as Arc<dyn FileLike>
                 }
                 _ => return_errno_with_message!(Errno::EAFNOSUPPORT, "unsupported protocol"),
             }
         }
+        (CSocketAddrFamily::AF_INET | CSocketAddrFamily::AF_INET6, SockType::SOCK_RAW) => {
+            // Raw IP sockets accept any protocol number, not just the ones in `Protocol`.
+            // Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/ipv4/af_inet.c>
+            let protocol = match u8::try_from(protocol) {
+                Ok(0) => return_errno_with_message!(
+                    Errno::EPROTONOSUPPORT,
+                    "the protocol of raw IP sockets must be specified"
+                ),
+                Ok(protocol) => protocol,
+                Err(_) => return_errno_with_message!(Errno::EINVAL, "the protocol is invalid"),
+            };
+            debug!("protocol = {:?}", protocol);
+            let family = match domain {
+                CSocketAddrFamily::AF_INET => IpAddressFamily::IPv4,
+                CSocketAddrFamily::AF_INET6 => IpAddressFamily::IPv6,
+                _ => unreachable!(),
+            };
+            RawSocket::new_raw(is_nonblocking, family, protocol, net_ns, ctx.posix_thread)?
+                as Arc<dyn FileLike>
+        }
         (CSocketAddrFamily::AF_NETLINK, SockType::SOCK_RAW | SockType::SOCK_DGRAM) => {
             let netlink_family = StandardNetlinkProtocol::try_from(protocol as u32);
             debug!("netlink family = {:?}", netlink_family);
             match netlink_family {
                 Ok(StandardNetlinkProtocol::ROUTE) => {
-                    NetlinkRouteSocket::new(is_nonblocking) as Arc<dyn FileLike>
+                    NetlinkRouteSocket::new(is_nonblocking, net_ns) as Arc<dyn FileLike>
+                }
+                Ok(StandardNetlinkProtocol::SOCK_DIAG) => {
+                    NetlinkSockDiagSocket::new(is_nonblocking, net_ns) as Arc<dyn FileLike>
                 }
                 Ok(StandardNetlinkProtocol::KOBJECT_UEVENT) => {
-                    NetlinkUeventSocket::new(is_nonblocking) as Arc<dyn FileLike>
+                    NetlinkUeventSocket::new(is_nonblocking, net_ns) as Arc<dyn FileLike>
+                }
+                Ok(StandardNetlinkProtocol::NETFILTER) => {
+                    NetlinkNetfilterSocket::new(is_nonblocking, net_ns) as Arc<dyn FileLike>
                 }
                 Ok(_) => {
                     return_errno_with_message!(
@@ -87,8 +137,34 @@ pub fn sys_socket(domain: i32, type_: i32, protocol: i32, ctx: &Context) -> Resu
                 }
             }
         }
+        (CSocketAddrFamily::AF_PACKET, SockType::SOCK_RAW | SockType::SOCK_DGRAM) => {
+            // The protocol is the EtherType in network byte order.
+            // Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/packet/af_packet.c> (`packet_create`)
+            let protocol = u16::from_be(protocol as u16);
+            debug!("protocol = {:#06x}", protocol);
+            match sock_type {
+                SockType::SOCK_RAW => {
+                    PacketSocket::new_raw(is_nonblocking, protocol, net_ns, ctx.posix_thread)?
+                        as Arc<dyn FileLike>
+                }
+                SockType::SOCK_DGRAM => {
+                    PacketSocket::new_dgram(is_nonblocking, protocol, net_ns, ctx.posix_thread)?
+                        as Arc<dyn FileLike>
+                }
+                _ => unreachable!(),
+            }
+        }
+        (CSocketAddrFamily::AF_PACKET, _) => {
+            return_errno_with_message!(
+                Errno::ESOCKTNOSUPPORT,
+                "the socket type is not supported by packet sockets"
+            );
+        }
         (CSocketAddrFamily::AF_VSOCK, SockType::SOCK_STREAM) => {
-            VsockStreamSocket::new(is_nonblocking)? as Arc<dyn FileLike>
+            VsockStreamSocket::new(is_nonblocking, false)? as Arc<dyn FileLike>
+        }
+        (CSocketAddrFamily::AF_VSOCK, SockType::SOCK_SEQPACKET) => {
+            VsockStreamSocket::new(is_nonblocking, true)? as Arc<dyn FileLike>
         }
         _ => return_errno_with_message!(Errno::EAFNOSUPPORT, "unsupported domain"),
     };
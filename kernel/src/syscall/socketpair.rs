@@ -37,15 +37,16 @@ pub fn sys_socketpair(
     }
 
     let nonblocking = sock_flags.contains(SockFlags::SOCK_NONBLOCK);
+    let net_ns = ctx.thread_local.borrow_ns_proxy().unwrap().net_ns().clone();
     let (socket_a, socket_b) = match (domain, sock_type) {
         (CSocketAddrFamily::AF_UNIX, SockType::SOCK_STREAM) => {
-            file_pair!(UnixStreamSocket::new_pair(nonblocking, false))
+            file_pair!(UnixStreamSocket::new_pair(nonblocking, false, net_ns))
         }
         (CSocketAddrFamily::AF_UNIX, SockType::SOCK_SEQPACKET) => {
-            file_pair!(UnixStreamSocket::new_pair(nonblocking, true))
+            file_pair!(UnixStreamSocket::new_pair(nonblocking, true, net_ns))
         }
         (CSocketAddrFamily::AF_UNIX, SockType::SOCK_RAW | SockType::SOCK_DGRAM) => {
-            file_pair!(UnixDatagramSocket::new_pair(nonblocking))
+            file_pair!(UnixDatagramSocket::new_pair(nonblocking, net_ns))
         }
         _ => return_errno_with_message!(
             Errno::EAFNOSUPPORT,
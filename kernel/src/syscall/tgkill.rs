@@ -39,6 +39,18 @@ fn do_sys_tgkill(tgid: Option<Pid>, tid: Tid, sig_num: u8, ctx: &Context) -> Res
         return_errno_with_message!(Errno::EINVAL, "non-positive TGIDs or TIDs are not valid");
     }
 
+    let pid_ns = ctx.process.pid_ns();
+    let tid = pid_ns
+        .global_id(tid)
+        .ok_or_else(|| Error::with_message(Errno::ESRCH, "the target thread does not exist"))?;
+    let tgid = tgid
+        .map(|tgid| {
+            pid_ns.global_id(tgid).ok_or_else(|| {
+                Error::with_message(Errno::ESRCH, "the target process does not exist")
+            })
+        })
+        .transpose()?;
+
     let signal = sig_num.map(|sig_num| {
         let pid = ctx.process.pid();
         let uid = ctx.posix_thread.credentials().ruid();
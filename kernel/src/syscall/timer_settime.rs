@@ -42,7 +42,10 @@ pub fn sys_timer_settime(
         let timeout = if (flags & TIMER_ABSTIME) == 0 {
             Timeout::After(expire_time)
         } else {
-            Timeout::When(expire_time)
+            // The absolute time is seen in the time namespace of the current thread.
+            let ns_proxy = ctx.thread_local.borrow_ns_proxy();
+            let time_ns = ns_proxy.unwrap().time_ns();
+            Timeout::When(time_ns.to_host_expired_time(timer.timer_manager(), expire_time))
         };
         timer_guard.set_timeout(timeout);
     }
@@ -11,7 +11,8 @@ use crate::{
         signal::{
             c_types::siginfo_t,
             constants::{
-                CLD_CONTINUED, CLD_EXITED, CLD_KILLED, CLD_STOPPED, CLD_TRAPPED, SIGCHLD, SIGCONT,
+                CLD_CONTINUED, CLD_DUMPED, CLD_EXITED, CLD_KILLED, CLD_STOPPED, CLD_TRAPPED,
+                SIGCHLD, SIGCONT,
             },
         },
     },
@@ -53,7 +54,7 @@ pub fn sys_waitid(
     if infoq_addr != 0 {
         let siginfo = {
             let (si_code, si_status) = calculate_si_code_and_si_status(&wait_status);
-            let pid = wait_status.pid();
+            let pid = ctx.process.pid_ns().local_id(wait_status.pid());
             let uid = wait_status.uid();
 
             let mut siginfo = siginfo_t::new(SIGCHLD, si_code);
@@ -72,18 +73,20 @@ pub fn sys_waitid(
 fn calculate_si_code_and_si_status(wait_status: &WaitStatus) -> (i32, i32) {
     let parse_exit_code = |exit_code: u32| {
         const NORMAL_EXIT_MASK: u32 = 0xff;
+        const CORE_DUMPED_MASK: u32 = 0x80;
 
         // If the process exits normally, the lowest 8 bits of `status_code`
         // will be zero. In this case, we return the actual exit code by
         // shifting the `status_code` right by 8 bits.
         if (exit_code & NORMAL_EXIT_MASK) == 0 {
             (CLD_EXITED, (exit_code >> 8) as i32)
+        } else if (exit_code & CORE_DUMPED_MASK) != 0 {
+            (CLD_DUMPED, (exit_code & !CORE_DUMPED_MASK) as i32)
         } else {
             (CLD_KILLED, exit_code as i32)
         }
     };
 
-    // TODO: Add supports for `CLD_DUMPED`.
     match wait_status {
         WaitStatus::Zombie(process) => {
             let exit_code = process.status().exit_code();
@@ -15,6 +15,7 @@ mod core;
 pub mod cpu_time_stats;
 mod softirq;
 mod system_time;
+pub mod time_ns;
 pub mod timerfd;
 pub mod wait;
 
@@ -0,0 +1,330 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use core::time::Duration;
+
+use spin::Once;
+
+use super::{
+    NSEC_PER_SEC, TimerManager,
+    clocks::{BootTimeClock, MonotonicClock},
+};
+use crate::{
+    fs::pseudofs::{NsCommonOps, NsType, StashedDentry},
+    prelude::*,
+    process::{UserNamespace, credentials::capabilities::CapSet, posix_thread::PosixThread},
+    syscall::ClockId,
+    vm::vmar::Vmar,
+};
+#[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
+use crate::{vdso, vm::page_cache::Vmo};
+
+/// The time namespace.
+///
+/// A time namespace shifts `CLOCK_MONOTONIC` and `CLOCK_BOOTTIME` (including their variants) by
+/// per-namespace offsets. The offsets can be changed via `/proc/[pid]/timens_offsets` until a
+/// thread enters the namespace for the first time. After that, the offsets are frozen.
+///
+/// The offsets are also visible to the vDSO. Processes in a non-initial time namespace map a
+/// vvar VMO of their own, which tells the vDSO library to apply the offsets.
+pub struct TimeNamespace {
+    inner: SpinLock<Inner>,
+    /// The vvar VMO that is mapped into the processes in the namespace.
+    ///
+    /// This is created when the offsets are frozen. It stays `None` for the initial time
+    /// namespace, whose processes map the vvar pages of the global vDSO VMO.
+    #[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
+    vvar_vmo: Mutex<Option<Arc<Vmo>>>,
+    owner: Arc<UserNamespace>,
+    stashed_dentry: StashedDentry,
+}
+
+struct Inner {
+    offsets: TimeNsOffsets,
+    is_frozen: bool,
+}
+
+impl TimeNamespace {
+    /// Returns a reference to the singleton initial time namespace.
+    pub fn get_init_singleton() -> &'static Arc<TimeNamespace> {
+        static INIT: Once<Arc<TimeNamespace>> = Once::new();
+
+        INIT.call_once(|| {
+            let owner = UserNamespace::get_init_singleton().clone();
+            // The offsets of the initial time namespace can never be changed.
+            Self::new(TimeNsOffsets::default(), true, owner)
+        })
+    }
+
+    fn new(offsets: TimeNsOffsets, is_frozen: bool, owner: Arc<UserNamespace>) -> Arc<Self> {
+        Arc::new(Self {
+            inner: SpinLock::new(Inner { offsets, is_frozen }),
+            #[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
+            vvar_vmo: Mutex::new(None),
+            owner,
+            stashed_dentry: StashedDentry::new(),
+        })
+    }
+
+    /// Clones a new time namespace from `self`.
+    ///
+    /// The new time namespace inherits the offsets, which can be changed until a thread enters it.
+    pub fn new_clone(
+        &self,
+        owner: Arc<UserNamespace>,
+        posix_thread: &PosixThread,
+    ) -> Result<Arc<Self>> {
+        owner.check_cap(CapSet::SYS_ADMIN, posix_thread)?;
+        Ok(Self::new(self.offsets(), false, owner))
+    }
+
+    /// Returns the clock offsets of the namespace.
+    pub fn offsets(&self) -> TimeNsOffsets {
+        self.inner.lock().offsets
+    }
+
+    /// Sets the offsets of the specified clocks.
+    ///
+    /// Only `CLOCK_MONOTONIC` and `CLOCK_BOOTTIME` can be specified. All the offsets are checked
+    /// before any of them is set.
+    ///
+    /// # Errors
+    ///
+    /// This method will fail with
+    /// - `EPERM` if the caller does not have the SYS_TIME capability in the owner user namespace;
+    /// - `ERANGE` if a shifted clock would be negative or too large;
+    /// - `EACCES` if a thread has entered the namespace, which freezes the offsets.
+    pub fn set_offsets(
+        &self,
+        offsets: &[(ClockId, ClockOffset)],
+        posix_thread: &PosixThread,
+    ) -> Result<()> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.18/source/kernel/time/namespace.c>
+
+        self.owner.check_cap(CapSet::SYS_TIME, posix_thread)?;
+
+        for (clock_id, offset) in offsets {
+            let now = match clock_id {
+                ClockId::CLOCK_MONOTONIC => MonotonicClock::get().read_time(),
+                ClockId::CLOCK_BOOTTIME => BootTimeClock::get().read_time(),
+                _ => return_errno_with_message!(Errno::EINVAL, "the clock cannot be offset"),
+            };
+
+            if offset.secs.unsigned_abs() > KTIME_SEC_MAX as u64 {
+                return_errno_with_message!(Errno::ERANGE, "the offset is out of range");
+            }
+            let shifted_nanos = now.as_nanos() as i128 + offset.as_nanos();
+            let shifted_secs = shifted_nanos.div_euclid(NSEC_PER_SEC as i128);
+            if shifted_secs < 0 || shifted_secs > (KTIME_SEC_MAX / 2) as i128 {
+                return_errno_with_message!(Errno::ERANGE, "the shifted clock is out of range");
+            }
+        }
+
+        let mut inner = self.inner.lock();
+        if inner.is_frozen {
+            return_errno_with_message!(
+                Errno::EACCES,
+                "the offsets cannot be changed after a thread has entered the time namespace"
+            );
+        }
+        for (clock_id, offset) in offsets {
+            match clock_id {
+                ClockId::CLOCK_MONOTONIC => inner.offsets.monotonic = *offset,
+                ClockId::CLOCK_BOOTTIME => inner.offsets.boottime = *offset,
+                _ => unreachable!(),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Converts a time of the clock in the host (i.e., the initial time namespace) to the time
+    /// seen in this namespace.
+    pub fn to_ns_time(&self, clock_id: ClockId, time: Duration) -> Duration {
+        match self.offset_of(clock_id) {
+            Some(offset) => offset.apply(time),
+            None => time,
+        }
+    }
+
+    /// Converts a time of the clock seen in this namespace to the time in the host.
+    pub fn to_host_time(&self, clock_id: ClockId, time: Duration) -> Duration {
+        match self.offset_of(clock_id) {
+            Some(offset) => offset.revert(time),
+            None => time,
+        }
+    }
+
+    /// Converts an absolute expiration time of a timer seen in this namespace to the time in the
+    /// host.
+    ///
+    /// The clock is identified by the timer manager of the timer.
+    pub fn to_host_expired_time(
+        &self,
+        timer_manager: &Arc<TimerManager>,
+        time: Duration,
+    ) -> Duration {
+        if Arc::ptr_eq(timer_manager, MonotonicClock::timer_manager()) {
+            self.to_host_time(ClockId::CLOCK_MONOTONIC, time)
+        } else if Arc::ptr_eq(timer_manager, BootTimeClock::timer_manager()) {
+            self.to_host_time(ClockId::CLOCK_BOOTTIME, time)
+        } else {
+            time
+        }
+    }
+
+    fn offset_of(&self, clock_id: ClockId) -> Option<ClockOffset> {
+        let offsets = self.offsets();
+        match clock_id {
+            ClockId::CLOCK_MONOTONIC
+            | ClockId::CLOCK_MONOTONIC_RAW
+            | ClockId::CLOCK_MONOTONIC_COARSE => Some(offsets.monotonic),
+            ClockId::CLOCK_BOOTTIME => Some(offsets.boottime),
+            ClockId::CLOCK_REALTIME
+            | ClockId::CLOCK_REALTIME_COARSE
+            | ClockId::CLOCK_PROCESS_CPUTIME_ID
+            | ClockId::CLOCK_THREAD_CPUTIME_ID => None,
+        }
+    }
+
+    /// Makes the vDSO in `vmar` use the offsets of this namespace.
+    ///
+    /// This should be called when a process enters the namespace. The offsets are frozen at the
+    /// first call.
+    #[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
+    pub fn join_vdso(&self, vmar: &Vmar) -> Result<()> {
+        if core::ptr::eq(self, Self::get_init_singleton().as_ref()) {
+            let Some(vdso_vmo) = vdso::vdso_vmo() else {
+                return Ok(());
+            };
+            return vdso::remap_vvar(vmar, &vdso_vmo);
+        }
+
+        let vvar_vmo = {
+            let mut vvar_vmo = self.vvar_vmo.lock();
+            if vvar_vmo.is_none() {
+                let offsets = self.freeze();
+                *vvar_vmo = vdso::new_timens_vvar_vmo(&offsets);
+            }
+            let Some(vvar_vmo) = vvar_vmo.as_ref() else {
+                return Ok(());
+            };
+            vvar_vmo.clone()
+        };
+        vdso::remap_vvar(vmar, &vvar_vmo)
+    }
+
+    /// Makes the vDSO in `vmar` use the offsets of this namespace.
+    ///
+    /// This should be called when a process enters the namespace. The offsets are frozen at the
+    /// first call.
+    // TODO: Add vDSO support for other architectures.
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "riscv64")))]
+    pub fn join_vdso(&self, _vmar: &Vmar) -> Result<()> {
+        self.freeze();
+        Ok(())
+    }
+
+    /// Freezes the offsets and returns them.
+    fn freeze(&self) -> TimeNsOffsets {
+        let mut inner = self.inner.lock();
+        inner.is_frozen = true;
+        inner.offsets
+    }
+}
+
+impl NsCommonOps for TimeNamespace {
+    const TYPE: NsType = NsType::Time;
+
+    fn owner_user_ns(&self) -> Option<&Arc<UserNamespace>> {
+        Some(&self.owner)
+    }
+
+    fn parent(&self) -> Result<&Arc<Self>> {
+        return_errno_with_message!(
+            Errno::EINVAL,
+            "a time namespace does not have a parent namespace"
+        );
+    }
+
+    fn stashed_dentry(&self) -> &StashedDentry {
+        &self.stashed_dentry
+    }
+}
+
+/// The clock offsets of a time namespace.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TimeNsOffsets {
+    monotonic: ClockOffset,
+    boottime: ClockOffset,
+}
+
+impl TimeNsOffsets {
+    /// Returns the offset of `CLOCK_MONOTONIC`.
+    pub fn monotonic(&self) -> ClockOffset {
+        self.monotonic
+    }
+
+    /// Returns the offset of `CLOCK_BOOTTIME`.
+    pub fn boottime(&self) -> ClockOffset {
+        self.boottime
+    }
+}
+
+/// The offset of a clock in a time namespace.
+///
+/// Like `struct timespec64` in Linux, the offset consists of a signed number of seconds and a
+/// non-negative number of nanoseconds less than one second.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ClockOffset {
+    secs: i64,
+    nanos: u32,
+}
+
+impl ClockOffset {
+    /// Creates a new offset.
+    ///
+    /// This method will fail with `EINVAL` if `nanos` is not less than one second.
+    pub fn new(secs: i64, nanos: u32) -> Result<Self> {
+        if nanos as i64 >= NSEC_PER_SEC {
+            return_errno_with_message!(Errno::EINVAL, "the nanoseconds are out of range");
+        }
+        Ok(Self { secs, nanos })
+    }
+
+    /// Returns the seconds of the offset.
+    pub fn secs(&self) -> i64 {
+        self.secs
+    }
+
+    /// Returns the nanoseconds of the offset.
+    pub fn nanos(&self) -> u32 {
+        self.nanos
+    }
+
+    fn as_nanos(&self) -> i128 {
+        self.secs as i128 * NSEC_PER_SEC as i128 + self.nanos as i128
+    }
+
+    fn apply(&self, time: Duration) -> Duration {
+        duration_from_nanos(time.as_nanos() as i128 + self.as_nanos())
+    }
+
+    fn revert(&self, time: Duration) -> Duration {
+        duration_from_nanos(time.as_nanos() as i128 - self.as_nanos())
+    }
+}
+
+/// Converts nanoseconds to a [`Duration`], saturating negative values to zero.
+fn duration_from_nanos(nanos: i128) -> Duration {
+    if nanos <= 0 {
+        return Duration::ZERO;
+    }
+    let secs = (nanos / NSEC_PER_SEC as i128).min(u64::MAX as i128) as u64;
+    let nanos = (nanos % NSEC_PER_SEC as i128) as u32;
+    Duration::new(secs, nanos)
+}
+
+/// The maximum number of seconds that can be represented by `ktime_t` in Linux.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/linux/time64.h>
+const KTIME_SEC_MAX: i64 = i64::MAX / NSEC_PER_SEC;
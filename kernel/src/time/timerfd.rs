@@ -137,6 +137,7 @@ impl TimerfdFile {
         expire_time: Duration,
         interval: Duration,
         flags: TFDSetTimeFlags,
+        ctx: &Context,
     ) -> (Duration, Duration) {
         let mut timer_guard = self.timer.lock();
 
@@ -157,7 +158,10 @@ impl TimerfdFile {
             }
 
             let timeout = if flags.contains(TFDSetTimeFlags::TFD_TIMER_ABSTIME) {
-                Timeout::When(expire_time)
+                // The absolute time is seen in the time namespace of the current thread.
+                let ns_proxy = ctx.thread_local.borrow_ns_proxy();
+                let time_ns = ns_proxy.unwrap().time_ns();
+                Timeout::When(time_ns.to_host_expired_time(self.timer.timer_manager(), expire_time))
             } else {
                 Timeout::After(expire_time)
             };
@@ -0,0 +1,412 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Classic BPF programs.
+//!
+//! Classic BPF (cBPF) is the filter language accepted by seccomp and socket filters.
+//! A program is a sequence of [`CSockFilter`] instructions that operates on an
+//! accumulator `A`, an index register `X`, and 16 scratch memory words. The
+//! program reads its input through [`BpfInput`] and finishes with a 32-bit return value.
+//!
+//! Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/core/filter.c>
+
+use ostd::mm::VmIo;
+
+use crate::prelude::*;
+
+/// The maximum number of instructions in a program.
+pub const BPF_MAXINSNS: usize = 4096;
+
+/// The number of scratch memory words.
+const BPF_MEMWORDS: usize = 16;
+
+// Instruction classes.
+pub const BPF_LD: u16 = 0x00;
+pub const BPF_LDX: u16 = 0x01;
+pub const BPF_ST: u16 = 0x02;
+pub const BPF_STX: u16 = 0x03;
+pub const BPF_ALU: u16 = 0x04;
+pub const BPF_JMP: u16 = 0x05;
+pub const BPF_RET: u16 = 0x06;
+pub const BPF_MISC: u16 = 0x07;
+
+// Load sizes.
+pub const BPF_W: u16 = 0x00;
+pub const BPF_H: u16 = 0x08;
+pub const BPF_B: u16 = 0x10;
+
+// Load modes.
+pub const BPF_IMM: u16 = 0x00;
+pub const BPF_ABS: u16 = 0x20;
+pub const BPF_IND: u16 = 0x40;
+pub const BPF_MEM: u16 = 0x60;
+pub const BPF_LEN: u16 = 0x80;
+pub const BPF_MSH: u16 = 0xa0;
+
+// ALU operations.
+pub const BPF_ADD: u16 = 0x00;
+pub const BPF_SUB: u16 = 0x10;
+pub const BPF_MUL: u16 = 0x20;
+pub const BPF_DIV: u16 = 0x30;
+pub const BPF_OR: u16 = 0x40;
+pub const BPF_AND: u16 = 0x50;
+pub const BPF_LSH: u16 = 0x60;
+pub const BPF_RSH: u16 = 0x70;
+pub const BPF_NEG: u16 = 0x80;
+pub const BPF_MOD: u16 = 0x90;
+pub const BPF_XOR: u16 = 0xa0;
+
+// Jump operations.
+pub const BPF_JA: u16 = 0x00;
+pub const BPF_JEQ: u16 = 0x10;
+pub const BPF_JGT: u16 = 0x20;
+pub const BPF_JGE: u16 = 0x30;
+pub const BPF_JSET: u16 = 0x40;
+
+// Operand sources.
+pub const BPF_K: u16 = 0x00;
+pub const BPF_X: u16 = 0x08;
+/// The return value source that denotes the accumulator.
+pub const BPF_A: u16 = 0x10;
+
+// Miscellaneous operations.
+pub const BPF_TAX: u16 = 0x00;
+pub const BPF_TXA: u16 = 0x80;
+
+/// Returns the class of an instruction code.
+pub const fn bpf_class(code: u16) -> u16 {
+    code & 0x07
+}
+
+/// Returns the load size of an instruction code.
+pub const fn bpf_size(code: u16) -> u16 {
+    code & 0x18
+}
+
+/// Returns the load mode of an instruction code.
+pub const fn bpf_mode(code: u16) -> u16 {
+    code & 0xe0
+}
+
+/// Returns the ALU or jump operation of an instruction code.
+pub const fn bpf_op(code: u16) -> u16 {
+    code & 0xf0
+}
+
+/// Returns the operand source of an instruction code.
+pub const fn bpf_src(code: u16) -> u16 {
+    code & 0x08
+}
+
+/// Returns the miscellaneous operation of an instruction code.
+pub const fn bpf_miscop(code: u16) -> u16 {
+    code & 0xf8
+}
+
+/// A classic BPF instruction (`struct sock_filter` in Linux).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub struct CSockFilter {
+    pub code: u16,
+    pub jt: u8,
+    pub jf: u8,
+    pub k: u32,
+}
+
+/// A classic BPF program in user space (`struct sock_fprog` in Linux).
+#[padding_struct]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+pub struct CSockFprog {
+    pub len: u16,
+    pub filter: Vaddr,
+}
+
+impl CSockFprog {
+    /// Reads the instructions of the program from user space.
+    ///
+    /// # Errors
+    ///
+    /// Returns `EINVAL` if the program is empty, has more than [`BPF_MAXINSNS`] instructions, or
+    /// has a null address.
+    pub fn read_insns(&self, ctx: &Context) -> Result<Vec<CSockFilter>> {
+        let len = self.len as usize;
+        if len == 0 || len > BPF_MAXINSNS {
+            return_errno_with_message!(Errno::EINVAL, "the BPF program length is invalid");
+        }
+        if self.filter == 0 {
+            return_errno_with_message!(Errno::EINVAL, "the BPF program address is null");
+        }
+
+        let user_space = ctx.user_space();
+        (0..len)
+            .map(|index| {
+                user_space.read_val::<CSockFilter>(self.filter + index * size_of::<CSockFilter>())
+            })
+            .collect()
+    }
+}
+
+/// The input of a classic BPF program.
+pub trait BpfInput {
+    /// Returns the size of the input in bytes.
+    ///
+    /// This is the value loaded by the `BPF_LEN` mode.
+    fn size(&self) -> u32;
+
+    /// Loads a word, a half-word, or a byte at `offset`.
+    ///
+    /// `size` is one of [`BPF_W`], [`BPF_H`], and [`BPF_B`].
+    ///
+    /// Returns `None` if the load is out of bounds, which aborts the program with a return value
+    /// of zero.
+    fn load(&self, offset: u32, size: u16) -> Option<u32>;
+}
+
+/// A validated classic BPF program.
+#[derive(Debug)]
+pub struct BpfProgram {
+    insns: Box<[CSockFilter]>,
+}
+
+impl BpfProgram {
+    /// Validates the instructions and creates a program.
+    ///
+    /// Like Linux, a program is rejected if it contains unknown instructions, jumps out of the
+    /// program, divides by a zero constant, reads scratch memory that may not have been written,
+    /// or does not end with a return instruction.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/core/filter.c#L1043>
+    pub fn new(insns: Vec<CSockFilter>) -> Result<Self> {
+        let len = insns.len();
+        if len == 0 || len > BPF_MAXINSNS {
+            return_errno_with_message!(Errno::EINVAL, "the BPF program length is invalid");
+        }
+
+        for (pc, insn) in insns.iter().enumerate() {
+            check_insn(insn, pc, len)?;
+        }
+        check_mem_accesses(&insns)?;
+
+        if bpf_class(insns[len - 1].code) != BPF_RET {
+            return_errno_with_message!(Errno::EINVAL, "the BPF program does not end with a return");
+        }
+
+        Ok(Self {
+            insns: insns.into_boxed_slice(),
+        })
+    }
+
+    /// Returns the number of instructions in the program.
+    pub fn num_insns(&self) -> usize {
+        self.insns.len()
+    }
+
+    /// Runs the program with the input and returns its return value.
+    pub fn run(&self, input: &dyn BpfInput) -> u32 {
+        let mut a: u32 = 0;
+        let mut x: u32 = 0;
+        let mut mem = [0u32; BPF_MEMWORDS];
+        let mut pc = 0;
+
+        // The validation guarantees that `pc` is always in bounds, that all jumps go forward,
+        // and that the last instruction returns.
+        loop {
+            let insn = &self.insns[pc];
+            let k = insn.k;
+            pc += 1;
+
+            match bpf_class(insn.code) {
+                BPF_LD | BPF_LDX => {
+                    let value = match bpf_mode(insn.code) {
+                        BPF_IMM => k,
+                        BPF_ABS => match input.load(k, bpf_size(insn.code)) {
+                            Some(value) => value,
+                            None => return 0,
+                        },
+                        BPF_IND => match input.load(x.wrapping_add(k), bpf_size(insn.code)) {
+                            Some(value) => value,
+                            None => return 0,
+                        },
+                        BPF_MEM => mem[k as usize],
+                        BPF_LEN => input.size(),
+                        BPF_MSH => match input.load(k, BPF_B) {
+                            Some(value) => (value & 0xf) << 2,
+                            None => return 0,
+                        },
+                        _ => unreachable!(),
+                    };
+                    if bpf_class(insn.code) == BPF_LD {
+                        a = value;
+                    } else {
+                        x = value;
+                    }
+                }
+                BPF_ST => mem[k as usize] = a,
+                BPF_STX => mem[k as usize] = x,
+                BPF_ALU => {
+                    let operand = if bpf_src(insn.code) == BPF_X { x } else { k };
+                    a = match bpf_op(insn.code) {
+                        BPF_ADD => a.wrapping_add(operand),
+                        BPF_SUB => a.wrapping_sub(operand),
+                        BPF_MUL => a.wrapping_mul(operand),
+                        BPF_DIV => match a.checked_div(operand) {
+                            Some(value) => value,
+                            None => return 0,
+                        },
+                        BPF_MOD => match a.checked_rem(operand) {
+                            Some(value) => value,
+                            None => return 0,
+                        },
+                        BPF_OR => a | operand,
+                        BPF_AND => a & operand,
+                        BPF_XOR => a ^ operand,
+                        BPF_LSH => a.checked_shl(operand).unwrap_or(0),
+                        BPF_RSH => a.checked_shr(operand).unwrap_or(0),
+                        BPF_NEG => a.wrapping_neg(),
+                        _ => unreachable!(),
+                    };
+                }
+                BPF_JMP => {
+                    let operand = if bpf_src(insn.code) == BPF_X { x } else { k };
+                    let is_taken = match bpf_op(insn.code) {
+                        BPF_JA => {
+                            pc += k as usize;
+                            continue;
+                        }
+                        BPF_JEQ => a == operand,
+                        BPF_JGT => a > operand,
+                        BPF_JGE => a >= operand,
+                        BPF_JSET => a & operand != 0,
+                        _ => unreachable!(),
+                    };
+                    pc += if is_taken { insn.jt } else { insn.jf } as usize;
+                }
+                BPF_RET => {
+                    return match bpf_size(insn.code) {
+                        BPF_K => k,
+                        BPF_X => x,
+                        BPF_A => a,
+                        _ => unreachable!(),
+                    };
+                }
+                BPF_MISC => {
+                    if bpf_miscop(insn.code) == BPF_TXA {
+                        a = x;
+                    } else {
+                        x = a;
+                    }
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+}
+
+fn check_insn(insn: &CSockFilter, pc: usize, len: usize) -> Result<()> {
+    let code = insn.code;
+    let k = insn.k;
+
+    let size = bpf_size(code);
+    let mode = bpf_mode(code);
+    let op = bpf_op(code);
+    let src = bpf_src(code);
+
+    let is_valid = code & !0xff == 0
+        && match bpf_class(code) {
+            BPF_LD => match mode {
+                BPF_IMM | BPF_MEM | BPF_LEN => size == BPF_W,
+                BPF_ABS | BPF_IND => matches!(size, BPF_W | BPF_H | BPF_B),
+                _ => false,
+            },
+            BPF_LDX => match mode {
+                BPF_IMM | BPF_MEM | BPF_LEN => size == BPF_W,
+                BPF_MSH => size == BPF_B,
+                _ => false,
+            },
+            BPF_ST | BPF_STX => code & !0x07 == 0,
+            BPF_ALU => op <= BPF_XOR && (op != BPF_NEG || src == BPF_K),
+            BPF_JMP => op <= BPF_JSET && (op != BPF_JA || src == BPF_K),
+            BPF_RET => mode == 0 && matches!(size, BPF_K | BPF_X | BPF_A),
+            BPF_MISC => matches!(bpf_miscop(code), BPF_TAX | BPF_TXA),
+            _ => false,
+        };
+    if !is_valid {
+        return_errno_with_message!(Errno::EINVAL, "the BPF instruction is invalid");
+    }
+
+    match bpf_class(code) {
+        BPF_LD | BPF_LDX if mode == BPF_MEM => check_mem_index(k)?,
+        BPF_ST | BPF_STX => check_mem_index(k)?,
+        BPF_ALU if src == BPF_K => match op {
+            BPF_DIV | BPF_MOD if k == 0 => {
+                return_errno_with_message!(Errno::EINVAL, "the BPF program divides by zero");
+            }
+            BPF_LSH | BPF_RSH if k >= 32 => {
+                return_errno_with_message!(Errno::EINVAL, "the BPF shift amount is too large");
+            }
+            _ => (),
+        },
+        BPF_JMP => {
+            let remaining = len - pc - 1;
+            let is_in_bounds = if op == BPF_JA {
+                (k as usize) < remaining
+            } else {
+                (insn.jt as usize) < remaining && (insn.jf as usize) < remaining
+            };
+            if !is_in_bounds {
+                return_errno_with_message!(Errno::EINVAL, "the BPF jump is out of bounds");
+            }
+        }
+        _ => (),
+    }
+
+    Ok(())
+}
+
+fn check_mem_index(k: u32) -> Result<()> {
+    if k as usize >= BPF_MEMWORDS {
+        return_errno_with_message!(Errno::EINVAL, "the BPF scratch memory index is invalid");
+    }
+    Ok(())
+}
+
+/// Checks that no scratch memory word is read before it is written on any path.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/core/filter.c#L975>
+fn check_mem_accesses(insns: &[CSockFilter]) -> Result<()> {
+    const ALL_VALID: u16 = u16::MAX;
+
+    // `masks[pc]` is the set of memory words that are valid on all paths reaching `pc` by jumps.
+    let mut masks = vec![ALL_VALID; insns.len()];
+    let mut mem_valid = 0u16;
+
+    for (pc, insn) in insns.iter().enumerate() {
+        mem_valid &= masks[pc];
+
+        let code = insn.code;
+        let k = insn.k;
+        match bpf_class(code) {
+            BPF_ST | BPF_STX => mem_valid |= 1 << k,
+            BPF_LD | BPF_LDX if bpf_mode(code) == BPF_MEM => {
+                if mem_valid & (1 << k) == 0 {
+                    return_errno_with_message!(
+                        Errno::EINVAL,
+                        "the BPF program reads uninitialized scratch memory"
+                    );
+                }
+            }
+            BPF_JMP => {
+                if bpf_op(code) == BPF_JA {
+                    masks[pc + 1 + k as usize] &= mem_valid;
+                } else {
+                    masks[pc + 1 + insn.jt as usize] &= mem_valid;
+                    masks[pc + 1 + insn.jf as usize] &= mem_valid;
+                }
+                mem_valid = ALL_VALID;
+            }
+            _ => (),
+        }
+    }
+
+    Ok(())
+}
@@ -408,6 +408,39 @@ impl<const MAGIC: u8, const NR: u8, const IS_MODERN: bool, T: Pod>
     }
 }
 
+/// Input and output data whose type does not match the ioctl command.
+///
+/// Some ioctl commands encode a wrong data size or direction for historical reasons. For example,
+/// `TUNSETIFF` is defined as `_IOW('T', 202, int)`, but its argument points to a `struct ifreq`
+/// that is both read and written.
+///
+/// `D` describes the data encoded in the ioctl command, and `T` describes the actual data type.
+pub struct MisencodedData<D, T>(PhantomData<D>, PhantomData<T>);
+
+impl<D: DataSpec, T> DataSpec for MisencodedData<D, T> {
+    const SIZE: Option<u16> = D::SIZE;
+    const DIR: IoctlDir = D::DIR;
+}
+
+impl<D: DataSpec, T> PtrDataSpec for MisencodedData<D, T> {
+    type Pointee = T;
+}
+
+impl<const MAGIC: u8, const NR: u8, const IS_MODERN: bool, D: DataSpec, T: Pod>
+    Ioctl<MAGIC, NR, IS_MODERN, MisencodedData<D, T>>
+{
+    /// Reads the ioctl argument from userspace.
+    pub fn read(&self) -> Result<T> {
+        Ok(self.with_data_ptr_unchecked_access(|ptr| ptr.read())?)
+    }
+
+    /// Writes the ioctl argument to userspace.
+    pub fn write(&self, val: &T) -> Result<()> {
+        self.with_data_ptr_unchecked_access(|ptr| ptr.write(val))?;
+        Ok(())
+    }
+}
+
 /// A marker that denotes the input is passed by value (i.e., encoded in the ioctl argument).
 pub enum PassByVal {}
 /// A marker that denotes the input is passed by pointer (i.e., pointed to by the ioctl argument).
@@ -7,6 +7,7 @@ use ostd::{mm::VmIo, task::Task};
 use super::{
     ip::{CSocketAddrInet, CSocketAddrInet6},
     netlink::CSocketAddrNetlink,
+    packet::CSocketAddrLinkLayer,
     unix,
     vsock::CSocketAddrVm,
 };
@@ -185,6 +186,13 @@ pub fn read_socket_addr_from_user(addr: Vaddr, addr_len: usize) -> Result<Socket
             let addr = CSocketAddrVm::from_first_bytes(storage.as_bytes());
             SocketAddr::Vsock(addr.into())
         }
+        Ok(CSocketAddrFamily::AF_PACKET) => {
+            if addr_len < size_of::<CSocketAddrLinkLayer>() {
+                return_errno_with_message!(Errno::EINVAL, "the socket address length is too small");
+            }
+            let addr = CSocketAddrLinkLayer::from_first_bytes(storage.as_bytes());
+            SocketAddr::Packet(addr.into())
+        }
         _ => {
             return_errno_with_message!(
                 Errno::EAFNOSUPPORT,
@@ -276,6 +284,9 @@ pub fn write_socket_addr_with_max_len(
         SocketAddr::Vsock(addr) => {
             write_c_socket_address_util::<CSocketAddrVm, _>(*addr, dest, max_len as usize)?
         }
+        SocketAddr::Packet(addr) => {
+            write_c_socket_address_util::<CSocketAddrLinkLayer, _>(*addr, dest, max_len as usize)?
+        }
     };
 
     Ok(actual_len as i32)
@@ -14,7 +14,7 @@ use crate::prelude::*;
 /// <https://elixir.bootlin.com/linux/v6.10.2/source/include/uapi/linux/in.h#L256>.
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Pod)]
-pub(super) struct CSocketAddrInet {
+pub struct CSocketAddrInet {
     /// Address family (AF_INET).
     sin_family: u16,
     /// Port number.
@@ -92,7 +92,7 @@ impl From<CPortNum> for PortNum {
 /// This corresponds to `struct sockaddr_in6` in Linux.
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Pod)]
-pub(super) struct CSocketAddrInet6 {
+pub struct CSocketAddrInet6 {
     /// Address family (AF_INET6).
     sin6_family: u16,
     /// Port number.
@@ -2,9 +2,12 @@
 
 use int_to_c_enum::TryFromInt;
 
-use super::{RawSocketOption, SocketOption, impl_raw_socket_option};
+use super::{RawSocketOption, SocketOption, impl_raw_sock_option_set_only, impl_raw_socket_option};
 use crate::{
-    net::socket::ip::options::{Hdrincl, Recverr, Tos, Ttl},
+    net::socket::ip::options::{
+        AddMembership, DropMembership, Hdrincl, MulticastAll, MulticastIf, MulticastLoop,
+        MulticastTtl, PktInfo, RecvTos, RecvTtl, Recverr, Tos, Ttl,
+    },
     prelude::*,
 };
 
@@ -70,7 +73,16 @@ pub fn new_ip_option(name: i32) -> Result<Box<dyn RawSocketOption>> {
         CIpOptionName::TOS => Ok(Box::new(Tos::new())),
         CIpOptionName::TTL => Ok(Box::new(Ttl::new())),
         CIpOptionName::HDRINCL => Ok(Box::new(Hdrincl::new())),
+        CIpOptionName::PKTINFO => Ok(Box::new(PktInfo::new())),
         CIpOptionName::RECVERR => Ok(Box::new(Recverr::new())),
+        CIpOptionName::RECVTTL => Ok(Box::new(RecvTtl::new())),
+        CIpOptionName::RECVTOS => Ok(Box::new(RecvTos::new())),
+        CIpOptionName::MULTICAST_IF => Ok(Box::new(MulticastIf::new())),
+        CIpOptionName::MULTICAST_TTL => Ok(Box::new(MulticastTtl::new())),
+        CIpOptionName::MULTICAST_LOOP => Ok(Box::new(MulticastLoop::new())),
+        CIpOptionName::ADD_MEMBERSHIP => Ok(Box::new(AddMembership::new())),
+        CIpOptionName::DROP_MEMBERSHIP => Ok(Box::new(DropMembership::new())),
+        CIpOptionName::MULTICAST_ALL => Ok(Box::new(MulticastAll::new())),
         _ => return_errno_with_message!(Errno::ENOPROTOOPT, "unsupported ip level option"),
     }
 }
@@ -79,3 +91,12 @@ impl_raw_socket_option!(Ttl);
 impl_raw_socket_option!(Tos);
 impl_raw_socket_option!(Hdrincl);
 impl_raw_socket_option!(Recverr);
+impl_raw_socket_option!(PktInfo);
+impl_raw_socket_option!(RecvTtl);
+impl_raw_socket_option!(RecvTos);
+impl_raw_socket_option!(MulticastIf);
+impl_raw_socket_option!(MulticastTtl);
+impl_raw_socket_option!(MulticastLoop);
+impl_raw_sock_option_set_only!(AddMembership);
+impl_raw_sock_option_set_only!(DropMembership);
+impl_raw_socket_option!(MulticastAll);
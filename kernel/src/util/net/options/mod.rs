@@ -52,15 +52,21 @@
 //!
 
 use ip::new_ip_option;
+use ipv6::new_ipv6_option;
 use netlink::new_netlink_option;
+use packet::new_packet_option;
+use vsock::new_vsock_option;
 
 use crate::{net::socket::options::SocketOption, prelude::*};
 
 mod ip;
+mod ipv6;
 mod netlink;
+mod packet;
 mod socket;
 mod tcp;
 mod utils;
+mod vsock;
 
 use self::{socket::new_socket_option, tcp::new_tcp_option};
 
@@ -170,7 +176,10 @@ pub fn new_raw_socket_option(
         CSocketOptionLevel::SOL_SOCKET => new_socket_option(name),
         CSocketOptionLevel::SOL_IP => new_ip_option(name),
         CSocketOptionLevel::SOL_TCP => new_tcp_option(name),
+        CSocketOptionLevel::SOL_IPV6 => new_ipv6_option(name),
         CSocketOptionLevel::SOL_NETLINK => new_netlink_option(name),
+        CSocketOptionLevel::SOL_PACKET => new_packet_option(name),
+        CSocketOptionLevel::SOL_VSOCK => new_vsock_option(name),
         _ => return_errno_with_message!(Errno::EOPNOTSUPP, "unsupported option level"),
     }
 }
@@ -188,7 +197,10 @@ pub enum CSocketOptionLevel {
     SOL_SOCKET = 1,
     SOL_TCP = 6,
     SOL_UDP = 17,
+    /// The level of vsock options, which is the same as `AF_VSOCK`.
+    SOL_VSOCK = 40,
     SOL_IPV6 = 41,
     SOL_RAW = 255,
+    SOL_PACKET = 263,
     SOL_NETLINK = 270,
 }
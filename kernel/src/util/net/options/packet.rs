@@ -0,0 +1,52 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::{RawSocketOption, SocketOption, impl_raw_sock_option_set_only};
+use crate::{
+    net::socket::packet::{AddMembership, DropMembership},
+    prelude::*,
+};
+
+/// Socket options for packet sockets.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/if_packet.h#L47>.
+#[expect(non_camel_case_types)]
+#[expect(clippy::upper_case_acronyms)]
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, TryFromInt)]
+pub enum CPacketOptionName {
+    ADD_MEMBERSHIP = 1,
+    DROP_MEMBERSHIP = 2,
+    RECV_OUTPUT = 3,
+    RX_RING = 5,
+    STATISTICS = 6,
+    COPY_THRESH = 7,
+    AUXDATA = 8,
+    ORIGDEV = 9,
+    VERSION = 10,
+    HDRLEN = 11,
+    RESERVE = 12,
+    TX_RING = 13,
+    LOSS = 14,
+    VNET_HDR = 15,
+    TX_TIMESTAMP = 16,
+    TIMESTAMP = 17,
+    FANOUT = 18,
+    TX_HAS_OFF = 19,
+    QDISC_BYPASS = 20,
+    ROLLOVER_STATS = 21,
+    FANOUT_DATA = 22,
+    IGNORE_OUTGOING = 23,
+    VNET_HDR_SZ = 24,
+}
+
+pub fn new_packet_option(name: i32) -> Result<Box<dyn RawSocketOption>> {
+    let name = CPacketOptionName::try_from(name).map_err(|_| Errno::ENOPROTOOPT)?;
+    match name {
+        CPacketOptionName::ADD_MEMBERSHIP => Ok(Box::new(AddMembership::new())),
+        CPacketOptionName::DROP_MEMBERSHIP => Ok(Box::new(DropMembership::new())),
+        _ => return_errno_with_message!(Errno::ENOPROTOOPT, "unsupported packet option"),
+    }
+}
+
+impl_raw_sock_option_set_only!(AddMembership);
+impl_raw_sock_option_set_only!(DropMembership);
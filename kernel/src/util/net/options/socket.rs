@@ -2,12 +2,16 @@
 
 use ostd::mm::VmIo;
 
-use super::{RawSocketOption, impl_raw_sock_option_get_only, impl_raw_socket_option};
+use super::{
+    RawSocketOption, impl_raw_sock_option_get_only, impl_raw_sock_option_set_only,
+    impl_raw_socket_option,
+};
 use crate::{
     context::current_userspace,
     net::socket::options::{
-        AcceptConn, Broadcast, Error, KeepAlive, Linger, PassCred, PeerCred, PeerGroups, Priority,
-        RecvBuf, RecvBufForce, ReuseAddr, ReusePort, SendBuf, SendBufForce, SocketOption,
+        AcceptConn, AttachFilter, Broadcast, DetachFilter, Error, KeepAlive, Linger, PassCred,
+        PeerCred, PeerGroups, Priority, RecvBuf, RecvBufForce, ReuseAddr, ReusePort, SendBuf,
+        SendBufForce, SocketOption, Timestamp, TimestampNs,
     },
     prelude::*,
     process::Gid,
@@ -40,10 +44,12 @@ enum CSocketOptionName {
     PEERCRED = 17,
     ATTACH_FILTER = 26,
     DETACH_FILTER = 27,
+    TIMESTAMP_OLD = 29,
     ACCPETCONN = 30,
     PEERSEC = 31,
     SNDBUFFORCE = 32,
     RCVBUFFORCE = 33,
+    TIMESTAMPNS_OLD = 35,
     PEERGROUPS = 59,
     RCVTIMEO_NEW = 66,
     SNDTIMEO_NEW = 67,
@@ -63,9 +69,13 @@ pub fn new_socket_option(name: i32) -> Result<Box<dyn RawSocketOption>> {
         CSocketOptionName::REUSEPORT => Ok(Box::new(ReusePort::new())),
         CSocketOptionName::PASSCRED => Ok(Box::new(PassCred::new())),
         CSocketOptionName::PEERCRED => Ok(Box::new(PeerCred::new())),
+        CSocketOptionName::ATTACH_FILTER => Ok(Box::new(AttachFilter::new())),
+        CSocketOptionName::DETACH_FILTER => Ok(Box::new(DetachFilter::new())),
+        CSocketOptionName::TIMESTAMP_OLD => Ok(Box::new(Timestamp::new())),
         CSocketOptionName::ACCPETCONN => Ok(Box::new(AcceptConn::new())),
         CSocketOptionName::SNDBUFFORCE => Ok(Box::new(SendBufForce::new())),
         CSocketOptionName::RCVBUFFORCE => Ok(Box::new(RecvBufForce::new())),
+        CSocketOptionName::TIMESTAMPNS_OLD => Ok(Box::new(TimestampNs::new())),
         CSocketOptionName::PEERGROUPS => Ok(Box::new(PeerGroups::new())),
         _ => return_errno_with_message!(Errno::ENOPROTOOPT, "unsupported socket-level option"),
     }
@@ -82,7 +92,11 @@ impl_raw_socket_option!(Linger);
 impl_raw_socket_option!(ReusePort);
 impl_raw_socket_option!(PassCred);
 impl_raw_sock_option_get_only!(PeerCred);
+impl_raw_sock_option_set_only!(AttachFilter);
+impl_raw_sock_option_set_only!(DetachFilter);
 impl_raw_sock_option_get_only!(AcceptConn);
+impl_raw_socket_option!(Timestamp);
+impl_raw_socket_option!(TimestampNs);
 impl_raw_socket_option!(SendBufForce);
 impl_raw_socket_option!(RecvBufForce);
 
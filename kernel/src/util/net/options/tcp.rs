@@ -1,10 +1,10 @@
 // SPDX-License-Identifier: MPL-2.0
 
-use super::{RawSocketOption, SocketOption, impl_raw_socket_option};
+use super::{RawSocketOption, SocketOption, impl_raw_sock_option_get_only, impl_raw_socket_option};
 use crate::{
     net::socket::ip::stream_options::{
-        Congestion, DeferAccept, Inq, KeepCnt, KeepIdle, KeepIntvl, MaxSegment, NoDelay, SynCnt,
-        UserTimeout, WindowClamp,
+        Congestion, DeferAccept, Info, Inq, KeepCnt, KeepIdle, KeepIntvl, MaxSegment, NoDelay,
+        SynCnt, UserTimeout, WindowClamp,
     },
     prelude::*,
 };
@@ -35,6 +35,8 @@ pub enum CTcpOptionName {
     DEFER_ACCEPT = 9,
     /// Bound advertised window
     WINDOW_CLAMP = 10,
+    /// Information about this connection
+    INFO = 11,
     /// Congestion control algorithm
     CONGESTION = 13,
     /// How long for loss retry before timeout
@@ -54,6 +56,7 @@ pub fn new_tcp_option(name: i32) -> Result<Box<dyn RawSocketOption>> {
         CTcpOptionName::SYNCNT => Ok(Box::new(SynCnt::new())),
         CTcpOptionName::DEFER_ACCEPT => Ok(Box::new(DeferAccept::new())),
         CTcpOptionName::WINDOW_CLAMP => Ok(Box::new(WindowClamp::new())),
+        CTcpOptionName::INFO => Ok(Box::new(Info::new())),
         CTcpOptionName::CONGESTION => Ok(Box::new(Congestion::new())),
         CTcpOptionName::USER_TIMEOUT => Ok(Box::new(UserTimeout::new())),
         CTcpOptionName::INQ => Ok(Box::new(Inq::new())),
@@ -72,3 +75,4 @@ impl_raw_socket_option!(WindowClamp);
 impl_raw_socket_option!(Congestion);
 impl_raw_socket_option!(UserTimeout);
 impl_raw_socket_option!(Inq);
+impl_raw_sock_option_get_only!(Info);
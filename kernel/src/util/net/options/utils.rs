@@ -2,16 +2,23 @@
 
 use core::{num::NonZeroU8, time::Duration};
 
+use aster_bigtcp::wire::{Ipv4Address, Ipv6Address};
 use ostd::mm::VmIo;
 
 use crate::{
     context::current_userspace,
     net::socket::{
-        ip::{options::IpTtl, stream_options::CongestionControl},
+        ip::{
+            ipv6_options::{Ipv6Hops, Ipv6Mreq, Ipv6MulticastHops},
+            options::{IpMreqn, IpMulticastFlag, IpMulticastIf, IpMulticastTtl, IpTtl},
+            stream_options::{CongestionControl, TcpInfo},
+        },
+        packet::CPacketMreq,
         unix::CUserCred,
-        util::LingerOption,
+        util::{BPF_MAXINSNS, CSockFilter, LingerOption, SocketFilter},
     },
     prelude::*,
+    time::timeval_t,
 };
 
 /// Create an object by reading its C counterpart from the user space.
@@ -68,6 +75,29 @@ macro_rules! impl_read_write_for_32bit_type {
 impl_read_write_for_32bit_type!(i32);
 impl_read_write_for_32bit_type!(u32);
 
+impl ReadFromUser for u64 {
+    fn read_from_user(addr: Vaddr, max_len: u32) -> Result<Self> {
+        if (max_len as usize) < size_of::<u64>() {
+            return_errno_with_message!(Errno::EINVAL, "max_len is too short");
+        }
+
+        Ok(current_userspace!().read_val::<u64>(addr)?)
+    }
+}
+
+impl WriteToUser for u64 {
+    fn write_to_user(&self, addr: Vaddr, max_len: u32) -> Result<usize> {
+        let write_len = size_of::<u64>();
+
+        if (max_len as usize) < write_len {
+            return_errno_with_message!(Errno::EINVAL, "max_len is too short");
+        }
+
+        current_userspace!().write_val(addr, self)?;
+        Ok(write_len)
+    }
+}
+
 impl ReadFromUser for bool {
     fn read_from_user(addr: Vaddr, max_len: u32) -> Result<Self> {
         let val = i32::read_from_user(addr, max_len)?;
@@ -121,6 +151,215 @@ impl WriteToUser for IpTtl {
     }
 }
 
+impl ReadFromUser for Ipv6Hops {
+    fn read_from_user(addr: Vaddr, max_len: u32) -> Result<Self> {
+        let val = i32::read_from_user(addr, max_len)?;
+
+        let hops_value = match val {
+            -1 => None,
+            0..=255 => Some(val as u8),
+            _ => return_errno_with_message!(Errno::EINVAL, "invalid hop limit value"),
+        };
+
+        Ok(Ipv6Hops::new(hops_value))
+    }
+}
+
+impl WriteToUser for Ipv6Hops {
+    fn write_to_user(&self, addr: Vaddr, max_len: u32) -> Result<usize> {
+        let val = self.get() as i32;
+        val.write_to_user(addr, max_len)
+    }
+}
+
+/// Reads the value of an IP-level option that is an `int`.
+///
+/// Like Linux, a single byte is read if the value is shorter than an `int`.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/ipv4/ip_sockglue.c>
+fn read_ip_int_from_user(addr: Vaddr, max_len: u32) -> Result<i32> {
+    if max_len as usize >= size_of::<i32>() {
+        i32::read_from_user(addr, max_len)
+    } else if max_len as usize >= size_of::<u8>() {
+        Ok(current_userspace!().read_val::<u8>(addr)? as i32)
+    } else {
+        return_errno_with_message!(Errno::EINVAL, "max_len is too short")
+    }
+}
+
+/// Writes the value of an IP-level option that is an `int`.
+///
+/// Like Linux, a single byte is written if the buffer is shorter than an `int` and the value
+/// fits in a byte.
+fn write_ip_int_to_user(val: i32, addr: Vaddr, max_len: u32) -> Result<usize> {
+    if (max_len as usize) < size_of::<i32>()
+        && max_len as usize >= size_of::<u8>()
+        && let Ok(val) = u8::try_from(val)
+    {
+        current_userspace!().write_val(addr, &val)?;
+        return Ok(size_of::<u8>());
+    }
+
+    val.write_to_user(addr, max_len)
+}
+
+impl ReadFromUser for IpMulticastTtl {
+    fn read_from_user(addr: Vaddr, max_len: u32) -> Result<Self> {
+        let val = read_ip_int_from_user(addr, max_len)?;
+        IpMulticastTtl::new(val)
+    }
+}
+
+impl WriteToUser for IpMulticastTtl {
+    fn write_to_user(&self, addr: Vaddr, max_len: u32) -> Result<usize> {
+        write_ip_int_to_user(self.get() as i32, addr, max_len)
+    }
+}
+
+impl ReadFromUser for IpMulticastFlag {
+    fn read_from_user(addr: Vaddr, max_len: u32) -> Result<Self> {
+        let val = read_ip_int_from_user(addr, max_len)?;
+        Ok(IpMulticastFlag::new(val != 0))
+    }
+}
+
+impl WriteToUser for IpMulticastFlag {
+    fn write_to_user(&self, addr: Vaddr, max_len: u32) -> Result<usize> {
+        write_ip_int_to_user(self.get() as i32, addr, max_len)
+    }
+}
+
+/// A request to join or leave an IPv4 multicast group (`struct ip_mreqn`).
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/in.h>.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct CIpMreqn {
+    imr_multiaddr: [u8; 4],
+    imr_address: [u8; 4],
+    imr_ifindex: i32,
+}
+
+/// The legacy version of [`CIpMreqn`] without the interface index (`struct ip_mreq`).
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/in.h>.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct CIpMreq {
+    imr_multiaddr: [u8; 4],
+    imr_interface: [u8; 4],
+}
+
+impl From<CIpMreqn> for IpMreqn {
+    fn from(value: CIpMreqn) -> Self {
+        Self {
+            multiaddr: Ipv4Address::from(value.imr_multiaddr),
+            address: Ipv4Address::from(value.imr_address),
+            ifindex: value.imr_ifindex as u32,
+        }
+    }
+}
+
+impl From<CIpMreq> for IpMreqn {
+    fn from(value: CIpMreq) -> Self {
+        Self {
+            multiaddr: Ipv4Address::from(value.imr_multiaddr),
+            address: Ipv4Address::from(value.imr_interface),
+            ifindex: 0,
+        }
+    }
+}
+
+impl ReadFromUser for IpMreqn {
+    fn read_from_user(addr: Vaddr, max_len: u32) -> Result<Self> {
+        let user_space = current_userspace!();
+
+        if max_len as usize >= size_of::<CIpMreqn>() {
+            Ok(user_space.read_val::<CIpMreqn>(addr)?.into())
+        } else if max_len as usize >= size_of::<CIpMreq>() {
+            Ok(user_space.read_val::<CIpMreq>(addr)?.into())
+        } else {
+            return_errno_with_message!(Errno::EINVAL, "max_len is too short")
+        }
+    }
+}
+
+impl ReadFromUser for IpMulticastIf {
+    fn read_from_user(addr: Vaddr, max_len: u32) -> Result<Self> {
+        // Like Linux, the interface can be specified by `struct ip_mreqn`, `struct ip_mreq`, or
+        // `struct in_addr`.
+        if max_len as usize >= size_of::<CIpMreq>() {
+            let mreqn = IpMreqn::read_from_user(addr, max_len)?;
+            return Ok(IpMulticastIf {
+                addr: mreqn.address,
+                ifindex: mreqn.ifindex,
+            });
+        }
+
+        if (max_len as usize) < size_of::<[u8; 4]>() {
+            return_errno_with_message!(Errno::EINVAL, "max_len is too short");
+        }
+        let addr = current_userspace!().read_val::<[u8; 4]>(addr)?;
+
+        Ok(IpMulticastIf {
+            addr: Ipv4Address::from(addr),
+            ifindex: 0,
+        })
+    }
+}
+
+impl WriteToUser for IpMulticastIf {
+    fn write_to_user(&self, addr: Vaddr, max_len: u32) -> Result<usize> {
+        let write_len = size_of::<[u8; 4]>();
+
+        if (max_len as usize) < write_len {
+            return_errno_with_message!(Errno::EINVAL, "max_len is too short");
+        }
+
+        current_userspace!().write_val(addr, &self.addr.octets())?;
+        Ok(write_len)
+    }
+}
+
+impl ReadFromUser for Ipv6MulticastHops {
+    fn read_from_user(addr: Vaddr, max_len: u32) -> Result<Self> {
+        let val = i32::read_from_user(addr, max_len)?;
+        Ipv6MulticastHops::new(val)
+    }
+}
+
+impl WriteToUser for Ipv6MulticastHops {
+    fn write_to_user(&self, addr: Vaddr, max_len: u32) -> Result<usize> {
+        let val = self.get() as i32;
+        val.write_to_user(addr, max_len)
+    }
+}
+
+/// A request to join or leave an IPv6 multicast group (`struct ipv6_mreq`).
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/in6.h>.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct CIpv6Mreq {
+    ipv6mr_multiaddr: [u8; 16],
+    ipv6mr_interface: i32,
+}
+
+impl ReadFromUser for Ipv6Mreq {
+    fn read_from_user(addr: Vaddr, max_len: u32) -> Result<Self> {
+        if (max_len as usize) < size_of::<CIpv6Mreq>() {
+            return_errno_with_message!(Errno::EINVAL, "max_len is too short");
+        }
+
+        let mreq = current_userspace!().read_val::<CIpv6Mreq>(addr)?;
+
+        Ok(Ipv6Mreq {
+            multiaddr: Ipv6Address::from(mreq.ipv6mr_multiaddr),
+            ifindex: mreq.ipv6mr_interface as u32,
+        })
+    }
+}
+
 impl WriteToUser for Option<Error> {
     fn write_to_user(&self, addr: Vaddr, max_len: u32) -> Result<usize> {
         let write_len = size_of::<i32>();
@@ -178,9 +417,12 @@ impl ReadFromUser for CongestionControl {
 
         current_userspace!().read_bytes(addr, dst.as_mut())?;
 
-        let name = core::str::from_utf8(dst)
+        // Like Linux, the name ends at the first null character.
+        let name_len = dst.iter().position(|byte| *byte == 0).unwrap_or(dst.len());
+        let name = core::str::from_utf8(&dst[..name_len])
             .map_err(|_| Error::with_message(Errno::ENOENT, "non-UTF8 congestion name"))?;
-        CongestionControl::new(name)
+        CongestionControl::from_name(name)
+            .ok_or_else(|| Error::with_message(Errno::ENOENT, "unsupported congestion name"))
     }
 }
 
@@ -200,6 +442,17 @@ impl WriteToUser for CongestionControl {
     }
 }
 
+impl WriteToUser for TcpInfo {
+    fn write_to_user(&self, addr: Vaddr, max_len: u32) -> Result<usize> {
+        // Like Linux, the information is truncated if the buffer is too short.
+        let write_len = size_of::<TcpInfo>().min(max_len as usize);
+
+        current_userspace!().write_bytes(addr, &self.as_bytes()[..write_len])?;
+
+        Ok(write_len)
+    }
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Pod)]
 struct CLinger {
@@ -225,6 +478,54 @@ impl From<CLinger> for LingerOption {
     }
 }
 
+/// A classic BPF program.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.18/source/include/uapi/linux/filter.h#L31>.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod)]
+struct CSockFprog {
+    len: u16,
+    _padding: [u8; 6],
+    filter: Vaddr,
+}
+
+impl ReadFromUser for SocketFilter {
+    fn read_from_user(addr: Vaddr, max_len: u32) -> Result<Self> {
+        // Reference: <https://elixir.bootlin.com/linux/v6.18/source/net/core/sock.c> (`copy_bpf_fprog_from_user`)
+        if max_len as usize != size_of::<CSockFprog>() {
+            return_errno_with_message!(Errno::EINVAL, "the length of the BPF program is invalid");
+        }
+
+        let user_space = current_userspace!();
+        let c_fprog = user_space.read_val::<CSockFprog>(addr)?;
+
+        let len = c_fprog.len as usize;
+        if len == 0 || len > BPF_MAXINSNS {
+            return_errno_with_message!(Errno::EINVAL, "the BPF program length is invalid");
+        }
+
+        let mut insns = vec![CSockFilter::new_zeroed(); len];
+        user_space.read_bytes(c_fprog.filter, insns.as_mut_bytes())?;
+
+        SocketFilter::new(insns)
+    }
+}
+
+impl ReadFromUser for CPacketMreq {
+    fn read_from_user(addr: Vaddr, max_len: u32) -> Result<Self> {
+        if (max_len as usize) < size_of::<CPacketMreq>() {
+            return_errno_with_message!(Errno::EINVAL, "max_len is too short");
+        }
+
+        let mreq = current_userspace!().read_val::<CPacketMreq>(addr)?;
+        if mreq.mr_alen as usize > mreq.mr_address.len() {
+            return_errno_with_message!(Errno::EINVAL, "the address length is too large");
+        }
+
+        Ok(mreq)
+    }
+}
+
 impl WriteToUser for CUserCred {
     fn write_to_user(&self, addr: Vaddr, max_len: u32) -> Result<usize> {
         let write_len = size_of::<CUserCred>();
@@ -238,3 +539,26 @@ impl WriteToUser for CUserCred {
         Ok(write_len)
     }
 }
+
+impl ReadFromUser for timeval_t {
+    fn read_from_user(addr: Vaddr, max_len: u32) -> Result<Self> {
+        if (max_len as usize) < size_of::<timeval_t>() {
+            return_errno_with_message!(Errno::EINVAL, "max_len is too short");
+        }
+
+        Ok(current_userspace!().read_val::<timeval_t>(addr)?)
+    }
+}
+
+impl WriteToUser for timeval_t {
+    fn write_to_user(&self, addr: Vaddr, max_len: u32) -> Result<usize> {
+        let write_len = size_of::<timeval_t>();
+
+        if (max_len as usize) < write_len {
+            return_errno_with_message!(Errno::EINVAL, "max_len is too short");
+        }
+
+        current_userspace!().write_val(addr, self)?;
+        Ok(write_len)
+    }
+}
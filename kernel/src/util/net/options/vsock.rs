@@ -0,0 +1,48 @@
+// SPDX-License-Identifier: MPL-2.0
+
+use super::{RawSocketOption, SocketOption, impl_raw_socket_option};
+use crate::{
+    net::socket::vsock::{BufferMaxSize, BufferMinSize, BufferSize, ConnectTimeout},
+    prelude::*,
+};
+
+/// Socket options for vsock sockets.
+///
+/// Reference: <https://elixir.bootlin.com/linux/v6.16.8/source/include/uapi/linux/vm_sockets.h>.
+#[expect(non_camel_case_types)]
+#[expect(clippy::upper_case_acronyms)]
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, TryFromInt)]
+pub enum CVsockOptionName {
+    BUFFER_SIZE = 0,
+    BUFFER_MIN_SIZE = 1,
+    BUFFER_MAX_SIZE = 2,
+    PEER_HOST_VM_ID = 3,
+    TRUSTED = 5,
+    /// The connect timeout as a `struct timeval`, which is `SO_VM_SOCKETS_CONNECT_TIMEOUT` on
+    /// 64-bit platforms.
+    CONNECT_TIMEOUT_OLD = 6,
+    NONBLOCK_TXRX = 7,
+    /// The connect timeout as a `struct __kernel_sock_timeval`.
+    CONNECT_TIMEOUT_NEW = 8,
+}
+
+pub fn new_vsock_option(name: i32) -> Result<Box<dyn RawSocketOption>> {
+    let name = CVsockOptionName::try_from(name).map_err(|_| Errno::ENOPROTOOPT)?;
+    match name {
+        CVsockOptionName::BUFFER_SIZE => Ok(Box::new(BufferSize::new())),
+        CVsockOptionName::BUFFER_MIN_SIZE => Ok(Box::new(BufferMinSize::new())),
+        CVsockOptionName::BUFFER_MAX_SIZE => Ok(Box::new(BufferMaxSize::new())),
+        // On 64-bit platforms, `struct timeval` and `struct __kernel_sock_timeval` have the same
+        // layout.
+        CVsockOptionName::CONNECT_TIMEOUT_OLD | CVsockOptionName::CONNECT_TIMEOUT_NEW => {
+            Ok(Box::new(ConnectTimeout::new()))
+        }
+        _ => return_errno_with_message!(Errno::ENOPROTOOPT, "unsupported vsock option"),
+    }
+}
+
+impl_raw_socket_option!(BufferSize);
+impl_raw_socket_option!(BufferMinSize);
+impl_raw_socket_option!(BufferMaxSize);
+impl_raw_socket_option!(ConnectTimeout);
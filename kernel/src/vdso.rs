@@ -11,6 +11,9 @@
 //! [`VdsoData`] instance with necessary time-related information, and a Virtual Memory Object
 //! ([`Vmo`]) that encapsulates both the data and the vDSO routines. The VMO is intended to be
 //! mapped into the address space of every user space process for efficient access.
+//!
+//! For processes in a non-initial time namespace, the vvar pages (i.e., the data segment) of the
+//! vDSO are replaced by a per-namespace VMO, which informs the vDSO routines of the clock offsets.
 
 use alloc::sync::Arc;
 use core::{mem::ManuallyDrop, time::Duration};
@@ -26,15 +29,22 @@ use ostd_pod::IntoBytes;
 use spin::Once;
 
 use crate::{
+    prelude::*,
     syscall::ClockId,
     time::{
         START_TIME, SystemTime,
         clocks::MonotonicClock,
+        time_ns::{ClockOffset, TimeNsOffsets},
         timer::{Timeout, TimerGuard},
     },
-    vm::page_cache::{Vmo, VmoOptions},
+    vm::{
+        page_cache::{Vmo, VmoOptions},
+        perms::VmPerms,
+        vmar::{VMAR_CAP_ADDR, VMAR_LOWEST_ADDR, Vmar, VmarMapOffset},
+    },
 };
 
+const CLOCK_BOOTTIME_ALARM: usize = 9;
 const CLOCK_TAI: usize = 11;
 const VDSO_BASES: usize = CLOCK_TAI + 1;
 const DEFAULT_CLOCK_MODE: VdsoClockMode = VdsoClockMode::Tsc;
@@ -46,6 +56,11 @@ static VDSO: Once<Arc<Vdso>> = Once::new();
 enum VdsoClockMode {
     None = 0,
     Tsc = 1,
+    /// The mode that tells the vDSO library to read the vDSO data in the timens page and apply
+    /// the offsets of the time namespace.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.2.10/source/include/vdso/clocksource.h>
+    TimeNs = i32::MAX as isize,
 }
 
 /// An instant used in [`VdsoData`]
@@ -72,6 +87,19 @@ impl VdsoInstant {
             nanos_info: 0,
         }
     }
+
+    /// Creates an instant that holds a clock offset of a time namespace.
+    ///
+    /// In the vDSO data of a time namespace, the offsets (i.e., `struct timens_offset` in Linux)
+    /// share the same memory as the instants.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.2.10/source/include/vdso/datapage.h>
+    fn from_offset(offset: ClockOffset) -> Self {
+        Self {
+            secs: offset.secs() as u64,
+            nanos_info: offset.nanos() as u64,
+        }
+    }
 }
 
 #[repr(C)]
@@ -134,6 +162,25 @@ impl VdsoData {
         }
     }
 
+    /// Creates the vDSO data of a time namespace.
+    ///
+    /// Reference: <https://elixir.bootlin.com/linux/v6.2.10/source/kernel/time/namespace.c>
+    fn new_timens(offsets: &TimeNsOffsets) -> Self {
+        let mut vdso_data = Self::empty();
+        vdso_data.seq = 1;
+        vdso_data.set_clock_mode(VdsoClockMode::TimeNs);
+
+        let monotonic = VdsoInstant::from_offset(offsets.monotonic());
+        let boottime = VdsoInstant::from_offset(offsets.boottime());
+        vdso_data.basetime[ClockId::CLOCK_MONOTONIC as usize] = monotonic;
+        vdso_data.basetime[ClockId::CLOCK_MONOTONIC_RAW as usize] = monotonic;
+        vdso_data.basetime[ClockId::CLOCK_MONOTONIC_COARSE as usize] = monotonic;
+        vdso_data.basetime[ClockId::CLOCK_BOOTTIME as usize] = boottime;
+        vdso_data.basetime[CLOCK_BOOTTIME_ALARM] = boottime;
+
+        vdso_data
+    }
+
     /// Initializes vDSO data based on the default clock source.
     fn init(&mut self) {
         let clocksource = aster_time::default_clocksource();
@@ -208,6 +255,14 @@ struct Vdso {
     ///
     /// Note: This frame should only be updated while holding the spin lock on [`Self::data`].
     data_frame: UFrame,
+    /// Frames that mirror the vDSO data for the time namespaces.
+    ///
+    /// Each frame is the timens page of a vvar VMO created by [`new_timens_vvar_vmo`], from which
+    /// the vDSO library reads the vDSO data in a non-initial time namespace. The frames whose VMOs
+    /// have been dropped are removed lazily.
+    ///
+    /// Note: These frames should only be updated while holding the spin lock on [`Self::data`].
+    timens_frames: SpinLock<Vec<(Weak<Vmo>, UFrame)>>,
 }
 
 /// The binary of a prebuilt Linux vDSO library.
@@ -265,32 +320,88 @@ impl Vdso {
             data: SpinLock::new(vdso_data),
             vmo: vdso_vmo,
             data_frame: data_frame.into(),
+            timens_frames: SpinLock::new(Vec::new()),
         }
     }
 
-    fn update_high_res_instant(&self, instant: Instant, instant_cycles: u64) {
-        let mut data = self.data.lock();
-
-        data.update_high_res_instant(instant, instant_cycles);
+    /// Creates a vvar VMO for a time namespace.
+    fn new_timens_vvar_vmo(&self, offsets: &TimeNsOffsets) -> Arc<Vmo> {
+        let vvar_vmo = VmoOptions::new(VDSO_VMO_LAYOUT.data_segment_size)
+            .alloc()
+            .unwrap();
 
-        // Update begins.
-        self.data_frame
-            .write_once(vdso_data_field_offset!(seq), &1)
+        let timens_data = VdsoData::new_timens(offsets);
+        let mut reader = VmReader::from(timens_data.as_bytes()).to_fallible();
+        vvar_vmo
+            .write(VDSO_VMO_LAYOUT.data_offset, &mut reader)
             .unwrap();
 
-        self.data_frame
-            .write_val(vdso_data_field_offset!(last_cycles), &instant_cycles)
+        let timens_frame: UFrame = vvar_vmo
+            .try_commit_page(VDSO_VMO_LAYOUT.timens_page_offset)
+            .unwrap()
+            .into();
+
+        let data = self.data.disable_irq().lock();
+        timens_frame
+            .write_val(VDSO_VMO_LAYOUT.data_offset, &*data)
             .unwrap();
-        for clock_id in HIGH_RES_CLOCK_IDS {
-            self.update_data_frame_instant(clock_id, &mut data);
+        self.timens_frames
+            .disable_irq()
+            .lock()
+            .push((Arc::downgrade(&vvar_vmo), timens_frame));
+
+        vvar_vmo
+    }
+
+    /// Returns whether the VMO is the vDSO VMO or a vvar VMO of a time namespace.
+    fn is_vdso_vmo(&self, vmo: &Arc<Vmo>) -> bool {
+        Arc::ptr_eq(vmo, &self.vmo)
+            || self
+                .timens_frames
+                .disable_irq()
+                .lock()
+                .iter()
+                .any(|(timens_vmo, _)| core::ptr::eq(timens_vmo.as_ptr(), Arc::as_ptr(vmo)))
+    }
+
+    /// Calls `f` on each frame that contains the vDSO data.
+    ///
+    /// The caller should hold the spin lock on [`Self::data`].
+    fn for_each_data_frame(&self, mut f: impl FnMut(&UFrame)) {
+        f(&self.data_frame);
+
+        let mut timens_frames = self.timens_frames.lock();
+        timens_frames.retain(|(timens_vmo, _)| timens_vmo.strong_count() > 0);
+        for (_, timens_frame) in timens_frames.iter() {
+            f(timens_frame);
         }
+    }
 
-        // Update finishes.
-        // FIXME: To synchronize with the vDSO library, this needs to be an atomic write with the
-        // Release memory order.
-        self.data_frame
-            .write_once(vdso_data_field_offset!(seq), &0)
-            .unwrap();
+    fn update_high_res_instant(&self, instant: Instant, instant_cycles: u64) {
+        let mut data = self.data.lock();
+
+        data.update_high_res_instant(instant, instant_cycles);
+
+        self.for_each_data_frame(|data_frame| {
+            // Update begins.
+            data_frame
+                .write_once(vdso_data_field_offset!(seq), &1)
+                .unwrap();
+
+            data_frame
+                .write_val(vdso_data_field_offset!(last_cycles), &instant_cycles)
+                .unwrap();
+            for clock_id in HIGH_RES_CLOCK_IDS {
+                update_data_frame_instant(data_frame, clock_id, &data);
+            }
+
+            // Update finishes.
+            // FIXME: To synchronize with the vDSO library, this needs to be an atomic write with
+            // the Release memory order.
+            data_frame
+                .write_once(vdso_data_field_offset!(seq), &0)
+                .unwrap();
+        });
     }
 
     fn update_coarse_res_instant(&self, instant: Instant) {
@@ -298,39 +409,40 @@ impl Vdso {
 
         data.update_coarse_res_instant(instant);
 
-        // Update begins.
-        self.data_frame
-            .write_once(vdso_data_field_offset!(seq), &1)
-            .unwrap();
+        self.for_each_data_frame(|data_frame| {
+            // Update begins.
+            data_frame
+                .write_once(vdso_data_field_offset!(seq), &1)
+                .unwrap();
 
-        for clock_id in COARSE_RES_CLOCK_IDS {
-            self.update_data_frame_instant(clock_id, &mut data);
-        }
+            for clock_id in COARSE_RES_CLOCK_IDS {
+                update_data_frame_instant(data_frame, clock_id, &data);
+            }
 
-        // Update finishes.
-        // FIXME: To synchronize with the vDSO library, this needs to be an atomic write with the
-        // Release memory order.
-        self.data_frame
-            .write_once(vdso_data_field_offset!(seq), &0)
-            .unwrap();
+            // Update finishes.
+            // FIXME: To synchronize with the vDSO library, this needs to be an atomic write with
+            // the Release memory order.
+            data_frame
+                .write_once(vdso_data_field_offset!(seq), &0)
+                .unwrap();
+        });
     }
+}
 
-    /// Updates the requisite fields of the vDSO data in the frame.
-    fn update_data_frame_instant(&self, clockid: ClockId, data: &mut VdsoData) {
-        let clock_index = clockid as usize;
-
-        let secs_offset =
-            vdso_data_field_offset!(basetime) + clock_index * size_of::<VdsoInstant>();
-        let nanos_info_offset = vdso_data_field_offset!(basetime)
-            + core::mem::offset_of!(VdsoInstant, nanos_info)
-            + clock_index * size_of::<VdsoInstant>();
-        self.data_frame
-            .write_val(secs_offset, &data.basetime[clock_index].secs)
-            .unwrap();
-        self.data_frame
-            .write_val(nanos_info_offset, &data.basetime[clock_index].nanos_info)
-            .unwrap();
-    }
+/// Updates the requisite fields of the vDSO data in the frame.
+fn update_data_frame_instant(data_frame: &UFrame, clockid: ClockId, data: &VdsoData) {
+    let clock_index = clockid as usize;
+
+    let secs_offset = vdso_data_field_offset!(basetime) + clock_index * size_of::<VdsoInstant>();
+    let nanos_info_offset = vdso_data_field_offset!(basetime)
+        + core::mem::offset_of!(VdsoInstant, nanos_info)
+        + clock_index * size_of::<VdsoInstant>();
+    data_frame
+        .write_val(secs_offset, &data.basetime[clock_index].secs)
+        .unwrap();
+    data_frame
+        .write_val(nanos_info_offset, &data.basetime[clock_index].nanos_info)
+        .unwrap();
 }
 
 /// Updates instants with respect to high-resolution clocks in vDSO data.
@@ -385,16 +497,65 @@ pub fn vdso_vmo() -> Option<Arc<Vmo>> {
     VDSO.get().map(|vdso| vdso.vmo.clone())
 }
 
+/// Creates a vvar VMO for a time namespace with the clock offsets.
+///
+/// The VMO covers the data segment of the vDSO VMO (see [`VDSO_VMO_LAYOUT`]). The vDSO data of
+/// the time namespace is placed in the first page, and the timens page mirrors the vDSO data.
+///
+/// This function will return `None` if vDSO does not exist (e.g., if it has not been initialized).
+pub fn new_timens_vvar_vmo(offsets: &TimeNsOffsets) -> Option<Arc<Vmo>> {
+    VDSO.get().map(|vdso| vdso.new_timens_vvar_vmo(offsets))
+}
+
+/// Returns whether the VMO is the vDSO VMO or a vvar VMO of a time namespace.
+pub fn is_vdso_vmo(vmo: &Arc<Vmo>) -> bool {
+    VDSO.get().is_some_and(|vdso| vdso.is_vdso_vmo(vmo))
+}
+
+/// Remaps the vvar pages of the vDSO in the VMAR to `vvar_vmo`.
+///
+/// `vvar_vmo` should be either the vDSO VMO or a vvar VMO of a time namespace. The vvar pages
+/// that have been unmapped or partially remapped by the user are left untouched.
+pub fn remap_vvar(vmar: &Vmar, vvar_vmo: &Arc<Vmo>) -> Result<()> {
+    let vvar_bases: Vec<Vaddr> = {
+        let query_guard = vmar.query(VMAR_LOWEST_ADDR..VMAR_CAP_ADDR);
+        query_guard
+            .iter()
+            .filter(|vm_mapping| {
+                vm_mapping.map_size() == VDSO_VMO_LAYOUT.data_segment_size
+                    && vm_mapping.backing_vmo().is_some_and(|(vmo, offset)| {
+                        offset == VDSO_VMO_LAYOUT.data_segment_offset
+                            && !Arc::ptr_eq(vmo, vvar_vmo)
+                            && is_vdso_vmo(vmo)
+                    })
+            })
+            .map(|vm_mapping| vm_mapping.map_to_addr())
+            .collect()
+    };
+
+    for vvar_base in vvar_bases {
+        vmar.new_map(VDSO_VMO_LAYOUT.data_segment_size, VmPerms::READ)?
+            .vmo(vvar_vmo.clone())
+            .vmo_offset(VDSO_VMO_LAYOUT.data_segment_offset)
+            .offset(VmarMapOffset::FixedReplace(vvar_base))
+            .build()?;
+    }
+
+    Ok(())
+}
+
 #[cfg(target_arch = "x86_64")]
 pub const VDSO_VMO_LAYOUT: VdsoVmoLayout = VdsoVmoLayout {
     // https://elixir.bootlin.com/linux/v6.2.10/source/arch/x86/entry/vdso/vdso-layout.lds.S#L20
     data_segment_offset: 0,
-    data_segment_size: PAGE_SIZE,
+    data_segment_size: 4 * PAGE_SIZE,
     // https://elixir.bootlin.com/linux/v6.2.10/source/arch/x86/entry/vdso/vdso-layout.lds.S#L19
     text_segment_offset: 4 * PAGE_SIZE,
     text_segment_size: PAGE_SIZE,
     // https://elixir.bootlin.com/linux/v6.2.10/source/arch/x86/include/asm/vvar.h#L51
     data_offset: 0x80,
+    // https://elixir.bootlin.com/linux/v6.2.10/source/arch/x86/entry/vdso/vdso-layout.lds.S
+    timens_page_offset: 3 * PAGE_SIZE,
 
     size: 5 * PAGE_SIZE,
 };
@@ -403,12 +564,14 @@ pub const VDSO_VMO_LAYOUT: VdsoVmoLayout = VdsoVmoLayout {
 pub const VDSO_VMO_LAYOUT: VdsoVmoLayout = VdsoVmoLayout {
     // https://elixir.bootlin.com/linux/v6.2.10/source/arch/riscv/kernel/vdso.c#L247
     data_segment_offset: 0,
-    data_segment_size: PAGE_SIZE,
+    data_segment_size: 2 * PAGE_SIZE,
     // https://elixir.bootlin.com/linux/v6.2.10/source/arch/riscv/kernel/vdso.c#L256
     text_segment_offset: 2 * PAGE_SIZE,
     text_segment_size: PAGE_SIZE,
     // https://elixir.bootlin.com/linux/v6.2.10/source/arch/riscv/kernel/vdso.c#L47
     data_offset: 0,
+    // https://elixir.bootlin.com/linux/v6.2.10/source/arch/riscv/kernel/vdso.c
+    timens_page_offset: PAGE_SIZE,
 
     size: 3 * PAGE_SIZE,
 };
@@ -419,6 +582,9 @@ pub struct VdsoVmoLayout {
     pub text_segment_offset: usize,
     pub text_segment_size: usize,
     pub data_offset: usize,
+    /// The offset of the timens page, which contains the vDSO data for the processes in a
+    /// non-initial time namespace.
+    pub timens_page_offset: usize,
     pub size: usize,
 }
 
@@ -435,6 +601,7 @@ const_assert!(
 );
 const_assert!(VDSO_VMO_LAYOUT.text_segment_size.is_multiple_of(PAGE_SIZE));
 const_assert!(VDSO_VMO_LAYOUT.size.is_multiple_of(PAGE_SIZE));
+const_assert!(VDSO_VMO_LAYOUT.timens_page_offset.is_multiple_of(PAGE_SIZE));
 
 // Ensure that the vDSO data at `VDSO_VMO_LAYOUT.data_offset` is in the data segment.
 //
@@ -444,3 +611,10 @@ const_assert!(
     VDSO_VMO_LAYOUT.data_offset + size_of::<VdsoData>()
         <= VDSO_VMO_LAYOUT.data_segment_offset + VDSO_VMO_LAYOUT.data_segment_size
 );
+
+// Ensure that the timens page is in the data segment, so it is included in the vvar VMOs of the
+// time namespaces.
+const_assert!(
+    VDSO_VMO_LAYOUT.timens_page_offset + PAGE_SIZE
+        <= VDSO_VMO_LAYOUT.data_segment_offset + VDSO_VMO_LAYOUT.data_segment_size
+);
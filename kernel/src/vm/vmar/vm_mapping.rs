@@ -85,6 +85,11 @@ pub struct VmMapping {
     ///
     /// All pages within the same `VmMapping` have the same permissions.
     perms: VmPerms,
+    /// Whether the mapping is excluded from core dumps.
+    ///
+    /// This is set by `madvise(MADV_DONTDUMP)` and cleared by
+    /// `madvise(MADV_DODUMP)`.
+    dont_dump: bool,
 }
 
 impl Interval<Vaddr> for VmMapping {
@@ -113,6 +118,7 @@ impl VmMapping {
             is_shared,
             handle_page_faults_around,
             perms,
+            dont_dump: false,
         }
     }
 
@@ -150,6 +156,26 @@ impl VmMapping {
         self.perms
     }
 
+    /// Returns whether the mapping is shared.
+    pub fn is_shared(&self) -> bool {
+        self.is_shared
+    }
+
+    /// Returns whether the mapping is excluded from core dumps.
+    pub fn is_dont_dump(&self) -> bool {
+        self.dont_dump
+    }
+
+    /// Returns whether the mapping maps device memory.
+    pub fn is_device(&self) -> bool {
+        matches!(self.mapped_mem, MappedMemory::Device)
+    }
+
+    /// Returns the path of the file that backs the mapping.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_ref()
+    }
+
     /// Returns the inode of the file that backs the mapping.
     pub fn inode(&self) -> Option<&Arc<dyn Inode>> {
         self.path.as_ref().map(|path| path.inode())
@@ -163,6 +189,13 @@ impl VmMapping {
         }
     }
 
+    /// Returns the backing VMO and the VMO offset of the mapping's start address, if this
+    /// mapping is VMO-backed.
+    pub fn backing_vmo(&self) -> Option<(&Arc<Vmo>, usize)> {
+        self.vmo()
+            .map(|mapped_vmo| (mapped_vmo.vmo(), mapped_vmo.offset()))
+    }
+
     /// Returns the mapping's RSS type.
     pub fn rss_type(&self) -> RssType {
         match &self.mapped_mem {
@@ -286,11 +319,9 @@ impl VmMapping {
 
             #[cfg(any(target_arch = "x86_64", target_arch = "riscv64"))]
             if let Some(vmo) = self.vmo() {
-                use crate::vdso::{VDSO_VMO_LAYOUT, vdso_vmo};
+                use crate::vdso::{VDSO_VMO_LAYOUT, is_vdso_vmo};
 
-                if let Some(vdso_vmo) = vdso_vmo()
-                    && Arc::ptr_eq(vmo.vmo(), &vdso_vmo)
-                {
+                if is_vdso_vmo(vmo.vmo()) {
                     let offset = vmo.offset();
                     if offset == VDSO_VMO_LAYOUT.data_segment_offset {
                         return Some(Cow::Borrowed("[vvar]"));
@@ -802,6 +833,11 @@ impl VmMapping {
 
         Self { perms, ..self }
     }
+
+    /// Changes whether the mapping is excluded from core dumps.
+    pub(super) fn set_dont_dump(self, dont_dump: bool) -> Self {
+        Self { dont_dump, ..self }
+    }
 }
 
 /// Memory mapped by a [`VmMapping`].
@@ -836,6 +872,20 @@ impl MappedMemory {
     }
 }
 
+/// A tracker that is notified when the mappings of a [`Vmo`] are created or destroyed.
+///
+/// Each [`VmMapping`] holding the tracker counts as one mapping. This includes the mappings that
+/// are inherited via `fork` or split from a tracked mapping.
+pub trait MappingTracker: Debug + Send + Sync {
+    /// Called when a new mapping is created.
+    fn on_map(&self);
+
+    /// Called when a mapping is destroyed.
+    ///
+    /// This method is called with the VMAR locked, so it must not lock the VMAR again.
+    fn on_unmap(&self);
+}
+
 /// A wrapper that represents a mapped [`Vmo`] and provide required functionalities
 /// that need to be provided to mappings from the VMO.
 #[derive(Debug)]
@@ -846,19 +896,30 @@ pub(super) struct MappedVmo {
     /// Whether the VMO's writable mappings need to be tracked, and the
     /// mapping is writable to the VMO.
     is_writable_tracked: bool,
+    /// The tracker that counts the mappings.
+    tracker: Option<Arc<dyn MappingTracker>>,
 }
 
 impl MappedVmo {
     /// Creates a `MappedVmo` used for the mapping.
-    pub(super) fn new(vmo: Arc<Vmo>, offset: usize, is_writable_tracked: bool) -> Result<Self> {
+    pub(super) fn new(
+        vmo: Arc<Vmo>,
+        offset: usize,
+        is_writable_tracked: bool,
+        tracker: Option<Arc<dyn MappingTracker>>,
+    ) -> Result<Self> {
         if is_writable_tracked {
             vmo.writable_mapping_status().map()?;
         }
+        if let Some(tracker) = tracker.as_ref() {
+            tracker.on_map();
+        }
 
         Ok(Self {
             vmo,
             offset,
             is_writable_tracked,
+            tracker,
         })
     }
 
@@ -932,13 +993,22 @@ impl MappedVmo {
         if self.is_writable_tracked {
             self.vmo.writable_mapping_status().increment();
         }
+        if let Some(tracker) = self.tracker.as_ref() {
+            tracker.on_map();
+        }
 
         Self {
             vmo: self.vmo.clone(),
             offset,
             is_writable_tracked: self.is_writable_tracked,
+            tracker: self.tracker.clone(),
         }
     }
+
+    /// Returns whether the mappings are counted by a [`MappingTracker`].
+    fn is_tracked(&self) -> bool {
+        self.tracker.is_some()
+    }
 }
 
 impl Drop for MappedVmo {
@@ -946,6 +1016,9 @@ impl Drop for MappedVmo {
         if self.is_writable_tracked {
             self.vmo.writable_mapping_status().decrement();
         }
+        if let Some(tracker) = self.tracker.as_ref() {
+            tracker.on_unmap();
+        }
     }
 }
 
@@ -962,7 +1035,8 @@ fn try_merge(left: &VmMapping, right: &VmMapping) -> Option<VmMapping> {
     let is_adjacent = left.map_end() == right.map_to_addr();
     let is_type_equal = left.is_shared == right.is_shared
         && left.handle_page_faults_around == right.handle_page_faults_around
-        && left.perms == right.perms;
+        && left.perms == right.perms
+        && left.dont_dump == right.dont_dump;
 
     if !is_adjacent || !is_type_equal {
         return None;
@@ -977,6 +1051,12 @@ fn try_merge(left: &VmMapping, right: &VmMapping) -> Option<VmMapping> {
             let l_vmo = l_vmo_obj.vmo();
             let r_vmo = r_vmo_obj.vmo();
 
+            // Like Linux, tracked mappings are never merged, so that the number of mappings seen
+            // by the tracker does not change unexpectedly.
+            if l_vmo_obj.is_tracked() || r_vmo_obj.is_tracked() {
+                return None;
+            }
+
             if Arc::ptr_eq(l_vmo, r_vmo) {
                 let is_offset_contiguous =
                     l_vmo_obj.offset() + left.map_size() == r_vmo_obj.offset();
@@ -0,0 +1,130 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! This module provides the VMAR operations used by core dumps.
+
+use core::ops::Range;
+
+use ostd::{
+    mm::{io::util::HasVmReaderWriter, vm_space::VmQueriedItem},
+    task::disable_preempt,
+};
+
+use super::{Interval, Vmar, util::get_intersected_range};
+use crate::prelude::*;
+
+impl Vmar {
+    /// Changes whether the memory mappings in the specified range are excluded
+    /// from core dumps.
+    ///
+    /// The range's start and end addresses must be page-aligned.
+    ///
+    /// If the range contains unmapped pages, an [`ENOMEM`] error will be returned.
+    /// Note that the mappings before the unmapped hole are still updated.
+    ///
+    /// [`ENOMEM`]: Errno::ENOMEM
+    pub fn set_dont_dump(&self, range: Range<usize>, dont_dump: bool) -> Result<()> {
+        debug_assert!(range.start.is_multiple_of(PAGE_SIZE));
+        debug_assert!(range.end.is_multiple_of(PAGE_SIZE));
+
+        let mut inner = self.inner.write();
+
+        let mut update_mappings = Vec::new();
+
+        for vm_mapping in inner.vm_mappings.find(&range) {
+            update_mappings.push((vm_mapping.range(), vm_mapping.is_dont_dump()))
+        }
+
+        let mut last_mapping_end = range.start;
+        for (vm_mapping_range, vm_mapping_dont_dump) in update_mappings {
+            if last_mapping_end < vm_mapping_range.start {
+                return_errno_with_message!(
+                    Errno::ENOMEM,
+                    "the range contains pages that are not mapped"
+                );
+            }
+            last_mapping_end = vm_mapping_range.end;
+
+            if dont_dump == vm_mapping_dont_dump {
+                continue;
+            }
+
+            let vm_mapping = inner.remove(&vm_mapping_range.start).unwrap();
+            let vm_mapping_range = vm_mapping.range();
+            let intersected_range = get_intersected_range(&range, &vm_mapping_range);
+
+            // Updates part of the taken `VmMapping`.
+            let (left, taken, right) = vm_mapping.split_range(&intersected_range);
+
+            // Puts the rest back.
+            if let Some(left) = left {
+                inner.insert_without_try_merge(left);
+            }
+            if let Some(right) = right {
+                inner.insert_without_try_merge(right);
+            }
+
+            let taken = taken.set_dont_dump(dont_dump);
+            inner.insert_try_merge(taken);
+        }
+
+        if last_mapping_end < range.end {
+            return_errno_with_message!(
+                Errno::ENOMEM,
+                "the range contains pages that are not mapped"
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Reads the page at `vaddr` for a core dump.
+    ///
+    /// Exactly [`PAGE_SIZE`] bytes are written to `writer`, which must have
+    /// enough available space.
+    ///
+    /// Unlike [`Self::read_alien`], this method never handles page faults, so
+    /// dumping a process does not allocate memory for pages that have never
+    /// been touched. Pages that are mapped in the page table are copied as is.
+    /// Otherwise, VMO-backed pages are read from the VMO, while other pages
+    /// (e.g., untouched anonymous pages or pages beyond the end of the file)
+    /// are filled with zeros.
+    pub fn read_page_for_dump(&self, vaddr: Vaddr, writer: &mut VmWriter) -> Result<()> {
+        debug_assert!(vaddr.is_multiple_of(PAGE_SIZE));
+        debug_assert!(writer.avail() >= PAGE_SIZE);
+
+        let inner = self.inner.read();
+
+        let frame = {
+            let preempt_guard = disable_preempt();
+            let mut cursor = self
+                .vm_space
+                .cursor(&preempt_guard, &(vaddr..vaddr + PAGE_SIZE))?;
+            match cursor.query()?.1 {
+                Some(VmQueriedItem::MappedRam { frame, .. }) => Some((*frame).clone()),
+                Some(VmQueriedItem::MappedIoMem { .. }) | None => None,
+            }
+        };
+
+        let mut page_writer = writer.clone_exclusive();
+        page_writer.limit(PAGE_SIZE);
+
+        if let Some(frame) = frame {
+            frame
+                .reader()
+                .read_fallible(&mut page_writer)
+                .map_err(|(err, _)| err)?;
+        } else if let Some(vm_mapping) = inner.vm_mappings.find_one(&vaddr)
+            && let Some((vmo, offset)) = vm_mapping.backing_vmo()
+        {
+            let vmo_offset = offset + (vaddr - vm_mapping.map_to_addr());
+            vmo.read(vmo_offset, &mut page_writer)?;
+        }
+
+        page_writer
+            .fill_zeros(page_writer.avail())
+            .map_err(|(err, _)| err)?;
+        writer.skip(PAGE_SIZE);
+
+        Ok(())
+    }
+}
@@ -2,7 +2,7 @@
 
 use core::num::NonZeroUsize;
 
-use super::{MappedMemory, MappedVmo, RssDelta, VmMapping, Vmar};
+use super::{MappedMemory, MappedVmo, MappingTracker, RssDelta, VmMapping, Vmar};
 use crate::{
     fs::{
         file::{FileLike, Mappable},
@@ -59,6 +59,7 @@ pub struct VmarMapOptions<'a> {
     parent: &'a Vmar,
     mappable: Option<Mappable>,
     path: Option<Path>,
+    tracker: Option<Arc<dyn MappingTracker>>,
     perms: VmPerms,
     may_perms: VmPerms,
     vmo_offset: usize,
@@ -103,6 +104,7 @@ impl<'a> VmarMapOptions<'a> {
             parent,
             mappable: None,
             path: None,
+            tracker: None,
             perms,
             may_perms: VmPerms::ALL_MAY_PERMS,
             vmo_offset: 0,
@@ -157,8 +159,9 @@ impl<'a> VmarMapOptions<'a> {
 
     /// Sets the [`Path`] of the mapping.
     ///
-    /// If a [`Vmo`] is specified, the inode behind the [`Path`] must have
-    /// the [`Vmo`] as the page cache.
+    /// If a [`Vmo`] is specified and the inode behind the [`Path`] has a page
+    /// cache, the page cache must be the [`Vmo`]. Inodes without page caches
+    /// (e.g., anonymous inodes) may be mapped with file-specific [`Vmo`]s.
     ///
     /// The [`Path`] of a mapping will be implicitly set if [`Self::mappable`]
     /// is set.
@@ -175,6 +178,14 @@ impl<'a> VmarMapOptions<'a> {
         self
     }
 
+    /// Sets the [`MappingTracker`] that counts the mappings of the [`Vmo`].
+    ///
+    /// The tracker only takes effect if a [`Vmo`] is mapped.
+    pub fn tracker(mut self, tracker: Arc<dyn MappingTracker>) -> Self {
+        self.tracker = Some(tracker);
+        self
+    }
+
     /// Sets the offset of the first memory page in the VMO that is to be
     /// mapped into the VMAR.
     ///
@@ -268,6 +279,7 @@ impl<'a> VmarMapOptions<'a> {
             parent,
             mappable,
             path,
+            tracker,
             perms,
             mut may_perms,
             vmo_offset,
@@ -326,11 +338,10 @@ impl<'a> VmarMapOptions<'a> {
         // Parse the `Mappable` and prepare the `MappedMemory`.
         let (mapped_mem, io_mem) = match mappable {
             Some(Mappable::Vmo(vmo)) => {
-                if let Some(ref path) = path {
-                    debug_assert!(Arc::ptr_eq(
-                        &vmo,
-                        &path.inode().page_cache().unwrap().as_vmo().clone()
-                    ));
+                if let Some(ref path) = path
+                    && let Some(page_cache) = path.inode().page_cache()
+                {
+                    debug_assert!(Arc::ptr_eq(&vmo, &page_cache.as_vmo().clone()));
                 }
 
                 let is_writable_tracked = if let Some(ref path) = path
@@ -344,8 +355,12 @@ impl<'a> VmarMapOptions<'a> {
                     false
                 };
 
-                let mapped_mem =
-                    MappedMemory::Vmo(MappedVmo::new(vmo, vmo_offset, is_writable_tracked)?);
+                let mapped_mem = MappedMemory::Vmo(MappedVmo::new(
+                    vmo,
+                    vmo_offset,
+                    is_writable_tracked,
+                    tracker,
+                )?);
                 (mapped_mem, None)
             }
             Some(Mappable::IoMem(io_mem)) => (MappedMemory::Device, Some(io_mem)),
@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: MPL-2.0
 
 mod access_alien;
+mod dump;
 mod fork;
 pub(super) mod map;
 pub(super) mod page_fault;
@@ -24,7 +25,7 @@ use super::{
     interval_set::{Interval, IntervalSet},
     is_userspace_vaddr,
     util::{self, get_intersected_range},
-    vm_mapping::{MappedMemory, MappedVmo, VmMapping},
+    vm_mapping::{MappedMemory, MappedVmo, MappingTracker, VmMapping},
 };
 use crate::{
     prelude::*,
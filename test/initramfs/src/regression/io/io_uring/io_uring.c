@@ -0,0 +1,579 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <linux/io_uring.h>
+#include <poll.h>
+#include <stdatomic.h>
+#include <stdint.h>
+#include <sys/eventfd.h>
+#include <sys/mman.h>
+#include <sys/syscall.h>
+#include <sys/uio.h>
+#include <time.h>
+#include <unistd.h>
+
+#include "../../common/test.h"
+
+#define TEST_FILE "/tmp/io_uring_test"
+#define IORING_MAX_ENTRIES 32768
+
+struct ring {
+	int fd;
+	struct io_uring_params params;
+	void *sq_ring;
+	void *cq_ring;
+	struct io_uring_sqe *sqes;
+	size_t sq_ring_size;
+	size_t cq_ring_size;
+	size_t sqes_size;
+};
+
+static int io_uring_setup(unsigned int entries, struct io_uring_params *params)
+{
+	return syscall(SYS_io_uring_setup, entries, params);
+}
+
+static int io_uring_enter(int fd, unsigned int to_submit,
+			  unsigned int min_complete, unsigned int flags)
+{
+	return syscall(SYS_io_uring_enter, fd, to_submit, min_complete, flags,
+		       NULL, 0);
+}
+
+static int io_uring_register(int fd, unsigned int opcode, void *arg,
+			     unsigned int nr_args)
+{
+	return syscall(SYS_io_uring_register, fd, opcode, arg, nr_args);
+}
+
+#define SQ_FIELD(ring, field) \
+	((_Atomic unsigned int *)((ring)->sq_ring + (ring)->params.sq_off.field))
+#define CQ_FIELD(ring, field) \
+	((_Atomic unsigned int *)((ring)->cq_ring + (ring)->params.cq_off.field))
+
+static int ring_init(struct ring *ring, unsigned int entries)
+{
+	memset(ring, 0, sizeof(*ring));
+
+	ring->fd = io_uring_setup(entries, &ring->params);
+	if (ring->fd < 0)
+		return -1;
+
+	ring->sq_ring_size = ring->params.sq_off.array +
+			     ring->params.sq_entries * sizeof(unsigned int);
+	ring->cq_ring_size = ring->params.cq_off.cqes +
+			     ring->params.cq_entries *
+				     sizeof(struct io_uring_cqe);
+	ring->sqes_size =
+		ring->params.sq_entries * sizeof(struct io_uring_sqe);
+
+	ring->sq_ring = mmap(NULL, ring->sq_ring_size, PROT_READ | PROT_WRITE,
+			     MAP_SHARED, ring->fd, IORING_OFF_SQ_RING);
+	ring->cq_ring = mmap(NULL, ring->cq_ring_size, PROT_READ | PROT_WRITE,
+			     MAP_SHARED, ring->fd, IORING_OFF_CQ_RING);
+	ring->sqes = mmap(NULL, ring->sqes_size, PROT_READ | PROT_WRITE,
+			  MAP_SHARED, ring->fd, IORING_OFF_SQES);
+	if (ring->sq_ring == MAP_FAILED || ring->cq_ring == MAP_FAILED ||
+	    ring->sqes == MAP_FAILED)
+		return -1;
+
+	return 0;
+}
+
+static int ring_exit(struct ring *ring)
+{
+	munmap(ring->sq_ring, ring->sq_ring_size);
+	munmap(ring->cq_ring, ring->cq_ring_size);
+	munmap(ring->sqes, ring->sqes_size);
+	return close(ring->fd);
+}
+
+static struct io_uring_sqe *ring_get_sqe(struct ring *ring)
+{
+	unsigned int tail = atomic_load_explicit(SQ_FIELD(ring, tail),
+						 memory_order_relaxed);
+	unsigned int index = tail & *SQ_FIELD(ring, ring_mask);
+	unsigned int *array = ring->sq_ring + ring->params.sq_off.array;
+	struct io_uring_sqe *sqe = &ring->sqes[index];
+
+	memset(sqe, 0, sizeof(*sqe));
+	array[index] = index;
+	atomic_store_explicit(SQ_FIELD(ring, tail), tail + 1,
+			      memory_order_release);
+
+	return sqe;
+}
+
+static struct io_uring_sqe *ring_prep(struct ring *ring, int opcode, int fd,
+				      const void *addr, unsigned int len,
+				      __u64 off, __u64 user_data)
+{
+	struct io_uring_sqe *sqe = ring_get_sqe(ring);
+
+	sqe->opcode = opcode;
+	sqe->fd = fd;
+	sqe->addr = (unsigned long)addr;
+	sqe->len = len;
+	sqe->off = off;
+	sqe->user_data = user_data;
+
+	return sqe;
+}
+
+static unsigned int ring_ready(struct ring *ring)
+{
+	return atomic_load_explicit(CQ_FIELD(ring, tail),
+				    memory_order_acquire) -
+	       atomic_load_explicit(CQ_FIELD(ring, head),
+				    memory_order_relaxed);
+}
+
+// Pops a CQE. The ring must have at least one CQE ready.
+static void ring_pop_cqe(struct ring *ring, struct io_uring_cqe *cqe)
+{
+	unsigned int head = atomic_load_explicit(CQ_FIELD(ring, head),
+						 memory_order_relaxed);
+	unsigned int index = head & *CQ_FIELD(ring, ring_mask);
+	struct io_uring_cqe *cqes = ring->cq_ring + ring->params.cq_off.cqes;
+	*cqe = cqes[index];
+	atomic_store_explicit(CQ_FIELD(ring, head), head + 1,
+			      memory_order_release);
+}
+
+// Pops two CQEs and sorts them by user data, since the CQE of a canceled
+// request may be posted before or after the CQE of the canceling request.
+static void ring_pop_cqe_pair(struct ring *ring, struct io_uring_cqe cqes[2])
+{
+	struct io_uring_cqe tmp;
+
+	ring_pop_cqe(ring, &cqes[0]);
+	ring_pop_cqe(ring, &cqes[1]);
+	if (cqes[0].user_data > cqes[1].user_data) {
+		tmp = cqes[0];
+		cqes[0] = cqes[1];
+		cqes[1] = tmp;
+	}
+}
+
+#define TEST_CQE(ring, data, cond)                                        \
+	({                                                                \
+		struct io_uring_cqe cqe;                                  \
+		ring_pop_cqe(ring, &cqe);                                 \
+		TEST_RES(cqe.res, cqe.user_data == (data) && (cond));     \
+	})
+
+static struct ring ring;
+static int pipe_fds[2];
+
+FN_SETUP(init)
+{
+	CHECK(ring_init(&ring, 4));
+	CHECK(pipe(pipe_fds));
+}
+END_SETUP()
+
+FN_TEST(setup_errors)
+{
+	struct io_uring_params params;
+
+	memset(&params, 0, sizeof(params));
+	TEST_ERRNO(io_uring_setup(0, &params), EINVAL);
+
+	memset(&params, 0, sizeof(params));
+	params.resv[0] = 1;
+	TEST_ERRNO(io_uring_setup(4, &params), EINVAL);
+
+	memset(&params, 0, sizeof(params));
+	params.flags = IORING_SETUP_CQSIZE;
+	params.cq_entries = 2;
+	TEST_ERRNO(io_uring_setup(4, &params), EINVAL);
+
+	memset(&params, 0, sizeof(params));
+	params.flags = 1 << 31;
+	TEST_ERRNO(io_uring_setup(4, &params), EINVAL);
+
+	memset(&params, 0, sizeof(params));
+	TEST_ERRNO(io_uring_setup(IORING_MAX_ENTRIES + 1, &params), EINVAL);
+
+	memset(&params, 0, sizeof(params));
+	params.flags = IORING_SETUP_CLAMP;
+	TEST_RES(io_uring_setup(IORING_MAX_ENTRIES + 1, &params),
+		 params.sq_entries == IORING_MAX_ENTRIES &&
+			 params.cq_entries == 2 * IORING_MAX_ENTRIES &&
+			 close(_ret) == 0);
+
+	TEST_ERRNO(io_uring_enter(pipe_fds[0], 0, 0, 0), EOPNOTSUPP);
+	TEST_ERRNO(io_uring_enter(ring.fd, 0, 0, 1 << 31), EINVAL);
+}
+END_TEST()
+
+FN_TEST(params)
+{
+	TEST_RES(ring.params.sq_entries,
+		 _ret == 4 && ring.params.cq_entries == 8);
+	TEST_RES(ring.params.features,
+		 (_ret & IORING_FEAT_NODROP) && (_ret & IORING_FEAT_RW_CUR_POS));
+	TEST_RES(*SQ_FIELD(&ring, ring_entries), _ret == 4);
+	TEST_RES(*CQ_FIELD(&ring, ring_entries), _ret == 8);
+}
+END_TEST()
+
+FN_TEST(nop)
+{
+	ring_prep(&ring, IORING_OP_NOP, -1, NULL, 0, 0, 1);
+	ring_prep(&ring, IORING_OP_NOP, -1, NULL, 0, 0, 2);
+	TEST_RES(io_uring_enter(ring.fd, 2, 2, IORING_ENTER_GETEVENTS),
+		 _ret == 2);
+	TEST_RES(ring_ready(&ring), _ret == 2);
+	TEST_CQE(&ring, 1, cqe.res == 0);
+	TEST_CQE(&ring, 2, cqe.res == 0);
+
+	// Nothing to submit
+	TEST_RES(io_uring_enter(ring.fd, 1, 0, 0), _ret == 0);
+	TEST_RES(*SQ_FIELD(&ring, head), _ret == *SQ_FIELD(&ring, tail));
+
+	// Invalid opcode
+	ring_prep(&ring, 0xff, -1, NULL, 0, 0, 3);
+	TEST_RES(io_uring_enter(ring.fd, 1, 1, IORING_ENTER_GETEVENTS),
+		 _ret == 1);
+	TEST_CQE(&ring, 3, cqe.res == -EINVAL);
+}
+END_TEST()
+
+FN_TEST(read_write)
+{
+	char buf[6] = {};
+	int fd;
+
+	fd = TEST_SUCC(open(TEST_FILE, O_RDWR | O_CREAT | O_TRUNC, 0600));
+
+	// Write and read at the given offsets
+	ring_prep(&ring, IORING_OP_WRITE, fd, "hello", 5, 0, 1);
+	TEST_RES(io_uring_enter(ring.fd, 1, 1, IORING_ENTER_GETEVENTS),
+		 _ret == 1);
+	TEST_CQE(&ring, 1, cqe.res == 5);
+	ring_prep(&ring, IORING_OP_READ, fd, buf, 3, 2, 2);
+	TEST_RES(io_uring_enter(ring.fd, 1, 1, IORING_ENTER_GETEVENTS),
+		 _ret == 1);
+	TEST_CQE(&ring, 2, cqe.res == 3 && memcmp(buf, "llo", 3) == 0);
+
+	// Read at the current file offset
+	TEST_RES(lseek(fd, 1, SEEK_SET), _ret == 1);
+	ring_prep(&ring, IORING_OP_READ, fd, buf, 5, -1, 3);
+	TEST_RES(io_uring_enter(ring.fd, 1, 1, IORING_ENTER_GETEVENTS),
+		 _ret == 1);
+	TEST_CQE(&ring, 3, cqe.res == 4 && memcmp(buf, "ello", 4) == 0);
+	TEST_RES(lseek(fd, 0, SEEK_CUR), _ret == 5);
+
+	// Vectored I/O
+	struct iovec iov[2] = {
+		{ .iov_base = buf, .iov_len = 2 },
+		{ .iov_base = buf + 2, .iov_len = 3 },
+	};
+	memset(buf, 0, sizeof(buf));
+	ring_prep(&ring, IORING_OP_READV, fd, iov, 2, 0, 4);
+	TEST_RES(io_uring_enter(ring.fd, 1, 1, IORING_ENTER_GETEVENTS),
+		 _ret == 1);
+	TEST_CQE(&ring, 4, cqe.res == 5 && memcmp(buf, "hello", 5) == 0);
+
+	// Fsync
+	ring_prep(&ring, IORING_OP_FSYNC, fd, NULL, 0, 0, 5);
+	TEST_RES(io_uring_enter(ring.fd, 1, 1, IORING_ENTER_GETEVENTS),
+		 _ret == 1);
+	TEST_CQE(&ring, 5, cqe.res == 0);
+
+	// Bad file descriptor
+	ring_prep(&ring, IORING_OP_READ, 1000, buf, 5, 0, 6);
+	TEST_RES(io_uring_enter(ring.fd, 1, 1, IORING_ENTER_GETEVENTS),
+		 _ret == 1);
+	TEST_CQE(&ring, 6, cqe.res == -EBADF);
+
+	TEST_SUCC(close(fd));
+	TEST_SUCC(unlink(TEST_FILE));
+}
+END_TEST()
+
+FN_TEST(pending_read)
+{
+	char buf[6] = {};
+
+	// The pipe is empty, so the request is pending
+	ring_prep(&ring, IORING_OP_READ, pipe_fds[0], buf, sizeof(buf), 0, 1);
+	TEST_RES(io_uring_enter(ring.fd, 1, 0, 0), _ret == 1);
+	TEST_RES(io_uring_enter(ring.fd, 0, 0, 0), _ret == 0);
+	TEST_RES(ring_ready(&ring), _ret == 0);
+
+	// The request completes after the pipe becomes readable
+	TEST_RES(write(pipe_fds[1], "hello", 5), _ret == 5);
+	TEST_RES(io_uring_enter(ring.fd, 0, 1, IORING_ENTER_GETEVENTS),
+		 _ret == 0);
+	TEST_CQE(&ring, 1, cqe.res == 5 && memcmp(buf, "hello", 5) == 0);
+}
+END_TEST()
+
+FN_TEST(links)
+{
+	char buf[6] = {};
+
+	// The read is issued after the write
+	ring_prep(&ring, IORING_OP_WRITE, pipe_fds[1], "hello", 5, 0, 1)
+		->flags = IOSQE_IO_LINK;
+	ring_prep(&ring, IORING_OP_READ, pipe_fds[0], buf, 5, 0, 2);
+	TEST_RES(io_uring_enter(ring.fd, 2, 2, IORING_ENTER_GETEVENTS),
+		 _ret == 2);
+	TEST_CQE(&ring, 1, cqe.res == 5);
+	TEST_CQE(&ring, 2, cqe.res == 5 && memcmp(buf, "hello", 5) == 0);
+
+	// A failed request cancels the rest of the link
+	ring_prep(&ring, IORING_OP_READ, 1000, buf, 5, 0, 3)->flags =
+		IOSQE_IO_LINK;
+	ring_prep(&ring, IORING_OP_NOP, -1, NULL, 0, 0, 4)->flags =
+		IOSQE_IO_LINK;
+	ring_prep(&ring, IORING_OP_NOP, -1, NULL, 0, 0, 5);
+	TEST_RES(io_uring_enter(ring.fd, 3, 3, IORING_ENTER_GETEVENTS),
+		 _ret == 3);
+	TEST_CQE(&ring, 3, cqe.res == -EBADF);
+	TEST_CQE(&ring, 4, cqe.res == -ECANCELED);
+	TEST_CQE(&ring, 5, cqe.res == -ECANCELED);
+
+	// A hard link is not broken by failures
+	ring_prep(&ring, IORING_OP_READ, 1000, buf, 5, 0, 6)->flags =
+		IOSQE_IO_HARDLINK;
+	ring_prep(&ring, IORING_OP_NOP, -1, NULL, 0, 0, 7);
+	TEST_RES(io_uring_enter(ring.fd, 2, 2, IORING_ENTER_GETEVENTS),
+		 _ret == 2);
+	TEST_CQE(&ring, 6, cqe.res == -EBADF);
+	TEST_CQE(&ring, 7, cqe.res == 0);
+
+	// A successful request can skip its CQE
+	ring_prep(&ring, IORING_OP_NOP, -1, NULL, 0, 0, 8)->flags =
+		IOSQE_CQE_SKIP_SUCCESS;
+	ring_prep(&ring, IORING_OP_NOP, -1, NULL, 0, 0, 9);
+	TEST_RES(io_uring_enter(ring.fd, 2, 1, IORING_ENTER_GETEVENTS),
+		 _ret == 2);
+	TEST_RES(ring_ready(&ring), _ret == 1);
+	TEST_CQE(&ring, 9, cqe.res == 0);
+}
+END_TEST()
+
+FN_TEST(poll)
+{
+	struct io_uring_cqe cqes[2];
+
+	// The pipe is writable
+	ring_prep(&ring, IORING_OP_POLL_ADD, pipe_fds[1], NULL, 0, 0, 1)
+		->poll32_events = POLLOUT;
+	TEST_RES(io_uring_enter(ring.fd, 1, 1, IORING_ENTER_GETEVENTS),
+		 _ret == 1);
+	TEST_CQE(&ring, 1, cqe.res == POLLOUT);
+
+	// The pipe is not readable, and the poll request is removed
+	ring_prep(&ring, IORING_OP_POLL_ADD, pipe_fds[0], NULL, 0, 0, 2)
+		->poll32_events = POLLIN;
+	TEST_RES(io_uring_enter(ring.fd, 1, 0, 0), _ret == 1);
+	TEST_RES(ring_ready(&ring), _ret == 0);
+	ring_prep(&ring, IORING_OP_POLL_REMOVE, -1, (void *)2, 0, 0, 3);
+	TEST_RES(io_uring_enter(ring.fd, 1, 2, IORING_ENTER_GETEVENTS),
+		 _ret == 1);
+	ring_pop_cqe_pair(&ring, cqes);
+	TEST_RES(cqes[0].res,
+		 cqes[0].user_data == 2 && cqes[0].res == -ECANCELED);
+	TEST_RES(cqes[1].res, cqes[1].user_data == 3 && cqes[1].res == 0);
+
+	// The poll request to remove does not exist
+	ring_prep(&ring, IORING_OP_POLL_REMOVE, -1, (void *)2, 0, 0, 4);
+	TEST_RES(io_uring_enter(ring.fd, 1, 1, IORING_ENTER_GETEVENTS),
+		 _ret == 1);
+	TEST_CQE(&ring, 4, cqe.res == -ENOENT);
+}
+END_TEST()
+
+FN_TEST(timeout)
+{
+	struct __kernel_timespec ts = { .tv_sec = 0, .tv_nsec = 10000000 };
+	struct io_uring_cqe cqes[2];
+
+	// The timeout expires
+	ring_prep(&ring, IORING_OP_TIMEOUT, -1, &ts, 1, 0, 1);
+	TEST_RES(io_uring_enter(ring.fd, 1, 1, IORING_ENTER_GETEVENTS),
+		 _ret == 1);
+	TEST_CQE(&ring, 1, cqe.res == -ETIME);
+
+	// The timeout completes after one request completes
+	ts.tv_sec = 100;
+	ring_prep(&ring, IORING_OP_TIMEOUT, -1, &ts, 1, 1, 2);
+	ring_prep(&ring, IORING_OP_NOP, -1, NULL, 0, 0, 3);
+	TEST_RES(io_uring_enter(ring.fd, 2, 2, IORING_ENTER_GETEVENTS),
+		 _ret == 2);
+	TEST_CQE(&ring, 3, cqe.res == 0);
+	TEST_CQE(&ring, 2, cqe.res == 0);
+
+	// The timeout is removed
+	ring_prep(&ring, IORING_OP_TIMEOUT, -1, &ts, 1, 0, 4);
+	ring_prep(&ring, IORING_OP_TIMEOUT_REMOVE, -1, (void *)4, 0, 0, 5);
+	TEST_RES(io_uring_enter(ring.fd, 2, 2, IORING_ENTER_GETEVENTS),
+		 _ret == 2);
+	ring_pop_cqe_pair(&ring, cqes);
+	TEST_RES(cqes[0].res,
+		 cqes[0].user_data == 4 && cqes[0].res == -ECANCELED);
+	TEST_RES(cqes[1].res, cqes[1].user_data == 5 && cqes[1].res == 0);
+}
+END_TEST()
+
+FN_TEST(open_close)
+{
+	char buf[6] = {};
+	struct io_uring_cqe cqe;
+	int fd;
+
+	ring_prep(&ring, IORING_OP_OPENAT, AT_FDCWD, TEST_FILE, 0600, 0, 1)
+		->open_flags = O_RDWR | O_CREAT | O_TRUNC;
+	TEST_RES(io_uring_enter(ring.fd, 1, 1, IORING_ENTER_GETEVENTS),
+		 _ret == 1);
+	ring_pop_cqe(&ring, &cqe);
+	fd = cqe.res;
+	TEST_RES(write(fd, "hello", 5), _ret == 5);
+	TEST_RES(pread(fd, buf, 5, 0),
+		 _ret == 5 && memcmp(buf, "hello", 5) == 0);
+
+	ring_prep(&ring, IORING_OP_CLOSE, fd, NULL, 0, 0, 2);
+	TEST_RES(io_uring_enter(ring.fd, 1, 1, IORING_ENTER_GETEVENTS),
+		 _ret == 1);
+	TEST_CQE(&ring, 2, cqe.res == 0);
+	TEST_ERRNO(close(fd), EBADF);
+
+	// The io_uring instance cannot be closed
+	ring_prep(&ring, IORING_OP_CLOSE, ring.fd, NULL, 0, 0, 3);
+	TEST_RES(io_uring_enter(ring.fd, 1, 1, IORING_ENTER_GETEVENTS),
+		 _ret == 1);
+	TEST_CQE(&ring, 3, cqe.res == -EBADF);
+
+	TEST_SUCC(unlink(TEST_FILE));
+}
+END_TEST()
+
+FN_TEST(registered_files_and_buffers)
+{
+	char buf[6] = {};
+	int fds[2] = { pipe_fds[0], -1 };
+	struct iovec iov = { .iov_base = buf, .iov_len = sizeof(buf) };
+
+	TEST_ERRNO(io_uring_register(ring.fd, IORING_UNREGISTER_FILES, NULL,
+				     0),
+		   ENXIO);
+	TEST_ERRNO(io_uring_register(ring.fd, IORING_REGISTER_FILES, &ring.fd,
+				     1),
+		   EBADF);
+	TEST_SUCC(io_uring_register(ring.fd, IORING_REGISTER_FILES, fds, 2));
+	TEST_ERRNO(io_uring_register(ring.fd, IORING_REGISTER_FILES, fds, 2),
+		   EBUSY);
+	TEST_SUCC(io_uring_register(ring.fd, IORING_REGISTER_BUFFERS, &iov, 1));
+
+	// Fill the empty slot
+	struct io_uring_files_update update = {
+		.offset = 1,
+		.fds = (unsigned long)&pipe_fds[1],
+	};
+	TEST_RES(io_uring_register(ring.fd, IORING_REGISTER_FILES_UPDATE,
+				   &update, 1),
+		 _ret == 1);
+
+	ring_prep(&ring, IORING_OP_WRITE, 1, "hello", 5, 0, 1)->flags =
+		IOSQE_FIXED_FILE | IOSQE_IO_LINK;
+	ring_prep(&ring, IORING_OP_READ_FIXED, 0, buf, 5, 0, 2)->flags =
+		IOSQE_FIXED_FILE;
+	TEST_RES(io_uring_enter(ring.fd, 2, 2, IORING_ENTER_GETEVENTS),
+		 _ret == 2);
+	TEST_CQE(&ring, 1, cqe.res == 5);
+	TEST_CQE(&ring, 2, cqe.res == 5 && memcmp(buf, "hello", 5) == 0);
+
+	// The buffer is out of the registered buffer
+	ring_prep(&ring, IORING_OP_READ_FIXED, 0, buf + 1, 6, 0, 3)->flags =
+		IOSQE_FIXED_FILE;
+	TEST_RES(io_uring_enter(ring.fd, 1, 1, IORING_ENTER_GETEVENTS),
+		 _ret == 1);
+	TEST_CQE(&ring, 3, cqe.res == -EFAULT);
+
+	// The fixed file does not exist
+	ring_prep(&ring, IORING_OP_NOP, -1, NULL, 0, 0, 4);
+	ring_prep(&ring, IORING_OP_READ, 2, buf, 5, 0, 5)->flags =
+		IOSQE_FIXED_FILE;
+	TEST_RES(io_uring_enter(ring.fd, 2, 2, IORING_ENTER_GETEVENTS),
+		 _ret == 2);
+	TEST_CQE(&ring, 4, cqe.res == 0);
+	TEST_CQE(&ring, 5, cqe.res == -EBADF);
+
+	TEST_SUCC(io_uring_register(ring.fd, IORING_UNREGISTER_BUFFERS, NULL,
+				    0));
+	TEST_SUCC(io_uring_register(ring.fd, IORING_UNREGISTER_FILES, NULL, 0));
+}
+END_TEST()
+
+FN_TEST(eventfd)
+{
+	int efd;
+	uint64_t count;
+
+	efd = TEST_SUCC(eventfd(0, EFD_NONBLOCK));
+	TEST_ERRNO(io_uring_register(ring.fd, IORING_REGISTER_EVENTFD,
+				     &pipe_fds[0], 1),
+		   EINVAL);
+	TEST_SUCC(io_uring_register(ring.fd, IORING_REGISTER_EVENTFD, &efd, 1));
+	TEST_ERRNO(io_uring_register(ring.fd, IORING_REGISTER_EVENTFD, &efd, 1),
+		   EBUSY);
+
+	ring_prep(&ring, IORING_OP_NOP, -1, NULL, 0, 0, 1);
+	ring_prep(&ring, IORING_OP_NOP, -1, NULL, 0, 0, 2);
+	TEST_RES(io_uring_enter(ring.fd, 2, 2, IORING_ENTER_GETEVENTS),
+		 _ret == 2);
+	TEST_RES(read(efd, &count, sizeof(count)),
+		 _ret == sizeof(count) && count > 0);
+	TEST_CQE(&ring, 1, cqe.res == 0);
+	TEST_CQE(&ring, 2, cqe.res == 0);
+
+	TEST_SUCC(io_uring_register(ring.fd, IORING_UNREGISTER_EVENTFD, NULL,
+				    0));
+	ring_prep(&ring, IORING_OP_NOP, -1, NULL, 0, 0, 3);
+	TEST_RES(io_uring_enter(ring.fd, 1, 1, IORING_ENTER_GETEVENTS),
+		 _ret == 1);
+	TEST_ERRNO(read(efd, &count, sizeof(count)), EAGAIN);
+	TEST_CQE(&ring, 3, cqe.res == 0);
+
+	TEST_SUCC(close(efd));
+}
+END_TEST()
+
+FN_TEST(probe)
+{
+	struct {
+		struct io_uring_probe probe;
+		struct io_uring_probe_op ops[256];
+	} probe;
+
+	memset(&probe, 0, sizeof(probe));
+	TEST_SUCC(io_uring_register(ring.fd, IORING_REGISTER_PROBE, &probe,
+				    256));
+	TEST_RES(probe.probe.ops_len,
+		 _ret == probe.probe.last_op + 1 &&
+			 _ret > IORING_OP_RECV &&
+			 (probe.ops[IORING_OP_READ].flags &
+			  IO_URING_OP_SUPPORTED) &&
+			 (probe.ops[IORING_OP_OPENAT].flags &
+			  IO_URING_OP_SUPPORTED));
+
+	// The probe structure must be zeroed
+	TEST_ERRNO(io_uring_register(ring.fd, IORING_REGISTER_PROBE, &probe,
+				     256),
+		   EINVAL);
+}
+END_TEST()
+
+FN_TEST(cleanup)
+{
+	TEST_SUCC(ring_exit(&ring));
+	TEST_SUCC(close(pipe_fds[0]));
+	TEST_SUCC(close(pipe_fds[1]));
+}
+END_TEST()
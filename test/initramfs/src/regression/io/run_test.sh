@@ -14,3 +14,5 @@ set -e
 ./file_io/fcntl_lock
 ./file_io/file_err
 ./file_io/iovec_err
+
+./io_uring/io_uring
@@ -0,0 +1,551 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <errno.h>
+#include <fcntl.h>
+#include <mqueue.h>
+#include <sched.h>
+#include <signal.h>
+#include <string.h>
+#include <sys/epoll.h>
+#include <sys/mount.h>
+#include <sys/socket.h>
+#include <sys/stat.h>
+#include <sys/syscall.h>
+#include <sys/wait.h>
+#include <time.h>
+#include <unistd.h>
+#include <linux/netlink.h>
+
+#include "../../common/test.h"
+
+#define QUEUE_NAME "/mqueue_test"
+#define MSG_SIZE 16
+#define MAX_MSGS 2
+
+#define MQUEUE_MNT "/tmp/mqueue_mnt"
+#define MQUEUE_FILE MQUEUE_MNT "/mqueue_test"
+
+#define SETTLE_MS 100
+
+// The following definitions are in `<linux/mqueue.h>`, which conflicts with `<mqueue.h>`.
+#define NOTIFY_WOKENUP 1
+#define NOTIFY_REMOVED 2
+#define NOTIFY_COOKIE_LEN 32
+
+static volatile int notified_signo;
+static volatile int notified_code;
+static volatile int notified_value;
+
+static void sleep_ms(long milliseconds)
+{
+	struct timespec request = {
+		.tv_sec = milliseconds / 1000,
+		.tv_nsec = (milliseconds % 1000) * 1000000L,
+	};
+
+	CHECK(nanosleep(&request, NULL));
+}
+
+static struct timespec deadline_after_ms(long milliseconds)
+{
+	struct timespec deadline;
+
+	CHECK(clock_gettime(CLOCK_REALTIME, &deadline));
+	deadline.tv_sec += milliseconds / 1000;
+	deadline.tv_nsec += (milliseconds % 1000) * 1000000L;
+	if (deadline.tv_nsec >= 1000000000L) {
+		deadline.tv_sec += 1;
+		deadline.tv_nsec -= 1000000000L;
+	}
+
+	return deadline;
+}
+
+static mqd_t create_queue(int flags)
+{
+	struct mq_attr attr = {
+		.mq_maxmsg = MAX_MSGS,
+		.mq_msgsize = MSG_SIZE,
+	};
+
+	return mq_open(QUEUE_NAME, O_RDWR | O_CREAT | O_EXCL | flags, 0600,
+		       &attr);
+}
+
+static void signal_handler(int signum, siginfo_t *info, void *context)
+{
+	(void)context;
+
+	notified_signo = signum;
+	notified_code = info->si_code;
+	notified_value = info->si_value.sival_int;
+}
+
+FN_SETUP(install_signal_handler)
+{
+	struct sigaction action = {
+		.sa_sigaction = signal_handler,
+		.sa_flags = SA_SIGINFO,
+	};
+
+	CHECK(sigemptyset(&action.sa_mask));
+	CHECK(sigaction(SIGUSR1, &action, NULL));
+}
+END_SETUP()
+
+FN_TEST(mq_open_and_unlink)
+{
+	struct mq_attr attr;
+	mqd_t mqd, other;
+
+	mqd = TEST_SUCC(mq_open(QUEUE_NAME, O_RDWR | O_CREAT | O_EXCL, 0600,
+				NULL));
+	TEST_RES(mq_getattr(mqd, &attr),
+		 attr.mq_maxmsg == 10 && attr.mq_msgsize == 8192 &&
+			 attr.mq_curmsgs == 0 && attr.mq_flags == 0);
+	TEST_RES(fcntl(mqd, F_GETFD), _ret == FD_CLOEXEC);
+
+	TEST_ERRNO(mq_open(QUEUE_NAME, O_RDWR | O_CREAT | O_EXCL, 0600, NULL),
+		   EEXIST);
+	other = TEST_SUCC(mq_open(QUEUE_NAME, O_RDONLY));
+	TEST_SUCC(mq_close(other));
+	other = TEST_SUCC(mq_open(QUEUE_NAME, O_WRONLY | O_CREAT, 0600, NULL));
+	TEST_SUCC(mq_close(other));
+	TEST_ERRNO(mq_open(QUEUE_NAME, O_RDWR | O_WRONLY), EINVAL);
+
+	TEST_SUCC(mq_unlink(QUEUE_NAME));
+	TEST_ERRNO(mq_open(QUEUE_NAME, O_RDWR), ENOENT);
+	TEST_ERRNO(mq_unlink(QUEUE_NAME), ENOENT);
+
+	// The unlinked queue is still usable.
+	TEST_SUCC(mq_send(mqd, "hello", 5, 0));
+	TEST_SUCC(mq_close(mqd));
+}
+END_TEST()
+
+FN_TEST(mq_open_reject_bad_args)
+{
+	struct mq_attr attr = {
+		.mq_maxmsg = MAX_MSGS,
+		.mq_msgsize = MSG_SIZE,
+	};
+
+	TEST_ERRNO(mq_open("/mqueue/test", O_RDWR | O_CREAT, 0600, NULL),
+		   EACCES);
+
+	attr.mq_maxmsg = 0;
+	TEST_ERRNO(mq_open(QUEUE_NAME, O_RDWR | O_CREAT, 0600, &attr), EINVAL);
+	attr.mq_maxmsg = 65537;
+	TEST_ERRNO(mq_open(QUEUE_NAME, O_RDWR | O_CREAT, 0600, &attr), EINVAL);
+	attr.mq_maxmsg = MAX_MSGS;
+	attr.mq_msgsize = -1;
+	TEST_ERRNO(mq_open(QUEUE_NAME, O_RDWR | O_CREAT, 0600, &attr), EINVAL);
+
+	TEST_ERRNO(mq_unlink(QUEUE_NAME), ENOENT);
+}
+END_TEST()
+
+FN_TEST(mq_receive_by_priority)
+{
+	char buf[MSG_SIZE];
+	unsigned int prio;
+	mqd_t mqd;
+
+	mqd = TEST_SUCC(create_queue(O_NONBLOCK));
+	TEST_SUCC(mq_unlink(QUEUE_NAME));
+
+	TEST_SUCC(mq_send(mqd, "low", 3, 1));
+	TEST_SUCC(mq_send(mqd, "high", 4, 5));
+	TEST_RES(mq_receive(mqd, buf, sizeof(buf), &prio),
+		 _ret == 4 && prio == 5 && memcmp(buf, "high", 4) == 0);
+	TEST_SUCC(mq_send(mqd, "low2", 4, 1));
+	TEST_RES(mq_receive(mqd, buf, sizeof(buf), &prio),
+		 _ret == 3 && prio == 1 && memcmp(buf, "low", 3) == 0);
+	TEST_RES(mq_receive(mqd, buf, sizeof(buf), NULL),
+		 _ret == 4 && memcmp(buf, "low2", 4) == 0);
+	TEST_ERRNO(mq_receive(mqd, buf, sizeof(buf), NULL), EAGAIN);
+
+	TEST_SUCC(mq_close(mqd));
+}
+END_TEST()
+
+FN_TEST(mq_send_receive_reject_bad_args)
+{
+	char buf[MSG_SIZE + 1];
+	mqd_t mqd, rdonly;
+
+	mqd = TEST_SUCC(create_queue(O_NONBLOCK));
+	rdonly = TEST_SUCC(mq_open(QUEUE_NAME, O_RDONLY));
+	TEST_SUCC(mq_unlink(QUEUE_NAME));
+
+	TEST_ERRNO(mq_send(mqd, buf, MSG_SIZE + 1, 0), EMSGSIZE);
+	TEST_ERRNO(mq_send(mqd, buf, 1, 32768), EINVAL);
+	TEST_ERRNO(mq_send(rdonly, buf, 1, 0), EBADF);
+	TEST_ERRNO(mq_send(STDIN_FILENO, buf, 1, 0), EBADF);
+
+	TEST_SUCC(mq_send(mqd, buf, MSG_SIZE, 0));
+	TEST_ERRNO(mq_receive(mqd, buf, MSG_SIZE - 1, NULL), EMSGSIZE);
+	TEST_RES(mq_receive(rdonly, buf, sizeof(buf), NULL), _ret == MSG_SIZE);
+
+	TEST_SUCC(mq_close(rdonly));
+	TEST_SUCC(mq_close(mqd));
+}
+END_TEST()
+
+FN_TEST(mq_nonblock_and_timeout)
+{
+	struct mq_attr attr, new_attr = { .mq_flags = 0 };
+	struct timespec deadline;
+	char buf[MSG_SIZE];
+	mqd_t mqd;
+
+	mqd = TEST_SUCC(create_queue(O_NONBLOCK));
+	TEST_SUCC(mq_unlink(QUEUE_NAME));
+
+	TEST_SUCC(mq_send(mqd, "1", 1, 0));
+	TEST_SUCC(mq_send(mqd, "2", 1, 0));
+	TEST_ERRNO(mq_send(mqd, "3", 1, 0), EAGAIN);
+	TEST_RES(mq_getattr(mqd, &attr),
+		 attr.mq_flags == O_NONBLOCK && attr.mq_maxmsg == MAX_MSGS &&
+			 attr.mq_msgsize == MSG_SIZE && attr.mq_curmsgs == 2);
+
+	TEST_SUCC(mq_setattr(mqd, &new_attr, &attr));
+	TEST_RES(mq_getattr(mqd, &attr), attr.mq_flags == 0);
+	new_attr.mq_flags = O_RDWR;
+	TEST_ERRNO(mq_setattr(mqd, &new_attr, NULL), EINVAL);
+
+	deadline = deadline_after_ms(SETTLE_MS);
+	TEST_ERRNO(mq_timedsend(mqd, "3", 1, 0, &deadline), ETIMEDOUT);
+	deadline.tv_nsec = 1000000000L;
+	TEST_ERRNO(mq_timedsend(mqd, "3", 1, 0, &deadline), EINVAL);
+
+	TEST_RES(mq_receive(mqd, buf, sizeof(buf), NULL), _ret == 1);
+	TEST_RES(mq_receive(mqd, buf, sizeof(buf), NULL), _ret == 1);
+	deadline = deadline_after_ms(SETTLE_MS);
+	TEST_ERRNO(mq_timedreceive(mqd, buf, sizeof(buf), NULL, &deadline),
+		   ETIMEDOUT);
+
+	TEST_SUCC(mq_close(mqd));
+}
+END_TEST()
+
+FN_TEST(mq_receive_blocks_until_sent)
+{
+	char buf[MSG_SIZE];
+	unsigned int prio;
+	pid_t child;
+	int status;
+	mqd_t mqd;
+
+	mqd = TEST_SUCC(create_queue(0));
+	TEST_SUCC(mq_unlink(QUEUE_NAME));
+
+	child = TEST_SUCC(fork());
+	if (child == 0) {
+		sleep_ms(SETTLE_MS);
+		CHECK(mq_send(mqd, "hello", 5, 3));
+		_exit(0);
+	}
+
+	TEST_RES(mq_receive(mqd, buf, sizeof(buf), &prio),
+		 _ret == 5 && prio == 3 && memcmp(buf, "hello", 5) == 0);
+	TEST_RES(waitpid(child, &status, 0),
+		 _ret == child && WIFEXITED(status) &&
+			 WEXITSTATUS(status) == 0);
+
+	TEST_SUCC(mq_close(mqd));
+}
+END_TEST()
+
+FN_TEST(mq_notify_signal)
+{
+	struct sigevent sev = {
+		.sigev_notify = SIGEV_SIGNAL,
+		.sigev_signo = SIGUSR1,
+		.sigev_value.sival_int = 42,
+	};
+	char buf[MSG_SIZE];
+	pid_t child;
+	int status;
+	mqd_t mqd;
+
+	mqd = TEST_SUCC(create_queue(O_NONBLOCK));
+	TEST_SUCC(mq_unlink(QUEUE_NAME));
+
+	TEST_SUCC(mq_notify(mqd, &sev));
+	TEST_ERRNO(mq_notify(mqd, &sev), EBUSY);
+
+	child = TEST_SUCC(fork());
+	if (child == 0) {
+		CHECK_WITH(mq_notify(mqd, &sev), _ret == -1 && errno == EBUSY);
+		// Only the registered process can remove the registration.
+		CHECK(mq_notify(mqd, NULL));
+		_exit(0);
+	}
+	TEST_RES(waitpid(child, &status, 0),
+		 _ret == child && WIFEXITED(status) &&
+			 WEXITSTATUS(status) == 0);
+	TEST_ERRNO(mq_notify(mqd, &sev), EBUSY);
+
+	notified_signo = 0;
+	TEST_SUCC(mq_send(mqd, "hello", 5, 0));
+	TEST_RES(notified_signo,
+		 _ret == SIGUSR1 && notified_code == SI_MESGQ &&
+			 notified_value == 42);
+
+	// The registration is removed after the notification.
+	notified_signo = 0;
+	TEST_RES(mq_receive(mqd, buf, sizeof(buf), NULL), _ret == 5);
+	TEST_SUCC(mq_send(mqd, "hello", 5, 0));
+	TEST_RES(notified_signo, _ret == 0);
+	TEST_SUCC(mq_notify(mqd, &sev));
+
+	// No notification is sent if the queue is not empty.
+	TEST_SUCC(mq_send(mqd, "hello", 5, 0));
+	TEST_RES(notified_signo, _ret == 0);
+
+	TEST_SUCC(mq_notify(mqd, NULL));
+	TEST_SUCC(mq_notify(mqd, NULL));
+
+	TEST_SUCC(mq_close(mqd));
+}
+END_TEST()
+
+// The C libraries implement `SIGEV_THREAD` on top of the cookies sent to netlink sockets, so the
+// raw system call is used here to check the cookies.
+static int raw_mq_notify(mqd_t mqd, const struct sigevent *sev)
+{
+	return syscall(SYS_mq_notify, mqd, sev);
+}
+
+static int recv_cookie(int sk, const unsigned char *cookie, int type)
+{
+	unsigned char buf[NOTIFY_COOKIE_LEN + 1];
+	ssize_t len;
+
+	len = recv(sk, buf, sizeof(buf), 0);
+	if (len < 0)
+		return -1;
+
+	return len == NOTIFY_COOKIE_LEN &&
+	       memcmp(buf, cookie, NOTIFY_COOKIE_LEN - 1) == 0 &&
+	       buf[NOTIFY_COOKIE_LEN - 1] == type;
+}
+
+FN_TEST(mq_notify_thread)
+{
+	unsigned char cookie[NOTIFY_COOKIE_LEN];
+	struct sigevent sev = {
+		.sigev_notify = SIGEV_THREAD,
+		.sigev_value.sival_ptr = cookie,
+	};
+	int nl_sk, unix_sk;
+	char buf[MSG_SIZE];
+	mqd_t mqd;
+
+	memset(cookie, 'c', sizeof(cookie));
+	nl_sk = TEST_SUCC(socket(AF_NETLINK, SOCK_DGRAM, NETLINK_ROUTE));
+	unix_sk = TEST_SUCC(socket(AF_UNIX, SOCK_DGRAM, 0));
+
+	mqd = TEST_SUCC(create_queue(O_NONBLOCK));
+	TEST_SUCC(mq_unlink(QUEUE_NAME));
+
+	// The netlink socket is given in `sigev_signo`.
+	sev.sigev_signo = -1;
+	TEST_ERRNO(raw_mq_notify(mqd, &sev), EBADF);
+	sev.sigev_signo = mqd;
+	TEST_ERRNO(raw_mq_notify(mqd, &sev), ENOTSOCK);
+	sev.sigev_signo = unix_sk;
+	TEST_ERRNO(raw_mq_notify(mqd, &sev), EINVAL);
+	sev.sigev_signo = nl_sk;
+	sev.sigev_value.sival_ptr = NULL;
+	TEST_ERRNO(raw_mq_notify(mqd, &sev), EFAULT);
+	sev.sigev_value.sival_ptr = cookie;
+
+	// A cookie is sent when a message arrives at the empty queue.
+	TEST_SUCC(raw_mq_notify(mqd, &sev));
+	TEST_ERRNO(raw_mq_notify(mqd, &sev), EBUSY);
+	TEST_SUCC(mq_send(mqd, "hello", 5, 0));
+	TEST_RES(recv_cookie(nl_sk, cookie, NOTIFY_WOKENUP), _ret == 1);
+	TEST_RES(mq_receive(mqd, buf, sizeof(buf), NULL), _ret == 5);
+
+	// A cookie is also sent when the registration is removed.
+	TEST_SUCC(raw_mq_notify(mqd, &sev));
+	TEST_SUCC(raw_mq_notify(mqd, NULL));
+	TEST_RES(recv_cookie(nl_sk, cookie, NOTIFY_REMOVED), _ret == 1);
+
+	TEST_SUCC(mq_close(mqd));
+	TEST_SUCC(close(unix_sk));
+	TEST_SUCC(close(nl_sk));
+}
+END_TEST()
+
+FN_TEST(mq_notify_removed_on_close)
+{
+	unsigned char cookie[NOTIFY_COOKIE_LEN];
+	struct sigevent sev = {
+		.sigev_notify = SIGEV_SIGNAL,
+		.sigev_signo = SIGUSR1,
+	};
+	pid_t child;
+	int status;
+	mqd_t mqd, mqd2;
+	int nl_sk;
+
+	mqd = TEST_SUCC(create_queue(O_NONBLOCK));
+	TEST_SUCC(mq_unlink(QUEUE_NAME));
+	mqd2 = TEST_SUCC(dup(mqd));
+
+	// Closing the file descriptor in another process keeps the registration.
+	TEST_SUCC(mq_notify(mqd, &sev));
+	child = TEST_SUCC(fork());
+	if (child == 0) {
+		CHECK(close(mqd2));
+		CHECK_WITH(mq_notify(mqd, &sev), _ret == -1 && errno == EBUSY);
+		_exit(0);
+	}
+	TEST_RES(waitpid(child, &status, 0),
+		 _ret == child && WIFEXITED(status) &&
+			 WEXITSTATUS(status) == 0);
+	TEST_ERRNO(mq_notify(mqd, &sev), EBUSY);
+
+	// Closing any file descriptor in the registered process removes the registration.
+	TEST_SUCC(close(mqd2));
+	TEST_SUCC(mq_notify(mqd, &sev));
+
+	// The removal is reported to `SIGEV_THREAD` registrations.
+	memset(cookie, 'c', sizeof(cookie));
+	nl_sk = TEST_SUCC(socket(AF_NETLINK, SOCK_DGRAM, NETLINK_ROUTE));
+	TEST_SUCC(mq_notify(mqd, NULL));
+	sev.sigev_notify = SIGEV_THREAD;
+	sev.sigev_signo = nl_sk;
+	sev.sigev_value.sival_ptr = cookie;
+	TEST_SUCC(raw_mq_notify(mqd, &sev));
+	mqd2 = TEST_SUCC(dup(mqd));
+	TEST_SUCC(close(mqd2));
+	TEST_RES(recv_cookie(nl_sk, cookie, NOTIFY_REMOVED), _ret == 1);
+
+	TEST_SUCC(mq_close(mqd));
+	TEST_SUCC(close(nl_sk));
+}
+END_TEST()
+
+FN_TEST(mq_notify_reject_bad_args)
+{
+	struct sigevent sev = {
+		.sigev_notify = SIGEV_SIGNAL,
+		.sigev_signo = 65,
+	};
+	mqd_t mqd;
+
+	mqd = TEST_SUCC(create_queue(0));
+	TEST_SUCC(mq_unlink(QUEUE_NAME));
+
+	TEST_ERRNO(mq_notify(mqd, &sev), EINVAL);
+	sev.sigev_notify = 3;
+	TEST_ERRNO(mq_notify(mqd, &sev), EINVAL);
+	TEST_ERRNO(mq_notify(STDIN_FILENO, NULL), EBADF);
+
+	TEST_SUCC(mq_close(mqd));
+}
+END_TEST()
+
+FN_TEST(mq_epoll)
+{
+	struct epoll_event event = { .events = EPOLLIN | EPOLLOUT };
+	char buf[MSG_SIZE];
+	int epfd;
+	mqd_t mqd;
+
+	mqd = TEST_SUCC(create_queue(O_NONBLOCK));
+	TEST_SUCC(mq_unlink(QUEUE_NAME));
+
+	epfd = TEST_SUCC(epoll_create1(0));
+	TEST_SUCC(epoll_ctl(epfd, EPOLL_CTL_ADD, mqd, &event));
+
+	TEST_RES(epoll_wait(epfd, &event, 1, 0),
+		 _ret == 1 && event.events == EPOLLOUT);
+	TEST_SUCC(mq_send(mqd, "1", 1, 0));
+	TEST_RES(epoll_wait(epfd, &event, 1, 0),
+		 _ret == 1 && event.events == (EPOLLIN | EPOLLOUT));
+	TEST_SUCC(mq_send(mqd, "2", 1, 0));
+	TEST_RES(epoll_wait(epfd, &event, 1, 0),
+		 _ret == 1 && event.events == EPOLLIN);
+
+	TEST_RES(mq_receive(mqd, buf, sizeof(buf), NULL), _ret == 1);
+	TEST_RES(mq_receive(mqd, buf, sizeof(buf), NULL), _ret == 1);
+	TEST_RES(epoll_wait(epfd, &event, 1, 0),
+		 _ret == 1 && event.events == EPOLLOUT);
+
+	TEST_SUCC(close(epfd));
+	TEST_SUCC(mq_close(mqd));
+}
+END_TEST()
+
+FN_TEST(mqueue_fs)
+{
+	char buf[128];
+	struct stat st;
+	mqd_t mqd;
+	int fd;
+
+	CHECK_WITH(mkdir(MQUEUE_MNT, 0755), _ret >= 0 || errno == EEXIST);
+	TEST_SUCC(mount("mqueue", MQUEUE_MNT, "mqueue", 0, NULL));
+
+	mqd = TEST_SUCC(create_queue(O_NONBLOCK));
+	TEST_SUCC(mq_send(mqd, "hello", 5, 0));
+
+	TEST_RES(stat(MQUEUE_FILE, &st),
+		 S_ISREG(st.st_mode) && (st.st_mode & 0777) == 0600 &&
+			 st.st_size == 80);
+	fd = TEST_SUCC(open(MQUEUE_FILE, O_RDONLY));
+	TEST_RES(read(fd, buf, sizeof(buf)),
+		 _ret > 0 && strncmp(buf, "QSIZE:5 ", 8) == 0);
+	TEST_ERRNO(write(fd, "hello", 5), EBADF);
+	TEST_SUCC(close(fd));
+
+	TEST_SUCC(unlink(MQUEUE_FILE));
+	TEST_ERRNO(mq_open(QUEUE_NAME, O_RDWR), ENOENT);
+	TEST_ERRNO(stat(MQUEUE_FILE, &st), ENOENT);
+	TEST_SUCC(mq_close(mqd));
+
+	// Files created in the mounted instance are message queues.
+	fd = TEST_SUCC(open(MQUEUE_FILE, O_RDWR | O_CREAT | O_EXCL, 0600));
+	TEST_SUCC(mq_send(fd, "hello", 5, 0));
+	TEST_ERRNO(mkdir(MQUEUE_MNT "/dir", 0755), EPERM);
+	TEST_SUCC(close(fd));
+	TEST_SUCC(mq_unlink(QUEUE_NAME));
+
+	TEST_SUCC(umount(MQUEUE_MNT));
+	TEST_SUCC(rmdir(MQUEUE_MNT));
+}
+END_TEST()
+
+FN_TEST(mq_isolated_by_ipc_ns)
+{
+	pid_t child;
+	int status;
+	mqd_t mqd;
+
+	mqd = TEST_SUCC(create_queue(0));
+
+	child = TEST_SUCC(fork());
+	if (child == 0) {
+		CHECK(unshare(CLONE_NEWIPC));
+		CHECK_WITH(mq_open(QUEUE_NAME, O_RDWR),
+			   _ret == -1 && errno == ENOENT);
+		mqd = CHECK(create_queue(0));
+		CHECK(mq_close(mqd));
+		_exit(0);
+	}
+	TEST_RES(waitpid(child, &status, 0),
+		 _ret == child && WIFEXITED(status) &&
+			 WEXITSTATUS(status) == 0);
+
+	TEST_SUCC(mq_unlink(QUEUE_NAME));
+	TEST_ERRNO(mq_unlink(QUEUE_NAME), ENOENT);
+	TEST_SUCC(mq_close(mqd));
+}
+END_TEST()
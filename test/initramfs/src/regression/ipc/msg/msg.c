@@ -0,0 +1,321 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <errno.h>
+#include <fcntl.h>
+#include <signal.h>
+#include <string.h>
+#include <sys/ipc.h>
+#include <sys/msg.h>
+#include <sys/wait.h>
+#include <time.h>
+#include <unistd.h>
+
+#include "../../common/test.h"
+
+#define MSGMAX 8192
+#define MSGMNB 16384
+
+#define CUSTOM_KEY 0xdeadcafe
+
+#define SETTLE_MS 100
+
+struct test_msg {
+	long mtype;
+	char mtext[64];
+};
+
+static void sleep_ms(long milliseconds)
+{
+	struct timespec request = {
+		.tv_sec = milliseconds / 1000,
+		.tv_nsec = (milliseconds % 1000) * 1000000L,
+	};
+
+	CHECK(nanosleep(&request, NULL));
+}
+
+static int create_queue(void)
+{
+	return msgget(IPC_PRIVATE, IPC_CREAT | 0600);
+}
+
+static int send_msg(int msqid, long mtype, const char *text, int flags)
+{
+	struct test_msg msg = { .mtype = mtype };
+
+	strcpy(msg.mtext, text);
+	return msgsnd(msqid, &msg, strlen(text) + 1, flags);
+}
+
+static long recv_type(int msqid, long msgtyp, int flags)
+{
+	struct test_msg msg;
+
+	if (msgrcv(msqid, &msg, sizeof(msg.mtext), msgtyp, flags) < 0)
+		return -1;
+
+	return msg.mtype;
+}
+
+static void signal_handler(int signum)
+{
+	(void)signum;
+}
+
+FN_SETUP(install_signal_handler)
+{
+	struct sigaction action = {
+		.sa_handler = signal_handler,
+	};
+
+	CHECK(sigemptyset(&action.sa_mask));
+	CHECK(sigaction(SIGUSR1, &action, NULL));
+}
+END_SETUP()
+
+FN_TEST(msgget_by_key)
+{
+	int msqid = TEST_SUCC(msgget(CUSTOM_KEY, IPC_CREAT | 0600));
+
+	TEST_RES(msgget(CUSTOM_KEY, 0), _ret == msqid);
+	TEST_RES(msgget(CUSTOM_KEY, IPC_CREAT | 0600), _ret == msqid);
+	TEST_ERRNO(msgget(CUSTOM_KEY, IPC_CREAT | IPC_EXCL | 0600), EEXIST);
+	TEST_ERRNO(msgget(CUSTOM_KEY + 1, 0), ENOENT);
+
+	TEST_SUCC(msgctl(msqid, IPC_RMID, NULL));
+	TEST_ERRNO(msgget(CUSTOM_KEY, 0), ENOENT);
+	TEST_ERRNO(msgctl(msqid, IPC_RMID, NULL), EINVAL);
+}
+END_TEST()
+
+FN_TEST(msgsnd_reject_bad_args)
+{
+	struct test_msg msg = { .mtype = 1 };
+	int msqid = TEST_SUCC(create_queue());
+
+	TEST_ERRNO(msgsnd(msqid, &msg, MSGMAX + 1, 0), EINVAL);
+	TEST_ERRNO(msgsnd(-1, &msg, 1, 0), EINVAL);
+	TEST_ERRNO(msgsnd(msqid + 1, &msg, 1, 0), EINVAL);
+	TEST_ERRNO(msgsnd(msqid, NULL, 1, 0), EFAULT);
+
+	msg.mtype = 0;
+	TEST_ERRNO(msgsnd(msqid, &msg, 1, 0), EINVAL);
+	msg.mtype = -1;
+	TEST_ERRNO(msgsnd(msqid, &msg, 1, 0), EINVAL);
+
+	TEST_SUCC(msgctl(msqid, IPC_RMID, NULL));
+}
+END_TEST()
+
+FN_TEST(msgrcv_select_by_type)
+{
+	int msqid = TEST_SUCC(create_queue());
+
+	TEST_SUCC(send_msg(msqid, 3, "three", 0));
+	TEST_SUCC(send_msg(msqid, 1, "one", 0));
+	TEST_SUCC(send_msg(msqid, 2, "two", 0));
+	TEST_SUCC(send_msg(msqid, 1, "another one", 0));
+
+	TEST_RES(recv_type(msqid, 2, IPC_NOWAIT), _ret == 2);
+	TEST_ERRNO(recv_type(msqid, 2, IPC_NOWAIT), ENOMSG);
+	TEST_RES(recv_type(msqid, 1, IPC_NOWAIT | MSG_EXCEPT), _ret == 3);
+	TEST_RES(recv_type(msqid, -5, IPC_NOWAIT), _ret == 1);
+	TEST_RES(recv_type(msqid, 0, IPC_NOWAIT), _ret == 1);
+	TEST_ERRNO(recv_type(msqid, 0, IPC_NOWAIT), ENOMSG);
+
+	TEST_SUCC(msgctl(msqid, IPC_RMID, NULL));
+}
+END_TEST()
+
+FN_TEST(msgrcv_truncate)
+{
+	struct test_msg msg;
+	struct msqid_ds ds;
+	int msqid = TEST_SUCC(create_queue());
+
+	TEST_SUCC(send_msg(msqid, 1, "hello", 0));
+
+	TEST_ERRNO(msgrcv(msqid, &msg, 3, 0, IPC_NOWAIT), E2BIG);
+	TEST_RES(msgctl(msqid, IPC_STAT, &ds), ds.msg_qnum == 1);
+
+	TEST_RES(msgrcv(msqid, &msg, 3, 0, IPC_NOWAIT | MSG_NOERROR),
+		 _ret == 3 && msg.mtype == 1 &&
+			 memcmp(msg.mtext, "hel", 3) == 0);
+	TEST_RES(msgctl(msqid, IPC_STAT, &ds),
+		 ds.msg_qnum == 0 && ds.msg_cbytes == 0);
+
+	TEST_SUCC(msgctl(msqid, IPC_RMID, NULL));
+}
+END_TEST()
+
+FN_TEST(msgsnd_blocks_when_full)
+{
+	static char buf[sizeof(long) + MSGMAX];
+	struct msqid_ds ds;
+	int msqid = TEST_SUCC(create_queue());
+	pid_t child;
+	int status;
+	int i;
+
+	*(long *)buf = 1;
+	for (i = 0; i < MSGMNB / MSGMAX; ++i)
+		TEST_SUCC(msgsnd(msqid, buf, MSGMAX, IPC_NOWAIT));
+	TEST_ERRNO(msgsnd(msqid, buf, 1, IPC_NOWAIT), EAGAIN);
+
+	child = TEST_SUCC(fork());
+	if (child == 0) {
+		CHECK(msgsnd(msqid, buf, MSGMAX, 0));
+		_exit(0);
+	}
+
+	sleep_ms(SETTLE_MS);
+	TEST_RES(msgctl(msqid, IPC_STAT, &ds),
+		 ds.msg_qnum == MSGMNB / MSGMAX && ds.msg_cbytes == MSGMNB);
+
+	TEST_RES(msgrcv(msqid, buf, MSGMAX, 0, 0), _ret == MSGMAX);
+	TEST_RES(waitpid(child, &status, 0),
+		 _ret == child && WIFEXITED(status) &&
+			 WEXITSTATUS(status) == 0);
+	TEST_RES(msgctl(msqid, IPC_STAT, &ds),
+		 ds.msg_qnum == MSGMNB / MSGMAX && ds.msg_lspid == child);
+
+	TEST_SUCC(msgctl(msqid, IPC_RMID, NULL));
+}
+END_TEST()
+
+FN_TEST(msgrcv_blocks_until_sent)
+{
+	int msqid = TEST_SUCC(create_queue());
+	pid_t child;
+	int status;
+
+	child = TEST_SUCC(fork());
+	if (child == 0) {
+		CHECK_WITH(recv_type(msqid, 2, 0), _ret == 2);
+		_exit(0);
+	}
+
+	sleep_ms(SETTLE_MS);
+	TEST_SUCC(send_msg(msqid, 1, "one", 0));
+	sleep_ms(SETTLE_MS);
+	TEST_SUCC(send_msg(msqid, 2, "two", 0));
+
+	TEST_RES(waitpid(child, &status, 0),
+		 _ret == child && WIFEXITED(status) &&
+			 WEXITSTATUS(status) == 0);
+	TEST_RES(recv_type(msqid, 0, IPC_NOWAIT), _ret == 1);
+
+	TEST_SUCC(msgctl(msqid, IPC_RMID, NULL));
+}
+END_TEST()
+
+FN_TEST(msgrcv_is_interrupted_by_signal)
+{
+	int msqid = TEST_SUCC(create_queue());
+	pid_t child;
+	int status;
+
+	child = TEST_SUCC(fork());
+	if (child == 0) {
+		CHECK_WITH(recv_type(msqid, 0, 0), _ret == -1 && errno == EINTR);
+		_exit(0);
+	}
+
+	sleep_ms(SETTLE_MS);
+	TEST_SUCC(kill(child, SIGUSR1));
+
+	TEST_RES(waitpid(child, &status, 0),
+		 _ret == child && WIFEXITED(status) &&
+			 WEXITSTATUS(status) == 0);
+
+	TEST_SUCC(msgctl(msqid, IPC_RMID, NULL));
+}
+END_TEST()
+
+FN_TEST(msgrcv_fails_on_removal)
+{
+	int msqid = TEST_SUCC(create_queue());
+	pid_t child;
+	int status;
+
+	child = TEST_SUCC(fork());
+	if (child == 0) {
+		CHECK_WITH(recv_type(msqid, 0, 0), _ret == -1 && errno == EIDRM);
+		_exit(0);
+	}
+
+	sleep_ms(SETTLE_MS);
+	TEST_SUCC(msgctl(msqid, IPC_RMID, NULL));
+
+	TEST_RES(waitpid(child, &status, 0),
+		 _ret == child && WIFEXITED(status) &&
+			 WEXITSTATUS(status) == 0);
+}
+END_TEST()
+
+FN_TEST(msgctl_stat_and_set)
+{
+	struct msqid_ds ds;
+	int msqid = TEST_SUCC(create_queue());
+
+	TEST_SUCC(send_msg(msqid, 1, "hello", 0));
+	TEST_RES(msgctl(msqid, IPC_STAT, &ds),
+		 ds.msg_qnum == 1 && ds.msg_cbytes == 6 &&
+			 ds.msg_qbytes == MSGMNB && ds.msg_lspid == getpid() &&
+			 (ds.msg_perm.mode & 0777) == 0600);
+
+	ds.msg_perm.mode = 0400;
+	ds.msg_qbytes = 100;
+	TEST_SUCC(msgctl(msqid, IPC_SET, &ds));
+	TEST_RES(msgctl(msqid, IPC_STAT, &ds),
+		 (ds.msg_perm.mode & 0777) == 0400 && ds.msg_qbytes == 100);
+
+	TEST_SUCC(msgctl(msqid, IPC_RMID, NULL));
+	TEST_ERRNO(msgctl(msqid, IPC_STAT, &ds), EINVAL);
+}
+END_TEST()
+
+FN_TEST(msgctl_info)
+{
+	struct msginfo info;
+	int msqid = TEST_SUCC(create_queue());
+
+	TEST_RES(msgctl(0, IPC_INFO, (struct msqid_ds *)&info),
+		 _ret >= msqid && info.msgmax == MSGMAX &&
+			 info.msgmnb == MSGMNB);
+
+	TEST_SUCC(send_msg(msqid, 1, "hello", 0));
+	TEST_RES(msgctl(0, MSG_INFO, (struct msqid_ds *)&info),
+		 _ret >= msqid && info.msgpool >= 1 && info.msgmap >= 1 &&
+			 info.msgtql >= 6);
+
+	TEST_SUCC(msgctl(msqid, IPC_RMID, NULL));
+}
+END_TEST()
+
+FN_TEST(proc_sys_msgmax)
+{
+	struct test_msg msg = { .mtype = 1 };
+	char buf[16];
+	int msqid = TEST_SUCC(create_queue());
+	int fd;
+
+	fd = TEST_SUCC(open("/proc/sys/kernel/msgmax", O_RDWR));
+	TEST_RES(read(fd, buf, sizeof(buf)),
+		 _ret == 5 && memcmp(buf, "8192\n", 5) == 0);
+	TEST_RES(pwrite(fd, "16\n", 3, 0), _ret == 3);
+	TEST_ERRNO(msgsnd(msqid, &msg, 17, IPC_NOWAIT), EINVAL);
+	TEST_SUCC(msgsnd(msqid, &msg, 16, IPC_NOWAIT));
+	TEST_RES(pwrite(fd, "8192\n", 5, 0), _ret == 5);
+	TEST_SUCC(close(fd));
+
+	fd = TEST_SUCC(open("/proc/sys/kernel/msgmnb", O_RDONLY));
+	TEST_RES(read(fd, buf, sizeof(buf)),
+		 _ret == 6 && memcmp(buf, "16384\n", 6) == 0);
+	TEST_SUCC(close(fd));
+
+	TEST_SUCC(msgctl(msqid, IPC_RMID, NULL));
+}
+END_TEST()
@@ -4,6 +4,10 @@
 
 set -e
 
+./mqueue/mqueue
+
+./msg/msg
+
 ./pipe/pipe_err
 ./pipe/process_pipe_available
 ./pipe/short_rw
@@ -11,3 +15,4 @@ set -e
 ./sem/sem
 
 ./shm/posix_shm
+./shm/sysv_shm
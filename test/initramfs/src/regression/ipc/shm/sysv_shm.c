@@ -0,0 +1,184 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <errno.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/ipc.h>
+#include <sys/mman.h>
+#include <sys/shm.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#include "../../common/test.h"
+
+#define PAGE_SIZE 4096
+#define SEG_SIZE (PAGE_SIZE * 2 + 100)
+
+#define CUSTOM_KEY 0xbeefdead
+
+static int create_segment(size_t size)
+{
+	return shmget(IPC_PRIVATE, size, IPC_CREAT | 0600);
+}
+
+static int get_nattch(int shmid)
+{
+	struct shmid_ds ds;
+
+	if (shmctl(shmid, IPC_STAT, &ds) < 0)
+		return -1;
+
+	return ds.shm_nattch;
+}
+
+FN_TEST(shmget_reject_bad_size)
+{
+	TEST_ERRNO(create_segment(0), EINVAL);
+	TEST_ERRNO(create_segment(-1), EINVAL);
+}
+END_TEST()
+
+FN_TEST(shmget_by_key)
+{
+	int shmid = TEST_SUCC(shmget(CUSTOM_KEY, SEG_SIZE, IPC_CREAT | 0600));
+
+	TEST_RES(shmget(CUSTOM_KEY, SEG_SIZE, 0), _ret == shmid);
+	TEST_RES(shmget(CUSTOM_KEY, 1, IPC_CREAT | 0600), _ret == shmid);
+	TEST_ERRNO(shmget(CUSTOM_KEY, SEG_SIZE, IPC_CREAT | IPC_EXCL | 0600),
+		   EEXIST);
+	TEST_ERRNO(shmget(CUSTOM_KEY, SEG_SIZE + 1, 0), EINVAL);
+	TEST_ERRNO(shmget(CUSTOM_KEY + 1, SEG_SIZE, 0), ENOENT);
+
+	TEST_SUCC(shmctl(shmid, IPC_RMID, NULL));
+	TEST_ERRNO(shmget(CUSTOM_KEY, SEG_SIZE, 0), ENOENT);
+}
+END_TEST()
+
+FN_TEST(shmat_shmdt)
+{
+	int shmid = TEST_SUCC(create_segment(SEG_SIZE));
+	char *addr;
+
+	addr = TEST_RES(shmat(shmid, NULL, 0), _ret != (void *)-1);
+	TEST_RES(get_nattch(shmid), _ret == 1);
+	TEST_RES(addr[0], _ret == 0);
+	TEST_RES(addr[SEG_SIZE - 1], _ret == 0);
+	addr[0] = 'a';
+
+	TEST_SUCC(shmdt(addr));
+	TEST_RES(get_nattch(shmid), _ret == 0);
+	TEST_ERRNO(shmdt(addr), EINVAL);
+
+	addr = TEST_RES(shmat(shmid, NULL, SHM_RDONLY), _ret != (void *)-1);
+	TEST_RES(addr[0], _ret == 'a');
+	TEST_ERRNO(mprotect(addr, PAGE_SIZE, PROT_READ | PROT_WRITE), EACCES);
+	TEST_SUCC(shmdt(addr));
+
+	TEST_SUCC(shmctl(shmid, IPC_RMID, NULL));
+}
+END_TEST()
+
+FN_TEST(shmat_reject_bad_args)
+{
+	int shmid = TEST_SUCC(create_segment(SEG_SIZE));
+	char *addr;
+
+	TEST_ERRNO(shmat(shmid + 1, NULL, 0), EINVAL);
+	TEST_ERRNO(shmat(-1, NULL, 0), EINVAL);
+	TEST_ERRNO(shmat(shmid, NULL, SHM_REMAP), EINVAL);
+
+	addr = TEST_RES(shmat(shmid, NULL, 0), _ret != (void *)-1);
+	TEST_ERRNO(shmat(shmid, addr + 1, 0), EINVAL);
+	TEST_ERRNO(shmat(shmid, addr, 0), EINVAL);
+	TEST_RES(shmat(shmid, addr + 1, SHM_RND | SHM_REMAP), _ret == addr);
+	TEST_SUCC(shmdt(addr));
+
+	TEST_SUCC(shmctl(shmid, IPC_RMID, NULL));
+}
+END_TEST()
+
+FN_TEST(shmat_shared_with_child)
+{
+	int shmid = TEST_SUCC(create_segment(SEG_SIZE));
+	char *addr;
+	pid_t child;
+	int status;
+
+	addr = TEST_RES(shmat(shmid, NULL, 0), _ret != (void *)-1);
+
+	child = TEST_SUCC(fork());
+	if (child == 0) {
+		CHECK_WITH(get_nattch(shmid), _ret == 2);
+		strcpy(addr, "hello");
+		CHECK(shmdt(addr));
+		exit(EXIT_SUCCESS);
+	}
+
+	TEST_RES(wait(&status), _ret == child && WIFEXITED(status) &&
+					WEXITSTATUS(status) == 0);
+	TEST_RES(strcmp(addr, "hello"), _ret == 0);
+	TEST_RES(get_nattch(shmid), _ret == 1);
+
+	TEST_SUCC(shmdt(addr));
+	TEST_SUCC(shmctl(shmid, IPC_RMID, NULL));
+}
+END_TEST()
+
+FN_TEST(shmctl_rmid_is_deferred)
+{
+	int shmid = TEST_SUCC(shmget(CUSTOM_KEY, SEG_SIZE, IPC_CREAT | 0600));
+	struct shmid_ds ds;
+	char *addr;
+
+	addr = TEST_RES(shmat(shmid, NULL, 0), _ret != (void *)-1);
+	TEST_SUCC(shmctl(shmid, IPC_RMID, NULL));
+
+	TEST_RES(shmctl(shmid, IPC_STAT, &ds),
+		 ds.shm_nattch == 1 && (ds.shm_perm.mode & SHM_DEST) &&
+			 ds.shm_perm.__key == IPC_PRIVATE);
+	TEST_ERRNO(shmget(CUSTOM_KEY, SEG_SIZE, 0), ENOENT);
+
+	addr[0] = 'a';
+	TEST_SUCC(shmdt(addr));
+	TEST_ERRNO(shmctl(shmid, IPC_STAT, &ds), EINVAL);
+}
+END_TEST()
+
+FN_TEST(shmctl_stat_and_set)
+{
+	int shmid = TEST_SUCC(create_segment(SEG_SIZE));
+	struct shmid_ds ds;
+
+	TEST_RES(shmctl(shmid, IPC_STAT, &ds),
+		 ds.shm_segsz == SEG_SIZE && ds.shm_cpid == getpid() &&
+			 ds.shm_nattch == 0 &&
+			 (ds.shm_perm.mode & 0777) == 0600);
+
+	ds.shm_perm.mode = 0400;
+	TEST_SUCC(shmctl(shmid, IPC_SET, &ds));
+	TEST_RES(shmctl(shmid, IPC_STAT, &ds),
+		 (ds.shm_perm.mode & 0777) == 0400);
+
+	TEST_SUCC(shmctl(shmid, IPC_RMID, NULL));
+	TEST_ERRNO(shmctl(shmid, IPC_STAT, &ds), EINVAL);
+	TEST_ERRNO(shmctl(shmid, IPC_RMID, NULL), EINVAL);
+}
+END_TEST()
+
+FN_TEST(shmctl_info)
+{
+	struct shminfo info;
+	struct shm_info shm_info;
+	int shmid = TEST_SUCC(create_segment(SEG_SIZE));
+
+	TEST_RES(shmctl(0, IPC_INFO, (struct shmid_ds *)&info),
+		 _ret >= shmid && info.shmmin == 1 && info.shmmni == 4096);
+	TEST_RES(shmctl(0, SHM_INFO, (struct shmid_ds *)&shm_info),
+		 _ret >= shmid && shm_info.used_ids >= 1 &&
+			 shm_info.shm_tot >= 3);
+
+	TEST_SUCC(shmctl(shmid, IPC_RMID, NULL));
+}
+END_TEST()
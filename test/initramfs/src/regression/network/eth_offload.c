@@ -0,0 +1,98 @@
+// SPDX-License-Identifier: MPL-2.0
+
+// Runs traffic over the Ethernet interface, which may be a virtio-net device
+// with multiple queue pairs and checksum offloads (see `tools/qemu_args.sh`).
+//
+// The host (i.e., the gateway) drops the TCP segments whose checksums are
+// wrong. So if the checksum offloads do not work, the connections below time
+// out instead of being refused.
+
+#define _GNU_SOURCE
+
+#include <sched.h>
+#include <unistd.h>
+#include <sys/poll.h>
+#include <sys/socket.h>
+#include <netinet/in.h>
+#include <arpa/inet.h>
+
+#include "../common/test.h"
+
+#define GATEWAY_ADDR "10.0.2.2"
+// No services should listen on this port on the host.
+#define CLOSED_PORT 1
+#define NUM_CONNECTS 16
+#define TIMEOUT_MS 5000
+
+static struct sockaddr_in gateway_addr;
+static cpu_set_t cpus;
+
+FN_SETUP(gateway)
+{
+	gateway_addr.sin_family = AF_INET;
+	gateway_addr.sin_port = htons(CLOSED_PORT);
+	CHECK(inet_aton(GATEWAY_ADDR, &gateway_addr.sin_addr));
+
+	CHECK(sched_getaffinity(0, sizeof(cpus), &cpus));
+}
+END_SETUP()
+
+static int connect_gateway(void)
+{
+	int sk = socket(PF_INET, SOCK_STREAM | SOCK_NONBLOCK, 0);
+	struct pollfd pfd = { .fd = sk, .events = POLLOUT };
+	int err;
+	socklen_t errlen = sizeof(err);
+
+	if (sk < 0)
+		return -1;
+
+	if (connect(sk, (struct sockaddr *)&gateway_addr,
+		    sizeof(gateway_addr)) == 0 ||
+	    errno != EINPROGRESS)
+		goto fail;
+
+	if (poll(&pfd, 1, TIMEOUT_MS) != 1 || !(pfd.revents & POLLERR))
+		goto fail;
+
+	if (getsockopt(sk, SOL_SOCKET, SO_ERROR, &err, &errlen) < 0)
+		goto fail;
+
+	close(sk);
+	// Clear `EINPROGRESS` so that it is not reported as an error.
+	errno = 0;
+	return err;
+
+fail:
+	close(sk);
+	return -1;
+}
+
+FN_TEST(connect_refused_on_each_cpu)
+{
+	cpu_set_t cpu;
+
+	// Each CPU sends packets via its own queue pair if the device has
+	// multiple queue pairs.
+	for (int i = 0; i < CPU_SETSIZE; i++) {
+		if (!CPU_ISSET(i, &cpus))
+			continue;
+
+		CPU_ZERO(&cpu);
+		CPU_SET(i, &cpu);
+		TEST_SUCC(sched_setaffinity(0, sizeof(cpu), &cpu));
+
+		TEST_RES(connect_gateway(), _ret == ECONNREFUSED);
+	}
+
+	TEST_SUCC(sched_setaffinity(0, sizeof(cpus), &cpus));
+}
+END_TEST()
+
+FN_TEST(connect_refused_repeatedly)
+{
+	// The replies may be received via any queue pair.
+	for (int i = 0; i < NUM_CONNECTS; i++)
+		TEST_RES(connect_gateway(), _ret == ECONNREFUSED);
+}
+END_TEST()
@@ -0,0 +1,88 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <ifaddrs.h>
+#include <netinet/in.h>
+#include <arpa/inet.h>
+
+#include "../common/test.h"
+
+#define ETHER_NAME "eth0"
+
+static struct ifaddrs *ifaddrs;
+
+FN_SETUP(getifaddrs)
+{
+	CHECK(getifaddrs(&ifaddrs));
+}
+END_SETUP()
+
+static struct ifaddrs *find_addr(int family, int link_local_only)
+{
+	struct ifaddrs *ifa;
+
+	for (ifa = ifaddrs; ifa != NULL; ifa = ifa->ifa_next) {
+		if (ifa->ifa_addr == NULL ||
+		    ifa->ifa_addr->sa_family != family ||
+		    strcmp(ifa->ifa_name, ETHER_NAME) != 0)
+			continue;
+		if (link_local_only &&
+		    !IN6_IS_ADDR_LINKLOCAL(
+			    &((struct sockaddr_in6 *)ifa->ifa_addr)->sin6_addr))
+			continue;
+		return ifa;
+	}
+
+	return NULL;
+}
+
+static struct ifaddrs *ipv4_ifa;
+static struct ifaddrs *ipv6_ifa;
+
+FN_SETUP(find_addrs)
+{
+	ipv4_ifa = CHECK_WITH(find_addr(AF_INET, 0), _ret != NULL);
+	ipv6_ifa = CHECK_WITH(find_addr(AF_INET6, 1), _ret != NULL);
+}
+END_SETUP()
+
+FN_TEST(default_ipv4_addr)
+{
+	struct sockaddr_in *addr = (struct sockaddr_in *)ipv4_ifa->ifa_addr;
+	struct sockaddr_in *netmask =
+		(struct sockaddr_in *)ipv4_ifa->ifa_netmask;
+
+	// Without the `ip=` kernel parameter, the address of QEMU user
+	// networking is used.
+	TEST_RES(addr->sin_addr.s_addr, _ret == inet_addr("10.0.2.15"));
+	TEST_RES(netmask->sin_addr.s_addr, _ret == inet_addr("255.255.255.0"));
+}
+END_TEST()
+
+FN_TEST(link_local_ipv6_addr)
+{
+	struct sockaddr_in6 *addr = (struct sockaddr_in6 *)ipv6_ifa->ifa_addr;
+	struct sockaddr_in6 *netmask =
+		(struct sockaddr_in6 *)ipv6_ifa->ifa_netmask;
+
+	// The link-local address is `fe80::/64` with the interface identifier
+	// in the modified EUI-64 format.
+	TEST_RES(addr->sin6_addr.s6_addr32[1], _ret == 0);
+	TEST_RES(addr->sin6_addr.s6_addr[11] == 0xff &&
+			 addr->sin6_addr.s6_addr[12] == 0xfe,
+		 _ret);
+	TEST_RES(netmask->sin6_addr.s6_addr32[0] &
+			 netmask->sin6_addr.s6_addr32[1],
+		 _ret == 0xffffffff);
+	TEST_RES(netmask->sin6_addr.s6_addr32[2] |
+			 netmask->sin6_addr.s6_addr32[3],
+		 _ret == 0);
+}
+END_TEST()
+
+FN_SETUP(freeifaddrs)
+{
+	freeifaddrs(ifaddrs);
+}
+END_SETUP()
@@ -0,0 +1,782 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <endian.h>
+#include <unistd.h>
+#include <sys/socket.h>
+#include <arpa/inet.h>
+#include <netinet/in.h>
+#include <linux/netlink.h>
+#include <linux/version.h>
+#include <linux/netfilter.h>
+#include <linux/netfilter/nfnetlink.h>
+#include <linux/netfilter/nf_tables.h>
+#include <linux/netfilter/nf_nat.h>
+
+#include "../common/test.h"
+
+// The destroy requests are introduced in Linux 6.3.
+#if LINUX_VERSION_CODE < KERNEL_VERSION(6, 3, 0)
+#define NFT_MSG_DESTROYTABLE 26
+#endif
+
+#define TABLE_NAME "nfttest"
+#define MISSING_NAME "nfttest_missing"
+
+#define DROP_PORT 9301
+#define DNAT_FROM_PORT 9302
+#define DNAT_TO_PORT 9303
+
+#define BUFFER_SIZE 65536
+
+static int nfnl_sk;
+static unsigned int seq;
+
+static char req_buf[4096];
+static struct nlmsghdr *req_last;
+
+static char resp_buf[BUFFER_SIZE];
+
+static size_t req_len(void)
+{
+	if (req_last == NULL)
+		return 0;
+	return (char *)req_last - req_buf + NLMSG_ALIGN(req_last->nlmsg_len);
+}
+
+static struct nlmsghdr *msg_add(int type, int flags, int family, int res_id)
+{
+	struct nlmsghdr *nlh = (struct nlmsghdr *)(req_buf + req_len());
+	struct nfgenmsg *nfg;
+
+	memset(nlh, 0, NLMSG_SPACE(sizeof(*nfg)));
+	nlh->nlmsg_len = NLMSG_LENGTH(sizeof(*nfg));
+	nlh->nlmsg_type = type;
+	nlh->nlmsg_flags = NLM_F_REQUEST | flags;
+	nlh->nlmsg_seq = ++seq;
+
+	nfg = NLMSG_DATA(nlh);
+	nfg->nfgen_family = family;
+	nfg->version = NFNETLINK_V0;
+	nfg->res_id = htons(res_id);
+
+	req_last = nlh;
+	return nlh;
+}
+
+static struct nlattr *attr_put(struct nlmsghdr *nlh, int type,
+			       const void *data, size_t len)
+{
+	struct nlattr *nla =
+		(struct nlattr *)((char *)nlh + NLMSG_ALIGN(nlh->nlmsg_len));
+
+	nla->nla_type = type;
+	nla->nla_len = NLA_HDRLEN + len;
+	if (len > 0)
+		memcpy((char *)nla + NLA_HDRLEN, data, len);
+	nlh->nlmsg_len = NLMSG_ALIGN(nlh->nlmsg_len) + NLA_ALIGN(nla->nla_len);
+
+	return nla;
+}
+
+static void attr_put_str(struct nlmsghdr *nlh, int type, const char *str)
+{
+	attr_put(nlh, type, str, strlen(str) + 1);
+}
+
+static void attr_put_be32(struct nlmsghdr *nlh, int type, uint32_t value)
+{
+	value = htonl(value);
+	attr_put(nlh, type, &value, sizeof(value));
+}
+
+static struct nlattr *nest_begin(struct nlmsghdr *nlh, int type)
+{
+	return attr_put(nlh, type | NLA_F_NESTED, NULL, 0);
+}
+
+static void nest_end(struct nlmsghdr *nlh, struct nlattr *nest)
+{
+	nest->nla_len = (char *)nlh + nlh->nlmsg_len - (char *)nest;
+}
+
+// Starts a batch that contains a single command.
+static struct nlmsghdr *cmd_begin(int type, int flags, int family)
+{
+	req_last = NULL;
+	msg_add(NFNL_MSG_BATCH_BEGIN, 0, AF_UNSPEC, NFNL_SUBSYS_NFTABLES);
+
+	return msg_add((NFNL_SUBSYS_NFTABLES << 8) | type, NLM_F_ACK | flags,
+		       family, 0);
+}
+
+// Waits for the acknowledgment of a request. Returns -1 and sets `errno` if
+// the request fails.
+static int recv_ack(void)
+{
+	struct {
+		struct nlmsghdr hdr;
+		struct nlmsgerr err;
+	} resp;
+
+	if (recv(nfnl_sk, &resp, sizeof(resp), 0) < 0)
+		return -1;
+
+	if (resp.hdr.nlmsg_type != NLMSG_ERROR) {
+		errno = EPROTO;
+		return -1;
+	}
+	if (resp.err.error != 0) {
+		errno = -resp.err.error;
+		return -1;
+	}
+	return 0;
+}
+
+// Ends the batch, sends it, and waits for the acknowledgment of the command.
+static int cmd_end(void)
+{
+	msg_add(NFNL_MSG_BATCH_END, 0, AF_UNSPEC, NFNL_SUBSYS_NFTABLES);
+
+	if (send(nfnl_sk, req_buf, req_len(), 0) < 0)
+		return -1;
+	return recv_ack();
+}
+
+static int send_single(void)
+{
+	return send(nfnl_sk, req_buf, req_len(), 0) < 0 ? -1 : 0;
+}
+
+static struct nlattr *attr_find(void *start, int len, int type)
+{
+	struct nlattr *nla = start;
+
+	while (len >= NLA_HDRLEN && nla->nla_len >= NLA_HDRLEN &&
+	       nla->nla_len <= len) {
+		if ((nla->nla_type & NLA_TYPE_MASK) == type)
+			return nla;
+		len -= NLA_ALIGN(nla->nla_len);
+		nla = (struct nlattr *)((char *)nla + NLA_ALIGN(nla->nla_len));
+	}
+	return NULL;
+}
+
+static struct nlattr *msg_attr(struct nlmsghdr *nlh, int type)
+{
+	size_t hdr_len = NLMSG_SPACE(sizeof(struct nfgenmsg));
+
+	return attr_find((char *)nlh + hdr_len, nlh->nlmsg_len - hdr_len, type);
+}
+
+static struct nlattr *nested_attr(struct nlattr *nest, int type)
+{
+	return attr_find((char *)nest + NLA_HDRLEN, nest->nla_len - NLA_HDRLEN,
+			 type);
+}
+
+static void *attr_data(struct nlattr *nla)
+{
+	return (char *)nla + NLA_HDRLEN;
+}
+
+static uint32_t attr_be32(struct nlattr *nla)
+{
+	uint32_t value;
+
+	memcpy(&value, attr_data(nla), sizeof(value));
+	return ntohl(value);
+}
+
+static uint64_t attr_be64(struct nlattr *nla)
+{
+	uint64_t value;
+
+	memcpy(&value, attr_data(nla), sizeof(value));
+	return be64toh(value);
+}
+
+static struct nlattr *expr_begin(struct nlmsghdr *nlh, const char *name,
+				 struct nlattr **data)
+{
+	struct nlattr *elem = nest_begin(nlh, NFTA_LIST_ELEM);
+
+	attr_put_str(nlh, NFTA_EXPR_NAME, name);
+	*data = nest_begin(nlh, NFTA_EXPR_DATA);
+	return elem;
+}
+
+static void expr_end(struct nlmsghdr *nlh, struct nlattr *elem,
+		     struct nlattr *data)
+{
+	nest_end(nlh, data);
+	nest_end(nlh, elem);
+}
+
+static void put_value(struct nlmsghdr *nlh, int type, const void *value,
+		      size_t len)
+{
+	struct nlattr *nest = nest_begin(nlh, type);
+
+	attr_put(nlh, NFTA_DATA_VALUE, value, len);
+	nest_end(nlh, nest);
+}
+
+static void expr_l4proto(struct nlmsghdr *nlh, uint8_t proto)
+{
+	struct nlattr *elem, *data;
+
+	elem = expr_begin(nlh, "meta", &data);
+	attr_put_be32(nlh, NFTA_META_DREG, NFT_REG_1);
+	attr_put_be32(nlh, NFTA_META_KEY, NFT_META_L4PROTO);
+	expr_end(nlh, elem, data);
+
+	elem = expr_begin(nlh, "cmp", &data);
+	attr_put_be32(nlh, NFTA_CMP_SREG, NFT_REG_1);
+	attr_put_be32(nlh, NFTA_CMP_OP, NFT_CMP_EQ);
+	put_value(nlh, NFTA_CMP_DATA, &proto, sizeof(proto));
+	expr_end(nlh, elem, data);
+}
+
+static void expr_dport(struct nlmsghdr *nlh, uint16_t port)
+{
+	struct nlattr *elem, *data;
+
+	port = htons(port);
+
+	elem = expr_begin(nlh, "payload", &data);
+	attr_put_be32(nlh, NFTA_PAYLOAD_DREG, NFT_REG_1);
+	attr_put_be32(nlh, NFTA_PAYLOAD_BASE, NFT_PAYLOAD_TRANSPORT_HEADER);
+	attr_put_be32(nlh, NFTA_PAYLOAD_OFFSET, 2);
+	attr_put_be32(nlh, NFTA_PAYLOAD_LEN, sizeof(port));
+	expr_end(nlh, elem, data);
+
+	elem = expr_begin(nlh, "cmp", &data);
+	attr_put_be32(nlh, NFTA_CMP_SREG, NFT_REG_1);
+	attr_put_be32(nlh, NFTA_CMP_OP, NFT_CMP_EQ);
+	put_value(nlh, NFTA_CMP_DATA, &port, sizeof(port));
+	expr_end(nlh, elem, data);
+}
+
+static void expr_counter(struct nlmsghdr *nlh)
+{
+	struct nlattr *elem, *data;
+
+	elem = expr_begin(nlh, "counter", &data);
+	expr_end(nlh, elem, data);
+}
+
+static void expr_verdict(struct nlmsghdr *nlh, int code, const char *chain)
+{
+	struct nlattr *elem, *data, *imm_data, *verdict;
+
+	elem = expr_begin(nlh, "immediate", &data);
+	attr_put_be32(nlh, NFTA_IMMEDIATE_DREG, NFT_REG_VERDICT);
+	imm_data = nest_begin(nlh, NFTA_IMMEDIATE_DATA);
+	verdict = nest_begin(nlh, NFTA_DATA_VERDICT);
+	attr_put_be32(nlh, NFTA_VERDICT_CODE, code);
+	if (chain != NULL)
+		attr_put_str(nlh, NFTA_VERDICT_CHAIN, chain);
+	nest_end(nlh, verdict);
+	nest_end(nlh, imm_data);
+	expr_end(nlh, elem, data);
+}
+
+static void expr_immediate(struct nlmsghdr *nlh, int reg, const void *value,
+			   size_t len)
+{
+	struct nlattr *elem, *data;
+
+	elem = expr_begin(nlh, "immediate", &data);
+	attr_put_be32(nlh, NFTA_IMMEDIATE_DREG, reg);
+	put_value(nlh, NFTA_IMMEDIATE_DATA, value, len);
+	expr_end(nlh, elem, data);
+}
+
+static void expr_dnat(struct nlmsghdr *nlh, int addr_reg, int port_reg)
+{
+	struct nlattr *elem, *data;
+
+	elem = expr_begin(nlh, "nat", &data);
+	attr_put_be32(nlh, NFTA_NAT_TYPE, NFT_NAT_DNAT);
+	attr_put_be32(nlh, NFTA_NAT_FAMILY, NFPROTO_IPV4);
+	attr_put_be32(nlh, NFTA_NAT_REG_ADDR_MIN, addr_reg);
+	attr_put_be32(nlh, NFTA_NAT_REG_PROTO_MIN, port_reg);
+	expr_end(nlh, elem, data);
+}
+
+static int new_table(const char *name, int flags)
+{
+	struct nlmsghdr *nlh;
+
+	nlh = cmd_begin(NFT_MSG_NEWTABLE, NLM_F_CREATE | flags, NFPROTO_IPV4);
+	attr_put_str(nlh, NFTA_TABLE_NAME, name);
+	return cmd_end();
+}
+
+static int del_table(int type, const char *name)
+{
+	struct nlmsghdr *nlh;
+
+	nlh = cmd_begin(type, 0, NFPROTO_IPV4);
+	attr_put_str(nlh, NFTA_TABLE_NAME, name);
+	return cmd_end();
+}
+
+// Creates a chain. It is a regular chain if `type` is NULL. The policy is not
+// specified if `policy` is negative.
+static int new_chain(const char *table, const char *name, const char *type,
+		     int hooknum, int priority, int policy, int flags)
+{
+	struct nlmsghdr *nlh;
+	struct nlattr *hook;
+
+	nlh = cmd_begin(NFT_MSG_NEWCHAIN, NLM_F_CREATE | flags, NFPROTO_IPV4);
+	attr_put_str(nlh, NFTA_CHAIN_TABLE, table);
+	attr_put_str(nlh, NFTA_CHAIN_NAME, name);
+	if (type != NULL) {
+		hook = nest_begin(nlh, NFTA_CHAIN_HOOK);
+		attr_put_be32(nlh, NFTA_HOOK_HOOKNUM, hooknum);
+		attr_put_be32(nlh, NFTA_HOOK_PRIORITY, priority);
+		nest_end(nlh, hook);
+		attr_put_str(nlh, NFTA_CHAIN_TYPE, type);
+	}
+	if (policy >= 0)
+		attr_put_be32(nlh, NFTA_CHAIN_POLICY, policy);
+	return cmd_end();
+}
+
+static int del_chain(const char *name)
+{
+	struct nlmsghdr *nlh;
+
+	nlh = cmd_begin(NFT_MSG_DELCHAIN, 0, NFPROTO_IPV4);
+	attr_put_str(nlh, NFTA_CHAIN_TABLE, TABLE_NAME);
+	attr_put_str(nlh, NFTA_CHAIN_NAME, name);
+	return cmd_end();
+}
+
+static struct nlmsghdr *rule_begin(const char *chain, struct nlattr **exprs)
+{
+	struct nlmsghdr *nlh;
+
+	nlh = cmd_begin(NFT_MSG_NEWRULE, NLM_F_CREATE | NLM_F_APPEND,
+			NFPROTO_IPV4);
+	attr_put_str(nlh, NFTA_RULE_TABLE, TABLE_NAME);
+	attr_put_str(nlh, NFTA_RULE_CHAIN, chain);
+	*exprs = nest_begin(nlh, NFTA_RULE_EXPRESSIONS);
+	return nlh;
+}
+
+static int rule_end(struct nlmsghdr *nlh, struct nlattr *exprs)
+{
+	nest_end(nlh, exprs);
+	return cmd_end();
+}
+
+static int add_verdict_rule(const char *chain, int code, const char *target)
+{
+	struct nlmsghdr *nlh;
+	struct nlattr *exprs;
+
+	nlh = rule_begin(chain, &exprs);
+	expr_verdict(nlh, code, target);
+	return rule_end(nlh, exprs);
+}
+
+FN_SETUP(socket)
+{
+	nfnl_sk = CHECK(socket(AF_NETLINK, SOCK_RAW, NETLINK_NETFILTER));
+}
+END_SETUP()
+
+FN_SETUP(cleanup)
+{
+	// Removes the leftovers of previous runs, if any.
+	CHECK(del_table(NFT_MSG_DESTROYTABLE, TABLE_NAME));
+}
+END_SETUP()
+
+static uint32_t get_generation(void)
+{
+	struct nlmsghdr *nlh = (struct nlmsghdr *)resp_buf;
+	struct nlattr *nla;
+
+	req_last = NULL;
+	msg_add((NFNL_SUBSYS_NFTABLES << 8) | NFT_MSG_GETGEN, 0, AF_UNSPEC, 0);
+	if (send_single() < 0 || recv(nfnl_sk, resp_buf, BUFFER_SIZE, 0) < 0)
+		return 0;
+
+	if (nlh->nlmsg_type != ((NFNL_SUBSYS_NFTABLES << 8) | NFT_MSG_NEWGEN))
+		return 0;
+	nla = msg_attr(nlh, NFTA_GEN_ID);
+	return nla == NULL ? 0 : attr_be32(nla);
+}
+
+FN_TEST(generation)
+{
+	uint32_t generation;
+	struct nlmsghdr *nlh;
+	struct nlattr *nla;
+
+	generation = TEST_RES(get_generation(), _ret != 0);
+
+	// A successful batch increases the generation.
+	TEST_SUCC(new_table(TABLE_NAME, 0));
+	TEST_RES(get_generation(), _ret == generation + 1);
+	generation = get_generation();
+
+	// A batch that changes nothing does not increase the generation.
+	TEST_SUCC(new_table(TABLE_NAME, 0));
+	TEST_RES(get_generation(), _ret == generation);
+
+	// A batch based on a stale generation is rejected.
+	req_last = NULL;
+	nlh = msg_add(NFNL_MSG_BATCH_BEGIN, NLM_F_ACK, AF_UNSPEC,
+		      NFNL_SUBSYS_NFTABLES);
+	attr_put_be32(nlh, NFNL_BATCH_GENID, generation + 1000);
+	nlh = msg_add((NFNL_SUBSYS_NFTABLES << 8) | NFT_MSG_NEWTABLE,
+		      NLM_F_CREATE | NLM_F_ACK, NFPROTO_IPV4, 0);
+	attr_put_str(nlh, NFTA_TABLE_NAME, MISSING_NAME);
+	msg_add(NFNL_MSG_BATCH_END, 0, AF_UNSPEC, NFNL_SUBSYS_NFTABLES);
+	TEST_SUCC(send_single());
+	TEST_ERRNO(recv_ack(), ERESTART);
+
+	// A batch of another subsystem is not supported.
+	req_last = NULL;
+	msg_add(NFNL_MSG_BATCH_BEGIN, NLM_F_ACK, AF_UNSPEC, 0);
+	msg_add(NFNL_MSG_BATCH_END, 0, AF_UNSPEC, 0);
+	TEST_SUCC(send_single());
+	TEST_ERRNO(recv_ack(), EOPNOTSUPP);
+
+	TEST_RES(get_generation(), _ret == generation);
+
+	// The name of a table is reported along with the generation.
+	req_last = NULL;
+	nlh = msg_add((NFNL_SUBSYS_NFTABLES << 8) | NFT_MSG_GETTABLE, 0,
+		      NFPROTO_IPV4, 0);
+	attr_put_str(nlh, NFTA_TABLE_NAME, TABLE_NAME);
+	TEST_SUCC(send_single());
+	nlh = (struct nlmsghdr *)resp_buf;
+	TEST_RES(recv(nfnl_sk, resp_buf, BUFFER_SIZE, 0),
+		 nlh->nlmsg_type ==
+				 ((NFNL_SUBSYS_NFTABLES << 8) |
+				  NFT_MSG_NEWTABLE) &&
+			 (nla = msg_attr(nlh, NFTA_TABLE_NAME)) != NULL &&
+			 strcmp(attr_data(nla), TABLE_NAME) == 0 &&
+			 ntohs(((struct nfgenmsg *)NLMSG_DATA(nlh))->res_id) ==
+				 (generation & 0xffff));
+}
+END_TEST()
+
+FN_TEST(table_errors)
+{
+	struct nlmsghdr *nlh;
+
+	TEST_ERRNO(new_table(TABLE_NAME, NLM_F_EXCL), EEXIST);
+	TEST_ERRNO(new_table(TABLE_NAME, NLM_F_REPLACE), EOPNOTSUPP);
+
+	// The name is required.
+	cmd_begin(NFT_MSG_NEWTABLE, NLM_F_CREATE, NFPROTO_IPV4);
+	TEST_ERRNO(cmd_end(), EINVAL);
+
+	// Unknown flags are not supported.
+	nlh = cmd_begin(NFT_MSG_NEWTABLE, NLM_F_CREATE, NFPROTO_IPV4);
+	attr_put_str(nlh, NFTA_TABLE_NAME, TABLE_NAME);
+	attr_put_be32(nlh, NFTA_TABLE_FLAGS, 0x80000000);
+	TEST_ERRNO(cmd_end(), EOPNOTSUPP);
+
+	TEST_ERRNO(del_table(NFT_MSG_DELTABLE, MISSING_NAME), ENOENT);
+	TEST_SUCC(del_table(NFT_MSG_DESTROYTABLE, MISSING_NAME));
+
+	// Requests that modify the ruleset must be sent in a batch.
+	req_last = NULL;
+	nlh = msg_add((NFNL_SUBSYS_NFTABLES << 8) | NFT_MSG_NEWTABLE,
+		      NLM_F_CREATE | NLM_F_ACK, NFPROTO_IPV4, 0);
+	attr_put_str(nlh, NFTA_TABLE_NAME, MISSING_NAME);
+	TEST_SUCC(send_single());
+	TEST_ERRNO(recv_ack(), EINVAL);
+
+	// Missing tables cannot be found.
+	req_last = NULL;
+	nlh = msg_add((NFNL_SUBSYS_NFTABLES << 8) | NFT_MSG_GETTABLE,
+		      NLM_F_ACK, NFPROTO_IPV4, 0);
+	attr_put_str(nlh, NFTA_TABLE_NAME, MISSING_NAME);
+	TEST_SUCC(send_single());
+	TEST_ERRNO(recv_ack(), ENOENT);
+}
+END_TEST()
+
+FN_TEST(chains)
+{
+	TEST_SUCC(new_chain(TABLE_NAME, "input", "filter", NF_INET_LOCAL_IN, 0,
+			    NF_ACCEPT, 0));
+	TEST_SUCC(new_chain(TABLE_NAME, "nat_output", "nat",
+			    NF_INET_LOCAL_OUT, -100, -1, 0));
+	TEST_SUCC(new_chain(TABLE_NAME, "regular", NULL, 0, 0, -1, 0));
+
+	TEST_ERRNO(new_chain(TABLE_NAME, "input", "filter", NF_INET_LOCAL_IN,
+			     0, -1, NLM_F_EXCL),
+		   EEXIST);
+	// The type, the hook, and the priority of a base chain cannot be
+	// changed.
+	TEST_ERRNO(new_chain(TABLE_NAME, "input", "nat", NF_INET_LOCAL_IN, 0,
+			     -1, 0),
+		   EEXIST);
+	TEST_ERRNO(new_chain(TABLE_NAME, "input", "filter", NF_INET_LOCAL_OUT,
+			     0, -1, 0),
+		   EOPNOTSUPP);
+	TEST_ERRNO(new_chain(TABLE_NAME, "input", "filter", NF_INET_LOCAL_IN,
+			     10, -1, 0),
+		   EOPNOTSUPP);
+	// A regular chain cannot become a base chain.
+	TEST_ERRNO(new_chain(TABLE_NAME, "regular", "filter", NF_INET_LOCAL_IN,
+			     0, -1, 0),
+		   EEXIST);
+	// The policy of a regular chain cannot be set.
+	TEST_ERRNO(new_chain(TABLE_NAME, "regular", NULL, 0, 0, NF_DROP, 0),
+		   EOPNOTSUPP);
+	TEST_ERRNO(new_chain(TABLE_NAME, "regular2", NULL, 0, 0, NF_DROP, 0),
+		   EOPNOTSUPP);
+	TEST_ERRNO(new_chain(TABLE_NAME, "input2", "filter", NF_INET_LOCAL_IN,
+			     0, 2, 0),
+		   EINVAL);
+	TEST_ERRNO(new_chain(TABLE_NAME, "input2", "unknown", NF_INET_LOCAL_IN,
+			     0, -1, 0),
+		   ENOENT);
+	TEST_ERRNO(new_chain(MISSING_NAME, "input", "filter", NF_INET_LOCAL_IN,
+			     0, -1, 0),
+		   ENOENT);
+
+	// The policy of a base chain can be changed.
+	TEST_SUCC(new_chain(TABLE_NAME, "input", "filter", NF_INET_LOCAL_IN, 0,
+			    NF_ACCEPT, 0));
+}
+END_TEST()
+
+FN_TEST(rule_errors)
+{
+	struct nlmsghdr *nlh;
+	struct nlattr *exprs, *elem, *data;
+
+	TEST_ERRNO(add_verdict_rule("missing", NF_ACCEPT, NULL), ENOENT);
+	TEST_ERRNO(add_verdict_rule("input", NFT_JUMP, "missing"), ENOENT);
+	TEST_ERRNO(add_verdict_rule("input", NFT_JUMP, "nat_output"),
+		   EOPNOTSUPP);
+	TEST_ERRNO(add_verdict_rule("input", NFT_JUMP, NULL), EINVAL);
+	TEST_ERRNO(add_verdict_rule("input", 42, NULL), EINVAL);
+
+	// Unknown expressions do not exist.
+	nlh = rule_begin("input", &exprs);
+	elem = expr_begin(nlh, "unknown", &data);
+	expr_end(nlh, elem, data);
+	TEST_ERRNO(rule_end(nlh, exprs), ENOENT);
+
+	// The comparison operator must be valid.
+	nlh = rule_begin("input", &exprs);
+	elem = expr_begin(nlh, "cmp", &data);
+	attr_put_be32(nlh, NFTA_CMP_SREG, NFT_REG_1);
+	attr_put_be32(nlh, NFTA_CMP_OP, 42);
+	put_value(nlh, NFTA_CMP_DATA, "a", 1);
+	expr_end(nlh, elem, data);
+	TEST_ERRNO(rule_end(nlh, exprs), EINVAL);
+
+	// The values must fit in the registers.
+	nlh = rule_begin("input", &exprs);
+	expr_immediate(nlh, NFT_REG_1, "0123456789abcdef0123", 20);
+	TEST_ERRNO(rule_end(nlh, exprs), EOVERFLOW);
+	nlh = rule_begin("input", &exprs);
+	expr_immediate(nlh, NFT_REG32_15, "01234567", 8);
+	TEST_ERRNO(rule_end(nlh, exprs), ERANGE);
+}
+END_TEST()
+
+// Finds the counter of the first rule in the chain via a dump request.
+static int get_counter(const char *chain, uint64_t *packets)
+{
+	struct nlmsghdr *nlh;
+	int len, found = 0, done = 0;
+
+	req_last = NULL;
+	nlh = msg_add((NFNL_SUBSYS_NFTABLES << 8) | NFT_MSG_GETRULE,
+		      NLM_F_DUMP, NFPROTO_IPV4, 0);
+	attr_put_str(nlh, NFTA_RULE_TABLE, TABLE_NAME);
+	attr_put_str(nlh, NFTA_RULE_CHAIN, chain);
+	if (send_single() < 0)
+		return -1;
+
+	while (!done) {
+		len = recv(nfnl_sk, resp_buf, BUFFER_SIZE, 0);
+		if (len < 0)
+			return -1;
+
+		for (nlh = (struct nlmsghdr *)resp_buf; NLMSG_OK(nlh, len);
+		     nlh = NLMSG_NEXT(nlh, len)) {
+			struct nlattr *exprs, *elem, *name, *data, *nla;
+			int exprs_len, elem_len;
+
+			if (nlh->nlmsg_type == NLMSG_DONE) {
+				done = 1;
+				break;
+			}
+			if (found)
+				continue;
+
+			exprs = msg_attr(nlh, NFTA_RULE_EXPRESSIONS);
+			if (exprs == NULL)
+				continue;
+
+			elem = attr_data(exprs);
+			exprs_len = exprs->nla_len - NLA_HDRLEN;
+			while (exprs_len >= NLA_HDRLEN) {
+				name = nested_attr(elem, NFTA_EXPR_NAME);
+				data = nested_attr(elem, NFTA_EXPR_DATA);
+				if (name != NULL && data != NULL &&
+				    strcmp(attr_data(name), "counter") == 0) {
+					nla = nested_attr(data,
+							  NFTA_COUNTER_PACKETS);
+					if (nla != NULL) {
+						*packets = attr_be64(nla);
+						found = 1;
+						break;
+					}
+				}
+				elem_len = NLA_ALIGN(elem->nla_len);
+				exprs_len -= elem_len;
+				elem = (struct nlattr *)((char *)elem +
+							  elem_len);
+			}
+		}
+	}
+
+	if (!found) {
+		errno = ENOENT;
+		return -1;
+	}
+	return 0;
+}
+
+static int udp_socket(int port)
+{
+	struct sockaddr_in addr = {
+		.sin_family = AF_INET,
+		.sin_port = htons(port),
+		.sin_addr = { htonl(INADDR_LOOPBACK) },
+	};
+	int sk;
+
+	sk = socket(AF_INET, SOCK_DGRAM | SOCK_NONBLOCK, 0);
+	if (sk < 0)
+		return -1;
+	if (bind(sk, (struct sockaddr *)&addr, sizeof(addr)) < 0) {
+		close(sk);
+		return -1;
+	}
+	return sk;
+}
+
+static int udp_send(int sk, int port)
+{
+	struct sockaddr_in addr = {
+		.sin_family = AF_INET,
+		.sin_port = htons(port),
+		.sin_addr = { htonl(INADDR_LOOPBACK) },
+	};
+
+	return sendto(sk, "hello", 5, 0, (struct sockaddr *)&addr,
+		      sizeof(addr));
+}
+
+FN_TEST(filter)
+{
+	struct nlmsghdr *nlh;
+	struct nlattr *exprs;
+	uint64_t packets;
+	char buf[16];
+	int sk_send, sk_recv;
+
+	nlh = rule_begin("input", &exprs);
+	expr_l4proto(nlh, IPPROTO_UDP);
+	expr_dport(nlh, DROP_PORT);
+	expr_counter(nlh);
+	expr_verdict(nlh, NF_DROP, NULL);
+	TEST_SUCC(rule_end(nlh, exprs));
+
+	TEST_RES(get_counter("input", &packets), packets == 0);
+
+	sk_send = TEST_SUCC(udp_socket(0));
+	sk_recv = TEST_SUCC(udp_socket(DROP_PORT));
+
+	TEST_RES(udp_send(sk_send, DROP_PORT), _ret == 5);
+	usleep(100 * 1000);
+	TEST_ERRNO(recv(sk_recv, buf, sizeof(buf), 0), EAGAIN);
+
+	TEST_RES(get_counter("input", &packets), packets == 1);
+
+	// Packets to other ports are not affected.
+	TEST_SUCC(close(sk_recv));
+	sk_recv = TEST_SUCC(udp_socket(DROP_PORT + 100));
+	TEST_RES(udp_send(sk_send, DROP_PORT + 100), _ret == 5);
+	usleep(100 * 1000);
+	TEST_RES(recv(sk_recv, buf, sizeof(buf), 0), _ret == 5);
+
+	TEST_RES(get_counter("input", &packets), packets == 1);
+
+	TEST_SUCC(close(sk_recv));
+	TEST_SUCC(close(sk_send));
+}
+END_TEST()
+
+FN_TEST(dnat)
+{
+	struct nlmsghdr *nlh;
+	struct nlattr *exprs;
+	struct sockaddr_in addr;
+	socklen_t addrlen = sizeof(addr);
+	uint16_t port = htons(DNAT_TO_PORT);
+	char buf[16];
+	int sk_send, sk_from, sk_to;
+
+	nlh = rule_begin("nat_output", &exprs);
+	expr_l4proto(nlh, IPPROTO_UDP);
+	expr_dport(nlh, DNAT_FROM_PORT);
+	expr_immediate(nlh, NFT_REG_1, "\x7f\x00\x00\x01", 4);
+	expr_immediate(nlh, NFT_REG_2, &port, sizeof(port));
+	expr_dnat(nlh, NFT_REG_1, NFT_REG_2);
+	TEST_SUCC(rule_end(nlh, exprs));
+
+	sk_send = TEST_SUCC(udp_socket(0));
+	sk_from = TEST_SUCC(udp_socket(DNAT_FROM_PORT));
+	sk_to = TEST_SUCC(udp_socket(DNAT_TO_PORT));
+
+	TEST_RES(udp_send(sk_send, DNAT_FROM_PORT), _ret == 5);
+	usleep(100 * 1000);
+	TEST_ERRNO(recv(sk_from, buf, sizeof(buf), 0), EAGAIN);
+	TEST_RES(recvfrom(sk_to, buf, sizeof(buf), 0, (struct sockaddr *)&addr,
+			  &addrlen),
+		 _ret == 5 && addr.sin_addr.s_addr == htonl(INADDR_LOOPBACK));
+
+	TEST_SUCC(close(sk_to));
+	TEST_SUCC(close(sk_from));
+	TEST_SUCC(close(sk_send));
+}
+END_TEST()
+
+FN_TEST(delete)
+{
+	struct nlmsghdr *nlh;
+
+	// Chains that contain rules cannot be deleted non-recursively.
+	nlh = cmd_begin(NFT_MSG_DELCHAIN, NLM_F_NONREC, NFPROTO_IPV4);
+	attr_put_str(nlh, NFTA_CHAIN_TABLE, TABLE_NAME);
+	attr_put_str(nlh, NFTA_CHAIN_NAME, "input");
+	TEST_ERRNO(cmd_end(), EBUSY);
+
+	TEST_SUCC(del_chain("regular"));
+	TEST_ERRNO(del_chain("regular"), ENOENT);
+
+	TEST_SUCC(del_table(NFT_MSG_DELTABLE, TABLE_NAME));
+	TEST_ERRNO(del_table(NFT_MSG_DELTABLE, TABLE_NAME), ENOENT);
+}
+END_TEST()
@@ -0,0 +1,454 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <poll.h>
+#include <stddef.h>
+#include <unistd.h>
+#include <net/if.h>
+#include <sys/socket.h>
+#include <sys/wait.h>
+#include <arpa/inet.h>
+#include <linux/filter.h>
+#include <linux/if_ether.h>
+#include <linux/if_packet.h>
+
+#include "../common/test.h"
+
+#define ETHER_NAME "eth0"
+
+// An EtherType reserved for local experiments.
+#define TEST_PROTO 0x88b5
+#define TEST_PAYLOAD "packet socket test"
+
+static int eth0_index;
+static unsigned char eth0_addr[ETH_ALEN];
+
+struct test_frame {
+	struct ethhdr hdr;
+	char payload[sizeof(TEST_PAYLOAD)];
+} __attribute__((packed));
+
+static struct sockaddr_ll eth0_sll(uint16_t proto)
+{
+	struct sockaddr_ll sll;
+
+	memset(&sll, 0, sizeof(sll));
+	sll.sll_family = AF_PACKET;
+	sll.sll_protocol = htons(proto);
+	sll.sll_ifindex = eth0_index;
+
+	return sll;
+}
+
+static int new_bound_socket(int type, uint16_t proto)
+{
+	struct sockaddr_ll sll = eth0_sll(proto);
+	int sk;
+
+	sk = CHECK(socket(AF_PACKET, type | SOCK_NONBLOCK, htons(proto)));
+	CHECK(bind(sk, (struct sockaddr *)&sll, sizeof(sll)));
+
+	return sk;
+}
+
+static void fill_frame(struct test_frame *frame)
+{
+	memset(frame->hdr.h_dest, 0xff, ETH_ALEN);
+	memcpy(frame->hdr.h_source, eth0_addr, ETH_ALEN);
+	frame->hdr.h_proto = htons(TEST_PROTO);
+	memcpy(frame->payload, TEST_PAYLOAD, sizeof(TEST_PAYLOAD));
+}
+
+// Receives frames until one that ends with the test payload arrives.
+static ssize_t recv_test_frame(int sk, char *buf, size_t len, int flags,
+			       struct sockaddr_ll *addr)
+{
+	struct pollfd pfd = { .fd = sk, .events = POLLIN };
+	socklen_t addrlen = sizeof(*addr);
+	ssize_t ret;
+
+	for (;;) {
+		if (poll(&pfd, 1, 1000) <= 0) {
+			errno = ETIMEDOUT;
+			return -1;
+		}
+
+		ret = recvfrom(sk, buf, len, flags, (struct sockaddr *)addr,
+			       &addrlen);
+		if (ret < 0)
+			return ret;
+
+		if ((size_t)ret >= sizeof(TEST_PAYLOAD) &&
+		    memcmp(buf + ret - sizeof(TEST_PAYLOAD), TEST_PAYLOAD,
+			   sizeof(TEST_PAYLOAD)) == 0)
+			return ret;
+	}
+}
+
+// Returns whether a frame that ends with the test payload has been received.
+static int has_test_frame(int sk)
+{
+	char buf[2048];
+	ssize_t ret;
+	int found = 0;
+
+	while ((ret = recv(sk, buf, sizeof(buf), MSG_DONTWAIT)) >= 0) {
+		if ((size_t)ret >= sizeof(TEST_PAYLOAD) &&
+		    memcmp(buf + ret - sizeof(TEST_PAYLOAD), TEST_PAYLOAD,
+			   sizeof(TEST_PAYLOAD)) == 0)
+			found = 1;
+	}
+	if (errno != EAGAIN)
+		return -1;
+
+	errno = 0;
+	return found;
+}
+
+FN_SETUP(eth0)
+{
+	struct sockaddr_ll sll;
+	socklen_t addrlen = sizeof(sll);
+	int sk;
+
+	eth0_index = CHECK(if_nametoindex(ETHER_NAME));
+
+	sk = new_bound_socket(SOCK_RAW, ETH_P_ALL);
+	CHECK_WITH(getsockname(sk, (struct sockaddr *)&sll, &addrlen),
+		   sll.sll_halen == ETH_ALEN);
+	memcpy(eth0_addr, sll.sll_addr, ETH_ALEN);
+	CHECK(close(sk));
+}
+END_SETUP()
+
+FN_TEST(create_errors)
+{
+	TEST_ERRNO(socket(AF_PACKET, SOCK_STREAM, htons(ETH_P_ALL)),
+		   ESOCKTNOSUPPORT);
+	TEST_ERRNO(socket(AF_PACKET, SOCK_SEQPACKET, htons(ETH_P_ALL)),
+		   ESOCKTNOSUPPORT);
+}
+END_TEST()
+
+FN_TEST(bind_and_getsockname)
+{
+	struct sockaddr_ll sll;
+	socklen_t addrlen = sizeof(sll);
+	int sk;
+
+	sk = TEST_SUCC(socket(AF_PACKET, SOCK_RAW, htons(ETH_P_ALL)));
+
+	TEST_RES(getsockname(sk, (struct sockaddr *)&sll, &addrlen),
+		 sll.sll_family == AF_PACKET &&
+			 sll.sll_protocol == htons(ETH_P_ALL) &&
+			 sll.sll_ifindex == 0 && sll.sll_halen == 0);
+
+	sll = eth0_sll(0);
+	TEST_ERRNO(bind(sk, (struct sockaddr *)&sll,
+			offsetof(struct sockaddr_ll, sll_addr)),
+		   EINVAL);
+	sll.sll_ifindex = 0x7fff;
+	TEST_ERRNO(bind(sk, (struct sockaddr *)&sll, sizeof(sll)), ENODEV);
+	sll.sll_ifindex = eth0_index;
+	sll.sll_family = AF_INET;
+	TEST_ERRNO(bind(sk, (struct sockaddr *)&sll, sizeof(sll)), EINVAL);
+
+	// A zero protocol keeps the current protocol.
+	sll.sll_family = AF_PACKET;
+	TEST_SUCC(bind(sk, (struct sockaddr *)&sll, sizeof(sll)));
+	addrlen = sizeof(sll);
+	TEST_RES(getsockname(sk, (struct sockaddr *)&sll, &addrlen),
+		 sll.sll_protocol == htons(ETH_P_ALL) &&
+			 sll.sll_ifindex == eth0_index &&
+			 sll.sll_hatype == 1 /* ARPHRD_ETHER */ &&
+			 sll.sll_halen == ETH_ALEN);
+
+	// Binding again is allowed.
+	sll = eth0_sll(TEST_PROTO);
+	TEST_SUCC(bind(sk, (struct sockaddr *)&sll, sizeof(sll)));
+	addrlen = sizeof(sll);
+	TEST_RES(getsockname(sk, (struct sockaddr *)&sll, &addrlen),
+		 sll.sll_protocol == htons(TEST_PROTO));
+
+	TEST_ERRNO(listen(sk, 1), EOPNOTSUPP);
+	TEST_ERRNO(connect(sk, (struct sockaddr *)&sll, sizeof(sll)),
+		   EOPNOTSUPP);
+
+	TEST_SUCC(close(sk));
+}
+END_TEST()
+
+FN_TEST(send_errors)
+{
+	struct test_frame frame;
+	struct sockaddr_ll sll = eth0_sll(TEST_PROTO);
+	char big[4096];
+	int sk_raw, sk_dgram;
+
+	fill_frame(&frame);
+	memset(big, 0, sizeof(big));
+
+	sk_raw = TEST_SUCC(socket(AF_PACKET, SOCK_RAW, htons(ETH_P_ALL)));
+	sk_dgram = new_bound_socket(SOCK_DGRAM, TEST_PROTO);
+
+	// The interface must be specified.
+	TEST_ERRNO(send(sk_raw, &frame, sizeof(frame), 0), ENXIO);
+	sll.sll_ifindex = 0x7fff;
+	TEST_ERRNO(sendto(sk_raw, &frame, sizeof(frame), 0,
+			  (struct sockaddr *)&sll, sizeof(sll)),
+		   ENXIO);
+	sll.sll_ifindex = eth0_index;
+
+	// The frame must contain an Ethernet header and fit in the MTU.
+	TEST_ERRNO(sendto(sk_raw, &frame, ETH_HLEN - 1, 0,
+			  (struct sockaddr *)&sll, sizeof(sll)),
+		   EINVAL);
+	TEST_ERRNO(sendto(sk_raw, big, sizeof(big), 0, (struct sockaddr *)&sll,
+			  sizeof(sll)),
+		   EMSGSIZE);
+	TEST_ERRNO(sendto(sk_dgram, big, sizeof(big), 0,
+			  (struct sockaddr *)&sll, sizeof(sll)),
+		   EMSGSIZE);
+
+	// `SOCK_DGRAM` sockets need the destination address.
+	TEST_ERRNO(send(sk_dgram, frame.payload, sizeof(frame.payload), 0),
+		   EINVAL);
+
+	TEST_SUCC(close(sk_raw));
+	TEST_SUCC(close(sk_dgram));
+}
+END_TEST()
+
+FN_TEST(send_and_capture)
+{
+	struct test_frame frame;
+	struct sockaddr_ll sll = eth0_sll(TEST_PROTO);
+	char buf[2048];
+	int sk_all, sk_sender, sk_proto;
+
+	fill_frame(&frame);
+
+	sk_all = new_bound_socket(SOCK_RAW, ETH_P_ALL);
+	sk_sender = new_bound_socket(SOCK_RAW, ETH_P_ALL);
+	sk_proto = new_bound_socket(SOCK_RAW, TEST_PROTO);
+
+	TEST_RES(send(sk_sender, &frame, sizeof(frame), 0),
+		 _ret == sizeof(frame));
+
+	// The frame is captured as an outgoing frame.
+	TEST_RES(recv_test_frame(sk_all, buf, sizeof(buf), 0, &sll),
+		 _ret == sizeof(frame) && memcmp(buf, &frame, _ret) == 0 &&
+			 sll.sll_family == AF_PACKET &&
+			 sll.sll_protocol == htons(TEST_PROTO) &&
+			 sll.sll_ifindex == eth0_index &&
+			 sll.sll_pkttype == PACKET_OUTGOING &&
+			 sll.sll_halen == ETH_ALEN &&
+			 memcmp(sll.sll_addr, eth0_addr, ETH_ALEN) == 0);
+
+	// The sender and the sockets of specific protocols do not see the
+	// outgoing frame.
+	TEST_RES(has_test_frame(sk_sender), _ret == 0);
+	TEST_RES(has_test_frame(sk_proto), _ret == 0);
+
+	TEST_SUCC(close(sk_all));
+	TEST_SUCC(close(sk_sender));
+	TEST_SUCC(close(sk_proto));
+}
+END_TEST()
+
+FN_TEST(dgram_send_and_capture)
+{
+	struct sockaddr_ll sll = eth0_sll(TEST_PROTO);
+	char buf[2048];
+	struct ethhdr *hdr = (struct ethhdr *)buf;
+	int sk_raw, sk_dgram, sk_sender;
+
+	sk_raw = new_bound_socket(SOCK_RAW, ETH_P_ALL);
+	sk_dgram = new_bound_socket(SOCK_DGRAM, ETH_P_ALL);
+	sk_sender = new_bound_socket(SOCK_DGRAM, TEST_PROTO);
+
+	// The Ethernet header is built from the destination address.
+	sll.sll_halen = ETH_ALEN;
+	memset(sll.sll_addr, 0xff, ETH_ALEN);
+	TEST_RES(sendto(sk_sender, TEST_PAYLOAD, sizeof(TEST_PAYLOAD), 0,
+			(struct sockaddr *)&sll, sizeof(sll)),
+		 _ret == sizeof(TEST_PAYLOAD));
+
+	TEST_RES(recv_test_frame(sk_raw, buf, sizeof(buf), 0, &sll),
+		 _ret == ETH_HLEN + sizeof(TEST_PAYLOAD) &&
+			 hdr->h_proto == htons(TEST_PROTO) &&
+			 memcmp(hdr->h_source, eth0_addr, ETH_ALEN) == 0 &&
+			 memcmp(hdr->h_dest, "\xff\xff\xff\xff\xff\xff",
+				ETH_ALEN) == 0);
+
+	// The Ethernet header is stripped for `SOCK_DGRAM` sockets.
+	TEST_RES(recv_test_frame(sk_dgram, buf, sizeof(buf), 0, &sll),
+		 _ret == sizeof(TEST_PAYLOAD) &&
+			 sll.sll_protocol == htons(TEST_PROTO) &&
+			 sll.sll_pkttype == PACKET_OUTGOING);
+
+	TEST_SUCC(close(sk_raw));
+	TEST_SUCC(close(sk_dgram));
+	TEST_SUCC(close(sk_sender));
+}
+END_TEST()
+
+FN_TEST(socket_filter)
+{
+	struct sock_filter accept_test_proto[] = {
+		BPF_STMT(BPF_LD | BPF_H | BPF_ABS, 12),
+		BPF_JUMP(BPF_JMP | BPF_JEQ | BPF_K, TEST_PROTO, 0, 1),
+		BPF_STMT(BPF_RET | BPF_K, 20),
+		BPF_STMT(BPF_RET | BPF_K, 0),
+	};
+	struct sock_filter no_ret[] = {
+		BPF_STMT(BPF_LD | BPF_H | BPF_ABS, 12),
+	};
+	struct sock_filter bad_jump[] = {
+		BPF_JUMP(BPF_JMP | BPF_JEQ | BPF_K, 0, 5, 0),
+		BPF_STMT(BPF_RET | BPF_K, 0),
+	};
+	struct sock_filter div_by_zero[] = {
+		BPF_STMT(BPF_ALU | BPF_DIV | BPF_K, 0),
+		BPF_STMT(BPF_RET | BPF_K, 0),
+	};
+	struct sock_filter uninit_mem[] = {
+		BPF_STMT(BPF_LD | BPF_MEM, 0),
+		BPF_STMT(BPF_RET | BPF_A, 0),
+	};
+	struct sock_filter bad_mem[] = {
+		BPF_STMT(BPF_ST, 16),
+		BPF_STMT(BPF_RET | BPF_K, 0),
+	};
+	struct sock_fprog prog;
+	struct test_frame frame;
+	char buf[2048];
+	struct sockaddr_ll sll;
+	int sk, sk_sender, val = 0;
+
+	sk = new_bound_socket(SOCK_RAW, ETH_P_ALL);
+	sk_sender = new_bound_socket(SOCK_RAW, ETH_P_ALL);
+
+#define TEST_INVALID_PROG(insns)                                          \
+	prog.len = sizeof(insns) / sizeof(insns[0]);                      \
+	prog.filter = insns;                                              \
+	TEST_ERRNO(setsockopt(sk, SOL_SOCKET, SO_ATTACH_FILTER, &prog, \
+			      sizeof(prog)),                              \
+		   EINVAL)
+
+	TEST_INVALID_PROG(no_ret);
+	TEST_INVALID_PROG(bad_jump);
+	TEST_INVALID_PROG(div_by_zero);
+	TEST_INVALID_PROG(uninit_mem);
+	TEST_INVALID_PROG(bad_mem);
+
+#undef TEST_INVALID_PROG
+
+	prog.len = 0;
+	TEST_ERRNO(setsockopt(sk, SOL_SOCKET, SO_ATTACH_FILTER, &prog,
+			      sizeof(prog)),
+		   EINVAL);
+	prog.len = sizeof(accept_test_proto) / sizeof(accept_test_proto[0]);
+	prog.filter = accept_test_proto;
+	TEST_ERRNO(setsockopt(sk, SOL_SOCKET, SO_ATTACH_FILTER, &prog,
+			      sizeof(prog) - 1),
+		   EINVAL);
+
+	TEST_ERRNO(setsockopt(sk, SOL_SOCKET, SO_DETACH_FILTER, &val,
+			      sizeof(val)),
+		   ENOENT);
+
+	// The filter drops other frames and truncates the test frames.
+	TEST_SUCC(setsockopt(sk, SOL_SOCKET, SO_ATTACH_FILTER, &prog,
+			     sizeof(prog)));
+	TEST_SUCC(has_test_frame(sk));
+
+	fill_frame(&frame);
+	TEST_RES(send(sk_sender, &frame, sizeof(frame), 0),
+		 _ret == sizeof(frame));
+	TEST_RES(recv(sk, buf, sizeof(buf), MSG_DONTWAIT),
+		 _ret == 20 && memcmp(buf, &frame, 20) == 0);
+	TEST_ERRNO(recv(sk, buf, sizeof(buf), MSG_DONTWAIT), EAGAIN);
+
+	// Without the filter, the whole frame is received.
+	TEST_SUCC(setsockopt(sk, SOL_SOCKET, SO_DETACH_FILTER, &val,
+			      sizeof(val)));
+	TEST_ERRNO(setsockopt(sk, SOL_SOCKET, SO_DETACH_FILTER, &val,
+			      sizeof(val)),
+		   ENOENT);
+	TEST_RES(send(sk_sender, &frame, sizeof(frame), 0),
+		 _ret == sizeof(frame));
+	TEST_RES(recv_test_frame(sk, buf, sizeof(buf), 0, &sll),
+		 _ret == sizeof(frame));
+
+	TEST_SUCC(close(sk));
+	TEST_SUCC(close(sk_sender));
+}
+END_TEST()
+
+FN_TEST(membership)
+{
+	struct packet_mreq mreq;
+	int sk;
+
+	sk = TEST_SUCC(socket(AF_PACKET, SOCK_RAW, htons(ETH_P_ALL)));
+
+	memset(&mreq, 0, sizeof(mreq));
+	mreq.mr_ifindex = eth0_index;
+	mreq.mr_type = PACKET_MR_PROMISC;
+
+	TEST_ERRNO(setsockopt(sk, SOL_PACKET, PACKET_ADD_MEMBERSHIP, &mreq,
+			      sizeof(mreq) - 1),
+		   EINVAL);
+	mreq.mr_ifindex = 0x7fff;
+	TEST_ERRNO(setsockopt(sk, SOL_PACKET, PACKET_ADD_MEMBERSHIP, &mreq,
+			      sizeof(mreq)),
+		   ENODEV);
+	mreq.mr_ifindex = eth0_index;
+	mreq.mr_alen = ETH_ALEN + 1;
+	TEST_ERRNO(setsockopt(sk, SOL_PACKET, PACKET_ADD_MEMBERSHIP, &mreq,
+			      sizeof(mreq)),
+		   EINVAL);
+	mreq.mr_alen = 0;
+
+	TEST_SUCC(setsockopt(sk, SOL_PACKET, PACKET_ADD_MEMBERSHIP, &mreq,
+			     sizeof(mreq)));
+	TEST_SUCC(setsockopt(sk, SOL_PACKET, PACKET_ADD_MEMBERSHIP, &mreq,
+			     sizeof(mreq)));
+	TEST_SUCC(setsockopt(sk, SOL_PACKET, PACKET_DROP_MEMBERSHIP, &mreq,
+			     sizeof(mreq)));
+
+	// Dropping a membership that does not exist succeeds.
+	mreq.mr_type = PACKET_MR_ALLMULTI;
+	TEST_SUCC(setsockopt(sk, SOL_PACKET, PACKET_DROP_MEMBERSHIP, &mreq,
+			     sizeof(mreq)));
+
+	// The remaining membership is dropped when the socket is closed.
+	TEST_SUCC(close(sk));
+}
+END_TEST()
+
+FN_TEST(need_cap_net_raw)
+{
+	int status;
+	pid_t pid;
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		// Dropping the root privileges clears the effective capabilities.
+		if (setuid(65534) < 0)
+			_exit(EXIT_FAILURE);
+		if (socket(AF_PACKET, SOCK_RAW, htons(ETH_P_ALL)) >= 0 ||
+		    errno != EPERM)
+			_exit(EXIT_FAILURE);
+		if (socket(AF_PACKET, SOCK_DGRAM, htons(ETH_P_ALL)) >= 0 ||
+		    errno != EPERM)
+			_exit(EXIT_FAILURE);
+		_exit(EXIT_SUCCESS);
+	}
+
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) && WEXITSTATUS(status) == 0);
+}
+END_TEST()
@@ -0,0 +1,173 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+#include <stdio.h>
+#include <string.h>
+#include <unistd.h>
+#include <netinet/in.h>
+#include <arpa/inet.h>
+#include "../common/test.h"
+
+#define RECEIVER_PORT 12347
+#define UNBOUND_PORT 12348
+
+#define MESSAGE "Hello from loopback"
+#define MESSAGE_LEN sizeof(MESSAGE)
+
+// The minimum size of an IPv4 packet carrying a UDP datagram of `MESSAGE_LEN` bytes
+#define MIN_PACKET_LEN (20 + 8 + MESSAGE_LEN)
+
+struct link_stats {
+	unsigned long long rx_bytes;
+	unsigned long long rx_packets;
+	unsigned long long tx_bytes;
+	unsigned long long tx_packets;
+};
+
+struct udp_stats {
+	long long in_datagrams;
+	long long no_ports;
+	long long out_datagrams;
+};
+
+static int read_file(const char *path, char *buf, size_t size)
+{
+	FILE *file;
+	size_t len;
+
+	file = fopen(path, "r");
+	if (file == NULL)
+		return -1;
+	len = fread(buf, 1, size - 1, file);
+	fclose(file);
+	buf[len] = '\0';
+
+	return 0;
+}
+
+static int read_lo_stats(struct link_stats *stats)
+{
+	char buf[4096];
+	unsigned long long values[16];
+	char *line;
+
+	if (read_file("/proc/net/dev", buf, sizeof(buf)) < 0)
+		return -1;
+
+	line = strstr(buf, "lo:");
+	if (line == NULL)
+		return -1;
+	if (sscanf(line + strlen("lo:"),
+		   "%llu %llu %llu %llu %llu %llu %llu %llu "
+		   "%llu %llu %llu %llu %llu %llu %llu %llu",
+		   &values[0], &values[1], &values[2], &values[3], &values[4],
+		   &values[5], &values[6], &values[7], &values[8], &values[9],
+		   &values[10], &values[11], &values[12], &values[13],
+		   &values[14], &values[15]) != 16)
+		return -1;
+
+	stats->rx_bytes = values[0];
+	stats->rx_packets = values[1];
+	stats->tx_bytes = values[8];
+	stats->tx_packets = values[9];
+
+	return 0;
+}
+
+static int read_udp_stats(struct udp_stats *stats)
+{
+	char buf[4096];
+	char *line;
+
+	if (read_file("/proc/net/snmp", buf, sizeof(buf)) < 0)
+		return -1;
+
+	// The first "Udp:" line contains the field names and the second one contains the values.
+	line = strstr(buf, "\nUdp:");
+	if (line == NULL)
+		return -1;
+	line = strstr(line + 1, "\nUdp:");
+	if (line == NULL)
+		return -1;
+	if (sscanf(line + strlen("\nUdp:"), "%lld %lld %*d %lld",
+		   &stats->in_datagrams, &stats->no_ports,
+		   &stats->out_datagrams) != 3)
+		return -1;
+
+	return 0;
+}
+
+static struct sockaddr_in loopback_addr(int port)
+{
+	struct sockaddr_in addr = {
+		.sin_family = AF_INET,
+		.sin_port = htons(port),
+		.sin_addr = { htonl(INADDR_LOOPBACK) },
+	};
+
+	return addr;
+}
+
+static int sender;
+static int receiver;
+
+FN_SETUP(create_sockets)
+{
+	struct sockaddr_in addr = loopback_addr(RECEIVER_PORT);
+
+	sender = CHECK(socket(AF_INET, SOCK_DGRAM, 0));
+	receiver = CHECK(socket(AF_INET, SOCK_DGRAM, 0));
+	CHECK(bind(receiver, (struct sockaddr *)&addr, sizeof(addr)));
+}
+END_SETUP()
+
+FN_TEST(loopback_datagram)
+{
+	struct sockaddr_in addr = loopback_addr(RECEIVER_PORT);
+	struct link_stats link_before, link_after;
+	struct udp_stats udp_before, udp_after;
+	char buf[64];
+
+	TEST_SUCC(read_lo_stats(&link_before));
+	TEST_SUCC(read_udp_stats(&udp_before));
+
+	TEST_RES(sendto(sender, MESSAGE, MESSAGE_LEN, 0, (struct sockaddr *)&addr,
+			sizeof(addr)),
+		 _ret == MESSAGE_LEN);
+	TEST_RES(recv(receiver, buf, sizeof(buf), 0), _ret == MESSAGE_LEN);
+
+	TEST_RES(read_lo_stats(&link_after),
+		 link_after.tx_packets > link_before.tx_packets &&
+			 link_after.rx_packets > link_before.rx_packets &&
+			 link_after.tx_bytes >=
+				 link_before.tx_bytes + MIN_PACKET_LEN &&
+			 link_after.rx_bytes >=
+				 link_before.rx_bytes + MIN_PACKET_LEN);
+	TEST_RES(read_udp_stats(&udp_after),
+		 udp_after.out_datagrams > udp_before.out_datagrams &&
+			 udp_after.in_datagrams > udp_before.in_datagrams);
+}
+END_TEST()
+
+FN_TEST(loopback_datagram_no_port)
+{
+	struct sockaddr_in addr = loopback_addr(UNBOUND_PORT);
+	struct udp_stats udp_before, udp_after;
+
+	TEST_SUCC(read_udp_stats(&udp_before));
+
+	TEST_RES(sendto(sender, MESSAGE, MESSAGE_LEN, 0, (struct sockaddr *)&addr,
+			sizeof(addr)),
+		 _ret == MESSAGE_LEN);
+
+	TEST_RES(read_udp_stats(&udp_after),
+		 udp_after.no_ports > udp_before.no_ports);
+}
+END_TEST()
+
+FN_SETUP(close_sockets)
+{
+	CHECK(close(sender));
+	CHECK(close(receiver));
+}
+END_SETUP()
@@ -0,0 +1,419 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <poll.h>
+#include <unistd.h>
+#include <sys/socket.h>
+#include <sys/wait.h>
+#include <netinet/in.h>
+#include <netinet/ip.h>
+#include <netinet/ip_icmp.h>
+#include <netinet/icmp6.h>
+#include <arpa/inet.h>
+
+#include "../common/test.h"
+
+#define PING_GROUP_RANGE_PATH "/proc/sys/net/ipv4/ping_group_range"
+
+#define ECHO_ID 0x4242
+#define PING_ID 0x1234
+#define ECHO_PAYLOAD "raw socket test"
+
+static struct sockaddr_in lo_addr;
+static struct sockaddr_in6 lo6_addr;
+
+static char old_ping_group_range[64];
+
+FN_SETUP(addrs)
+{
+	lo_addr.sin_family = AF_INET;
+	lo_addr.sin_addr.s_addr = htonl(INADDR_LOOPBACK);
+
+	lo6_addr.sin6_family = AF_INET6;
+	lo6_addr.sin6_addr = in6addr_loopback;
+}
+END_SETUP()
+
+static uint16_t inet_checksum(const void *data, size_t len)
+{
+	const uint8_t *bytes = data;
+	uint32_t sum = 0;
+
+	for (size_t i = 0; i + 1 < len; i += 2)
+		sum += (bytes[i] << 8) | bytes[i + 1];
+	if (len % 2)
+		sum += bytes[len - 1] << 8;
+
+	while (sum >> 16)
+		sum = (sum & 0xffff) + (sum >> 16);
+
+	return htons(~sum);
+}
+
+struct echo_msg {
+	struct icmphdr hdr;
+	char payload[sizeof(ECHO_PAYLOAD)];
+};
+
+static void fill_echo(struct echo_msg *msg, uint8_t type, uint16_t id,
+		      uint16_t seq)
+{
+	memset(msg, 0, sizeof(*msg));
+	msg->hdr.type = type;
+	msg->hdr.un.echo.id = htons(id);
+	msg->hdr.un.echo.sequence = htons(seq);
+	memcpy(msg->payload, ECHO_PAYLOAD, sizeof(ECHO_PAYLOAD));
+	msg->hdr.checksum = inet_checksum(msg, sizeof(*msg));
+}
+
+// Receives ICMP or ICMPv6 echo messages until one with the specified type,
+// identifier, and sequence number arrives. The message starts at `hdr_len`.
+static ssize_t recv_echo(int sk, char *buf, size_t len, size_t hdr_len,
+			 uint8_t type, uint16_t id, uint16_t seq,
+			 struct sockaddr *addr, socklen_t *addrlen)
+{
+	struct pollfd pfd = { .fd = sk, .events = POLLIN };
+	struct icmphdr *hdr = (struct icmphdr *)(buf + hdr_len);
+	ssize_t ret;
+
+	for (;;) {
+		if (poll(&pfd, 1, 1000) <= 0) {
+			errno = ETIMEDOUT;
+			return -1;
+		}
+
+		ret = recvfrom(sk, buf, len, 0, addr, addrlen);
+		if (ret < 0)
+			return ret;
+
+		if ((size_t)ret >= hdr_len + sizeof(*hdr) &&
+		    hdr->type == type && hdr->un.echo.id == htons(id) &&
+		    hdr->un.echo.sequence == htons(seq))
+			return ret;
+	}
+}
+
+FN_TEST(create_errors)
+{
+	TEST_ERRNO(socket(AF_INET, SOCK_RAW, 0), EPROTONOSUPPORT);
+	TEST_ERRNO(socket(AF_INET, SOCK_RAW, 300), EINVAL);
+	TEST_ERRNO(socket(AF_INET6, SOCK_RAW, 0), EPROTONOSUPPORT);
+}
+END_TEST()
+
+FN_TEST(getsockname)
+{
+	struct sockaddr_in saddr;
+	socklen_t addrlen = sizeof(saddr);
+	int sk;
+
+	sk = TEST_SUCC(socket(AF_INET, SOCK_RAW, IPPROTO_ICMP));
+
+	// The protocol number is reported as the port.
+	TEST_RES(getsockname(sk, (struct sockaddr *)&saddr, &addrlen),
+		 addrlen == sizeof(saddr) && saddr.sin_family == AF_INET &&
+			 saddr.sin_port == htons(IPPROTO_ICMP) &&
+			 saddr.sin_addr.s_addr == htonl(INADDR_ANY));
+
+	// The port is ignored when binding.
+	saddr = lo_addr;
+	saddr.sin_port = htons(1234);
+	TEST_SUCC(bind(sk, (struct sockaddr *)&saddr, sizeof(saddr)));
+	TEST_RES(getsockname(sk, (struct sockaddr *)&saddr, &addrlen),
+		 addrlen == sizeof(saddr) &&
+			 saddr.sin_port == htons(IPPROTO_ICMP) &&
+			 saddr.sin_addr.s_addr == htonl(INADDR_LOOPBACK));
+
+	TEST_SUCC(close(sk));
+}
+END_TEST()
+
+FN_TEST(hdrincl_option)
+{
+	int sk_icmp, sk_raw, sk_udp;
+	int val;
+	socklen_t len = sizeof(val);
+
+	sk_icmp = TEST_SUCC(socket(AF_INET, SOCK_RAW, IPPROTO_ICMP));
+	sk_raw = TEST_SUCC(socket(AF_INET, SOCK_RAW, IPPROTO_RAW));
+	sk_udp = TEST_SUCC(socket(AF_INET, SOCK_DGRAM, 0));
+
+	TEST_RES(getsockopt(sk_icmp, SOL_IP, IP_HDRINCL, &val, &len),
+		 len == sizeof(val) && val == 0);
+	TEST_RES(getsockopt(sk_raw, SOL_IP, IP_HDRINCL, &val, &len),
+		 len == sizeof(val) && val == 1);
+
+	val = 1;
+	TEST_SUCC(setsockopt(sk_icmp, SOL_IP, IP_HDRINCL, &val, sizeof(val)));
+	TEST_RES(getsockopt(sk_icmp, SOL_IP, IP_HDRINCL, &val, &len),
+		 len == sizeof(val) && val == 1);
+	TEST_ERRNO(setsockopt(sk_udp, SOL_IP, IP_HDRINCL, &val, sizeof(val)),
+		   ENOPROTOOPT);
+
+	TEST_SUCC(close(sk_icmp));
+	TEST_SUCC(close(sk_raw));
+	TEST_SUCC(close(sk_udp));
+}
+END_TEST()
+
+FN_TEST(raw_icmp_echo)
+{
+	struct echo_msg msg;
+	char buf[256];
+	struct iphdr *iph = (struct iphdr *)buf;
+	struct sockaddr_in saddr;
+	socklen_t addrlen = sizeof(saddr);
+	int sk;
+
+	sk = TEST_SUCC(socket(AF_INET, SOCK_RAW | SOCK_NONBLOCK, IPPROTO_ICMP));
+
+	fill_echo(&msg, ICMP_ECHO, ECHO_ID, 1);
+	TEST_RES(sendto(sk, &msg, sizeof(msg), 0, (struct sockaddr *)&lo_addr,
+			sizeof(lo_addr)),
+		 _ret == sizeof(msg));
+
+	// The raw socket sees both the echo request and the echo reply, including
+	// the IP headers.
+	TEST_RES(recv_echo(sk, buf, sizeof(buf), sizeof(*iph), ICMP_ECHO,
+			   ECHO_ID, 1, (struct sockaddr *)&saddr, &addrlen),
+		 _ret == sizeof(*iph) + sizeof(msg) && iph->version == 4 &&
+			 iph->ihl == 5 && iph->protocol == IPPROTO_ICMP &&
+			 iph->saddr == htonl(INADDR_LOOPBACK) &&
+			 iph->daddr == htonl(INADDR_LOOPBACK) &&
+			 saddr.sin_family == AF_INET && saddr.sin_port == 0 &&
+			 saddr.sin_addr.s_addr == htonl(INADDR_LOOPBACK));
+	TEST_RES(recv_echo(sk, buf, sizeof(buf), sizeof(*iph), ICMP_ECHOREPLY,
+			   ECHO_ID, 1, NULL, NULL),
+		 _ret == sizeof(*iph) + sizeof(msg) &&
+			 memcmp(buf + sizeof(*iph) + sizeof(msg.hdr),
+				ECHO_PAYLOAD, sizeof(ECHO_PAYLOAD)) == 0);
+
+	TEST_SUCC(close(sk));
+}
+END_TEST()
+
+FN_TEST(raw_hdrincl_send)
+{
+	struct {
+		struct iphdr iph;
+		struct echo_msg msg;
+	} packet;
+	char buf[256];
+	int sk_raw, sk_icmp;
+
+	sk_raw = TEST_SUCC(socket(AF_INET, SOCK_RAW, IPPROTO_RAW));
+	sk_icmp = TEST_SUCC(socket(AF_INET, SOCK_RAW | SOCK_NONBLOCK,
+				   IPPROTO_ICMP));
+	TEST_SUCC(bind(sk_icmp, (struct sockaddr *)&lo_addr, sizeof(lo_addr)));
+
+	// The total length and the checksum are filled in by the kernel.
+	memset(&packet, 0, sizeof(packet));
+	packet.iph.version = 4;
+	packet.iph.ihl = 5;
+	packet.iph.ttl = 64;
+	packet.iph.protocol = IPPROTO_ICMP;
+	packet.iph.saddr = htonl(INADDR_LOOPBACK);
+	packet.iph.daddr = htonl(INADDR_LOOPBACK);
+	fill_echo(&packet.msg, ICMP_ECHO, ECHO_ID, 2);
+
+	TEST_RES(sendto(sk_raw, &packet, sizeof(packet), 0,
+			(struct sockaddr *)&lo_addr, sizeof(lo_addr)),
+		 _ret == sizeof(packet));
+	TEST_RES(recv_echo(sk_icmp, buf, sizeof(buf), sizeof(struct iphdr),
+			   ICMP_ECHOREPLY, ECHO_ID, 2, NULL, NULL),
+		 _ret == sizeof(packet));
+
+	// An `IPPROTO_RAW` socket receives nothing.
+	TEST_ERRNO(recv(sk_raw, buf, sizeof(buf), MSG_DONTWAIT), EAGAIN);
+
+	// The IP header must fit in the packet.
+	packet.iph.ihl = 15;
+	TEST_ERRNO(sendto(sk_raw, &packet, sizeof(packet), 0,
+			  (struct sockaddr *)&lo_addr, sizeof(lo_addr)),
+		   EINVAL);
+
+	TEST_SUCC(close(sk_raw));
+	TEST_SUCC(close(sk_icmp));
+}
+END_TEST()
+
+FN_TEST(raw_icmpv6_echo)
+{
+	struct echo_msg msg;
+	char buf[256];
+	struct sockaddr_in6 saddr;
+	socklen_t addrlen = sizeof(saddr);
+	int sk;
+
+	sk = TEST_SUCC(
+		socket(AF_INET6, SOCK_RAW | SOCK_NONBLOCK, IPPROTO_ICMPV6));
+
+	// The ICMPv6 checksum is computed by the kernel.
+	fill_echo(&msg, ICMP6_ECHO_REQUEST, ECHO_ID, 3);
+	msg.hdr.checksum = 0;
+	TEST_RES(sendto(sk, &msg, sizeof(msg), 0, (struct sockaddr *)&lo6_addr,
+			sizeof(lo6_addr)),
+		 _ret == sizeof(msg));
+
+	// The IPv6 header is not included.
+	TEST_RES(recv_echo(sk, buf, sizeof(buf), 0, ICMP6_ECHO_REPLY, ECHO_ID,
+			   3, (struct sockaddr *)&saddr, &addrlen),
+		 _ret == sizeof(msg) && saddr.sin6_family == AF_INET6 &&
+			 saddr.sin6_port == 0 &&
+			 memcmp(&saddr.sin6_addr, &in6addr_loopback,
+				sizeof(in6addr_loopback)) == 0);
+
+	TEST_SUCC(close(sk));
+}
+END_TEST()
+
+static void write_ping_group_range(const char *range)
+{
+	int fd = CHECK(open(PING_GROUP_RANGE_PATH, O_WRONLY | O_TRUNC));
+	CHECK_WITH(write(fd, range, strlen(range)),
+		   _ret == (ssize_t)strlen(range));
+	CHECK(close(fd));
+}
+
+FN_SETUP(ping_group_range)
+{
+	int fd = CHECK(open(PING_GROUP_RANGE_PATH, O_RDONLY));
+	CHECK(read(fd, old_ping_group_range, sizeof(old_ping_group_range) - 1));
+	CHECK(close(fd));
+}
+END_SETUP()
+
+FN_TEST(ping_group_range)
+{
+	char buf[64];
+	int fd;
+
+	write_ping_group_range("1 0");
+	TEST_ERRNO(socket(AF_INET, SOCK_DGRAM, IPPROTO_ICMP), EACCES);
+	TEST_ERRNO(socket(AF_INET6, SOCK_DGRAM, IPPROTO_ICMPV6), EACCES);
+
+	write_ping_group_range("0 2147483647");
+	fd = TEST_SUCC(open(PING_GROUP_RANGE_PATH, O_RDWR));
+	TEST_RES(read(fd, buf, sizeof(buf)),
+		 _ret == 13 && memcmp(buf, "0\t2147483647\n", 13) == 0);
+	TEST_ERRNO(pwrite(fd, "abc", 3, 0), EINVAL);
+	TEST_ERRNO(pwrite(fd, "-1 5", 4, 0), EINVAL);
+	TEST_SUCC(close(fd));
+
+	// A reversed range is reset to the empty range.
+	write_ping_group_range("8 6");
+	fd = TEST_SUCC(open(PING_GROUP_RANGE_PATH, O_RDONLY));
+	TEST_RES(read(fd, buf, sizeof(buf)),
+		 _ret == 4 && memcmp(buf, "1\t0\n", 4) == 0);
+	TEST_SUCC(close(fd));
+
+	write_ping_group_range("0 2147483647");
+}
+END_TEST()
+
+FN_TEST(ping_socket)
+{
+	struct echo_msg msg;
+	char buf[256];
+	struct sockaddr_in saddr;
+	socklen_t addrlen = sizeof(saddr);
+	int sk, val = 1;
+
+	sk = TEST_SUCC(socket(AF_INET, SOCK_DGRAM | SOCK_NONBLOCK, IPPROTO_ICMP));
+
+	saddr = lo_addr;
+	saddr.sin_port = htons(PING_ID);
+	TEST_SUCC(bind(sk, (struct sockaddr *)&saddr, sizeof(saddr)));
+	TEST_RES(getsockname(sk, (struct sockaddr *)&saddr, &addrlen),
+		 addrlen == sizeof(saddr) && saddr.sin_port == htons(PING_ID));
+
+	TEST_ERRNO(setsockopt(sk, SOL_IP, IP_HDRINCL, &val, sizeof(val)),
+		   ENOPROTOOPT);
+
+	// Only echo requests can be sent.
+	fill_echo(&msg, ICMP_ECHOREPLY, ECHO_ID, 4);
+	TEST_ERRNO(sendto(sk, &msg, sizeof(msg), 0, (struct sockaddr *)&lo_addr,
+			  sizeof(lo_addr)),
+		   EINVAL);
+	TEST_ERRNO(sendto(sk, &msg, 4, 0, (struct sockaddr *)&lo_addr,
+			  sizeof(lo_addr)),
+		   EINVAL);
+
+	// The identifier and the checksum are filled in by the kernel.
+	fill_echo(&msg, ICMP_ECHO, ECHO_ID, 4);
+	msg.hdr.checksum = 0;
+	TEST_RES(sendto(sk, &msg, sizeof(msg), 0, (struct sockaddr *)&lo_addr,
+			sizeof(lo_addr)),
+		 _ret == sizeof(msg));
+
+	// Only the echo reply is received, without the IP header.
+	TEST_RES(recv_echo(sk, buf, sizeof(buf), 0, ICMP_ECHOREPLY, PING_ID, 4,
+			   (struct sockaddr *)&saddr, &addrlen),
+		 _ret == sizeof(msg) && saddr.sin_port == 0 &&
+			 saddr.sin_addr.s_addr == htonl(INADDR_LOOPBACK));
+	TEST_ERRNO(recv(sk, buf, sizeof(buf), 0), EAGAIN);
+
+	TEST_SUCC(close(sk));
+}
+END_TEST()
+
+FN_TEST(ping6_socket)
+{
+	struct echo_msg msg;
+	char buf[256];
+	struct sockaddr_in6 saddr;
+	socklen_t addrlen = sizeof(saddr);
+	int sk;
+
+	sk = TEST_SUCC(
+		socket(AF_INET6, SOCK_DGRAM | SOCK_NONBLOCK, IPPROTO_ICMPV6));
+
+	saddr = lo6_addr;
+	saddr.sin6_port = htons(PING_ID);
+	TEST_SUCC(bind(sk, (struct sockaddr *)&saddr, sizeof(saddr)));
+
+	fill_echo(&msg, ICMP6_ECHO_REQUEST, ECHO_ID, 5);
+	msg.hdr.checksum = 0;
+	TEST_RES(sendto(sk, &msg, sizeof(msg), 0, (struct sockaddr *)&lo6_addr,
+			sizeof(lo6_addr)),
+		 _ret == sizeof(msg));
+
+	TEST_RES(recv_echo(sk, buf, sizeof(buf), 0, ICMP6_ECHO_REPLY, PING_ID,
+			   5, (struct sockaddr *)&saddr, &addrlen),
+		 _ret == sizeof(msg) && saddr.sin6_port == 0);
+
+	TEST_SUCC(close(sk));
+}
+END_TEST()
+
+FN_SETUP(restore_ping_group_range)
+{
+	write_ping_group_range(old_ping_group_range);
+}
+END_SETUP()
+
+FN_TEST(need_cap_net_raw)
+{
+	int status;
+	pid_t pid;
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		// Dropping the root privileges clears the effective capabilities.
+		if (setuid(65534) < 0)
+			_exit(EXIT_FAILURE);
+		if (socket(AF_INET, SOCK_RAW, IPPROTO_ICMP) >= 0 ||
+		    errno != EPERM)
+			_exit(EXIT_FAILURE);
+		if (socket(AF_INET6, SOCK_RAW, IPPROTO_ICMPV6) >= 0 ||
+		    errno != EPERM)
+			_exit(EXIT_FAILURE);
+		_exit(EXIT_SUCCESS);
+	}
+
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) && WEXITSTATUS(status) == 0);
+}
+END_TEST()
@@ -0,0 +1,494 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <sched.h>
+#include <unistd.h>
+#include <net/if.h>
+#include <sys/ioctl.h>
+#include <sys/socket.h>
+#include <arpa/inet.h>
+#include <linux/if_tun.h>
+#include <linux/netlink.h>
+#include <linux/rtnetlink.h>
+
+#include "../common/test.h"
+
+#define TUN_PATH "/dev/net/tun"
+#define TUN_NAME "rtnltest0"
+#define TUN_NEW_NAME "rtnltest1"
+
+#define BUFFER_SIZE 8192
+
+struct nl_req {
+	struct nlmsghdr hdr;
+	char buf[256];
+};
+
+static int tun_fd;
+static int tun_index;
+static int rtnl_sk;
+static int addr_group_sk;
+static int route_group_sk;
+
+static char buffer[BUFFER_SIZE];
+
+static void *req_init(struct nl_req *req, int type, int flags, size_t body_len)
+{
+	memset(req, 0, sizeof(*req));
+	req->hdr.nlmsg_len = NLMSG_LENGTH(body_len);
+	req->hdr.nlmsg_type = type;
+	req->hdr.nlmsg_flags = NLM_F_REQUEST | NLM_F_ACK | flags;
+
+	return NLMSG_DATA(&req->hdr);
+}
+
+static void req_add_attr(struct nl_req *req, int type, const void *data,
+			 size_t len)
+{
+	struct rtattr *rta =
+		(struct rtattr *)((char *)req + NLMSG_ALIGN(req->hdr.nlmsg_len));
+
+	rta->rta_type = type;
+	rta->rta_len = RTA_LENGTH(len);
+	memcpy(RTA_DATA(rta), data, len);
+	req->hdr.nlmsg_len = NLMSG_ALIGN(req->hdr.nlmsg_len) + RTA_SPACE(len);
+}
+
+// Sends the request and waits for the acknowledgment. Returns -1 and sets
+// `errno` if the request fails.
+static int do_request(struct nl_req *req)
+{
+	struct {
+		struct nlmsghdr hdr;
+		struct nlmsgerr err;
+	} resp;
+
+	if (send(rtnl_sk, req, req->hdr.nlmsg_len, 0) < 0)
+		return -1;
+	if (recv(rtnl_sk, &resp, sizeof(resp), 0) < 0)
+		return -1;
+
+	if (resp.hdr.nlmsg_type != NLMSG_ERROR) {
+		errno = EPROTO;
+		return -1;
+	}
+	if (resp.err.error != 0) {
+		errno = -resp.err.error;
+		return -1;
+	}
+	return 0;
+}
+
+static int set_link(unsigned int flags, unsigned int change, int attr,
+		    const void *data, size_t len)
+{
+	struct nl_req req;
+	struct ifinfomsg *ifi;
+
+	ifi = req_init(&req, RTM_NEWLINK, 0, sizeof(*ifi));
+	ifi->ifi_family = AF_UNSPEC;
+	ifi->ifi_index = tun_index;
+	ifi->ifi_flags = flags;
+	ifi->ifi_change = change;
+	if (data != NULL)
+		req_add_attr(&req, attr, data, len);
+
+	return do_request(&req);
+}
+
+static int set_link_up(int up)
+{
+	return set_link(up ? IFF_UP : 0, IFF_UP, 0, NULL, 0);
+}
+
+static int do_addr(int type, int flags, int index, const char *addr,
+		   int prefix_len)
+{
+	struct nl_req req;
+	struct ifaddrmsg *ifa;
+	struct in_addr in;
+
+	inet_pton(AF_INET, addr, &in);
+
+	ifa = req_init(&req, type, flags, sizeof(*ifa));
+	ifa->ifa_family = AF_INET;
+	ifa->ifa_prefixlen = prefix_len;
+	ifa->ifa_index = index;
+	req_add_attr(&req, IFA_LOCAL, &in, sizeof(in));
+	req_add_attr(&req, IFA_ADDRESS, &in, sizeof(in));
+
+	return do_request(&req);
+}
+
+static int do_route(int type, int flags, const char *dst, int dst_len,
+		    const char *gateway, int oif)
+{
+	struct nl_req req;
+	struct rtmsg *rtm;
+	struct in_addr in;
+
+	rtm = req_init(&req, type, flags, sizeof(*rtm));
+	rtm->rtm_family = AF_INET;
+	rtm->rtm_dst_len = dst_len;
+	rtm->rtm_table = RT_TABLE_MAIN;
+	rtm->rtm_protocol = RTPROT_BOOT;
+	if (type == RTM_DELROUTE)
+		rtm->rtm_scope = RT_SCOPE_NOWHERE;
+	else if (gateway != NULL)
+		rtm->rtm_scope = RT_SCOPE_UNIVERSE;
+	else
+		rtm->rtm_scope = RT_SCOPE_LINK;
+	rtm->rtm_type = RTN_UNICAST;
+
+	inet_pton(AF_INET, dst, &in);
+	req_add_attr(&req, RTA_DST, &in, sizeof(in));
+	if (gateway != NULL) {
+		inet_pton(AF_INET, gateway, &in);
+		req_add_attr(&req, RTA_GATEWAY, &in, sizeof(in));
+	}
+	if (oif != 0)
+		req_add_attr(&req, RTA_OIF, &oif, sizeof(oif));
+
+	return do_request(&req);
+}
+
+struct route_info {
+	int protocol;
+	int scope;
+	int oif;
+	struct in_addr gateway;
+	struct in_addr pref_src;
+};
+
+// Looks up the route in the main routing table by dumping all IPv4 routes.
+// Returns 1 if the route is found and 0 otherwise.
+static int find_route(const char *dst, int dst_len, struct route_info *info)
+{
+	struct nl_req req;
+	struct rtmsg *rtm;
+	struct in_addr dst_in;
+	int found = 0;
+	int done = 0;
+
+	inet_pton(AF_INET, dst, &dst_in);
+
+	rtm = req_init(&req, RTM_GETROUTE, NLM_F_DUMP, sizeof(*rtm));
+	req.hdr.nlmsg_flags &= ~NLM_F_ACK;
+	rtm->rtm_family = AF_INET;
+	if (send(rtnl_sk, &req, req.hdr.nlmsg_len, 0) < 0)
+		return -1;
+
+	while (!done) {
+		int len = recv(rtnl_sk, buffer, BUFFER_SIZE, 0);
+		struct nlmsghdr *nlh;
+
+		if (len < 0)
+			return -1;
+
+		for (nlh = (struct nlmsghdr *)buffer; NLMSG_OK(nlh, len);
+		     nlh = NLMSG_NEXT(nlh, len)) {
+			struct rtattr *rta;
+			int rta_len;
+			struct route_info cur;
+			struct in_addr cur_dst = { 0 };
+
+			if (nlh->nlmsg_type == NLMSG_DONE) {
+				done = 1;
+				break;
+			}
+			if (nlh->nlmsg_type != RTM_NEWROUTE)
+				continue;
+
+			rtm = NLMSG_DATA(nlh);
+			if (rtm->rtm_table != RT_TABLE_MAIN ||
+			    rtm->rtm_dst_len != dst_len)
+				continue;
+
+			memset(&cur, 0, sizeof(cur));
+			cur.protocol = rtm->rtm_protocol;
+			cur.scope = rtm->rtm_scope;
+
+			rta = RTM_RTA(rtm);
+			rta_len = RTM_PAYLOAD(nlh);
+			for (; RTA_OK(rta, rta_len);
+			     rta = RTA_NEXT(rta, rta_len)) {
+				switch (rta->rta_type) {
+				case RTA_DST:
+					memcpy(&cur_dst, RTA_DATA(rta),
+					       sizeof(cur_dst));
+					break;
+				case RTA_OIF:
+					memcpy(&cur.oif, RTA_DATA(rta),
+					       sizeof(cur.oif));
+					break;
+				case RTA_GATEWAY:
+					memcpy(&cur.gateway, RTA_DATA(rta),
+					       sizeof(cur.gateway));
+					break;
+				case RTA_PREFSRC:
+					memcpy(&cur.pref_src, RTA_DATA(rta),
+					       sizeof(cur.pref_src));
+					break;
+				}
+			}
+
+			if (cur_dst.s_addr == dst_in.s_addr) {
+				found = 1;
+				if (info != NULL)
+					*info = cur;
+			}
+		}
+	}
+
+	return found;
+}
+
+// Returns the MTU of the TUN interface by sending an `RTM_GETLINK` request.
+static int get_mtu(void)
+{
+	struct nl_req req;
+	struct ifinfomsg *ifi;
+	struct nlmsghdr *nlh = (struct nlmsghdr *)buffer;
+	struct rtattr *rta;
+	int len, rta_len;
+
+	ifi = req_init(&req, RTM_GETLINK, 0, sizeof(*ifi));
+	req.hdr.nlmsg_flags &= ~NLM_F_ACK;
+	ifi->ifi_family = AF_UNSPEC;
+	ifi->ifi_index = tun_index;
+	if (send(rtnl_sk, &req, req.hdr.nlmsg_len, 0) < 0)
+		return -1;
+
+	len = recv(rtnl_sk, buffer, BUFFER_SIZE, 0);
+	if (len < 0)
+		return -1;
+	if (!NLMSG_OK(nlh, len) || nlh->nlmsg_type != RTM_NEWLINK) {
+		errno = EPROTO;
+		return -1;
+	}
+
+	ifi = NLMSG_DATA(nlh);
+	rta = IFLA_RTA(ifi);
+	rta_len = IFLA_PAYLOAD(nlh);
+	for (; RTA_OK(rta, rta_len); rta = RTA_NEXT(rta, rta_len)) {
+		if (rta->rta_type == IFLA_MTU)
+			return *(unsigned int *)RTA_DATA(rta);
+	}
+
+	errno = ENOENT;
+	return -1;
+}
+
+// Receives notifications until one of the specified type arrives. Returns the
+// notification or NULL if there is no such notification.
+static struct nlmsghdr *recv_notification(int sk, int type)
+{
+	int len;
+
+	while ((len = recv(sk, buffer, BUFFER_SIZE, MSG_DONTWAIT)) > 0) {
+		struct nlmsghdr *nlh;
+
+		for (nlh = (struct nlmsghdr *)buffer; NLMSG_OK(nlh, len);
+		     nlh = NLMSG_NEXT(nlh, len)) {
+			if (nlh->nlmsg_type == type)
+				return nlh;
+		}
+	}
+
+	return NULL;
+}
+
+// Discards all pending notifications.
+static void drain_notifications(int sk)
+{
+	while (recv(sk, buffer, BUFFER_SIZE, MSG_DONTWAIT) > 0)
+		;
+}
+
+// Returns the source address selected to reach the destination.
+static in_addr_t get_src_addr(const char *dst)
+{
+	struct sockaddr_in addr = { .sin_family = AF_INET,
+				    .sin_port = htons(9) };
+	socklen_t addrlen = sizeof(addr);
+	int sk;
+
+	inet_pton(AF_INET, dst, &addr.sin_addr);
+
+	sk = CHECK(socket(AF_INET, SOCK_DGRAM, 0));
+	if (connect(sk, (struct sockaddr *)&addr, sizeof(addr)) < 0) {
+		int err = errno;
+		close(sk);
+		errno = err;
+		return INADDR_NONE;
+	}
+	CHECK(getsockname(sk, (struct sockaddr *)&addr, &addrlen));
+	CHECK(close(sk));
+
+	return addr.sin_addr.s_addr;
+}
+
+FN_SETUP(init)
+{
+	struct sockaddr_nl sa = { .nl_family = AF_NETLINK };
+	struct ifreq ifr;
+	int group = RTNLGRP_IPV4_ROUTE;
+
+	CHECK(unshare(CLONE_NEWNET));
+
+	tun_fd = CHECK(open(TUN_PATH, O_RDWR | O_NONBLOCK));
+	memset(&ifr, 0, sizeof(ifr));
+	strncpy(ifr.ifr_name, TUN_NAME, IFNAMSIZ - 1);
+	ifr.ifr_flags = IFF_TUN | IFF_NO_PI;
+	CHECK(ioctl(tun_fd, TUNSETIFF, &ifr));
+	tun_index = CHECK_WITH(if_nametoindex(TUN_NAME), _ret != 0);
+
+	rtnl_sk = CHECK(socket(AF_NETLINK, SOCK_RAW, NETLINK_ROUTE));
+
+	addr_group_sk = CHECK(socket(AF_NETLINK, SOCK_RAW, NETLINK_ROUTE));
+	sa.nl_groups = RTMGRP_IPV4_IFADDR;
+	CHECK(bind(addr_group_sk, (struct sockaddr *)&sa, sizeof(sa)));
+
+	route_group_sk = CHECK(socket(AF_NETLINK, SOCK_RAW, NETLINK_ROUTE));
+	sa.nl_groups = 0;
+	CHECK(bind(route_group_sk, (struct sockaddr *)&sa, sizeof(sa)));
+	CHECK(setsockopt(route_group_sk, SOL_NETLINK, NETLINK_ADD_MEMBERSHIP,
+			 &group, sizeof(group)));
+}
+END_SETUP()
+
+FN_TEST(set_link)
+{
+	unsigned int mtu;
+
+	mtu = 1400;
+	TEST_SUCC(set_link(0, 0, IFLA_MTU, &mtu, sizeof(mtu)));
+	TEST_RES(get_mtu(), _ret == 1400);
+
+	mtu = 10;
+	TEST_ERRNO(set_link(0, 0, IFLA_MTU, &mtu, sizeof(mtu)), EINVAL);
+	TEST_RES(get_mtu(), _ret == 1400);
+
+	TEST_SUCC(set_link(0, 0, IFLA_IFNAME, TUN_NEW_NAME,
+			   sizeof(TUN_NEW_NAME)));
+	TEST_RES(if_nametoindex(TUN_NEW_NAME), _ret == tun_index);
+	TEST_ERRNO(if_nametoindex(TUN_NAME), ENODEV);
+
+	// Like Linux, renaming links that are up is allowed.
+	TEST_SUCC(set_link_up(1));
+	TEST_SUCC(set_link(0, 0, IFLA_IFNAME, TUN_NAME, sizeof(TUN_NAME)));
+	TEST_RES(if_nametoindex(TUN_NAME), _ret == tun_index);
+}
+END_TEST()
+
+FN_TEST(new_addr)
+{
+	struct route_info info;
+
+	TEST_SUCC(do_addr(RTM_NEWADDR, NLM_F_CREATE | NLM_F_EXCL, tun_index,
+			  "10.20.0.1", 24));
+	TEST_RES(recv_notification(addr_group_sk, RTM_NEWADDR),
+		 _ret != NULL && ((struct ifaddrmsg *)NLMSG_DATA(_ret))
+						 ->ifa_index == tun_index);
+
+	TEST_ERRNO(do_addr(RTM_NEWADDR, NLM_F_CREATE | NLM_F_EXCL, tun_index,
+			   "10.20.0.1", 24),
+		   EEXIST);
+	TEST_SUCC(do_addr(RTM_NEWADDR, NLM_F_CREATE | NLM_F_REPLACE, tun_index,
+			  "10.20.0.1", 24));
+
+	TEST_ERRNO(do_addr(RTM_NEWADDR, NLM_F_CREATE | NLM_F_EXCL, tun_index,
+			   "10.20.0.1", 33),
+		   EINVAL);
+	TEST_ERRNO(do_addr(RTM_NEWADDR, NLM_F_CREATE | NLM_F_EXCL, 9999,
+			   "10.20.0.1", 24),
+		   ENODEV);
+
+	// The prefix route is added along with the address.
+	TEST_RES(find_route("10.20.0.0", 24, &info),
+		 _ret == 1 && info.oif == tun_index &&
+			 info.protocol == RTPROT_KERNEL &&
+			 info.scope == RT_SCOPE_LINK &&
+			 info.pref_src.s_addr == inet_addr("10.20.0.1"));
+}
+END_TEST()
+
+FN_TEST(new_route)
+{
+	struct route_info info;
+
+	// Linux also reports the routes that are added along with the address.
+	drain_notifications(route_group_sk);
+
+	TEST_SUCC(do_route(RTM_NEWROUTE, NLM_F_CREATE | NLM_F_EXCL,
+			   "10.30.0.0", 16, "10.20.0.2", 0));
+	TEST_RES(recv_notification(route_group_sk, RTM_NEWROUTE),
+		 _ret != NULL &&
+			 ((struct rtmsg *)NLMSG_DATA(_ret))->rtm_dst_len == 16);
+
+	TEST_ERRNO(do_route(RTM_NEWROUTE, NLM_F_CREATE | NLM_F_EXCL,
+			    "10.30.0.0", 16, "10.20.0.2", 0),
+		   EEXIST);
+	TEST_ERRNO(do_route(RTM_NEWROUTE, 0, "10.31.0.0", 16, "10.20.0.2", 0),
+		   ENOENT);
+	TEST_ERRNO(do_route(RTM_NEWROUTE, NLM_F_CREATE | NLM_F_EXCL,
+			    "10.31.0.1", 16, "10.20.0.2", 0),
+		   EINVAL);
+	TEST_ERRNO(do_route(RTM_NEWROUTE, NLM_F_CREATE | NLM_F_EXCL,
+			    "10.31.0.0", 16, "10.99.0.1", 0),
+		   ENETUNREACH);
+	TEST_ERRNO(do_route(RTM_NEWROUTE, NLM_F_CREATE | NLM_F_EXCL,
+			    "10.31.0.0", 16, NULL, 0),
+		   ENODEV);
+
+	TEST_RES(find_route("10.30.0.0", 16, &info),
+		 _ret == 1 && info.oif == tun_index &&
+			 info.gateway.s_addr == inet_addr("10.20.0.2"));
+
+	// The route is used to select the source address.
+	TEST_RES(get_src_addr("10.30.0.5"), _ret == inet_addr("10.20.0.1"));
+}
+END_TEST()
+
+FN_TEST(del_route)
+{
+	TEST_SUCC(do_route(RTM_DELROUTE, 0, "10.30.0.0", 16, NULL, 0));
+	TEST_ERRNO(do_route(RTM_DELROUTE, 0, "10.30.0.0", 16, NULL, 0), ESRCH);
+	TEST_RES(find_route("10.30.0.0", 16, NULL), _ret == 0);
+
+	TEST_ERRNO(get_src_addr("10.30.0.5"), ENETUNREACH);
+}
+END_TEST()
+
+FN_TEST(set_link_down)
+{
+	TEST_SUCC(set_link_up(0));
+	TEST_RES(find_route("10.20.0.0", 24, NULL), _ret == 0);
+
+	TEST_SUCC(set_link_up(1));
+	TEST_RES(find_route("10.20.0.0", 24, NULL), _ret == 1);
+}
+END_TEST()
+
+FN_TEST(del_addr)
+{
+	TEST_SUCC(do_addr(RTM_DELADDR, 0, tun_index, "10.20.0.1", 24));
+	TEST_RES(recv_notification(addr_group_sk, RTM_DELADDR), _ret != NULL);
+	TEST_ERRNO(do_addr(RTM_DELADDR, 0, tun_index, "10.20.0.1", 24),
+		   EADDRNOTAVAIL);
+
+	// The prefix route is removed along with the address.
+	TEST_RES(find_route("10.20.0.0", 24, NULL), _ret == 0);
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(route_group_sk));
+	CHECK(close(addr_group_sk));
+	CHECK(close(rtnl_sk));
+	CHECK(close(tun_fd));
+}
+END_SETUP()
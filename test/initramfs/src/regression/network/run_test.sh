@@ -16,23 +16,40 @@ sleep 0.2
 sleep 0.2
 ./unix_client
 
+./eth_offload
+./ipconfig
 ./listen_backlog
+./packet_socket
 ./privileged_ports
+./proc_net_stats
+./raw_socket
 ./send_buf_full
 ./sendmmsg
 ./socketpair
 ./sockoption
 ./sockoption_unix
+./tcp_cmsg
+./tcp_congestion
 ./tcp_err
+./tcp_info
 ./tcp_poll
 ./tcp_reuseaddr
 ./tcp_wrapped_buffer_io
+./tun
 ./udp_broadcast
 ./udp_err
+./udp_multicast
+./udp_cmsg
+./udp6
 ./unix_datagram_err
 ./unix_seqpacket_err
 ./unix_stream_err
+./veth_bridge
+./vsock_loopback
 
 ./netlink_route
+./nftables
+./rtnl_config
 ./rtnl_err
+./sock_diag
 ./uevent_err
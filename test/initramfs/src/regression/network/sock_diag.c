@@ -0,0 +1,657 @@
+// SPDX-License-Identifier: MPL-2.0
+
+/*
+ * The socket tables in `/proc/net` and the `NETLINK_SOCK_DIAG` protocol.
+ */
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <sched.h>
+#include <stddef.h>
+#include <stdio.h>
+#include <unistd.h>
+#include <sys/socket.h>
+#include <sys/stat.h>
+#include <sys/un.h>
+#include <arpa/inet.h>
+#include <netinet/in.h>
+#include <netinet/tcp.h>
+#include <linux/netlink.h>
+#include <linux/rtnetlink.h>
+#include <linux/sock_diag.h>
+#include <linux/inet_diag.h>
+#include <linux/unix_diag.h>
+
+#include "../common/test.h"
+
+#define TCP_PORT 9401
+#define UDP_PORT 9402
+#define MISSING_PORT 9403
+#define UNIX_PATH "/tmp/sock_diag.sock"
+
+#define MAX_SOCKS 64
+#define BUFFER_SIZE 65536
+
+static int tcp_listener;
+static int tcp_client;
+static int tcp_accepted;
+static int udp_sk;
+static int unix_listener;
+static int unix_pair[2];
+
+static int diag_sk;
+static unsigned int seq;
+
+static char buf[BUFFER_SIZE];
+
+static ino_t ino_of(int fd)
+{
+	struct stat st;
+
+	if (fstat(fd, &st) < 0)
+		return 0;
+	return st.st_ino;
+}
+
+static uint16_t local_port_of(int fd)
+{
+	struct sockaddr_in addr;
+	socklen_t len = sizeof(addr);
+
+	if (getsockname(fd, (struct sockaddr *)&addr, &len) < 0)
+		return 0;
+	return ntohs(addr.sin_port);
+}
+
+FN_SETUP(inet_sockets)
+{
+	struct sockaddr_in addr = {
+		.sin_family = AF_INET,
+		.sin_addr.s_addr = htonl(INADDR_LOOPBACK),
+	};
+
+	tcp_listener = CHECK(socket(AF_INET, SOCK_STREAM, 0));
+	addr.sin_port = htons(TCP_PORT);
+	CHECK(bind(tcp_listener, (struct sockaddr *)&addr, sizeof(addr)));
+	CHECK(listen(tcp_listener, 2));
+
+	tcp_client = CHECK(socket(AF_INET, SOCK_STREAM, 0));
+	CHECK(connect(tcp_client, (struct sockaddr *)&addr, sizeof(addr)));
+	tcp_accepted = CHECK(accept(tcp_listener, NULL, NULL));
+
+	udp_sk = CHECK(socket(AF_INET, SOCK_DGRAM, 0));
+	addr.sin_port = htons(UDP_PORT);
+	CHECK(bind(udp_sk, (struct sockaddr *)&addr, sizeof(addr)));
+}
+END_SETUP()
+
+FN_SETUP(unix_sockets)
+{
+	struct sockaddr_un addr = { .sun_family = AF_UNIX,
+				    .sun_path = UNIX_PATH };
+
+	unlink(UNIX_PATH);
+
+	unix_listener = CHECK(socket(AF_UNIX, SOCK_STREAM, 0));
+	CHECK(bind(unix_listener, (struct sockaddr *)&addr, sizeof(addr)));
+	CHECK(listen(unix_listener, 3));
+
+	CHECK(socketpair(AF_UNIX, SOCK_DGRAM, 0, unix_pair));
+}
+END_SETUP()
+
+FN_SETUP(diag_socket)
+{
+	diag_sk = CHECK(socket(AF_NETLINK, SOCK_DGRAM, NETLINK_SOCK_DIAG));
+}
+END_SETUP()
+
+// Reads the whole file into `buf`. Returns -1 and sets `errno` on failure.
+static int read_file(const char *path)
+{
+	int fd;
+	ssize_t len, total = 0;
+
+	fd = open(path, O_RDONLY);
+	if (fd < 0)
+		return -1;
+
+	while ((len = read(fd, buf + total, sizeof(buf) - 1 - total)) > 0)
+		total += len;
+	close(fd);
+
+	if (len < 0)
+		return -1;
+	buf[total] = '\0';
+	return 0;
+}
+
+struct proc_inet_line {
+	char local[64];
+	char remote[64];
+	unsigned int state;
+	unsigned int uid;
+	unsigned long inode;
+};
+
+// Finds the line whose local address is `local` and whose state is `state` in
+// `/proc/net/{tcp,udp}`.
+static int find_proc_inet_line(const char *path, const char *local,
+			       unsigned int state, struct proc_inet_line *line)
+{
+	char *cur;
+
+	if (read_file(path) < 0)
+		return -1;
+
+	// Skip the header line.
+	cur = strchr(buf, '\n');
+	while (cur != NULL && *(++cur) != '\0') {
+		if (sscanf(cur,
+			   "%*d: %63s %63s %x %*x:%*x %*x:%*x %*x %u %*d %lu",
+			   line->local, line->remote, &line->state, &line->uid,
+			   &line->inode) == 5 &&
+		    strcmp(line->local, local) == 0 && line->state == state)
+			return 0;
+		cur = strchr(cur, '\n');
+	}
+
+	errno = ENOENT;
+	return -1;
+}
+
+FN_TEST(proc_net_tcp)
+{
+	struct proc_inet_line line;
+	char local[64];
+
+	snprintf(local, sizeof(local), "%08X:%04X", htonl(INADDR_LOOPBACK),
+		 TCP_PORT);
+
+	TEST_RES(find_proc_inet_line("/proc/net/tcp", local, TCP_LISTEN, &line),
+		 strcmp(line.remote, "00000000:0000") == 0 &&
+			 line.uid == getuid() &&
+			 line.inode == ino_of(tcp_listener));
+	TEST_RES(find_proc_inet_line("/proc/self/net/tcp", local, TCP_LISTEN,
+				     &line),
+		 line.inode == ino_of(tcp_listener));
+
+	snprintf(local, sizeof(local), "%08X:%04X", htonl(INADDR_LOOPBACK),
+		 local_port_of(tcp_client));
+
+	TEST_RES(find_proc_inet_line("/proc/net/tcp", local, TCP_ESTABLISHED,
+				     &line),
+		 line.inode == ino_of(tcp_client));
+
+	// IPv4 sockets are not reported in `/proc/net/tcp6`.
+	TEST_ERRNO(find_proc_inet_line("/proc/net/tcp6", local, TCP_ESTABLISHED,
+				       &line),
+		   ENOENT);
+}
+END_TEST()
+
+FN_TEST(proc_net_udp)
+{
+	struct proc_inet_line line;
+	char local[64];
+
+	snprintf(local, sizeof(local), "%08X:%04X", htonl(INADDR_LOOPBACK),
+		 UDP_PORT);
+
+	TEST_RES(find_proc_inet_line("/proc/net/udp", local, TCP_CLOSE, &line),
+		 strcmp(line.remote, "00000000:0000") == 0 &&
+			 line.inode == ino_of(udp_sk));
+}
+END_TEST()
+
+struct proc_unix_line {
+	unsigned int flags;
+	unsigned int type;
+	unsigned int state;
+	char path[108];
+};
+
+// Finds the line of the socket in `/proc/net/unix`.
+static int find_proc_unix_line(ino_t ino, struct proc_unix_line *line)
+{
+	char *cur, *end;
+	unsigned long inode;
+	int num;
+
+	if (read_file("/proc/net/unix") < 0)
+		return -1;
+
+	// Skip the header line.
+	cur = strchr(buf, '\n');
+	while (cur != NULL && *(++cur) != '\0') {
+		// Unnamed sockets have no paths, so the path must not be
+		// read from the next line.
+		end = strchr(cur, '\n');
+		if (end != NULL)
+			*end = '\0';
+
+		line->path[0] = '\0';
+		num = sscanf(cur, "%*x: %*x %*x %x %x %x %lu %107s",
+			     &line->flags, &line->type, &line->state, &inode,
+			     line->path);
+		if (num >= 4 && inode == ino)
+			return 0;
+		cur = end;
+	}
+
+	errno = ENOENT;
+	return -1;
+}
+
+FN_TEST(proc_net_unix)
+{
+	struct proc_unix_line line;
+
+	TEST_RES(find_proc_unix_line(ino_of(unix_listener), &line),
+		 line.flags == 0x10000 && line.type == SOCK_STREAM &&
+			 line.state == 1 &&
+			 strcmp(line.path, UNIX_PATH) == 0);
+	TEST_RES(find_proc_unix_line(ino_of(unix_pair[0]), &line),
+		 line.flags == 0 && line.type == SOCK_DGRAM &&
+			 line.state == 3 && line.path[0] == '\0');
+}
+END_TEST()
+
+FN_TEST(proc_net_unix_abstract)
+{
+	// The bytes of abstract names are printed as is, except that null
+	// bytes (including the leading one) are printed as `@`.
+	static const char name[] = "\0sock_diag\0\xe9";
+	struct sockaddr_un addr = { .sun_family = AF_UNIX };
+	struct proc_unix_line line;
+	int sk;
+
+	memcpy(addr.sun_path, name, sizeof(name) - 1);
+	sk = CHECK(socket(AF_UNIX, SOCK_DGRAM, 0));
+	TEST_SUCC(bind(sk, (struct sockaddr *)&addr,
+		       offsetof(struct sockaddr_un, sun_path) + sizeof(name) -
+			       1));
+
+	TEST_RES(find_proc_unix_line(ino_of(sk), &line),
+		 strcmp(line.path, "@sock_diag@\xe9") == 0);
+
+	TEST_SUCC(close(sk));
+}
+END_TEST()
+
+// Sends a `SOCK_DIAG_BY_FAMILY` request.
+static int send_req(const void *req, size_t len, int flags)
+{
+	struct {
+		struct nlmsghdr hdr;
+		char body[64];
+	} msg;
+
+	memset(&msg, 0, sizeof(msg));
+	msg.hdr.nlmsg_len = NLMSG_LENGTH(len);
+	msg.hdr.nlmsg_type = SOCK_DIAG_BY_FAMILY;
+	msg.hdr.nlmsg_flags = NLM_F_REQUEST | flags;
+	msg.hdr.nlmsg_seq = ++seq;
+	memcpy(NLMSG_DATA(&msg.hdr), req, len);
+
+	return send(diag_sk, &msg, msg.hdr.nlmsg_len, 0);
+}
+
+// Receives the responses to the last request and calls `fn` on each socket.
+// Returns -1 and sets `errno` if the request fails.
+static int recv_resp(void (*fn)(struct nlmsghdr *))
+{
+	struct nlmsghdr *nlh;
+	struct nlmsgerr *err;
+	int len;
+
+	for (;;) {
+		len = recv(diag_sk, buf, sizeof(buf), 0);
+		if (len < 0)
+			return -1;
+
+		for (nlh = (struct nlmsghdr *)buf; NLMSG_OK(nlh, len);
+		     nlh = NLMSG_NEXT(nlh, len)) {
+			if (nlh->nlmsg_seq != seq) {
+				errno = EPROTO;
+				return -1;
+			}
+
+			if (nlh->nlmsg_type == NLMSG_DONE)
+				return 0;
+
+			if (nlh->nlmsg_type == NLMSG_ERROR) {
+				err = NLMSG_DATA(nlh);
+				if (err->error == 0)
+					return 0;
+				errno = -err->error;
+				return -1;
+			}
+
+			fn(nlh);
+
+			if (!(nlh->nlmsg_flags & NLM_F_MULTI))
+				return 0;
+		}
+	}
+}
+
+static struct inet_diag_msg inet_msgs[MAX_SOCKS];
+static int inet_tcpi_states[MAX_SOCKS];
+static int num_inet_msgs;
+
+static void on_inet_msg(struct nlmsghdr *nlh)
+{
+	struct inet_diag_msg *msg = NLMSG_DATA(nlh);
+	struct rtattr *attr;
+	int len;
+
+	if (num_inet_msgs >= MAX_SOCKS)
+		return;
+
+	inet_msgs[num_inet_msgs] = *msg;
+	inet_tcpi_states[num_inet_msgs] = -1;
+
+	len = nlh->nlmsg_len - NLMSG_LENGTH(sizeof(*msg));
+	for (attr = (struct rtattr *)(msg + 1); RTA_OK(attr, len);
+	     attr = RTA_NEXT(attr, len)) {
+		if (attr->rta_type == INET_DIAG_INFO)
+			inet_tcpi_states[num_inet_msgs] =
+				((struct tcp_info *)RTA_DATA(attr))->tcpi_state;
+	}
+
+	++num_inet_msgs;
+}
+
+static int query_inet(int protocol, uint32_t states, int ext)
+{
+	struct inet_diag_req_v2 req = {
+		.sdiag_family = AF_INET,
+		.sdiag_protocol = protocol,
+		.idiag_ext = ext,
+		.idiag_states = states,
+	};
+
+	num_inet_msgs = 0;
+
+	if (send_req(&req, sizeof(req), NLM_F_DUMP) < 0)
+		return -1;
+	return recv_resp(on_inet_msg);
+}
+
+// Finds the reported socket whose local port is `port`.
+static int find_inet_msg(uint16_t port)
+{
+	int i;
+
+	for (i = 0; i < num_inet_msgs; ++i)
+		if (inet_msgs[i].id.idiag_sport == htons(port))
+			return i;
+	return -1;
+}
+
+FN_TEST(inet_diag_dump)
+{
+	int i;
+
+	TEST_RES(query_inet(IPPROTO_TCP, 1 << TCP_LISTEN,
+			    1 << (INET_DIAG_INFO - 1)),
+		 (i = find_inet_msg(TCP_PORT)) >= 0 &&
+			 inet_msgs[i].idiag_family == AF_INET &&
+			 inet_msgs[i].idiag_state == TCP_LISTEN &&
+			 inet_msgs[i].id.idiag_src[0] ==
+				 htonl(INADDR_LOOPBACK) &&
+			 inet_msgs[i].idiag_wqueue == 2 &&
+			 inet_msgs[i].idiag_uid == getuid() &&
+			 inet_msgs[i].idiag_inode == ino_of(tcp_listener) &&
+			 inet_tcpi_states[i] == TCP_LISTEN &&
+			 find_inet_msg(local_port_of(tcp_client)) < 0);
+
+	TEST_RES(query_inet(IPPROTO_TCP, 1 << TCP_ESTABLISHED, 0),
+		 (i = find_inet_msg(local_port_of(tcp_client))) >= 0 &&
+			 inet_msgs[i].idiag_state == TCP_ESTABLISHED &&
+			 inet_msgs[i].id.idiag_dport == htons(TCP_PORT) &&
+			 inet_msgs[i].idiag_inode == ino_of(tcp_client) &&
+			 inet_tcpi_states[i] == -1 &&
+			 (i = find_inet_msg(TCP_PORT)) >= 0 &&
+			 inet_msgs[i].idiag_state == TCP_ESTABLISHED &&
+			 inet_msgs[i].idiag_inode == ino_of(tcp_accepted));
+
+	TEST_RES(query_inet(IPPROTO_UDP, 1 << TCP_CLOSE, 0),
+		 (i = find_inet_msg(UDP_PORT)) >= 0 &&
+			 inet_msgs[i].idiag_state == TCP_CLOSE &&
+			 inet_msgs[i].idiag_inode == ino_of(udp_sk) &&
+			 find_inet_msg(TCP_PORT) < 0);
+}
+END_TEST()
+
+static int lookup_inet(uint16_t sport, uint16_t dport)
+{
+	struct inet_diag_req_v2 req = {
+		.sdiag_family = AF_INET,
+		.sdiag_protocol = IPPROTO_TCP,
+		.id = {
+			.idiag_sport = htons(sport),
+			.idiag_dport = htons(dport),
+			.idiag_src = { htonl(INADDR_LOOPBACK) },
+			.idiag_dst = { htonl(INADDR_LOOPBACK) },
+			.idiag_cookie = { INET_DIAG_NOCOOKIE,
+					  INET_DIAG_NOCOOKIE },
+		},
+	};
+
+	num_inet_msgs = 0;
+
+	if (send_req(&req, sizeof(req), 0) < 0)
+		return -1;
+	return recv_resp(on_inet_msg);
+}
+
+FN_TEST(inet_diag_lookup)
+{
+	TEST_RES(lookup_inet(local_port_of(tcp_client), TCP_PORT),
+		 num_inet_msgs == 1 &&
+			 inet_msgs[0].idiag_state == TCP_ESTABLISHED &&
+			 inet_msgs[0].idiag_inode == ino_of(tcp_client));
+
+	TEST_ERRNO(lookup_inet(MISSING_PORT, TCP_PORT), ENOENT);
+}
+END_TEST()
+
+static struct unix_diag_msg unix_msgs[MAX_SOCKS];
+static char unix_names[MAX_SOCKS][108];
+static uint32_t unix_peers[MAX_SOCKS];
+static uint32_t unix_uids[MAX_SOCKS];
+static int num_unix_msgs;
+
+static void on_unix_msg(struct nlmsghdr *nlh)
+{
+	struct unix_diag_msg *msg = NLMSG_DATA(nlh);
+	struct rtattr *attr;
+	int len, name_len;
+
+	if (num_unix_msgs >= MAX_SOCKS)
+		return;
+
+	unix_msgs[num_unix_msgs] = *msg;
+	unix_names[num_unix_msgs][0] = '\0';
+	unix_peers[num_unix_msgs] = 0;
+	unix_uids[num_unix_msgs] = -1;
+
+	len = nlh->nlmsg_len - NLMSG_LENGTH(sizeof(*msg));
+	for (attr = (struct rtattr *)(msg + 1); RTA_OK(attr, len);
+	     attr = RTA_NEXT(attr, len)) {
+		switch (attr->rta_type) {
+		case UNIX_DIAG_NAME:
+			name_len = RTA_PAYLOAD(attr);
+			if (name_len >= 108)
+				name_len = 107;
+			memcpy(unix_names[num_unix_msgs], RTA_DATA(attr),
+			       name_len);
+			unix_names[num_unix_msgs][name_len] = '\0';
+			break;
+		case UNIX_DIAG_PEER:
+			unix_peers[num_unix_msgs] =
+				*(uint32_t *)RTA_DATA(attr);
+			break;
+		case UNIX_DIAG_UID:
+			unix_uids[num_unix_msgs] = *(uint32_t *)RTA_DATA(attr);
+			break;
+		}
+	}
+
+	++num_unix_msgs;
+}
+
+static int query_unix(uint32_t states, uint32_t ino, int flags)
+{
+	struct unix_diag_req req = {
+		.sdiag_family = AF_UNIX,
+		.udiag_states = states,
+		.udiag_ino = ino,
+		.udiag_show = UDIAG_SHOW_NAME | UDIAG_SHOW_PEER |
+			      UDIAG_SHOW_UID,
+		.udiag_cookie = { INET_DIAG_NOCOOKIE, INET_DIAG_NOCOOKIE },
+	};
+
+	num_unix_msgs = 0;
+
+	if (send_req(&req, sizeof(req), flags) < 0)
+		return -1;
+	return recv_resp(on_unix_msg);
+}
+
+// Finds the reported socket whose inode number is `ino`.
+static int find_unix_msg(ino_t ino)
+{
+	int i;
+
+	for (i = 0; i < num_unix_msgs; ++i)
+		if (unix_msgs[i].udiag_ino == ino)
+			return i;
+	return -1;
+}
+
+FN_TEST(unix_diag_dump)
+{
+	int i;
+
+	TEST_RES(query_unix(1 << TCP_LISTEN, 0, NLM_F_DUMP),
+		 (i = find_unix_msg(ino_of(unix_listener))) >= 0 &&
+			 unix_msgs[i].udiag_family == AF_UNIX &&
+			 unix_msgs[i].udiag_type == SOCK_STREAM &&
+			 unix_msgs[i].udiag_state == TCP_LISTEN &&
+			 strcmp(unix_names[i], UNIX_PATH) == 0 &&
+			 unix_peers[i] == 0 && unix_uids[i] == getuid() &&
+			 find_unix_msg(ino_of(unix_pair[0])) < 0);
+
+	TEST_RES(query_unix(1 << TCP_ESTABLISHED, 0, NLM_F_DUMP),
+		 (i = find_unix_msg(ino_of(unix_pair[0]))) >= 0 &&
+			 unix_msgs[i].udiag_type == SOCK_DGRAM &&
+			 unix_msgs[i].udiag_state == TCP_ESTABLISHED &&
+			 unix_names[i][0] == '\0' &&
+			 unix_peers[i] == ino_of(unix_pair[1]) &&
+			 find_unix_msg(ino_of(unix_listener)) < 0);
+}
+END_TEST()
+
+FN_TEST(unix_diag_lookup)
+{
+	TEST_RES(query_unix(0, ino_of(unix_pair[1]), 0),
+		 num_unix_msgs == 1 &&
+			 unix_msgs[0].udiag_ino == ino_of(unix_pair[1]) &&
+			 unix_peers[0] == ino_of(unix_pair[0]));
+
+	TEST_ERRNO(query_unix(0, 0, 0), EINVAL);
+}
+END_TEST()
+
+FN_TEST(unix_netns)
+{
+	struct proc_unix_line line;
+	int init_ns_fd;
+	int init_diag_sk;
+	int sk;
+
+	init_ns_fd = CHECK(open("/proc/self/ns/net", O_RDONLY));
+	TEST_SUCC(unshare(CLONE_NEWNET));
+
+	// Only the sockets in the network namespace of the reader are reported.
+	sk = CHECK(socket(AF_UNIX, SOCK_STREAM, 0));
+	TEST_RES(find_proc_unix_line(ino_of(sk), &line),
+		 line.type == SOCK_STREAM);
+	TEST_ERRNO(find_proc_unix_line(ino_of(unix_listener), &line), ENOENT);
+
+	// Only the sockets in the network namespace of the diag socket are
+	// reported.
+	init_diag_sk = diag_sk;
+	diag_sk = CHECK(socket(AF_NETLINK, SOCK_DGRAM, NETLINK_SOCK_DIAG));
+	TEST_RES(query_unix(-1, 0, NLM_F_DUMP),
+		 find_unix_msg(ino_of(sk)) >= 0 &&
+			 find_unix_msg(ino_of(unix_listener)) < 0);
+	TEST_ERRNO(query_unix(0, ino_of(unix_listener), 0), ENOENT);
+	CHECK(close(diag_sk));
+	diag_sk = init_diag_sk;
+
+	TEST_SUCC(setns(init_ns_fd, CLONE_NEWNET));
+
+	// The socket stays in the network namespace where it was created.
+	TEST_ERRNO(find_proc_unix_line(ino_of(sk), &line), ENOENT);
+	TEST_RES(query_unix(-1, 0, NLM_F_DUMP),
+		 find_unix_msg(ino_of(sk)) < 0 &&
+			 find_unix_msg(ino_of(unix_listener)) >= 0);
+
+	TEST_SUCC(close(sk));
+	TEST_SUCC(close(init_ns_fd));
+}
+END_TEST()
+
+FN_TEST(invalid_requests)
+{
+	struct inet_diag_req_v2 req = {
+		.sdiag_family = AF_APPLETALK,
+	};
+	struct nlmsghdr nlh = {
+		.nlmsg_len = NLMSG_LENGTH(0),
+		.nlmsg_type = SOCK_DIAG_BY_FAMILY + 1,
+		.nlmsg_flags = NLM_F_REQUEST,
+	};
+
+	// Unknown families
+	TEST_SUCC(send_req(&req, sizeof(req), NLM_F_DUMP));
+	TEST_ERRNO(recv_resp(on_inet_msg), ENOENT);
+
+	// Unknown protocols
+	req.sdiag_family = AF_INET;
+	req.sdiag_protocol = 200;
+	TEST_SUCC(send_req(&req, sizeof(req), 0));
+	TEST_ERRNO(recv_resp(on_inet_msg), ENOENT);
+
+	// Dumping the sockets of unknown protocols reports nothing
+	num_inet_msgs = 0;
+	TEST_SUCC(send_req(&req, sizeof(req), NLM_F_DUMP));
+	TEST_RES(recv_resp(on_inet_msg), num_inet_msgs == 0);
+
+	// Unknown message types
+	nlh.nlmsg_seq = ++seq;
+	TEST_SUCC(send(diag_sk, &nlh, sizeof(nlh), 0));
+	TEST_ERRNO(recv_resp(on_inet_msg), EINVAL);
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(diag_sk));
+
+	CHECK(close(unix_pair[0]));
+	CHECK(close(unix_pair[1]));
+	CHECK(close(unix_listener));
+	CHECK(unlink(UNIX_PATH));
+
+	CHECK(close(udp_sk));
+	CHECK(close(tcp_client));
+	CHECK(close(tcp_accepted));
+	CHECK(close(tcp_listener));
+}
+END_SETUP()
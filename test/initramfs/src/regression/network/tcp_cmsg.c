@@ -0,0 +1,173 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+#include <string.h>
+#include <unistd.h>
+#include <sys/socket.h>
+#include <sys/time.h>
+#include <netinet/in.h>
+#include <netinet/tcp.h>
+#include <arpa/inet.h>
+#include "../common/test.h"
+
+#ifndef TCP_CM_INQ
+#define TCP_CM_INQ TCP_INQ
+#endif
+
+#define MESSAGE "Hello from client"
+
+static int sk_listen;
+static int sk_connected;
+static int sk_accepted;
+
+static char cbuf[512];
+static struct iovec iov;
+static struct msghdr msg;
+
+FN_SETUP(connect)
+{
+	struct sockaddr_in addr;
+	socklen_t addrlen = sizeof(addr);
+
+	sk_listen = CHECK(socket(AF_INET, SOCK_STREAM, 0));
+	sk_connected = CHECK(socket(AF_INET, SOCK_STREAM, 0));
+
+	addr.sin_family = AF_INET;
+	addr.sin_port = htons(0);
+	CHECK(inet_aton("127.0.0.1", &addr.sin_addr));
+	CHECK(bind(sk_listen, (struct sockaddr *)&addr, sizeof(addr)));
+	CHECK(getsockname(sk_listen, (struct sockaddr *)&addr, &addrlen));
+	CHECK(listen(sk_listen, 1));
+
+	CHECK(connect(sk_connected, (struct sockaddr *)&addr, sizeof(addr)));
+	sk_accepted = CHECK(accept(sk_listen, NULL, NULL));
+}
+END_SETUP()
+
+static void init_msg(void *buf, size_t len)
+{
+	iov.iov_base = buf;
+	iov.iov_len = len;
+
+	memset(&msg, 0, sizeof(msg));
+	msg.msg_iov = &iov;
+	msg.msg_iovlen = 1;
+	msg.msg_control = cbuf;
+	msg.msg_controllen = sizeof(cbuf);
+	memset(cbuf, 0, sizeof(cbuf));
+}
+
+static int get_cmsg(int level, int type, void *data, size_t len)
+{
+	struct cmsghdr *cmsg;
+
+	for (cmsg = CMSG_FIRSTHDR(&msg); cmsg; cmsg = CMSG_NXTHDR(&msg, cmsg)) {
+		if (cmsg->cmsg_level != level || cmsg->cmsg_type != type)
+			continue;
+
+		if (cmsg->cmsg_len != CMSG_LEN(len)) {
+			errno = EINVAL;
+			return -1;
+		}
+		memcpy(data, CMSG_DATA(cmsg), len);
+		return 0;
+	}
+
+	errno = ENOMSG;
+	return -1;
+}
+
+FN_TEST(send_cmsgs)
+{
+	char buf[64] = MESSAGE;
+	struct cmsghdr *cmsg;
+	struct in_pktinfo pktinfo = { 0 };
+
+	// IP-level control messages are ignored for TCP sockets.
+	init_msg(buf, sizeof(MESSAGE));
+	msg.msg_controllen = CMSG_SPACE(sizeof(pktinfo));
+	cmsg = CMSG_FIRSTHDR(&msg);
+	cmsg->cmsg_level = IPPROTO_IP;
+	cmsg->cmsg_type = IP_PKTINFO;
+	cmsg->cmsg_len = CMSG_LEN(sizeof(pktinfo));
+	memcpy(CMSG_DATA(cmsg), &pktinfo, sizeof(pktinfo));
+	TEST_RES(sendmsg(sk_connected, &msg, 0), _ret == sizeof(MESSAGE));
+
+	TEST_RES(recv(sk_accepted, buf, sizeof(buf), 0),
+		 _ret == sizeof(MESSAGE) && memcmp(buf, MESSAGE, _ret) == 0);
+}
+END_TEST()
+
+FN_TEST(recv_cmsgs)
+{
+	char buf[64];
+	int val;
+	int inq;
+	struct timeval before, after;
+	struct timeval tv;
+
+	val = 1;
+	TEST_SUCC(setsockopt(sk_accepted, SOL_SOCKET, SO_TIMESTAMP, &val,
+			     sizeof(val)));
+	TEST_SUCC(setsockopt(sk_accepted, IPPROTO_TCP, TCP_INQ, &val,
+			     sizeof(val)));
+	// IP-level control messages are never generated for TCP sockets.
+	TEST_SUCC(setsockopt(sk_accepted, IPPROTO_IP, IP_PKTINFO, &val,
+			     sizeof(val)));
+
+	CHECK(gettimeofday(&before, NULL));
+	TEST_RES(send(sk_connected, MESSAGE, sizeof(MESSAGE), 0),
+		 _ret == sizeof(MESSAGE));
+	usleep(100 * 1000);
+
+	init_msg(buf, 5);
+	TEST_RES(recvmsg(sk_accepted, &msg, 0),
+		 _ret == 5 && memcmp(buf, MESSAGE, _ret) == 0);
+	CHECK(gettimeofday(&after, NULL));
+
+	TEST_RES(get_cmsg(IPPROTO_TCP, TCP_CM_INQ, &inq, sizeof(inq)),
+		 inq == sizeof(MESSAGE) - 5);
+
+	// The timestamp has a coarse granularity, so allow some deviations.
+	before.tv_sec -= 1;
+	after.tv_sec += 1;
+	TEST_RES(get_cmsg(SOL_SOCKET, SO_TIMESTAMP, &tv, sizeof(tv)),
+		 !timercmp(&tv, &before, <) && !timercmp(&tv, &after, >));
+
+	TEST_ERRNO(get_cmsg(IPPROTO_IP, IP_PKTINFO, buf, 12), ENOMSG);
+
+	init_msg(buf, sizeof(buf));
+	TEST_RES(recvmsg(sk_accepted, &msg, 0),
+		 _ret == sizeof(MESSAGE) - 5 &&
+			 memcmp(buf, MESSAGE + 5, _ret) == 0);
+	TEST_RES(get_cmsg(IPPROTO_TCP, TCP_CM_INQ, &inq, sizeof(inq)),
+		 inq == 0);
+
+	val = 0;
+	TEST_SUCC(setsockopt(sk_accepted, SOL_SOCKET, SO_TIMESTAMP, &val,
+			     sizeof(val)));
+	TEST_SUCC(setsockopt(sk_accepted, IPPROTO_TCP, TCP_INQ, &val,
+			     sizeof(val)));
+}
+END_TEST()
+
+FN_TEST(recv_cmsgs_disabled)
+{
+	char buf[64];
+
+	TEST_RES(send(sk_connected, MESSAGE, sizeof(MESSAGE), 0),
+		 _ret == sizeof(MESSAGE));
+
+	init_msg(buf, sizeof(buf));
+	TEST_RES(recvmsg(sk_accepted, &msg, 0),
+		 _ret == sizeof(MESSAGE) && msg.msg_controllen == 0);
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(sk_accepted));
+	CHECK(close(sk_connected));
+	CHECK(close(sk_listen));
+}
+END_SETUP()
@@ -0,0 +1,282 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+#include <fcntl.h>
+#include <string.h>
+#include <unistd.h>
+#include <sys/socket.h>
+#include <netinet/in.h>
+#include <netinet/tcp.h>
+#include <arpa/inet.h>
+#include "../common/test.h"
+
+#define CONGESTION_CONTROL_FILE "/proc/sys/net/ipv4/tcp_congestion_control"
+#define AVAILABLE_CONGESTION_CONTROL_FILE \
+	"/proc/sys/net/ipv4/tcp_available_congestion_control"
+
+#define NAME_MAX_LEN 16
+
+static char default_name[NAME_MAX_LEN];
+
+static int sk_unbound;
+static int sk_listen;
+static int sk_connected;
+static int sk_accepted;
+
+static struct sockaddr_in listen_addr;
+
+static int read_file(const char *path, char *buf, size_t size)
+{
+	int fd;
+	ssize_t len;
+
+	fd = open(path, O_RDONLY);
+	if (fd < 0)
+		return -1;
+
+	len = read(fd, buf, size - 1);
+	close(fd);
+	if (len < 0)
+		return -1;
+
+	buf[len] = '\0';
+	if (len > 0 && buf[len - 1] == '\n')
+		buf[len - 1] = '\0';
+
+	return 0;
+}
+
+static ssize_t write_file(const char *path, const char *content)
+{
+	int fd;
+	ssize_t len;
+
+	fd = open(path, O_WRONLY);
+	if (fd < 0)
+		return -1;
+
+	len = write(fd, content, strlen(content));
+	close(fd);
+
+	return len;
+}
+
+static int get_congestion(int sk, char *name)
+{
+	socklen_t len = NAME_MAX_LEN;
+
+	memset(name, 0, NAME_MAX_LEN);
+	return getsockopt(sk, IPPROTO_TCP, TCP_CONGESTION, name, &len);
+}
+
+FN_SETUP(general)
+{
+	socklen_t addr_len = sizeof(listen_addr);
+
+	CHECK(read_file(CONGESTION_CONTROL_FILE, default_name,
+			sizeof(default_name)));
+
+	sk_unbound = CHECK(socket(AF_INET, SOCK_STREAM, 0));
+
+	listen_addr.sin_family = AF_INET;
+	listen_addr.sin_port = htons(0);
+	CHECK(inet_aton("127.0.0.1", &listen_addr.sin_addr));
+
+	sk_listen = CHECK(socket(AF_INET, SOCK_STREAM, 0));
+	CHECK(bind(sk_listen, (struct sockaddr *)&listen_addr,
+		   sizeof(listen_addr)));
+	CHECK(getsockname(sk_listen, (struct sockaddr *)&listen_addr,
+			  &addr_len));
+	CHECK(listen(sk_listen, 3));
+}
+END_SETUP()
+
+FN_TEST(available_congestion_control)
+{
+	char buf[64];
+
+	TEST_RES(read_file(AVAILABLE_CONGESTION_CONTROL_FILE, buf, sizeof(buf)),
+		 strstr(buf, "reno") != NULL && strstr(buf, "cubic") != NULL &&
+			 strstr(buf, default_name) != NULL);
+
+#ifdef __asterinas__
+	TEST_RES(read_file(AVAILABLE_CONGESTION_CONTROL_FILE, buf, sizeof(buf)),
+		 strcmp(buf, "reno cubic bbr") == 0);
+	TEST_RES(read_file(CONGESTION_CONTROL_FILE, buf, sizeof(buf)),
+		 strcmp(buf, "cubic") == 0);
+#endif
+}
+END_TEST()
+
+FN_TEST(default_congestion_control)
+{
+	char name[NAME_MAX_LEN];
+	socklen_t len;
+
+	len = sizeof(name);
+	TEST_RES(getsockopt(sk_unbound, IPPROTO_TCP, TCP_CONGESTION, name,
+			    &len),
+		 len == sizeof(name) && strcmp(name, default_name) == 0);
+
+	len = 2;
+	TEST_RES(getsockopt(sk_unbound, IPPROTO_TCP, TCP_CONGESTION, name,
+			    &len),
+		 len == 2 && strncmp(name, default_name, 2) == 0);
+}
+END_TEST()
+
+FN_TEST(set_congestion_control)
+{
+	int sk;
+	char name[NAME_MAX_LEN];
+
+	sk = TEST_SUCC(socket(AF_INET, SOCK_STREAM, 0));
+
+	TEST_SUCC(setsockopt(sk, IPPROTO_TCP, TCP_CONGESTION, "reno", 4));
+	TEST_RES(get_congestion(sk, name), strcmp(name, "reno") == 0);
+
+	// The name ends at the first null character.
+	TEST_SUCC(setsockopt(sk, IPPROTO_TCP, TCP_CONGESTION, "cubic\0reno",
+			     sizeof("cubic\0reno")));
+	TEST_RES(get_congestion(sk, name), strcmp(name, "cubic") == 0);
+
+	TEST_SUCC(setsockopt(sk, IPPROTO_TCP, TCP_CONGESTION, "bbr", 3));
+	TEST_RES(get_congestion(sk, name), strcmp(name, "bbr") == 0);
+
+	TEST_ERRNO(setsockopt(sk, IPPROTO_TCP, TCP_CONGESTION, "foo", 3),
+		   ENOENT);
+	TEST_ERRNO(setsockopt(sk, IPPROTO_TCP, TCP_CONGESTION, "cubi", 4),
+		   ENOENT);
+	TEST_RES(get_congestion(sk, name), strcmp(name, "bbr") == 0);
+
+	TEST_SUCC(close(sk));
+}
+END_TEST()
+
+FN_TEST(sysctl_congestion_control)
+{
+	int sk;
+	char name[NAME_MAX_LEN];
+
+	TEST_RES(write_file(CONGESTION_CONTROL_FILE, "reno\n"), _ret == 5);
+	TEST_RES(read_file(CONGESTION_CONTROL_FILE, name, sizeof(name)),
+		 strcmp(name, "reno") == 0);
+
+	sk = TEST_SUCC(socket(AF_INET, SOCK_STREAM, 0));
+	TEST_RES(get_congestion(sk, name), strcmp(name, "reno") == 0);
+	TEST_SUCC(close(sk));
+
+	// Existing sockets are not affected.
+	TEST_RES(get_congestion(sk_unbound, name),
+		 strcmp(name, default_name) == 0);
+
+	TEST_ERRNO(write_file(CONGESTION_CONTROL_FILE, "foo\n"), ENOENT);
+	TEST_RES(read_file(CONGESTION_CONTROL_FILE, name, sizeof(name)),
+		 strcmp(name, "reno") == 0);
+
+	TEST_RES(write_file(CONGESTION_CONTROL_FILE, default_name),
+		 _ret == (ssize_t)strlen(default_name));
+	TEST_RES(read_file(CONGESTION_CONTROL_FILE, name, sizeof(name)),
+		 strcmp(name, default_name) == 0);
+}
+END_TEST()
+
+FN_TEST(inherit_congestion_control)
+{
+	char name[NAME_MAX_LEN];
+
+	TEST_SUCC(setsockopt(sk_listen, IPPROTO_TCP, TCP_CONGESTION, "reno",
+			     4));
+
+	sk_connected = TEST_SUCC(socket(AF_INET, SOCK_STREAM, 0));
+	TEST_SUCC(connect(sk_connected, (struct sockaddr *)&listen_addr,
+			  sizeof(listen_addr)));
+	sk_accepted = TEST_SUCC(accept(sk_listen, NULL, NULL));
+
+	TEST_RES(get_congestion(sk_accepted, name), strcmp(name, "reno") == 0);
+	TEST_RES(get_congestion(sk_connected, name),
+		 strcmp(name, default_name) == 0);
+}
+END_TEST()
+
+FN_TEST(tcp_info_unconnected)
+{
+	struct tcp_info info;
+	socklen_t len;
+
+	len = sizeof(info);
+	TEST_RES(getsockopt(sk_unbound, IPPROTO_TCP, TCP_INFO, &info, &len),
+		 len == sizeof(info) && info.tcpi_state == TCP_CLOSE &&
+			 info.tcpi_snd_cwnd == 10 &&
+			 info.tcpi_snd_ssthresh == 0x7fffffff);
+
+	len = sizeof(info);
+	TEST_RES(getsockopt(sk_listen, IPPROTO_TCP, TCP_INFO, &info, &len),
+		 len == sizeof(info) && info.tcpi_state == TCP_LISTEN);
+
+	TEST_ERRNO(setsockopt(sk_unbound, IPPROTO_TCP, TCP_INFO, &info,
+			      sizeof(info)),
+		   ENOPROTOOPT);
+}
+END_TEST()
+
+FN_TEST(tcp_info_connected)
+{
+	struct tcp_info info;
+	socklen_t len;
+	char buf[1];
+
+	TEST_RES(send(sk_connected, "a", 1, 0), _ret == 1);
+	TEST_RES(recv(sk_accepted, buf, sizeof(buf), 0), _ret == 1);
+
+	len = sizeof(info);
+	TEST_RES(getsockopt(sk_connected, IPPROTO_TCP, TCP_INFO, &info, &len),
+		 len == sizeof(info) && info.tcpi_state == TCP_ESTABLISHED &&
+			 info.tcpi_ca_state == TCP_CA_Open &&
+			 info.tcpi_snd_mss > 0 && info.tcpi_snd_cwnd >= 10 &&
+			 info.tcpi_snd_ssthresh == 0x7fffffff &&
+			 info.tcpi_total_retrans == 0);
+
+	len = sizeof(info);
+	TEST_RES(getsockopt(sk_accepted, IPPROTO_TCP, TCP_INFO, &info, &len),
+		 len == sizeof(info) && info.tcpi_state == TCP_ESTABLISHED &&
+			 info.tcpi_snd_cwnd >= 10);
+
+	// The information is truncated if the buffer is too short.
+	memset(&info, 0, sizeof(info));
+	len = 1;
+	TEST_RES(getsockopt(sk_connected, IPPROTO_TCP, TCP_INFO, &info, &len),
+		 len == 1 && info.tcpi_state == TCP_ESTABLISHED &&
+			 info.tcpi_ca_state == 0);
+}
+END_TEST()
+
+FN_TEST(switch_congestion_control)
+{
+	struct tcp_info info;
+	socklen_t len;
+	char name[NAME_MAX_LEN];
+
+	TEST_SUCC(setsockopt(sk_connected, IPPROTO_TCP, TCP_CONGESTION, "bbr",
+			     3));
+	TEST_RES(get_congestion(sk_connected, name),
+		 strcmp(name, "bbr") == 0);
+
+	TEST_RES(send(sk_connected, "b", 1, 0), _ret == 1);
+	TEST_RES(recv(sk_accepted, name, sizeof(name), 0), _ret == 1);
+
+	len = sizeof(info);
+	TEST_RES(getsockopt(sk_connected, IPPROTO_TCP, TCP_INFO, &info, &len),
+		 len == sizeof(info) && info.tcpi_state == TCP_ESTABLISHED &&
+			 info.tcpi_snd_cwnd > 0);
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(sk_unbound));
+	CHECK(close(sk_listen));
+	CHECK(close(sk_connected));
+	CHECK(close(sk_accepted));
+}
+END_SETUP()
@@ -0,0 +1,211 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+#include <string.h>
+#include <unistd.h>
+#include <sys/socket.h>
+#include <netinet/in.h>
+#include <linux/tcp.h>
+#include <arpa/inet.h>
+#include "../common/test.h"
+
+// The `tcpi_state` value of an established connection.
+#define TCP_ESTABLISHED 1
+
+#define KEEP_IDLE 1
+#define KEEP_INTVL 1
+#define KEEP_CNT 2
+
+static int sk_listen;
+static int sk_connected;
+static int sk_accepted;
+
+static struct sockaddr_in listen_addr;
+
+static int set_int(int sk, int level, int name, int val)
+{
+	return setsockopt(sk, level, name, &val, sizeof(val));
+}
+
+static int get_int(int sk, int level, int name)
+{
+	int val = -1;
+	socklen_t len = sizeof(val);
+
+	if (getsockopt(sk, level, name, &val, &len) < 0)
+		return -1;
+
+	return val;
+}
+
+static int get_info(int sk, struct tcp_info *info)
+{
+	socklen_t len = sizeof(*info);
+
+	memset(info, 0, sizeof(*info));
+	if (getsockopt(sk, IPPROTO_TCP, TCP_INFO, info, &len) < 0)
+		return -1;
+
+	return len;
+}
+
+static int set_keep_alive(int sk)
+{
+	if (set_int(sk, SOL_SOCKET, SO_KEEPALIVE, 1) < 0 ||
+	    set_int(sk, IPPROTO_TCP, TCP_KEEPIDLE, KEEP_IDLE) < 0 ||
+	    set_int(sk, IPPROTO_TCP, TCP_KEEPINTVL, KEEP_INTVL) < 0 ||
+	    set_int(sk, IPPROTO_TCP, TCP_KEEPCNT, KEEP_CNT) < 0)
+		return -1;
+
+	return 0;
+}
+
+FN_SETUP(general)
+{
+	socklen_t addr_len = sizeof(listen_addr);
+
+	listen_addr.sin_family = AF_INET;
+	listen_addr.sin_port = htons(0);
+	CHECK(inet_aton("127.0.0.1", &listen_addr.sin_addr));
+
+	sk_listen = CHECK(socket(AF_INET, SOCK_STREAM, 0));
+	CHECK(bind(sk_listen, (struct sockaddr *)&listen_addr,
+		   sizeof(listen_addr)));
+	CHECK(getsockname(sk_listen, (struct sockaddr *)&listen_addr,
+			  &addr_len));
+	CHECK(set_keep_alive(sk_listen));
+	CHECK(listen(sk_listen, 3));
+
+	sk_connected = CHECK(socket(AF_INET, SOCK_STREAM, 0));
+	CHECK(connect(sk_connected, (struct sockaddr *)&listen_addr,
+		      sizeof(listen_addr)));
+	sk_accepted = CHECK(accept(sk_listen, NULL, NULL));
+}
+END_SETUP()
+
+FN_TEST(inherit_keep_alive)
+{
+	TEST_RES(get_int(sk_accepted, SOL_SOCKET, SO_KEEPALIVE), _ret == 1);
+	TEST_RES(get_int(sk_accepted, IPPROTO_TCP, TCP_KEEPIDLE),
+		 _ret == KEEP_IDLE);
+	TEST_RES(get_int(sk_accepted, IPPROTO_TCP, TCP_KEEPINTVL),
+		 _ret == KEEP_INTVL);
+	TEST_RES(get_int(sk_accepted, IPPROTO_TCP, TCP_KEEPCNT),
+		 _ret == KEEP_CNT);
+
+	TEST_RES(get_int(sk_connected, SOL_SOCKET, SO_KEEPALIVE), _ret == 0);
+	TEST_RES(get_int(sk_connected, IPPROTO_TCP, TCP_KEEPIDLE),
+		 _ret == 7200);
+}
+END_TEST()
+
+FN_TEST(tcp_info_stats)
+{
+	struct tcp_info info;
+	char buf[8];
+
+	TEST_RES(send(sk_connected, "hello", 5, 0), _ret == 5);
+	TEST_RES(recv(sk_accepted, buf, sizeof(buf), 0), _ret == 5);
+	TEST_RES(send(sk_accepted, "bye", 3, 0), _ret == 3);
+	TEST_RES(recv(sk_connected, buf, sizeof(buf), 0), _ret == 3);
+
+	// The SYN is counted as acknowledged bytes, but not as received bytes.
+	TEST_RES(get_info(sk_connected, &info),
+		 _ret == (int)sizeof(info) &&
+			 info.tcpi_state == TCP_ESTABLISHED &&
+			 info.tcpi_bytes_acked == 6 &&
+			 info.tcpi_bytes_received == 3 &&
+			 info.tcpi_bytes_sent == 5 &&
+			 info.tcpi_bytes_retrans == 0 &&
+			 info.tcpi_data_segs_out == 1 &&
+			 info.tcpi_data_segs_in == 1 &&
+			 info.tcpi_segs_out >= 3 && info.tcpi_segs_in >= 2 &&
+			 info.tcpi_retransmits == 0 && info.tcpi_probes == 0 &&
+			 info.tcpi_rtt > 0 && info.tcpi_rto >= 200000 &&
+			 info.tcpi_last_data_recv < 1000 &&
+			 info.tcpi_last_data_sent < 1000);
+
+	TEST_RES(get_info(sk_accepted, &info),
+		 _ret == (int)sizeof(info) &&
+			 info.tcpi_state == TCP_ESTABLISHED &&
+			 info.tcpi_bytes_acked >= 1 &&
+			 info.tcpi_bytes_received == 5 &&
+			 info.tcpi_bytes_sent == 3 &&
+			 info.tcpi_data_segs_out == 1 &&
+			 info.tcpi_data_segs_in == 1);
+}
+END_TEST()
+
+FN_TEST(keep_alive_probes)
+{
+	struct tcp_info info;
+	char buf[8];
+
+	TEST_SUCC(set_keep_alive(sk_connected));
+
+	// Both sides send probes after the idle time, and the peers answer.
+	TEST_SUCC(sleep(KEEP_IDLE + KEEP_INTVL * KEEP_CNT + 1));
+
+	TEST_RES(get_info(sk_connected, &info),
+		 _ret == (int)sizeof(info) &&
+			 info.tcpi_state == TCP_ESTABLISHED &&
+			 info.tcpi_probes == 0 &&
+			 info.tcpi_last_data_recv >= 3000 &&
+			 info.tcpi_last_ack_recv < 2000);
+	TEST_RES(get_info(sk_accepted, &info),
+		 _ret == (int)sizeof(info) &&
+			 info.tcpi_state == TCP_ESTABLISHED &&
+			 info.tcpi_probes == 0 &&
+			 info.tcpi_last_data_recv >= 3000 &&
+			 info.tcpi_last_ack_recv < 2000);
+
+	// Probes do not carry new data.
+	TEST_RES(get_info(sk_connected, &info),
+		 info.tcpi_bytes_sent == 5 && info.tcpi_bytes_received == 3);
+
+	TEST_RES(send(sk_connected, "hello", 5, 0), _ret == 5);
+	TEST_RES(recv(sk_accepted, buf, sizeof(buf), 0), _ret == 5);
+}
+END_TEST()
+
+FN_TEST(keep_alive_idle_after_answer)
+{
+	struct tcp_info info;
+	int sk_client, sk_server;
+
+	sk_client = TEST_SUCC(socket(AF_INET, SOCK_STREAM, 0));
+	TEST_SUCC(connect(sk_client, (struct sockaddr *)&listen_addr,
+			  sizeof(listen_addr)));
+	sk_server = TEST_SUCC(accept(sk_listen, NULL, NULL));
+	TEST_SUCC(set_int(sk_server, SOL_SOCKET, SO_KEEPALIVE, 0));
+
+	TEST_SUCC(set_int(sk_client, SOL_SOCKET, SO_KEEPALIVE, 1));
+	TEST_SUCC(set_int(sk_client, IPPROTO_TCP, TCP_KEEPIDLE, 2));
+	TEST_SUCC(set_int(sk_client, IPPROTO_TCP, TCP_KEEPINTVL, 1));
+
+	// The first probe is sent after 2 seconds and is answered.
+	TEST_SUCC(usleep(2500 * 1000));
+	TEST_RES(get_info(sk_client, &info),
+		 info.tcpi_probes == 0 && info.tcpi_last_ack_recv < 1000);
+
+	// The next probe is sent after the idle time, not after the interval.
+	TEST_SUCC(usleep(1000 * 1000));
+	TEST_RES(get_info(sk_client, &info),
+		 info.tcpi_probes == 0 && info.tcpi_last_ack_recv >= 1000);
+
+	TEST_SUCC(usleep(1000 * 1000));
+	TEST_RES(get_info(sk_client, &info),
+		 info.tcpi_probes == 0 && info.tcpi_last_ack_recv < 1000);
+
+	TEST_SUCC(close(sk_client));
+	TEST_SUCC(close(sk_server));
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(sk_listen));
+	CHECK(close(sk_connected));
+	CHECK(close(sk_accepted));
+}
+END_SETUP()
@@ -0,0 +1,455 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <poll.h>
+#include <unistd.h>
+#include <net/if.h>
+#include <sys/ioctl.h>
+#include <sys/socket.h>
+#include <sys/wait.h>
+#include <arpa/inet.h>
+#include <linux/if_ether.h>
+#include <linux/if_packet.h>
+#include <linux/if_tun.h>
+#include <linux/netlink.h>
+#include <linux/rtnetlink.h>
+
+#include "../common/test.h"
+
+#define TUN_PATH "/dev/net/tun"
+#define TUN_TEMPLATE "asttun%d"
+#define TAP_TEMPLATE "asttap%d"
+
+// An EtherType reserved for local experiments.
+#define TEST_PROTO 0x88b5
+#define TEST_PAYLOAD "tun test"
+
+struct test_frame {
+	struct ethhdr hdr;
+	char payload[sizeof(TEST_PAYLOAD)];
+} __attribute__((packed));
+
+static int open_tun(void)
+{
+	return CHECK(open(TUN_PATH, O_RDWR | O_NONBLOCK));
+}
+
+static int set_iff(int fd, const char *name, short flags, struct ifreq *ifr)
+{
+	memset(ifr, 0, sizeof(*ifr));
+	strncpy(ifr->ifr_name, name, IFNAMSIZ - 1);
+	ifr->ifr_flags = flags;
+
+	return ioctl(fd, TUNSETIFF, ifr);
+}
+
+static int set_queue(int fd, short flags)
+{
+	struct ifreq ifr;
+
+	memset(&ifr, 0, sizeof(ifr));
+	ifr.ifr_flags = flags;
+
+	return ioctl(fd, TUNSETQUEUE, &ifr);
+}
+
+// Finds the interface index via `if_nameindex`, which queries the kernel
+// using rtnetlink.
+static int find_link(const char *name)
+{
+	struct if_nameindex *ifs, *it;
+	int index = 0;
+
+	ifs = if_nameindex();
+	if (ifs == NULL)
+		return -1;
+
+	for (it = ifs; it->if_index != 0; ++it) {
+		if (strcmp(it->if_name, name) == 0) {
+			index = it->if_index;
+			break;
+		}
+	}
+
+	if_freenameindex(ifs);
+	return index;
+}
+
+// Brings up the interface via an `RTM_NEWLINK` request.
+static int set_link_up(int index)
+{
+	struct {
+		struct nlmsghdr hdr;
+		struct ifinfomsg ifi;
+	} req;
+	struct {
+		struct nlmsghdr hdr;
+		struct nlmsgerr err;
+	} resp;
+	int sk;
+
+	sk = CHECK(socket(AF_NETLINK, SOCK_RAW, NETLINK_ROUTE));
+
+	memset(&req, 0, sizeof(req));
+	req.hdr.nlmsg_len = sizeof(req);
+	req.hdr.nlmsg_type = RTM_NEWLINK;
+	req.hdr.nlmsg_flags = NLM_F_REQUEST | NLM_F_ACK;
+	req.ifi.ifi_family = AF_UNSPEC;
+	req.ifi.ifi_index = index;
+	req.ifi.ifi_flags = IFF_UP;
+	req.ifi.ifi_change = IFF_UP;
+	CHECK(send(sk, &req, sizeof(req), 0));
+
+	CHECK(recv(sk, &resp, sizeof(resp), 0));
+	CHECK(close(sk));
+
+	if (resp.hdr.nlmsg_type != NLMSG_ERROR) {
+		errno = EPROTO;
+		return -1;
+	}
+	if (resp.err.error != 0) {
+		errno = -resp.err.error;
+		return -1;
+	}
+	return 0;
+}
+
+static void fill_frame(struct test_frame *frame)
+{
+	static const unsigned char src[ETH_ALEN] = { 0x02, 0x00, 0x00,
+						     0x00, 0x00, 0x01 };
+
+	memset(frame->hdr.h_dest, 0xff, ETH_ALEN);
+	memcpy(frame->hdr.h_source, src, ETH_ALEN);
+	frame->hdr.h_proto = htons(TEST_PROTO);
+	memcpy(frame->payload, TEST_PAYLOAD, sizeof(TEST_PAYLOAD));
+}
+
+// Reads from the TUN/TAP file until a test frame arrives. Other frames (e.g.,
+// IPv6 router solicitations) may be sent by the kernel once the link is up.
+static ssize_t read_test_frame(int fd, char *buf, size_t len, size_t offset)
+{
+	struct pollfd pfd = { .fd = fd, .events = POLLIN };
+	struct ethhdr *hdr = (struct ethhdr *)(buf + offset);
+	ssize_t ret;
+
+	for (;;) {
+		if (poll(&pfd, 1, 1000) <= 0) {
+			errno = ETIMEDOUT;
+			return -1;
+		}
+
+		ret = read(fd, buf, len);
+		if (ret < 0)
+			return ret;
+		if ((size_t)ret >= offset + sizeof(*hdr) &&
+		    hdr->h_proto == htons(TEST_PROTO))
+			return ret;
+	}
+}
+
+static int new_packet_socket(int index)
+{
+	struct sockaddr_ll sll;
+	int sk;
+
+	sk = CHECK(socket(AF_PACKET, SOCK_RAW | SOCK_NONBLOCK,
+			  htons(TEST_PROTO)));
+
+	memset(&sll, 0, sizeof(sll));
+	sll.sll_family = AF_PACKET;
+	sll.sll_protocol = htons(TEST_PROTO);
+	sll.sll_ifindex = index;
+	CHECK(bind(sk, (struct sockaddr *)&sll, sizeof(sll)));
+
+	return sk;
+}
+
+FN_TEST(get_features)
+{
+	unsigned int features;
+	int fd;
+
+	fd = open_tun();
+
+	TEST_RES(ioctl(fd, TUNGETFEATURES, &features),
+		 (features & IFF_TUN) && (features & IFF_TAP) &&
+			 (features & IFF_NO_PI) &&
+			 (features & IFF_MULTI_QUEUE));
+
+	TEST_SUCC(close(fd));
+}
+END_TEST()
+
+FN_TEST(not_attached)
+{
+	struct pollfd pfd;
+	struct ifreq ifr;
+	char buf[64];
+	int fd;
+
+	fd = open_tun();
+
+	TEST_ERRNO(read(fd, buf, sizeof(buf)), EBADFD);
+	TEST_ERRNO(write(fd, buf, sizeof(buf)), EBADFD);
+	TEST_ERRNO(ioctl(fd, TUNGETIFF, &ifr), EBADFD);
+	TEST_ERRNO(ioctl(fd, TUNSETPERSIST, 1), EBADFD);
+	TEST_ERRNO(set_queue(fd, IFF_DETACH_QUEUE), EINVAL);
+	TEST_ERRNO(set_queue(fd, IFF_ATTACH_QUEUE), EINVAL);
+
+	pfd.fd = fd;
+	pfd.events = POLLIN | POLLOUT;
+	TEST_RES(poll(&pfd, 1, 0), _ret == 1 && pfd.revents == POLLERR);
+
+	TEST_SUCC(close(fd));
+}
+END_TEST()
+
+FN_TEST(set_iff_invalid)
+{
+	struct ifreq ifr;
+	int fd;
+
+	fd = open_tun();
+
+	TEST_ERRNO(set_iff(fd, TUN_TEMPLATE, IFF_NO_PI, &ifr), EINVAL);
+	TEST_ERRNO(set_iff(fd, "ast%d%d", IFF_TUN, &ifr), EINVAL);
+	TEST_ERRNO(set_iff(fd, "ast/tun", IFF_TUN, &ifr), EINVAL);
+
+	TEST_SUCC(close(fd));
+}
+END_TEST()
+
+FN_TEST(tun_lifetime)
+{
+	struct ifreq ifr, ifr2;
+	char name[IFNAMSIZ];
+	int fd, fd2;
+
+	fd = open_tun();
+	fd2 = open_tun();
+
+	TEST_RES(set_iff(fd, TUN_TEMPLATE, IFF_TUN | IFF_NO_PI, &ifr),
+		 strncmp(ifr.ifr_name, "asttun", 6) == 0 &&
+			 strchr(ifr.ifr_name, '%') == NULL);
+	strcpy(name, ifr.ifr_name);
+
+	TEST_RES(ioctl(fd, TUNGETIFF, &ifr2),
+		 strcmp(ifr2.ifr_name, name) == 0 &&
+			 (ifr2.ifr_flags & IFF_TUN) &&
+			 (ifr2.ifr_flags & IFF_NO_PI));
+	TEST_RES(find_link(name), _ret > 0);
+
+	// A file can only be attached once.
+	TEST_ERRNO(set_iff(fd, TUN_TEMPLATE, IFF_TUN, &ifr2), EEXIST);
+
+	// A single-queue device can only have one file attached.
+	TEST_ERRNO(set_iff(fd2, name, IFF_TUN | IFF_NO_PI, &ifr2), EBUSY);
+	TEST_ERRNO(set_iff(fd2, name, IFF_TUN | IFF_NO_PI | IFF_TUN_EXCL,
+			   &ifr2),
+		   EBUSY);
+	TEST_ERRNO(set_iff(fd2, name, IFF_TAP | IFF_NO_PI, &ifr2), EINVAL);
+
+	// Non-persistent devices are deleted when the last file is closed.
+	TEST_SUCC(close(fd));
+	TEST_RES(find_link(name), _ret == 0);
+
+	TEST_SUCC(close(fd2));
+}
+END_TEST()
+
+FN_TEST(tun_persist)
+{
+	struct ifreq ifr;
+	char name[IFNAMSIZ];
+	int fd;
+
+	fd = open_tun();
+	TEST_SUCC(set_iff(fd, TUN_TEMPLATE, IFF_TUN, &ifr));
+	strcpy(name, ifr.ifr_name);
+	TEST_SUCC(ioctl(fd, TUNSETPERSIST, 1));
+	TEST_SUCC(close(fd));
+
+	// Persistent devices survive the last file being closed.
+	TEST_RES(find_link(name), _ret > 0);
+
+	fd = open_tun();
+	TEST_RES(set_iff(fd, name, IFF_TUN, &ifr),
+		 strcmp(ifr.ifr_name, name) == 0);
+	TEST_SUCC(ioctl(fd, TUNSETPERSIST, 0));
+	TEST_SUCC(close(fd));
+
+	TEST_RES(find_link(name), _ret == 0);
+}
+END_TEST()
+
+FN_TEST(tun_multi_queue)
+{
+	struct ifreq ifr;
+	char name[IFNAMSIZ];
+	char buf[64];
+	int fd, fd2, fd3;
+
+	fd = open_tun();
+	fd2 = open_tun();
+	fd3 = open_tun();
+
+	TEST_SUCC(set_iff(fd, TUN_TEMPLATE, IFF_TUN | IFF_MULTI_QUEUE, &ifr));
+	strcpy(name, ifr.ifr_name);
+	TEST_SUCC(set_iff(fd2, name, IFF_TUN | IFF_MULTI_QUEUE, &ifr));
+	TEST_ERRNO(set_iff(fd3, name, IFF_TUN, &ifr), EINVAL);
+
+	// Detached queues still belong to the device.
+	TEST_SUCC(set_queue(fd2, IFF_DETACH_QUEUE));
+	TEST_ERRNO(set_queue(fd2, IFF_DETACH_QUEUE), EINVAL);
+	TEST_ERRNO(set_iff(fd2, name, IFF_TUN | IFF_MULTI_QUEUE, &ifr), EEXIST);
+	TEST_RES(ioctl(fd2, TUNGETIFF, &ifr), strcmp(ifr.ifr_name, name) == 0);
+	TEST_ERRNO(read(fd2, buf, sizeof(buf)), EAGAIN);
+
+	// Detached queues keep the device alive.
+	TEST_SUCC(close(fd));
+	TEST_RES(find_link(name), _ret > 0);
+
+	TEST_SUCC(set_queue(fd2, IFF_ATTACH_QUEUE));
+	TEST_ERRNO(set_queue(fd2, IFF_ATTACH_QUEUE), EINVAL);
+	TEST_ERRNO(read(fd2, buf, sizeof(buf)), EAGAIN);
+
+	TEST_SUCC(close(fd2));
+	TEST_RES(find_link(name), _ret == 0);
+
+	TEST_SUCC(close(fd3));
+}
+END_TEST()
+
+FN_TEST(tun_write_invalid)
+{
+	struct ifreq ifr;
+	char buf[64];
+	int fd;
+
+	fd = open_tun();
+	TEST_SUCC(set_iff(fd, TUN_TEMPLATE, IFF_TUN | IFF_NO_PI, &ifr));
+	TEST_SUCC(set_link_up(find_link(ifr.ifr_name)));
+
+	// The IP version must be 4 or 6.
+	memset(buf, 0, sizeof(buf));
+	TEST_ERRNO(write(fd, buf, sizeof(buf)), EINVAL);
+
+	TEST_SUCC(close(fd));
+}
+END_TEST()
+
+FN_TEST(tap_no_pi)
+{
+	struct test_frame frame, recv_frame;
+	struct ifreq ifr;
+	char buf[ETH_FRAME_LEN];
+	int fd, sk, index;
+
+	fd = open_tun();
+	TEST_SUCC(set_iff(fd, TAP_TEMPLATE, IFF_TAP | IFF_NO_PI, &ifr));
+	index = TEST_RES(find_link(ifr.ifr_name), _ret > 0);
+	TEST_SUCC(set_link_up(index));
+
+	sk = new_packet_socket(index);
+	fill_frame(&frame);
+
+	// An Ethernet frame must contain at least the header.
+	TEST_ERRNO(write(fd, &frame, ETH_HLEN - 1), EINVAL);
+
+	// Frames written to the file are received by the interface.
+	TEST_RES(write(fd, &frame, sizeof(frame)), _ret == sizeof(frame));
+	TEST_RES(recv(sk, &recv_frame, sizeof(recv_frame), 0),
+		 _ret == sizeof(frame) &&
+			 memcmp(&recv_frame, &frame, sizeof(frame)) == 0);
+
+	// Frames sent by the interface are read from the file.
+	TEST_RES(send(sk, &frame, sizeof(frame), 0), _ret == sizeof(frame));
+	TEST_RES(read_test_frame(fd, buf, sizeof(buf), 0),
+		 _ret == sizeof(frame) &&
+			 memcmp(buf, &frame, sizeof(frame)) == 0);
+
+	TEST_SUCC(close(sk));
+	TEST_SUCC(close(fd));
+}
+END_TEST()
+
+FN_TEST(tap_pi)
+{
+	struct {
+		struct tun_pi pi;
+		struct test_frame frame;
+	} __attribute__((packed)) packet, recv_packet;
+	struct test_frame recv_frame;
+	struct ifreq ifr;
+	int fd, sk, index;
+
+	fd = open_tun();
+	TEST_SUCC(set_iff(fd, TAP_TEMPLATE, IFF_TAP, &ifr));
+	index = TEST_RES(find_link(ifr.ifr_name), _ret > 0);
+	TEST_SUCC(set_link_up(index));
+
+	sk = new_packet_socket(index);
+	memset(&packet.pi, 0, sizeof(packet.pi));
+	packet.pi.proto = htons(TEST_PROTO);
+	fill_frame(&packet.frame);
+
+	// The packet information is stripped from written frames.
+	TEST_RES(write(fd, &packet, sizeof(packet)), _ret == sizeof(packet));
+	TEST_RES(recv(sk, &recv_frame, sizeof(recv_frame), 0),
+		 _ret == sizeof(recv_frame) &&
+			 memcmp(&recv_frame, &packet.frame,
+				sizeof(recv_frame)) == 0);
+
+	// The packet information is prepended to read frames.
+	TEST_RES(send(sk, &packet.frame, sizeof(packet.frame), 0),
+		 _ret == sizeof(packet.frame));
+	TEST_RES(read_test_frame(fd, (char *)&recv_packet, sizeof(recv_packet),
+				 sizeof(struct tun_pi)),
+		 _ret == sizeof(recv_packet) && recv_packet.pi.flags == 0 &&
+			 recv_packet.pi.proto == htons(TEST_PROTO) &&
+			 memcmp(&recv_packet.frame, &packet.frame,
+				sizeof(packet.frame)) == 0);
+
+	// Truncated frames are marked in the packet information.
+	TEST_RES(send(sk, &packet.frame, sizeof(packet.frame), 0),
+		 _ret == sizeof(packet.frame));
+	TEST_RES(read_test_frame(fd, (char *)&recv_packet,
+				 sizeof(struct tun_pi) + ETH_HLEN,
+				 sizeof(struct tun_pi)),
+		 _ret == sizeof(struct tun_pi) + ETH_HLEN &&
+			 recv_packet.pi.flags == TUN_PKT_STRIP);
+
+	TEST_SUCC(close(sk));
+	TEST_SUCC(close(fd));
+}
+END_TEST()
+
+FN_TEST(need_cap_net_admin)
+{
+	struct ifreq ifr;
+	int status;
+	pid_t pid;
+	int fd;
+
+	fd = open_tun();
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		// Dropping the root privileges clears the effective capabilities.
+		if (setuid(65534) < 0)
+			_exit(EXIT_FAILURE);
+		if (set_iff(fd, TUN_TEMPLATE, IFF_TUN, &ifr) >= 0 ||
+		    errno != EPERM)
+			_exit(EXIT_FAILURE);
+		_exit(EXIT_SUCCESS);
+	}
+
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFEXITED(status) && WEXITSTATUS(status) == 0);
+
+	TEST_SUCC(close(fd));
+}
+END_TEST()
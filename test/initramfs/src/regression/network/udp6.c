@@ -0,0 +1,351 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <unistd.h>
+#include <sys/socket.h>
+#include <netinet/in.h>
+#include <arpa/inet.h>
+
+#include "../common/test.h"
+
+#define V6_PORT htons(0x1236)
+#define MAPPED_PORT htons(0x1237)
+#define V4_PORT htons(0x1238)
+
+static struct sockaddr_in6 v6_addr;
+static struct sockaddr_in6 mapped_addr;
+static struct sockaddr_in v4_addr;
+
+static int sk_v6;
+static int sk_mapped;
+static int sk_v4;
+
+FN_SETUP(addrs)
+{
+	v6_addr.sin6_family = AF_INET6;
+	v6_addr.sin6_port = V6_PORT;
+	v6_addr.sin6_addr = in6addr_loopback;
+
+	mapped_addr.sin6_family = AF_INET6;
+	mapped_addr.sin6_port = MAPPED_PORT;
+	CHECK_WITH(inet_pton(AF_INET6, "::ffff:127.0.0.1",
+			     &mapped_addr.sin6_addr),
+		   _ret == 1);
+
+	v4_addr.sin_family = AF_INET;
+	v4_addr.sin_port = V4_PORT;
+	v4_addr.sin_addr.s_addr = htonl(INADDR_LOOPBACK);
+}
+END_SETUP()
+
+FN_SETUP(sockets)
+{
+	sk_v6 = CHECK(socket(AF_INET6, SOCK_DGRAM | SOCK_NONBLOCK, 0));
+	CHECK(bind(sk_v6, (struct sockaddr *)&v6_addr, sizeof(v6_addr)));
+
+	sk_mapped = CHECK(socket(AF_INET6, SOCK_DGRAM | SOCK_NONBLOCK, 0));
+	CHECK(bind(sk_mapped, (struct sockaddr *)&mapped_addr,
+		   sizeof(mapped_addr)));
+
+	sk_v4 = CHECK(socket(AF_INET, SOCK_DGRAM | SOCK_NONBLOCK, 0));
+	CHECK(bind(sk_v4, (struct sockaddr *)&v4_addr, sizeof(v4_addr)));
+}
+END_SETUP()
+
+FN_TEST(getsockname_unbound)
+{
+	struct sockaddr_in6 saddr;
+	socklen_t addrlen = sizeof(saddr);
+	int sk;
+
+	sk = TEST_SUCC(socket(AF_INET6, SOCK_DGRAM, 0));
+	TEST_RES(getsockname(sk, (struct sockaddr *)&saddr, &addrlen),
+		 addrlen == sizeof(saddr) && saddr.sin6_family == AF_INET6 &&
+			 saddr.sin6_port == 0 &&
+			 IN6_IS_ADDR_UNSPECIFIED(&saddr.sin6_addr));
+	TEST_SUCC(close(sk));
+}
+END_TEST()
+
+FN_TEST(bind_family_mismatch)
+{
+	int sk;
+
+	sk = TEST_SUCC(socket(AF_INET6, SOCK_DGRAM, 0));
+	TEST_ERRNO(bind(sk, (struct sockaddr *)&v4_addr, sizeof(v4_addr)),
+		   EINVAL);
+	TEST_SUCC(close(sk));
+
+	sk = TEST_SUCC(socket(AF_INET, SOCK_DGRAM, 0));
+	TEST_ERRNO(bind(sk, (struct sockaddr *)&v6_addr, sizeof(v6_addr)),
+		   EAFNOSUPPORT);
+	TEST_SUCC(close(sk));
+}
+END_TEST()
+
+FN_TEST(getsockname_bound)
+{
+	struct sockaddr_in6 saddr;
+	socklen_t addrlen = sizeof(saddr);
+
+	TEST_RES(getsockname(sk_v6, (struct sockaddr *)&saddr, &addrlen),
+		 addrlen == sizeof(saddr) && saddr.sin6_family == AF_INET6 &&
+			 saddr.sin6_port == V6_PORT &&
+			 IN6_IS_ADDR_LOOPBACK(&saddr.sin6_addr));
+
+	TEST_RES(getsockname(sk_mapped, (struct sockaddr *)&saddr, &addrlen),
+		 addrlen == sizeof(saddr) && saddr.sin6_family == AF_INET6 &&
+			 saddr.sin6_port == MAPPED_PORT &&
+			 IN6_ARE_ADDR_EQUAL(&saddr.sin6_addr,
+					    &mapped_addr.sin6_addr));
+}
+END_TEST()
+
+FN_TEST(send_recv_v6)
+{
+	struct sockaddr_in6 saddr;
+	socklen_t addrlen = sizeof(saddr);
+	char buf[16];
+	int sk;
+
+	sk = TEST_SUCC(socket(AF_INET6, SOCK_DGRAM, 0));
+	TEST_RES(sendto(sk, "hello", 5, 0, (struct sockaddr *)&v6_addr,
+			sizeof(v6_addr)),
+		 _ret == 5);
+
+	TEST_RES(recvfrom(sk_v6, buf, sizeof(buf), 0,
+			  (struct sockaddr *)&saddr, &addrlen),
+		 _ret == 5 && memcmp(buf, "hello", 5) == 0 &&
+			 addrlen == sizeof(saddr) &&
+			 saddr.sin6_family == AF_INET6 &&
+			 IN6_IS_ADDR_LOOPBACK(&saddr.sin6_addr));
+
+	TEST_RES(sendto(sk_v6, "world", 5, 0, (struct sockaddr *)&saddr,
+			addrlen),
+		 _ret == 5);
+	TEST_RES(recv(sk, buf, sizeof(buf), 0),
+		 _ret == 5 && memcmp(buf, "world", 5) == 0);
+
+	TEST_SUCC(close(sk));
+}
+END_TEST()
+
+FN_TEST(send_recv_mapped)
+{
+	struct sockaddr_in6 saddr6;
+	struct sockaddr_in saddr4;
+	socklen_t addrlen;
+	char buf[16];
+
+	// IPv4 -> IPv6 (bound to an IPv4-mapped address)
+	TEST_ERRNO(sendto(sk_v4, "hello", 5, 0, (struct sockaddr *)&mapped_addr,
+			  sizeof(mapped_addr)),
+		   EAFNOSUPPORT);
+
+	v4_addr.sin_port = MAPPED_PORT;
+	TEST_RES(sendto(sk_v4, "hello", 5, 0, (struct sockaddr *)&v4_addr,
+			sizeof(v4_addr)),
+		 _ret == 5);
+	v4_addr.sin_port = V4_PORT;
+
+	addrlen = sizeof(saddr6);
+	TEST_RES(recvfrom(sk_mapped, buf, sizeof(buf), 0,
+			  (struct sockaddr *)&saddr6, &addrlen),
+		 _ret == 5 && memcmp(buf, "hello", 5) == 0 &&
+			 addrlen == sizeof(saddr6) &&
+			 saddr6.sin6_family == AF_INET6 &&
+			 saddr6.sin6_port == V4_PORT &&
+			 IN6_IS_ADDR_V4MAPPED(&saddr6.sin6_addr) &&
+			 saddr6.sin6_addr.s6_addr32[3] ==
+				 htonl(INADDR_LOOPBACK));
+
+	// IPv6 (to an IPv4-mapped address) -> IPv4
+	TEST_RES(sendto(sk_mapped, "world", 5, 0, (struct sockaddr *)&saddr6,
+			addrlen),
+		 _ret == 5);
+
+	addrlen = sizeof(saddr4);
+	TEST_RES(recvfrom(sk_v4, buf, sizeof(buf), 0,
+			  (struct sockaddr *)&saddr4, &addrlen),
+		 _ret == 5 && memcmp(buf, "world", 5) == 0 &&
+			 addrlen == sizeof(saddr4) &&
+			 saddr4.sin_family == AF_INET &&
+			 saddr4.sin_port == MAPPED_PORT);
+}
+END_TEST()
+
+FN_TEST(connect_mapped)
+{
+	struct sockaddr_in6 saddr;
+	socklen_t addrlen = sizeof(saddr);
+	char buf[16];
+	int sk;
+
+	mapped_addr.sin6_port = V4_PORT;
+
+	sk = TEST_SUCC(socket(AF_INET6, SOCK_DGRAM, 0));
+	TEST_SUCC(connect(sk, (struct sockaddr *)&mapped_addr,
+			  sizeof(mapped_addr)));
+	TEST_RES(getpeername(sk, (struct sockaddr *)&saddr, &addrlen),
+		 addrlen == sizeof(saddr) && saddr.sin6_family == AF_INET6 &&
+			 saddr.sin6_port == V4_PORT &&
+			 IN6_ARE_ADDR_EQUAL(&saddr.sin6_addr,
+					    &mapped_addr.sin6_addr));
+	TEST_RES(getsockname(sk, (struct sockaddr *)&saddr, &addrlen),
+		 addrlen == sizeof(saddr) && saddr.sin6_family == AF_INET6 &&
+			 IN6_IS_ADDR_V4MAPPED(&saddr.sin6_addr));
+
+	TEST_RES(send(sk, "hello", 5, 0), _ret == 5);
+	TEST_RES(recv(sk_v4, buf, sizeof(buf), 0),
+		 _ret == 5 && memcmp(buf, "hello", 5) == 0);
+
+	TEST_SUCC(close(sk));
+
+	mapped_addr.sin6_port = MAPPED_PORT;
+}
+END_TEST()
+
+FN_TEST(v6only)
+{
+	int sk;
+	int val;
+	socklen_t len = sizeof(val);
+
+	sk = TEST_SUCC(socket(AF_INET6, SOCK_DGRAM, 0));
+
+	TEST_RES(getsockopt(sk, IPPROTO_IPV6, IPV6_V6ONLY, &val, &len),
+		 len == sizeof(val) && val == 0);
+
+	val = 1;
+	TEST_SUCC(setsockopt(sk, IPPROTO_IPV6, IPV6_V6ONLY, &val, sizeof(val)));
+	TEST_RES(getsockopt(sk, IPPROTO_IPV6, IPV6_V6ONLY, &val, &len),
+		 len == sizeof(val) && val == 1);
+
+	TEST_ERRNO(bind(sk, (struct sockaddr *)&mapped_addr,
+			sizeof(mapped_addr)),
+		   EINVAL);
+	TEST_ERRNO(connect(sk, (struct sockaddr *)&mapped_addr,
+			   sizeof(mapped_addr)),
+		   ENETUNREACH);
+	TEST_ERRNO(sendto(sk, "hello", 5, 0, (struct sockaddr *)&mapped_addr,
+			  sizeof(mapped_addr)),
+		   ENETUNREACH);
+
+	TEST_SUCC(connect(sk, (struct sockaddr *)&v6_addr, sizeof(v6_addr)));
+	val = 0;
+	TEST_ERRNO(setsockopt(sk, IPPROTO_IPV6, IPV6_V6ONLY, &val,
+			      sizeof(val)),
+		   EINVAL);
+
+	TEST_SUCC(close(sk));
+}
+END_TEST()
+
+FN_TEST(unicast_hops)
+{
+	int val;
+	socklen_t len = sizeof(val);
+
+	TEST_RES(getsockopt(sk_v6, IPPROTO_IPV6, IPV6_UNICAST_HOPS, &val, &len),
+		 len == sizeof(val) && val == 64);
+
+	val = 0;
+	TEST_SUCC(setsockopt(sk_v6, IPPROTO_IPV6, IPV6_UNICAST_HOPS, &val,
+			     sizeof(val)));
+	TEST_RES(getsockopt(sk_v6, IPPROTO_IPV6, IPV6_UNICAST_HOPS, &val, &len),
+		 len == sizeof(val) && val == 0);
+
+	val = 255;
+	TEST_SUCC(setsockopt(sk_v6, IPPROTO_IPV6, IPV6_UNICAST_HOPS, &val,
+			     sizeof(val)));
+	TEST_RES(getsockopt(sk_v6, IPPROTO_IPV6, IPV6_UNICAST_HOPS, &val, &len),
+		 len == sizeof(val) && val == 255);
+
+	val = 256;
+	TEST_ERRNO(setsockopt(sk_v6, IPPROTO_IPV6, IPV6_UNICAST_HOPS, &val,
+			      sizeof(val)),
+		   EINVAL);
+	val = -2;
+	TEST_ERRNO(setsockopt(sk_v6, IPPROTO_IPV6, IPV6_UNICAST_HOPS, &val,
+			      sizeof(val)),
+		   EINVAL);
+
+	val = -1;
+	TEST_SUCC(setsockopt(sk_v6, IPPROTO_IPV6, IPV6_UNICAST_HOPS, &val,
+			     sizeof(val)));
+	TEST_RES(getsockopt(sk_v6, IPPROTO_IPV6, IPV6_UNICAST_HOPS, &val, &len),
+		 len == sizeof(val) && val == 64);
+}
+END_TEST()
+
+static int recv_pktinfo(int sk, struct in6_pktinfo *pktinfo)
+{
+	char buf[16];
+	char control[CMSG_SPACE(sizeof(*pktinfo))];
+	struct iovec iov = { .iov_base = buf, .iov_len = sizeof(buf) };
+	struct msghdr msg = {
+		.msg_iov = &iov,
+		.msg_iovlen = 1,
+		.msg_control = control,
+		.msg_controllen = sizeof(control),
+	};
+	struct cmsghdr *cmsg;
+	ssize_t res;
+
+	res = recvmsg(sk, &msg, 0);
+	if (res < 0)
+		return -1;
+
+	cmsg = CMSG_FIRSTHDR(&msg);
+	if (cmsg == NULL || cmsg->cmsg_level != IPPROTO_IPV6 ||
+	    cmsg->cmsg_type != IPV6_PKTINFO ||
+	    cmsg->cmsg_len != CMSG_LEN(sizeof(*pktinfo))) {
+		errno = EINVAL;
+		return -1;
+	}
+	memcpy(pktinfo, CMSG_DATA(cmsg), sizeof(*pktinfo));
+
+	return res;
+}
+
+FN_TEST(recvpktinfo)
+{
+	struct in6_pktinfo pktinfo;
+	int val;
+	socklen_t len = sizeof(val);
+
+	TEST_RES(getsockopt(sk_v6, IPPROTO_IPV6, IPV6_RECVPKTINFO, &val, &len),
+		 len == sizeof(val) && val == 0);
+	val = 1;
+	TEST_SUCC(setsockopt(sk_v6, IPPROTO_IPV6, IPV6_RECVPKTINFO, &val,
+			     sizeof(val)));
+	TEST_SUCC(setsockopt(sk_mapped, IPPROTO_IPV6, IPV6_RECVPKTINFO, &val,
+			     sizeof(val)));
+
+	TEST_RES(sendto(sk_v6, "hello", 5, 0, (struct sockaddr *)&v6_addr,
+			sizeof(v6_addr)),
+		 _ret == 5);
+	TEST_RES(recv_pktinfo(sk_v6, &pktinfo),
+		 _ret == 5 && IN6_IS_ADDR_LOOPBACK(&pktinfo.ipi6_addr) &&
+			 pktinfo.ipi6_ifindex > 0);
+
+	v4_addr.sin_port = MAPPED_PORT;
+	TEST_RES(sendto(sk_v4, "world", 5, 0, (struct sockaddr *)&v4_addr,
+			sizeof(v4_addr)),
+		 _ret == 5);
+	v4_addr.sin_port = V4_PORT;
+	TEST_RES(recv_pktinfo(sk_mapped, &pktinfo),
+		 _ret == 5 && IN6_ARE_ADDR_EQUAL(&pktinfo.ipi6_addr,
+						 &mapped_addr.sin6_addr) &&
+			 pktinfo.ipi6_ifindex > 0);
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(sk_v6));
+	CHECK(close(sk_mapped));
+	CHECK(close(sk_v4));
+}
+END_SETUP()
@@ -0,0 +1,417 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+#include <poll.h>
+#include <stdint.h>
+#include <string.h>
+#include <time.h>
+#include <unistd.h>
+#include <sys/time.h>
+#include <netinet/in.h>
+#include <netinet/udp.h>
+#include <arpa/inet.h>
+#include <linux/errqueue.h>
+#include "../common/test.h"
+
+#ifndef UDP_SEGMENT
+#define UDP_SEGMENT 103
+#endif
+
+#define LOCAL_ADDR "127.0.0.1"
+#define UNICAST_ADDR "10.0.0.1"
+#define PORT 16001
+#define CLOSED_PORT 16002
+
+#define MESSAGE "Hello from sender"
+
+static int sender;
+static int receiver;
+
+static struct sockaddr_in receiver_addr;
+
+static char cbuf[512];
+static struct iovec iov;
+static struct msghdr msg;
+
+FN_SETUP(create_and_bind)
+{
+	struct sockaddr_in addr;
+
+	sender = CHECK(socket(AF_INET, SOCK_DGRAM, 0));
+	receiver = CHECK(socket(AF_INET, SOCK_DGRAM, 0));
+
+	addr.sin_family = AF_INET;
+	addr.sin_port = htons(0);
+	CHECK(inet_aton(LOCAL_ADDR, &addr.sin_addr));
+	CHECK(bind(sender, (struct sockaddr *)&addr, sizeof(addr)));
+
+	receiver_addr.sin_family = AF_INET;
+	receiver_addr.sin_port = htons(PORT);
+	CHECK(inet_aton(LOCAL_ADDR, &receiver_addr.sin_addr));
+	CHECK(bind(receiver, (struct sockaddr *)&receiver_addr,
+		   sizeof(receiver_addr)));
+}
+END_SETUP()
+
+static void init_msg(void *buf, size_t len, struct sockaddr_in *addr)
+{
+	iov.iov_base = buf;
+	iov.iov_len = len;
+
+	memset(&msg, 0, sizeof(msg));
+	msg.msg_name = addr;
+	msg.msg_namelen = addr ? sizeof(*addr) : 0;
+	msg.msg_iov = &iov;
+	msg.msg_iovlen = 1;
+	msg.msg_control = cbuf;
+	msg.msg_controllen = sizeof(cbuf);
+	memset(cbuf, 0, sizeof(cbuf));
+}
+
+static int get_cmsg(int level, int type, void *data, size_t len)
+{
+	struct cmsghdr *cmsg;
+
+	for (cmsg = CMSG_FIRSTHDR(&msg); cmsg; cmsg = CMSG_NXTHDR(&msg, cmsg)) {
+		if (cmsg->cmsg_level != level || cmsg->cmsg_type != type)
+			continue;
+
+		if (cmsg->cmsg_len != CMSG_LEN(len)) {
+			errno = EINVAL;
+			return -1;
+		}
+		memcpy(data, CMSG_DATA(cmsg), len);
+		return 0;
+	}
+
+	errno = ENOMSG;
+	return -1;
+}
+
+FN_TEST(recv_options)
+{
+	int val;
+	socklen_t len;
+
+	val = 1;
+	TEST_SUCC(setsockopt(receiver, IPPROTO_IP, IP_PKTINFO, &val,
+			     sizeof(val)));
+	TEST_SUCC(setsockopt(receiver, IPPROTO_IP, IP_RECVTTL, &val,
+			     sizeof(val)));
+	TEST_SUCC(setsockopt(receiver, IPPROTO_IP, IP_RECVTOS, &val,
+			     sizeof(val)));
+
+	len = sizeof(val);
+	TEST_RES(getsockopt(receiver, IPPROTO_IP, IP_PKTINFO, &val, &len),
+		 val == 1 && len == sizeof(val));
+	len = sizeof(val);
+	TEST_RES(getsockopt(receiver, IPPROTO_IP, IP_RECVTTL, &val, &len),
+		 val == 1 && len == sizeof(val));
+	len = sizeof(val);
+	TEST_RES(getsockopt(receiver, IPPROTO_IP, IP_RECVTOS, &val, &len),
+		 val == 1 && len == sizeof(val));
+}
+END_TEST()
+
+FN_TEST(timestamp_options)
+{
+	int val;
+	socklen_t len;
+
+	val = 1;
+	TEST_SUCC(setsockopt(receiver, SOL_SOCKET, SO_TIMESTAMPNS, &val,
+			     sizeof(val)));
+	len = sizeof(val);
+	TEST_RES(getsockopt(receiver, SOL_SOCKET, SO_TIMESTAMPNS, &val, &len),
+		 val == 1 && len == sizeof(val));
+
+	// Enabling one format disables the other.
+	val = 1;
+	TEST_SUCC(setsockopt(receiver, SOL_SOCKET, SO_TIMESTAMP, &val,
+			     sizeof(val)));
+	len = sizeof(val);
+	TEST_RES(getsockopt(receiver, SOL_SOCKET, SO_TIMESTAMPNS, &val, &len),
+		 val == 0 && len == sizeof(val));
+	len = sizeof(val);
+	TEST_RES(getsockopt(receiver, SOL_SOCKET, SO_TIMESTAMP, &val, &len),
+		 val == 1 && len == sizeof(val));
+}
+END_TEST()
+
+FN_TEST(recv_cmsgs)
+{
+	char buf[64];
+	struct timeval before, after;
+	struct in_pktinfo pktinfo;
+	struct timeval tv;
+	int ttl;
+	uint8_t tos;
+
+	CHECK(gettimeofday(&before, NULL));
+	TEST_RES(sendto(sender, MESSAGE, sizeof(MESSAGE), 0,
+			(struct sockaddr *)&receiver_addr,
+			sizeof(receiver_addr)),
+		 _ret == sizeof(MESSAGE));
+
+	init_msg(buf, sizeof(buf), NULL);
+	TEST_RES(recvmsg(receiver, &msg, MSG_DONTWAIT),
+		 _ret == sizeof(MESSAGE) && memcmp(buf, MESSAGE, _ret) == 0);
+	CHECK(gettimeofday(&after, NULL));
+
+	TEST_RES(get_cmsg(IPPROTO_IP, IP_PKTINFO, &pktinfo, sizeof(pktinfo)),
+		 pktinfo.ipi_ifindex == 1 &&
+			 pktinfo.ipi_spec_dst.s_addr == htonl(INADDR_LOOPBACK) &&
+			 pktinfo.ipi_addr.s_addr == htonl(INADDR_LOOPBACK));
+	TEST_RES(get_cmsg(IPPROTO_IP, IP_TTL, &ttl, sizeof(ttl)), ttl == 64);
+	TEST_RES(get_cmsg(IPPROTO_IP, IP_TOS, &tos, sizeof(tos)), tos == 0);
+
+	// The timestamp has a coarse granularity, so allow some deviations.
+	before.tv_sec -= 1;
+	after.tv_sec += 1;
+	TEST_RES(get_cmsg(SOL_SOCKET, SO_TIMESTAMP, &tv, sizeof(tv)),
+		 !timercmp(&tv, &before, <) && !timercmp(&tv, &after, >));
+}
+END_TEST()
+
+FN_TEST(recv_timestampns)
+{
+	char buf[64];
+	struct timespec now, ts;
+	struct timeval tv;
+	int val;
+
+	val = 1;
+	TEST_SUCC(setsockopt(receiver, SOL_SOCKET, SO_TIMESTAMPNS, &val,
+			     sizeof(val)));
+
+	TEST_RES(sendto(sender, MESSAGE, sizeof(MESSAGE), 0,
+			(struct sockaddr *)&receiver_addr,
+			sizeof(receiver_addr)),
+		 _ret == sizeof(MESSAGE));
+
+	init_msg(buf, sizeof(buf), NULL);
+	TEST_RES(recvmsg(receiver, &msg, MSG_DONTWAIT),
+		 _ret == sizeof(MESSAGE));
+	CHECK(clock_gettime(CLOCK_REALTIME, &now));
+
+	TEST_ERRNO(get_cmsg(SOL_SOCKET, SO_TIMESTAMP, &tv, sizeof(tv)), ENOMSG);
+	TEST_RES(get_cmsg(SOL_SOCKET, SO_TIMESTAMPNS, &ts, sizeof(ts)),
+		 ts.tv_sec <= now.tv_sec && ts.tv_sec + 1 >= now.tv_sec);
+
+	// Disabling one format disables both.
+	val = 0;
+	TEST_SUCC(setsockopt(receiver, SOL_SOCKET, SO_TIMESTAMP, &val,
+			     sizeof(val)));
+	TEST_RES(getsockopt(receiver, SOL_SOCKET, SO_TIMESTAMPNS, &val,
+			    &(socklen_t){ sizeof(val) }),
+		 val == 0);
+}
+END_TEST()
+
+FN_TEST(recv_cmsgs_disabled)
+{
+	char buf[64];
+	int val;
+
+	val = 0;
+	TEST_SUCC(setsockopt(receiver, IPPROTO_IP, IP_PKTINFO, &val,
+			     sizeof(val)));
+	TEST_SUCC(setsockopt(receiver, IPPROTO_IP, IP_RECVTTL, &val,
+			     sizeof(val)));
+	TEST_SUCC(setsockopt(receiver, IPPROTO_IP, IP_RECVTOS, &val,
+			     sizeof(val)));
+
+	TEST_RES(sendto(sender, MESSAGE, sizeof(MESSAGE), 0,
+			(struct sockaddr *)&receiver_addr,
+			sizeof(receiver_addr)),
+		 _ret == sizeof(MESSAGE));
+
+	init_msg(buf, sizeof(buf), NULL);
+	TEST_RES(recvmsg(receiver, &msg, MSG_DONTWAIT),
+		 _ret == sizeof(MESSAGE) && msg.msg_controllen == 0);
+}
+END_TEST()
+
+static void init_pktinfo(const char *spec_dst, int ifindex)
+{
+	struct cmsghdr *cmsg;
+	struct in_pktinfo *pktinfo;
+
+	msg.msg_controllen = CMSG_SPACE(sizeof(struct in_pktinfo));
+	cmsg = CMSG_FIRSTHDR(&msg);
+	cmsg->cmsg_level = IPPROTO_IP;
+	cmsg->cmsg_type = IP_PKTINFO;
+	cmsg->cmsg_len = CMSG_LEN(sizeof(struct in_pktinfo));
+
+	pktinfo = (struct in_pktinfo *)CMSG_DATA(cmsg);
+	pktinfo->ipi_ifindex = ifindex;
+	CHECK(inet_aton(spec_dst, &pktinfo->ipi_spec_dst));
+}
+
+FN_TEST(send_pktinfo)
+{
+	int sock;
+	char buf[64];
+	struct sockaddr_in addr;
+
+	sock = TEST_SUCC(socket(AF_INET, SOCK_DGRAM, 0));
+
+	init_msg(MESSAGE, sizeof(MESSAGE), &receiver_addr);
+	init_pktinfo(UNICAST_ADDR, 0);
+	TEST_ERRNO(sendmsg(sock, &msg, 0), ENETUNREACH);
+
+	init_msg(MESSAGE, sizeof(MESSAGE), &receiver_addr);
+	init_pktinfo(LOCAL_ADDR, 100);
+	TEST_ERRNO(sendmsg(sock, &msg, 0), ENODEV);
+
+	init_msg(MESSAGE, sizeof(MESSAGE), &receiver_addr);
+	init_pktinfo(LOCAL_ADDR, 1);
+	TEST_RES(sendmsg(sock, &msg, 0), _ret == sizeof(MESSAGE));
+
+	init_msg(buf, sizeof(buf), &addr);
+	TEST_RES(recvmsg(receiver, &msg, MSG_DONTWAIT),
+		 _ret == sizeof(MESSAGE) &&
+			 addr.sin_addr.s_addr == htonl(INADDR_LOOPBACK));
+
+	TEST_SUCC(close(sock));
+}
+END_TEST()
+
+static void init_udp_segment(uint16_t segment_size)
+{
+	struct cmsghdr *cmsg;
+
+	msg.msg_controllen = CMSG_SPACE(sizeof(uint16_t));
+	cmsg = CMSG_FIRSTHDR(&msg);
+	cmsg->cmsg_level = SOL_UDP;
+	cmsg->cmsg_type = UDP_SEGMENT;
+	cmsg->cmsg_len = CMSG_LEN(sizeof(uint16_t));
+	*(uint16_t *)CMSG_DATA(cmsg) = segment_size;
+}
+
+FN_TEST(send_udp_segment)
+{
+	char buf[300];
+	struct cmsghdr *cmsg;
+
+	memset(buf, 'a', sizeof(buf));
+
+	init_msg(buf, 10, &receiver_addr);
+	init_udp_segment(4);
+	TEST_RES(sendmsg(sender, &msg, 0), _ret == 10);
+
+	TEST_RES(recv(receiver, buf, sizeof(buf), MSG_DONTWAIT), _ret == 4);
+	TEST_RES(recv(receiver, buf, sizeof(buf), MSG_DONTWAIT), _ret == 4);
+	TEST_RES(recv(receiver, buf, sizeof(buf), MSG_DONTWAIT), _ret == 2);
+	TEST_ERRNO(recv(receiver, buf, sizeof(buf), MSG_DONTWAIT), EAGAIN);
+
+	// Too many segments
+	init_msg(buf, 200, &receiver_addr);
+	init_udp_segment(1);
+	TEST_ERRNO(sendmsg(sender, &msg, 0), EINVAL);
+
+	// Invalid length
+	init_msg(buf, 10, &receiver_addr);
+	init_udp_segment(4);
+	msg.msg_controllen = CMSG_SPACE(sizeof(int));
+	cmsg = CMSG_FIRSTHDR(&msg);
+	cmsg->cmsg_len = CMSG_LEN(sizeof(int));
+	TEST_ERRNO(sendmsg(sender, &msg, 0), EINVAL);
+
+	TEST_ERRNO(recv(receiver, buf, sizeof(buf), MSG_DONTWAIT), EAGAIN);
+}
+END_TEST()
+
+FN_TEST(recverr)
+{
+	int sock;
+	int val;
+	char buf[64];
+	struct sockaddr_in addr;
+	struct pollfd pfd;
+	struct {
+		struct sock_extended_err ee;
+		struct sockaddr_in offender;
+	} err;
+
+	sock = TEST_SUCC(socket(AF_INET, SOCK_DGRAM, 0));
+
+	init_msg(buf, sizeof(buf), &addr);
+	TEST_ERRNO(recvmsg(sock, &msg, MSG_ERRQUEUE), EAGAIN);
+
+	val = 1;
+	TEST_SUCC(setsockopt(sock, IPPROTO_IP, IP_RECVERR, &val, sizeof(val)));
+
+	addr = receiver_addr;
+	addr.sin_port = htons(CLOSED_PORT);
+	TEST_RES(sendto(sock, MESSAGE, sizeof(MESSAGE), 0,
+			(struct sockaddr *)&addr, sizeof(addr)),
+		 _ret == sizeof(MESSAGE));
+
+	pfd.fd = sock;
+	pfd.events = 0;
+	TEST_RES(poll(&pfd, 1, 1000), _ret == 1 && pfd.revents == POLLERR);
+
+	memset(&addr, 0, sizeof(addr));
+	init_msg(buf, sizeof(buf), &addr);
+	TEST_RES(recvmsg(sock, &msg, MSG_ERRQUEUE),
+		 _ret == sizeof(MESSAGE) && memcmp(buf, MESSAGE, _ret) == 0 &&
+			 msg.msg_namelen == sizeof(addr) &&
+			 addr.sin_port == htons(CLOSED_PORT) &&
+			 addr.sin_addr.s_addr == htonl(INADDR_LOOPBACK));
+
+	TEST_RES(get_cmsg(IPPROTO_IP, IP_RECVERR, &err, sizeof(err)),
+		 err.ee.ee_errno == ECONNREFUSED &&
+			 err.ee.ee_origin == SO_EE_ORIGIN_ICMP &&
+			 err.ee.ee_type == 3 && err.ee.ee_code == 3 &&
+			 err.offender.sin_family == AF_INET &&
+			 err.offender.sin_addr.s_addr ==
+				 htonl(INADDR_LOOPBACK));
+
+	init_msg(buf, sizeof(buf), &addr);
+	TEST_ERRNO(recvmsg(sock, &msg, MSG_ERRQUEUE), EAGAIN);
+
+	pfd.fd = sock;
+	pfd.events = POLLIN;
+	TEST_RES(poll(&pfd, 1, 0), _ret == 0);
+
+	TEST_SUCC(close(sock));
+}
+END_TEST()
+
+FN_TEST(recverr_disabled)
+{
+	int sock;
+	int val;
+	char buf[64];
+	struct sockaddr_in addr;
+
+	sock = TEST_SUCC(socket(AF_INET, SOCK_DGRAM, 0));
+
+	val = 1;
+	TEST_SUCC(setsockopt(sock, IPPROTO_IP, IP_RECVERR, &val, sizeof(val)));
+
+	addr = receiver_addr;
+	addr.sin_port = htons(CLOSED_PORT);
+	TEST_RES(sendto(sock, MESSAGE, sizeof(MESSAGE), 0,
+			(struct sockaddr *)&addr, sizeof(addr)),
+		 _ret == sizeof(MESSAGE));
+	TEST_SUCC(poll(&(struct pollfd){ .fd = sock }, 1, 1000));
+
+	// Disabling `IP_RECVERR` purges the error queue.
+	val = 0;
+	TEST_SUCC(setsockopt(sock, IPPROTO_IP, IP_RECVERR, &val, sizeof(val)));
+
+	init_msg(buf, sizeof(buf), &addr);
+	TEST_ERRNO(recvmsg(sock, &msg, MSG_ERRQUEUE), EAGAIN);
+
+	TEST_SUCC(close(sock));
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(sender));
+	CHECK(close(receiver));
+}
+END_SETUP()
@@ -461,3 +461,41 @@ FN_TEST(bind_tcp_and_udp_to_same_port)
 	TEST_SUCC(close(udp));
 }
 END_TEST()
+
+FN_TEST(connection_refused)
+{
+	int sk;
+	int err;
+	socklen_t errlen = sizeof(err);
+	char buf[1] = { 'z' };
+	struct pollfd pfd = { .events = POLLIN };
+
+	sk = TEST_SUCC(socket(PF_INET, SOCK_DGRAM, 0));
+
+	// No socket is bound to the port.
+	sk_addr.sin_port = htons(8084);
+	TEST_SUCC(connect(sk, (struct sockaddr *)&sk_addr, sizeof(sk_addr)));
+
+	TEST_RES(send(sk, buf, 1, 0), _ret == 1);
+	pfd.fd = sk;
+	TEST_RES(poll(&pfd, 1, 1000), _ret == 1 && pfd.revents == POLLERR);
+
+	TEST_RES(getsockopt(sk, SOL_SOCKET, SO_ERROR, &err, &errlen),
+		 errlen == sizeof(err) && err == ECONNREFUSED);
+	TEST_RES(getsockopt(sk, SOL_SOCKET, SO_ERROR, &err, &errlen),
+		 errlen == sizeof(err) && err == 0);
+	TEST_RES(poll(&pfd, 1, 0), _ret == 0);
+
+	// The error is also reported by the next `recv` or `send`.
+	TEST_RES(send(sk, buf, 1, 0), _ret == 1);
+	TEST_SUCC(poll(&pfd, 1, 1000));
+	TEST_ERRNO(recv(sk, buf, 1, MSG_DONTWAIT), ECONNREFUSED);
+	TEST_ERRNO(recv(sk, buf, 1, MSG_DONTWAIT), EAGAIN);
+
+	TEST_RES(send(sk, buf, 1, 0), _ret == 1);
+	TEST_SUCC(poll(&pfd, 1, 1000));
+	TEST_ERRNO(send(sk, buf, 1, 0), ECONNREFUSED);
+
+	TEST_SUCC(close(sk));
+}
+END_TEST()
@@ -0,0 +1,275 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+#include <string.h>
+#include <unistd.h>
+#include <netinet/in.h>
+#include <arpa/inet.h>
+#include "../common/test.h"
+
+#define LOCAL_ADDR "127.0.0.1"
+#define GROUP_ADDR "224.0.0.251"
+#define OTHER_GROUP_ADDR "224.0.0.252"
+#define UNICAST_ADDR "10.0.0.1"
+#define PORT 15353
+
+// FIXME: Asterinas cannot support binding to the unspecified address now, so the receiver binds
+// to the loopback address instead. Linux only delivers multicast packets to sockets bound to the
+// unspecified address or the group address.
+#ifdef __asterinas__
+#define RECEIVER_BIND_ADDR LOCAL_ADDR
+#else
+#define RECEIVER_BIND_ADDR "0.0.0.0"
+#endif
+
+#define MESSAGE "Hello from multicast"
+
+static int sender;
+static int receiver;
+
+static struct sockaddr_in group_addr;
+static struct ip_mreq mreq;
+static struct ip_mreqn mreqn;
+
+FN_SETUP(create_and_bind)
+{
+	struct sockaddr_in addr;
+
+	sender = CHECK(socket(AF_INET, SOCK_DGRAM, 0));
+	receiver = CHECK(socket(AF_INET, SOCK_DGRAM, 0));
+
+	addr.sin_family = AF_INET;
+	addr.sin_port = htons(0);
+	CHECK(inet_aton(LOCAL_ADDR, &addr.sin_addr));
+	CHECK(bind(sender, (struct sockaddr *)&addr, sizeof(addr)));
+
+	addr.sin_port = htons(PORT);
+	CHECK(inet_aton(RECEIVER_BIND_ADDR, &addr.sin_addr));
+	CHECK(bind(receiver, (struct sockaddr *)&addr, sizeof(addr)));
+
+	group_addr.sin_family = AF_INET;
+	group_addr.sin_port = htons(PORT);
+	CHECK(inet_aton(GROUP_ADDR, &group_addr.sin_addr));
+
+	CHECK(inet_aton(GROUP_ADDR, &mreq.imr_multiaddr));
+	CHECK(inet_aton(LOCAL_ADDR, &mreq.imr_interface));
+
+	CHECK(inet_aton(GROUP_ADDR, &mreqn.imr_multiaddr));
+	mreqn.imr_address.s_addr = htonl(INADDR_ANY);
+	mreqn.imr_ifindex = 1;
+}
+END_SETUP()
+
+FN_TEST(default_options)
+{
+	int val;
+	socklen_t len;
+
+	len = sizeof(val);
+	TEST_RES(getsockopt(receiver, IPPROTO_IP, IP_MULTICAST_TTL, &val, &len),
+		 val == 1 && len == sizeof(val));
+
+	len = sizeof(val);
+	TEST_RES(getsockopt(receiver, IPPROTO_IP, IP_MULTICAST_LOOP, &val,
+			    &len),
+		 val == 1 && len == sizeof(val));
+
+	len = sizeof(val);
+	TEST_RES(getsockopt(receiver, IPPROTO_IP, IP_MULTICAST_ALL, &val, &len),
+		 val == 1 && len == sizeof(val));
+}
+END_TEST()
+
+FN_TEST(set_options)
+{
+	int val;
+	unsigned char byte;
+	socklen_t len;
+
+	val = 256;
+	TEST_ERRNO(setsockopt(sender, IPPROTO_IP, IP_MULTICAST_TTL, &val,
+			      sizeof(val)),
+		   EINVAL);
+
+	val = -1;
+	TEST_SUCC(setsockopt(sender, IPPROTO_IP, IP_MULTICAST_TTL, &val,
+			     sizeof(val)));
+	len = sizeof(val);
+	TEST_RES(getsockopt(sender, IPPROTO_IP, IP_MULTICAST_TTL, &val, &len),
+		 val == 1 && len == sizeof(val));
+
+	byte = 5;
+	TEST_SUCC(setsockopt(sender, IPPROTO_IP, IP_MULTICAST_TTL, &byte,
+			     sizeof(byte)));
+	len = sizeof(val);
+	TEST_RES(getsockopt(sender, IPPROTO_IP, IP_MULTICAST_TTL, &val, &len),
+		 val == 5 && len == sizeof(val));
+	len = sizeof(byte);
+	TEST_RES(getsockopt(sender, IPPROTO_IP, IP_MULTICAST_TTL, &byte, &len),
+		 byte == 5 && len == sizeof(byte));
+
+	val = 1;
+	TEST_SUCC(setsockopt(sender, IPPROTO_IP, IP_MULTICAST_TTL, &val,
+			     sizeof(val)));
+}
+END_TEST()
+
+FN_TEST(multicast_if)
+{
+	struct in_addr addr;
+	socklen_t len;
+
+	CHECK(inet_aton(UNICAST_ADDR, &addr));
+	TEST_ERRNO(setsockopt(sender, IPPROTO_IP, IP_MULTICAST_IF, &addr,
+			      sizeof(addr)),
+		   EADDRNOTAVAIL);
+
+	CHECK(inet_aton(LOCAL_ADDR, &addr));
+	TEST_SUCC(setsockopt(sender, IPPROTO_IP, IP_MULTICAST_IF, &addr,
+			     sizeof(addr)));
+
+	memset(&addr, 0, sizeof(addr));
+	len = sizeof(addr);
+	TEST_RES(getsockopt(sender, IPPROTO_IP, IP_MULTICAST_IF, &addr, &len),
+		 addr.s_addr == htonl(INADDR_LOOPBACK) && len == sizeof(addr));
+}
+END_TEST()
+
+FN_TEST(invalid_membership)
+{
+	struct ip_mreq bad_mreq;
+
+	TEST_ERRNO(setsockopt(receiver, IPPROTO_IP, IP_ADD_MEMBERSHIP, &mreq,
+			      sizeof(mreq) - 1),
+		   EINVAL);
+
+	bad_mreq = mreq;
+	CHECK(inet_aton(UNICAST_ADDR, &bad_mreq.imr_multiaddr));
+	TEST_ERRNO(setsockopt(receiver, IPPROTO_IP, IP_ADD_MEMBERSHIP,
+			      &bad_mreq, sizeof(bad_mreq)),
+		   EINVAL);
+
+	TEST_ERRNO(setsockopt(receiver, IPPROTO_IP, IP_DROP_MEMBERSHIP, &mreq,
+			      sizeof(mreq)),
+		   EADDRNOTAVAIL);
+
+	TEST_ERRNO(getsockopt(receiver, IPPROTO_IP, IP_ADD_MEMBERSHIP, &mreq,
+			      &(socklen_t){ sizeof(mreq) }),
+		   ENOPROTOOPT);
+}
+END_TEST()
+
+FN_TEST(join_and_receive)
+{
+	char buf[64];
+
+	TEST_SUCC(setsockopt(receiver, IPPROTO_IP, IP_ADD_MEMBERSHIP, &mreq,
+			     sizeof(mreq)));
+	TEST_ERRNO(setsockopt(receiver, IPPROTO_IP, IP_ADD_MEMBERSHIP, &mreqn,
+			      sizeof(mreqn)),
+		   EADDRINUSE);
+
+	TEST_RES(sendto(sender, MESSAGE, sizeof(MESSAGE), 0,
+			(struct sockaddr *)&group_addr, sizeof(group_addr)),
+		 _ret == sizeof(MESSAGE));
+	TEST_RES(recv(receiver, buf, sizeof(buf), MSG_DONTWAIT),
+		 _ret == sizeof(MESSAGE) && memcmp(buf, MESSAGE, _ret) == 0);
+}
+END_TEST()
+
+FN_TEST(multicast_loop)
+{
+	int val;
+	char buf[64];
+
+	val = 0;
+	TEST_SUCC(setsockopt(sender, IPPROTO_IP, IP_MULTICAST_LOOP, &val,
+			     sizeof(val)));
+
+	// The loopback interface always delivers the packets back, regardless of
+	// `IP_MULTICAST_LOOP`.
+	TEST_RES(sendto(sender, MESSAGE, sizeof(MESSAGE), 0,
+			(struct sockaddr *)&group_addr, sizeof(group_addr)),
+		 _ret == sizeof(MESSAGE));
+	TEST_RES(recv(receiver, buf, sizeof(buf), MSG_DONTWAIT),
+		 _ret == sizeof(MESSAGE));
+
+	val = 1;
+	TEST_SUCC(setsockopt(sender, IPPROTO_IP, IP_MULTICAST_LOOP, &val,
+			     sizeof(val)));
+}
+END_TEST()
+
+FN_TEST(multicast_all)
+{
+	int val;
+	char buf[64];
+	struct sockaddr_in other_group_addr;
+	struct ip_mreq other_mreq;
+	int other;
+
+	other_group_addr = group_addr;
+	CHECK(inet_aton(OTHER_GROUP_ADDR, &other_group_addr.sin_addr));
+	other_mreq = mreq;
+	other_mreq.imr_multiaddr = other_group_addr.sin_addr;
+
+	// Another socket joins another group on the same interface.
+	other = TEST_SUCC(socket(AF_INET, SOCK_DGRAM, 0));
+	TEST_SUCC(setsockopt(other, IPPROTO_IP, IP_ADD_MEMBERSHIP, &other_mreq,
+			     sizeof(other_mreq)));
+
+	// With `IP_MULTICAST_ALL` enabled, the receiver receives the packets sent
+	// to the groups joined by other sockets.
+	TEST_RES(sendto(sender, MESSAGE, sizeof(MESSAGE), 0,
+			(struct sockaddr *)&other_group_addr,
+			sizeof(other_group_addr)),
+		 _ret == sizeof(MESSAGE));
+	TEST_RES(recv(receiver, buf, sizeof(buf), MSG_DONTWAIT),
+		 _ret == sizeof(MESSAGE));
+
+	val = 0;
+	TEST_SUCC(setsockopt(receiver, IPPROTO_IP, IP_MULTICAST_ALL, &val,
+			     sizeof(val)));
+
+	TEST_RES(sendto(sender, MESSAGE, sizeof(MESSAGE), 0,
+			(struct sockaddr *)&other_group_addr,
+			sizeof(other_group_addr)),
+		 _ret == sizeof(MESSAGE));
+	TEST_ERRNO(recv(receiver, buf, sizeof(buf), MSG_DONTWAIT), EAGAIN);
+
+	TEST_RES(sendto(sender, MESSAGE, sizeof(MESSAGE), 0,
+			(struct sockaddr *)&group_addr, sizeof(group_addr)),
+		 _ret == sizeof(MESSAGE));
+	TEST_RES(recv(receiver, buf, sizeof(buf), MSG_DONTWAIT),
+		 _ret == sizeof(MESSAGE));
+
+	val = 1;
+	TEST_SUCC(setsockopt(receiver, IPPROTO_IP, IP_MULTICAST_ALL, &val,
+			     sizeof(val)));
+	TEST_SUCC(close(other));
+}
+END_TEST()
+
+FN_TEST(leave_group)
+{
+	char buf[64];
+
+	TEST_SUCC(setsockopt(receiver, IPPROTO_IP, IP_DROP_MEMBERSHIP, &mreqn,
+			     sizeof(mreqn)));
+	TEST_ERRNO(setsockopt(receiver, IPPROTO_IP, IP_DROP_MEMBERSHIP, &mreq,
+			      sizeof(mreq)),
+		   EADDRNOTAVAIL);
+
+	TEST_RES(sendto(sender, MESSAGE, sizeof(MESSAGE), 0,
+			(struct sockaddr *)&group_addr, sizeof(group_addr)),
+		 _ret == sizeof(MESSAGE));
+	TEST_ERRNO(recv(receiver, buf, sizeof(buf), MSG_DONTWAIT), EAGAIN);
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(sender));
+	CHECK(close(receiver));
+}
+END_SETUP()
@@ -0,0 +1,605 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <poll.h>
+#include <sched.h>
+#include <unistd.h>
+#include <net/if.h>
+#include <sys/socket.h>
+#include <sys/wait.h>
+#include <arpa/inet.h>
+#include <linux/if_ether.h>
+#include <linux/if_link.h>
+#include <linux/if_packet.h>
+#include <linux/netlink.h>
+#include <linux/rtnetlink.h>
+#include <linux/veth.h>
+
+#include "../common/test.h"
+
+// An EtherType reserved for local experiments.
+#define TEST_PROTO 0x88b5
+#define TEST_PAYLOAD "veth test"
+
+struct test_frame {
+	struct ethhdr hdr;
+	char payload[sizeof(TEST_PAYLOAD)];
+} __attribute__((packed));
+
+struct link_req {
+	struct nlmsghdr hdr;
+	struct ifinfomsg ifi;
+	char attrs[512];
+};
+
+struct link_info {
+	int master;
+	int link;
+	char kind[16];
+};
+
+static const unsigned char broadcast_addr[ETH_ALEN] = { 0xff, 0xff, 0xff,
+							0xff, 0xff, 0xff };
+static const unsigned char unknown_addr[ETH_ALEN] = { 0x02, 0x00, 0x00,
+						      0x00, 0x00, 0xff };
+// The source address of the frames with the source ID 0 (see `fill_frame`).
+static const unsigned char learned_addr[ETH_ALEN] = { 0x02, 0x00, 0x00,
+						      0x00, 0x00, 0x00 };
+
+// Finds the interface index via `if_nameindex`, which queries the kernel
+// using rtnetlink.
+static int find_link(const char *name)
+{
+	struct if_nameindex *ifs, *it;
+	int index = 0;
+
+	ifs = if_nameindex();
+	if (ifs == NULL)
+		return -1;
+
+	for (it = ifs; it->if_index != 0; ++it) {
+		if (strcmp(it->if_name, name) == 0) {
+			index = it->if_index;
+			break;
+		}
+	}
+
+	if_freenameindex(ifs);
+	return index;
+}
+
+static void init_req(struct link_req *req, int type, int flags, int index)
+{
+	memset(req, 0, sizeof(*req));
+	req->hdr.nlmsg_len = NLMSG_LENGTH(sizeof(req->ifi));
+	req->hdr.nlmsg_type = type;
+	req->hdr.nlmsg_flags = NLM_F_REQUEST | NLM_F_ACK | flags;
+	req->ifi.ifi_family = AF_UNSPEC;
+	req->ifi.ifi_index = index;
+}
+
+static struct rtattr *add_attr(struct link_req *req, int type,
+			       const void *data, size_t len)
+{
+	struct rtattr *attr;
+
+	attr = (struct rtattr *)((char *)req + NLMSG_ALIGN(req->hdr.nlmsg_len));
+	attr->rta_type = type;
+	attr->rta_len = RTA_LENGTH(len);
+	if (len > 0)
+		memcpy(RTA_DATA(attr), data, len);
+	req->hdr.nlmsg_len = NLMSG_ALIGN(req->hdr.nlmsg_len) +
+			     RTA_ALIGN(attr->rta_len);
+
+	return attr;
+}
+
+static void end_nest(struct link_req *req, struct rtattr *nest)
+{
+	nest->rta_len = (char *)req + req->hdr.nlmsg_len - (char *)nest;
+}
+
+// Sends the request and waits for the acknowledgment.
+static int rtnl_talk(struct link_req *req)
+{
+	struct {
+		struct nlmsghdr hdr;
+		struct nlmsgerr err;
+	} resp;
+	int sk;
+
+	sk = CHECK(socket(AF_NETLINK, SOCK_RAW, NETLINK_ROUTE));
+	CHECK(send(sk, req, req->hdr.nlmsg_len, 0));
+	CHECK(recv(sk, &resp, sizeof(resp), 0));
+	CHECK(close(sk));
+
+	if (resp.hdr.nlmsg_type != NLMSG_ERROR) {
+		errno = EPROTO;
+		return -1;
+	}
+	if (resp.err.error != 0) {
+		errno = -resp.err.error;
+		return -1;
+	}
+	return 0;
+}
+
+static void add_kind(struct link_req *req, const char *kind)
+{
+	struct rtattr *link_info;
+
+	link_info = add_attr(req, IFLA_LINKINFO, NULL, 0);
+	add_attr(req, IFLA_INFO_KIND, kind, strlen(kind) + 1);
+	end_nest(req, link_info);
+}
+
+static int new_link(const char *name, const char *kind)
+{
+	struct link_req req;
+
+	init_req(&req, RTM_NEWLINK, NLM_F_CREATE | NLM_F_EXCL, 0);
+	if (name != NULL)
+		add_attr(&req, IFLA_IFNAME, name, strlen(name) + 1);
+	if (kind != NULL)
+		add_kind(&req, kind);
+
+	return rtnl_talk(&req);
+}
+
+// Creates a veth pair. If `peer_ns_pid` is not zero, the peer is created in
+// the network namespace of that process.
+static int new_veth(const char *name, const char *peer_name, int peer_ns_pid)
+{
+	struct rtattr *link_info, *data, *peer;
+	struct link_req req;
+
+	init_req(&req, RTM_NEWLINK, NLM_F_CREATE | NLM_F_EXCL, 0);
+	add_attr(&req, IFLA_IFNAME, name, strlen(name) + 1);
+
+	link_info = add_attr(&req, IFLA_LINKINFO, NULL, 0);
+	add_attr(&req, IFLA_INFO_KIND, "veth", sizeof("veth"));
+	data = add_attr(&req, IFLA_INFO_DATA, NULL, 0);
+	peer = add_attr(&req, VETH_INFO_PEER, NULL, 0);
+	// The peer's `ifinfomsg` precedes its attributes.
+	memset(RTA_DATA(peer), 0, sizeof(struct ifinfomsg));
+	req.hdr.nlmsg_len += sizeof(struct ifinfomsg);
+	add_attr(&req, IFLA_IFNAME, peer_name, strlen(peer_name) + 1);
+	if (peer_ns_pid != 0)
+		add_attr(&req, IFLA_NET_NS_PID, &peer_ns_pid,
+			 sizeof(peer_ns_pid));
+	end_nest(&req, peer);
+	end_nest(&req, data);
+	end_nest(&req, link_info);
+
+	return rtnl_talk(&req);
+}
+
+static int del_link(int index, const char *name)
+{
+	struct link_req req;
+
+	init_req(&req, RTM_DELLINK, 0, index);
+	if (name != NULL)
+		add_attr(&req, IFLA_IFNAME, name, strlen(name) + 1);
+
+	return rtnl_talk(&req);
+}
+
+static int set_link_attr(int index, int type, int val)
+{
+	struct link_req req;
+
+	init_req(&req, RTM_SETLINK, 0, index);
+	add_attr(&req, type, &val, sizeof(val));
+
+	return rtnl_talk(&req);
+}
+
+static int set_link_up(int index, int is_up)
+{
+	struct link_req req;
+
+	init_req(&req, RTM_SETLINK, 0, index);
+	req.ifi.ifi_flags = is_up ? IFF_UP : 0;
+	req.ifi.ifi_change = IFF_UP;
+
+	return rtnl_talk(&req);
+}
+
+static int get_link(int index, struct link_info *info)
+{
+	struct link_req req;
+	char buf[4096];
+	struct nlmsghdr *hdr = (struct nlmsghdr *)buf;
+	struct rtattr *attr, *sub;
+	int len, sub_len, sk;
+
+	memset(info, 0, sizeof(*info));
+
+	init_req(&req, RTM_GETLINK, 0, index);
+	req.hdr.nlmsg_flags = NLM_F_REQUEST;
+
+	sk = CHECK(socket(AF_NETLINK, SOCK_RAW, NETLINK_ROUTE));
+	CHECK(send(sk, &req, req.hdr.nlmsg_len, 0));
+	CHECK(recv(sk, buf, sizeof(buf), 0));
+	CHECK(close(sk));
+
+	if (hdr->nlmsg_type == NLMSG_ERROR) {
+		errno = -((struct nlmsgerr *)NLMSG_DATA(hdr))->error;
+		return -1;
+	}
+	if (hdr->nlmsg_type != RTM_NEWLINK) {
+		errno = EPROTO;
+		return -1;
+	}
+
+	len = IFLA_PAYLOAD(hdr);
+	for (attr = IFLA_RTA(NLMSG_DATA(hdr)); RTA_OK(attr, len);
+	     attr = RTA_NEXT(attr, len)) {
+		switch (attr->rta_type) {
+		case IFLA_MASTER:
+			info->master = *(int *)RTA_DATA(attr);
+			break;
+		case IFLA_LINK:
+			info->link = *(int *)RTA_DATA(attr);
+			break;
+		case IFLA_LINKINFO:
+			sub_len = RTA_PAYLOAD(attr);
+			for (sub = RTA_DATA(attr); RTA_OK(sub, sub_len);
+			     sub = RTA_NEXT(sub, sub_len)) {
+				if (sub->rta_type == IFLA_INFO_KIND)
+					strncpy(info->kind, RTA_DATA(sub),
+						sizeof(info->kind) - 1);
+			}
+			break;
+		}
+	}
+
+	return 0;
+}
+
+static void fill_frame(struct test_frame *frame, const unsigned char *dst,
+		       unsigned char src_id)
+{
+	static const unsigned char src[ETH_ALEN] = { 0x02, 0x00, 0x00,
+						     0x00, 0x00, 0x00 };
+
+	memcpy(frame->hdr.h_dest, dst, ETH_ALEN);
+	memcpy(frame->hdr.h_source, src, ETH_ALEN);
+	frame->hdr.h_source[ETH_ALEN - 1] = src_id;
+	frame->hdr.h_proto = htons(TEST_PROTO);
+	memcpy(frame->payload, TEST_PAYLOAD, sizeof(TEST_PAYLOAD));
+}
+
+// Creates a packet socket. If `promisc` is true, the interface is put into
+// the promiscuous mode, so that the frames sent to other hosts are received.
+static int new_packet_socket(int index, int promisc)
+{
+	struct packet_mreq mreq;
+	struct sockaddr_ll sll;
+	int sk;
+
+	sk = CHECK(socket(AF_PACKET, SOCK_RAW | SOCK_NONBLOCK,
+			  htons(TEST_PROTO)));
+
+	memset(&sll, 0, sizeof(sll));
+	sll.sll_family = AF_PACKET;
+	sll.sll_protocol = htons(TEST_PROTO);
+	sll.sll_ifindex = index;
+	CHECK(bind(sk, (struct sockaddr *)&sll, sizeof(sll)));
+
+	if (promisc) {
+		memset(&mreq, 0, sizeof(mreq));
+		mreq.mr_ifindex = index;
+		mreq.mr_type = PACKET_MR_PROMISC;
+		CHECK(setsockopt(sk, SOL_PACKET, PACKET_ADD_MEMBERSHIP, &mreq,
+				 sizeof(mreq)));
+	}
+
+	return sk;
+}
+
+// Receives a test frame and checks that it is the expected one. The frames
+// are delivered asynchronously, so this function waits for a while.
+static int recv_frame(int sk, const struct test_frame *frame, int timeout_ms)
+{
+	struct pollfd pfd = { .fd = sk, .events = POLLIN };
+	struct test_frame recv_frame;
+	ssize_t ret;
+
+	if (poll(&pfd, 1, timeout_ms) <= 0) {
+		errno = ETIMEDOUT;
+		return -1;
+	}
+
+	ret = recv(sk, &recv_frame, sizeof(recv_frame), 0);
+	if (ret < 0)
+		return -1;
+	if (ret != sizeof(*frame) ||
+	    memcmp(&recv_frame, frame, sizeof(*frame)) != 0) {
+		errno = EBADMSG;
+		return -1;
+	}
+
+	return 0;
+}
+
+#define RECV_TIMEOUT 1000
+#define NO_RECV_TIMEOUT 200
+
+static int init_ns_fd;
+static int child_ns_fd;
+static pid_t child_pid;
+static int child_pipe;
+
+// Finds the interface index in another network namespace.
+static int find_link_in(int ns_fd, const char *name)
+{
+	int index;
+
+	CHECK(setns(ns_fd, CLONE_NEWNET));
+	index = find_link(name);
+	CHECK(setns(init_ns_fd, CLONE_NEWNET));
+
+	return index;
+}
+
+FN_SETUP(child_ns)
+{
+	int to_child[2], to_parent[2];
+	char path[64];
+	char c;
+
+	CHECK(pipe(to_child));
+	CHECK(pipe(to_parent));
+
+	// The child holds a new network namespace until the pipe is closed.
+	child_pid = CHECK(fork());
+	if (child_pid == 0) {
+		CHECK(close(to_child[1]));
+		CHECK(unshare(CLONE_NEWNET));
+		CHECK(write(to_parent[1], "", 1));
+		CHECK(read(to_child[0], &c, 1));
+		_exit(EXIT_SUCCESS);
+	}
+
+	CHECK(close(to_child[0]));
+	CHECK(close(to_parent[1]));
+	CHECK(read(to_parent[0], &c, 1));
+	CHECK(close(to_parent[0]));
+	child_pipe = to_child[1];
+
+	snprintf(path, sizeof(path), "/proc/%d/ns/net", child_pid);
+	child_ns_fd = CHECK(open(path, O_RDONLY));
+	init_ns_fd = CHECK(open("/proc/self/ns/net", O_RDONLY));
+}
+END_SETUP()
+
+FN_TEST(invalid_requests)
+{
+	// Like Linux, unknown kinds are not supported.
+	TEST_ERRNO(new_link("astnone0", "astnone"), EOPNOTSUPP);
+	TEST_ERRNO(new_link("astnone0", NULL), EOPNOTSUPP);
+	TEST_RES(find_link("astnone0"), _ret == 0);
+
+	// The loopback interface cannot be deleted.
+	TEST_ERRNO(del_link(find_link("lo"), NULL), EOPNOTSUPP);
+	TEST_ERRNO(del_link(0, "astnone0"), ENODEV);
+	TEST_ERRNO(del_link(0, NULL), EINVAL);
+}
+END_TEST()
+
+FN_TEST(veth_pair)
+{
+	struct test_frame frame;
+	struct link_info info;
+	int index0, index1, sk0, sk1;
+
+	TEST_SUCC(new_veth("astveth0", "astveth1", 0));
+	TEST_ERRNO(new_veth("astveth0", "astveth2", 0), EEXIST);
+	TEST_RES(find_link("astveth2"), _ret == 0);
+
+	index0 = TEST_RES(find_link("astveth0"), _ret > 0);
+	index1 = TEST_RES(find_link("astveth1"), _ret > 0);
+
+	// The ends are linked to each other.
+	TEST_RES(get_link(index0, &info),
+		 info.link == index1 && info.master == 0 &&
+			 strcmp(info.kind, "veth") == 0);
+	TEST_RES(get_link(index1, &info),
+		 info.link == index0 && info.master == 0 &&
+			 strcmp(info.kind, "veth") == 0);
+
+	TEST_SUCC(set_link_up(index0, 1));
+	TEST_SUCC(set_link_up(index1, 1));
+	sk0 = new_packet_socket(index0, 1);
+	sk1 = new_packet_socket(index1, 1);
+
+	// Frames sent via one end are received by the other end.
+	fill_frame(&frame, broadcast_addr, 1);
+	TEST_RES(send(sk0, &frame, sizeof(frame), 0), _ret == sizeof(frame));
+	TEST_SUCC(recv_frame(sk1, &frame, RECV_TIMEOUT));
+	fill_frame(&frame, unknown_addr, 2);
+	TEST_RES(send(sk1, &frame, sizeof(frame), 0), _ret == sizeof(frame));
+	TEST_SUCC(recv_frame(sk0, &frame, RECV_TIMEOUT));
+
+	TEST_SUCC(close(sk0));
+	TEST_SUCC(close(sk1));
+
+	// Deleting one end also deletes the other end.
+	TEST_SUCC(del_link(0, "astveth1"));
+	TEST_RES(find_link("astveth0"), _ret == 0);
+	TEST_RES(find_link("astveth1"), _ret == 0);
+}
+END_TEST()
+
+FN_TEST(veth_netns)
+{
+	// The peer can be created in another network namespace.
+	TEST_SUCC(new_veth("astveth0", "astveth1", child_pid));
+	TEST_RES(find_link("astveth0"), _ret > 0);
+	TEST_RES(find_link("astveth1"), _ret == 0);
+	TEST_RES(find_link_in(child_ns_fd, "astveth1"), _ret > 0);
+	TEST_RES(find_link_in(child_ns_fd, "astveth0"), _ret == 0);
+
+	// Deleting one end also deletes the other end in the other network
+	// namespace.
+	TEST_SUCC(del_link(0, "astveth0"));
+	TEST_RES(find_link("astveth0"), _ret == 0);
+	TEST_RES(find_link_in(child_ns_fd, "astveth1"), _ret == 0);
+}
+END_TEST()
+
+FN_TEST(veth_netns_exit)
+{
+	int to_child[2], to_parent[2];
+	pid_t pid;
+	int status;
+	int i;
+	char c;
+
+	CHECK(pipe(to_child));
+	CHECK(pipe(to_parent));
+
+	pid = CHECK(fork());
+	if (pid == 0) {
+		CHECK(close(to_child[1]));
+		CHECK(unshare(CLONE_NEWNET));
+		CHECK(write(to_parent[1], "", 1));
+		CHECK(read(to_child[0], &c, 1));
+		_exit(EXIT_SUCCESS);
+	}
+
+	CHECK(close(to_child[0]));
+	CHECK(close(to_parent[1]));
+	CHECK(read(to_parent[0], &c, 1));
+	CHECK(close(to_parent[0]));
+
+	TEST_SUCC(new_veth("astveth0", "astveth1", pid));
+	TEST_RES(find_link("astveth0"), _ret > 0);
+
+	// Destroying the network namespace deletes the peer in it, which also
+	// deletes the end in this network namespace. Linux destroys network
+	// namespaces asynchronously.
+	CHECK(close(to_child[1]));
+	CHECK(waitpid(pid, &status, 0));
+	for (i = 0; i < 50 && find_link("astveth0") > 0; ++i)
+		CHECK(usleep(100 * 1000));
+	TEST_RES(find_link("astveth0"), _ret == 0);
+}
+END_TEST()
+
+FN_TEST(bridge_default_name)
+{
+	TEST_SUCC(new_link(NULL, "bridge"));
+	TEST_SUCC(del_link(0, "bridge0"));
+	TEST_RES(find_link("bridge0"), _ret == 0);
+}
+END_TEST()
+
+FN_TEST(bridge_ports)
+{
+	static const char *const outer[3] = { "astva0", "astva1", "astva2" };
+	static const char *const inner[3] = { "astvb0", "astvb1", "astvb2" };
+	struct test_frame frame;
+	struct link_info info;
+	int bridge, lo, outer0;
+	int sk_bridge, sk[3];
+	int port[3];
+	int i;
+
+	TEST_SUCC(new_link("astbr0", "bridge"));
+	bridge = TEST_RES(find_link("astbr0"), _ret > 0);
+	lo = TEST_RES(find_link("lo"), _ret > 0);
+	TEST_RES(get_link(bridge, &info),
+		 info.master == 0 && strcmp(info.kind, "bridge") == 0);
+	TEST_SUCC(set_link_up(bridge, 1));
+
+	for (i = 0; i < 3; ++i) {
+		TEST_SUCC(new_veth(outer[i], inner[i], 0));
+		port[i] = TEST_RES(find_link(inner[i]), _ret > 0);
+		TEST_SUCC(set_link_attr(port[i], IFLA_MASTER, bridge));
+		TEST_RES(get_link(port[i], &info), info.master == bridge);
+		TEST_SUCC(set_link_up(port[i], 1));
+		TEST_SUCC(set_link_up(find_link(outer[i]), 1));
+		sk[i] = new_packet_socket(find_link(outer[i]), 1);
+	}
+	sk_bridge = new_packet_socket(bridge, 0);
+	outer0 = TEST_RES(find_link(outer[0]), _ret > 0);
+
+	// Only bridges can be masters, and bridges cannot be ports.
+	TEST_ERRNO(set_link_attr(outer0, IFLA_MASTER, port[1]), EOPNOTSUPP);
+	TEST_ERRNO(set_link_attr(outer0, IFLA_MASTER, lo), EOPNOTSUPP);
+	TEST_ERRNO(set_link_attr(outer0, IFLA_MASTER, 0x7fffffff), EINVAL);
+	TEST_ERRNO(set_link_attr(bridge, IFLA_MASTER, bridge), ELOOP);
+	TEST_ERRNO(set_link_attr(lo, IFLA_MASTER, bridge), EINVAL);
+	TEST_RES(get_link(outer0, &info), info.master == 0);
+
+	// Bridges cannot be moved to other network namespaces.
+	TEST_ERRNO(set_link_attr(bridge, IFLA_NET_NS_FD, child_ns_fd), EINVAL);
+	TEST_RES(find_link("astbr0"), _ret == bridge);
+
+#ifndef __asterinas__
+	// Linux enables the ports asynchronously after their carriers are on.
+	TEST_SUCC(sleep(2));
+#endif
+
+	// Broadcast frames are flooded to all other ports and the bridge.
+	fill_frame(&frame, broadcast_addr, 0);
+	TEST_RES(send(sk[0], &frame, sizeof(frame), 0), _ret == sizeof(frame));
+	TEST_SUCC(recv_frame(sk[1], &frame, RECV_TIMEOUT));
+	TEST_SUCC(recv_frame(sk[2], &frame, RECV_TIMEOUT));
+	TEST_SUCC(recv_frame(sk_bridge, &frame, RECV_TIMEOUT));
+	TEST_ERRNO(recv_frame(sk[0], &frame, NO_RECV_TIMEOUT), ETIMEDOUT);
+
+	// Unicast frames to unknown addresses are flooded to all other ports.
+	fill_frame(&frame, unknown_addr, 1);
+	TEST_RES(send(sk[1], &frame, sizeof(frame), 0), _ret == sizeof(frame));
+	TEST_SUCC(recv_frame(sk[0], &frame, RECV_TIMEOUT));
+	TEST_SUCC(recv_frame(sk[2], &frame, RECV_TIMEOUT));
+	TEST_ERRNO(recv_frame(sk_bridge, &frame, NO_RECV_TIMEOUT), ETIMEDOUT);
+
+	// Unicast frames to learned addresses are forwarded to the ports only.
+	fill_frame(&frame, learned_addr, 1);
+	TEST_RES(send(sk[1], &frame, sizeof(frame), 0), _ret == sizeof(frame));
+	TEST_SUCC(recv_frame(sk[0], &frame, RECV_TIMEOUT));
+	TEST_ERRNO(recv_frame(sk[2], &frame, NO_RECV_TIMEOUT), ETIMEDOUT);
+
+	// Detached ports no longer receive frames from the bridge. Ports moved
+	// to other network namespaces are detached as well.
+	TEST_SUCC(set_link_attr(port[1], IFLA_MASTER, 0));
+	TEST_RES(get_link(port[1], &info), info.master == 0);
+	TEST_SUCC(set_link_attr(port[2], IFLA_NET_NS_FD, child_ns_fd));
+	fill_frame(&frame, broadcast_addr, 0);
+	TEST_RES(send(sk[0], &frame, sizeof(frame), 0), _ret == sizeof(frame));
+	TEST_SUCC(recv_frame(sk_bridge, &frame, RECV_TIMEOUT));
+	TEST_ERRNO(recv_frame(sk[1], &frame, NO_RECV_TIMEOUT), ETIMEDOUT);
+	TEST_ERRNO(recv_frame(sk[2], &frame, NO_RECV_TIMEOUT), ETIMEDOUT);
+
+	// Deleting the bridge detaches all the ports.
+	TEST_SUCC(close(sk_bridge));
+	TEST_SUCC(del_link(bridge, NULL));
+	TEST_RES(find_link("astbr0"), _ret == 0);
+	TEST_RES(get_link(port[0], &info), info.master == 0);
+
+	for (i = 0; i < 3; ++i) {
+		TEST_SUCC(close(sk[i]));
+		TEST_SUCC(del_link(0, outer[i]));
+		TEST_RES(find_link(inner[i]), _ret == 0);
+	}
+	TEST_RES(find_link_in(child_ns_fd, inner[2]), _ret == 0);
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	int status;
+
+	CHECK(close(init_ns_fd));
+	CHECK(close(child_ns_fd));
+	CHECK(close(child_pipe));
+	CHECK(waitpid(child_pid, &status, 0));
+}
+END_SETUP()
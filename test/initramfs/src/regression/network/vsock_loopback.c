@@ -0,0 +1,348 @@
+// SPDX-License-Identifier: MPL-2.0
+
+/*
+ * vsock sockets connected via the loopback transport, and vsock-level
+ * socket options.
+ */
+
+#include <poll.h>
+#include <stdint.h>
+#include <sys/socket.h>
+#include <sys/time.h>
+#include <linux/vm_sockets.h>
+#include <unistd.h>
+#include "../common/test.h"
+
+#define STREAM_PORT 20000
+#define SEQPACKET_PORT 20001
+#define UNBOUND_PORT 20002
+
+#define BUF_SIZE (256 * 1024)
+#define MSG_SIZE (64 * 1024)
+
+FN_TEST(buffer_size)
+{
+	int sk;
+	uint64_t val;
+	socklen_t len = sizeof(val);
+
+	sk = TEST_SUCC(socket(AF_VSOCK, SOCK_SEQPACKET, 0));
+
+	TEST_RES(getsockopt(sk, AF_VSOCK, SO_VM_SOCKETS_BUFFER_SIZE, &val,
+			    &len),
+		 val == BUF_SIZE && len == sizeof(val));
+	TEST_RES(getsockopt(sk, AF_VSOCK, SO_VM_SOCKETS_BUFFER_MIN_SIZE, &val,
+			    &len),
+		 val == 128 && len == sizeof(val));
+	TEST_RES(getsockopt(sk, AF_VSOCK, SO_VM_SOCKETS_BUFFER_MAX_SIZE, &val,
+			    &len),
+		 val == BUF_SIZE && len == sizeof(val));
+
+	// The buffer size is clamped to the minimum size.
+	val = 100;
+	TEST_SUCC(setsockopt(sk, AF_VSOCK, SO_VM_SOCKETS_BUFFER_SIZE, &val,
+			     sizeof(val)));
+	TEST_RES(getsockopt(sk, AF_VSOCK, SO_VM_SOCKETS_BUFFER_SIZE, &val,
+			    &len),
+		 val == 128);
+
+	// Changing the minimum size clamps the buffer size again.
+	val = 900;
+	TEST_SUCC(setsockopt(sk, AF_VSOCK, SO_VM_SOCKETS_BUFFER_MIN_SIZE, &val,
+			     sizeof(val)));
+	TEST_RES(getsockopt(sk, AF_VSOCK, SO_VM_SOCKETS_BUFFER_SIZE, &val,
+			    &len),
+		 val == 900);
+
+	// The buffer size is clamped to the maximum size.
+	val = 1000;
+	TEST_SUCC(setsockopt(sk, AF_VSOCK, SO_VM_SOCKETS_BUFFER_MAX_SIZE, &val,
+			     sizeof(val)));
+	val = 2000;
+	TEST_SUCC(setsockopt(sk, AF_VSOCK, SO_VM_SOCKETS_BUFFER_SIZE, &val,
+			     sizeof(val)));
+	TEST_RES(getsockopt(sk, AF_VSOCK, SO_VM_SOCKETS_BUFFER_SIZE, &val,
+			    &len),
+		 val == 1000);
+
+	// Without a connection, the buffer size can exceed the default size.
+	val = 1024 * 1024;
+	TEST_SUCC(setsockopt(sk, AF_VSOCK, SO_VM_SOCKETS_BUFFER_MAX_SIZE, &val,
+			     sizeof(val)));
+	TEST_SUCC(setsockopt(sk, AF_VSOCK, SO_VM_SOCKETS_BUFFER_SIZE, &val,
+			     sizeof(val)));
+	TEST_RES(getsockopt(sk, AF_VSOCK, SO_VM_SOCKETS_BUFFER_SIZE, &val,
+			    &len),
+		 val == 1024 * 1024);
+
+	// The option value is a 64-bit integer.
+	TEST_ERRNO(setsockopt(sk, AF_VSOCK, SO_VM_SOCKETS_BUFFER_SIZE, &val,
+			      4),
+		   EINVAL);
+	len = 4;
+	TEST_ERRNO(getsockopt(sk, AF_VSOCK, SO_VM_SOCKETS_BUFFER_SIZE, &val,
+			      &len),
+		   EINVAL);
+	len = 16;
+	TEST_RES(getsockopt(sk, AF_VSOCK, SO_VM_SOCKETS_BUFFER_SIZE, &val,
+			    &len),
+		 len == sizeof(val));
+
+	TEST_SUCC(close(sk));
+}
+END_TEST()
+
+FN_TEST(connect_timeout)
+{
+	int sk;
+	struct timeval tv;
+	socklen_t len = sizeof(tv);
+
+	sk = TEST_SUCC(socket(AF_VSOCK, SOCK_STREAM, 0));
+
+	TEST_RES(getsockopt(sk, AF_VSOCK, SO_VM_SOCKETS_CONNECT_TIMEOUT, &tv,
+			    &len),
+		 tv.tv_sec == 2 && tv.tv_usec == 0 && len == sizeof(tv));
+
+	tv.tv_sec = 1;
+	tv.tv_usec = 500000;
+	TEST_SUCC(setsockopt(sk, AF_VSOCK, SO_VM_SOCKETS_CONNECT_TIMEOUT, &tv,
+			     sizeof(tv)));
+	TEST_RES(getsockopt(sk, AF_VSOCK, SO_VM_SOCKETS_CONNECT_TIMEOUT, &tv,
+			    &len),
+		 tv.tv_sec == 1 && tv.tv_usec == 500000);
+
+	// A zero timeout restores the default timeout.
+	tv.tv_sec = 0;
+	tv.tv_usec = 0;
+	TEST_SUCC(setsockopt(sk, AF_VSOCK, SO_VM_SOCKETS_CONNECT_TIMEOUT, &tv,
+			     sizeof(tv)));
+	TEST_RES(getsockopt(sk, AF_VSOCK, SO_VM_SOCKETS_CONNECT_TIMEOUT, &tv,
+			    &len),
+		 tv.tv_sec == 2 && tv.tv_usec == 0);
+
+	tv.tv_sec = -1;
+	tv.tv_usec = 0;
+	TEST_ERRNO(setsockopt(sk, AF_VSOCK, SO_VM_SOCKETS_CONNECT_TIMEOUT, &tv,
+			      sizeof(tv)),
+		   ERANGE);
+	tv.tv_sec = 0;
+	tv.tv_usec = 1000000;
+	TEST_ERRNO(setsockopt(sk, AF_VSOCK, SO_VM_SOCKETS_CONNECT_TIMEOUT, &tv,
+			      sizeof(tv)),
+		   ERANGE);
+	TEST_ERRNO(setsockopt(sk, AF_VSOCK, SO_VM_SOCKETS_CONNECT_TIMEOUT, &tv,
+			      8),
+		   EINVAL);
+
+	TEST_SUCC(close(sk));
+}
+END_TEST()
+
+FN_TEST(unknown_options)
+{
+	int sk;
+	uint64_t val = 0;
+	socklen_t len = sizeof(val);
+
+	sk = TEST_SUCC(socket(AF_VSOCK, SOCK_STREAM, 0));
+
+	TEST_ERRNO(getsockopt(sk, AF_VSOCK, SO_VM_SOCKETS_PEER_HOST_VM_ID,
+			      &val, &len),
+		   ENOPROTOOPT);
+	TEST_ERRNO(setsockopt(sk, AF_VSOCK, SO_VM_SOCKETS_TRUSTED, &val, len),
+		   ENOPROTOOPT);
+	TEST_ERRNO(getsockopt(sk, AF_VSOCK, 100, &val, &len), ENOPROTOOPT);
+	TEST_ERRNO(setsockopt(sk, AF_VSOCK, 100, &val, len), ENOPROTOOPT);
+
+	TEST_SUCC(close(sk));
+}
+END_TEST()
+
+static int sk_stream_listen;
+static int sk_seqpacket_listen;
+
+static struct sockaddr_vm stream_addr = {
+	.svm_family = AF_VSOCK,
+	.svm_cid = VMADDR_CID_LOCAL,
+	.svm_port = STREAM_PORT,
+};
+static struct sockaddr_vm seqpacket_addr = {
+	.svm_family = AF_VSOCK,
+	.svm_cid = VMADDR_CID_LOCAL,
+	.svm_port = SEQPACKET_PORT,
+};
+
+FN_SETUP(listen)
+{
+	sk_stream_listen = CHECK(socket(AF_VSOCK, SOCK_STREAM, 0));
+	CHECK(bind(sk_stream_listen, (struct sockaddr *)&stream_addr,
+		   sizeof(stream_addr)));
+	CHECK(listen(sk_stream_listen, 2));
+
+	sk_seqpacket_listen = CHECK(socket(AF_VSOCK, SOCK_SEQPACKET, 0));
+	CHECK(bind(sk_seqpacket_listen, (struct sockaddr *)&seqpacket_addr,
+		   sizeof(seqpacket_addr)));
+	CHECK(listen(sk_seqpacket_listen, 2));
+}
+END_SETUP()
+
+FN_TEST(stream)
+{
+	int sk_connected, sk_accepted;
+	struct sockaddr_vm addr, peer_addr;
+	socklen_t addrlen = sizeof(addr);
+	char buf[16];
+
+	sk_connected = TEST_SUCC(socket(AF_VSOCK, SOCK_STREAM, 0));
+	TEST_SUCC(connect(sk_connected, (struct sockaddr *)&stream_addr,
+			  sizeof(stream_addr)));
+	sk_accepted = TEST_SUCC(accept(sk_stream_listen, NULL, NULL));
+
+	TEST_RES(getsockname(sk_connected, (struct sockaddr *)&addr, &addrlen),
+		 addrlen == sizeof(addr));
+	TEST_RES(getpeername(sk_accepted, (struct sockaddr *)&peer_addr,
+			     &addrlen),
+		 addrlen == sizeof(peer_addr) &&
+			 peer_addr.svm_cid == VMADDR_CID_LOCAL &&
+			 peer_addr.svm_port == addr.svm_port);
+	TEST_RES(getpeername(sk_connected, (struct sockaddr *)&peer_addr,
+			     &addrlen),
+		 peer_addr.svm_cid == VMADDR_CID_LOCAL &&
+			 peer_addr.svm_port == STREAM_PORT);
+
+	// Message boundaries are not preserved.
+	TEST_RES(send(sk_connected, "hello", 5, 0), _ret == 5);
+	TEST_RES(send(sk_connected, "world", 5, 0), _ret == 5);
+	TEST_RES(recv(sk_accepted, buf, 3, MSG_WAITALL), _ret == 3);
+	TEST_RES(recv(sk_accepted, buf, 7, MSG_WAITALL),
+		 _ret == 7 && memcmp(buf, "loworld", 7) == 0);
+
+	TEST_RES(send(sk_accepted, "reply", 5, 0), _ret == 5);
+	TEST_RES(recv(sk_connected, buf, sizeof(buf), 0),
+		 _ret == 5 && memcmp(buf, "reply", 5) == 0);
+
+	TEST_SUCC(shutdown(sk_connected, SHUT_WR));
+	TEST_RES(recv(sk_accepted, buf, sizeof(buf), 0), _ret == 0);
+
+	TEST_SUCC(close(sk_connected));
+	TEST_SUCC(close(sk_accepted));
+}
+END_TEST()
+
+FN_TEST(seqpacket)
+{
+	int sk_connected, sk_accepted;
+	static char big_buf[BUF_SIZE + 1];
+	char buf[16];
+
+	sk_connected = TEST_SUCC(socket(AF_VSOCK, SOCK_SEQPACKET, 0));
+	TEST_SUCC(connect(sk_connected, (struct sockaddr *)&seqpacket_addr,
+			  sizeof(seqpacket_addr)));
+	sk_accepted = TEST_SUCC(accept(sk_seqpacket_listen, NULL, NULL));
+
+	// Message boundaries are preserved.
+	TEST_RES(send(sk_connected, "hello", 5, 0), _ret == 5);
+	TEST_RES(send(sk_connected, "world!", 6, MSG_EOR), _ret == 6);
+	TEST_RES(recv(sk_accepted, buf, sizeof(buf), 0),
+		 _ret == 5 && memcmp(buf, "hello", 5) == 0);
+	TEST_RES(recv(sk_accepted, buf, sizeof(buf), 0),
+		 _ret == 6 && memcmp(buf, "world!", 6) == 0);
+
+	// The remaining bytes of a truncated message are discarded.
+	TEST_RES(send(sk_connected, "truncated", 9, 0), _ret == 9);
+	TEST_RES(send(sk_connected, "next", 4, 0), _ret == 4);
+	TEST_RES(recv(sk_accepted, buf, 5, 0),
+		 _ret == 5 && memcmp(buf, "trunc", 5) == 0);
+	TEST_RES(recv(sk_accepted, buf, sizeof(buf), 0),
+		 _ret == 4 && memcmp(buf, "next", 4) == 0);
+
+	// The real length of the message is returned with `MSG_TRUNC`.
+	TEST_RES(send(sk_connected, "truncated", 9, 0), _ret == 9);
+	TEST_RES(recv(sk_accepted, buf, 5, MSG_TRUNC),
+		 _ret == 9 && memcmp(buf, "trunc", 5) == 0);
+
+	// A message spanning multiple packets is received as a whole.
+	memset(big_buf, 'a', MSG_SIZE);
+	big_buf[MSG_SIZE - 1] = 'z';
+	TEST_RES(send(sk_connected, big_buf, MSG_SIZE, 0), _ret == MSG_SIZE);
+	memset(big_buf, 0, MSG_SIZE);
+	TEST_RES(recv(sk_accepted, big_buf, BUF_SIZE, 0),
+		 _ret == MSG_SIZE && big_buf[0] == 'a' &&
+			 big_buf[MSG_SIZE - 1] == 'z');
+
+	// A message cannot exceed the receive buffer of the peer.
+	TEST_ERRNO(send(sk_connected, big_buf, BUF_SIZE + 1, 0), EMSGSIZE);
+
+	TEST_SUCC(close(sk_connected));
+	TEST_SUCC(close(sk_accepted));
+}
+END_TEST()
+
+FN_TEST(seqpacket_credit)
+{
+	int sk_connected, sk_accepted;
+	static char msg_buf[MSG_SIZE];
+	struct pollfd pfd = { .events = POLLOUT };
+	int i;
+
+	sk_connected = TEST_SUCC(socket(AF_VSOCK, SOCK_SEQPACKET, 0));
+	TEST_SUCC(connect(sk_connected, (struct sockaddr *)&seqpacket_addr,
+			  sizeof(seqpacket_addr)));
+	sk_accepted = TEST_SUCC(accept(sk_seqpacket_listen, NULL, NULL));
+
+	// Fill the receive buffer of the peer.
+	for (i = 0; i < BUF_SIZE / MSG_SIZE; ++i)
+		TEST_RES(send(sk_connected, msg_buf, MSG_SIZE, 0),
+			 _ret == MSG_SIZE);
+
+	TEST_ERRNO(send(sk_connected, msg_buf, MSG_SIZE, MSG_DONTWAIT),
+		   EAGAIN);
+	pfd.fd = sk_connected;
+	TEST_RES(poll(&pfd, 1, 0), _ret == 0);
+
+	// Receiving a message makes room for another message.
+	TEST_RES(recv(sk_accepted, msg_buf, MSG_SIZE, 0), _ret == MSG_SIZE);
+	TEST_RES(poll(&pfd, 1, 1000), _ret == 1 && pfd.revents == POLLOUT);
+	TEST_RES(send(sk_connected, msg_buf, MSG_SIZE, MSG_DONTWAIT),
+		 _ret == MSG_SIZE);
+
+	TEST_SUCC(close(sk_connected));
+	TEST_SUCC(close(sk_accepted));
+}
+END_TEST()
+
+FN_TEST(type_mismatch)
+{
+	int sk;
+	struct sockaddr_vm addr = {
+		.svm_family = AF_VSOCK,
+		.svm_cid = VMADDR_CID_LOCAL,
+		.svm_port = UNBOUND_PORT,
+	};
+
+	sk = TEST_SUCC(socket(AF_VSOCK, SOCK_STREAM, 0));
+	TEST_ERRNO(connect(sk, (struct sockaddr *)&seqpacket_addr,
+			   sizeof(seqpacket_addr)),
+		   ECONNRESET);
+	TEST_SUCC(close(sk));
+
+	sk = TEST_SUCC(socket(AF_VSOCK, SOCK_SEQPACKET, 0));
+	TEST_ERRNO(connect(sk, (struct sockaddr *)&stream_addr,
+			   sizeof(stream_addr)),
+		   ECONNRESET);
+	TEST_SUCC(close(sk));
+
+	sk = TEST_SUCC(socket(AF_VSOCK, SOCK_SEQPACKET, 0));
+	TEST_ERRNO(connect(sk, (struct sockaddr *)&addr, sizeof(addr)),
+		   ECONNRESET);
+	TEST_SUCC(close(sk));
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(close(sk_stream_listen));
+	CHECK(close(sk_seqpacket_listen));
+}
+END_SETUP()
@@ -60,6 +60,7 @@ fi
 ./sched/sched_param_getset
 ./sched/sched_param_idle
 
+./signal/coredump
 ./signal/kill
 ./signal/parent_death_signal
 ./signal/pidfd_send_signal
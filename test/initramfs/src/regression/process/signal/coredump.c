@@ -0,0 +1,449 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+#include <elf.h>
+#include <fcntl.h>
+#include <linux/filter.h>
+#include <linux/seccomp.h>
+#include <pthread.h>
+#include <signal.h>
+#include <stddef.h>
+#include <sys/mman.h>
+#include <sys/prctl.h>
+#include <sys/procfs.h>
+#include <sys/resource.h>
+#include <sys/stat.h>
+#include <sys/syscall.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#include "../../common/test.h"
+
+#define CORE_PATTERN_PATH "/proc/sys/kernel/core_pattern"
+#define CORE_PATTERN "/tmp/coredump_test.%p"
+#define CORE_FILE_FMT "/tmp/coredump_test.%d"
+#define FIXED_CORE_FILE "/tmp/coredump_test.fixed"
+#define OTHER_FILE "/tmp/coredump_test.other"
+
+static char old_core_pattern[256];
+static long page_size;
+static unsigned char *dumped_page;
+static unsigned char *dont_dumped_page;
+
+static char *core_file_buf;
+static size_t core_file_len;
+
+static void write_core_pattern(const char *pattern)
+{
+	int fd = CHECK(open(CORE_PATTERN_PATH, O_WRONLY | O_TRUNC));
+	CHECK_WITH(write(fd, pattern, strlen(pattern)),
+		   _ret == (ssize_t)strlen(pattern));
+	CHECK(close(fd));
+}
+
+FN_SETUP(core_pattern)
+{
+	int fd = CHECK(open(CORE_PATTERN_PATH, O_RDONLY));
+	CHECK(read(fd, old_core_pattern, sizeof(old_core_pattern) - 1));
+	CHECK(close(fd));
+
+	write_core_pattern(CORE_PATTERN "\n");
+}
+END_SETUP()
+
+FN_SETUP(mappings)
+{
+	page_size = CHECK(sysconf(_SC_PAGESIZE));
+
+	dumped_page = CHECK_WITH(mmap(NULL, page_size, PROT_READ | PROT_WRITE,
+				      MAP_PRIVATE | MAP_ANONYMOUS, -1, 0),
+				 _ret != MAP_FAILED);
+	memset(dumped_page, 0xab, page_size);
+
+	dont_dumped_page = CHECK_WITH(mmap(NULL, page_size,
+					   PROT_READ | PROT_WRITE,
+					   MAP_PRIVATE | MAP_ANONYMOUS, -1, 0),
+				      _ret != MAP_FAILED);
+	memset(dont_dumped_page, 0xcd, page_size);
+	CHECK(madvise(dont_dumped_page, page_size, MADV_DONTDUMP));
+}
+END_SETUP()
+
+static int read_core_pattern(char *buf, size_t len)
+{
+	int fd = open(CORE_PATTERN_PATH, O_RDONLY);
+	if (fd < 0)
+		return -1;
+
+	ssize_t res = read(fd, buf, len - 1);
+	close(fd);
+	if (res < 0)
+		return -1;
+
+	buf[res] = '\0';
+	return 0;
+}
+
+FN_TEST(core_pattern)
+{
+	char buf[256];
+
+	TEST_RES(read_core_pattern(buf, sizeof(buf)),
+		 strcmp(buf, CORE_PATTERN "\n") == 0);
+}
+END_TEST()
+
+FN_TEST(prctl_dumpable)
+{
+	TEST_RES(prctl(PR_GET_DUMPABLE), _ret == 1);
+
+	TEST_SUCC(prctl(PR_SET_DUMPABLE, 0));
+	TEST_RES(prctl(PR_GET_DUMPABLE), _ret == 0);
+
+	TEST_SUCC(prctl(PR_SET_DUMPABLE, 1));
+	TEST_RES(prctl(PR_GET_DUMPABLE), _ret == 1);
+
+	TEST_ERRNO(prctl(PR_SET_DUMPABLE, 2), EINVAL);
+	TEST_ERRNO(prctl(PR_SET_DUMPABLE, 3), EINVAL);
+	TEST_RES(prctl(PR_GET_DUMPABLE), _ret == 1);
+}
+END_TEST()
+
+static void set_core_limit(rlim_t limit)
+{
+	struct rlimit rlimit = { .rlim_cur = limit, .rlim_max = limit };
+
+	if (setrlimit(RLIMIT_CORE, &rlimit) < 0)
+		exit(EXIT_FAILURE);
+}
+
+static void *sleeping_thread(void *arg)
+{
+	for (;;)
+		pause();
+
+	return NULL;
+}
+
+enum child_kind {
+	CHILD_SINGLE_THREAD,
+	CHILD_MULTI_THREAD,
+	CHILD_NO_CORE_LIMIT,
+	CHILD_NOT_DUMPABLE,
+	CHILD_SECCOMP_KILL_PROCESS,
+	CHILD_SECCOMP_KILL_THREAD,
+};
+
+// Makes `getppid` kill the calling thread or process with the seccomp action.
+static void filter_getppid(unsigned int action)
+{
+	struct sock_filter insns[] = {
+		BPF_STMT(BPF_LD | BPF_W | BPF_ABS,
+			 offsetof(struct seccomp_data, nr)),
+		BPF_JUMP(BPF_JMP | BPF_JEQ | BPF_K, SYS_getppid, 0, 1),
+		BPF_STMT(BPF_RET | BPF_K, action),
+		BPF_STMT(BPF_RET | BPF_K, SECCOMP_RET_ALLOW),
+	};
+	struct sock_fprog prog = {
+		.len = sizeof(insns) / sizeof(insns[0]),
+		.filter = insns,
+	};
+
+	if (prctl(PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) < 0 ||
+	    syscall(SYS_seccomp, SECCOMP_SET_MODE_FILTER, 0, &prog) < 0)
+		exit(EXIT_FAILURE);
+}
+
+// Forks a child that kills itself with `SIGABRT`, or with `SIGSYS` through
+// seccomp, and returns its wait status.
+static int run_child(enum child_kind kind, pid_t *pid)
+{
+	int status;
+	pthread_t thread;
+
+	*pid = fork();
+	if (*pid < 0)
+		return -1;
+
+	if (*pid == 0) {
+		set_core_limit(kind == CHILD_NO_CORE_LIMIT ? 0 : RLIM_INFINITY);
+		if (kind == CHILD_NOT_DUMPABLE && prctl(PR_SET_DUMPABLE, 0) < 0)
+			exit(EXIT_FAILURE);
+		if ((kind == CHILD_MULTI_THREAD ||
+		     kind == CHILD_SECCOMP_KILL_PROCESS) &&
+		    pthread_create(&thread, NULL, sleeping_thread, NULL) != 0)
+			exit(EXIT_FAILURE);
+
+		if (kind == CHILD_SECCOMP_KILL_PROCESS) {
+			filter_getppid(SECCOMP_RET_KILL_PROCESS);
+			syscall(SYS_getppid);
+		} else if (kind == CHILD_SECCOMP_KILL_THREAD) {
+			filter_getppid(SECCOMP_RET_KILL_THREAD);
+			syscall(SYS_getppid);
+		}
+
+		abort();
+	}
+
+	if (waitpid(*pid, &status, 0) != *pid)
+		return -1;
+
+	return status;
+}
+
+static const char *core_file_path(pid_t pid)
+{
+	static char path[64];
+
+	snprintf(path, sizeof(path), CORE_FILE_FMT, pid);
+
+	return path;
+}
+
+static int read_core_file(pid_t pid)
+{
+	const char *path = core_file_path(pid);
+	struct stat stat;
+
+	if (core_file_buf != NULL) {
+		munmap(core_file_buf, core_file_len);
+		core_file_buf = NULL;
+		core_file_len = 0;
+	}
+
+	int fd = open(path, O_RDONLY);
+	if (fd < 0)
+		return -1;
+	unlink(path);
+
+	if (fstat(fd, &stat) < 0 || (stat.st_mode & 0777) != 0600 ||
+	    stat.st_size == 0) {
+		close(fd);
+		errno = EACCES;
+		return -1;
+	}
+
+	char *buf = mmap(NULL, stat.st_size, PROT_READ, MAP_PRIVATE, fd, 0);
+	close(fd);
+	if (buf == MAP_FAILED)
+		return -1;
+
+	core_file_buf = buf;
+	core_file_len = stat.st_size;
+	return 0;
+}
+
+static Elf64_Ehdr *core_ehdr(void)
+{
+	return (Elf64_Ehdr *)core_file_buf;
+}
+
+static Elf64_Phdr *core_phdr(int index)
+{
+	return (Elf64_Phdr *)(core_file_buf + core_ehdr()->e_phoff) + index;
+}
+
+static int is_elf_core(void)
+{
+	Elf64_Ehdr *ehdr = core_ehdr();
+
+	return core_file_len >= sizeof(*ehdr) &&
+	       memcmp(ehdr->e_ident, ELFMAG, SELFMAG) == 0 &&
+	       ehdr->e_ident[EI_CLASS] == ELFCLASS64 &&
+	       ehdr->e_type == ET_CORE &&
+	       ehdr->e_phentsize == sizeof(Elf64_Phdr) &&
+	       ehdr->e_phoff + ehdr->e_phnum * sizeof(Elf64_Phdr) <=
+		       core_file_len &&
+	       ehdr->e_phnum >= 1 && core_phdr(0)->p_type == PT_NOTE;
+}
+
+static Elf64_Phdr *find_load_phdr(void *addr)
+{
+	for (int i = 0; i < core_ehdr()->e_phnum; i++) {
+		Elf64_Phdr *phdr = core_phdr(i);
+
+		if (phdr->p_type == PT_LOAD &&
+		    phdr->p_vaddr <= (unsigned long)addr &&
+		    (unsigned long)addr < phdr->p_vaddr + phdr->p_memsz)
+			return phdr;
+	}
+
+	return NULL;
+}
+
+static int is_page_dumped(void *addr, unsigned char value)
+{
+	Elf64_Phdr *phdr = find_load_phdr(addr);
+	if (phdr == NULL)
+		return 0;
+
+	unsigned long offset = (unsigned long)addr - phdr->p_vaddr;
+	if (offset + page_size > phdr->p_filesz ||
+	    phdr->p_offset + offset + page_size > core_file_len)
+		return 0;
+
+	unsigned char *page = (unsigned char *)core_file_buf + phdr->p_offset +
+			      offset;
+	for (long i = 0; i < page_size; i++)
+		if (page[i] != value)
+			return 0;
+
+	return 1;
+}
+
+static int is_page_not_dumped(void *addr)
+{
+	Elf64_Phdr *phdr = find_load_phdr(addr);
+
+	return phdr != NULL &&
+	       (unsigned long)addr - phdr->p_vaddr >= phdr->p_filesz;
+}
+
+// Counts the `NT_PRSTATUS` notes, and checks that the first one belongs to the
+// thread receiving the signal.
+static int count_prstatus(pid_t pid)
+{
+	Elf64_Phdr *phdr = core_phdr(0);
+	size_t offset = phdr->p_offset;
+	size_t end = phdr->p_offset + phdr->p_filesz;
+	int count = 0;
+
+	if (end > core_file_len)
+		return -1;
+
+	while (offset + sizeof(Elf64_Nhdr) <= end) {
+		Elf64_Nhdr *nhdr = (Elf64_Nhdr *)(core_file_buf + offset);
+		char *name = core_file_buf + offset + sizeof(*nhdr);
+		char *desc = name + ((nhdr->n_namesz + 3) & ~3);
+
+		if (nhdr->n_namesz == 5 && memcmp(name, "CORE", 5) == 0 &&
+		    nhdr->n_type == NT_PRSTATUS) {
+			struct elf_prstatus *prstatus =
+				(struct elf_prstatus *)desc;
+
+			if (nhdr->n_descsz != sizeof(*prstatus))
+				return -1;
+			if (count == 0 && (prstatus->pr_pid != pid ||
+					   prstatus->pr_cursig != SIGABRT))
+				return -1;
+			count++;
+		}
+
+		offset = desc + ((nhdr->n_descsz + 3) & ~3) - core_file_buf;
+	}
+
+	return count;
+}
+
+FN_TEST(dump_single_thread)
+{
+	pid_t pid;
+
+	TEST_RES(run_child(CHILD_SINGLE_THREAD, &pid),
+		 WIFSIGNALED(_ret) && WTERMSIG(_ret) == SIGABRT &&
+			 WCOREDUMP(_ret));
+	TEST_RES(read_core_file(pid), is_elf_core());
+
+	TEST_RES(count_prstatus(pid), _ret == 1);
+	TEST_RES(is_page_dumped(dumped_page, 0xab), _ret);
+	TEST_RES(is_page_not_dumped(dont_dumped_page), _ret);
+}
+END_TEST()
+
+FN_TEST(dump_multi_thread)
+{
+	pid_t pid;
+
+	TEST_RES(run_child(CHILD_MULTI_THREAD, &pid),
+		 WIFSIGNALED(_ret) && WTERMSIG(_ret) == SIGABRT &&
+			 WCOREDUMP(_ret));
+	TEST_RES(read_core_file(pid), is_elf_core());
+
+	TEST_RES(count_prstatus(pid), _ret == 2);
+}
+END_TEST()
+
+FN_TEST(dump_seccomp_kill)
+{
+	pid_t pid;
+
+	TEST_RES(run_child(CHILD_SECCOMP_KILL_PROCESS, &pid),
+		 WIFSIGNALED(_ret) && WTERMSIG(_ret) == SIGSYS &&
+			 WCOREDUMP(_ret));
+	TEST_RES(read_core_file(pid), is_elf_core());
+
+	// Killing the last thread also dumps the core.
+	TEST_RES(run_child(CHILD_SECCOMP_KILL_THREAD, &pid),
+		 WIFSIGNALED(_ret) && WTERMSIG(_ret) == SIGSYS &&
+			 WCOREDUMP(_ret));
+	TEST_RES(read_core_file(pid), is_elf_core());
+}
+END_TEST()
+
+FN_TEST(no_dump_without_core_limit)
+{
+	pid_t pid;
+
+	TEST_RES(run_child(CHILD_NO_CORE_LIMIT, &pid),
+		 WIFSIGNALED(_ret) && WTERMSIG(_ret) == SIGABRT &&
+			 !WCOREDUMP(_ret));
+	TEST_ERRNO(access(core_file_path(pid), F_OK), ENOENT);
+}
+END_TEST()
+
+FN_TEST(no_dump_if_not_dumpable)
+{
+	pid_t pid;
+
+	TEST_RES(run_child(CHILD_NOT_DUMPABLE, &pid),
+		 WIFSIGNALED(_ret) && WTERMSIG(_ret) == SIGABRT &&
+			 !WCOREDUMP(_ret));
+	TEST_ERRNO(access(core_file_path(pid), F_OK), ENOENT);
+}
+END_TEST()
+
+static int file_size(const char *path)
+{
+	struct stat stat_buf;
+
+	if (stat(path, &stat_buf) < 0)
+		return -1;
+
+	return stat_buf.st_size;
+}
+
+FN_TEST(no_dump_to_unsafe_file)
+{
+	pid_t pid;
+	int fd;
+
+	write_core_pattern(FIXED_CORE_FILE);
+
+	// Symbolic links are not followed.
+	TEST_SUCC(symlink(OTHER_FILE, FIXED_CORE_FILE));
+	TEST_RES(run_child(CHILD_SINGLE_THREAD, &pid),
+		 WIFSIGNALED(_ret) && WTERMSIG(_ret) == SIGABRT &&
+			 !WCOREDUMP(_ret));
+	TEST_ERRNO(access(OTHER_FILE, F_OK), ENOENT);
+	TEST_SUCC(unlink(FIXED_CORE_FILE));
+
+	// Files with other hard links are not reused.
+	fd = TEST_RES(open(OTHER_FILE, O_WRONLY | O_CREAT, 0600), _ret >= 0);
+	TEST_SUCC(close(fd));
+	TEST_SUCC(link(OTHER_FILE, FIXED_CORE_FILE));
+	TEST_RES(run_child(CHILD_SINGLE_THREAD, &pid),
+		 WIFSIGNALED(_ret) && WTERMSIG(_ret) == SIGABRT &&
+			 !WCOREDUMP(_ret));
+	TEST_RES(file_size(OTHER_FILE), _ret == 0);
+	TEST_SUCC(unlink(FIXED_CORE_FILE));
+	TEST_SUCC(unlink(OTHER_FILE));
+
+	write_core_pattern(CORE_PATTERN "\n");
+}
+END_TEST()
+
+FN_SETUP(restore_core_pattern)
+{
+	write_core_pattern(old_core_pattern);
+}
+END_SETUP()
@@ -0,0 +1,337 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <fcntl.h>
+#include <libgen.h>
+#include <stdint.h>
+#include <unistd.h>
+#include <sys/prctl.h>
+#include <sys/syscall.h>
+#include <sys/wait.h>
+#include <sys/xattr.h>
+#include <linux/capability.h>
+
+#include "../../common/test.h"
+
+static uid_t nobody = 65534;
+
+#define CHILD_COPY "/tmp/file_caps_child"
+#define XATTR_NAME "security.capability"
+
+#define CAPS_NONE "0000000000000000"
+#define CAPS_NET "0000000000002400"
+#define CAPS_NET_RAW "0000000000002000"
+
+#define NET_CAPS ((1ULL << CAP_NET_RAW) | (1ULL << CAP_NET_BIND_SERVICE))
+
+static char child_path[4096];
+
+FN_SETUP(copy_child)
+{
+	char exe_path[4096] = { 0 };
+	char buf[4096];
+	int src, dst;
+	ssize_t len;
+
+	CHECK(readlink("/proc/self/exe", exe_path, sizeof(exe_path) - 1));
+	snprintf(child_path, sizeof(child_path), "%s/execve_child",
+		 dirname(exe_path));
+
+	src = CHECK(open(child_path, O_RDONLY));
+	dst = CHECK(open(CHILD_COPY, O_WRONLY | O_CREAT | O_TRUNC, 0755));
+	while ((len = CHECK(read(src, buf, sizeof(buf)))) > 0)
+		CHECK_WITH(write(dst, buf, len), _ret == len);
+	CHECK(close(src));
+	CHECK(close(dst));
+}
+END_SETUP()
+
+static int set_file_caps(uint32_t revision, int effective, uint64_t permitted,
+			 uint64_t inheritable, uint32_t rootid)
+{
+	struct vfs_ns_cap_data data;
+	size_t size;
+
+	memset(&data, 0, sizeof(data));
+	data.magic_etc = revision | (effective ? VFS_CAP_FLAGS_EFFECTIVE : 0);
+	data.data[0].permitted = permitted;
+	data.data[0].inheritable = inheritable;
+	data.data[1].permitted = permitted >> 32;
+	data.data[1].inheritable = inheritable >> 32;
+	data.rootid = rootid;
+
+	switch (revision) {
+	case VFS_CAP_REVISION_2:
+		size = XATTR_CAPS_SZ_2;
+		break;
+	case VFS_CAP_REVISION_3:
+		size = XATTR_CAPS_SZ_3;
+		break;
+	default:
+		size = XATTR_CAPS_SZ_1;
+		break;
+	}
+
+	return setxattr(CHILD_COPY, XATTR_NAME, &data, size, 0);
+}
+
+static int set_caps(uint64_t effective, uint64_t permitted,
+		    uint64_t inheritable)
+{
+	struct __user_cap_header_struct hdr;
+	struct __user_cap_data_struct data[2];
+
+	hdr.version = _LINUX_CAPABILITY_VERSION_3;
+	hdr.pid = 0;
+	data[0].effective = effective;
+	data[0].permitted = permitted;
+	data[0].inheritable = inheritable;
+	data[1].effective = effective >> 32;
+	data[1].permitted = permitted >> 32;
+	data[1].inheritable = inheritable >> 32;
+
+	return syscall(SYS_capset, &hdr, data);
+}
+
+static uint64_t get_permitted_caps(void)
+{
+	struct __user_cap_header_struct hdr;
+	struct __user_cap_data_struct data[2];
+
+	hdr.version = _LINUX_CAPABILITY_VERSION_3;
+	hdr.pid = 0;
+	if (syscall(SYS_capget, &hdr, data) < 0)
+		return 0;
+
+	return data[0].permitted | ((uint64_t)data[1].permitted << 32);
+}
+
+static int wait_for_child(pid_t pid)
+{
+	int status;
+
+	if (waitpid(pid, &status, 0) != pid)
+		return -1;
+	if (!WIFEXITED(status) || WEXITSTATUS(status) != 0)
+		return -1;
+	return 0;
+}
+
+// =====================================
+// Tests for writing file capabilities
+// =====================================
+
+FN_TEST(set_invalid_file_caps)
+{
+	uint32_t magic = VFS_CAP_REVISION_2;
+
+	TEST_ERRNO(setxattr(CHILD_COPY, XATTR_NAME, &magic, 2, 0), EINVAL);
+	TEST_ERRNO(setxattr(CHILD_COPY, XATTR_NAME, &magic, sizeof(magic), 0),
+		   EINVAL);
+	TEST_ERRNO(set_file_caps(0x04000000, 1, NET_CAPS, 0, 0), EINVAL);
+}
+END_TEST()
+
+FN_TEST(set_file_caps_without_setfcap)
+{
+	pid_t pid = TEST_SUCC(fork());
+
+	if (pid == 0) {
+		CHECK(set_caps(0, 0, 0));
+		CHECK_WITH(set_file_caps(VFS_CAP_REVISION_2, 1, NET_CAPS, 0, 0),
+			   _ret < 0 && errno == EPERM);
+		CHECK_WITH(removexattr(CHILD_COPY, XATTR_NAME),
+			   _ret < 0 && errno == EPERM);
+		_exit(0);
+	}
+
+	TEST_RES(wait_for_child(pid), _ret == 0);
+}
+END_TEST()
+
+FN_TEST(get_file_caps)
+{
+	struct vfs_ns_cap_data data;
+
+	TEST_SUCC(set_file_caps(VFS_CAP_REVISION_2, 1, NET_CAPS, 0, 0));
+	TEST_RES(getxattr(CHILD_COPY, XATTR_NAME, &data, sizeof(data)),
+		 _ret == XATTR_CAPS_SZ_2);
+	TEST_RES(data.magic_etc,
+		 _ret == (VFS_CAP_REVISION_2 | VFS_CAP_FLAGS_EFFECTIVE));
+	TEST_RES(data.data[0].permitted, _ret == NET_CAPS);
+
+	TEST_SUCC(removexattr(CHILD_COPY, XATTR_NAME));
+	TEST_ERRNO(getxattr(CHILD_COPY, XATTR_NAME, &data, sizeof(data)),
+		   ENODATA);
+}
+END_TEST()
+
+// =====================================
+// Tests for executing with file capabilities
+// =====================================
+
+#define TEST_CAPS_AFTER_EXECVE(name, revision, effective, ecaps, pcaps) \
+	FN_TEST(name)                                                   \
+	{                                                               \
+		pid_t pid;                                              \
+                                                                        \
+		TEST_SUCC(set_file_caps(revision, effective, NET_CAPS,  \
+					0, 0));                         \
+                                                                        \
+		pid = TEST_SUCC(fork());                                \
+		if (pid == 0) {                                         \
+			CHECK(setresuid(nobody, nobody, nobody));       \
+			CHECK(execl(CHILD_COPY, CHILD_COPY, ecaps,      \
+				    pcaps, CAPS_NONE, NULL));           \
+		}                                                       \
+                                                                        \
+		TEST_RES(wait_for_child(pid), _ret == 0);               \
+		TEST_SUCC(removexattr(CHILD_COPY, XATTR_NAME));         \
+	}                                                               \
+	END_TEST()
+
+TEST_CAPS_AFTER_EXECVE(exec_v2_effective, VFS_CAP_REVISION_2, 1, CAPS_NET,
+		       CAPS_NET);
+TEST_CAPS_AFTER_EXECVE(exec_v2_not_effective, VFS_CAP_REVISION_2, 0,
+		       CAPS_NONE, CAPS_NET);
+TEST_CAPS_AFTER_EXECVE(exec_v3_effective, VFS_CAP_REVISION_3, 1, CAPS_NET,
+		       CAPS_NET);
+
+// File capabilities whose root user ID is not the root user are ignored.
+FN_TEST(exec_v3_other_rootid)
+{
+	pid_t pid;
+
+	TEST_SUCC(set_file_caps(VFS_CAP_REVISION_3, 1, NET_CAPS, 0, nobody));
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(setresuid(nobody, nobody, nobody));
+		CHECK(execl(CHILD_COPY, CHILD_COPY, CAPS_NONE, CAPS_NONE,
+			    CAPS_NONE, NULL));
+	}
+
+	TEST_RES(wait_for_child(pid), _ret == 0);
+	TEST_SUCC(removexattr(CHILD_COPY, XATTR_NAME));
+}
+END_TEST()
+
+FN_TEST(exec_insufficient_caps)
+{
+	pid_t pid;
+
+	TEST_SUCC(set_file_caps(VFS_CAP_REVISION_2, 1, NET_CAPS, 0, 0));
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(prctl(PR_CAPBSET_DROP, CAP_NET_RAW, 0, 0, 0));
+		CHECK(setresuid(nobody, nobody, nobody));
+		CHECK_WITH(execl(CHILD_COPY, CHILD_COPY, CAPS_NET, CAPS_NET,
+				 CAPS_NONE, NULL),
+			   _ret < 0 && errno == EPERM);
+		_exit(0);
+	}
+
+	TEST_RES(wait_for_child(pid), _ret == 0);
+	TEST_SUCC(removexattr(CHILD_COPY, XATTR_NAME));
+}
+END_TEST()
+
+// =====================================
+// Tests for ambient capabilities
+// =====================================
+
+FN_TEST(ambient_raise_and_lower)
+{
+	pid_t pid = TEST_SUCC(fork());
+
+	if (pid == 0) {
+		uint64_t all = get_permitted_caps();
+
+		// Capabilities not in the inheritable set cannot be raised.
+		CHECK_WITH(prctl(PR_CAP_AMBIENT, PR_CAP_AMBIENT_RAISE,
+				 CAP_NET_RAW, 0, 0),
+			   _ret < 0 && errno == EPERM);
+
+		CHECK(set_caps(all, all, 1ULL << CAP_NET_RAW));
+		CHECK(prctl(PR_CAP_AMBIENT, PR_CAP_AMBIENT_RAISE, CAP_NET_RAW,
+			    0, 0));
+		CHECK_WITH(prctl(PR_CAP_AMBIENT, PR_CAP_AMBIENT_IS_SET,
+				 CAP_NET_RAW, 0, 0),
+			   _ret == 1);
+		CHECK(prctl(PR_CAP_AMBIENT, PR_CAP_AMBIENT_LOWER, CAP_NET_RAW,
+			    0, 0));
+		CHECK_WITH(prctl(PR_CAP_AMBIENT, PR_CAP_AMBIENT_IS_SET,
+				 CAP_NET_RAW, 0, 0),
+			   _ret == 0);
+
+		// Lowering the inheritable set drops the ambient capability.
+		CHECK(prctl(PR_CAP_AMBIENT, PR_CAP_AMBIENT_RAISE, CAP_NET_RAW,
+			    0, 0));
+		CHECK(set_caps(all, all, 0));
+		CHECK_WITH(prctl(PR_CAP_AMBIENT, PR_CAP_AMBIENT_IS_SET,
+				 CAP_NET_RAW, 0, 0),
+			   _ret == 0);
+
+		CHECK_WITH(prctl(PR_CAP_AMBIENT, 5, CAP_NET_RAW, 0, 0),
+			   _ret < 0 && errno == EINVAL);
+		CHECK_WITH(prctl(PR_CAP_AMBIENT, PR_CAP_AMBIENT_CLEAR_ALL,
+				 CAP_NET_RAW, 0, 0),
+			   _ret < 0 && errno == EINVAL);
+		CHECK(prctl(PR_CAP_AMBIENT, PR_CAP_AMBIENT_CLEAR_ALL, 0, 0, 0));
+		_exit(0);
+	}
+
+	TEST_RES(wait_for_child(pid), _ret == 0);
+}
+END_TEST()
+
+FN_TEST(ambient_exec)
+{
+	pid_t pid = TEST_SUCC(fork());
+
+	if (pid == 0) {
+		uint64_t all = get_permitted_caps();
+
+		CHECK(set_caps(all, all, 1ULL << CAP_NET_RAW));
+		CHECK(prctl(PR_SET_KEEPCAPS, 1, 0, 0, 0));
+		CHECK(setresuid(nobody, nobody, nobody));
+		CHECK(prctl(PR_CAP_AMBIENT, PR_CAP_AMBIENT_RAISE, CAP_NET_RAW,
+			    0, 0));
+
+		CHECK(execl(child_path, child_path, CAPS_NET_RAW, CAPS_NET_RAW,
+			    CAPS_NET_RAW, NULL));
+	}
+
+	TEST_RES(wait_for_child(pid), _ret == 0);
+}
+END_TEST()
+
+FN_TEST(ambient_cleared_by_root_to_nonroot)
+{
+	pid_t pid = TEST_SUCC(fork());
+
+	if (pid == 0) {
+		uint64_t all = get_permitted_caps();
+
+		CHECK(set_caps(all, all, 1ULL << CAP_NET_RAW));
+		CHECK(prctl(PR_CAP_AMBIENT, PR_CAP_AMBIENT_RAISE, CAP_NET_RAW,
+			    0, 0));
+		CHECK(prctl(PR_SET_KEEPCAPS, 1, 0, 0, 0));
+		CHECK(setresuid(nobody, nobody, nobody));
+		CHECK_WITH(prctl(PR_CAP_AMBIENT, PR_CAP_AMBIENT_IS_SET,
+				 CAP_NET_RAW, 0, 0),
+			   _ret == 0);
+		_exit(0);
+	}
+
+	TEST_RES(wait_for_child(pid), _ret == 0);
+}
+END_TEST()
+
+FN_SETUP(cleanup)
+{
+	CHECK(unlink(CHILD_COPY));
+}
+END_SETUP()
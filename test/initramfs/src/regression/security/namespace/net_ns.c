@@ -0,0 +1,148 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+#include <errno.h>
+#include <fcntl.h>
+#include <net/if.h>
+#include <netinet/in.h>
+#include <sched.h>
+#include <string.h>
+#include <sys/socket.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#include "../../common/test.h"
+
+#define TEST_PORT 8080
+
+static int wait_for_child(pid_t pid)
+{
+	int status;
+
+	if (waitpid(pid, &status, 0) != pid)
+		return -1;
+	if (!WIFEXITED(status) || WEXITSTATUS(status) != 0)
+		return -1;
+	return 0;
+}
+
+static int bind_test_port(void)
+{
+	struct sockaddr_in addr = {
+		.sin_family = AF_INET,
+		.sin_port = htons(TEST_PORT),
+		.sin_addr = { htonl(INADDR_LOOPBACK) },
+	};
+	int sk;
+
+	sk = socket(AF_INET, SOCK_STREAM, 0);
+	if (sk < 0)
+		return -1;
+	if (bind(sk, (struct sockaddr *)&addr, sizeof(addr)) < 0) {
+		close(sk);
+		return -1;
+	}
+	return sk;
+}
+
+static int count_ifaces(void)
+{
+	struct if_nameindex *ifs, *it;
+	int count = 0;
+
+	ifs = if_nameindex();
+	if (ifs == NULL)
+		return -1;
+	for (it = ifs; it->if_index != 0; it++)
+		count++;
+	if_freenameindex(ifs);
+
+	return count;
+}
+
+// --- Test: A new network namespace contains only the loopback interface ---
+
+FN_TEST(only_loopback)
+{
+	pid_t pid = TEST_SUCC(fork());
+
+	if (pid == 0) {
+		CHECK(unshare(CLONE_NEWNET));
+		CHECK_WITH(count_ifaces(), _ret == 1);
+		CHECK_WITH(if_nametoindex("lo"), _ret != 0);
+		_exit(0);
+	}
+
+	TEST_RES(wait_for_child(pid), _ret == 0);
+}
+END_TEST()
+
+// --- Test: The ports are isolated between network namespaces ---
+
+FN_TEST(isolated_ports)
+{
+	int sk = TEST_SUCC(bind_test_port());
+	TEST_ERRNO(bind_test_port(), EADDRINUSE);
+
+	pid_t pid = TEST_SUCC(fork());
+
+	if (pid == 0) {
+		CHECK(unshare(CLONE_NEWNET));
+		int child_sk = CHECK(bind_test_port());
+		CHECK(close(child_sk));
+		_exit(0);
+	}
+
+	TEST_RES(wait_for_child(pid), _ret == 0);
+	TEST_SUCC(close(sk));
+}
+END_TEST()
+
+// --- Test: `setns` switches back to the original network namespace ---
+
+FN_TEST(setns_back)
+{
+	pid_t pid = TEST_SUCC(fork());
+
+	if (pid == 0) {
+		int sk = CHECK(bind_test_port());
+
+		int ns_fd = CHECK(open("/proc/self/ns/net", O_RDONLY));
+		CHECK(unshare(CLONE_NEWNET));
+		int new_sk = CHECK(bind_test_port());
+		CHECK(close(new_sk));
+
+		CHECK(setns(ns_fd, CLONE_NEWNET));
+		CHECK_WITH(bind_test_port(), _ret < 0 && errno == EADDRINUSE);
+
+		CHECK(close(ns_fd));
+		CHECK(close(sk));
+		_exit(0);
+	}
+
+	TEST_RES(wait_for_child(pid), _ret == 0);
+}
+END_TEST()
+
+// --- Test: `/proc/net/dev` lists the interfaces in the namespace ---
+
+FN_TEST(proc_net_dev)
+{
+	pid_t pid = TEST_SUCC(fork());
+
+	if (pid == 0) {
+		char buf[1024] = { 0 };
+
+		CHECK(unshare(CLONE_NEWNET));
+
+		int fd = CHECK(open("/proc/net/dev", O_RDONLY));
+		CHECK(read(fd, buf, sizeof(buf) - 1));
+		CHECK(close(fd));
+		CHECK_WITH(strstr(buf, "lo:") != NULL, _ret);
+		CHECK_WITH(strstr(buf, "eth0:") == NULL, _ret);
+		_exit(0);
+	}
+
+	TEST_RES(wait_for_child(pid), _ret == 0);
+}
+END_TEST()
@@ -0,0 +1,321 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+#include <errno.h>
+#include <fcntl.h>
+#include <sched.h>
+#include <signal.h>
+#include <string.h>
+#include <sys/ipc.h>
+#include <sys/msg.h>
+#include <sys/shm.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#include "../../common/test.h"
+
+static int wait_for_child(pid_t pid)
+{
+	int status;
+
+	if (waitpid(pid, &status, 0) != pid)
+		return -1;
+	if (!WIFEXITED(status) || WEXITSTATUS(status) != 0)
+		return -1;
+	return 0;
+}
+
+// --- Test: The first child in a new PID namespace becomes its init ---
+
+FN_TEST(first_child_is_init)
+{
+	pid_t pid = TEST_SUCC(fork());
+
+	if (pid == 0) {
+		pid_t old_pid = CHECK(getpid());
+		CHECK(unshare(CLONE_NEWPID));
+		// The caller itself does not move into the new namespace.
+		CHECK_WITH(getpid(), _ret == old_pid);
+
+		pid_t init = CHECK(fork());
+		if (init == 0) {
+			CHECK_WITH(getpid(), _ret == 1);
+			// The parent lives outside of the namespace.
+			CHECK_WITH(getppid(), _ret == 0);
+
+			pid_t child = CHECK(fork());
+			if (child == 0) {
+				CHECK_WITH(getppid(), _ret == 1);
+				_exit(0);
+			}
+			CHECK_WITH(child, _ret == 2);
+			CHECK(wait_for_child(child));
+			_exit(0);
+		}
+
+		CHECK_WITH(init, _ret != 1);
+		CHECK(wait_for_child(init));
+		_exit(0);
+	}
+
+	TEST_RES(wait_for_child(pid), _ret == 0);
+}
+END_TEST()
+
+// --- Test: `pid_for_children` differs from `pid` after `unshare` ---
+
+FN_TEST(pid_for_children_link)
+{
+	pid_t pid = TEST_SUCC(fork());
+
+	if (pid == 0) {
+		char pid_link[64] = { 0 };
+		char children_link[64] = { 0 };
+		int pipefd[2];
+
+		CHECK(pipe(pipefd));
+		CHECK(unshare(CLONE_NEWPID));
+
+		pid_t init = CHECK(fork());
+		if (init == 0) {
+			char buf;
+			CHECK(close(pipefd[1]));
+			CHECK_WITH(read(pipefd[0], &buf, 1), _ret == 0);
+			_exit(0);
+		}
+		CHECK(close(pipefd[0]));
+
+		CHECK(readlink("/proc/self/ns/pid", pid_link,
+			       sizeof(pid_link) - 1));
+		CHECK(readlink("/proc/self/ns/pid_for_children", children_link,
+			       sizeof(children_link) - 1));
+		CHECK_WITH(strcmp(pid_link, children_link), _ret != 0);
+
+		CHECK(close(pipefd[1]));
+		CHECK(wait_for_child(init));
+		_exit(0);
+	}
+
+	TEST_RES(wait_for_child(pid), _ret == 0);
+}
+END_TEST()
+
+// --- Test: No new process can join a PID namespace after its init exits ---
+
+FN_TEST(fork_after_init_exits)
+{
+	pid_t pid = TEST_SUCC(fork());
+
+	if (pid == 0) {
+		CHECK(unshare(CLONE_NEWPID));
+
+		pid_t init = CHECK(fork());
+		if (init == 0)
+			_exit(0);
+		CHECK(wait_for_child(init));
+
+		CHECK_WITH(fork(), _ret < 0 && errno == ENOMEM);
+		_exit(0);
+	}
+
+	TEST_RES(wait_for_child(pid), _ret == 0);
+}
+END_TEST()
+
+// --- Test: The init process is protected from signals in its namespace ---
+
+FN_TEST(init_ignores_sigkill_from_inside)
+{
+	pid_t pid = TEST_SUCC(fork());
+
+	if (pid == 0) {
+		CHECK(unshare(CLONE_NEWPID));
+
+		pid_t init = CHECK(fork());
+		if (init == 0) {
+			pid_t child = CHECK(fork());
+			if (child == 0) {
+				CHECK(kill(1, SIGKILL));
+				_exit(0);
+			}
+			CHECK(wait_for_child(child));
+			_exit(0);
+		}
+
+		CHECK(wait_for_child(init));
+		_exit(0);
+	}
+
+	TEST_RES(wait_for_child(pid), _ret == 0);
+}
+END_TEST()
+
+// --- Test: The other processes are killed when the init process exits ---
+
+FN_TEST(init_exit_kills_namespace)
+{
+	int pipefd[2];
+
+	TEST_SUCC(pipe(pipefd));
+
+	pid_t pid = TEST_SUCC(fork());
+
+	if (pid == 0) {
+		CHECK(close(pipefd[0]));
+		CHECK(unshare(CLONE_NEWPID));
+
+		pid_t init = CHECK(fork());
+		if (init == 0) {
+			pid_t child = CHECK(fork());
+			if (child == 0) {
+				// Hold the write end until we are killed.
+				pause();
+				_exit(0);
+			}
+			_exit(0);
+		}
+
+		CHECK(close(pipefd[1]));
+		CHECK(wait_for_child(init));
+		_exit(0);
+	}
+
+	TEST_SUCC(close(pipefd[1]));
+	TEST_RES(wait_for_child(pid), _ret == 0);
+
+	// The read returns EOF only after the orphaned child is killed.
+	char buf;
+	TEST_RES(read(pipefd[0], &buf, 1), _ret == 0);
+	TEST_SUCC(close(pipefd[0]));
+}
+END_TEST()
+
+// --- Test: The init process waits for the other processes before it exits ---
+
+FN_TEST(init_waits_for_namespace)
+{
+	pid_t pid = TEST_SUCC(fork());
+
+	if (pid == 0) {
+		int pipefd[2];
+
+		CHECK(pipe2(pipefd, O_NONBLOCK));
+		CHECK(unshare(CLONE_NEWPID));
+
+		pid_t init = CHECK(fork());
+		if (init == 0) {
+			CHECK(close(pipefd[0]));
+
+			pid_t child = CHECK(fork());
+			if (child == 0) {
+				// Hold the write end until we are killed.
+				pause();
+				_exit(0);
+			}
+			_exit(0);
+		}
+
+		CHECK(close(pipefd[1]));
+		CHECK(wait_for_child(init));
+
+		// The child has been killed and reaped when the init process
+		// is reaped, so the read must not block.
+		char buf;
+		CHECK_WITH(read(pipefd[0], &buf, 1), _ret == 0);
+		CHECK(close(pipefd[0]));
+		_exit(0);
+	}
+
+	TEST_RES(wait_for_child(pid), _ret == 0);
+}
+END_TEST()
+
+// --- Test: PIDs reported to a process are in its PID namespace ---
+
+static volatile pid_t sigusr1_pid = -1;
+
+static void handle_sigusr1(int signum, siginfo_t *info, void *context)
+{
+	(void)signum;
+	(void)context;
+
+	sigusr1_pid = info->si_pid;
+}
+
+FN_TEST(sender_pid_in_receiver_ns)
+{
+	struct sigaction action = {
+		.sa_sigaction = handle_sigusr1,
+		.sa_flags = SA_SIGINFO,
+	};
+	sigset_t mask;
+	int shmid, msqid;
+
+	TEST_SUCC(sigaction(SIGUSR1, &action, NULL));
+	TEST_SUCC(sigemptyset(&mask));
+	TEST_SUCC(sigaddset(&mask, SIGUSR1));
+	TEST_SUCC(sigprocmask(SIG_BLOCK, &mask, NULL));
+	shmid = TEST_SUCC(shmget(IPC_PRIVATE, 4096, IPC_CREAT | 0600));
+	msqid = TEST_SUCC(msgget(IPC_PRIVATE, IPC_CREAT | 0600));
+
+	pid_t pid = TEST_SUCC(fork());
+
+	if (pid == 0) {
+		CHECK(unshare(CLONE_NEWPID));
+
+		pid_t init = CHECK(fork());
+		if (init == 0) {
+			struct msgbuf {
+				long mtype;
+				char mtext[1];
+			} msg = { .mtype = 1 };
+			struct shmid_ds shm_ds;
+			struct msqid_ds msq_ds;
+			sigset_t empty_mask;
+
+			// The sender in the parent namespace is not visible.
+			CHECK(sigemptyset(&empty_mask));
+			while (sigusr1_pid < 0)
+				sigsuspend(&empty_mask);
+			CHECK_WITH(sigusr1_pid, _ret == 0);
+			CHECK(sigprocmask(SIG_UNBLOCK, &mask, NULL));
+
+			// The sender in the same namespace is visible.
+			sigusr1_pid = -1;
+			CHECK(kill(1, SIGUSR1));
+			CHECK_WITH(sigusr1_pid, _ret == 1);
+
+			CHECK_WITH(shmat(shmid, NULL, 0), _ret != (void *)-1);
+			CHECK(shmctl(shmid, IPC_STAT, &shm_ds));
+			CHECK_WITH(shm_ds.shm_cpid, _ret == 0);
+			CHECK_WITH(shm_ds.shm_lpid, _ret == 1);
+
+			CHECK(msgsnd(msqid, &msg, sizeof(msg.mtext), 0));
+			CHECK(msgctl(msqid, IPC_STAT, &msq_ds));
+			CHECK_WITH(msq_ds.msg_lspid, _ret == 1);
+			_exit(0);
+		}
+
+		CHECK(kill(init, SIGUSR1));
+		CHECK(wait_for_child(init));
+		_exit(0);
+	}
+
+	TEST_RES(wait_for_child(pid), _ret == 0);
+
+	// The PIDs in the child namespace are translated into this namespace.
+	struct shmid_ds shm_ds;
+	struct msqid_ds msq_ds;
+
+	TEST_RES(shmctl(shmid, IPC_STAT, &shm_ds),
+		 shm_ds.shm_cpid == getpid() && shm_ds.shm_lpid > 1);
+	TEST_RES(msgctl(msqid, IPC_STAT, &msq_ds), msq_ds.msg_lspid > 1);
+
+	TEST_SUCC(shmctl(shmid, IPC_RMID, NULL));
+	TEST_SUCC(msgctl(msqid, IPC_RMID, NULL));
+	TEST_SUCC(sigprocmask(SIG_UNBLOCK, &mask, NULL));
+	action.sa_handler = SIG_DFL;
+	action.sa_flags = 0;
+	TEST_SUCC(sigaction(SIGUSR1, &action, NULL));
+}
+END_TEST()
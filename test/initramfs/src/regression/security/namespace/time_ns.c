@@ -0,0 +1,282 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+#include <errno.h>
+#include <fcntl.h>
+#include <sched.h>
+#include <signal.h>
+#include <string.h>
+#include <sys/syscall.h>
+#include <sys/wait.h>
+#include <time.h>
+#include <unistd.h>
+
+#include "../../common/test.h"
+
+#define OFFSET_SECS 1000
+
+static int wait_for_child(pid_t pid)
+{
+	int status;
+
+	if (waitpid(pid, &status, 0) != pid)
+		return -1;
+	if (!WIFEXITED(status) || WEXITSTATUS(status) != 0)
+		return -1;
+	return 0;
+}
+
+static int write_offsets(const char *offsets)
+{
+	int fd, ret;
+
+	fd = open("/proc/self/timens_offsets", O_WRONLY);
+	if (fd < 0)
+		return -1;
+	ret = write(fd, offsets, strlen(offsets));
+	close(fd);
+	return ret;
+}
+
+static int read_offsets(char *buf, size_t len)
+{
+	int fd, ret;
+
+	fd = open("/proc/self/timens_offsets", O_RDONLY);
+	if (fd < 0)
+		return -1;
+	ret = read(fd, buf, len - 1);
+	close(fd);
+	if (ret >= 0)
+		buf[ret] = '\0';
+	return ret;
+}
+
+static long monotonic_secs_vdso(void)
+{
+	struct timespec ts;
+
+	if (clock_gettime(CLOCK_MONOTONIC, &ts) < 0)
+		return -1;
+	return ts.tv_sec;
+}
+
+static long monotonic_secs_syscall(void)
+{
+	struct timespec ts;
+
+	if (syscall(SYS_clock_gettime, CLOCK_MONOTONIC, &ts) < 0)
+		return -1;
+	return ts.tv_sec;
+}
+
+// --- Test: The offsets are shown in `/proc/[pid]/timens_offsets` ---
+
+FN_TEST(read_offsets)
+{
+	pid_t pid = TEST_SUCC(fork());
+
+	if (pid == 0) {
+		char buf[128];
+
+		CHECK(read_offsets(buf, sizeof(buf)));
+		CHECK_WITH(strcmp(buf, "monotonic           0         0\n"
+				       "boottime            0         0\n"),
+			   _ret == 0);
+
+		CHECK(unshare(CLONE_NEWTIME));
+		CHECK(write_offsets("monotonic 1000 0\nboottime -5 1\n"));
+
+		CHECK(read_offsets(buf, sizeof(buf)));
+		CHECK_WITH(strcmp(buf, "monotonic        1000         0\n"
+				       "boottime           -5         1\n"),
+			   _ret == 0);
+		_exit(0);
+	}
+
+	TEST_RES(wait_for_child(pid), _ret == 0);
+}
+END_TEST()
+
+// --- Test: The offsets of the initial time namespace cannot be changed ---
+
+FN_TEST(write_offsets_init_ns)
+{
+	TEST_ERRNO(write_offsets("monotonic 1000 0"), EACCES);
+}
+END_TEST()
+
+// --- Test: Invalid offsets are rejected ---
+
+FN_TEST(write_offsets_invalid)
+{
+	pid_t pid = TEST_SUCC(fork());
+
+	if (pid == 0) {
+		CHECK(unshare(CLONE_NEWTIME));
+
+		CHECK_WITH(write_offsets("realtime 1000 0"),
+			   _ret < 0 && errno == EINVAL);
+		CHECK_WITH(write_offsets("monotonic 1000"),
+			   _ret < 0 && errno == EINVAL);
+		CHECK_WITH(write_offsets("monotonic 0 1000000000"),
+			   _ret < 0 && errno == EINVAL);
+		CHECK_WITH(write_offsets("monotonic -1000000000 0"),
+			   _ret < 0 && errno == ERANGE);
+		_exit(0);
+	}
+
+	TEST_RES(wait_for_child(pid), _ret == 0);
+}
+END_TEST()
+
+// --- Test: The clocks are shifted in the new time namespace ---
+
+FN_TEST(clocks_are_shifted)
+{
+	pid_t pid = TEST_SUCC(fork());
+
+	if (pid == 0) {
+		long host_secs = CHECK(monotonic_secs_syscall());
+
+		CHECK(unshare(CLONE_NEWTIME));
+		CHECK(write_offsets("monotonic 1000 0"));
+		// The caller itself does not move into the new namespace.
+		CHECK_WITH(monotonic_secs_syscall(),
+			   _ret >= host_secs && _ret < host_secs + OFFSET_SECS);
+
+		pid_t child = CHECK(fork());
+		if (child == 0) {
+			CHECK_WITH(monotonic_secs_syscall(),
+				   _ret >= host_secs + OFFSET_SECS &&
+					   _ret < host_secs + 2 * OFFSET_SECS);
+			CHECK_WITH(monotonic_secs_vdso(),
+				   _ret >= host_secs + OFFSET_SECS &&
+					   _ret < host_secs + 2 * OFFSET_SECS);
+			_exit(0);
+		}
+		CHECK(wait_for_child(child));
+
+		CHECK_WITH(monotonic_secs_vdso(),
+			   _ret >= host_secs && _ret < host_secs + OFFSET_SECS);
+		_exit(0);
+	}
+
+	TEST_RES(wait_for_child(pid), _ret == 0);
+}
+END_TEST()
+
+// --- Test: The offsets are frozen after a process enters the namespace ---
+
+FN_TEST(offsets_are_frozen)
+{
+	pid_t pid = TEST_SUCC(fork());
+
+	if (pid == 0) {
+		CHECK(unshare(CLONE_NEWTIME));
+		CHECK(write_offsets("monotonic 1000 0"));
+
+		pid_t child = CHECK(fork());
+		if (child == 0)
+			_exit(0);
+		CHECK(wait_for_child(child));
+
+		CHECK_WITH(write_offsets("monotonic 2000 0"),
+			   _ret < 0 && errno == EACCES);
+		_exit(0);
+	}
+
+	TEST_RES(wait_for_child(pid), _ret == 0);
+}
+END_TEST()
+
+// --- Test: `time_for_children` differs from `time` after `unshare` ---
+
+FN_TEST(time_for_children_link)
+{
+	pid_t pid = TEST_SUCC(fork());
+
+	if (pid == 0) {
+		char time_link[64] = { 0 };
+		char children_link[64] = { 0 };
+
+		CHECK(readlink("/proc/self/ns/time", time_link,
+			       sizeof(time_link) - 1));
+		CHECK(readlink("/proc/self/ns/time_for_children",
+			       children_link, sizeof(children_link) - 1));
+		CHECK_WITH(strcmp(time_link, children_link), _ret == 0);
+
+		CHECK(unshare(CLONE_NEWTIME));
+
+		CHECK(readlink("/proc/self/ns/time_for_children",
+			       children_link, sizeof(children_link) - 1));
+		CHECK_WITH(strcmp(time_link, children_link), _ret != 0);
+		_exit(0);
+	}
+
+	TEST_RES(wait_for_child(pid), _ret == 0);
+}
+END_TEST()
+
+// --- Test: A child sharing the VM stays in the caller's time namespace ---
+
+FN_TEST(clone_vm_stays_in_time_ns)
+{
+	pid_t pid = TEST_SUCC(fork());
+
+	if (pid == 0) {
+		char time_link[64] = { 0 };
+
+		CHECK(readlink("/proc/self/ns/time", time_link,
+			       sizeof(time_link) - 1));
+		CHECK(unshare(CLONE_NEWTIME));
+
+		pid_t child = CHECK(vfork());
+		if (child == 0) {
+			char child_link[64] = { 0 };
+
+			if (readlink("/proc/self/ns/time", child_link,
+				     sizeof(child_link) - 1) < 0)
+				_exit(1);
+			_exit(strcmp(time_link, child_link) == 0 ? 0 : 1);
+		}
+		CHECK(wait_for_child(child));
+		_exit(0);
+	}
+
+	TEST_RES(wait_for_child(pid), _ret == 0);
+}
+END_TEST()
+
+// --- Test: `setns` switches the clocks immediately ---
+
+FN_TEST(setns_switches_clocks)
+{
+	pid_t pid = TEST_SUCC(fork());
+
+	if (pid == 0) {
+		long host_secs = CHECK(monotonic_secs_vdso());
+		int host_fd = CHECK(open("/proc/self/ns/time", O_RDONLY));
+
+		CHECK(unshare(CLONE_NEWTIME));
+		CHECK(write_offsets("monotonic 1000 0"));
+		int new_fd =
+			CHECK(open("/proc/self/ns/time_for_children", O_RDONLY));
+
+		CHECK(setns(new_fd, CLONE_NEWTIME));
+		CHECK_WITH(monotonic_secs_vdso(),
+			   _ret >= host_secs + OFFSET_SECS &&
+				   _ret < host_secs + 2 * OFFSET_SECS);
+
+		CHECK(setns(host_fd, CLONE_NEWTIME));
+		CHECK_WITH(monotonic_secs_vdso(),
+			   _ret >= host_secs && _ret < host_secs + OFFSET_SECS);
+
+		CHECK(close(new_fd));
+		CHECK(close(host_fd));
+		_exit(0);
+	}
+
+	TEST_RES(wait_for_child(pid), _ret == 0);
+}
+END_TEST()
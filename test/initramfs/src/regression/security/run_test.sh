@@ -7,12 +7,18 @@ set -e
 ./capability/capabilities
 ./capability/capset
 ./capability/execve
+./capability/file_caps
 
 ./lsm/yama
 
 ./namespace/cgroup_ns
 ./namespace/ipc_ns_sem
 ./namespace/mnt_ns
+./namespace/net_ns
+./namespace/pid_ns
 ./namespace/proc_nsfs
 ./namespace/setns
+./namespace/time_ns
 ./namespace/unshare
+
+./seccomp/seccomp
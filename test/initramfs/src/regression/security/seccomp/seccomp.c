@@ -0,0 +1,511 @@
+// SPDX-License-Identifier: MPL-2.0
+
+#define _GNU_SOURCE
+
+#include <linux/audit.h>
+#include <linux/filter.h>
+#include <linux/seccomp.h>
+#include <pthread.h>
+#include <signal.h>
+#include <stddef.h>
+#include <sys/prctl.h>
+#include <sys/ptrace.h>
+#include <sys/syscall.h>
+#include <sys/user.h>
+#include <sys/wait.h>
+#include <unistd.h>
+
+#include "../../common/test.h"
+#include "../../common/yama_ptrace_scope.h"
+
+#ifndef SYS_SECCOMP
+#define SYS_SECCOMP 1
+#endif
+
+#ifndef SECCOMP_FILTER_FLAG_TSYNC_ESRCH
+#define SECCOMP_FILTER_FLAG_TSYNC_ESRCH (1UL << 4)
+#endif
+
+#if defined(__x86_64__)
+#define CURRENT_AUDIT_ARCH AUDIT_ARCH_X86_64
+#elif defined(__riscv)
+#define CURRENT_AUDIT_ARCH AUDIT_ARCH_RISCV64
+#elif defined(__loongarch64)
+#define CURRENT_AUDIT_ARCH AUDIT_ARCH_LOONGARCH64
+#endif
+
+static int install_prog(struct sock_filter *insns, unsigned short len,
+			unsigned int flags)
+{
+	struct sock_fprog prog = { .len = len, .filter = insns };
+	return syscall(SYS_seccomp, SECCOMP_SET_MODE_FILTER, flags, &prog);
+}
+
+// A filter that returns `action` for the syscall `nr` and allows all other
+// syscalls.
+#define FILTER_SYSCALL_INSNS(syscall_nr, action)                       \
+	{                                                              \
+		BPF_STMT(BPF_LD | BPF_W | BPF_ABS,                     \
+			 offsetof(struct seccomp_data, nr)),           \
+		BPF_JUMP(BPF_JMP | BPF_JEQ | BPF_K, syscall_nr, 0, 1), \
+		BPF_STMT(BPF_RET | BPF_K, action),                     \
+		BPF_STMT(BPF_RET | BPF_K, SECCOMP_RET_ALLOW),          \
+	}
+
+static int filter_syscall(int nr, unsigned int action, unsigned int flags)
+{
+	struct sock_filter insns[] = FILTER_SYSCALL_INSNS(nr, action);
+	return install_prog(insns, sizeof(insns) / sizeof(insns[0]), flags);
+}
+
+static int wait_for_child(pid_t pid)
+{
+	int status;
+	CHECK_WITH(waitpid(pid, &status, 0), _ret == pid);
+	return status;
+}
+
+#define EXITED_NORMALLY(status) \
+	(WIFEXITED(status) && WEXITSTATUS(status) == 0)
+#define KILLED_BY(status, sig) (WIFSIGNALED(status) && WTERMSIG(status) == sig)
+
+FN_TEST(invalid_args)
+{
+	struct sock_filter no_ret[] = {
+		BPF_STMT(BPF_LD | BPF_W | BPF_ABS,
+			 offsetof(struct seccomp_data, nr)),
+	};
+	struct sock_filter unaligned_load[] = {
+		BPF_STMT(BPF_LD | BPF_W | BPF_ABS, 1),
+		BPF_STMT(BPF_RET | BPF_K, SECCOMP_RET_ALLOW),
+	};
+	struct sock_filter out_of_bounds_load[] = {
+		BPF_STMT(BPF_LD | BPF_W | BPF_ABS, sizeof(struct seccomp_data)),
+		BPF_STMT(BPF_RET | BPF_K, SECCOMP_RET_ALLOW),
+	};
+	struct sock_filter byte_load[] = {
+		BPF_STMT(BPF_LD | BPF_B | BPF_ABS, 0),
+		BPF_STMT(BPF_RET | BPF_K, SECCOMP_RET_ALLOW),
+	};
+	struct sock_filter out_of_bounds_jump[] = {
+		BPF_JUMP(BPF_JMP | BPF_JA, 1, 0, 0),
+		BPF_STMT(BPF_RET | BPF_K, SECCOMP_RET_ALLOW),
+	};
+	struct sock_filter uninit_mem[] = {
+		BPF_STMT(BPF_LD | BPF_MEM, 0),
+		BPF_STMT(BPF_RET | BPF_A, 0),
+	};
+	struct sock_filter div_by_zero[] = {
+		BPF_STMT(BPF_ALU | BPF_DIV | BPF_K, 0),
+		BPF_STMT(BPF_RET | BPF_A, 0),
+	};
+
+	TEST_ERRNO(syscall(SYS_seccomp, 100, 0, NULL), EINVAL);
+	TEST_ERRNO(syscall(SYS_seccomp, SECCOMP_SET_MODE_STRICT, 1, NULL),
+		   EINVAL);
+	TEST_ERRNO(filter_syscall(SYS_getppid, SECCOMP_RET_ALLOW, 1U << 31),
+		   EINVAL);
+
+	TEST_ERRNO(install_prog(no_ret, 0, 0), EINVAL);
+	TEST_ERRNO(install_prog(no_ret, 1, 0), EINVAL);
+	TEST_ERRNO(install_prog(unaligned_load, 2, 0), EINVAL);
+	TEST_ERRNO(install_prog(out_of_bounds_load, 2, 0), EINVAL);
+	TEST_ERRNO(install_prog(byte_load, 2, 0), EINVAL);
+	TEST_ERRNO(install_prog(out_of_bounds_jump, 2, 0), EINVAL);
+	TEST_ERRNO(install_prog(uninit_mem, 2, 0), EINVAL);
+	TEST_ERRNO(install_prog(div_by_zero, 2, 0), EINVAL);
+	TEST_ERRNO(install_prog(NULL, 1, 0), EINVAL);
+	TEST_ERRNO(syscall(SYS_seccomp, SECCOMP_SET_MODE_FILTER, 0, NULL),
+		   EFAULT);
+
+	TEST_ERRNO(prctl(PR_SET_SECCOMP, 3, 0, 0, 0), EINVAL);
+	TEST_RES(prctl(PR_GET_SECCOMP, 0, 0, 0, 0), _ret == 0);
+}
+END_TEST()
+
+FN_TEST(action_avail)
+{
+	unsigned int action = SECCOMP_RET_ALLOW;
+	TEST_RES(syscall(SYS_seccomp, SECCOMP_GET_ACTION_AVAIL, 0, &action),
+		 _ret == 0);
+	action = SECCOMP_RET_KILL_PROCESS;
+	TEST_RES(syscall(SYS_seccomp, SECCOMP_GET_ACTION_AVAIL, 0, &action),
+		 _ret == 0);
+	action = SECCOMP_RET_TRACE;
+	TEST_RES(syscall(SYS_seccomp, SECCOMP_GET_ACTION_AVAIL, 0, &action),
+		 _ret == 0);
+
+	action = 0x12340000;
+	TEST_ERRNO(syscall(SYS_seccomp, SECCOMP_GET_ACTION_AVAIL, 0, &action),
+		   EOPNOTSUPP);
+	TEST_ERRNO(syscall(SYS_seccomp, SECCOMP_GET_ACTION_AVAIL, 1, &action),
+		   EINVAL);
+}
+END_TEST()
+
+FN_TEST(no_new_privs)
+{
+	TEST_RES(prctl(PR_GET_NO_NEW_PRIVS, 0, 0, 0, 0), _ret == 0);
+	TEST_ERRNO(prctl(PR_GET_NO_NEW_PRIVS, 1, 0, 0, 0), EINVAL);
+	TEST_ERRNO(prctl(PR_SET_NO_NEW_PRIVS, 0, 0, 0, 0), EINVAL);
+	TEST_ERRNO(prctl(PR_SET_NO_NEW_PRIVS, 1, 1, 0, 0), EINVAL);
+
+	pid_t pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		// Drop all capabilities, including `CAP_SYS_ADMIN`.
+		CHECK(setresuid(65534, 65534, 65534));
+		CHECK_WITH(filter_syscall(SYS_getppid, SECCOMP_RET_ALLOW, 0),
+			   _ret < 0 && errno == EACCES);
+
+		CHECK(prctl(PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0));
+		CHECK_WITH(prctl(PR_GET_NO_NEW_PRIVS, 0, 0, 0, 0), _ret == 1);
+		CHECK(filter_syscall(SYS_getppid, SECCOMP_RET_ALLOW, 0));
+
+		pid_t grandchild = CHECK(fork());
+		if (grandchild == 0) {
+			CHECK_WITH(prctl(PR_GET_NO_NEW_PRIVS, 0, 0, 0, 0),
+				   _ret == 1);
+			_exit(0);
+		}
+		CHECK_WITH(wait_for_child(grandchild),
+			   EXITED_NORMALLY(_ret));
+		_exit(0);
+	}
+
+	TEST_RES(wait_for_child(pid), EXITED_NORMALLY(_ret));
+}
+END_TEST()
+
+FN_TEST(strict_mode)
+{
+	int fds[2];
+	TEST_SUCC(pipe(fds));
+
+	pid_t pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(close(fds[0]));
+		CHECK(syscall(SYS_seccomp, SECCOMP_SET_MODE_STRICT, 0, NULL));
+
+		// `write` is allowed, but `getpid` is not.
+		syscall(SYS_write, fds[1], "x", 1);
+		syscall(SYS_getpid);
+		syscall(SYS_exit, 1);
+	}
+
+	char buf;
+	TEST_SUCC(close(fds[1]));
+	TEST_RES(read(fds[0], &buf, 1), _ret == 1 && buf == 'x');
+	TEST_SUCC(close(fds[0]));
+	TEST_RES(wait_for_child(pid), KILLED_BY(_ret, SIGKILL));
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(prctl(PR_SET_SECCOMP, SECCOMP_MODE_STRICT, 0, 0, 0));
+		syscall(SYS_exit, 0);
+	}
+	TEST_RES(wait_for_child(pid), EXITED_NORMALLY(_ret));
+}
+END_TEST()
+
+FN_TEST(errno_action)
+{
+	pid_t pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(filter_syscall(SYS_getppid, SECCOMP_RET_ERRNO | ENOTTY,
+				     0));
+		CHECK_WITH(syscall(SYS_getppid), _ret < 0 && errno == ENOTTY);
+		CHECK_WITH(prctl(PR_GET_SECCOMP, 0, 0, 0, 0), _ret == 2);
+
+		// The strict mode cannot be set once filters are attached.
+		CHECK_WITH(syscall(SYS_seccomp, SECCOMP_SET_MODE_STRICT, 0,
+				   NULL),
+			   _ret < 0 && errno == EINVAL);
+
+		// `SECCOMP_RET_ALLOW` has a lower precedence.
+		CHECK(filter_syscall(SYS_getppid, SECCOMP_RET_ALLOW, 0));
+		CHECK_WITH(syscall(SYS_getppid), _ret < 0 && errno == ENOTTY);
+
+		// The most recent filter wins among the same actions.
+		struct sock_filter insns[] = FILTER_SYSCALL_INSNS(
+			SYS_getppid, SECCOMP_RET_ERRNO | EBADF);
+		struct sock_fprog prog = { .len = 4, .filter = insns };
+		CHECK(prctl(PR_SET_SECCOMP, SECCOMP_MODE_FILTER, &prog, 0, 0));
+		CHECK_WITH(syscall(SYS_getppid), _ret < 0 && errno == EBADF);
+
+		// The error number is capped at 4095.
+		CHECK(filter_syscall(SYS_getpgid, SECCOMP_RET_ERRNO | 0xffff,
+				     0));
+		CHECK_WITH(syscall(SYS_getpgid, 0),
+			   _ret < 0 && errno == 4095);
+
+		// The filters are inherited by child processes.
+		pid_t grandchild = CHECK(fork());
+		if (grandchild == 0) {
+			CHECK_WITH(syscall(SYS_getppid),
+				   _ret < 0 && errno == EBADF);
+			CHECK_WITH(prctl(PR_GET_SECCOMP, 0, 0, 0, 0),
+				   _ret == 2);
+			_exit(0);
+		}
+		CHECK_WITH(wait_for_child(grandchild),
+			   EXITED_NORMALLY(_ret));
+		_exit(0);
+	}
+
+	TEST_RES(wait_for_child(pid), EXITED_NORMALLY(_ret));
+}
+END_TEST()
+
+static volatile int sigsys_code;
+static volatile int sigsys_errno;
+static volatile int sigsys_syscall;
+static volatile unsigned int sigsys_arch;
+
+static void handle_sigsys(int sig, siginfo_t *info, void *ucontext)
+{
+	(void)sig;
+	(void)ucontext;
+
+	sigsys_code = info->si_code;
+	sigsys_errno = info->si_errno;
+	sigsys_syscall = info->si_syscall;
+	sigsys_arch = info->si_arch;
+}
+
+FN_TEST(trap_action)
+{
+	pid_t pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		struct sigaction action = {
+			.sa_sigaction = handle_sigsys,
+			.sa_flags = SA_SIGINFO,
+		};
+		CHECK(sigaction(SIGSYS, &action, NULL));
+
+		// The syscall is not executed, but `SIGSYS` is delivered.
+		CHECK(filter_syscall(SYS_getppid, SECCOMP_RET_TRAP | 42, 0));
+		syscall(SYS_getppid);
+
+		CHECK_WITH(sigsys_code, _ret == SYS_SECCOMP);
+		CHECK_WITH(sigsys_errno, _ret == 42);
+		CHECK_WITH(sigsys_syscall, _ret == SYS_getppid);
+		CHECK_WITH(sigsys_arch, _ret == CURRENT_AUDIT_ARCH);
+		_exit(0);
+	}
+
+	TEST_RES(wait_for_child(pid), EXITED_NORMALLY(_ret));
+}
+END_TEST()
+
+FN_TEST(kill_and_log_actions)
+{
+	pid_t pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(filter_syscall(SYS_getppid, SECCOMP_RET_KILL_PROCESS, 0));
+		syscall(SYS_getppid);
+		_exit(0);
+	}
+	TEST_RES(wait_for_child(pid), KILLED_BY(_ret, SIGSYS));
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(filter_syscall(SYS_getppid, SECCOMP_RET_KILL_THREAD, 0));
+		syscall(SYS_getppid);
+		_exit(0);
+	}
+	TEST_RES(wait_for_child(pid), KILLED_BY(_ret, SIGSYS));
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(filter_syscall(SYS_getppid, SECCOMP_RET_LOG,
+				     SECCOMP_FILTER_FLAG_LOG));
+		CHECK_WITH(syscall(SYS_getppid), _ret > 0);
+		_exit(0);
+	}
+	TEST_RES(wait_for_child(pid), EXITED_NORMALLY(_ret));
+}
+END_TEST()
+
+FN_TEST(arch_and_args)
+{
+	pid_t pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		// Fail `getpgid(12345)` only if the architecture matches.
+		struct sock_filter insns[] = {
+			BPF_STMT(BPF_LD | BPF_W | BPF_ABS,
+				 offsetof(struct seccomp_data, arch)),
+			BPF_JUMP(BPF_JMP | BPF_JEQ | BPF_K, CURRENT_AUDIT_ARCH,
+				 0, 3),
+			BPF_STMT(BPF_LD | BPF_W | BPF_ABS,
+				 offsetof(struct seccomp_data, args[0])),
+			BPF_JUMP(BPF_JMP | BPF_JEQ | BPF_K, 12345, 0, 1),
+			BPF_STMT(BPF_RET | BPF_K, SECCOMP_RET_ERRNO | EPERM),
+			BPF_STMT(BPF_RET | BPF_K, SECCOMP_RET_ALLOW),
+		};
+		CHECK(install_prog(insns, sizeof(insns) / sizeof(insns[0]), 0));
+
+		CHECK_WITH(syscall(SYS_getpgid, 12345),
+			   _ret < 0 && errno == EPERM);
+		CHECK(syscall(SYS_getpgid, 0));
+		_exit(0);
+	}
+
+	TEST_RES(wait_for_child(pid), EXITED_NORMALLY(_ret));
+}
+END_TEST()
+
+FN_TEST(execve_inherits_filters)
+{
+	int fds[2];
+	TEST_SUCC(pipe(fds));
+
+	pid_t pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(dup2(fds[1], STDOUT_FILENO));
+		CHECK(prctl(PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0));
+		CHECK(filter_syscall(SYS_getppid, SECCOMP_RET_ERRNO | EPERM,
+				     0));
+		CHECK(execlp("cat", "cat", "/proc/self/status", NULL));
+	}
+
+	char buf[4096];
+	size_t len = 0;
+	ssize_t nread;
+	TEST_SUCC(close(fds[1]));
+	while ((nread = read(fds[0], buf + len, sizeof(buf) - 1 - len)) > 0)
+		len += nread;
+	buf[len] = '\0';
+	TEST_SUCC(close(fds[0]));
+
+	TEST_RES(wait_for_child(pid), EXITED_NORMALLY(_ret));
+	TEST_RES(strstr(buf, "NoNewPrivs:\t1\n"), _ret != NULL);
+	TEST_RES(strstr(buf, "Seccomp:\t2\n"), _ret != NULL);
+	TEST_RES(strstr(buf, "Seccomp_filters:\t1\n"), _ret != NULL);
+}
+END_TEST()
+
+static int thread_pipe[2];
+static int main_pipe[2];
+
+static void *thread_fn(void *arg)
+{
+	char buf;
+	pid_t *tid = arg;
+
+	*tid = syscall(SYS_gettid);
+
+	// Wait for the main thread to synchronize its filter.
+	CHECK_WITH(read(thread_pipe[0], &buf, 1), _ret == 1);
+	CHECK_WITH(syscall(SYS_getppid), _ret < 0 && errno == ENOTTY);
+
+	// Install a filter that the main thread does not have.
+	CHECK(filter_syscall(SYS_getpgid, SECCOMP_RET_ERRNO | EPERM, 0));
+	CHECK_WITH(write(main_pipe[1], "x", 1), _ret == 1);
+
+	CHECK_WITH(read(thread_pipe[0], &buf, 1), _ret == 1);
+	return NULL;
+}
+
+FN_TEST(tsync)
+{
+	pid_t pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		pthread_t thread;
+		pid_t tid = 0;
+		char buf;
+
+		CHECK(pipe(thread_pipe));
+		CHECK(pipe(main_pipe));
+		CHECK_WITH(pthread_create(&thread, NULL, thread_fn, &tid),
+			   _ret == 0);
+
+		CHECK_WITH(filter_syscall(SYS_getppid,
+					  SECCOMP_RET_ERRNO | ENOTTY,
+					  SECCOMP_FILTER_FLAG_TSYNC),
+			   _ret == 0);
+		CHECK_WITH(write(thread_pipe[1], "x", 1), _ret == 1);
+		CHECK_WITH(read(main_pipe[0], &buf, 1), _ret == 1);
+
+		// The thread's filters are no longer an ancestor of ours.
+		CHECK_WITH(filter_syscall(SYS_getuid, SECCOMP_RET_ALLOW,
+					  SECCOMP_FILTER_FLAG_TSYNC),
+			   _ret == tid);
+		CHECK_WITH(filter_syscall(SYS_getuid, SECCOMP_RET_ALLOW,
+					  SECCOMP_FILTER_FLAG_TSYNC |
+						  SECCOMP_FILTER_FLAG_TSYNC_ESRCH),
+			   _ret < 0 && errno == ESRCH);
+
+		// The main thread does not have the thread's filter.
+		CHECK(syscall(SYS_getpgid, 0));
+
+		CHECK_WITH(write(thread_pipe[1], "x", 1), _ret == 1);
+		CHECK_WITH(pthread_join(thread, NULL), _ret == 0);
+		_exit(0);
+	}
+
+	TEST_RES(wait_for_child(pid), EXITED_NORMALLY(_ret));
+}
+END_TEST()
+
+FN_TEST(trace_action)
+{
+	SKIP_TEST_IF(read_yama_scope() == YAMA_SCOPE_NO_ATTACH);
+
+	// Without a tracer, the syscall fails with `ENOSYS`.
+	pid_t pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		CHECK(filter_syscall(SYS_getppid, SECCOMP_RET_TRACE | 7, 0));
+		CHECK_WITH(syscall(SYS_getppid), _ret < 0 && errno == ENOSYS);
+		_exit(0);
+	}
+	TEST_RES(wait_for_child(pid), EXITED_NORMALLY(_ret));
+
+	pid = TEST_SUCC(fork());
+	if (pid == 0) {
+		pid_t ppid = getppid();
+
+		CHECK(ptrace(PTRACE_TRACEME, 0, 0, 0));
+		CHECK(raise(SIGSTOP));
+
+		CHECK(filter_syscall(SYS_getppid, SECCOMP_RET_TRACE | 7, 0));
+		CHECK_WITH(syscall(SYS_getppid), _ret == ppid);
+#ifdef __x86_64__
+		CHECK(filter_syscall(SYS_getpgid, SECCOMP_RET_TRACE | 8, 0));
+		CHECK_WITH(syscall(SYS_getpgid, 0), _ret == 1234);
+#endif
+		_exit(0);
+	}
+
+	int status;
+	unsigned long msg;
+	int event_status = SIGTRAP | (PTRACE_EVENT_SECCOMP << 8);
+
+	TEST_RES(waitpid(pid, &status, 0), _ret == pid && WIFSTOPPED(status) &&
+						   WSTOPSIG(status) == SIGSTOP);
+	TEST_SUCC(ptrace(PTRACE_SETOPTIONS, pid, 0, PTRACE_O_TRACESECCOMP));
+	TEST_SUCC(ptrace(PTRACE_CONT, pid, 0, 0));
+
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSTOPPED(status) &&
+			 (status >> 8) == event_status);
+	TEST_RES(ptrace(PTRACE_GETEVENTMSG, pid, 0, &msg), msg == 7);
+	TEST_SUCC(ptrace(PTRACE_CONT, pid, 0, 0));
+
+#ifdef __x86_64__
+	// Skip the syscall by setting the syscall number to -1.
+	struct user_regs_struct regs;
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && WIFSTOPPED(status) &&
+			 (status >> 8) == event_status);
+	TEST_RES(ptrace(PTRACE_GETEVENTMSG, pid, 0, &msg), msg == 8);
+	TEST_SUCC(ptrace(PTRACE_GETREGS, pid, 0, &regs));
+	regs.orig_rax = -1;
+	regs.rax = 1234;
+	TEST_SUCC(ptrace(PTRACE_SETREGS, pid, 0, &regs));
+	TEST_SUCC(ptrace(PTRACE_CONT, pid, 0, 0));
+#endif
+
+	TEST_RES(waitpid(pid, &status, 0),
+		 _ret == pid && EXITED_NORMALLY(status));
+}
+END_TEST()